@@ -0,0 +1,317 @@
+//! Commitment Cadence — when state roots are due, keyed to the journal sequence
+//!
+//! Roots are submitted on a cadence measured in persistence journal events:
+//! - A root is due once `min_interval_events` have been journaled since the last one
+//! - A root must land within `max_interval_events`; later submissions are refused as gaps
+//! - Warnings are emitted as time since the last root approaches `max_staleness`,
+//!   the point at which users may fall back to emergency exit
+//!
+//! The scheduler holds no clock. Every decision is made from the journal
+//! sequence and timestamp supplied by the caller, so replaying the same
+//! inputs reproduces the same submissions and warnings.
+
+use crate::commitment::CommitmentStore;
+use crate::errors::CommitmentError;
+use crate::events::{CommitmentStale, ContractEvent};
+
+/// Staleness warning levels, as a percentage of `max_staleness`.
+pub const STALENESS_WARNING_LEVELS: [u8; 3] = [50, 75, 90];
+
+/// Cadence policy for state root submissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentPolicy {
+    /// Journal events required before a new root is due
+    pub min_interval_events: u64,
+    /// Journal events after which a root may no longer be submitted normally
+    pub max_interval_events: u64,
+    /// Seconds without a root before emergency exit opens
+    pub max_staleness: i64,
+}
+
+impl Default for CommitmentPolicy {
+    fn default() -> Self {
+        Self {
+            min_interval_events: 1_000,
+            max_interval_events: 10_000,
+            max_staleness: 86_400,
+        }
+    }
+}
+
+/// Decides when roots are due and records them into a `CommitmentStore`.
+#[derive(Debug)]
+pub struct CommitmentScheduler {
+    policy: CommitmentPolicy,
+    /// Journal sequence covered by the last root
+    last_sequence: u64,
+    /// Timestamp of the last root
+    last_submitted_at: i64,
+    /// Highest warning level already emitted since the last root
+    warned_percent: u8,
+    /// Emitted events
+    events: Vec<ContractEvent>,
+}
+
+impl CommitmentScheduler {
+    /// Create a scheduler starting from a known committed sequence and time.
+    ///
+    /// Use sequence 0 and the deployment time for a fresh exchange.
+    pub fn new(policy: CommitmentPolicy, last_sequence: u64, last_submitted_at: i64) -> Self {
+        Self {
+            policy,
+            last_sequence,
+            last_submitted_at,
+            warned_percent: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Get the active policy.
+    pub fn policy(&self) -> &CommitmentPolicy {
+        &self.policy
+    }
+
+    /// Journal sequence covered by the last root.
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Timestamp of the last root.
+    pub fn last_submitted_at(&self) -> i64 {
+        self.last_submitted_at
+    }
+
+    /// Last journal sequence a root may cover without a gap.
+    pub fn deadline_sequence(&self) -> u64 {
+        self.last_sequence.saturating_add(self.policy.max_interval_events)
+    }
+
+    /// Whether enough events have been journaled for a new root.
+    pub fn is_due(&self, journal_sequence: u64) -> bool {
+        journal_sequence.saturating_sub(self.last_sequence) >= self.policy.min_interval_events
+    }
+
+    /// Check that a root covering `journal_sequence` would be accepted.
+    pub fn check_submission(&self, journal_sequence: u64) -> Result<(), CommitmentError> {
+        if journal_sequence <= self.last_sequence {
+            return Err(CommitmentError::SequenceNotAdvanced {
+                last_sequence: self.last_sequence,
+                journal_sequence,
+            });
+        }
+
+        let events_since_last = journal_sequence - self.last_sequence;
+        if events_since_last < self.policy.min_interval_events {
+            return Err(CommitmentError::TooEarly {
+                events_since_last,
+                min_interval: self.policy.min_interval_events,
+            });
+        }
+        if events_since_last > self.policy.max_interval_events {
+            return Err(CommitmentError::Gap {
+                last_sequence: self.last_sequence,
+                journal_sequence,
+                max_interval: self.policy.max_interval_events,
+            });
+        }
+        Ok(())
+    }
+
+    /// Submit the vault's `state_hash()` as the root covering `journal_sequence`.
+    ///
+    /// The journal sequence is recorded as the commitment's block number.
+    /// Nothing is recorded if the cadence check or the store rejects it.
+    pub fn submit(
+        &mut self,
+        store: &mut CommitmentStore,
+        caller: &str,
+        state_hash: [u8; 32],
+        journal_sequence: u64,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        self.check_submission(journal_sequence)?;
+        let event = store.submit_root(caller, state_hash, journal_sequence, current_time)?;
+        self.record(journal_sequence, current_time);
+        Ok(event)
+    }
+
+    /// Realign the scheduler after a root was set outside the cadence.
+    ///
+    /// Call after `CommitmentStore::admin_override` to recover from a gap.
+    pub fn realign(&mut self, journal_sequence: u64, current_time: i64) {
+        self.record(journal_sequence, current_time);
+    }
+
+    /// Emit a staleness warning if a new warning level has been crossed.
+    ///
+    /// At most one event is emitted per call, for the highest level crossed;
+    /// each level is emitted at most once between submissions.
+    pub fn check_staleness(&mut self, current_time: i64) -> Option<ContractEvent> {
+        let staleness = current_time - self.last_submitted_at;
+        let level = STALENESS_WARNING_LEVELS
+            .iter()
+            .rev()
+            .copied()
+            .find(|&percent| staleness * 100 >= self.policy.max_staleness * percent as i64)?;
+        if level <= self.warned_percent {
+            return None;
+        }
+        self.warned_percent = level;
+
+        let event = ContractEvent::CommitmentStale(CommitmentStale {
+            last_sequence: self.last_sequence,
+            last_submitted_at: self.last_submitted_at,
+            staleness_seconds: staleness,
+            max_staleness_seconds: self.policy.max_staleness,
+            warning_percent: level,
+        });
+        self.events.push(event.clone());
+        Some(event)
+    }
+
+    /// Whether the last root is older than `max_staleness`.
+    pub fn is_stale(&self, current_time: i64) -> bool {
+        current_time - self.last_submitted_at >= self.policy.max_staleness
+    }
+
+    /// Get emitted events.
+    pub fn events(&self) -> &[ContractEvent] {
+        &self.events
+    }
+
+    /// Drain emitted events.
+    pub fn drain_events(&mut self) -> Vec<ContractEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn record(&mut self, journal_sequence: u64, current_time: i64) {
+        self.last_sequence = journal_sequence;
+        self.last_submitted_at = current_time;
+        self.warned_percent = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::compute_hash;
+
+    fn policy() -> CommitmentPolicy {
+        CommitmentPolicy {
+            min_interval_events: 50,
+            max_interval_events: 100,
+            max_staleness: 1000,
+        }
+    }
+
+    fn setup() -> (CommitmentScheduler, CommitmentStore) {
+        (
+            CommitmentScheduler::new(policy(), 0, 0),
+            CommitmentStore::with_default_window("admin"),
+        )
+    }
+
+    #[test]
+    fn test_submission_on_interval_boundary_accepted() {
+        let (mut scheduler, mut store) = setup();
+        let root = compute_hash(b"state_100");
+
+        let event = scheduler.submit(&mut store, "admin", root, 100, 10).unwrap();
+        assert!(matches!(event, ContractEvent::CommitmentSubmitted(_)));
+        assert_eq!(store.get_latest_root().unwrap().block_number, 100);
+        assert_eq!(scheduler.last_sequence(), 100);
+        assert_eq!(scheduler.deadline_sequence(), 200);
+    }
+
+    #[test]
+    fn test_two_intervals_without_submission_is_gap() {
+        let (mut scheduler, mut store) = setup();
+        let result = scheduler.submit(&mut store, "admin", compute_hash(b"late"), 201, 10);
+        assert_eq!(
+            result,
+            Err(CommitmentError::Gap {
+                last_sequence: 0,
+                journal_sequence: 201,
+                max_interval: 100,
+            })
+        );
+        assert!(store.history().is_empty());
+        assert_eq!(scheduler.last_sequence(), 0);
+    }
+
+    #[test]
+    fn test_too_early_and_not_advanced() {
+        let (mut scheduler, mut store) = setup();
+        assert!(!scheduler.is_due(49));
+        assert!(matches!(
+            scheduler.check_submission(49),
+            Err(CommitmentError::TooEarly { events_since_last: 49, .. })
+        ));
+
+        assert!(scheduler.is_due(50));
+        scheduler.submit(&mut store, "admin", compute_hash(b"a"), 50, 10).unwrap();
+        assert!(matches!(
+            scheduler.check_submission(50),
+            Err(CommitmentError::SequenceNotAdvanced { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unauthorized_submission_not_recorded() {
+        let (mut scheduler, mut store) = setup();
+        let result = scheduler.submit(&mut store, "eve", compute_hash(b"a"), 60, 10);
+        assert_eq!(result, Err(CommitmentError::Unauthorized));
+        assert_eq!(scheduler.last_sequence(), 0);
+    }
+
+    #[test]
+    fn test_staleness_warnings_escalate_once_per_level() {
+        let (mut scheduler, mut store) = setup();
+        assert!(scheduler.check_staleness(499).is_none());
+
+        let event = scheduler.check_staleness(500).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::CommitmentStale(CommitmentStale { warning_percent: 50, .. })
+        ));
+        assert!(scheduler.check_staleness(600).is_none());
+
+        // Jumping past two levels emits only the highest
+        let event = scheduler.check_staleness(950).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::CommitmentStale(CommitmentStale { warning_percent: 90, .. })
+        ));
+        assert!(scheduler.check_staleness(999).is_none());
+        assert!(scheduler.is_stale(1000));
+        assert_eq!(scheduler.events().len(), 2);
+
+        // A new root resets the warnings
+        scheduler.submit(&mut store, "admin", compute_hash(b"a"), 80, 1000).unwrap();
+        assert!(scheduler.check_staleness(1400).is_none());
+        assert!(scheduler.check_staleness(1500).is_some());
+    }
+
+    #[test]
+    fn test_realign_after_override_recovers_from_gap() {
+        let (mut scheduler, mut store) = setup();
+        assert!(scheduler.check_submission(300).is_err());
+
+        store.admin_override("admin", compute_hash(b"override"), 300, 50).unwrap();
+        scheduler.realign(300, 50);
+        assert!(scheduler.submit(&mut store, "admin", compute_hash(b"b"), 400, 60).is_ok());
+    }
+
+    #[test]
+    fn test_vault_state_hash_as_root() {
+        let mut vault = crate::vault::Vault::new("admin");
+        vault.add_to_whitelist("admin", "BTC").unwrap();
+        vault
+            .deposit(types::ids::AccountId::new(), "BTC", rust_decimal::Decimal::from(1), "tx_01")
+            .unwrap();
+
+        let (mut scheduler, mut store) = setup();
+        scheduler.submit(&mut store, "admin", vault.state_hash(), 75, 10).unwrap();
+        assert_eq!(store.get_latest_root().unwrap().root_hash, vault.state_hash());
+    }
+}
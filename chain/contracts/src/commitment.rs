@@ -3,15 +3,63 @@
 //! Implements the state commitment layer for the exchange:
 //! - Authorized submitters post periodic state root hashes
 //! - Fraud proof window allows challenges
-//! - Dispute resolution by admin
-//! - Admin override for emergency situations
-
+//! - Dispute resolution by fraud proof: journal entries re-executed from the
+//!   previous root
+//! - Bonds: challengers lock a dispute bond, committers stake behind roots;
+//!   the losing side is slashed
+//! - Admin overrides (state root, dispute verdict) for emergencies,
+//!   optionally timelocked
+//! - Withdrawal batch roots with Merkle inclusion proofs
+
+use persistence::journal::JournalEntry;
+use persistence::recovery::EventApplier;
+use persistence::snapshot::EngineState;
+use rust_decimal::{Decimal, RoundingStrategy};
 use sha2::{Digest, Sha256};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use types::ids::AccountId;
+
+use crate::errors::{CommitmentError, VaultError};
+use crate::events::{
+    BondKind, BondLocked, BondReleased, BondSlashed, CommitmentSubmitted, ContractEvent,
+    DisputeRaised, DisputeResolved,
+};
+use crate::security::{
+    AccessControl, OperationId, QueuedOperation, Role, Timelock, TimelockOperation,
+};
+use crate::vault::Vault;
+use crate::withdrawal::{verify_strict, WithdrawalQueue};
+
+/// Privileged commitment operations that can be timelocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentOperation {
+    AdminOverride {
+        root_hash: [u8; 32],
+        block_number: u64,
+    },
+    ResolveDispute {
+        root_hash: [u8; 32],
+        accept: bool,
+    },
+}
 
-use crate::errors::CommitmentError;
-use crate::events::{CommitmentSubmitted, ContractEvent, DisputeRaised};
-use crate::security::AccessControl;
+impl CommitmentOperation {
+    /// Timelock name of [`CommitmentOperation::AdminOverride`]
+    pub const ADMIN_OVERRIDE: &'static str = "admin_override";
+    /// Timelock name of [`CommitmentOperation::ResolveDispute`]
+    pub const RESOLVE_DISPUTE: &'static str = "resolve_dispute";
+}
+
+impl TimelockOperation for CommitmentOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            CommitmentOperation::AdminOverride { .. } => Self::ADMIN_OVERRIDE,
+            CommitmentOperation::ResolveDispute { .. } => Self::RESOLVE_DISPUTE,
+        }
+    }
+}
 
 /// A single state commitment record.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -41,6 +89,113 @@ pub struct Dispute {
     pub reason: String,
     pub raised_at: i64,
     pub status: DisputeStatus,
+    /// Vault account the bond was locked from
+    pub account_id: AccountId,
+    /// Bond held in escrow while the dispute is pending
+    pub bond: Decimal,
+}
+
+/// Bond economics of disputes and commitments. Amounts are in `asset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondConfig {
+    pub asset: String,
+    /// Locked from the challenger's vault balance by each dispute
+    pub dispute_bond: Decimal,
+    /// Locked from the committer's posted stake by each submitted root
+    pub commit_stake: Decimal,
+    /// Paid to each successful challenger out of the slashed stake
+    pub challenger_reward: Decimal,
+    /// Receives forfeited bonds and the rest of slashed stakes
+    pub treasury: AccountId,
+}
+
+/// A committer's stake held in escrow by the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitterStake {
+    /// Vault account the stake was posted from and is withdrawn to
+    pub account_id: AccountId,
+    /// Posted and not locked against any root
+    pub free: Decimal,
+    /// Locked per root until its fraud window passes undisputed
+    pub locked: HashMap<[u8; 32], Decimal>,
+}
+
+/// Evidence that a committed state root does not follow from the journal.
+///
+/// A commitment's block number is the journal sequence it covers, so the
+/// disputed root must be the state reached by applying `entries` — the
+/// sequences after the previous commitment's, up to and including the
+/// disputed one's — to `pre_state`, whose root is the previous commitment
+/// (or the empty state's, for the first commitment).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FraudProof {
+    /// Root of the state the disputed commitment was built on
+    pub pre_state_root: [u8; 32],
+    /// The state under `pre_state_root`
+    pub pre_state: EngineState,
+    /// Journal entries covered by the disputed commitment, in order
+    pub entries: Vec<JournalEntry>,
+    /// Root the challenger says re-execution reaches
+    pub claimed_post_root: [u8; 32],
+}
+
+/// Why a fraud proof proves nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofDefect {
+    /// `pre_state_root` is not the root the disputed commitment built on
+    PreStateMismatch { expected: [u8; 32], found: [u8; 32] },
+    /// `pre_state` does not hash to `pre_state_root`
+    PreStateCorrupt,
+    /// An entry fails its checksum
+    CorruptEntry { sequence: u64 },
+    /// The entries are not exactly the sequences the commitment covers
+    WrongEntries { expected: (u64, u64) },
+    /// The state transition rejected an entry
+    EntryRejected { sequence: u64, reason: String },
+    /// Re-execution reaches neither the committed nor the claimed root
+    ClaimMismatch { recomputed_root: [u8; 32] },
+}
+
+/// Outcome of verifying a fraud proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Re-execution reaches the claimed root, not the committed one: the
+    /// commitment is fraudulent
+    CommitmentRejected { recomputed_root: [u8; 32] },
+    /// Re-execution reaches the committed root
+    CommitmentUpheld,
+    /// The proof does not hold; the commitment stands
+    InvalidProof(ProofDefect),
+}
+
+/// State root of an engine state: its snapshot hash
+/// ([`EngineState::compute_hash`]) as bytes.
+pub fn engine_state_root(state: &EngineState) -> [u8; 32] {
+    let digest = state.compute_hash();
+    let mut root = [0u8; 32];
+    for (i, byte) in root.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digest[2 * i..2 * i + 2], 16).expect("hex SHA-256 digest");
+    }
+    root
+}
+
+/// Settlement commitment for one processed withdrawal batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalBatchCommitment {
+    pub batch_id: u64,
+    /// Merkle root over the settled withdrawals, in batch order
+    pub root: [u8; 32],
+    pub item_count: u64,
+    pub committed_at: i64,
+}
+
+/// Deterministic state transition that fraud proofs are re-executed through.
+struct StateTransition(Box<dyn EventApplier + Send + Sync>);
+
+impl fmt::Debug for StateTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StateTransition")
+    }
 }
 
 /// State commitment store managing roots, fraud proofs, and disputes.
@@ -54,28 +209,69 @@ pub struct CommitmentStore {
     fraud_window_seconds: i64,
     /// Access control for admin/operator roles
     access_control: AccessControl,
+    /// Delay queue for admin overrides
+    timelock: Timelock<CommitmentOperation>,
+    /// Withdrawal batch settlement roots, in batch order
+    withdrawal_batches: Vec<WithdrawalBatchCommitment>,
+    /// Bond economics; `None` leaves disputes and commitments unbonded
+    bond_config: Option<BondConfig>,
+    /// Committer stakes in escrow, by committer
+    stakes: HashMap<String, CommitterStake>,
+    /// Vault account each challenger's dispute bonds are locked from
+    challengers: HashMap<String, AccountId>,
+    /// Fixed at construction; `None` leaves fraud proofs unverifiable
+    state_transition: Option<StateTransition>,
     /// Emitted events
     events: Vec<ContractEvent>,
 }
 
 impl CommitmentStore {
     /// Create a new commitment store with an admin.
+    ///
+    /// Disputes can only be settled by the admin; see
+    /// [`with_state_transition`](Self::with_state_transition) for fraud proofs.
     pub fn new(admin: impl Into<String>, fraud_window_seconds: i64) -> Self {
         Self {
             history: Vec::new(),
             disputes: Vec::new(),
             fraud_window_seconds,
             access_control: AccessControl::new(admin),
+            timelock: Timelock::new(),
+            withdrawal_batches: Vec::new(),
+            bond_config: None,
+            stakes: HashMap::new(),
+            challengers: HashMap::new(),
+            state_transition: None,
             events: Vec::new(),
         }
     }
 
+    /// Create a store that verifies fraud proofs by re-executing journal
+    /// entries through `state_transition`.
+    ///
+    /// The transition is the one the committed roots were built with. It
+    /// cannot be replaced afterwards, and no caller supplies their own: a
+    /// proof judged by the challenger's transition would prove nothing.
+    pub fn with_state_transition(
+        admin: impl Into<String>,
+        fraud_window_seconds: i64,
+        state_transition: impl EventApplier + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state_transition: Some(StateTransition(Box::new(state_transition))),
+            ..Self::new(admin, fraud_window_seconds)
+        }
+    }
+
     /// Create with default 2-hour fraud window.
     pub fn with_default_window(admin: impl Into<String>) -> Self {
         Self::new(admin, 7200)
     }
 
     /// Submit a new state root. Only admin or operator can submit.
+    ///
+    /// With bonds configured, locks the commit stake against the root out
+    /// of the committer's posted stake; see [`post_stake`](Self::post_stake).
     pub fn submit_root(
         &mut self,
         caller: &str,
@@ -88,6 +284,7 @@ impl CommitmentStore {
         {
             return Err(CommitmentError::Unauthorized);
         }
+        self.lock_commit_stake(caller, root_hash, current_time)?;
 
         let commitment = StateCommitment {
             root_hash,
@@ -140,19 +337,65 @@ impl CommitmentStore {
         }
     }
 
+    /// Canonical bytes an account's signer signs to let `challenger` bond
+    /// disputes from it: `challenger|challenger|account_id`.
+    pub fn challenger_signing_payload(challenger: &str, account_id: AccountId) -> Vec<u8> {
+        format!("challenger|{}|{}", challenger, account_id).into_bytes()
+    }
+
+    /// Bind `challenger` to the vault account its dispute bonds are locked
+    /// from, replacing any earlier binding.
+    ///
+    /// `signature` is the Ed25519 signature of the account's withdrawal
+    /// signer (see [`WithdrawalQueue::register_signer`]) over the SHA-256
+    /// hash of [`challenger_signing_payload`](Self::challenger_signing_payload);
+    /// accounts without a registered signer cannot be bound.
+    pub fn register_challenger(
+        &mut self,
+        signers: &WithdrawalQueue,
+        challenger: &str,
+        account_id: AccountId,
+        signature: &[u8],
+    ) -> Result<(), CommitmentError> {
+        let hash: [u8; 32] =
+            Sha256::digest(Self::challenger_signing_payload(challenger, account_id)).into();
+        let signed = signers
+            .signer(&account_id)
+            .is_some_and(|public_key| verify_strict(public_key, &hash, signature));
+        if !signed {
+            return Err(CommitmentError::InvalidSignature);
+        }
+        self.challengers.insert(challenger.to_string(), account_id);
+        Ok(())
+    }
+
+    /// Vault account a registered challenger bonds disputes from.
+    pub fn challenger_account(&self, challenger: &str) -> Option<AccountId> {
+        self.challengers.get(challenger).copied()
+    }
+
     /// Raise a dispute against the latest commitment.
     ///
-    /// Anyone can dispute within the fraud proof window.
+    /// Anyone can dispute within the fraud proof window, once registered
+    /// with [`register_challenger`](Self::register_challenger). With bonds
+    /// configured, the dispute bond is locked from the challenger's vault
+    /// balance; a challenger who cannot cover it is refused with
+    /// `InsufficientBond`.
     pub fn raise_dispute(
         &mut self,
+        vault: &mut Vault,
         challenger: &str,
         reason: &str,
         current_time: i64,
     ) -> Result<ContractEvent, CommitmentError> {
+        let account_id = self
+            .challenger_account(challenger)
+            .ok_or(CommitmentError::ChallengerNotRegistered)?;
         let latest = self
             .history
             .last()
             .ok_or(CommitmentError::NoCommitment)?;
+        let root_hash = latest.root_hash;
 
         // Check fraud window
         let elapsed = current_time - latest.submitted_at;
@@ -170,18 +413,26 @@ impl CommitmentStore {
             return Err(CommitmentError::DuplicateDispute);
         }
 
+        let bond = self
+            .bond_config
+            .as_ref()
+            .map_or(Decimal::ZERO, |config| config.dispute_bond);
+        self.lock_from_vault(vault, account_id, bond, BondKind::DisputeBond, Some(root_hash))?;
+
         let dispute = Dispute {
-            root_hash: latest.root_hash,
+            root_hash,
             challenger: challenger.to_string(),
             reason: reason.to_string(),
             raised_at: current_time,
             status: DisputeStatus::Pending,
+            account_id,
+            bond,
         };
 
         self.disputes.push(dispute);
 
         let event = ContractEvent::DisputeRaised(DisputeRaised {
-            root_hash: latest.root_hash,
+            root_hash,
             challenger: challenger.to_string(),
             reason: reason.to_string(),
             raised_at: current_time,
@@ -191,40 +442,185 @@ impl CommitmentStore {
         Ok(event)
     }
 
-    /// Resolve a pending dispute. Admin-only.
+    /// Verify a fraud proof against the committed root `root_hash`.
+    ///
+    /// Re-executes `proof.entries` from `proof.pre_state` through the
+    /// store's state transition and compares the recomputed root with the
+    /// committed and the claimed one. Read-only; see
+    /// [`resolve_dispute`](Self::resolve_dispute).
+    pub fn verify_fraud_proof(
+        &self,
+        root_hash: [u8; 32],
+        proof: &FraudProof,
+    ) -> Result<Verdict, CommitmentError> {
+        let StateTransition(applier) = self
+            .state_transition
+            .as_ref()
+            .ok_or(CommitmentError::NoStateTransition)?;
+        let index = self
+            .history
+            .iter()
+            .position(|c| c.root_hash == root_hash)
+            .ok_or(CommitmentError::NoCommitment)?;
+        let (expected_pre_root, first_sequence) = match index {
+            0 => (engine_state_root(&EngineState::empty()), 1),
+            i => (self.history[i - 1].root_hash, self.history[i - 1].block_number + 1),
+        };
+        let last_sequence = self.history[index].block_number;
+
+        if proof.pre_state_root != expected_pre_root {
+            return Ok(Verdict::InvalidProof(ProofDefect::PreStateMismatch {
+                expected: expected_pre_root,
+                found: proof.pre_state_root,
+            }));
+        }
+        if engine_state_root(&proof.pre_state) != proof.pre_state_root {
+            return Ok(Verdict::InvalidProof(ProofDefect::PreStateCorrupt));
+        }
+        let covers_range = proof.entries.len() as u64 + first_sequence == last_sequence + 1
+            && proof
+                .entries
+                .iter()
+                .zip(first_sequence..)
+                .all(|(entry, sequence)| entry.sequence == sequence);
+        if !covers_range {
+            return Ok(Verdict::InvalidProof(ProofDefect::WrongEntries {
+                expected: (first_sequence, last_sequence),
+            }));
+        }
+
+        let mut state = proof.pre_state.clone();
+        for entry in &proof.entries {
+            if !entry.verify_checksum() {
+                return Ok(Verdict::InvalidProof(ProofDefect::CorruptEntry {
+                    sequence: entry.sequence,
+                }));
+            }
+            if let Err(reason) = applier.apply(&mut state, entry) {
+                return Ok(Verdict::InvalidProof(ProofDefect::EntryRejected {
+                    sequence: entry.sequence,
+                    reason,
+                }));
+            }
+        }
+
+        let recomputed_root = engine_state_root(&state);
+        Ok(if recomputed_root == root_hash {
+            Verdict::CommitmentUpheld
+        } else if recomputed_root == proof.claimed_post_root {
+            Verdict::CommitmentRejected { recomputed_root }
+        } else {
+            Verdict::InvalidProof(ProofDefect::ClaimMismatch { recomputed_root })
+        })
+    }
+
+    /// Resolve a pending dispute with a fraud proof. Anyone may submit one.
     ///
-    /// If accepted, the disputed commitment is invalidated (removed from history).
-    /// If rejected, the dispute is marked as rejected.
+    /// If the verdict is [`Verdict::CommitmentRejected`] the dispute is
+    /// accepted and the commitment invalidated (removed from history);
+    /// otherwise the dispute is rejected and the commitment stands. Bonds
+    /// are settled through `vault`; see [`BondConfig`].
+    /// Emits `DisputeResolved`.
     pub fn resolve_dispute(
         &mut self,
+        vault: &mut Vault,
+        caller: &str,
+        root_hash: [u8; 32],
+        proof: &FraudProof,
+        current_time: i64,
+    ) -> Result<Verdict, CommitmentError> {
+        if !self.has_pending_dispute(root_hash) {
+            return Err(CommitmentError::DisputeNotFound);
+        }
+        let verdict = self.verify_fraud_proof(root_hash, proof)?;
+        let accept = matches!(verdict, Verdict::CommitmentRejected { .. });
+        self.settle_dispute(vault, root_hash, accept, caller, current_time)?;
+        Ok(verdict)
+    }
+
+    /// Admin override of a pending dispute's verdict, without a proof.
+    ///
+    /// With a timelock delay configured the resolution is queued instead
+    /// and the `TimelockQueued` event returned; see
+    /// [`execute_override`](Self::execute_override). A fraud proof may
+    /// still settle the dispute while it waits.
+    pub fn admin_resolve_dispute(
+        &mut self,
+        vault: &mut Vault,
         caller: &str,
         root_hash: [u8; 32],
         accept: bool,
-    ) -> Result<(), CommitmentError> {
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
         if !self.access_control.is_admin(caller) {
             return Err(CommitmentError::Unauthorized);
         }
+        if !self.has_pending_dispute(root_hash) {
+            return Err(CommitmentError::DisputeNotFound);
+        }
+
+        let operation = CommitmentOperation::ResolveDispute { root_hash, accept };
+        if self.timelock.delay(operation.name()) > 0 {
+            let (_, event) = self.timelock.queue(operation, caller, current_time);
+            self.events.push(event.clone());
+            return Ok(event);
+        }
+        self.settle_dispute(vault, root_hash, accept, caller, current_time)
+    }
+
+    fn has_pending_dispute(&self, root_hash: [u8; 32]) -> bool {
+        self.disputes
+            .iter()
+            .any(|d| d.root_hash == root_hash && d.status == DisputeStatus::Pending)
+    }
+
+    fn settle_dispute(
+        &mut self,
+        vault: &mut Vault,
+        root_hash: [u8; 32],
+        accept: bool,
+        resolved_by: &str,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        if !self.has_pending_dispute(root_hash) {
+            return Err(CommitmentError::DisputeNotFound);
+        }
+        self.settle_bonds(vault, root_hash, accept)?;
 
-        let dispute = self
+        // One verdict settles every challenger's dispute on the root
+        let status = if accept {
+            DisputeStatus::Accepted
+        } else {
+            DisputeStatus::Rejected
+        };
+        for dispute in self
             .disputes
             .iter_mut()
-            .find(|d| d.root_hash == root_hash && d.status == DisputeStatus::Pending)
-            .ok_or(CommitmentError::DisputeNotFound)?;
-
+            .filter(|d| d.root_hash == root_hash && d.status == DisputeStatus::Pending)
+        {
+            dispute.status = status;
+        }
         if accept {
-            dispute.status = DisputeStatus::Accepted;
             // Remove the disputed commitment from history
             self.history.retain(|c| c.root_hash != root_hash);
-        } else {
-            dispute.status = DisputeStatus::Rejected;
         }
 
-        Ok(())
+        let event = ContractEvent::DisputeResolved(DisputeResolved {
+            root_hash,
+            accepted: accept,
+            resolved_by: resolved_by.to_string(),
+            resolved_at: current_time,
+        });
+        self.events.push(event.clone());
+        Ok(event)
     }
 
     /// Admin override to force-set a new state root.
     ///
-    /// Used in emergency situations. Bypasses normal submission flow.
+    /// Used in emergency situations. Bypasses normal submission flow. With a
+    /// timelock delay configured the override is queued instead and the
+    /// `TimelockQueued` event returned; see
+    /// [`execute_override`](Self::execute_override).
     pub fn admin_override(
         &mut self,
         caller: &str,
@@ -236,6 +632,446 @@ impl CommitmentStore {
             return Err(CommitmentError::Unauthorized);
         }
 
+        let operation = CommitmentOperation::AdminOverride {
+            root_hash,
+            block_number,
+        };
+        if self.timelock.delay(operation.name()) > 0 {
+            let (_, event) = self.timelock.queue(operation, caller, current_time);
+            self.events.push(event.clone());
+            return Ok(event);
+        }
+        Ok(self.apply_override(caller, root_hash, block_number, current_time))
+    }
+
+    /// Execute a queued override once its delay has passed. Admin-only.
+    ///
+    /// The root is committed (or the dispute settled) at `current_time`,
+    /// attributed to the admin who queued it. A dispute resolution whose
+    /// dispute is no longer pending fails with `DisputeNotFound` and stays
+    /// queued; its bonds are settled through `vault`.
+    pub fn execute_override(
+        &mut self,
+        vault: &mut Vault,
+        caller: &str,
+        operation_id: OperationId,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        if !self.access_control.is_admin(caller) {
+            return Err(CommitmentError::Unauthorized);
+        }
+        if let Some(QueuedOperation {
+            operation: CommitmentOperation::ResolveDispute { root_hash, .. },
+            ..
+        }) = self.timelock.get(operation_id)
+        {
+            if !self.has_pending_dispute(*root_hash) {
+                return Err(CommitmentError::DisputeNotFound);
+            }
+        }
+        let (operation, event) = self.timelock.execute(operation_id, current_time)?;
+        self.events.push(event);
+        let queued_by = self
+            .timelock
+            .get(operation_id)
+            .map(|op| op.queued_by.clone())
+            .unwrap_or_default();
+        match operation {
+            CommitmentOperation::AdminOverride {
+                root_hash,
+                block_number,
+            } => Ok(self.apply_override(&queued_by, root_hash, block_number, current_time)),
+            CommitmentOperation::ResolveDispute { root_hash, accept } => {
+                self.settle_dispute(vault, root_hash, accept, &queued_by, current_time)
+            }
+        }
+    }
+
+    // ───────────────────────── Bonds ─────────────────────────
+
+    /// Configure bond economics. Admin-only.
+    ///
+    /// The bond asset cannot change while any bond or stake is held.
+    pub fn set_bond_config(
+        &mut self,
+        caller: &str,
+        config: BondConfig,
+    ) -> Result<(), CommitmentError> {
+        if !self.access_control.is_admin(caller) {
+            return Err(CommitmentError::Unauthorized);
+        }
+        let asset_changes = self
+            .bond_config
+            .as_ref()
+            .is_some_and(|current| current.asset != config.asset);
+        if asset_changes && self.holds_bonds() {
+            return Err(CommitmentError::BondsOutstanding);
+        }
+        self.bond_config = Some(config);
+        Ok(())
+    }
+
+    /// Current bond economics, if configured.
+    pub fn bond_config(&self) -> Option<&BondConfig> {
+        self.bond_config.as_ref()
+    }
+
+    /// Post stake for `committer` from `account_id`'s vault balance.
+    /// Only admin or operator can post.
+    ///
+    /// A committer's stake is tied to the account it was first posted
+    /// from. Emits `BondLocked`.
+    pub fn post_stake(
+        &mut self,
+        vault: &mut Vault,
+        committer: &str,
+        account_id: AccountId,
+        amount: Decimal,
+    ) -> Result<ContractEvent, CommitmentError> {
+        if !self.access_control.is_admin(committer)
+            && !self.access_control.has_role(committer, Role::Operator)
+        {
+            return Err(CommitmentError::Unauthorized);
+        }
+        if self.bond_config.is_none() {
+            return Err(CommitmentError::BondsNotConfigured);
+        }
+        if amount <= Decimal::ZERO {
+            return Err(VaultError::InvalidAmount.into());
+        }
+        if self
+            .stakes
+            .get(committer)
+            .is_some_and(|stake| stake.account_id != account_id)
+        {
+            return Err(CommitmentError::Unauthorized);
+        }
+
+        let event = self
+            .lock_from_vault(vault, account_id, amount, BondKind::CommitStake, None)?
+            .expect("positive stake emits BondLocked");
+        self.stakes
+            .entry(committer.to_string())
+            .or_insert_with(|| CommitterStake {
+                account_id,
+                free: Decimal::ZERO,
+                locked: HashMap::new(),
+            })
+            .free += amount;
+        Ok(event)
+    }
+
+    /// Withdraw free stake back to the committer's vault account.
+    ///
+    /// Stake locked against roots whose fraud window has passed without a
+    /// pending dispute is released first. Emits `BondReleased`.
+    pub fn withdraw_stake(
+        &mut self,
+        vault: &mut Vault,
+        committer: &str,
+        amount: Decimal,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        if amount <= Decimal::ZERO {
+            return Err(VaultError::InvalidAmount.into());
+        }
+        self.release_expired_stakes(current_time);
+        let asset = self.bond_asset()?;
+        let available = self.free_stake(committer);
+        let stake = self
+            .stakes
+            .get_mut(committer)
+            .filter(|stake| stake.free >= amount)
+            .ok_or_else(|| CommitmentError::InsufficientStake {
+                required: amount.to_string(),
+                available: available.to_string(),
+            })?;
+        vault.safe_credit(stake.account_id, &asset, amount)?;
+        stake.free -= amount;
+
+        let event = ContractEvent::BondReleased(BondReleased {
+            account_id: stake.account_id,
+            asset,
+            amount,
+            kind: BondKind::CommitStake,
+            root_hash: None,
+        });
+        self.events.push(event.clone());
+        Ok(event)
+    }
+
+    /// A committer's stake in escrow.
+    pub fn committer_stake(&self, committer: &str) -> Option<&CommitterStake> {
+        self.stakes.get(committer)
+    }
+
+    fn free_stake(&self, committer: &str) -> Decimal {
+        self.stakes
+            .get(committer)
+            .map_or(Decimal::ZERO, |stake| stake.free)
+    }
+
+    fn bond_asset(&self) -> Result<String, CommitmentError> {
+        self.bond_config
+            .as_ref()
+            .map(|config| config.asset.clone())
+            .ok_or(CommitmentError::BondsNotConfigured)
+    }
+
+    fn holds_bonds(&self) -> bool {
+        let stake_held = self
+            .stakes
+            .values()
+            .any(|stake| stake.free > Decimal::ZERO || !stake.locked.is_empty());
+        let bond_held = self
+            .disputes
+            .iter()
+            .any(|d| d.status == DisputeStatus::Pending && d.bond > Decimal::ZERO);
+        stake_held || bond_held
+    }
+
+    /// Debit `amount` of the bond asset from `account_id` into escrow.
+    /// Emits `BondLocked`, unless `amount` is zero.
+    fn lock_from_vault(
+        &mut self,
+        vault: &mut Vault,
+        account_id: AccountId,
+        amount: Decimal,
+        kind: BondKind,
+        root_hash: Option<[u8; 32]>,
+    ) -> Result<Option<ContractEvent>, CommitmentError> {
+        if amount.is_zero() {
+            return Ok(None);
+        }
+        let asset = self.bond_asset()?;
+        let available = vault.get_balance(&account_id, &asset);
+        if available < amount {
+            return Err(CommitmentError::InsufficientBond {
+                required: amount.to_string(),
+                available: available.to_string(),
+            });
+        }
+        vault.safe_debit(&account_id, &asset, amount)?;
+
+        let event = ContractEvent::BondLocked(BondLocked {
+            account_id,
+            asset,
+            amount,
+            kind,
+            root_hash,
+        });
+        self.events.push(event.clone());
+        Ok(Some(event))
+    }
+
+    /// Lock the commit stake against a newly submitted root.
+    fn lock_commit_stake(
+        &mut self,
+        committer: &str,
+        root_hash: [u8; 32],
+        current_time: i64,
+    ) -> Result<(), CommitmentError> {
+        let Some(config) = &self.bond_config else {
+            return Ok(());
+        };
+        let (asset, required) = (config.asset.clone(), config.commit_stake);
+        if required.is_zero() {
+            return Ok(());
+        }
+        self.release_expired_stakes(current_time);
+        let available = self.free_stake(committer);
+        let stake = self
+            .stakes
+            .get_mut(committer)
+            .filter(|stake| stake.free >= required)
+            .ok_or_else(|| CommitmentError::InsufficientStake {
+                required: required.to_string(),
+                available: available.to_string(),
+            })?;
+        stake.free -= required;
+        *stake.locked.entry(root_hash).or_default() += required;
+
+        self.events.push(ContractEvent::BondLocked(BondLocked {
+            account_id: stake.account_id,
+            asset,
+            amount: required,
+            kind: BondKind::CommitStake,
+            root_hash: Some(root_hash),
+        }));
+        Ok(())
+    }
+
+    /// Return stake locked against roots whose fraud window has passed
+    /// without a pending dispute to the committers' free stake.
+    fn release_expired_stakes(&mut self, current_time: i64) {
+        let Some(asset) = self.bond_config.as_ref().map(|config| config.asset.clone()) else {
+            return;
+        };
+        let expired: Vec<[u8; 32]> = self
+            .history
+            .iter()
+            .filter(|c| current_time - c.submitted_at >= self.fraud_window_seconds)
+            .map(|c| c.root_hash)
+            .filter(|root| !self.has_pending_dispute(*root))
+            .collect();
+        for stake in self.stakes.values_mut() {
+            for root_hash in &expired {
+                let Some(amount) = stake.locked.remove(root_hash) else {
+                    continue;
+                };
+                stake.free += amount;
+                self.events.push(ContractEvent::BondReleased(BondReleased {
+                    account_id: stake.account_id,
+                    asset: asset.clone(),
+                    amount,
+                    kind: BondKind::CommitStake,
+                    root_hash: Some(*root_hash),
+                }));
+            }
+        }
+    }
+
+    /// Settle the bonds of every pending dispute on `root_hash`.
+    ///
+    /// Accepted: each challenger gets their bond back plus the reward, paid
+    /// out of the committer's stake on the root in equal shares if it
+    /// cannot cover every reward; the rest of the stake goes to the
+    /// treasury. Rejected: every bond goes to the treasury.
+    fn settle_bonds(
+        &mut self,
+        vault: &mut Vault,
+        root_hash: [u8; 32],
+        accept: bool,
+    ) -> Result<(), CommitmentError> {
+        let Some(config) = self.bond_config.clone() else {
+            return Ok(());
+        };
+        let challengers: Vec<(AccountId, Decimal)> = self
+            .disputes
+            .iter()
+            .filter(|d| d.root_hash == root_hash && d.status == DisputeStatus::Pending)
+            .map(|d| (d.account_id, d.bond))
+            .collect();
+        let mut transfers = Vec::new();
+
+        if !accept {
+            for (account_id, bond) in challengers {
+                transfers.push(ContractEvent::BondSlashed(BondSlashed {
+                    from_account: account_id,
+                    to_account: config.treasury,
+                    asset: config.asset.clone(),
+                    amount: bond,
+                    kind: BondKind::DisputeBond,
+                    root_hash,
+                }));
+            }
+        } else {
+            let committer = self
+                .history
+                .iter()
+                .find(|c| c.root_hash == root_hash)
+                .map(|c| c.submitter.clone());
+            let slashed = committer
+                .and_then(|committer| self.stakes.get_mut(&committer))
+                .and_then(|stake| Some((stake.account_id, stake.locked.remove(&root_hash)?)));
+            let (committer_account, mut stake) =
+                slashed.unwrap_or((config.treasury, Decimal::ZERO));
+            let share = (stake / Decimal::from(challengers.len()))
+                .round_dp_with_strategy(stake.scale(), RoundingStrategy::ToZero);
+            let reward = config.challenger_reward.min(share);
+
+            for (account_id, bond) in challengers {
+                transfers.push(ContractEvent::BondReleased(BondReleased {
+                    account_id,
+                    asset: config.asset.clone(),
+                    amount: bond,
+                    kind: BondKind::DisputeBond,
+                    root_hash: Some(root_hash),
+                }));
+                transfers.push(ContractEvent::BondSlashed(BondSlashed {
+                    from_account: committer_account,
+                    to_account: account_id,
+                    asset: config.asset.clone(),
+                    amount: reward,
+                    kind: BondKind::CommitStake,
+                    root_hash,
+                }));
+                stake -= reward;
+            }
+            transfers.push(ContractEvent::BondSlashed(BondSlashed {
+                from_account: committer_account,
+                to_account: config.treasury,
+                asset: config.asset.clone(),
+                amount: stake,
+                kind: BondKind::CommitStake,
+                root_hash,
+            }));
+        }
+
+        for event in transfers {
+            let (to_account, amount) = match &event {
+                ContractEvent::BondReleased(e) => (e.account_id, e.amount),
+                ContractEvent::BondSlashed(e) => (e.to_account, e.amount),
+                _ => unreachable!("bond settlement emits releases and slashes only"),
+            };
+            if amount.is_zero() {
+                continue;
+            }
+            vault.safe_credit(to_account, &config.asset, amount)?;
+            self.events.push(event);
+        }
+        Ok(())
+    }
+
+    /// Cancel a queued override. Admin or guardian only.
+    pub fn cancel_operation(
+        &mut self,
+        caller: &str,
+        operation_id: OperationId,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        if !self.access_control.is_admin(caller)
+            && !self.access_control.has_role(caller, Role::Guardian)
+        {
+            return Err(CommitmentError::Unauthorized);
+        }
+        let event = self.timelock.cancel(operation_id, caller, current_time)?;
+        self.events.push(event.clone());
+        Ok(event)
+    }
+
+    /// Set the timelock delay for a [`CommitmentOperation`] by name.
+    /// Admin-only. A delay of zero applies the operation immediately.
+    pub fn set_timelock_delay(
+        &mut self,
+        caller: &str,
+        operation: &str,
+        delay_seconds: i64,
+    ) -> Result<(), CommitmentError> {
+        if !self.access_control.is_admin(caller) {
+            return Err(CommitmentError::Unauthorized);
+        }
+        self.timelock.set_delay(operation, delay_seconds);
+        Ok(())
+    }
+
+    /// Get the store's timelock.
+    pub fn timelock(&self) -> &Timelock<CommitmentOperation> {
+        &self.timelock
+    }
+
+    /// Grant guardian role for cancelling queued overrides.
+    pub fn grant_guardian(&mut self, admin: &str, guardian: impl Into<String>) -> bool {
+        self.access_control.grant_role(admin, guardian, Role::Guardian)
+    }
+
+    fn apply_override(
+        &mut self,
+        caller: &str,
+        root_hash: [u8; 32],
+        block_number: u64,
+        current_time: i64,
+    ) -> ContractEvent {
         let commitment = StateCommitment {
             root_hash,
             block_number,
@@ -253,7 +1089,27 @@ impl CommitmentStore {
         });
 
         self.events.push(event.clone());
-        Ok(event)
+        event
+    }
+
+    /// Record the settlement root of a processed withdrawal batch.
+    ///
+    /// Called by the withdrawal queue; batch roots are not state roots and
+    /// are not subject to disputes.
+    pub fn record_withdrawal_batch(&mut self, commitment: WithdrawalBatchCommitment) {
+        self.withdrawal_batches.push(commitment);
+    }
+
+    /// Get all recorded withdrawal batch commitments.
+    pub fn withdrawal_batches(&self) -> &[WithdrawalBatchCommitment] {
+        &self.withdrawal_batches
+    }
+
+    /// Get the commitment for a withdrawal batch by ID.
+    pub fn withdrawal_batch(&self, batch_id: u64) -> Option<&WithdrawalBatchCommitment> {
+        self.withdrawal_batches
+            .iter()
+            .find(|b| b.batch_id == batch_id)
     }
 
     /// Get active disputes.
@@ -285,14 +1141,138 @@ pub fn compute_hash(data: &[u8]) -> [u8; 32] {
     hasher.finalize().into()
 }
 
+// ───────────────────────── Merkle Tree ─────────────────────────
+
+/// One step of a Merkle inclusion proof, from the leaf upwards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    /// Whether the sibling is the left child
+    pub sibling_is_left: bool,
+}
+
+/// Merkle inclusion proof for one leaf.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub steps: Vec<ProofStep>,
+}
+
+/// Hash of a Merkle leaf. Leaves and inner nodes are domain-separated so a
+/// node can never be passed off as a leaf.
+pub fn merkle_leaf(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Hash one tree level into the next. An odd last node is carried up
+/// unchanged rather than paired with itself.
+fn merkle_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => merkle_node(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Merkle root over leaf hashes, in order. The root of no leaves is all
+/// zeroes.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = merkle_level(&level);
+    }
+    level[0]
+}
+
+/// Inclusion proof for the leaf at `index`, or `None` if out of range.
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Option<MerkleProof> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut steps = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            steps.push(ProofStep {
+                sibling: level[sibling],
+                sibling_is_left: sibling < index,
+            });
+        }
+        level = merkle_level(&level);
+        index /= 2;
+    }
+    Some(MerkleProof { steps })
+}
+
+/// Check that `leaf` is included under `root` via `proof`.
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &MerkleProof) -> bool {
+    let computed = proof.steps.iter().fold(*leaf, |acc, step| {
+        if step.sibling_is_left {
+            merkle_node(&step.sibling, &acc)
+        } else {
+            merkle_node(&acc, &step.sibling)
+        }
+    });
+    computed == *root
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::TimelockError;
+    use persistence::recovery::DefaultEventApplier;
 
     fn test_root() -> [u8; 32] {
         compute_hash(b"test_state_data")
     }
 
+    fn signer(name: &str) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&compute_hash(name.as_bytes()))
+    }
+
+    fn registration(key: &ed25519_dalek::SigningKey, challenger: &str, account_id: AccountId) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+        let payload = CommitmentStore::challenger_signing_payload(challenger, account_id);
+        let hash: [u8; 32] = Sha256::digest(payload).into();
+        key.sign(&hash).to_bytes().to_vec()
+    }
+
+    /// Register `challenger` to bond from `account_id`, signed by the
+    /// account's signer, and raise a dispute
+    fn raise(
+        store: &mut CommitmentStore,
+        vault: &mut Vault,
+        challenger: &str,
+        account_id: AccountId,
+        reason: &str,
+        current_time: i64,
+    ) -> Result<ContractEvent, CommitmentError> {
+        let key = signer(&account_id.to_string());
+        let mut signers = WithdrawalQueue::new(0);
+        signers.register_signer(account_id, key.verifying_key().to_bytes());
+        let signature = registration(&key, challenger, account_id);
+        store.register_challenger(&signers, challenger, account_id, &signature)?;
+        store.raise_dispute(vault, challenger, reason, current_time)
+    }
+
     #[test]
     fn test_submit_root_success() {
         let mut store = CommitmentStore::with_default_window("admin");
@@ -358,10 +1338,10 @@ mod tests {
     #[test]
     fn test_raise_dispute_within_window() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         store.submit_root("admin", test_root(), 1, 1000).unwrap();
 
-        let event = store
-            .raise_dispute("challenger", "invalid balances", 2000)
+        let event = raise(&mut store, &mut vault, "challenger", AccountId::new(), "invalid balances", 2000)
             .unwrap();
         assert!(matches!(event, ContractEvent::DisputeRaised(_)));
         assert_eq!(store.disputes().len(), 1);
@@ -370,32 +1350,259 @@ mod tests {
     #[test]
     fn test_raise_dispute_after_window_expires() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         store.submit_root("admin", test_root(), 1, 1000).unwrap();
 
-        let result = store.raise_dispute("challenger", "too late", 5000);
+        let result =
+            raise(&mut store, &mut vault, "challenger", AccountId::new(), "too late", 5000);
         assert_eq!(result, Err(CommitmentError::FraudWindowExpired));
     }
 
     #[test]
     fn test_raise_dispute_duplicate() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         store.submit_root("admin", test_root(), 1, 1000).unwrap();
 
-        store
-            .raise_dispute("challenger", "reason 1", 2000)
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "reason 1", 2000)
             .unwrap();
-        let result = store.raise_dispute("challenger", "reason 2", 2500);
+        let result =
+            raise(&mut store, &mut vault, "challenger", AccountId::new(), "reason 2", 2500);
         assert_eq!(result, Err(CommitmentError::DuplicateDispute));
     }
 
+    fn journal(sequences: std::ops::RangeInclusive<u64>) -> Vec<JournalEntry> {
+        sequences
+            .map(|seq| JournalEntry::new(seq, 1000 + seq as i64, "Trade".into(), vec![seq as u8]))
+            .collect()
+    }
+
+    /// Root reached by applying `entries` to `state`
+    fn replay(mut state: EngineState, entries: &[JournalEntry]) -> (EngineState, [u8; 32]) {
+        for entry in entries {
+            DefaultEventApplier.apply(&mut state, entry).unwrap();
+        }
+        let root = engine_state_root(&state);
+        (state, root)
+    }
+
+    /// Store judging fraud proofs by `DefaultEventApplier`
+    fn proving_store() -> CommitmentStore {
+        CommitmentStore::with_state_transition("admin", 3600, DefaultEventApplier)
+    }
+
+    fn proof_from_genesis(entries: Vec<JournalEntry>, claimed_post_root: [u8; 32]) -> FraudProof {
+        FraudProof {
+            pre_state_root: engine_state_root(&EngineState::empty()),
+            pre_state: EngineState::empty(),
+            entries,
+            claimed_post_root,
+        }
+    }
+
+    #[test]
+    fn test_valid_fraud_proof_rejects_commitment() {
+        let mut store = proving_store();
+        let mut vault = Vault::new("admin");
+        let entries = journal(1..=3);
+        let (_, honest_root) = replay(EngineState::empty(), &entries);
+        let forged = test_root();
+        store.submit_root("admin", forged, 3, 1000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "forged root", 1500)
+            .unwrap();
+
+        let proof = proof_from_genesis(entries, honest_root);
+        let verdict = store
+            .resolve_dispute(&mut vault, "challenger", forged, &proof, 2000)
+            .unwrap();
+        assert_eq!(
+            verdict,
+            Verdict::CommitmentRejected {
+                recomputed_root: honest_root
+            }
+        );
+        assert!(store.history().is_empty());
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Accepted);
+        assert!(matches!(
+            store.events().last(),
+            Some(ContractEvent::DisputeResolved(e)) if e.accepted && e.resolved_by == "challenger"
+        ));
+    }
+
     #[test]
-    fn test_resolve_dispute_accept() {
+    fn test_invalid_fraud_proof_upholds_commitment() {
+        let mut store = proving_store();
+        let mut vault = Vault::new("admin");
+        let entries = journal(1..=3);
+        let (_, honest_root) = replay(EngineState::empty(), &entries);
+        store.submit_root("admin", honest_root, 3, 1000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "looks wrong", 1500)
+            .unwrap();
+
+        // Re-execution reaches the committed root, whatever was claimed
+        let proof = proof_from_genesis(entries.clone(), test_root());
+        let verdict = store
+            .resolve_dispute(
+                &mut vault,
+                "challenger",
+                honest_root,
+                &proof,
+                2000,
+            )
+            .unwrap();
+        assert_eq!(verdict, Verdict::CommitmentUpheld);
+        assert_eq!(store.history().len(), 1);
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Rejected);
+
+        // Without an open dispute there is nothing to resolve
+        assert_eq!(
+            store.resolve_dispute(
+                &mut vault,
+                "challenger",
+                honest_root,
+                &proof,
+                2100
+            ),
+            Err(CommitmentError::DisputeNotFound)
+        );
+    }
+
+    /// Transition a challenger would like disputes judged by: every entry
+    /// is dropped, so state never moves
+    struct ForgingApplier;
+
+    impl EventApplier for ForgingApplier {
+        fn apply(&self, _state: &mut EngineState, _entry: &JournalEntry) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_challenger_transition_cannot_reject_honest_root() {
+        let mut store = proving_store();
+        let mut vault = Vault::new("admin");
+        let entries = journal(1..=3);
+        let (_, honest_root) = replay(EngineState::empty(), &entries);
+        store.submit_root("admin", honest_root, 3, 1000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "forged root", 1500)
+            .unwrap();
+
+        // The claim matches the challenger's transition, not the store's
+        let mut forged_state = EngineState::empty();
+        for entry in &entries {
+            ForgingApplier.apply(&mut forged_state, entry).unwrap();
+        }
+        let proof = proof_from_genesis(entries, engine_state_root(&forged_state));
+        let verdict = store
+            .resolve_dispute(&mut vault, "challenger", honest_root, &proof, 2000)
+            .unwrap();
+        assert_eq!(verdict, Verdict::CommitmentUpheld);
+        assert_eq!(store.history().len(), 1);
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Rejected);
+
+        // Without a transition there is nothing to judge a proof by
+        let unproven = CommitmentStore::new("admin", 3600);
+        assert_eq!(
+            unproven.verify_fraud_proof(honest_root, &proof),
+            Err(CommitmentError::NoStateTransition)
+        );
+    }
+
+    #[test]
+    fn test_fraud_proof_claim_and_entries_must_hold() {
+        let mut store = proving_store();
+        let forged = test_root();
+        store.submit_root("admin", forged, 3, 1000).unwrap();
+        let verify = |proof: &FraudProof| {
+            store
+                .verify_fraud_proof(forged, proof)
+                .unwrap()
+        };
+
+        // Claimed root is not what re-execution reaches
+        let (_, honest_root) = replay(EngineState::empty(), &journal(1..=3));
+        assert_eq!(
+            verify(&proof_from_genesis(journal(1..=3), [7u8; 32])),
+            Verdict::InvalidProof(ProofDefect::ClaimMismatch {
+                recomputed_root: honest_root
+            })
+        );
+        // Entries must cover exactly the committed sequences
+        for entries in [journal(1..=2), journal(2..=4), journal(1..=4)] {
+            assert_eq!(
+                verify(&proof_from_genesis(entries, honest_root)),
+                Verdict::InvalidProof(ProofDefect::WrongEntries { expected: (1, 3) })
+            );
+        }
+        let mut tampered = journal(1..=3);
+        tampered[1].payload = b"forged".to_vec();
+        assert_eq!(
+            verify(&proof_from_genesis(tampered, honest_root)),
+            Verdict::InvalidProof(ProofDefect::CorruptEntry { sequence: 2 })
+        );
+    }
+
+    #[test]
+    fn test_fraud_proof_with_wrong_pre_state() {
+        let mut store = proving_store();
+        let mut vault = Vault::new("admin");
+        let (first_state, first_root) = replay(EngineState::empty(), &journal(1..=3));
+        store.submit_root("admin", first_root, 3, 1000).unwrap();
+        let forged = test_root();
+        store.submit_root("admin", forged, 5, 2000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "forged root", 2500)
+            .unwrap();
+
+        // Built on the genesis state rather than the previous commitment
+        let (_, from_genesis) = replay(EngineState::empty(), &journal(4..=5));
+        let proof = proof_from_genesis(journal(4..=5), from_genesis);
+        let verdict = store
+            .resolve_dispute(&mut vault, "challenger", forged, &proof, 3000)
+            .unwrap();
+        assert_eq!(
+            verdict,
+            Verdict::InvalidProof(ProofDefect::PreStateMismatch {
+                expected: first_root,
+                found: proof.pre_state_root,
+            })
+        );
+        assert_eq!(store.history().len(), 2);
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Rejected);
+
+        // The right root over the wrong state is caught too
+        let proof = FraudProof {
+            pre_state_root: first_root,
+            ..proof
+        };
+        assert_eq!(
+            store.verify_fraud_proof(forged, &proof),
+            Ok(Verdict::InvalidProof(ProofDefect::PreStateCorrupt))
+        );
+        let (_, honest_root) = replay(first_state.clone(), &journal(4..=5));
+        let proof = FraudProof {
+            pre_state_root: first_root,
+            pre_state: first_state,
+            entries: journal(4..=5),
+            claimed_post_root: honest_root,
+        };
+        assert_eq!(
+            store.verify_fraud_proof(forged, &proof),
+            Ok(Verdict::CommitmentRejected {
+                recomputed_root: honest_root
+            })
+        );
+    }
+
+    #[test]
+    fn test_admin_resolve_dispute_accept() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         let root = test_root();
         store.submit_root("admin", root, 1, 1000).unwrap();
-        store.raise_dispute("challenger", "bad root", 2000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "bad root", 2000)
+            .unwrap();
 
-        store.resolve_dispute("admin", root, true).unwrap();
+        store.admin_resolve_dispute(&mut vault, "admin", root, true, 2500).unwrap();
 
         // Commitment removed from history
         assert!(store.history().is_empty());
@@ -403,13 +1610,15 @@ mod tests {
     }
 
     #[test]
-    fn test_resolve_dispute_reject() {
+    fn test_admin_resolve_dispute_reject() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         let root = test_root();
         store.submit_root("admin", root, 1, 1000).unwrap();
-        store.raise_dispute("challenger", "bad root", 2000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "bad root", 2000)
+            .unwrap();
 
-        store.resolve_dispute("admin", root, false).unwrap();
+        store.admin_resolve_dispute(&mut vault, "admin", root, false, 2500).unwrap();
 
         // Commitment still in history
         assert_eq!(store.history().len(), 1);
@@ -417,13 +1626,15 @@ mod tests {
     }
 
     #[test]
-    fn test_resolve_dispute_unauthorized() {
+    fn test_admin_resolve_dispute_unauthorized() {
         let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
         let root = test_root();
         store.submit_root("admin", root, 1, 1000).unwrap();
-        store.raise_dispute("challenger", "bad root", 2000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "bad root", 2000)
+            .unwrap();
 
-        let result = store.resolve_dispute("eve", root, true);
+        let result = store.admin_resolve_dispute(&mut vault, "eve", root, true, 2500);
         assert_eq!(result, Err(CommitmentError::Unauthorized));
     }
 
@@ -447,6 +1658,302 @@ mod tests {
         assert_eq!(result, Err(CommitmentError::Unauthorized));
     }
 
+    #[test]
+    fn test_timelocked_admin_override() {
+        let mut store = CommitmentStore::with_default_window("admin");
+        let mut vault = Vault::new("admin");
+        store
+            .set_timelock_delay("admin", CommitmentOperation::ADMIN_OVERRIDE, 600)
+            .unwrap();
+        let root = test_root();
+
+        let event = store.admin_override("admin", root, 99, 5000).unwrap();
+        let ContractEvent::TimelockQueued(queued) = event else {
+            panic!("override was not queued");
+        };
+        assert!(store.history().is_empty());
+        assert_eq!(
+            store.execute_override(&mut vault, "admin", queued.operation_id, 5599),
+            Err(CommitmentError::Timelock(TimelockError::NotReady { execute_after: 5600 }))
+        );
+
+        let event = store.execute_override(&mut vault, "admin", queued.operation_id, 5600).unwrap();
+        assert!(matches!(event, ContractEvent::CommitmentSubmitted(_)));
+        assert_eq!(store.history().len(), 1);
+        assert_eq!(store.history()[0].root_hash, root);
+        assert!(store.execute_override(&mut vault, "admin", queued.operation_id, 5700).is_err());
+    }
+
+    #[test]
+    fn test_timelocked_admin_resolve_dispute() {
+        let mut store = CommitmentStore::new("admin", 3600);
+        let mut vault = Vault::new("admin");
+        store
+            .set_timelock_delay("admin", CommitmentOperation::RESOLVE_DISPUTE, 600)
+            .unwrap();
+        let root = test_root();
+        store.submit_root("admin", root, 1, 1000).unwrap();
+        raise(&mut store, &mut vault, "challenger", AccountId::new(), "bad root", 1500)
+            .unwrap();
+
+        let event = store.admin_resolve_dispute(&mut vault, "admin", root, true, 2000).unwrap();
+        let ContractEvent::TimelockQueued(queued) = event else {
+            panic!("resolution was not queued");
+        };
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Pending);
+        assert_eq!(
+            store.execute_override(&mut vault, "admin", queued.operation_id, 2599),
+            Err(CommitmentError::Timelock(TimelockError::NotReady { execute_after: 2600 }))
+        );
+
+        let event = store.execute_override(&mut vault, "admin", queued.operation_id, 2600).unwrap();
+        assert!(matches!(event, ContractEvent::DisputeResolved(e) if e.accepted));
+        assert!(store.history().is_empty());
+        assert_eq!(store.disputes()[0].status, DisputeStatus::Accepted);
+    }
+
+    /// Store with bonds in USDT (bond 10, stake 100 per root, reward
+    /// `reward`) and "admin" staked 500 from a funded account
+    fn bonded(reward: i64) -> (CommitmentStore, Vault, AccountId, AccountId) {
+        let mut vault = Vault::new("admin");
+        vault.add_to_whitelist("admin", "USDT").unwrap();
+        let mut store = proving_store();
+        let (committer, treasury) = (AccountId::new(), AccountId::new());
+        store
+            .set_bond_config(
+                "admin",
+                BondConfig {
+                    asset: "USDT".to_string(),
+                    dispute_bond: Decimal::from(10),
+                    commit_stake: Decimal::from(100),
+                    challenger_reward: Decimal::from(reward),
+                    treasury,
+                },
+            )
+            .unwrap();
+        vault.deposit(committer, "USDT", Decimal::from(500), "stake_tx").unwrap();
+        store
+            .post_stake(&mut vault, "admin", committer, Decimal::from(500))
+            .unwrap();
+        (store, vault, committer, treasury)
+    }
+
+    fn funded_challenger(vault: &mut Vault, amount: i64) -> AccountId {
+        let account = AccountId::new();
+        vault.deposit(account, "USDT", Decimal::from(amount), "bond_tx").unwrap();
+        account
+    }
+
+    #[test]
+    fn test_successful_dispute_returns_bond_with_reward() {
+        let (mut store, mut vault, committer, treasury) = bonded(30);
+        assert_eq!(vault.get_balance(&committer, "USDT"), Decimal::ZERO);
+        let entries = journal(1..=3);
+        let (_, honest_root) = replay(EngineState::empty(), &entries);
+        let forged = test_root();
+        store.submit_root("admin", forged, 3, 1000).unwrap();
+        let stake = store.committer_stake("admin").unwrap();
+        assert_eq!(stake.free, Decimal::from(400));
+        assert_eq!(stake.locked[&forged], Decimal::from(100));
+
+        let challenger = funded_challenger(&mut vault, 50);
+        raise(&mut store, &mut vault, "challenger", challenger, "forged root", 1500)
+            .unwrap();
+        assert_eq!(vault.get_balance(&challenger, "USDT"), Decimal::from(40));
+        assert!(matches!(
+            &store.events()[store.events().len() - 2],
+            ContractEvent::BondLocked(e)
+                if e.kind == BondKind::DisputeBond && e.root_hash == Some(forged)
+        ));
+
+        let proof = proof_from_genesis(entries, honest_root);
+        store
+            .resolve_dispute(&mut vault, "challenger", forged, &proof, 2000)
+            .unwrap();
+        assert_eq!(vault.get_balance(&challenger, "USDT"), Decimal::from(80));
+        assert_eq!(vault.get_balance(&treasury, "USDT"), Decimal::from(70));
+        let stake = store.committer_stake("admin").unwrap();
+        assert_eq!(stake.free, Decimal::from(400));
+        assert!(stake.locked.is_empty());
+        let slashed: Vec<_> = store
+            .events()
+            .iter()
+            .filter_map(|e| match e {
+                ContractEvent::BondSlashed(e) => Some((e.from_account, e.to_account, e.amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            slashed,
+            vec![
+                (committer, challenger, Decimal::from(30)),
+                (committer, treasury, Decimal::from(70)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_disputes_forfeit_bonds() {
+        let (mut store, mut vault, committer, treasury) = bonded(30);
+        let entries = journal(1..=3);
+        let (_, honest_root) = replay(EngineState::empty(), &entries);
+        store.submit_root("admin", honest_root, 3, 1000).unwrap();
+        let first = funded_challenger(&mut vault, 10);
+        let second = funded_challenger(&mut vault, 25);
+        raise(&mut store, &mut vault, "first", first, "looks wrong", 1500)
+            .unwrap();
+        raise(&mut store, &mut vault, "second", second, "looks wrong", 1600)
+            .unwrap();
+
+        let proof = proof_from_genesis(entries, test_root());
+        let verdict = store
+            .resolve_dispute(&mut vault, "first", honest_root, &proof, 2000)
+            .unwrap();
+        assert_eq!(verdict, Verdict::CommitmentUpheld);
+        assert!(store.disputes().iter().all(|d| d.status == DisputeStatus::Rejected));
+        assert_eq!(vault.get_balance(&first, "USDT"), Decimal::ZERO);
+        assert_eq!(vault.get_balance(&second, "USDT"), Decimal::from(15));
+        assert_eq!(vault.get_balance(&treasury, "USDT"), Decimal::from(20));
+
+        // The committer's stake stays locked until the window passes
+        assert_eq!(
+            store.withdraw_stake(&mut vault, "admin", Decimal::from(500), 4599),
+            Err(CommitmentError::InsufficientStake {
+                required: "500".to_string(),
+                available: "400".to_string(),
+            })
+        );
+        store
+            .withdraw_stake(&mut vault, "admin", Decimal::from(500), 4600)
+            .unwrap();
+        assert_eq!(vault.get_balance(&committer, "USDT"), Decimal::from(500));
+        assert!(store.committer_stake("admin").unwrap().locked.is_empty());
+    }
+
+    #[test]
+    fn test_bonds_must_be_covered() {
+        let (mut store, mut vault, _, _) = bonded(30);
+        store.submit_root("admin", test_root(), 1, 1000).unwrap();
+
+        let challenger = funded_challenger(&mut vault, 5);
+        assert_eq!(
+            raise(&mut store, &mut vault, "challenger", challenger, "bad root", 1500),
+            Err(CommitmentError::InsufficientBond {
+                required: "10".to_string(),
+                available: "5".to_string(),
+            })
+        );
+        assert_eq!(
+            raise(&mut store, &mut vault, "stranger", AccountId::new(), "bad root", 1500),
+            Err(CommitmentError::InsufficientBond {
+                required: "10".to_string(),
+                available: "0".to_string(),
+            })
+        );
+        assert!(store.disputes().is_empty());
+        assert_eq!(vault.get_balance(&challenger, "USDT"), Decimal::from(5));
+
+        // Four more roots use up the posted stake
+        for block in 2..=5 {
+            store.submit_root("admin", compute_hash(&[block]), block as u64, 1000).unwrap();
+        }
+        assert_eq!(
+            store.submit_root("admin", compute_hash(b"sixth"), 6, 1000),
+            Err(CommitmentError::InsufficientStake {
+                required: "100".to_string(),
+                available: "0".to_string(),
+            })
+        );
+        assert_eq!(store.history().len(), 5);
+    }
+
+    #[test]
+    fn test_dispute_bonds_only_from_the_challengers_own_account() {
+        let (mut store, mut vault, _, _) = bonded(30);
+        store.submit_root("admin", test_root(), 1, 1000).unwrap();
+        let victim = funded_challenger(&mut vault, 50);
+        let mut signers = WithdrawalQueue::new(0);
+        signers.register_signer(victim, signer("victim").verifying_key().to_bytes());
+
+        // Naming someone else's account takes their signature
+        assert_eq!(
+            store.raise_dispute(&mut vault, "attacker", "frivolous", 1500),
+            Err(CommitmentError::ChallengerNotRegistered)
+        );
+        let forged = registration(&signer("attacker"), "attacker", victim);
+        assert_eq!(
+            store.register_challenger(&signers, "attacker", victim, &forged),
+            Err(CommitmentError::InvalidSignature)
+        );
+        // A signature binds the challenger it names, and only accounts with a signer
+        let victims = registration(&signer("victim"), "victim", victim);
+        assert_eq!(
+            store.register_challenger(&signers, "attacker", victim, &victims),
+            Err(CommitmentError::InvalidSignature)
+        );
+        let unsigned = funded_challenger(&mut vault, 50);
+        let signature = registration(&signer("attacker"), "attacker", unsigned);
+        assert_eq!(
+            store.register_challenger(&signers, "attacker", unsigned, &signature),
+            Err(CommitmentError::InvalidSignature)
+        );
+        assert!(store.disputes().is_empty());
+        assert_eq!(vault.get_balance(&victim, "USDT"), Decimal::from(50));
+
+        store.register_challenger(&signers, "victim", victim, &victims).unwrap();
+        assert_eq!(store.challenger_account("victim"), Some(victim));
+        store.raise_dispute(&mut vault, "victim", "bad root", 1500).unwrap();
+        assert_eq!(store.disputes()[0].account_id, victim);
+        assert_eq!(vault.get_balance(&victim, "USDT"), Decimal::from(40));
+    }
+
+    #[test]
+    fn test_concurrent_disputes_share_slashed_stake() {
+        let (mut store, mut vault, _, treasury) = bonded(60);
+        let forged = test_root();
+        store.submit_root("admin", forged, 1, 1000).unwrap();
+        let challengers: Vec<AccountId> = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                let account = funded_challenger(&mut vault, 10);
+                raise(&mut store, &mut vault, name, account, "forged root", 1500)
+                    .unwrap();
+                account
+            })
+            .collect();
+        assert_eq!(store.disputes().len(), 3);
+
+        // 100 of stake cannot pay three rewards of 60: each gets a third
+        store.admin_resolve_dispute(&mut vault, "admin", forged, true, 2000).unwrap();
+        for account in &challengers {
+            assert_eq!(vault.get_balance(account, "USDT"), Decimal::from(43));
+        }
+        assert_eq!(vault.get_balance(&treasury, "USDT"), Decimal::from(1));
+        assert!(store.disputes().iter().all(|d| d.status == DisputeStatus::Accepted));
+        assert!(store.history().is_empty());
+    }
+
+    #[test]
+    fn test_merkle_proofs_verify_for_every_leaf() {
+        for count in 1..=9usize {
+            let leaves: Vec<[u8; 32]> = (0..count)
+                .map(|i| merkle_leaf(format!("leaf-{i}").as_bytes()))
+                .collect();
+            let root = merkle_root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, i).unwrap();
+                assert!(
+                    verify_merkle_proof(&root, leaf, &proof),
+                    "{count} leaves, index {i}"
+                );
+                let other = leaves[(i + 1) % count];
+                assert_eq!(verify_merkle_proof(&root, &other, &proof), other == *leaf);
+            }
+            assert!(merkle_proof(&leaves, count).is_none());
+        }
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+    }
+
     #[test]
     fn test_compute_hash_deterministic() {
         let h1 = compute_hash(b"same input");
@@ -2,6 +2,7 @@
 //!
 //! Comprehensive error taxonomy for vault, withdrawal, and commitment operations.
 
+use rust_decimal::Decimal;
 use thiserror::Error;
 
 /// Vault-specific errors
@@ -34,6 +35,43 @@ pub enum VaultError {
 
     #[error("Arithmetic overflow in balance calculation")]
     Overflow,
+
+    #[error("Timelock error: {0}")]
+    Timelock(#[from] TimelockError),
+
+    #[error("Deposit not found: {tx_id}")]
+    DepositNotFound { tx_id: String },
+
+    #[error("Deposit {tx_id} already recorded with different details")]
+    DepositConflict { tx_id: String },
+
+    #[error("Deposit {tx_id} was reverted")]
+    DepositReverted { tx_id: String },
+
+    #[error("Token paused: {token}")]
+    TokenPaused { token: String },
+
+    #[error("Deposit below the {token} minimum of {minimum}")]
+    BelowMinimumDeposit { token: String, minimum: String },
+
+    #[error("Amount has more precision than {token} supports ({decimals} decimals)")]
+    ExcessPrecision { token: String, decimals: u32 },
+}
+
+/// Timelock errors
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TimelockError {
+    #[error("Timelocked operation not found: {operation_id}")]
+    NotFound { operation_id: u64 },
+
+    #[error("Timelocked operation not ready: executable at {execute_after}")]
+    NotReady { execute_after: i64 },
+
+    #[error("Timelocked operation {operation_id} already executed")]
+    AlreadyExecuted { operation_id: u64 },
+
+    #[error("Timelocked operation {operation_id} was cancelled")]
+    Cancelled { operation_id: u64 },
 }
 
 /// Withdrawal-specific errors
@@ -42,6 +80,9 @@ pub enum WithdrawalError {
     #[error("Invalid signature for withdrawal")]
     InvalidSignature,
 
+    #[error("No signer registered for account {account_id}")]
+    SignerNotRegistered { account_id: String },
+
     #[error("Nonce already used: account {account_id}, nonce {nonce}")]
     NonceReused { account_id: String, nonce: u64 },
 
@@ -71,6 +112,45 @@ pub enum WithdrawalError {
 
     #[error("Empty batch: no withdrawals to process")]
     EmptyBatch,
+
+    #[error("Unauthorized: only operator or admin can expedite")]
+    ExpediteUnauthorized,
+
+    #[error("Batch rolled back: withdrawal {withdrawal_id} failed: {reason}")]
+    BatchRolledBack { withdrawal_id: String, reason: String },
+
+    #[error("Withdrawal awaiting approval: {approvals} of {required} approvals")]
+    AwaitingApproval { approvals: usize, required: usize },
+
+    #[error("Withdrawal is not pending approval")]
+    NotPendingApproval,
+
+    #[error("Not an approver: {approver}")]
+    NotApprover { approver: String },
+
+    #[error("Duplicate approval from {approver}")]
+    DuplicateApproval { approver: String },
+
+    #[error("Approval window expired at {expired_at}; withdrawal rejected")]
+    ApprovalExpired { expired_at: i64 },
+
+    #[error("Withdrawal rejected")]
+    Rejected,
+
+    #[error("Unauthorized: only admin can manage approvers")]
+    ApproverUnauthorized,
+
+    #[error("Withdrawal exceeds the {asset} per-withdrawal limit of {limit}")]
+    PerWithdrawalLimitExceeded { asset: String, limit: Decimal },
+
+    #[error("Withdrawal exceeds the {asset} daily limit: {remaining} remaining")]
+    DailyLimitExceeded { asset: String, remaining: Decimal },
+
+    #[error("Unauthorized: only admin can set withdrawal limits")]
+    LimitsUnauthorized,
+
+    #[error("Withdrawal expired at {expired_at}")]
+    Expired { expired_at: i64 },
 }
 
 /// Commitment-specific errors
@@ -96,6 +176,56 @@ pub enum CommitmentError {
 
     #[error("Dispute not found")]
     DisputeNotFound,
+
+    #[error("Commitment too early: {events_since_last} events since last root, minimum {min_interval}")]
+    TooEarly { events_since_last: u64, min_interval: u64 },
+
+    #[error("Commitment gap: sequence {journal_sequence} is more than {max_interval} events past last root at {last_sequence}")]
+    Gap {
+        last_sequence: u64,
+        journal_sequence: u64,
+        max_interval: u64,
+    },
+
+    #[error("Journal sequence {journal_sequence} does not advance past last root at {last_sequence}")]
+    SequenceNotAdvanced { last_sequence: u64, journal_sequence: u64 },
+
+    #[error("Timelock error: {0}")]
+    Timelock(#[from] TimelockError),
+
+    #[error("Insufficient balance for bond: required {required}, available {available}")]
+    InsufficientBond { required: String, available: String },
+
+    #[error("Insufficient stake: required {required}, available {available}")]
+    InsufficientStake { required: String, available: String },
+
+    #[error("Bonds not configured")]
+    BondsNotConfigured,
+
+    #[error("No state transition configured to verify fraud proofs")]
+    NoStateTransition,
+
+    #[error("Challenger is not registered with a bond account")]
+    ChallengerNotRegistered,
+
+    #[error("Invalid signature for challenger registration")]
+    InvalidSignature,
+
+    #[error("Bond asset cannot change while bonds are held")]
+    BondsOutstanding,
+
+    #[error("Vault error: {0}")]
+    Vault(#[from] VaultError),
+}
+
+/// Vault state restore errors
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RestoreError {
+    #[error("Invalid vault state at {key}: {reason}")]
+    InvalidState { key: String, reason: String },
+
+    #[error("Cannot replay {event}: {reason}")]
+    Replay { event: String, reason: String },
 }
 
 #[cfg(test)]
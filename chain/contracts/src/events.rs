@@ -2,12 +2,26 @@
 //!
 //! Events are immutable records emitted by contract operations.
 //! All event types here directly correspond to the frozen event taxonomy.
+//! Journaled `ContractEvent`s carry JSON payloads, registered with the
+//! persistence `EventRegistry` by `register_events`.
 
+use persistence::registry::{Codec, EventRegistry, JournalEvent, RegistryError};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use types::ids::AccountId;
 use uuid::Uuid;
 
+/// Finality state of a deposit in the vault's deposit ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepositState {
+    /// Seen on-chain, below the asset's finality threshold; not spendable
+    Detected,
+    /// Reached finality and credited to the spendable balance
+    Credited,
+    /// Reorged out of the chain
+    Reverted,
+}
+
 /// Deposit detected on-chain (awaiting confirmations)
 ///
 /// Spec §08 §3.7: DepositDetected
@@ -18,11 +32,13 @@ pub struct DepositDetected {
     pub amount: Decimal,
     pub tx_id: String,
     pub confirmations: u64,
+    pub state: DepositState,
 }
 
 /// Deposit confirmed after required confirmations
 ///
-/// Spec §08 §3.7: DepositConfirmed
+/// Spec §08 §3.7: DepositConfirmed. Also emitted for confirmation updates
+/// below the finality threshold, with `state` still `Detected`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DepositConfirmed {
     pub account_id: AccountId,
@@ -30,11 +46,48 @@ pub struct DepositConfirmed {
     pub amount: Decimal,
     pub tx_id: String,
     pub confirmations: u64,
+    pub state: DepositState,
+}
+
+/// Deposit reorged out of the chain
+///
+/// `debited` is what was taken back from the spendable balance. If the
+/// credited funds were already withdrawn, the rest is a `shortfall` for the
+/// risk engine to recover.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositReverted {
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub tx_id: String,
+    pub debited: Decimal,
+    pub shortfall: Decimal,
+}
+
+/// Token metadata set in the vault's token registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenConfigured {
+    pub token: String,
+    pub decimals: u32,
+    pub min_deposit: Decimal,
+    pub confirmation_threshold: u64,
+    pub configured_by: String,
+}
+
+/// Deposits and withdrawals of one token paused or resumed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenPaused {
+    pub token: String,
+    /// `false` when the token is unpaused
+    pub paused: bool,
+    pub by: String,
 }
 
 /// Withdrawal requested by user
 ///
-/// Spec §08 §3.7: WithdrawalRequested
+/// Spec §08 §3.7: WithdrawalRequested. Carries the whole request so the
+/// withdrawal queue can be rebuilt from the journal; `approval_deadline`
+/// is set when the request awaits approval.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WithdrawalRequested {
     pub withdrawal_id: Uuid,
@@ -42,16 +95,121 @@ pub struct WithdrawalRequested {
     pub asset: String,
     pub amount: Decimal,
     pub destination: String,
+    pub nonce: u64,
+    pub requested_at: i64,
+    pub delay_until: i64,
+    pub expires_at: i64,
+    pub approval_deadline: Option<i64>,
 }
 
 /// Withdrawal completed and broadcast on-chain
 ///
-/// Spec §08 §3.7: WithdrawalCompleted
+/// Spec §08 §3.7: WithdrawalCompleted. Settled in batch `batch_id`, the
+/// `fee` was debited from the account's balance.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WithdrawalCompleted {
     pub withdrawal_id: Uuid,
     pub tx_id: String,
     pub fee: Decimal,
+    pub batch_id: Option<u64>,
+}
+
+/// Withdrawal cancelled by an admin; the locked amount was refunded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalCancelled {
+    pub withdrawal_id: Uuid,
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub cancelled_by: String,
+}
+
+/// Withdrawal rejected when its approval window closed; the locked amount
+/// was refunded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalRejected {
+    pub withdrawal_id: Uuid,
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub approval_deadline: i64,
+}
+
+/// Eligible withdrawals deferred by the processing scheduler
+///
+/// Emitted once per asset when a processing window's caps leave ready
+/// withdrawals queued for a later window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalThrottled {
+    pub asset: String,
+    pub window_start: i64,
+    pub deferred: Vec<Uuid>,
+    pub deferred_notional: Decimal,
+}
+
+/// Withdrawal expired unprocessed; the locked amount was refunded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalExpired {
+    pub withdrawal_id: Uuid,
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub expired_at: i64,
+}
+
+/// Approval added to a withdrawal above the approval threshold
+///
+/// `approvals` counts the approvals from current approvers, this one
+/// included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalApprovalAdded {
+    pub withdrawal_id: Uuid,
+    pub approver: String,
+    pub approvals: u64,
+    pub required: u64,
+}
+
+/// Withdrawal reached its required approvals and can be processed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalApproved {
+    pub withdrawal_id: Uuid,
+    pub approvers: Vec<String>,
+}
+
+/// Withdrawal velocity limits changed for an asset
+///
+/// `None` means no limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalLimitUpdated {
+    pub asset: String,
+    pub max_per_withdrawal: Option<Decimal>,
+    pub daily_cap: Option<Decimal>,
+    /// Requests over the daily cap go to approval instead of being rejected
+    pub over_daily_cap_requires_approval: bool,
+    pub updated_by: String,
+}
+
+/// Withdrawal batch settled under a single commitment
+///
+/// Emitted once per processed batch; `root` is the Merkle root recorded
+/// in the `CommitmentStore`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalBatchProcessed {
+    pub batch_id: u64,
+    pub root: [u8; 32],
+    pub item_count: u64,
+    pub processed_at: i64,
+}
+
+/// Withdrawal left out of a batch
+///
+/// Emitted when an item fails under the skip policy; the request stays
+/// pending for a later batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalSkipped {
+    pub batch_id: u64,
+    pub withdrawal_id: Uuid,
+    pub reason: String,
 }
 
 /// State commitment root submitted
@@ -65,6 +223,19 @@ pub struct CommitmentSubmitted {
     pub submitted_at: i64,
 }
 
+/// State commitment is approaching the emergency-exit threshold
+///
+/// Emitted when the time since the last root crosses a warning level of the
+/// cadence policy's maximum staleness.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentStale {
+    pub last_sequence: u64,
+    pub last_submitted_at: i64,
+    pub staleness_seconds: i64,
+    pub max_staleness_seconds: i64,
+    pub warning_percent: u8,
+}
+
 /// Dispute raised against a committed state root
 ///
 /// Emitted when a challenger contests a state root within the fraud proof window.
@@ -76,15 +247,189 @@ pub struct DisputeRaised {
     pub raised_at: i64,
 }
 
+/// Dispute settled, by fraud proof or timelocked admin override
+///
+/// `accepted` means the commitment was invalidated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisputeResolved {
+    pub root_hash: [u8; 32],
+    pub accepted: bool,
+    pub resolved_by: String,
+    pub resolved_at: i64,
+}
+
+/// What a bond held by the commitment store secures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BondKind {
+    /// Challenger's bond on a dispute
+    DisputeBond,
+    /// Committer's stake behind submitted roots
+    CommitStake,
+}
+
+/// Funds locked as a bond: moved from the vault into escrow, or locked
+/// against a root out of a committer's posted stake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondLocked {
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub kind: BondKind,
+    pub root_hash: Option<[u8; 32]>,
+}
+
+/// Bond released to its owner: credited back to the vault, or a root's
+/// stake returned to the committer's posted stake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondReleased {
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub kind: BondKind,
+    pub root_hash: Option<[u8; 32]>,
+}
+
+/// Bond forfeited on a settled dispute and credited to `to_account`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondSlashed {
+    pub from_account: AccountId,
+    pub to_account: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub kind: BondKind,
+    pub root_hash: [u8; 32],
+}
+
+/// Privileged operation queued behind a timelock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockQueued {
+    pub operation_id: u64,
+    pub operation: String,
+    pub queued_by: String,
+    pub execute_after: i64,
+}
+
+/// Timelocked operation executed after its delay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockExecuted {
+    pub operation_id: u64,
+    pub operation: String,
+    pub executed_at: i64,
+}
+
+/// Timelocked operation cancelled before execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockCancelled {
+    pub operation_id: u64,
+    pub operation: String,
+    pub cancelled_by: String,
+    pub cancelled_at: i64,
+}
+
 /// Enum wrapper for all contract events, enabling uniform handling.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContractEvent {
     DepositDetected(DepositDetected),
     DepositConfirmed(DepositConfirmed),
+    DepositReverted(DepositReverted),
+    TokenConfigured(TokenConfigured),
+    TokenPaused(TokenPaused),
     WithdrawalRequested(WithdrawalRequested),
     WithdrawalCompleted(WithdrawalCompleted),
+    WithdrawalCancelled(WithdrawalCancelled),
+    WithdrawalRejected(WithdrawalRejected),
+    WithdrawalThrottled(WithdrawalThrottled),
+    WithdrawalExpired(WithdrawalExpired),
+    WithdrawalApprovalAdded(WithdrawalApprovalAdded),
+    WithdrawalApproved(WithdrawalApproved),
+    WithdrawalLimitUpdated(WithdrawalLimitUpdated),
+    WithdrawalBatchProcessed(WithdrawalBatchProcessed),
+    WithdrawalSkipped(WithdrawalSkipped),
     CommitmentSubmitted(CommitmentSubmitted),
+    CommitmentStale(CommitmentStale),
     DisputeRaised(DisputeRaised),
+    DisputeResolved(DisputeResolved),
+    BondLocked(BondLocked),
+    BondReleased(BondReleased),
+    BondSlashed(BondSlashed),
+    TimelockQueued(TimelockQueued),
+    TimelockExecuted(TimelockExecuted),
+    TimelockCancelled(TimelockCancelled),
+}
+
+/// Journal event types carrying `ContractEvent`s.
+pub const CONTRACT_EVENT_TYPES: [&str; 26] = [
+    "DepositDetected",
+    "DepositConfirmed",
+    "DepositReverted",
+    "TokenConfigured",
+    "TokenPaused",
+    "WithdrawalRequested",
+    "WithdrawalCompleted",
+    "WithdrawalCancelled",
+    "WithdrawalRejected",
+    "WithdrawalThrottled",
+    "WithdrawalExpired",
+    "WithdrawalApprovalAdded",
+    "WithdrawalApproved",
+    "WithdrawalLimitUpdated",
+    "WithdrawalBatchProcessed",
+    "WithdrawalSkipped",
+    "CommitmentSubmitted",
+    "CommitmentStale",
+    "DisputeRaised",
+    "DisputeResolved",
+    "BondLocked",
+    "BondReleased",
+    "BondSlashed",
+    "TimelockQueued",
+    "TimelockExecuted",
+    "TimelockCancelled",
+];
+
+impl ContractEvent {
+    /// Journal event type
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ContractEvent::DepositDetected(_) => "DepositDetected",
+            ContractEvent::DepositConfirmed(_) => "DepositConfirmed",
+            ContractEvent::DepositReverted(_) => "DepositReverted",
+            ContractEvent::TokenConfigured(_) => "TokenConfigured",
+            ContractEvent::TokenPaused(_) => "TokenPaused",
+            ContractEvent::WithdrawalRequested(_) => "WithdrawalRequested",
+            ContractEvent::WithdrawalCompleted(_) => "WithdrawalCompleted",
+            ContractEvent::WithdrawalCancelled(_) => "WithdrawalCancelled",
+            ContractEvent::WithdrawalRejected(_) => "WithdrawalRejected",
+            ContractEvent::WithdrawalThrottled(_) => "WithdrawalThrottled",
+            ContractEvent::WithdrawalExpired(_) => "WithdrawalExpired",
+            ContractEvent::WithdrawalApprovalAdded(_) => "WithdrawalApprovalAdded",
+            ContractEvent::WithdrawalApproved(_) => "WithdrawalApproved",
+            ContractEvent::WithdrawalLimitUpdated(_) => "WithdrawalLimitUpdated",
+            ContractEvent::WithdrawalBatchProcessed(_) => "WithdrawalBatchProcessed",
+            ContractEvent::WithdrawalSkipped(_) => "WithdrawalSkipped",
+            ContractEvent::CommitmentSubmitted(_) => "CommitmentSubmitted",
+            ContractEvent::CommitmentStale(_) => "CommitmentStale",
+            ContractEvent::DisputeRaised(_) => "DisputeRaised",
+            ContractEvent::DisputeResolved(_) => "DisputeResolved",
+            ContractEvent::BondLocked(_) => "BondLocked",
+            ContractEvent::BondReleased(_) => "BondReleased",
+            ContractEvent::BondSlashed(_) => "BondSlashed",
+            ContractEvent::TimelockQueued(_) => "TimelockQueued",
+            ContractEvent::TimelockExecuted(_) => "TimelockExecuted",
+            ContractEvent::TimelockCancelled(_) => "TimelockCancelled",
+        }
+    }
+}
+
+impl JournalEvent for ContractEvent {
+    fn event_type(&self) -> &str {
+        ContractEvent::event_type(self)
+    }
+}
+
+/// Register the `ContractEvent` event types.
+pub fn register_events(registry: &mut EventRegistry) -> Result<(), RegistryError> {
+    registry.register::<ContractEvent>(&CONTRACT_EVENT_TYPES, Codec::Json)
 }
 
 #[cfg(test)]
@@ -100,6 +445,7 @@ mod tests {
             amount: Decimal::new(100_000_000, 8), // 1.0 BTC
             tx_id: "abc123".to_string(),
             confirmations: 1,
+            state: DepositState::Detected,
         };
         let json = serde_json::to_string(&event).unwrap();
         let deser: DepositDetected = serde_json::from_str(&json).unwrap();
@@ -114,6 +460,7 @@ mod tests {
             amount: Decimal::new(5, 0),
             tx_id: "tx_001".to_string(),
             confirmations: 3,
+            state: DepositState::Detected,
         });
         assert!(matches!(event, ContractEvent::DepositDetected(_)));
     }
@@ -126,6 +473,11 @@ mod tests {
             asset: "USDT".to_string(),
             amount: Decimal::new(500_000, 2), // 5000.00
             destination: "0xabc...".to_string(),
+            nonce: 3,
+            requested_at: 1708123456,
+            delay_until: 1708209856,
+            expires_at: 1708728256,
+            approval_deadline: None,
         };
         let json = serde_json::to_string(&event).unwrap();
         let deser: WithdrawalRequested = serde_json::from_str(&json).unwrap();
@@ -144,4 +496,23 @@ mod tests {
         let deser: CommitmentSubmitted = serde_json::from_str(&json).unwrap();
         assert_eq!(event, deser);
     }
+
+    #[test]
+    fn test_contract_event_journal_round_trip() {
+        let mut registry = EventRegistry::new();
+        register_events(&mut registry).unwrap();
+
+        let event = ContractEvent::WithdrawalCompleted(WithdrawalCompleted {
+            withdrawal_id: Uuid::now_v7(),
+            tx_id: "0xdef".to_string(),
+            fee: Decimal::new(25, 2),
+            batch_id: None,
+        });
+        let entry = registry.journal_entry(11, 1708123456789, &event).unwrap();
+        assert_eq!(entry.event_type, "WithdrawalCompleted");
+
+        let decoded = registry.decode_entry(&entry).unwrap();
+        assert_eq!(decoded.downcast::<ContractEvent>().unwrap(), event);
+        assert!(registry.decode("DepositDetected", &entry.payload).is_err());
+    }
 }
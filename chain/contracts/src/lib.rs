@@ -10,6 +10,8 @@
 //! - `vault`: Asset storage, deposits, balance tracking, token whitelist
 //! - `withdrawal`: Withdrawal requests, signature verification, batch processing
 //! - `commitment`: State root commitment, fraud proofs, dispute resolution
+//! - `cadence`: Root submission cadence keyed to the journal sequence
+//! - `restore`: Vault state in persistence snapshots, journal replay
 //!
 //! # Version
 //! v0.1.0 — Spec-compliant initial implementation
@@ -20,6 +22,8 @@ pub mod security;
 pub mod vault;
 pub mod withdrawal;
 pub mod commitment;
+pub mod cadence;
+pub mod restore;
 
 /// Contract ABI version — frozen after release
 pub const CONTRACT_ABI_VERSION: &str = "1.0.0";
@@ -0,0 +1,806 @@
+//! Vault Restore — Vault state in persistence snapshots
+//!
+//! Exports the vault and its withdrawal queue into the `vault` section of
+//! the persistence crate's `EngineState` and restores them from it, so
+//! `SnapshotWriter` and `RecoveryEngine` persist and recover custody state
+//! together with the engines'.
+//!
+//! - Every map of `VaultState` is a `BTreeMap` with string keys, and
+//!   amounts are exact decimal strings, so `compute_hash` over an export is
+//!   stable: equal vaults hash equally however they were built.
+//! - Only state is exported. Roles, timelocks, signer and approver keys and
+//!   the queue's delay, expiry and policies are configuration: restore into
+//!   a vault and queue constructed with the same configuration.
+//! - [`VaultEventApplier`] replays journaled `ContractEvent`s on top of a
+//!   restored section. Whitelist edits, pausing, selecting or expediting a
+//!   withdrawal and nonces taken by refused requests emit no event, so a
+//!   snapshot is their only record. Usage of the current processing window
+//!   is not carried either.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use persistence::journal::JournalEntry;
+use persistence::recovery::TypedEventApplier;
+use persistence::registry::{EventRegistry, TypedEvent};
+use persistence::snapshot::{
+    DepositSnapshot, EngineState, TokenSnapshot, VaultState, WithdrawalSnapshot,
+};
+use rust_decimal::Decimal;
+use types::ids::AccountId;
+use uuid::Uuid;
+
+use crate::errors::RestoreError;
+use crate::events::{register_events, BondKind, ContractEvent, DepositState};
+use crate::vault::{DepositRecord, TokenMetadata, Vault};
+use crate::withdrawal::{WithdrawalQueue, WithdrawalRequest, WithdrawalStatus};
+
+/// Admin of the scratch vault events are replayed into.
+const REPLAY_ADMIN: &str = "replay";
+
+impl Vault {
+    /// Export balances, whitelist, token registry and deposit ledger, with
+    /// the requests and used nonces of `withdrawals`.
+    ///
+    /// Every token that is whitelisted or has metadata is exported with its
+    /// effective [`TokenMetadata`]. Requests keep their queue position.
+    pub fn export_state(&self, withdrawals: &WithdrawalQueue) -> VaultState {
+        let balances = self
+            .balances
+            .iter()
+            .flat_map(|(account, assets)| {
+                assets.iter().map(move |(asset, amount)| {
+                    (format!("{}:{}", account, asset), amount.to_string())
+                })
+            })
+            .collect();
+        let symbols: BTreeSet<&String> = self.whitelist.iter().chain(self.tokens.keys()).collect();
+        let tokens = symbols
+            .into_iter()
+            .map(|symbol| {
+                let metadata = self.token_metadata(symbol);
+                let token = TokenSnapshot {
+                    whitelisted: self.is_whitelisted(symbol),
+                    decimals: metadata.decimals,
+                    min_deposit: metadata.min_deposit.to_string(),
+                    confirmation_threshold: metadata.confirmation_threshold,
+                    paused: metadata.paused,
+                };
+                (symbol.clone(), token)
+            })
+            .collect();
+        let deposits = self
+            .deposits
+            .iter()
+            .map(|(tx_id, record)| (tx_id.clone(), deposit_snapshot(record)))
+            .collect();
+        let requests = withdrawals
+            .queue
+            .iter()
+            .enumerate()
+            .map(|(position, request)| {
+                (
+                    request.withdrawal_id.to_string(),
+                    withdrawal_snapshot(position as u64, request),
+                )
+            })
+            .collect();
+        let mut nonces: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for (account_id, nonce) in &withdrawals.nonce_tracker.used_nonces {
+            nonces.entry(account_id.to_string()).or_default().push(*nonce);
+        }
+        for used in nonces.values_mut() {
+            used.sort_unstable();
+        }
+
+        VaultState {
+            balances,
+            tokens,
+            deposits,
+            withdrawals: requests,
+            nonces,
+            paused: self.is_paused(),
+            batches: withdrawals.next_batch_id - 1,
+        }
+    }
+
+    /// Replace the state of the vault and `withdrawals` with `state`,
+    /// keeping their configuration.
+    ///
+    /// The whole state is parsed first: on error neither is changed.
+    pub fn restore(
+        &mut self,
+        withdrawals: &mut WithdrawalQueue,
+        state: &VaultState,
+    ) -> Result<(), RestoreError> {
+        let mut balances: BTreeMap<AccountId, BTreeMap<String, Decimal>> = BTreeMap::new();
+        for (key, amount) in &state.balances {
+            let (account, asset) = key
+                .split_once(':')
+                .ok_or_else(|| invalid(key, "expected account_id:asset"))?;
+            balances
+                .entry(parse_account(key, account)?)
+                .or_default()
+                .insert(asset.to_string(), parse_decimal(key, amount)?);
+        }
+
+        let mut whitelist = HashSet::new();
+        let mut tokens = HashMap::new();
+        for (symbol, token) in &state.tokens {
+            if token.whitelisted {
+                whitelist.insert(symbol.clone());
+            }
+            let metadata = TokenMetadata {
+                decimals: token.decimals,
+                min_deposit: parse_decimal(symbol, &token.min_deposit)?,
+                confirmation_threshold: token.confirmation_threshold,
+                paused: token.paused,
+            };
+            tokens.insert(symbol.clone(), metadata);
+        }
+
+        let deposits = state
+            .deposits
+            .iter()
+            .map(|(tx_id, deposit)| Ok((tx_id.clone(), restore_deposit(tx_id, deposit)?)))
+            .collect::<Result<HashMap<_, _>, RestoreError>>()?;
+
+        let mut requests = state
+            .withdrawals
+            .iter()
+            .map(|(id, request)| Ok((request.position, restore_withdrawal(id, request)?)))
+            .collect::<Result<Vec<_>, RestoreError>>()?;
+        requests.sort_by_key(|(position, _)| *position);
+
+        let mut used_nonces = HashSet::new();
+        for (account, nonces) in &state.nonces {
+            let account_id = parse_account(account, account)?;
+            used_nonces.extend(nonces.iter().map(|&nonce| (account_id, nonce)));
+        }
+
+        self.balances = balances;
+        self.whitelist = whitelist;
+        self.tokens = tokens;
+        self.deposits = deposits;
+        if state.paused {
+            self.pause_guard.pause();
+        } else {
+            self.pause_guard.unpause();
+        }
+        withdrawals.next_expedite = requests
+            .iter()
+            .filter_map(|(_, request)| request.expedited)
+            .max()
+            .map_or(0, |last| last + 1);
+        withdrawals.queue = requests.into_iter().map(|(_, request)| request).collect();
+        withdrawals.nonce_tracker.used_nonces = used_nonces;
+        withdrawals.next_batch_id = state.batches + 1;
+        Ok(())
+    }
+}
+
+// ── Journal Replay ──────────────────────────────────────────────────
+
+fn event_registry() -> &'static EventRegistry {
+    static REGISTRY: OnceLock<EventRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = EventRegistry::new();
+        register_events(&mut registry).expect("contract event types are distinct");
+        registry
+    })
+}
+
+/// Replays journaled `ContractEvent`s into the `vault` section of engine
+/// state.
+///
+/// Each event's effect on balances, the deposit ledger, the token registry
+/// and the withdrawal queue is applied to a vault restored from the
+/// section. Refunds arrive as the vault's credited `DepositDetected`
+/// events, so a cancelled, rejected or expired withdrawal only changes its
+/// request's status. Entries of other types are skipped.
+#[derive(Debug, Default)]
+pub struct VaultEventApplier;
+
+impl TypedEventApplier for VaultEventApplier {
+    fn registry(&self) -> &EventRegistry {
+        event_registry()
+    }
+
+    fn apply_typed(
+        &self,
+        state: &mut EngineState,
+        entry: &JournalEntry,
+        event: TypedEvent,
+    ) -> Result<(), String> {
+        let Ok(event) = event.downcast::<ContractEvent>() else {
+            return Ok(());
+        };
+        let mut vault = Vault::new(REPLAY_ADMIN);
+        let mut withdrawals = WithdrawalQueue::new(0);
+        vault
+            .restore(&mut withdrawals, &state.vault)
+            .map_err(|e| e.to_string())?;
+        apply_event(&mut vault, &mut withdrawals, &event)
+            .map_err(|e| format!("seq={}: {}", entry.sequence, e))?;
+        state.vault = vault.export_state(&withdrawals);
+        Ok(())
+    }
+}
+
+/// Apply `event` to a restored vault and queue.
+fn apply_event(
+    vault: &mut Vault,
+    withdrawals: &mut WithdrawalQueue,
+    event: &ContractEvent,
+) -> Result<(), RestoreError> {
+    let failed = |reason: String| RestoreError::Replay {
+        event: event.event_type().to_string(),
+        reason,
+    };
+    match event {
+        ContractEvent::DepositDetected(e) => match e.state {
+            DepositState::Credited => vault
+                .safe_credit(e.account_id, &e.asset, e.amount)
+                .map_err(|err| failed(err.to_string()))?,
+            DepositState::Detected => {
+                vault.deposits.insert(
+                    e.tx_id.clone(),
+                    DepositRecord {
+                        account_id: e.account_id,
+                        asset: e.asset.clone(),
+                        amount: e.amount,
+                        tx_id: e.tx_id.clone(),
+                        confirmations: e.confirmations,
+                        state: DepositState::Detected,
+                        debited: Decimal::ZERO,
+                        shortfall: Decimal::ZERO,
+                    },
+                );
+            }
+            DepositState::Reverted => return Err(failed("detected as reverted".to_string())),
+        },
+        ContractEvent::DepositConfirmed(e) => {
+            let record = vault
+                .deposits
+                .get_mut(&e.tx_id)
+                .ok_or_else(|| failed(format!("unknown deposit {}", e.tx_id)))?;
+            let credit =
+                record.state == DepositState::Detected && e.state == DepositState::Credited;
+            record.confirmations = e.confirmations;
+            record.state = e.state;
+            if credit {
+                vault
+                    .safe_credit(e.account_id, &e.asset, e.amount)
+                    .map_err(|err| failed(err.to_string()))?;
+            }
+        }
+        ContractEvent::DepositReverted(e) => {
+            let record = vault
+                .deposits
+                .get_mut(&e.tx_id)
+                .ok_or_else(|| failed(format!("unknown deposit {}", e.tx_id)))?;
+            record.state = DepositState::Reverted;
+            record.debited = e.debited;
+            record.shortfall = e.shortfall;
+            if e.debited > Decimal::ZERO {
+                vault
+                    .safe_debit(&e.account_id, &e.asset, e.debited)
+                    .map_err(|err| failed(err.to_string()))?;
+            }
+        }
+        ContractEvent::TokenConfigured(e) => {
+            let metadata = vault.tokens.entry(e.token.clone()).or_default();
+            metadata.decimals = e.decimals;
+            metadata.min_deposit = e.min_deposit;
+            metadata.confirmation_threshold = e.confirmation_threshold;
+        }
+        ContractEvent::TokenPaused(e) => {
+            vault.tokens.entry(e.token.clone()).or_default().paused = e.paused;
+        }
+        ContractEvent::WithdrawalRequested(e) => {
+            vault
+                .safe_debit(&e.account_id, &e.asset, e.amount)
+                .map_err(|err| failed(err.to_string()))?;
+            withdrawals.nonce_tracker.use_nonce(e.account_id, e.nonce);
+            withdrawals.queue.push_back(WithdrawalRequest {
+                withdrawal_id: e.withdrawal_id,
+                account_id: e.account_id,
+                asset: e.asset.clone(),
+                amount: e.amount,
+                destination: e.destination.clone(),
+                nonce: e.nonce,
+                requested_at: e.requested_at,
+                delay_until: e.delay_until,
+                status: if e.approval_deadline.is_some() {
+                    WithdrawalStatus::PendingApproval
+                } else {
+                    WithdrawalStatus::Pending
+                },
+                expedited: None,
+                approvals: Vec::new(),
+                approval_deadline: e.approval_deadline,
+                expires_at: e.expires_at,
+            });
+        }
+        ContractEvent::WithdrawalApprovalAdded(e) => {
+            request_mut(withdrawals, e.withdrawal_id)
+                .ok_or_else(|| failed(format!("unknown withdrawal {}", e.withdrawal_id)))?
+                .approvals
+                .push(e.approver.clone());
+        }
+        ContractEvent::WithdrawalCompleted(e) => {
+            let request = request_mut(withdrawals, e.withdrawal_id)
+                .ok_or_else(|| failed(format!("unknown withdrawal {}", e.withdrawal_id)))?;
+            request.status = WithdrawalStatus::Completed;
+            if e.batch_id.is_some() && e.fee > Decimal::ZERO {
+                let (account_id, asset) = (request.account_id, request.asset.clone());
+                vault
+                    .safe_debit(&account_id, &asset, e.fee)
+                    .map_err(|err| failed(err.to_string()))?;
+            }
+        }
+        ContractEvent::WithdrawalApproved(e) => {
+            set_status(withdrawals, e.withdrawal_id, WithdrawalStatus::Pending)
+                .map_err(failed)?;
+        }
+        ContractEvent::WithdrawalCancelled(e) => {
+            set_status(withdrawals, e.withdrawal_id, WithdrawalStatus::Cancelled)
+                .map_err(failed)?;
+        }
+        ContractEvent::WithdrawalRejected(e) => {
+            set_status(withdrawals, e.withdrawal_id, WithdrawalStatus::Rejected)
+                .map_err(failed)?;
+        }
+        ContractEvent::WithdrawalExpired(e) => {
+            set_status(withdrawals, e.withdrawal_id, WithdrawalStatus::Expired)
+                .map_err(failed)?;
+        }
+        ContractEvent::WithdrawalBatchProcessed(e) => {
+            withdrawals.next_batch_id = withdrawals.next_batch_id.max(e.batch_id + 1);
+        }
+        // Commit stake moving between free and locked against a root stays
+        // in escrow; every other bond movement debits or credits the vault
+        ContractEvent::BondLocked(e) if !in_escrow(e.kind, e.root_hash) => vault
+            .safe_debit(&e.account_id, &e.asset, e.amount)
+            .map_err(|err| failed(err.to_string()))?,
+        ContractEvent::BondReleased(e) if !in_escrow(e.kind, e.root_hash) => vault
+            .safe_credit(e.account_id, &e.asset, e.amount)
+            .map_err(|err| failed(err.to_string()))?,
+        ContractEvent::BondSlashed(e) => vault
+            .safe_credit(e.to_account, &e.asset, e.amount)
+            .map_err(|err| failed(err.to_string()))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn in_escrow(kind: BondKind, root_hash: Option<[u8; 32]>) -> bool {
+    kind == BondKind::CommitStake && root_hash.is_some()
+}
+
+fn request_mut(
+    withdrawals: &mut WithdrawalQueue,
+    withdrawal_id: Uuid,
+) -> Option<&mut WithdrawalRequest> {
+    withdrawals
+        .queue
+        .iter_mut()
+        .find(|request| request.withdrawal_id == withdrawal_id)
+}
+
+fn set_status(
+    withdrawals: &mut WithdrawalQueue,
+    withdrawal_id: Uuid,
+    status: WithdrawalStatus,
+) -> Result<(), String> {
+    let request = request_mut(withdrawals, withdrawal_id)
+        .ok_or_else(|| format!("unknown withdrawal {}", withdrawal_id))?;
+    request.status = status;
+    Ok(())
+}
+
+// ── Conversions ─────────────────────────────────────────────────────
+
+fn deposit_snapshot(record: &DepositRecord) -> DepositSnapshot {
+    DepositSnapshot {
+        tx_id: record.tx_id.clone(),
+        account_id: record.account_id.to_string(),
+        asset: record.asset.clone(),
+        amount: record.amount.to_string(),
+        confirmations: record.confirmations,
+        state: format!("{:?}", record.state),
+        debited: record.debited.to_string(),
+        shortfall: record.shortfall.to_string(),
+    }
+}
+
+fn restore_deposit(key: &str, deposit: &DepositSnapshot) -> Result<DepositRecord, RestoreError> {
+    let state = match deposit.state.as_str() {
+        "Detected" => DepositState::Detected,
+        "Credited" => DepositState::Credited,
+        "Reverted" => DepositState::Reverted,
+        other => return Err(invalid(key, format!("unknown deposit state {}", other))),
+    };
+    Ok(DepositRecord {
+        account_id: parse_account(key, &deposit.account_id)?,
+        asset: deposit.asset.clone(),
+        amount: parse_decimal(key, &deposit.amount)?,
+        tx_id: key.to_string(),
+        confirmations: deposit.confirmations,
+        state,
+        debited: parse_decimal(key, &deposit.debited)?,
+        shortfall: parse_decimal(key, &deposit.shortfall)?,
+    })
+}
+
+fn withdrawal_snapshot(position: u64, request: &WithdrawalRequest) -> WithdrawalSnapshot {
+    WithdrawalSnapshot {
+        withdrawal_id: request.withdrawal_id.to_string(),
+        position,
+        account_id: request.account_id.to_string(),
+        asset: request.asset.clone(),
+        amount: request.amount.to_string(),
+        destination: request.destination.clone(),
+        nonce: request.nonce,
+        requested_at: request.requested_at,
+        delay_until: request.delay_until,
+        status: format!("{:?}", request.status),
+        expedited: request.expedited,
+        approvals: request.approvals.clone(),
+        approval_deadline: request.approval_deadline,
+        expires_at: request.expires_at,
+    }
+}
+
+fn restore_withdrawal(
+    key: &str,
+    request: &WithdrawalSnapshot,
+) -> Result<WithdrawalRequest, RestoreError> {
+    let status = match request.status.as_str() {
+        "PendingApproval" => WithdrawalStatus::PendingApproval,
+        "Pending" => WithdrawalStatus::Pending,
+        "Ready" => WithdrawalStatus::Ready,
+        "Completed" => WithdrawalStatus::Completed,
+        "Cancelled" => WithdrawalStatus::Cancelled,
+        "Rejected" => WithdrawalStatus::Rejected,
+        "Expired" => WithdrawalStatus::Expired,
+        other => return Err(invalid(key, format!("unknown withdrawal status {}", other))),
+    };
+    Ok(WithdrawalRequest {
+        withdrawal_id: Uuid::parse_str(key).map_err(|e| invalid(key, e.to_string()))?,
+        account_id: parse_account(key, &request.account_id)?,
+        asset: request.asset.clone(),
+        amount: parse_decimal(key, &request.amount)?,
+        destination: request.destination.clone(),
+        nonce: request.nonce,
+        requested_at: request.requested_at,
+        delay_until: request.delay_until,
+        status,
+        expedited: request.expedited,
+        approvals: request.approvals.clone(),
+        approval_deadline: request.approval_deadline,
+        expires_at: request.expires_at,
+    })
+}
+
+fn invalid(key: &str, reason: impl Into<String>) -> RestoreError {
+    RestoreError::InvalidState {
+        key: key.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn parse_decimal(key: &str, value: &str) -> Result<Decimal, RestoreError> {
+    Decimal::from_str(value).map_err(|e| invalid(key, format!("{}: {}", value, e)))
+}
+
+fn parse_account(key: &str, value: &str) -> Result<AccountId, RestoreError> {
+    Uuid::parse_str(value)
+        .map(AccountId::from_uuid)
+        .map_err(|e| invalid(key, format!("account {}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::{BondConfig, CommitmentStore};
+    use crate::errors::{VaultError, WithdrawalError};
+    use crate::withdrawal::{ApprovalPolicy, BatchFailurePolicy, BatchPolicy};
+    use persistence::journal::{JournalConfig, JournalWriter};
+    use persistence::recovery::RecoveryEngine;
+    use persistence::snapshot::{Snapshot, SnapshotWriter};
+    use tempfile::TempDir;
+
+    const T0: i64 = 1_708_000_000;
+
+    fn setup() -> (Vault, WithdrawalQueue) {
+        let mut vault = Vault::new("admin");
+        for token in ["BTC", "USDT", "ETH"] {
+            vault.add_to_whitelist("admin", token).unwrap();
+        }
+        vault.configure_token("admin", "BTC", 8, Decimal::new(1, 3), 3).unwrap();
+        vault.pause_token("admin", "ETH").unwrap();
+        (vault, WithdrawalQueue::new(3600))
+    }
+
+    fn signer_key() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn sign(acc: AccountId, asset: &str, amount: Decimal, nonce: u64) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+        use sha2::{Digest, Sha256};
+        let payload =
+            WithdrawalQueue::withdrawal_signing_payload(acc, asset, amount, nonce, "dest");
+        signer_key().sign(&Sha256::digest(payload)).to_bytes().to_vec()
+    }
+
+    fn request(
+        vault: &mut Vault,
+        wq: &mut WithdrawalQueue,
+        acc: AccountId,
+        asset: &str,
+        amount: Decimal,
+        nonce: u64,
+        time: i64,
+    ) -> Uuid {
+        wq.register_signer(acc, signer_key().verifying_key().to_bytes());
+        let sig = sign(acc, asset, amount, nonce);
+        match wq
+            .request_withdrawal(vault, acc, asset, amount, "dest", nonce, &sig, time)
+            .unwrap()
+        {
+            ContractEvent::WithdrawalRequested(e) => e.withdrawal_id,
+            _ => panic!("Expected WithdrawalRequested"),
+        }
+    }
+
+    /// Deposits in every ledger state, and withdrawals settled in a batch,
+    /// cancelled, and pending after being expedited
+    fn populated() -> (Vault, WithdrawalQueue, [AccountId; 2], [Uuid; 3]) {
+        let (mut vault, mut wq) = setup();
+        let (a, b) = (AccountId::new(), AccountId::new());
+        vault.deposit(a, "USDT", Decimal::from(1_000), "fund_a").unwrap();
+        vault.deposit(b, "BTC", Decimal::from(2), "fund_b").unwrap();
+        vault.detect_deposit(a, "BTC", Decimal::new(5, 1), "tx_pending", 1).unwrap();
+        vault.detect_deposit(b, "BTC", Decimal::ONE, "tx_final", 3).unwrap();
+        vault.detect_deposit(a, "BTC", Decimal::new(25, 2), "tx_reorg", 3).unwrap();
+        vault.revert_deposit("tx_reorg").unwrap();
+
+        let settled = request(&mut vault, &mut wq, a, "USDT", Decimal::from(100), 1, T0);
+        let cancelled = request(&mut vault, &mut wq, a, "USDT", Decimal::from(50), 2, T0 + 1);
+        let pending = request(&mut vault, &mut wq, b, "BTC", Decimal::ONE, 7, T0 + 2);
+        wq.expedite(&vault, pending, "admin").unwrap();
+        wq.cancel_withdrawal(&mut vault, cancelled, "admin").unwrap();
+        let mut commitments = CommitmentStore::new("admin", 3600);
+        wq.process_batch(&mut vault, &mut commitments, 1, T0 + 3600).unwrap();
+        (vault, wq, [a, b], [settled, cancelled, pending])
+    }
+
+    /// Move every emitted event into `events`, stamped with `time`
+    fn log(
+        events: &mut Vec<(i64, ContractEvent)>,
+        vault: &mut Vault,
+        wq: &mut WithdrawalQueue,
+        commitments: &mut CommitmentStore,
+        time: i64,
+    ) {
+        let drained = vault
+            .drain_events()
+            .into_iter()
+            .chain(wq.drain_events())
+            .chain(commitments.drain_events());
+        events.extend(drained.map(|event| (time, event)));
+    }
+
+    fn export_of(deposits: [(AccountId, &str); 2], tokens: [&str; 2]) -> VaultState {
+        let mut vault = Vault::new("admin");
+        for token in tokens {
+            vault.add_to_whitelist("admin", token).unwrap();
+        }
+        for (acc, tx_id) in deposits {
+            vault.detect_deposit(acc, "BTC", Decimal::ONE, tx_id, 1).unwrap();
+            vault.deposit(acc, "USDT", Decimal::TEN, tx_id).unwrap();
+        }
+        vault.export_state(&WithdrawalQueue::new(3600))
+    }
+
+    #[test]
+    fn test_export_restore_round_trip() {
+        let (vault, wq, [a, b], [settled, cancelled, pending]) = populated();
+        let state = vault.export_state(&wq);
+        assert_eq!(state.batches, 1);
+        assert_eq!(state.nonces[&a.to_string()], vec![1, 2]);
+        assert_eq!(state.withdrawals[&settled.to_string()].status, "Completed");
+        assert_eq!(state.withdrawals[&cancelled.to_string()].status, "Cancelled");
+        assert_eq!(state.withdrawals[&pending.to_string()].expedited, Some(0));
+        assert_eq!(state.deposits["tx_reorg"].state, "Reverted");
+        assert!(state.tokens["ETH"].whitelisted && state.tokens["ETH"].paused);
+
+        let (mut restored, mut restored_wq) = (Vault::new("admin"), WithdrawalQueue::new(3600));
+        restored.restore(&mut restored_wq, &state).unwrap();
+        let again = restored.export_state(&restored_wq);
+        assert_eq!(again, state);
+        assert_eq!(again.compute_hash(), state.compute_hash());
+        assert_eq!(restored.balances_merkle_root(), vault.balances_merkle_root());
+
+        // The restored vault and queue carry on where the originals stopped;
+        // signer keys are registration, not state, and are registered again
+        assert_eq!(restored.get_balance(&a, "USDT"), Decimal::from(900));
+        restored_wq.register_signer(b, signer_key().verifying_key().to_bytes());
+        let sig = sign(b, "BTC", Decimal::ONE, 7);
+        assert!(matches!(
+            restored_wq.request_withdrawal(
+                &mut restored, b, "BTC", Decimal::ONE, "dest", 7, &sig, T0 + 10
+            ),
+            Err(WithdrawalError::NonceReused { nonce: 7, .. })
+        ));
+        restored.confirm_deposit("tx_pending", 3).unwrap();
+        assert_eq!(restored.get_balance(&a, "BTC"), Decimal::new(5, 1));
+        assert!(matches!(
+            restored.deposit(a, "BTC", Decimal::new(1, 9), "dust"),
+            Err(VaultError::ExcessPrecision { decimals: 8, .. })
+        ));
+        assert!(matches!(
+            restored.deposit(a, "ETH", Decimal::ONE, "eth"),
+            Err(VaultError::TokenPaused { .. })
+        ));
+        restored_wq
+            .process_withdrawal(pending, T0 + 3602, "0xtx", Decimal::ZERO)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_export_is_independent_of_insertion_order() {
+        let (a, b) = (AccountId::new(), AccountId::new());
+        let one = export_of([(a, "tx_a"), (b, "tx_b")], ["BTC", "USDT"]);
+        let other = export_of([(b, "tx_b"), (a, "tx_a")], ["USDT", "BTC"]);
+        assert_eq!(one, other);
+        assert_eq!(one.compute_hash(), other.compute_hash());
+
+        let (mut ours, mut theirs) = (EngineState::empty(), EngineState::empty());
+        ours.vault = one;
+        theirs.vault = other;
+        assert_eq!(ours.compute_hash(), theirs.compute_hash());
+    }
+
+    #[test]
+    fn test_recovery_replays_events_on_restored_vault() {
+        let tmp = TempDir::new().unwrap();
+        let snap_dir = tmp.path().join("snapshots");
+        let journal_dir = tmp.path().join("journal");
+        let mut commitments = CommitmentStore::new("admin", 3600);
+        let mut events: Vec<(i64, ContractEvent)> = Vec::new();
+        let (mut vault, mut wq, [a, b], [_, _, pending]) = populated();
+        log(&mut events, &mut vault, &mut wq, &mut commitments, T0 + 3600);
+        let mut snap_state = EngineState::empty();
+        snap_state.vault = vault.export_state(&wq);
+        let split = events.len() as u64;
+
+        // Deposits confirmed, credited and reorged; token registry changes
+        let t = T0 + 4000;
+        vault.confirm_deposit("tx_pending", 3).unwrap();
+        vault.detect_deposit(b, "BTC", Decimal::from(2), "tx_late", 4).unwrap();
+        vault.revert_deposit("tx_late").unwrap();
+        vault.configure_token("admin", "USDT", 6, Decimal::ONE, 12).unwrap();
+        vault.unpause_token("admin", "ETH").unwrap();
+        vault.deposit(b, "ETH", Decimal::new(15, 1), "eth_tx").unwrap();
+        log(&mut events, &mut vault, &mut wq, &mut commitments, t);
+
+        // Withdrawals completed singly and in a batch with a fee
+        wq.process_withdrawal(pending, t, "0xtx", Decimal::new(1, 4)).unwrap();
+        wq.set_batch_policy(BatchPolicy {
+            fee: Decimal::new(1, 1),
+            on_failure: BatchFailurePolicy::SkipItem,
+        });
+        request(&mut vault, &mut wq, a, "USDT", Decimal::TEN, 4, t);
+        wq.process_batch(&mut vault, &mut commitments, 10, t + 3600).unwrap();
+        log(&mut events, &mut vault, &mut wq, &mut commitments, t + 3600);
+
+        // Cancelled, rejected and expired requests are refunded
+        let cancelled = request(&mut vault, &mut wq, b, "BTC", Decimal::ONE, 8, t + 3600);
+        wq.cancel_withdrawal(&mut vault, cancelled, "admin").unwrap();
+        wq.set_approval_policy(ApprovalPolicy {
+            thresholds: HashMap::from([("BTC".to_string(), Decimal::new(5, 1))]),
+            required_approvals: 2,
+            expiry_seconds: 60,
+        });
+        request(&mut vault, &mut wq, b, "BTC", Decimal::ONE, 9, t + 3600);
+        wq.expire_approvals(&mut vault, t + 3660).unwrap();
+        request(&mut vault, &mut wq, a, "USDT", Decimal::from(200), 5, t + 3660);
+        wq.sweep_expired(&mut vault, t + 3660 + 7 * 86400).unwrap();
+        log(&mut events, &mut vault, &mut wq, &mut commitments, t + 3660 + 7 * 86400);
+
+        // Stake posted from and withdrawn to the vault; stake locked
+        // against a root stays in escrow
+        let t = t + 3660 + 7 * 86400;
+        commitments
+            .set_bond_config(
+                "admin",
+                BondConfig {
+                    asset: "USDT".to_string(),
+                    dispute_bond: Decimal::TEN,
+                    commit_stake: Decimal::from(100),
+                    challenger_reward: Decimal::from(5),
+                    treasury: AccountId::new(),
+                },
+            )
+            .unwrap();
+        commitments.post_stake(&mut vault, "admin", a, Decimal::from(300)).unwrap();
+        commitments.submit_root("admin", [7u8; 32], 1, t).unwrap();
+        commitments.withdraw_stake(&mut vault, "admin", Decimal::from(50), t).unwrap();
+        log(&mut events, &mut vault, &mut wq, &mut commitments, t);
+
+        let registry = event_registry();
+        let mut writer = JournalWriter::open(JournalConfig::new(&journal_dir)).unwrap();
+        writer.set_next_sequence(1);
+        for (i, (ts, event)) in events.iter().enumerate() {
+            writer.append(&registry.journal_entry(i as u64 + 1, *ts, event).unwrap()).unwrap();
+        }
+        // Entries for other consumers are skipped
+        let last = events.len() as u64 + 1;
+        writer
+            .append(&JournalEntry::new(last, t, "OrderSubmitted".into(), vec![1, 2, 3]))
+            .unwrap();
+        writer.sync().unwrap();
+        SnapshotWriter::new(&snap_dir, false)
+            .write(&Snapshot::new(split, T0 + 3600, snap_state, false))
+            .unwrap();
+
+        let (state, metrics) = RecoveryEngine::new(&snap_dir, &journal_dir)
+            .recover_without_validation(&VaultEventApplier)
+            .unwrap();
+        assert_eq!(metrics.snapshot_sequence, split);
+        assert_eq!(metrics.replay_count, last - split);
+        let live = vault.export_state(&wq);
+        assert_eq!(state.vault, live);
+        assert_eq!(state.vault.compute_hash(), live.compute_hash());
+        assert_eq!(live.batches, 2);
+
+        let (mut recovered, mut recovered_wq) = (Vault::new("admin"), WithdrawalQueue::new(3600));
+        recovered.restore(&mut recovered_wq, &state.vault).unwrap();
+        assert_eq!(recovered.balances_merkle_root(), vault.balances_merkle_root());
+        assert_eq!(recovered.get_balance(&a, "USDT"), Decimal::new(6399, 1));
+    }
+
+    #[test]
+    fn test_invalid_state_is_reported() {
+        let (vault, wq, ..) = populated();
+        let (mut target, mut target_wq) = (Vault::new("admin"), WithdrawalQueue::new(3600));
+
+        let mut state = vault.export_state(&wq);
+        state.balances.insert("no-account".into(), "1".into());
+        assert!(matches!(
+            target.restore(&mut target_wq, &state),
+            Err(RestoreError::InvalidState { key, .. }) if key == "no-account"
+        ));
+        let mut state = vault.export_state(&wq);
+        state.deposits.get_mut("tx_final").unwrap().amount = "lots".into();
+        assert!(matches!(
+            target.restore(&mut target_wq, &state),
+            Err(RestoreError::InvalidState { key, .. }) if key == "tx_final"
+        ));
+        let mut state = vault.export_state(&wq);
+        state.withdrawals.values_mut().next().unwrap().status = "Lost".into();
+        assert!(matches!(
+            target.restore(&mut target_wq, &state),
+            Err(RestoreError::InvalidState { reason, .. })
+                if reason == "unknown withdrawal status Lost"
+        ));
+        // Nothing was restored
+        assert_eq!(target.export_state(&target_wq), VaultState::default());
+
+        let unknown = ContractEvent::WithdrawalCancelled(crate::events::WithdrawalCancelled {
+            withdrawal_id: Uuid::now_v7(),
+            account_id: AccountId::new(),
+            asset: "BTC".into(),
+            amount: Decimal::ONE,
+            cancelled_by: "admin".into(),
+        });
+        assert!(matches!(
+            apply_event(&mut target, &mut target_wq, &unknown),
+            Err(RestoreError::Replay { event, .. }) if event == "WithdrawalCancelled"
+        ));
+    }
+}
@@ -1,12 +1,16 @@
 //! Shared security primitives for contract modules
 //!
 //! Provides reusable guards and access control used across vault,
-//! withdrawal, and commitment modules.
+//! withdrawal, and commitment modules, and a timelock for privileged
+//! operations.
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use types::ids::AccountId;
 
+use crate::errors::TimelockError;
+use crate::events::{ContractEvent, TimelockCancelled, TimelockExecuted, TimelockQueued};
+
 /// Reentrancy guard preventing nested calls into protected functions.
 ///
 /// A contract function acquires the guard before executing state-changing
@@ -56,6 +60,10 @@ pub enum Role {
     Admin,
     /// Operational tasks (e.g., submitting roots)
     Operator,
+    /// Co-signs withdrawals above the approval threshold
+    Approver,
+    /// May cancel queued timelocked operations
+    Guardian,
     /// Regular user
     User,
 }
@@ -84,7 +92,7 @@ impl AccessControl {
 
     /// Check if a caller has the specified role.
     pub fn has_role(&self, caller: &str, role: Role) -> bool {
-        self.roles.get(caller).map_or(false, |r| *r == role)
+        self.roles.get(caller).is_some_and(|r| *r == role)
     }
 
     /// Check if a caller is admin.
@@ -174,7 +182,7 @@ impl Default for PauseGuard {
 /// A nonce can only be used once per account.
 #[derive(Debug, Clone)]
 pub struct NonceTracker {
-    used_nonces: HashSet<(AccountId, u64)>,
+    pub(crate) used_nonces: HashSet<(AccountId, u64)>,
 }
 
 impl NonceTracker {
@@ -207,6 +215,170 @@ impl Default for NonceTracker {
     }
 }
 
+/// Identifier of an operation queued in a [`Timelock`].
+pub type OperationId = u64;
+
+/// An operation that can be put behind a [`Timelock`].
+pub trait TimelockOperation: Clone {
+    /// Operation name, the key for its delay and the name in its events.
+    fn name(&self) -> &'static str;
+}
+
+/// Lifecycle of a queued operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockStatus {
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+/// An operation waiting in (or done with) a [`Timelock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedOperation<Op> {
+    pub id: OperationId,
+    pub operation: Op,
+    pub queued_by: String,
+    pub queued_at: i64,
+    /// Earliest time the operation may execute
+    pub execute_after: i64,
+    pub status: TimelockStatus,
+}
+
+/// Delay queue for privileged operations.
+///
+/// Operations are [`queue`](Self::queue)d with an execute-after timestamp
+/// of `now + delay` and can only [`execute`](Self::execute) from then on,
+/// exactly once, unless [`cancel`](Self::cancel)led first. Each transition
+/// returns its event for the owning contract to emit. Authorization is the
+/// owning contract's job. Operations without a configured delay have a
+/// delay of zero; contracts apply those directly without queueing.
+#[derive(Debug, Clone)]
+pub struct Timelock<Op> {
+    delays: HashMap<String, i64>,
+    operations: BTreeMap<OperationId, QueuedOperation<Op>>,
+    next_id: OperationId,
+}
+
+impl<Op: TimelockOperation> Timelock<Op> {
+    /// Create a timelock with no delays configured.
+    pub fn new() -> Self {
+        Self {
+            delays: HashMap::new(),
+            operations: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Set the delay in seconds for operations named `name`.
+    pub fn set_delay(&mut self, name: impl Into<String>, delay_seconds: i64) {
+        self.delays.insert(name.into(), delay_seconds.max(0));
+    }
+
+    /// Delay in seconds for operations named `name`.
+    pub fn delay(&self, name: &str) -> i64 {
+        self.delays.get(name).copied().unwrap_or(0)
+    }
+
+    /// Queue `operation`. Returns its ID and the `TimelockQueued` event.
+    pub fn queue(
+        &mut self,
+        operation: Op,
+        queued_by: &str,
+        current_time: i64,
+    ) -> (OperationId, ContractEvent) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let execute_after = current_time + self.delay(operation.name());
+        let event = ContractEvent::TimelockQueued(TimelockQueued {
+            operation_id: id,
+            operation: operation.name().to_string(),
+            queued_by: queued_by.to_string(),
+            execute_after,
+        });
+        self.operations.insert(
+            id,
+            QueuedOperation {
+                id,
+                operation,
+                queued_by: queued_by.to_string(),
+                queued_at: current_time,
+                execute_after,
+                status: TimelockStatus::Queued,
+            },
+        );
+        (id, event)
+    }
+
+    /// Cancel a queued operation. Returns the `TimelockCancelled` event.
+    pub fn cancel(
+        &mut self,
+        id: OperationId,
+        cancelled_by: &str,
+        current_time: i64,
+    ) -> Result<ContractEvent, TimelockError> {
+        let queued = self.queued_mut(id)?;
+        queued.status = TimelockStatus::Cancelled;
+        Ok(ContractEvent::TimelockCancelled(TimelockCancelled {
+            operation_id: id,
+            operation: queued.operation.name().to_string(),
+            cancelled_by: cancelled_by.to_string(),
+            cancelled_at: current_time,
+        }))
+    }
+
+    /// Mark a queued operation executed once its delay has passed.
+    /// Returns the operation to apply and the `TimelockExecuted` event.
+    pub fn execute(
+        &mut self,
+        id: OperationId,
+        current_time: i64,
+    ) -> Result<(Op, ContractEvent), TimelockError> {
+        let queued = self.queued_mut(id)?;
+        if current_time < queued.execute_after {
+            return Err(TimelockError::NotReady {
+                execute_after: queued.execute_after,
+            });
+        }
+        queued.status = TimelockStatus::Executed;
+        let event = ContractEvent::TimelockExecuted(TimelockExecuted {
+            operation_id: id,
+            operation: queued.operation.name().to_string(),
+            executed_at: current_time,
+        });
+        Ok((queued.operation.clone(), event))
+    }
+
+    /// Get a queued operation by ID.
+    pub fn get(&self, id: OperationId) -> Option<&QueuedOperation<Op>> {
+        self.operations.get(&id)
+    }
+
+    /// Operations still waiting to execute, in queue order.
+    pub fn pending(&self) -> impl Iterator<Item = &QueuedOperation<Op>> {
+        self.operations
+            .values()
+            .filter(|op| op.status == TimelockStatus::Queued)
+    }
+
+    fn queued_mut(&mut self, id: OperationId) -> Result<&mut QueuedOperation<Op>, TimelockError> {
+        let queued = self
+            .operations
+            .get_mut(&id)
+            .ok_or(TimelockError::NotFound { operation_id: id })?;
+        match queued.status {
+            TimelockStatus::Queued => Ok(queued),
+            TimelockStatus::Executed => Err(TimelockError::AlreadyExecuted { operation_id: id }),
+            TimelockStatus::Cancelled => Err(TimelockError::Cancelled { operation_id: id }),
+        }
+    }
+}
+
+impl<Op: TimelockOperation> Default for Timelock<Op> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,4 +502,72 @@ mod tests {
         tracker.use_nonce(acc, 2);
         assert_eq!(tracker.count(), 2);
     }
+
+    // --- Timelock tests ---
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Noop;
+
+    impl TimelockOperation for Noop {
+        fn name(&self) -> &'static str {
+            "noop"
+        }
+    }
+
+    fn timelock() -> Timelock<Noop> {
+        let mut timelock = Timelock::new();
+        timelock.set_delay("noop", 100);
+        timelock
+    }
+
+    #[test]
+    fn test_timelock_execute_early_rejected() {
+        let mut timelock = timelock();
+        let (id, event) = timelock.queue(Noop, "admin", 1000);
+        assert!(matches!(event, ContractEvent::TimelockQueued(ref e) if e.execute_after == 1100));
+
+        assert_eq!(
+            timelock.execute(id, 1099).unwrap_err(),
+            TimelockError::NotReady { execute_after: 1100 }
+        );
+        let (op, event) = timelock.execute(id, 1100).unwrap();
+        assert_eq!(op, Noop);
+        assert!(matches!(event, ContractEvent::TimelockExecuted(_)));
+    }
+
+    #[test]
+    fn test_timelock_execute_twice_rejected() {
+        let mut timelock = timelock();
+        let (id, _) = timelock.queue(Noop, "admin", 1000);
+        timelock.execute(id, 1200).unwrap();
+        assert_eq!(
+            timelock.execute(id, 1300).unwrap_err(),
+            TimelockError::AlreadyExecuted { operation_id: id }
+        );
+        assert_eq!(timelock.get(id).unwrap().status, TimelockStatus::Executed);
+        assert_eq!(timelock.pending().count(), 0);
+    }
+
+    #[test]
+    fn test_timelock_cancel_races_execute() {
+        let mut timelock = timelock();
+        let (first, _) = timelock.queue(Noop, "admin", 1000);
+        let (second, _) = timelock.queue(Noop, "admin", 1000);
+
+        // Whichever lands first wins; the other is rejected
+        timelock.cancel(first, "guardian", 1100).unwrap();
+        assert_eq!(
+            timelock.execute(first, 1100).unwrap_err(),
+            TimelockError::Cancelled { operation_id: first }
+        );
+        timelock.execute(second, 1100).unwrap();
+        assert_eq!(
+            timelock.cancel(second, "guardian", 1100).unwrap_err(),
+            TimelockError::AlreadyExecuted { operation_id: second }
+        );
+        assert_eq!(
+            timelock.cancel(99, "guardian", 1100).unwrap_err(),
+            TimelockError::NotFound { operation_id: 99 }
+        );
+    }
 }
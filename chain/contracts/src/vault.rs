@@ -2,23 +2,108 @@
 //!
 //! Implements the custody layer per spec §16 (Custody Assumptions):
 //! - Token whitelist (add/remove allowed assets)
+//! - Token registry: per-token precision, minimum deposit, finality, pause
 //! - Deposit flow with detection and confirmation
+//! - Deposit ledger: per-asset finality thresholds, reorg rollback
+//! - Proof of reserves: Merkle root and inclusion proofs over balances
 //! - Balance tracking by (account, asset)
 //! - Safe transfer wrapper with overflow protection
 //! - Pause modifier, access control, reentrancy guard
+//! - Timelock on admin changes and token delisting
 
 use rust_decimal::Decimal;
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use types::ids::AccountId;
 
+use crate::commitment::{merkle_leaf, merkle_proof, merkle_root, verify_merkle_proof, MerkleProof};
 use crate::errors::VaultError;
-use crate::events::{ContractEvent, DepositConfirmed, DepositDetected};
-use crate::security::{AccessControl, PauseGuard, ReentrancyGuard};
+use crate::events::{
+    ContractEvent, DepositConfirmed, DepositDetected, DepositReverted, DepositState,
+    TokenConfigured, TokenPaused,
+};
+use crate::security::{
+    AccessControl, OperationId, PauseGuard, ReentrancyGuard, Role, Timelock, TimelockOperation,
+};
+
+/// Privileged vault operations that can be timelocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultOperation {
+    SetAdmin { new_admin: String },
+    RemoveFromWhitelist { token: String },
+}
+
+impl VaultOperation {
+    /// Timelock name of [`VaultOperation::SetAdmin`]
+    pub const SET_ADMIN: &'static str = "set_admin";
+    /// Timelock name of [`VaultOperation::RemoveFromWhitelist`]
+    pub const REMOVE_FROM_WHITELIST: &'static str = "remove_from_whitelist";
+}
+
+impl TimelockOperation for VaultOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            VaultOperation::SetAdmin { .. } => Self::SET_ADMIN,
+            VaultOperation::RemoveFromWhitelist { .. } => Self::REMOVE_FROM_WHITELIST,
+        }
+    }
+}
+
+/// Confirmations required to credit a deposit in an asset without a
+/// configured finality threshold.
+pub const DEFAULT_FINALITY_CONFIRMATIONS: u64 = 6;
+
+/// Decimal places accepted for a token without configured metadata.
+pub const DEFAULT_TOKEN_DECIMALS: u32 = 18;
+
+/// Per-token settings in the vault's token registry.
+///
+/// Whitelisted tokens without an entry use [`TokenMetadata::default`]:
+/// [`DEFAULT_TOKEN_DECIMALS`], no minimum deposit,
+/// [`DEFAULT_FINALITY_CONFIRMATIONS`], not paused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// Decimal places the token supports; finer amounts are refused
+    pub decimals: u32,
+    /// Smallest accepted deposit
+    pub min_deposit: Decimal,
+    /// Confirmations required to credit an on-chain deposit
+    pub confirmation_threshold: u64,
+    /// Deposits, confirmations and withdrawal requests refused
+    pub paused: bool,
+}
+
+impl Default for TokenMetadata {
+    fn default() -> Self {
+        Self {
+            decimals: DEFAULT_TOKEN_DECIMALS,
+            min_deposit: Decimal::ZERO,
+            confirmation_threshold: DEFAULT_FINALITY_CONFIRMATIONS,
+            paused: false,
+        }
+    }
+}
+
+/// An on-chain deposit tracked through to finality, keyed by `tx_id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositRecord {
+    pub account_id: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+    pub tx_id: String,
+    pub confirmations: u64,
+    pub state: DepositState,
+    /// Taken back from the balance on revert
+    pub debited: Decimal,
+    /// Part of a reverted credit that had already left the balance
+    pub shortfall: Decimal,
+}
 
 /// Core vault contract managing asset custody.
 ///
-/// Balances are stored as `HashMap<AccountId, HashMap<String, Decimal>>` where
-/// the inner map keys are asset symbol strings (e.g. "BTC", "ETH", "USDT").
+/// Balances are stored as `BTreeMap<AccountId, BTreeMap<String, Decimal>>` where
+/// the inner map keys are asset symbol strings (e.g. "BTC", "ETH", "USDT"),
+/// so they iterate in a deterministic (account, asset) order.
 ///
 /// All state-changing operations check:
 /// 1. Reentrancy guard
@@ -28,15 +113,21 @@ use crate::security::{AccessControl, PauseGuard, ReentrancyGuard};
 #[derive(Debug)]
 pub struct Vault {
     /// Balances: account -> (asset -> amount)
-    balances: HashMap<AccountId, HashMap<String, Decimal>>,
+    pub(crate) balances: BTreeMap<AccountId, BTreeMap<String, Decimal>>,
     /// Whitelisted token symbols
-    whitelist: HashSet<String>,
+    pub(crate) whitelist: HashSet<String>,
     /// Security: reentrancy guard
     reentrancy_guard: ReentrancyGuard,
     /// Security: pause guard
-    pause_guard: PauseGuard,
+    pub(crate) pause_guard: PauseGuard,
     /// Security: role-based access control
     access_control: AccessControl,
+    /// Security: delay queue for privileged operations
+    timelock: Timelock<VaultOperation>,
+    /// Deposit ledger by tx_id
+    pub(crate) deposits: HashMap<String, DepositRecord>,
+    /// Token registry: metadata per token symbol
+    pub(crate) tokens: HashMap<String, TokenMetadata>,
     /// Emitted events log (append-only)
     events: Vec<ContractEvent>,
 }
@@ -45,11 +136,14 @@ impl Vault {
     /// Create a new vault with an admin caller.
     pub fn new(admin: impl Into<String>) -> Self {
         Self {
-            balances: HashMap::new(),
+            balances: BTreeMap::new(),
             whitelist: HashSet::new(),
             reentrancy_guard: ReentrancyGuard::new(),
             pause_guard: PauseGuard::new(),
             access_control: AccessControl::new(admin),
+            timelock: Timelock::new(),
+            deposits: HashMap::new(),
+            tokens: HashMap::new(),
             events: Vec::new(),
         }
     }
@@ -70,16 +164,25 @@ impl Vault {
     }
 
     /// Remove a token from the whitelist. Admin-only.
+    ///
+    /// With a timelock delay configured the removal is queued instead and
+    /// its operation ID returned; see [`execute_operation`](Self::execute_operation).
     pub fn remove_from_whitelist(
         &mut self,
         caller: &str,
         token: &str,
-    ) -> Result<(), VaultError> {
+        current_time: i64,
+    ) -> Result<Option<OperationId>, VaultError> {
         if !self.access_control.is_admin(caller) {
             return Err(VaultError::Unauthorized);
         }
-        self.whitelist.remove(token);
-        Ok(())
+        self.dispatch(
+            VaultOperation::RemoveFromWhitelist {
+                token: token.to_string(),
+            },
+            caller,
+            current_time,
+        )
     }
 
     /// Check if a token is whitelisted.
@@ -87,11 +190,156 @@ impl Vault {
         self.whitelist.contains(token)
     }
 
+    // ───────────────────────── Token Registry ─────────────────────────
+
+    /// Set a whitelisted token's metadata. Admin or operator only.
+    ///
+    /// The token's paused flag is left as is. Emits `TokenConfigured`.
+    pub fn configure_token(
+        &mut self,
+        caller: &str,
+        token: &str,
+        decimals: u32,
+        min_deposit: Decimal,
+        confirmation_threshold: u64,
+    ) -> Result<ContractEvent, VaultError> {
+        self.check_token_manager(caller)?;
+        if !self.is_whitelisted(token) {
+            return Err(VaultError::TokenNotWhitelisted {
+                token: token.to_string(),
+            });
+        }
+        let metadata = self.tokens.entry(token.to_string()).or_default();
+        metadata.decimals = decimals;
+        metadata.min_deposit = min_deposit;
+        metadata.confirmation_threshold = confirmation_threshold;
+
+        let event = ContractEvent::TokenConfigured(TokenConfigured {
+            token: token.to_string(),
+            decimals,
+            min_deposit,
+            confirmation_threshold,
+            configured_by: caller.to_string(),
+        });
+        self.events.push(event.clone());
+        Ok(event)
+    }
+
+    /// Pause deposits, confirmations and withdrawal requests of one token,
+    /// leaving the rest of the vault running. Admin or operator only.
+    ///
+    /// Emits `TokenPaused`.
+    pub fn pause_token(&mut self, caller: &str, token: &str) -> Result<ContractEvent, VaultError> {
+        self.set_token_paused(caller, token, true)
+    }
+
+    /// Lift a token pause. Admin or operator only.
+    ///
+    /// Emits `TokenPaused` with `paused: false`.
+    pub fn unpause_token(
+        &mut self,
+        caller: &str,
+        token: &str,
+    ) -> Result<ContractEvent, VaultError> {
+        self.set_token_paused(caller, token, false)
+    }
+
+    /// Get a token's metadata (the defaults if none is configured).
+    pub fn token_metadata(&self, token: &str) -> TokenMetadata {
+        self.tokens.get(token).cloned().unwrap_or_default()
+    }
+
+    /// Check if a token is paused.
+    pub fn is_token_paused(&self, token: &str) -> bool {
+        self.tokens.get(token).is_some_and(|metadata| metadata.paused)
+    }
+
+    /// Check that neither the vault nor `token` is paused.
+    pub fn check_token_active(&self, token: &str) -> Result<(), VaultError> {
+        self.check_not_paused()?;
+        if self.is_token_paused(token) {
+            return Err(VaultError::TokenPaused {
+                token: token.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn set_token_paused(
+        &mut self,
+        caller: &str,
+        token: &str,
+        paused: bool,
+    ) -> Result<ContractEvent, VaultError> {
+        self.check_token_manager(caller)?;
+        if !self.is_whitelisted(token) {
+            return Err(VaultError::TokenNotWhitelisted {
+                token: token.to_string(),
+            });
+        }
+        self.tokens.entry(token.to_string()).or_default().paused = paused;
+
+        let event = ContractEvent::TokenPaused(TokenPaused {
+            token: token.to_string(),
+            paused,
+            by: caller.to_string(),
+        });
+        self.events.push(event.clone());
+        Ok(event)
+    }
+
+    fn check_token_manager(&self, caller: &str) -> Result<(), VaultError> {
+        if !self.access_control.is_admin(caller)
+            && !self.access_control.has_role(caller, Role::Operator)
+        {
+            return Err(VaultError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Validate an incoming deposit against the whitelist and the token's
+    /// metadata.
+    fn validate_deposit(&self, asset: &str, amount: Decimal) -> Result<(), VaultError> {
+        if !self.is_whitelisted(asset) {
+            return Err(VaultError::TokenNotWhitelisted {
+                token: asset.to_string(),
+            });
+        }
+        let metadata = self.token_metadata(asset);
+        if metadata.paused {
+            return Err(VaultError::TokenPaused {
+                token: asset.to_string(),
+            });
+        }
+        if amount <= Decimal::ZERO {
+            return Err(VaultError::InvalidAmount);
+        }
+        if amount.normalize().scale() > metadata.decimals {
+            return Err(VaultError::ExcessPrecision {
+                token: asset.to_string(),
+                decimals: metadata.decimals,
+            });
+        }
+        if amount < metadata.min_deposit {
+            return Err(VaultError::BelowMinimumDeposit {
+                token: asset.to_string(),
+                minimum: metadata.min_deposit.to_string(),
+            });
+        }
+        Ok(())
+    }
+
     // ───────────────────────── Deposit ─────────────────────────
 
     /// Deposit assets into the vault for a given account.
     ///
-    /// Validates: not paused, no reentrancy, token whitelisted, amount positive.
+    /// Credits immediately, without finality tracking, for funds already
+    /// final. On-chain deposits go through
+    /// [`detect_deposit`](Self::detect_deposit).
+    ///
+    /// Validates: not paused, no reentrancy, token whitelisted and not
+    /// paused, amount positive, within the token's precision and at least
+    /// its minimum deposit.
     /// Emits `DepositDetected` event.
     pub fn deposit(
         &mut self,
@@ -104,20 +352,45 @@ impl Vault {
         self.check_not_paused()?;
         self.check_reentrancy()?;
 
-        // Validate token whitelist
-        if !self.is_whitelisted(asset) {
+        // Validate token and amount
+        if let Err(err) = self.validate_deposit(asset, amount) {
             self.reentrancy_guard.release();
-            return Err(VaultError::TokenNotWhitelisted {
-                token: asset.to_string(),
-            });
+            return Err(err);
         }
 
-        // Validate amount
+        self.credit(account_id, asset, amount, tx_id)
+    }
+
+    /// Return locked withdrawal funds to an account.
+    ///
+    /// Like [`deposit`](Self::deposit), but exempt from the token's pause,
+    /// precision and minimum deposit: those guard funds entering custody,
+    /// and a refund never left it.
+    pub fn refund(
+        &mut self,
+        account_id: AccountId,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<ContractEvent, VaultError> {
+        self.check_not_paused()?;
+        self.check_reentrancy()?;
+
         if amount <= Decimal::ZERO {
             self.reentrancy_guard.release();
             return Err(VaultError::InvalidAmount);
         }
 
+        self.credit(account_id, asset, amount, "refund")
+    }
+
+    /// Credit a validated deposit and release the reentrancy guard.
+    fn credit(
+        &mut self,
+        account_id: AccountId,
+        asset: &str,
+        amount: Decimal,
+        tx_id: &str,
+    ) -> Result<ContractEvent, VaultError> {
         // Credit balance using safe_transfer
         self.safe_credit(account_id, asset, amount)?;
 
@@ -128,6 +401,7 @@ impl Vault {
             amount,
             tx_id: tx_id.to_string(),
             confirmations: 1,
+            state: DepositState::Credited,
         });
 
         self.events.push(event.clone());
@@ -135,10 +409,19 @@ impl Vault {
         Ok(event)
     }
 
-    /// Confirm a deposit after required blockchain confirmations.
+    /// Record an on-chain deposit seen with `confirmations` confirmations.
     ///
-    /// Emits `DepositConfirmed` event. This is an idempotent status update.
-    pub fn confirm_deposit(
+    /// The deposit enters the ledger as `Detected` and is credited to the
+    /// spendable balance once it reaches the asset's finality threshold,
+    /// possibly right away. Detecting a recorded `tx_id` again is a
+    /// confirmation update; with different details it is refused.
+    ///
+    /// Validates: not paused, token whitelisted and not paused, amount
+    /// positive, within the token's precision and at least its minimum
+    /// deposit.
+    /// Emits `DepositDetected`, plus `DepositConfirmed` if credited; returns
+    /// the last event.
+    pub fn detect_deposit(
         &mut self,
         account_id: AccountId,
         asset: &str,
@@ -148,18 +431,178 @@ impl Vault {
     ) -> Result<ContractEvent, VaultError> {
         self.check_not_paused()?;
 
-        let event = ContractEvent::DepositConfirmed(DepositConfirmed {
+        if let Some(record) = self.deposits.get(tx_id) {
+            if record.account_id != account_id || record.asset != asset || record.amount != amount
+            {
+                return Err(VaultError::DepositConflict {
+                    tx_id: tx_id.to_string(),
+                });
+            }
+            return self.confirm_deposit(tx_id, confirmations);
+        }
+        self.validate_deposit(asset, amount)?;
+
+        self.deposits.insert(
+            tx_id.to_string(),
+            DepositRecord {
+                account_id,
+                asset: asset.to_string(),
+                amount,
+                tx_id: tx_id.to_string(),
+                confirmations,
+                state: DepositState::Detected,
+                debited: Decimal::ZERO,
+                shortfall: Decimal::ZERO,
+            },
+        );
+        let event = ContractEvent::DepositDetected(DepositDetected {
             account_id,
             asset: asset.to_string(),
             amount,
             tx_id: tx_id.to_string(),
             confirmations,
+            state: DepositState::Detected,
         });
+        self.events.push(event.clone());
+
+        if confirmations >= self.finality_threshold(asset) {
+            return self.update_confirmations(tx_id, confirmations);
+        }
+        Ok(event)
+    }
+
+    /// Update a recorded deposit's confirmation count.
+    ///
+    /// Credits the deposit once it reaches the asset's finality threshold.
+    /// Idempotent: counts never go down, a deposit is credited at most
+    /// once, and a repeated update emits nothing.
+    ///
+    /// Refused while the vault or the deposit's token is paused.
+    /// Emits `DepositConfirmed` with the deposit's state.
+    pub fn confirm_deposit(
+        &mut self,
+        tx_id: &str,
+        confirmations: u64,
+    ) -> Result<ContractEvent, VaultError> {
+        self.check_not_paused()?;
+        if let Some(record) = self.deposits.get(tx_id) {
+            self.check_token_active(&record.asset)?;
+        }
+        self.update_confirmations(tx_id, confirmations)
+    }
+
+    /// Roll back a deposit reorged out of the chain.
+    ///
+    /// A deposit not yet final is just marked `Reverted`. A credited one is
+    /// debited back; whatever the account no longer holds is recorded as a
+    /// shortfall. Reverting twice returns the same event without emitting
+    /// it again. Allowed while paused: the ledger must follow the chain.
+    ///
+    /// Emits `DepositReverted`.
+    pub fn revert_deposit(&mut self, tx_id: &str) -> Result<ContractEvent, VaultError> {
+        let record = self
+            .deposits
+            .get(tx_id)
+            .ok_or_else(|| VaultError::DepositNotFound {
+                tx_id: tx_id.to_string(),
+            })?;
+
+        let (debited, shortfall) = match record.state {
+            DepositState::Reverted => return Ok(Self::reverted_event(record)),
+            DepositState::Detected => (Decimal::ZERO, Decimal::ZERO),
+            DepositState::Credited => {
+                let available = self.get_balance(&record.account_id, &record.asset);
+                let debited = available.min(record.amount);
+                (debited, record.amount - debited)
+            }
+        };
+        let (account_id, asset) = (record.account_id, record.asset.clone());
+        if debited > Decimal::ZERO {
+            self.safe_debit(&account_id, &asset, debited)?;
+        }
 
+        let record = self.deposits.get_mut(tx_id).expect("deposit looked up above");
+        record.state = DepositState::Reverted;
+        record.debited = debited;
+        record.shortfall = shortfall;
+        let event = Self::reverted_event(record);
         self.events.push(event.clone());
         Ok(event)
     }
 
+    /// Confirmations required to credit deposits in `asset`, from its
+    /// [`TokenMetadata`]. Set with [`configure_token`](Self::configure_token);
+    /// applies to deposits not yet credited.
+    pub fn finality_threshold(&self, asset: &str) -> u64 {
+        self.tokens
+            .get(asset)
+            .map_or(DEFAULT_FINALITY_CONFIRMATIONS, |metadata| {
+                metadata.confirmation_threshold
+            })
+    }
+
+    /// Get a ledger deposit by on-chain transaction ID.
+    pub fn deposit_record(&self, tx_id: &str) -> Option<&DepositRecord> {
+        self.deposits.get(tx_id)
+    }
+
+    fn update_confirmations(
+        &mut self,
+        tx_id: &str,
+        confirmations: u64,
+    ) -> Result<ContractEvent, VaultError> {
+        let record = self
+            .deposits
+            .get(tx_id)
+            .ok_or_else(|| VaultError::DepositNotFound {
+                tx_id: tx_id.to_string(),
+            })?;
+        if record.state == DepositState::Reverted {
+            return Err(VaultError::DepositReverted {
+                tx_id: tx_id.to_string(),
+            });
+        }
+
+        let confirmations = record.confirmations.max(confirmations);
+        let changed = confirmations != record.confirmations;
+        let credit = record.state == DepositState::Detected
+            && confirmations >= self.finality_threshold(&record.asset);
+        if credit {
+            let (account_id, asset, amount) =
+                (record.account_id, record.asset.clone(), record.amount);
+            self.safe_credit(account_id, &asset, amount)?;
+        }
+
+        let record = self.deposits.get_mut(tx_id).expect("deposit looked up above");
+        record.confirmations = confirmations;
+        if credit {
+            record.state = DepositState::Credited;
+        }
+        let event = ContractEvent::DepositConfirmed(DepositConfirmed {
+            account_id: record.account_id,
+            asset: record.asset.clone(),
+            amount: record.amount,
+            tx_id: record.tx_id.clone(),
+            confirmations,
+            state: record.state,
+        });
+        if changed || credit {
+            self.events.push(event.clone());
+        }
+        Ok(event)
+    }
+
+    fn reverted_event(record: &DepositRecord) -> ContractEvent {
+        ContractEvent::DepositReverted(DepositReverted {
+            account_id: record.account_id,
+            asset: record.asset.clone(),
+            amount: record.amount,
+            tx_id: record.tx_id.clone(),
+            debited: record.debited,
+            shortfall: record.shortfall,
+        })
+    }
+
     // ───────────────────────── Balance Queries ─────────────────────────
 
     /// Get balance for a specific account and asset.
@@ -175,16 +618,77 @@ impl Vault {
     pub fn get_account_balances(
         &self,
         account_id: &AccountId,
-    ) -> Option<&HashMap<String, Decimal>> {
+    ) -> Option<&BTreeMap<String, Decimal>> {
         self.balances.get(account_id)
     }
 
+    /// Deterministic SHA-256 digest of all non-zero balances.
+    ///
+    /// Entries are hashed in (account, asset) order with normalized amounts,
+    /// so map iteration order and decimal scale do not affect the result.
+    /// This is the value a state commitment root is taken over.
+    pub fn state_hash(&self) -> [u8; 32] {
+        let mut entries: Vec<(String, &str, Decimal)> = self
+            .balances
+            .iter()
+            .flat_map(|(account, assets)| {
+                assets
+                    .iter()
+                    .filter(|(_, amount)| !amount.is_zero())
+                    .map(move |(asset, amount)| (account.to_string(), asset.as_str(), amount.normalize()))
+            })
+            .collect();
+        entries.sort();
+
+        let mut hasher = Sha256::new();
+        for (account, asset, amount) in entries {
+            hasher.update(format!("{}:{}:{};", account, asset, amount).as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    // ───────────────────────── Proof of Reserves ─────────────────────────
+
+    /// Merkle root over all non-zero balances, for proof of reserves.
+    ///
+    /// Leaves are [`reserve_leaf`]s in (account, asset) order, so the root
+    /// depends only on the balances, not on how they came about. An empty
+    /// vault has the all-zero root.
+    pub fn balances_merkle_root(&self) -> [u8; 32] {
+        merkle_root(&self.reserve_leaves())
+    }
+
+    /// Inclusion proof for an account's balance in an asset under
+    /// [`balances_merkle_root`](Self::balances_merkle_root), or `None` if
+    /// the account holds none of it.
+    pub fn generate_proof(&self, account_id: &AccountId, asset: &str) -> Option<MerkleProof> {
+        let index = self
+            .reserve_entries()
+            .position(|(account, held, _)| account == account_id && held == asset)?;
+        merkle_proof(&self.reserve_leaves(), index)
+    }
+
+    fn reserve_entries(&self) -> impl Iterator<Item = (&AccountId, &str, Decimal)> {
+        self.balances.iter().flat_map(|(account, assets)| {
+            assets
+                .iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .map(move |(asset, amount)| (account, asset.as_str(), *amount))
+        })
+    }
+
+    fn reserve_leaves(&self) -> Vec<[u8; 32]> {
+        self.reserve_entries()
+            .map(|(account, asset, amount)| reserve_leaf(account, asset, amount))
+            .collect()
+    }
+
     // ───────────────────────── Safe Transfer ─────────────────────────
 
     /// Internal credit with overflow protection.
     ///
     /// Adds `amount` to the account's asset balance, checking for arithmetic overflow.
-    fn safe_credit(
+    pub(crate) fn safe_credit(
         &mut self,
         account_id: AccountId,
         asset: &str,
@@ -269,18 +773,140 @@ impl Vault {
     // ───────────────────────── Access Control ─────────────────────────
 
     /// Transfer admin to a new address.
-    pub fn set_admin(&mut self, current_admin: &str, new_admin: &str) -> Result<(), VaultError> {
-        if !self.access_control.transfer_admin(current_admin, new_admin) {
+    ///
+    /// With a timelock delay configured the transfer is queued instead and
+    /// its operation ID returned; see [`execute_operation`](Self::execute_operation).
+    pub fn set_admin(
+        &mut self,
+        current_admin: &str,
+        new_admin: &str,
+        current_time: i64,
+    ) -> Result<Option<OperationId>, VaultError> {
+        if !self.access_control.is_admin(current_admin) {
+            return Err(VaultError::Unauthorized);
+        }
+        self.dispatch(
+            VaultOperation::SetAdmin {
+                new_admin: new_admin.to_string(),
+            },
+            current_admin,
+            current_time,
+        )
+    }
+
+    /// Grant guardian role (admin only). Guardians may cancel queued
+    /// operations.
+    pub fn grant_guardian(&mut self, admin: &str, guardian: impl Into<String>) -> bool {
+        self.access_control.grant_role(admin, guardian, Role::Guardian)
+    }
+
+    // ───────────────────────── Timelock ─────────────────────────
+
+    /// Set the timelock delay for a [`VaultOperation`] by name. Admin-only.
+    /// A delay of zero applies the operation immediately.
+    pub fn set_timelock_delay(
+        &mut self,
+        caller: &str,
+        operation: &str,
+        delay_seconds: i64,
+    ) -> Result<(), VaultError> {
+        if !self.access_control.is_admin(caller) {
             return Err(VaultError::Unauthorized);
         }
+        self.timelock.set_delay(operation, delay_seconds);
         Ok(())
     }
 
+    /// Execute a queued operation once its delay has passed. Admin-only.
+    pub fn execute_operation(
+        &mut self,
+        caller: &str,
+        operation_id: OperationId,
+        current_time: i64,
+    ) -> Result<(), VaultError> {
+        if !self.access_control.is_admin(caller) {
+            return Err(VaultError::Unauthorized);
+        }
+        let (operation, event) = self.timelock.execute(operation_id, current_time)?;
+        self.events.push(event);
+        self.apply(operation, caller);
+        Ok(())
+    }
+
+    /// Cancel a queued operation. Admin or guardian only.
+    pub fn cancel_operation(
+        &mut self,
+        caller: &str,
+        operation_id: OperationId,
+        current_time: i64,
+    ) -> Result<(), VaultError> {
+        if !self.access_control.is_admin(caller)
+            && !self.access_control.has_role(caller, Role::Guardian)
+        {
+            return Err(VaultError::Unauthorized);
+        }
+        let event = self.timelock.cancel(operation_id, caller, current_time)?;
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Get the vault's timelock.
+    pub fn timelock(&self) -> &Timelock<VaultOperation> {
+        &self.timelock
+    }
+
+    /// Apply `operation` now if it has no delay, otherwise queue it.
+    fn dispatch(
+        &mut self,
+        operation: VaultOperation,
+        caller: &str,
+        current_time: i64,
+    ) -> Result<Option<OperationId>, VaultError> {
+        if self.timelock.delay(operation.name()) == 0 {
+            self.apply(operation, caller);
+            return Ok(None);
+        }
+        let (id, event) = self.timelock.queue(operation, caller, current_time);
+        self.events.push(event);
+        Ok(Some(id))
+    }
+
+    /// Apply an authorized operation on behalf of admin `caller`.
+    fn apply(&mut self, operation: VaultOperation, caller: &str) {
+        match operation {
+            VaultOperation::SetAdmin { new_admin } => {
+                self.access_control.transfer_admin(caller, new_admin);
+            }
+            VaultOperation::RemoveFromWhitelist { token } => {
+                self.whitelist.remove(&token);
+            }
+        }
+    }
+
     /// Get the current admin.
     pub fn admin(&self) -> &str {
         self.access_control.admin()
     }
 
+    /// Grant operator role (admin only).
+    pub fn grant_operator(&mut self, admin: &str, operator: impl Into<String>) -> bool {
+        self.access_control
+            .grant_role(admin, operator, crate::security::Role::Operator)
+    }
+
+    /// Grant approver role (admin only).
+    pub fn grant_approver(&mut self, admin: &str, approver: impl Into<String>) -> bool {
+        self.access_control
+            .grant_role(admin, approver, crate::security::Role::Approver)
+    }
+
+    /// Revoke approver role (admin only). Fails if `approver` is not one.
+    pub fn revoke_approver(&mut self, admin: &str, approver: &str) -> bool {
+        self.access_control
+            .has_role(approver, crate::security::Role::Approver)
+            && self.access_control.revoke_role(admin, approver)
+    }
+
     /// Get reference to access control (for withdrawal module).
     pub(crate) fn access_control(&self) -> &AccessControl {
         &self.access_control
@@ -315,9 +941,26 @@ impl Vault {
     }
 }
 
+/// Merkle leaf for one balance in the proof-of-reserves tree.
+///
+/// Hashes `account:asset:amount` with the amount normalized, so `1.50` and
+/// `1.5` give the same leaf.
+pub fn reserve_leaf(account_id: &AccountId, asset: &str, amount: Decimal) -> [u8; 32] {
+    merkle_leaf(format!("{}:{}:{}", account_id, asset, amount.normalize()).as_bytes())
+}
+
+/// Verify a proof of reserves inclusion proof against a published root.
+///
+/// Needs nothing but the root, the client's own leaf (see
+/// [`reserve_leaf`]) and the proof.
+pub fn verify_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &MerkleProof) -> bool {
+    verify_merkle_proof(root, leaf, proof)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::TimelockError;
 
     fn setup_vault() -> Vault {
         let mut vault = Vault::new("admin");
@@ -340,7 +983,7 @@ mod tests {
     #[test]
     fn test_whitelist_remove() {
         let mut vault = setup_vault();
-        vault.remove_from_whitelist("admin", "ETH").unwrap();
+        vault.remove_from_whitelist("admin", "ETH", 0).unwrap();
         assert!(!vault.is_whitelisted("ETH"));
     }
 
@@ -416,16 +1059,218 @@ mod tests {
         assert_eq!(result, Err(VaultError::InvalidAmount));
     }
 
+    #[test]
+    fn test_deposit_below_minimum_rejected() {
+        let mut vault = setup_vault();
+        vault
+            .configure_token("admin", "BTC", 8, Decimal::new(1, 3), 3)
+            .unwrap();
+        let account = AccountId::new();
+
+        let result = vault.deposit(account, "BTC", Decimal::new(9, 4), "tx_01");
+        assert_eq!(
+            result,
+            Err(VaultError::BelowMinimumDeposit {
+                token: "BTC".to_string(),
+                minimum: "0.001".to_string(),
+            })
+        );
+        vault.deposit(account, "BTC", Decimal::new(1, 3), "tx_02").unwrap();
+        assert_eq!(vault.get_balance(&account, "BTC"), Decimal::new(1, 3));
+    }
+
+    #[test]
+    fn test_deposit_precision_checked_against_token_decimals() {
+        let mut vault = setup_vault();
+        vault
+            .configure_token("admin", "USDT", 6, Decimal::ZERO, 12)
+            .unwrap();
+        let account = AccountId::new();
+
+        let result = vault.deposit(account, "USDT", Decimal::new(10_000_001, 7), "tx_01");
+        assert_eq!(
+            result,
+            Err(VaultError::ExcessPrecision {
+                token: "USDT".to_string(),
+                decimals: 6,
+            })
+        );
+        // Trailing zeros are not extra precision
+        vault
+            .deposit(account, "USDT", Decimal::new(1_500_000_000, 9), "tx_02")
+            .unwrap();
+        assert_eq!(vault.get_balance(&account, "USDT"), Decimal::new(15, 1));
+    }
+
+    #[test]
+    fn test_configure_token() {
+        let mut vault = setup_vault();
+        assert!(vault.grant_operator("admin", "ops"));
+        assert_eq!(vault.token_metadata("ETH"), TokenMetadata::default());
+
+        let event = vault
+            .configure_token("ops", "ETH", 18, Decimal::new(1, 2), 12)
+            .unwrap();
+        assert!(matches!(event, ContractEvent::TokenConfigured(_)));
+        let metadata = vault.token_metadata("ETH");
+        assert_eq!(metadata.min_deposit, Decimal::new(1, 2));
+        assert_eq!(vault.finality_threshold("ETH"), 12);
+
+        assert_eq!(
+            vault.configure_token("eve", "ETH", 18, Decimal::ZERO, 1),
+            Err(VaultError::Unauthorized)
+        );
+        assert_eq!(
+            vault.configure_token("admin", "SHIB", 18, Decimal::ZERO, 1),
+            Err(VaultError::TokenNotWhitelisted {
+                token: "SHIB".to_string()
+            })
+        );
+
+        // Reconfiguring keeps the pause
+        vault.pause_token("ops", "ETH").unwrap();
+        vault.configure_token("ops", "ETH", 18, Decimal::ZERO, 6).unwrap();
+        assert!(vault.is_token_paused("ETH"));
+    }
+
     // ─── Confirm deposit tests ───
 
     #[test]
     fn test_confirm_deposit() {
         let mut vault = setup_vault();
         let account = AccountId::new();
-        let event = vault
-            .confirm_deposit(account, "BTC", Decimal::from(1), "tx_01", 6)
+        vault
+            .detect_deposit(account, "BTC", Decimal::from(1), "tx_01", 1)
             .unwrap();
+        let event = vault.confirm_deposit("tx_01", 6).unwrap();
         assert!(matches!(event, ContractEvent::DepositConfirmed(_)));
+        assert_eq!(
+            vault.confirm_deposit("tx_02", 6),
+            Err(VaultError::DepositNotFound {
+                tx_id: "tx_02".to_string()
+            })
+        );
+    }
+
+    // ─── Deposit ledger tests ───
+
+    fn confirmed(event: &ContractEvent) -> &DepositConfirmed {
+        match event {
+            ContractEvent::DepositConfirmed(e) => e,
+            other => panic!("Expected DepositConfirmed, got {:?}", other),
+        }
+    }
+
+    fn reverted(event: &ContractEvent) -> &DepositReverted {
+        match event {
+            ContractEvent::DepositReverted(e) => e,
+            other => panic!("Expected DepositReverted, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_confirm_then_reorg() {
+        let mut vault = setup_vault();
+        vault.configure_token("admin", "BTC", 8, Decimal::ZERO, 3).unwrap();
+        let acc = AccountId::new();
+
+        let event = vault.detect_deposit(acc, "BTC", Decimal::from(2), "tx_a", 1).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::DepositDetected(DepositDetected { state: DepositState::Detected, .. })
+        ));
+        vault.confirm_deposit("tx_a", 2).unwrap();
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::ZERO);
+
+        let event = vault.confirm_deposit("tx_a", 3).unwrap();
+        assert_eq!(confirmed(&event).state, DepositState::Credited);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(2));
+        vault.confirm_deposit("tx_a", 4).unwrap();
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(2));
+
+        // Reorg after part of the credit was spent
+        vault.safe_debit(&acc, "BTC", Decimal::new(15, 1)).unwrap();
+        let event = vault.revert_deposit("tx_a").unwrap();
+        assert_eq!(reverted(&event).debited, Decimal::new(5, 1));
+        assert_eq!(reverted(&event).shortfall, Decimal::new(15, 1));
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::ZERO);
+        assert_eq!(
+            vault.confirm_deposit("tx_a", 10),
+            Err(VaultError::DepositReverted {
+                tx_id: "tx_a".to_string()
+            })
+        );
+
+        // Reverting again changes nothing
+        let events = vault.events().len();
+        assert_eq!(vault.revert_deposit("tx_a").unwrap(), event);
+        assert_eq!(vault.events().len(), events);
+    }
+
+    #[test]
+    fn test_reorg_of_unconfirmed_deposit() {
+        let mut vault = setup_vault();
+        let acc = AccountId::new();
+        vault.detect_deposit(acc, "ETH", Decimal::from(5), "tx_b", 2).unwrap();
+
+        let event = vault.revert_deposit("tx_b").unwrap();
+        assert_eq!(reverted(&event).debited, Decimal::ZERO);
+        assert_eq!(reverted(&event).shortfall, Decimal::ZERO);
+        assert_eq!(vault.deposit_record("tx_b").unwrap().state, DepositState::Reverted);
+
+        // Late confirmations of the orphaned transaction never credit it
+        assert!(vault.confirm_deposit("tx_b", 50).is_err());
+        assert!(vault.detect_deposit(acc, "ETH", Decimal::from(5), "tx_b", 50).is_err());
+        assert_eq!(vault.get_balance(&acc, "ETH"), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_finality_threshold_per_asset() {
+        let mut vault = setup_vault();
+        vault.configure_token("admin", "BTC", 8, Decimal::ZERO, 3).unwrap();
+        vault.configure_token("admin", "ETH", 18, Decimal::ZERO, 12).unwrap();
+        assert_eq!(
+            vault.configure_token("eve", "ETH", 18, Decimal::ZERO, 1),
+            Err(VaultError::Unauthorized)
+        );
+        assert_eq!(vault.finality_threshold("USDT"), DEFAULT_FINALITY_CONFIRMATIONS);
+        let acc = AccountId::new();
+
+        let event = vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx_btc", 3).unwrap();
+        assert_eq!(confirmed(&event).state, DepositState::Credited);
+        vault.detect_deposit(acc, "ETH", Decimal::from(1), "tx_eth", 3).unwrap();
+        vault.detect_deposit(acc, "USDT", Decimal::from(1), "tx_usdt", 3).unwrap();
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(1));
+        assert_eq!(vault.get_balance(&acc, "ETH"), Decimal::ZERO);
+        assert_eq!(vault.get_balance(&acc, "USDT"), Decimal::ZERO);
+
+        vault.confirm_deposit("tx_eth", 11).unwrap();
+        assert_eq!(vault.get_balance(&acc, "ETH"), Decimal::ZERO);
+        vault.confirm_deposit("tx_eth", 12).unwrap();
+        vault.confirm_deposit("tx_usdt", 6).unwrap();
+        assert_eq!(vault.get_balance(&acc, "ETH"), Decimal::from(1));
+        assert_eq!(vault.get_balance(&acc, "USDT"), Decimal::from(1));
+    }
+
+    #[test]
+    fn test_duplicate_detection_is_idempotent() {
+        let mut vault = setup_vault();
+        let acc = AccountId::new();
+        vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx_c", 6).unwrap();
+        let events = vault.events().len();
+
+        vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx_c", 6).unwrap();
+        vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx_c", 2).unwrap();
+        assert_eq!(vault.events().len(), events);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(1));
+        assert_eq!(vault.deposit_record("tx_c").unwrap().confirmations, 6);
+
+        assert_eq!(
+            vault.detect_deposit(acc, "BTC", Decimal::from(2), "tx_c", 6),
+            Err(VaultError::DepositConflict {
+                tx_id: "tx_c".to_string()
+            })
+        );
     }
 
     // ─── Balance query tests ───
@@ -449,6 +1294,24 @@ mod tests {
         assert_eq!(balances["BTC"], Decimal::from(5));
     }
 
+    #[test]
+    fn test_state_hash_ignores_order_and_scale() {
+        let a = AccountId::new();
+        let b = AccountId::new();
+
+        let mut first = setup_vault();
+        first.deposit(a, "BTC", Decimal::from(5), "tx_01").unwrap();
+        first.deposit(b, "ETH", Decimal::new(2000, 2), "tx_02").unwrap();
+
+        let mut second = setup_vault();
+        second.deposit(b, "ETH", Decimal::from(20), "tx_02").unwrap();
+        second.deposit(a, "BTC", Decimal::new(50, 1), "tx_01").unwrap();
+        assert_eq!(first.state_hash(), second.state_hash());
+
+        second.safe_debit(&a, "BTC", Decimal::from(1)).unwrap();
+        assert_ne!(first.state_hash(), second.state_hash());
+    }
+
     // ─── Safe debit tests ───
 
     #[test]
@@ -509,14 +1372,14 @@ mod tests {
     #[test]
     fn test_set_admin() {
         let mut vault = Vault::new("alice");
-        vault.set_admin("alice", "bob").unwrap();
+        vault.set_admin("alice", "bob", 0).unwrap();
         assert_eq!(vault.admin(), "bob");
     }
 
     #[test]
     fn test_set_admin_unauthorized() {
         let mut vault = Vault::new("alice");
-        let result = vault.set_admin("eve", "bob");
+        let result = vault.set_admin("eve", "bob", 0);
         assert_eq!(result, Err(VaultError::Unauthorized));
     }
 
@@ -557,4 +1420,134 @@ mod tests {
         assert_eq!(vault.get_balance(&acc1, "BTC"), Decimal::from(10));
         assert_eq!(vault.get_balance(&acc2, "BTC"), Decimal::from(5));
     }
+
+    // ─── Timelock ───
+
+    #[test]
+    fn test_timelocked_set_admin() {
+        let mut vault = setup_vault();
+        vault.set_timelock_delay("admin", VaultOperation::SET_ADMIN, 3600).unwrap();
+        assert!(vault.grant_guardian("admin", "guardian"));
+
+        let id = vault.set_admin("admin", "new_admin", 1000).unwrap().unwrap();
+        assert_eq!(vault.admin(), "admin");
+        assert!(matches!(vault.events().last(), Some(ContractEvent::TimelockQueued(_))));
+
+        assert_eq!(
+            vault.execute_operation("admin", id, 4599),
+            Err(VaultError::Timelock(TimelockError::NotReady { execute_after: 4600 }))
+        );
+        vault.execute_operation("admin", id, 4600).unwrap();
+        assert_eq!(vault.admin(), "new_admin");
+        assert_eq!(
+            vault.execute_operation("new_admin", id, 4700),
+            Err(VaultError::Timelock(TimelockError::AlreadyExecuted { operation_id: id }))
+        );
+    }
+
+    #[test]
+    fn test_guardian_cancels_queued_removal() {
+        let mut vault = setup_vault();
+        vault
+            .set_timelock_delay("admin", VaultOperation::REMOVE_FROM_WHITELIST, 60)
+            .unwrap();
+        assert!(vault.grant_guardian("admin", "guardian"));
+        let id = vault.remove_from_whitelist("admin", "ETH", 1000).unwrap().unwrap();
+
+        assert_eq!(vault.cancel_operation("eve", id, 1010), Err(VaultError::Unauthorized));
+        vault.cancel_operation("guardian", id, 1010).unwrap();
+        assert!(matches!(vault.events().last(), Some(ContractEvent::TimelockCancelled(_))));
+        assert_eq!(
+            vault.execute_operation("admin", id, 2000),
+            Err(VaultError::Timelock(TimelockError::Cancelled { operation_id: id }))
+        );
+        assert!(vault.is_whitelisted("ETH"));
+
+        // Back to no delay: the removal applies at once
+        vault
+            .set_timelock_delay("admin", VaultOperation::REMOVE_FROM_WHITELIST, 0)
+            .unwrap();
+        assert_eq!(vault.remove_from_whitelist("admin", "BTC", 2000), Ok(None));
+        assert!(!vault.is_whitelisted("BTC"));
+    }
+
+    // ─── Proof of reserves ───
+
+    #[test]
+    fn test_single_leaf_reserves_tree() {
+        let mut vault = setup_vault();
+        let acc = AccountId::new();
+        vault.deposit(acc, "BTC", Decimal::new(150, 2), "tx_01").unwrap();
+
+        let leaf = reserve_leaf(&acc, "BTC", Decimal::new(15, 1));
+        let root = vault.balances_merkle_root();
+        assert_eq!(root, leaf);
+        let proof = vault.generate_proof(&acc, "BTC").unwrap();
+        assert!(proof.steps.is_empty());
+        assert!(verify_proof(&root, &leaf, &proof));
+    }
+
+    #[test]
+    fn test_reserves_proofs_with_odd_leaf_counts() {
+        for count in [3usize, 5] {
+            let mut vault = setup_vault();
+            let accounts: Vec<AccountId> = (0..count).map(|_| AccountId::new()).collect();
+            for (i, acc) in accounts.iter().enumerate() {
+                let amount = Decimal::from(i as u64 + 1);
+                vault.deposit(*acc, "ETH", amount, &format!("tx_{i}")).unwrap();
+            }
+            let root = vault.balances_merkle_root();
+            for (i, acc) in accounts.iter().enumerate() {
+                let leaf = reserve_leaf(acc, "ETH", Decimal::from(i as u64 + 1));
+                let proof = vault.generate_proof(acc, "ETH").unwrap();
+                assert!(verify_proof(&root, &leaf, &proof), "{count} leaves, leaf {i}");
+                // A different amount is not covered
+                let inflated = reserve_leaf(acc, "ETH", Decimal::from(i as u64 + 2));
+                assert!(!verify_proof(&root, &inflated, &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reserves_proof_for_nonexistent_account_fails() {
+        let mut vault = setup_vault();
+        let acc = AccountId::new();
+        vault.deposit(acc, "BTC", Decimal::from(1), "tx_01").unwrap();
+        vault.deposit(AccountId::new(), "BTC", Decimal::from(2), "tx_02").unwrap();
+        let root = vault.balances_merkle_root();
+
+        let stranger = AccountId::new();
+        assert!(vault.generate_proof(&stranger, "BTC").is_none());
+        assert!(vault.generate_proof(&acc, "ETH").is_none());
+
+        // Borrowing a real account's path proves nothing for another leaf
+        let proof = vault.generate_proof(&acc, "BTC").unwrap();
+        let forged = reserve_leaf(&stranger, "BTC", Decimal::from(1));
+        assert!(!verify_proof(&root, &forged, &proof));
+    }
+
+    #[test]
+    fn test_reserves_root_independent_of_insertion_order() {
+        let accounts: Vec<AccountId> = (0..4).map(|_| AccountId::new()).collect();
+        let deposits: Vec<(AccountId, &str, Decimal)> = accounts
+            .iter()
+            .flat_map(|acc| [(*acc, "BTC", Decimal::from(1)), (*acc, "USDT", Decimal::from(100))])
+            .collect();
+
+        let mut forward = setup_vault();
+        for (i, (acc, asset, amount)) in deposits.iter().enumerate() {
+            forward.deposit(*acc, asset, *amount, &format!("tx_{i}")).unwrap();
+        }
+        let mut backward = setup_vault();
+        for (i, (acc, asset, amount)) in deposits.iter().enumerate().rev() {
+            backward.deposit(*acc, asset, *amount, &format!("tx_{i}")).unwrap();
+        }
+        assert_eq!(forward.balances_merkle_root(), backward.balances_merkle_root());
+
+        // Emptied balances drop out of the tree
+        backward.deposit(accounts[0], "ETH", Decimal::from(3), "tx_eth").unwrap();
+        assert_ne!(forward.balances_merkle_root(), backward.balances_merkle_root());
+        backward.safe_debit(&accounts[0], "ETH", Decimal::from(3)).unwrap();
+        assert_eq!(forward.balances_merkle_root(), backward.balances_merkle_root());
+    }
 }
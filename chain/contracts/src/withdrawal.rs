@@ -4,22 +4,38 @@
 //! - Withdrawal request with signature verification
 //! - Nonce-based replay protection
 //! - Time-delay enforcement (24h for new addresses per spec §16.6.3)
-//! - Batch withdrawal processing
+//! - Batch withdrawal processing, settled under one Merkle commitment
+//! - Rate-limited, prioritized processing windows
 //! - Emergency cancellation
+//! - Expiry of unprocessed requests, refunding the locked funds
+//! - M-of-N approvals for withdrawals above per-asset thresholds
+//! - Per-withdrawal and rolling daily per-account limits
 
+use ed25519_dalek::{Signature, VerifyingKey};
 use rust_decimal::Decimal;
-use std::collections::VecDeque;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use types::ids::AccountId;
 use uuid::Uuid;
 
-use crate::errors::WithdrawalError;
-use crate::events::{ContractEvent, WithdrawalCompleted, WithdrawalRequested};
-use crate::security::NonceTracker;
+use crate::commitment::{
+    merkle_leaf, merkle_proof, merkle_root, verify_merkle_proof, CommitmentStore, MerkleProof,
+    WithdrawalBatchCommitment,
+};
+use crate::errors::{VaultError, WithdrawalError};
+use crate::events::{
+    ContractEvent, WithdrawalApprovalAdded, WithdrawalApproved, WithdrawalBatchProcessed,
+    WithdrawalCancelled, WithdrawalCompleted, WithdrawalExpired, WithdrawalLimitUpdated,
+    WithdrawalRejected, WithdrawalRequested, WithdrawalSkipped, WithdrawalThrottled,
+};
+use crate::security::{NonceTracker, Role};
 use crate::vault::Vault;
 
 /// Status of a withdrawal request.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WithdrawalStatus {
+    /// Above the approval threshold, awaiting approvals
+    PendingApproval,
     /// Queued, awaiting delay period
     Pending,
     /// Ready to process (delay elapsed)
@@ -28,6 +44,10 @@ pub enum WithdrawalStatus {
     Completed,
     /// Cancelled by owner or admin
     Cancelled,
+    /// Approval window expired before enough approvals (funds refunded)
+    Rejected,
+    /// Expired unprocessed (funds refunded)
+    Expired,
 }
 
 /// A single withdrawal request.
@@ -42,6 +62,194 @@ pub struct WithdrawalRequest {
     pub requested_at: i64,
     pub delay_until: i64,
     pub status: WithdrawalStatus,
+    /// Expedite sequence number; expedited requests are processed first,
+    /// in the order they were expedited
+    pub expedited: Option<u64>,
+    /// Approvers who approved, in order (approval-gated requests only)
+    pub approvals: Vec<String>,
+    /// End of the approval window (approval-gated requests only)
+    pub approval_deadline: Option<i64>,
+    /// Time from which the request can no longer be processed
+    pub expires_at: i64,
+}
+
+impl WithdrawalRequest {
+    /// Whether the request can be processed at `time`: pending, delay
+    /// elapsed and not expired.
+    fn is_eligible(&self, time: i64) -> bool {
+        self.status == WithdrawalStatus::Pending
+            && time >= self.delay_until
+            && time < self.expires_at
+    }
+}
+
+/// Multi-signature approval configuration.
+///
+/// Requests whose amount exceeds the asset's threshold need
+/// `required_approvals` approvals from distinct approvers within
+/// `expiry_seconds` of the request. Assets without a threshold never need
+/// approval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalPolicy {
+    pub thresholds: HashMap<String, Decimal>,
+    pub required_approvals: usize,
+    pub expiry_seconds: i64,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            thresholds: HashMap::new(),
+            required_approvals: 2,
+            expiry_seconds: 86400,
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    /// Whether a withdrawal of `amount` in `asset` needs approval.
+    pub fn requires_approval(&self, asset: &str, amount: Decimal) -> bool {
+        self.thresholds
+            .get(asset)
+            .is_some_and(|threshold| amount > *threshold)
+    }
+}
+
+/// Default lifetime of a withdrawal request, in seconds (7 days).
+pub const DEFAULT_EXPIRY_SECONDS: i64 = 7 * 86400;
+
+/// Length of the rolling window for daily withdrawal caps, in seconds.
+pub const DAILY_LIMIT_WINDOW_SECONDS: i64 = 86400;
+
+/// What happens to a request over its account's daily cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DailyCapAction {
+    /// Refuse the request
+    #[default]
+    Reject,
+    /// Accept it, pending approval like a request above the approval
+    /// threshold
+    RequireApproval,
+}
+
+/// Withdrawal velocity limits for one asset.
+///
+/// The daily cap applies per account to the total requested over the
+/// trailing [`DAILY_LIMIT_WINDOW_SECONDS`] of request timestamps, i.e.
+/// requests made in `(now - window, now]`. Cancelled and rejected requests
+/// do not count. `None` means no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WithdrawalLimits {
+    pub max_per_withdrawal: Option<Decimal>,
+    pub daily_cap: Option<Decimal>,
+    pub over_daily_cap: DailyCapAction,
+}
+
+/// Per-asset throughput caps for a single processing window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowCap {
+    /// Maximum number of withdrawals selected per window
+    pub max_withdrawals: usize,
+    /// Maximum total amount selected per window
+    pub max_notional: Decimal,
+}
+
+impl WindowCap {
+    /// No throughput limit.
+    pub const UNLIMITED: WindowCap = WindowCap {
+        max_withdrawals: usize::MAX,
+        max_notional: Decimal::MAX,
+    };
+}
+
+/// Processing scheduler configuration.
+///
+/// Windows are aligned to multiples of `window_seconds` on the caller-supplied
+/// clock. Assets without an entry in `asset_caps` use `default_cap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessingSchedule {
+    pub window_seconds: i64,
+    pub default_cap: WindowCap,
+    pub asset_caps: HashMap<String, WindowCap>,
+}
+
+impl ProcessingSchedule {
+    /// Get the cap applied to an asset.
+    pub fn cap_for(&self, asset: &str) -> WindowCap {
+        self.asset_caps
+            .get(asset)
+            .copied()
+            .unwrap_or(self.default_cap)
+    }
+
+    /// Start of the window containing `time`.
+    pub fn window_start(&self, time: i64) -> i64 {
+        time - time.rem_euclid(self.window_seconds.max(1))
+    }
+}
+
+impl Default for ProcessingSchedule {
+    fn default() -> Self {
+        Self {
+            window_seconds: 3600,
+            default_cap: WindowCap::UNLIMITED,
+            asset_caps: HashMap::new(),
+        }
+    }
+}
+
+/// What [`WithdrawalQueue::process_batch`] does when an item cannot settle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchFailurePolicy {
+    /// Leave the item pending for a later batch and settle the rest
+    #[default]
+    SkipItem,
+    /// Settle nothing; the batch fails with the first failing item
+    RollbackBatch,
+}
+
+/// Settlement configuration for [`WithdrawalQueue::process_batch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchPolicy {
+    /// Network fee debited from the vault per settled item, in its asset
+    pub fee: Decimal,
+    pub on_failure: BatchFailurePolicy,
+}
+
+/// A settled withdrawal batch.
+#[derive(Debug, Clone)]
+pub struct WithdrawalBatch {
+    pub batch_id: u64,
+    /// Merkle root over `items`, as recorded in the `CommitmentStore`
+    pub root: [u8; 32],
+    /// Settled withdrawals, in FIFO order
+    pub items: Vec<WithdrawalRequest>,
+    /// Withdrawals skipped under [`BatchFailurePolicy::SkipItem`]
+    pub skipped: Vec<Uuid>,
+}
+
+impl WithdrawalBatch {
+    /// Inclusion proof for a settled withdrawal of this batch.
+    pub fn proof(&self, withdrawal_id: Uuid) -> Option<MerkleProof> {
+        let index = self
+            .items
+            .iter()
+            .position(|r| r.withdrawal_id == withdrawal_id)?;
+        let leaves: Vec<[u8; 32]> = self
+            .items
+            .iter()
+            .map(WithdrawalQueue::withdrawal_leaf)
+            .collect();
+        merkle_proof(&leaves, index)
+    }
+}
+
+/// Usage accumulated in the current processing window.
+#[derive(Debug, Default)]
+struct WindowUsage {
+    window_start: i64,
+    /// Per asset: (withdrawals selected, notional selected)
+    per_asset: HashMap<String, (usize, Decimal)>,
 }
 
 /// Withdrawal queue and processor.
@@ -50,10 +258,31 @@ pub struct WithdrawalRequest {
 /// `request → queue → (wait delay) → process → complete`
 #[derive(Debug)]
 pub struct WithdrawalQueue {
-    queue: VecDeque<WithdrawalRequest>,
-    nonce_tracker: NonceTracker,
+    pub(crate) queue: VecDeque<WithdrawalRequest>,
+    pub(crate) nonce_tracker: NonceTracker,
+    /// Registered Ed25519 signer keys per account
+    signers: HashMap<AccountId, [u8; 32]>,
     /// Withdrawal delay in seconds (default: 86400 = 24h per spec §16.6.3)
     delay_seconds: i64,
+    /// Request lifetime in seconds, from the request time
+    expiry_seconds: i64,
+    /// Processing window caps
+    schedule: ProcessingSchedule,
+    /// Usage in the current processing window
+    window: WindowUsage,
+    /// Next expedite sequence number
+    pub(crate) next_expedite: u64,
+    /// Batch settlement configuration
+    batch_policy: BatchPolicy,
+    /// Next withdrawal batch ID
+    pub(crate) next_batch_id: u64,
+    /// Approval thresholds, quorum and window
+    approval_policy: ApprovalPolicy,
+    /// Ed25519 keys of approvers; the approver role itself lives in the
+    /// vault's access control
+    approver_keys: HashMap<String, [u8; 32]>,
+    /// Velocity limits per asset
+    limits: HashMap<String, WithdrawalLimits>,
     /// Emitted events
     events: Vec<ContractEvent>,
 }
@@ -64,7 +293,17 @@ impl WithdrawalQueue {
         Self {
             queue: VecDeque::new(),
             nonce_tracker: NonceTracker::new(),
+            signers: HashMap::new(),
             delay_seconds,
+            expiry_seconds: DEFAULT_EXPIRY_SECONDS,
+            schedule: ProcessingSchedule::default(),
+            window: WindowUsage::default(),
+            next_expedite: 0,
+            batch_policy: BatchPolicy::default(),
+            next_batch_id: 1,
+            approval_policy: ApprovalPolicy::default(),
+            approver_keys: HashMap::new(),
+            limits: HashMap::new(),
             events: Vec::new(),
         }
     }
@@ -74,10 +313,40 @@ impl WithdrawalQueue {
         Self::new(86400)
     }
 
+    /// Set the request lifetime in seconds. Applies to requests made
+    /// afterwards; it should exceed the delay, or requests expire before
+    /// they can be processed.
+    pub fn set_expiry(&mut self, expiry_seconds: i64) {
+        self.expiry_seconds = expiry_seconds;
+    }
+
+    /// Get the request lifetime in seconds.
+    pub fn expiry_seconds(&self) -> i64 {
+        self.expiry_seconds
+    }
+
+    /// Register the Ed25519 signer key for an account.
+    ///
+    /// Withdrawals for the account require a valid signature over
+    /// [`withdrawal_signing_payload`](Self::withdrawal_signing_payload); an
+    /// account without a signer cannot withdraw.
+    pub fn register_signer(&mut self, account_id: AccountId, public_key: [u8; 32]) {
+        self.signers.insert(account_id, public_key);
+    }
+
+    /// Get the registered signer key for an account.
+    pub fn signer(&self, account_id: &AccountId) -> Option<&[u8; 32]> {
+        self.signers.get(account_id)
+    }
+
     /// Request a withdrawal.
     ///
-    /// Validates: signature (via `verify_signature`), nonce uniqueness,
-    /// sufficient balance, positive amount. Applies time delay.
+    /// Validates: vault and token not paused, a registered signer and its
+    /// signature (via `verify_signer_signature`), nonce uniqueness,
+    /// withdrawal limits, sufficient balance, positive amount. Applies time
+    /// delay. Requests over the approval threshold, or over the daily cap
+    /// when it routes to approval, start in `PendingApproval`.
+    #[allow(clippy::too_many_arguments)]
     pub fn request_withdrawal(
         &mut self,
         vault: &mut Vault,
@@ -94,11 +363,30 @@ impl WithdrawalQueue {
             return Err(WithdrawalError::InvalidAmount);
         }
 
+        // Refuse while the vault or the token is paused
+        vault.check_token_active(asset).map_err(WithdrawalError::Vault)?;
+
         // Validate signature
-        if !Self::verify_signature(account_id, asset, amount, nonce, destination, signature) {
+        let Some(public_key) = self.signers.get(&account_id) else {
+            return Err(WithdrawalError::SignerNotRegistered {
+                account_id: account_id.to_string(),
+            });
+        };
+        if !Self::verify_signer_signature(
+            public_key,
+            account_id,
+            asset,
+            amount,
+            nonce,
+            destination,
+            signature,
+        ) {
             return Err(WithdrawalError::InvalidSignature);
         }
 
+        // Enforce velocity limits
+        let over_daily_cap = self.check_limits(account_id, asset, amount, current_time)?;
+
         // Validate nonce (replay protection)
         if !self.nonce_tracker.use_nonce(account_id, nonce) {
             return Err(WithdrawalError::NonceReused {
@@ -120,6 +408,8 @@ impl WithdrawalQueue {
 
         let withdrawal_id = Uuid::now_v7();
         let delay_until = current_time + self.delay_seconds;
+        let needs_approval =
+            over_daily_cap || self.approval_policy.requires_approval(asset, amount);
 
         let request = WithdrawalRequest {
             withdrawal_id,
@@ -130,24 +420,39 @@ impl WithdrawalQueue {
             nonce,
             requested_at: current_time,
             delay_until,
-            status: WithdrawalStatus::Pending,
+            status: if needs_approval {
+                WithdrawalStatus::PendingApproval
+            } else {
+                WithdrawalStatus::Pending
+            },
+            expedited: None,
+            approvals: Vec::new(),
+            approval_deadline: needs_approval
+                .then(|| current_time + self.approval_policy.expiry_seconds),
+            expires_at: current_time + self.expiry_seconds,
         };
 
-        self.queue.push_back(request);
-
         let event = ContractEvent::WithdrawalRequested(WithdrawalRequested {
             withdrawal_id,
             account_id,
             asset: asset.to_string(),
             amount,
             destination: destination.to_string(),
+            nonce,
+            requested_at: current_time,
+            delay_until,
+            expires_at: request.expires_at,
+            approval_deadline: request.approval_deadline,
         });
 
+        self.queue.push_back(request);
         self.events.push(event.clone());
         Ok(event)
     }
 
-    /// Process a single withdrawal by ID if the delay has elapsed.
+    /// Process a single withdrawal by ID if the delay has elapsed and it
+    /// has not expired. An expired pending request is refused with
+    /// `Expired` and left for [`sweep_expired`](Self::sweep_expired).
     pub fn process_withdrawal(
         &mut self,
         withdrawal_id: Uuid,
@@ -166,6 +471,23 @@ impl WithdrawalQueue {
         match request.status {
             WithdrawalStatus::Cancelled => return Err(WithdrawalError::AlreadyCancelled),
             WithdrawalStatus::Completed => return Err(WithdrawalError::AlreadyProcessed),
+            WithdrawalStatus::Rejected => return Err(WithdrawalError::Rejected),
+            WithdrawalStatus::Expired => {
+                return Err(WithdrawalError::Expired {
+                    expired_at: request.expires_at,
+                })
+            }
+            WithdrawalStatus::PendingApproval => {
+                return Err(WithdrawalError::AwaitingApproval {
+                    approvals: request.approvals.len(),
+                    required: self.approval_policy.required_approvals,
+                })
+            }
+            WithdrawalStatus::Pending if current_time >= request.expires_at => {
+                return Err(WithdrawalError::Expired {
+                    expired_at: request.expires_at,
+                })
+            }
             _ => {}
         }
 
@@ -181,6 +503,7 @@ impl WithdrawalQueue {
             withdrawal_id,
             tx_id: tx_id.to_string(),
             fee,
+            batch_id: None,
         });
 
         self.events.push(event.clone());
@@ -199,9 +522,7 @@ impl WithdrawalQueue {
         let ready_ids: Vec<Uuid> = self
             .queue
             .iter()
-            .filter(|r| {
-                r.status == WithdrawalStatus::Pending && current_time >= r.delay_until
-            })
+            .filter(|r| r.is_eligible(current_time))
             .map(|r| r.withdrawal_id)
             .collect();
 
@@ -219,15 +540,76 @@ impl WithdrawalQueue {
         Ok(events)
     }
 
-    /// Emergency cancel a withdrawal by owner or admin.
+    /// Set the approval configuration.
     ///
-    /// Refunds the locked amount back to the vault.
-    pub fn cancel_withdrawal(
+    /// Applies to requests made afterwards; the quorum applies to every
+    /// request still pending approval.
+    pub fn set_approval_policy(&mut self, policy: ApprovalPolicy) {
+        self.approval_policy = policy;
+    }
+
+    /// Get the approval configuration.
+    pub fn approval_policy(&self) -> &ApprovalPolicy {
+        &self.approval_policy
+    }
+
+    /// Add or rotate an approver (admin only): grants the approver role in
+    /// the vault and sets the key its approvals are verified against.
+    pub fn register_approver(
         &mut self,
         vault: &mut Vault,
-        withdrawal_id: Uuid,
-        caller: &str,
+        admin: &str,
+        approver: &str,
+        public_key: [u8; 32],
+    ) -> Result<(), WithdrawalError> {
+        if !vault.grant_approver(admin, approver) {
+            return Err(WithdrawalError::ApproverUnauthorized);
+        }
+        self.approver_keys.insert(approver.to_string(), public_key);
+        Ok(())
+    }
+
+    /// Remove an approver (admin only). Approvals it already gave stop
+    /// counting towards requests still pending approval.
+    pub fn remove_approver(
+        &mut self,
+        vault: &mut Vault,
+        admin: &str,
+        approver: &str,
     ) -> Result<(), WithdrawalError> {
+        if !vault.access_control().is_admin(admin) {
+            return Err(WithdrawalError::ApproverUnauthorized);
+        }
+        if !vault.revoke_approver(admin, approver) {
+            return Err(WithdrawalError::NotApprover {
+                approver: approver.to_string(),
+            });
+        }
+        self.approver_keys.remove(approver);
+        Ok(())
+    }
+
+    /// Approve a withdrawal pending approval.
+    ///
+    /// `signature` must be the approver's Ed25519 signature over
+    /// [`withdrawal_hash`](Self::withdrawal_hash). A second approval from the
+    /// same approver is refused. Only approvals from current approvers
+    /// count; once they reach the required number the request moves to
+    /// `Pending` and follows the normal processing flow. Approving after
+    /// the window has closed rejects the request instead, emitting
+    /// `WithdrawalRejected`.
+    ///
+    /// Emits `WithdrawalApprovalAdded`, plus `WithdrawalApproved` when the
+    /// quorum is reached; returns the last event.
+    pub fn approve_withdrawal(
+        &mut self,
+        vault: &mut Vault,
+        withdrawal_id: Uuid,
+        approver: &str,
+        signature: &[u8],
+        current_time: i64,
+    ) -> Result<ContractEvent, WithdrawalError> {
+        let required = self.approval_policy.required_approvals;
         let request = self
             .queue
             .iter_mut()
@@ -235,155 +617,759 @@ impl WithdrawalQueue {
             .ok_or(WithdrawalError::NotFound {
                 withdrawal_id: withdrawal_id.to_string(),
             })?;
-
         match request.status {
+            WithdrawalStatus::PendingApproval => {}
             WithdrawalStatus::Cancelled => return Err(WithdrawalError::AlreadyCancelled),
             WithdrawalStatus::Completed => return Err(WithdrawalError::AlreadyProcessed),
-            _ => {}
+            WithdrawalStatus::Rejected => return Err(WithdrawalError::Rejected),
+            WithdrawalStatus::Expired => {
+                return Err(WithdrawalError::Expired {
+                    expired_at: request.expires_at,
+                })
+            }
+            _ => return Err(WithdrawalError::NotPendingApproval),
+        }
+        if current_time >= request.expires_at {
+            let event = Self::expire(vault, request)?;
+            self.events.push(event);
+            return Err(WithdrawalError::Expired {
+                expired_at: request.expires_at,
+            });
+        }
+        if let Some(deadline) = request.approval_deadline.filter(|&d| current_time >= d) {
+            let event = Self::reject(vault, request)?;
+            self.events.push(event);
+            return Err(WithdrawalError::ApprovalExpired {
+                expired_at: deadline,
+            });
         }
 
-        // Only account owner (by checking admin) or admin can cancel
-        let is_admin = vault.access_control().is_admin(caller);
-        if !is_admin {
-            return Err(WithdrawalError::Unauthorized);
+        let access = vault.access_control();
+        let key = self
+            .approver_keys
+            .get(approver)
+            .filter(|_| access.has_role(approver, Role::Approver))
+            .ok_or_else(|| WithdrawalError::NotApprover {
+                approver: approver.to_string(),
+            })?;
+        if request.approvals.iter().any(|a| a == approver) {
+            return Err(WithdrawalError::DuplicateApproval {
+                approver: approver.to_string(),
+            });
+        }
+        if !verify_strict(key, &Self::withdrawal_hash(request), signature) {
+            return Err(WithdrawalError::InvalidSignature);
         }
+        request.approvals.push(approver.to_string());
 
-        // Refund the amount
-        vault
-            .deposit(
-                request.account_id,
-                &request.asset,
-                request.amount,
-                "refund",
-            )
-            .map_err(|e| WithdrawalError::Vault(e))?;
+        let approvers: Vec<String> = request
+            .approvals
+            .iter()
+            .filter(|a| {
+                access.has_role(a, Role::Approver) && self.approver_keys.contains_key(a.as_str())
+            })
+            .cloned()
+            .collect();
+        let mut event = ContractEvent::WithdrawalApprovalAdded(WithdrawalApprovalAdded {
+            withdrawal_id,
+            approver: approver.to_string(),
+            approvals: approvers.len() as u64,
+            required: required as u64,
+        });
+        self.events.push(event.clone());
 
-        request.status = WithdrawalStatus::Cancelled;
-        Ok(())
+        if approvers.len() >= required {
+            request.status = WithdrawalStatus::Pending;
+            event = ContractEvent::WithdrawalApproved(WithdrawalApproved {
+                withdrawal_id,
+                approvers,
+            });
+            self.events.push(event.clone());
+        }
+        Ok(event)
     }
 
-    /// Verify a withdrawal signature.
-    ///
-    /// In production, this would verify an ed25519 signature over:
-    /// `(account_id || asset || amount || nonce || destination)`.
-    ///
-    /// Current implementation accepts any non-empty signature as valid
-    /// for the contract logic layer. The actual cryptographic verification
-    /// is performed at the wallet service boundary.
-    pub fn verify_signature(
-        _account_id: AccountId,
-        _asset: &str,
-        _amount: Decimal,
-        _nonce: u64,
-        _destination: &str,
-        signature: &[u8],
-    ) -> bool {
-        // Signature must be non-empty to be considered valid
-        !signature.is_empty()
+    /// Reject every request whose approval window has closed, refunding
+    /// the locked amount. Emits one `WithdrawalRejected` per request;
+    /// returns the rejected withdrawal IDs.
+    pub fn expire_approvals(
+        &mut self,
+        vault: &mut Vault,
+        current_time: i64,
+    ) -> Result<Vec<Uuid>, WithdrawalError> {
+        let mut rejected = Vec::new();
+        for request in self.queue.iter_mut() {
+            let expired = request.status == WithdrawalStatus::PendingApproval
+                && request
+                    .approval_deadline
+                    .is_some_and(|deadline| current_time >= deadline);
+            if expired {
+                let event = Self::reject(vault, request)?;
+                self.events.push(event);
+                rejected.push(request.withdrawal_id);
+            }
+        }
+        Ok(rejected)
     }
 
-    /// Get all queued withdrawals.
-    pub fn queue(&self) -> &VecDeque<WithdrawalRequest> {
-        &self.queue
+    /// Expire every pending request (awaiting the delay or approval) whose
+    /// lifetime has ended, refunding the locked amount, in withdrawal ID
+    /// order. The request's nonce stays used. Emits one `WithdrawalExpired`
+    /// per request; returns the expired withdrawal IDs.
+    ///
+    /// Requests already selected for processing (`Ready`) are left alone.
+    pub fn sweep_expired(
+        &mut self,
+        vault: &mut Vault,
+        current_time: i64,
+    ) -> Result<Vec<Uuid>, WithdrawalError> {
+        let mut expired: Vec<usize> = (0..self.queue.len())
+            .filter(|&i| {
+                let r = &self.queue[i];
+                matches!(
+                    r.status,
+                    WithdrawalStatus::Pending | WithdrawalStatus::PendingApproval
+                ) && current_time >= r.expires_at
+            })
+            .collect();
+        expired.sort_by_key(|&i| self.queue[i].withdrawal_id);
+
+        let mut ids = Vec::with_capacity(expired.len());
+        for i in expired {
+            let request = &mut self.queue[i];
+            let event = Self::expire(vault, request)?;
+            self.events.push(event);
+            ids.push(request.withdrawal_id);
+        }
+        Ok(ids)
     }
 
-    /// Get all emitted events.
-    pub fn events(&self) -> &[ContractEvent] {
-        &self.events
+    /// Set the withdrawal limits for an asset (admin only). Applies to
+    /// requests made afterwards. Emits `WithdrawalLimitUpdated`.
+    pub fn set_withdrawal_limits(
+        &mut self,
+        vault: &Vault,
+        admin: &str,
+        asset: &str,
+        limits: WithdrawalLimits,
+    ) -> Result<ContractEvent, WithdrawalError> {
+        if !vault.access_control().is_admin(admin) {
+            return Err(WithdrawalError::LimitsUnauthorized);
+        }
+        self.limits.insert(asset.to_string(), limits);
+
+        let event = ContractEvent::WithdrawalLimitUpdated(WithdrawalLimitUpdated {
+            asset: asset.to_string(),
+            max_per_withdrawal: limits.max_per_withdrawal,
+            daily_cap: limits.daily_cap,
+            over_daily_cap_requires_approval: limits.over_daily_cap
+                == DailyCapAction::RequireApproval,
+            updated_by: admin.to_string(),
+        });
+        self.events.push(event.clone());
+        Ok(event)
     }
 
-    /// Drain all events.
-    pub fn drain_events(&mut self) -> Vec<ContractEvent> {
-        std::mem::take(&mut self.events)
+    /// Get the withdrawal limits for an asset.
+    pub fn withdrawal_limits(&self, asset: &str) -> WithdrawalLimits {
+        self.limits.get(asset).copied().unwrap_or_default()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Total an account requested in `asset` over the daily window ending
+    /// at `current_time`, excluding cancelled and rejected requests.
+    pub fn daily_usage(&self, account_id: &AccountId, asset: &str, current_time: i64) -> Decimal {
+        let window_start = current_time - DAILY_LIMIT_WINDOW_SECONDS;
+        self.queue
+            .iter()
+            .filter(|r| {
+                r.account_id == *account_id
+                    && r.asset == asset
+                    && r.requested_at > window_start
+                    && r.requested_at <= current_time
+                    && !matches!(
+                        r.status,
+                        WithdrawalStatus::Cancelled
+                            | WithdrawalStatus::Rejected
+                            | WithdrawalStatus::Expired
+                    )
+            })
+            .map(|r| r.amount)
+            .sum()
+    }
 
-    fn setup() -> (Vault, WithdrawalQueue) {
-        let mut vault = Vault::new("admin");
-        vault.add_to_whitelist("admin", "BTC").unwrap();
-        vault.add_to_whitelist("admin", "USDT").unwrap();
-        let wq = WithdrawalQueue::new(3600); // 1-hour delay for tests
-        (vault, wq)
+    /// Check a request against the asset's limits. Returns whether it is
+    /// over the daily cap but routed to approval.
+    fn check_limits(
+        &self,
+        account_id: AccountId,
+        asset: &str,
+        amount: Decimal,
+        current_time: i64,
+    ) -> Result<bool, WithdrawalError> {
+        let limits = self.withdrawal_limits(asset);
+        if let Some(limit) = limits.max_per_withdrawal {
+            if amount > limit {
+                return Err(WithdrawalError::PerWithdrawalLimitExceeded {
+                    asset: asset.to_string(),
+                    limit,
+                });
+            }
+        }
+        let Some(cap) = limits.daily_cap else {
+            return Ok(false);
+        };
+        let used = self.daily_usage(&account_id, asset, current_time);
+        let remaining = (cap - used).max(Decimal::ZERO);
+        if amount <= remaining {
+            return Ok(false);
+        }
+        match limits.over_daily_cap {
+            DailyCapAction::Reject => Err(WithdrawalError::DailyLimitExceeded {
+                asset: asset.to_string(),
+                remaining,
+            }),
+            DailyCapAction::RequireApproval => Ok(true),
+        }
     }
 
-    fn fund_account(vault: &mut Vault, account: AccountId, asset: &str, amount: Decimal) {
-        vault.deposit(account, asset, amount, "fund_tx").unwrap();
+    fn reject(
+        vault: &mut Vault,
+        request: &mut WithdrawalRequest,
+    ) -> Result<ContractEvent, WithdrawalError> {
+        vault
+            .refund(request.account_id, &request.asset, request.amount)
+            .map_err(WithdrawalError::Vault)?;
+        request.status = WithdrawalStatus::Rejected;
+        Ok(ContractEvent::WithdrawalRejected(WithdrawalRejected {
+            withdrawal_id: request.withdrawal_id,
+            account_id: request.account_id,
+            asset: request.asset.clone(),
+            amount: request.amount,
+            approval_deadline: request.approval_deadline.unwrap_or(request.requested_at),
+        }))
     }
 
-    #[test]
-    fn test_request_withdrawal_success() {
-        let (mut vault, mut wq) = setup();
-        let acc = AccountId::new();
-        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+    fn expire(
+        vault: &mut Vault,
+        request: &mut WithdrawalRequest,
+    ) -> Result<ContractEvent, WithdrawalError> {
+        vault
+            .refund(request.account_id, &request.asset, request.amount)
+            .map_err(WithdrawalError::Vault)?;
+        request.status = WithdrawalStatus::Expired;
+        Ok(ContractEvent::WithdrawalExpired(WithdrawalExpired {
+            withdrawal_id: request.withdrawal_id,
+            account_id: request.account_id,
+            asset: request.asset.clone(),
+            amount: request.amount,
+            expired_at: request.expires_at,
+        }))
+    }
 
-        let event = wq
-            .request_withdrawal(
-                &mut vault,
-                acc,
-                "BTC",
-                Decimal::from(2),
-                "bc1q...",
-                1,
-                b"valid_sig",
-                1000,
-            )
-            .unwrap();
+    /// Set the batch settlement configuration.
+    pub fn set_batch_policy(&mut self, policy: BatchPolicy) {
+        self.batch_policy = policy;
+    }
 
-        assert!(matches!(event, ContractEvent::WithdrawalRequested(_)));
-        // Balance debited
-        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(8));
-        assert_eq!(wq.queue().len(), 1);
+    /// Get the batch settlement configuration.
+    pub fn batch_policy(&self) -> &BatchPolicy {
+        &self.batch_policy
     }
 
-    #[test]
-    fn test_request_withdrawal_invalid_signature() {
-        let (mut vault, mut wq) = setup();
-        let acc = AccountId::new();
-        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+    /// Settle up to `max_items` eligible withdrawals as one batch.
+    ///
+    /// Eligible requests (pending, delay elapsed, not expired) are taken in
+    /// FIFO queue order. The amounts were locked at request time; settlement
+    /// debits the vault the [`BatchPolicy::fee`] per item. An item the
+    /// account cannot pay the fee for is handled per
+    /// [`BatchPolicy::on_failure`]: skipped with a `WithdrawalSkipped`
+    /// event, or the whole batch fails with
+    /// [`WithdrawalError::BatchRolledBack`] and nothing changes. Every item
+    /// is checked before anything is debited, so either way no partial
+    /// batch is applied.
+    ///
+    /// The Merkle root over the settled items (see
+    /// [`withdrawal_leaf`](Self::withdrawal_leaf)) is recorded in
+    /// `commitments`, and a `WithdrawalBatchProcessed` event is emitted. A
+    /// batch in which every item was skipped settles and commits nothing.
+    pub fn process_batch(
+        &mut self,
+        vault: &mut Vault,
+        commitments: &mut CommitmentStore,
+        max_items: usize,
+        current_time: i64,
+    ) -> Result<WithdrawalBatch, WithdrawalError> {
+        let candidates: Vec<usize> = (0..self.queue.len())
+            .filter(|&i| self.queue[i].is_eligible(current_time))
+            .take(max_items)
+            .collect();
+        if candidates.is_empty() {
+            return Err(WithdrawalError::EmptyBatch);
+        }
 
-        let result = wq.request_withdrawal(
-            &mut vault,
-            acc,
-            "BTC",
-            Decimal::from(1),
-            "bc1q...",
-            1,
-            b"", // empty = invalid
-            1000,
-        );
-        assert_eq!(result, Err(WithdrawalError::InvalidSignature));
-    }
+        let fee = self.batch_policy.fee;
+        let mut owed: HashMap<(AccountId, String), Decimal> = HashMap::new();
+        let mut settled = Vec::new();
+        let mut skipped = Vec::new();
+        for i in candidates {
+            let request = &self.queue[i];
+            let key = (request.account_id, request.asset.clone());
+            let due = owed.get(&key).copied().unwrap_or(Decimal::ZERO);
+            let available = vault.get_balance(&request.account_id, &request.asset);
+            let total = due.checked_add(fee);
+            match total {
+                Some(total) if total <= available => {
+                    owed.insert(key, total);
+                    settled.push(i);
+                }
+                _ => {
+                    let reason = VaultError::InsufficientBalance {
+                        asset: request.asset.clone(),
+                        required: total.map_or_else(|| "overflow".to_string(), |t| t.to_string()),
+                        available: available.to_string(),
+                    }
+                    .to_string();
+                    match self.batch_policy.on_failure {
+                        BatchFailurePolicy::SkipItem => {
+                            skipped.push((request.withdrawal_id, reason))
+                        }
+                        BatchFailurePolicy::RollbackBatch => {
+                            return Err(WithdrawalError::BatchRolledBack {
+                                withdrawal_id: request.withdrawal_id.to_string(),
+                                reason,
+                            })
+                        }
+                    }
+                }
+            }
+        }
 
-    #[test]
-    fn test_request_withdrawal_nonce_reuse() {
-        let (mut vault, mut wq) = setup();
-        let acc = AccountId::new();
-        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        let batch_id = self.next_batch_id;
+        self.next_batch_id += 1;
+        for (withdrawal_id, reason) in &skipped {
+            self.events
+                .push(ContractEvent::WithdrawalSkipped(WithdrawalSkipped {
+                    batch_id,
+                    withdrawal_id: *withdrawal_id,
+                    reason: reason.clone(),
+                }));
+        }
 
-        wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, b"sig", 1000,
-        )
-        .unwrap();
+        let mut items = Vec::with_capacity(settled.len());
+        for i in settled {
+            let request = &mut self.queue[i];
+            if fee > Decimal::ZERO {
+                vault.safe_debit(&request.account_id, &request.asset, fee)?;
+            }
+            request.status = WithdrawalStatus::Completed;
+            self.events
+                .push(ContractEvent::WithdrawalCompleted(WithdrawalCompleted {
+                    withdrawal_id: request.withdrawal_id,
+                    tx_id: format!("batch_{}", batch_id),
+                    fee,
+                    batch_id: Some(batch_id),
+                }));
+            items.push(request.clone());
+        }
 
-        let result = wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, b"sig", 1001,
+        let leaves: Vec<[u8; 32]> = items.iter().map(Self::withdrawal_leaf).collect();
+        let root = merkle_root(&leaves);
+        if !items.is_empty() {
+            commitments.record_withdrawal_batch(WithdrawalBatchCommitment {
+                batch_id,
+                root,
+                item_count: items.len() as u64,
+                committed_at: current_time,
+            });
+            self.events.push(ContractEvent::WithdrawalBatchProcessed(
+                WithdrawalBatchProcessed {
+                    batch_id,
+                    root,
+                    item_count: items.len() as u64,
+                    processed_at: current_time,
+                },
+            ));
+        }
+
+        Ok(WithdrawalBatch {
+            batch_id,
+            root,
+            items,
+            skipped: skipped.into_iter().map(|(id, _)| id).collect(),
+        })
+    }
+
+    /// Merkle leaf of a withdrawal: the hash of
+    /// `account_id|asset|amount|destination|nonce`, with `amount` normalized.
+    pub fn withdrawal_leaf(request: &WithdrawalRequest) -> [u8; 32] {
+        let data = format!(
+            "{}|{}|{}|{}|{}",
+            request.account_id,
+            request.asset,
+            request.amount.normalize(),
+            request.destination,
+            request.nonce
         );
-        assert!(matches!(result, Err(WithdrawalError::NonceReused { .. })));
+        merkle_leaf(data.as_bytes())
     }
 
-    #[test]
-    fn test_request_withdrawal_insufficient_balance() {
-        let (mut vault, mut wq) = setup();
+    /// Verify that `withdrawal` was settled in the batch with `batch_root`.
+    pub fn verify_inclusion(
+        batch_root: &[u8; 32],
+        withdrawal: &WithdrawalRequest,
+        proof: &MerkleProof,
+    ) -> bool {
+        verify_merkle_proof(batch_root, &Self::withdrawal_leaf(withdrawal), proof)
+    }
+
+    /// Set the processing scheduler caps.
+    ///
+    /// Takes effect from the next call to
+    /// [`process_next_batch`](Self::process_next_batch); usage already counted
+    /// in the current window is kept.
+    pub fn set_processing_schedule(&mut self, schedule: ProcessingSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// Get the processing scheduler caps.
+    pub fn processing_schedule(&self) -> &ProcessingSchedule {
+        &self.schedule
+    }
+
+    /// Move a pending withdrawal to the front of the processing order
+    /// (operator or admin only).
+    ///
+    /// Expedited requests are processed before all others, in the order they
+    /// were expedited. Expediting an already expedited request is a no-op.
+    pub fn expedite(
+        &mut self,
+        vault: &Vault,
+        withdrawal_id: Uuid,
+        caller: &str,
+    ) -> Result<(), WithdrawalError> {
+        let access = vault.access_control();
+        if !access.is_admin(caller) && !access.has_role(caller, Role::Operator) {
+            return Err(WithdrawalError::ExpediteUnauthorized);
+        }
+
+        let request = self
+            .queue
+            .iter_mut()
+            .find(|r| r.withdrawal_id == withdrawal_id)
+            .ok_or(WithdrawalError::NotFound {
+                withdrawal_id: withdrawal_id.to_string(),
+            })?;
+
+        match request.status {
+            WithdrawalStatus::Cancelled => return Err(WithdrawalError::AlreadyCancelled),
+            WithdrawalStatus::Completed => return Err(WithdrawalError::AlreadyProcessed),
+            WithdrawalStatus::Rejected => return Err(WithdrawalError::Rejected),
+            WithdrawalStatus::Expired => {
+                return Err(WithdrawalError::Expired {
+                    expired_at: request.expires_at,
+                })
+            }
+            _ => {}
+        }
+
+        if request.expedited.is_none() {
+            request.expedited = Some(self.next_expedite);
+            self.next_expedite += 1;
+        }
+        Ok(())
+    }
+
+    /// Select the next batch of withdrawals under the processing caps.
+    ///
+    /// Eligible requests (pending, delay elapsed, not expired) are taken in
+    /// priority order: expedited first, then oldest `requested_at`, then
+    /// queue order. Each selected request is marked `Ready` and counted
+    /// against its asset's cap for the window containing `current_time`;
+    /// the caller completes it with
+    /// [`process_withdrawal`](Self::process_withdrawal).
+    ///
+    /// Once a request is deferred, later requests for the same asset are
+    /// deferred too so priority is never inverted. Requests larger than the
+    /// asset's notional cap can never be selected and do not block others.
+    /// One `WithdrawalThrottled` event is emitted per asset with deferrals.
+    pub fn process_next_batch(&mut self, current_time: i64) -> Vec<WithdrawalRequest> {
+        let window_start = self.schedule.window_start(current_time);
+        if window_start != self.window.window_start {
+            self.window = WindowUsage {
+                window_start,
+                per_asset: HashMap::new(),
+            };
+        }
+
+        let mut order: Vec<usize> = (0..self.queue.len())
+            .filter(|&i| self.queue[i].is_eligible(current_time))
+            .collect();
+        order.sort_by_key(|&i| {
+            let r = &self.queue[i];
+            (r.expedited.is_none(), r.expedited, r.requested_at, i)
+        });
+
+        let mut selected = Vec::new();
+        let mut deferred: BTreeMap<String, (Vec<Uuid>, Decimal)> = BTreeMap::new();
+        let mut blocked: HashSet<String> = HashSet::new();
+        for i in order {
+            let request = &mut self.queue[i];
+            let cap = self.schedule.cap_for(&request.asset);
+            let (count, notional) = self
+                .window
+                .per_asset
+                .entry(request.asset.clone())
+                .or_insert((0, Decimal::ZERO));
+
+            let fits = *count < cap.max_withdrawals
+                && notional
+                    .checked_add(request.amount)
+                    .is_some_and(|total| total <= cap.max_notional);
+
+            if fits && !blocked.contains(&request.asset) {
+                *count += 1;
+                *notional += request.amount;
+                request.status = WithdrawalStatus::Ready;
+                selected.push(request.clone());
+            } else {
+                if request.amount <= cap.max_notional {
+                    blocked.insert(request.asset.clone());
+                }
+                let entry = deferred
+                    .entry(request.asset.clone())
+                    .or_insert((Vec::new(), Decimal::ZERO));
+                entry.0.push(request.withdrawal_id);
+                entry.1 += request.amount;
+            }
+        }
+
+        for (asset, (ids, deferred_notional)) in deferred {
+            self.events
+                .push(ContractEvent::WithdrawalThrottled(WithdrawalThrottled {
+                    asset,
+                    window_start,
+                    deferred: ids,
+                    deferred_notional,
+                }));
+        }
+
+        selected
+    }
+
+    /// Emergency cancel a withdrawal by owner or admin.
+    ///
+    /// Refunds the locked amount back to the vault. Emits
+    /// `WithdrawalCancelled`.
+    pub fn cancel_withdrawal(
+        &mut self,
+        vault: &mut Vault,
+        withdrawal_id: Uuid,
+        caller: &str,
+    ) -> Result<(), WithdrawalError> {
+        let request = self
+            .queue
+            .iter_mut()
+            .find(|r| r.withdrawal_id == withdrawal_id)
+            .ok_or(WithdrawalError::NotFound {
+                withdrawal_id: withdrawal_id.to_string(),
+            })?;
+
+        match request.status {
+            WithdrawalStatus::Cancelled => return Err(WithdrawalError::AlreadyCancelled),
+            WithdrawalStatus::Completed => return Err(WithdrawalError::AlreadyProcessed),
+            WithdrawalStatus::Rejected => return Err(WithdrawalError::Rejected),
+            WithdrawalStatus::Expired => {
+                return Err(WithdrawalError::Expired {
+                    expired_at: request.expires_at,
+                })
+            }
+            _ => {}
+        }
+
+        // Only account owner (by checking admin) or admin can cancel
+        let is_admin = vault.access_control().is_admin(caller);
+        if !is_admin {
+            return Err(WithdrawalError::Unauthorized);
+        }
+
+        // Refund the amount
+        vault
+            .refund(request.account_id, &request.asset, request.amount)
+            .map_err(WithdrawalError::Vault)?;
+
+        request.status = WithdrawalStatus::Cancelled;
+        self.events
+            .push(ContractEvent::WithdrawalCancelled(WithdrawalCancelled {
+                withdrawal_id,
+                account_id: request.account_id,
+                asset: request.asset.clone(),
+                amount: request.amount,
+                cancelled_by: caller.to_string(),
+            }));
+        Ok(())
+    }
+
+    /// Canonical bytes signed by the account owner for a withdrawal:
+    /// `account_id|asset|amount|nonce|destination`, with `amount` normalized.
+    pub fn withdrawal_signing_payload(
+        account_id: AccountId,
+        asset: &str,
+        amount: Decimal,
+        nonce: u64,
+        destination: &str,
+    ) -> Vec<u8> {
+        format!(
+            "{}|{}|{}|{}|{}",
+            account_id,
+            asset,
+            amount.normalize(),
+            nonce,
+            destination
+        )
+        .into_bytes()
+    }
+
+    /// Verify an Ed25519 withdrawal signature against a signer key.
+    ///
+    /// The signature covers the SHA-256 hash of
+    /// [`withdrawal_signing_payload`](Self::withdrawal_signing_payload).
+    /// Uses strict verification: non-canonical signatures and small-order
+    /// (weak) keys are rejected. Never panics on malformed input.
+    pub fn verify_signer_signature(
+        public_key: &[u8],
+        account_id: AccountId,
+        asset: &str,
+        amount: Decimal,
+        nonce: u64,
+        destination: &str,
+        signature: &[u8],
+    ) -> bool {
+        let payload =
+            Self::withdrawal_signing_payload(account_id, asset, amount, nonce, destination);
+        let hash: [u8; 32] = Sha256::digest(payload).into();
+        verify_strict(public_key, &hash, signature)
+    }
+
+    /// Hash an approver signs: SHA-256 of
+    /// `withdrawal_id|` followed by the
+    /// [`withdrawal_signing_payload`](Self::withdrawal_signing_payload), so
+    /// an approval is bound to one request.
+    pub fn withdrawal_hash(request: &WithdrawalRequest) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}|", request.withdrawal_id));
+        hasher.update(Self::withdrawal_signing_payload(
+            request.account_id,
+            &request.asset,
+            request.amount,
+            request.nonce,
+            &request.destination,
+        ));
+        hasher.finalize().into()
+    }
+
+    /// Get all queued withdrawals.
+    pub fn queue(&self) -> &VecDeque<WithdrawalRequest> {
+        &self.queue
+    }
+
+    /// Get all emitted events.
+    pub fn events(&self) -> &[ContractEvent] {
+        &self.events
+    }
+
+    /// Drain all events.
+    pub fn drain_events(&mut self) -> Vec<ContractEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Strict Ed25519 verification of `signature` over `message`. Never panics
+/// on malformed input.
+pub(crate) fn verify_strict(public_key: &[u8], message: &[u8; 32], signature: &[u8]) -> bool {
+    let Ok(key_bytes) = <[u8; 32]>::try_from(public_key) else {
+        return false;
+    };
+    let Ok(sig_bytes) = <[u8; 64]>::try_from(signature) else {
+        return false;
+    };
+    let Ok(verifying_key) = VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    let signature = Signature::from_bytes(&sig_bytes);
+    verifying_key.verify_strict(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (Vault, WithdrawalQueue) {
+        let mut vault = Vault::new("admin");
+        vault.add_to_whitelist("admin", "BTC").unwrap();
+        vault.add_to_whitelist("admin", "USDT").unwrap();
+        let wq = WithdrawalQueue::new(3600); // 1-hour delay for tests
+        (vault, wq)
+    }
+
+    fn fund_account(vault: &mut Vault, account: AccountId, asset: &str, amount: Decimal) {
+        vault.deposit(account, asset, amount, "fund_tx").unwrap();
+    }
+
+    #[test]
+    fn test_request_withdrawal_success() {
+        let (mut vault, mut wq) = setup();
         let acc = AccountId::new();
-        fund_account(&mut vault, acc, "BTC", Decimal::from(1));
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        let event = request(&mut wq, &mut vault, acc, "BTC", Decimal::from(2), "bc1q...", 1, 1000)
+            .unwrap();
+
+        assert!(matches!(event, ContractEvent::WithdrawalRequested(_)));
+        // Balance debited
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(8));
+        assert_eq!(wq.queue().len(), 1);
+    }
+
+    #[test]
+    fn test_request_withdrawal_invalid_signature() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        wq.register_signer(acc, signer_key().verifying_key().to_bytes());
 
         let result = wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(5), "bc1q...", 1, b"sig", 1000,
+            &mut vault,
+            acc,
+            "BTC",
+            Decimal::from(1),
+            "bc1q...",
+            1,
+            b"", // empty = invalid
+            1000,
         );
+        assert_eq!(result, Err(WithdrawalError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_request_withdrawal_nonce_reuse() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, 1000)
+        .unwrap();
+
+        let result = request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, 1001);
+        assert!(matches!(result, Err(WithdrawalError::NonceReused { .. })));
+    }
+
+    #[test]
+    fn test_request_withdrawal_insufficient_balance() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(1));
+
+        let result = request(&mut wq, &mut vault, acc, "BTC", Decimal::from(5), "bc1q...", 1, 1000);
         assert_eq!(result, Err(WithdrawalError::InsufficientBalance));
     }
 
@@ -393,9 +1379,7 @@ mod tests {
         let acc = AccountId::new();
         fund_account(&mut vault, acc, "BTC", Decimal::from(10));
 
-        wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, b"sig", 1000,
-        )
+        request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, 1000)
         .unwrap();
 
         let wid = wq.queue()[0].withdrawal_id;
@@ -412,9 +1396,7 @@ mod tests {
         let acc = AccountId::new();
         fund_account(&mut vault, acc, "BTC", Decimal::from(10));
 
-        wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, b"sig", 1000,
-        )
+        request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, 1000)
         .unwrap();
 
         let wid = wq.queue()[0].withdrawal_id;
@@ -432,16 +1414,7 @@ mod tests {
 
         // Queue 3 withdrawals
         for i in 1..=3u64 {
-            wq.request_withdrawal(
-                &mut vault,
-                acc,
-                "BTC",
-                Decimal::from(1),
-                "bc1q...",
-                i,
-                b"sig",
-                1000,
-            )
+            request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", i, 1000)
             .unwrap();
         }
 
@@ -465,9 +1438,7 @@ mod tests {
         let acc = AccountId::new();
         fund_account(&mut vault, acc, "BTC", Decimal::from(10));
 
-        wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(3), "bc1q...", 1, b"sig", 1000,
-        )
+        request(&mut wq, &mut vault, acc, "BTC", Decimal::from(3), "bc1q...", 1, 1000)
         .unwrap();
         assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(7));
 
@@ -485,9 +1456,7 @@ mod tests {
         let acc = AccountId::new();
         fund_account(&mut vault, acc, "BTC", Decimal::from(10));
 
-        wq.request_withdrawal(
-            &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, b"sig", 1000,
-        )
+        request(&mut wq, &mut vault, acc, "BTC", Decimal::from(1), "bc1q...", 1, 1000)
         .unwrap();
 
         let wid = wq.queue()[0].withdrawal_id;
@@ -495,22 +1464,789 @@ mod tests {
         assert_eq!(result, Err(WithdrawalError::Unauthorized));
     }
 
+    fn signer_key() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn sign_withdrawal(
+        key: &ed25519_dalek::SigningKey,
+        acc: AccountId,
+        amount: Decimal,
+        nonce: u64,
+    ) -> Vec<u8> {
+        sign(key, acc, "BTC", amount, nonce, "bc1q...")
+    }
+
+    fn sign(
+        key: &ed25519_dalek::SigningKey,
+        acc: AccountId,
+        asset: &str,
+        amount: Decimal,
+        nonce: u64,
+        destination: &str,
+    ) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+        let payload =
+            WithdrawalQueue::withdrawal_signing_payload(acc, asset, amount, nonce, destination);
+        let hash: [u8; 32] = Sha256::digest(payload).into();
+        key.sign(&hash).to_bytes().to_vec()
+    }
+
+    /// Request a withdrawal signed by the account's registered signer.
+    #[allow(clippy::too_many_arguments)]
+    fn request(
+        wq: &mut WithdrawalQueue,
+        vault: &mut Vault,
+        acc: AccountId,
+        asset: &str,
+        amount: Decimal,
+        destination: &str,
+        nonce: u64,
+        time: i64,
+    ) -> Result<ContractEvent, WithdrawalError> {
+        let key = signer_key();
+        wq.register_signer(acc, key.verifying_key().to_bytes());
+        let sig = sign(&key, acc, asset, amount, nonce, destination);
+        wq.request_withdrawal(vault, acc, asset, amount, destination, nonce, &sig, time)
+    }
+
     #[test]
-    fn test_invalid_withdrawal_amount() {
+    fn test_registered_signer_valid_signature() {
         let (mut vault, mut wq) = setup();
         let acc = AccountId::new();
         fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        let key = signer_key();
+        wq.register_signer(acc, key.verifying_key().to_bytes());
 
+        let sig = sign_withdrawal(&key, acc, Decimal::from(2), 1);
         let result = wq.request_withdrawal(
-            &mut vault,
+            &mut vault, acc, "BTC", Decimal::from(2), "bc1q...", 1, &sig, 1000,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_registered_signer_rejects_arbitrary_bytes() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        wq.register_signer(acc, signer_key().verifying_key().to_bytes());
+
+        let result = wq.request_withdrawal(
+            &mut vault, acc, "BTC", Decimal::from(2), "bc1q...", 1, b"sig", 1000,
+        );
+        assert_eq!(result, Err(WithdrawalError::InvalidSignature));
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+    }
+
+    #[test]
+    fn test_account_without_signer_cannot_withdraw() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        let sig = sign_withdrawal(&signer_key(), acc, Decimal::from(2), 1);
+        for signature in [&b"sig"[..], &[0u8; 64], &sig] {
+            let result = wq.request_withdrawal(
+                &mut vault, acc, "BTC", Decimal::from(2), "bc1q...", 1, signature, 1000,
+            );
+            assert_eq!(
+                result,
+                Err(WithdrawalError::SignerNotRegistered { account_id: acc.to_string() })
+            );
+        }
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+    }
+
+    #[test]
+    fn test_registered_signer_rejects_signature_for_other_amount() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        let key = signer_key();
+        wq.register_signer(acc, key.verifying_key().to_bytes());
+
+        let sig = sign_withdrawal(&key, acc, Decimal::from(1), 1);
+        let result = wq.request_withdrawal(
+            &mut vault, acc, "BTC", Decimal::from(9), "bc1q...", 1, &sig, 1000,
+        );
+        assert_eq!(result, Err(WithdrawalError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_verify_signer_signature_malformed_lengths() {
+        let acc = AccountId::new();
+        assert!(!WithdrawalQueue::verify_signer_signature(
+            &[1u8; 31], acc, "BTC", Decimal::ONE, 1, "bc1q...", &[0u8; 64],
+        ));
+        assert!(!WithdrawalQueue::verify_signer_signature(
+            &signer_key().verifying_key().to_bytes(),
             acc,
             "BTC",
-            Decimal::ZERO,
-            "bc1q...",
+            Decimal::ONE,
             1,
-            b"sig",
-            1000,
-        );
+            "bc1q...",
+            &[0u8; 63],
+        ));
+    }
+
+    #[test]
+    fn test_invalid_withdrawal_amount() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        let result = request(&mut wq, &mut vault, acc, "BTC", Decimal::ZERO, "bc1q...", 1, 1000);
         assert_eq!(result, Err(WithdrawalError::InvalidAmount));
     }
+
+    fn capped_queue(cap: WindowCap) -> (Vault, WithdrawalQueue) {
+        let (vault, mut wq) = setup();
+        let mut schedule = ProcessingSchedule::default();
+        schedule.asset_caps.insert("BTC".to_string(), cap);
+        wq.set_processing_schedule(schedule);
+        (vault, wq)
+    }
+
+    fn request_at(
+        vault: &mut Vault,
+        wq: &mut WithdrawalQueue,
+        acc: AccountId,
+        amount: Decimal,
+        nonce: u64,
+        time: i64,
+    ) -> Uuid {
+        match request(wq, vault, acc, "BTC", amount, "bc1q...", nonce, time)
+            .unwrap()
+        {
+            ContractEvent::WithdrawalRequested(e) => e.withdrawal_id,
+            _ => panic!("Expected WithdrawalRequested"),
+        }
+    }
+
+    fn throttled(events: &[ContractEvent]) -> Vec<&WithdrawalThrottled> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                ContractEvent::WithdrawalThrottled(t) => Some(t),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Queue one 1 BTC withdrawal each for `accounts`, funded with 2 BTC
+    fn batch_setup(accounts: &[AccountId]) -> (Vault, WithdrawalQueue, CommitmentStore) {
+        let (mut vault, mut wq) = setup();
+        for (nonce, &acc) in accounts.iter().enumerate() {
+            fund_account(&mut vault, acc, "BTC", Decimal::from(2));
+            request_at(&mut vault, &mut wq, acc, Decimal::ONE, nonce as u64, 1000);
+        }
+        (vault, wq, CommitmentStore::with_default_window("admin"))
+    }
+
+    #[test]
+    fn test_process_batch_commits_root_of_fifo_items() {
+        let accounts: Vec<AccountId> = (0..5).map(|_| AccountId::new()).collect();
+        let (mut vault, mut wq, mut store) = batch_setup(&accounts);
+
+        let batch = wq.process_batch(&mut vault, &mut store, 3, 5000).unwrap();
+        assert_eq!(batch.batch_id, 1);
+        let ids: Vec<Uuid> = batch.items.iter().map(|r| r.withdrawal_id).collect();
+        let fifo: Vec<Uuid> = wq.queue().iter().take(3).map(|r| r.withdrawal_id).collect();
+        assert_eq!(ids, fifo);
+        assert!(wq.queue().iter().take(3).all(|r| r.status == WithdrawalStatus::Completed));
+        assert_eq!(wq.queue()[3].status, WithdrawalStatus::Pending);
+
+        let committed = store.withdrawal_batch(1).unwrap();
+        assert_eq!((committed.root, committed.item_count), (batch.root, 3));
+        assert!(wq.events().iter().any(|e| matches!(
+            e,
+            ContractEvent::WithdrawalBatchProcessed(p) if p.root == batch.root && p.item_count == 3
+        )));
+
+        for item in &batch.items {
+            let proof = batch.proof(item.withdrawal_id).unwrap();
+            assert!(WithdrawalQueue::verify_inclusion(&batch.root, item, &proof));
+            let mut forged = item.clone();
+            forged.amount += Decimal::ONE;
+            assert!(!WithdrawalQueue::verify_inclusion(&batch.root, &forged, &proof));
+        }
+
+        // The rest goes out in the next batch
+        let next = wq.process_batch(&mut vault, &mut store, 10, 5000).unwrap();
+        assert_eq!((next.batch_id, next.items.len()), (2, 2));
+        assert_eq!(
+            wq.process_batch(&mut vault, &mut store, 10, 5000).unwrap_err(),
+            WithdrawalError::EmptyBatch
+        );
+    }
+
+    #[test]
+    fn test_process_batch_skips_failing_item() {
+        let accounts: Vec<AccountId> = (0..3).map(|_| AccountId::new()).collect();
+        let (mut vault, mut wq, mut store) = batch_setup(&accounts);
+        // The second account cannot pay the fee
+        vault.safe_debit(&accounts[1], "BTC", Decimal::ONE).unwrap();
+        wq.set_batch_policy(BatchPolicy {
+            fee: Decimal::new(1, 1),
+            on_failure: BatchFailurePolicy::SkipItem,
+        });
+
+        let batch = wq.process_batch(&mut vault, &mut store, 10, 5000).unwrap();
+        let skipped_id = wq.queue()[1].withdrawal_id;
+        assert_eq!(batch.items.len(), 2);
+        assert_eq!(batch.skipped, vec![skipped_id]);
+        assert_eq!(wq.queue()[1].status, WithdrawalStatus::Pending);
+        assert_eq!(vault.get_balance(&accounts[0], "BTC"), Decimal::new(9, 1));
+        assert_eq!(vault.get_balance(&accounts[1], "BTC"), Decimal::ZERO);
+        assert!(wq.events().iter().any(|e| matches!(
+            e,
+            ContractEvent::WithdrawalSkipped(s) if s.withdrawal_id == skipped_id && s.batch_id == 1
+        )));
+        assert_eq!(store.withdrawal_batch(1).unwrap().item_count, 2);
+    }
+
+    #[test]
+    fn test_process_batch_rollback_changes_nothing() {
+        let accounts: Vec<AccountId> = (0..3).map(|_| AccountId::new()).collect();
+        let (mut vault, mut wq, mut store) = batch_setup(&accounts);
+        vault.safe_debit(&accounts[2], "BTC", Decimal::ONE).unwrap();
+        wq.set_batch_policy(BatchPolicy {
+            fee: Decimal::new(1, 1),
+            on_failure: BatchFailurePolicy::RollbackBatch,
+        });
+        let events_before = wq.events().len();
+
+        let err = wq.process_batch(&mut vault, &mut store, 10, 5000).unwrap_err();
+        assert_eq!(
+            err,
+            WithdrawalError::BatchRolledBack {
+                withdrawal_id: wq.queue()[2].withdrawal_id.to_string(),
+                reason: VaultError::InsufficientBalance {
+                    asset: "BTC".to_string(),
+                    required: "0.1".to_string(),
+                    available: "0".to_string(),
+                }
+                .to_string(),
+            }
+        );
+        assert!(wq.queue().iter().all(|r| r.status == WithdrawalStatus::Pending));
+        assert_eq!(vault.get_balance(&accounts[0], "BTC"), Decimal::ONE);
+        assert!(store.withdrawal_batches().is_empty());
+        assert_eq!(wq.events().len(), events_before);
+
+        // Once the balance is there, the same batch settles as batch 1
+        fund_account(&mut vault, accounts[2], "BTC", Decimal::ONE);
+        let batch = wq.process_batch(&mut vault, &mut store, 10, 5000).unwrap();
+        assert_eq!((batch.batch_id, batch.items.len()), (1, 3));
+    }
+
+    #[test]
+    fn test_process_next_batch_caps_across_windows() {
+        let (mut vault, mut wq) = capped_queue(WindowCap {
+            max_withdrawals: 2,
+            max_notional: Decimal::from(100),
+        });
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(50));
+
+        let ids: Vec<Uuid> = (0..5)
+            .map(|i| request_at(&mut vault, &mut wq, acc, Decimal::from(10), i + 1, 1000 + i as i64))
+            .collect();
+
+        // Window [7200, 10800): oldest two selected, rest deferred
+        let batch = wq.process_next_batch(7200);
+        assert_eq!(
+            batch.iter().map(|r| r.withdrawal_id).collect::<Vec<_>>(),
+            ids[0..2]
+        );
+        assert!(batch.iter().all(|r| r.status == WithdrawalStatus::Ready));
+        let events = wq.drain_events();
+        let throttle = throttled(&events);
+        assert_eq!(throttle.len(), 1);
+        assert_eq!(throttle[0].window_start, 7200);
+        assert_eq!(throttle[0].deferred, ids[2..5]);
+        assert_eq!(throttle[0].deferred_notional, Decimal::from(30));
+
+        // Same window: cap already spent
+        assert!(wq.process_next_batch(9000).is_empty());
+        assert_eq!(throttled(&wq.drain_events()).len(), 1);
+
+        // Next window: next two oldest
+        let batch = wq.process_next_batch(10800);
+        assert_eq!(
+            batch.iter().map(|r| r.withdrawal_id).collect::<Vec<_>>(),
+            ids[2..4]
+        );
+        wq.drain_events();
+
+        // Final window: remaining item, nothing deferred
+        let batch = wq.process_next_batch(14400);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].withdrawal_id, ids[4]);
+        assert!(throttled(wq.events()).is_empty());
+    }
+
+    #[test]
+    fn test_process_next_batch_ignores_undelayed_requests() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        request_at(&mut vault, &mut wq, acc, Decimal::ONE, 1, 1000);
+
+        assert!(wq.process_next_batch(2000).is_empty());
+        assert!(throttled(wq.events()).is_empty());
+        assert_eq!(wq.process_next_batch(4600).len(), 1);
+    }
+
+    #[test]
+    fn test_expedited_jumps_queue_within_notional_cap() {
+        let (mut vault, mut wq) = capped_queue(WindowCap {
+            max_withdrawals: usize::MAX,
+            max_notional: Decimal::from(10),
+        });
+        vault.grant_operator("admin", "operator1");
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(20));
+
+        let a = request_at(&mut vault, &mut wq, acc, Decimal::from(4), 1, 1000);
+        let b = request_at(&mut vault, &mut wq, acc, Decimal::from(4), 2, 1001);
+        let c = request_at(&mut vault, &mut wq, acc, Decimal::from(5), 3, 1002);
+        wq.expedite(&vault, c, "operator1").unwrap();
+
+        let batch = wq.process_next_batch(7200);
+        assert_eq!(
+            batch.iter().map(|r| r.withdrawal_id).collect::<Vec<_>>(),
+            vec![c, a]
+        );
+        let total: Decimal = batch.iter().map(|r| r.amount).sum();
+        assert!(total <= Decimal::from(10));
+        assert_eq!(throttled(wq.events())[0].deferred, vec![b]);
+    }
+
+    #[test]
+    fn test_expedite_order_is_deterministic() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        let a = request_at(&mut vault, &mut wq, acc, Decimal::ONE, 1, 1000);
+        let b = request_at(&mut vault, &mut wq, acc, Decimal::ONE, 2, 1000);
+        let c = request_at(&mut vault, &mut wq, acc, Decimal::ONE, 3, 1000);
+        wq.expedite(&vault, c, "admin").unwrap();
+        wq.expedite(&vault, b, "admin").unwrap();
+        wq.expedite(&vault, c, "admin").unwrap(); // no-op
+
+        let ids: Vec<Uuid> = wq
+            .process_next_batch(7200)
+            .iter()
+            .map(|r| r.withdrawal_id)
+            .collect();
+        assert_eq!(ids, vec![c, b, a]);
+    }
+
+    #[test]
+    fn test_expedite_requires_operator() {
+        let (mut vault, mut wq) = setup();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+        let id = request_at(&mut vault, &mut wq, acc, Decimal::ONE, 1, 1000);
+
+        assert_eq!(
+            wq.expedite(&vault, id, "user1"),
+            Err(WithdrawalError::ExpediteUnauthorized)
+        );
+        wq.cancel_withdrawal(&mut vault, id, "admin").unwrap();
+        assert_eq!(
+            wq.expedite(&vault, id, "admin"),
+            Err(WithdrawalError::AlreadyCancelled)
+        );
+    }
+
+    #[test]
+    fn test_oversized_request_does_not_block_asset() {
+        let (mut vault, mut wq) = capped_queue(WindowCap {
+            max_withdrawals: usize::MAX,
+            max_notional: Decimal::from(5),
+        });
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(10));
+
+        let big = request_at(&mut vault, &mut wq, acc, Decimal::from(6), 1, 1000);
+        let small = request_at(&mut vault, &mut wq, acc, Decimal::from(2), 2, 1001);
+
+        let batch = wq.process_next_batch(7200);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].withdrawal_id, small);
+        assert_eq!(throttled(wq.events())[0].deferred, vec![big]);
+    }
+
+    fn approver_key(seed: u8) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn approval(wq: &WithdrawalQueue, id: Uuid, key: &ed25519_dalek::SigningKey) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+        let request = wq.queue().iter().find(|r| r.withdrawal_id == id).unwrap();
+        key.sign(&WithdrawalQueue::withdrawal_hash(request))
+            .to_bytes()
+            .to_vec()
+    }
+
+    fn status(wq: &WithdrawalQueue, id: Uuid) -> WithdrawalStatus {
+        wq.queue().iter().find(|r| r.withdrawal_id == id).unwrap().status
+    }
+
+    /// 2-of-3 approvers a1..a3 above 5 BTC, 2h window; one 10 BTC request
+    fn approval_setup() -> (Vault, WithdrawalQueue, AccountId, Uuid) {
+        let (mut vault, mut wq) = setup();
+        let mut policy = ApprovalPolicy {
+            required_approvals: 2,
+            expiry_seconds: 7200,
+            ..ApprovalPolicy::default()
+        };
+        policy.thresholds.insert("BTC".to_string(), Decimal::from(5));
+        wq.set_approval_policy(policy);
+        for seed in 1..=3u8 {
+            let key = approver_key(seed).verifying_key().to_bytes();
+            wq.register_approver(&mut vault, "admin", &format!("a{seed}"), key)
+                .unwrap();
+        }
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(30));
+        let id = request_at(&mut vault, &mut wq, acc, Decimal::from(10), 1, 1000);
+        (vault, wq, acc, id)
+    }
+
+    #[test]
+    fn test_exactly_m_approvals_release_withdrawal() {
+        let (mut vault, mut wq, acc, id) = approval_setup();
+        let small = request_at(&mut vault, &mut wq, acc, Decimal::from(5), 2, 1000);
+        assert_eq!(status(&wq, small), WithdrawalStatus::Pending);
+        assert_eq!(status(&wq, id), WithdrawalStatus::PendingApproval);
+        assert_eq!(
+            wq.process_withdrawal(id, 5000, "tx", Decimal::ZERO),
+            Err(WithdrawalError::AwaitingApproval {
+                approvals: 0,
+                required: 2
+            })
+        );
+
+        let sig1 = approval(&wq, id, &approver_key(1));
+        let event = wq.approve_withdrawal(&mut vault, id, "a1", &sig1, 2000).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::WithdrawalApprovalAdded(ref a) if a.approvals == 1 && a.required == 2
+        ));
+
+        // Neither a repeat, an outsider nor a wrong key counts
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, id, "a1", &sig1, 2000),
+            Err(WithdrawalError::DuplicateApproval {
+                approver: "a1".to_string()
+            })
+        );
+        assert!(matches!(
+            wq.approve_withdrawal(&mut vault, id, "eve", &sig1, 2000),
+            Err(WithdrawalError::NotApprover { .. })
+        ));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, id, "a2", &sig1, 2000),
+            Err(WithdrawalError::InvalidSignature)
+        );
+        assert_eq!(status(&wq, id), WithdrawalStatus::PendingApproval);
+
+        let sig2 = approval(&wq, id, &approver_key(2));
+        let event = wq.approve_withdrawal(&mut vault, id, "a2", &sig2, 2500).unwrap();
+        assert_eq!(
+            event,
+            ContractEvent::WithdrawalApproved(WithdrawalApproved {
+                withdrawal_id: id,
+                approvers: vec!["a1".to_string(), "a2".to_string()],
+            })
+        );
+        assert_eq!(status(&wq, id), WithdrawalStatus::Pending);
+        let sig3 = approval(&wq, id, &approver_key(3));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, id, "a3", &sig3, 2600),
+            Err(WithdrawalError::NotPendingApproval)
+        );
+        assert!(wq.process_withdrawal(id, 5000, "tx", Decimal::ZERO).is_ok());
+    }
+
+    #[test]
+    fn test_m_minus_one_approvals_expire() {
+        let (mut vault, mut wq, acc, id) = approval_setup();
+        let sig1 = approval(&wq, id, &approver_key(1));
+        wq.approve_withdrawal(&mut vault, id, "a1", &sig1, 2000).unwrap();
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(20));
+
+        // The second approval arrives as the window closes
+        let sig2 = approval(&wq, id, &approver_key(2));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, id, "a2", &sig2, 8200),
+            Err(WithdrawalError::ApprovalExpired { expired_at: 8200 })
+        );
+        assert_eq!(status(&wq, id), WithdrawalStatus::Rejected);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(30));
+        assert_eq!(
+            wq.process_withdrawal(id, 9000, "tx", Decimal::ZERO),
+            Err(WithdrawalError::Rejected)
+        );
+
+        // Stale requests are swept without a late approval
+        let late = request_at(&mut vault, &mut wq, acc, Decimal::from(10), 2, 9000);
+        let sig1 = approval(&wq, late, &approver_key(1));
+        wq.approve_withdrawal(&mut vault, late, "a1", &sig1, 9100).unwrap();
+        assert!(wq.expire_approvals(&mut vault, 16199).unwrap().is_empty());
+        assert_eq!(wq.expire_approvals(&mut vault, 16200).unwrap(), vec![late]);
+        assert_eq!(status(&wq, late), WithdrawalStatus::Rejected);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_removed_approver_no_longer_counts() {
+        let (mut vault, mut wq, acc, id) = approval_setup();
+        let sig1 = approval(&wq, id, &approver_key(1));
+        wq.approve_withdrawal(&mut vault, id, "a1", &sig1, 2000).unwrap();
+
+        assert_eq!(
+            wq.remove_approver(&mut vault, "a2", "a1"),
+            Err(WithdrawalError::ApproverUnauthorized)
+        );
+        wq.remove_approver(&mut vault, "admin", "a1").unwrap();
+        assert!(!vault.access_control().has_role("a1", Role::Approver));
+
+        let sig2 = approval(&wq, id, &approver_key(2));
+        let event = wq.approve_withdrawal(&mut vault, id, "a2", &sig2, 2100).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::WithdrawalApprovalAdded(ref a) if a.approvals == 1
+        ));
+        assert_eq!(status(&wq, id), WithdrawalStatus::PendingApproval);
+
+        let sig3 = approval(&wq, id, &approver_key(3));
+        let event = wq.approve_withdrawal(&mut vault, id, "a3", &sig3, 2200).unwrap();
+        assert!(matches!(
+            event,
+            ContractEvent::WithdrawalApproved(ref a) if a.approvers == ["a2", "a3"]
+        ));
+
+        // Rotated back in with a new key, the old key is worthless
+        let rotated = approver_key(9);
+        wq.register_approver(&mut vault, "admin", "a1", rotated.verifying_key().to_bytes())
+            .unwrap();
+        let next = request_at(&mut vault, &mut wq, acc, Decimal::from(10), 2, 3000);
+        let stale = approval(&wq, next, &approver_key(1));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, next, "a1", &stale, 3100),
+            Err(WithdrawalError::InvalidSignature)
+        );
+        let sig = approval(&wq, next, &rotated);
+        assert!(wq.approve_withdrawal(&mut vault, next, "a1", &sig, 3100).is_ok());
+    }
+
+    fn daily_limited(action: DailyCapAction) -> (Vault, WithdrawalQueue, AccountId) {
+        let (mut vault, mut wq) = setup();
+        let limits = WithdrawalLimits {
+            max_per_withdrawal: Some(Decimal::from(8)),
+            daily_cap: Some(Decimal::from(10)),
+            over_daily_cap: action,
+        };
+        wq.set_withdrawal_limits(&vault, "admin", "BTC", limits).unwrap();
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(100));
+        (vault, wq, acc)
+    }
+
+    #[test]
+    fn test_per_withdrawal_limit_rejects_before_locking() {
+        let (mut vault, mut wq, acc) = daily_limited(DailyCapAction::Reject);
+        let result = request(&mut wq, &mut vault, acc, "BTC", Decimal::from(9), "bc1q...", 1, 1000);
+        assert_eq!(
+            result,
+            Err(WithdrawalError::PerWithdrawalLimitExceeded {
+                asset: "BTC".to_string(),
+                limit: Decimal::from(8),
+            })
+        );
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(100));
+
+        // The nonce was not consumed; other assets are unlimited
+        request_at(&mut vault, &mut wq, acc, Decimal::from(8), 1, 1000);
+        fund_account(&mut vault, acc, "USDT", Decimal::from(50));
+        request(&mut wq, &mut vault, acc, "USDT", Decimal::from(50), "0x", 2, 1000)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_daily_cap_boundary_and_rolling_window() {
+        let (mut vault, mut wq, acc) = daily_limited(DailyCapAction::Reject);
+        let first = request_at(&mut vault, &mut wq, acc, Decimal::from(4), 1, 1000);
+
+        // Exactly the remaining allowance is fine, anything more is not
+        request_at(&mut vault, &mut wq, acc, Decimal::from(6), 2, 2000);
+        assert_eq!(wq.daily_usage(&acc, "BTC", 2000), Decimal::from(10));
+        let result = request(&mut wq, &mut vault, acc, "BTC", Decimal::new(1, 8), "bc1q...", 3, 3000);
+        assert_eq!(
+            result,
+            Err(WithdrawalError::DailyLimitExceeded {
+                asset: "BTC".to_string(),
+                remaining: Decimal::ZERO,
+            })
+        );
+
+        // Cancelling releases the request's share of the window
+        wq.cancel_withdrawal(&mut vault, first, "admin").unwrap();
+        request_at(&mut vault, &mut wq, acc, Decimal::from(4), 3, 3000);
+
+        // The 2000 request leaves the window 24h later, to the second
+        assert_eq!(wq.daily_usage(&acc, "BTC", 2000 + 86399), Decimal::from(10));
+        assert_eq!(wq.daily_usage(&acc, "BTC", 2000 + 86400), Decimal::from(4));
+        request_at(&mut vault, &mut wq, acc, Decimal::from(6), 4, 2000 + 86400);
+    }
+
+    #[test]
+    fn test_daily_cap_routes_to_approval() {
+        let (mut vault, mut wq, acc) = daily_limited(DailyCapAction::RequireApproval);
+        let under = request_at(&mut vault, &mut wq, acc, Decimal::from(7), 1, 1000);
+        let over = request_at(&mut vault, &mut wq, acc, Decimal::from(7), 2, 1000);
+        assert_eq!(status(&wq, under), WithdrawalStatus::Pending);
+        assert_eq!(status(&wq, over), WithdrawalStatus::PendingApproval);
+        assert_eq!(
+            wq.process_withdrawal(over, 5000, "tx", Decimal::ZERO),
+            Err(WithdrawalError::AwaitingApproval {
+                approvals: 0,
+                required: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_withdrawal_limits_admin_only() {
+        let (vault, mut wq) = setup();
+        let limits = WithdrawalLimits {
+            daily_cap: Some(Decimal::from(10)),
+            ..WithdrawalLimits::default()
+        };
+        assert_eq!(
+            wq.set_withdrawal_limits(&vault, "eve", "BTC", limits),
+            Err(WithdrawalError::LimitsUnauthorized)
+        );
+        assert_eq!(wq.withdrawal_limits("BTC"), WithdrawalLimits::default());
+
+        let event = wq.set_withdrawal_limits(&vault, "admin", "BTC", limits).unwrap();
+        let ContractEvent::WithdrawalLimitUpdated(updated) = event else {
+            panic!("Expected WithdrawalLimitUpdated");
+        };
+        assert_eq!(updated.daily_cap, Some(Decimal::from(10)));
+        assert_eq!(updated.max_per_withdrawal, None);
+        assert!(!updated.over_daily_cap_requires_approval);
+        assert_eq!(wq.withdrawal_limits("BTC"), limits);
+    }
+
+    /// Queue with a 2h request lifetime and an account funded with 30 BTC
+    fn expiring_setup() -> (Vault, WithdrawalQueue, AccountId) {
+        let (mut vault, mut wq) = setup();
+        wq.set_expiry(7200);
+        let acc = AccountId::new();
+        fund_account(&mut vault, acc, "BTC", Decimal::from(30));
+        (vault, wq, acc)
+    }
+
+    #[test]
+    fn test_sweep_expired_in_withdrawal_id_order() {
+        let (mut vault, mut wq, acc) = expiring_setup();
+        let a = request_at(&mut vault, &mut wq, acc, Decimal::from(5), 1, 1000);
+        let b = request_at(&mut vault, &mut wq, acc, Decimal::from(7), 2, 1000);
+        let c = request_at(&mut vault, &mut wq, acc, Decimal::from(3), 3, 2000);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(15));
+
+        assert!(wq.sweep_expired(&mut vault, 8199).unwrap().is_empty());
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(wq.sweep_expired(&mut vault, 8200).unwrap(), expected);
+        let expired: Vec<Uuid> = wq
+            .events()
+            .iter()
+            .filter_map(|e| match e {
+                ContractEvent::WithdrawalExpired(e) => Some(e.withdrawal_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(expired, expected);
+        assert_eq!(status(&wq, a), WithdrawalStatus::Expired);
+        assert_eq!(status(&wq, b), WithdrawalStatus::Expired);
+        assert_eq!(status(&wq, c), WithdrawalStatus::Pending);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(27));
+
+        // A second sweep finds nothing new until the next request expires
+        assert!(wq.sweep_expired(&mut vault, 9199).unwrap().is_empty());
+        assert_eq!(wq.sweep_expired(&mut vault, 9200).unwrap(), vec![c]);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_expired_nonce_stays_used() {
+        let (mut vault, mut wq, acc) = expiring_setup();
+        request_at(&mut vault, &mut wq, acc, Decimal::from(5), 1, 1000);
+        wq.sweep_expired(&mut vault, 8200).unwrap();
+
+        let replay = request(&mut wq, &mut vault, acc, "BTC", Decimal::from(5), "bc1q...", 1, 9000);
+        assert_eq!(
+            replay,
+            Err(WithdrawalError::NonceReused {
+                account_id: acc.to_string(),
+                nonce: 1,
+            })
+        );
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_expired_request_cannot_be_processed_or_approved() {
+        let (mut vault, mut wq, acc) = expiring_setup();
+        let id = request_at(&mut vault, &mut wq, acc, Decimal::from(5), 1, 1000);
+
+        // Past its lifetime but not yet swept: no longer eligible
+        assert_eq!(
+            wq.process_withdrawal(id, 8200, "tx", Decimal::ZERO),
+            Err(WithdrawalError::Expired { expired_at: 8200 })
+        );
+        assert_eq!(
+            wq.batch_withdraw(8200, "tx", Decimal::ZERO),
+            Err(WithdrawalError::EmptyBatch)
+        );
+        assert!(wq.process_next_batch(8200).is_empty());
+        wq.sweep_expired(&mut vault, 8200).unwrap();
+        assert_eq!(
+            wq.process_withdrawal(id, 8300, "tx", Decimal::ZERO),
+            Err(WithdrawalError::Expired { expired_at: 8200 })
+        );
+        assert_eq!(
+            wq.cancel_withdrawal(&mut vault, id, "admin"),
+            Err(WithdrawalError::Expired { expired_at: 8200 })
+        );
+
+        // A request awaiting approval expires on approval
+        let (mut vault, mut wq, acc, _) = approval_setup();
+        wq.set_expiry(1800);
+        let late = request_at(&mut vault, &mut wq, acc, Decimal::from(10), 2, 2000);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+        let sig = approval(&wq, late, &approver_key(1));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, late, "a1", &sig, 3800),
+            Err(WithdrawalError::Expired { expired_at: 3800 })
+        );
+        assert_eq!(status(&wq, late), WithdrawalStatus::Expired);
+        assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(20));
+        assert_eq!(
+            wq.approve_withdrawal(&mut vault, late, "a2", &sig, 3900),
+            Err(WithdrawalError::Expired { expired_at: 3800 })
+        );
+        assert!(matches!(
+            wq.events().last(),
+            Some(ContractEvent::WithdrawalExpired(e)) if e.withdrawal_id == late
+        ));
+    }
 }
@@ -9,6 +9,7 @@
 //! - Repeated withdrawal (replay)
 //! - Incorrect signature
 //! - Pause functionality
+//! - Per-token pause
 //! - Upgrade path (ABI freeze)
 
 use contracts::commitment::{compute_hash, CommitmentStore};
@@ -16,7 +17,9 @@ use contracts::errors::{CommitmentError, VaultError, WithdrawalError};
 use contracts::vault::Vault;
 use contracts::withdrawal::WithdrawalQueue;
 use contracts::CONTRACT_ABI_VERSION;
+use ed25519_dalek::{Signer, SigningKey};
 use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
 use types::ids::AccountId;
 
 // ═══════════════════════════════════════════════════════════════════
@@ -149,7 +152,7 @@ fn test_non_admin_cannot_whitelist() {
 #[test]
 fn test_non_admin_cannot_remove_from_whitelist() {
     let mut vault = setup_vault();
-    let result = vault.remove_from_whitelist("attacker", "BTC");
+    let result = vault.remove_from_whitelist("attacker", "BTC", 0);
     assert_eq!(result, Err(VaultError::Unauthorized));
 }
 
@@ -171,7 +174,7 @@ fn test_non_admin_cannot_unpause() {
 #[test]
 fn test_non_admin_cannot_transfer_admin() {
     let mut vault = setup_vault();
-    let result = vault.set_admin("attacker", "attacker");
+    let result = vault.set_admin("attacker", "attacker", 0);
     assert_eq!(result, Err(VaultError::Unauthorized));
 }
 
@@ -192,11 +195,18 @@ fn test_commitment_non_admin_cannot_override() {
 #[test]
 fn test_commitment_non_admin_cannot_resolve_dispute() {
     let mut store = CommitmentStore::new("admin", 3600);
+    let mut vault = Vault::new("admin");
     let root = compute_hash(b"data");
     store.submit_root("admin", root, 1, 1000).unwrap();
-    store.raise_dispute("challenger", "reason", 1500).unwrap();
-
-    let result = store.resolve_dispute("attacker", root, true);
+    let (account_id, key) = (AccountId::new(), SigningKey::from_bytes(&[9u8; 32]));
+    let mut signers = WithdrawalQueue::new(0);
+    signers.register_signer(account_id, key.verifying_key().to_bytes());
+    let payload = CommitmentStore::challenger_signing_payload("challenger", account_id);
+    let signature = key.sign(&Sha256::digest(payload)).to_bytes();
+    store.register_challenger(&signers, "challenger", account_id, &signature).unwrap();
+    store.raise_dispute(&mut vault, "challenger", "reason", 1500).unwrap();
+
+    let result = store.admin_resolve_dispute(&mut vault, "attacker", root, true, 1600);
     assert_eq!(result, Err(CommitmentError::Unauthorized));
 }
 
@@ -205,6 +215,7 @@ fn test_withdrawal_cancel_unauthorized() {
     let (mut vault, mut wq) = setup_withdrawal();
     let acc = AccountId::new();
     fund(&mut vault, acc, "BTC", Decimal::from(10));
+    let sig = register_and_sign(&mut wq, acc, Decimal::from(1), 1);
 
     wq.request_withdrawal(
         &mut vault,
@@ -213,7 +224,7 @@ fn test_withdrawal_cancel_unauthorized() {
         Decimal::from(1),
         "dest",
         1,
-        b"sig",
+        &sig,
         1000,
     )
     .unwrap();
@@ -254,7 +265,7 @@ fn test_malicious_token_after_delist() {
         .unwrap();
 
     // Admin removes ETH from whitelist (simulates delisting)
-    vault.remove_from_whitelist("admin", "ETH").unwrap();
+    vault.remove_from_whitelist("admin", "ETH", 0).unwrap();
 
     // Further deposits rejected
     let result = vault.deposit(acc, "ETH", Decimal::from(5), "tx2");
@@ -278,6 +289,7 @@ fn test_repeated_withdraw_same_nonce_rejected() {
     let (mut vault, mut wq) = setup_withdrawal();
     let acc = AccountId::new();
     fund(&mut vault, acc, "BTC", Decimal::from(100));
+    let sig = register_and_sign(&mut wq, acc, Decimal::from(1), 42);
 
     // First withdrawal succeeds
     wq.request_withdrawal(
@@ -287,12 +299,12 @@ fn test_repeated_withdraw_same_nonce_rejected() {
         Decimal::from(1),
         "dest",
         42,
-        b"sig",
+        &sig,
         1000,
     )
     .unwrap();
 
-    // Replay with same nonce — must fail
+    // Replay of the same signed request — must fail
     let result = wq.request_withdrawal(
         &mut vault,
         acc,
@@ -300,7 +312,7 @@ fn test_repeated_withdraw_same_nonce_rejected() {
         Decimal::from(1),
         "dest",
         42,
-        b"sig",
+        &sig,
         1001,
     );
 
@@ -312,6 +324,8 @@ fn test_repeated_withdraw_different_nonce_allowed() {
     let (mut vault, mut wq) = setup_withdrawal();
     let acc = AccountId::new();
     fund(&mut vault, acc, "BTC", Decimal::from(100));
+    let first = register_and_sign(&mut wq, acc, Decimal::from(1), 1);
+    let second = register_and_sign(&mut wq, acc, Decimal::from(1), 2);
 
     wq.request_withdrawal(
         &mut vault,
@@ -320,7 +334,7 @@ fn test_repeated_withdraw_different_nonce_allowed() {
         Decimal::from(1),
         "dest",
         1,
-        b"sig",
+        &first,
         1000,
     )
     .unwrap();
@@ -333,7 +347,7 @@ fn test_repeated_withdraw_different_nonce_allowed() {
         Decimal::from(1),
         "dest",
         2,
-        b"sig",
+        &second,
         1001,
     )
     .unwrap();
@@ -350,6 +364,7 @@ fn test_incorrect_signature_empty() {
     let (mut vault, mut wq) = setup_withdrawal();
     let acc = AccountId::new();
     fund(&mut vault, acc, "BTC", Decimal::from(10));
+    register_and_sign(&mut wq, acc, Decimal::from(1), 1);
 
     let result = wq.request_withdrawal(
         &mut vault,
@@ -365,12 +380,36 @@ fn test_incorrect_signature_empty() {
 }
 
 #[test]
-fn test_valid_signature_non_empty() {
+fn test_incorrect_signature_non_empty() {
     let (mut vault, mut wq) = setup_withdrawal();
     let acc = AccountId::new();
     fund(&mut vault, acc, "BTC", Decimal::from(10));
+    let sig = register_and_sign(&mut wq, acc, Decimal::from(1), 1);
+
+    // Arbitrary bytes, and a real signature over a different amount
+    for forged in [&b"any_valid_sig"[..], &sig] {
+        let result = wq.request_withdrawal(
+            &mut vault,
+            acc,
+            "BTC",
+            Decimal::from(2),
+            "dest",
+            1,
+            forged,
+            1000,
+        );
+        assert_eq!(result, Err(WithdrawalError::InvalidSignature));
+    }
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+}
+
+#[test]
+fn test_valid_signature_accepted() {
+    let (mut vault, mut wq) = setup_withdrawal();
+    let acc = AccountId::new();
+    fund(&mut vault, acc, "BTC", Decimal::from(10));
+    let sig = register_and_sign(&mut wq, acc, Decimal::from(1), 1);
 
-    // Any non-empty signature is accepted at contract layer
     let result = wq.request_withdrawal(
         &mut vault,
         acc,
@@ -378,12 +417,30 @@ fn test_valid_signature_non_empty() {
         Decimal::from(1),
         "dest",
         1,
-        b"any_valid_sig",
+        &sig,
         1000,
     );
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_unregistered_account_cannot_withdraw() {
+    let (mut vault, mut wq) = setup_withdrawal();
+    let acc = AccountId::new();
+    fund(&mut vault, acc, "BTC", Decimal::from(10));
+    let payload = WithdrawalQueue::withdrawal_signing_payload(acc, "BTC", Decimal::ONE, 1, "dest");
+    let sig = signer_key().sign(&Sha256::digest(payload)).to_bytes();
+
+    // Without a registered key even a well-formed signature is refused
+    let result =
+        wq.request_withdrawal(&mut vault, acc, "BTC", Decimal::ONE, "dest", 1, &sig, 1000);
+    assert_eq!(
+        result,
+        Err(WithdrawalError::SignerNotRegistered { account_id: acc.to_string() })
+    );
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Test Pause Functionality
 // ═══════════════════════════════════════════════════════════════════
@@ -410,13 +467,13 @@ fn test_pause_does_not_block_confirm() {
 
     // Deposit before pause
     vault
-        .deposit(acc, "BTC", Decimal::from(1), "tx1")
+        .detect_deposit(acc, "BTC", Decimal::from(1), "tx1", 1)
         .unwrap();
 
     vault.pause("admin").unwrap();
 
     // Confirm should still check pause
-    let result = vault.confirm_deposit(acc, "BTC", Decimal::from(1), "tx1", 6);
+    let result = vault.confirm_deposit("tx1", 6);
     // confirm_deposit checks pause too
     assert_eq!(result, Err(VaultError::Paused));
 }
@@ -442,6 +499,121 @@ fn test_pause_unpause_cycle() {
     assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(1));
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// Per-Token Pause
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn test_token_pause_blocks_only_that_token() {
+    let mut vault = setup_vault();
+    let acc = AccountId::new();
+    vault.pause_token("admin", "BTC").unwrap();
+
+    let result = vault.deposit(acc, "BTC", Decimal::from(1), "tx1");
+    assert_eq!(
+        result,
+        Err(VaultError::TokenPaused {
+            token: "BTC".to_string()
+        })
+    );
+    vault.deposit(acc, "ETH", Decimal::from(1), "tx2").unwrap();
+    assert!(!vault.is_paused());
+
+    // The guard was released: deposits work again after unpause
+    vault.unpause_token("admin", "BTC").unwrap();
+    vault.deposit(acc, "BTC", Decimal::from(1), "tx3").unwrap();
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(1));
+}
+
+#[test]
+fn test_token_pause_blocks_onchain_deposit_paths() {
+    let mut vault = setup_vault();
+    let acc = AccountId::new();
+    vault
+        .detect_deposit(acc, "BTC", Decimal::from(1), "tx1", 1)
+        .unwrap();
+    vault.pause_token("admin", "BTC").unwrap();
+
+    let paused = Err(VaultError::TokenPaused {
+        token: "BTC".to_string(),
+    });
+    assert_eq!(vault.confirm_deposit("tx1", 6), paused);
+    // Re-detecting a known deposit is a confirmation: no way around it
+    assert_eq!(
+        vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx1", 6),
+        paused
+    );
+    assert_eq!(
+        vault.detect_deposit(acc, "BTC", Decimal::from(1), "tx2", 6),
+        paused
+    );
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::ZERO);
+
+    // A reorg is still recorded
+    vault.revert_deposit("tx1").unwrap();
+}
+
+#[test]
+fn test_token_pause_blocks_withdrawal_requests() {
+    let (mut vault, mut wq) = setup_withdrawal();
+    let acc = AccountId::new();
+    fund(&mut vault, acc, "BTC", Decimal::from(10));
+    let (first, second) = (
+        register_and_sign(&mut wq, acc, Decimal::from(4), 1),
+        register_and_sign(&mut wq, acc, Decimal::from(1), 2),
+    );
+    let id = match wq
+        .request_withdrawal(&mut vault, acc, "BTC", Decimal::from(4), "dest", 1, &first, 1000)
+        .unwrap()
+    {
+        contracts::events::ContractEvent::WithdrawalRequested(e) => e.withdrawal_id,
+        _ => panic!("Expected WithdrawalRequested"),
+    };
+    vault.pause_token("admin", "BTC").unwrap();
+
+    let result =
+        wq.request_withdrawal(&mut vault, acc, "BTC", Decimal::from(1), "dest", 2, &second, 1001);
+    assert_eq!(
+        result,
+        Err(WithdrawalError::Vault(VaultError::TokenPaused {
+            token: "BTC".to_string()
+        }))
+    );
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(6));
+
+    // Cancelling still returns the locked funds
+    wq.cancel_withdrawal(&mut vault, id, "admin").unwrap();
+    assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+}
+
+#[test]
+fn test_non_operator_cannot_toggle_token_pause() {
+    let mut vault = setup_vault();
+    assert!(vault.grant_operator("admin", "ops"));
+    assert_eq!(vault.pause_token("eve", "BTC"), Err(VaultError::Unauthorized));
+    assert!(!vault.is_token_paused("BTC"));
+
+    vault.pause_token("ops", "BTC").unwrap();
+    assert_eq!(vault.unpause_token("eve", "BTC"), Err(VaultError::Unauthorized));
+    assert!(vault.is_token_paused("BTC"));
+    // Pausing an unlisted token does not list it
+    assert!(vault.pause_token("ops", "SHIB").is_err());
+    assert!(!vault.is_whitelisted("SHIB"));
+}
+
+#[test]
+fn test_global_pause_overrides_token_unpause() {
+    let mut vault = setup_vault();
+    let acc = AccountId::new();
+    vault.pause("admin").unwrap();
+    vault.unpause_token("admin", "BTC").unwrap();
+    assert_eq!(
+        vault.deposit(acc, "BTC", Decimal::from(1), "tx1"),
+        Err(VaultError::Paused)
+    );
+    assert_eq!(vault.check_token_active("BTC"), Err(VaultError::Paused));
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Test Upgrade Path (ABI Freeze)
 // ═══════════════════════════════════════════════════════════════════
@@ -549,6 +721,38 @@ mod fuzz {
                 }
             }
         }
+
+        /// Invariant: `request_withdrawal` accepts only the registered
+        /// signer's signature over the exact request, and a refused request
+        /// moves no funds.
+        #[test]
+        fn fuzz_request_withdrawal_signature(
+            signature in prop::collection::vec(any::<u8>(), 0..96),
+            registered in any::<bool>(),
+            amount in 1u64..10u64,
+            nonce in 1u64..1_000u64,
+        ) {
+            let (mut vault, mut wq) = setup_withdrawal();
+            let acc = AccountId::new();
+            fund(&mut vault, acc, "BTC", Decimal::from(10));
+            if registered {
+                register_and_sign(&mut wq, acc, Decimal::from(amount), nonce);
+            }
+
+            let result = wq.request_withdrawal(
+                &mut vault, acc, "BTC", Decimal::from(amount), "dest", nonce, &signature, 1000,
+            );
+            if registered {
+                prop_assert_eq!(result, Err(WithdrawalError::InvalidSignature));
+            } else {
+                prop_assert_eq!(
+                    result,
+                    Err(WithdrawalError::SignerNotRegistered { account_id: acc.to_string() })
+                );
+            }
+            prop_assert_eq!(vault.get_balance(&acc, "BTC"), Decimal::from(10));
+            prop_assert!(wq.queue().is_empty());
+        }
     }
 }
 
@@ -573,3 +777,20 @@ fn setup_withdrawal() -> (Vault, WithdrawalQueue) {
 fn fund(vault: &mut Vault, acc: AccountId, asset: &str, amount: Decimal) {
     vault.deposit(acc, asset, amount, "fund_tx").unwrap();
 }
+
+fn signer_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32])
+}
+
+/// Register the test signer for `acc` and sign a BTC withdrawal to "dest".
+fn register_and_sign(
+    wq: &mut WithdrawalQueue,
+    acc: AccountId,
+    amount: Decimal,
+    nonce: u64,
+) -> Vec<u8> {
+    let key = signer_key();
+    wq.register_signer(acc, key.verifying_key().to_bytes());
+    let payload = WithdrawalQueue::withdrawal_signing_payload(acc, "BTC", amount, nonce, "dest");
+    key.sign(&Sha256::digest(payload)).to_bytes().to_vec()
+}
@@ -0,0 +1,1797 @@
+//! Margin Core — Authoritative margin computation
+//!
+//! Implements spec §5 (Margin Methodology): equity, initial/maintenance
+//! margin aggregation, margin ratio, and risk level classification, in
+//! cross mode (shared collateral) and isolated mode (per-position margin).
+//!
+//! This crate is the single source of truth for per-account margin math.
+//! The risk engine wraps it with its position store and mark-price feed;
+//! `wasm-core` re-exports it unchanged for client-side previews, so a given
+//! account snapshot yields bit-identical results on both sides.
+//!
+//! All calculations are deterministic: fixed-point `Decimal`, no system calls,
+//! sorted iteration via `BTreeMap` per spec §12.
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use types::ids::AccountId;
+use types::numeric::{Price, Quantity};
+use types::order::Side;
+use types::position::{Position, PositionSide};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Internal precision (spec §12, 18 dp).
+const INTERNAL_DP: u32 = 18;
+
+/// Display precision (8 dp).
+const DISPLAY_DP: u32 = 8;
+
+/// Liquidation trigger threshold (spec §5.3.3: margin_ratio < 1.1).
+const LIQUIDATION_THRESHOLD: &str = "1.1";
+
+/// Danger threshold (spec §5.3.3).
+const DANGER_THRESHOLD: &str = "1.5";
+
+/// Warning threshold (spec §5.3.3).
+const WARNING_THRESHOLD: &str = "2.0";
+
+// ---------------------------------------------------------------------------
+// Maintenance margin rate table (spec §5.4.1)
+// ---------------------------------------------------------------------------
+
+/// Return maintenance margin rate given leverage tier.
+pub fn maintenance_margin_rate(leverage: u8) -> Decimal {
+    match leverage {
+        1..=10 => Decimal::from_str_exact("0.005").unwrap(),   // 0.5%
+        11..=20 => Decimal::from_str_exact("0.01").unwrap(),   // 1.0%
+        21..=50 => Decimal::from_str_exact("0.02").unwrap(),   // 2.0%
+        51..=100 => Decimal::from_str_exact("0.05").unwrap(),  // 5.0%
+        101..=125 => Decimal::from_str_exact("0.10").unwrap(), // 10.0%
+        _ => Decimal::from_str_exact("0.10").unwrap(),         // max
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Risk level enum
+// ---------------------------------------------------------------------------
+
+/// Risk level derived from margin ratio thresholds (spec §5.3.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RiskLevel {
+    /// margin_ratio >= 2.0
+    Healthy,
+    /// 1.5 <= margin_ratio < 2.0
+    Warning,
+    /// 1.1 <= margin_ratio < 1.5
+    Danger,
+    /// margin_ratio < 1.1
+    Liquidation,
+}
+
+/// Derive risk level from a margin ratio.
+pub fn risk_level_from_ratio(margin_ratio: Decimal) -> RiskLevel {
+    let liq = Decimal::from_str_exact(LIQUIDATION_THRESHOLD).unwrap();
+    let danger = Decimal::from_str_exact(DANGER_THRESHOLD).unwrap();
+    let warning = Decimal::from_str_exact(WARNING_THRESHOLD).unwrap();
+
+    if margin_ratio < liq {
+        RiskLevel::Liquidation
+    } else if margin_ratio < danger {
+        RiskLevel::Danger
+    } else if margin_ratio < warning {
+        RiskLevel::Warning
+    } else {
+        RiskLevel::Healthy
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Margin mode
+// ---------------------------------------------------------------------------
+
+/// Margin mode (spec §5.2.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MarginMode {
+    /// Shared collateral pool across all positions
+    Cross,
+    /// Per-position isolated margin
+    Isolated,
+}
+
+// ---------------------------------------------------------------------------
+// Margin preview result
+// ---------------------------------------------------------------------------
+
+/// Result of a margin simulation — what would happen if the order executed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginPreview {
+    /// Equity after hypothetical trade
+    pub equity_after: Decimal,
+    /// Total margin used after trade
+    pub margin_used_after: Decimal,
+    /// Available margin after trade
+    pub margin_available_after: Decimal,
+    /// Margin ratio after trade
+    pub margin_ratio_after: Decimal,
+    /// Estimated liquidation price for the resulting position
+    pub liquidation_price: Decimal,
+    /// Effective leverage after trade
+    pub leverage_ratio: Decimal,
+    /// Risk classification after trade
+    pub risk_level: RiskLevel,
+    /// Whether any computed balance would become negative
+    pub has_negative_balance: bool,
+    /// Collateral assets held without a configured price, valued at zero
+    #[serde(default)]
+    pub unpriced_collateral: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Collateral valuation
+// ---------------------------------------------------------------------------
+
+/// Valuation of one collateral asset in quote terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollateralAsset {
+    /// Quote price of one unit
+    pub price: Decimal,
+    /// Fraction of the value discounted, from 0 (full credit) to 1 (none)
+    pub haircut: Decimal,
+}
+
+// ---------------------------------------------------------------------------
+// Stress test results
+// ---------------------------------------------------------------------------
+
+/// Account state after a hypothetical mark price shock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressResult {
+    /// Equity at the shocked marks
+    pub equity: Decimal,
+    /// Margin ratio at the shocked marks
+    pub margin_ratio: Decimal,
+    /// Risk classification at the shocked marks
+    pub risk_level: RiskLevel,
+    /// Symbols whose shocked mark reaches the position's liquidation price
+    pub liquidating: Vec<String>,
+    /// Symbol losing the most to the shock, if any loses
+    pub worst_contributor: Option<StressContributor>,
+}
+
+/// One symbol's PnL change under a stress scenario.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressContributor {
+    pub symbol: String,
+    /// Shocked minus current unrealized PnL (negative = loss)
+    pub pnl_change: Decimal,
+}
+
+/// One point of a single-symbol shock sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressPoint {
+    /// Shock in percent (−20 = mark 20% lower)
+    pub shock_pct: Decimal,
+    pub margin_ratio: Decimal,
+    pub risk_level: RiskLevel,
+}
+
+// ---------------------------------------------------------------------------
+// Cross-margin engine
+// ---------------------------------------------------------------------------
+
+/// Cross-margin preview engine.
+///
+/// Holds the account snapshot and computes margin previews for hypothetical
+/// orders without mutating the underlying state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossMarginEngine {
+    pub account_id: AccountId,
+    /// Account balance already in quote currency
+    pub total_balance: Decimal,
+    /// Existing positions keyed by symbol (sorted)
+    pub positions: BTreeMap<String, Position>,
+    /// Funding accrued but not yet paid, keyed by symbol (positive = owed)
+    #[serde(default)]
+    pub accrued_funding: BTreeMap<String, Decimal>,
+    /// Collateral balances keyed by asset (sorted), valued via `collateral_config`
+    #[serde(default)]
+    pub collateral: BTreeMap<String, Decimal>,
+    /// Price and haircut per collateral asset
+    #[serde(default)]
+    pub collateral_config: BTreeMap<String, CollateralAsset>,
+}
+
+impl CrossMarginEngine {
+    /// Create a new engine from an account snapshot.
+    pub fn new(account_id: AccountId, total_balance: Decimal) -> Self {
+        Self {
+            account_id,
+            total_balance,
+            positions: BTreeMap::new(),
+            accrued_funding: BTreeMap::new(),
+            collateral: BTreeMap::new(),
+            collateral_config: BTreeMap::new(),
+        }
+    }
+
+    /// Create an engine whose collateral is held in several assets.
+    ///
+    /// Each balance is valued at its configured price less its haircut;
+    /// `total_balance` starts at zero.
+    pub fn with_collateral(
+        account_id: AccountId,
+        balances: BTreeMap<String, Decimal>,
+        config: BTreeMap<String, CollateralAsset>,
+    ) -> Self {
+        Self {
+            collateral: balances,
+            collateral_config: config,
+            ..Self::new(account_id, Decimal::ZERO)
+        }
+    }
+
+    /// Add an existing position to the snapshot.
+    pub fn add_position(&mut self, position: Position) {
+        self.positions
+            .insert(position.symbol.as_str().to_owned(), position);
+    }
+
+    /// Record funding accrued on a symbol since its last payment.
+    pub fn set_accrued_funding(&mut self, symbol: &str, amount: Decimal) {
+        self.accrued_funding.insert(symbol.to_owned(), amount);
+    }
+
+    // -- collateral --------------------------------------------------------
+
+    /// Quote value of all collateral: `total_balance` plus each asset's
+    /// `balance × price × (1 − haircut)`.
+    ///
+    /// Assets without a configured price count as zero (see
+    /// `unpriced_collateral`). Haircuts are clamped to [0, 1].
+    pub fn collateral_value(&self) -> Decimal {
+        let mut total = self.total_balance;
+        for (asset, balance) in &self.collateral {
+            if let Some(config) = self.collateral_config.get(asset) {
+                let credit = Decimal::ONE - config.haircut.clamp(Decimal::ZERO, Decimal::ONE);
+                total += round_internal(balance * config.price * credit);
+            }
+        }
+        round_internal(total)
+    }
+
+    /// Collateral assets with a balance but no configured price, in asset order.
+    pub fn unpriced_collateral(&self) -> Vec<String> {
+        self.collateral
+            .iter()
+            .filter(|(asset, balance)| !balance.is_zero() && !self.collateral_config.contains_key(*asset))
+            .map(|(asset, _)| asset.clone())
+            .collect()
+    }
+
+    // -- core queries ------------------------------------------------------
+
+    /// Total unrealized PnL across all positions.
+    pub fn total_unrealized_pnl(&self) -> Decimal {
+        let mut total = Decimal::ZERO;
+        for pos in self.positions.values() {
+            total += unrealized_pnl(pos);
+        }
+        round_internal(total)
+    }
+
+    /// Total funding accrued but not yet paid (positive = owed).
+    pub fn total_accrued_funding(&self) -> Decimal {
+        round_internal(self.accrued_funding.values().sum())
+    }
+
+    /// Equity = collateral_value + unrealized_pnl − accrued funding (spec §5.3.2).
+    pub fn equity(&self) -> Decimal {
+        round_display(self.collateral_value() + self.total_unrealized_pnl() - self.total_accrued_funding())
+    }
+
+    /// Total maintenance margin across all positions.
+    pub fn total_maintenance_margin(&self) -> Decimal {
+        let mut total = Decimal::ZERO;
+        for pos in self.positions.values() {
+            let pv = position_value(pos);
+            let rate = maintenance_margin_rate(pos.leverage);
+            total += round_up(pv * rate, INTERNAL_DP);
+        }
+        round_display(total)
+    }
+
+    /// Total initial margin used across all positions.
+    pub fn total_initial_margin(&self) -> Decimal {
+        let mut total = Decimal::ZERO;
+        for pos in self.positions.values() {
+            total += pos.initial_margin;
+        }
+        round_display(total)
+    }
+
+    /// Margin available = equity − margin_used (spec §5.3.1).
+    /// Rounded DOWN conservatively.
+    pub fn margin_available(&self) -> Decimal {
+        let avail = self.equity() - self.total_initial_margin();
+        round_down(avail, DISPLAY_DP)
+    }
+
+    /// Margin ratio = equity / maintenance_margin (spec §5.3.3).
+    pub fn margin_ratio(&self) -> Decimal {
+        let mm = self.total_maintenance_margin();
+        if mm == Decimal::ZERO {
+            return Decimal::MAX;
+        }
+        round_display(self.equity() / mm)
+    }
+
+    /// Current risk level.
+    pub fn risk_level(&self) -> RiskLevel {
+        risk_level_from_ratio(self.margin_ratio())
+    }
+
+    /// Mark price of `symbol` at which the account's margin ratio falls to
+    /// the liquidation threshold, holding every other mark constant.
+    ///
+    /// Solves `equity(mark) = 1.1 × total_mm` over the whole account, so
+    /// other positions' PnL and margin and all collateral count. MM is
+    /// entry-based and does not move with the mark. A long that cannot be
+    /// liquidated above zero returns zero. `None` without a position.
+    pub fn liquidation_price_for(&self, symbol: &str) -> Option<Decimal> {
+        let pos = self.positions.get(symbol)?;
+        let size = pos.size.as_decimal();
+        if size.is_zero() {
+            return None;
+        }
+        let others_pnl: Decimal = self
+            .positions
+            .iter()
+            .filter(|(key, _)| key.as_str() != symbol)
+            .map(|(_, other)| unrealized_pnl(other))
+            .sum();
+        let equity_without = self.collateral_value() + round_internal(others_pnl) - self.total_accrued_funding();
+        let threshold = Decimal::from_str_exact(LIQUIDATION_THRESHOLD).unwrap();
+        // Loss the position can take before equity reaches the threshold
+        let buffer = equity_without - threshold * self.total_maintenance_margin();
+
+        let entry = pos.entry_price.as_decimal();
+        let price = match pos.side {
+            PositionSide::LONG => entry - buffer / size,
+            PositionSide::SHORT => entry + buffer / size,
+        };
+        Some(round_display(price.max(Decimal::ZERO)))
+    }
+
+    // -- stress testing ----------------------------------------------------
+
+    /// Revalue the account with each symbol's mark shocked by a percentage.
+    ///
+    /// `scenario` maps symbol → shock in percent (−20 = mark 20% lower);
+    /// symbols without a position are ignored and unlisted positions keep
+    /// their mark. A shocked mark floors at zero. Maintenance margin is
+    /// entry-based and does not move with the mark. Does **not** mutate
+    /// `self`.
+    pub fn stress(&self, scenario: &BTreeMap<String, Decimal>) -> StressResult {
+        let mut total_pnl = Decimal::ZERO;
+        let mut liquidating = Vec::new();
+        let mut worst_contributor: Option<StressContributor> = None;
+
+        for (symbol, pos) in &self.positions {
+            let Some(shock_pct) = scenario.get(symbol) else {
+                total_pnl += unrealized_pnl(pos);
+                continue;
+            };
+            let mark = (pos.mark_price.as_decimal() * (Decimal::ONE + shock_pct / Decimal::ONE_HUNDRED))
+                .max(Decimal::ZERO);
+            let shocked_pnl = pnl_at(pos, mark);
+            total_pnl += shocked_pnl;
+
+            let liquidation_price = pos.liquidation_price.as_decimal();
+            let reached = match pos.side {
+                PositionSide::LONG => mark <= liquidation_price,
+                PositionSide::SHORT => mark >= liquidation_price,
+            };
+            if reached {
+                liquidating.push(symbol.clone());
+            }
+
+            let pnl_change = round_display(shocked_pnl - unrealized_pnl(pos));
+            let worse = pnl_change < Decimal::ZERO
+                && worst_contributor.as_ref().is_none_or(|worst| pnl_change < worst.pnl_change);
+            if worse {
+                worst_contributor = Some(StressContributor { symbol: symbol.clone(), pnl_change });
+            }
+        }
+
+        let equity = round_display(self.collateral_value() + round_internal(total_pnl) - self.total_accrued_funding());
+        let mm = self.total_maintenance_margin();
+        let margin_ratio = if mm == Decimal::ZERO {
+            Decimal::MAX
+        } else {
+            round_display(equity / mm)
+        };
+
+        StressResult {
+            equity,
+            margin_ratio,
+            risk_level: risk_level_from_ratio(margin_ratio),
+            liquidating,
+            worst_contributor,
+        }
+    }
+
+    /// Sweep one symbol's shock from `from_pct` to `to_pct` inclusive in
+    /// `step_pct` increments, for charting the margin ratio curve.
+    ///
+    /// Empty if `step_pct` is not positive or the range is reversed.
+    pub fn stress_grid(&self, symbol: &str, from_pct: Decimal, to_pct: Decimal, step_pct: Decimal) -> Vec<StressPoint> {
+        let mut points = Vec::new();
+        if step_pct <= Decimal::ZERO {
+            return points;
+        }
+        let mut scenario = BTreeMap::new();
+        let mut shock_pct = from_pct;
+        while shock_pct <= to_pct {
+            scenario.insert(symbol.to_owned(), shock_pct);
+            let result = self.stress(&scenario);
+            points.push(StressPoint {
+                shock_pct,
+                margin_ratio: result.margin_ratio,
+                risk_level: result.risk_level,
+            });
+            shock_pct += step_pct;
+        }
+        points
+    }
+
+    // -- simulation --------------------------------------------------------
+
+    /// Simulate the effect of a hypothetical new order.
+    ///
+    /// Returns a `MarginPreview` describing the margin state *after* the
+    /// order fills completely.  Does **not** mutate `self`.
+    ///
+    /// The fill nets against an existing position in `symbol`: a same-side
+    /// order extends it at a blended entry; an opposite-side order reduces,
+    /// closes or flips it, realizing PnL on the closed size at the order
+    /// price. IM and MM for the symbol are recomputed from the net result,
+    /// and the liquidation price is that of the net position (zero if the
+    /// order closes it).
+    pub fn simulate_order(
+        &self,
+        symbol: &str,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        leverage: u8,
+    ) -> MarginPreview {
+        let leg = self.net_leg(symbol, side, price, quantity, leverage);
+
+        // Every other position is unaffected by the order
+        let mut others_im = Decimal::ZERO;
+        let mut others_mm = Decimal::ZERO;
+        let mut others_value = Decimal::ZERO;
+        let mut others_pnl = Decimal::ZERO;
+        for (key, pos) in &self.positions {
+            if key == symbol {
+                continue;
+            }
+            let pv = position_value(pos);
+            others_im += pos.initial_margin;
+            others_mm += round_up(pv * maintenance_margin_rate(pos.leverage), INTERNAL_DP);
+            others_value += pv;
+            others_pnl += unrealized_pnl(pos);
+        }
+
+        let total_im_after = round_display(others_im + leg.initial_margin);
+        let total_mm_after = round_display(others_mm + leg.maintenance_margin);
+
+        // Unrealized PnL stays the same until mark moves; closed size realizes
+        // at the order price. Accrued funding stays owed even on a close.
+        let equity_after = round_display(
+            self.collateral_value() + round_internal(others_pnl + leg.unrealized_pnl + leg.realized_pnl)
+                - self.total_accrued_funding(),
+        );
+
+        let margin_available_after = round_down(equity_after - total_im_after, DISPLAY_DP);
+
+        let margin_ratio_after = if total_mm_after == Decimal::ZERO {
+            Decimal::MAX
+        } else {
+            round_display(equity_after / total_mm_after)
+        };
+
+        let leverage_ratio = if total_im_after == Decimal::ZERO {
+            Decimal::ZERO
+        } else {
+            let total_notional = round_internal(others_value + leg.entry_price * leg.size);
+            round_display(total_notional / equity_after)
+        };
+
+        MarginPreview {
+            equity_after,
+            margin_used_after: total_im_after,
+            margin_available_after,
+            margin_ratio_after,
+            liquidation_price: round_display(leg.liquidation_price),
+            leverage_ratio,
+            risk_level: risk_level_from_ratio(margin_ratio_after),
+            has_negative_balance: margin_available_after < Decimal::ZERO,
+            unpriced_collateral: self.unpriced_collateral(),
+        }
+    }
+
+    /// Net the hypothetical fill against the existing position in `symbol`.
+    fn net_leg(&self, symbol: &str, side: Side, price: Price, quantity: Quantity, leverage: u8) -> NetLeg {
+        let price_dec = price.as_decimal();
+        let qty_dec = quantity.as_decimal();
+        let order_side = side_to_position_side(side);
+
+        // A leg opened entirely by the order, at the order price and leverage
+        let opened = |size: Decimal, realized_pnl: Decimal| {
+            let notional = round_internal(price_dec * size);
+            let mm_rate = maintenance_margin_rate(leverage);
+            NetLeg {
+                size,
+                entry_price: price_dec,
+                // New initial margin for the order (rounded UP for safety)
+                initial_margin: round_up(notional / Decimal::from(leverage), INTERNAL_DP),
+                maintenance_margin: round_up(notional * mm_rate, INTERNAL_DP),
+                unrealized_pnl: Decimal::ZERO,
+                realized_pnl,
+                liquidation_price: compute_liquidation_price(order_side, price_dec, leverage, mm_rate),
+            }
+        };
+
+        let Some(pos) = self.positions.get(symbol) else {
+            return opened(qty_dec, Decimal::ZERO);
+        };
+        let size = pos.size.as_decimal();
+        let entry = pos.entry_price.as_decimal();
+
+        if pos.side == order_side {
+            let new = opened(qty_dec, Decimal::ZERO);
+            let size_after = size + qty_dec;
+            let entry_after = round_internal((entry * size + price_dec * qty_dec) / size_after);
+            let initial_margin = pos.initial_margin + new.initial_margin;
+            let maintenance_margin = round_up(
+                position_value(pos) * maintenance_margin_rate(pos.leverage),
+                INTERNAL_DP,
+            ) + new.maintenance_margin;
+            return NetLeg {
+                size: size_after,
+                entry_price: entry_after,
+                initial_margin,
+                maintenance_margin,
+                unrealized_pnl: unrealized_pnl(pos),
+                realized_pnl: Decimal::ZERO,
+                liquidation_price: margin_liquidation_price(
+                    pos.side,
+                    entry_after,
+                    size_after,
+                    initial_margin,
+                    maintenance_margin,
+                ),
+            };
+        }
+
+        let closed = qty_dec.min(size);
+        let realized_pnl = match pos.side {
+            PositionSide::LONG => (price_dec - entry) * closed,
+            PositionSide::SHORT => (entry - price_dec) * closed,
+        };
+
+        if qty_dec > size {
+            // Flip: the excess opens a position on the order side
+            return opened(qty_dec - size, realized_pnl);
+        }
+
+        // Reduce (or close): the remainder keeps its entry and leverage
+        let remaining = size - qty_dec;
+        let fraction = remaining / size;
+        let initial_margin = round_up(pos.initial_margin * fraction, INTERNAL_DP);
+        let maintenance_margin = round_up(
+            entry * remaining * maintenance_margin_rate(pos.leverage),
+            INTERNAL_DP,
+        );
+        NetLeg {
+            size: remaining,
+            entry_price: entry,
+            initial_margin,
+            maintenance_margin,
+            unrealized_pnl: round_internal(unrealized_pnl(pos) * fraction),
+            realized_pnl,
+            liquidation_price: if remaining == Decimal::ZERO {
+                Decimal::ZERO
+            } else {
+                margin_liquidation_price(pos.side, entry, remaining, initial_margin, maintenance_margin)
+            },
+        }
+    }
+}
+
+/// One symbol's position after netting a hypothetical fill.
+#[derive(Debug, Clone, Copy)]
+struct NetLeg {
+    size: Decimal,
+    entry_price: Decimal,
+    initial_margin: Decimal,
+    maintenance_margin: Decimal,
+    /// Unrealized PnL of the remaining size at the current mark
+    unrealized_pnl: Decimal,
+    /// PnL realized on the size the order closes
+    realized_pnl: Decimal,
+    liquidation_price: Decimal,
+}
+
+// ---------------------------------------------------------------------------
+// Isolated-margin engine
+// ---------------------------------------------------------------------------
+
+/// Isolated-margin failures.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IsolatedMarginError {
+    #[error("No isolated position for {0}")]
+    UnknownPosition(String),
+
+    #[error("Margin amount must be positive, got {0}")]
+    NonPositiveAmount(Decimal),
+
+    #[error("Insufficient free balance: requested {requested}, available {available}")]
+    InsufficientBalance { requested: Decimal, available: Decimal },
+
+    #[error("Insufficient position margin: requested {requested}, available {available}")]
+    InsufficientMargin { requested: Decimal, available: Decimal },
+}
+
+/// A position together with the margin allocated to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IsolatedPosition {
+    pub position: Position,
+    /// Collateral dedicated to this position (quote currency)
+    pub margin: Decimal,
+    /// Funding accrued but not yet paid (positive = owed)
+    #[serde(default)]
+    pub accrued_funding: Decimal,
+}
+
+impl IsolatedPosition {
+    /// Position equity = allocated margin + unrealized PnL − accrued funding.
+    pub fn equity(&self) -> Decimal {
+        self.pool().equity()
+    }
+
+    /// Maintenance margin of this position alone.
+    pub fn maintenance_margin(&self) -> Decimal {
+        round_display(self.pool().maintenance_margin)
+    }
+
+    /// Margin available = position equity − initial margin (rounded DOWN).
+    pub fn margin_available(&self) -> Decimal {
+        self.pool().margin_available()
+    }
+
+    /// Margin ratio = position equity / position maintenance margin.
+    pub fn margin_ratio(&self) -> Decimal {
+        self.pool().margin_ratio()
+    }
+
+    /// Mark price at which this position's equity falls to its
+    /// maintenance margin. Other positions and free balance play no part.
+    pub fn liquidation_price(&self) -> Decimal {
+        self.pool().liquidation_price()
+    }
+
+    fn pool(&self) -> MarginPool {
+        let rate = maintenance_margin_rate(self.position.leverage);
+        let notional = position_value(&self.position);
+        MarginPool {
+            side: self.position.side,
+            size: self.position.size.as_decimal(),
+            notional,
+            unrealized_pnl: unrealized_pnl(&self.position),
+            initial_margin: self.position.initial_margin,
+            maintenance_margin: round_up(notional * rate, INTERNAL_DP),
+            margin: self.margin,
+            accrued_funding: self.accrued_funding,
+        }
+    }
+}
+
+/// Figures of one isolated margin pool, before display rounding.
+#[derive(Debug, Clone, Copy)]
+struct MarginPool {
+    side: PositionSide,
+    size: Decimal,
+    /// Entry notional
+    notional: Decimal,
+    unrealized_pnl: Decimal,
+    initial_margin: Decimal,
+    maintenance_margin: Decimal,
+    margin: Decimal,
+    accrued_funding: Decimal,
+}
+
+impl MarginPool {
+    fn equity(&self) -> Decimal {
+        round_display(self.margin + self.unrealized_pnl - self.accrued_funding)
+    }
+
+    fn margin_available(&self) -> Decimal {
+        round_down(self.equity() - self.initial_margin, DISPLAY_DP)
+    }
+
+    fn margin_ratio(&self) -> Decimal {
+        let mm = round_display(self.maintenance_margin);
+        if mm == Decimal::ZERO {
+            return Decimal::MAX;
+        }
+        round_display(self.equity() / mm)
+    }
+
+    fn liquidation_price(&self) -> Decimal {
+        if self.size == Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        round_display(margin_liquidation_price(
+            self.side,
+            self.notional / self.size,
+            self.size,
+            self.margin - self.accrued_funding,
+            self.maintenance_margin,
+        ))
+    }
+
+    fn preview(&self, free_balance: Decimal) -> MarginPreview {
+        let equity_after = self.equity();
+        let margin_available_after = self.margin_available();
+        let margin_ratio_after = self.margin_ratio();
+        let leverage_ratio = if equity_after <= Decimal::ZERO {
+            Decimal::ZERO
+        } else {
+            round_display(self.notional / equity_after)
+        };
+
+        MarginPreview {
+            equity_after,
+            margin_used_after: round_display(self.initial_margin),
+            margin_available_after,
+            margin_ratio_after,
+            liquidation_price: self.liquidation_price(),
+            leverage_ratio,
+            risk_level: risk_level_from_ratio(margin_ratio_after),
+            has_negative_balance: free_balance < Decimal::ZERO || margin_available_after < Decimal::ZERO,
+            unpriced_collateral: Vec::new(),
+        }
+    }
+}
+
+/// Isolated-margin preview engine.
+///
+/// Each position draws only on the margin allocated to it, so a loss on
+/// one position never touches the free balance or any other position.
+/// Positions are keyed by symbol; there is no netting across positions
+/// (spec §5.11.3).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IsolatedMarginEngine {
+    pub account_id: AccountId,
+    /// Total account balance (quote currency), allocated margin included
+    pub total_balance: Decimal,
+    /// Isolated positions keyed by symbol (sorted)
+    pub positions: BTreeMap<String, IsolatedPosition>,
+}
+
+impl IsolatedMarginEngine {
+    /// Create a new engine from an account snapshot.
+    pub fn new(account_id: AccountId, total_balance: Decimal) -> Self {
+        Self {
+            account_id,
+            total_balance,
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// Add an existing position to the snapshot with its allocated margin.
+    pub fn add_position(&mut self, position: Position, margin: Decimal) {
+        self.positions.insert(
+            position.symbol.as_str().to_owned(),
+            IsolatedPosition {
+                position,
+                margin,
+                accrued_funding: Decimal::ZERO,
+            },
+        );
+    }
+
+    /// Record funding accrued on a position since its last payment.
+    pub fn set_accrued_funding(&mut self, symbol: &str, amount: Decimal) -> Result<(), IsolatedMarginError> {
+        let pos = self
+            .positions
+            .get_mut(symbol)
+            .ok_or_else(|| IsolatedMarginError::UnknownPosition(symbol.to_owned()))?;
+        pos.accrued_funding = amount;
+        Ok(())
+    }
+
+    // -- core queries ------------------------------------------------------
+
+    /// Balance not allocated to any position (rounded DOWN).
+    pub fn free_balance(&self) -> Decimal {
+        let allocated: Decimal = self.positions.values().map(|p| p.margin).sum();
+        round_down(self.total_balance - allocated, DISPLAY_DP)
+    }
+
+    /// Margin currently allocated to a position.
+    pub fn position_margin(&self, symbol: &str) -> Option<Decimal> {
+        self.positions.get(symbol).map(|p| p.margin)
+    }
+
+    /// Preview of an existing position's own margin state.
+    pub fn position_preview(&self, symbol: &str) -> Option<MarginPreview> {
+        let pos = self.positions.get(symbol)?;
+        Some(pos.pool().preview(self.free_balance()))
+    }
+
+    // -- margin transfers (spec §5.7.3: manual only) -----------------------
+
+    /// Move `amount` from the free balance into a position's margin.
+    ///
+    /// Returns the position's new margin.
+    pub fn allocate_margin(&mut self, symbol: &str, amount: Decimal) -> Result<Decimal, IsolatedMarginError> {
+        if amount <= Decimal::ZERO {
+            return Err(IsolatedMarginError::NonPositiveAmount(amount));
+        }
+        let available = self.free_balance();
+        let pos = self
+            .positions
+            .get_mut(symbol)
+            .ok_or_else(|| IsolatedMarginError::UnknownPosition(symbol.to_owned()))?;
+        if amount > available {
+            return Err(IsolatedMarginError::InsufficientBalance { requested: amount, available });
+        }
+        pos.margin += amount;
+        Ok(pos.margin)
+    }
+
+    /// Move `amount` out of a position's margin back to the free balance.
+    ///
+    /// The position must keep at least its initial margin, so at most
+    /// `margin_available` can be removed. Returns the position's new margin.
+    pub fn reduce_margin(&mut self, symbol: &str, amount: Decimal) -> Result<Decimal, IsolatedMarginError> {
+        if amount <= Decimal::ZERO {
+            return Err(IsolatedMarginError::NonPositiveAmount(amount));
+        }
+        let pos = self
+            .positions
+            .get_mut(symbol)
+            .ok_or_else(|| IsolatedMarginError::UnknownPosition(symbol.to_owned()))?;
+        let available = pos.margin_available().max(Decimal::ZERO);
+        if amount > available {
+            return Err(IsolatedMarginError::InsufficientMargin { requested: amount, available });
+        }
+        pos.margin -= amount;
+        Ok(pos.margin)
+    }
+
+    // -- simulation --------------------------------------------------------
+
+    /// Simulate the effect of a hypothetical new order.
+    ///
+    /// The order's initial margin is allocated from the free balance. A
+    /// same-side order on a symbol with an isolated position extends that
+    /// position and its margin pool; otherwise the order opens a fresh
+    /// pool. The preview reports the resulting position only. Does **not**
+    /// mutate `self`.
+    pub fn simulate_order(
+        &self,
+        symbol: &str,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        leverage: u8,
+    ) -> MarginPreview {
+        let price_dec = price.as_decimal();
+        let qty_dec = quantity.as_decimal();
+        let notional = round_internal(price_dec * qty_dec);
+
+        // New initial margin for the order (rounded UP for safety)
+        let new_im = round_up(notional / Decimal::from(leverage), INTERNAL_DP);
+        let free_after = round_down(self.free_balance() - new_im, DISPLAY_DP);
+
+        let new_mm = round_up(notional * maintenance_margin_rate(leverage), INTERNAL_DP);
+
+        let position_side = side_to_position_side(side);
+        let mut pool = match self.positions.get(symbol) {
+            Some(pos) if pos.position.side == position_side => pos.pool(),
+            _ => MarginPool {
+                side: position_side,
+                size: Decimal::ZERO,
+                notional: Decimal::ZERO,
+                unrealized_pnl: Decimal::ZERO,
+                initial_margin: Decimal::ZERO,
+                maintenance_margin: Decimal::ZERO,
+                margin: Decimal::ZERO,
+                accrued_funding: Decimal::ZERO,
+            },
+        };
+        // Hypothetical unrealized PnL stays the same until mark moves
+        pool.size += qty_dec;
+        pool.notional += notional;
+        pool.initial_margin += new_im;
+        pool.maintenance_margin += new_mm;
+        pool.margin += new_im;
+        pool.preview(free_after)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pure helpers
+// ---------------------------------------------------------------------------
+
+/// Compute unrealized PnL for a single position (spec §4.4.3).
+fn unrealized_pnl(pos: &Position) -> Decimal {
+    pnl_at(pos, pos.mark_price.as_decimal())
+}
+
+/// Unrealized PnL of a position at an arbitrary mark.
+fn pnl_at(pos: &Position, mark: Decimal) -> Decimal {
+    let size = pos.size.as_decimal();
+    let entry = pos.entry_price.as_decimal();
+    match pos.side {
+        PositionSide::LONG => (mark - entry) * size,
+        PositionSide::SHORT => (entry - mark) * size,
+    }
+}
+
+/// Position notional value.
+fn position_value(pos: &Position) -> Decimal {
+    pos.entry_price.as_decimal() * pos.size.as_decimal()
+}
+
+/// Compute estimated liquidation price (spec §5, derived).
+///
+/// LONG:  liq_price = entry × (1 − 1/leverage + mm_rate)
+/// SHORT: liq_price = entry × (1 + 1/leverage − mm_rate)
+fn compute_liquidation_price(
+    side: PositionSide,
+    entry_price: Decimal,
+    leverage: u8,
+    mm_rate: Decimal,
+) -> Decimal {
+    let one = Decimal::ONE;
+    let lev_inv = one / Decimal::from(leverage);
+    match side {
+        PositionSide::LONG => entry_price * (one - lev_inv + mm_rate),
+        PositionSide::SHORT => entry_price * (one + lev_inv - mm_rate),
+    }
+}
+
+/// Compute the price at which a position's margin plus PnL falls to `mm`.
+///
+/// Equity `margin + (p − entry) × size` (LONG) reaches `mm` at
+/// LONG:  liq_price = entry − (margin − mm) / size
+/// SHORT: liq_price = entry + (margin − mm) / size
+///
+/// With `margin = entry × size / leverage` this is the order-only estimate
+/// used by `compute_liquidation_price`.
+fn margin_liquidation_price(
+    side: PositionSide,
+    entry_price: Decimal,
+    size: Decimal,
+    margin: Decimal,
+    mm: Decimal,
+) -> Decimal {
+    let cushion = (margin - mm) / size;
+    match side {
+        PositionSide::LONG => (entry_price - cushion).max(Decimal::ZERO),
+        PositionSide::SHORT => entry_price + cushion,
+    }
+}
+
+/// Convert order `Side` to `PositionSide`.
+fn side_to_position_side(side: Side) -> PositionSide {
+    match side {
+        Side::BUY => PositionSide::LONG,
+        Side::SELL => PositionSide::SHORT,
+    }
+}
+
+/// Round to internal precision, HALF_UP.
+fn round_internal(v: Decimal) -> Decimal {
+    v.round_dp_with_strategy(INTERNAL_DP, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Round to display precision, HALF_UP.
+fn round_display(v: Decimal) -> Decimal {
+    v.round_dp_with_strategy(DISPLAY_DP, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Round UP (away from zero) — used for margin requirements (spec §12.9.2).
+fn round_up(v: Decimal, dp: u32) -> Decimal {
+    v.round_dp_with_strategy(dp, RoundingStrategy::AwayFromZero)
+}
+
+/// Round DOWN — used for available margin (conservative; spec §12.9.2).
+fn round_down(v: Decimal, dp: u32) -> Decimal {
+    v.round_dp_with_strategy(dp, RoundingStrategy::ToZero)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::ids::{AccountId, MarketId};
+    use types::numeric::{Price, Quantity};
+
+    fn make_engine() -> CrossMarginEngine {
+        let account_id = AccountId::new();
+        let mut engine = CrossMarginEngine::new(account_id, Decimal::from(100_000));
+
+        // Existing position: Long 2 BTC @ 50 000, mark 51 000, leverage 10
+        let pos = Position::new(
+            account_id,
+            MarketId::new("BTC/USDT"),
+            PositionSide::LONG,
+            Quantity::from_str("2.0").unwrap(),
+            Price::from_u64(50_000),
+            Price::from_u64(51_000),
+            Price::from_u64(49_500),
+            Decimal::from(10_000), // IM = 100k / 10
+            Decimal::from(500),
+            10,
+            1_708_123_456_789_000_000,
+        );
+        engine.add_position(pos);
+        engine
+    }
+
+    #[test]
+    fn test_unrealized_pnl() {
+        let engine = make_engine();
+        // Long 2 BTC: (51000 - 50000) × 2 = 2000
+        assert_eq!(engine.total_unrealized_pnl(), Decimal::from(2_000));
+    }
+
+    #[test]
+    fn test_equity() {
+        let engine = make_engine();
+        // 100 000 + 2 000 = 102 000
+        assert_eq!(engine.equity(), Decimal::from(102_000));
+    }
+
+    #[test]
+    fn test_margin_ratio() {
+        let engine = make_engine();
+        // MM = 100 000 × 0.005 = 500 (position value 100k, leverage 10)
+        // equity = 102 000, ratio = 102000 / 500 = 204
+        let ratio = engine.margin_ratio();
+        assert_eq!(ratio, Decimal::from(204));
+    }
+
+    #[test]
+    fn test_risk_level_healthy() {
+        let engine = make_engine();
+        assert_eq!(engine.risk_level(), RiskLevel::Healthy);
+    }
+
+    #[test]
+    fn test_risk_level_warning() {
+        let level = risk_level_from_ratio(Decimal::from_str_exact("1.8").unwrap());
+        assert_eq!(level, RiskLevel::Warning);
+    }
+
+    #[test]
+    fn test_risk_level_danger() {
+        let level = risk_level_from_ratio(Decimal::from_str_exact("1.3").unwrap());
+        assert_eq!(level, RiskLevel::Danger);
+    }
+
+    #[test]
+    fn test_risk_level_liquidation() {
+        let level = risk_level_from_ratio(Decimal::from_str_exact("1.05").unwrap());
+        assert_eq!(level, RiskLevel::Liquidation);
+    }
+
+    #[test]
+    fn test_simulate_order() {
+        let engine = make_engine();
+
+        let preview = engine.simulate_order(
+            "ETH/USDT",
+            Side::BUY,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            20,
+        );
+
+        // New notional = 3000 × 10 = 30 000
+        // New IM = 30 000 / 20 = 1 500
+        // Total IM = 10 000 + 1 500 = 11 500
+        assert_eq!(preview.margin_used_after, Decimal::from(11_500));
+
+        // equity_after still = 102 000 (mark hasn't moved)
+        assert_eq!(preview.equity_after, Decimal::from(102_000));
+
+        assert!(!preview.has_negative_balance);
+        assert_eq!(preview.risk_level, RiskLevel::Healthy);
+    }
+
+    #[test]
+    fn test_simulate_order_negative_balance() {
+        let account_id = AccountId::new();
+        // Very small balance
+        let engine = CrossMarginEngine::new(account_id, Decimal::from(100));
+
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::BUY,
+            Price::from_u64(50_000),
+            Quantity::from_str("1.0").unwrap(),
+            10,
+        );
+
+        // IM = 5 000, balance = 100 → margin_available = 100 − 5000 < 0
+        assert!(preview.has_negative_balance);
+    }
+
+    #[test]
+    fn test_simulate_order_reduces_position() {
+        let engine = make_engine();
+        assert_eq!(engine.total_initial_margin(), Decimal::from(10_000));
+
+        // Long 2 BTC, sell 1 at the mark: IM halves, equity unchanged
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::SELL,
+            Price::from_u64(51_000),
+            Quantity::from_str("1.0").unwrap(),
+            10,
+        );
+        assert_eq!(preview.margin_used_after, Decimal::from(5_000));
+        assert_eq!(preview.equity_after, Decimal::from(102_000));
+        // MM on the remaining 1 BTC = 250; 50000 − (5000 − 250) = 45 250
+        assert_eq!(preview.margin_ratio_after, Decimal::from(408));
+        assert_eq!(preview.liquidation_price, Decimal::from(45_250));
+
+        // Selling above the mark realizes the difference on the closed size
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::SELL,
+            Price::from_u64(52_000),
+            Quantity::from_str("1.0").unwrap(),
+            10,
+        );
+        // 100 000 + 2 000 realized + 1 000 unrealized on the remainder
+        assert_eq!(preview.equity_after, Decimal::from(103_000));
+    }
+
+    #[test]
+    fn test_simulate_order_closes_position() {
+        let engine = make_engine();
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::SELL,
+            Price::from_u64(51_000),
+            Quantity::from_str("2.0").unwrap(),
+            10,
+        );
+        assert_eq!(preview.margin_used_after, Decimal::ZERO);
+        assert_eq!(preview.margin_available_after, Decimal::from(102_000));
+        assert_eq!(preview.margin_ratio_after, Decimal::MAX);
+        assert_eq!(preview.liquidation_price, Decimal::ZERO);
+        assert_eq!(preview.leverage_ratio, Decimal::ZERO);
+        assert_eq!(preview.risk_level, RiskLevel::Healthy);
+    }
+
+    #[test]
+    fn test_simulate_order_flips_position() {
+        let engine = make_engine();
+
+        // Long 2, sell 5: closes 2 (realizing +2 000) and opens short 3 @ 51 000
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::SELL,
+            Price::from_u64(51_000),
+            Quantity::from_str("5.0").unwrap(),
+            10,
+        );
+        // IM = 153 000 / 10
+        assert_eq!(preview.margin_used_after, Decimal::from(15_300));
+        assert_eq!(preview.equity_after, Decimal::from(102_000));
+        // MM = 153 000 × 0.005 = 765
+        assert_eq!(preview.margin_ratio_after, Decimal::from_str_exact("133.33333333").unwrap());
+        // 51000 × (1 + 0.1 − 0.005) = 55 845
+        assert_eq!(preview.liquidation_price, Decimal::from(55_845));
+    }
+
+    #[test]
+    fn test_simulate_order_extends_position_at_blended_entry() {
+        let engine = make_engine();
+
+        // Long 2 @ 50 000 plus 2 @ 52 000: entry 51 000, IM 10 000 + 10 400
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::BUY,
+            Price::from_u64(52_000),
+            Quantity::from_str("2.0").unwrap(),
+            10,
+        );
+        assert_eq!(preview.margin_used_after, Decimal::from(20_400));
+        // MM = 500 + 520; 51000 − (20400 − 1020) / 4 = 46 155
+        assert_eq!(preview.liquidation_price, Decimal::from(46_155));
+    }
+
+    #[test]
+    fn test_liquidation_price_long() {
+        let liq = compute_liquidation_price(
+            PositionSide::LONG,
+            Decimal::from(50_000),
+            10,
+            Decimal::from_str_exact("0.005").unwrap(),
+        );
+        // entry × (1 − 1/10 + 0.005) = 50000 × 0.905 = 45 250
+        assert_eq!(round_display(liq), Decimal::from(45_250));
+    }
+
+    #[test]
+    fn test_liquidation_price_short() {
+        let liq = compute_liquidation_price(
+            PositionSide::SHORT,
+            Decimal::from(50_000),
+            10,
+            Decimal::from_str_exact("0.005").unwrap(),
+        );
+        // entry × (1 + 1/10 − 0.005) = 50000 × 1.095 = 54 750
+        assert_eq!(round_display(liq), Decimal::from(54_750));
+    }
+
+    #[test]
+    fn test_leverage_ratio() {
+        let engine = make_engine();
+        let preview = engine.simulate_order(
+            "ETH/USDT",
+            Side::BUY,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            20,
+        );
+
+        // Total notional = 100 000 (existing) + 30 000 (new) = 130 000
+        // equity = 102 000
+        // leverage_ratio = 130 000 / 102 000 ≈ 1.27
+        assert!(preview.leverage_ratio > Decimal::ONE);
+        assert!(preview.leverage_ratio < Decimal::TWO);
+    }
+
+    #[test]
+    fn test_maintenance_margin_rate_tiers() {
+        assert_eq!(maintenance_margin_rate(5), Decimal::from_str_exact("0.005").unwrap());
+        assert_eq!(maintenance_margin_rate(15), Decimal::from_str_exact("0.01").unwrap());
+        assert_eq!(maintenance_margin_rate(30), Decimal::from_str_exact("0.02").unwrap());
+        assert_eq!(maintenance_margin_rate(75), Decimal::from_str_exact("0.05").unwrap());
+        assert_eq!(maintenance_margin_rate(110), Decimal::from_str_exact("0.10").unwrap());
+    }
+
+    #[test]
+    fn test_cross_margin_shared_collateral() {
+        let account_id = AccountId::new();
+        let mut engine = CrossMarginEngine::new(account_id, Decimal::from(50_000));
+
+        // Two positions sharing the same collateral pool
+        let pos1 = Position::new(
+            account_id,
+            MarketId::new("BTC/USDT"),
+            PositionSide::LONG,
+            Quantity::from_str("1.0").unwrap(),
+            Price::from_u64(50_000),
+            Price::from_u64(51_000),
+            Price::from_u64(49_500),
+            Decimal::from(5_000),
+            Decimal::from(250),
+            10,
+            1_708_123_456_789_000_000,
+        );
+        engine.add_position(pos1);
+
+        let pos2 = Position::new(
+            account_id,
+            MarketId::new("ETH/USDT"),
+            PositionSide::SHORT,
+            Quantity::from_str("10.0").unwrap(),
+            Price::from_u64(3_000),
+            Price::from_u64(2_900),
+            Price::from_u64(3_100),
+            Decimal::from(3_000),
+            Decimal::from(150),
+            10,
+            1_708_123_456_789_000_000,
+        );
+        engine.add_position(pos2);
+
+        // uPnL = +1000 (BTC) + +1000 (ETH) = 2000
+        assert_eq!(engine.total_unrealized_pnl(), Decimal::from(2_000));
+
+        // equity = 50 000 + 2 000 = 52 000
+        assert_eq!(engine.equity(), Decimal::from(52_000));
+
+        // total IM = 5000 + 3000 = 8000 (shared pool)
+        assert_eq!(engine.total_initial_margin(), Decimal::from(8_000));
+    }
+
+    #[test]
+    fn test_deterministic_simulation() {
+        let engine = make_engine();
+        let p1 = engine.simulate_order(
+            "ETH/USDT",
+            Side::BUY,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            20,
+        );
+        let p2 = engine.simulate_order(
+            "ETH/USDT",
+            Side::BUY,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            20,
+        );
+        assert_eq!(p1, p2, "Simulation must be deterministic");
+    }
+
+    #[test]
+    fn test_accrued_funding_reduces_equity() {
+        let mut engine = make_engine();
+        engine.set_accrued_funding("BTC/USDT", Decimal::from(400));
+        // 100 000 + 2 000 − 400
+        assert_eq!(engine.equity(), Decimal::from(101_600));
+        // MM 500
+        assert_eq!(engine.margin_ratio(), Decimal::from_str_exact("203.2").unwrap());
+
+        // Closing the position does not clear what it owes
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::SELL,
+            Price::from_u64(51_000),
+            Quantity::from_str("2.0").unwrap(),
+            10,
+        );
+        assert_eq!(preview.equity_after, Decimal::from(101_600));
+
+        // Funding received adds to equity
+        engine.set_accrued_funding("BTC/USDT", Decimal::from(-100));
+        assert_eq!(engine.equity(), Decimal::from(102_100));
+    }
+
+    #[test]
+    fn test_isolated_accrued_funding_moves_liquidation_price() {
+        let account_id = AccountId::new();
+        let mut engine = IsolatedMarginEngine::new(account_id, Decimal::from(10_000));
+        engine.add_position(
+            isolated_position(account_id, "BTC/USDT", PositionSide::LONG, "1.0", 50_000, 50_000),
+            Decimal::from(5_000),
+        );
+        engine.set_accrued_funding("BTC/USDT", Decimal::from(50)).unwrap();
+
+        let preview = engine.position_preview("BTC/USDT").unwrap();
+        assert_eq!(preview.equity_after, Decimal::from(4_950));
+        // 50000 − (4950 − 250) = 45 300
+        assert_eq!(preview.liquidation_price, Decimal::from(45_300));
+        assert_eq!(
+            engine.set_accrued_funding("ETH/USDT", Decimal::ONE),
+            Err(IsolatedMarginError::UnknownPosition("ETH/USDT".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_empty_engine() {
+        let engine = CrossMarginEngine::new(AccountId::new(), Decimal::from(10_000));
+        assert_eq!(engine.equity(), Decimal::from(10_000));
+        assert_eq!(engine.total_unrealized_pnl(), Decimal::ZERO);
+        assert_eq!(engine.total_initial_margin(), Decimal::ZERO);
+        assert_eq!(engine.risk_level(), RiskLevel::Healthy);
+    }
+
+    fn isolated_position(
+        account_id: AccountId,
+        symbol: &str,
+        side: PositionSide,
+        size: &str,
+        entry: u64,
+        mark: u64,
+    ) -> Position {
+        let size = Quantity::from_str(size).unwrap();
+        let im = Decimal::from(entry) * size.as_decimal() / Decimal::from(10);
+        Position::new(
+            account_id,
+            MarketId::new(symbol),
+            side,
+            size,
+            Price::from_u64(entry),
+            Price::from_u64(mark),
+            Price::from_u64(entry),
+            im,
+            Decimal::ZERO,
+            10,
+            1_708_123_456_789_000_000,
+        )
+    }
+
+    #[test]
+    fn test_cross_vs_isolated_liquidation_price() {
+        // Same account in both modes: 100k balance, long 2 BTC down 4 000
+        let account_id = AccountId::new();
+        let existing = isolated_position(account_id, "BTC/USDT", PositionSide::LONG, "2.0", 50_000, 48_000);
+        let mut cross = CrossMarginEngine::new(account_id, Decimal::from(100_000));
+        cross.add_position(existing.clone());
+        let mut isolated = IsolatedMarginEngine::new(account_id, Decimal::from(100_000));
+        isolated.add_position(existing, Decimal::from(10_000));
+
+        let (price, qty) = (Price::from_u64(3_000), Quantity::from_str("10.0").unwrap());
+        let cross_preview = cross.simulate_order("ETH/USDT", Side::BUY, price, qty, 10);
+        let isolated_preview = isolated.simulate_order("ETH/USDT", Side::BUY, price, qty, 10);
+
+        // A fresh pool funded with exactly its IM liquidates where the
+        // order-only estimate says: 3000 × (1 − 0.1 + 0.005) = 2 715
+        assert_eq!(cross_preview.liquidation_price, Decimal::from(2_715));
+        assert_eq!(isolated_preview.liquidation_price, Decimal::from(2_715));
+
+        // Cross draws on the whole account; isolated sees only the new
+        // position: equity 3 000 (its IM), MM 150, ratio 20
+        assert_eq!(cross_preview.equity_after, Decimal::from(96_000));
+        assert_eq!(isolated_preview.equity_after, Decimal::from(3_000));
+        assert_eq!(isolated_preview.margin_used_after, Decimal::from(3_000));
+        assert_eq!(isolated_preview.margin_available_after, Decimal::ZERO);
+        assert_eq!(isolated_preview.margin_ratio_after, Decimal::from(20));
+        assert_eq!(isolated_preview.leverage_ratio, Decimal::from(10));
+
+        // Extra margin moves only the isolated liquidation price of that position
+        let mut funded = isolated.clone();
+        funded.add_position(
+            isolated_position(account_id, "ETH/USDT", PositionSide::LONG, "10.0", 3_000, 3_000),
+            Decimal::from(3_000),
+        );
+        let before = funded.position_preview("ETH/USDT").unwrap();
+        assert_eq!(before.liquidation_price, isolated_preview.liquidation_price);
+        funded.allocate_margin("ETH/USDT", Decimal::from(1_000)).unwrap();
+        // 3000 − (4000 − 150) / 10 = 2 615
+        assert_eq!(funded.position_preview("ETH/USDT").unwrap().liquidation_price, Decimal::from(2_615));
+        assert_eq!(
+            funded.position_preview("BTC/USDT").unwrap(),
+            isolated.position_preview("BTC/USDT").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_isolated_losing_position_liquidates_on_its_own_margin() {
+        let account_id = AccountId::new();
+        let mut engine = IsolatedMarginEngine::new(account_id, Decimal::from(1_000_000));
+        engine.add_position(
+            isolated_position(account_id, "BTC/USDT", PositionSide::LONG, "1.0", 50_000, 48_000),
+            Decimal::from(5_000),
+        );
+
+        // A large free balance does not help: equity 5000 − 2000 = 3000, MM 250
+        let preview = engine.position_preview("BTC/USDT").unwrap();
+        assert_eq!(preview.equity_after, Decimal::from(3_000));
+        assert_eq!(preview.margin_ratio_after, Decimal::from(12));
+        assert_eq!(preview.margin_available_after, Decimal::from(-2_000));
+        assert!(preview.has_negative_balance);
+        // 50000 − (5000 − 250) = 45 250
+        assert_eq!(preview.liquidation_price, Decimal::from(45_250));
+    }
+
+    #[test]
+    fn test_allocate_and_reduce_margin() {
+        let account_id = AccountId::new();
+        let mut engine = IsolatedMarginEngine::new(account_id, Decimal::from(20_000));
+        engine.add_position(
+            isolated_position(account_id, "BTC/USDT", PositionSide::SHORT, "1.0", 50_000, 50_000),
+            Decimal::from(5_000),
+        );
+        assert_eq!(engine.free_balance(), Decimal::from(15_000));
+
+        assert_eq!(engine.allocate_margin("BTC/USDT", Decimal::from(2_500)), Ok(Decimal::from(7_500)));
+        assert_eq!(engine.free_balance(), Decimal::from(12_500));
+        // SHORT: 50000 + (7500 − 250) = 57 250
+        assert_eq!(engine.position_preview("BTC/USDT").unwrap().liquidation_price, Decimal::from(57_250));
+
+        assert_eq!(
+            engine.allocate_margin("BTC/USDT", Decimal::from(20_000)),
+            Err(IsolatedMarginError::InsufficientBalance {
+                requested: Decimal::from(20_000),
+                available: Decimal::from(12_500),
+            })
+        );
+
+        // Only margin above the 5 000 IM can be withdrawn
+        assert_eq!(
+            engine.reduce_margin("BTC/USDT", Decimal::from(3_000)),
+            Err(IsolatedMarginError::InsufficientMargin {
+                requested: Decimal::from(3_000),
+                available: Decimal::from(2_500),
+            })
+        );
+        assert_eq!(engine.reduce_margin("BTC/USDT", Decimal::from(2_500)), Ok(Decimal::from(5_000)));
+        assert_eq!(engine.free_balance(), Decimal::from(15_000));
+
+        assert_eq!(
+            engine.reduce_margin("ETH/USDT", Decimal::ONE),
+            Err(IsolatedMarginError::UnknownPosition("ETH/USDT".to_string()))
+        );
+        assert_eq!(
+            engine.allocate_margin("BTC/USDT", Decimal::ZERO),
+            Err(IsolatedMarginError::NonPositiveAmount(Decimal::ZERO))
+        );
+    }
+
+    #[test]
+    fn test_isolated_order_extends_same_side_pool() {
+        let account_id = AccountId::new();
+        let mut engine = IsolatedMarginEngine::new(account_id, Decimal::from(10_000));
+        engine.add_position(
+            isolated_position(account_id, "ETH/USDT", PositionSide::SHORT, "10.0", 3_000, 3_000),
+            Decimal::from(3_000),
+        );
+
+        let preview = engine.simulate_order(
+            "ETH/USDT",
+            Side::SELL,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            10,
+        );
+        // 20 ETH short, margin 6 000, MM 300: 3000 + 5700 / 20 = 3 285
+        assert_eq!(preview.margin_used_after, Decimal::from(6_000));
+        assert_eq!(preview.liquidation_price, Decimal::from(3_285));
+        assert!(!preview.has_negative_balance);
+
+        // The order's IM must come out of the free balance (7 000 here)
+        let preview = engine.simulate_order(
+            "BTC/USDT",
+            Side::BUY,
+            Price::from_u64(50_000),
+            Quantity::from_str("2.0").unwrap(),
+            10,
+        );
+        assert!(preview.has_negative_balance);
+    }
+
+    #[test]
+    fn test_margin_preview_serialization() {
+        let engine = make_engine();
+        let preview = engine.simulate_order(
+            "ETH/USDT",
+            Side::BUY,
+            Price::from_u64(3_000),
+            Quantity::from_str("10.0").unwrap(),
+            20,
+        );
+        let json = serde_json::to_string(&preview).unwrap();
+        let restored: MarginPreview = serde_json::from_str(&json).unwrap();
+        assert_eq!(preview, restored);
+    }
+
+    // -- stress testing ------------------------------------------------------
+
+    fn stress_engine() -> CrossMarginEngine {
+        let account_id = AccountId::new();
+        let mut engine = CrossMarginEngine::new(account_id, Decimal::from(19_600));
+        let position = |symbol: &str, side, size: &str, price: u64, liq: u64, leverage| {
+            Position::new(
+                account_id,
+                MarketId::new(symbol),
+                side,
+                Quantity::from_str(size).unwrap(),
+                Price::from_u64(price),
+                Price::from_u64(price),
+                Price::from_u64(liq),
+                Decimal::from(price) * Decimal::from_str_exact(size).unwrap() / Decimal::from(leverage),
+                Decimal::ZERO,
+                leverage,
+                1_708_123_456_789_000_000,
+            )
+        };
+        // Long 1 BTC @ 50 000 (5x, liq 41 000), long 10 ETH @ 3 000 (10x,
+        // liq 2 715), short 100 SOL @ 100 (5x, liq 119)
+        engine.add_position(position("BTC/USDT", PositionSide::LONG, "1.0", 50_000, 41_000, 5));
+        engine.add_position(position("ETH/USDT", PositionSide::LONG, "10.0", 3_000, 2_715, 10));
+        engine.add_position(position("SOL/USDT", PositionSide::SHORT, "100.0", 100, 119, 5));
+        engine
+    }
+
+    #[test]
+    fn test_stress_btc_and_eth_drop() {
+        let engine = stress_engine();
+        let scenario = BTreeMap::from([
+            ("BTC/USDT".to_string(), Decimal::from(-20)),
+            ("ETH/USDT".to_string(), Decimal::from(-30)),
+        ]);
+        let result = engine.stress(&scenario);
+
+        // BTC −10 000, ETH −9 000; SOL unchanged
+        assert_eq!(result.equity, Decimal::from(600));
+        // MM = 50 000 × 0.5% + 30 000 × 0.5% + 10 000 × 0.5% = 450
+        assert_eq!(result.margin_ratio, round_display(Decimal::from(600) / Decimal::from(450)));
+        assert_eq!(result.risk_level, RiskLevel::Danger);
+        // BTC at 40 000 is through 41 000, ETH at 2 100 through 2 715
+        assert_eq!(result.liquidating, vec!["BTC/USDT", "ETH/USDT"]);
+        assert_eq!(
+            result.worst_contributor,
+            Some(StressContributor { symbol: "BTC/USDT".to_string(), pnl_change: Decimal::from(-10_000) })
+        );
+
+        // The engine itself is untouched
+        assert_eq!(engine.equity(), Decimal::from(19_600));
+    }
+
+    #[test]
+    fn test_stress_short_squeeze_and_no_loss() {
+        let engine = stress_engine();
+        let result = engine.stress(&BTreeMap::from([("SOL/USDT".to_string(), Decimal::from(25))]));
+        assert_eq!(result.equity, Decimal::from(17_100));
+        assert_eq!(result.liquidating, vec!["SOL/USDT"]);
+        assert_eq!(result.worst_contributor.unwrap().symbol, "SOL/USDT");
+
+        // A rally helps the longs; nothing loses
+        let result = engine.stress(&BTreeMap::from([("BTC/USDT".to_string(), Decimal::from(10))]));
+        assert_eq!(result.equity, Decimal::from(24_600));
+        assert!(result.liquidating.is_empty());
+        assert_eq!(result.worst_contributor, None);
+
+        // Unknown symbols are ignored; −150% floors the mark at zero
+        let result = engine.stress(&BTreeMap::from([
+            ("DOGE/USDT".to_string(), Decimal::from(-50)),
+            ("ETH/USDT".to_string(), Decimal::from(-150)),
+        ]));
+        assert_eq!(result.equity, Decimal::from(-10_400));
+        assert_eq!(result.risk_level, RiskLevel::Liquidation);
+    }
+
+    #[test]
+    fn test_stress_grid_curve() {
+        let engine = stress_engine();
+        let curve = engine.stress_grid("BTC/USDT", Decimal::from(-40), Decimal::ZERO, Decimal::from(10));
+        let shocks: Vec<_> = curve.iter().map(|p| p.shock_pct).collect();
+        assert_eq!(shocks, vec![
+            Decimal::from(-40), Decimal::from(-30), Decimal::from(-20), Decimal::from(-10), Decimal::ZERO,
+        ]);
+        // Each 10% of BTC is 5 000 of equity, ratio monotone in the shock
+        assert!(curve.windows(2).all(|w| w[0].margin_ratio < w[1].margin_ratio));
+        assert_eq!(curve[4].margin_ratio, engine.margin_ratio());
+        assert_eq!(curve[0].risk_level, RiskLevel::Liquidation);
+
+        assert!(engine.stress_grid("BTC/USDT", Decimal::ZERO, Decimal::from(10), Decimal::ZERO).is_empty());
+        assert!(engine.stress_grid("BTC/USDT", Decimal::from(10), Decimal::ZERO, Decimal::ONE).is_empty());
+    }
+
+    #[test]
+    fn test_stress_result_serialization_and_determinism() {
+        let engine = stress_engine();
+        let scenario = BTreeMap::from([("BTC/USDT".to_string(), Decimal::from_str_exact("-12.5").unwrap())]);
+        let result = engine.stress(&scenario);
+        assert_eq!(result, engine.stress(&scenario));
+
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: StressResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, restored);
+        let curve = engine.stress_grid("ETH/USDT", Decimal::from(-10), Decimal::from(10), Decimal::from(5));
+        let json = serde_json::to_string(&curve).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<StressPoint>>(&json).unwrap(), curve);
+    }
+
+    // -- multi-asset collateral ----------------------------------------------
+
+    fn collateral_engine() -> CrossMarginEngine {
+        let d = |v: &str| Decimal::from_str_exact(v).unwrap();
+        let balances = BTreeMap::from([
+            ("BTC".to_string(), d("1")),
+            ("DOGE".to_string(), d("1000")),
+            ("ETH".to_string(), d("10")),
+            ("LUNA".to_string(), d("100")),
+            ("USDT".to_string(), d("10000")),
+        ]);
+        let config = BTreeMap::from([
+            ("BTC".to_string(), CollateralAsset { price: d("50000"), haircut: d("0.1") }),
+            ("ETH".to_string(), CollateralAsset { price: d("3000"), haircut: d("0.2") }),
+            ("LUNA".to_string(), CollateralAsset { price: d("5"), haircut: Decimal::ONE }),
+            ("USDT".to_string(), CollateralAsset { price: Decimal::ONE, haircut: Decimal::ZERO }),
+        ]);
+        let mut engine = CrossMarginEngine::with_collateral(AccountId::new(), balances, config);
+        for pos in make_engine().positions.into_values() {
+            engine.add_position(pos);
+        }
+        engine
+    }
+
+    #[test]
+    fn test_collateral_haircut_valuation() {
+        let engine = collateral_engine();
+        // 45 000 BTC + 24 000 ETH + 10 000 USDT; LUNA fully haircut, DOGE unpriced
+        assert_eq!(engine.collateral_value(), Decimal::from(79_000));
+        assert_eq!(engine.unpriced_collateral(), vec!["DOGE"]);
+        // + 2 000 uPnL
+        assert_eq!(engine.equity(), Decimal::from(81_000));
+        assert_eq!(engine.margin_available(), Decimal::from(71_000));
+        assert_eq!(engine.margin_ratio(), Decimal::from(162));
+    }
+
+    #[test]
+    fn test_collateral_preview_matches_single_balance() {
+        let engine = collateral_engine();
+        let preview = engine.simulate_order("ETH/USDT", Side::BUY, Price::from_u64(3_000), Quantity::from_str("5.0").unwrap(), 10);
+
+        // The same collateral value held as one quote balance previews identically
+        let mut single = make_engine();
+        single.total_balance = Decimal::from(79_000);
+        let expected = single.simulate_order("ETH/USDT", Side::BUY, Price::from_u64(3_000), Quantity::from_str("5.0").unwrap(), 10);
+        assert!(expected.unpriced_collateral.is_empty());
+        assert_eq!(preview, MarginPreview { unpriced_collateral: vec!["DOGE".to_string()], ..expected });
+    }
+
+    #[test]
+    fn test_collateral_serialization_defaults() {
+        let engine = collateral_engine();
+        let json = serde_json::to_string(&engine).unwrap();
+        let restored: CrossMarginEngine = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.equity(), engine.equity());
+
+        // Snapshots without collateral fields load as quote-only
+        let mut value = serde_json::to_value(make_engine()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("collateral");
+        object.remove("collateral_config");
+        let legacy: CrossMarginEngine = serde_json::from_value(value).unwrap();
+        assert_eq!(legacy.equity(), Decimal::from(102_000));
+    }
+
+    // -- existing position liquidation price --------------------------------
+
+    #[test]
+    fn test_liquidation_price_for_hand_computed() {
+        let engine = stress_engine();
+        // MM = 450, threshold equity = 495; the other positions are flat, so
+        // each position can lose 19 600 − 495 = 19 105 before liquidation
+        assert_eq!(engine.liquidation_price_for("BTC/USDT"), Some(Decimal::from(30_895)));
+        assert_eq!(engine.liquidation_price_for("ETH/USDT"), Some(Decimal::from_str_exact("1089.5").unwrap()));
+        assert_eq!(engine.liquidation_price_for("SOL/USDT"), Some(Decimal::from_str_exact("291.05").unwrap()));
+        assert_eq!(engine.liquidation_price_for("DOGE/USDT"), None);
+
+        // At that mark the account sits exactly on the threshold
+        let shock = BTreeMap::from([("BTC/USDT".to_string(), Decimal::from_str_exact("-38.21").unwrap())]);
+        assert_eq!(engine.stress(&shock).margin_ratio, Decimal::from_str_exact("1.1").unwrap());
+    }
+
+    #[test]
+    fn test_liquidation_price_for_counts_other_positions_pnl() {
+        let mut engine = stress_engine();
+        // ETH 500 under water eats into BTC's buffer
+        engine.positions.get_mut("ETH/USDT").unwrap().mark_price = Price::from_u64(2_950);
+        assert_eq!(engine.liquidation_price_for("BTC/USDT"), Some(Decimal::from(31_395)));
+        // ETH's own PnL does not move its liquidation price
+        assert_eq!(engine.liquidation_price_for("ETH/USDT"), Some(Decimal::from_str_exact("1089.5").unwrap()));
+    }
+
+    #[test]
+    fn test_liquidation_price_for_moves_away_with_collateral() {
+        let mut engine = stress_engine();
+        let long_before = engine.liquidation_price_for("BTC/USDT").unwrap();
+        let short_before = engine.liquidation_price_for("SOL/USDT").unwrap();
+
+        engine.total_balance += Decimal::from(1_000);
+        assert_eq!(engine.liquidation_price_for("BTC/USDT").unwrap(), long_before - Decimal::from(1_000));
+        assert_eq!(engine.liquidation_price_for("SOL/USDT").unwrap(), short_before + Decimal::from(10));
+
+        // Enough collateral and a long never liquidates above zero
+        engine.total_balance = Decimal::from(1_000_000);
+        assert_eq!(engine.liquidation_price_for("BTC/USDT"), Some(Decimal::ZERO));
+    }
+}
@@ -0,0 +1,410 @@
+//! Metrics Core — counters, gauges and histograms for the services
+//!
+//! A [`Registry`] owns metric families and renders them in the Prometheus
+//! text exposition format (version 0.0.4) for a `/metrics` endpoint. Each
+//! service builds its own registry rather than using process globals, so a
+//! test can create one per case and assert exact values.
+//!
+//! Handles ([`Counter`], [`Gauge`], [`Histogram`]) are cheap to clone and
+//! safe to share between threads. Label values are passed on every call, in
+//! the order of the label names the family was registered with.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::{Arc, Mutex};
+
+/// `Content-Type` of [`Registry::render`] output.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Request latency buckets in seconds, 1ms to 10s.
+pub const DEFAULT_LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+// ---------------------------------------------------------------------------
+// Families
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram => "histogram",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Series {
+    Counter(u64),
+    Gauge(i64),
+    Histogram {
+        /// Observations per bucket, not cumulative
+        buckets: Vec<u64>,
+        sum: f64,
+        count: u64,
+    },
+}
+
+/// One metric name and all its labelled series.
+#[derive(Debug)]
+struct Family {
+    name: String,
+    help: String,
+    kind: Kind,
+    labels: Vec<String>,
+    /// Histogram upper bounds, ascending
+    bounds: Vec<f64>,
+    series: Mutex<BTreeMap<Vec<String>, Series>>,
+}
+
+impl Family {
+    fn empty(&self) -> Series {
+        match self.kind {
+            Kind::Counter => Series::Counter(0),
+            Kind::Gauge => Series::Gauge(0),
+            Kind::Histogram => Series::Histogram {
+                buckets: vec![0; self.bounds.len()],
+                sum: 0.0,
+                count: 0,
+            },
+        }
+    }
+
+    fn key(&self, values: &[&str]) -> Vec<String> {
+        assert_eq!(
+            values.len(),
+            self.labels.len(),
+            "{} takes labels {:?}",
+            self.name,
+            self.labels
+        );
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn update<R>(&self, values: &[&str], f: impl FnOnce(&mut Series) -> R) -> R {
+        let key = self.key(values);
+        let mut series = self.series.lock().unwrap();
+        f(series.entry(key).or_insert_with(|| self.empty()))
+    }
+
+    fn read<R>(&self, values: &[&str], f: impl FnOnce(&Series) -> R) -> R {
+        let key = self.key(values);
+        let series = self.series.lock().unwrap();
+        match series.get(&key) {
+            Some(found) => f(found),
+            None => f(&self.empty()),
+        }
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, escape(&self.help, false));
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind.as_str());
+        for (values, series) in self.series.lock().unwrap().iter() {
+            let labels: Vec<(&str, &str)> =
+                self.labels.iter().map(String::as_str).zip(values.iter().map(String::as_str)).collect();
+            match series {
+                Series::Counter(value) => sample(out, &self.name, &labels, None, value),
+                Series::Gauge(value) => sample(out, &self.name, &labels, None, value),
+                Series::Histogram { buckets, sum, count } => {
+                    let name = format!("{}_bucket", self.name);
+                    let mut cumulative = 0;
+                    for (bound, observed) in self.bounds.iter().zip(buckets) {
+                        cumulative += observed;
+                        let le = bound.to_string();
+                        sample(out, &name, &labels, Some(&le), cumulative);
+                    }
+                    sample(out, &name, &labels, Some("+Inf"), count);
+                    sample(out, &format!("{}_sum", self.name), &labels, None, sum);
+                    sample(out, &format!("{}_count", self.name), &labels, None, count);
+                }
+            }
+        }
+    }
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], le: Option<&str>, value: impl Display) {
+    out.push_str(name);
+    if !labels.is_empty() || le.is_some() {
+        let pairs: Vec<String> = labels
+            .iter()
+            .copied()
+            .chain(le.map(|le| ("le", le)))
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape(value, true)))
+            .collect();
+        let _ = write!(out, "{{{}}}", pairs.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+/// Escape backslashes and newlines, and double quotes in label values.
+fn escape(text: &str, quotes: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '"' if quotes => escaped.push_str("\\\""),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+/// The metric families one service exposes, rendered in registration order.
+#[derive(Debug, Default)]
+pub struct Registry {
+    families: Mutex<Vec<Arc<Family>>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A monotonically increasing count.
+    pub fn counter(&self, name: &str, help: &str, labels: &[&str]) -> Counter {
+        Counter(self.register(name, help, Kind::Counter, labels, &[]))
+    }
+
+    /// A value that goes up and down.
+    pub fn gauge(&self, name: &str, help: &str, labels: &[&str]) -> Gauge {
+        Gauge(self.register(name, help, Kind::Gauge, labels, &[]))
+    }
+
+    /// A distribution of observations over ascending `buckets` upper bounds.
+    pub fn histogram(&self, name: &str, help: &str, labels: &[&str], buckets: &[f64]) -> Histogram {
+        assert!(
+            buckets.windows(2).all(|w| w[0] < w[1]),
+            "{} buckets must be ascending",
+            name
+        );
+        Histogram(self.register(name, help, Kind::Histogram, labels, buckets))
+    }
+
+    fn register(&self, name: &str, help: &str, kind: Kind, labels: &[&str], bounds: &[f64]) -> Arc<Family> {
+        let family = Arc::new(Family {
+            name: name.to_string(),
+            help: help.to_string(),
+            kind,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            bounds: bounds.to_vec(),
+            series: Mutex::new(BTreeMap::new()),
+        });
+        // An unlabelled metric is exported as zero before its first update
+        if labels.is_empty() {
+            family.series.lock().unwrap().insert(Vec::new(), family.empty());
+        }
+        let mut families = self.families.lock().unwrap();
+        assert!(
+            families.iter().all(|f| f.name != name),
+            "metric {} registered twice",
+            name
+        );
+        families.push(family.clone());
+        family
+    }
+
+    /// Every family in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for family in self.families.lock().unwrap().iter() {
+            family.render(&mut out);
+        }
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handles
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct Counter(Arc<Family>);
+
+impl Counter {
+    pub fn inc(&self, labels: &[&str]) {
+        self.inc_by(labels, 1);
+    }
+
+    pub fn inc_by(&self, labels: &[&str], n: u64) {
+        self.0.update(labels, |series| {
+            if let Series::Counter(value) = series {
+                *value += n;
+            }
+        });
+    }
+
+    /// Overwrite the count with a total kept elsewhere, such as a writer's
+    /// own statistics.
+    pub fn set(&self, labels: &[&str], total: u64) {
+        self.0.update(labels, |series| *series = Series::Counter(total));
+    }
+
+    pub fn get(&self, labels: &[&str]) -> u64 {
+        self.0.read(labels, |series| match series {
+            Series::Counter(value) => *value,
+            _ => 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Gauge(Arc<Family>);
+
+impl Gauge {
+    pub fn set(&self, labels: &[&str], value: i64) {
+        self.0.update(labels, |series| *series = Series::Gauge(value));
+    }
+
+    pub fn add(&self, labels: &[&str], delta: i64) {
+        self.0.update(labels, |series| {
+            if let Series::Gauge(value) = series {
+                *value += delta;
+            }
+        });
+    }
+
+    pub fn get(&self, labels: &[&str]) -> i64 {
+        self.0.read(labels, |series| match series {
+            Series::Gauge(value) => *value,
+            _ => 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<Family>);
+
+impl Histogram {
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        let bucket = self.0.bounds.iter().position(|bound| value <= *bound);
+        self.0.update(labels, |series| {
+            if let Series::Histogram { buckets, sum, count } = series {
+                if let Some(bucket) = bucket {
+                    buckets[bucket] += 1;
+                }
+                *sum += value;
+                *count += 1;
+            }
+        });
+    }
+
+    /// Number of observations recorded.
+    pub fn count(&self, labels: &[&str]) -> u64 {
+        self.0.read(labels, |series| match series {
+            Series::Histogram { count, .. } => *count,
+            _ => 0,
+        })
+    }
+}
+
+/// Samples of a rendered exposition, keyed by the series as written
+/// (`name{label="value"}`).
+///
+/// Meant for tests that scrape an endpoint and check what moved.
+pub fn samples(text: &str) -> BTreeMap<String, f64> {
+    text.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (series, value) = line.rsplit_once(' ')?;
+            Some((series.to_string(), value.parse().ok()?))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_gauges_render_with_help_and_labels() {
+        let registry = Registry::new();
+        let requests = registry.counter("http_requests_total", "Requests served.", &["route", "status"]);
+        let open = registry.gauge("ws_connections", "Open connections.", &[]);
+        requests.inc(&["/orders", "200"]);
+        requests.inc_by(&["/orders", "200"], 2);
+        requests.inc(&["/health", "200"]);
+
+        let text = registry.render();
+        assert_eq!(
+            text,
+            "# HELP http_requests_total Requests served.\n\
+             # TYPE http_requests_total counter\n\
+             http_requests_total{route=\"/health\",status=\"200\"} 1\n\
+             http_requests_total{route=\"/orders\",status=\"200\"} 3\n\
+             # HELP ws_connections Open connections.\n\
+             # TYPE ws_connections gauge\n\
+             ws_connections 0\n"
+        );
+
+        open.add(&[], 2);
+        open.add(&[], -1);
+        assert_eq!(open.get(&[]), 1);
+        assert_eq!(requests.get(&["/orders", "200"]), 3);
+        assert_eq!(requests.get(&["/orders", "500"]), 0);
+        requests.set(&["/orders", "500"], 7);
+        assert_eq!(samples(&registry.render())["http_requests_total{route=\"/orders\",status=\"500\"}"], 7.0);
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let registry = Registry::new();
+        let latency = registry.histogram("latency_seconds", "Latency.", &["route"], &[0.01, 0.1, 1.0]);
+        for value in [0.005, 0.01, 0.05, 0.5, 3.0] {
+            latency.observe(&["/orders"], value);
+        }
+        assert_eq!(latency.count(&["/orders"]), 5);
+
+        let samples = samples(&registry.render());
+        let bucket =
+            |le: &str| samples[&format!("latency_seconds_bucket{{route=\"/orders\",le=\"{}\"}}", le)];
+        assert_eq!(
+            [bucket("0.01"), bucket("0.1"), bucket("1"), bucket("+Inf")],
+            [2.0, 3.0, 4.0, 5.0]
+        );
+        assert_eq!(samples["latency_seconds_count{route=\"/orders\"}"], 5.0);
+        assert!((samples["latency_seconds_sum{route=\"/orders\"}"] - 3.565).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let registry = Registry::new();
+        let errors = registry.counter("errors_total", "Errors by \\ kind\nsplit.", &["kind"]);
+        errors.inc(&["say \"hi\"\n"]);
+        let text = registry.render();
+        assert!(text.contains("# HELP errors_total Errors by \\\\ kind\\nsplit.\n"));
+        assert!(text.contains("errors_total{kind=\"say \\\"hi\\\"\\n\"} 1\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "takes labels")]
+    fn test_wrong_label_count_panics() {
+        let registry = Registry::new();
+        registry.counter("orders_total", "Orders.", &["outcome"]).inc(&[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "registered twice")]
+    fn test_duplicate_names_panic() {
+        let registry = Registry::new();
+        registry.counter("orders_total", "Orders.", &[]);
+        registry.gauge("orders_total", "Orders.", &[]);
+    }
+}
@@ -0,0 +1,295 @@
+//! Shutdown Core — coordinated, deadline-bounded shutdown for the services
+//!
+//! A [`ShutdownCoordinator`] is shared by everything that must finish
+//! before the process exits. Work in flight (an HTTP request, a WebSocket
+//! session) holds a [`ShutdownGuard`] from [`ShutdownCoordinator::register`]
+//! and watches [`ShutdownGuard::requested`] to wind down. Components that
+//! need a final step, such as syncing a journal writer, add a hook with
+//! [`ShutdownCoordinator::on_shutdown`].
+//!
+//! [`ShutdownCoordinator::drain`] triggers shutdown, waits up to a deadline
+//! for every guard to drop, then runs the hooks in registration order. The
+//! hooks run even when the deadline passes, so buffered journal writes are
+//! synced whatever the state of the connections.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+
+/// Final step of a component: a summary to log, or why it failed.
+type Hook = Box<dyn FnOnce() -> Result<String, String> + Send>;
+
+struct Inner {
+    triggered: watch::Sender<bool>,
+    next_id: AtomicU64,
+    /// Name of every live guard, by registration id
+    active: Mutex<BTreeMap<u64, String>>,
+    /// Woken whenever a guard drops
+    released: Notify,
+    hooks: Mutex<Vec<(String, Hook)>>,
+}
+
+/// Coordinates a shutdown across the components registered with it.
+///
+/// Cheap to clone; clones share the same state.
+#[derive(Clone)]
+pub struct ShutdownCoordinator {
+    inner: Arc<Inner>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        let (triggered, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(Inner {
+                triggered,
+                next_id: AtomicU64::new(0),
+                active: Mutex::new(BTreeMap::new()),
+                released: Notify::new(),
+                hooks: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Hold off [`drain`](Self::drain) until the returned guard drops.
+    pub fn register(&self, name: impl Into<String>) -> ShutdownGuard {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        self.inner.active.lock().unwrap().insert(id, name.into());
+        ShutdownGuard {
+            id,
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Run `hook` once in-flight work has drained (or the deadline passed).
+    pub fn on_shutdown(
+        &self,
+        name: impl Into<String>,
+        hook: impl FnOnce() -> Result<String, String> + Send + 'static,
+    ) {
+        self.inner.hooks.lock().unwrap().push((name.into(), Box::new(hook)));
+    }
+
+    /// Start shutting down. Idempotent.
+    pub fn trigger(&self) {
+        self.inner.triggered.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.inner.triggered.borrow()
+    }
+
+    /// Resolves once shutdown is triggered, e.g. for a server's graceful
+    /// shutdown signal.
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + 'static {
+        wait_triggered(self.inner.triggered.subscribe())
+    }
+
+    /// Names of the guards still held.
+    pub fn active(&self) -> Vec<String> {
+        self.inner.active.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Trigger shutdown, wait up to `deadline` for every guard to drop,
+    /// then run the hooks.
+    pub async fn drain(&self, deadline: Duration) -> ShutdownReport {
+        self.trigger();
+        let drained = tokio::time::timeout(deadline, self.idle()).await.is_ok();
+        let abandoned = if drained { Vec::new() } else { self.active() };
+
+        let hooks = std::mem::take(&mut *self.inner.hooks.lock().unwrap());
+        let hooks = hooks
+            .into_iter()
+            .map(|(name, hook)| HookOutcome {
+                name,
+                result: hook(),
+            })
+            .collect();
+        ShutdownReport {
+            drained,
+            abandoned,
+            hooks,
+        }
+    }
+
+    async fn idle(&self) {
+        loop {
+            // Created before the check, so a release in between still wakes it
+            let released = self.inner.released.notified();
+            if self.inner.active.lock().unwrap().is_empty() {
+                return;
+            }
+            released.await;
+        }
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn wait_triggered(mut triggered: watch::Receiver<bool>) {
+    // The sender lives as long as the coordinator or any guard
+    let _ = triggered.wait_for(|triggered| *triggered).await;
+}
+
+/// Work in flight, counted by its coordinator until dropped.
+pub struct ShutdownGuard {
+    id: u64,
+    inner: Arc<Inner>,
+}
+
+impl ShutdownGuard {
+    /// Resolves once shutdown is triggered.
+    pub fn requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        wait_triggered(self.inner.triggered.subscribe())
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.inner.triggered.borrow()
+    }
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        self.inner.active.lock().unwrap().remove(&self.id);
+        self.inner.released.notify_waiters();
+    }
+}
+
+/// Result of a hook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOutcome {
+    pub name: String,
+    pub result: Result<String, String>,
+}
+
+/// How a drain went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShutdownReport {
+    /// Every guard dropped before the deadline
+    pub drained: bool,
+    /// Guards still held at the deadline
+    pub abandoned: Vec<String>,
+    /// Hook outcomes, in registration order
+    pub hooks: Vec<HookOutcome>,
+}
+
+impl ShutdownReport {
+    /// Drained in time and every hook succeeded.
+    pub fn is_clean(&self) -> bool {
+        self.drained && self.hooks.iter().all(|hook| hook.result.is_ok())
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.drained {
+            write!(f, "drained")?;
+        } else {
+            let abandoned = self.abandoned.join(", ");
+            write!(f, "deadline passed with {} still active: {}", self.abandoned.len(), abandoned)?;
+        }
+        for hook in &self.hooks {
+            match &hook.result {
+                Ok(summary) => write!(f, "; {}: {}", hook.name, summary)?,
+                Err(reason) => write!(f, "; {} failed: {}", hook.name, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM on Unix.
+pub async fn signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_guards_then_runs_hooks() {
+        let coordinator = ShutdownCoordinator::new();
+        let guard = coordinator.register("GET /slow");
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for name in ["first", "second"] {
+            let order = Arc::clone(&order);
+            coordinator.on_shutdown(name, move || {
+                order.lock().unwrap().push(name);
+                Ok(format!("{} done", name))
+            });
+        }
+
+        let worker = tokio::spawn(async move {
+            guard.requested().await;
+            // Finish the work in flight after being asked to stop
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            drop(guard);
+        });
+        let report = coordinator.drain(Duration::from_secs(5)).await;
+        worker.await.unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(*order.lock().unwrap(), ["first", "second"]);
+        assert_eq!(report.to_string(), "drained; first: first done; second: second done");
+        assert!(coordinator.active().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deadline_reports_stragglers_and_still_runs_hooks() {
+        let coordinator = ShutdownCoordinator::new();
+        let _stuck = coordinator.register("ws user");
+        let done = coordinator.register("GET /health");
+        drop(done);
+        coordinator.on_shutdown("journal", || Err("disk full".into()));
+
+        let report = coordinator.drain(Duration::from_secs(1)).await;
+        assert!(!report.drained);
+        assert_eq!(report.abandoned, ["ws user"]);
+        assert_eq!(report.hooks[0].result, Err("disk full".to_string()));
+        assert!(!report.is_clean());
+
+        // Hooks run once
+        let again = coordinator.drain(Duration::ZERO).await;
+        assert!(again.hooks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_triggered_resolves_for_late_subscribers() {
+        let coordinator = ShutdownCoordinator::new();
+        let guard = coordinator.register("session");
+        assert!(!guard.is_requested());
+        let waiting = coordinator.triggered();
+        coordinator.trigger();
+        waiting.await;
+        guard.requested().await;
+        coordinator.triggered().await;
+        assert!(coordinator.is_triggered() && guard.is_requested());
+    }
+}
@@ -1,11 +1,14 @@
 //! Error types for the matching engine
 //!
-//! Comprehensive error taxonomy using thiserror
+//! Comprehensive error taxonomy using thiserror. Errors serialize so
+//! services can return them over the wire and callers can match on them.
 
+use crate::market::MarketRejectReason;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Top-level engine error
-#[derive(Error, Debug, Clone, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EngineError {
     #[error("Order error: {0}")]
     Order(#[from] OrderError),
@@ -22,12 +25,15 @@ pub enum EngineError {
     #[error("Invalid market: {symbol}")]
     InvalidMarket { symbol: String },
     
+    #[error("Market closed: {0}")]
+    MarketClosed(#[from] MarketRejectReason),
+    
     #[error("System error: {message}")]
     System { message: String },
 }
 
 /// Order-specific errors
-#[derive(Error, Debug, Clone, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderError {
     #[error("Invalid price: {0}")]
     InvalidPrice(String),
@@ -55,7 +61,7 @@ pub enum OrderError {
 }
 
 /// Trade-specific errors
-#[derive(Error, Debug, Clone, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TradeError {
     #[error("Trade not found: {trade_id}")]
     NotFound { trade_id: String },
@@ -71,7 +77,7 @@ pub enum TradeError {
 }
 
 /// Account-specific errors
-#[derive(Error, Debug, Clone, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AccountError {
     #[error("Account not found: {account_id}")]
     NotFound { account_id: String },
@@ -97,7 +103,7 @@ pub enum AccountError {
 }
 
 /// Liquidation-specific errors
-#[derive(Error, Debug, Clone, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LiquidationError {
     #[error("Position not found")]
     PositionNotFound,
@@ -139,4 +145,25 @@ mod tests {
         let engine_err: EngineError = order_err.into();
         assert!(matches!(engine_err, EngineError::Order(_)));
     }
+
+    #[test]
+    fn test_engine_error_wire_round_trip() {
+        let errors = [
+            EngineError::from(AccountError::InsufficientBalance {
+                asset: "USDT".to_string(),
+                required: "10".to_string(),
+                available: "5".to_string(),
+            }),
+            EngineError::from(OrderError::PostOnlyReject),
+            EngineError::from(MarketRejectReason::HaltedManual),
+        ];
+        for err in errors {
+            let json = serde_json::to_string(&err).unwrap();
+            assert_eq!(serde_json::from_str::<EngineError>(&json).unwrap(), err);
+        }
+        assert_eq!(
+            serde_json::to_value(EngineError::from(OrderError::SelfTrade)).unwrap(),
+            serde_json::json!({"Order": "SelfTrade"})
+        );
+    }
 }
@@ -4,6 +4,10 @@
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Length of the fee tier volume window: 30 days of exchange time in ns
+pub const VOLUME_WINDOW_NS: i64 = 30 * 24 * 60 * 60 * 1_000_000_000;
 
 /// Fee type per spec §7.1
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -71,6 +75,94 @@ pub fn default_fee_tiers() -> Vec<FeeTier> {
     ]
 }
 
+/// Fee tiers ordered by volume threshold per spec §7.3
+///
+/// An account pays the highest tier whose threshold its 30-day volume
+/// reaches; volume exactly at a threshold qualifies for that tier. A trade
+/// is charged at the tier of the volume *before* it, so the volume a trade
+/// adds only counts from the next trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    tiers: Vec<FeeTier>,
+}
+
+impl FeeSchedule {
+    /// Create a schedule, ordering the tiers by threshold
+    ///
+    /// # Panics
+    /// Panics if `tiers` is empty
+    pub fn new(mut tiers: Vec<FeeTier>) -> Self {
+        assert!(!tiers.is_empty(), "Fee schedule needs at least one tier");
+        tiers.sort_by_key(|tier| tier.volume_threshold);
+        Self { tiers }
+    }
+
+    /// A schedule charging one tier regardless of volume
+    pub fn flat(tier: FeeTier) -> Self {
+        Self { tiers: vec![tier] }
+    }
+
+    /// Tiers in ascending threshold order
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers
+    }
+
+    /// Tier for a 30-day volume; below every threshold, the lowest tier
+    pub fn tier_for_volume(&self, volume: Decimal) -> &FeeTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| volume >= tier.volume_threshold)
+            .unwrap_or(&self.tiers[0])
+    }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self::new(default_fee_tiers())
+    }
+}
+
+/// One account's traded notional over the trailing `VOLUME_WINDOW_NS`
+///
+/// Driven purely by the exchange timestamps passed in. A fill counts while
+/// `now - timestamp < VOLUME_WINDOW_NS`, so it drops out exactly 30 days
+/// after it traded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollingVolume {
+    /// (timestamp, notional), oldest first
+    fills: VecDeque<(i64, Decimal)>,
+}
+
+impl RollingVolume {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a fill's notional, dropping fills that left the window
+    ///
+    /// Fills are expected in non-decreasing timestamp order.
+    pub fn record(&mut self, timestamp: i64, notional: Decimal) {
+        while self
+            .fills
+            .front()
+            .is_some_and(|(ts, _)| timestamp - ts >= VOLUME_WINDOW_NS)
+        {
+            self.fills.pop_front();
+        }
+        self.fills.push_back((timestamp, notional.abs()));
+    }
+
+    /// Volume traded in the window ending at `now`
+    pub fn volume_at(&self, now: i64) -> Decimal {
+        self.fills
+            .iter()
+            .filter(|(ts, _)| *ts <= now && now - ts < VOLUME_WINDOW_NS)
+            .map(|(_, notional)| *notional)
+            .sum()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,5 +204,38 @@ mod tests {
         assert_eq!(tiers[0].volume_threshold, Decimal::ZERO);
         assert_eq!(tiers[3].volume_threshold, Decimal::from(50_000_000));
     }
-}
 
+    #[test]
+    fn test_schedule_tier_for_volume() {
+        let mut tiers = default_fee_tiers();
+        tiers.reverse();
+        let schedule = FeeSchedule::new(tiers);
+        assert_eq!(schedule.tiers()[0].volume_threshold, Decimal::ZERO);
+
+        assert_eq!(schedule.tier_for_volume(Decimal::ZERO), &schedule.tiers()[0]);
+        assert_eq!(schedule.tier_for_volume(Decimal::from(999_999)), &schedule.tiers()[0]);
+        // Boundary-equal volume qualifies for the tier
+        assert_eq!(schedule.tier_for_volume(Decimal::from(1_000_000)), &schedule.tiers()[1]);
+        assert_eq!(schedule.tier_for_volume(Decimal::from(75_000_000)), &schedule.tiers()[3]);
+
+        // Below the lowest threshold still resolves to the lowest tier
+        let flat = FeeSchedule::flat(default_fee_tiers().remove(1));
+        assert_eq!(flat.tier_for_volume(Decimal::ZERO).volume_threshold, Decimal::from(1_000_000));
+    }
+
+    #[test]
+    fn test_rolling_volume_window() {
+        let t0: i64 = 1708123456789000000;
+        let mut volume = RollingVolume::new();
+        volume.record(t0, Decimal::from(600_000));
+        volume.record(t0 + 1_000, Decimal::from(-400_000));
+        assert_eq!(volume.volume_at(t0 + 1_000), Decimal::from(1_000_000));
+
+        // The first fill drops out exactly 30 days after it traded
+        assert_eq!(volume.volume_at(t0 + VOLUME_WINDOW_NS - 1), Decimal::from(1_000_000));
+        assert_eq!(volume.volume_at(t0 + VOLUME_WINDOW_NS), Decimal::from(400_000));
+
+        volume.record(t0 + VOLUME_WINDOW_NS + 1_000, Decimal::from(5));
+        assert_eq!(volume.volume_at(t0 + VOLUME_WINDOW_NS + 1_000), Decimal::from(5));
+    }
+}
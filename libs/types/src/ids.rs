@@ -11,7 +11,7 @@ use uuid::Uuid;
 ///
 /// Uses UUID v7 for time-based sorting. Orders can be efficiently
 /// queried in chronological order using the embedded timestamp.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct OrderId(Uuid);
 
@@ -78,7 +78,7 @@ impl fmt::Display for TradeId {
 }
 
 /// Unique identifier for an account
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct AccountId(Uuid);
 
@@ -146,6 +146,16 @@ impl MarketId {
         let parts: Vec<&str> = self.0.split('/').collect();
         (parts[0], parts[1])
     }
+
+    /// Base asset (e.g., "BTC" for "BTC/USDT")
+    pub fn base(&self) -> &str {
+        self.split().0
+    }
+
+    /// Quote asset (e.g., "USDT" for "BTC/USDT")
+    pub fn quote(&self) -> &str {
+        self.split().1
+    }
 }
 
 impl fmt::Display for MarketId {
@@ -203,6 +213,13 @@ mod tests {
         assert_eq!(quote, "USDT");
     }
 
+    #[test]
+    fn test_market_id_base_quote() {
+        let market = MarketId::new("ETH/USDC");
+        assert_eq!(market.base(), "ETH");
+        assert_eq!(market.quote(), "USDC");
+    }
+
     #[test]
     fn test_market_id_try_new() {
         assert!(MarketId::try_new("BTC/USDT").is_some());
@@ -15,6 +15,7 @@
 //! - `position`: Position tracking types
 //! - `fee`: Fee calculation types
 //! - `risk`: Risk management types
+//! - `market`: Market lifecycle types
 //! - `errors`: Error taxonomy
 
 // Public modules
@@ -26,6 +27,7 @@ pub mod account;
 pub mod position;
 pub mod fee;
 pub mod risk;
+pub mod market;
 pub mod errors;
 
 // Library version constant
@@ -41,5 +43,6 @@ pub mod prelude {
     pub use crate::position::*;
     pub use crate::fee::*;
     pub use crate::risk::*;
+    pub use crate::market::*;
     pub use crate::errors::*;
 }
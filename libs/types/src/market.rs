@@ -0,0 +1,406 @@
+//! Market lifecycle and trading rule types
+//!
+//! Trading status and price/quantity increments held per market by the
+//! matching engine and consulted by the gateway and risk engine before an
+//! order is admitted.
+
+use crate::numeric::{Price, Quantity};
+use crate::order::{Order, OrderType};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Decimal places prices and quantities are rounded to before increment checks
+pub const INCREMENT_CHECK_DP: u32 = 8;
+
+/// Market trading status
+///
+/// Lifecycle: PRE_LISTING → AUCTION/TRADING ⇄ halts/CANCEL_ONLY → DELISTED
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MarketStatus {
+    /// Announced but not yet open; no orders accepted
+    PreListing,
+    /// Call auction: orders rest without matching
+    Auction,
+    /// Continuous matching
+    Trading,
+    /// Halted by volatility circuit breaker; cancels only
+    HaltedVolatility,
+    /// Halted by an operator; cancels only
+    HaltedManual,
+    /// Winding down; cancels only
+    CancelOnly,
+    /// Removed from trading (terminal)
+    Delisted,
+}
+
+/// Reason an order was refused because of market status
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketRejectReason {
+    #[error("Market is not yet open for trading")]
+    NotYetListed,
+
+    #[error("Market halted by volatility circuit breaker")]
+    HaltedVolatility,
+
+    #[error("Market halted by operator")]
+    HaltedManual,
+
+    #[error("Market accepts cancels only")]
+    CancelOnly,
+
+    #[error("Market is delisted")]
+    Delisted,
+}
+
+/// Refused market status transition
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Invalid market status transition from {from} to {to}")]
+pub struct MarketStatusTransitionError {
+    pub from: MarketStatus,
+    pub to: MarketStatus,
+}
+
+impl MarketStatus {
+    /// Check if status is terminal (no further transitions possible)
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, MarketStatus::Delisted)
+    }
+
+    /// Whether `next` is a legal transition from this status.
+    ///
+    /// Delisted is terminal, nothing returns to PreListing, and a status
+    /// never transitions to itself.
+    pub fn can_transition_to(&self, next: MarketStatus) -> bool {
+        use MarketStatus::*;
+        if *self == next || self.is_terminal() || next == PreListing {
+            return false;
+        }
+        match self {
+            PreListing => matches!(next, Auction | Trading | Delisted),
+            // Volatility halts are only triggered from live markets
+            Auction | Trading => true,
+            HaltedVolatility | HaltedManual | CancelOnly => next != HaltedVolatility,
+            Delisted => false,
+        }
+    }
+
+    /// Validate a transition, returning an error if it is not allowed.
+    pub fn transition_to(&self, next: MarketStatus) -> Result<MarketStatus, MarketStatusTransitionError> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(MarketStatusTransitionError { from: *self, to: next })
+        }
+    }
+
+    /// Whether new orders are admitted; returns the rejection reason if not.
+    pub fn check_new_order(&self) -> Result<(), MarketRejectReason> {
+        match self {
+            MarketStatus::Auction | MarketStatus::Trading => Ok(()),
+            MarketStatus::PreListing => Err(MarketRejectReason::NotYetListed),
+            MarketStatus::HaltedVolatility => Err(MarketRejectReason::HaltedVolatility),
+            MarketStatus::HaltedManual => Err(MarketRejectReason::HaltedManual),
+            MarketStatus::CancelOnly => Err(MarketRejectReason::CancelOnly),
+            MarketStatus::Delisted => Err(MarketRejectReason::Delisted),
+        }
+    }
+
+    /// Whether resting orders may be canceled.
+    pub fn accepts_cancels(&self) -> bool {
+        !matches!(self, MarketStatus::PreListing | MarketStatus::Delisted)
+    }
+
+    /// Whether incoming orders match against the book.
+    pub fn is_matching(&self) -> bool {
+        matches!(self, MarketStatus::Trading)
+    }
+
+    /// Wire name (e.g., "CANCEL_ONLY")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketStatus::PreListing => "PRE_LISTING",
+            MarketStatus::Auction => "AUCTION",
+            MarketStatus::Trading => "TRADING",
+            MarketStatus::HaltedVolatility => "HALTED_VOLATILITY",
+            MarketStatus::HaltedManual => "HALTED_MANUAL",
+            MarketStatus::CancelOnly => "CANCEL_ONLY",
+            MarketStatus::Delisted => "DELISTED",
+        }
+    }
+}
+
+impl fmt::Display for MarketStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Price and quantity increments for a market
+///
+/// A zero `tick_size` or `lot_size` disables that check; a zero
+/// `min_notional` admits any size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketConfig {
+    /// Prices must be a multiple of this
+    pub tick_size: Decimal,
+    /// Quantities must be a multiple of this
+    pub lot_size: Decimal,
+    /// Minimum price × quantity
+    pub min_notional: Decimal,
+}
+
+/// Order price or quantity outside a market's increments
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketConfigViolation {
+    #[error("Price {price} is not a multiple of tick size {tick_size}")]
+    PriceNotOnTick { price: Decimal, tick_size: Decimal },
+
+    #[error("Quantity {quantity} is not a multiple of lot size {lot_size}")]
+    QuantityNotOnLot { quantity: Decimal, lot_size: Decimal },
+
+    #[error("Notional {notional} is below the minimum {min_notional}")]
+    BelowMinNotional { notional: Decimal, min_notional: Decimal },
+}
+
+impl MarketConfigViolation {
+    /// Order field the violation is reported against
+    pub fn field(&self) -> &'static str {
+        match self {
+            MarketConfigViolation::PriceNotOnTick { .. } => "price",
+            MarketConfigViolation::QuantityNotOnLot { .. } => "quantity",
+            MarketConfigViolation::BelowMinNotional { .. } => "quantity",
+        }
+    }
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            tick_size: Decimal::new(1, 2),
+            lot_size: Decimal::new(1, INCREMENT_CHECK_DP),
+            min_notional: Decimal::ZERO,
+        }
+    }
+}
+
+impl MarketConfig {
+    /// Check that a price lies on the tick grid
+    pub fn check_price(&self, price: Price) -> Result<(), MarketConfigViolation> {
+        if is_multiple(price.as_decimal(), self.tick_size) {
+            Ok(())
+        } else {
+            Err(MarketConfigViolation::PriceNotOnTick {
+                price: price.as_decimal(),
+                tick_size: self.tick_size,
+            })
+        }
+    }
+
+    /// Check that a quantity is a whole number of lots
+    pub fn check_quantity(&self, quantity: Quantity) -> Result<(), MarketConfigViolation> {
+        if is_multiple(quantity.as_decimal(), self.lot_size) {
+            Ok(())
+        } else {
+            Err(MarketConfigViolation::QuantityNotOnLot {
+                quantity: quantity.as_decimal(),
+                lot_size: self.lot_size,
+            })
+        }
+    }
+
+    /// Check that price × quantity meets the minimum notional
+    pub fn check_notional(&self, price: Price, quantity: Quantity) -> Result<(), MarketConfigViolation> {
+        let notional = price.as_decimal() * quantity.as_decimal();
+        if notional >= self.min_notional {
+            Ok(())
+        } else {
+            Err(MarketConfigViolation::BelowMinNotional {
+                notional,
+                min_notional: self.min_notional,
+            })
+        }
+    }
+
+    /// Run the price, quantity and notional checks in that order
+    pub fn check_limit(&self, price: Price, quantity: Quantity) -> Result<(), MarketConfigViolation> {
+        self.check_price(price)?;
+        self.check_quantity(quantity)?;
+        self.check_notional(price, quantity)
+    }
+
+    /// Check an order's prices and quantity against the increments
+    ///
+    /// Market orders carry no price, so only their lot size is checked.
+    /// Stop prices must lie on the tick grid; stop-market notional is taken
+    /// at the stop price.
+    pub fn check_order(&self, order: &Order) -> Result<(), MarketConfigViolation> {
+        match (order.order_type, order.stop_price) {
+            (OrderType::Market, _) => self.check_quantity(order.quantity),
+            (OrderType::StopMarket, Some(stop_price)) => self.check_limit(stop_price, order.quantity),
+            (_, stop_price) => {
+                if let Some(stop_price) = stop_price {
+                    self.check_price(stop_price)?;
+                }
+                self.check_limit(order.price, order.quantity)
+            }
+        }
+    }
+}
+
+/// Whether `value`, rounded to `INCREMENT_CHECK_DP` places, is a multiple of `step`
+fn is_multiple(value: Decimal, step: Decimal) -> bool {
+    if step <= Decimal::ZERO {
+        return true;
+    }
+    let rounded = value.round_dp_with_strategy(INCREMENT_CHECK_DP, rust_decimal::RoundingStrategy::MidpointAwayFromZero);
+    (rounded % step).is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [MarketStatus; 7] = [
+        MarketStatus::PreListing,
+        MarketStatus::Auction,
+        MarketStatus::Trading,
+        MarketStatus::HaltedVolatility,
+        MarketStatus::HaltedManual,
+        MarketStatus::CancelOnly,
+        MarketStatus::Delisted,
+    ];
+
+    #[test]
+    fn test_delisted_is_terminal() {
+        for next in ALL {
+            assert!(!MarketStatus::Delisted.can_transition_to(next));
+        }
+    }
+
+    #[test]
+    fn test_no_return_to_pre_listing_or_self() {
+        for status in ALL {
+            assert!(!status.can_transition_to(MarketStatus::PreListing));
+            assert!(!status.can_transition_to(status));
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_transitions() {
+        assert!(MarketStatus::PreListing.can_transition_to(MarketStatus::Auction));
+        assert!(!MarketStatus::PreListing.can_transition_to(MarketStatus::CancelOnly));
+        assert!(MarketStatus::Trading.can_transition_to(MarketStatus::HaltedVolatility));
+        assert!(!MarketStatus::CancelOnly.can_transition_to(MarketStatus::HaltedVolatility));
+        assert!(MarketStatus::HaltedManual.can_transition_to(MarketStatus::Trading));
+
+        let err = MarketStatus::Delisted.transition_to(MarketStatus::Trading).unwrap_err();
+        assert_eq!(err.from, MarketStatus::Delisted);
+        assert_eq!(err.to, MarketStatus::Trading);
+    }
+
+    #[test]
+    fn test_admission_rules() {
+        assert!(MarketStatus::Trading.check_new_order().is_ok());
+        assert!(MarketStatus::Auction.check_new_order().is_ok());
+        assert_eq!(
+            MarketStatus::CancelOnly.check_new_order(),
+            Err(MarketRejectReason::CancelOnly)
+        );
+        assert!(MarketStatus::CancelOnly.accepts_cancels());
+        assert!(!MarketStatus::Delisted.accepts_cancels());
+    }
+
+    fn config(tick: &str, lot: &str, min_notional: &str) -> MarketConfig {
+        MarketConfig {
+            tick_size: tick.parse().unwrap(),
+            lot_size: lot.parse().unwrap(),
+            min_notional: min_notional.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_tick_size_half() {
+        let config = config("0.5", "0.001", "0");
+        assert!(config.check_price(Price::from_str("100.5").unwrap()).is_ok());
+        assert!(config.check_price(Price::from_str("100").unwrap()).is_ok());
+
+        let err = config.check_price(Price::from_str("100.25").unwrap()).unwrap_err();
+        assert_eq!(
+            err,
+            MarketConfigViolation::PriceNotOnTick {
+                price: "100.25".parse().unwrap(),
+                tick_size: "0.5".parse().unwrap(),
+            }
+        );
+        assert_eq!(err.field(), "price");
+    }
+
+    #[test]
+    fn test_lot_size_after_8dp_rounding() {
+        let config = config("0.01", "0.001", "0");
+        assert!(config.check_quantity(Quantity::from_str("0.003").unwrap()).is_ok());
+        // Noise beyond 8 dp rounds away
+        assert!(config.check_quantity(Quantity::from_str("0.003000000004").unwrap()).is_ok());
+        // Noise at 8 dp does not
+        assert!(config.check_quantity(Quantity::from_str("0.00300001").unwrap()).is_err());
+        assert!(matches!(
+            config.check_quantity(Quantity::from_str("0.0035").unwrap()),
+            Err(MarketConfigViolation::QuantityNotOnLot { .. })
+        ));
+    }
+
+    #[test]
+    fn test_min_notional_and_disabled_checks() {
+        let config = config("0", "0", "10");
+        let price = Price::from_str("100.123456789").unwrap();
+        assert!(config.check_limit(price, Quantity::from_str("0.1").unwrap()).is_ok());
+
+        let err = config.check_limit(price, Quantity::from_str("0.05").unwrap()).unwrap_err();
+        assert!(matches!(err, MarketConfigViolation::BelowMinNotional { .. }));
+        assert_eq!(err.field(), "quantity");
+
+        // Defaults admit anything already limited to 2 dp prices and 8 dp quantities
+        let default = MarketConfig::default();
+        assert!(default.check_limit(Price::from_str("50000.01").unwrap(), Quantity::from_str("0.00000001").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_check_order_by_type() {
+        use crate::ids::{AccountId, MarketId};
+        use crate::order::{Side, TimeInForce};
+
+        let config = config("0.5", "0.1", "10");
+        let account = AccountId::new();
+        let symbol = MarketId::new("BTC/USDT");
+        let qty = Quantity::from_str("0.2").unwrap();
+
+        // Placeholder market price is never checked against the tick
+        let market = Order::market(account, symbol.clone(), Side::BUY, qty, 1);
+        assert!(config.check_order(&market).is_ok());
+
+        let stop = Order::stop_market(account, symbol.clone(), Side::SELL, Price::from_str("99.75").unwrap(), qty, 1);
+        assert!(matches!(config.check_order(&stop), Err(MarketConfigViolation::PriceNotOnTick { .. })));
+
+        let stop_limit = Order::stop_limit(
+            account,
+            symbol,
+            Side::SELL,
+            Price::from_str("100").unwrap(),
+            Price::from_str("99.5").unwrap(),
+            Quantity::from_str("0.05").unwrap(),
+            TimeInForce::GTC,
+            1,
+        );
+        assert!(matches!(config.check_order(&stop_limit), Err(MarketConfigViolation::QuantityNotOnLot { .. })));
+    }
+
+    #[test]
+    fn test_market_status_serialization() {
+        let json = serde_json::to_string(&MarketStatus::HaltedVolatility).unwrap();
+        assert_eq!(json, "\"HALTED_VOLATILITY\"");
+        assert_eq!(MarketStatus::CancelOnly.to_string(), "CANCEL_ONLY");
+    }
+}
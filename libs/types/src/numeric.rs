@@ -41,6 +41,7 @@ impl Price {
     }
 
     /// Create from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, rust_decimal::Error> {
         let decimal = Decimal::from_str(s)?;
         Ok(Self::new(decimal))
@@ -156,6 +157,7 @@ impl Quantity {
     }
 
     /// Create from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, rust_decimal::Error> {
         let decimal = Decimal::from_str(s)?;
         Ok(Self::new(decimal))
@@ -4,6 +4,7 @@
 
 use crate::ids::{AccountId, MarketId, OrderId};
 use crate::numeric::{Price, Quantity};
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
 /// Order side (buyer or seller)
@@ -26,6 +27,21 @@ impl Side {
     }
 }
 
+/// Order type per spec §8.3.1 (`type` field of OrderSubmitted)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderType {
+    /// Executes at the limit price or better; remainder may rest
+    #[default]
+    Limit,
+    /// Sweeps available depth at any price; never rests
+    Market,
+    /// Held off-book until the stop price trades, then submitted as Market
+    StopMarket,
+    /// Held off-book until the stop price trades, then submitted as Limit
+    StopLimit,
+}
+
 /// Time-in-force policy for orders
 ///
 /// Defines how long an order remains active per spec §1.5
@@ -118,6 +134,10 @@ pub enum RejectReason {
     SymbolNotFound,
     AccountSuspended,
     RateLimited,
+    /// Market order with no opposing liquidity
+    NoLiquidity,
+    /// Fill-or-kill order that the book cannot fill in full
+    FillOrKillUnfillable,
 }
 
 /// Complete order structure per spec §1
@@ -129,12 +149,22 @@ pub struct Order {
     pub account_id: AccountId,
     pub symbol: MarketId,
     pub side: Side,
+    #[serde(default)]
+    pub order_type: OrderType,
+    /// Limit price; ignored for market orders
     pub price: Price,
+    /// Trigger price for stop orders
+    #[serde(default)]
+    pub stop_price: Option<Price>,
     pub quantity: Quantity,
     pub filled_quantity: Quantity,
     pub remaining_quantity: Quantity,
     pub status: OrderStatus,
     pub time_in_force: TimeInForce,
+    /// Market orders only: stop filling once the sweep's VWAP would move
+    /// more than this fraction from the best price (0.005 = 0.5%)
+    #[serde(default)]
+    pub max_slippage: Option<Decimal>,
     pub created_at: i64,  // Unix nanos
     pub updated_at: i64,  // Unix nanos
     pub version: u64,     // Optimistic locking
@@ -156,18 +186,101 @@ impl Order {
             account_id,
             symbol,
             side,
+            order_type: OrderType::Limit,
             price,
+            stop_price: None,
             quantity,
             filled_quantity: Quantity::zero(),
             remaining_quantity: quantity,
             status: OrderStatus::Pending,
             time_in_force,
+            max_slippage: None,
             created_at: timestamp,
             updated_at: timestamp,
             version: 0,
         }
     }
 
+    /// Create a new pending market order
+    ///
+    /// Market orders carry no limit: `price` is set to the smallest
+    /// positive increment and never used for matching or resting.
+    pub fn market(
+        account_id: AccountId,
+        symbol: MarketId,
+        side: Side,
+        quantity: Quantity,
+        timestamp: i64,
+    ) -> Self {
+        let mut order = Self::new(
+            account_id,
+            symbol,
+            side,
+            Price::new(rust_decimal::Decimal::new(1, 8)),
+            quantity,
+            TimeInForce::IOC,
+            timestamp,
+        );
+        order.order_type = OrderType::Market;
+        order
+    }
+
+    /// Create a new pending stop-market order
+    pub fn stop_market(
+        account_id: AccountId,
+        symbol: MarketId,
+        side: Side,
+        stop_price: Price,
+        quantity: Quantity,
+        timestamp: i64,
+    ) -> Self {
+        let mut order = Self::market(account_id, symbol, side, quantity, timestamp);
+        order.order_type = OrderType::StopMarket;
+        order.stop_price = Some(stop_price);
+        order
+    }
+
+    /// Create a new pending stop-limit order
+    #[allow(clippy::too_many_arguments)]
+    pub fn stop_limit(
+        account_id: AccountId,
+        symbol: MarketId,
+        side: Side,
+        stop_price: Price,
+        price: Price,
+        quantity: Quantity,
+        time_in_force: TimeInForce,
+        timestamp: i64,
+    ) -> Self {
+        let mut order = Self::new(account_id, symbol, side, price, quantity, time_in_force, timestamp);
+        order.order_type = OrderType::StopLimit;
+        order.stop_price = Some(stop_price);
+        order
+    }
+
+    /// Check if this is a market order
+    pub fn is_market(&self) -> bool {
+        self.order_type == OrderType::Market
+    }
+
+    /// Check if this is a stop order awaiting its trigger
+    pub fn is_stop(&self) -> bool {
+        matches!(self.order_type, OrderType::StopMarket | OrderType::StopLimit)
+    }
+
+    /// Convert a triggered stop into the order it submits
+    ///
+    /// Stop-market becomes Market and stop-limit becomes Limit; other
+    /// orders are returned unchanged.
+    pub fn into_triggered(mut self) -> Self {
+        self.order_type = match self.order_type {
+            OrderType::StopMarket => OrderType::Market,
+            OrderType::StopLimit => OrderType::Limit,
+            other => other,
+        };
+        self
+    }
+
     /// Check quantity invariant: filled + remaining = total
     pub fn check_invariant(&self) -> bool {
         self.filled_quantity.as_decimal() + self.remaining_quantity.as_decimal()
@@ -227,15 +340,70 @@ impl Order {
     }
 
     /// Reject the order
-    pub fn reject(reason: RejectReason, timestamp: i64) -> OrderStatus {
+    pub fn reject(reason: RejectReason, _timestamp: i64) -> OrderStatus {
         OrderStatus::Rejected(reason)
     }
 }
 
+/// Fill-to-quantity decimal places for slippage-capped sweeps
+const SLIPPAGE_QTY_DP: u32 = 8;
+
+/// Quantity a sweep may fill before its running VWAP moves more than
+/// `max_slippage` (a fraction) away from the first level's price
+///
+/// `levels` are (price, quantity) pairs, best first. The level that would
+/// carry the VWAP past the cap fills only the part that lands on it,
+/// rounded down to 8 dp. Returns the quantity and whether the cap cut the
+/// sweep short. Shared by the matching engine and client-side simulation so
+/// both stop at the same fill.
+pub fn slippage_fill_limit(
+    side: Side,
+    levels: impl IntoIterator<Item = (Decimal, Decimal)>,
+    quantity: Decimal,
+    max_slippage: Decimal,
+) -> (Decimal, bool) {
+    let mut levels = levels.into_iter().peekable();
+    let Some(&(best, _)) = levels.peek() else {
+        return (Decimal::ZERO, false);
+    };
+    let cap = match side {
+        Side::BUY => best * (Decimal::ONE + max_slippage),
+        Side::SELL => best * (Decimal::ONE - max_slippage),
+    };
+
+    let mut filled = Decimal::ZERO;
+    let mut value = Decimal::ZERO;
+    for (price, available) in levels {
+        let take = (quantity - filled).min(available);
+        if take <= Decimal::ZERO {
+            break;
+        }
+        let within = match side {
+            Side::BUY => price <= cap,
+            Side::SELL => price >= cap,
+        };
+        if !within {
+            // Largest q with (value + price × q) / (filled + q) on the cap
+            let room = match side {
+                Side::BUY => (cap * filled - value) / (price - cap),
+                Side::SELL => (value - cap * filled) / (cap - price),
+            };
+            let room = room
+                .max(Decimal::ZERO)
+                .round_dp_with_strategy(SLIPPAGE_QTY_DP, RoundingStrategy::ToZero);
+            if room < take {
+                return (filled + room, true);
+            }
+        }
+        filled += take;
+        value += price * take;
+    }
+    (filled, false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rust_decimal::Decimal;
 
     #[test]
     fn test_side_opposite() {
@@ -364,6 +532,89 @@ mod tests {
         assert_eq!(order.order_id, deserialized.order_id);
         assert_eq!(order.side, deserialized.side);
         assert_eq!(order.price, deserialized.price);
+        assert_eq!(deserialized.order_type, OrderType::Limit);
     }
-}
 
+    #[test]
+    fn test_market_order_creation() {
+        let order = Order::market(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            Side::BUY,
+            Quantity::from_str("1.0").unwrap(),
+            1708123456789000000,
+        );
+
+        assert!(order.is_market());
+        assert_eq!(order.time_in_force, TimeInForce::IOC);
+        assert!(order.check_invariant());
+
+        let json = serde_json::to_string(&order).unwrap();
+        assert!(json.contains("\"order_type\":\"MARKET\""));
+        let deserialized: Order = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn test_stop_orders_convert_on_trigger() {
+        let stop = Order::stop_limit(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            Side::SELL,
+            Price::from_u64(49000),
+            Price::from_u64(48900),
+            Quantity::from_str("1.0").unwrap(),
+            TimeInForce::GTC,
+            1708123456789000000,
+        );
+        assert!(stop.is_stop());
+        assert_eq!(serde_json::to_value(stop.order_type).unwrap(), "STOP_LIMIT");
+
+        let triggered = stop.into_triggered();
+        assert!(!triggered.is_stop());
+        assert_eq!(triggered.order_type, OrderType::Limit);
+        assert_eq!(triggered.price, Price::from_u64(48900));
+
+        let stop = Order::stop_market(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            Side::BUY,
+            Price::from_u64(51000),
+            Quantity::from_str("1.0").unwrap(),
+            1708123456789000000,
+        );
+        assert_eq!(stop.stop_price, Some(Price::from_u64(51000)));
+        assert!(stop.into_triggered().is_market());
+    }
+
+    fn levels(levels: &[(u64, u64)]) -> Vec<(Decimal, Decimal)> {
+        levels.iter().map(|&(p, q)| (Decimal::from(p), Decimal::from(q))).collect()
+    }
+
+    #[test]
+    fn test_slippage_fill_limit_mid_level() {
+        // Cap 1% over 100 = 101: all of 100, then the part of 102 that lands
+        // the VWAP on 101: (100 + 102q) / (1 + q) = 101 → q = 1
+        let asks = levels(&[(100, 1), (102, 5)]);
+        let tolerance = Decimal::new(1, 2);
+        assert_eq!(slippage_fill_limit(Side::BUY, asks.clone(), Decimal::from(10), tolerance), (Decimal::from(2), true));
+        // Within the cap for the whole quantity
+        assert_eq!(slippage_fill_limit(Side::BUY, asks, Decimal::from(2), tolerance), (Decimal::from(2), false));
+
+        // Mirrored for sells: cap 99, (100 + 98q) / (1 + q) = 99 → q = 1
+        let bids = levels(&[(100, 1), (98, 5)]);
+        assert_eq!(slippage_fill_limit(Side::SELL, bids, Decimal::from(10), tolerance), (Decimal::from(2), true));
+    }
+
+    #[test]
+    fn test_slippage_fill_limit_edges() {
+        let asks = levels(&[(100, 1), (105, 5)]);
+        // A tolerance the best level itself breaches fills nothing
+        assert_eq!(slippage_fill_limit(Side::BUY, asks.clone(), Decimal::ONE, Decimal::new(-1, 2)), (Decimal::ZERO, true));
+        // Zero tolerance takes only the best level
+        assert_eq!(slippage_fill_limit(Side::BUY, asks.clone(), Decimal::from(3), Decimal::ZERO), (Decimal::ONE, true));
+        // Book exhausted without reaching the cap
+        assert_eq!(slippage_fill_limit(Side::BUY, asks, Decimal::from(10), Decimal::ONE), (Decimal::from(6), false));
+        assert_eq!(slippage_fill_limit(Side::BUY, Vec::new(), Decimal::ONE, Decimal::ONE), (Decimal::ZERO, false));
+    }
+}
@@ -3,6 +3,7 @@
 //! Implements spec §6 (Liquidation Process)
 
 use crate::ids::AccountId;
+use crate::market::{MarketConfigViolation, MarketStatus};
 use crate::numeric::{Price, Quantity};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -28,6 +29,14 @@ pub enum RiskCheckResult {
         max_leverage: u8,
         requested: u8,
     },
+    /// Failed: market status does not admit new orders
+    MarketUnavailable {
+        status: MarketStatus,
+    },
+    /// Failed: price or quantity outside the market's increments
+    MarketConfigViolated {
+        violation: MarketConfigViolation,
+    },
 }
 
 /// Liquidation event per spec §6.3
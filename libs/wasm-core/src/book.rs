@@ -0,0 +1,86 @@
+//! Book Module — Client-side order book checksum
+//!
+//! Recomputes the CRC-32 the market-data service attaches to depth
+//! snapshots and book deltas, so a client can check its local book
+//! after every update and resync on a mismatch.
+//!
+//! Format (must match `market_data::order_book::book_checksum_string`):
+//! - `price:quantity` pairs of the top N levels per side, best first.
+//! - Alternating bid and ask by rank (`bid0:ask0:bid1:ask1:...`), joined with `:`.
+//! - A side that runs out is skipped; decimals are normalized.
+
+use rust_decimal::Decimal;
+
+use crate::payload::canonical_decimal;
+
+/// Levels per side the server checksums (`market_data::order_book::CHECKSUM_LEVELS`).
+pub const CHECKSUM_LEVELS: usize = 25;
+
+/// Canonical string over the top `levels` of each side.
+///
+/// `bids` descending and `asks` ascending, as `(price, quantity)`.
+pub fn book_checksum_string(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)], levels: usize) -> String {
+    let bids = &bids[..bids.len().min(levels)];
+    let asks = &asks[..asks.len().min(levels)];
+    let mut parts = Vec::with_capacity(bids.len() + asks.len());
+    for i in 0..bids.len().max(asks.len()) {
+        for (price, quantity) in bids.get(i).into_iter().chain(asks.get(i)) {
+            parts.push(format!("{}:{}", canonical_decimal(*price), canonical_decimal(*quantity)));
+        }
+    }
+    parts.join(":")
+}
+
+/// CRC-32 (ISO-HDLC, as zlib's `crc32`) of [`book_checksum_string`].
+pub fn book_checksum(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)], levels: usize) -> u32 {
+    crc32fast::hash(book_checksum_string(bids, asks, levels).as_bytes())
+}
+
+/// Whether the local book matches a server checksum.
+pub fn verify_book_checksum(
+    bids: &[(Decimal, Decimal)],
+    asks: &[(Decimal, Decimal)],
+    levels: usize,
+    expected: u32,
+) -> bool {
+    book_checksum(bids, asks, levels) == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        name: String,
+        levels: usize,
+        bids: Vec<(Decimal, Decimal)>,
+        asks: Vec<(Decimal, Decimal)>,
+        canonical: String,
+        checksum: u32,
+    }
+
+    // Same fixtures the market-data service tests against.
+    const FIXTURES: &str = include_str!("../../../services/market-data/fixtures/book_checksum.json");
+
+    #[test]
+    fn test_matches_server_fixtures() {
+        let fixtures: Vec<Fixture> = serde_json::from_str(FIXTURES).unwrap();
+        assert!(!fixtures.is_empty());
+        for f in fixtures {
+            assert_eq!(book_checksum_string(&f.bids, &f.asks, f.levels), f.canonical, "{}", f.name);
+            assert_eq!(book_checksum(&f.bids, &f.asks, f.levels), f.checksum, "{}", f.name);
+            assert!(verify_book_checksum(&f.bids, &f.asks, f.levels, f.checksum));
+        }
+    }
+
+    #[test]
+    fn test_detects_changed_level() {
+        let bids = vec![(Decimal::from(100), Decimal::ONE)];
+        let asks = vec![(Decimal::from(101), Decimal::from(2))];
+        let expected = book_checksum(&bids, &asks, CHECKSUM_LEVELS);
+        let drifted = vec![(Decimal::from(101), Decimal::from(3))];
+        assert!(!verify_book_checksum(&bids, &drifted, CHECKSUM_LEVELS, expected));
+    }
+}
@@ -5,6 +5,8 @@
 //! - Margin preview and risk assessment
 //! - Order fill simulation against mock order books
 //! - Transaction signing and verification
+//! - Canonical signing payloads for order actions
+//! - Order book checksum verification
 //!
 //! # Determinism
 //! All functions are pure: no system time, no RNG, no external calls.
@@ -17,6 +19,8 @@ pub mod portfolio;
 pub mod margin;
 pub mod simulation;
 pub mod signing;
+pub mod payload;
+pub mod book;
 
 /// Crate version constant
 pub const WASM_CORE_VERSION: &str = "1.0.0";
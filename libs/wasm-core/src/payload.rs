@@ -0,0 +1,326 @@
+//! Payload Module — Canonical signing payloads for order actions
+//!
+//! Typed builders for the `SignableMessage` payloads of each action, so
+//! frontends and the gateway produce byte-identical maps.
+//!
+//! Conventions:
+//! - Keys are fixed snake_case names; optional fields are omitted, never empty.
+//! - Decimals are normalized: no trailing zeros, no exponent, `0` for zero.
+//! - Enums use their wire spelling (`BUY`, `LIMIT`, `GTC`, ...).
+
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use types::order::{OrderType, Side, TimeInForce};
+
+use crate::signing::SignableMessage;
+
+/// Action name for order placement.
+pub const ACTION_CREATE_ORDER: &str = "CreateOrder";
+/// Action name for order cancellation.
+pub const ACTION_CANCEL_ORDER: &str = "CancelOrder";
+/// Action name for withdrawals.
+pub const ACTION_WITHDRAW: &str = "Withdraw";
+/// Action name for batch order instructions.
+pub const ACTION_BATCH_ORDERS: &str = "BatchOrders";
+
+/// Canonical decimal string: trailing zeros stripped, never an exponent.
+pub fn canonical_decimal(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// Order placement payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignableOrder {
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    /// Limit price (`None` for market orders)
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub time_in_force: TimeInForce,
+    /// Caller-chosen id echoed back by the gateway
+    pub client_order_id: Option<String>,
+}
+
+impl SignableOrder {
+    /// A GTC order; a limit order when `price` is set, market otherwise.
+    pub fn new(symbol: impl Into<String>, side: Side, price: Option<Decimal>, quantity: Decimal) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            order_type: if price.is_some() { OrderType::Limit } else { OrderType::Market },
+            price,
+            quantity,
+            time_in_force: TimeInForce::GTC,
+            client_order_id: None,
+        }
+    }
+
+    pub fn with_order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = order_type;
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    /// Canonical payload map.
+    pub fn payload(&self) -> BTreeMap<String, String> {
+        let mut payload = BTreeMap::new();
+        insert(&mut payload, "symbol", self.symbol.clone());
+        insert(
+            &mut payload,
+            "side",
+            match self.side {
+                Side::BUY => "BUY",
+                Side::SELL => "SELL",
+            },
+        );
+        insert(
+            &mut payload,
+            "order_type",
+            match self.order_type {
+                OrderType::Limit => "LIMIT",
+                OrderType::Market => "MARKET",
+                OrderType::StopMarket => "STOP_MARKET",
+                OrderType::StopLimit => "STOP_LIMIT",
+            },
+        );
+        if let Some(price) = self.price {
+            insert(&mut payload, "price", canonical_decimal(price));
+        }
+        insert(&mut payload, "quantity", canonical_decimal(self.quantity));
+        let time_in_force = match self.time_in_force {
+            TimeInForce::GTC => "GTC",
+            TimeInForce::IOC => "IOC",
+            TimeInForce::FOK => "FOK",
+            TimeInForce::GTD(expire_at) => {
+                insert(&mut payload, "expire_at", expire_at.to_string());
+                "GTD"
+            }
+        };
+        insert(&mut payload, "time_in_force", time_in_force);
+        if let Some(client_order_id) = &self.client_order_id {
+            insert(&mut payload, "client_order_id", client_order_id.clone());
+        }
+        payload
+    }
+
+    pub fn into_signable(self, timestamp: i64, nonce: u64) -> SignableMessage {
+        SignableMessage::new(ACTION_CREATE_ORDER, self.payload(), timestamp, nonce)
+    }
+}
+
+/// Order cancellation payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignableCancel {
+    pub order_id: String,
+}
+
+impl SignableCancel {
+    pub fn new(order_id: impl Into<String>) -> Self {
+        Self {
+            order_id: order_id.into(),
+        }
+    }
+
+    /// Canonical payload map.
+    pub fn payload(&self) -> BTreeMap<String, String> {
+        let mut payload = BTreeMap::new();
+        insert(&mut payload, "order_id", self.order_id.clone());
+        payload
+    }
+
+    pub fn into_signable(self, timestamp: i64, nonce: u64) -> SignableMessage {
+        SignableMessage::new(ACTION_CANCEL_ORDER, self.payload(), timestamp, nonce)
+    }
+}
+
+/// Withdrawal payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignableWithdrawal {
+    pub asset: String,
+    pub amount: Decimal,
+    /// Destination address on the settlement chain
+    pub destination: String,
+}
+
+impl SignableWithdrawal {
+    pub fn new(asset: impl Into<String>, amount: Decimal, destination: impl Into<String>) -> Self {
+        Self {
+            asset: asset.into(),
+            amount,
+            destination: destination.into(),
+        }
+    }
+
+    /// Canonical payload map.
+    pub fn payload(&self) -> BTreeMap<String, String> {
+        let mut payload = BTreeMap::new();
+        insert(&mut payload, "asset", self.asset.clone());
+        insert(&mut payload, "amount", canonical_decimal(self.amount));
+        insert(&mut payload, "destination", self.destination.clone());
+        payload
+    }
+
+    pub fn into_signable(self, timestamp: i64, nonce: u64) -> SignableMessage {
+        SignableMessage::new(ACTION_WITHDRAW, self.payload(), timestamp, nonce)
+    }
+}
+
+/// Batch of order instructions, signed as a whole.
+///
+/// Items are signed as sent rather than rebuilt from typed values, so a
+/// batch with an invalid item still verifies and the other items can run.
+/// Each field becomes `NNNN.field` (zero-padded item index); strings are
+/// taken verbatim and other JSON values as their compact JSON text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignableBatch {
+    pub atomic: bool,
+    pub items: Vec<Map<String, Value>>,
+}
+
+impl SignableBatch {
+    pub fn new(atomic: bool, items: Vec<Map<String, Value>>) -> Self {
+        Self { atomic, items }
+    }
+
+    /// Canonical payload map.
+    pub fn payload(&self) -> BTreeMap<String, String> {
+        let mut payload = BTreeMap::new();
+        insert(&mut payload, "atomic", self.atomic.to_string());
+        for (index, item) in self.items.iter().enumerate() {
+            for (field, value) in item {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                insert(&mut payload, &format!("{:04}.{}", index, field), value);
+            }
+        }
+        payload
+    }
+
+    pub fn into_signable(self, timestamp: i64, nonce: u64) -> SignableMessage {
+        SignableMessage::new(ACTION_BATCH_ORDERS, self.payload(), timestamp, nonce)
+    }
+}
+
+fn insert(payload: &mut BTreeMap<String, String>, key: &str, value: impl Into<String>) {
+    payload.insert(key.to_owned(), value.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const TS: i64 = 1_708_123_456_789_000_000;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn canonical_json(message: &SignableMessage) -> String {
+        String::from_utf8(message.canonical_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_decimal() {
+        assert_eq!(canonical_decimal(dec("50000.2500")), "50000.25");
+        assert_eq!(canonical_decimal(dec("1.0")), "1");
+        assert_eq!(canonical_decimal(dec("0.00000001")), "0.00000001");
+        assert_eq!(canonical_decimal(Decimal::from_scientific("1e3").unwrap()), "1000");
+        assert_eq!(canonical_decimal(dec("-0.000")), "0");
+    }
+
+    // Frozen vectors: a change here breaks every client signature.
+
+    #[test]
+    fn test_create_order_vector() {
+        let message = SignableOrder::new("BTC/USDT", Side::BUY, Some(dec("50000.2500")), dec("0.50"))
+            .with_time_in_force(TimeInForce::IOC)
+            .into_signable(TS, 7);
+        assert_eq!(
+            canonical_json(&message),
+            r#"{"version":"1.0.0","action":"CreateOrder","payload":{"order_type":"LIMIT","price":"50000.25","quantity":"0.5","side":"BUY","symbol":"BTC/USDT","time_in_force":"IOC"},"timestamp":1708123456789000000,"nonce":7}"#
+        );
+
+        let message = SignableOrder::new("ETH/USDT", Side::SELL, None, dec("2"))
+            .with_time_in_force(TimeInForce::GTD(TS + 1))
+            .into_signable(TS, 8);
+        assert_eq!(
+            canonical_json(&message),
+            r#"{"version":"1.0.0","action":"CreateOrder","payload":{"expire_at":"1708123456789000001","order_type":"MARKET","quantity":"2","side":"SELL","symbol":"ETH/USDT","time_in_force":"GTD"},"timestamp":1708123456789000000,"nonce":8}"#
+        );
+
+        let message = SignableOrder::new("BTC/USDT", Side::SELL, Some(dec("1")), dec("3"))
+            .with_client_order_id("strat-42")
+            .into_signable(TS, 11);
+        assert_eq!(
+            canonical_json(&message),
+            r#"{"version":"1.0.0","action":"CreateOrder","payload":{"client_order_id":"strat-42","order_type":"LIMIT","price":"1","quantity":"3","side":"SELL","symbol":"BTC/USDT","time_in_force":"GTC"},"timestamp":1708123456789000000,"nonce":11}"#
+        );
+    }
+
+    #[test]
+    fn test_cancel_vector() {
+        let message = SignableCancel::new("0190b6d1-8a6e-7c3e-9f1a-2b3c4d5e6f70").into_signable(TS, 9);
+        assert_eq!(
+            canonical_json(&message),
+            r#"{"version":"1.0.0","action":"CancelOrder","payload":{"order_id":"0190b6d1-8a6e-7c3e-9f1a-2b3c4d5e6f70"},"timestamp":1708123456789000000,"nonce":9}"#
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_vector() {
+        let message = SignableWithdrawal::new("USDT", dec("1250.000"), "0x00000000000000000000000000000000000000aa")
+            .into_signable(TS, 10);
+        assert_eq!(
+            canonical_json(&message),
+            r#"{"version":"1.0.0","action":"Withdraw","payload":{"amount":"1250","asset":"USDT","destination":"0x00000000000000000000000000000000000000aa"},"timestamp":1708123456789000000,"nonce":10}"#
+        );
+    }
+
+    #[test]
+    fn test_batch_vector() {
+        let items = serde_json::json!([
+            {"action": "place", "symbol": "BTC/USDT", "side": "BUY", "price": "100.5", "quantity": "1"},
+            {"action": "cancel", "order_id": "0190b6d1-8a6e-7c3e-9f1a-2b3c4d5e6f70"},
+            {"action": "place", "symbol": "ETH/USDT", "side": "SELL", "quantity": "2", "expire_at": 5}
+        ]);
+        let items = items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item.as_object().unwrap().clone())
+            .collect();
+        let message = SignableBatch::new(true, items).into_signable(TS, 12);
+        assert_eq!(
+            canonical_json(&message),
+            concat!(
+                r#"{"version":"1.0.0","action":"BatchOrders","payload":{"#,
+                r#""0000.action":"place","0000.price":"100.5","0000.quantity":"1","0000.side":"BUY","#,
+                r#""0000.symbol":"BTC/USDT","0001.action":"cancel","#,
+                r#""0001.order_id":"0190b6d1-8a6e-7c3e-9f1a-2b3c4d5e6f70","0002.action":"place","#,
+                r#""0002.expire_at":"5","0002.quantity":"2","0002.side":"SELL","0002.symbol":"ETH/USDT","#,
+                r#""atomic":"true"},"timestamp":1708123456789000000,"nonce":12}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_equal_values_sign_identically() {
+        let a = SignableOrder::new("BTC/USDT", Side::BUY, Some(dec("100.10")), dec("1.000")).into_signable(TS, 1);
+        let b = SignableOrder::new("BTC/USDT", Side::BUY, Some(dec("100.1")), dec("1")).into_signable(TS, 1);
+        assert_eq!(a.hash(), b.hash());
+    }
+}
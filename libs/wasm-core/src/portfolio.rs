@@ -10,6 +10,7 @@ use std::collections::BTreeMap;
 use types::account::Balance;
 use types::ids::AccountId;
 use types::numeric::Price;
+use types::order::Side;
 use types::position::{Position, PositionSide};
 
 // ---------------------------------------------------------------------------
@@ -43,6 +44,222 @@ pub struct Portfolio {
 
     /// Mark / index prices keyed by symbol (sorted)
     pub prices: BTreeMap<String, Price>,
+
+    /// Lot-level fill history for realized PnL
+    #[serde(default)]
+    pub lots: LotBook,
+}
+
+// ---------------------------------------------------------------------------
+// Lot accounting
+// ---------------------------------------------------------------------------
+
+/// Which open lots a closing fill consumes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LotMethod {
+    /// Oldest lot first
+    #[default]
+    Fifo,
+    /// Newest lot first
+    Lifo,
+    /// All opens merged into one lot at the weighted-average price
+    WeightedAverage,
+}
+
+/// Quantity opened by one fill and not yet closed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    pub quantity: Decimal,
+    pub price: Decimal,
+    /// Opening fee still to be realized, released pro rata as the lot closes
+    pub fee: Decimal,
+    pub timestamp: i64,
+}
+
+/// Open lots for one symbol, all on the same side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionLots {
+    pub side: PositionSide,
+    pub lots: Vec<Lot>,
+}
+
+impl PositionLots {
+    /// Total open quantity.
+    pub fn quantity(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+}
+
+/// PnL realized by closing (part of) one lot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RealizedPnl {
+    pub symbol: String,
+    /// Side of the lot that was closed
+    pub side: PositionSide,
+    pub quantity: Decimal,
+    pub open_price: Decimal,
+    pub close_price: Decimal,
+    /// Opening and closing fees attributed to this quantity
+    pub fee: Decimal,
+    /// Price PnL less `fee`
+    pub pnl: Decimal,
+    /// When the lot was opened
+    pub opened_at: i64,
+    pub timestamp: i64,
+}
+
+/// Lot-based realized PnL tracking across symbols.
+///
+/// Fees are split across the pieces of a fill by quantity; the opening
+/// share stays on the lot and is realized with it, so every fee lands in
+/// exactly one record. Intermediate values round at `INTERNAL_DP`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LotBook {
+    pub method: LotMethod,
+    /// Open lots keyed by symbol (sorted)
+    pub positions: BTreeMap<String, PositionLots>,
+    /// Every realized record, in fill order
+    pub realized: Vec<RealizedPnl>,
+    /// Cumulative realized PnL keyed by symbol (sorted)
+    pub realized_by_symbol: BTreeMap<String, Decimal>,
+}
+
+impl LotBook {
+    /// Create an empty book using the given lot method.
+    pub fn new(method: LotMethod) -> Self {
+        Self {
+            method,
+            ..Self::default()
+        }
+    }
+
+    /// Apply a fill, returning the records for any quantity it closed.
+    ///
+    /// A fill larger than the open position closes it entirely and opens
+    /// the remainder on the other side.
+    pub fn apply_fill(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        quantity: Decimal,
+        price: Decimal,
+        fee: Decimal,
+        timestamp: i64,
+    ) -> Vec<RealizedPnl> {
+        let mut records = Vec::new();
+        if quantity <= Decimal::ZERO {
+            return records;
+        }
+        let fill_side = match side {
+            Side::BUY => PositionSide::LONG,
+            Side::SELL => PositionSide::SHORT,
+        };
+        let method = self.method;
+        let open = self
+            .positions
+            .entry(symbol.to_owned())
+            .or_insert_with(|| PositionLots { side: fill_side, lots: Vec::new() });
+
+        let mut remaining = quantity;
+        let mut fee_left = fee;
+        // Share of the fill fee for `piece` of the `remaining` quantity;
+        // the last piece takes whatever is left so nothing is lost to rounding
+        let mut take_fee = |piece: Decimal, remaining: Decimal| {
+            let share = if piece == remaining {
+                fee_left
+            } else {
+                round_internal(fee * piece / quantity)
+            };
+            fee_left -= share;
+            share
+        };
+
+        if open.side != fill_side {
+            while remaining > Decimal::ZERO {
+                let index = match method {
+                    LotMethod::Lifo => open.lots.len().checked_sub(1),
+                    LotMethod::Fifo | LotMethod::WeightedAverage => (!open.lots.is_empty()).then_some(0),
+                };
+                let Some(index) = index else { break };
+                let lot = &mut open.lots[index];
+
+                let closed = remaining.min(lot.quantity);
+                let open_fee = if closed == lot.quantity {
+                    lot.fee
+                } else {
+                    round_internal(lot.fee * closed / lot.quantity)
+                };
+                let record_fee = open_fee + take_fee(closed, remaining);
+                let price_pnl = match open.side {
+                    PositionSide::LONG => (price - lot.price) * closed,
+                    PositionSide::SHORT => (lot.price - price) * closed,
+                };
+                records.push(RealizedPnl {
+                    symbol: symbol.to_owned(),
+                    side: open.side,
+                    quantity: closed,
+                    open_price: lot.price,
+                    close_price: price,
+                    fee: record_fee,
+                    pnl: round_internal(price_pnl) - record_fee,
+                    opened_at: lot.timestamp,
+                    timestamp,
+                });
+
+                lot.quantity -= closed;
+                lot.fee -= open_fee;
+                remaining -= closed;
+                if lot.quantity.is_zero() {
+                    open.lots.remove(index);
+                }
+            }
+            if open.lots.is_empty() {
+                open.side = fill_side;
+            }
+        }
+
+        if remaining > Decimal::ZERO {
+            let lot_fee = take_fee(remaining, remaining);
+            match (method, open.lots.first_mut()) {
+                (LotMethod::WeightedAverage, Some(lot)) => {
+                    let total = lot.quantity + remaining;
+                    lot.price = round_internal((lot.price * lot.quantity + price * remaining) / total);
+                    lot.quantity = total;
+                    lot.fee += lot_fee;
+                }
+                _ => open.lots.push(Lot {
+                    quantity: remaining,
+                    price,
+                    fee: lot_fee,
+                    timestamp,
+                }),
+            }
+        }
+        if open.lots.is_empty() {
+            self.positions.remove(symbol);
+        }
+
+        for record in &records {
+            *self.realized_by_symbol.entry(symbol.to_owned()).or_default() += record.pnl;
+        }
+        self.realized.extend(records.iter().cloned());
+        records
+    }
+
+    /// Open lots for a symbol.
+    pub fn position(&self, symbol: &str) -> Option<&PositionLots> {
+        self.positions.get(symbol)
+    }
+
+    /// Cumulative realized PnL for one symbol.
+    pub fn realized_pnl(&self, symbol: &str) -> Decimal {
+        round_display(self.realized_by_symbol.get(symbol).copied().unwrap_or_default())
+    }
+
+    /// Cumulative realized PnL across all symbols.
+    pub fn total_realized_pnl(&self) -> Decimal {
+        round_display(self.realized_by_symbol.values().copied().sum())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -73,6 +290,15 @@ impl Portfolio {
             balances: BTreeMap::new(),
             positions: BTreeMap::new(),
             prices: BTreeMap::new(),
+            lots: LotBook::default(),
+        }
+    }
+
+    /// Create an empty portfolio that accounts lots with `method`.
+    pub fn with_lot_method(account_id: AccountId, method: LotMethod) -> Self {
+        Self {
+            lots: LotBook::new(method),
+            ..Self::new(account_id)
         }
     }
 
@@ -94,6 +320,19 @@ impl Portfolio {
         self.prices.insert(symbol.to_owned(), price);
     }
 
+    /// Record a fill in the lot book, returning any PnL it realized.
+    pub fn apply_fill(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        quantity: Decimal,
+        price: Decimal,
+        fee: Decimal,
+        timestamp: i64,
+    ) -> Vec<RealizedPnl> {
+        self.lots.apply_fill(symbol, side, quantity, price, fee, timestamp)
+    }
+
     // -- balance queries ---------------------------------------------------
 
     /// Retrieve balance for a single asset.
@@ -152,6 +391,7 @@ impl Portfolio {
     /// Total realized PnL across every open position.
     ///
     /// This is the sum of `position.realized_pnl` for all tracked positions.
+    /// PnL booked through fills is tracked separately by `lots`.
     pub fn total_realized_pnl(&self) -> Decimal {
         let mut total = Decimal::ZERO;
         for position in self.positions.values() {
@@ -218,6 +458,11 @@ fn compute_unrealized_pnl(position: &Position) -> Decimal {
     pnl.round_dp_with_strategy(INTERNAL_DP, RoundingStrategy::MidpointAwayFromZero)
 }
 
+/// Round a value to internal precision using HALF_UP (spec §12.4.2).
+fn round_internal(value: Decimal) -> Decimal {
+    value.round_dp_with_strategy(INTERNAL_DP, RoundingStrategy::MidpointAwayFromZero)
+}
+
 /// Round a value to display precision using HALF_UP (spec §12.4.2).
 fn round_display(value: Decimal) -> Decimal {
     value.round_dp_with_strategy(DISPLAY_DP, RoundingStrategy::MidpointAwayFromZero)
@@ -485,4 +730,131 @@ mod tests {
         let restored: PortfolioSummary = serde_json::from_str(&json).unwrap();
         assert_eq!(s, restored);
     }
+
+    // -- lot accounting ----------------------------------------------------
+
+    const T0: i64 = 1_708_123_456_789_000_000;
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str_exact(value).unwrap()
+    }
+
+    /// Buy 1 @ 100, buy 1 @ 200, sell 1 @ 300.
+    fn close_one(method: LotMethod) -> (LotBook, Vec<RealizedPnl>) {
+        let mut book = LotBook::new(method);
+        book.apply_fill("BTC/USDT", Side::BUY, d("1"), d("100"), Decimal::ZERO, T0);
+        book.apply_fill("BTC/USDT", Side::BUY, d("1"), d("200"), Decimal::ZERO, T0 + 1);
+        let records = book.apply_fill("BTC/USDT", Side::SELL, d("1"), d("300"), Decimal::ZERO, T0 + 2);
+        (book, records)
+    }
+
+    #[test]
+    fn test_lot_methods_pick_different_lots() {
+        let (fifo, records) = close_one(LotMethod::Fifo);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].open_price, d("100"));
+        assert_eq!(records[0].opened_at, T0);
+        assert_eq!(fifo.realized_pnl("BTC/USDT"), d("200"));
+        assert_eq!(fifo.position("BTC/USDT").unwrap().lots[0].price, d("200"));
+
+        let (lifo, records) = close_one(LotMethod::Lifo);
+        assert_eq!(records[0].open_price, d("200"));
+        assert_eq!(lifo.realized_pnl("BTC/USDT"), d("100"));
+
+        let (average, records) = close_one(LotMethod::WeightedAverage);
+        assert_eq!(records[0].open_price, d("150"));
+        assert_eq!(average.realized_pnl("BTC/USDT"), d("150"));
+        let open = average.position("BTC/USDT").unwrap();
+        assert_eq!(open.lots.len(), 1);
+        assert_eq!(open.quantity(), d("1"));
+    }
+
+    #[test]
+    fn test_close_spanning_lots_splits_records() {
+        let mut book = LotBook::new(LotMethod::Fifo);
+        book.apply_fill("ETH/USDT", Side::SELL, d("1"), d("3000"), Decimal::ZERO, T0);
+        book.apply_fill("ETH/USDT", Side::SELL, d("2"), d("3100"), Decimal::ZERO, T0 + 1);
+        // Buy 3 @ 2900 with a 3 fee: one record per lot, fee split by quantity
+        let records = book.apply_fill("ETH/USDT", Side::BUY, d("3"), d("2900"), d("3"), T0 + 2);
+        assert_eq!(records.len(), 2);
+        assert_eq!((records[0].quantity, records[0].fee, records[0].pnl), (d("1"), d("1"), d("99")));
+        assert_eq!((records[1].quantity, records[1].fee, records[1].pnl), (d("2"), d("2"), d("398")));
+        assert!(book.position("ETH/USDT").is_none());
+        assert_eq!(book.total_realized_pnl(), d("497"));
+    }
+
+    #[test]
+    fn test_flip_closes_then_opens_opposite_side() {
+        let mut book = LotBook::new(LotMethod::Fifo);
+        book.apply_fill("BTC/USDT", Side::BUY, d("2"), d("100"), Decimal::ZERO, T0);
+
+        // Sell 5 while long 2: close 2, open 3 short; fee 5 split 2 / 3
+        let records = book.apply_fill("BTC/USDT", Side::SELL, d("5"), d("110"), d("5"), T0 + 1);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].side, PositionSide::LONG);
+        assert_eq!(records[0].quantity, d("2"));
+        assert_eq!(records[0].pnl, d("18"));
+
+        let open = book.position("BTC/USDT").unwrap();
+        assert_eq!(open.side, PositionSide::SHORT);
+        assert_eq!(open.lots, vec![Lot { quantity: d("3"), price: d("110"), fee: d("3"), timestamp: T0 + 1 }]);
+
+        // Covering the short realizes its carried opening fee
+        let records = book.apply_fill("BTC/USDT", Side::BUY, d("3"), d("100"), Decimal::ZERO, T0 + 2);
+        assert_eq!(records[0].side, PositionSide::SHORT);
+        assert_eq!(records[0].fee, d("3"));
+        assert_eq!(records[0].pnl, d("27"));
+        assert_eq!(book.realized_pnl("BTC/USDT"), d("45"));
+        assert_eq!(book.realized.len(), 2);
+    }
+
+    #[test]
+    fn test_fee_split_is_exact() {
+        let mut book = LotBook::new(LotMethod::Fifo);
+        book.apply_fill("BTC/USDT", Side::BUY, d("3"), d("100"), d("1"), T0);
+        let mut fees = Decimal::ZERO;
+        for i in 0..3 {
+            let records = book.apply_fill("BTC/USDT", Side::SELL, d("1"), d("100"), Decimal::ZERO, T0 + i + 1);
+            fees += records[0].fee;
+        }
+        // Thirds of the opening fee round, but the last close takes the rest
+        assert_eq!(fees, d("1"));
+        assert_eq!(book.total_realized_pnl(), d("-1"));
+    }
+
+    #[test]
+    fn test_lot_state_serialization_roundtrip() {
+        let mut book = LotBook::new(LotMethod::Lifo);
+        book.apply_fill("BTC/USDT", Side::BUY, d("1.5"), d("50000.12345678"), d("0.75"), T0);
+        book.apply_fill("BTC/USDT", Side::BUY, d("0.5"), d("51000"), d("0.25"), T0 + 1);
+        book.apply_fill("BTC/USDT", Side::SELL, d("1"), d("52000"), d("0.5"), T0 + 2);
+
+        let json = serde_json::to_string(&book).unwrap();
+        let mut restored: LotBook = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, book);
+
+        // A reloaded book carries on exactly as the original
+        let a = book.apply_fill("BTC/USDT", Side::SELL, d("2"), d("49000"), d("1"), T0 + 3);
+        let b = restored.apply_fill("BTC/USDT", Side::SELL, d("2"), d("49000"), d("1"), T0 + 3);
+        assert_eq!(a, b);
+        assert_eq!(restored, book);
+    }
+
+    #[test]
+    fn test_portfolio_persists_lots() {
+        let mut p = Portfolio::with_lot_method(AccountId::new(), LotMethod::WeightedAverage);
+        p.apply_fill("BTC/USDT", Side::BUY, d("1"), d("100"), Decimal::ZERO, T0);
+        p.apply_fill("BTC/USDT", Side::BUY, d("1"), d("200"), Decimal::ZERO, T0 + 1);
+        p.apply_fill("BTC/USDT", Side::SELL, d("1"), d("300"), Decimal::ZERO, T0 + 2);
+
+        let restored = Portfolio::from_json(&p.to_json().unwrap()).unwrap();
+        assert_eq!(restored, p);
+        assert_eq!(restored.lots.realized_pnl("BTC/USDT"), d("150"));
+
+        // Portfolios saved before lot tracking load with an empty book
+        let mut value: serde_json::Value = serde_json::from_str(&sample_portfolio().to_json().unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("lots");
+        let legacy = Portfolio::from_json(&value.to_string()).unwrap();
+        assert_eq!(legacy.lots, LotBook::default());
+    }
 }
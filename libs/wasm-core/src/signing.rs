@@ -4,7 +4,10 @@
 //! Ed25519 signing/verification, nonce tracking, and replay protection.
 //! Implements spec §19 (Security Invariants).
 
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand::{CryptoRng, RngCore};
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -20,6 +23,9 @@ pub const SIGNING_SCHEMA_VERSION: &str = "1.0.0";
 /// Maximum age of a signable message (replay protection window, 5 minutes).
 const MAX_MESSAGE_AGE_NS: i64 = 5 * 60 * 1_000_000_000;
 
+/// PBKDF2-HMAC-SHA256 rounds for software HSM keys.
+pub const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
 // ---------------------------------------------------------------------------
 // Signable message
 // ---------------------------------------------------------------------------
@@ -69,7 +75,7 @@ impl SignableMessage {
     /// SHA-256 hash of the canonical bytes.
     pub fn hash(&self) -> [u8; 32] {
         let mut hasher = Sha256::new();
-        hasher.update(&self.canonical_bytes());
+        hasher.update(self.canonical_bytes());
         hasher.finalize().into()
     }
 
@@ -109,9 +115,34 @@ pub fn sign_message(message: &SignableMessage, signing_key: &SigningKey) -> Sign
     }
 }
 
+/// Sign a message through a hardware wallet in one go.
+///
+/// Begins a request for the message hash and completes it with the user's
+/// answer. UIs that show a confirm step in between call `begin_sign` and
+/// `complete_sign` themselves.
+pub fn sign_message_with_wallet<W: HardwareWallet + ?Sized>(
+    message: &SignableMessage,
+    wallet: &mut W,
+    confirmation: UserConfirmation,
+) -> Result<SignedMessage, SigningError> {
+    let request = wallet.begin_sign(&message.hash())?;
+    let signature = wallet.complete_sign(request, confirmation)?;
+    if signature.len() != 64 {
+        return Err(SigningError::InvalidSignature);
+    }
+
+    Ok(SignedMessage {
+        message: message.clone(),
+        signature: hex::encode(signature),
+        public_key: hex::encode(wallet.public_key()?),
+    })
+}
+
 /// Verify a signed message.
 ///
 /// Returns `Ok(())` if the signature is valid, `Err` otherwise.
+/// Uses strict Ed25519 verification: non-canonical signatures and
+/// small-order (weak) public keys are rejected.
 pub fn verify_signature(signed: &SignedMessage) -> Result<(), SigningError> {
     let pub_bytes = hex::decode(&signed.public_key)
         .map_err(|_| SigningError::InvalidPublicKey)?;
@@ -131,7 +162,7 @@ pub fn verify_signature(signed: &SignedMessage) -> Result<(), SigningError> {
 
     let hash = signed.message.hash();
     verifying_key
-        .verify(&hash, &signature)
+        .verify_strict(&hash, &signature)
         .map_err(|_| SigningError::VerificationFailed)
 }
 
@@ -139,13 +170,55 @@ pub fn verify_signature(signed: &SignedMessage) -> Result<(), SigningError> {
 // Nonce tracking (replay protection)
 // ---------------------------------------------------------------------------
 
+/// Largest supported anti-replay window (one bit per nonce in a `u64`).
+pub const MAX_NONCE_WINDOW: u32 = 64;
+
+/// How a `NonceTracker` orders nonces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NonceMode {
+    /// Each nonce must exceed the last seen
+    #[default]
+    Strict,
+    /// Any unseen nonce among the `size` highest is accepted once
+    /// (IPsec-style anti-replay), so interleaved clients can sign out
+    /// of order
+    Windowed { size: u32 },
+}
+
+/// Per-account nonce state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceWindow {
+    /// Highest nonce accepted
+    pub highest: u64,
+    /// Bit `i` set when nonce `highest - i` has been accepted
+    pub seen: u64,
+}
+
+impl Default for NonceWindow {
+    fn default() -> Self {
+        // Nonce 0 is never valid
+        Self { highest: 0, seen: 1 }
+    }
+}
+
+/// Serializable snapshot of a `NonceTracker`, for local storage or
+/// gateway state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceTrackerState {
+    pub mode: NonceMode,
+    /// account_id hex → window
+    pub accounts: BTreeMap<String, NonceWindow>,
+}
+
 /// Nonce validator for replay protection.
 ///
-/// Tracks the last-seen nonce per account and rejects replayed messages.
+/// Tracks the nonces seen per account and rejects replayed messages.
+/// Strict by default; see `NonceMode::Windowed` for out-of-order clients.
 #[derive(Debug, Clone, Default)]
 pub struct NonceTracker {
-    /// Last seen nonce per account (account_id hex → nonce)
-    last_nonce: BTreeMap<String, u64>,
+    mode: NonceMode,
+    /// Nonce state per account (account_id hex → window)
+    windows: BTreeMap<String, NonceWindow>,
 }
 
 impl NonceTracker {
@@ -153,22 +226,112 @@ impl NonceTracker {
         Self::default()
     }
 
+    /// Tracker accepting nonces out of order within a window of `size`.
+    ///
+    /// # Panics
+    /// If `size` is 0 or above `MAX_NONCE_WINDOW`.
+    pub fn windowed(size: u32) -> Self {
+        assert!(
+            (1..=MAX_NONCE_WINDOW).contains(&size),
+            "nonce window must be 1..={MAX_NONCE_WINDOW}, got {size}"
+        );
+        Self {
+            mode: NonceMode::Windowed { size },
+            windows: BTreeMap::new(),
+        }
+    }
+
+    pub fn mode(&self) -> NonceMode {
+        self.mode
+    }
+
     /// Validate and advance the nonce for an account.
     ///
-    /// Returns `Ok(())` if the nonce is strictly greater than the last seen.
+    /// Strict mode returns `Ok(())` if the nonce is strictly greater than
+    /// the last seen. Windowed mode also accepts an unseen nonce no more
+    /// than `size - 1` below the highest.
     pub fn validate_and_advance(
         &mut self,
         account_id: &str,
         nonce: u64,
     ) -> Result<(), SigningError> {
-        let last = self.last_nonce.get(account_id).copied().unwrap_or(0);
-        if nonce <= last {
-            return Err(SigningError::NonceReplay {
-                provided: nonce,
-                last_seen: last,
-            });
+        let window = self.windows.get(account_id).copied().unwrap_or_default();
+        let size = match self.mode {
+            NonceMode::Strict => 1,
+            NonceMode::Windowed { size } => size,
+        };
+
+        let updated = if nonce > window.highest {
+            let shift = nonce - window.highest;
+            let seen = if shift >= u64::from(MAX_NONCE_WINDOW) { 0 } else { window.seen << shift };
+            NonceWindow { highest: nonce, seen: (seen | 1) & window_mask(size) }
+        } else {
+            let offset = window.highest - nonce;
+            let bit = u32::try_from(offset).ok().and_then(|offset| 1u64.checked_shl(offset));
+            let Some(bit) = bit.filter(|_| offset < u64::from(size)) else {
+                return Err(match self.mode {
+                    NonceMode::Strict => SigningError::NonceReplay {
+                        provided: nonce,
+                        last_seen: window.highest,
+                    },
+                    NonceMode::Windowed { size } => SigningError::NonceOutsideWindow {
+                        provided: nonce,
+                        highest: window.highest,
+                        window: size,
+                    },
+                });
+            };
+            if window.seen & bit != 0 {
+                return Err(SigningError::NonceReplay {
+                    provided: nonce,
+                    last_seen: window.highest,
+                });
+            }
+            NonceWindow { seen: window.seen | bit, ..window }
+        };
+
+        self.windows.insert(account_id.to_owned(), updated);
+        Ok(())
+    }
+
+    /// Snapshot of the mode and every account's nonce state.
+    pub fn export_state(&self) -> NonceTrackerState {
+        NonceTrackerState {
+            mode: self.mode,
+            accounts: self.windows.clone(),
         }
-        self.last_nonce.insert(account_id.to_owned(), nonce);
+    }
+
+    /// Replace the mode and per-account state with a snapshot.
+    ///
+    /// The snapshot is checked first and left unapplied if the window size
+    /// is outside `1..=MAX_NONCE_WINDOW` or any account's window could not
+    /// have been produced by `validate_and_advance` in that mode.
+    pub fn import_state(&mut self, state: NonceTrackerState) -> Result<(), SigningError> {
+        let size = match state.mode {
+            NonceMode::Strict => 1,
+            NonceMode::Windowed { size } if (1..=MAX_NONCE_WINDOW).contains(&size) => size,
+            NonceMode::Windowed { size } => {
+                return Err(SigningError::InvalidNonceState(format!(
+                    "window must be 1..={MAX_NONCE_WINDOW}, got {size}"
+                )));
+            }
+        };
+        for (account_id, window) in &state.accounts {
+            // The highest nonce is always seen, and no bit may fall outside
+            // the window or below nonce 0
+            let below_zero = window.highest.checked_add(1).and_then(|n| u32::try_from(n).ok());
+            let below_zero = below_zero.and_then(|n| window.seen.checked_shr(n)).unwrap_or(0);
+            if window.seen & 1 == 0 || window.seen & !window_mask(size) != 0 || below_zero != 0 {
+                return Err(SigningError::InvalidNonceState(format!(
+                    "inconsistent window for account {account_id}: highest {}, seen {:#x}",
+                    window.highest, window.seen
+                )));
+            }
+        }
+
+        self.mode = state.mode;
+        self.windows = state.accounts;
         Ok(())
     }
 
@@ -189,14 +352,48 @@ impl NonceTracker {
     }
 }
 
+/// Bits of a seen-bitmap covered by a window of `size`.
+fn window_mask(size: u32) -> u64 {
+    if size >= MAX_NONCE_WINDOW {
+        u64::MAX
+    } else {
+        (1 << size) - 1
+    }
+}
+
 // ---------------------------------------------------------------------------
-// Hardware wallet stub
+// Hardware wallets
 // ---------------------------------------------------------------------------
 
-/// Trait for hardware wallet integration (stub).
+/// Handle for a signing request awaiting the user's confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SignRequestId(pub u64);
+
+/// The user's answer to a confirm-on-device prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserConfirmation {
+    /// Approved, with the passphrase or PIN the device asks for
+    Approve { passphrase: String },
+    /// Declined on the device
+    Reject,
+}
+
+/// Trait for hardware wallet integration.
+///
+/// Signing is two-step so the UI can model the confirm-on-device prompt:
+/// `begin_sign` queues a hash, `complete_sign` answers it exactly once.
 pub trait HardwareWallet {
-    /// Sign a message hash with the hardware wallet.
-    fn sign(&self, message_hash: &[u8; 32]) -> Result<Vec<u8>, SigningError>;
+    /// Queue a message hash for signing.
+    fn begin_sign(&mut self, message_hash: &[u8; 32]) -> Result<SignRequestId, SigningError>;
+
+    /// Answer a queued request, returning the 64-byte Ed25519 signature.
+    ///
+    /// The request is consumed whatever the outcome.
+    fn complete_sign(
+        &mut self,
+        request: SignRequestId,
+        confirmation: UserConfirmation,
+    ) -> Result<Vec<u8>, SigningError>;
 
     /// Get the public key from the hardware wallet.
     fn public_key(&self) -> Result<Vec<u8>, SigningError>;
@@ -207,7 +404,15 @@ pub trait HardwareWallet {
 pub struct StubHardwareWallet;
 
 impl HardwareWallet for StubHardwareWallet {
-    fn sign(&self, _message_hash: &[u8; 32]) -> Result<Vec<u8>, SigningError> {
+    fn begin_sign(&mut self, _message_hash: &[u8; 32]) -> Result<SignRequestId, SigningError> {
+        Err(SigningError::HardwareWalletUnsupported)
+    }
+
+    fn complete_sign(
+        &mut self,
+        _request: SignRequestId,
+        _confirmation: UserConfirmation,
+    ) -> Result<Vec<u8>, SigningError> {
         Err(SigningError::HardwareWalletUnsupported)
     }
 
@@ -216,6 +421,144 @@ impl HardwareWallet for StubHardwareWallet {
     }
 }
 
+/// An Ed25519 key encrypted under a passphrase.
+///
+/// The passphrase is stretched with PBKDF2-HMAC-SHA256 into a
+/// ChaCha20-Poly1305 key; all byte fields are hex so the struct can be
+/// persisted as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedKey {
+    pub public_key: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub kdf_iterations: u32,
+}
+
+impl EncryptedKey {
+    /// Encrypt `signing_key` under `passphrase` with fresh salt and nonce.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        signing_key: &SigningKey,
+        passphrase: &str,
+        kdf_iterations: u32,
+        rng: &mut R,
+    ) -> Self {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let cipher = passphrase_cipher(passphrase, &salt, kdf_iterations);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), signing_key.to_bytes().as_slice())
+            .expect("ChaCha20-Poly1305 encryption of 32 bytes must not fail");
+
+        Self {
+            public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            kdf_iterations,
+        }
+    }
+
+    /// Decrypt the key, failing if the passphrase is wrong or the record
+    /// was tampered with.
+    pub fn decrypt(&self, passphrase: &str) -> Result<SigningKey, SigningError> {
+        let decode = |field: &str| hex::decode(field).map_err(|_| SigningError::CorruptKey);
+        let salt = decode(&self.salt)?;
+        let nonce = decode(&self.nonce)?;
+        let ciphertext = decode(&self.ciphertext)?;
+        if nonce.len() != 12 {
+            return Err(SigningError::CorruptKey);
+        }
+
+        let cipher = passphrase_cipher(passphrase, &salt, self.kdf_iterations);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| SigningError::WrongPassphrase)?;
+        let key_bytes: [u8; 32] = plaintext.try_into().map_err(|_| SigningError::CorruptKey)?;
+
+        let signing_key = SigningKey::from_bytes(&key_bytes);
+        if hex::encode(signing_key.verifying_key().to_bytes()) != self.public_key {
+            return Err(SigningError::CorruptKey);
+        }
+        Ok(signing_key)
+    }
+}
+
+/// Cipher keyed by the passphrase stretched over `salt`.
+fn passphrase_cipher(passphrase: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+/// Software stand-in for a hardware wallet.
+///
+/// Holds only the encrypted key; each signature decrypts it with the
+/// passphrase given at confirmation, so the plaintext key never outlives
+/// a `complete_sign` call.
+#[derive(Debug, Clone)]
+pub struct SoftwareHsm {
+    key: EncryptedKey,
+    /// Hashes awaiting confirmation
+    pending: BTreeMap<SignRequestId, [u8; 32]>,
+    next_request: u64,
+}
+
+impl SoftwareHsm {
+    /// Load an HSM from an encrypted key.
+    pub fn new(key: EncryptedKey) -> Self {
+        Self {
+            key,
+            pending: BTreeMap::new(),
+            next_request: 1,
+        }
+    }
+
+    /// The encrypted key, for persistence.
+    pub fn encrypted_key(&self) -> &EncryptedKey {
+        &self.key
+    }
+
+    /// Requests begun and not yet completed.
+    pub fn pending_requests(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl HardwareWallet for SoftwareHsm {
+    fn begin_sign(&mut self, message_hash: &[u8; 32]) -> Result<SignRequestId, SigningError> {
+        let request = SignRequestId(self.next_request);
+        self.next_request += 1;
+        self.pending.insert(request, *message_hash);
+        Ok(request)
+    }
+
+    fn complete_sign(
+        &mut self,
+        request: SignRequestId,
+        confirmation: UserConfirmation,
+    ) -> Result<Vec<u8>, SigningError> {
+        let hash = self
+            .pending
+            .remove(&request)
+            .ok_or(SigningError::UnknownSignRequest(request.0))?;
+        match confirmation {
+            UserConfirmation::Approve { passphrase } => {
+                let signing_key = self.key.decrypt(&passphrase)?;
+                Ok(signing_key.sign(&hash).to_bytes().to_vec())
+            }
+            UserConfirmation::Reject => Err(SigningError::SignRequestRejected),
+        }
+    }
+
+    fn public_key(&self) -> Result<Vec<u8>, SigningError> {
+        hex::decode(&self.key.public_key).map_err(|_| SigningError::CorruptKey)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Errors
 // ---------------------------------------------------------------------------
@@ -235,6 +578,12 @@ pub enum SigningError {
     #[error("Nonce replay: provided {provided}, last seen {last_seen}")]
     NonceReplay { provided: u64, last_seen: u64 },
 
+    #[error("Nonce {provided} is outside the window of {window} below {highest}")]
+    NonceOutsideWindow { provided: u64, highest: u64, window: u32 },
+
+    #[error("Invalid nonce state: {0}")]
+    InvalidNonceState(String),
+
     #[error("Message timestamp is in the future")]
     FutureTimestamp,
 
@@ -243,6 +592,18 @@ pub enum SigningError {
 
     #[error("Hardware wallet not supported")]
     HardwareWalletUnsupported,
+
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+
+    #[error("Encrypted key is corrupt")]
+    CorruptKey,
+
+    #[error("Unknown or already completed sign request {0}")]
+    UnknownSignRequest(u64),
+
+    #[error("Sign request rejected by the user")]
+    SignRequestRejected,
 }
 
 // ---------------------------------------------------------------------------
@@ -413,6 +774,113 @@ mod tests {
         assert!(tracker.validate_and_advance("acc2", 1).is_ok());
     }
 
+    #[test]
+    fn test_windowed_nonces_out_of_order_accepted_once() {
+        let mut tracker = NonceTracker::windowed(8);
+        // Two tabs interleaving 1..=6
+        for nonce in [2, 1, 5, 3, 6, 4] {
+            assert!(tracker.validate_and_advance("acc1", nonce).is_ok(), "nonce {nonce}");
+        }
+        assert_eq!(
+            tracker.validate_and_advance("acc1", 3),
+            Err(SigningError::NonceReplay {
+                provided: 3,
+                last_seen: 6
+            })
+        );
+        assert_eq!(
+            tracker.validate_and_advance("acc1", 0),
+            Err(SigningError::NonceReplay {
+                provided: 0,
+                last_seen: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_windowed_nonces_older_than_window_rejected() {
+        let mut tracker = NonceTracker::windowed(8);
+        tracker.validate_and_advance("acc1", 20).unwrap();
+        // 13 is the lowest nonce still inside the window
+        assert!(tracker.validate_and_advance("acc1", 13).is_ok());
+        assert_eq!(
+            tracker.validate_and_advance("acc1", 12),
+            Err(SigningError::NonceOutsideWindow {
+                provided: 12,
+                highest: 20,
+                window: 8
+            })
+        );
+
+        // A jump past the window forgets everything below it
+        tracker.validate_and_advance("acc1", 100).unwrap();
+        assert!(tracker.validate_and_advance("acc1", 99).is_ok());
+        assert!(tracker.validate_and_advance("acc1", 20).is_err());
+    }
+
+    #[test]
+    fn test_nonce_state_export_import() {
+        let mut tracker = NonceTracker::windowed(MAX_NONCE_WINDOW);
+        tracker.validate_and_advance("acc1", 70).unwrap();
+        tracker.validate_and_advance("acc1", 7).unwrap();
+        tracker.validate_and_advance("acc2", 3).unwrap();
+
+        let json = serde_json::to_string(&tracker.export_state()).unwrap();
+        let mut restored = NonceTracker::new();
+        restored.import_state(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(restored.mode(), NonceMode::Windowed { size: MAX_NONCE_WINDOW });
+        assert_eq!(restored.export_state(), tracker.export_state());
+
+        assert!(restored.validate_and_advance("acc1", 7).is_err());
+        assert!(restored.validate_and_advance("acc1", 8).is_ok());
+        assert!(restored.validate_and_advance("acc2", 2).is_ok());
+        assert_eq!(NonceTracker::new().mode(), NonceMode::Strict);
+    }
+
+    #[test]
+    fn test_nonce_state_import_rejects_oversized_window() {
+        let mut tracker = NonceTracker::windowed(8);
+        tracker.validate_and_advance("acc1", 5).unwrap();
+        let before = tracker.export_state();
+
+        let mut state = NonceTrackerState {
+            mode: NonceMode::Windowed { size: 65 },
+            ..Default::default()
+        };
+        state.accounts.insert("acc1".to_owned(), NonceWindow { highest: 100, seen: 1 });
+        assert!(matches!(
+            tracker.import_state(state.clone()),
+            Err(SigningError::InvalidNonceState(_))
+        ));
+        state.mode = NonceMode::Windowed { size: 0 };
+        assert!(tracker.import_state(state).is_err());
+
+        // A refused snapshot leaves the tracker as it was
+        assert_eq!(tracker.export_state(), before);
+        assert!(tracker.validate_and_advance("acc1", 36).is_ok());
+    }
+
+    #[test]
+    fn test_nonce_state_import_rejects_inconsistent_windows() {
+        let import = |mode, highest, seen| {
+            let mut state = NonceTrackerState { mode, ..Default::default() };
+            state.accounts.insert("acc1".to_owned(), NonceWindow { highest, seen });
+            NonceTracker::new().import_state(state)
+        };
+        let windowed = NonceMode::Windowed { size: 8 };
+
+        assert!(import(windowed, 20, 0b1011).is_ok());
+        assert!(import(NonceMode::Strict, 20, 1).is_ok());
+        // Highest not marked seen
+        assert!(import(windowed, 20, 0b1010).is_err());
+        // Bits beyond the window
+        assert!(import(windowed, 20, 1 << 8 | 1).is_err());
+        assert!(import(NonceMode::Strict, 20, 0b11).is_err());
+        // Bits for nonces below zero
+        assert!(import(windowed, 2, 0b1001).is_err());
+        assert!(import(windowed, 3, 0b1001).is_ok());
+    }
+
     #[test]
     fn test_timestamp_within_window() {
         let tracker = NonceTracker::new();
@@ -445,10 +913,10 @@ mod tests {
 
     #[test]
     fn test_hardware_wallet_stub() {
-        let hw = StubHardwareWallet;
+        let mut hw = StubHardwareWallet;
         let hash = [0u8; 32];
         assert_eq!(
-            hw.sign(&hash),
+            hw.begin_sign(&hash),
             Err(SigningError::HardwareWalletUnsupported)
         );
         assert_eq!(
@@ -457,6 +925,75 @@ mod tests {
         );
     }
 
+    /// Few KDF rounds keep the tests fast; production uses the default.
+    fn test_hsm() -> SoftwareHsm {
+        SoftwareHsm::new(EncryptedKey::encrypt(&test_keypair(), "correct horse", 1_000, &mut OsRng))
+    }
+
+    fn approve(passphrase: &str) -> UserConfirmation {
+        UserConfirmation::Approve { passphrase: passphrase.to_owned() }
+    }
+
+    #[test]
+    fn test_software_hsm_signs_like_raw_key() {
+        let mut hsm = test_hsm();
+        let msg = sample_message(1);
+        let signed = sign_message_with_wallet(&msg, &mut hsm, approve("correct horse")).unwrap();
+        assert!(verify_signature(&signed).is_ok());
+        // Ed25519 is deterministic: same signature as the raw key
+        assert_eq!(signed, sign_message(&msg, &test_keypair()));
+        assert_eq!(hsm.pending_requests(), 0);
+    }
+
+    #[test]
+    fn test_software_hsm_wrong_passphrase() {
+        let key = EncryptedKey::encrypt(&test_keypair(), "correct horse", 1_000, &mut OsRng);
+        assert_eq!(key.decrypt("battery staple"), Err(SigningError::WrongPassphrase));
+
+        let mut hsm = SoftwareHsm::new(key);
+        let request = hsm.begin_sign(&sample_message(1).hash()).unwrap();
+        assert_eq!(
+            hsm.complete_sign(request, approve("battery staple")),
+            Err(SigningError::WrongPassphrase)
+        );
+    }
+
+    #[test]
+    fn test_software_hsm_request_completes_once() {
+        let mut hsm = test_hsm();
+        let first = hsm.begin_sign(&sample_message(1).hash()).unwrap();
+        let second = hsm.begin_sign(&sample_message(2).hash()).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(hsm.pending_requests(), 2);
+
+        assert_eq!(hsm.complete_sign(first, approve("correct horse")).unwrap().len(), 64);
+        assert_eq!(
+            hsm.complete_sign(first, approve("correct horse")),
+            Err(SigningError::UnknownSignRequest(first.0))
+        );
+
+        // A rejected request is consumed too
+        assert_eq!(hsm.complete_sign(second, UserConfirmation::Reject), Err(SigningError::SignRequestRejected));
+        assert_eq!(
+            hsm.complete_sign(second, approve("correct horse")),
+            Err(SigningError::UnknownSignRequest(second.0))
+        );
+    }
+
+    #[test]
+    fn test_encrypted_key_persists_and_detects_tampering() {
+        let key = EncryptedKey::encrypt(&test_keypair(), "correct horse", 1_000, &mut OsRng);
+        let json = serde_json::to_string(&key).unwrap();
+        let restored: EncryptedKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.decrypt("correct horse").unwrap().to_bytes(), test_keypair().to_bytes());
+        assert_eq!(SoftwareHsm::new(restored).public_key().unwrap(), test_keypair().verifying_key().to_bytes());
+
+        // Swapping in another public key is caught on decryption
+        let mut swapped = key.clone();
+        swapped.public_key = hex::encode(SigningKey::generate(&mut OsRng).verifying_key().to_bytes());
+        assert_eq!(swapped.decrypt("correct horse"), Err(SigningError::CorruptKey));
+    }
+
     #[test]
     fn test_signing_schema_version() {
         let msg = sample_message(1);
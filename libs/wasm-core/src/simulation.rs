@@ -7,9 +7,9 @@
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use types::fee::FeeTier;
+use types::fee::{FeeSchedule, FeeTier};
 use types::numeric::{Price, Quantity};
-use types::order::Side;
+use types::order::{slippage_fill_limit, Side};
 
 // ---------------------------------------------------------------------------
 // Constants
@@ -49,8 +49,8 @@ impl MockOrderBook {
     ///
     /// Bids are automatically sorted desc, asks asc.
     pub fn new(mut bids: Vec<PriceLevel>, mut asks: Vec<PriceLevel>) -> Self {
-        bids.sort_by(|a, b| b.price.cmp(&a.price)); // descending
-        asks.sort_by(|a, b| a.price.cmp(&b.price)); // ascending
+        bids.sort_by_key(|b| std::cmp::Reverse(b.price)); // descending
+        asks.sort_by_key(|a| a.price); // ascending
         Self { bids, asks }
     }
 
@@ -76,6 +76,10 @@ pub struct SimOrder {
     pub quantity: Quantity,
     /// If `None`, simulate as market order taking best available price.
     pub limit_price: Option<Price>,
+    /// Stop filling once the VWAP would move more than this fraction from
+    /// the best price (0.005 = 0.5%); `None` for no cap.
+    #[serde(default)]
+    pub max_slippage: Option<Decimal>,
 }
 
 // ---------------------------------------------------------------------------
@@ -107,12 +111,59 @@ pub struct SimResult {
     pub avg_execution_price: Decimal,
     /// Slippage relative to best price (percentage, e.g., 0.0012 = 0.12%)
     pub slippage: Decimal,
-    /// Estimated fee (based on provided fee tier)
+    /// Estimated fee (at the tier of the account's 30-day volume)
     pub estimated_fee: Decimal,
     /// Total cost/proceeds including fee
     pub total_cost: Decimal,
     /// Whether the order was fully filled
     pub is_fully_filled: bool,
+    /// Whether `max_slippage` stopped the fill short
+    #[serde(default)]
+    pub slippage_capped: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Batch summary
+// ---------------------------------------------------------------------------
+
+/// Aggregate of a batch of simulated orders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchSummary {
+    /// Total quantity filled across the batch
+    pub filled_quantity: Decimal,
+    /// Quantity left unfilled across the batch
+    pub unfilled_quantity: Decimal,
+    /// Volume-weighted average price over every fill
+    pub avg_execution_price: Decimal,
+    /// Sum of the orders' estimated fees
+    pub total_fees: Decimal,
+}
+
+impl BatchSummary {
+    /// Summarize the results of `simulate_batch` or `simulate_sequential`.
+    pub fn from_results(results: &[SimResult]) -> Self {
+        let mut filled = Decimal::ZERO;
+        let mut unfilled = Decimal::ZERO;
+        let mut value = Decimal::ZERO;
+        let mut fees = Decimal::ZERO;
+        for result in results {
+            filled += result.filled_quantity;
+            unfilled += result.unfilled_quantity;
+            value += result.fills.iter().map(|f| f.value).sum::<Decimal>();
+            fees += result.estimated_fee;
+        }
+        let avg_execution_price = if filled > Decimal::ZERO {
+            round_display(value / filled)
+        } else {
+            Decimal::ZERO
+        };
+        Self {
+            filled_quantity: round_display(filled),
+            unfilled_quantity: round_display(unfilled),
+            avg_execution_price,
+            total_fees: fees,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -137,26 +188,67 @@ pub struct CancelSimResult {
 /// Public API is frozen — methods are stable and will not change.
 pub struct SimulationEngine {
     book: MockOrderBook,
-    fee_tier: FeeTier,
+    fee_schedule: FeeSchedule,
+    /// Account's traded notional over the trailing 30 days
+    thirty_day_volume: Decimal,
 }
 
 impl SimulationEngine {
     /// Create a new simulation engine.
     pub fn new(book: MockOrderBook, fee_tier: FeeTier) -> Self {
-        Self { book, fee_tier }
+        Self::with_fee_schedule(book, FeeSchedule::flat(fee_tier), Decimal::ZERO)
+    }
+
+    /// Create a simulation engine that charges the schedule's tier for the
+    /// account's 30-day volume.
+    ///
+    /// The tier comes from the volume before the simulated order, so an
+    /// order that itself crosses a threshold still pays the lower tier.
+    pub fn with_fee_schedule(book: MockOrderBook, fee_schedule: FeeSchedule, thirty_day_volume: Decimal) -> Self {
+        Self {
+            book,
+            fee_schedule,
+            thirty_day_volume,
+        }
+    }
+
+    /// Fee tier applied to simulated orders.
+    pub fn fee_tier(&self) -> &FeeTier {
+        self.fee_schedule.tier_for_volume(self.thirty_day_volume)
     }
 
     /// Simulate a single order fill against the order book.
     pub fn simulate(&self, order: &SimOrder) -> SimResult {
+        self.simulate_on(&self.book, order, self.thirty_day_volume)
+    }
+
+    /// Simulate `order` against `book` for an account with `volume` traded.
+    fn simulate_on(&self, book: &MockOrderBook, order: &SimOrder, volume: Decimal) -> SimResult {
         let levels = match order.side {
-            Side::BUY => &self.book.asks,
-            Side::SELL => &self.book.bids,
+            Side::BUY => &book.asks,
+            Side::SELL => &book.bids,
         };
 
         let best_price = levels.first().map(|l| l.price.as_decimal());
 
+        // The slippage cap, when set, bounds the quantity up front
+        let within_limit = |level: &&PriceLevel| match (order.limit_price, order.side) {
+            (None, _) => true,
+            (Some(limit), Side::BUY) => level.price <= limit,
+            (Some(limit), Side::SELL) => level.price >= limit,
+        };
+        let (fillable, slippage_capped) = match order.max_slippage {
+            Some(max_slippage) => slippage_fill_limit(
+                order.side,
+                levels.iter().take_while(within_limit).map(|l| (l.price.as_decimal(), l.quantity.as_decimal())),
+                order.quantity.as_decimal(),
+                max_slippage,
+            ),
+            None => (order.quantity.as_decimal(), false),
+        };
+
         let mut fills = Vec::new();
-        let mut remaining = order.quantity.as_decimal();
+        let mut remaining = fillable;
         let mut total_value = Decimal::ZERO;
         let mut total_filled = Decimal::ZERO;
 
@@ -210,7 +302,7 @@ impl SimulationEngine {
         };
 
         // Fee: taker fee for market orders (round UP, spec §7.2)
-        let fee = round_up_fee(total_value * self.fee_tier.taker_rate);
+        let fee = round_up_fee(total_value * self.fee_schedule.tier_for_volume(volume).taker_rate);
 
         let total_cost = match order.side {
             Side::BUY => round_display(total_value + fee),
@@ -227,7 +319,8 @@ impl SimulationEngine {
             slippage,
             estimated_fee: fee,
             total_cost,
-            is_fully_filled: remaining <= Decimal::ZERO,
+            is_fully_filled: total_filled >= order.quantity.as_decimal(),
+            slippage_capped,
         }
     }
 
@@ -236,6 +329,28 @@ impl SimulationEngine {
         orders.iter().map(|order| self.simulate(order)).collect()
     }
 
+    /// Simulate a batch of orders executed one after another.
+    ///
+    /// Unlike `simulate_batch`, each order fills against the liquidity left
+    /// by the ones before it, and its traded value counts toward the fee
+    /// tier of the next. Works on a copy of the book; `self` is unchanged.
+    pub fn simulate_sequential(&self, orders: &[SimOrder]) -> Vec<SimResult> {
+        let mut book = self.book.clone();
+        let mut volume = self.thirty_day_volume;
+        let mut results = Vec::with_capacity(orders.len());
+        for order in orders {
+            let result = self.simulate_on(&book, order, volume);
+            let levels = match order.side {
+                Side::BUY => &mut book.asks,
+                Side::SELL => &mut book.bids,
+            };
+            consume_levels(levels, &result.fills);
+            volume += result.fills.iter().map(|f| f.value).sum::<Decimal>();
+            results.push(result);
+        }
+        results
+    }
+
     /// Simulate cancellation of an existing order.
     ///
     /// Returns the margin that would be released and the cancelled quantity.
@@ -266,6 +381,7 @@ impl SimulationEngine {
             side,
             quantity,
             limit_price: None,
+            max_slippage: None,
         };
         self.simulate(&order).avg_execution_price
     }
@@ -276,6 +392,7 @@ impl SimulationEngine {
             side,
             quantity,
             limit_price: None,
+            max_slippage: None,
         };
         self.simulate(&order).slippage
     }
@@ -286,6 +403,7 @@ impl SimulationEngine {
             side,
             quantity,
             limit_price: None,
+            max_slippage: None,
         };
         self.simulate(&order).estimated_fee
     }
@@ -300,6 +418,17 @@ fn round_display(v: Decimal) -> Decimal {
     v.round_dp_with_strategy(DISPLAY_DP, RoundingStrategy::MidpointAwayFromZero)
 }
 
+/// Remove filled quantity from the front of a book side.
+///
+/// `fills` come from walking `levels` best first, one fill per level.
+fn consume_levels(levels: &mut Vec<PriceLevel>, fills: &[SimFill]) {
+    for (level, fill) in levels.iter_mut().zip(fills) {
+        level.quantity = Quantity::try_new(level.quantity.as_decimal() - fill.quantity.as_decimal())
+            .unwrap_or(Quantity::zero());
+    }
+    levels.retain(|level| !level.quantity.is_zero());
+}
+
 /// Round fee UP (never undercharge, spec §7.2).
 fn round_up_fee(v: Decimal) -> Decimal {
     v.round_dp_with_strategy(FEE_DP, RoundingStrategy::AwayFromZero)
@@ -373,6 +502,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("1.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -391,6 +521,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("2.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -407,6 +538,7 @@ mod tests {
             side: Side::SELL,
             quantity: Quantity::from_str("2.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -422,6 +554,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("10.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -437,6 +570,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("5.0").unwrap(),
             limit_price: Some(Price::from_u64(50_150)),
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -452,6 +586,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("3.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -468,6 +603,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("1.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -482,6 +618,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("1.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -496,6 +633,7 @@ mod tests {
             side: Side::SELL,
             quantity: Quantity::from_str("2.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
 
@@ -511,11 +649,13 @@ mod tests {
                 side: Side::BUY,
                 quantity: Quantity::from_str("1.0").unwrap(),
                 limit_price: None,
+                max_slippage: None,
             },
             SimOrder {
                 side: Side::SELL,
                 quantity: Quantity::from_str("1.0").unwrap(),
                 limit_price: None,
+                max_slippage: None,
             },
         ];
         let results = engine.simulate_batch(&orders);
@@ -567,6 +707,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("1.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
         assert!(!result.is_fully_filled);
@@ -580,6 +721,7 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("3.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let r1 = engine.simulate(&order);
         let r2 = engine.simulate(&order);
@@ -593,10 +735,124 @@ mod tests {
             side: Side::BUY,
             quantity: Quantity::from_str("1.0").unwrap(),
             limit_price: None,
+            max_slippage: None,
         };
         let result = engine.simulate(&order);
         let json = serde_json::to_string(&result).unwrap();
         let restored: SimResult = serde_json::from_str(&json).unwrap();
         assert_eq!(result, restored);
     }
+
+    #[test]
+    fn test_fee_schedule_tier_from_prior_volume() {
+        let schedule = FeeSchedule::new(types::fee::default_fee_tiers());
+        let order = SimOrder {
+            side: Side::BUY,
+            quantity: Quantity::from_str("1.0").unwrap(),
+            limit_price: None,
+            max_slippage: None,
+        };
+
+        // 999 000 + this 50 100 order crosses 1M, but the order pays tier 0
+        let engine = SimulationEngine::with_fee_schedule(sample_book(), schedule.clone(), Decimal::from(999_000));
+        assert_eq!(engine.simulate(&order).estimated_fee, Decimal::from_str_exact("25.05").unwrap());
+
+        // Volume exactly at the threshold qualifies: 50 100 × 0.045%
+        let engine = SimulationEngine::with_fee_schedule(sample_book(), schedule, Decimal::from(1_000_000));
+        assert_eq!(engine.fee_tier().volume_threshold, Decimal::from(1_000_000));
+        assert_eq!(engine.simulate(&order).estimated_fee, Decimal::from_str_exact("22.545").unwrap());
+    }
+
+    /// `count` market buys of `quantity` each.
+    fn clips_of(quantity: &str, count: usize) -> Vec<SimOrder> {
+        let clip = SimOrder {
+            side: Side::BUY,
+            quantity: Quantity::from_str(quantity).unwrap(),
+            limit_price: None,
+            max_slippage: None,
+        };
+        vec![clip; count]
+    }
+
+    #[test]
+    fn test_sequential_clips_walk_the_book() {
+        let engine = sample_engine();
+        let clips = clips_of("2.0", 3);
+
+        let independent = engine.simulate_batch(&clips);
+        let sequential = engine.simulate_sequential(&clips);
+
+        // Each independent clip sees full depth: 1 @ 50 100 + 1 @ 50 200
+        assert!(independent.iter().all(|r| r.avg_execution_price == Decimal::from(50_150)));
+        // Sequential clips eat into it: 50 150, then 50 250, then 50 300
+        let prices: Vec<_> = sequential.iter().map(|r| r.avg_execution_price).collect();
+        assert_eq!(prices, vec![Decimal::from(50_150), Decimal::from(50_250), Decimal::from(50_300)]);
+
+        let batch = BatchSummary::from_results(&independent);
+        let walked = BatchSummary::from_results(&sequential);
+        assert!(walked.avg_execution_price > batch.avg_execution_price);
+        assert_eq!(walked.avg_execution_price, Decimal::from_str_exact("50233.33333333").unwrap());
+        assert_eq!(walked.filled_quantity, Decimal::from(6));
+        assert_eq!(walked.unfilled_quantity, Decimal::ZERO);
+        assert_eq!(walked.total_fees, sequential.iter().map(|r| r.estimated_fee).sum::<Decimal>());
+
+        // The engine's own book is untouched
+        assert_eq!(engine.simulate_sequential(&clips), sequential);
+    }
+
+    #[test]
+    fn test_sequential_reports_residual_when_book_runs_out() {
+        let engine = sample_engine();
+        // 8 BTC of asks in total
+        let results = engine.simulate_sequential(&clips_of("3.0", 3));
+        assert!(results[0].is_fully_filled && results[1].is_fully_filled);
+        assert_eq!(results[2].filled_quantity, Decimal::from(2));
+        assert_eq!(results[2].unfilled_quantity, Decimal::ONE);
+
+        let summary = BatchSummary::from_results(&results);
+        assert_eq!(summary.filled_quantity, Decimal::from(8));
+        assert_eq!(summary.unfilled_quantity, Decimal::ONE);
+
+        // Deterministic across runs
+        assert_eq!(engine.simulate_sequential(&clips_of("3.0", 3)), results);
+    }
+
+    fn capped_buy(quantity: &str, max_slippage: &str) -> SimOrder {
+        SimOrder {
+            side: Side::BUY,
+            quantity: Quantity::from_str(quantity).unwrap(),
+            limit_price: None,
+            max_slippage: Some(Decimal::from_str_exact(max_slippage).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_slippage_cap_lands_mid_level() {
+        let engine = sample_engine();
+        // Cap 50 100 × 1.001 = 50 150.1: all of 50 100, then 50.1 / 49.9 of 50 200
+        let result = engine.simulate(&capped_buy("5.0", "0.001"));
+        assert!(result.slippage_capped);
+        assert!(!result.is_fully_filled);
+        assert_eq!(result.fills.len(), 2);
+        assert_eq!(result.fills[1].quantity, Quantity::from_str("1.00400801").unwrap());
+        assert_eq!(result.filled_quantity, Decimal::from_str_exact("2.00400801").unwrap());
+        assert_eq!(result.unfilled_quantity, Decimal::from_str_exact("2.99599199").unwrap());
+        assert!(result.avg_execution_price <= Decimal::from_str_exact("50150.1").unwrap());
+
+        // A cap the order never reaches leaves it untouched
+        let result = engine.simulate(&capped_buy("1.0", "0.001"));
+        assert!(!result.slippage_capped);
+        assert!(result.is_fully_filled);
+    }
+
+    #[test]
+    fn test_slippage_cap_breached_by_first_level_fills_nothing() {
+        let engine = sample_engine();
+        let result = engine.simulate(&capped_buy("1.0", "-0.01"));
+        assert!(result.slippage_capped);
+        assert!(result.fills.is_empty());
+        assert_eq!(result.filled_quantity, Decimal::ZERO);
+        assert_eq!(result.unfilled_quantity, Decimal::ONE);
+        assert_eq!(result.estimated_fee, Decimal::ZERO);
+    }
 }
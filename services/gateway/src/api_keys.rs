@@ -0,0 +1,400 @@
+use axum::http::Method;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+use thiserror::Error;
+use types::ids::AccountId;
+
+/// Largest accepted distance between `X-Timestamp` and the gateway clock.
+pub const DEFAULT_MAX_SKEW: Duration = Duration::from_secs(5);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// What an API key may do; bearer tokens may do everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    Read,
+    Trade,
+    Withdraw,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Permission::Read => "read",
+            Permission::Trade => "trade",
+            Permission::Withdraw => "withdraw",
+        })
+    }
+}
+
+/// Permission an API key needs for a route (`path` as matched, e.g.
+/// `/v1/orders/{id}`).
+pub fn required_permission(method: &Method, path: &str) -> Permission {
+    match (method, path) {
+        (&Method::POST, "/v1/withdrawals") => Permission::Withdraw,
+        (&Method::GET, _) => Permission::Read,
+        _ => Permission::Trade,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("Unknown API key")]
+    UnknownKey,
+
+    #[error("API key was revoked")]
+    Revoked,
+
+    #[error("Timestamp {timestamp} is outside the {}ms window", max_skew.as_millis())]
+    StaleTimestamp { timestamp: i64, max_skew: Duration },
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("API key lacks the {0} permission")]
+    PermissionDenied(Permission),
+
+    #[error("Key store I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Key store file is malformed: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+/// An API key and the HMAC secret it signs with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Sent as `X-API-Key`
+    pub key_id: String,
+    pub secret: String,
+    pub account_id: AccountId,
+    pub permissions: BTreeSet<Permission>,
+    /// Cancel the account's orders when its sessions on this key drop
+    #[serde(default)]
+    pub cancel_on_disconnect: bool,
+    /// Unix nanos
+    pub created_at: i64,
+    pub revoked_at: Option<i64>,
+}
+
+impl ApiKey {
+    pub fn require(&self, permission: Permission) -> Result<(), ApiKeyError> {
+        if self.permissions.contains(&permission) {
+            Ok(())
+        } else {
+            Err(ApiKeyError::PermissionDenied(permission))
+        }
+    }
+}
+
+/// Hex `HMAC-SHA256(secret, timestamp + method + path + body)`, keyed with
+/// the secret string as issued. This is what clients send as `X-Signature`.
+#[cfg(test)]
+pub fn sign(secret: &str, timestamp: i64, method: &str, path: &str, body: &[u8]) -> String {
+    hex::encode(mac(secret, timestamp, method, path, body).finalize().into_bytes())
+}
+
+fn mac(secret: &str, timestamp: i64, method: &str, path: &str, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(method.as_bytes());
+    mac.update(path.as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// In-memory API keys, optionally mirrored to a JSON file.
+///
+/// Revoked keys are kept so that their ids are never reissued and requests
+/// signed with them are told why they fail.
+pub struct ApiKeyStore {
+    keys: RwLock<HashMap<String, ApiKey>>,
+    max_skew: Duration,
+    /// Rewritten after every change
+    path: Option<PathBuf>,
+}
+
+impl ApiKeyStore {
+    pub fn new(max_skew: Duration) -> Self {
+        Self {
+            keys: RwLock::new(HashMap::new()),
+            max_skew,
+            path: None,
+        }
+    }
+
+    /// Load the keys saved at `path`; a missing file is an empty store.
+    pub fn open(path: impl Into<PathBuf>, max_skew: Duration) -> Result<Self, ApiKeyError> {
+        let path = path.into();
+        let keys: Vec<ApiKey> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            keys: RwLock::new(keys.into_iter().map(|key| (key.key_id.clone(), key)).collect()),
+            max_skew,
+            path: Some(path),
+        })
+    }
+
+    /// Issue a key for the account; the secret is only ever returned here.
+    pub fn create(
+        &self,
+        account_id: AccountId,
+        permissions: BTreeSet<Permission>,
+        cancel_on_disconnect: bool,
+        now: i64,
+    ) -> Result<ApiKey, ApiKeyError> {
+        let key = ApiKey {
+            key_id: random_hex(16),
+            secret: random_hex(32),
+            account_id,
+            permissions,
+            cancel_on_disconnect,
+            created_at: now,
+            revoked_at: None,
+        };
+        let mut keys = self.keys.write().unwrap();
+        keys.insert(key.key_id.clone(), key.clone());
+        self.save(&keys)?;
+        Ok(key)
+    }
+
+    /// Revoke one of the account's keys; revoking twice keeps the first time.
+    pub fn revoke(&self, account_id: AccountId, key_id: &str, now: i64) -> Result<ApiKey, ApiKeyError> {
+        let mut keys = self.keys.write().unwrap();
+        let key = match keys.get_mut(key_id) {
+            Some(key) if key.account_id == account_id => key,
+            _ => return Err(ApiKeyError::UnknownKey),
+        };
+        key.revoked_at.get_or_insert(now);
+        let key = key.clone();
+        self.save(&keys)?;
+        Ok(key)
+    }
+
+    pub fn get(&self, key_id: &str) -> Option<ApiKey> {
+        self.keys.read().unwrap().get(key_id).cloned()
+    }
+
+    /// The account's keys, oldest first.
+    pub fn list(&self, account_id: AccountId) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self
+            .keys
+            .read()
+            .unwrap()
+            .values()
+            .filter(|key| key.account_id == account_id)
+            .cloned()
+            .collect();
+        keys.sort_by(|a, b| (a.created_at, &a.key_id).cmp(&(b.created_at, &b.key_id)));
+        keys
+    }
+
+    /// Authenticate a signed request at `now` (unix nanos).
+    ///
+    /// The timestamp may be up to `max_skew` either side of `now`, so a
+    /// captured request cannot be replayed once the window has passed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify(
+        &self,
+        key_id: &str,
+        timestamp: i64,
+        signature: &str,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<ApiKey, ApiKeyError> {
+        let key = self
+            .keys
+            .read()
+            .unwrap()
+            .get(key_id)
+            .cloned()
+            .ok_or(ApiKeyError::UnknownKey)?;
+        if key.revoked_at.is_some() {
+            return Err(ApiKeyError::Revoked);
+        }
+        if now.abs_diff(timestamp) > self.max_skew.as_nanos() as u64 {
+            return Err(ApiKeyError::StaleTimestamp {
+                timestamp,
+                max_skew: self.max_skew,
+            });
+        }
+        let signature = hex::decode(signature).map_err(|_| ApiKeyError::InvalidSignature)?;
+        // Constant-time comparison
+        mac(&key.secret, timestamp, method, path, body)
+            .verify_slice(&signature)
+            .map_err(|_| ApiKeyError::InvalidSignature)?;
+        Ok(key)
+    }
+
+    fn save(&self, keys: &HashMap<String, ApiKey>) -> Result<(), ApiKeyError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut keys: Vec<&ApiKey> = keys.values().collect();
+        keys.sort_by(|a, b| a.key_id.cmp(&b.key_id));
+        // Write aside and rename so a crash never leaves a partial file
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&keys)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+impl Default for ApiKeyStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SKEW)
+    }
+}
+
+/// Headers signing a request with `key` at the current time.
+#[cfg(test)]
+pub fn signed_headers(key: &ApiKey, method: &str, path: &str, body: &[u8]) -> [(&'static str, String); 3] {
+    use crate::auth::{unix_nanos, API_KEY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+    let timestamp = unix_nanos();
+    [
+        (API_KEY_HEADER, key.key_id.clone()),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (SIGNATURE_HEADER, sign(&key.secret, timestamp, method, path, body)),
+    ]
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes).expect("OS randomness is available");
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_708_123_456_789_000_000;
+
+    fn store_with_key(permissions: &[Permission]) -> (ApiKeyStore, ApiKey) {
+        let store = ApiKeyStore::default();
+        let key = store
+            .create(AccountId::new(), permissions.iter().copied().collect(), false, NOW)
+            .unwrap();
+        (store, key)
+    }
+
+    fn verify_at(store: &ApiKeyStore, key: &ApiKey, timestamp: i64) -> Result<ApiKey, ApiKeyError> {
+        let signature = sign(&key.secret, timestamp, "POST", "/v1/orders", b"{}");
+        store.verify(&key.key_id, timestamp, &signature, "POST", "/v1/orders", b"{}", NOW)
+    }
+
+    #[test]
+    fn test_skew_window_boundaries() {
+        let (store, key) = store_with_key(&[Permission::Trade]);
+        let skew = DEFAULT_MAX_SKEW.as_nanos() as i64;
+        for timestamp in [NOW, NOW - skew, NOW + skew] {
+            assert_eq!(verify_at(&store, &key, timestamp).unwrap(), key);
+        }
+        for timestamp in [NOW - skew - 1, NOW + skew + 1, 0] {
+            assert!(matches!(
+                verify_at(&store, &key, timestamp),
+                Err(ApiKeyError::StaleTimestamp { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_signature_covers_every_part_of_the_request() {
+        let (store, key) = store_with_key(&[Permission::Trade]);
+        let signature = sign(&key.secret, NOW, "POST", "/v1/orders", b"{}");
+        assert!(store.verify(&key.key_id, NOW, &signature, "POST", "/v1/orders", b"{}", NOW).is_ok());
+
+        let tampered = [
+            (NOW + 1, "POST", "/v1/orders", &b"{}"[..]),
+            (NOW, "DELETE", "/v1/orders", b"{}"),
+            (NOW, "POST", "/v1/withdrawals", b"{}"),
+            (NOW, "POST", "/v1/orders", b"{ }"),
+        ];
+        for (timestamp, method, path, body) in tampered {
+            assert!(matches!(
+                store.verify(&key.key_id, timestamp, &signature, method, path, body, NOW),
+                Err(ApiKeyError::InvalidSignature)
+            ));
+        }
+        for signature in ["zz", ""] {
+            assert!(matches!(
+                store.verify(&key.key_id, NOW, signature, "POST", "/v1/orders", b"{}", NOW),
+                Err(ApiKeyError::InvalidSignature)
+            ));
+        }
+        assert!(matches!(
+            store.verify("nope", NOW, &signature, "POST", "/v1/orders", b"{}", NOW),
+            Err(ApiKeyError::UnknownKey)
+        ));
+    }
+
+    #[test]
+    fn test_revoked_keys_are_rejected() {
+        let (store, key) = store_with_key(&[Permission::Read]);
+        assert!(matches!(
+            store.revoke(AccountId::new(), &key.key_id, NOW),
+            Err(ApiKeyError::UnknownKey)
+        ));
+        assert!(verify_at(&store, &key, NOW).is_ok());
+
+        let revoked = store.revoke(key.account_id, &key.key_id, NOW + 1).unwrap();
+        assert_eq!(revoked.revoked_at, Some(NOW + 1));
+        assert_eq!(store.revoke(key.account_id, &key.key_id, NOW + 2).unwrap().revoked_at, Some(NOW + 1));
+        assert!(matches!(verify_at(&store, &key, NOW), Err(ApiKeyError::Revoked)));
+        assert_eq!(store.list(key.account_id), vec![revoked]);
+    }
+
+    #[test]
+    fn test_route_permissions() {
+        let cases = [
+            (Method::GET, "/v1/orders", Permission::Read),
+            (Method::GET, "/v1/ws/user", Permission::Read),
+            (Method::POST, "/v1/orders", Permission::Trade),
+            (Method::POST, "/v1/orders/batch", Permission::Trade),
+            (Method::DELETE, "/v1/orders/{id}", Permission::Trade),
+            (Method::POST, "/v1/withdrawals", Permission::Withdraw),
+        ];
+        for (method, path, permission) in cases {
+            assert_eq!(required_permission(&method, path), permission, "{method} {path}");
+        }
+        let (_, key) = store_with_key(&[Permission::Read, Permission::Trade]);
+        assert!(key.require(Permission::Trade).is_ok());
+        assert!(matches!(
+            key.require(Permission::Withdraw),
+            Err(ApiKeyError::PermissionDenied(Permission::Withdraw))
+        ));
+    }
+
+    #[test]
+    fn test_keys_persist_across_restarts() {
+        let path = std::env::temp_dir().join(format!("gateway-api-keys-{}.json", random_hex(8)));
+        let store = ApiKeyStore::open(&path, DEFAULT_MAX_SKEW).unwrap();
+        let account_id = AccountId::new();
+        let kept = store.create(account_id, [Permission::Read].into(), false, NOW).unwrap();
+        let revoked = store.create(account_id, [Permission::Trade].into(), true, NOW + 1).unwrap();
+        let revoked = store.revoke(account_id, &revoked.key_id, NOW + 2).unwrap();
+        assert_ne!(kept.secret, revoked.secret);
+
+        let reopened = ApiKeyStore::open(&path, DEFAULT_MAX_SKEW).unwrap();
+        assert_eq!(reopened.list(account_id), vec![kept.clone(), revoked.clone()]);
+        assert!(verify_at(&reopened, &kept, NOW).is_ok());
+        assert!(matches!(verify_at(&reopened, &revoked, NOW), Err(ApiKeyError::Revoked)));
+        std::fs::remove_file(&path).unwrap();
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(ApiKeyStore::open(&path, DEFAULT_MAX_SKEW), Err(ApiKeyError::Malformed(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+use crate::engine_client::{correlation_id, MatchingEngineClient};
+use crate::user_events::{UserEvent, UserEventHub};
+use axum::http::HeaderMap;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::AbortHandle;
+use types::ids::AccountId;
+
+/// How long a protected account may go without a session, or a session
+/// without a heartbeat, before its orders are canceled.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(5);
+
+/// What set off a cancel-on-disconnect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// The last protected session closed and none reopened in time
+    Disconnected,
+    /// The last protected session sent nothing for the grace period
+    HeartbeatTimeout,
+}
+
+/// A trigger waiting out its delay.
+struct Pending {
+    generation: u64,
+    timer: AbortHandle,
+}
+
+/// Cancels an account's resting orders when its protected sessions go away.
+///
+/// Only accounts with a pending trigger own a timer task; reconnecting
+/// aborts it, and firing removes it, so nothing outlives its session.
+pub struct CancelOnDisconnect {
+    engine: MatchingEngineClient,
+    user_events: Arc<UserEventHub>,
+    grace: Duration,
+    /// Open protected sessions per account
+    sessions: DashMap<AccountId, usize>,
+    pending: DashMap<AccountId, Pending>,
+    generation: AtomicU64,
+}
+
+impl CancelOnDisconnect {
+    pub fn new(engine: MatchingEngineClient, user_events: Arc<UserEventHub>, grace: Duration) -> Self {
+        Self {
+            engine,
+            user_events,
+            grace,
+            sessions: DashMap::new(),
+            pending: DashMap::new(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Longest silence tolerated from a protected session.
+    pub fn grace(&self) -> Duration {
+        self.grace
+    }
+
+    /// A protected session opened; any pending trigger is disarmed.
+    pub fn connect(&self, account_id: AccountId) {
+        *self.sessions.entry(account_id).or_insert(0) += 1;
+        if let Some((_, pending)) = self.pending.remove(&account_id) {
+            pending.timer.abort();
+            tracing::info!(target: "audit", %account_id, "Cancel-on-disconnect disarmed by reconnect");
+        }
+    }
+
+    /// A protected session ended.
+    ///
+    /// Once the account has none left its orders are canceled: after the
+    /// grace period for a closed socket, unless a session reopens first, and
+    /// at once for a heartbeat timeout, which has already waited it out.
+    pub fn disconnect(self: &Arc<Self>, account_id: AccountId, trigger: Trigger) {
+        // The sessions entry stays locked until the timer is armed, so a
+        // `connect` lands before the count drops or finds the trigger to abort
+        match self.sessions.entry(account_id) {
+            Entry::Occupied(mut sessions) => {
+                *sessions.get_mut() -= 1;
+                if *sessions.get() > 0 {
+                    return;
+                }
+                self.arm(account_id, trigger);
+                sessions.remove();
+            }
+            Entry::Vacant(_sessions) => self.arm(account_id, trigger),
+        }
+    }
+
+    fn arm(self: &Arc<Self>, account_id: AccountId, trigger: Trigger) {
+        let delay = match trigger {
+            Trigger::Disconnected => self.grace,
+            Trigger::HeartbeatTimeout => Duration::ZERO,
+        };
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+
+        // Spawned under the entry lock, so the timer cannot look for itself
+        // before it is stored
+        let entry = self.pending.entry(account_id);
+        let switch = Arc::clone(self);
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            switch.fire(account_id, generation, trigger).await;
+        })
+        .abort_handle();
+        let pending = Pending { generation, timer };
+        match entry {
+            Entry::Occupied(mut previous) => previous.insert(pending).timer.abort(),
+            Entry::Vacant(vacant) => {
+                vacant.insert(pending);
+            }
+        }
+    }
+
+    async fn fire(&self, account_id: AccountId, generation: u64, trigger: Trigger) {
+        // A reconnect wins the race once its session is counted, even
+        // before it has removed the trigger
+        if self.sessions.contains_key(&account_id)
+            || self
+                .pending
+                .remove_if(&account_id, |_, pending| pending.generation == generation)
+                .is_none()
+        {
+            return;
+        }
+        match self.engine.cancel_all(account_id, &correlation_id(&HeaderMap::new())).await {
+            Ok(ack) => {
+                tracing::warn!(
+                    target: "audit",
+                    %account_id,
+                    ?trigger,
+                    canceled = ack.canceled.len(),
+                    "Cancel-on-disconnect fired"
+                );
+                self.user_events.publish(
+                    account_id,
+                    UserEvent::SessionOrdersCanceled {
+                        trigger,
+                        canceled: ack.canceled,
+                    },
+                );
+            }
+            Err(e) => tracing::error!(
+                target: "audit",
+                %account_id,
+                ?trigger,
+                error = %e,
+                "Cancel-on-disconnect fired but cancel-all failed"
+            ),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn is_armed(&self, account_id: AccountId) -> bool {
+        self.pending.contains_key(&account_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_client::CancelAllAck;
+    use crate::state::InternalClient;
+    use axum::{extract::Path, extract::State, routing::post, Json, Router};
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+    use types::ids::OrderId;
+
+    /// Accounts the mock engine was asked to cancel, in call order.
+    type Calls = Arc<Mutex<Vec<AccountId>>>;
+
+    async fn spawn_engine() -> (String, Calls) {
+        let calls = Calls::default();
+        let app = Router::new()
+            .route(
+                "/internal/accounts/{id}/cancel-all",
+                post(|State(calls): State<Calls>, Path(account_id): Path<AccountId>| async move {
+                    calls.lock().unwrap().push(account_id);
+                    Json(CancelAllAck {
+                        canceled: vec![OrderId::new()],
+                    })
+                }),
+            )
+            .with_state(calls.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, calls)
+    }
+
+    async fn switch(grace: Duration) -> (Arc<CancelOnDisconnect>, Arc<UserEventHub>, Calls) {
+        let (url, calls) = spawn_engine().await;
+        let hub = Arc::new(UserEventHub::new(8));
+        let engine = MatchingEngineClient::new(InternalClient::default(), url);
+        (Arc::new(CancelOnDisconnect::new(engine, hub.clone(), grace)), hub, calls)
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_cancels_after_grace() {
+        let (switch, hub, calls) = switch(Duration::from_millis(50)).await;
+        let account_id = AccountId::new();
+        let mut events = hub.subscribe(account_id);
+        switch.connect(account_id);
+        switch.connect(account_id);
+
+        // Another protected session is still open
+        switch.disconnect(account_id, Trigger::Disconnected);
+        assert!(!switch.is_armed(account_id));
+        switch.disconnect(account_id, Trigger::Disconnected);
+        assert!(switch.is_armed(account_id));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(calls.lock().unwrap().is_empty(), "still within the grace period");
+
+        match &*events.recv().await.unwrap() {
+            UserEvent::SessionOrdersCanceled { trigger, canceled } => {
+                assert_eq!((*trigger, canceled.len()), (Trigger::Disconnected, 1));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert_eq!(*calls.lock().unwrap(), vec![account_id]);
+        assert!(!switch.is_armed(account_id));
+        assert!(switch.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_timeout_cancels_at_once() {
+        let (switch, hub, calls) = switch(Duration::from_secs(60)).await;
+        let account_id = AccountId::new();
+        let mut events = hub.subscribe(account_id);
+        switch.connect(account_id);
+        switch.disconnect(account_id, Trigger::HeartbeatTimeout);
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        assert!(matches!(
+            &*event,
+            UserEvent::SessionOrdersCanceled {
+                trigger: Trigger::HeartbeatTimeout,
+                ..
+            }
+        ));
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_within_grace_disarms() {
+        let (switch, _, calls) = switch(Duration::from_millis(30)).await;
+        let account_id = AccountId::new();
+        switch.connect(account_id);
+        switch.disconnect(account_id, Trigger::Disconnected);
+        switch.connect(account_id);
+        assert!(!switch.is_armed(account_id));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trigger_never_fires_on_a_counted_session() {
+        let (switch, _, calls) = switch(Duration::from_secs(60)).await;
+        let account_id = AccountId::new();
+        switch.connect(account_id);
+        switch.disconnect(account_id, Trigger::HeartbeatTimeout);
+        // A reconnect counted its session but has not disarmed the trigger yet
+        *switch.sessions.entry(account_id).or_insert(0) += 1;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(calls.lock().unwrap().is_empty());
+
+        switch.connect(account_id);
+        assert!(!switch.is_armed(account_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_reconnect_racing_the_trigger_cancels_at_most_once() {
+        let (switch, _, calls) = switch(Duration::from_millis(5)).await;
+        let accounts: Vec<AccountId> = (0..200).map(|_| AccountId::new()).collect();
+        for (i, account_id) in accounts.iter().enumerate() {
+            switch.connect(*account_id);
+            switch.disconnect(*account_id, Trigger::Disconnected);
+            let switch = switch.clone();
+            let account_id = *account_id;
+            // Reconnects land on both sides of the deadline
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_micros(50 * i as u64)).await;
+                switch.connect(account_id);
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let calls = calls.lock().unwrap();
+        let mut canceled = calls.clone();
+        canceled.sort();
+        canceled.dedup();
+        assert_eq!(canceled.len(), calls.len(), "no account canceled twice");
+        assert!(!calls.is_empty() && calls.len() < accounts.len(), "{} canceled", calls.len());
+        assert!(switch.pending.is_empty());
+        assert!(accounts.iter().all(|account_id| switch.sessions.get(account_id).map(|n| *n) == Some(1)));
+    }
+
+    #[tokio::test]
+    async fn test_disarmed_timers_do_not_leak() {
+        let (switch, _, calls) = switch(Duration::from_secs(60)).await;
+        let metrics = tokio::runtime::Handle::current().metrics();
+        let baseline = metrics.num_alive_tasks();
+        let accounts: Vec<AccountId> = (0..5_000).map(|_| AccountId::new()).collect();
+        for account_id in &accounts {
+            switch.connect(*account_id);
+            switch.disconnect(*account_id, Trigger::Disconnected);
+        }
+        assert_eq!(switch.pending.len(), accounts.len());
+        assert_eq!(metrics.num_alive_tasks(), baseline + accounts.len());
+
+        for account_id in &accounts {
+            switch.connect(*account_id);
+        }
+        tokio::task::yield_now().await;
+        assert!(switch.pending.is_empty());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while metrics.num_alive_tasks() > baseline {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(calls.lock().unwrap().is_empty());
+    }
+}
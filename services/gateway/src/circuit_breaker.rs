@@ -0,0 +1,293 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// When a breaker opens and how it recovers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakerConfig {
+    /// Outcomes of the most recent calls considered while closed
+    pub window: usize,
+    /// Calls in the window before the failure rate is acted on
+    pub min_calls: usize,
+    /// Failed fraction of the window that opens the breaker
+    pub failure_rate: f64,
+    /// A call slower than this counts as failed, even if it succeeded
+    pub slow_call: Duration,
+    /// How long an open breaker fails fast before probing
+    pub open_for: Duration,
+    /// Successful trial calls in half-open that close the breaker
+    pub half_open_probes: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            min_calls: 10,
+            failure_rate: 0.5,
+            slow_call: Duration::from_secs(2),
+            open_for: Duration::from_secs(5),
+            half_open_probes: 3,
+        }
+    }
+}
+
+/// Position of a breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls flow; outcomes are tracked
+    Closed,
+    /// Calls fail fast until `open_for` has passed
+    Open,
+    /// A limited number of trial calls decide between closed and open
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Value of the `gateway_circuit_breaker_state` gauge.
+    pub fn gauge_value(self) -> i64 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
+/// State of one breaker, as reported by `GET /status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BreakerStatus {
+    pub dependency: &'static str,
+    pub state: BreakerState,
+    /// Calls in the current window and how many failed
+    pub window_calls: usize,
+    pub window_failures: usize,
+    /// Time left before an open breaker lets a probe through
+    pub retry_after_ms: Option<u64>,
+    /// Calls refused while open (or over the half-open probe limit)
+    pub rejected_calls: u64,
+    /// Retries of idempotent calls through this breaker
+    pub retries: u64,
+}
+
+/// A call was refused without reaching the dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerOpen {
+    pub retry_after: Duration,
+}
+
+struct Inner {
+    state: BreakerState,
+    /// `true` for each failed call, most recent last
+    outcomes: VecDeque<bool>,
+    opened_at: Instant,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+/// Circuit breaker for calls to one downstream dependency.
+///
+/// Closed, it tracks the outcomes of the last `window` calls and opens when
+/// at least `min_calls` of them have been made and the failed fraction
+/// reaches `failure_rate`. Open, it refuses every call for `open_for`.
+/// Then it turns half-open and lets `half_open_probes` trial calls
+/// through: if they all succeed it closes, and the first failure opens it
+/// again.
+pub struct CircuitBreaker {
+    dependency: &'static str,
+    config: BreakerConfig,
+    inner: Mutex<Inner>,
+    rejected: AtomicU64,
+    retries: AtomicU64,
+}
+
+impl CircuitBreaker {
+    pub fn new(dependency: &'static str, config: BreakerConfig) -> Self {
+        Self {
+            dependency,
+            config,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                outcomes: VecDeque::with_capacity(config.window),
+                opened_at: Instant::now(),
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+            rejected: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+        }
+    }
+
+    pub fn dependency(&self) -> &'static str {
+        self.dependency
+    }
+
+    /// Ask to make a call. Every granted call must be followed by
+    /// [`record`](Self::record).
+    pub fn try_acquire(&self) -> Result<(), BreakerOpen> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == BreakerState::Open {
+            let elapsed = inner.opened_at.elapsed();
+            if elapsed < self.config.open_for {
+                drop(inner);
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(BreakerOpen {
+                    retry_after: self.config.open_for - elapsed,
+                });
+            }
+            inner.state = BreakerState::HalfOpen;
+            inner.probes_in_flight = 0;
+            inner.probe_successes = 0;
+        }
+        if inner.state == BreakerState::HalfOpen {
+            if inner.probes_in_flight + inner.probe_successes >= self.config.half_open_probes {
+                drop(inner);
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                // Probing is under way; its outcome is due within a call
+                return Err(BreakerOpen {
+                    retry_after: self.config.slow_call,
+                });
+            }
+            inner.probes_in_flight += 1;
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of a granted call that took `latency`.
+    pub fn record(&self, succeeded: bool, latency: Duration) {
+        let failed = !succeeded || latency > self.config.slow_call;
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => {
+                if inner.outcomes.len() == self.config.window.max(1) {
+                    inner.outcomes.pop_front();
+                }
+                inner.outcomes.push_back(failed);
+                let failures = inner.outcomes.iter().filter(|failed| **failed).count();
+                let calls = inner.outcomes.len();
+                let tripped = failures as f64 >= self.config.failure_rate * calls as f64;
+                if calls >= self.config.min_calls && tripped {
+                    Self::open(&mut inner);
+                }
+            }
+            BreakerState::HalfOpen => {
+                inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+                if failed {
+                    Self::open(&mut inner);
+                } else {
+                    inner.probe_successes += 1;
+                    if inner.probe_successes >= self.config.half_open_probes {
+                        inner.state = BreakerState::Closed;
+                        inner.outcomes.clear();
+                    }
+                }
+            }
+            // A call granted before the breaker opened
+            BreakerState::Open => {}
+        }
+    }
+
+    fn open(inner: &mut Inner) {
+        inner.state = BreakerState::Open;
+        inner.opened_at = Instant::now();
+        inner.outcomes.clear();
+        inner.probes_in_flight = 0;
+        inner.probe_successes = 0;
+    }
+
+    /// Count a retry made through this breaker.
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().unwrap();
+        let elapsed = inner.opened_at.elapsed();
+        // An open breaker past `open_for` lets the next call probe
+        let (state, retry_after) = match inner.state {
+            BreakerState::Open if elapsed >= self.config.open_for => (BreakerState::HalfOpen, None),
+            BreakerState::Open => (BreakerState::Open, Some(self.config.open_for - elapsed)),
+            state => (state, None),
+        };
+        BreakerStatus {
+            dependency: self.dependency,
+            state,
+            window_calls: inner.outcomes.len(),
+            window_failures: inner.outcomes.iter().filter(|failed| **failed).count(),
+            retry_after_ms: retry_after.map(|wait| wait.as_millis() as u64),
+            rejected_calls: self.rejected.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BreakerConfig {
+        BreakerConfig {
+            window: 4,
+            min_calls: 4,
+            failure_rate: 0.5,
+            slow_call: Duration::from_millis(100),
+            open_for: Duration::from_secs(10),
+            half_open_probes: 2,
+        }
+    }
+
+    fn state(breaker: &CircuitBreaker) -> BreakerState {
+        breaker.status().state
+    }
+
+    fn call(breaker: &CircuitBreaker, succeeded: bool) -> Result<(), BreakerOpen> {
+        breaker.try_acquire()?;
+        breaker.record(succeeded, Duration::from_millis(1));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_on_failure_rate_and_recovers_through_half_open() {
+        let breaker = CircuitBreaker::new("matching-engine", config());
+        // One failure in four stays closed; the window then slides to two
+        for succeeded in [true, false, true, true, false] {
+            call(&breaker, succeeded).unwrap();
+        }
+        assert_eq!(state(&breaker), BreakerState::Open);
+        let refused = breaker.try_acquire().unwrap_err();
+        assert_eq!(refused.retry_after, Duration::from_secs(10));
+
+        tokio::time::advance(Duration::from_secs(4)).await;
+        assert_eq!(breaker.status().retry_after_ms, Some(6_000));
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert_eq!(state(&breaker), BreakerState::HalfOpen);
+
+        // Two probes in flight at most; a third caller is refused
+        breaker.try_acquire().unwrap();
+        breaker.try_acquire().unwrap();
+        assert!(breaker.try_acquire().is_err());
+        breaker.record(true, Duration::from_millis(1));
+        breaker.record(true, Duration::from_millis(1));
+        assert_eq!(state(&breaker), BreakerState::Closed);
+        assert_eq!(breaker.status().rejected_calls, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_probe_reopens_and_slow_calls_count_as_failures() {
+        let breaker = CircuitBreaker::new("matching-engine", config());
+        for _ in 0..4 {
+            breaker.try_acquire().unwrap();
+            breaker.record(true, Duration::from_millis(150));
+        }
+        assert_eq!(state(&breaker), BreakerState::Open);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        call(&breaker, false).unwrap();
+        assert_eq!(state(&breaker), BreakerState::Open);
+        assert!(breaker.try_acquire().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::AppError;
+use crate::models::{AmendOrderRequest, CancelOrderRequest, PlaceOrderRequest};
+use crate::request_id::REQUEST_ID_HEADER;
+use crate::state::{CallError, InternalClient, InternalRequest, MATCHING_ENGINE};
+use axum::http::HeaderMap;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use types::errors::EngineError;
+use types::ids::{AccountId, OrderId};
+use uuid::Uuid;
+
+/// Header carrying the id that ties a gateway request to its engine call.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+/// Longest caller-supplied correlation id that is propagated as is.
+const MAX_CORRELATION_ID_LEN: usize = 64;
+
+/// Engine acknowledgement of an accepted order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAck {
+    pub order_id: OrderId,
+    pub status: String,
+}
+
+/// Engine acknowledgement of a cancel-all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelAllAck {
+    /// Canceled order ids in acceptance sequence
+    pub canceled: Vec<OrderId>,
+}
+
+/// Client for the matching engine's internal order API.
+///
+/// Calls go through the engine's circuit breaker; only cancels are retried.
+#[derive(Clone)]
+pub struct MatchingEngineClient {
+    internal: InternalClient,
+    base_url: String,
+}
+
+impl MatchingEngineClient {
+    pub fn new(internal: InternalClient, base_url: impl Into<String>) -> Self {
+        Self {
+            internal,
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Submit a validated order. Sent once: a lost response is not retried.
+    ///
+    /// Rejections carry an [`EngineError`] body and surface as
+    /// [`AppError::Engine`]; an open breaker as [`AppError::CircuitOpen`].
+    /// A connection lost after sending, or an unreadable acknowledgement, is
+    /// [`AppError::OutcomeUnknown`]: the engine may hold the order. Any other
+    /// failure means the engine is unavailable.
+    pub async fn place_order(&self, order: &PlaceOrderRequest, correlation_id: &str) -> Result<OrderAck, AppError> {
+        let request = self.internal.post(MATCHING_ENGINE, format!("{}/internal/orders", self.base_url));
+        let res = forward_ids(request.json(order), correlation_id)
+            .send()
+            .await
+            .map_err(|e| e.unanswered("Order service"))?;
+        answered(res)
+            .await?
+            .json::<OrderAck>()
+            .await
+            .map_err(|_| AppError::OutcomeUnknown("Order service sent an unreadable acknowledgement".into()))
+    }
+
+    /// Look up an account's order by `client_order_id`; `None` if the engine
+    /// holds no such order.
+    pub async fn find_order(
+        &self,
+        account_id: AccountId,
+        client_order_id: &str,
+        correlation_id: &str,
+    ) -> Result<Option<OrderAck>, AppError> {
+        let url = format!(
+            "{}/internal/accounts/{}/orders/by-client-id/{}",
+            self.base_url, account_id, client_order_id
+        );
+        let request = self.internal.get(MATCHING_ENGINE, url);
+        let res = forward_ids(request, correlation_id)
+            .send_with_retry()
+            .await
+            .map_err(|e| e.unavailable("Order service"))?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        ack(answered(res).await?).await.map(Some)
+    }
+
+    /// Cancel a resting order, retrying while the engine is unreachable.
+    pub async fn cancel_order(
+        &self,
+        order_id: OrderId,
+        cancel: &CancelOrderRequest,
+        correlation_id: &str,
+    ) -> Result<(), AppError> {
+        let request = self
+            .internal
+            .delete(MATCHING_ENGINE, format!("{}/internal/orders/{}", self.base_url, order_id))
+            .json(cancel);
+        check(forward_ids(request, correlation_id).send_with_retry().await).await.map(|_| ())
+    }
+
+    /// Change a resting order's price and/or quantity.
+    pub async fn amend_order(
+        &self,
+        order_id: OrderId,
+        amend: &AmendOrderRequest,
+        correlation_id: &str,
+    ) -> Result<OrderAck, AppError> {
+        let request = self
+            .internal
+            .patch(MATCHING_ENGINE, format!("{}/internal/orders/{}", self.base_url, order_id))
+            .json(amend);
+        ack(check(forward_ids(request, correlation_id).send().await).await?).await
+    }
+
+    /// Cancel every resting order of an account.
+    pub async fn cancel_all(
+        &self,
+        account_id: AccountId,
+        correlation_id: &str,
+    ) -> Result<CancelAllAck, AppError> {
+        let url = format!("{}/internal/accounts/{}/cancel-all", self.base_url, account_id);
+        let request = self.internal.post(MATCHING_ENGINE, url);
+        check(forward_ids(request, correlation_id).send().await)
+            .await?
+            .json::<CancelAllAck>()
+            .await
+            .map_err(|_| AppError::InternalError(anyhow::anyhow!("Invalid cancel-all acknowledgement")))
+    }
+}
+
+async fn check(res: Result<Response, CallError>) -> Result<Response, AppError> {
+    answered(res.map_err(|e| e.unavailable("Order service"))?).await
+}
+
+/// The engine's response if successful, otherwise its rejection.
+async fn answered(res: Response) -> Result<Response, AppError> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    match res.json::<EngineError>().await {
+        Ok(err) => Err(AppError::Engine(err)),
+        Err(_) => Err(AppError::ServiceUnavailable(format!("Order service returned {}", status))),
+    }
+}
+
+async fn ack(res: Response) -> Result<OrderAck, AppError> {
+    res.json::<OrderAck>()
+        .await
+        .map_err(|_| AppError::InternalError(anyhow::anyhow!("Invalid order acknowledgement")))
+}
+
+/// The caller's request id (or else correlation id) if usable, otherwise
+/// a fresh one.
+pub fn correlation_id(headers: &HeaderMap) -> String {
+    [REQUEST_ID_HEADER, CORRELATION_ID_HEADER]
+        .iter()
+        .filter_map(|name| headers.get(*name))
+        .filter_map(|v| v.to_str().ok())
+        .map(str::trim)
+        .find(|id| !id.is_empty() && id.len() <= MAX_CORRELATION_ID_LEN)
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::now_v7().to_string())
+}
+
+/// Tag a downstream call with the request's id, under both headers.
+pub fn forward_ids<K>(request: InternalRequest<K>, correlation_id: &str) -> InternalRequest<K> {
+    request
+        .header(REQUEST_ID_HEADER, correlation_id)
+        .header(CORRELATION_ID_HEADER, correlation_id)
+}
@@ -1,10 +1,18 @@
 use axum::{
-    http::StatusCode,
+    http::{HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde_json::{json, Value};
+use std::time::Duration;
+use persistence::history::HistoryError;
 use thiserror::Error;
+use types::errors::{AccountError, EngineError, LiquidationError, OrderError, TradeError};
+
+use crate::api_keys::ApiKeyError;
+use crate::engine_client::CORRELATION_ID_HEADER;
+use crate::models::{BatchItemResult, BatchItemStatus, FieldError};
+use crate::rate_limit::RETRY_AFTER_HEADER;
 
 /// Central error type for the Gateway application
 #[derive(Debug, Error)]
@@ -12,6 +20,12 @@ pub enum AppError {
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("API key rejected: {0}")]
+    ApiKey(#[from] ApiKeyError),
+
     #[error("Rate limit exceeded: {0}")]
     RateLimitExceeded(String),
 
@@ -24,14 +38,107 @@ pub enum AppError {
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
+    #[error("Circuit open for {dependency}")]
+    CircuitOpen { dependency: &'static str, retry_after: Duration },
+
+    /// The request may have been accepted downstream, but no answer came back
+    #[error("Outcome unknown: {0}")]
+    OutcomeUnknown(String),
+
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Validation failed: {} invalid field(s)", .0.len())]
+    Validation(Vec<FieldError>),
+
+    #[error("Engine rejected request: {0}")]
+    Engine(#[from] EngineError),
+
+    #[error("Batch rejected: {} invalid item(s)", failed_items(.0))]
+    BatchRejected(Vec<BatchItemResult>),
+
+    #[error("History query failed: {0}")]
+    History(#[from] HistoryError),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
+fn failed_items(items: &[BatchItemResult]) -> usize {
+    items.iter().filter(|item| item.status == BatchItemStatus::Failed).count()
+}
+
+/// HTTP status and envelope code for an engine error.
+///
+/// Internal faults keep their message out of the response.
+pub fn engine_error_status(err: &EngineError) -> (StatusCode, &'static str) {
+    match err {
+        EngineError::Order(OrderError::InvalidPrice(_)) => (StatusCode::BAD_REQUEST, "INVALID_PRICE"),
+        EngineError::Order(OrderError::InvalidQuantity(_)) => (StatusCode::BAD_REQUEST, "INVALID_QUANTITY"),
+        EngineError::Order(OrderError::InsufficientBalance { .. })
+        | EngineError::Account(AccountError::InsufficientBalance { .. }) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "INSUFFICIENT_BALANCE")
+        }
+        EngineError::Order(OrderError::NotFound { .. }) => (StatusCode::NOT_FOUND, "ORDER_NOT_FOUND"),
+        EngineError::Order(OrderError::AlreadyTerminal { .. })
+        | EngineError::Order(OrderError::InvalidStateTransition { .. }) => {
+            (StatusCode::CONFLICT, "ORDER_STATE_CONFLICT")
+        }
+        EngineError::Order(OrderError::SelfTrade) => (StatusCode::UNPROCESSABLE_ENTITY, "SELF_TRADE"),
+        EngineError::Order(OrderError::PostOnlyReject) => (StatusCode::UNPROCESSABLE_ENTITY, "POST_ONLY_REJECT"),
+        EngineError::Trade(TradeError::NotFound { .. }) => (StatusCode::NOT_FOUND, "TRADE_NOT_FOUND"),
+        EngineError::Trade(_) => (StatusCode::CONFLICT, "TRADE_CONFLICT"),
+        EngineError::Account(AccountError::NotFound { .. }) => (StatusCode::NOT_FOUND, "ACCOUNT_NOT_FOUND"),
+        EngineError::Account(AccountError::Suspended) | EngineError::Account(AccountError::Closed) => {
+            (StatusCode::FORBIDDEN, "ACCOUNT_INACTIVE")
+        }
+        EngineError::Account(AccountError::AssetNotFound { .. }) => (StatusCode::BAD_REQUEST, "ASSET_NOT_FOUND"),
+        EngineError::Liquidation(LiquidationError::PositionNotFound) => {
+            (StatusCode::NOT_FOUND, "POSITION_NOT_FOUND")
+        }
+        EngineError::Liquidation(_) => (StatusCode::CONFLICT, "LIQUIDATION_CONFLICT"),
+        EngineError::InvalidMarket { .. } => (StatusCode::BAD_REQUEST, "INVALID_MARKET"),
+        EngineError::MarketClosed(_) => (StatusCode::CONFLICT, "MARKET_CLOSED"),
+        EngineError::Account(AccountError::InvariantViolation { .. }) | EngineError::System { .. } => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR")
+        }
+    }
+}
+
+impl AppError {
+    /// `Retry-After` value, in whole seconds, for errors that name a wait.
+    fn retry_after(&self) -> Option<HeaderValue> {
+        match self {
+            AppError::CircuitOpen { retry_after, .. } => {
+                Some(HeaderValue::from(retry_after.as_secs_f64().ceil().max(1.0) as u64))
+            }
+            _ => None,
+        }
+    }
+
+    /// Status and JSON error envelope.
+    pub(crate) fn envelope(self) -> (StatusCode, Value) {
+        let mut details = None;
         let (status, error_message, code) = match self {
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, "UNAUTHORIZED"),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, "FORBIDDEN"),
+            AppError::ApiKey(err) => {
+                let (status, code) = match err {
+                    ApiKeyError::UnknownKey => (StatusCode::UNAUTHORIZED, "UNKNOWN_API_KEY"),
+                    ApiKeyError::Revoked => (StatusCode::UNAUTHORIZED, "API_KEY_REVOKED"),
+                    ApiKeyError::StaleTimestamp { .. } => (StatusCode::UNAUTHORIZED, "STALE_TIMESTAMP"),
+                    ApiKeyError::InvalidSignature => (StatusCode::UNAUTHORIZED, "INVALID_SIGNATURE"),
+                    ApiKeyError::PermissionDenied(_) => (StatusCode::FORBIDDEN, "PERMISSION_DENIED"),
+                    ApiKeyError::Io(_) | ApiKeyError::Malformed(_) => {
+                        (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR")
+                    }
+                };
+                let msg = match status {
+                    StatusCode::INTERNAL_SERVER_ERROR => "Internal server error".to_string(),
+                    _ => err.to_string(),
+                };
+                (status, msg, code)
+            }
             AppError::RateLimitExceeded(msg) => {
                 // Rate limits should preferably include Retry-After, but we handle that in the middleware mostly.
                 // For explicit endpoint rate limits, we use this.
@@ -43,7 +150,39 @@ impl IntoResponse for AppError {
                 msg,
                 "SERVICE_UNAVAILABLE",
             ),
+            AppError::CircuitOpen { dependency, retry_after } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("{} is failing; retry in {}ms", dependency, retry_after.as_millis()),
+                "CIRCUIT_OPEN",
+            ),
+            AppError::OutcomeUnknown(msg) => (StatusCode::GATEWAY_TIMEOUT, msg, "OUTCOME_UNKNOWN"),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, "NOT_FOUND"),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg, "CONFLICT"),
+            AppError::Validation(errors) => {
+                let msg = format!("Request validation failed: {} invalid field(s)", errors.len());
+                details = Some(json!(errors));
+                (StatusCode::BAD_REQUEST, msg, "VALIDATION_FAILED")
+            }
+            AppError::BatchRejected(items) => {
+                let msg = format!("Atomic batch rejected: {} invalid item(s)", failed_items(&items));
+                details = Some(json!(items));
+                (StatusCode::BAD_REQUEST, msg, "BATCH_REJECTED")
+            }
+            AppError::History(err) => {
+                let code = match err {
+                    HistoryError::InvalidCursor => "INVALID_CURSOR",
+                    HistoryError::CursorExpired { .. } => "CURSOR_EXPIRED",
+                    HistoryError::InvalidLimit => "INVALID_LIMIT",
+                    HistoryError::InvalidRange { .. } => "INVALID_RANGE",
+                };
+                (StatusCode::BAD_REQUEST, err.to_string(), code)
+            }
+            AppError::Engine(err) => match engine_error_status(&err) {
+                (StatusCode::INTERNAL_SERVER_ERROR, code) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), code)
+                }
+                (status, code) => (status, err.to_string(), code),
+            },
             AppError::InternalError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
@@ -51,11 +190,124 @@ impl IntoResponse for AppError {
             ),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": code,
             "message": error_message
-        }));
+        });
+        if let Some(details) = details {
+            body["details"] = details;
+        }
+        (status, body)
+    }
+}
 
-        (status, body).into_response()
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after();
+        let (status, body) = self.envelope();
+        with_retry_after((status, Json(body)).into_response(), retry_after)
+    }
+}
+
+fn with_retry_after(mut response: Response, retry_after: Option<HeaderValue>) -> Response {
+    if let Some(value) = retry_after {
+        response.headers_mut().insert(RETRY_AFTER_HEADER, value);
+    }
+    response
+}
+
+/// A failed order placement, echoing the caller's identifiers so the
+/// rejection can be matched to the request.
+#[derive(Debug)]
+pub struct OrderRejection {
+    pub error: AppError,
+    pub client_order_id: Option<String>,
+    pub correlation_id: String,
+}
+
+impl IntoResponse for OrderRejection {
+    fn into_response(self) -> Response {
+        let retry_after = self.error.retry_after();
+        let (status, mut body) = self.error.envelope();
+        if let Some(client_order_id) = self.client_order_id {
+            body["client_order_id"] = json!(client_order_id);
+        }
+        let mut response = with_retry_after((status, Json(body)).into_response(), retry_after);
+        if let Ok(value) = HeaderValue::from_str(&self.correlation_id) {
+            response.headers_mut().insert(CORRELATION_ID_HEADER, value);
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FieldErrorKind;
+
+    #[tokio::test]
+    async fn test_validation_envelope_lists_every_field() {
+        let err = AppError::Validation(vec![
+            FieldError::new("side", FieldErrorKind::Missing),
+            FieldError::new("price", FieldErrorKind::NotAString("number")),
+        ]);
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "VALIDATION_FAILED");
+        assert_eq!(body["details"][0]["field"], "side");
+        assert_eq!(body["details"][1]["code"], "NOT_A_STRING");
+        assert_eq!(body["details"][1]["message"], "must be a string, got number");
+    }
+
+    #[test]
+    fn test_engine_errors_map_to_http_status() {
+        let cases = [
+            (EngineError::from(OrderError::InvalidPrice("0".into())), StatusCode::BAD_REQUEST),
+            (EngineError::from(OrderError::SelfTrade), StatusCode::UNPROCESSABLE_ENTITY),
+            (EngineError::from(OrderError::NotFound { order_id: "x".into() }), StatusCode::NOT_FOUND),
+            (
+                EngineError::from(OrderError::AlreadyTerminal { status: "FILLED".into() }),
+                StatusCode::CONFLICT,
+            ),
+            (EngineError::from(AccountError::Suspended), StatusCode::FORBIDDEN),
+            (
+                EngineError::from(types::market::MarketRejectReason::Delisted),
+                StatusCode::CONFLICT,
+            ),
+            (EngineError::System { message: "disk".into() }, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (err, status) in cases {
+            assert_eq!(engine_error_status(&err).0, status, "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_error_envelope_hides_internal_faults() {
+        let response = AppError::Engine(EngineError::System { message: "disk full".into() }).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "INTERNAL_ERROR");
+        assert_eq!(body["message"], "Internal server error");
+    }
+
+    #[test]
+    fn test_circuit_open_advises_whole_seconds() {
+        let err = AppError::CircuitOpen {
+            dependency: "matching-engine",
+            retry_after: Duration::from_millis(1_200),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER_HEADER], "2");
+
+        let err = AppError::CircuitOpen {
+            dependency: "matching-engine",
+            retry_after: Duration::from_millis(40),
+        };
+        assert_eq!(err.into_response().headers()[RETRY_AFTER_HEADER], "1");
     }
 }
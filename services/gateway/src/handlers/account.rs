@@ -1,8 +1,10 @@
 use crate::auth::AuthenticatedUser;
+use crate::engine_client::{correlation_id, forward_ids};
 use crate::error::AppError;
-use crate::state::AppState;
+use crate::state::{AppState, ACCOUNT_SERVICE};
 use axum::{
     extract::{Path, State},
+    http::HeaderMap,
     Json,
 };
 use types::account::Account;
@@ -10,28 +12,25 @@ use types::account::Account;
 pub async fn get_account(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Path(account_id): Path<String>,
 ) -> Result<Json<Account>, AppError> {
-    // Rate limits
-    state
-        .rate_limiter
-        .check_rate_limit(&format!("{}:account_query", user.account_id), 60, 1.0)?;
-
     // Identity validation
     if user.account_id.to_string() != account_id {
         return Err(AppError::Unauthorized("Cannot view another account".into()));
     }
 
     // Forward to internal Account Service
-    let res = state
-        .http_client
-        .get(format!(
+    let request = state
+        .internal
+        .get(ACCOUNT_SERVICE, format!(
             "{}/internal/accounts/{}",
             state.internal_services_url, account_id
-        ))
-        .send()
+        ));
+    let res = forward_ids(request, &correlation_id(&headers))
+        .send_with_retry()
         .await
-        .map_err(|e| AppError::ServiceUnavailable(format!("Account service error: {}", e)))?;
+        .map_err(|e| e.unavailable("Account service"))?;
 
     if !res.status().is_success() {
         return Err(AppError::BadRequest("Failed to retrieve account".into()));
@@ -0,0 +1,130 @@
+use crate::health::{GIT_SHA, VERSION};
+use crate::state::AppState;
+use axum::{extract::State, http::StatusCode, Json};
+use serde_json::{json, Value};
+
+/// Liveness: the process is up.
+pub async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness: every downstream dependency passed its recent checks.
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let failing: Vec<&str> = state
+        .health
+        .statuses()
+        .iter()
+        .filter(|status| !status.up)
+        .map(|status| status.name)
+        .collect();
+    if failing.is_empty() {
+        (StatusCode::OK, Json(json!({ "ready": true })))
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "ready": false, "failing": failing })))
+    }
+}
+
+/// Build constants, the cached result of every dependency check and the
+/// state of the internal call breakers.
+pub async fn status(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "service": "gateway",
+        "version": VERSION,
+        "git_sha": GIT_SHA,
+        "ready": state.health.is_ready(),
+        "dependencies": state.health.statuses(),
+        "circuit_breakers": state.internal.breakers(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::health::{Dependency, HealthCheckConfig, HealthRegistry, VERSION};
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+
+    /// Downstream stand-in that is healthy while `up` is set.
+    async fn spawn_downstream() -> (String, Arc<AtomicBool>) {
+        let up = Arc::new(AtomicBool::new(true));
+        let app = Router::new()
+            .route(
+                "/health",
+                get(|State(up): State<Arc<AtomicBool>>| async move {
+                    if up.load(Ordering::SeqCst) {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                }),
+            )
+            .with_state(up.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, up)
+    }
+
+    async fn get_json(state: &AppState, uri: &str) -> (StatusCode, Value) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_readiness_follows_dependency_checks() {
+        let (engine_url, _) = spawn_downstream().await;
+        let (risk_url, risk_up) = spawn_downstream().await;
+        let mut state = AppState::new(engine_url.clone());
+        state.health = Arc::new(HealthRegistry::new(
+            vec![Dependency::new("matching-engine", engine_url), Dependency::new("risk-engine", risk_url)],
+            HealthCheckConfig {
+                failure_threshold: 2,
+                ..HealthCheckConfig::default()
+            },
+        ));
+
+        // Liveness never depends on downstream services
+        assert_eq!(get_json(&state, "/health").await, (StatusCode::OK, serde_json::json!({ "status": "ok" })));
+        let (status, _) = get_json(&state, "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        state.health.check_all(&state.http_client).await;
+        assert_eq!(get_json(&state, "/ready").await.0, StatusCode::OK);
+
+        risk_up.store(false, Ordering::SeqCst);
+        state.health.check_all(&state.http_client).await;
+        assert_eq!(get_json(&state, "/ready").await.0, StatusCode::OK);
+        state.health.check_all(&state.http_client).await;
+        let (status, body) = get_json(&state, "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["failing"], serde_json::json!(["risk-engine"]));
+
+        let (status, body) = get_json(&state, "/status").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], VERSION);
+        assert_eq!(body["ready"], false);
+        let risk = &body["dependencies"][1];
+        assert_eq!((risk["name"].as_str(), risk["up"].as_bool()), (Some("risk-engine"), Some(false)));
+        assert_eq!(risk["consecutive_failures"], 2);
+        assert!(risk["last_success_at"].is_i64());
+        assert!(body["dependencies"][0]["latency_ms"].is_u64());
+
+        risk_up.store(true, Ordering::SeqCst);
+        state.health.check_all(&state.http_client).await;
+        assert_eq!(get_json(&state, "/ready").await, (StatusCode::OK, serde_json::json!({ "ready": true })));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::auth::AuthenticatedUser;
+use crate::error::AppError;
+use crate::models::{OrderHistoryQuery, TradeHistoryQuery};
+use crate::state::AppState;
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    Json,
+};
+use persistence::history::{OrderRecord, Page, TradeRecord, DEFAULT_PAGE_SIZE};
+
+pub async fn list_orders(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    query: Result<Query<OrderHistoryQuery>, QueryRejection>,
+) -> Result<Json<Page<OrderRecord>>, AppError> {
+    // 1. Validate filters
+    let Query(query) = query.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let filter = query.filter().map_err(AppError::Validation)?;
+
+    // 2. Read the caller's own history
+    let page = state.history.read().unwrap().orders(
+        user.account_id,
+        &filter,
+        query.cursor.as_deref(),
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    )?;
+    Ok(Json(page))
+}
+
+pub async fn list_trades(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    query: Result<Query<TradeHistoryQuery>, QueryRejection>,
+) -> Result<Json<Page<TradeRecord>>, AppError> {
+    // 1. Validate filters
+    let Query(query) = query.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let filter = query.filter().map_err(AppError::Validation)?;
+
+    // 2. Read the caller's own history
+    let page = state.history.read().unwrap().trades(
+        user.account_id,
+        &filter,
+        query.cursor.as_deref(),
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    )?;
+    Ok(Json(page))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::test_token;
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use persistence::history::{Fill, HistoryIndex, OrderRecord, MAX_PAGE_SIZE};
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::sync::{Arc, RwLock};
+    use tower::ServiceExt;
+    use types::ids::{AccountId, OrderId, TradeId};
+    use types::order::{OrderStatus, Side};
+
+    const T0: i64 = 1708128000000000000;
+
+    /// `count` orders, four per timestamp, alternating BTC and ETH; every
+    /// third BTC order is filled.
+    fn seeded(account_id: AccountId, count: u64) -> AppState {
+        let mut history = HistoryIndex::default();
+        for i in 0..count {
+            let symbol = if i % 2 == 0 { "BTC/USDT" } else { "ETH/USDT" };
+            let order_id = OrderId::new();
+            history.record_order(OrderRecord {
+                sequence: 2 * i + 1,
+                timestamp: T0 + (i / 4) as i64,
+                order_id,
+                account_id,
+                symbol: symbol.to_string(),
+                side: Side::BUY,
+                price: Some(Decimal::from(100)),
+                quantity: Decimal::from(1),
+                filled_quantity: Decimal::ZERO,
+                status: OrderStatus::Pending,
+                updated_at: T0,
+            });
+            if i % 6 == 0 {
+                history.record_fill(Fill {
+                    sequence: 2 * i + 2,
+                    timestamp: T0 + (i / 4) as i64,
+                    order_id,
+                    trade_id: TradeId::new(),
+                    price: Decimal::from(100),
+                    quantity: Decimal::from(1),
+                    remaining: Decimal::ZERO,
+                });
+            }
+        }
+        let mut state = AppState::new("http://127.0.0.1:1".into());
+        state.history = Arc::new(RwLock::new(history));
+        state
+    }
+
+    async fn get(state: &AppState, account_id: AccountId, uri: &str) -> (StatusCode, Value) {
+        let token = test_token(account_id);
+        let request = Request::get(uri)
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    /// Follow `next_cursor` from `uri` to the end, returning every row.
+    async fn collect(state: &AppState, account_id: AccountId, uri: &str) -> Vec<Value> {
+        let mut rows = Vec::new();
+        let mut next = uri.to_string();
+        loop {
+            let (status, page) = get(state, account_id, &next).await;
+            assert_eq!(status, StatusCode::OK, "{page}");
+            rows.extend(page["items"].as_array().unwrap().iter().cloned());
+            match page["next_cursor"].as_str() {
+                Some(cursor) => next = format!("{uri}&cursor={cursor}"),
+                None => return rows,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_history_pages_newest_first_with_filters() {
+        let account_id = AccountId::new();
+        let state = seeded(account_id, 1_200);
+
+        let (_, page) = get(&state, account_id, "/v1/orders").await;
+        assert_eq!(page["items"].as_array().unwrap().len(), 100);
+        let (_, page) = get(&state, account_id, "/v1/orders?limit=5000").await;
+        assert_eq!(page["items"].as_array().unwrap().len(), MAX_PAGE_SIZE);
+
+        let rows = collect(&state, account_id, "/v1/orders?limit=500").await;
+        assert_eq!(rows.len(), 1_200);
+        let sequences: Vec<u64> = rows.iter().map(|r| r["sequence"].as_u64().unwrap()).collect();
+        assert!(sequences.windows(2).all(|w| w[0] > w[1]));
+
+        let rows = collect(&state, account_id, "/v1/orders?status=filled&symbol=BTC/USDT&limit=30").await;
+        assert_eq!(rows.len(), 200);
+        assert!(rows.iter().all(|r| r["status"]["state"] == "FILLED" && r["symbol"] == "BTC/USDT"));
+        let rows = collect(&state, account_id, "/v1/orders?status=PENDING&symbol=ETH/USDT&limit=250").await;
+        assert_eq!(rows.len(), 600);
+
+        // History is per account
+        let (_, page) = get(&state, AccountId::new(), "/v1/orders").await;
+        assert_eq!(page["items"], Value::Array(vec![]));
+        assert_eq!(page["next_cursor"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_trade_history_filters_by_time() {
+        let account_id = AccountId::new();
+        let state = seeded(account_id, 1_200);
+        let uri = format!("/v1/trades?symbol=BTC/USDT&from={}&to={}&limit=7", T0 + 10, T0 + 99);
+        let rows = collect(&state, account_id, &uri).await;
+        // Orders 40..400 fall in the window; every sixth one filled
+        assert_eq!(rows.len(), 60);
+
+        let uri = format!("/v1/trades?from={}&to={}", T0 + 1, T0);
+        let (status, body) = get(&state, account_id, &uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "INVALID_RANGE");
+    }
+
+    #[tokio::test]
+    async fn test_bad_queries_and_cursors_are_rejected() {
+        let account_id = AccountId::new();
+        let state = seeded(account_id, 50);
+        let (_, page) = get(&state, account_id, "/v1/orders?limit=10").await;
+        let orders_cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+        let cases = [
+            ("/v1/orders?cursor=zz".to_string(), "INVALID_CURSOR"),
+            (format!("/v1/trades?cursor={orders_cursor}"), "INVALID_CURSOR"),
+            ("/v1/orders?limit=0".to_string(), "INVALID_LIMIT"),
+            ("/v1/orders?limit=ten".to_string(), "BAD_REQUEST"),
+            ("/v1/orders?status=OPEN".to_string(), "VALIDATION_FAILED"),
+            ("/v1/trades?symbol=BTCUSDT".to_string(), "VALIDATION_FAILED"),
+        ];
+        for (uri, code) in cases {
+            let (status, body) = get(&state, account_id, &uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert_eq!(body["error"], code, "{uri}");
+        }
+        // Another account's cursor does not resume anything
+        let (_, body) = get(&state, AccountId::new(), &format!("/v1/orders?cursor={orders_cursor}")).await;
+        assert_eq!(body["error"], "INVALID_CURSOR");
+    }
+
+    #[tokio::test]
+    async fn test_expired_cursor_is_rejected() {
+        let account_id = AccountId::new();
+        let mut state = seeded(account_id, 0);
+        state.history = Arc::new(RwLock::new(HistoryIndex::new(5)));
+        let order = |sequence| OrderRecord {
+            sequence,
+            timestamp: T0,
+            order_id: OrderId::new(),
+            account_id,
+            symbol: "BTC/USDT".into(),
+            side: Side::SELL,
+            price: None,
+            quantity: Decimal::from(1),
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Pending,
+            updated_at: T0,
+        };
+        for sequence in 1..=5 {
+            state.history.write().unwrap().record_order(order(sequence));
+        }
+        let (_, page) = get(&state, account_id, "/v1/orders?limit=2").await;
+        let cursor = page["next_cursor"].as_str().unwrap().to_string();
+        for sequence in 6..=9 {
+            state.history.write().unwrap().record_order(order(sequence));
+        }
+        let (status, body) = get(&state, account_id, &format!("/v1/orders?cursor={cursor}")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "CURSOR_EXPIRED");
+    }
+}
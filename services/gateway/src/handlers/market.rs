@@ -0,0 +1,59 @@
+use crate::error::AppError;
+use crate::models::MarketInfo;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use types::ids::MarketId;
+
+pub async fn get_market(
+    State(state): State<AppState>,
+    Path((base, quote)): Path<(String, String)>,
+) -> Result<Json<MarketInfo>, AppError> {
+    let symbol = MarketId::try_new(format!("{}/{}", base, quote))
+        .ok_or_else(|| AppError::BadRequest("Invalid market symbol".into()))?;
+
+    let rules = state.market_rules(symbol.as_str());
+    let status = state.market_status(symbol.as_str());
+
+    Ok(Json(MarketInfo {
+        symbol,
+        status,
+        price_decimals: rules.price_decimals,
+        quantity_decimals: rules.quantity_decimals,
+        tick_size: rules.config.tick_size,
+        lot_size: rules.config.lot_size,
+        min_notional: rules.config.min_notional,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::market::MarketStatus;
+
+    #[tokio::test]
+    async fn test_market_info_surfaces_status() {
+        let state = AppState::new("http://localhost".into());
+        state
+            .market_status
+            .insert("BTC/USDT".to_string(), MarketStatus::CancelOnly);
+
+        let Json(info) = get_market(
+            State(state.clone()),
+            Path(("BTC".to_string(), "USDT".to_string())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(info.status, MarketStatus::CancelOnly);
+        assert_eq!(info.price_decimals, 2);
+        assert_eq!(info.tick_size.to_string(), "0.01");
+
+        let Json(other) = get_market(State(state), Path(("ETH".to_string(), "USDT".to_string())))
+            .await
+            .unwrap();
+        assert_eq!(other.status, MarketStatus::Trading);
+        assert_eq!(serde_json::to_value(&other).unwrap()["status"], "TRADING");
+    }
+}
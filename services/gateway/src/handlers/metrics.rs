@@ -0,0 +1,120 @@
+use crate::state::AppState;
+use axum::{extract::State, http::header, response::IntoResponse};
+use metrics_core::CONTENT_TYPE;
+
+/// Prometheus scrape endpoint.
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.observe_breakers(&state.internal.breakers());
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], state.metrics.render())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::test_token;
+    use crate::rate_limit::{BucketLimits, RateLimitConfig, RateLimiter};
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use metrics_core::{samples, CONTENT_TYPE};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use types::ids::AccountId;
+
+    async fn status_of(state: &AppState, request: Request<Body>) -> StatusCode {
+        create_router(state.clone()).oneshot(request).await.unwrap().status()
+    }
+
+    async fn scrape(state: &AppState) -> BTreeMap<String, f64> {
+        let response = create_router(state.clone())
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        samples(std::str::from_utf8(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_routes_outcomes_and_rejections() {
+        // The engine is unreachable, so valid orders end in `error`
+        let mut state = AppState::new("http://127.0.0.1:1".into());
+        // No refill: two orders at five tokens and two lookups at one fit
+        state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            standard: BucketLimits::new(12, 0.0),
+            ..RateLimitConfig::default()
+        }));
+        let account_id = AccountId::new();
+        let token = test_token(account_id);
+        let authorized = |request: axum::http::request::Builder| {
+            request.header("authorization", format!("Bearer {}", token))
+        };
+        let order = json!({
+            "account_id": account_id.to_string(),
+            "symbol": "BTC/USDT",
+            "side": "BUY",
+            "order_type": "LIMIT",
+            "price": "100",
+            "quantity": "1",
+            "time_in_force": "GTC"
+        });
+        let place = |body: String| {
+            authorized(Request::post("/v1/orders"))
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        for _ in 0..2 {
+            let request = Request::get("/health").body(Body::empty()).unwrap();
+            assert_eq!(status_of(&state, request).await, StatusCode::OK);
+        }
+        assert_eq!(status_of(&state, place("{".into())).await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_of(&state, place(order.to_string())).await, StatusCode::SERVICE_UNAVAILABLE);
+        let lookup = authorized(Request::get(format!("/v1/accounts/{}", AccountId::new())));
+        let other_account = status_of(&state, lookup.body(Body::empty()).unwrap()).await;
+        assert_eq!(other_account, StatusCode::UNAUTHORIZED);
+        for expected in [StatusCode::SERVICE_UNAVAILABLE, StatusCode::TOO_MANY_REQUESTS] {
+            let lookup = authorized(Request::get(format!("/v1/accounts/{}", account_id)));
+            assert_eq!(status_of(&state, lookup.body(Body::empty()).unwrap()).await, expected);
+        }
+
+        let metrics = scrape(&state).await;
+        let requests = |method: &str, route: &str, status: u16| {
+            let series = format!(
+                "gateway_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}}",
+                method, route, status
+            );
+            metrics.get(&series).copied()
+        };
+        assert_eq!(requests("GET", "/health", 200), Some(2.0));
+        assert_eq!(requests("POST", "/v1/orders", 400), Some(1.0));
+        assert_eq!(requests("POST", "/v1/orders", 503), Some(1.0));
+        assert_eq!(requests("GET", "/v1/accounts/{id}", 401), Some(1.0));
+        assert_eq!(requests("GET", "/v1/accounts/{id}", 429), Some(1.0));
+        assert_eq!(requests("GET", "/metrics", 200), None, "scrapes are not counted");
+
+        let health = "method=\"GET\",route=\"/health\"";
+        let bucket = |le: &str| {
+            metrics[&format!("gateway_http_request_duration_seconds_bucket{{{},le=\"{}\"}}", health, le)]
+        };
+        assert!(bucket("0.001") <= bucket("10"));
+        assert_eq!(bucket("+Inf"), 2.0);
+        assert_eq!(metrics[&format!("gateway_http_request_duration_seconds_count{{{}}}", health)], 2.0);
+
+        let outcomes = ["accepted", "rejected", "invalid", "error"]
+            .map(|outcome| metrics[&format!("gateway_order_submissions_total{{outcome=\"{}\"}}", outcome)]);
+        assert_eq!(outcomes, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(metrics["gateway_rate_limit_rejections_total{route=\"/v1/accounts/{id}\"}"], 1.0);
+        assert_eq!(metrics["gateway_ws_connections{channel=\"user\"}"], 0.0);
+        // The lookup is a read, retried twice before giving up
+        assert_eq!(metrics["gateway_internal_retries_total{dependency=\"account-service\"}"], 2.0);
+        assert_eq!(metrics["gateway_circuit_breaker_state{dependency=\"account-service\"}"], 0.0);
+        assert_eq!(metrics["gateway_circuit_breaker_rejections_total{dependency=\"matching-engine\"}"], 0.0);
+    }
+}
@@ -1,3 +1,9 @@
 pub mod account;
+pub mod api_key;
+pub mod health;
+pub mod history;
+pub mod market;
+pub mod metrics;
 pub mod order;
+pub mod withdrawal;
 pub mod ws;
@@ -1,79 +1,307 @@
-use crate::auth::AuthenticatedUser;
-use crate::error::AppError;
-use crate::models::{CancelOrderRequest, CreateOrderRequest, OrderResponse};
-use crate::state::AppState;
+use crate::auth::{verify_request, AuthenticatedUser};
+use crate::engine_client::{correlation_id, forward_ids, OrderAck, CORRELATION_ID_HEADER};
+use crate::error::{AppError, OrderRejection};
+use crate::idempotency::{fingerprint, idempotency_key, Claim};
+use crate::metrics::order_outcome;
+use crate::models::{
+    BatchAction, BatchInstruction, BatchItemResult, BatchPayload, BatchResponse, CancelOrderRequest,
+    OrderResponse, PlaceOrderPayload, MAX_BATCH_ITEMS,
+};
+use crate::rate_limit::Identity;
+use crate::state::{AppState, MATCHING_ENGINE};
 use axum::{
-    extract::{Path, State},
-    Json,
+    extract::{rejection::JsonRejection, ConnectInfo, Path, State},
+    http::{HeaderMap, Method},
+    response::AppendHeaders,
+    Extension, Json,
 };
+use serde_json::{Map, Value};
+use std::net::SocketAddr;
+use types::errors::EngineError;
 use types::ids::OrderId;
 use types::order::Order;
+use wasm_core::payload::SignableCancel;
 use axum::http::StatusCode;
 
-pub async fn create_order(
+pub async fn place_order(
     State(state): State<AppState>,
+    headers: HeaderMap,
     user: AuthenticatedUser,
-    Json(payload): Json<CreateOrderRequest>,
-) -> Result<Json<OrderResponse>, AppError> {
-    // 1. Check rate limits (API Level)
-    // For VIP / Institutional this would vary based on auth user tier
+    payload: Result<Json<PlaceOrderPayload>, JsonRejection>,
+) -> Result<(AppendHeaders<[(&'static str, String); 1]>, Json<OrderResponse>), OrderRejection> {
+    let correlation_id = correlation_id(&headers);
+    let client_order_id = payload
+        .as_ref()
+        .ok()
+        .and_then(|Json(p)| p.client_order_id_str())
+        .map(str::to_owned);
+
+    let result = submit_order(&state, &headers, &user, payload, &correlation_id).await;
+    state.metrics.order_submissions.inc(&[order_outcome(&result)]);
+    match result {
+        Ok(ack) => Ok((
+            AppendHeaders([(CORRELATION_ID_HEADER, correlation_id)]),
+            Json(OrderResponse {
+                order_id: ack.order_id,
+                status: ack.status,
+                client_order_id,
+            }),
+        )),
+        Err(error) => Err(OrderRejection {
+            error,
+            client_order_id,
+            correlation_id,
+        }),
+    }
+}
+
+async fn submit_order(
+    state: &AppState,
+    headers: &HeaderMap,
+    user: &AuthenticatedUser,
+    payload: Result<Json<PlaceOrderPayload>, JsonRejection>,
+    correlation_id: &str,
+) -> Result<OrderAck, AppError> {
+    // 1. Validate payload, reporting every invalid field at once
+    let Json(payload) = payload.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let rules = state.market_rules(payload.symbol_str().unwrap_or_default());
+    let payload = payload.validate(&rules).map_err(AppError::Validation)?;
     state
-        .rate_limiter
-        .check_rate_limit(&format!("{}:order_placement", user.account_id), 20, 20.0)?;
+        .market_status(payload.symbol.as_str())
+        .check_new_order()
+        .map_err(EngineError::from)?;
 
-    // 2. Validate user identity matches order owner
+    // 2. Validate the signature against the canonical payload of the
+    //    validated order, and that the caller owns the order
+    verify_request(user, |timestamp, nonce| payload.signable().into_signable(timestamp, nonce))?;
     if user.account_id != payload.account_id {
         return Err(AppError::Unauthorized("Cannot place order for another account".into()));
     }
 
-    // 3. Forward to internal Order Service
-    // POST /internal/orders
-    let res = state
-        .http_client
-        .post(format!("{}/internal/orders", state.internal_services_url))
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| AppError::ServiceUnavailable(format!("Order service error: {}", e)))?;
+    // 3. Forward to the matching engine, at most once per idempotency key;
+    //    `client_order_id` is the key when no header is sent
+    let key = idempotency_key(headers)?.or_else(|| payload.client_order_id.clone());
+    let Some(key) = key else {
+        return state.engine.place_order(&payload, correlation_id).await;
+    };
+    let key = format!("{}:{}", user.account_id, key);
+    let pending = match state.order_idempotency.claim(key, fingerprint(&payload)).await? {
+        Claim::Replay(outcome) => return outcome.map_err(AppError::Engine),
+        Claim::Execute(pending) => pending,
+        Claim::Reconcile(pending) => {
+            // An earlier attempt may have been accepted: ask the engine first
+            let Some(client_order_id) = payload.client_order_id.as_deref() else {
+                return Err(AppError::OutcomeUnknown(
+                    "An earlier order with this Idempotency-Key may have been accepted; \
+                     check open orders before placing it under a new key"
+                        .into(),
+                ));
+            };
+            let found = state.engine.find_order(user.account_id, client_order_id, correlation_id).await?;
+            if let Some(ack) = found {
+                pending.complete(Ok(ack.clone()));
+                return Ok(ack);
+            }
+            pending
+        }
+    };
+    // Only the engine's own answer is replayed. A failure before the order
+    // was sent releases the key; one after it leaves the outcome unknown.
+    match state.engine.place_order(&payload, correlation_id).await {
+        Ok(ack) => {
+            pending.complete(Ok(ack.clone()));
+            Ok(ack)
+        }
+        Err(AppError::Engine(err)) => {
+            pending.complete(Err(err.clone()));
+            Err(AppError::Engine(err))
+        }
+        Err(err @ AppError::OutcomeUnknown(_)) => Err(err),
+        Err(err) => {
+            pending.release();
+            Err(err)
+        }
+    }
+}
 
-    if !res.status().is_success() {
-        return Err(AppError::BadRequest("Failed to create order".into()));
+/// Place, cancel and amend orders in one signed request.
+///
+/// Items run in the listed order and each gets its own result. An atomic
+/// batch is rejected whole if any item is invalid, before anything reaches
+/// the engine; once executing, it stops at the first engine rejection and
+/// skips the remaining items.
+pub async fn place_batch(
+    State(state): State<AppState>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    payload: Result<Json<BatchPayload>, JsonRejection>,
+) -> Result<(AppendHeaders<[(&'static str, String); 1]>, Json<BatchResponse>), AppError> {
+    // 1. Bound the batch size before charging or verifying anything
+    let Json(batch) = payload.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    if batch.items.is_empty() || batch.items.len() > MAX_BATCH_ITEMS {
+        return Err(AppError::BadRequest(format!(
+            "A batch holds 1 to {} items, got {}",
+            MAX_BATCH_ITEMS,
+            batch.items.len()
+        )));
+    }
+
+    // 2. Charge each item as the single request it stands for
+    let weights = &state.rate_limiter.config().weights;
+    let weight = batch
+        .items
+        .iter()
+        .map(|item| match BatchAction::of(item) {
+            Ok(BatchAction::Cancel) => weights.weight(&Method::DELETE, "/v1/orders/{id}"),
+            _ => weights.weight(&Method::POST, "/v1/orders"),
+        })
+        .sum();
+    let caller = Identity::of(&user);
+    let ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    if !state.rate_limiter.check(Some(&caller), ip, weight).allowed {
+        state.metrics.rate_limit_rejections.inc(&["/v1/orders/batch"]);
+        return Err(AppError::RateLimitExceeded(format!(
+            "Batch of {} item(s) costs {} token(s)",
+            batch.items.len(),
+            weight
+        )));
+    }
+
+    // 3. One signature covers every item as sent
+    verify_request(&user, |timestamp, nonce| batch.signable().into_signable(timestamp, nonce))?;
+
+    // 4. Validate every item
+    let client_order_ids: Vec<Option<String>> = batch
+        .items
+        .iter()
+        .map(|item| item.get("client_order_id").and_then(Value::as_str).map(str::to_owned))
+        .collect();
+    let checked: Vec<Result<BatchInstruction, AppError>> =
+        batch.items.iter().map(|item| check_batch_item(&state, &user, item)).collect();
+    for (item, checked) in batch.items.iter().zip(&checked) {
+        if checked.is_err() && matches!(BatchAction::of(item), Ok(BatchAction::Place)) {
+            state.metrics.order_submissions.inc(&["invalid"]);
+        }
     }
+    if batch.atomic && checked.iter().any(Result::is_err) {
+        let items = checked
+            .into_iter()
+            .zip(client_order_ids)
+            .enumerate()
+            .map(|(index, (checked, client_order_id))| match checked {
+                Ok(_) => BatchItemResult::skipped(index, client_order_id),
+                Err(err) => BatchItemResult::failed(index, client_order_id, err.envelope().1),
+            })
+            .collect();
+        return Err(AppError::BatchRejected(items));
+    }
+
+    // 5. Execute in order
+    let correlation_id = correlation_id(&headers);
+    let mut items = Vec::with_capacity(checked.len());
+    let mut stopped = false;
+    for (index, (checked, client_order_id)) in checked.into_iter().zip(client_order_ids).enumerate() {
+        let result = match checked {
+            _ if stopped => BatchItemResult::skipped(index, client_order_id),
+            Err(err) => BatchItemResult::failed(index, client_order_id, err.envelope().1),
+            Ok(instruction) => match execute_batch_item(&state, &user, instruction, &correlation_id).await {
+                Ok(order_id) => BatchItemResult::accepted(index, order_id, client_order_id),
+                Err(err) => {
+                    stopped = batch.atomic;
+                    BatchItemResult::failed(index, client_order_id, err.envelope().1)
+                }
+            },
+        };
+        items.push(result);
+    }
+
+    Ok((
+        AppendHeaders([(CORRELATION_ID_HEADER, correlation_id)]),
+        Json(BatchResponse {
+            atomic: batch.atomic,
+            items,
+        }),
+    ))
+}
 
-    // Mock successful response
-    Ok(Json(OrderResponse {
-        order_id: OrderId::new(),
-        status: "PENDING".to_string(),
-    }))
+/// Validate a batch item with the checks its single-item endpoint applies.
+fn check_batch_item(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    item: &Map<String, Value>,
+) -> Result<BatchInstruction, AppError> {
+    let instruction = BatchInstruction::parse(item, user.account_id, |symbol| state.market_rules(symbol))
+        .map_err(AppError::Validation)?;
+    match &instruction {
+        BatchInstruction::Place(order) => {
+            if user.account_id != order.account_id {
+                return Err(AppError::Unauthorized("Cannot place order for another account".into()));
+            }
+            state.market_status(order.symbol.as_str()).check_new_order().map_err(EngineError::from)?;
+        }
+        BatchInstruction::Amend { request, .. } => {
+            state.market_status(request.symbol.as_str()).check_new_order().map_err(EngineError::from)?;
+        }
+        BatchInstruction::Cancel { .. } => {}
+    }
+    Ok(instruction)
+}
+
+/// Forward a validated batch item, returning the affected order's id.
+async fn execute_batch_item(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    instruction: BatchInstruction,
+    correlation_id: &str,
+) -> Result<OrderId, AppError> {
+    match instruction {
+        BatchInstruction::Place(order) => {
+            let result = state.engine.place_order(&order, correlation_id).await.map(|ack| ack.order_id);
+            state.metrics.order_submissions.inc(&[order_outcome(&result)]);
+            result
+        }
+        BatchInstruction::Cancel { order_id } => {
+            let cancel = CancelOrderRequest {
+                account_id: user.account_id,
+            };
+            state.engine.cancel_order(order_id, &cancel, correlation_id).await.map(|()| order_id)
+        }
+        BatchInstruction::Amend { order_id, request } => state
+            .engine
+            .amend_order(order_id, &request, correlation_id)
+            .await
+            .map(|ack| ack.order_id),
+    }
 }
 
 pub async fn cancel_order(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Path(order_id): Path<String>,
     Json(payload): Json<CancelOrderRequest>,
 ) -> Result<StatusCode, AppError> {
-    // 1. Rate limiting
-    state
-        .rate_limiter
-        .check_rate_limit(&format!("{}:order_cancel", user.account_id), 50, 50.0)?;
-
-    // 2. Identity validation
+    // 1. Identity and signature validation
     if user.account_id != payload.account_id {
         return Err(AppError::Unauthorized("Cannot cancel order for another account".into()));
     }
+    verify_request(&user, |timestamp, nonce| {
+        SignableCancel::new(order_id.as_str()).into_signable(timestamp, nonce)
+    })?;
 
-    // 3. Forward
-    let res = state
-        .http_client
-        .delete(format!(
+    // 2. Forward
+    let request = state
+        .internal
+        .delete(MATCHING_ENGINE, format!(
             "{}/internal/orders/{}",
             state.internal_services_url, order_id
-        ))
+        ));
+    let res = forward_ids(request, &correlation_id(&headers))
         .json(&payload)
-        .send()
+        .send_with_retry()
         .await
-        .map_err(|e| AppError::ServiceUnavailable(format!("Order service error: {}", e)))?;
+        .map_err(|e| e.unavailable("Order service"))?;
 
     if !res.status().is_success() {
         return Err(AppError::BadRequest("Failed to cancel order".into()));
@@ -85,23 +313,20 @@ pub async fn cancel_order(
 pub async fn get_order(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Path(order_id): Path<String>,
 ) -> Result<Json<Order>, AppError> {
-    // 1. Rate limiting
-    state
-        .rate_limiter
-        .check_rate_limit(&format!("{}:order_query", user.account_id), 60, 1.0)?;
-
-    // 2. Forward to internal Order Service
-    let res = state
-        .http_client
-        .get(format!(
+    // 1. Forward to internal Order Service
+    let request = state
+        .internal
+        .get(MATCHING_ENGINE, format!(
             "{}/internal/orders/{}",
             state.internal_services_url, order_id
-        ))
-        .send()
+        ));
+    let res = forward_ids(request, &correlation_id(&headers))
+        .send_with_retry()
         .await
-        .map_err(|e| AppError::ServiceUnavailable(format!("Order service error: {}", e)))?;
+        .map_err(|e| e.unavailable("Order service"))?;
 
     if res.status() == reqwest::StatusCode::NOT_FOUND {
         return Err(AppError::NotFound(format!("Order {} not found", order_id)));
@@ -111,13 +336,13 @@ pub async fn get_order(
         return Err(AppError::BadRequest("Failed to retrieve order".into()));
     }
 
-    // 3. Deserialize Order
+    // 2. Deserialize Order
     let order = res
         .json::<Order>()
         .await
         .map_err(|_| AppError::InternalError(anyhow::anyhow!("Invalid order parsing")))?;
 
-    // 4. Identity check — caller must own the order
+    // 3. Identity check — caller must own the order
     if user.account_id != order.account_id {
         return Err(AppError::Unauthorized(
             "Cannot view order for another account".into(),
@@ -126,3 +351,728 @@ pub async fn get_order(
 
     Ok(Json(order))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api_keys::{signed_headers, ApiKey, Permission};
+    use crate::auth::{
+        test_token, WALLET_KEY_HEADER, WALLET_NONCE_HEADER, WALLET_SIGNATURE_HEADER, WALLET_TIMESTAMP_HEADER,
+    };
+    use crate::engine_client::{MatchingEngineClient, OrderAck, CORRELATION_ID_HEADER};
+    use crate::idempotency::IDEMPOTENCY_KEY_HEADER;
+    use crate::models::{MarketRules, MAX_BATCH_ITEMS};
+    use crate::rate_limit::{BucketLimits, RateLimitConfig, RateLimiter};
+    use crate::router::create_router;
+    use crate::state::{AppState, InternalClient};
+    use axum::{
+        body::Body,
+        extract::{Path, State},
+        http::{HeaderMap, Request, StatusCode},
+        response::{IntoResponse, Response},
+        routing::{delete, post},
+        Json, Router,
+    };
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tower::ServiceExt;
+    use types::errors::{AccountError, EngineError, OrderError};
+    use types::ids::{AccountId, OrderId};
+    use types::market::{MarketConfig, MarketStatus};
+    use types::order::Side;
+    use uuid::Uuid;
+    use wasm_core::payload::SignableOrder;
+    use wasm_core::signing::{sign_message, SignableMessage};
+
+    /// Correlation id and body of every order the mock engine received.
+    type Received = Arc<Mutex<Vec<(String, Value)>>>;
+
+    /// Engine stand-in: rejects 13-lot orders for insufficient balance and
+    /// answers `slow` client order ids late.
+    async fn engine_orders(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        Json(body): Json<Value>,
+    ) -> Response {
+        let correlation_id = headers[CORRELATION_ID_HEADER].to_str().unwrap().to_string();
+        let rejected = body["quantity"] == "13";
+        if body["client_order_id"] == "slow" {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        received.lock().unwrap().push((correlation_id, body));
+        if rejected {
+            let err = EngineError::from(AccountError::InsufficientBalance {
+                asset: "USDT".into(),
+                required: "1306.5".into(),
+                available: "100".into(),
+            });
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(err)).into_response();
+        }
+        Json(OrderAck {
+            order_id: OrderId::new(),
+            status: "NEW".into(),
+        })
+        .into_response()
+    }
+
+    /// Cancels fail for the nil order id; everything else succeeds.
+    async fn engine_cancel(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        Path(order_id): Path<String>,
+    ) -> Response {
+        let correlation_id = headers[CORRELATION_ID_HEADER].to_str().unwrap().to_string();
+        received.lock().unwrap().push((correlation_id, json!({ "cancel": order_id })));
+        if order_id == Uuid::nil().to_string() {
+            let err = EngineError::from(OrderError::NotFound { order_id });
+            return (StatusCode::NOT_FOUND, Json(err)).into_response();
+        }
+        StatusCode::OK.into_response()
+    }
+
+    async fn engine_amend(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        Path(order_id): Path<String>,
+        Json(mut body): Json<Value>,
+    ) -> Json<OrderAck> {
+        let correlation_id = headers[CORRELATION_ID_HEADER].to_str().unwrap().to_string();
+        body["amend"] = json!(order_id);
+        received.lock().unwrap().push((correlation_id, body));
+        Json(OrderAck {
+            order_id: OrderId::from_uuid(Uuid::parse_str(&order_id).unwrap()),
+            status: "NEW".into(),
+        })
+    }
+
+    async fn spawn_engine() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/internal/orders", post(engine_orders))
+            .route("/internal/orders/{id}", delete(engine_cancel).patch(engine_amend))
+            .with_state(received.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    async fn gateway() -> (AppState, Received) {
+        let (url, received) = spawn_engine().await;
+        let mut state = AppState::new(url);
+        let rules = MarketRules {
+            config: MarketConfig {
+                tick_size: Decimal::from_str("0.5").unwrap(),
+                lot_size: Decimal::from_str("0.001").unwrap(),
+                min_notional: Decimal::from(10),
+            },
+            ..MarketRules::default()
+        };
+        state.market_rules = Arc::new(HashMap::from([("BTC/USDT".to_string(), rules)]));
+        (state, received)
+    }
+
+    fn valid(account_id: AccountId) -> Value {
+        json!({
+            "account_id": account_id.to_string(),
+            "symbol": "BTC/USDT",
+            "side": "BUY",
+            "order_type": "LIMIT",
+            "price": "100.5",
+            "quantity": "0.5",
+            "time_in_force": "GTC",
+            "client_order_id": "grid-1"
+        })
+    }
+
+    async fn send(state: &AppState, request: Request<Body>) -> (StatusCode, HeaderMap, Value) {
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    fn order_request(account_id: AccountId, body: impl Into<Body>) -> Request<Body> {
+        Request::post("/v1/orders")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", test_token(account_id)))
+            .body(body.into())
+            .unwrap()
+    }
+
+    async fn place(state: &AppState, account_id: AccountId, body: &Value) -> (StatusCode, HeaderMap, Value) {
+        send(state, order_request(account_id, body.to_string())).await
+    }
+
+    #[tokio::test]
+    async fn test_accepted_order_echoes_ids_and_reaches_engine() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let request = Request::post("/v1/orders")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", test_token(account_id)))
+            .header(CORRELATION_ID_HEADER, "trace-abc")
+            .body(Body::from(valid(account_id).to_string()))
+            .unwrap();
+
+        let (status, headers, body) = send(&state, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "NEW");
+        assert_eq!(body["client_order_id"], "grid-1");
+        assert!(body["order_id"].is_string());
+        assert_eq!(headers[CORRELATION_ID_HEADER], "trace-abc");
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "trace-abc");
+        assert_eq!(received[0].1["price"], "100.5");
+        assert_eq!(received[0].1["client_order_id"], "grid-1");
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_is_generated_when_absent() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let mut body = valid(account_id);
+        body.as_object_mut().unwrap().remove("client_order_id");
+
+        let (status, headers, body) = place(&state, account_id, &body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.get("client_order_id").is_none());
+        let generated = headers[CORRELATION_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+        assert_eq!(received.lock().unwrap()[0].0, generated);
+    }
+
+    #[tokio::test]
+    async fn test_field_validation_failures() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let cases: Vec<(&str, Value, &str)> = vec![
+            ("symbol", Value::Null, "MISSING"),
+            ("symbol", json!("BTCUSDT"), "MALFORMED"),
+            ("side", json!("LONG"), "UNKNOWN_VARIANT"),
+            ("order_type", json!("STOP"), "UNKNOWN_VARIANT"),
+            ("price", json!(100.5), "NOT_A_STRING"),
+            ("price", json!("abc"), "NOT_NUMERIC"),
+            ("price", json!("100.505"), "TOO_MANY_DECIMAL_PLACES"),
+            ("price", json!("100.25"), "PRICE_NOT_ON_TICK"),
+            ("price", json!("0"), "NOT_POSITIVE"),
+            ("quantity", json!("-1"), "NEGATIVE"),
+            ("quantity", json!("0.0005"), "QUANTITY_NOT_ON_LOT"),
+            ("quantity", json!("0.05"), "BELOW_MIN_NOTIONAL"),
+            ("quantity", json!(""), "EMPTY"),
+            ("time_in_force", json!("DAY"), "UNKNOWN_VARIANT"),
+            ("client_order_id", json!("x".repeat(40)), "TOO_LONG"),
+            ("client_order_id", json!("has space"), "MALFORMED"),
+        ];
+        for (field, value, code) in cases {
+            let mut body = valid(account_id);
+            match value {
+                Value::Null => body.as_object_mut().unwrap().remove(field),
+                value => body.as_object_mut().unwrap().insert(field.to_string(), value),
+            };
+            let (status, headers, response) = place(&state, account_id, &body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{field} {code}");
+            assert_eq!(response["error"], "VALIDATION_FAILED");
+            assert_eq!(response["details"][0]["field"], field, "{code}");
+            assert_eq!(response["details"][0]["code"], code, "{field}");
+            assert!(headers.contains_key(CORRELATION_ID_HEADER));
+        }
+
+        // Market orders carry no price; GTD needs an expiry
+        let mut body = valid(account_id);
+        body["order_type"] = json!("MARKET");
+        let (_, _, response) = place(&state, account_id, &body).await;
+        assert_eq!(response["details"][0]["code"], "NOT_ALLOWED");
+        assert_eq!(response["client_order_id"], "grid-1");
+        let mut body = valid(account_id);
+        body["time_in_force"] = json!("GTD");
+        let (_, _, response) = place(&state, account_id, &body).await;
+        assert_eq!(response["details"][0]["field"], "expire_at");
+        assert_eq!(response["details"][0]["code"], "MISSING");
+
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_and_foreign_account_are_rejected() {
+        let (state, _) = gateway().await;
+        let account_id = AccountId::new();
+
+        let (status, headers, body) = send(&state, order_request(account_id, "{\"symbol\":")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "BAD_REQUEST");
+        assert!(headers.contains_key(CORRELATION_ID_HEADER));
+
+        let (status, _, body) = place(&state, AccountId::new(), &valid(account_id)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["message"], "Cannot place order for another account");
+        assert_eq!(body["client_order_id"], "grid-1");
+    }
+
+    /// Wallet signature headers for `message`, signed with a fixed key.
+    fn wallet_headers(message: &SignableMessage) -> [(&'static str, String); 4] {
+        let signed = sign_message(message, &ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]));
+        [
+            (WALLET_KEY_HEADER, signed.public_key),
+            (WALLET_SIGNATURE_HEADER, signed.signature),
+            (WALLET_NONCE_HEADER, message.nonce.to_string()),
+            (WALLET_TIMESTAMP_HEADER, message.timestamp.to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature_is_rejected() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let request = Request::post("/v1/orders")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", test_token(account_id)))
+            .header(WALLET_KEY_HEADER, "00".repeat(32))
+            .header(WALLET_SIGNATURE_HEADER, "00".repeat(64))
+            .header(WALLET_NONCE_HEADER, "1")
+            .header(WALLET_TIMESTAMP_HEADER, "1708123456789000000")
+            .body(Body::from(valid(account_id).to_string()))
+            .unwrap();
+
+        let (status, _, body) = send(&state, request).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["message"].as_str().unwrap().starts_with("Invalid signature"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_signature_covers_the_canonical_order() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let key = state.api_keys.create(account_id, [Permission::Trade].into(), false, 0).unwrap();
+        let (price, quantity) = (Decimal::from_str("100.5").unwrap(), Decimal::from_str("0.5").unwrap());
+        let message = SignableOrder::new("BTC/USDT", Side::BUY, Some(price), quantity)
+            .with_client_order_id("grid-1")
+            .into_signable(1708123456789000000, 1);
+        let bearer = |body: &Value| {
+            let mut request = Request::post("/v1/orders")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", test_token(account_id)));
+            for (name, value) in wallet_headers(&message) {
+                request = request.header(name, value);
+            }
+            request.body(Body::from(body.to_string())).unwrap()
+        };
+
+        // A body other than the one signed is refused under either credential
+        let mut tampered = valid(account_id);
+        tampered["quantity"] = json!("0.6");
+        let (status, _, _) = send(&state, bearer(&tampered)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let body = tampered.to_string();
+        let mut request = Request::post("/v1/orders").header("content-type", "application/json");
+        for (name, value) in signed_headers(&key, "POST", "/v1/orders", body.as_bytes()) {
+            request = request.header(name, value);
+        }
+        for (name, value) in wallet_headers(&message) {
+            request = request.header(name, value);
+        }
+        let (status, _, response) = send(&state, request.body(Body::from(body)).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(response["message"].as_str().unwrap().starts_with("Invalid signature"));
+        assert!(received.lock().unwrap().is_empty());
+
+        // Trailing zeros in the body do not change the canonical payload
+        let mut body = valid(account_id);
+        body["quantity"] = json!("0.500");
+        let (status, _, _) = send(&state, bearer(&body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_orders_are_signed_over_the_body() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let key = state.api_keys.create(account_id, [Permission::Trade].into(), false, 0).unwrap();
+        let body = valid(account_id).to_string();
+        let request = |key: &ApiKey| {
+            let mut request = Request::post("/v1/orders").header("content-type", "application/json");
+            for (name, value) in signed_headers(key, "POST", "/v1/orders", body.as_bytes()) {
+                request = request.header(name, value);
+            }
+            request.body(Body::from(body.clone())).unwrap()
+        };
+
+        let forged = ApiKey {
+            secret: "guessed".into(),
+            ..key.clone()
+        };
+        let (status, _, response) = send(&state, request(&forged)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(response["error"], "INVALID_SIGNATURE");
+        assert!(received.lock().unwrap().is_empty());
+
+        let (status, _, _) = send(&state, request(&key)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap()[0].1["account_id"], json!(account_id));
+    }
+
+    #[tokio::test]
+    async fn test_closed_market_and_engine_rejections_use_shared_taxonomy() {
+        let (state, _) = gateway().await;
+        let account_id = AccountId::new();
+
+        let mut body = valid(account_id);
+        body["quantity"] = json!("13");
+        let (status, _, response) = place(&state, account_id, &body).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response["error"], "INSUFFICIENT_BALANCE");
+        assert_eq!(response["client_order_id"], "grid-1");
+
+        state.market_status.insert("BTC/USDT".into(), MarketStatus::HaltedManual);
+        let (status, _, response) = place(&state, account_id, &valid(account_id)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(response["error"], "MARKET_CLOSED");
+        assert_eq!(response["message"], "Market closed: Market halted by operator");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_per_account() {
+        let (mut state, _) = gateway().await;
+        // No refill, so the 100-token bucket pays for exactly 20 orders
+        state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            standard: BucketLimits::new(100, 0.0),
+            ..RateLimitConfig::default()
+        }));
+        let account_id = AccountId::new();
+        for _ in 0..20 {
+            let (status, _, _) = place(&state, account_id, &valid(account_id)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, _, body) = place(&state, account_id, &valid(account_id)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["error"], "RATE_LIMIT_EXCEEDED");
+
+        let other = AccountId::new();
+        let (status, _, _) = place(&state, other, &valid(other)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    fn keyed(account_id: AccountId, key: &str, body: &Value) -> Request<Body> {
+        let mut request = order_request(account_id, body.to_string());
+        request.headers_mut().insert(IDEMPOTENCY_KEY_HEADER, key.parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_same_key_replays_without_reaching_engine() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let body = valid(account_id);
+
+        let (status, _, first) = send(&state, keyed(account_id, "retry-1", &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, headers, second) = send(&state, keyed(account_id, "retry-1", &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(second, first);
+        assert!(headers.contains_key(CORRELATION_ID_HEADER));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Same key, different body
+        let mut changed = body.clone();
+        changed["quantity"] = json!("0.6");
+        let (status, _, response) = send(&state, keyed(account_id, "retry-1", &changed)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(response["error"], "CONFLICT");
+        assert_eq!(response["client_order_id"], "grid-1");
+
+        // Keys are per account
+        let other = AccountId::new();
+        let (status, _, _) = send(&state, keyed(other, "retry-1", &valid(other))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_order_id_keys_retries_and_engine_rejections_replay() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let mut body = valid(account_id);
+        body["quantity"] = json!("13");
+
+        for _ in 0..2 {
+            let (status, _, response) = place(&state, account_id, &body).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(response["error"], "INSUFFICIENT_BALANCE");
+        }
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Requests rejected before the engine leave the key unused
+        body["client_order_id"] = json!("fresh");
+        body["price"] = json!("100.25");
+        let (status, _, _) = place(&state, account_id, &body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        body["price"] = json!("100.5");
+        body["quantity"] = json!("0.5");
+        let (status, _, _) = place(&state, account_id, &body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_reach_engine_once() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let mut body = valid(account_id);
+        body["client_order_id"] = json!("slow");
+
+        let ((a_status, _, a), (b_status, _, b)) =
+            tokio::join!(place(&state, account_id, &body), place(&state, account_id, &body));
+        assert_eq!((a_status, b_status), (StatusCode::OK, StatusCode::OK));
+        assert_eq!(a["order_id"], b["order_id"]);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_engine_releases_key() {
+        let (mut state, _) = gateway().await;
+        state.engine = MatchingEngineClient::new(InternalClient::default(), "http://127.0.0.1:1");
+        let account_id = AccountId::new();
+        let (status, _, _) = place(&state, account_id, &valid(account_id)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (url, received) = spawn_engine().await;
+        state.engine = MatchingEngineClient::new(InternalClient::default(), url);
+        let (status, _, _) = place(&state, account_id, &valid(account_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    /// Request head and body read off a raw connection.
+    async fn read_request(socket: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut buf = Vec::new();
+        let mut chunk = [0; 4096];
+        loop {
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&buf[..end]).into_owned();
+                let len = head
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .map_or(0, |(_, value)| value.trim().parse().unwrap());
+                if buf.len() >= end + 4 + len {
+                    return (head, buf[end + 4..end + 4 + len].to_vec());
+                }
+            }
+            let n = socket.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed mid-request");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Engine stand-in that takes every order, then drops the connection
+    /// without answering. Lookups by client order id find the orders taken.
+    async fn spawn_dropping_engine() -> (String, Received, OrderId) {
+        let received = Received::default();
+        let order_id = OrderId::new();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let taken = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (head, body) = read_request(&mut socket).await;
+                let path = head.split(' ').nth(1).unwrap_or_default().to_string();
+                if head.starts_with("POST /internal/orders ") {
+                    taken.lock().unwrap().push((String::new(), serde_json::from_slice(&body).unwrap()));
+                    continue;
+                }
+                let client_order_id = path.rsplit('/').next().unwrap_or_default();
+                let found = {
+                    let taken = taken.lock().unwrap();
+                    taken.iter().any(|(_, order)| order["client_order_id"] == client_order_id)
+                };
+                let response = match found {
+                    true => {
+                        let ack = json!({ "order_id": order_id, "status": "NEW" }).to_string();
+                        format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                                 content-length: {}\r\nconnection: close\r\n\r\n{}", ack.len(), ack)
+                    }
+                    false => "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n".into(),
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, received, order_id)
+    }
+
+    #[tokio::test]
+    async fn test_order_taken_before_the_connection_dropped_is_reconciled_not_resent() {
+        let (mut state, _) = gateway().await;
+        let (url, received, order_id) = spawn_dropping_engine().await;
+        state.engine = MatchingEngineClient::new(InternalClient::default(), url);
+        let account_id = AccountId::new();
+
+        let (status, _, body) = place(&state, account_id, &valid(account_id)).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body["error"], "OUTCOME_UNKNOWN");
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // The retry finds the order by its client order id instead of resending
+        for _ in 0..2 {
+            let (status, _, body) = place(&state, account_id, &valid(account_id)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["order_id"], order_id.to_string());
+        }
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // An order the engine turns out not to hold is sent again
+        let mut other = valid(account_id);
+        other["client_order_id"] = json!("grid-2");
+        let (status, _, _) = place(&state, account_id, &other).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        received.lock().unwrap().clear();
+        let (status, _, _) = place(&state, account_id, &other).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    fn batch_request(account_id: AccountId, body: &Value) -> Request<Body> {
+        Request::post("/v1/orders/batch")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", test_token(account_id)))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn place_item(account_id: AccountId) -> Value {
+        let mut item = valid(account_id);
+        item["action"] = json!("place");
+        item
+    }
+
+    fn statuses(items: &Value) -> Vec<&str> {
+        items.as_array().unwrap().iter().map(|item| item["status"].as_str().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_batch_reports_each_item_and_runs_the_valid_ones() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let resting = OrderId::new();
+        let mut off_tick = place_item(account_id);
+        off_tick["price"] = json!("100.25");
+        off_tick["client_order_id"] = json!("grid-2");
+        let body = json!({ "items": [
+            place_item(account_id),
+            off_tick,
+            { "action": "cancel", "order_id": Uuid::nil().to_string() },
+            { "action": "AMEND", "order_id": resting.to_string(), "symbol": "BTC/USDT", "price": "101" },
+            { "action": "amend", "order_id": resting.to_string(), "symbol": "BTC/USDT" },
+            { "action": "cancel", "order_id": resting.to_string() },
+        ]});
+
+        let (status, headers, response) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["atomic"], false);
+        let items = &response["items"];
+        assert_eq!(statuses(items), ["accepted", "failed", "failed", "accepted", "failed", "accepted"]);
+        assert_eq!(items[0]["client_order_id"], "grid-1");
+        assert!(items[0]["order_id"].is_string());
+        assert_eq!(items[1]["client_order_id"], "grid-2");
+        assert_eq!(items[1]["error"]["error"], "VALIDATION_FAILED");
+        assert_eq!(items[1]["error"]["details"][0]["code"], "PRICE_NOT_ON_TICK");
+        assert_eq!(items[2]["error"]["error"], "ORDER_NOT_FOUND");
+        assert_eq!(items[3]["order_id"], resting.to_string());
+        assert_eq!(items[4]["error"]["details"][0]["code"], "ONE_OF_REQUIRED");
+        assert_eq!(items[5]["index"], 5);
+
+        // Valid items reached the engine in order, under one correlation id
+        let received = received.lock().unwrap();
+        let forwarded: Vec<&Value> = received.iter().map(|(_, body)| body).collect();
+        assert_eq!(forwarded.len(), 4);
+        assert_eq!(forwarded[0]["client_order_id"], "grid-1");
+        assert_eq!(forwarded[1]["cancel"], Uuid::nil().to_string());
+        assert_eq!(forwarded[2]["amend"], resting.to_string());
+        assert_eq!(forwarded[2]["account_id"], account_id.to_string());
+        assert_eq!(forwarded[3]["cancel"], resting.to_string());
+        assert!(received.iter().all(|(id, _)| *id == headers[CORRELATION_ID_HEADER]));
+    }
+
+    #[tokio::test]
+    async fn test_atomic_batch_is_rejected_whole_before_reaching_engine() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        let body = json!({ "atomic": true, "items": [
+            place_item(account_id),
+            { "action": "cancel", "order_id": "not-a-uuid" },
+            { "action": "close" },
+            place_item(AccountId::new()),
+        ]});
+
+        let (status, _, response) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(response["error"], "BATCH_REJECTED");
+        assert_eq!(response["message"], "Atomic batch rejected: 3 invalid item(s)");
+        let items = &response["details"];
+        assert_eq!(statuses(items), ["skipped", "failed", "failed", "failed"]);
+        assert_eq!(items[1]["error"]["details"][0]["field"], "order_id");
+        assert_eq!(items[2]["error"]["details"][0]["code"], "UNKNOWN_VARIANT");
+        assert_eq!(items[3]["error"]["error"], "UNAUTHORIZED");
+        assert!(received.lock().unwrap().is_empty());
+
+        // An engine rejection stops an atomic batch; later items are skipped
+        let mut broke = place_item(account_id);
+        broke["quantity"] = json!("13");
+        let body = json!({ "atomic": true, "items": [broke, place_item(account_id)] });
+        let (status, _, response) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(statuses(&response["items"]), ["failed", "skipped"]);
+        assert_eq!(response["items"][0]["error"]["error"], "INSUFFICIENT_BALANCE");
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_size_is_bounded() {
+        let (state, received) = gateway().await;
+        let account_id = AccountId::new();
+        for count in [0, MAX_BATCH_ITEMS + 1] {
+            let body = json!({ "items": vec![place_item(account_id); count] });
+            let (status, _, response) = send(&state, batch_request(account_id, &body)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(response["message"], format!("A batch holds 1 to 20 items, got {count}"));
+        }
+        let body = json!({ "items": vec![place_item(account_id); MAX_BATCH_ITEMS] });
+        let (status, _, response) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["items"].as_array().unwrap().len(), MAX_BATCH_ITEMS);
+        assert_eq!(received.lock().unwrap().len(), MAX_BATCH_ITEMS);
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_rate_limited_by_item_weight() {
+        let (mut state, received) = gateway().await;
+        state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            standard: BucketLimits::new(100, 0.0),
+            ..RateLimitConfig::default()
+        }));
+        // 5 per placement and 2 per cancel, leaving 3 of the 100 tokens
+        let account_id = AccountId::new();
+        let mut items = vec![place_item(account_id); 19];
+        items.push(json!({ "action": "cancel", "order_id": OrderId::new().to_string() }));
+        let (status, _, _) = send(&state, batch_request(account_id, &json!({ "items": items }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let body = json!({ "items": [place_item(account_id)] });
+        let (status, _, response) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response["message"], "Batch of 1 item(s) costs 5 token(s)");
+        let body = json!({ "items": [{ "action": "cancel", "order_id": OrderId::new().to_string() }] });
+        let (status, _, _) = send(&state, batch_request(account_id, &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.lock().unwrap().len(), 21);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::auth::{verify_request, AuthenticatedUser};
+use crate::engine_client::{correlation_id, forward_ids, CORRELATION_ID_HEADER};
+use crate::error::AppError;
+use crate::idempotency::{fingerprint, idempotency_key, Claim};
+use crate::models::{FieldError, FieldErrorKind, WithdrawalRequest, WithdrawalResponse};
+use crate::state::{AppState, CUSTODY_SERVICE};
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::HeaderMap,
+    response::AppendHeaders,
+    Json,
+};
+
+pub async fn submit_withdrawal(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    payload: Result<Json<WithdrawalRequest>, JsonRejection>,
+) -> Result<(AppendHeaders<[(&'static str, String); 1]>, Json<WithdrawalResponse>), AppError> {
+    // 1. Validate payload
+    let Json(payload) = payload.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let mut errors = Vec::new();
+    if payload.asset.trim().is_empty() {
+        errors.push(FieldError::new("asset", FieldErrorKind::Empty));
+    }
+    if payload.amount.is_sign_negative() || payload.amount.is_zero() {
+        errors.push(FieldError::new("amount", FieldErrorKind::NotPositive));
+    }
+    if payload.destination.trim().is_empty() {
+        errors.push(FieldError::new("destination", FieldErrorKind::Empty));
+    }
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+
+    // 2. Identity and signature validation
+    verify_request(&user, |timestamp, nonce| payload.signable().into_signable(timestamp, nonce))?;
+    if user.account_id != payload.account_id {
+        return Err(AppError::Unauthorized("Cannot withdraw from another account".into()));
+    }
+
+    // 3. Forward, at most once per idempotency key; only accepted
+    //    withdrawals are replayed. A failure before the request was sent, or
+    //    a refusal, releases the key; a lost answer leaves it unknown.
+    let correlation_id = correlation_id(&headers);
+    let pending = match idempotency_key(&headers)? {
+        Some(key) => {
+            let key = format!("{}:{}", user.account_id, key);
+            match state.withdrawal_idempotency.claim(key, fingerprint(&payload)).await? {
+                Claim::Replay(response) => {
+                    return Ok((AppendHeaders([(CORRELATION_ID_HEADER, correlation_id)]), Json(response)));
+                }
+                Claim::Execute(pending) => Some(pending),
+                // Custody assigns the withdrawal id, so only the client can
+                // tell whether the earlier attempt went through
+                Claim::Reconcile(_) => {
+                    return Err(AppError::OutcomeUnknown(
+                        "An earlier withdrawal with this Idempotency-Key may have been accepted; \
+                         check its status before submitting under a new key"
+                            .into(),
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+
+    let request = state
+        .internal
+        .post(CUSTODY_SERVICE, format!("{}/internal/withdrawals", state.internal_services_url));
+    let sent = forward_ids(request, &correlation_id).json(&payload).send().await;
+    let res = match sent {
+        Ok(res) => res,
+        Err(e) => {
+            let err = e.unanswered("Custody service");
+            // Dropping `pending` unreleased leaves a lost answer unknown
+            if !matches!(err, AppError::OutcomeUnknown(_))
+                && let Some(pending) = pending
+            {
+                pending.release();
+            }
+            return Err(err);
+        }
+    };
+
+    if !res.status().is_success() {
+        if let Some(pending) = pending {
+            pending.release();
+        }
+        return Err(AppError::BadRequest("Failed to submit withdrawal".into()));
+    }
+
+    let response = res.json::<WithdrawalResponse>().await.map_err(|_| {
+        AppError::OutcomeUnknown("Custody service sent an unreadable withdrawal acknowledgement".into())
+    })?;
+    if let Some(pending) = pending {
+        pending.complete(response.clone());
+    }
+
+    Ok((AppendHeaders([(CORRELATION_ID_HEADER, correlation_id)]), Json(response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::test_token;
+    use crate::idempotency::IDEMPOTENCY_KEY_HEADER;
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{header::CONTENT_TYPE, Request, StatusCode},
+        response::Response,
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+    use types::ids::AccountId;
+
+    /// Custody stand-in numbering every withdrawal it receives.
+    async fn spawn_custody() -> (String, Arc<AtomicU64>) {
+        let count = Arc::new(AtomicU64::new(0));
+        let app = Router::new()
+            .route(
+                "/internal/withdrawals",
+                post(|State(count): State<Arc<AtomicU64>>| async move {
+                    let id = count.fetch_add(1, Ordering::SeqCst) + 1;
+                    Json(json!({ "withdrawal_id": id, "status": "PENDING" }))
+                }),
+            )
+            .with_state(count.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, count)
+    }
+
+    async fn withdraw(
+        state: &AppState,
+        account_id: AccountId,
+        key: Option<&str>,
+        body: &Value,
+    ) -> (StatusCode, Value) {
+        let token = test_token(account_id);
+        let mut request = Request::post("/v1/withdrawals")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", token));
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn withdrawal(account_id: AccountId) -> Value {
+        json!({
+            "account_id": account_id.to_string(),
+            "asset": "USDT",
+            "amount": "250.5",
+            "destination": "0x00000000000000000000000000000000000000aa"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_retries_are_idempotent() {
+        let (url, count) = spawn_custody().await;
+        let state = AppState::new(url);
+        let account_id = AccountId::new();
+        let body = withdrawal(account_id);
+
+        let (status, first) = withdraw(&state, account_id, Some("w-1"), &body).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, second) = withdraw(&state, account_id, Some("w-1"), &body).await;
+        assert_eq!(second, first);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let mut changed = body.clone();
+        changed["amount"] = "251".into();
+        let (status, response) = withdraw(&state, account_id, Some("w-1"), &changed).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(response["error"], "CONFLICT");
+
+        // Without a key every submission is forwarded
+        let (_, third) = withdraw(&state, account_id, None, &body).await;
+        assert_eq!(third["withdrawal_id"], 2);
+
+        changed["amount"] = "0".into();
+        changed["asset"] = " ".into();
+        let (status, response) = withdraw(&state, account_id, Some("w-2"), &changed).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(response["details"][0]["field"], "asset");
+        assert_eq!(response["details"][1]["code"], "NOT_POSITIVE");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_taken_before_the_connection_dropped_is_not_resent() {
+        // Custody takes the withdrawal, then the connection drops mid-answer
+        let count = Arc::new(AtomicU64::new(0));
+        let app = Router::new()
+            .route(
+                "/internal/withdrawals",
+                post(|State(count): State<Arc<AtomicU64>>| async move {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    let gone = std::io::Error::other("connection lost");
+                    let gone = futures::stream::once(async { Err::<String, _>(gone) });
+                    Response::builder()
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(Body::from_stream(gone))
+                        .unwrap()
+                }),
+            )
+            .with_state(count.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let state = AppState::new(url);
+        let account_id = AccountId::new();
+
+        for _ in 0..2 {
+            let (status, response) = withdraw(&state, account_id, Some("w-1"), &withdrawal(account_id)).await;
+            assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+            assert_eq!(response["error"], "OUTCOME_UNKNOWN");
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_custody_releases_key() {
+        let state = AppState::new("http://127.0.0.1:1".into());
+        let account_id = AccountId::new();
+        let (status, _) = withdraw(&state, account_id, Some("w-1"), &withdrawal(account_id)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (url, count) = spawn_custody().await;
+        let state = AppState {
+            internal_services_url: url,
+            ..state
+        };
+        let (status, _) = withdraw(&state, account_id, Some("w-1"), &withdrawal(account_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,41 +1,75 @@
 use crate::auth::AuthenticatedUser;
+use crate::cancel_on_disconnect::Trigger;
 use crate::error::AppError;
+use crate::models::UserStreamQuery;
+use crate::rate_limit::Identity;
 use crate::state::AppState;
+use crate::user_events::{Notice, UserEvent};
 use axum::{
-    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, State},
+    extract::{
+        rejection::QueryRejection,
+        ws::{close_code, CloseFrame, Message, Utf8Bytes, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
     response::Response,
 };
 use futures::stream::StreamExt;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Instant;
+use types::ids::AccountId;
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Response, AppError> {
-    // 1. Rate limiting
-    state
-        .rate_limiter
-        .check_rate_limit(&format!("{}:ws_connections", user.account_id), 10, 10.0)?;
-
+    // Connections are rate limited by the router middleware
     Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, user)))
 }
 
+/// Close frame sent to sessions still open at shutdown: 1012 (service
+/// restart) tells clients to reconnect.
+fn restart_frame() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: close_code::RESTART,
+        reason: Utf8Bytes::from_static("server shutting down, reconnect"),
+    }))
+}
+
 async fn handle_socket(mut socket: WebSocket, state: AppState, user: AuthenticatedUser) {
+    let _connection = state.metrics.ws_connected("market");
+    let session = state.shutdown.register("ws market");
     // Mock WebSocket loop
     if socket.send(Message::Text(axum::extract::ws::Utf8Bytes::from("Connected"))).await.is_err() {
         return;
     }
 
-    while let Some(msg) = socket.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = socket.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = session.requested() => {
+                let _ = socket.send(restart_frame()).await;
+                break;
+            }
+        };
         if let Ok(msg) = msg {
             match msg {
-                Message::Text(text) => {
-                    // E.g., subscription requests
-                    if text == "subscribe:market_data" {
-                        // Rate Limit API
-                        let _ = state.rate_limiter.check_rate_limit(&format!("{}:ws_subscriptions", user.account_id), 50, 50.0);
-                        let _ = socket.send(Message::Text(axum::extract::ws::Utf8Bytes::from("Subscribed"))).await;
-                    }
+                // E.g., subscription requests
+                Message::Text(text) if text == "subscribe:market_data" => {
+                    // Subscriptions draw on the account's bucket like requests do
+                    let identity = Identity::Account(user.account_id);
+                    let reply = if state.rate_limiter.check(Some(&identity), None, 1).allowed {
+                        "Subscribed"
+                    } else {
+                        state.metrics.rate_limit_rejections.inc(&["/v1/ws"]);
+                        "Rate limit exceeded"
+                    };
+                    let _ = socket.send(Message::Text(axum::extract::ws::Utf8Bytes::from(reply))).await;
                 }
                 Message::Close(_) => {
                     break;
@@ -47,3 +81,361 @@ async fn handle_socket(mut socket: WebSocket, state: AppState, user: Authenticat
         }
     }
 }
+
+/// Private channel streaming the caller's order, fill, position and balance
+/// updates.
+///
+/// Every frame carries a per-connection `seq` starting at 1 with the
+/// `subscribed` notice. A connection that falls behind gets a `lagged`
+/// notice with the number of skipped events instead of blocking the feed.
+///
+/// With `?cancel_on_disconnect=true`, or on an API key that has it set, the
+/// connection protects the account's quotes: any frame from the client is a
+/// heartbeat, and once the account's last protected connection closes or
+/// goes quiet for the grace period its resting orders are canceled.
+pub async fn user_ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    query: Result<Query<UserStreamQuery>, QueryRejection>,
+) -> Result<Response, AppError> {
+    let Query(query) = query.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let key_protected = user
+        .api_key
+        .as_deref()
+        .and_then(|key_id| state.api_keys.get(key_id))
+        .is_some_and(|key| key.cancel_on_disconnect);
+    let protected = query.cancel_on_disconnect || key_protected;
+    let events = state.user_events.subscribe(user.account_id);
+    Ok(ws.on_upgrade(move |socket| stream_user_events(socket, state, user.account_id, events, protected)))
+}
+
+async fn stream_user_events(
+    mut socket: WebSocket,
+    state: AppState,
+    account_id: AccountId,
+    mut events: broadcast::Receiver<Arc<UserEvent>>,
+    protected: bool,
+) {
+    let _connection = state.metrics.ws_connected("user");
+    let session = state.shutdown.register("ws user");
+    if protected {
+        state.cancel_on_disconnect.connect(account_id);
+    }
+    let grace = state.cancel_on_disconnect.grace();
+    let heartbeat = tokio::time::sleep(grace);
+    tokio::pin!(heartbeat);
+    let mut trigger = Trigger::Disconnected;
+
+    let mut seq = 1;
+    let subscribed = Notice::Subscribed {
+        account_id,
+        cancel_on_disconnect: protected,
+    };
+    if socket.send(frame(seq, &subscribed)).await.is_ok() {
+        loop {
+            let message = tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => frame(seq + 1, &*event),
+                    Err(RecvError::Lagged(dropped)) => frame(seq + 1, &Notice::Lagged { dropped }),
+                    Err(RecvError::Closed) => break,
+                },
+                incoming = socket.next() => match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    // The channel is server-to-client only; inbound frames
+                    // just keep the connection alive
+                    Some(Ok(_)) => {
+                        heartbeat.as_mut().reset(Instant::now() + grace);
+                        continue;
+                    }
+                },
+                _ = &mut heartbeat, if protected => {
+                    trigger = Trigger::HeartbeatTimeout;
+                    break;
+                }
+                _ = session.requested() => {
+                    let _ = socket.send(restart_frame()).await;
+                    break;
+                }
+            };
+            seq += 1;
+            if socket.send(message).await.is_err() {
+                break;
+            }
+        }
+    }
+    drop(events);
+    state.user_events.release(account_id);
+    if protected {
+        state.cancel_on_disconnect.disconnect(account_id, trigger);
+    }
+}
+
+/// JSON text frame: the payload's fields plus `seq`.
+fn frame(seq: u64, payload: &impl Serialize) -> Message {
+    let mut value = serde_json::to_value(payload).expect("frame serializes to JSON");
+    value["seq"] = seq.into();
+    Message::Text(Utf8Bytes::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::test_token;
+    use crate::cancel_on_disconnect::CancelOnDisconnect;
+    use crate::engine_client::CancelAllAck;
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use crate::user_events::{UserEvent, UserEventHub};
+    use futures::{SinkExt, StreamExt};
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest, Message};
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+    use types::ids::{AccountId, OrderId};
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn serve(state: AppState) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_router(state).into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    async fn connect(addr: SocketAddr, account_id: Option<AccountId>) -> Result<Client, tungstenite::Error> {
+        connect_to(addr, "/v1/ws/user", account_id).await
+    }
+
+    async fn connect_to(
+        addr: SocketAddr,
+        path: &str,
+        account_id: Option<AccountId>,
+    ) -> Result<Client, tungstenite::Error> {
+        let mut request = format!("ws://{}{}", addr, path).into_client_request().unwrap();
+        if let Some(account_id) = account_id {
+            let token = test_token(account_id);
+            request
+                .headers_mut()
+                .insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+        }
+        connect_async(request).await.map(|(client, _)| client)
+    }
+
+    async fn next_frame(client: &mut Client) -> Value {
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("unexpected frame {other:?}"),
+        }
+    }
+
+    fn filled(order_id: OrderId, remaining: i64) -> UserEvent {
+        UserEvent::OrderFilled {
+            order_id,
+            trade_id: types::ids::TradeId::new(),
+            price: Decimal::from(100),
+            quantity: Decimal::ONE,
+            remaining: Decimal::from(remaining),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_upgrade_is_rejected() {
+        let addr = serve(AppState::new("http://127.0.0.1:1".into())).await;
+        match connect(addr, None).await {
+            Err(tungstenite::Error::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("expected 401, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_connection_of_the_account_gets_its_events() {
+        let state = AppState::new("http://127.0.0.1:1".into());
+        let addr = serve(state.clone()).await;
+        let (account_id, other) = (AccountId::new(), AccountId::new());
+        let mut first = connect(addr, Some(account_id)).await.unwrap();
+        let mut second = connect(addr, Some(account_id)).await.unwrap();
+        let mut stranger = connect(addr, Some(other)).await.unwrap();
+        for client in [&mut first, &mut second, &mut stranger] {
+            let hello = next_frame(client).await;
+            assert_eq!((&hello["type"], &hello["seq"]), (&json!("subscribed"), &json!(1)));
+        }
+        assert_eq!(state.user_events.connections(account_id), 2);
+        assert_eq!(state.metrics.ws_connections.get(&["user"]), 3);
+
+        let order_id = OrderId::new();
+        for remaining in [2, 1] {
+            assert_eq!(state.user_events.publish(account_id, filled(order_id, remaining)), 2);
+        }
+        for client in [&mut first, &mut second] {
+            for (seq, remaining) in [(2, "2"), (3, "1")] {
+                let frame = next_frame(client).await;
+                assert_eq!(frame["type"], "order_filled");
+                assert_eq!(frame["order_id"], json!(order_id));
+                assert_eq!(frame["remaining"], remaining);
+                assert_eq!(frame["seq"], seq);
+            }
+        }
+
+        // The other account's connection saw nothing; closing releases the channel
+        let silence = tokio::time::timeout(std::time::Duration::from_millis(50), stranger.next());
+        assert!(silence.await.is_err());
+        first.close(None).await.unwrap();
+        second.close(None).await.unwrap();
+        for _ in 0..100 {
+            let open = state.metrics.ws_connections.get(&["user"]);
+            if state.user_events.connections(account_id) == 0 && open == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(state.user_events.connections(account_id), 0);
+        assert_eq!(state.metrics.ws_connections.get(&["user"]), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_closes_sessions_with_reconnect_hint() {
+        let state = AppState::new("http://127.0.0.1:1".into());
+        let addr = serve(state.clone()).await;
+        let account_id = AccountId::new();
+        let mut user = connect(addr, Some(account_id)).await.unwrap();
+        let mut market = connect_to(addr, "/v1/ws", Some(account_id)).await.unwrap();
+        assert_eq!(next_frame(&mut user).await["type"], "subscribed");
+        assert_eq!(market.next().await.unwrap().unwrap(), Message::text("Connected"));
+        assert_eq!(state.shutdown.active().len(), 2);
+
+        let drain = tokio::spawn({
+            let state = state.clone();
+            async move { state.shutdown.drain(Duration::from_secs(5)).await }
+        });
+        for client in [&mut user, &mut market] {
+            match client.next().await.unwrap().unwrap() {
+                Message::Close(Some(frame)) => {
+                    assert_eq!(u16::from(frame.code), 1012);
+                    assert!(frame.reason.contains("reconnect"));
+                }
+                other => panic!("expected a close frame, got {other:?}"),
+            }
+        }
+        let report = drain.await.unwrap();
+        assert!(report.drained, "{}", report);
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_gets_lagged_notice() {
+        let mut state = AppState::new("http://127.0.0.1:1".into());
+        state.user_events = Arc::new(UserEventHub::new(2));
+        let addr = serve(state.clone()).await;
+        let account_id = AccountId::new();
+        let mut client = connect(addr, Some(account_id)).await.unwrap();
+        assert_eq!(next_frame(&mut client).await["type"], "subscribed");
+
+        // Published without yielding, so the connection cannot keep up
+        let order_id = OrderId::new();
+        for remaining in (0..5).rev() {
+            state.user_events.publish(account_id, filled(order_id, remaining));
+        }
+
+        let lagged = next_frame(&mut client).await;
+        assert_eq!(lagged, json!({"type": "lagged", "dropped": 3, "seq": 2}));
+        for (seq, remaining) in [(3, "1"), (4, "0")] {
+            let frame = next_frame(&mut client).await;
+            assert_eq!((&frame["seq"], &frame["remaining"]), (&json!(seq), &json!(remaining)));
+        }
+
+        // Back in step afterwards
+        state.user_events.publish(account_id, filled(order_id, 0));
+        assert_eq!(next_frame(&mut client).await["seq"], 5);
+    }
+
+    /// Engine stand-in counting cancel-all calls.
+    async fn cancel_all_engine() -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = axum::Router::new().route(
+            "/internal/accounts/{id}/cancel-all",
+            axum::routing::post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                axum::Json(CancelAllAck { canceled: vec![] })
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, calls)
+    }
+
+    async fn wait_for(calls: &AtomicUsize, expected: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while calls.load(Ordering::SeqCst) < expected {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_protected_session_cancels_on_timeout_and_disconnect() {
+        let (url, cancels) = cancel_all_engine().await;
+        let mut state = AppState::new(url);
+        let grace = Duration::from_millis(300);
+        state.cancel_on_disconnect = Arc::new(CancelOnDisconnect::new(
+            state.engine.clone(),
+            state.user_events.clone(),
+            grace,
+        ));
+        let addr = serve(state.clone()).await;
+        let account_id = AccountId::new();
+        let path = "/v1/ws/user?cancel_on_disconnect=true";
+        let heartbeat = || Message::Text(r#"{"type":"heartbeat"}"#.into());
+
+        // Unprotected connections never trigger
+        let mut plain = connect(addr, Some(account_id)).await.unwrap();
+        assert_eq!(next_frame(&mut plain).await["cancel_on_disconnect"], false);
+
+        // Heartbeats keep a protected connection alive past the grace period
+        let mut client = connect_to(addr, path, Some(account_id)).await.unwrap();
+        assert_eq!(next_frame(&mut client).await["cancel_on_disconnect"], true);
+        for _ in 0..4 {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            client.send(heartbeat()).await.unwrap();
+        }
+
+        // Reconnecting within the grace period disarms the trigger
+        client.close(None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut client = connect_to(addr, path, Some(account_id)).await.unwrap();
+        next_frame(&mut client).await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        client.send(heartbeat()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(cancels.load(Ordering::SeqCst), 0);
+
+        // Going quiet: the gateway drops the connection and cancels at once
+        let quiet = tokio::time::Instant::now();
+        let closed = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(Ok(_)) = client.next().await {}
+        });
+        closed.await.unwrap();
+        wait_for(&cancels, 1).await;
+        assert!(quiet.elapsed() >= Duration::from_millis(100));
+        let canceled = next_frame(&mut plain).await;
+        assert_eq!(canceled["type"], "session_orders_canceled");
+        assert_eq!(canceled["trigger"], "heartbeat_timeout");
+
+        // Closing without reconnecting cancels after the grace period
+        let mut client = connect_to(addr, path, Some(account_id)).await.unwrap();
+        next_frame(&mut client).await;
+        client.close(None).await.unwrap();
+        let closed = tokio::time::Instant::now();
+        wait_for(&cancels, 2).await;
+        assert!(closed.elapsed() >= grace - Duration::from_millis(50));
+        assert_eq!(next_frame(&mut plain).await["trigger"], "disconnected");
+    }
+}
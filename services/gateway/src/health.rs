@@ -0,0 +1,270 @@
+use futures::future::join_all;
+use reqwest::Client;
+use serde::Serialize;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Gateway package version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Commit the binary was built from, if `GIT_SHA` was set at build time.
+pub const GIT_SHA: &str = match option_env!("GIT_SHA") {
+    Some(sha) => sha,
+    None => "unknown",
+};
+
+/// How often and how strictly downstream services are checked.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthCheckConfig {
+    pub interval: Duration,
+    /// A check slower than this fails
+    pub timeout: Duration,
+    /// Consecutive failures before a dependency counts as down
+    pub failure_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(1),
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// A downstream service checked with `GET {base_url}/health`.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: &'static str,
+    pub base_url: String,
+}
+
+impl Dependency {
+    pub fn new(name: &'static str, base_url: impl Into<String>) -> Self {
+        Self {
+            name,
+            base_url: base_url.into(),
+        }
+    }
+}
+
+/// Latest check results for one dependency, as reported by `GET /status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub up: bool,
+    pub consecutive_failures: u32,
+    /// Duration of the latest check
+    pub latency_ms: Option<u64>,
+    /// Unix nanos of the latest passing check
+    pub last_success_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Cached health of the downstream services.
+///
+/// Checks run on a background interval ([`run_checks`]) so probes never
+/// reach the services themselves. A dependency is up once a check has
+/// passed and until `failure_threshold` consecutive checks fail.
+pub struct HealthRegistry {
+    dependencies: Vec<Dependency>,
+    statuses: RwLock<Vec<DependencyStatus>>,
+    config: HealthCheckConfig,
+}
+
+impl HealthRegistry {
+    pub fn new(dependencies: Vec<Dependency>, config: HealthCheckConfig) -> Self {
+        let statuses = dependencies
+            .iter()
+            .map(|dependency| DependencyStatus {
+                name: dependency.name,
+                up: false,
+                consecutive_failures: 0,
+                latency_ms: None,
+                last_success_at: None,
+                last_error: None,
+            })
+            .collect();
+        Self {
+            dependencies,
+            statuses: RwLock::new(statuses),
+            config,
+        }
+    }
+
+    /// Check every dependency once, concurrently, and record the results.
+    pub async fn check_all(&self, client: &Client) {
+        let results = join_all(self.dependencies.iter().map(|dependency| self.check(client, dependency))).await;
+        let mut statuses = self.statuses.write().unwrap();
+        for (status, (latency, result)) in statuses.iter_mut().zip(results) {
+            status.latency_ms = Some(latency.as_millis() as u64);
+            match result {
+                Ok(()) => {
+                    status.up = true;
+                    status.consecutive_failures = 0;
+                    status.last_success_at = Some(unix_nanos());
+                    status.last_error = None;
+                }
+                Err(error) => {
+                    status.consecutive_failures += 1;
+                    if status.consecutive_failures >= self.config.failure_threshold {
+                        status.up = false;
+                    }
+                    status.last_error = Some(error);
+                }
+            }
+        }
+    }
+
+    async fn check(&self, client: &Client, dependency: &Dependency) -> (Duration, Result<(), String>) {
+        let started = Instant::now();
+        let result = client
+            .get(format!("{}/health", dependency.base_url))
+            .timeout(self.config.timeout)
+            .send()
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|res| match res.status() {
+                status if status.is_success() => Ok(()),
+                status => Err(format!("returned {}", status)),
+            });
+        (started.elapsed(), result)
+    }
+
+    /// Whether every dependency is up.
+    pub fn is_ready(&self) -> bool {
+        self.statuses.read().unwrap().iter().all(|status| status.up)
+    }
+
+    pub fn statuses(&self) -> Vec<DependencyStatus> {
+        self.statuses.read().unwrap().clone()
+    }
+}
+
+/// Check the registry's dependencies every `interval`, forever.
+pub async fn run_checks(registry: std::sync::Arc<HealthRegistry>, client: Client) {
+    let mut interval = tokio::time::interval(registry.config.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        registry.check_all(&client).await;
+    }
+}
+
+fn unix_nanos() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::get, Router};
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    const UP: u8 = 0;
+    const DOWN: u8 = 1;
+    const SLOW: u8 = 2;
+
+    /// Downstream stand-in whose `/health` answers according to `mode`.
+    async fn spawn_downstream() -> (String, Arc<AtomicU8>) {
+        let mode = Arc::new(AtomicU8::new(UP));
+        let app = Router::new()
+            .route(
+                "/health",
+                get(|State(mode): State<Arc<AtomicU8>>| async move {
+                    match mode.load(Ordering::SeqCst) {
+                        UP => StatusCode::OK,
+                        DOWN => StatusCode::SERVICE_UNAVAILABLE,
+                        _ => {
+                            tokio::time::sleep(Duration::from_millis(500)).await;
+                            StatusCode::OK
+                        }
+                    }
+                }),
+            )
+            .with_state(mode.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, mode)
+    }
+
+    fn config() -> HealthCheckConfig {
+        HealthCheckConfig {
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(100),
+            failure_threshold: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dependency_goes_down_after_consecutive_failures_and_recovers() {
+        let (url, mode) = spawn_downstream().await;
+        let registry = HealthRegistry::new(vec![Dependency::new("matching-engine", url)], config());
+        let client = Client::new();
+        assert!(!registry.is_ready(), "not ready before the first check");
+
+        registry.check_all(&client).await;
+        assert!(registry.is_ready());
+        let passed_at = registry.statuses()[0].last_success_at.unwrap();
+
+        mode.store(DOWN, Ordering::SeqCst);
+        for failures in 1..=2 {
+            registry.check_all(&client).await;
+            assert!(registry.is_ready(), "tolerates {failures} failure(s)");
+        }
+        registry.check_all(&client).await;
+        assert!(!registry.is_ready());
+        let status = &registry.statuses()[0];
+        assert_eq!(status.consecutive_failures, 3);
+        assert_eq!(status.last_error.as_deref(), Some("returned 503 Service Unavailable"));
+        assert_eq!(status.last_success_at, Some(passed_at));
+
+        mode.store(UP, Ordering::SeqCst);
+        registry.check_all(&client).await;
+        assert!(registry.is_ready());
+        assert_eq!(registry.statuses()[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_and_unreachable_dependencies_fail() {
+        let (url, mode) = spawn_downstream().await;
+        mode.store(SLOW, Ordering::SeqCst);
+        let config = HealthCheckConfig {
+            failure_threshold: 1,
+            ..config()
+        };
+        let registry = HealthRegistry::new(
+            vec![Dependency::new("risk-engine", url), Dependency::new("market-data", "http://127.0.0.1:1")],
+            config,
+        );
+        registry.check_all(&Client::new()).await;
+        let statuses = registry.statuses();
+        assert!(statuses.iter().all(|status| !status.up && status.last_error.is_some()));
+        assert!(statuses[0].latency_ms.unwrap() < 500);
+    }
+
+    #[tokio::test]
+    async fn test_background_checks_update_the_cache() {
+        let (url, mode) = spawn_downstream().await;
+        let registry = Arc::new(HealthRegistry::new(vec![Dependency::new("market-data", url)], config()));
+        let checks = tokio::spawn(run_checks(registry.clone(), Client::new()));
+
+        let wait_for = |ready: bool| {
+            let registry = registry.clone();
+            async move {
+                while registry.is_ready() != ready {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), wait_for(true)).await.unwrap();
+        mode.store(DOWN, Ordering::SeqCst);
+        tokio::time::timeout(Duration::from_secs(5), wait_for(false)).await.unwrap();
+        checks.abort();
+    }
+}
@@ -0,0 +1,398 @@
+use crate::error::AppError;
+use axum::http::HeaderMap;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Header carrying the client's idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Longest accepted idempotency key.
+const MAX_KEY_LEN: usize = 255;
+/// Completed submissions remembered by default.
+pub const DEFAULT_CAPACITY: usize = 100_000;
+/// How long a completed submission is replayed by default.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Outcome of claiming an idempotency key.
+pub enum Claim<T: Clone> {
+    /// First use of the key: run the submission and record the result.
+    Execute(Pending<T>),
+    /// The key already completed with this result.
+    Replay(T),
+    /// An earlier attempt may have reached the backend without its result
+    /// being recorded: find out what became of it before submitting again.
+    Reconcile(Pending<T>),
+}
+
+/// Remembers submission results per key so retries are not forwarded twice.
+///
+/// A key is tied to the fingerprint of its first request. While that request
+/// is in flight, duplicates wait for its result instead of racing it to the
+/// backend. An attempt that ends without a result leaves the key
+/// indeterminate, and the next claim must reconcile with the backend rather
+/// than submit blindly. Results expire `ttl` after completion, and at most
+/// `capacity` keys are held.
+pub struct IdempotencyStore<T> {
+    inner: Mutex<Inner<T>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+struct Inner<T> {
+    entries: HashMap<String, Entry<T>>,
+    next_generation: u64,
+}
+
+struct Entry<T> {
+    fingerprint: u64,
+    /// Distinguishes a re-claimed key from the entry a stale `Pending` owned
+    generation: u64,
+    slot: Slot<T>,
+}
+
+enum Slot<T> {
+    InFlight(watch::Sender<Option<T>>),
+    Done { value: T, expires_at: Instant },
+    /// An attempt gave up, possibly after the backend took the request
+    Unknown { expires_at: Instant },
+}
+
+impl<T> Entry<T> {
+    fn expires_at(&self) -> Option<Instant> {
+        match self.slot {
+            Slot::InFlight(_) => None,
+            Slot::Done { expires_at, .. } | Slot::Unknown { expires_at } => Some(expires_at),
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at().is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> IdempotencyStore<T> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                next_generation: 0,
+            }),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Claim `key` for a request with `fingerprint`.
+    ///
+    /// Reusing a key with a different fingerprint is a conflict. If the
+    /// holder of an in-flight key gives up without completing or releasing
+    /// it, a waiting duplicate takes the key over to reconcile.
+    pub async fn claim(self: &Arc<Self>, key: String, fingerprint: u64) -> Result<Claim<T>, AppError> {
+        loop {
+            let mut receiver = {
+                let mut inner = self.inner.lock().unwrap();
+                let now = Instant::now();
+                if inner.entries.get(&key).is_some_and(|e| e.is_expired(now)) {
+                    inner.entries.remove(&key);
+                }
+                match inner.entries.get(&key) {
+                    Some(entry) if entry.fingerprint != fingerprint => {
+                        return Err(AppError::Conflict(
+                            "Idempotency key was used with a different request".into(),
+                        ));
+                    }
+                    Some(Entry { slot: Slot::Done { value, .. }, .. }) => {
+                        return Ok(Claim::Replay(value.clone()));
+                    }
+                    Some(Entry { slot: Slot::InFlight(sender), .. }) => sender.subscribe(),
+                    Some(Entry { slot: Slot::Unknown { .. }, .. }) => {
+                        return Ok(Claim::Reconcile(self.hold(&mut inner, key, fingerprint)));
+                    }
+                    None => {
+                        self.make_room(&mut inner, now)?;
+                        return Ok(Claim::Execute(self.hold(&mut inner, key, fingerprint)));
+                    }
+                }
+            };
+            if let Ok(value) = receiver.wait_for(Option::is_some).await
+                && let Some(value) = value.clone()
+            {
+                return Ok(Claim::Replay(value));
+            }
+            // The holder was dropped without a result: try to take the key over
+        }
+    }
+
+    /// Mark `key` in flight under a new generation.
+    fn hold(self: &Arc<Self>, inner: &mut Inner<T>, key: String, fingerprint: u64) -> Pending<T> {
+        let generation = inner.next_generation;
+        inner.next_generation += 1;
+        let (sender, _) = watch::channel(None);
+        inner.entries.insert(
+            key.clone(),
+            Entry {
+                fingerprint,
+                generation,
+                slot: Slot::InFlight(sender),
+            },
+        );
+        Pending {
+            store: self.clone(),
+            key,
+            generation,
+            completed: false,
+        }
+    }
+
+    /// Drop expired results, then the oldest one, until a key fits.
+    fn make_room(&self, inner: &mut Inner<T>, now: Instant) -> Result<(), AppError> {
+        if inner.entries.len() < self.capacity {
+            return Ok(());
+        }
+        inner.entries.retain(|_, entry| !entry.is_expired(now));
+        while inner.entries.len() >= self.capacity {
+            let oldest = inner
+                .entries
+                .iter()
+                .filter_map(|(key, entry)| Some((entry.expires_at()?, key.clone())))
+                .min();
+            match oldest {
+                Some((_, key)) => inner.entries.remove(&key),
+                None => {
+                    return Err(AppError::ServiceUnavailable(
+                        "Too many submissions in flight".into(),
+                    ));
+                }
+            };
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+}
+
+/// A claimed key whose submission is running.
+///
+/// An attempt that certainly never reached the backend (e.g. backend
+/// unavailable) calls [`Pending::release`] so it can be retried. Dropping it
+/// without a result, as on a lost connection or a canceled handler, leaves
+/// the key indeterminate until it expires.
+pub struct Pending<T: Clone> {
+    store: Arc<IdempotencyStore<T>>,
+    key: String,
+    generation: u64,
+    completed: bool,
+}
+
+impl<T: Clone> Pending<T> {
+    /// Record the result and hand it to any waiting duplicates.
+    pub fn complete(mut self, value: T) {
+        self.completed = true;
+        let mut inner = self.store.inner.lock().unwrap();
+        if let Some(entry) = inner.entries.get_mut(&self.key)
+            && entry.generation == self.generation
+        {
+            let expires_at = Instant::now() + self.store.ttl;
+            let done = Slot::Done {
+                value: value.clone(),
+                expires_at,
+            };
+            if let Slot::InFlight(sender) = std::mem::replace(&mut entry.slot, done) {
+                sender.send_replace(Some(value));
+            }
+        }
+    }
+
+    /// Free the key: nothing was submitted, so the next claim may execute.
+    pub fn release(mut self) {
+        self.completed = true;
+        let mut inner = self.store.inner.lock().unwrap();
+        if inner
+            .entries
+            .get(&self.key)
+            .is_some_and(|entry| entry.generation == self.generation)
+        {
+            inner.entries.remove(&self.key);
+        }
+    }
+}
+
+impl<T: Clone> Drop for Pending<T> {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        let mut inner = self.store.inner.lock().unwrap();
+        if let Some(entry) = inner.entries.get_mut(&self.key)
+            && entry.generation == self.generation
+        {
+            // Waiters see the sender go and come back to reconcile
+            entry.slot = Slot::Unknown {
+                expires_at: Instant::now() + self.store.ttl,
+            };
+        }
+    }
+}
+
+/// Key from the `Idempotency-Key` header, if sent.
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => Ok(Some(key.to_owned())),
+        _ => Err(AppError::BadRequest(format!(
+            "Idempotency-Key must be 1 to {} visible ASCII characters",
+            MAX_KEY_LEN
+        ))),
+    }
+}
+
+/// Fingerprint of a request, compared when a key is reused.
+pub fn fingerprint(request: &impl Serialize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(request)
+        .expect("request serializes to JSON")
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(capacity: usize) -> Arc<IdempotencyStore<u32>> {
+        Arc::new(IdempotencyStore::new(capacity, Duration::from_secs(60)))
+    }
+
+    fn execute(claim: Claim<u32>) -> Pending<u32> {
+        match claim {
+            Claim::Execute(pending) => pending,
+            Claim::Replay(value) => panic!("unexpected replay of {value}"),
+            Claim::Reconcile(_) => panic!("unexpected reconcile"),
+        }
+    }
+
+    fn replay(claim: Claim<u32>) -> u32 {
+        match claim {
+            Claim::Replay(value) => value,
+            _ => panic!("unexpected claim"),
+        }
+    }
+
+    fn reconcile(claim: Claim<u32>) -> Pending<u32> {
+        match claim {
+            Claim::Reconcile(pending) => pending,
+            _ => panic!("expected a reconcile"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_after_success_and_conflicting_body() {
+        let store = store(10);
+        execute(store.claim("k".into(), 1).await.unwrap()).complete(7);
+        assert_eq!(replay(store.claim("k".into(), 1).await.unwrap()), 7);
+        assert!(matches!(store.claim("k".into(), 2).await, Err(AppError::Conflict(_))));
+        // Other keys are unaffected
+        execute(store.claim("other".into(), 2).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_waits_for_in_flight_result() {
+        let store = store(10);
+        let pending = execute(store.claim("k".into(), 1).await.unwrap());
+
+        let waiter = tokio::spawn({
+            let store = store.clone();
+            async move { replay(store.claim("k".into(), 1).await.unwrap()) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        assert!(matches!(store.claim("k".into(), 9).await, Err(AppError::Conflict(_))));
+
+        pending.complete(42);
+        assert_eq!(waiter.await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_claim_is_taken_over_to_reconcile() {
+        let store = store(10);
+        let pending = execute(store.claim("k".into(), 1).await.unwrap());
+        let waiter = tokio::spawn({
+            let store = store.clone();
+            async move { store.claim("k".into(), 1).await.unwrap() }
+        });
+        tokio::task::yield_now().await;
+
+        // The holder may have reached the backend: never a blind re-execute
+        drop(pending);
+        drop(reconcile(waiter.await.unwrap()));
+        let reconciled = reconcile(store.claim("k".into(), 1).await.unwrap());
+        assert!(matches!(store.claim("k".into(), 2).await, Err(AppError::Conflict(_))));
+        reconciled.complete(3);
+        assert_eq!(replay(store.claim("k".into(), 1).await.unwrap()), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_released_claim_executes_again_and_unknown_outcome_expires() {
+        let store = store(10);
+        execute(store.claim("k".into(), 1).await.unwrap()).release();
+        drop(execute(store.claim("k".into(), 1).await.unwrap()));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        execute(store.claim("k".into(), 2).await.unwrap()).complete(4);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_key_expires_after_ttl() {
+        let store = store(10);
+        execute(store.claim("k".into(), 1).await.unwrap()).complete(7);
+
+        tokio::time::advance(Duration::from_secs(59)).await;
+        assert_eq!(replay(store.claim("k".into(), 1).await.unwrap()), 7);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        // Expired: the key is free again, even for a different body
+        execute(store.claim("k".into(), 2).await.unwrap()).complete(8);
+        assert_eq!(replay(store.claim("k".into(), 2).await.unwrap()), 8);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_capacity_evicts_oldest_result_but_never_in_flight() {
+        let store = store(2);
+        execute(store.claim("a".into(), 1).await.unwrap()).complete(1);
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let b = execute(store.claim("b".into(), 1).await.unwrap());
+
+        execute(store.claim("c".into(), 1).await.unwrap()).complete(3);
+        assert_eq!(store.len(), 2);
+        let a = execute(store.claim("a".into(), 1).await.unwrap());
+
+        // Only in-flight keys left: refuse rather than forget one
+        assert!(matches!(
+            store.claim("d".into(), 1).await,
+            Err(AppError::ServiceUnavailable(_))
+        ));
+        a.complete(1);
+        b.complete(2);
+    }
+
+    #[test]
+    fn test_idempotency_key_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers).unwrap(), None);
+        headers.insert(IDEMPOTENCY_KEY_HEADER, " retry-1 ".parse().unwrap());
+        assert_eq!(idempotency_key(&headers).unwrap().as_deref(), Some("retry-1"));
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "k".repeat(256).parse().unwrap());
+        assert!(matches!(idempotency_key(&headers), Err(AppError::BadRequest(_))));
+    }
+}
@@ -1,14 +1,29 @@
+mod api_keys;
 mod auth;
+mod cancel_on_disconnect;
+mod circuit_breaker;
+mod engine_client;
 mod error;
 mod handlers;
+mod health;
+mod idempotency;
+mod metrics;
 mod models;
 mod rate_limit;
+mod request_id;
 mod router;
+mod shutdown;
 mod state;
+mod user_events;
 
+use api_keys::{ApiKeyStore, DEFAULT_MAX_SKEW};
+use cancel_on_disconnect::CancelOnDisconnect;
+use health::{Dependency, HealthCheckConfig, HealthRegistry};
 use router::create_router;
 use state::AppState;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 
 #[tokio::main]
@@ -19,8 +34,61 @@ async fn main() -> Result<(), anyhow::Error> {
     tracing::info!("Starting Gateway API service");
 
     // Initialize application state
-    // Pointing to a dummy internal service URL for compiling/testing
-    let state = AppState::new("http://localhost:8081".to_string());
+    let engine_url = std::env::var("MATCHING_ENGINE_URL")
+        .unwrap_or_else(|_| "http://localhost:8081".to_string());
+    let risk_url = std::env::var("RISK_ENGINE_URL")
+        .unwrap_or_else(|_| "http://localhost:8082".to_string());
+    let market_data_url = std::env::var("MARKET_DATA_URL")
+        .unwrap_or_else(|_| "http://localhost:8083".to_string());
+    let mut state = AppState::new(engine_url.clone());
+    state.health = Arc::new(HealthRegistry::new(
+        vec![
+            Dependency::new("matching-engine", engine_url),
+            Dependency::new("risk-engine", risk_url),
+            Dependency::new("market-data", market_data_url),
+        ],
+        HealthCheckConfig::default(),
+    ));
+    if let Some(grace) = std::env::var("CANCEL_ON_DISCONNECT_GRACE_MS").ok().and_then(|ms| ms.parse().ok()) {
+        state.cancel_on_disconnect = Arc::new(CancelOnDisconnect::new(
+            state.engine.clone(),
+            state.user_events.clone(),
+            Duration::from_millis(grace),
+        ));
+    }
+    if let Ok(path) = std::env::var("API_KEYS_PATH") {
+        state.api_keys = Arc::new(ApiKeyStore::open(path, DEFAULT_MAX_SKEW)?);
+    }
+    if let Ok(path) = std::env::var("ACCOUNT_TIERS_PATH") {
+        let count = state.rate_limiter.load_account_tiers(path.as_ref())?;
+        tracing::info!("Loaded rate limit tiers for {} account(s)", count);
+    }
+
+    // Check downstream services in the background for `/ready` and `/status`
+    tokio::spawn(health::run_checks(state.health.clone(), state.http_client.clone()));
+
+    // Feed private account events to `/ws/user` connections and the history
+    tokio::spawn(user_events::consume_feed(
+        state.user_events.clone(),
+        state.history.clone(),
+        state.http_client.clone(),
+        state.internal_services_url.clone(),
+    ));
+
+    // SIGTERM or Ctrl-C starts a drain bounded by the deadline
+    let coordinator = state.shutdown.clone();
+    let deadline = std::env::var("SHUTDOWN_DEADLINE_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .map_or(shutdown::DEFAULT_DRAIN_DEADLINE, Duration::from_millis);
+    tokio::spawn({
+        let coordinator = coordinator.clone();
+        async move {
+            shutdown_core::signal().await;
+            tracing::info!("Shutdown requested, draining for up to {:?}", deadline);
+            coordinator.trigger();
+        }
+    });
 
     // Create router
     let app = create_router(state);
@@ -30,7 +98,12 @@ async fn main() -> Result<(), anyhow::Error> {
     let listener = TcpListener::bind(addr).await?;
     
     tracing::info!("Listening on {}", addr);
-    axum::serve(listener, app).await?;
+    let report = shutdown::serve(listener, app, coordinator, deadline).await?;
+    if report.is_clean() {
+        tracing::info!("Shutdown complete: {}", report);
+    } else {
+        tracing::warn!("Shutdown incomplete: {}", report);
+    }
 
     Ok(())
 }
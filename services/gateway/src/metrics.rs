@@ -0,0 +1,170 @@
+use crate::circuit_breaker::BreakerStatus;
+use crate::error::AppError;
+use crate::state::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use metrics_core::{Counter, Gauge, Histogram, Registry, DEFAULT_LATENCY_BUCKETS};
+use std::time::Instant;
+
+/// WebSocket channels counted by `gateway_ws_connections`.
+pub const WS_CHANNELS: [&str; 2] = ["market", "user"];
+/// Values of the `outcome` label on `gateway_order_submissions_total`.
+pub const ORDER_OUTCOMES: [&str; 4] = ["accepted", "rejected", "invalid", "error"];
+
+/// Counters and histograms exported on `GET /metrics`.
+pub struct GatewayMetrics {
+    registry: Registry,
+    /// By method, route template and status code
+    pub http_requests: Counter,
+    /// By method and route template
+    pub http_latency: Histogram,
+    pub ws_connections: Gauge,
+    /// By route template
+    pub rate_limit_rejections: Counter,
+    /// Single and batched order placements by [`order_outcome`]
+    pub order_submissions: Counter,
+    /// By dependency, refreshed from the breakers by [`observe_breakers`](Self::observe_breakers)
+    pub circuit_breaker_state: Gauge,
+    pub circuit_breaker_rejections: Counter,
+    pub internal_retries: Counter,
+}
+
+impl GatewayMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let metrics = Self {
+            http_requests: registry.counter(
+                "gateway_http_requests_total",
+                "HTTP requests served, by route template.",
+                &["method", "route", "status"],
+            ),
+            http_latency: registry.histogram(
+                "gateway_http_request_duration_seconds",
+                "Time to produce an HTTP response.",
+                &["method", "route"],
+                DEFAULT_LATENCY_BUCKETS,
+            ),
+            ws_connections: registry.gauge(
+                "gateway_ws_connections",
+                "Open WebSocket connections.",
+                &["channel"],
+            ),
+            rate_limit_rejections: registry.counter(
+                "gateway_rate_limit_rejections_total",
+                "Requests and subscriptions refused by the rate limiter.",
+                &["route"],
+            ),
+            order_submissions: registry.counter(
+                "gateway_order_submissions_total",
+                "Order placements by outcome.",
+                &["outcome"],
+            ),
+            circuit_breaker_state: registry.gauge(
+                "gateway_circuit_breaker_state",
+                "Breaker position per internal dependency: 0 closed, 1 half-open, 2 open.",
+                &["dependency"],
+            ),
+            circuit_breaker_rejections: registry.counter(
+                "gateway_circuit_breaker_rejections_total",
+                "Internal calls failed fast by a breaker.",
+                &["dependency"],
+            ),
+            internal_retries: registry.counter(
+                "gateway_internal_retries_total",
+                "Retries of idempotent internal calls.",
+                &["dependency"],
+            ),
+            registry,
+        };
+        // Export fixed label sets from the start, so rates work from zero
+        for channel in WS_CHANNELS {
+            metrics.ws_connections.set(&[channel], 0);
+        }
+        for outcome in ORDER_OUTCOMES {
+            metrics.order_submissions.inc_by(&[outcome], 0);
+        }
+        metrics
+    }
+
+    /// Count an open connection on `channel` until the guard drops.
+    pub fn ws_connected(&self, channel: &'static str) -> ConnectionGuard {
+        self.ws_connections.add(&[channel], 1);
+        ConnectionGuard {
+            gauge: self.ws_connections.clone(),
+            channel,
+        }
+    }
+
+    /// Copy the breakers' state and counts into the exported series.
+    pub fn observe_breakers(&self, breakers: &[BreakerStatus]) {
+        for breaker in breakers {
+            let labels = [breaker.dependency];
+            self.circuit_breaker_state.set(&labels, breaker.state.gauge_value());
+            self.circuit_breaker_rejections.set(&labels, breaker.rejected_calls);
+            self.internal_retries.set(&labels, breaker.retries);
+        }
+    }
+
+    /// Prometheus text exposition of every metric.
+    pub fn render(&self) -> String {
+        self.registry.render()
+    }
+}
+
+impl Default for GatewayMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An open WebSocket connection, counted while alive.
+pub struct ConnectionGuard {
+    gauge: Gauge,
+    channel: &'static str,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.gauge.add(&[self.channel], -1);
+    }
+}
+
+/// `outcome` label for an order placement.
+///
+/// `rejected` is the engine's refusal; `invalid` covers everything turned
+/// away before reaching it, and `error` a failure to get an answer.
+pub fn order_outcome<T>(result: &Result<T, AppError>) -> &'static str {
+    match result {
+        Ok(_) => "accepted",
+        Err(AppError::Engine(_)) => "rejected",
+        Err(
+            AppError::ServiceUnavailable(_)
+            | AppError::CircuitOpen { .. }
+            | AppError::OutcomeUnknown(_)
+            | AppError::InternalError(_),
+        ) => "error",
+        Err(_) => "invalid",
+    }
+}
+
+/// Middleware counting and timing every response by route template.
+pub async fn track_requests(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |p| p.as_str().to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    let status = response.status();
+    state.metrics.http_requests.inc(&[method.as_str(), &route, status.as_str()]);
+    state
+        .metrics
+        .http_latency
+        .observe(&[method.as_str(), &route], started.elapsed().as_secs_f64());
+    response
+}
@@ -1,25 +1,908 @@
+use crate::api_keys::{ApiKey, Permission};
+use persistence::history::{OrderFilter, TradeFilter};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use thiserror::Error;
 use types::numeric::{Price, Quantity};
+pub use types::order::OrderType;
 use types::order::{Side, TimeInForce};
 use types::ids::{AccountId, MarketId, OrderId};
+use types::market::{MarketConfig, MarketConfigViolation, MarketStatus};
+use uuid::Uuid;
+use wasm_core::payload::{SignableBatch, SignableOrder, SignableWithdrawal};
 
+const SIDES: &[&str] = &["BUY", "SELL"];
+const ORDER_TYPES: &[&str] = &["LIMIT", "MARKET"];
+const TIME_IN_FORCES: &[&str] = &["GTC", "IOC", "FOK", "GTD"];
+const BATCH_ACTIONS: &[&str] = &["PLACE", "CANCEL", "AMEND"];
+/// Order state names, indexed by state ID.
+const ORDER_STATES: &[&str] = &["PENDING", "PARTIAL", "FILLED", "CANCELED", "REJECTED", "EXPIRED"];
+/// Longest accepted `client_order_id`, long enough for a UUID.
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 36;
+/// Most instructions accepted in one batch.
+pub const MAX_BATCH_ITEMS: usize = 20;
+
+/// Decimal precision and increments allowed for a market's prices and quantities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketRules {
+    pub price_decimals: u32,
+    pub quantity_decimals: u32,
+    pub config: MarketConfig,
+}
+
+impl Default for MarketRules {
+    fn default() -> Self {
+        Self {
+            price_decimals: 2,
+            quantity_decimals: 8,
+            config: MarketConfig::default(),
+        }
+    }
+}
+
+/// Public market info returned by `GET /v1/markets/:base/:quote`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketInfo {
+    pub symbol: MarketId,
+    pub status: MarketStatus,
+    pub price_decimals: u32,
+    pub quantity_decimals: u32,
+    pub tick_size: Decimal,
+    pub lot_size: Decimal,
+    pub min_notional: Decimal,
+}
+
+/// Why a single request field failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FieldErrorKind {
+    #[error("field is required")]
+    Missing,
+
+    #[error("must be a string, got {0}")]
+    NotAString(&'static str),
+
+    #[error("must not be empty")]
+    Empty,
+
+    #[error("'{0}' is not a valid decimal number")]
+    NotNumeric(String),
+
+    #[error("at most {max} decimal places allowed, got {actual}")]
+    TooManyDecimalPlaces { max: u32, actual: u32 },
+
+    #[error("must not be negative")]
+    Negative,
+
+    #[error("must be greater than zero")]
+    NotPositive,
+
+    #[error("'{value}' is not one of: {}", allowed.join(", "))]
+    UnknownVariant {
+        value: String,
+        allowed: &'static [&'static str],
+    },
+
+    #[error("must be a valid {0}")]
+    Malformed(&'static str),
+
+    #[error("not allowed {0}")]
+    NotAllowed(&'static str),
+
+    #[error("at most {max} characters allowed, got {actual}")]
+    TooLong { max: usize, actual: usize },
+
+    #[error("one of {0} is required")]
+    OneOfRequired(&'static str),
+
+    #[error("{0}")]
+    MarketConfig(MarketConfigViolation),
+}
+
+impl FieldErrorKind {
+    /// Stable machine-readable code for the error envelope.
+    pub fn code(&self) -> &'static str {
+        match self {
+            FieldErrorKind::Missing => "MISSING",
+            FieldErrorKind::NotAString(_) => "NOT_A_STRING",
+            FieldErrorKind::Empty => "EMPTY",
+            FieldErrorKind::NotNumeric(_) => "NOT_NUMERIC",
+            FieldErrorKind::TooManyDecimalPlaces { .. } => "TOO_MANY_DECIMAL_PLACES",
+            FieldErrorKind::Negative => "NEGATIVE",
+            FieldErrorKind::NotPositive => "NOT_POSITIVE",
+            FieldErrorKind::UnknownVariant { .. } => "UNKNOWN_VARIANT",
+            FieldErrorKind::Malformed(_) => "MALFORMED",
+            FieldErrorKind::NotAllowed(_) => "NOT_ALLOWED",
+            FieldErrorKind::TooLong { .. } => "TOO_LONG",
+            FieldErrorKind::OneOfRequired(_) => "ONE_OF_REQUIRED",
+            FieldErrorKind::MarketConfig(MarketConfigViolation::PriceNotOnTick { .. }) => "PRICE_NOT_ON_TICK",
+            FieldErrorKind::MarketConfig(MarketConfigViolation::QuantityNotOnLot { .. }) => "QUANTITY_NOT_ON_LOT",
+            FieldErrorKind::MarketConfig(MarketConfigViolation::BelowMinNotional { .. }) => "BELOW_MIN_NOTIONAL",
+        }
+    }
+}
+
+/// A field-level validation failure, as reported in the error envelope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, kind: FieldErrorKind) -> Self {
+        Self {
+            field,
+            code: kind.code(),
+            message: kind.to_string(),
+        }
+    }
+}
+
+/// Order placement payload as received on the wire.
+///
+/// Fields are kept as raw JSON so that every problem can be reported at once;
+/// use [`PlaceOrderPayload::validate`] to obtain a [`PlaceOrderRequest`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlaceOrderPayload {
+    #[serde(default)]
+    pub account_id: Option<Value>,
+    #[serde(default)]
+    pub symbol: Option<Value>,
+    #[serde(default)]
+    pub side: Option<Value>,
+    #[serde(default)]
+    pub order_type: Option<Value>,
+    #[serde(default)]
+    pub price: Option<Value>,
+    #[serde(default)]
+    pub quantity: Option<Value>,
+    #[serde(default)]
+    pub time_in_force: Option<Value>,
+    #[serde(default)]
+    pub expire_at: Option<Value>,
+    #[serde(default)]
+    pub client_order_id: Option<Value>,
+}
+
+/// Validated order placement request forwarded to the Order Service.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CreateOrderRequest {
+pub struct PlaceOrderRequest {
     pub account_id: AccountId,
     pub symbol: MarketId,
     pub side: Side,
-    pub price: Price,
+    pub order_type: OrderType,
+    /// Limit price (`None` for market orders)
+    pub price: Option<Price>,
     pub quantity: Quantity,
     pub time_in_force: TimeInForce,
+    /// Caller-chosen id, echoed back in the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+}
+
+impl PlaceOrderRequest {
+    /// Canonical signing payload, rebuilt the way clients build it.
+    pub fn signable(&self) -> SignableOrder {
+        let order = SignableOrder::new(
+            self.symbol.as_str(),
+            self.side,
+            self.price.map(|p| p.as_decimal()),
+            self.quantity.as_decimal(),
+        )
+        .with_order_type(self.order_type)
+        .with_time_in_force(self.time_in_force);
+        match &self.client_order_id {
+            Some(id) => order.with_client_order_id(id),
+            None => order,
+        }
+    }
+}
+
+impl PlaceOrderPayload {
+    /// Symbol as sent, used to look up market rules before validation.
+    pub fn symbol_str(&self) -> Option<&str> {
+        self.symbol.as_ref().and_then(Value::as_str)
+    }
+
+    /// Client order id as sent, echoed back even when the request is rejected.
+    pub fn client_order_id_str(&self) -> Option<&str> {
+        self.client_order_id.as_ref().and_then(Value::as_str)
+    }
+
+    /// Validate every field, collecting all failures.
+    ///
+    /// Prices and quantities must be JSON strings; JSON numbers are rejected
+    /// so no value ever passes through a float. Enums are case-insensitive.
+    /// `order_type` defaults to `LIMIT` and `time_in_force` to `GTC`.
+    /// `client_order_id` is optional: up to 36 ASCII letters, digits, `-` or `_`.
+    pub fn validate(&self, rules: &MarketRules) -> Result<PlaceOrderRequest, Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        let account_id = check(&mut errors, "account_id", required_str(&self.account_id).and_then(|s| {
+            Uuid::parse_str(s)
+                .map(AccountId::from_uuid)
+                .map_err(|_| FieldErrorKind::Malformed("account id"))
+        }));
+        let symbol = check(&mut errors, "symbol", required_str(&self.symbol).and_then(|s| {
+            MarketId::try_new(s).ok_or(FieldErrorKind::Malformed("BASE/QUOTE symbol"))
+        }));
+        let side = check(&mut errors, "side", required_str(&self.side).and_then(|s| {
+            match parse_variant(s, SIDES)? {
+                "BUY" => Ok(Side::BUY),
+                _ => Ok(Side::SELL),
+            }
+        }));
+        let order_type = check(&mut errors, "order_type", match self.order_type {
+            None => Ok(OrderType::Limit),
+            Some(_) => required_str(&self.order_type).and_then(|s| {
+                match parse_variant(s, ORDER_TYPES)? {
+                    "LIMIT" => Ok(OrderType::Limit),
+                    _ => Ok(OrderType::Market),
+                }
+            }),
+        });
+        let price = check(&mut errors, "price", match (order_type, &self.price) {
+            (Some(OrderType::Market), None) => Ok(None),
+            (Some(OrderType::Market), Some(_)) => {
+                Err(FieldErrorKind::NotAllowed("for MARKET orders"))
+            }
+            _ => parse_price(&self.price, rules).map(Some),
+        });
+        let quantity = check(&mut errors, "quantity", parse_quantity(&self.quantity, rules));
+        if let (Some(Some(price)), Some(quantity)) = (price, quantity) {
+            check(&mut errors, "quantity", rules.config.check_notional(price, quantity)
+                .map_err(FieldErrorKind::MarketConfig));
+        }
+        let time_in_force = check(&mut errors, "time_in_force", match self.time_in_force {
+            None => Ok(None),
+            Some(_) => required_str(&self.time_in_force)
+                .and_then(|s| parse_variant(s, TIME_IN_FORCES))
+                .map(Some),
+        });
+        let time_in_force = match time_in_force {
+            Some(Some("GTD")) => check(&mut errors, "expire_at", match &self.expire_at {
+                None => Err(FieldErrorKind::Missing),
+                Some(v) => v.as_i64().ok_or(FieldErrorKind::Malformed("unix nanosecond timestamp")),
+            })
+            .map(TimeInForce::GTD),
+            Some(Some("IOC")) => Some(TimeInForce::IOC),
+            Some(Some("FOK")) => Some(TimeInForce::FOK),
+            Some(_) => Some(TimeInForce::GTC),
+            None => None,
+        };
+        let client_order_id = check(&mut errors, "client_order_id", match self.client_order_id {
+            None => Ok(None),
+            Some(_) => required_str(&self.client_order_id).and_then(|s| {
+                if s.len() > MAX_CLIENT_ORDER_ID_LEN {
+                    return Err(FieldErrorKind::TooLong {
+                        max: MAX_CLIENT_ORDER_ID_LEN,
+                        actual: s.len(),
+                    });
+                }
+                if !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                    return Err(FieldErrorKind::Malformed("client order id"));
+                }
+                Ok(Some(s.to_string()))
+            }),
+        });
+
+        match (account_id, symbol, side, order_type, price, quantity, time_in_force) {
+            (
+                Some(account_id),
+                Some(symbol),
+                Some(side),
+                Some(order_type),
+                Some(price),
+                Some(quantity),
+                Some(time_in_force),
+            ) if errors.is_empty() => Ok(PlaceOrderRequest {
+                account_id,
+                symbol,
+                side,
+                order_type,
+                price,
+                quantity,
+                time_in_force,
+                client_order_id: client_order_id.flatten(),
+            }),
+            _ => Err(errors),
+        }
+    }
+}
+
+/// Instruction kind named by a batch item's `action` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+    Place,
+    Cancel,
+    Amend,
+}
+
+impl BatchAction {
+    /// Action of a raw batch item, matched case-insensitively.
+    pub fn of(item: &Map<String, Value>) -> Result<Self, FieldErrorKind> {
+        let action = item.get("action").cloned();
+        match parse_variant(required_str(&action)?, BATCH_ACTIONS)? {
+            "PLACE" => Ok(BatchAction::Place),
+            "CANCEL" => Ok(BatchAction::Cancel),
+            _ => Ok(BatchAction::Amend),
+        }
+    }
+}
+
+/// Batch submission as received on the wire.
+///
+/// Items stay raw JSON objects: the batch is signed over them as sent and
+/// each one is validated on its own with [`BatchInstruction::parse`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchPayload {
+    /// Reject the whole batch if any item is invalid
+    #[serde(default)]
+    pub atomic: bool,
+    pub items: Vec<Map<String, Value>>,
+}
+
+impl BatchPayload {
+    /// Canonical signing payload, rebuilt the way clients build it.
+    pub fn signable(&self) -> SignableBatch {
+        SignableBatch::new(self.atomic, self.items.clone())
+    }
+}
+
+/// New price and/or quantity for a resting order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmendOrderRequest {
+    pub account_id: AccountId,
+    pub symbol: MarketId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<Price>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<Quantity>,
+}
+
+/// A validated batch item.
+#[derive(Debug, Clone)]
+pub enum BatchInstruction {
+    Place(PlaceOrderRequest),
+    Cancel { order_id: OrderId },
+    Amend { order_id: OrderId, request: AmendOrderRequest },
+}
+
+impl BatchInstruction {
+    /// Validate one batch item on behalf of `account_id`.
+    ///
+    /// `place` items take the fields of a single order placement; `cancel`
+    /// items an `order_id`; `amend` items an `order_id`, the order's `symbol`
+    /// and a new `price`, `quantity` or both, checked against `rules`.
+    pub fn parse(
+        item: &Map<String, Value>,
+        account_id: AccountId,
+        rules: impl Fn(&str) -> MarketRules,
+    ) -> Result<Self, Vec<FieldError>> {
+        let field = |name: &str| item.get(name).cloned();
+        let mut errors = Vec::new();
+        match check(&mut errors, "action", BatchAction::of(item)) {
+            None => Err(errors),
+            Some(BatchAction::Place) => {
+                // Every field is optional raw JSON, so any object deserializes
+                let payload = PlaceOrderPayload::deserialize(Value::Object(item.clone())).unwrap_or_default();
+                payload
+                    .validate(&rules(payload.symbol_str().unwrap_or_default()))
+                    .map(BatchInstruction::Place)
+            }
+            Some(BatchAction::Cancel) => {
+                match check(&mut errors, "order_id", parse_order_id(&field("order_id"))) {
+                    Some(order_id) => Ok(BatchInstruction::Cancel { order_id }),
+                    None => Err(errors),
+                }
+            }
+            Some(BatchAction::Amend) => {
+                let order_id = check(&mut errors, "order_id", parse_order_id(&field("order_id")));
+                let symbol = check(&mut errors, "symbol", required_str(&field("symbol")).and_then(|s| {
+                    MarketId::try_new(s).ok_or(FieldErrorKind::Malformed("BASE/QUOTE symbol"))
+                }));
+                let rules = symbol.as_ref().map(|s| rules(s.as_str())).unwrap_or_default();
+                let (price, quantity) = (field("price"), field("quantity"));
+                if price.is_none() && quantity.is_none() {
+                    errors.push(FieldError::new("price", FieldErrorKind::OneOfRequired("price, quantity")));
+                }
+                let price = match price {
+                    None => Some(None),
+                    Some(_) => check(&mut errors, "price", parse_price(&price, &rules).map(Some)),
+                };
+                let quantity = match quantity {
+                    None => Some(None),
+                    Some(_) => check(&mut errors, "quantity", parse_quantity(&quantity, &rules).map(Some)),
+                };
+                if let (Some(Some(price)), Some(Some(quantity))) = (price, quantity) {
+                    check(&mut errors, "quantity", rules.config.check_notional(price, quantity)
+                        .map_err(FieldErrorKind::MarketConfig));
+                }
+                match (order_id, symbol, price, quantity) {
+                    (Some(order_id), Some(symbol), Some(price), Some(quantity)) if errors.is_empty() => {
+                        Ok(BatchInstruction::Amend {
+                            order_id,
+                            request: AmendOrderRequest {
+                                account_id,
+                                symbol,
+                                price,
+                                quantity,
+                            },
+                        })
+                    }
+                    _ => Err(errors),
+                }
+            }
+        }
+    }
+}
+
+/// How a batch item ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Accepted,
+    /// Rejected by validation or by the engine
+    Failed,
+    /// Not executed because its atomic batch stopped
+    Skipped,
+}
+
+/// Outcome of one batch item, reported in request order.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: BatchItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<OrderId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    /// Error envelope, as the single-item endpoint would have returned it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+impl BatchItemResult {
+    pub fn accepted(index: usize, order_id: OrderId, client_order_id: Option<String>) -> Self {
+        Self {
+            index,
+            status: BatchItemStatus::Accepted,
+            order_id: Some(order_id),
+            client_order_id,
+            error: None,
+        }
+    }
+
+    pub fn failed(index: usize, client_order_id: Option<String>, error: Value) -> Self {
+        Self {
+            index,
+            status: BatchItemStatus::Failed,
+            order_id: None,
+            client_order_id,
+            error: Some(error),
+        }
+    }
+
+    pub fn skipped(index: usize, client_order_id: Option<String>) -> Self {
+        Self {
+            index,
+            status: BatchItemStatus::Skipped,
+            order_id: None,
+            client_order_id,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResponse {
+    pub atomic: bool,
+    pub items: Vec<BatchItemResult>,
+}
+
+/// Record a failed field check, passing through the value on success.
+fn check<T>(
+    errors: &mut Vec<FieldError>,
+    field: &'static str,
+    result: Result<T, FieldErrorKind>,
+) -> Option<T> {
+    result.map_err(|kind| errors.push(FieldError::new(field, kind))).ok()
+}
+
+/// JSON type name for error messages.
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Require a non-empty JSON string.
+fn required_str(value: &Option<Value>) -> Result<&str, FieldErrorKind> {
+    match value {
+        None => Err(FieldErrorKind::Missing),
+        Some(Value::String(s)) if s.trim().is_empty() => Err(FieldErrorKind::Empty),
+        Some(Value::String(s)) => Ok(s.trim()),
+        Some(other) => Err(FieldErrorKind::NotAString(json_type(other))),
+    }
+}
+
+/// Match an enum value case-insensitively, returning its canonical spelling.
+fn parse_variant(value: &str, allowed: &'static [&'static str]) -> Result<&'static str, FieldErrorKind> {
+    allowed
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(value))
+        .copied()
+        .ok_or_else(|| FieldErrorKind::UnknownVariant {
+            value: value.to_string(),
+            allowed,
+        })
+}
+
+/// Parse a positive price within the market's precision and tick size.
+fn parse_price(value: &Option<Value>, rules: &MarketRules) -> Result<Price, FieldErrorKind> {
+    let d = parse_decimal(value, rules.price_decimals)?;
+    let price = Price::try_new(d).ok_or(FieldErrorKind::NotPositive)?;
+    rules.config.check_price(price).map_err(FieldErrorKind::MarketConfig)?;
+    Ok(price)
+}
+
+/// Parse a positive quantity within the market's precision and lot size.
+fn parse_quantity(value: &Option<Value>, rules: &MarketRules) -> Result<Quantity, FieldErrorKind> {
+    let d = parse_decimal(value, rules.quantity_decimals)?;
+    if d.is_zero() {
+        return Err(FieldErrorKind::NotPositive);
+    }
+    let quantity = Quantity::new(d);
+    rules.config.check_quantity(quantity).map_err(FieldErrorKind::MarketConfig)?;
+    Ok(quantity)
+}
+
+fn parse_order_id(value: &Option<Value>) -> Result<OrderId, FieldErrorKind> {
+    required_str(value).and_then(|s| {
+        Uuid::parse_str(s)
+            .map(OrderId::from_uuid)
+            .map_err(|_| FieldErrorKind::Malformed("order id"))
+    })
+}
+
+/// Parse a non-negative decimal string with at most `max_decimals` places.
+fn parse_decimal(value: &Option<Value>, max_decimals: u32) -> Result<Decimal, FieldErrorKind> {
+    let s = required_str(value)?;
+    let d = Decimal::from_str(s).map_err(|_| FieldErrorKind::NotNumeric(s.to_string()))?;
+    if d.is_sign_negative() && !d.is_zero() {
+        return Err(FieldErrorKind::Negative);
+    }
+    let actual = d.normalize().scale();
+    if actual > max_decimals {
+        return Err(FieldErrorKind::TooManyDecimalPlaces {
+            max: max_decimals,
+            actual,
+        });
+    }
+    Ok(d)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderResponse {
     pub order_id: OrderId,
     pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelOrderRequest {
     pub account_id: AccountId,
 }
+
+/// Query of `GET /v1/ws/user`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserStreamQuery {
+    /// Cancel the account's orders if this connection drops
+    #[serde(default)]
+    pub cancel_on_disconnect: bool,
+}
+
+/// Query of `GET /v1/orders`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OrderHistoryQuery {
+    /// Order state name, e.g. `FILLED` (case-insensitive)
+    pub status: Option<String>,
+    pub symbol: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl OrderHistoryQuery {
+    pub fn filter(&self) -> Result<OrderFilter, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let state_id = check(&mut errors, "status", match &self.status {
+            None => Ok(None),
+            Some(s) => parse_variant(s.trim(), ORDER_STATES)
+                .map(|name| ORDER_STATES.iter().position(|state| *state == name).map(|id| id as u8)),
+        });
+        let symbol = check(&mut errors, "symbol", parse_symbol_filter(&self.symbol));
+        match (state_id, symbol) {
+            (Some(state_id), Some(symbol)) if errors.is_empty() => Ok(OrderFilter { state_id, symbol }),
+            _ => Err(errors),
+        }
+    }
+}
+
+/// Query of `GET /v1/trades`; `from` and `to` are inclusive Unix nanos.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TradeHistoryQuery {
+    pub symbol: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl TradeHistoryQuery {
+    pub fn filter(&self) -> Result<TradeFilter, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        match check(&mut errors, "symbol", parse_symbol_filter(&self.symbol)) {
+            Some(symbol) => Ok(TradeFilter {
+                symbol,
+                from: self.from,
+                to: self.to,
+            }),
+            None => Err(errors),
+        }
+    }
+}
+
+fn parse_symbol_filter(symbol: &Option<String>) -> Result<Option<String>, FieldErrorKind> {
+    match symbol {
+        None => Ok(None),
+        Some(s) => MarketId::try_new(s.trim())
+            .map(|m| Some(m.as_str().to_string()))
+            .ok_or(FieldErrorKind::Malformed("BASE/QUOTE symbol")),
+    }
+}
+
+/// Withdrawal submission forwarded to the Custody Service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalRequest {
+    pub account_id: AccountId,
+    pub asset: String,
+    /// Sent as a decimal string
+    pub amount: Decimal,
+    /// Destination address on the settlement chain
+    pub destination: String,
+}
+
+impl WithdrawalRequest {
+    /// Canonical signing payload, rebuilt the way clients build it.
+    pub fn signable(&self) -> SignableWithdrawal {
+        SignableWithdrawal::new(self.asset.as_str(), self.amount, self.destination.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalResponse {
+    pub withdrawal_id: u64,
+    pub status: String,
+}
+
+/// Body of `POST /v1/api-keys`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub permissions: BTreeSet<Permission>,
+    #[serde(default)]
+    pub cancel_on_disconnect: bool,
+}
+
+/// An API key as listed, without its secret.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+    pub key_id: String,
+    pub account_id: AccountId,
+    pub permissions: BTreeSet<Permission>,
+    pub cancel_on_disconnect: bool,
+    pub created_at: i64,
+    pub revoked_at: Option<i64>,
+}
+
+impl From<ApiKey> for ApiKeyInfo {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            key_id: key.key_id,
+            account_id: key.account_id,
+            permissions: key.permissions,
+            cancel_on_disconnect: key.cancel_on_disconnect,
+            created_at: key.created_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(value: Value) -> PlaceOrderPayload {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn valid() -> Value {
+        json!({
+            "account_id": Uuid::now_v7().to_string(),
+            "symbol": "BTC/USDT",
+            "side": "buy",
+            "price": "50000.25",
+            "quantity": "0.5",
+            "time_in_force": "ioc"
+        })
+    }
+
+    #[test]
+    fn test_valid_payload_parses_case_insensitively() {
+        let req = payload(valid()).validate(&MarketRules::default()).unwrap();
+        assert_eq!(req.side, Side::BUY);
+        assert_eq!(req.order_type, OrderType::Limit);
+        assert_eq!(req.time_in_force, TimeInForce::IOC);
+        assert_eq!(req.price, Some(Price::from_str("50000.25").unwrap()));
+        assert_eq!(req.quantity.as_decimal(), Decimal::from_str("0.5").unwrap());
+    }
+
+    #[test]
+    fn test_awful_payload_reports_every_violation() {
+        let mut body = valid();
+        body["price"] = json!(50000.5);
+        body["side"] = json!("LONG");
+        body["quantity"] = json!("-1");
+
+        let errors = payload(body).validate(&MarketRules::default()).unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("side", "UNKNOWN_VARIANT"),
+                ("price", "NOT_A_STRING"),
+                ("quantity", "NEGATIVE"),
+            ]
+        );
+        assert_eq!(errors[0].message, "'LONG' is not one of: BUY, SELL");
+        assert_eq!(errors[1].message, "must be a string, got number");
+    }
+
+    #[test]
+    fn test_decimal_failure_modes() {
+        let rules = MarketRules::default();
+        let cases = [
+            (json!(""), "EMPTY"),
+            (json!("abc"), "NOT_NUMERIC"),
+            (json!("1.123"), "TOO_MANY_DECIMAL_PLACES"),
+            (json!("0"), "NOT_POSITIVE"),
+        ];
+        for (price, code) in cases {
+            let mut body = valid();
+            body["price"] = price;
+            let errors = payload(body).validate(&rules).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].field, "price");
+            assert_eq!(errors[0].code, code);
+        }
+
+        // Trailing zeros do not count as extra precision
+        let mut body = valid();
+        body["price"] = json!("1.1000");
+        assert!(payload(body).validate(&rules).is_ok());
+    }
+
+    #[test]
+    fn test_missing_fields_and_market_orders() {
+        let errors = payload(json!({})).validate(&MarketRules::default()).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["account_id", "symbol", "side", "price", "quantity"]);
+        assert!(errors.iter().all(|e| e.code == "MISSING"));
+
+        let mut body = valid();
+        body["order_type"] = json!("Market");
+        assert_eq!(
+            payload(body.clone()).validate(&MarketRules::default()).unwrap_err()[0].code,
+            "NOT_ALLOWED"
+        );
+        body.as_object_mut().unwrap().remove("price");
+        let req = payload(body).validate(&MarketRules::default()).unwrap();
+        assert_eq!(req.order_type, OrderType::Market);
+        assert_eq!(req.price, None);
+    }
+
+    #[test]
+    fn test_gtd_requires_expiry() {
+        let mut body = valid();
+        body["time_in_force"] = json!("gtd");
+        let errors = payload(body.clone()).validate(&MarketRules::default()).unwrap_err();
+        assert_eq!((errors[0].field, errors[0].code), ("expire_at", "MISSING"));
+
+        body["expire_at"] = json!(1708123456789000000i64);
+        let req = payload(body).validate(&MarketRules::default()).unwrap();
+        assert_eq!(req.time_in_force, TimeInForce::GTD(1708123456789000000));
+    }
+
+    #[test]
+    fn test_increment_violations_name_the_field() {
+        let rules = MarketRules {
+            config: MarketConfig {
+                tick_size: Decimal::from_str("0.5").unwrap(),
+                lot_size: Decimal::from_str("0.001").unwrap(),
+                min_notional: Decimal::from(10),
+            },
+            ..MarketRules::default()
+        };
+        let mut body = valid();
+        body["price"] = json!("100.25");
+        body["quantity"] = json!("0.0005");
+        let errors = payload(body).validate(&rules).unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(found, vec![("price", "PRICE_NOT_ON_TICK"), ("quantity", "QUANTITY_NOT_ON_LOT")]);
+        assert_eq!(errors[0].message, "Price 100.25 is not a multiple of tick size 0.5");
+
+        let mut body = valid();
+        body["price"] = json!("100.5");
+        body["quantity"] = json!("0.05");
+        let errors = payload(body.clone()).validate(&rules).unwrap_err();
+        assert_eq!((errors[0].field, errors[0].code), ("quantity", "BELOW_MIN_NOTIONAL"));
+
+        body["quantity"] = json!("0.1");
+        assert!(payload(body).validate(&rules).is_ok());
+    }
+
+    #[test]
+    fn test_signable_matches_client_payload() {
+        let mut body = valid();
+        body["price"] = json!("50000.250");
+        body["quantity"] = json!("0.50");
+        let req = payload(body).validate(&MarketRules::default()).unwrap();
+
+        let client = SignableOrder::new(
+            "BTC/USDT",
+            Side::BUY,
+            Some(Decimal::from_str("50000.25").unwrap()),
+            Decimal::from_str("0.5").unwrap(),
+        )
+        .with_time_in_force(TimeInForce::IOC);
+        assert_eq!(req.signable(), client.clone().with_order_type(OrderType::Limit));
+        assert_eq!(
+            req.signable().into_signable(1, 2).canonical_bytes(),
+            client.into_signable(1, 2).canonical_bytes()
+        );
+    }
+
+    #[test]
+    fn test_client_order_id_is_optional_and_signed() {
+        let req = payload(valid()).validate(&MarketRules::default()).unwrap();
+        assert_eq!(req.client_order_id, None);
+        assert!(!req.signable().payload().contains_key("client_order_id"));
+
+        let mut body = valid();
+        body["client_order_id"] = json!("grid-7_a");
+        let req = payload(body.clone()).validate(&MarketRules::default()).unwrap();
+        assert_eq!(req.client_order_id.as_deref(), Some("grid-7_a"));
+        assert_eq!(req.signable().payload()["client_order_id"], "grid-7_a");
+
+        let cases = [
+            (json!("a".repeat(37)), "TOO_LONG"),
+            (json!("a b"), "MALFORMED"),
+            (json!(7), "NOT_A_STRING"),
+        ];
+        for (id, code) in cases {
+            body["client_order_id"] = id;
+            let errors = payload(body.clone()).validate(&MarketRules::default()).unwrap_err();
+            assert_eq!((errors[0].field, errors[0].code), ("client_order_id", code));
+        }
+    }
+}
@@ -1,67 +1,629 @@
+use crate::auth::AuthenticatedUser;
 use crate::error::AppError;
-use std::time::Instant;
+use crate::state::AppState;
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use types::ids::AccountId;
 
-#[derive(Clone)]
-struct Bucket {
-    capacity: u32,
-    tokens: f64,
-    refill_rate: f64,
-    last_update: Instant,
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+pub const RETRY_AFTER_HEADER: &str = "retry-after";
+
+/// Account tier; higher tiers get larger buckets that refill faster.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Tier {
+    #[default]
+    Standard,
+    Vip,
+    Institutional,
 }
 
-impl Bucket {
-    fn new(capacity: u32, refill_rate: f64) -> Self {
+/// Size and refill rate of a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketLimits {
+    pub capacity: u32,
+    pub refill_per_sec: f64,
+}
+
+impl BucketLimits {
+    pub const fn new(capacity: u32, refill_per_sec: f64) -> Self {
         Self {
             capacity,
-            tokens: capacity as f64,
-            refill_rate,
-            last_update: Instant::now(),
+            refill_per_sec,
         }
     }
+}
 
-    fn allow_request(&mut self, tokens: u32) -> bool {
-        let now = Instant::now();
-        let elapsed = now.duration_since(self.last_update).as_secs_f64();
+/// Tokens each endpoint costs, keyed by method and route pattern
+/// (e.g. `POST /v1/orders`). Unlisted endpoints cost `default_weight`.
+#[derive(Debug, Clone)]
+pub struct EndpointWeights {
+    weights: HashMap<(Method, String), u32>,
+    default_weight: u32,
+}
+
+impl EndpointWeights {
+    pub fn new(default_weight: u32) -> Self {
+        Self {
+            weights: HashMap::new(),
+            default_weight,
+        }
+    }
 
-        // Refill tokens
-        self.tokens = f64::min(
-            self.capacity as f64,
-            self.tokens + elapsed * self.refill_rate,
-        );
-        self.last_update = now;
+    pub fn with(mut self, method: Method, path: &str, weight: u32) -> Self {
+        self.weights.insert((method, path.to_string()), weight);
+        self
+    }
 
-        // Consume token
-        if self.tokens >= tokens as f64 {
-            self.tokens -= tokens as f64;
-            true
-        } else {
-            false // Rate limited
+    pub fn weight(&self, method: &Method, path: &str) -> u32 {
+        self.weights
+            .get(&(method.clone(), path.to_string()))
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+impl Default for EndpointWeights {
+    fn default() -> Self {
+        Self::new(1)
+            .with(Method::POST, "/v1/orders", 5)
+            // Charged per item by the handler
+            .with(Method::POST, "/v1/orders/batch", 0)
+            .with(Method::DELETE, "/v1/orders/{id}", 2)
+            .with(Method::POST, "/v1/withdrawals", 20)
+            .with(Method::GET, "/v1/ws", 10)
+            .with(Method::GET, "/v1/ws/user", 10)
+    }
+}
+
+/// Rate limiting configuration.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub standard: BucketLimits,
+    pub vip: BucketLimits,
+    pub institutional: BucketLimits,
+    /// Applied per client IP on top of the caller's own bucket
+    pub per_ip: BucketLimits,
+    pub weights: EndpointWeights,
+    /// Buckets kept before the least recently used one is evicted
+    pub max_keys: usize,
+}
+
+impl RateLimitConfig {
+    pub fn limits(&self, tier: Tier) -> BucketLimits {
+        match tier {
+            Tier::Standard => self.standard,
+            Tier::Vip => self.vip,
+            Tier::Institutional => self.institutional,
         }
     }
 }
 
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            standard: BucketLimits::new(100, 20.0),
+            vip: BucketLimits::new(500, 100.0),
+            institutional: BucketLimits::new(2_000, 400.0),
+            per_ip: BucketLimits::new(300, 60.0),
+            weights: EndpointWeights::default(),
+            max_keys: 100_000,
+        }
+    }
+}
+
+/// Who a request is charged to, besides its IP.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Identity {
+    ApiKey(String),
+    Account(AccountId),
+}
+
+impl Identity {
+    /// The key an authenticated request was signed with, or its account.
+    pub fn of(user: &AuthenticatedUser) -> Self {
+        match &user.api_key {
+            Some(key_id) => Identity::ApiKey(key_id.clone()),
+            None => Identity::Account(user.account_id),
+        }
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identity::ApiKey(key) => write!(f, "key:{}", key),
+            Identity::Account(account_id) => write!(f, "account:{}", account_id),
+        }
+    }
+}
+
+/// Outcome of a rate limit check, reported in the response headers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    /// Capacity of the tightest bucket
+    pub limit: u32,
+    /// Whole tokens left in the tightest bucket
+    pub remaining: u32,
+    /// Wait until the request would fit, when denied
+    pub retry_after: Option<Duration>,
+}
+
+impl Decision {
+    /// Combine the checks of two separately charged buckets.
+    pub fn merge(self, other: Decision) -> Decision {
+        let tightest = if other.remaining < self.remaining { other } else { self };
+        Decision {
+            allowed: self.allowed && other.allowed,
+            limit: tightest.limit,
+            remaining: tightest.remaining,
+            retry_after: self.retry_after.max(other.retry_after),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_update: Instant,
+    /// Recency stamp, the key into `BucketCache::recency`
+    touched: u64,
+}
+
+/// Buckets with least-recently-used eviction past `max_keys`.
+struct BucketCache {
+    buckets: HashMap<String, Bucket>,
+    recency: BTreeMap<u64, String>,
+    clock: u64,
+    max_keys: usize,
+}
+
+impl BucketCache {
+    /// Refill and return the tokens in `key`'s bucket, creating it full.
+    fn refill(&mut self, key: &str, limits: BucketLimits, now: Instant) -> f64 {
+        self.clock += 1;
+        let touched = self.clock;
+        let tokens = match self.buckets.get_mut(key) {
+            Some(bucket) => {
+                let elapsed = now.duration_since(bucket.last_update).as_secs_f64();
+                bucket.tokens = f64::min(
+                    limits.capacity as f64,
+                    bucket.tokens + elapsed * limits.refill_per_sec,
+                );
+                bucket.last_update = now;
+                self.recency.remove(&bucket.touched);
+                bucket.touched = touched;
+                bucket.tokens
+            }
+            None => {
+                while self.buckets.len() >= self.max_keys {
+                    let Some((_, idle)) = self.recency.pop_first() else { break };
+                    self.buckets.remove(&idle);
+                }
+                let bucket = Bucket {
+                    tokens: limits.capacity as f64,
+                    last_update: now,
+                    touched,
+                };
+                self.buckets.insert(key.to_string(), bucket);
+                bucket.tokens
+            }
+        };
+        self.recency.insert(touched, key.to_string());
+        tokens
+    }
+
+    fn consume(&mut self, key: &str, tokens: f64) {
+        if let Some(bucket) = self.buckets.get_mut(key) {
+            bucket.tokens -= tokens;
+        }
+    }
+}
+
+/// Token-bucket rate limiter charging each request to its caller and its IP.
 pub struct RateLimiter {
-    // Maps unique keys e.g., "user_id:endpoint" to Bucket
-    buckets: DashMap<String, Bucket>,
+    config: RateLimitConfig,
+    buckets: Mutex<BucketCache>,
+    tiers: DashMap<Identity, Tier>,
 }
 
 impl RateLimiter {
-    pub fn new() -> Self {
+    pub fn new(config: RateLimitConfig) -> Self {
         Self {
-            buckets: DashMap::new(),
+            buckets: Mutex::new(BucketCache {
+                buckets: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+                max_keys: config.max_keys,
+            }),
+            config,
+            tiers: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Assign a tier; takes effect on the caller's next request.
+    ///
+    /// Tiers are only ever assigned by the venue, never read from the
+    /// credentials a request carries.
+    pub fn set_tier(&self, identity: Identity, tier: Tier) {
+        self.tiers.insert(identity, tier);
+    }
+
+    /// Assign account tiers from the JSON object at `path`, mapping account
+    /// ids to tiers (e.g. `{"<account id>": "VIP"}`). Returns how many were read.
+    pub fn load_account_tiers(&self, path: &Path) -> Result<usize, anyhow::Error> {
+        let tiers: HashMap<AccountId, Tier> = serde_json::from_slice(&std::fs::read(path)?)?;
+        for (account_id, tier) in &tiers {
+            self.set_tier(Identity::Account(*account_id), *tier);
+        }
+        Ok(tiers.len())
+    }
+
+    pub fn tier(&self, identity: &Identity) -> Tier {
+        self.tiers.get(identity).map(|t| *t).unwrap_or_default()
+    }
+
+    /// Charge `weight` tokens to the caller's bucket and the IP's bucket.
+    ///
+    /// Tokens are taken only if both buckets can pay.
+    pub fn check(&self, identity: Option<&Identity>, ip: Option<IpAddr>, weight: u32) -> Decision {
+        let mut charged = Vec::with_capacity(2);
+        if let Some(identity) = identity {
+            charged.push((identity.to_string(), self.config.limits(self.tier(identity))));
+        }
+        if let Some(ip) = ip {
+            charged.push((format!("ip:{}", ip), self.config.per_ip));
+        }
+
+        let now = Instant::now();
+        let weight = weight as f64;
+        let mut cache = self.buckets.lock().unwrap();
+        let tokens: Vec<f64> = charged
+            .iter()
+            .map(|(key, limits)| cache.refill(key, *limits, now))
+            .collect();
+        let allowed = tokens.iter().all(|t| *t >= weight);
+        if allowed {
+            for (key, _) in &charged {
+                cache.consume(key, weight);
+            }
+        }
+
+        let mut decision = Decision {
+            allowed,
+            limit: u32::MAX,
+            remaining: u32::MAX,
+            retry_after: None,
+        };
+        for ((_, limits), tokens) in charged.iter().zip(tokens) {
+            let left = if allowed { tokens - weight } else { tokens };
+            if left.floor() < decision.remaining as f64 {
+                decision.remaining = left.max(0.0).floor() as u32;
+                decision.limit = limits.capacity;
+            }
+            if !allowed && tokens < weight && limits.refill_per_sec > 0.0 {
+                let wait = Duration::from_secs_f64((weight - tokens) / limits.refill_per_sec);
+                decision.retry_after = decision.retry_after.max(Some(wait));
+            }
+        }
+        decision
+    }
+}
+
+fn route_weight(state: &AppState, request: &Request) -> (String, u32) {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let weight = state.rate_limiter.config().weights.weight(request.method(), &path);
+    (path, weight)
+}
+
+fn rejection(state: &AppState, request: &Request, path: &str, weight: u32) -> Response {
+    state.metrics.rate_limit_rejections.inc(&[path]);
+    AppError::RateLimitExceeded(format!("{} {} costs {} token(s)", request.method(), path, weight))
+        .into_response()
+}
+
+/// Middleware charging each request its endpoint's weight to the peer IP.
+///
+/// It runs before authentication, so it charges nothing to the key or
+/// account a request names: anyone can send another caller's key id.
+/// [`rate_limit_caller`] charges the caller once the credentials are
+/// verified, and the tighter of the two buckets is reported in the
+/// response headers. The peer address is used; forwarding headers are not
+/// trusted.
+pub async fn rate_limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let (path, weight) = route_weight(&state, &request);
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    let mut decision = state.rate_limiter.check(None, ip, weight);
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        rejection(&state, &request, &path, weight)
+    };
+    if let Some(caller) = response.extensions_mut().remove::<Decision>() {
+        decision = decision.merge(caller);
+    }
+
+    let headers = response.headers_mut();
+    if decision.limit != u32::MAX {
+        headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(decision.limit));
+        headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(decision.remaining));
+    }
+    if let Some(wait) = decision.retry_after {
+        headers.insert(RETRY_AFTER_HEADER, HeaderValue::from(wait.as_secs_f64().ceil() as u64));
+    }
+    response
+}
+
+/// Middleware charging authenticated requests to their key or account.
+///
+/// Layered inside `api_key_auth`, so API keys are charged only once their
+/// signature has been checked; the bucket size follows the tier assigned
+/// with [`RateLimiter::set_tier`]. Requests that do not authenticate are
+/// left to the handler, having been charged to their IP alone.
+pub async fn rate_limit_caller(
+    State(state): State<AppState>,
+    user: Result<AuthenticatedUser, AppError>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(user) = user else {
+        return next.run(request).await;
+    };
+    let (path, weight) = route_weight(&state, &request);
+    let decision = state.rate_limiter.check(Some(&Identity::of(&user)), None, weight);
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        rejection(&state, &request, &path, weight)
+    };
+    response.extensions_mut().insert(decision);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::{signed_headers, Permission};
+    use crate::router::create_router;
+    use axum::{body::Body, http::StatusCode};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            standard: BucketLimits::new(100, 20.0),
+            vip: BucketLimits::new(500, 100.0),
+            per_ip: BucketLimits::new(1_000, 100.0),
+            ..RateLimitConfig::default()
         }
     }
 
-    pub fn check_rate_limit(&self, key: &str, capacity: u32, refill_rate: f64) -> Result<(), AppError> {
-        let mut bucket = self.buckets.entry(key.to_string()).or_insert_with(|| {
-            Bucket::new(capacity, refill_rate)
+    fn key(name: &str) -> Identity {
+        Identity::ApiKey(name.to_string())
+    }
+
+    /// Fire `count` requests of `weight` and count how many were allowed.
+    fn burst(limiter: &RateLimiter, identity: &Identity, weight: u32, count: usize) -> usize {
+        (0..count)
+            .filter(|_| limiter.check(Some(identity), None, weight).allowed)
+            .count()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_across_two_tiers() {
+        let limiter = RateLimiter::new(config());
+        let (standard, vip) = (key("standard"), key("vip"));
+        limiter.set_tier(vip.clone(), Tier::Vip);
+
+        // Order placement costs 5: 100 / 5 and 500 / 5 fit in a burst
+        assert_eq!(burst(&limiter, &standard, 5, 150), 20);
+        assert_eq!(burst(&limiter, &vip, 5, 150), 100);
+
+        // One second refills 20 and 100 tokens
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(burst(&limiter, &standard, 5, 150), 4);
+        assert_eq!(burst(&limiter, &vip, 5, 150), 20);
+
+        // Refills never exceed capacity
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(burst(&limiter, &standard, 1, 150), 100);
+        assert_eq!(burst(&limiter, &vip, 1, 1_000), 500);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_weights_share_one_bucket_and_report_retry_after() {
+        let limiter = RateLimiter::new(config());
+        let trader = key("trader");
+        assert_eq!(burst(&limiter, &trader, 5, 19), 19);
+
+        // 5 tokens left: cheap queries still fit, another order does not
+        let decision = limiter.check(Some(&trader), None, 1);
+        assert_eq!((decision.allowed, decision.limit, decision.remaining), (true, 100, 4));
+        let denied = limiter.check(Some(&trader), None, 5);
+        assert!(!denied.allowed);
+        assert_eq!(denied.remaining, 4);
+        assert_eq!(denied.retry_after, Some(Duration::from_millis(50)));
+
+        tokio::time::advance(Duration::from_millis(50)).await;
+        assert!(limiter.check(Some(&trader), None, 5).allowed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ip_bucket_caps_every_caller_behind_it() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            per_ip: BucketLimits::new(30, 1.0),
+            ..config()
+        });
+        let ip: IpAddr = "10.0.0.7".parse().unwrap();
+        let allowed = (0..20)
+            .filter(|i| limiter.check(Some(&key(&format!("k{i}"))), Some(ip), 5).allowed)
+            .count();
+        assert_eq!(allowed, 6);
+
+        // A denial charges neither bucket
+        let fresh = key("fresh");
+        assert!(!limiter.check(Some(&fresh), Some(ip), 5).allowed);
+        assert_eq!(burst(&limiter, &fresh, 5, 50), 20);
+
+        // Unauthenticated requests are charged to the IP alone
+        let other: IpAddr = "10.0.0.8".parse().unwrap();
+        let allowed = (0..20).filter(|_| limiter.check(None, Some(other), 5).allowed).count();
+        assert_eq!(allowed, 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_keys_are_evicted_first() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_keys: 2,
+            ..config()
         });
+        let (a, b, c) = (key("a"), key("b"), key("c"));
+        assert_eq!(burst(&limiter, &a, 50, 3), 2);
+        assert_eq!(burst(&limiter, &b, 50, 3), 2);
+        // `a` is touched again, so `b` is the idle one evicted for `c`
+        assert!(!limiter.check(Some(&a), None, 50).allowed);
+        assert!(limiter.check(Some(&c), None, 50).allowed);
+        assert_eq!(limiter.buckets.lock().unwrap().buckets.len(), 2);
+
+        assert!(!limiter.check(Some(&a), None, 50).allowed);
+        assert_eq!(burst(&limiter, &b, 50, 3), 2, "evicted bucket starts full");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_forged_credentials_charge_only_the_ip() {
+        let mut state = AppState::new("http://127.0.0.1:1".into());
+        state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            standard: BucketLimits::new(12, 0.0),
+            ..config()
+        }));
+        let key = state.api_keys.create(AccountId::new(), [Permission::Read].into(), false, 0).unwrap();
+        let victim = AccountId::new();
+        let tiers = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tiers.path(), serde_json::json!({ victim.to_string(): "VIP" }).to_string()).unwrap();
+        assert_eq!(state.rate_limiter.load_account_tiers(tiers.path()).unwrap(), 1);
+        let app = create_router(state.clone());
+        let get = |headers: Vec<(&str, String)>| {
+            let mut request = Request::builder().uri("/v1/markets/BTC/USDT");
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
 
-        if bucket.allow_request(1) {
-            Ok(())
-        } else {
-            Err(AppError::RateLimitExceeded(format!("Rate limit for {}", key)))
+        // Junk signatures under the market maker's key id are refused unbilled
+        for _ in 0..20 {
+            let junk = vec![
+                ("X-API-Key", key.key_id.clone()),
+                ("X-Timestamp", "1".to_string()),
+                ("X-Signature", "00".to_string()),
+            ];
+            assert_eq!(get(junk).await.unwrap().status(), StatusCode::UNAUTHORIZED);
         }
+        let signed = signed_headers(&key, "GET", "/v1/markets/BTC/USDT", b"");
+        let response = get(signed.into_iter().collect()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "11");
+
+        // An unsigned token naming the victim, claiming a tier, is not charged to them
+        let claims = serde_json::json!({
+            "sub": "trader",
+            "exp": 4_102_444_800u64,
+            "account_id": victim,
+            "tier": "INSTITUTIONAL",
+        });
+        let forged = jsonwebtoken::EncodingKey::from_secret(b"guessed");
+        let token = jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &forged).unwrap();
+        let response = get(vec![("Authorization", format!("Bearer {}", token))]).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // No peer address in these requests, so no bucket was charged at all
+        assert!(!response.headers().contains_key(RATE_LIMIT_LIMIT_HEADER));
+        let victim = Identity::Account(victim);
+        assert_eq!(state.rate_limiter.tier(&victim), Tier::Vip);
+        assert_eq!(state.rate_limiter.check(Some(&victim), None, 0).remaining, 500);
+
+        // Signed with the venue's secret, the tier claim is still ignored
+        let secret = jsonwebtoken::EncodingKey::from_secret(b"secret");
+        let token = jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &secret).unwrap();
+        let response = get(vec![("Authorization", format!("Bearer {}", token))]).await.unwrap();
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT_HEADER], "500");
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "499");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_middleware_sets_headers_and_returns_429() {
+        let mut state = AppState::new("http://127.0.0.1:1".into());
+        state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            standard: BucketLimits::new(12, 2.0),
+            ..config()
+        }));
+        let all = [Permission::Read, Permission::Trade, Permission::Withdraw];
+        let key = state.api_keys.create(AccountId::new(), all.into(), false, 0).unwrap();
+        let app = create_router(state);
+        let request = |method: &str, uri: &str| {
+            let mut request = Request::builder().method(method).uri(uri);
+            for (name, value) in signed_headers(&key, method, uri, b"{}") {
+                request = request.header(name, value);
+            }
+            let mut request = request
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+            request
+        };
+
+        let response = app.clone().oneshot(request("GET", "/v1/markets/BTC/USDT")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT_HEADER], "12");
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "11");
+
+        // Orders cost 5: two fit (rejected past the limiter as invalid)
+        for remaining in ["6", "1"] {
+            let response = app.clone().oneshot(request("POST", "/v1/orders")).await.unwrap();
+            assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], remaining);
+        }
+        let response = app.clone().oneshot(request("POST", "/v1/orders")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "1");
+        assert_eq!(response.headers()[RETRY_AFTER_HEADER], "2");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "RATE_LIMIT_EXCEEDED");
+        assert_eq!(body["message"], "POST /v1/orders costs 5 token(s)");
+
+        // A query still fits in the last token
+        let response = app.oneshot(request("GET", "/v1/markets/BTC/USDT")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "0");
     }
 }
@@ -0,0 +1,236 @@
+use crate::engine_client::{correlation_id, CORRELATION_ID_HEADER};
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+
+/// Header carrying the id of a request, accepted from callers and echoed
+/// on every response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Middleware giving every request an id before anything else runs.
+///
+/// A valid `X-Request-Id` is kept, then a valid `X-Correlation-Id`;
+/// otherwise a UUID v7 is generated. The id replaces both headers on the
+/// request, so handlers forward the same id downstream, where the engines
+/// journal it as the events' correlation id.
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let id = correlation_id(request.headers());
+    // Accepted and generated ids are visible ASCII
+    let value = HeaderValue::from_str(&id).expect("request ids are valid header values");
+    request.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+    request.headers_mut().insert(CORRELATION_ID_HEADER, value.clone());
+
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    response
+}
+
+/// Tracing span of a request, tagged with its id.
+pub fn request_span<B>(request: &axum::http::Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::REQUEST_ID_HEADER;
+    use crate::auth::test_token;
+    use crate::engine_client::{OrderAck, CORRELATION_ID_HEADER};
+    use crate::models::PlaceOrderRequest;
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{HeaderMap, Request, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use matching_engine::engine::{MatchingEngine, SubmitResult};
+    use matching_engine::events::{BookEvent, TradeExecutedEvent};
+    use matching_engine::restore::{correlated_journal_entry, decode_correlated, register_export_decoders};
+    use persistence::export::{export_jsonl_with, ExportFilter, ExportOptions, ExportRecord};
+    use persistence::journal::{JournalConfig, JournalWriter};
+    use persistence::reader::JournalReader;
+    use serde_json::json;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+    use types::ids::AccountId;
+    use types::order::Order;
+
+    /// Engine stand-in that matches orders and journals the resulting book
+    /// events under the request id the gateway forwarded.
+    struct Engine {
+        book: MatchingEngine,
+        journal: JournalWriter,
+        clock: i64,
+    }
+
+    impl Engine {
+        fn journal(&mut self, event: &BookEvent, request_id: &str) {
+            let sequence = self.journal.next_sequence();
+            let entry = correlated_journal_entry(sequence, self.clock, event, Some(request_id));
+            self.journal.append(&entry).unwrap();
+        }
+    }
+
+    async fn engine_orders(
+        State(engine): State<Arc<Mutex<Engine>>>,
+        headers: HeaderMap,
+        Json(request): Json<PlaceOrderRequest>,
+    ) -> Json<OrderAck> {
+        let request_id = headers[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let mut engine = engine.lock().unwrap();
+        engine.clock += 1;
+        let order = Order::new(
+            request.account_id,
+            request.symbol,
+            request.side,
+            request.price.unwrap(),
+            request.quantity,
+            request.time_in_force,
+            engine.clock,
+        );
+        let (order_id, accepted_at) = (order.order_id, order.created_at);
+        let (symbol, side, price, quantity) = (order.symbol.to_string(), order.side, order.price, order.quantity);
+        let result = engine.book.submit_order(order, accepted_at).unwrap();
+        for trade in result.trades() {
+            engine.journal(&BookEvent::TradeExecuted(TradeExecutedEvent::from_trade(trade)), &request_id);
+        }
+        if matches!(result, SubmitResult::Resting) {
+            let accepted = BookEvent::OrderAccepted {
+                order_id,
+                account_id: request.account_id,
+                symbol,
+                side,
+                price,
+                quantity,
+                accepted_at,
+            };
+            engine.journal(&accepted, &request_id);
+        }
+        engine.journal.sync().unwrap();
+        Json(OrderAck {
+            order_id,
+            status: "NEW".into(),
+        })
+    }
+
+    async fn spawn_engine(journal_dir: &Path) -> String {
+        let mut journal = JournalWriter::open(JournalConfig::new(journal_dir)).unwrap();
+        journal.set_next_sequence(1);
+        let engine = Engine {
+            book: MatchingEngine::new(1),
+            journal,
+            clock: 0,
+        };
+        let app = Router::new()
+            .route("/internal/orders", post(engine_orders))
+            .with_state(Arc::new(Mutex::new(engine)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    fn place(account_id: AccountId, side: &str, request_id: Option<(&str, &str)>) -> Request<Body> {
+        let token = test_token(account_id);
+        let order = json!({
+            "account_id": account_id.to_string(),
+            "symbol": "BTC/USDT",
+            "side": side,
+            "order_type": "LIMIT",
+            "price": "100",
+            "quantity": "1",
+            "time_in_force": "GTC"
+        });
+        let mut request = Request::post("/v1/orders")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", token));
+        if let Some((header, id)) = request_id {
+            request = request.header(header, id);
+        }
+        request.body(Body::from(order.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_id_reaches_journaled_events() {
+        let tmp = tempfile::tempdir().unwrap();
+        let journal_dir = tmp.path().join("journal");
+        let state = AppState::new(spawn_engine(&journal_dir).await);
+
+        let maker = place(AccountId::new(), "SELL", Some((CORRELATION_ID_HEADER, "maker-1")));
+        let response = create_router(state.clone()).oneshot(maker).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "maker-1");
+
+        let taker = place(AccountId::new(), "BUY", Some((REQUEST_ID_HEADER, "fill-7f3a")));
+        let response = create_router(state.clone()).oneshot(taker).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "fill-7f3a");
+        assert_eq!(response.headers()[CORRELATION_ID_HEADER], "fill-7f3a");
+
+        let entries = JournalReader::open(&journal_dir).unwrap().read_all().unwrap();
+        let tagged: Vec<(String, Option<String>)> = entries
+            .iter()
+            .map(|entry| {
+                let event = decode_correlated(entry).unwrap().unwrap();
+                (entry.event_type.clone(), event.correlation_id)
+            })
+            .collect();
+        assert_eq!(
+            tagged,
+            [
+                ("OrderAccepted".to_string(), Some("maker-1".to_string())),
+                ("TradeExecuted.v2".to_string(), Some("fill-7f3a".to_string())),
+            ]
+        );
+
+        // The export selects the fill by its request id
+        let mut options = ExportOptions::default();
+        register_export_decoders(&mut options.decoders);
+        let filter = ExportFilter {
+            correlation_id: Some("fill-7f3a".into()),
+            ..ExportFilter::default()
+        };
+        let out = tmp.path().join("fill.jsonl");
+        let report = export_jsonl_with(&journal_dir, &out, &filter, &options).unwrap();
+        assert_eq!(report.exported, 1);
+        let line = std::fs::read_to_string(&out).unwrap();
+        let record: ExportRecord = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(record.event_type.as_deref(), Some("TradeExecuted.v2"));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated_when_absent() {
+        let state = AppState::new("http://127.0.0.1:1".into());
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+
+        // Unusable ids are replaced rather than propagated
+        let oversized = "x".repeat(65);
+        let request = Request::get("/health")
+            .header(REQUEST_ID_HEADER, oversized.as_str())
+            .body(Body::empty())
+            .unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        assert_ne!(response.headers()[REQUEST_ID_HEADER], oversized.as_str());
+    }
+}
@@ -1,7 +1,12 @@
-use crate::handlers::{account, order, ws};
+use crate::auth::api_key_auth;
+use crate::handlers::{account, api_key, health, history, market, metrics, order, withdrawal, ws};
+use crate::metrics::track_requests;
+use crate::request_id::{assign_request_id, request_span};
 use crate::state::AppState;
+use crate::rate_limit::{rate_limit, rate_limit_caller};
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     Router,
 };
 use tower_http::cors::CorsLayer;
@@ -9,14 +14,35 @@ use tower_http::trace::TraceLayer;
 
 pub fn create_router(state: AppState) -> Router {
     let api_routes = Router::new()
-        .route("/orders", post(order::create_order))
-        .route("/orders/:id", get(order::get_order).delete(order::cancel_order))
-        .route("/accounts/:id", get(account::get_account))
-        .route("/ws", get(ws::ws_handler));
+        .route("/orders", post(order::place_order).get(history::list_orders))
+        .route("/orders/batch", post(order::place_batch))
+        .route("/orders/{id}", get(order::get_order).delete(order::cancel_order))
+        .route("/trades", get(history::list_trades))
+        .route("/accounts/{id}", get(account::get_account))
+        .route("/withdrawals", post(withdrawal::submit_withdrawal))
+        .route("/markets/{base}/{quote}", get(market::get_market))
+        .route("/ws", get(ws::ws_handler))
+        .route("/ws/user", get(ws::user_ws_handler))
+        .route("/api-keys", post(api_key::create_api_key).get(api_key::list_api_keys))
+        .route("/api-keys/{id}", delete(api_key::revoke_api_key))
+        // IPs are charged before signatures are checked, so bad keys are throttled
+        // too; a key or account is charged only once its credentials verify
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit_caller))
+        .route_layer(middleware::from_fn_with_state(state.clone(), api_key_auth))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit));
 
+    // Probes and metrics stay outside the API: no auth and no rate limit
     Router::new()
+        .route("/health", get(health::health))
+        .route("/ready", get(health::ready))
+        .route("/status", get(health::status))
         .nest("/v1", api_routes)
+        .route_layer(middleware::from_fn_with_state(state.clone(), track_requests))
+        // Added after the tracking layer, so scrapes do not count themselves
+        .route("/metrics", get(metrics::metrics))
         .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        // Outermost, so the trace span and every handler see the id
+        .layer(middleware::from_fn(assign_request_id))
         .with_state(state)
 }
@@ -0,0 +1,146 @@
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use shutdown_core::{ShutdownCoordinator, ShutdownReport};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+
+/// How long shutdown waits for in-flight requests and WebSocket sessions.
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Middleware holding off shutdown while a request is in flight.
+async fn track_in_flight(
+    State(coordinator): State<ShutdownCoordinator>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let _in_flight = coordinator.register(format!("{} {}", request.method(), request.uri().path()));
+    next.run(request).await
+}
+
+/// Serve `app` until `coordinator` is triggered, then drain.
+///
+/// The listener closes as soon as shutdown starts, so new connections are
+/// refused. In-flight requests, tracked for every route of `app`, and
+/// WebSocket sessions, which register themselves, get until `deadline`
+/// to finish; the coordinator's hooks run after them. Idle keep-alive
+/// connections are closed once the last response is written.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    coordinator: ShutdownCoordinator,
+    deadline: Duration,
+) -> std::io::Result<ShutdownReport> {
+    let signal = coordinator.triggered();
+    let app = app.layer(middleware::from_fn_with_state(coordinator.clone(), track_in_flight));
+    let mut server = tokio::spawn(async move {
+        // Peer addresses feed the per-IP rate limit
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(signal)
+            .await
+    });
+
+    tokio::select! {
+        result = &mut server => {
+            // The server stopped on its own: an accept error
+            result.map_err(std::io::Error::other)??;
+        }
+        _ = coordinator.triggered() => {}
+    }
+    let started = Instant::now();
+    let report = coordinator.drain(deadline).await;
+
+    // Let the last responses reach the wire before dropping connections
+    let remaining = deadline.saturating_sub(started.elapsed());
+    if tokio::time::timeout(remaining, &mut server).await.is_err() {
+        server.abort();
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::create_router;
+    use crate::state::AppState;
+    use axum::routing::get;
+    use persistence::async_writer::{AsyncJournalWriter, AsyncWriterConfig};
+    use persistence::journal::{JournalConfig, JournalEntry, JournalWriter};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Raw HTTP/1.1 request, so the test controls when the connection opens.
+    async fn http_get(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(addr).await?;
+        let request = format!("GET {} HTTP/1.1\r\nhost: gateway\r\nconnection: close\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_finishes_in_flight_requests_and_refuses_new_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut writer = JournalWriter::open(JournalConfig::new(tmp.path())).unwrap();
+        writer.set_next_sequence(1);
+        let journal = Arc::new(std::sync::Mutex::new(Some(AsyncJournalWriter::spawn(
+            writer,
+            AsyncWriterConfig::default(),
+        ))));
+
+        let state = AppState::new("http://127.0.0.1:1".into());
+        let coordinator = state.shutdown.clone();
+        // The slow handler journals as it finishes, before the final sync
+        let slow_journal = Arc::clone(&journal);
+        let app = create_router(state).route(
+            "/slow",
+            get(move || async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                let entry = JournalEntry::new(1, 1, "OrderAccepted".into(), vec![1]);
+                slow_journal.lock().unwrap().as_ref().unwrap().submit(entry).unwrap();
+                "done"
+            }),
+        );
+        let hook_journal = Arc::clone(&journal);
+        coordinator.on_shutdown("journal", move || {
+            let writer = hook_journal.lock().unwrap().take().expect("synced once");
+            let durable = writer.shutdown().map_err(|e| e.to_string())?;
+            Ok(format!("durable through sequence {}", durable))
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(listener, app, coordinator.clone(), Duration::from_secs(5)));
+
+        let in_flight = tokio::spawn(http_get(addr, "/slow"));
+        while coordinator.active().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        coordinator.trigger();
+
+        // The listener closes while the slow request is still running
+        let refused = async {
+            while TcpStream::connect(addr).await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), refused).await.unwrap();
+        assert!(!in_flight.is_finished());
+
+        let response = in_flight.await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("done"));
+
+        let report = server.await.unwrap().unwrap();
+        assert!(report.is_clean(), "{}", report);
+        assert_eq!(report.to_string(), "drained; journal: durable through sequence 1");
+        assert!(http_get(addr, "/health").await.is_err());
+    }
+}
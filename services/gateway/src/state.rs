@@ -1,20 +1,541 @@
-use crate::rate_limit::RateLimiter;
-use reqwest::Client;
-use std::sync::Arc;
+use crate::api_keys::ApiKeyStore;
+use crate::circuit_breaker::{BreakerConfig, BreakerOpen, BreakerStatus, CircuitBreaker};
+use crate::error::AppError;
+use crate::cancel_on_disconnect::{CancelOnDisconnect, DEFAULT_GRACE};
+use crate::engine_client::{MatchingEngineClient, OrderAck};
+use crate::health::{Dependency, HealthCheckConfig, HealthRegistry};
+use crate::idempotency::{IdempotencyStore, DEFAULT_CAPACITY, DEFAULT_TTL};
+use crate::metrics::GatewayMetrics;
+use crate::models::{MarketRules, WithdrawalResponse};
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::user_events::{UserEventHub, DEFAULT_BUFFER};
+use persistence::history::HistoryIndex;
+use dashmap::DashMap;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use shutdown_core::ShutdownCoordinator;
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use types::errors::EngineError;
+use types::market::MarketStatus;
 
 #[derive(Clone)]
 pub struct AppState {
     pub rate_limiter: Arc<RateLimiter>,
     pub http_client: Client,
-    pub internal_services_url: String, // Mock base URL for the internal dummy gRPC/HTTP endpoints
+    pub internal: InternalClient, // Breaker-guarded calls to the internal services
+    pub internal_services_url: String, // Base URL of the internal order service endpoints
+    pub engine: MatchingEngineClient,
+    pub order_idempotency: Arc<IdempotencyStore<Result<OrderAck, EngineError>>>, // Engine outcome per account and key
+    pub withdrawal_idempotency: Arc<IdempotencyStore<WithdrawalResponse>>,
+    pub user_events: Arc<UserEventHub>, // Private account events for `/ws/user` connections
+    pub history: Arc<RwLock<HistoryIndex>>, // Order and trade history, maintained from the user event feed
+    pub health: Arc<HealthRegistry>, // Cached downstream checks behind `/ready` and `/status`
+    pub api_keys: Arc<ApiKeyStore>, // HMAC keys accepted by `api_key_auth`
+    pub cancel_on_disconnect: Arc<CancelOnDisconnect>, // Triggers for protected `/ws/user` sessions
+    pub metrics: Arc<GatewayMetrics>, // Counters and histograms behind `/metrics`
+    pub shutdown: ShutdownCoordinator, // WebSocket sessions close with a reconnect hint when triggered
+    pub market_rules: Arc<HashMap<String, MarketRules>>, // Per-symbol precision and increments; unlisted symbols use the default
+    pub market_status: Arc<DashMap<String, MarketStatus>>, // Mirrored from MarketStatusChanged; unlisted symbols are TRADING
 }
 
 impl AppState {
     pub fn new(service_url: String) -> Self {
+        let http_client = Client::new();
+        let internal = InternalClient::new(http_client.clone(), InternalClientConfig::default());
+        let engine = MatchingEngineClient::new(internal.clone(), service_url.clone());
+        let user_events = Arc::new(UserEventHub::new(DEFAULT_BUFFER));
         Self {
-            rate_limiter: Arc::new(RateLimiter::new()),
-            http_client: Client::new(),
+            rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            cancel_on_disconnect: Arc::new(CancelOnDisconnect::new(
+                engine.clone(),
+                user_events.clone(),
+                DEFAULT_GRACE,
+            )),
+            engine,
+            order_idempotency: Arc::new(IdempotencyStore::new(DEFAULT_CAPACITY, DEFAULT_TTL)),
+            withdrawal_idempotency: Arc::new(IdempotencyStore::new(DEFAULT_CAPACITY, DEFAULT_TTL)),
+            user_events,
+            history: Arc::new(RwLock::new(HistoryIndex::default())),
+            health: Arc::new(HealthRegistry::new(
+                vec![Dependency::new("matching-engine", service_url.clone())],
+                HealthCheckConfig::default(),
+            )),
+            api_keys: Arc::new(ApiKeyStore::default()),
+            metrics: Arc::new(GatewayMetrics::new()),
+            shutdown: ShutdownCoordinator::new(),
+            http_client,
+            internal,
             internal_services_url: service_url,
+            market_rules: Arc::new(HashMap::new()),
+            market_status: Arc::new(DashMap::new()),
         }
     }
+
+    /// Precision rules for a market symbol.
+    pub fn market_rules(&self, symbol: &str) -> MarketRules {
+        self.market_rules.get(symbol).copied().unwrap_or_default()
+    }
+
+    /// Last known trading status for a market symbol.
+    pub fn market_status(&self, symbol: &str) -> MarketStatus {
+        self.market_status
+            .get(symbol)
+            .map(|s| *s)
+            .unwrap_or(MarketStatus::Trading)
+    }
+}
+
+/// Dependency name of the matching engine's internal API.
+pub const MATCHING_ENGINE: &str = "matching-engine";
+/// Dependency name of the internal account endpoints.
+pub const ACCOUNT_SERVICE: &str = "account-service";
+/// Dependency name of the internal withdrawal endpoints.
+pub const CUSTODY_SERVICE: &str = "custody-service";
+
+/// Bounded retries with full-jitter exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first included
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (from 1): uniform up to the
+    /// doubled, capped delay, so retrying callers spread out.
+    fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self.base_delay.saturating_mul(1 << (retry - 1).min(16)).min(self.max_delay);
+        let mut bytes = [0u8; 8];
+        getrandom::fill(&mut bytes).expect("OS randomness is available");
+        let fraction = u64::from_le_bytes(bytes) as f64 / u64::MAX as f64;
+        ceiling.mul_f64(fraction)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Settings shared by every dependency of an [`InternalClient`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InternalClientConfig {
+    pub breaker: BreakerConfig,
+    pub retry: RetryPolicy,
+    /// Longest a single attempt may take before it is abandoned
+    pub timeout: Duration,
+}
+
+impl Default for InternalClientConfig {
+    fn default() -> Self {
+        Self {
+            breaker: BreakerConfig::default(),
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Marks a request that may be sent again: reads and cancels.
+pub struct Idempotent;
+/// Marks a request that must be sent at most once, such as an order placement.
+pub struct NonIdempotent;
+
+/// Failure of an internal call.
+#[derive(Debug)]
+pub enum CallError {
+    /// The dependency's breaker is open; nothing was sent
+    Open { dependency: &'static str, retry_after: Duration },
+    /// The request failed or timed out
+    Transport(reqwest::Error),
+}
+
+impl CallError {
+    /// Surface as [`AppError::CircuitOpen`], or as unavailability of `service`.
+    pub fn unavailable(self, service: &str) -> AppError {
+        match self {
+            CallError::Open { dependency, retry_after } => AppError::CircuitOpen { dependency, retry_after },
+            CallError::Transport(e) => AppError::ServiceUnavailable(format!("{} error: {}", service, e)),
+        }
+    }
+
+    /// Like [`unavailable`](Self::unavailable), except that a request lost
+    /// after it was sent is [`AppError::OutcomeUnknown`]: the dependency may
+    /// have acted on it.
+    pub fn unanswered(self, service: &str) -> AppError {
+        match self {
+            CallError::Transport(e) if !e.is_connect() => {
+                AppError::OutcomeUnknown(format!("{} did not answer: {}", service, e))
+            }
+            e => e.unavailable(service),
+        }
+    }
+}
+
+/// HTTP client for the internal services, with a circuit breaker per
+/// dependency.
+///
+/// A slow or failing dependency opens its breaker, and calls to it then
+/// fail fast with [`CallError::Open`] rather than waiting out a timeout.
+/// Retries are only possible for requests built as [`Idempotent`]: `GET`
+/// and `DELETE`. `POST` and `PATCH` requests have no retrying send, so an
+/// order is never placed twice by the gateway.
+#[derive(Clone)]
+pub struct InternalClient {
+    http_client: Client,
+    config: InternalClientConfig,
+    breakers: Arc<Mutex<BTreeMap<&'static str, Arc<CircuitBreaker>>>>,
+}
+
+impl InternalClient {
+    pub fn new(http_client: Client, config: InternalClientConfig) -> Self {
+        let client = Self {
+            http_client,
+            config,
+            breakers: Arc::new(Mutex::new(BTreeMap::new())),
+        };
+        // Reported from the start, before their first call
+        for dependency in [MATCHING_ENGINE, ACCOUNT_SERVICE, CUSTODY_SERVICE] {
+            client.breaker(dependency);
+        }
+        client
+    }
+
+    pub fn get(&self, dependency: &'static str, url: impl AsRef<str>) -> InternalRequest<Idempotent> {
+        self.request(dependency, self.http_client.get(url.as_ref()))
+    }
+
+    pub fn delete(&self, dependency: &'static str, url: impl AsRef<str>) -> InternalRequest<Idempotent> {
+        self.request(dependency, self.http_client.delete(url.as_ref()))
+    }
+
+    pub fn post(&self, dependency: &'static str, url: impl AsRef<str>) -> InternalRequest<NonIdempotent> {
+        self.request(dependency, self.http_client.post(url.as_ref()))
+    }
+
+    pub fn patch(&self, dependency: &'static str, url: impl AsRef<str>) -> InternalRequest<NonIdempotent> {
+        self.request(dependency, self.http_client.patch(url.as_ref()))
+    }
+
+    fn request<K>(&self, dependency: &'static str, builder: RequestBuilder) -> InternalRequest<K> {
+        InternalRequest {
+            builder: builder.timeout(self.config.timeout),
+            breaker: self.breaker(dependency),
+            retry: self.config.retry,
+            kind: PhantomData,
+        }
+    }
+
+    fn breaker(&self, dependency: &'static str) -> Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(dependency)
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(dependency, self.config.breaker)));
+        Arc::clone(breaker)
+    }
+
+    /// State of every dependency's breaker, by name.
+    pub fn breakers(&self) -> Vec<BreakerStatus> {
+        let breakers = self.breakers.lock().unwrap();
+        breakers.values().map(|breaker| breaker.status()).collect()
+    }
+}
+
+impl Default for InternalClient {
+    fn default() -> Self {
+        Self::new(Client::new(), InternalClientConfig::default())
+    }
+}
+
+/// A request to an internal service, sent through its breaker.
+pub struct InternalRequest<K> {
+    builder: RequestBuilder,
+    breaker: Arc<CircuitBreaker>,
+    retry: RetryPolicy,
+    kind: PhantomData<K>,
+}
+
+impl<K> InternalRequest<K> {
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.builder = self.builder.json(body);
+        self
+    }
+
+    /// Send once.
+    ///
+    /// Server errors (5xx) come back as responses, and count against the
+    /// breaker along with transport failures and calls slower than its
+    /// latency threshold.
+    pub async fn send(self) -> Result<Response, CallError> {
+        attempt(&self.breaker, self.builder).await
+    }
+}
+
+impl InternalRequest<Idempotent> {
+    /// Send, retrying transport failures and 502/503/504 responses up to
+    /// the policy's attempt limit while the breaker stays closed.
+    pub async fn send_with_retry(self) -> Result<Response, CallError> {
+        let mut retry = 0;
+        loop {
+            // A body that cannot be replayed gets a single attempt
+            let next = (retry + 1 < self.retry.max_attempts)
+                .then(|| self.builder.try_clone())
+                .flatten();
+            let Some(next) = next else {
+                return attempt(&self.breaker, self.builder).await;
+            };
+            match attempt(&self.breaker, next).await {
+                Err(CallError::Transport(_)) => {}
+                Ok(res) if retryable(res.status()) => {}
+                outcome => return outcome,
+            }
+            retry += 1;
+            self.breaker.record_retry();
+            tokio::time::sleep(self.retry.backoff(retry)).await;
+        }
+    }
+}
+
+fn retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+async fn attempt(breaker: &CircuitBreaker, builder: RequestBuilder) -> Result<Response, CallError> {
+    breaker.try_acquire().map_err(|BreakerOpen { retry_after }| CallError::Open {
+        dependency: breaker.dependency(),
+        retry_after,
+    })?;
+    let started = Instant::now();
+    let result = builder.send().await;
+    let succeeded = matches!(&result, Ok(res) if !res.status().is_server_error());
+    breaker.record(succeeded, started.elapsed());
+    result.map_err(CallError::Transport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::test_token;
+    use crate::circuit_breaker::BreakerState;
+    use crate::rate_limit::RETRY_AFTER_HEADER;
+    use crate::router::create_router;
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{Method, Request},
+        routing::any,
+        Router,
+    };
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+    use types::ids::AccountId;
+
+    /// Downstream stand-in answering 503 to its next `failing` requests and
+    /// 200 after that, recording the method of each.
+    #[derive(Default)]
+    struct Flapping {
+        failing: AtomicU32,
+        hits: Mutex<Vec<Method>>,
+    }
+
+    impl Flapping {
+        fn fail_next(&self, requests: u32) {
+            self.failing.store(requests, Ordering::SeqCst);
+        }
+
+        fn hits(&self) -> Vec<Method> {
+            std::mem::take(&mut *self.hits.lock().unwrap())
+        }
+    }
+
+    async fn flapping(State(downstream): State<Arc<Flapping>>, method: Method) -> axum::http::StatusCode {
+        downstream.hits.lock().unwrap().push(method);
+        let failing = &downstream.failing;
+        match failing.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)) {
+            Ok(_) => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            Err(_) => axum::http::StatusCode::OK,
+        }
+    }
+
+    async fn spawn_downstream() -> (String, Arc<Flapping>) {
+        let downstream = Arc::new(Flapping::default());
+        let app = Router::new()
+            .route("/internal/orders", any(flapping))
+            .route("/internal/orders/{id}", any(flapping))
+            .with_state(downstream.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, downstream)
+    }
+
+    fn config() -> InternalClientConfig {
+        InternalClientConfig {
+            breaker: BreakerConfig {
+                window: 4,
+                min_calls: 4,
+                failure_rate: 0.5,
+                slow_call: Duration::from_secs(1),
+                open_for: Duration::from_millis(200),
+                half_open_probes: 2,
+            },
+            retry: RetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(5),
+            },
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    fn engine_breaker(client: &InternalClient) -> BreakerStatus {
+        client.breakers().into_iter().find(|b| b.dependency == MATCHING_ENGINE).unwrap()
+    }
+
+    fn engine_state(client: &InternalClient) -> BreakerState {
+        engine_breaker(client).state
+    }
+
+    #[tokio::test]
+    async fn test_breaker_follows_a_flapping_dependency() {
+        let (url, downstream) = spawn_downstream().await;
+        let client = InternalClient::new(Client::new(), config());
+        let order = format!("{}/internal/orders/1", url);
+        let status = |res: Result<Response, CallError>| res.map(|res| res.status().as_u16()).ok();
+
+        for _ in 0..4 {
+            assert_eq!(status(client.get(MATCHING_ENGINE, &order).send().await), Some(200));
+        }
+        // Two failures make half of the sliding window
+        downstream.fail_next(u32::MAX);
+        assert_eq!(status(client.get(MATCHING_ENGINE, &order).send().await), Some(503));
+        assert_eq!(engine_state(&client), BreakerState::Closed);
+        assert_eq!(status(client.get(MATCHING_ENGINE, &order).send().await), Some(503));
+        assert_eq!(engine_state(&client), BreakerState::Open);
+        downstream.hits();
+
+        // Open: calls fail fast without reaching the dependency
+        let refused = client.get(MATCHING_ENGINE, &order).send_with_retry().await;
+        assert!(matches!(refused, Err(CallError::Open { dependency: MATCHING_ENGINE, .. })));
+        assert!(downstream.hits().is_empty());
+
+        // Half-open: a failed probe opens it again
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(engine_state(&client), BreakerState::HalfOpen);
+        assert_eq!(status(client.get(MATCHING_ENGINE, &order).send().await), Some(503));
+        assert_eq!(engine_state(&client), BreakerState::Open);
+
+        // Recovered: both probes succeed and the breaker closes
+        downstream.fail_next(0);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        for _ in 0..2 {
+            assert_eq!(status(client.get(MATCHING_ENGINE, &order).send().await), Some(200));
+        }
+        assert_eq!(engine_state(&client), BreakerState::Closed);
+        assert_eq!(downstream.hits().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_only_idempotent_calls_are_retried() {
+        let (url, downstream) = spawn_downstream().await;
+        // A breaker that stays closed throughout
+        let mut config = config();
+        config.breaker.window = 20;
+        config.breaker.failure_rate = 0.9;
+        let client = InternalClient::new(Client::new(), config);
+        let orders = format!("{}/internal/orders", url);
+        let order = format!("{}/internal/orders/1", url);
+
+        // A placement that fails is reported, not sent again
+        downstream.fail_next(1);
+        let res = client.post(MATCHING_ENGINE, &orders).json(&json!({})).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let res = client.patch(MATCHING_ENGINE, &order).json(&json!({})).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(downstream.hits(), [Method::POST, Method::PATCH]);
+
+        // Reads and cancels ride out the blip
+        downstream.fail_next(2);
+        let res = client.get(MATCHING_ENGINE, &order).send_with_retry().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        downstream.fail_next(1);
+        let res = client.delete(MATCHING_ENGINE, &order).json(&json!({})).send_with_retry().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let hits = downstream.hits();
+        assert_eq!(hits, [Method::GET, Method::GET, Method::GET, Method::DELETE, Method::DELETE]);
+
+        // Attempts are bounded
+        downstream.fail_next(u32::MAX);
+        let res = client.get(MATCHING_ENGINE, &order).send_with_retry().await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(downstream.hits().len(), 3);
+        assert_eq!(engine_breaker(&client).retries, 5);
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_fails_fast_with_retry_after() {
+        let (url, downstream) = spawn_downstream().await;
+        let mut state = AppState::new(url.clone());
+        state.internal = InternalClient::new(Client::new(), config());
+        state.engine = MatchingEngineClient::new(state.internal.clone(), url.clone());
+        downstream.fail_next(u32::MAX);
+        for _ in 0..4 {
+            let orders = format!("{}/internal/orders", url);
+            state.internal.post(MATCHING_ENGINE, orders).send().await.unwrap();
+        }
+        downstream.hits();
+
+        let account_id = AccountId::new();
+        let token = test_token(account_id);
+        let order = json!({
+            "account_id": account_id.to_string(),
+            "symbol": "BTC/USDT",
+            "side": "BUY",
+            "order_type": "LIMIT",
+            "price": "100",
+            "quantity": "1",
+            "time_in_force": "GTC"
+        });
+        let request = Request::post("/v1/orders")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::from(order.to_string()))
+            .unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER_HEADER], "1");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "CIRCUIT_OPEN");
+        assert!(downstream.hits().is_empty());
+
+        let request = Request::get("/status").body(Body::empty()).unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: Value = serde_json::from_slice(&bytes).unwrap();
+        let breakers = status["circuit_breakers"].as_array().unwrap();
+        let engine = breakers.iter().find(|b| b["dependency"] == MATCHING_ENGINE).unwrap();
+        assert_eq!(engine["state"], "open");
+        assert_eq!(engine["rejected_calls"], 1);
+        assert!(breakers.iter().any(|b| b["dependency"] == CUSTODY_SERVICE && b["state"] == "closed"));
+    }
 }
@@ -0,0 +1,374 @@
+use crate::cancel_on_disconnect::Trigger;
+use dashmap::DashMap;
+use persistence::history::{Fill, HistoryIndex, OrderRecord};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use types::ids::{AccountId, MarketId, OrderId, TradeId};
+use types::order::{CancelReason, OrderStatus, Side};
+
+/// Events buffered per account before a slow connection starts to lag.
+pub const DEFAULT_BUFFER: usize = 256;
+/// Wait before reconnecting to the downstream event feed.
+const FEED_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Private account event streamed on `GET /v1/ws/user`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserEvent {
+    OrderAccepted {
+        order_id: OrderId,
+        symbol: MarketId,
+        side: Side,
+        /// Limit price (`None` for market orders)
+        price: Option<Decimal>,
+        quantity: Decimal,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_order_id: Option<String>,
+    },
+    OrderFilled {
+        order_id: OrderId,
+        trade_id: TradeId,
+        price: Decimal,
+        quantity: Decimal,
+        /// Quantity still open after this fill
+        remaining: Decimal,
+    },
+    OrderCancelled {
+        order_id: OrderId,
+        remaining: Decimal,
+    },
+    PositionUpdated {
+        symbol: MarketId,
+        /// Signed: negative for shorts
+        size: Decimal,
+        entry_price: Decimal,
+    },
+    BalanceUpdated {
+        asset: String,
+        available: Decimal,
+        locked: Decimal,
+    },
+    /// Cancel-on-disconnect canceled the account's resting orders
+    SessionOrdersCanceled {
+        trigger: Trigger,
+        canceled: Vec<OrderId>,
+    },
+}
+
+/// Control frames sent alongside events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notice {
+    /// The connection is now receiving the account's events
+    Subscribed {
+        account_id: AccountId,
+        /// Whether dropping this connection cancels the account's orders
+        cancel_on_disconnect: bool,
+    },
+    /// The connection fell behind and `dropped` events were skipped
+    Lagged { dropped: u64 },
+}
+
+/// One line of the downstream event feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedRecord {
+    /// Journal sequence of the event
+    pub sequence: u64,
+    /// Unix nanos
+    pub timestamp: i64,
+    pub account_id: AccountId,
+    pub event: UserEvent,
+}
+
+/// Apply a feed record to the order and trade history.
+pub fn record_history(history: &RwLock<HistoryIndex>, record: &FeedRecord) {
+    let mut history = history.write().unwrap();
+    match &record.event {
+        UserEvent::OrderAccepted {
+            order_id,
+            symbol,
+            side,
+            price,
+            quantity,
+            ..
+        } => history.record_order(OrderRecord {
+            sequence: record.sequence,
+            timestamp: record.timestamp,
+            order_id: *order_id,
+            account_id: record.account_id,
+            symbol: symbol.as_str().to_string(),
+            side: *side,
+            price: *price,
+            quantity: *quantity,
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Pending,
+            updated_at: record.timestamp,
+        }),
+        UserEvent::OrderFilled {
+            order_id,
+            trade_id,
+            price,
+            quantity,
+            remaining,
+        } => {
+            history.record_fill(Fill {
+                sequence: record.sequence,
+                timestamp: record.timestamp,
+                order_id: *order_id,
+                trade_id: *trade_id,
+                price: *price,
+                quantity: *quantity,
+                remaining: *remaining,
+            });
+        }
+        // The feed does not say who canceled
+        UserEvent::OrderCancelled { order_id, .. } => {
+            history.record_cancel(*order_id, CancelReason::UserRequested, record.timestamp);
+        }
+        // Each canceled order gets its own `OrderCancelled`
+        UserEvent::PositionUpdated { .. }
+        | UserEvent::BalanceUpdated { .. }
+        | UserEvent::SessionOrdersCanceled { .. } => {}
+    }
+}
+
+/// Fans private events out to every connection of the owning account.
+///
+/// Each account has a bounded buffer; a connection that falls more than
+/// `buffer` events behind skips the oldest ones and is told how many.
+pub struct UserEventHub {
+    channels: DashMap<AccountId, broadcast::Sender<Arc<UserEvent>>>,
+    buffer: usize,
+}
+
+impl UserEventHub {
+    pub fn new(buffer: usize) -> Self {
+        Self {
+            channels: DashMap::new(),
+            buffer,
+        }
+    }
+
+    pub fn subscribe(&self, account_id: AccountId) -> broadcast::Receiver<Arc<UserEvent>> {
+        self.channels
+            .entry(account_id)
+            .or_insert_with(|| broadcast::channel(self.buffer).0)
+            .subscribe()
+    }
+
+    /// Deliver an event to the account's connections; returns how many.
+    pub fn publish(&self, account_id: AccountId, event: UserEvent) -> usize {
+        let delivered = match self.channels.get(&account_id) {
+            Some(sender) => sender.send(Arc::new(event)).unwrap_or(0),
+            None => return 0,
+        };
+        if delivered == 0 {
+            self.release(account_id);
+        }
+        delivered
+    }
+
+    /// Forget the account's channel once its last connection is gone.
+    pub fn release(&self, account_id: AccountId) {
+        self.channels
+            .remove_if(&account_id, |_, sender| sender.receiver_count() == 0);
+    }
+
+    #[cfg(test)]
+    pub fn connections(&self, account_id: AccountId) -> usize {
+        self.channels
+            .get(&account_id)
+            .map_or(0, |sender| sender.receiver_count())
+    }
+}
+
+/// Publish the newline-delimited [`FeedRecord`] stream served by the
+/// downstream services at `{base_url}/internal/user-events`, recording it
+/// in `history` on the way.
+///
+/// Runs forever, reconnecting after errors; malformed lines are skipped.
+pub async fn consume_feed(
+    hub: Arc<UserEventHub>,
+    history: Arc<RwLock<HistoryIndex>>,
+    client: Client,
+    base_url: String,
+) {
+    let url = format!("{}/internal/user-events", base_url);
+    loop {
+        if let Err(e) = read_feed(&hub, &history, &client, &url).await {
+            tracing::warn!("User event feed error: {}", e);
+        }
+        tokio::time::sleep(FEED_RETRY_DELAY).await;
+    }
+}
+
+async fn read_feed(
+    hub: &UserEventHub,
+    history: &RwLock<HistoryIndex>,
+    client: &Client,
+    url: &str,
+) -> Result<(), reqwest::Error> {
+    let mut res = client.get(url).send().await?.error_for_status()?;
+    let mut pending = Vec::new();
+    while let Some(chunk) = res.chunk().await? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            match serde_json::from_slice::<FeedRecord>(&line) {
+                Ok(record) => {
+                    record_history(history, &record);
+                    hub.publish(record.account_id, record.event);
+                }
+                Err(e) => tracing::warn!("Skipping malformed user event: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tokio::net::TcpListener;
+
+    fn balance(available: i64) -> UserEvent {
+        UserEvent::BalanceUpdated {
+            asset: "USDT".into(),
+            available: Decimal::from(available),
+            locked: Decimal::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_reach_only_the_owning_account() {
+        let hub = UserEventHub::new(8);
+        let (alice, bob) = (AccountId::new(), AccountId::new());
+        let mut a1 = hub.subscribe(alice);
+        let mut a2 = hub.subscribe(alice);
+        let mut b = hub.subscribe(bob);
+
+        assert_eq!(hub.publish(alice, balance(1)), 2);
+        assert_eq!(*a1.recv().await.unwrap(), balance(1));
+        assert_eq!(*a2.recv().await.unwrap(), balance(1));
+        assert!(b.try_recv().is_err());
+
+        // Nobody listening: nothing kept around
+        drop(b);
+        assert_eq!(hub.publish(bob, balance(2)), 0);
+        assert_eq!(hub.connections(bob), 0);
+        assert!(!hub.channels.contains_key(&bob));
+    }
+
+    #[tokio::test]
+    async fn test_slow_connection_lags_without_blocking_others() {
+        let hub = UserEventHub::new(4);
+        let account_id = AccountId::new();
+        let mut slow = hub.subscribe(account_id);
+        let mut fast = hub.subscribe(account_id);
+        for i in 0..10 {
+            hub.publish(account_id, balance(i));
+            assert_eq!(*fast.recv().await.unwrap(), balance(i));
+        }
+        assert!(matches!(slow.recv().await, Err(broadcast::error::RecvError::Lagged(6))));
+        assert_eq!(*slow.recv().await.unwrap(), balance(6));
+    }
+
+    #[test]
+    fn test_event_wire_format() {
+        let order_id = OrderId::new();
+        let event = UserEvent::OrderCancelled {
+            order_id,
+            remaining: Decimal::new(25, 1),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"type": "order_cancelled", "order_id": order_id, "remaining": "2.5"})
+        );
+        assert_eq!(
+            serde_json::to_value(Notice::Lagged { dropped: 3 }).unwrap(),
+            serde_json::json!({"type": "lagged", "dropped": 3})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feed_lines_are_published() {
+        let account_id = AccountId::new();
+        let record = |i| {
+            let record = FeedRecord {
+                sequence: i as u64,
+                timestamp: 0,
+                account_id,
+                event: balance(i),
+            };
+            serde_json::to_string(&record).unwrap()
+        };
+        let body = format!("{}\nnot json\n\n{}\n", record(1), record(2));
+        let app = Router::new().route("/internal/user-events", get(move || async move { body }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let hub = Arc::new(UserEventHub::new(8));
+        let mut events = hub.subscribe(account_id);
+        let history = Arc::new(RwLock::new(HistoryIndex::default()));
+        let feed = tokio::spawn(consume_feed(hub.clone(), history, Client::new(), url));
+        assert_eq!(*events.recv().await.unwrap(), balance(1));
+        assert_eq!(*events.recv().await.unwrap(), balance(2));
+        feed.abort();
+    }
+
+    #[test]
+    fn test_feed_maintains_order_and_trade_history() {
+        let history = RwLock::new(HistoryIndex::default());
+        let account_id = AccountId::new();
+        let order_id = OrderId::new();
+        let events = [
+            UserEvent::OrderAccepted {
+                order_id,
+                symbol: MarketId::try_new("BTC/USDT").unwrap(),
+                side: Side::BUY,
+                price: Some(Decimal::from(100)),
+                quantity: Decimal::from(3),
+                client_order_id: None,
+            },
+            UserEvent::OrderFilled {
+                order_id,
+                trade_id: TradeId::new(),
+                price: Decimal::from(100),
+                quantity: Decimal::from(1),
+                remaining: Decimal::from(2),
+            },
+            balance(5),
+            UserEvent::OrderCancelled {
+                order_id,
+                remaining: Decimal::from(2),
+            },
+        ];
+        for (sequence, event) in events.into_iter().enumerate() {
+            let record = FeedRecord {
+                sequence: sequence as u64 + 1,
+                timestamp: 10 * sequence as i64,
+                account_id,
+                event,
+            };
+            record_history(&history, &record);
+        }
+
+        let history = history.read().unwrap();
+        let order = history.order(order_id).unwrap();
+        assert_eq!(order.filled_quantity, Decimal::from(1));
+        assert_eq!(order.status, OrderStatus::Canceled(CancelReason::UserRequested));
+        assert_eq!(order.updated_at, 30);
+        let trades = history.trades(account_id, &Default::default(), None, 10).unwrap();
+        assert_eq!(trades.items.len(), 1);
+        assert_eq!((trades.items[0].sequence, trades.items[0].side), (2, Side::BUY));
+    }
+}
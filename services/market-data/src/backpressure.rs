@@ -21,11 +21,24 @@ pub enum DropPolicy {
     DropOldest,
 }
 
+/// WebSocket frame carrying a serialized message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl From<String> for Frame {
+    fn from(text: String) -> Self {
+        Frame::Text(text)
+    }
+}
+
 /// A queued outbound message for a client.
 #[derive(Debug, Clone)]
 pub struct OutboundMessage {
     /// Serialized message payload.
-    pub payload: String,
+    pub payload: Frame,
     /// Sequence number of the event this message represents.
     pub sequence: u64,
     /// Timestamp when the message was queued.
@@ -288,7 +301,7 @@ mod tests {
 
     fn make_message(seq: u64) -> OutboundMessage {
         OutboundMessage {
-            payload: format!("{{\"seq\":{}}}", seq),
+            payload: format!("{{\"seq\":{}}}", seq).into(),
             sequence: seq,
             queued_at: 1708123456789000000 + (seq as i64 * 1000),
         }
@@ -376,7 +389,6 @@ mod tests {
             adaptive_batch_threshold: 1,
             normal_batch_size: 10,
             stressed_batch_size: 50,
-            ..BackpressureConfig::default()
         };
         let mut mgr = BackpressureManager::new(config);
         mgr.register_client(1);
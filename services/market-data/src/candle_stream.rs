@@ -0,0 +1,267 @@
+//! Candle stream channel with live (unclosed) candle updates
+//!
+//! Publishes the forming candle for each tracked (symbol, timeframe) at a
+//! fixed cadence of exchange time, tagged `is_closed: false`, followed by a
+//! single `is_closed: true` message once the candle boundary is crossed.
+//!
+//! Live and closed messages are read from the same `CandleBuilder`, so a
+//! closed candle always equals the last live update plus any trades that
+//! arrived after it. Each stream carries its own monotonic sequence
+//! (spec §14) so clients can detect gaps on `candles@{symbol}@{timeframe}`.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::ids::MarketId;
+use types::numeric::Price;
+
+use crate::candles::{Candle, CandleBuilder, Timeframe};
+use crate::websocket::Channel;
+
+/// Default live update cadence: 1 second of exchange time (spec §9.3.8).
+pub const DEFAULT_LIVE_CADENCE_NANOS: i64 = 1_000_000_000;
+
+/// A candle message on the candle channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandleUpdate {
+    /// Per-(symbol, timeframe) sequence number, starting at 1.
+    pub sequence: u64,
+    /// Whether this is the final message for the candle.
+    pub is_closed: bool,
+    /// Candle state at the time of the message.
+    pub candle: Candle,
+}
+
+impl CandleUpdate {
+    /// Channel this update is published on.
+    pub fn channel(&self) -> Channel {
+        Channel::Candles {
+            symbol: self.candle.symbol.as_str().to_string(),
+            timeframe: self.candle.timeframe.as_str().to_string(),
+        }
+    }
+}
+
+/// Per-(symbol, timeframe) stream state.
+struct StreamState {
+    builder: CandleBuilder,
+    sequence: u64,
+    /// Earliest exchange time at which the next live update is due.
+    next_live_at: i64,
+}
+
+impl StreamState {
+    fn emit(&mut self, candle: Candle, is_closed: bool) -> CandleUpdate {
+        self.sequence += 1;
+        CandleUpdate {
+            sequence: self.sequence,
+            is_closed,
+            candle,
+        }
+    }
+}
+
+/// Drives candle channel updates for all tracked streams.
+///
+/// Uses BTreeMap so updates are emitted in deterministic order (spec §12).
+pub struct CandleStream {
+    streams: BTreeMap<(String, Timeframe), StreamState>,
+    cadence_nanos: i64,
+    max_history: usize,
+}
+
+impl CandleStream {
+    /// Create a stream publishing live updates every `cadence_nanos`.
+    pub fn new(cadence_nanos: i64, max_history: usize) -> Self {
+        assert!(cadence_nanos > 0, "Cadence must be positive");
+        Self {
+            streams: BTreeMap::new(),
+            cadence_nanos,
+            max_history,
+        }
+    }
+
+    /// Start tracking a (symbol, timeframe) stream. No-op if already tracked.
+    pub fn track(&mut self, symbol: MarketId, timeframe: Timeframe) {
+        let key = (symbol.as_str().to_string(), timeframe);
+        let max_history = self.max_history;
+        self.streams.entry(key).or_insert_with(|| StreamState {
+            builder: CandleBuilder::new(timeframe, symbol, max_history),
+            sequence: 0,
+            next_live_at: i64::MIN,
+        });
+    }
+
+    /// Whether a (symbol, timeframe) stream is tracked.
+    pub fn is_tracked(&self, symbol: &str, timeframe: Timeframe) -> bool {
+        self.streams.contains_key(&(symbol.to_string(), timeframe))
+    }
+
+    /// Apply a trade to every tracked stream for `symbol`.
+    ///
+    /// Returns closed messages for candles whose boundary the trade crossed,
+    /// including flat candles backfilled over periods without trades.
+    /// The forming candle is only published on [`CandleStream::tick`].
+    pub fn on_trade(
+        &mut self,
+        symbol: &MarketId,
+        price: Price,
+        quantity: Decimal,
+        timestamp: i64,
+    ) -> Vec<CandleUpdate> {
+        let mut updates = Vec::new();
+        for ((sym, _), state) in self.streams.iter_mut() {
+            if sym != symbol.as_str() {
+                continue;
+            }
+            for closed in state.builder.process_trade(price, quantity, timestamp) {
+                updates.push(state.emit(closed, true));
+            }
+        }
+        updates
+    }
+
+    /// Advance exchange time to `now`.
+    ///
+    /// Closes any candle whose boundary has passed, then publishes the
+    /// forming candle of each stream whose cadence is due.
+    pub fn tick(&mut self, now: i64) -> Vec<CandleUpdate> {
+        let cadence = self.cadence_nanos;
+        let mut updates = Vec::new();
+        for ((_, timeframe), state) in self.streams.iter_mut() {
+            let expired = state
+                .builder
+                .current_candle()
+                .is_some_and(|c| c.open_time < timeframe.align_to_boundary(now));
+            if expired {
+                if let Some(closed) = state.builder.close_current() {
+                    updates.push(state.emit(closed, true));
+                }
+                continue;
+            }
+
+            if now < state.next_live_at {
+                continue;
+            }
+            if let Some(current) = state.builder.current_candle().cloned() {
+                updates.push(state.emit(current, false));
+                state.next_live_at = (now.div_euclid(cadence) + 1) * cadence;
+            }
+        }
+        updates
+    }
+
+    /// Last sequence number emitted on a stream (0 if none).
+    pub fn sequence(&self, symbol: &str, timeframe: Timeframe) -> u64 {
+        self.streams
+            .get(&(symbol.to_string(), timeframe))
+            .map_or(0, |s| s.sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: i64 = 1_000_000_000;
+    const MIN: i64 = 60 * SEC;
+
+    fn btc() -> MarketId {
+        MarketId::new("BTC/USDT")
+    }
+
+    fn stream() -> CandleStream {
+        let mut stream = CandleStream::new(DEFAULT_LIVE_CADENCE_NANOS, 100);
+        stream.track(btc(), Timeframe::M1);
+        stream
+    }
+
+    #[test]
+    fn test_live_updates_follow_cadence() {
+        let mut stream = stream();
+        stream.on_trade(&btc(), Price::from_u64(50000), Decimal::ONE, 10 * SEC);
+
+        let first = stream.tick(10 * SEC + 1);
+        assert_eq!(first.len(), 1);
+        assert!(!first[0].is_closed);
+        assert_eq!(first[0].sequence, 1);
+
+        // Still inside the same second
+        assert!(stream.tick(10 * SEC + 500_000_000).is_empty());
+
+        let second = stream.tick(11 * SEC);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].sequence, 2);
+        assert_eq!(second[0].channel().to_channel_string(), "candles@BTC/USDT@M1");
+    }
+
+    #[test]
+    fn test_no_updates_before_first_trade() {
+        let mut stream = stream();
+        assert!(stream.tick(5 * SEC).is_empty());
+        assert_eq!(stream.sequence("BTC/USDT", Timeframe::M1), 0);
+    }
+
+    #[test]
+    fn test_last_nanosecond_trade_lands_in_closed_candle() {
+        let mut stream = stream();
+        stream.on_trade(&btc(), Price::from_u64(50000), Decimal::ONE, 10 * SEC);
+        let live = stream.tick(59 * SEC).pop().unwrap();
+        assert!(!live.is_closed);
+
+        // Trade in the last nanosecond of minute 0
+        let closed_by_trade =
+            stream.on_trade(&btc(), Price::from_u64(50100), Decimal::TWO, MIN - 1);
+        assert!(closed_by_trade.is_empty());
+
+        let closed = stream.tick(MIN).pop().unwrap();
+        assert!(closed.is_closed);
+        assert_eq!(closed.sequence, live.sequence + 1);
+        assert_eq!(closed.candle.open_time, 0);
+        assert_eq!(closed.candle.close, Decimal::from(50100));
+        assert_eq!(closed.candle.high, Decimal::from(50100));
+        assert_eq!(closed.candle.volume, Decimal::from(3));
+        assert_eq!(closed.candle.trade_count, 2);
+        // Live values plus the trade in between
+        assert_eq!(closed.candle.open, live.candle.open);
+        assert_eq!(closed.candle.volume, live.candle.volume + Decimal::TWO);
+
+        // Next candle starts clean
+        stream.on_trade(&btc(), Price::from_u64(50200), Decimal::ONE, MIN + 1);
+        let next = stream.tick(MIN + 1).pop().unwrap();
+        assert!(!next.is_closed);
+        assert_eq!(next.candle.open_time, MIN);
+        assert_eq!(next.candle.open, Decimal::from(50200));
+        assert_eq!(next.candle.volume, Decimal::ONE);
+        assert_eq!(next.candle.trade_count, 1);
+    }
+
+    #[test]
+    fn test_trade_crossing_boundary_closes_candle() {
+        let mut stream = stream();
+        stream.on_trade(&btc(), Price::from_u64(50000), Decimal::ONE, 10 * SEC);
+        stream.tick(10 * SEC);
+
+        let updates = stream.on_trade(&btc(), Price::from_u64(51000), Decimal::ONE, MIN + SEC);
+        assert_eq!(updates.len(), 1);
+        assert!(updates[0].is_closed);
+        assert_eq!(updates[0].sequence, 2);
+        assert_eq!(updates[0].candle.close, Decimal::from(50000));
+    }
+
+    #[test]
+    fn test_sequences_are_per_stream() {
+        let mut stream = stream();
+        stream.track(btc(), Timeframe::M5);
+        stream.track(MarketId::new("ETH/USDT"), Timeframe::M1);
+
+        stream.on_trade(&btc(), Price::from_u64(50000), Decimal::ONE, SEC);
+        stream.tick(SEC);
+        stream.tick(2 * SEC);
+
+        assert_eq!(stream.sequence("BTC/USDT", Timeframe::M1), 2);
+        assert_eq!(stream.sequence("BTC/USDT", Timeframe::M5), 2);
+        assert_eq!(stream.sequence("ETH/USDT", Timeframe::M1), 0);
+    }
+}
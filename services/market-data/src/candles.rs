@@ -67,6 +67,25 @@ impl Timeframe {
         ]
     }
 
+    /// Channel label for this timeframe (e.g. `M1`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Timeframe::M1 => "M1",
+            Timeframe::M5 => "M5",
+            Timeframe::M15 => "M15",
+            Timeframe::M30 => "M30",
+            Timeframe::H1 => "H1",
+            Timeframe::H4 => "H4",
+            Timeframe::D1 => "D1",
+            Timeframe::W1 => "W1",
+        }
+    }
+
+    /// Parse a channel label produced by [`Timeframe::as_str`].
+    pub fn parse(label: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|tf| tf.as_str() == label)
+    }
+
     /// Align a timestamp to this timeframe's boundary (floor).
     pub fn align_to_boundary(&self, timestamp_nanos: i64) -> i64 {
         let duration = self.duration_nanos();
@@ -185,25 +204,43 @@ impl CandleBuilder {
 
     /// Process a trade: update or create candle, close candle at boundary.
     ///
-    /// Returns a closed candle if the trade crosses a boundary.
+    /// Returns the candles closed by the trade in chronological order: the
+    /// current candle if the trade crosses its boundary, then flat candles
+    /// at the previous close for every period skipped (e.g. after downtime).
     pub fn process_trade(
         &mut self,
         price: Price,
         quantity: Decimal,
         timestamp: i64,
-    ) -> Option<Candle> {
+    ) -> Vec<Candle> {
         let price_dec = price.as_decimal();
         let boundary = self.timeframe.align_to_boundary(timestamp);
 
         // Check if we need to close the current candle
-        let mut closed_candle = None;
+        let mut closed_candles = Vec::new();
 
         if let Some(ref current) = self.current {
             let current_boundary =
                 self.timeframe.align_to_boundary(current.open_time);
+            if boundary < current_boundary {
+                // Regressed timestamp: bucket by the time as given
+                self.apply_late_trade(boundary, price_dec, quantity);
+                return closed_candles;
+            }
             if boundary > current_boundary {
                 // Close the current candle
-                closed_candle = self.close_current();
+                closed_candles.extend(self.close_current());
+            }
+        }
+
+        // Fill periods with no trades since the last candle, which may have
+        // been closed just now or earlier on a timer
+        if self.current.is_none() {
+            if let Some((&last_open, last)) = self.closed.last_key_value() {
+                if boundary > last_open + self.timeframe.duration_nanos() {
+                    let prev_close = last.close;
+                    closed_candles.extend(self.backfill(prev_close, last_open, boundary));
+                }
             }
         }
 
@@ -221,7 +258,7 @@ impl CandleBuilder {
             }
         }
 
-        closed_candle
+        closed_candles
     }
 
     /// Force-close the current candle (e.g., on timer).
@@ -276,6 +313,21 @@ impl CandleBuilder {
         self.current.as_ref()
     }
 
+    /// Apply a trade whose timestamp falls before the current candle.
+    ///
+    /// Updates the closed candle for that period, creating it if needed.
+    fn apply_late_trade(&mut self, boundary: i64, price: Decimal, quantity: Decimal) {
+        match self.closed.get_mut(&boundary) {
+            Some(candle) => candle.update(price, quantity),
+            None => {
+                let candle =
+                    Candle::new(price, quantity, boundary, self.timeframe, self.symbol.clone());
+                self.closed.insert(boundary, candle);
+                self.trim_history();
+            }
+        }
+    }
+
     /// Trim history to max_history.
     fn trim_history(&mut self) {
         while self.closed.len() > self.max_history {
@@ -305,7 +357,8 @@ impl MultiTimeframeCandleManager {
 
     /// Process a trade across all timeframes.
     ///
-    /// Returns closed candles (if any) from each timeframe.
+    /// Returns closed and backfilled candles from each timeframe, shortest
+    /// timeframe first, each in chronological order.
     pub fn process_trade(
         &mut self,
         price: Price,
@@ -314,10 +367,7 @@ impl MultiTimeframeCandleManager {
     ) -> Vec<Candle> {
         let mut closed = Vec::new();
         for builder in self.builders.values_mut() {
-            if let Some(candle) = builder.process_trade(price, quantity, timestamp)
-            {
-                closed.push(candle);
-            }
+            closed.extend(builder.process_trade(price, quantity, timestamp));
         }
         closed
     }
@@ -334,6 +384,11 @@ impl MultiTimeframeCandleManager {
             .unwrap_or_default()
     }
 
+    /// Get the current (unclosed) candle for a specific timeframe.
+    pub fn current_candle(&self, timeframe: Timeframe) -> Option<&Candle> {
+        self.builders.get(&timeframe).and_then(|b| b.current_candle())
+    }
+
     /// Symbol managed by this instance.
     pub fn symbol(&self) -> &MarketId {
         &self.symbol
@@ -343,7 +398,6 @@ impl MultiTimeframeCandleManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use types::numeric::Quantity;
 
     fn nanos(minutes: i64) -> i64 {
         minutes * 60 * 1_000_000_000
@@ -359,6 +413,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_timeframe_labels_round_trip() {
+        for &tf in Timeframe::all() {
+            assert_eq!(Timeframe::parse(tf.as_str()), Some(tf));
+        }
+        assert_eq!(Timeframe::parse("M2"), None);
+    }
+
     #[test]
     fn test_timeframe_alignment() {
         let ts = nanos(5) + 30_000_000_000; // 5m30s
@@ -422,7 +484,7 @@ mod tests {
             Decimal::from(1),
             nanos(0) + 10_000_000_000, // 10 seconds
         );
-        assert!(result.is_none()); // No candle closed yet
+        assert!(result.is_empty()); // No candle closed yet
 
         let current = builder.current_candle().unwrap();
         assert_eq!(current.open, Decimal::from(50000));
@@ -450,8 +512,8 @@ mod tests {
             nanos(1) + 5_000_000_000,
         );
 
-        assert!(closed.is_some());
-        let closed_candle = closed.unwrap();
+        assert_eq!(closed.len(), 1);
+        let closed_candle = &closed[0];
         assert_eq!(closed_candle.open, Decimal::from(50000));
         assert_eq!(closed_candle.close, Decimal::from(50000));
         assert_eq!(closed_candle.trade_count, 1);
@@ -572,4 +634,54 @@ mod tests {
         let candles = builder.get_candles(10);
         assert!(candles.len() <= 3);
     }
+
+    #[test]
+    fn test_trade_after_gap_backfills_flats() {
+        let mut builder = CandleBuilder::new(Timeframe::M1, MarketId::new("BTC/USDT"), 100);
+        builder.process_trade(Price::from_u64(50000), Decimal::from(1), nanos(2) + 5_000_000_000);
+        builder.process_trade(Price::from_u64(50100), Decimal::from(1), nanos(2) + 30_000_000_000);
+
+        // Service down for minutes 3 and 4
+        let closed = builder.process_trade(Price::from_u64(50500), Decimal::from(1), nanos(5));
+        let open_times: Vec<i64> = closed.iter().map(|c| c.open_time).collect();
+        assert_eq!(open_times, vec![nanos(2), nanos(3), nanos(4)]);
+        for flat in &closed[1..] {
+            assert_eq!((flat.open, flat.close, flat.volume), (Decimal::from(50100), Decimal::from(50100), Decimal::ZERO));
+            assert_eq!(flat.trade_count, 0);
+        }
+        assert_eq!(builder.current_candle().unwrap().open_time, nanos(5));
+
+        // Closed on a timer, then a trade after a further gap
+        builder.close_current();
+        let closed = builder.process_trade(Price::from_u64(50600), Decimal::from(1), nanos(8));
+        let open_times: Vec<i64> = closed.iter().map(|c| c.open_time).collect();
+        assert_eq!(open_times, vec![nanos(6), nanos(7)]);
+        assert_eq!(closed[0].close, Decimal::from(50500));
+    }
+
+    #[test]
+    fn test_multi_timeframe_gap_spanning_hours() {
+        let mut manager = MultiTimeframeCandleManager::new(MarketId::new("BTC/USDT"), 1_000);
+        manager.process_trade(Price::from_u64(50000), Decimal::from(1), nanos(10));
+
+        // Three hours and ten minutes of downtime
+        let closed = manager.process_trade(Price::from_u64(52000), Decimal::from(1), nanos(200));
+
+        let m1: Vec<&Candle> = closed.iter().filter(|c| c.timeframe == Timeframe::M1).collect();
+        assert_eq!(m1.len(), 190);
+        assert_eq!(m1[0].open_time, nanos(10));
+        assert_eq!(m1.last().unwrap().open_time, nanos(199));
+        assert!(m1.windows(2).all(|w| w[1].open_time == w[0].close_time + 1));
+
+        let h1: Vec<i64> = closed
+            .iter()
+            .filter(|c| c.timeframe == Timeframe::H1)
+            .map(|c| c.open_time)
+            .collect();
+        assert_eq!(h1, vec![nanos(0), nanos(60), nanos(120)]);
+        assert!(closed.iter().all(|c| c.close == Decimal::from(50000)));
+
+        assert_eq!(manager.get_candles(Timeframe::M1, 1_000).len(), 190);
+        assert_eq!(manager.get_candles(Timeframe::H1, 10).len(), 3);
+    }
 }
@@ -0,0 +1,389 @@
+//! Conflated book deltas for bandwidth-limited clients
+//!
+//! Coalesces the per-event `DeltaMessage` stream of each symbol into one
+//! merged update per window. A window closes once `interval_nanos` of
+//! exchange time has passed since its first message, or after
+//! `max_events` messages, whichever comes first.
+//!
+//! Conventions:
+//! - Later changes to a price level overwrite earlier ones in the window.
+//! - A level that ends the window removed is sent as an explicit
+//!   zero-quantity delta, even if it was also added inside the window.
+//!   The conflator does not know the book before the window, and a zero
+//!   for an absent level is a no-op for the client.
+//! - `(first_sequence, last_sequence)` is the full range of book events
+//!   covered; `prev_sequence` chains windows exactly like raw messages.
+//! - The conflator only reads raw messages, so the uncoalesced stream
+//!   stays available for the persistence and audit path.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::ids::MarketId;
+use types::order::Side;
+
+use crate::deltas::{BookDelta, DeltaMessage};
+
+/// Window limits for conflation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConflationConfig {
+    /// Exchange-time length of a window, from its first message.
+    pub interval_nanos: i64,
+    /// Raw messages after which a window closes early.
+    pub max_events: usize,
+}
+
+impl Default for ConflationConfig {
+    fn default() -> Self {
+        Self {
+            interval_nanos: 50_000_000, // 50ms
+            max_events: 100,
+        }
+    }
+}
+
+/// Merged level changes of one window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflatedDelta {
+    pub symbol: MarketId,
+    /// Book sequence this update applies on top of
+    pub prev_sequence: u64,
+    /// First book event covered
+    pub first_sequence: u64,
+    /// Last book event covered; the book sequence after applying
+    pub last_sequence: u64,
+    /// Timestamp of the last event covered
+    pub timestamp: i64,
+    /// Raw messages merged
+    pub events: u64,
+    /// Sorted by side (bids first), then price ascending
+    pub deltas: Vec<BookDelta>,
+    /// Book checksum after `last_sequence`
+    pub checksum: u32,
+}
+
+impl ConflatedDelta {
+    /// Equivalent delta message covering the whole window.
+    pub fn to_message(&self) -> DeltaMessage {
+        DeltaMessage {
+            symbol: self.symbol.clone(),
+            prev_sequence: self.prev_sequence,
+            sequence: self.last_sequence,
+            timestamp: self.timestamp,
+            deltas: self.deltas.clone(),
+            checksum: self.checksum,
+        }
+    }
+}
+
+/// Open window of one symbol.
+#[derive(Debug, Clone)]
+struct Window {
+    symbol: MarketId,
+    prev_sequence: u64,
+    first_sequence: u64,
+    last_sequence: u64,
+    opened_at: i64,
+    timestamp: i64,
+    events: u64,
+    /// (side rank, price) → latest delta; bids rank first
+    levels: BTreeMap<(u8, Decimal), BookDelta>,
+    checksum: u32,
+}
+
+impl Window {
+    fn open(message: &DeltaMessage) -> Self {
+        Self {
+            symbol: message.symbol.clone(),
+            prev_sequence: message.prev_sequence,
+            first_sequence: message.sequence,
+            last_sequence: message.prev_sequence,
+            opened_at: message.timestamp,
+            timestamp: message.timestamp,
+            events: 0,
+            levels: BTreeMap::new(),
+            checksum: message.checksum,
+        }
+    }
+
+    fn merge(&mut self, message: &DeltaMessage) {
+        for delta in &message.deltas {
+            let rank = match delta.side {
+                Side::BUY => 0,
+                Side::SELL => 1,
+            };
+            self.levels.insert((rank, delta.price.as_decimal()), delta.clone());
+        }
+        self.last_sequence = message.sequence;
+        self.timestamp = message.timestamp;
+        self.checksum = message.checksum;
+        self.events += 1;
+    }
+
+    fn close(self) -> ConflatedDelta {
+        ConflatedDelta {
+            symbol: self.symbol,
+            prev_sequence: self.prev_sequence,
+            first_sequence: self.first_sequence,
+            last_sequence: self.last_sequence,
+            timestamp: self.timestamp,
+            events: self.events,
+            deltas: self.levels.into_values().collect(),
+            checksum: self.checksum,
+        }
+    }
+}
+
+/// Per-symbol delta conflation.
+///
+/// Uses BTreeMap for deterministic iteration (spec §12).
+#[derive(Debug, Clone, Default)]
+pub struct DeltaConflator {
+    config: ConflationConfig,
+    windows: BTreeMap<String, Window>,
+}
+
+impl DeltaConflator {
+    pub fn new(config: ConflationConfig) -> Self {
+        Self {
+            config,
+            windows: BTreeMap::new(),
+        }
+    }
+
+    /// Add a raw message, returning the windows it closed.
+    ///
+    /// A message past its symbol's interval closes the open window and
+    /// starts the next one. A message that does not follow the window's
+    /// last sequence also closes it, so a gap in the raw stream stays
+    /// visible to clients as a break in the `prev_sequence` chain.
+    pub fn push(&mut self, message: &DeltaMessage) -> Vec<ConflatedDelta> {
+        let mut closed = Vec::new();
+        let key = message.symbol.as_str();
+        if let Some(window) = self.windows.get(key) {
+            let expired = message.timestamp - window.opened_at >= self.config.interval_nanos;
+            if expired || window.last_sequence != message.prev_sequence {
+                closed.extend(self.windows.remove(key).map(Window::close));
+            }
+        }
+
+        let window = self
+            .windows
+            .entry(key.to_string())
+            .or_insert_with(|| Window::open(message));
+        window.merge(message);
+        if window.events as usize >= self.config.max_events {
+            closed.extend(self.windows.remove(key).map(Window::close));
+        }
+        closed
+    }
+
+    /// Close windows whose interval has elapsed by exchange time `now`.
+    pub fn tick(&mut self, now: i64) -> Vec<ConflatedDelta> {
+        let interval = self.config.interval_nanos;
+        let expired: Vec<String> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| now - w.opened_at >= interval)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|symbol| self.windows.remove(&symbol))
+            .map(Window::close)
+            .collect()
+    }
+
+    /// Close every open window, in symbol order.
+    pub fn flush(&mut self) -> Vec<ConflatedDelta> {
+        std::mem::take(&mut self.windows)
+            .into_values()
+            .map(Window::close)
+            .collect()
+    }
+
+    /// Raw messages held in a symbol's open window.
+    pub fn pending(&self, symbol: &str) -> u64 {
+        self.windows.get(symbol).map_or(0, |w| w.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deltas::{BookFeed, BookReplica};
+    use crate::events::{CancelSource, MarketEvent, MarketEventPayload};
+    use types::ids::{AccountId, OrderId, TradeId};
+    use types::numeric::{Price, Quantity};
+    use uuid::Uuid;
+
+    const T0: i64 = 1708123456789000000;
+    const MS: i64 = 1_000_000;
+
+    fn btc() -> MarketId {
+        MarketId::new("BTC/USDT")
+    }
+
+    fn make_event(seq: u64, ts: i64, payload: MarketEventPayload) -> MarketEvent {
+        MarketEvent {
+            event_id: Uuid::now_v7(),
+            sequence: seq,
+            timestamp: ts,
+            source: "matching-engine".to_string(),
+            payload,
+            schema_version: "1.0.0".to_string(),
+            correlation_id: Uuid::now_v7(),
+        }
+    }
+
+    fn accept(order_id: OrderId, side: Side, price: u64, qty: &str) -> MarketEventPayload {
+        MarketEventPayload::OrderAccepted {
+            order_id,
+            account_id: AccountId::new(),
+            symbol: btc(),
+            side,
+            price: Price::from_u64(price),
+            quantity: Quantity::from_str(qty).unwrap(),
+        }
+    }
+
+    fn cancel(order_id: OrderId, side: Side, price: u64) -> MarketEventPayload {
+        MarketEventPayload::OrderCanceled {
+            order_id,
+            symbol: btc(),
+            side,
+            price: Price::from_u64(price),
+            remaining_quantity: Quantity::from_str("1").unwrap(),
+            canceled_by: CancelSource::User,
+            reason: "user".to_string(),
+        }
+    }
+
+    fn trade(maker_order_id: OrderId) -> MarketEventPayload {
+        MarketEventPayload::TradeExecuted {
+            trade_id: TradeId::new(),
+            symbol: btc(),
+            maker_order_id,
+            taker_order_id: OrderId::new(),
+            maker_account_id: AccountId::new(),
+            taker_account_id: AccountId::new(),
+            price: Price::from_u64(50_100),
+            quantity: Quantity::from_str("0.25").unwrap(),
+            side: Side::BUY,
+            executed_at: 0,
+        }
+    }
+
+    /// A burst of 40 events, 7ms apart, adding, trading and canceling.
+    fn burst() -> Vec<MarketEvent> {
+        let mut payloads = Vec::new();
+        let mut bids = Vec::new();
+        let ask = OrderId::new();
+        payloads.push(accept(ask, Side::SELL, 50_100, "10"));
+        for i in 0..13u64 {
+            let bid = OrderId::new();
+            payloads.push(accept(bid, Side::BUY, 49_900 - (i % 4) * 10, "1"));
+            bids.push((bid, 49_900 - (i % 4) * 10));
+            payloads.push(trade(ask));
+            if i % 2 == 1 {
+                let (order_id, price) = bids.remove(0);
+                payloads.push(cancel(order_id, Side::BUY, price));
+            }
+        }
+        payloads
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| make_event(i as u64 + 1, T0 + i as i64 * 7 * MS, p))
+            .collect()
+    }
+
+    #[test]
+    fn test_level_added_and_removed_in_window_sends_zero() {
+        let mut feed = BookFeed::new(btc());
+        let mut conflator = DeltaConflator::default();
+        let order_id = OrderId::new();
+
+        let added = feed.apply(&make_event(1, T0, accept(order_id, Side::BUY, 49_000, "1"))).unwrap();
+        let removed = feed
+            .apply(&make_event(2, T0 + 10 * MS, cancel(order_id, Side::BUY, 49_000)))
+            .unwrap();
+        assert!(conflator.push(&added).is_empty());
+        assert!(conflator.push(&removed).is_empty());
+
+        let [window] = &conflator.flush()[..] else { panic!("expected one window") };
+        assert_eq!((window.prev_sequence, window.first_sequence, window.last_sequence), (0, 1, 2));
+        assert_eq!(window.events, 2);
+        assert_eq!(
+            window.deltas,
+            vec![BookDelta {
+                side: Side::BUY,
+                price: Price::from_u64(49_000),
+                new_total_quantity: Decimal::ZERO,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_windows_close_on_interval_or_event_count() {
+        let mut feed = BookFeed::new(btc());
+        let messages: Vec<DeltaMessage> = burst().iter().filter_map(|e| feed.apply(e)).collect();
+
+        // 50ms at 7ms spacing: a window holds messages 0..=7
+        let mut by_time = DeltaConflator::new(ConflationConfig {
+            interval_nanos: 50 * MS,
+            max_events: 1_000,
+        });
+        let closed: Vec<ConflatedDelta> = messages.iter().flat_map(|m| by_time.push(m)).collect();
+        assert_eq!(closed[0].first_sequence, 1);
+        assert_eq!(closed[0].last_sequence, 8);
+        assert_eq!(closed[1].prev_sequence, 8);
+        assert_eq!(by_time.tick(messages.last().unwrap().timestamp + 50 * MS).len(), 1);
+        assert_eq!(by_time.pending("BTC/USDT"), 0);
+
+        let mut by_count = DeltaConflator::new(ConflationConfig {
+            interval_nanos: i64::MAX,
+            max_events: 5,
+        });
+        let closed: Vec<ConflatedDelta> = messages.iter().flat_map(|m| by_count.push(m)).collect();
+        assert_eq!(closed.len(), messages.len() / 5);
+        assert!(closed.iter().all(|c| c.events == 5));
+        assert_eq!(closed[1].first_sequence, 6);
+    }
+
+    #[test]
+    fn test_conflated_application_equals_raw_application() {
+        let events = burst();
+        for config in [
+            ConflationConfig::default(),
+            ConflationConfig {
+                interval_nanos: 20 * MS,
+                max_events: 3,
+            },
+        ] {
+            let mut feed = BookFeed::new(btc());
+            let mut raw = BookReplica::from_snapshot(&feed.snapshot());
+            let mut conflated = raw.clone();
+            let mut conflator = DeltaConflator::new(config);
+            let mut windows = 0;
+
+            for event in &events {
+                let message = feed.apply(event).unwrap();
+                raw.apply(&message).unwrap();
+                for window in conflator.push(&message) {
+                    // Checksums verify at every window boundary
+                    assert_eq!(conflated.apply(&window.to_message()), Ok(true));
+                    windows += 1;
+                }
+            }
+            for window in conflator.flush() {
+                conflated.apply(&window.to_message()).unwrap();
+                windows += 1;
+            }
+
+            assert!(windows < events.len());
+            assert!(raw.matches(feed.book()));
+            assert_eq!(conflated, raw);
+        }
+    }
+}
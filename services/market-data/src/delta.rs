@@ -182,6 +182,7 @@ impl DeltaBatcher {
 }
 
 /// High-level delta generator that wraps snapshot + diff logic.
+#[derive(Debug, Clone)]
 pub struct DeltaGenerator {
     /// Last captured book snapshot for diff calculation.
     last_snapshot: Option<LevelSnapshot>,
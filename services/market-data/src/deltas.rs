@@ -0,0 +1,439 @@
+//! Incremental book delta stream with snapshot+delta resync
+//!
+//! Clients sync a book by taking a `DepthSnapshot` and then applying
+//! `DeltaMessage`s, each tagged `(prev_sequence, sequence)`. A message
+//! whose `prev_sequence` is not the replica's last sequence means a gap;
+//! the client requests a fresh snapshot and carries on from there.
+//!
+//! Conventions:
+//! - `BookFeed` applies an event and diffs the book inside one `&mut`
+//!   call, so no snapshot can observe a state between an event and its
+//!   delta: a snapshot at sequence `s` plus the deltas after `s` always
+//!   rebuilds the server book.
+//! - Every event applied to a book yields a message, even one that moved
+//!   no level, so the `(prev_sequence, sequence)` chain has no holes.
+//! - A delta carries a level's new total; quantity 0 means removed.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::ids::MarketId;
+use types::numeric::Price;
+use types::order::Side;
+
+use crate::delta::{self, DeltaGenerator};
+use crate::events::{MarketEvent, MarketEventPayload};
+use crate::order_book::{book_checksum, depth_checksum, DepthSnapshot, OrderBookState, CHECKSUM_LEVELS};
+
+/// Compact level update: the level's new total (0 = level removed).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookDelta {
+    pub side: Side,
+    pub price: Price,
+    pub new_total_quantity: Decimal,
+}
+
+impl From<delta::BookDelta> for BookDelta {
+    fn from(delta: delta::BookDelta) -> Self {
+        Self {
+            side: delta.side,
+            price: delta.price,
+            new_total_quantity: delta.new_quantity,
+        }
+    }
+}
+
+/// Level updates caused by one event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaMessage {
+    pub symbol: MarketId,
+    /// Book sequence this message applies on top of
+    pub prev_sequence: u64,
+    /// Book sequence after applying it
+    pub sequence: u64,
+    pub timestamp: i64,
+    /// Sorted by side (bids first), then price ascending
+    pub deltas: Vec<BookDelta>,
+    /// Book checksum after applying, over the top [`CHECKSUM_LEVELS`]
+    pub checksum: u32,
+}
+
+/// Why a replica could not apply a delta message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DeltaError {
+    #[error("sequence gap: replica at {expected}, message follows {prev_sequence}")]
+    SequenceGap { expected: u64, prev_sequence: u64 },
+
+    #[error("delta for {actual} applied to {expected} replica")]
+    WrongSymbol { expected: String, actual: String },
+
+    #[error("checksum mismatch at sequence {sequence}: expected {expected}, replica has {actual}")]
+    ChecksumMismatch { sequence: u64, expected: u32, actual: u32 },
+}
+
+/// Server-side book that emits a delta message per applied event.
+#[derive(Debug, Clone)]
+pub struct BookFeed {
+    book: OrderBookState,
+    generator: DeltaGenerator,
+}
+
+impl BookFeed {
+    pub fn new(symbol: MarketId) -> Self {
+        let book = OrderBookState::new(symbol);
+        let mut generator = DeltaGenerator::new();
+        generator.capture_before(&book);
+        Self { book, generator }
+    }
+
+    pub fn book(&self) -> &OrderBookState {
+        &self.book
+    }
+
+    /// Apply a book event and return its deltas.
+    ///
+    /// `None` when the event does not touch this book.
+    pub fn apply(&mut self, event: &MarketEvent) -> Option<DeltaMessage> {
+        let prev_sequence = self.book.last_sequence();
+        match &event.payload {
+            MarketEventPayload::OrderAccepted {
+                order_id,
+                symbol,
+                side,
+                price,
+                quantity,
+                ..
+            } if *symbol == self.book.symbol => {
+                self.book.apply_order_accepted(*order_id, *side, *price, *quantity, event.sequence);
+            }
+            MarketEventPayload::TradeExecuted {
+                symbol,
+                maker_order_id,
+                quantity,
+                ..
+            } if *symbol == self.book.symbol => {
+                self.book.apply_trade_executed(*maker_order_id, *quantity, event.sequence);
+            }
+            MarketEventPayload::OrderCanceled {
+                order_id,
+                symbol,
+                remaining_quantity,
+                ..
+            } if *symbol == self.book.symbol => {
+                self.book.apply_cancel(*order_id, *remaining_quantity, event.sequence);
+            }
+            _ => return None,
+        }
+
+        let deltas = self
+            .generator
+            .generate_after(&self.book, event.sequence, event.timestamp)
+            .into_iter()
+            .map(BookDelta::from)
+            .collect();
+        Some(DeltaMessage {
+            symbol: self.book.symbol.clone(),
+            prev_sequence,
+            sequence: self.book.last_sequence(),
+            timestamp: event.timestamp,
+            deltas,
+            checksum: self.book.checksum(CHECKSUM_LEVELS),
+        })
+    }
+
+    /// Full-depth snapshot, for subscribe and resync.
+    pub fn snapshot(&self) -> DepthSnapshot {
+        self.book.depth_snapshot(usize::MAX)
+    }
+}
+
+/// Client-side copy of a book kept in sync from snapshot and deltas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookReplica {
+    symbol: MarketId,
+    /// price → total quantity
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    last_sequence: u64,
+}
+
+impl BookReplica {
+    pub fn from_snapshot(snapshot: &DepthSnapshot) -> Self {
+        let levels = |levels: &[crate::order_book::PriceLevel]| {
+            levels
+                .iter()
+                .map(|l| (l.price.as_decimal(), l.total_quantity))
+                .collect()
+        };
+        Self {
+            symbol: snapshot.symbol.clone(),
+            bids: levels(&snapshot.bids),
+            asks: levels(&snapshot.asks),
+            last_sequence: snapshot.last_sequence,
+        }
+    }
+
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Apply a delta message.
+    ///
+    /// Returns `Ok(false)` for a message already covered by the snapshot
+    /// (deltas buffered while it was in flight), `Ok(true)` once applied.
+    /// A `ChecksumMismatch` leaves the message applied; the replica is
+    /// no longer trustworthy and should resync.
+    pub fn apply(&mut self, message: &DeltaMessage) -> Result<bool, DeltaError> {
+        if message.symbol != self.symbol {
+            return Err(DeltaError::WrongSymbol {
+                expected: self.symbol.to_string(),
+                actual: message.symbol.to_string(),
+            });
+        }
+        if message.sequence <= self.last_sequence {
+            return Ok(false);
+        }
+        if message.prev_sequence != self.last_sequence {
+            return Err(DeltaError::SequenceGap {
+                expected: self.last_sequence,
+                prev_sequence: message.prev_sequence,
+            });
+        }
+
+        for delta in &message.deltas {
+            let levels = match delta.side {
+                Side::BUY => &mut self.bids,
+                Side::SELL => &mut self.asks,
+            };
+            if delta.new_total_quantity.is_zero() {
+                levels.remove(&delta.price.as_decimal());
+            } else {
+                levels.insert(delta.price.as_decimal(), delta.new_total_quantity);
+            }
+        }
+        self.last_sequence = message.sequence;
+
+        let actual = self.checksum(CHECKSUM_LEVELS);
+        if actual != message.checksum {
+            return Err(DeltaError::ChecksumMismatch {
+                sequence: message.sequence,
+                expected: message.checksum,
+                actual,
+            });
+        }
+        Ok(true)
+    }
+
+    /// Checksum comparable with [`OrderBookState::checksum`].
+    pub fn checksum(&self, levels: usize) -> u32 {
+        book_checksum(
+            self.bids.iter().rev().map(|(p, q)| (*p, *q)),
+            self.asks.iter().map(|(p, q)| (*p, *q)),
+            levels,
+        )
+    }
+
+    /// Checksum comparable with [`OrderBookState::full_checksum`].
+    pub fn full_checksum(&self) -> String {
+        let level = |(price, quantity): (&Decimal, &Decimal)| (Price::try_new(*price).unwrap(), *quantity);
+        depth_checksum(self.bids.iter().rev().map(level), self.asks.iter().map(level))
+    }
+
+    /// Whether the replica holds exactly the book's levels and sequence.
+    pub fn matches(&self, book: &OrderBookState) -> bool {
+        self.last_sequence == book.last_sequence() && self.full_checksum() == book.full_checksum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::CancelSource;
+    use types::ids::{AccountId, OrderId, TradeId};
+    use types::numeric::Quantity;
+    use uuid::Uuid;
+
+    fn make_event(seq: u64, payload: MarketEventPayload) -> MarketEvent {
+        MarketEvent {
+            event_id: Uuid::now_v7(),
+            sequence: seq,
+            timestamp: 1708123456789000000 + (seq as i64 * 1000),
+            source: "matching-engine".to_string(),
+            payload,
+            schema_version: "1.0.0".to_string(),
+            correlation_id: Uuid::now_v7(),
+        }
+    }
+
+    fn btc() -> MarketId {
+        MarketId::new("BTC/USDT")
+    }
+
+    /// A recorded session: resting orders, trades that shrink and clear
+    /// levels, cancels, and an event for another market.
+    fn recorded_stream() -> Vec<MarketEvent> {
+        let orders: Vec<OrderId> = (0..6).map(|_| OrderId::new()).collect();
+        let accept = |order_id, side, price, qty: &str| MarketEventPayload::OrderAccepted {
+            order_id,
+            account_id: AccountId::new(),
+            symbol: btc(),
+            side,
+            price: Price::from_u64(price),
+            quantity: Quantity::from_str(qty).unwrap(),
+        };
+        let trade = |maker_order_id, qty: &str| MarketEventPayload::TradeExecuted {
+            trade_id: TradeId::new(),
+            symbol: btc(),
+            maker_order_id,
+            taker_order_id: OrderId::new(),
+            maker_account_id: AccountId::new(),
+            taker_account_id: AccountId::new(),
+            price: Price::from_u64(50_000),
+            quantity: Quantity::from_str(qty).unwrap(),
+            side: Side::BUY,
+            executed_at: 0,
+        };
+        let cancel = |order_id, side, price, qty: &str| MarketEventPayload::OrderCanceled {
+            order_id,
+            symbol: btc(),
+            side,
+            price: Price::from_u64(price),
+            remaining_quantity: Quantity::from_str(qty).unwrap(),
+            canceled_by: CancelSource::User,
+            reason: "user".to_string(),
+        };
+
+        let payloads = vec![
+            accept(orders[0], Side::BUY, 49_900, "1.5"),
+            accept(orders[1], Side::BUY, 49_900, "0.5"),
+            accept(orders[2], Side::SELL, 50_100, "2"),
+            accept(orders[3], Side::SELL, 50_200, "1"),
+            MarketEventPayload::OrderAccepted {
+                order_id: OrderId::new(),
+                account_id: AccountId::new(),
+                symbol: MarketId::new("ETH/USDT"),
+                side: Side::BUY,
+                price: Price::from_u64(3_000),
+                quantity: Quantity::from_str("1").unwrap(),
+            },
+            trade(orders[2], "0.75"),
+            cancel(orders[1], Side::BUY, 49_900, "0.5"),
+            trade(orders[2], "1.25"),
+            accept(orders[4], Side::BUY, 49_950, "3"),
+            cancel(orders[5], Side::BUY, 1, "1"), // unknown order: no level moves
+            trade(orders[0], "1.5"),
+            cancel(orders[3], Side::SELL, 50_200, "1"),
+        ];
+        payloads
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| make_event(i as u64 + 1, p))
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_plus_deltas_matches_server_at_every_sequence() {
+        let events = recorded_stream();
+        // A client joining after each event in turn
+        for join_at in 0..=events.len() {
+            let mut server = BookFeed::new(btc());
+            let mut deltas = Vec::new();
+            let mut client = None;
+            for (i, event) in events.iter().enumerate() {
+                if i == join_at {
+                    client = Some(BookReplica::from_snapshot(&server.snapshot()));
+                }
+                if let Some(message) = server.apply(event) {
+                    deltas.push(message);
+                }
+                if let Some(client) = client.as_mut() {
+                    for message in deltas.drain(..) {
+                        client.apply(&message).unwrap();
+                    }
+                    assert!(client.matches(server.book()), "joined at {join_at}, diverged at seq {}", event.sequence);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_messages_chain_and_remove_levels_with_zero() {
+        let mut server = BookFeed::new(btc());
+        let messages: Vec<DeltaMessage> = recorded_stream().iter().filter_map(|e| server.apply(e)).collect();
+
+        // The ETH event is skipped; every other event extends the chain
+        assert_eq!(messages.len(), 11);
+        assert_eq!(messages[0].prev_sequence, 0);
+        for pair in messages.windows(2) {
+            assert_eq!(pair[1].prev_sequence, pair[0].sequence);
+        }
+        assert_eq!(messages[4].prev_sequence, 4);
+        assert_eq!(messages[4].sequence, 6);
+
+        // Second trade clears the 50 100 ask
+        assert_eq!(
+            messages[6].deltas,
+            vec![BookDelta {
+                side: Side::SELL,
+                price: Price::from_u64(50_100),
+                new_total_quantity: Decimal::ZERO,
+            }]
+        );
+        // The unknown cancel still advances the sequence
+        assert!(messages[8].deltas.is_empty());
+    }
+
+    #[test]
+    fn test_gap_detected_then_resync() {
+        let events = recorded_stream();
+        let mut server = BookFeed::new(btc());
+        let mut client = BookReplica::from_snapshot(&server.snapshot());
+
+        let first = server.apply(&events[0]).unwrap();
+        client.apply(&first).unwrap();
+        let _lost = server.apply(&events[1]).unwrap();
+        let third = server.apply(&events[2]).unwrap();
+        assert_eq!(
+            client.apply(&third),
+            Err(DeltaError::SequenceGap {
+                expected: 1,
+                prev_sequence: 2
+            })
+        );
+
+        // Resync: fresh snapshot, then deltas buffered meanwhile are skipped
+        client = BookReplica::from_snapshot(&server.snapshot());
+        assert_eq!(client.apply(&third), Ok(false));
+        let fourth = server.apply(&events[3]).unwrap();
+        assert_eq!(client.apply(&fourth), Ok(true));
+        assert!(client.matches(server.book()));
+
+        let mut eth = BookReplica::from_snapshot(&BookFeed::new(MarketId::new("ETH/USDT")).snapshot());
+        assert!(matches!(eth.apply(&fourth), Err(DeltaError::WrongSymbol { .. })));
+    }
+
+    #[test]
+    fn test_checksum_mismatch_detects_drift() {
+        let events = recorded_stream();
+        let mut server = BookFeed::new(btc());
+        let mut client = BookReplica::from_snapshot(&server.snapshot());
+        assert_eq!(server.snapshot().checksum, client.checksum(CHECKSUM_LEVELS));
+
+        let first = server.apply(&events[0]).unwrap();
+        assert_eq!(first.checksum, server.book().checksum(CHECKSUM_LEVELS));
+        client.apply(&first).unwrap();
+
+        // A delta lost in transit but with an unbroken sequence chain
+        let mut second = server.apply(&events[1]).unwrap();
+        second.deltas.clear();
+        assert_eq!(
+            client.apply(&second),
+            Err(DeltaError::ChecksumMismatch {
+                sequence: 2,
+                expected: server.book().checksum(CHECKSUM_LEVELS),
+                actual: client.checksum(CHECKSUM_LEVELS),
+            })
+        );
+    }
+}
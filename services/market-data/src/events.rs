@@ -5,13 +5,19 @@
 //! Taxonomy) event types.
 //!
 //! Uses `Ord` on sequence for deterministic ordering per §12 and §14.
+//!
+//! Also defines `TradeTick`, the fixed-layout binary form of a public
+//! trade offered to high-frequency WebSocket consumers.
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use types::ids::{AccountId, MarketId, OrderId, TradeId};
 use types::numeric::{Price, Quantity};
 use types::order::Side;
 use uuid::Uuid;
 
+use crate::trades::PublicTrade;
+
 /// Source of a cancel action
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -99,6 +105,22 @@ pub enum MarketEventPayload {
         canceled_by: CancelSource,
         reason: String,
     },
+
+    /// A funding rate was applied to a perpetual market
+    FundingRateUpdated {
+        symbol: MarketId,
+        funding_rate: Decimal,
+        mark_price: Price,
+    },
+
+    /// A position was liquidated; public fields only (spec §8.3.5)
+    PositionLiquidated {
+        symbol: MarketId,
+        /// Side of the liquidation order sent to the book
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+    },
 }
 
 impl MarketEvent {
@@ -108,6 +130,8 @@ impl MarketEvent {
             MarketEventPayload::OrderAccepted { symbol, .. } => Some(symbol),
             MarketEventPayload::TradeExecuted { symbol, .. } => Some(symbol),
             MarketEventPayload::OrderCanceled { symbol, .. } => Some(symbol),
+            MarketEventPayload::FundingRateUpdated { symbol, .. } => Some(symbol),
+            MarketEventPayload::PositionLiquidated { symbol, .. } => Some(symbol),
             MarketEventPayload::OrderPartiallyFilled { .. } => None,
             MarketEventPayload::OrderFilled { .. } => None,
         }
@@ -121,6 +145,8 @@ impl MarketEvent {
             MarketEventPayload::OrderPartiallyFilled { .. } => "OrderPartiallyFilled",
             MarketEventPayload::OrderFilled { .. } => "OrderFilled",
             MarketEventPayload::OrderCanceled { .. } => "OrderCanceled",
+            MarketEventPayload::FundingRateUpdated { .. } => "FundingRateUpdated",
+            MarketEventPayload::PositionLiquidated { .. } => "PositionLiquidated",
         }
     }
 }
@@ -158,6 +184,137 @@ impl RecoveryRequest {
     }
 }
 
+/// Version byte of the [`TradeTick`] binary layout.
+pub const TRADE_TICK_VERSION: u8 = 1;
+/// Encoded length of a [`TradeTick`] in bytes.
+pub const TRADE_TICK_LEN: usize = 46;
+
+/// Public trade in fixed-layout binary form.
+///
+/// Layout v1, little-endian, 46 bytes:
+///
+/// ```text
+/// offset size field
+///      0    1 version (1)
+///      1    2 symbol_id (u16, assigned in the subscribe ack)
+///      3    8 book_sequence (u64)
+///     11    8 trade_sequence (u64)
+///     19    8 timestamp (i64, Unix nanos)
+///     27    8 price mantissa (i64)
+///     35    1 price scale (u8, 0..=28)
+///     36    8 quantity mantissa (i64)
+///     44    1 quantity scale (u8, 0..=28)
+///     45    1 taker side (0 = BUY, 1 = SELL)
+/// ```
+///
+/// Decimals are normalized before encoding, so `1.50` travels as
+/// mantissa 15, scale 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeTick {
+    pub symbol_id: u16,
+    /// Book sequence after the trade
+    pub book_sequence: u64,
+    /// Per-market trade sequence
+    pub trade_sequence: u64,
+    pub timestamp: i64,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub taker_side: Side,
+}
+
+/// Errors encoding or decoding a [`TradeTick`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TickCodecError {
+    #[error("{field} mantissa does not fit in 64 bits")]
+    MantissaOverflow { field: &'static str },
+
+    #[error("trade tick must be {TRADE_TICK_LEN} bytes, got {0}")]
+    InvalidLength(usize),
+
+    #[error("unsupported trade tick version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("invalid {field} scale {scale}")]
+    InvalidScale { field: &'static str, scale: u8 },
+
+    #[error("invalid taker side byte {0}")]
+    InvalidSide(u8),
+}
+
+impl TradeTick {
+    /// Tick for a public trade, under the subscriber's symbol id.
+    pub fn from_trade(trade: &PublicTrade, symbol_id: u16, book_sequence: u64) -> Self {
+        Self {
+            symbol_id,
+            book_sequence,
+            trade_sequence: trade.trade_sequence,
+            timestamp: trade.timestamp,
+            price: trade.price.as_decimal(),
+            quantity: trade.quantity.as_decimal(),
+            taker_side: trade.taker_side,
+        }
+    }
+
+    /// Encode to the v1 layout.
+    pub fn encode(&self) -> Result<Vec<u8>, TickCodecError> {
+        let mut out = Vec::with_capacity(TRADE_TICK_LEN);
+        out.push(TRADE_TICK_VERSION);
+        out.extend_from_slice(&self.symbol_id.to_le_bytes());
+        out.extend_from_slice(&self.book_sequence.to_le_bytes());
+        out.extend_from_slice(&self.trade_sequence.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        encode_decimal(&mut out, self.price, "price")?;
+        encode_decimal(&mut out, self.quantity, "quantity")?;
+        out.push(match self.taker_side {
+            Side::BUY => 0,
+            Side::SELL => 1,
+        });
+        debug_assert_eq!(out.len(), TRADE_TICK_LEN);
+        Ok(out)
+    }
+
+    /// Decode a v1 tick.
+    pub fn decode(bytes: &[u8]) -> Result<Self, TickCodecError> {
+        if bytes.len() != TRADE_TICK_LEN {
+            return Err(TickCodecError::InvalidLength(bytes.len()));
+        }
+        if bytes[0] != TRADE_TICK_VERSION {
+            return Err(TickCodecError::UnsupportedVersion(bytes[0]));
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let i64_at = |at: usize| i64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let decimal_at = |at: usize, field: &'static str| {
+            let scale = bytes[at + 8];
+            if scale > 28 {
+                return Err(TickCodecError::InvalidScale { field, scale });
+            }
+            Ok(Decimal::new(i64_at(at), u32::from(scale)))
+        };
+        let taker_side = match bytes[45] {
+            0 => Side::BUY,
+            1 => Side::SELL,
+            other => return Err(TickCodecError::InvalidSide(other)),
+        };
+        Ok(Self {
+            symbol_id: u16::from_le_bytes([bytes[1], bytes[2]]),
+            book_sequence: u64_at(3),
+            trade_sequence: u64_at(11),
+            timestamp: i64_at(19),
+            price: decimal_at(27, "price")?,
+            quantity: decimal_at(36, "quantity")?,
+            taker_side,
+        })
+    }
+}
+
+fn encode_decimal(out: &mut Vec<u8>, value: Decimal, field: &'static str) -> Result<(), TickCodecError> {
+    let value = value.normalize();
+    let mantissa = i64::try_from(value.mantissa()).map_err(|_| TickCodecError::MantissaOverflow { field })?;
+    out.extend_from_slice(&mantissa.to_le_bytes());
+    out.push(value.scale() as u8);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,7 +351,7 @@ mod tests {
         let e2 = sample_order_accepted(2);
         let e3 = sample_order_accepted(3);
 
-        let mut events = vec![e3.clone(), e1.clone(), e2.clone()];
+        let mut events = [e3.clone(), e1.clone(), e2.clone()];
         events.sort();
 
         assert_eq!(events[0].sequence, 1);
@@ -231,4 +388,82 @@ mod tests {
         };
         assert_eq!(req.gap_size(), 6);
     }
+
+    fn sample_tick() -> TradeTick {
+        TradeTick {
+            symbol_id: 3,
+            book_sequence: 1_234_567,
+            trade_sequence: 42,
+            timestamp: 1708123456789000000,
+            price: Decimal::from_str_exact("50000.25").unwrap(),
+            quantity: Decimal::from_str_exact("0.0150").unwrap(),
+            taker_side: Side::SELL,
+        }
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_trade_tick_roundtrip() {
+        let tick = sample_tick();
+        let bytes = tick.encode().unwrap();
+        assert_eq!(bytes.len(), TRADE_TICK_LEN);
+        assert_eq!(TradeTick::decode(&bytes).unwrap(), tick);
+
+        let buy = TradeTick {
+            taker_side: Side::BUY,
+            price: Decimal::from(1),
+            ..tick
+        };
+        assert_eq!(TradeTick::decode(&buy.encode().unwrap()).unwrap(), buy);
+    }
+
+    // Frozen vector: a change here breaks every binary client.
+    #[test]
+    fn test_trade_tick_byte_layout() {
+        assert_eq!(
+            to_hex(&sample_tick().encode().unwrap()),
+            concat!(
+                "01",               // version
+                "0300",             // symbol_id
+                "87d6120000000000", // book_sequence
+                "2a00000000000000", // trade_sequence
+                "40af6be23b79b417", // timestamp
+                "594b4c0000000000", // price mantissa 5000025
+                "02",               // price scale
+                "0f00000000000000", // quantity mantissa 15
+                "03",               // quantity scale
+                "01",               // taker side SELL
+            )
+        );
+    }
+
+    #[test]
+    fn test_trade_tick_decode_errors() {
+        let bytes = sample_tick().encode().unwrap();
+        assert_eq!(TradeTick::decode(&bytes[..45]), Err(TickCodecError::InvalidLength(45)));
+
+        let mut bad = bytes.clone();
+        bad[0] = 2;
+        assert_eq!(TradeTick::decode(&bad), Err(TickCodecError::UnsupportedVersion(2)));
+
+        let mut bad = bytes.clone();
+        bad[35] = 29;
+        assert_eq!(
+            TradeTick::decode(&bad),
+            Err(TickCodecError::InvalidScale { field: "price", scale: 29 })
+        );
+
+        let mut bad = bytes;
+        bad[45] = 7;
+        assert_eq!(TradeTick::decode(&bad), Err(TickCodecError::InvalidSide(7)));
+
+        let huge = TradeTick {
+            quantity: Decimal::MAX,
+            ..sample_tick()
+        };
+        assert_eq!(huge.encode(), Err(TickCodecError::MantissaOverflow { field: "quantity" }));
+    }
 }
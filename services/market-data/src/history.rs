@@ -0,0 +1,531 @@
+//! Historical funding and liquidation queries
+//!
+//! Keeps bounded per-symbol histories of funding rate updates and public
+//! liquidation events, queryable by time range with sequence-based cursors.
+//! - Ring buffers with configurable retention per symbol
+//! - Chronological pages with a cursor that survives new arrivals
+//! - Explicit metadata when a query reaches past retention
+//! - Optional rebuild from the persistence journal on startup
+//!
+//! Records are ordered by global sequence (spec §14), so a cursor pointing at
+//! the last returned sequence stays valid while new events are appended.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+
+use persistence::journal::JournalEntry;
+use persistence::reader::{JournalReader, ReaderError};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::ids::MarketId;
+use types::numeric::{Price, Quantity};
+use types::order::Side;
+
+use crate::events::{MarketEvent, MarketEventPayload};
+
+/// Journal event type for funding rate updates.
+pub const FUNDING_EVENT_TYPE: &str = "FundingRateUpdated";
+/// Journal event type for public liquidation events.
+pub const LIQUIDATION_EVENT_TYPE: &str = "PositionLiquidated";
+
+/// A funding rate applied to a market.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingRecord {
+    /// Global sequence number of the source event.
+    pub sequence: u64,
+    /// Unix nanos timestamp of the source event.
+    pub timestamp: i64,
+    /// Market symbol.
+    pub symbol: MarketId,
+    /// Funding rate for the interval.
+    pub funding_rate: Decimal,
+    /// Mark price the payment was computed against.
+    pub mark_price: Price,
+}
+
+/// A public liquidation event (no account information).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiquidationRecord {
+    /// Global sequence number of the source event.
+    pub sequence: u64,
+    /// Unix nanos timestamp of the source event.
+    pub timestamp: i64,
+    /// Market symbol.
+    pub symbol: MarketId,
+    /// Side of the liquidation order.
+    pub side: Side,
+    /// Liquidation price.
+    pub price: Price,
+    /// Liquidated quantity.
+    pub quantity: Quantity,
+}
+
+/// Common accessors for history records.
+pub trait HistoryRecord: Clone {
+    fn sequence(&self) -> u64;
+    fn timestamp(&self) -> i64;
+    fn symbol(&self) -> &MarketId;
+}
+
+impl HistoryRecord for FundingRecord {
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+    fn symbol(&self) -> &MarketId {
+        &self.symbol
+    }
+}
+
+impl HistoryRecord for LiquidationRecord {
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+    fn symbol(&self) -> &MarketId {
+        &self.symbol
+    }
+}
+
+/// One page of a history query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryPage<T> {
+    /// Records in chronological (sequence) order.
+    pub records: Vec<T>,
+    /// Cursor for the next page; `None` when the range is exhausted.
+    pub next_cursor: Option<u64>,
+    /// True when records matching the query were evicted by retention.
+    pub retention_exceeded: bool,
+    /// Timestamp of the oldest retained record for the symbol.
+    pub oldest_available: Option<i64>,
+}
+
+/// Retention settings for the history store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryConfig {
+    /// Funding records kept per symbol.
+    pub funding_retention: usize,
+    /// Liquidation records kept per symbol.
+    pub liquidation_retention: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            // 8-hour funding: one year of history
+            funding_retention: 1_095,
+            liquidation_retention: 10_000,
+        }
+    }
+}
+
+/// Errors while rebuilding history from the journal.
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("journal read failed: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("failed to decode {event_type} at sequence {sequence}: {reason}")]
+    Decode {
+        sequence: u64,
+        event_type: String,
+        reason: String,
+    },
+}
+
+/// Bounded ring buffer of records for one symbol.
+#[derive(Debug)]
+struct RingBuffer<T> {
+    records: VecDeque<T>,
+    capacity: usize,
+    /// (sequence, timestamp) of the newest evicted record.
+    evicted_through: Option<(u64, i64)>,
+}
+
+impl<T: HistoryRecord> RingBuffer<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity.min(1024)),
+            capacity,
+            evicted_through: None,
+        }
+    }
+
+    /// Append a record; out-of-order or duplicate sequences are ignored.
+    fn push(&mut self, record: T) -> bool {
+        if self.records.back().is_some_and(|last| record.sequence() <= last.sequence())
+            || self.evicted_through.is_some_and(|(seq, _)| record.sequence() <= seq)
+        {
+            return false;
+        }
+        if self.records.len() >= self.capacity {
+            if let Some(evicted) = self.records.pop_front() {
+                self.evicted_through = Some((evicted.sequence(), evicted.timestamp()));
+            }
+        }
+        self.records.push_back(record);
+        true
+    }
+
+    fn query(&self, from: i64, to: i64, limit: usize, cursor: Option<u64>) -> HistoryPage<T> {
+        let after = cursor.unwrap_or(0);
+        let start = self.records.partition_point(|r| r.sequence() <= after);
+        let mut matching = self
+            .records
+            .range(start..)
+            .filter(|r| r.timestamp() >= from && r.timestamp() <= to);
+
+        let records: Vec<T> = matching.by_ref().take(limit).cloned().collect();
+        let next_cursor = match (records.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.sequence()),
+            _ => None,
+        };
+
+        // An evicted record could have matched if it was inside the range
+        // and not already returned before the cursor.
+        let retention_exceeded = self
+            .evicted_through
+            .is_some_and(|(seq, ts)| from <= ts && after < seq);
+
+        HistoryPage {
+            records,
+            next_cursor,
+            retention_exceeded,
+            oldest_available: self.records.front().map(|r| r.timestamp()),
+        }
+    }
+}
+
+/// Per-symbol funding and liquidation history.
+#[derive(Debug)]
+pub struct HistoryStore {
+    config: HistoryConfig,
+    funding: BTreeMap<String, RingBuffer<FundingRecord>>,
+    liquidations: BTreeMap<String, RingBuffer<LiquidationRecord>>,
+    last_sequence: u64,
+}
+
+impl HistoryStore {
+    /// Create an empty store.
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            config,
+            funding: BTreeMap::new(),
+            liquidations: BTreeMap::new(),
+            last_sequence: 0,
+        }
+    }
+
+    /// Rebuild a store from the journal, keeping only history event types.
+    ///
+    /// Other event types are skipped, so this can run over the shared
+    /// exchange journal. `last_sequence()` reports the last entry read.
+    pub fn rebuild_from_journal(
+        config: HistoryConfig,
+        journal_dir: &Path,
+        from_sequence: u64,
+    ) -> Result<Self, HistoryError> {
+        let mut store = Self::new(config);
+        let mut reader = JournalReader::open(journal_dir)?;
+        reader.seek_to_sequence(from_sequence)?;
+        while let Some(entry) = reader.next_entry()? {
+            store.apply_journal_entry(&entry)?;
+            store.last_sequence = store.last_sequence.max(entry.sequence);
+        }
+        Ok(store)
+    }
+
+    /// Apply one journal entry; returns false for unrelated event types.
+    pub fn apply_journal_entry(&mut self, entry: &JournalEntry) -> Result<bool, HistoryError> {
+        let decode_error = |e: bincode::Error| HistoryError::Decode {
+            sequence: entry.sequence,
+            event_type: entry.event_type.clone(),
+            reason: e.to_string(),
+        };
+        // Header sequence and timestamp are authoritative over the payload
+        match entry.event_type.as_str() {
+            FUNDING_EVENT_TYPE => {
+                let mut record: FundingRecord =
+                    bincode::deserialize(&entry.payload).map_err(decode_error)?;
+                record.sequence = entry.sequence;
+                record.timestamp = entry.timestamp;
+                Ok(self.record_funding(record))
+            }
+            LIQUIDATION_EVENT_TYPE => {
+                let mut record: LiquidationRecord =
+                    bincode::deserialize(&entry.payload).map_err(decode_error)?;
+                record.sequence = entry.sequence;
+                record.timestamp = entry.timestamp;
+                Ok(self.record_liquidation(record))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Apply a live market event; returns false for unrelated payloads.
+    pub fn apply_event(&mut self, event: &MarketEvent) -> bool {
+        match &event.payload {
+            MarketEventPayload::FundingRateUpdated {
+                symbol,
+                funding_rate,
+                mark_price,
+            } => self.record_funding(FundingRecord {
+                sequence: event.sequence,
+                timestamp: event.timestamp,
+                symbol: symbol.clone(),
+                funding_rate: *funding_rate,
+                mark_price: *mark_price,
+            }),
+            MarketEventPayload::PositionLiquidated {
+                symbol,
+                side,
+                price,
+                quantity,
+            } => self.record_liquidation(LiquidationRecord {
+                sequence: event.sequence,
+                timestamp: event.timestamp,
+                symbol: symbol.clone(),
+                side: *side,
+                price: *price,
+                quantity: *quantity,
+            }),
+            _ => false,
+        }
+    }
+
+    /// Record a funding update. Returns false if its sequence is not new.
+    pub fn record_funding(&mut self, record: FundingRecord) -> bool {
+        let capacity = self.config.funding_retention;
+        self.last_sequence = self.last_sequence.max(record.sequence);
+        self.funding
+            .entry(record.symbol.as_str().to_string())
+            .or_insert_with(|| RingBuffer::new(capacity))
+            .push(record)
+    }
+
+    /// Record a liquidation. Returns false if its sequence is not new.
+    pub fn record_liquidation(&mut self, record: LiquidationRecord) -> bool {
+        let capacity = self.config.liquidation_retention;
+        self.last_sequence = self.last_sequence.max(record.sequence);
+        self.liquidations
+            .entry(record.symbol.as_str().to_string())
+            .or_insert_with(|| RingBuffer::new(capacity))
+            .push(record)
+    }
+
+    /// Funding records for `symbol` with `from <= timestamp <= to`.
+    ///
+    /// Pass the previous page's `next_cursor` to continue.
+    pub fn funding_history(
+        &self,
+        symbol: &str,
+        from: i64,
+        to: i64,
+        limit: usize,
+        cursor: Option<u64>,
+    ) -> HistoryPage<FundingRecord> {
+        self.funding
+            .get(symbol)
+            .map(|buf| buf.query(from, to, limit, cursor))
+            .unwrap_or_else(empty_page)
+    }
+
+    /// Liquidation records for `symbol` with `from <= timestamp <= to`.
+    ///
+    /// Pass the previous page's `next_cursor` to continue.
+    pub fn liquidation_history(
+        &self,
+        symbol: &str,
+        from: i64,
+        to: i64,
+        limit: usize,
+        cursor: Option<u64>,
+    ) -> HistoryPage<LiquidationRecord> {
+        self.liquidations
+            .get(symbol)
+            .map(|buf| buf.query(from, to, limit, cursor))
+            .unwrap_or_else(empty_page)
+    }
+
+    /// Highest sequence applied to the store.
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+}
+
+fn empty_page<T>() -> HistoryPage<T> {
+    HistoryPage {
+        records: Vec::new(),
+        next_cursor: None,
+        retention_exceeded: false,
+        oldest_available: None,
+    }
+}
+
+/// Build a journal entry for a funding record.
+pub fn funding_journal_entry(record: &FundingRecord) -> JournalEntry {
+    JournalEntry::new(
+        record.sequence,
+        record.timestamp,
+        FUNDING_EVENT_TYPE.to_string(),
+        bincode::serialize(record).expect("funding record serializes"),
+    )
+}
+
+/// Build a journal entry for a liquidation record.
+pub fn liquidation_journal_entry(record: &LiquidationRecord) -> JournalEntry {
+    JournalEntry::new(
+        record.sequence,
+        record.timestamp,
+        LIQUIDATION_EVENT_TYPE.to_string(),
+        bincode::serialize(record).expect("liquidation record serializes"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use persistence::journal::{JournalConfig, JournalWriter};
+
+    const BASE_TS: i64 = 1708123456789000000;
+    const HOUR: i64 = 3_600_000_000_000;
+
+    fn funding(seq: u64, hours: i64) -> FundingRecord {
+        FundingRecord {
+            sequence: seq,
+            timestamp: BASE_TS + hours * HOUR,
+            symbol: MarketId::new("BTC/USDT"),
+            funding_rate: Decimal::new(1, 4),
+            mark_price: Price::from_u64(50000),
+        }
+    }
+
+    fn liquidation(seq: u64, hours: i64) -> LiquidationRecord {
+        LiquidationRecord {
+            sequence: seq,
+            timestamp: BASE_TS + hours * HOUR,
+            symbol: MarketId::new("BTC/USDT"),
+            side: Side::SELL,
+            price: Price::from_u64(49500),
+            quantity: Quantity::from_str("0.5").unwrap(),
+        }
+    }
+
+    fn small_store() -> HistoryStore {
+        HistoryStore::new(HistoryConfig {
+            funding_retention: 4,
+            liquidation_retention: 4,
+        })
+    }
+
+    #[test]
+    fn test_retention_eviction_reported() {
+        let mut store = small_store();
+        for i in 1..=6 {
+            assert!(store.record_funding(funding(i * 10, i as i64 * 8)));
+        }
+
+        // Only the newest four remain
+        let page = store.funding_history("BTC/USDT", i64::MIN, i64::MAX, 10, None);
+        let seqs: Vec<u64> = page.records.iter().map(|r| r.sequence).collect();
+        assert_eq!(seqs, vec![30, 40, 50, 60]);
+        assert!(page.retention_exceeded);
+        assert_eq!(page.oldest_available, Some(BASE_TS + 24 * HOUR));
+
+        // A range starting after the evicted records is complete
+        let page = store.funding_history("BTC/USDT", BASE_TS + 20 * HOUR, i64::MAX, 10, None);
+        assert!(!page.retention_exceeded);
+        assert_eq!(page.records.len(), 4);
+    }
+
+    #[test]
+    fn test_cursor_stable_while_events_arrive() {
+        let mut store = HistoryStore::new(HistoryConfig::default());
+        for i in 1..=5 {
+            store.record_liquidation(liquidation(i, i as i64));
+        }
+
+        let first = store.liquidation_history("BTC/USDT", BASE_TS, i64::MAX, 2, None);
+        assert_eq!(first.records.len(), 2);
+        assert_eq!(first.next_cursor, Some(2));
+
+        // New events land mid-pagination
+        store.record_liquidation(liquidation(6, 6));
+        store.record_liquidation(liquidation(7, 7));
+
+        let mut seen: Vec<u64> = first.records.iter().map(|r| r.sequence).collect();
+        let mut cursor = first.next_cursor;
+        while let Some(c) = cursor {
+            let page = store.liquidation_history("BTC/USDT", BASE_TS, i64::MAX, 2, Some(c));
+            seen.extend(page.records.iter().map(|r| r.sequence));
+            cursor = page.next_cursor;
+        }
+        assert_eq!(seen, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_cursor_past_eviction_flags_retention() {
+        let mut store = small_store();
+        for i in 1..=4 {
+            store.record_funding(funding(i, i as i64));
+        }
+        let first = store.funding_history("BTC/USDT", i64::MIN, i64::MAX, 1, None);
+        assert!(!first.retention_exceeded);
+
+        // Records 2 and 3 are evicted before the client continues
+        for i in 5..=7 {
+            store.record_funding(funding(i, i as i64));
+        }
+        let next = store.funding_history("BTC/USDT", i64::MIN, i64::MAX, 10, first.next_cursor);
+        assert!(next.retention_exceeded);
+        assert_eq!(next.records.first().unwrap().sequence, 4);
+    }
+
+    #[test]
+    fn test_time_range_and_unknown_symbol() {
+        let mut store = HistoryStore::new(HistoryConfig::default());
+        for i in 1..=5 {
+            store.record_funding(funding(i, i as i64));
+        }
+        let page = store.funding_history("BTC/USDT", BASE_TS + 2 * HOUR, BASE_TS + 3 * HOUR, 10, None);
+        let seqs: Vec<u64> = page.records.iter().map(|r| r.sequence).collect();
+        assert_eq!(seqs, vec![2, 3]);
+        assert_eq!(page.next_cursor, None);
+
+        let empty = store.funding_history("ETH/USDT", i64::MIN, i64::MAX, 10, None);
+        assert!(empty.records.is_empty());
+        assert!(!empty.retention_exceeded);
+    }
+
+    #[test]
+    fn test_duplicate_sequence_ignored() {
+        let mut store = HistoryStore::new(HistoryConfig::default());
+        assert!(store.record_funding(funding(5, 1)));
+        assert!(!store.record_funding(funding(5, 1)));
+        assert!(!store.record_funding(funding(4, 1)));
+    }
+
+    #[test]
+    fn test_rebuild_from_journal_filters_by_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = JournalWriter::open(JournalConfig::new(dir.path())).unwrap();
+        writer.append(&funding_journal_entry(&funding(1, 0))).unwrap();
+        writer
+            .append(&JournalEntry::new(2, BASE_TS, "OrderAccepted".to_string(), vec![1, 2, 3]))
+            .unwrap();
+        writer.append(&liquidation_journal_entry(&liquidation(3, 1))).unwrap();
+        writer.append(&funding_journal_entry(&funding(4, 8))).unwrap();
+        writer.sync().unwrap();
+
+        let store = HistoryStore::rebuild_from_journal(HistoryConfig::default(), dir.path(), 0).unwrap();
+        assert_eq!(store.last_sequence(), 4);
+        let funding_page = store.funding_history("BTC/USDT", i64::MIN, i64::MAX, 10, None);
+        assert_eq!(funding_page.records, vec![funding(1, 0), funding(4, 8)]);
+        let liq_page = store.liquidation_history("BTC/USDT", i64::MIN, i64::MAX, 10, None);
+        assert_eq!(liq_page.records, vec![liquidation(3, 1)]);
+    }
+}
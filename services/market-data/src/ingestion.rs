@@ -7,9 +7,18 @@
 //! - No gaps in sequence numbers
 //! - No duplicate sequences
 //! - Strictly increasing sequence order
+//!
+//! `ReorderBuffer` sits in front of the ingester for transports that can
+//! redeliver or reorder: it drops duplicates and releases each source's
+//! events in sequence order, holding early arrivals until the gap fills.
+//!
+//! Also monitors spec §13 (Timestamp Policy) per-symbol monotonicity.
+//! Sequence is authoritative: a regressed timestamp is reported as a
+//! diagnostic but the event is still accepted unchanged.
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, warn};
 
 use crate::events::{MarketEvent, RecoveryRequest};
@@ -41,6 +50,114 @@ pub enum IngestionResult {
     GapDetected(RecoveryRequest),
 }
 
+/// A later sequence carried an earlier timestamp within one symbol.
+///
+/// Indicates an upstream clock bug; the event itself is still applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampRegression {
+    pub symbol: String,
+    pub sequence: u64,
+    pub prev_ts: i64,
+    pub ts: i64,
+}
+
+impl TimestampRegression {
+    /// How far the timestamp went backwards, in nanoseconds.
+    pub fn regression_nanos(&self) -> i64 {
+        self.prev_ts - self.ts
+    }
+}
+
+/// What counts as a timestamp regression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampTolerance {
+    /// Whether equal timestamps on consecutive events are allowed.
+    pub allow_equal: bool,
+    /// Backwards steps up to this many nanoseconds are not reported.
+    pub max_regression_nanos: i64,
+}
+
+impl Default for TimestampTolerance {
+    fn default() -> Self {
+        Self {
+            allow_equal: true,
+            max_regression_nanos: 0,
+        }
+    }
+}
+
+/// Per-symbol timestamp monotonicity monitor.
+#[derive(Debug, Clone, Default)]
+pub struct TimestampMonitor {
+    tolerance: TimestampTolerance,
+    /// Last timestamp seen per symbol.
+    last_ts: BTreeMap<String, i64>,
+    /// Regressions reported per symbol.
+    regressions: BTreeMap<String, u64>,
+    /// Largest regression observed so far.
+    worst: Option<TimestampRegression>,
+}
+
+impl TimestampMonitor {
+    pub fn new(tolerance: TimestampTolerance) -> Self {
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+
+    /// Observe an accepted event, returning a diagnostic on regression.
+    ///
+    /// The event's timestamp always becomes the new baseline for its
+    /// symbol, so one bad clock reading reports once, not on every
+    /// event that follows it. Events without a symbol are ignored.
+    pub fn observe(&mut self, event: &MarketEvent) -> Option<TimestampRegression> {
+        let symbol = event.symbol()?.as_str();
+        let ts = event.timestamp;
+        let prev_ts = self.last_ts.insert(symbol.to_string(), ts)?;
+
+        let regressed = if ts == prev_ts {
+            !self.tolerance.allow_equal
+        } else {
+            prev_ts - ts > self.tolerance.max_regression_nanos
+        };
+        if !regressed {
+            return None;
+        }
+
+        let regression = TimestampRegression {
+            symbol: symbol.to_string(),
+            sequence: event.sequence,
+            prev_ts,
+            ts,
+        };
+        *self.regressions.entry(symbol.to_string()).or_insert(0) += 1;
+        if self
+            .worst
+            .as_ref()
+            .is_none_or(|w| regression.regression_nanos() > w.regression_nanos())
+        {
+            self.worst = Some(regression.clone());
+        }
+        Some(regression)
+    }
+
+    /// Regressions reported for a symbol.
+    pub fn regressions(&self, symbol: &str) -> u64 {
+        self.regressions.get(symbol).copied().unwrap_or(0)
+    }
+
+    /// Regressions reported across all symbols.
+    pub fn total_regressions(&self) -> u64 {
+        self.regressions.values().sum()
+    }
+
+    /// Largest regression observed, for ops dashboards.
+    pub fn worst_regression(&self) -> Option<&TimestampRegression> {
+        self.worst.as_ref()
+    }
+}
+
 /// Configuration for the event ingester.
 #[derive(Debug, Clone)]
 pub struct IngesterConfig {
@@ -48,6 +165,8 @@ pub struct IngesterConfig {
     pub buffer_capacity: usize,
     /// Maximum number of recent sequence IDs to track for dedup.
     pub dedup_window: usize,
+    /// Timestamp regression tolerance (spec §13).
+    pub timestamp_tolerance: TimestampTolerance,
 }
 
 impl Default for IngesterConfig {
@@ -55,6 +174,7 @@ impl Default for IngesterConfig {
         Self {
             buffer_capacity: 100_000,
             dedup_window: 10_000,
+            timestamp_tolerance: TimestampTolerance::default(),
         }
     }
 }
@@ -78,6 +198,10 @@ pub struct EventIngester {
     events_dropped: u64,
     /// Total gaps detected.
     gaps_detected: u64,
+    /// Per-symbol timestamp monotonicity monitor.
+    timestamp_monitor: TimestampMonitor,
+    /// Regression diagnostics not yet drained.
+    timestamp_diagnostics: Vec<TimestampRegression>,
 }
 
 impl EventIngester {
@@ -93,10 +217,12 @@ impl EventIngester {
             last_sequence: None,
             seen_sequences: VecDeque::with_capacity(config.dedup_window),
             buffer: VecDeque::with_capacity(config.buffer_capacity),
+            timestamp_monitor: TimestampMonitor::new(config.timestamp_tolerance),
             config,
             events_accepted: 0,
             events_dropped: 0,
             gaps_detected: 0,
+            timestamp_diagnostics: Vec::new(),
         }
     }
 
@@ -160,6 +286,18 @@ impl EventIngester {
         self.last_sequence = Some(seq);
         self.events_accepted += 1;
 
+        // Sequence is authoritative: report the regression, keep the event as-is
+        if let Some(regression) = self.timestamp_monitor.observe(&event) {
+            warn!(
+                symbol = %regression.symbol,
+                sequence = regression.sequence,
+                prev_ts = regression.prev_ts,
+                ts = regression.ts,
+                "Timestamp regression detected"
+            );
+            self.timestamp_diagnostics.push(regression);
+        }
+
         debug!(
             sequence = seq,
             event_type = event.event_type_label(),
@@ -229,6 +367,16 @@ impl EventIngester {
         self.gaps_detected
     }
 
+    /// Timestamp monotonicity monitor (counters and worst regression).
+    pub fn timestamp_monitor(&self) -> &TimestampMonitor {
+        &self.timestamp_monitor
+    }
+
+    /// Drain timestamp regression diagnostics raised since the last call.
+    pub fn drain_timestamp_regressions(&mut self) -> Vec<TimestampRegression> {
+        std::mem::take(&mut self.timestamp_diagnostics)
+    }
+
     /// Check if a sequence number has been recently seen.
     fn is_duplicate(&self, seq: u64) -> bool {
         self.seen_sequences.contains(&seq)
@@ -260,6 +408,152 @@ impl EventIngester {
     }
 }
 
+/// Configuration for the reorder buffer.
+#[derive(Debug, Clone)]
+pub struct ReorderConfig {
+    /// Out-of-order events held per source while waiting for a gap to fill.
+    pub window: usize,
+}
+
+impl Default for ReorderConfig {
+    fn default() -> Self {
+        Self { window: 1_024 }
+    }
+}
+
+/// Result of pushing an event into the reorder buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReorderOutcome {
+    /// Events now ready to apply, in sequence order. Empty when the event
+    /// was a duplicate or is being held until the gap before it fills.
+    Ready(Vec<MarketEvent>),
+    /// The window filled before the gap did. The event was not retained;
+    /// the consumer should fetch a snapshot and call [`ReorderBuffer::resync`].
+    GapDetected { expected: u64, got: u64 },
+}
+
+/// Reorder buffer counters, for the metrics endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorderStats {
+    /// Events at or below the applied sequence, or already held.
+    pub duplicates_dropped: u64,
+    /// Held events released once the gap before them filled.
+    pub reorders_healed: u64,
+    /// Times a source overflowed its window (once per stall).
+    pub gaps: u64,
+}
+
+/// Per-source sequencing state.
+#[derive(Debug, Clone, Default)]
+struct SourceState {
+    /// Last sequence released for this source (0 = expecting 1).
+    last_applied: u64,
+    /// Early arrivals keyed by sequence.
+    pending: BTreeMap<u64, MarketEvent>,
+    /// Whether a gap was signaled and no resync has happened since.
+    stalled: bool,
+}
+
+impl SourceState {
+    /// Move held events that now follow `last_applied` into `ready`.
+    fn release(&mut self, ready: &mut Vec<MarketEvent>) -> u64 {
+        let mut released = 0;
+        while let Some(event) = self.pending.remove(&(self.last_applied + 1)) {
+            self.last_applied = event.sequence;
+            ready.push(event);
+            released += 1;
+        }
+        released
+    }
+}
+
+/// Dedupes and orders events per source ahead of the [`EventIngester`].
+///
+/// Sequences are tracked independently per `MarketEvent::source` and a
+/// new source is expected to start at 1; use [`ReorderBuffer::resync`] to
+/// join a stream mid-way.
+#[derive(Debug, Clone, Default)]
+pub struct ReorderBuffer {
+    config: ReorderConfig,
+    sources: BTreeMap<String, SourceState>,
+    stats: ReorderStats,
+}
+
+impl ReorderBuffer {
+    pub fn new(config: ReorderConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Push one received event.
+    pub fn push(&mut self, event: MarketEvent) -> ReorderOutcome {
+        let seq = event.sequence;
+        let state = self.sources.entry(event.source.clone()).or_default();
+
+        if seq <= state.last_applied || state.pending.contains_key(&seq) {
+            self.stats.duplicates_dropped += 1;
+            debug!(source = %event.source, sequence = seq, "Dropping duplicate event");
+            return ReorderOutcome::Ready(Vec::new());
+        }
+
+        if seq == state.last_applied + 1 {
+            state.last_applied = seq;
+            let mut ready = vec![event];
+            self.stats.reorders_healed += state.release(&mut ready);
+            return ReorderOutcome::Ready(ready);
+        }
+
+        if state.pending.len() >= self.config.window {
+            let expected = state.last_applied + 1;
+            if !state.stalled {
+                state.stalled = true;
+                self.stats.gaps += 1;
+                warn!(
+                    source = %event.source,
+                    expected,
+                    got = seq,
+                    window = self.config.window,
+                    "Reorder window exceeded — snapshot needed"
+                );
+            }
+            return ReorderOutcome::GapDetected { expected, got: seq };
+        }
+
+        state.pending.insert(seq, event);
+        ReorderOutcome::Ready(Vec::new())
+    }
+
+    /// Restart a source from a snapshot taken at `sequence`.
+    ///
+    /// Held events the snapshot covers are discarded; any that follow it
+    /// contiguously are returned, ready to apply.
+    pub fn resync(&mut self, source: &str, sequence: u64) -> Vec<MarketEvent> {
+        let state = self.sources.entry(source.to_string()).or_default();
+        state.last_applied = sequence;
+        state.stalled = false;
+        state.pending = state.pending.split_off(&(sequence + 1));
+        let mut ready = Vec::new();
+        self.stats.reorders_healed += state.release(&mut ready);
+        ready
+    }
+
+    /// Last sequence released for a source.
+    pub fn last_applied(&self, source: &str) -> Option<u64> {
+        self.sources.get(source).map(|s| s.last_applied)
+    }
+
+    /// Events held for a source.
+    pub fn pending(&self, source: &str) -> usize {
+        self.sources.get(source).map_or(0, |s| s.pending.len())
+    }
+
+    pub fn stats(&self) -> ReorderStats {
+        self.stats
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -358,6 +652,7 @@ mod tests {
         let config = IngesterConfig {
             buffer_capacity: 3,
             dedup_window: 100,
+            ..IngesterConfig::default()
         };
         let mut ingester = EventIngester::new(config);
 
@@ -398,6 +693,7 @@ mod tests {
         let config = IngesterConfig {
             buffer_capacity: 100_000,
             dedup_window: 3,
+            ..IngesterConfig::default()
         };
         let mut ingester = EventIngester::new(config);
 
@@ -447,4 +743,189 @@ mod tests {
         assert_eq!(ingester.events_dropped(), 1);
         assert_eq!(ingester.gaps_detected(), 1);
     }
+
+    fn make_trade(seq: u64, timestamp: i64, price: u64) -> MarketEvent {
+        MarketEvent {
+            event_id: Uuid::now_v7(),
+            sequence: seq,
+            timestamp,
+            source: "matching-engine".to_string(),
+            payload: MarketEventPayload::TradeExecuted {
+                trade_id: types::ids::TradeId::new(),
+                symbol: MarketId::new("BTC/USDT"),
+                maker_order_id: OrderId::new(),
+                taker_order_id: OrderId::new(),
+                maker_account_id: AccountId::new(),
+                taker_account_id: AccountId::new(),
+                price: Price::from_u64(price),
+                quantity: Quantity::from_str("1.0").unwrap(),
+                side: Side::BUY,
+                executed_at: timestamp,
+            },
+            schema_version: "1.0.0".to_string(),
+            correlation_id: Uuid::now_v7(),
+        }
+    }
+
+    #[test]
+    fn test_timestamp_regression_applied_and_reported_once() {
+        use crate::candles::{CandleBuilder, Timeframe};
+
+        const MINUTE: i64 = 60_000_000_000;
+        let base = 1708123440000000000; // minute-aligned
+        let mut ingester = EventIngester::with_defaults();
+
+        ingester.ingest(make_trade(1, base + 10 * 1_000_000_000, 50000)).unwrap();
+        ingester.ingest(make_trade(2, base + MINUTE + 5_000_000_000, 50100)).unwrap();
+        // Upstream clock bug: seq 3 is stamped back in the first minute
+        let result = ingester.ingest(make_trade(3, base + 20 * 1_000_000_000, 49900)).unwrap();
+        assert_eq!(result, IngestionResult::Accepted);
+        ingester.ingest(make_trade(4, base + MINUTE + 30_000_000_000, 50200)).unwrap();
+
+        let diagnostics = ingester.drain_timestamp_regressions();
+        assert_eq!(
+            diagnostics,
+            vec![TimestampRegression {
+                symbol: "BTC/USDT".to_string(),
+                sequence: 3,
+                prev_ts: base + MINUTE + 5_000_000_000,
+                ts: base + 20 * 1_000_000_000,
+            }]
+        );
+        let monitor = ingester.timestamp_monitor();
+        assert_eq!(monitor.regressions("BTC/USDT"), 1);
+        assert_eq!(monitor.worst_regression(), diagnostics.first());
+
+        // All four events are applied with their timestamps untouched
+        let events = ingester.drain_buffer();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2].timestamp, base + 20 * 1_000_000_000);
+
+        let mut builder = CandleBuilder::new(Timeframe::M1, MarketId::new("BTC/USDT"), 10);
+        for event in &events {
+            if let MarketEventPayload::TradeExecuted { price, quantity, .. } = &event.payload {
+                builder.process_trade(*price, quantity.as_decimal(), event.timestamp);
+            }
+        }
+        // The regressed trade lands in the first minute, not the current one
+        let first = builder.get_candles(10).pop().unwrap();
+        assert_eq!(first.open_time, base);
+        assert_eq!(first.trade_count, 2);
+        assert_eq!(first.close, rust_decimal::Decimal::from(49900));
+        let current = builder.current_candle().unwrap();
+        assert_eq!(current.open_time, base + MINUTE);
+        assert_eq!(current.trade_count, 2);
+    }
+
+    #[test]
+    fn test_timestamp_tolerance() {
+        let mut strict = TimestampMonitor::new(TimestampTolerance {
+            allow_equal: false,
+            max_regression_nanos: 0,
+        });
+        assert!(strict.observe(&make_trade(1, 1000, 50000)).is_none());
+        assert!(strict.observe(&make_trade(2, 1000, 50000)).is_some());
+
+        let mut lenient = TimestampMonitor::new(TimestampTolerance {
+            allow_equal: true,
+            max_regression_nanos: 500,
+        });
+        assert!(lenient.observe(&make_trade(1, 1000, 50000)).is_none());
+        assert!(lenient.observe(&make_trade(2, 1000, 50000)).is_none());
+        assert!(lenient.observe(&make_trade(3, 600, 50000)).is_none());
+        assert!(lenient.observe(&make_trade(4, 0, 50000)).is_some());
+        assert_eq!(lenient.total_regressions(), 1);
+    }
+
+    fn from_source(seq: u64, source: &str) -> MarketEvent {
+        MarketEvent {
+            source: source.to_string(),
+            ..make_event(seq)
+        }
+    }
+
+    fn ready(outcome: ReorderOutcome) -> Vec<u64> {
+        match outcome {
+            ReorderOutcome::Ready(events) => events.iter().map(|e| e.sequence).collect(),
+            other => panic!("expected Ready, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reorder_shuffled_stream_with_duplicates_matches_in_order() {
+        let recorded: Vec<MarketEvent> = (1..=200).map(make_event).collect();
+
+        // Redeliver every 7th event and shuffle within blocks of 16 with a
+        // fixed LCG, so no event lands more than 16 places from home.
+        let mut received: Vec<MarketEvent> = Vec::new();
+        for event in &recorded {
+            received.push(event.clone());
+            if event.sequence % 7 == 0 {
+                received.push(event.clone());
+            }
+        }
+        let mut rng: u64 = 42;
+        for block in received.chunks_mut(16) {
+            for i in (1..block.len()).rev() {
+                rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                block.swap(i, (rng >> 33) as usize % (i + 1));
+            }
+        }
+
+        let mut buffer = ReorderBuffer::new(ReorderConfig { window: 32 });
+        let mut applied = Vec::new();
+        for event in received {
+            match buffer.push(event) {
+                ReorderOutcome::Ready(events) => applied.extend(events),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        assert_eq!(applied, recorded);
+        let stats = buffer.stats();
+        assert_eq!(stats.duplicates_dropped, 28);
+        assert!(stats.reorders_healed > 0);
+        assert_eq!(stats.gaps, 0);
+        assert_eq!(buffer.pending("matching-engine"), 0);
+    }
+
+    #[test]
+    fn test_reorder_tracks_sources_independently() {
+        let mut buffer = ReorderBuffer::default();
+
+        assert_eq!(ready(buffer.push(from_source(2, "a"))), Vec::<u64>::new());
+        assert_eq!(ready(buffer.push(from_source(1, "b"))), vec![1]);
+        assert_eq!(ready(buffer.push(from_source(1, "a"))), vec![1, 2]);
+        assert_eq!(ready(buffer.push(from_source(1, "b"))), Vec::<u64>::new());
+
+        assert_eq!(buffer.last_applied("a"), Some(2));
+        assert_eq!(buffer.last_applied("b"), Some(1));
+        assert_eq!(buffer.stats().reorders_healed, 1);
+        assert_eq!(buffer.stats().duplicates_dropped, 1);
+    }
+
+    #[test]
+    fn test_reorder_window_exceeded_signals_gap_then_resyncs() {
+        let mut buffer = ReorderBuffer::new(ReorderConfig { window: 2 });
+
+        ready(buffer.push(make_event(1)));
+        // Sequence 2 is lost
+        ready(buffer.push(make_event(3)));
+        ready(buffer.push(make_event(4)));
+        assert_eq!(buffer.push(make_event(5)), ReorderOutcome::GapDetected { expected: 2, got: 5 });
+        assert_eq!(buffer.push(make_event(6)), ReorderOutcome::GapDetected { expected: 2, got: 6 });
+        assert_eq!(buffer.stats().gaps, 1);
+
+        // Snapshot taken at 3: 3 is covered, 4 follows it
+        assert_eq!(
+            buffer
+                .resync("matching-engine", 3)
+                .iter()
+                .map(|e| e.sequence)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(ready(buffer.push(make_event(5))), vec![5]);
+        assert_eq!(buffer.pending("matching-engine"), 0);
+    }
 }
@@ -3,10 +3,17 @@
 //! Consumes matching-engine events and produces:
 //! - Order book mirrors with depth aggregation
 //! - Book deltas for incremental client updates
+//! - Snapshot+delta book stream with gap detection and resync
+//! - Conflated deltas with a configurable publish interval
 //! - Full depth snapshots for reconnect logic
 //! - Public trade streams
 //! - OHLCV candle aggregation (multi-timeframe)
+//! - Candle channel with live (unclosed) updates
 //! - WebSocket real-time feeds with backpressure
+//! - WebSocket server with a JSON subscribe/unsubscribe protocol
+//! - Historical funding and liquidation queries
+//! - Book and candle rebuild from the persistence journal
+//! - Prometheus metrics at `/metrics`
 //!
 //! Implements spec §9 section 3.8 (Market Data Service) with deterministic
 //! behavior per §12 (Determinism Rules) and §14 (Sequence Numbering).
@@ -39,13 +46,19 @@ pub mod events;
 pub mod ingestion;
 pub mod order_book;
 pub mod delta;
+pub mod deltas;
+pub mod conflation;
 pub mod snapshot;
 pub mod trades;
 pub mod candles;
+pub mod candle_stream;
 pub mod websocket;
+pub mod ws;
 pub mod backpressure;
 pub mod replay;
 pub mod metrics;
+pub mod history;
+pub mod rebuild;
 
 // Library version
 pub const SERVICE_VERSION: &str = "0.1.0";
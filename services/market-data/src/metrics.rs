@@ -5,10 +5,27 @@
 //! queue depths, dropped messages, and resource usage.
 //!
 //! Implements spec §10: failure detection via observable metrics.
+//!
+//! [`router`] serves everything on `GET /metrics` in the Prometheus text
+//! format, and [`track_requests`] adds per-route request counts and latency
+//! histograms to any router.
 
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use metrics_core::{Counter, Histogram, Registry, CONTENT_TYPE, DEFAULT_LATENCY_BUCKETS};
+use persistence::journal::JournalStats;
+
+use crate::ingestion::ReorderStats;
+use crate::ws::WsHub;
 
 /// Core metrics for the Market Data Service.
 pub struct ServiceMetrics {
@@ -29,16 +46,33 @@ pub struct ServiceMetrics {
     pub replay_events: AtomicU64,
     pub replay_duration_ms: AtomicU64,
 
+    // Ingestion reorder buffer
+    pub ingest_duplicates_dropped: AtomicU64,
+    pub ingest_reorders_healed: AtomicU64,
+    pub ingest_gaps: AtomicU64,
+
     // Per-client metrics
     pub connected_clients: AtomicU64,
     pub messages_dropped_backpressure: AtomicU64,
 
+    // Journal writer
+    pub journal_appends: AtomicU64,
+    pub journal_fsyncs: AtomicU64,
+    pub journal_rotations: AtomicU64,
+    pub journal_bytes_written: AtomicU64,
+
+    // HTTP endpoints, by method, route template and status
+    registry: Registry,
+    pub http_requests: Counter,
+    pub http_latency: Histogram,
+
     // Alerts
     pub alerts: Mutex<Vec<Alert>>,
 }
 
 impl ServiceMetrics {
     pub fn new() -> Self {
+        let registry = Registry::new();
         Self {
             events_processed: AtomicU64::new(0),
             events_dropped: AtomicU64::new(0),
@@ -49,8 +83,27 @@ impl ServiceMetrics {
             snapshot_build_ns: Mutex::new(LatencyTracker::new(100)),
             replay_events: AtomicU64::new(0),
             replay_duration_ms: AtomicU64::new(0),
+            ingest_duplicates_dropped: AtomicU64::new(0),
+            ingest_reorders_healed: AtomicU64::new(0),
+            ingest_gaps: AtomicU64::new(0),
             connected_clients: AtomicU64::new(0),
             messages_dropped_backpressure: AtomicU64::new(0),
+            journal_appends: AtomicU64::new(0),
+            journal_fsyncs: AtomicU64::new(0),
+            journal_rotations: AtomicU64::new(0),
+            journal_bytes_written: AtomicU64::new(0),
+            http_requests: registry.counter(
+                "market_data_http_requests_total",
+                "HTTP requests served, by route template.",
+                &["method", "route", "status"],
+            ),
+            http_latency: registry.histogram(
+                "market_data_http_request_duration_seconds",
+                "Time to produce an HTTP response.",
+                &["method", "route"],
+                DEFAULT_LATENCY_BUCKETS,
+            ),
+            registry,
             alerts: Mutex::new(Vec::new()),
         }
     }
@@ -90,6 +143,21 @@ impl ServiceMetrics {
         self.replay_duration_ms.store(duration_ms, Ordering::Relaxed);
     }
 
+    /// Record the ingestion reorder buffer counters.
+    pub fn record_reorder_stats(&self, stats: &ReorderStats) {
+        self.ingest_duplicates_dropped.store(stats.duplicates_dropped, Ordering::Relaxed);
+        self.ingest_reorders_healed.store(stats.reorders_healed, Ordering::Relaxed);
+        self.ingest_gaps.store(stats.gaps, Ordering::Relaxed);
+    }
+
+    /// Record the journal writer's running totals.
+    pub fn record_journal_stats(&self, stats: &JournalStats) {
+        self.journal_appends.store(stats.appends, Ordering::Relaxed);
+        self.journal_fsyncs.store(stats.fsyncs, Ordering::Relaxed);
+        self.journal_rotations.store(stats.rotations, Ordering::Relaxed);
+        self.journal_bytes_written.store(stats.bytes_written, Ordering::Relaxed);
+    }
+
     /// Update connected client count.
     pub fn set_connected_clients(&self, count: u64) {
         self.connected_clients.store(count, Ordering::Relaxed);
@@ -153,10 +221,93 @@ impl ServiceMetrics {
         m.insert("snapshots_built".to_string(), self.snapshots_built.load(Ordering::Relaxed));
         m.insert("replay_events".to_string(), self.replay_events.load(Ordering::Relaxed));
         m.insert("replay_duration_ms".to_string(), self.replay_duration_ms.load(Ordering::Relaxed));
+        m.insert("ingest_duplicates_dropped".to_string(), self.ingest_duplicates_dropped.load(Ordering::Relaxed));
+        m.insert("ingest_reorders_healed".to_string(), self.ingest_reorders_healed.load(Ordering::Relaxed));
+        m.insert("ingest_gaps".to_string(), self.ingest_gaps.load(Ordering::Relaxed));
         m.insert("connected_clients".to_string(), self.connected_clients.load(Ordering::Relaxed));
         m.insert("messages_dropped_backpressure".to_string(), self.messages_dropped_backpressure.load(Ordering::Relaxed));
+        m.insert("journal_appends".to_string(), self.journal_appends.load(Ordering::Relaxed));
+        m.insert("journal_fsyncs".to_string(), self.journal_fsyncs.load(Ordering::Relaxed));
+        m.insert("journal_rotations".to_string(), self.journal_rotations.load(Ordering::Relaxed));
+        m.insert("journal_bytes_written".to_string(), self.journal_bytes_written.load(Ordering::Relaxed));
         m
     }
+
+    /// Prometheus text exposition of the HTTP metrics and every value in
+    /// [`export`](Self::export), names prefixed with `market_data_`.
+    pub fn render(&self) -> String {
+        let counters = [
+            ("events_processed_total", "Events applied.", &self.events_processed),
+            ("events_dropped_total", "Events discarded.", &self.events_dropped),
+            ("messages_broadcast_total", "Messages sent to subscribers.", &self.messages_broadcast),
+            ("snapshots_built_total", "Depth snapshots built.", &self.snapshots_built),
+            ("ingest_duplicates_dropped_total", "Duplicate events dropped.", &self.ingest_duplicates_dropped),
+            ("ingest_reorders_healed_total", "Held events released in order.", &self.ingest_reorders_healed),
+            ("ingest_gaps_total", "Sequence gaps that overflowed the reorder window.", &self.ingest_gaps),
+            (
+                "messages_dropped_backpressure_total",
+                "Messages dropped for slow clients.",
+                &self.messages_dropped_backpressure,
+            ),
+            ("journal_appends_total", "Entries appended to the journal.", &self.journal_appends),
+            ("journal_fsyncs_total", "Journal file fsyncs.", &self.journal_fsyncs),
+            ("journal_rotations_total", "Journal file rotations.", &self.journal_rotations),
+            ("journal_bytes_written_total", "Bytes written to journal files.", &self.journal_bytes_written),
+        ];
+        let gauges = [
+            ("connected_clients", "Open WebSocket connections.", &self.connected_clients),
+            ("replay_events", "Events in the last replay.", &self.replay_events),
+            ("replay_duration_ms", "Duration of the last replay.", &self.replay_duration_ms),
+        ];
+
+        // Values live in the atomics; a scratch registry formats them
+        let values = Registry::new();
+        for (name, help, value) in counters {
+            values
+                .counter(&format!("market_data_{}", name), help, &[])
+                .set(&[], value.load(Ordering::Relaxed));
+        }
+        for (name, help, value) in gauges {
+            values
+                .gauge(&format!("market_data_{}", name), help, &[])
+                .set(&[], value.load(Ordering::Relaxed) as i64);
+        }
+        let mut out = self.registry.render();
+        out.push_str(&values.render());
+        out
+    }
+}
+
+/// Router serving [`ServiceMetrics::render`] at `/metrics`, with the
+/// connection count read from `hub` on each scrape.
+pub fn router(metrics: Arc<ServiceMetrics>, hub: Arc<WsHub>) -> Router {
+    Router::new().route("/metrics", get(scrape)).with_state((metrics, hub))
+}
+
+async fn scrape(State((metrics, hub)): State<(Arc<ServiceMetrics>, Arc<WsHub>)>) -> impl IntoResponse {
+    metrics.set_connected_clients(hub.client_count() as u64);
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics.render())
+}
+
+/// Count and time every request to `router`'s routes by route template.
+pub fn track_requests(router: Router, metrics: Arc<ServiceMetrics>) -> Router {
+    router.route_layer(middleware::from_fn_with_state(metrics, track))
+}
+
+async fn track(State(metrics): State<Arc<ServiceMetrics>>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |p| p.as_str().to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    metrics.http_requests.inc(&[method.as_str(), &route, response.status().as_str()]);
+    metrics
+        .http_latency
+        .observe(&[method.as_str(), &route], started.elapsed().as_secs_f64());
+    response
 }
 
 impl Default for ServiceMetrics {
@@ -256,6 +407,8 @@ impl Default for AlertThresholds {
 mod tests {
     use super::*;
 
+    const T0: i64 = 1708123456789000000;
+
     #[test]
     fn test_metrics_recording() {
         let metrics = ServiceMetrics::new();
@@ -278,10 +431,10 @@ mod tests {
         }
 
         let p50 = tracker.percentile(50).unwrap();
-        assert!(p50 >= 49 && p50 <= 51);
+        assert!((49..=51).contains(&p50));
 
         let p99 = tracker.percentile(99).unwrap();
-        assert!(p99 >= 98 && p99 <= 100);
+        assert!((98..=100).contains(&p99));
     }
 
     #[test]
@@ -340,6 +493,96 @@ mod tests {
         assert_eq!(exported["replay_duration_ms"], 500);
     }
 
+    #[test]
+    fn test_reorder_stats_metric() {
+        let metrics = ServiceMetrics::new();
+        metrics.record_reorder_stats(&ReorderStats {
+            duplicates_dropped: 4,
+            reorders_healed: 9,
+            gaps: 1,
+        });
+
+        let exported = metrics.export();
+        assert_eq!(exported["ingest_duplicates_dropped"], 4);
+        assert_eq!(exported["ingest_reorders_healed"], 9);
+        assert_eq!(exported["ingest_gaps"], 1);
+    }
+
+    #[test]
+    fn test_journal_stats_metric() {
+        let metrics = ServiceMetrics::new();
+        metrics.record_journal_stats(&JournalStats {
+            appends: 12,
+            bytes_written: 4096,
+            fsyncs: 3,
+            rotations: 1,
+        });
+
+        let exported = metrics.export();
+        assert_eq!(exported["journal_appends"], 12);
+        assert_eq!(exported["journal_fsyncs"], 3);
+        assert_eq!(exported["journal_rotations"], 1);
+        assert_eq!(exported["journal_bytes_written"], 4096);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exports_routes_and_counters() {
+        use axum::body::{to_bytes, Body};
+        use metrics_core::samples;
+        use tower::ServiceExt;
+
+        let metrics = Arc::new(ServiceMetrics::new());
+        let hub = Arc::new(WsHub::default());
+        hub.connect(T0);
+        hub.connect(T0);
+        metrics.record_reorder_stats(&ReorderStats {
+            duplicates_dropped: 0,
+            reorders_healed: 2,
+            gaps: 3,
+        });
+        metrics.record_journal_stats(&JournalStats {
+            appends: 12,
+            bytes_written: 4096,
+            fsyncs: 3,
+            rotations: 1,
+        });
+        let routes = Router::new()
+            .route("/probe", get(|| async { "ok" }))
+            .merge(crate::ws::router(hub.clone()));
+        let app = track_requests(routes, metrics.clone()).merge(router(metrics.clone(), hub));
+
+        let send = |uri: &str| app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap());
+        for _ in 0..2 {
+            assert_eq!(send("/probe").await.unwrap().status(), 200);
+        }
+        // Not an upgrade request, so refused, but still counted under its route
+        let refused = send("/ws").await.unwrap().status();
+        assert!(refused.is_client_error());
+        let response = send("/metrics").await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let samples = samples(std::str::from_utf8(&body).unwrap());
+
+        let probe = "method=\"GET\",route=\"/probe\"";
+        assert_eq!(samples[&format!("market_data_http_requests_total{{{},status=\"200\"}}", probe)], 2.0);
+        let ws = format!("method=\"GET\",route=\"/ws\",status=\"{}\"", refused.as_u16());
+        assert_eq!(samples[&format!("market_data_http_requests_total{{{}}}", ws)], 1.0);
+        let bucket = |le: &str| {
+            samples[&format!("market_data_http_request_duration_seconds_bucket{{{},le=\"{}\"}}", probe, le)]
+        };
+        assert!(bucket("0.001") <= bucket("10"));
+        assert_eq!(bucket("+Inf"), 2.0);
+        assert!(samples.keys().all(|series| !series.contains("/metrics")));
+
+        assert_eq!(samples["market_data_connected_clients"], 2.0);
+        assert_eq!(samples["market_data_ingest_gaps_total"], 3.0);
+        assert_eq!(samples["market_data_ingest_reorders_healed_total"], 2.0);
+        assert_eq!(samples["market_data_journal_appends_total"], 12.0);
+        assert_eq!(samples["market_data_journal_fsyncs_total"], 3.0);
+        assert_eq!(samples["market_data_journal_rotations_total"], 1.0);
+        assert_eq!(samples["market_data_events_processed_total"], 0.0);
+    }
+
     #[test]
     fn test_backpressure_drop_metric() {
         let metrics = ServiceMetrics::new();
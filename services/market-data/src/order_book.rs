@@ -15,6 +15,7 @@ use std::collections::BTreeMap;
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use types::ids::{MarketId, OrderId};
 use types::numeric::{Price, Quantity};
 use types::order::Side;
@@ -49,6 +50,7 @@ impl PriceLevel {
 /// Tracks an individual order resting on the book for accurate level updates.
 #[derive(Debug, Clone)]
 struct RestingOrder {
+    #[allow(dead_code)]
     pub order_id: OrderId,
     pub side: Side,
     pub price: Price,
@@ -273,11 +275,17 @@ impl OrderBookState {
             .cloned()
             .collect();
 
+        let checksum = book_checksum(
+            bids.iter().map(|l| (l.price.as_decimal(), l.total_quantity)),
+            asks.iter().map(|l| (l.price.as_decimal(), l.total_quantity)),
+            CHECKSUM_LEVELS,
+        );
         DepthSnapshot {
             symbol: self.symbol.clone(),
             bids,
             asks,
             last_sequence: self.last_sequence,
+            checksum,
         }
     }
 
@@ -311,6 +319,23 @@ impl OrderBookState {
         self.asks.values().cloned().collect()
     }
 
+    /// CRC-32 of the top `levels` per side (see [`book_checksum`]).
+    pub fn checksum(&self, levels: usize) -> u32 {
+        book_checksum(
+            self.bids.values().rev().map(|l| (l.price.as_decimal(), l.total_quantity)),
+            self.asks.values().map(|l| (l.price.as_decimal(), l.total_quantity)),
+            levels,
+        )
+    }
+
+    /// Checksum of all the book's levels (see [`depth_checksum`]).
+    pub fn full_checksum(&self) -> String {
+        depth_checksum(
+            self.bids.values().rev().map(|l| (l.price, l.total_quantity)),
+            self.asks.values().map(|l| (l.price, l.total_quantity)),
+        )
+    }
+
     /// Recalculate best bid and best ask from the book state.
     fn update_best_prices(&mut self) {
         // Best bid = highest price in bids (last in BTreeMap)
@@ -325,6 +350,68 @@ impl OrderBookState {
     }
 }
 
+/// SHA-256 over (price, quantity) levels, bids then asks, best first.
+///
+/// Values are normalized before hashing so books built by other components
+/// (e.g. the matching engine) agree regardless of decimal scale.
+pub fn depth_checksum(
+    bids: impl IntoIterator<Item = (Price, Decimal)>,
+    asks: impl IntoIterator<Item = (Price, Decimal)>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for (price, quantity) in bids {
+        hasher.update(format!("{}:{},", price.as_decimal().normalize(), quantity.normalize()).as_bytes());
+    }
+    hasher.update(b"---");
+    for (price, quantity) in asks {
+        hasher.update(format!("{}:{},", price.as_decimal().normalize(), quantity.normalize()).as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Levels per side covered by the checksum on depth and delta messages.
+pub const CHECKSUM_LEVELS: usize = 25;
+
+/// Canonical string of the top `levels` per side, hashed by [`book_checksum`].
+///
+/// `price:quantity` pairs, best first, alternating bid and ask by rank
+/// (`bid0:ask0:bid1:ask1:...`), all joined with `:`. A side that runs out
+/// is skipped. Decimals are normalized: no trailing zeros, no exponent.
+pub fn book_checksum_string(
+    bids: impl IntoIterator<Item = (Decimal, Decimal)>,
+    asks: impl IntoIterator<Item = (Decimal, Decimal)>,
+    levels: usize,
+) -> String {
+    let mut bids = bids.into_iter().take(levels);
+    let mut asks = asks.into_iter().take(levels);
+    let mut parts = Vec::new();
+    loop {
+        let bid = bids.next();
+        let ask = asks.next();
+        if bid.is_none() && ask.is_none() {
+            break;
+        }
+        for (price, quantity) in bid.into_iter().chain(ask) {
+            parts.push(format!("{}:{}", price.normalize(), quantity.normalize()));
+        }
+    }
+    parts.join(":")
+}
+
+/// CRC-32 of [`book_checksum_string`]: the ISO-HDLC variant of zlib and
+/// most standard libraries, not CRC-32C.
+///
+/// Clients recompute it over their local book (`wasm_core::book`) after
+/// each snapshot or delta; a mismatch means the replica drifted and must
+/// resync.
+pub fn book_checksum(
+    bids: impl IntoIterator<Item = (Decimal, Decimal)>,
+    asks: impl IntoIterator<Item = (Decimal, Decimal)>,
+    levels: usize,
+) -> u32 {
+    crc32fast::hash(book_checksum_string(bids, asks, levels).as_bytes())
+}
+
 /// A snapshot of the order book depth at a point in time.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DepthSnapshot {
@@ -335,6 +422,8 @@ pub struct DepthSnapshot {
     pub asks: Vec<PriceLevel>,
     /// Sequence number at the time of the snapshot.
     pub last_sequence: u64,
+    /// [`book_checksum`] of the top [`CHECKSUM_LEVELS`] levels above.
+    pub checksum: u32,
 }
 
 #[cfg(test)]
@@ -532,7 +621,7 @@ mod tests {
                 Side::BUY,
                 Price::from_u64(50000 - i * 100),
                 Quantity::from_str("1.0").unwrap(),
-                i as u64,
+                i,
             );
         }
 
@@ -543,7 +632,7 @@ mod tests {
                 Side::SELL,
                 Price::from_u64(51000 + i * 100),
                 Quantity::from_str("1.0").unwrap(),
-                (5 + i) as u64,
+                5 + i,
             );
         }
 
@@ -622,4 +711,85 @@ mod tests {
 
         assert_eq!(book.last_sequence(), 42);
     }
+
+    #[test]
+    fn test_checksum_ignores_decimal_scale() {
+        let mut book = make_book();
+        book.apply_order_accepted(
+            OrderId::new(),
+            Side::BUY,
+            Price::from_u64(50000),
+            Quantity::from_str("1.50").unwrap(),
+            1,
+        );
+
+        let external = depth_checksum(
+            [(Price::from_u64(50000), Decimal::from_str_exact("1.5").unwrap())],
+            [],
+        );
+        assert_eq!(book.full_checksum(), external);
+        assert_ne!(book.full_checksum(), depth_checksum([], []));
+    }
+
+    #[derive(Deserialize)]
+    struct ChecksumFixture {
+        name: String,
+        levels: usize,
+        bids: Vec<(Decimal, Decimal)>,
+        asks: Vec<(Decimal, Decimal)>,
+        canonical: String,
+        checksum: u32,
+    }
+
+    // Shared with wasm-core's client implementation.
+    const CHECKSUM_FIXTURES: &str = include_str!("../fixtures/book_checksum.json");
+
+    #[test]
+    fn test_book_checksum_fixtures() {
+        let fixtures: Vec<ChecksumFixture> = serde_json::from_str(CHECKSUM_FIXTURES).unwrap();
+        assert!(!fixtures.is_empty());
+        for f in fixtures {
+            let bids = f.bids.iter().copied();
+            let asks = f.asks.iter().copied();
+            assert_eq!(book_checksum_string(bids.clone(), asks.clone(), f.levels), f.canonical, "{}", f.name);
+            assert_eq!(book_checksum(bids, asks, f.levels), f.checksum, "{}", f.name);
+        }
+    }
+
+    #[test]
+    fn test_checksum_covers_top_levels_only() {
+        let mut book = make_book();
+        for (i, price) in [50000u64, 49990, 49980].into_iter().enumerate() {
+            book.apply_order_accepted(
+                OrderId::new(),
+                Side::BUY,
+                Price::from_u64(price),
+                Quantity::from_str("1.0").unwrap(),
+                i as u64 + 1,
+            );
+        }
+        book.apply_order_accepted(
+            OrderId::new(),
+            Side::SELL,
+            Price::from_u64(50010),
+            Quantity::from_str("2.50").unwrap(),
+            4,
+        );
+
+        let top2 = book.checksum(2);
+        // zlib.crc32(b"50000:1:50010:2.5:49990:1")
+        assert_eq!(top2, 0x6ef65110);
+        book.apply_order_accepted(
+            OrderId::new(),
+            Side::BUY,
+            Price::from_u64(49970),
+            Quantity::from_str("1.0").unwrap(),
+            5,
+        );
+        assert_eq!(book.checksum(2), top2);
+        assert_ne!(book.checksum(4), top2);
+
+        let snapshot = book.depth_snapshot(10);
+        assert_eq!(snapshot.checksum, book.checksum(CHECKSUM_LEVELS));
+    }
 }
@@ -0,0 +1,570 @@
+//! Book and candle rebuild from the persistence journal
+//!
+//! After a restart the service rebuilds its mirrors before resuming the
+//! live feed, instead of waiting for the book to churn:
+//! 1. Optionally seed books with the live orders of an `EngineState` snapshot
+//! 2. Replay journaled `OrderAccepted` / `TradeExecuted` / `OrderCanceled`
+//!    entries through `OrderBookState` and `MultiTimeframeCandleManager`
+//! 3. Resume the live feed at `last_sequence() + 1`
+//!
+//! Every journal entry advances `last_sequence`, including event types the
+//! mirrors ignore, so the resume point matches the global sequence (spec
+//! §14) with no gap or overlap. A snapshot carries no candles: entries at
+//! or below its sequence still feed the candles, but not the books.
+//!
+//! Journal payloads are JSON-encoded `MarketEventPayload`s; the payload
+//! enum is internally tagged, which bincode cannot decode.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use persistence::journal::JournalEntry;
+use persistence::reader::{JournalReader, ReaderError};
+use persistence::recovery::EventApplier;
+use persistence::snapshot::{EngineState, OrderSnapshot, Snapshot};
+use rust_decimal::Decimal;
+use types::ids::{MarketId, OrderId};
+use types::numeric::{Price, Quantity};
+use types::order::Side;
+use uuid::Uuid;
+
+use crate::candles::MultiTimeframeCandleManager;
+use crate::events::{MarketEvent, MarketEventPayload};
+use crate::order_book::OrderBookState;
+
+/// Journal event types that move the mirrors.
+pub const BOOK_EVENT_TYPES: [&str; 3] = ["OrderAccepted", "TradeExecuted", "OrderCanceled"];
+
+/// Errors rebuilding market data state.
+#[derive(Debug, thiserror::Error)]
+pub enum RebuildError {
+    #[error("journal read failed: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("failed to decode {event_type} at sequence {sequence}: {reason}")]
+    Decode {
+        sequence: u64,
+        event_type: String,
+        reason: String,
+    },
+
+    #[error("invalid order snapshot {order_id}: {reason}")]
+    InvalidOrder { order_id: String, reason: String },
+}
+
+/// Books and candles for every symbol, plus the last applied sequence.
+pub struct MarketDataState {
+    books: BTreeMap<String, OrderBookState>,
+    candles: BTreeMap<String, MultiTimeframeCandleManager>,
+    /// Closed candles kept per timeframe
+    candle_history: usize,
+    last_sequence: u64,
+}
+
+impl MarketDataState {
+    pub fn new(candle_history: usize) -> Self {
+        Self {
+            books: BTreeMap::new(),
+            candles: BTreeMap::new(),
+            candle_history,
+            last_sequence: 0,
+        }
+    }
+
+    /// Rebuild from the journal, seeded from `snapshot` when given.
+    pub fn rebuild(
+        journal_dir: &Path,
+        snapshot: Option<&Snapshot>,
+        candle_history: usize,
+    ) -> Result<Self, RebuildError> {
+        let mut state = Self::new(candle_history);
+        let snapshot_sequence = match snapshot {
+            Some(snapshot) => {
+                state.seed_from_snapshot(&snapshot.state, snapshot.sequence)?;
+                snapshot.sequence
+            }
+            None => 0,
+        };
+
+        let mut reader = JournalReader::open(journal_dir)?;
+        while let Some(entry) = reader.next_entry()? {
+            if entry.sequence <= snapshot_sequence {
+                // Already in the seeded books; candles still need the trade
+                if let Some(event) = decode_entry(&entry)? {
+                    state.apply_candles(&event);
+                }
+                continue;
+            }
+            state.apply_journal_entry(&entry)?;
+        }
+        Ok(state)
+    }
+
+    /// Place a snapshot's live orders on the books, at `sequence`.
+    ///
+    /// Terminal orders and orders with nothing left are skipped. Returns
+    /// the number of orders placed.
+    pub fn seed_from_snapshot(&mut self, state: &EngineState, sequence: u64) -> Result<usize, RebuildError> {
+        let mut live: Vec<&OrderSnapshot> = state
+            .orders
+            .values()
+            .filter(|o| {
+                !matches!(
+                    o.status.to_ascii_uppercase().as_str(),
+                    "FILLED" | "CANCELED" | "REJECTED" | "EXPIRED"
+                )
+            })
+            .collect();
+        live.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.order_id.cmp(&b.order_id)));
+
+        let mut placed = 0;
+        for order in live {
+            let invalid = |reason: String| RebuildError::InvalidOrder {
+                order_id: order.order_id.clone(),
+                reason,
+            };
+            let order_id = Uuid::parse_str(&order.order_id)
+                .map(OrderId::from_uuid)
+                .map_err(|e| invalid(format!("order_id: {}", e)))?;
+            let symbol =
+                MarketId::try_new(order.symbol.as_str()).ok_or_else(|| invalid(format!("symbol: {}", order.symbol)))?;
+            let side = match order.side.to_ascii_uppercase().as_str() {
+                "BUY" => Side::BUY,
+                "SELL" => Side::SELL,
+                other => return Err(invalid(format!("side: {}", other))),
+            };
+            let price = Decimal::from_str(&order.price)
+                .ok()
+                .and_then(Price::try_new)
+                .ok_or_else(|| invalid(format!("price: {}", order.price)))?;
+            let remaining = Decimal::from_str(&order.remaining_quantity)
+                .map_err(|e| invalid(format!("remaining_quantity: {}", e)))?;
+            let Some(remaining) = Quantity::try_new(remaining) else {
+                continue;
+            };
+            self.book_mut(&symbol).apply_order_accepted(order_id, side, price, remaining, sequence);
+            placed += 1;
+        }
+        self.last_sequence = self.last_sequence.max(sequence);
+        Ok(placed)
+    }
+
+    /// Apply one journal entry; returns false if it moved no mirror.
+    ///
+    /// Entries at or below `last_sequence` are skipped.
+    pub fn apply_journal_entry(&mut self, entry: &JournalEntry) -> Result<bool, RebuildError> {
+        if entry.sequence <= self.last_sequence {
+            return Ok(false);
+        }
+        let applied = match decode_entry(entry)? {
+            Some(event) => self.apply_event(&event),
+            None => false,
+        };
+        self.last_sequence = entry.sequence;
+        Ok(applied)
+    }
+
+    /// Apply a live event; returns false for payloads the mirrors ignore.
+    pub fn apply_event(&mut self, event: &MarketEvent) -> bool {
+        let applied = match &event.payload {
+            MarketEventPayload::OrderAccepted {
+                order_id,
+                symbol,
+                side,
+                price,
+                quantity,
+                ..
+            } => {
+                self.book_mut(symbol)
+                    .apply_order_accepted(*order_id, *side, *price, *quantity, event.sequence);
+                true
+            }
+            MarketEventPayload::TradeExecuted {
+                symbol,
+                maker_order_id,
+                quantity,
+                ..
+            } => {
+                self.book_mut(symbol)
+                    .apply_trade_executed(*maker_order_id, *quantity, event.sequence);
+                self.apply_candles(event);
+                true
+            }
+            MarketEventPayload::OrderCanceled {
+                order_id,
+                symbol,
+                remaining_quantity,
+                ..
+            } => {
+                self.book_mut(symbol)
+                    .apply_cancel(*order_id, *remaining_quantity, event.sequence);
+                true
+            }
+            _ => false,
+        };
+        self.last_sequence = self.last_sequence.max(event.sequence);
+        applied
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBookState> {
+        self.books.get(symbol)
+    }
+
+    pub fn candles(&self, symbol: &str) -> Option<&MultiTimeframeCandleManager> {
+        self.candles.get(symbol)
+    }
+
+    /// Last sequence applied; the live feed resumes after it.
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    fn book_mut(&mut self, symbol: &MarketId) -> &mut OrderBookState {
+        self.books
+            .entry(symbol.as_str().to_string())
+            .or_insert_with(|| OrderBookState::new(symbol.clone()))
+    }
+
+    fn apply_candles(&mut self, event: &MarketEvent) {
+        if let MarketEventPayload::TradeExecuted {
+            symbol,
+            price,
+            quantity,
+            executed_at,
+            ..
+        } = &event.payload
+        {
+            let history = self.candle_history;
+            self.candles
+                .entry(symbol.as_str().to_string())
+                .or_insert_with(|| MultiTimeframeCandleManager::new(symbol.clone(), history))
+                .process_trade(*price, quantity.as_decimal(), *executed_at);
+        }
+    }
+}
+
+/// [`EventApplier`] over [`MarketDataState`], for `RecoveryEngine`.
+///
+/// The engine hands every entry after its snapshot to `apply`; seed the
+/// books first with [`MarketDataState::seed_from_snapshot`]. The
+/// `EngineState` argument is left untouched.
+pub struct MarketDataApplier {
+    state: RefCell<MarketDataState>,
+}
+
+impl MarketDataApplier {
+    pub fn new(state: MarketDataState) -> Self {
+        Self {
+            state: RefCell::new(state),
+        }
+    }
+
+    pub fn into_state(self) -> MarketDataState {
+        self.state.into_inner()
+    }
+}
+
+impl EventApplier for MarketDataApplier {
+    fn apply(&self, _state: &mut EngineState, entry: &JournalEntry) -> Result<(), String> {
+        self.state
+            .borrow_mut()
+            .apply_journal_entry(entry)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Build a journal entry for a market event.
+pub fn market_event_journal_entry(event: &MarketEvent) -> JournalEntry {
+    JournalEntry::new(
+        event.sequence,
+        event.timestamp,
+        event.event_type_label().to_string(),
+        serde_json::to_vec(&event.payload).expect("market event payload serializes"),
+    )
+}
+
+/// Decode a book event entry; `None` for other event types.
+///
+/// Only the header sequence and timestamp are journaled, so the rebuilt
+/// event carries fresh ids and an empty source.
+fn decode_entry(entry: &JournalEntry) -> Result<Option<MarketEvent>, RebuildError> {
+    if !BOOK_EVENT_TYPES.contains(&entry.event_type.as_str()) {
+        return Ok(None);
+    }
+    let decode_error = |reason: String| RebuildError::Decode {
+        sequence: entry.sequence,
+        event_type: entry.event_type.clone(),
+        reason,
+    };
+    let payload: MarketEventPayload =
+        serde_json::from_slice(&entry.payload).map_err(|e| decode_error(e.to_string()))?;
+    let event = MarketEvent {
+        event_id: Uuid::nil(),
+        sequence: entry.sequence,
+        timestamp: entry.timestamp,
+        source: String::new(),
+        payload,
+        schema_version: "1.0.0".to_string(),
+        correlation_id: Uuid::nil(),
+    };
+    if event.event_type_label() != entry.event_type {
+        return Err(decode_error(format!("payload is {}", event.event_type_label())));
+    }
+    Ok(Some(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candles::{Candle, Timeframe};
+    use crate::events::CancelSource;
+    use crate::history::{funding_journal_entry, FundingRecord};
+    use crate::order_book::DepthSnapshot;
+    use persistence::journal::{JournalConfig, JournalWriter};
+    use persistence::recovery::RecoveryEngine;
+    use types::ids::{AccountId, TradeId};
+
+    const T0: i64 = 1708123440000000000; // minute-aligned
+    const SECOND: i64 = 1_000_000_000;
+    const HISTORY: usize = 100;
+
+    /// A resting order as the matching engine would snapshot it.
+    #[derive(Clone)]
+    struct Resting {
+        order_id: OrderId,
+        side: Side,
+        price: Price,
+        remaining: Decimal,
+        created_at: i64,
+    }
+
+    fn btc() -> MarketId {
+        MarketId::new("BTC/USDT")
+    }
+
+    /// A recorded session spanning twenty minutes, plus the resting orders
+    /// after each event (index `i` = state after sequence `i`).
+    fn session() -> (Vec<MarketEvent>, Vec<Vec<Resting>>) {
+        let mut events = Vec::new();
+        let mut resting: Vec<Resting> = Vec::new();
+        let mut after = vec![Vec::new()];
+        for seq in 1..=60u64 {
+            let ts = T0 + seq as i64 * 20 * SECOND;
+            let payload = match seq % 4 {
+                0 | 1 => {
+                    let side = if seq % 4 == 0 { Side::SELL } else { Side::BUY };
+                    let price = match side {
+                        Side::SELL => Price::from_u64(50_000 + (seq % 5) * 10),
+                        Side::BUY => Price::from_u64(49_990 - (seq % 3) * 10),
+                    };
+                    let order_id = OrderId::new();
+                    resting.push(Resting {
+                        order_id,
+                        side,
+                        price,
+                        remaining: Decimal::from(2),
+                        created_at: ts,
+                    });
+                    MarketEventPayload::OrderAccepted {
+                        order_id,
+                        account_id: AccountId::new(),
+                        symbol: btc(),
+                        side,
+                        price,
+                        quantity: Quantity::from_u64(2),
+                    }
+                }
+                2 if seq % 12 == 2 && resting.iter().any(|o| o.side == Side::BUY) => {
+                    let at = resting.iter().position(|o| o.side == Side::BUY).unwrap();
+                    let order = resting.remove(at);
+                    MarketEventPayload::OrderCanceled {
+                        order_id: order.order_id,
+                        symbol: btc(),
+                        side: order.side,
+                        price: order.price,
+                        remaining_quantity: Quantity::try_new(order.remaining).unwrap(),
+                        canceled_by: CancelSource::User,
+                        reason: "user".to_string(),
+                    }
+                }
+                _ => match resting.iter().position(|o| o.side == Side::SELL) {
+                    Some(at) => {
+                        let fill = Decimal::new(75, 2).min(resting[at].remaining);
+                        let maker = resting[at].clone();
+                        resting[at].remaining -= fill;
+                        if resting[at].remaining.is_zero() {
+                            resting.remove(at);
+                        }
+                        MarketEventPayload::TradeExecuted {
+                            trade_id: TradeId::new(),
+                            symbol: btc(),
+                            maker_order_id: maker.order_id,
+                            taker_order_id: OrderId::new(),
+                            maker_account_id: AccountId::new(),
+                            taker_account_id: AccountId::new(),
+                            price: maker.price,
+                            quantity: Quantity::try_new(fill).unwrap(),
+                            side: Side::BUY,
+                            executed_at: ts,
+                        }
+                    }
+                    None => MarketEventPayload::FundingRateUpdated {
+                        symbol: btc(),
+                        funding_rate: Decimal::new(1, 4),
+                        mark_price: Price::from_u64(50_000),
+                    },
+                },
+            };
+            events.push(MarketEvent {
+                event_id: Uuid::now_v7(),
+                sequence: seq,
+                timestamp: ts,
+                source: "matching-engine".to_string(),
+                payload,
+                schema_version: "1.0.0".to_string(),
+                correlation_id: Uuid::now_v7(),
+            });
+            after.push(resting.clone());
+        }
+        (events, after)
+    }
+
+    fn journal(dir: &Path, events: &[MarketEvent]) {
+        let mut writer = JournalWriter::open(JournalConfig::new(dir)).unwrap();
+        for event in events {
+            let entry = match &event.payload {
+                // Non-book entries share the journal and the sequence
+                MarketEventPayload::FundingRateUpdated {
+                    symbol,
+                    funding_rate,
+                    mark_price,
+                } => funding_journal_entry(&FundingRecord {
+                    sequence: event.sequence,
+                    timestamp: event.timestamp,
+                    symbol: symbol.clone(),
+                    funding_rate: *funding_rate,
+                    mark_price: *mark_price,
+                }),
+                _ => market_event_journal_entry(event),
+            };
+            writer.append(&entry).unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    fn engine_state(resting: &[Resting]) -> EngineState {
+        let mut state = EngineState::empty();
+        for order in resting {
+            state.orders.insert(
+                order.order_id.to_string(),
+                OrderSnapshot {
+                    order_id: order.order_id.to_string(),
+                    account_id: AccountId::new().to_string(),
+                    symbol: "BTC/USDT".to_string(),
+                    side: format!("{:?}", order.side),
+                    price: order.price.as_decimal().to_string(),
+                    quantity: "2".to_string(),
+                    filled_quantity: (Decimal::from(2) - order.remaining).to_string(),
+                    remaining_quantity: order.remaining.to_string(),
+                    status: "NEW".to_string(),
+                    created_at: order.created_at,
+                    updated_at: order.created_at,
+                },
+            );
+        }
+        state
+    }
+
+    fn applied(events: &[MarketEvent]) -> MarketDataState {
+        let mut state = MarketDataState::new(HISTORY);
+        for event in events {
+            state.apply_event(event);
+        }
+        state
+    }
+
+    type CandleView = Vec<(Vec<Candle>, Option<Candle>)>;
+
+    fn view(state: &MarketDataState) -> (DepthSnapshot, CandleView) {
+        let depth = state.book("BTC/USDT").unwrap().depth_snapshot(usize::MAX);
+        let candles = state.candles("BTC/USDT").unwrap();
+        let candles = Timeframe::all()
+            .iter()
+            .map(|&tf| (candles.get_candles(tf, HISTORY), candles.current_candle(tf).cloned()))
+            .collect();
+        (depth, candles)
+    }
+
+    #[test]
+    fn test_crash_and_rebuild_matches_uninterrupted_run() {
+        let (events, _) = session();
+        let reference = view(&applied(&events));
+        assert!(!reference.0.bids.is_empty() && !reference.0.asks.is_empty());
+        assert!(!reference.1[0].0.is_empty());
+
+        for crash_at in [7, 30, 59] {
+            let dir = tempfile::tempdir().unwrap();
+            journal(dir.path(), &events[..crash_at]);
+
+            let mut state = MarketDataState::rebuild(dir.path(), None, HISTORY).unwrap();
+            assert_eq!(state.last_sequence(), crash_at as u64);
+            // The live feed resumes right after the rebuild point
+            for event in &events[crash_at..] {
+                assert_eq!(event.sequence, state.last_sequence() + 1);
+                state.apply_event(event);
+            }
+            assert_eq!(view(&state), reference, "crash at {crash_at}");
+        }
+    }
+
+    #[test]
+    fn test_rebuild_seeded_from_snapshot() {
+        let (events, resting) = session();
+        let reference = view(&applied(&events));
+
+        let snapshot_at = 25;
+        let snapshot = Snapshot::new(snapshot_at, T0, engine_state(&resting[snapshot_at as usize]), false);
+        let dir = tempfile::tempdir().unwrap();
+        journal(dir.path(), &events[..40]);
+
+        let mut state = MarketDataState::rebuild(dir.path(), Some(&snapshot), HISTORY).unwrap();
+        assert_eq!(state.last_sequence(), 40);
+        for event in &events[40..] {
+            state.apply_event(event);
+        }
+        assert_eq!(view(&state), reference);
+    }
+
+    #[test]
+    fn test_applier_drives_recovery_engine() {
+        let (events, _) = session();
+        let snapshots = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        journal(dir.path(), &events);
+
+        let applier = MarketDataApplier::new(MarketDataState::new(HISTORY));
+        let (_, metrics) = RecoveryEngine::new(snapshots.path(), dir.path())
+            .recover_without_validation(&applier)
+            .unwrap();
+        assert_eq!(metrics.final_sequence, 60);
+
+        let state = applier.into_state();
+        assert_eq!(state.last_sequence(), 60);
+        assert_eq!(view(&state), view(&applied(&events)));
+    }
+
+    #[test]
+    fn test_mislabeled_entry_rejected() {
+        let (events, _) = session();
+        let mut entry = market_event_journal_entry(&events[0]);
+        entry.event_type = "OrderCanceled".to_string();
+        let mut state = MarketDataState::new(HISTORY);
+        assert!(matches!(
+            state.apply_journal_entry(&entry),
+            Err(RebuildError::Decode { sequence: 1, .. })
+        ));
+    }
+}
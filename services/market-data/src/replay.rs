@@ -238,6 +238,10 @@ impl ReplayEngine {
             | MarketEventPayload::OrderFilled { .. } => {
                 // These are informational; actual book update happens via TradeExecuted
             }
+            MarketEventPayload::FundingRateUpdated { .. }
+            | MarketEventPayload::PositionLiquidated { .. } => {
+                // Kept by the history store; liquidation fills arrive as trades
+            }
         }
     }
 }
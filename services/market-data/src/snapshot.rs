@@ -10,7 +10,7 @@ use sha2::{Digest, Sha256};
 use serde::{Deserialize, Serialize};
 use types::ids::MarketId;
 
-use crate::order_book::{DepthSnapshot, OrderBookState, PriceLevel};
+use crate::order_book::{OrderBookState, PriceLevel};
 
 /// A versioned, checksummed snapshot of the full order book.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -205,7 +205,7 @@ mod tests {
                 Side::BUY,
                 Price::from_u64(50000 - i * 100),
                 Quantity::from_str("1.0").unwrap(),
-                i as u64,
+                i,
             );
         }
         for i in 1..=5 {
@@ -214,7 +214,7 @@ mod tests {
                 Side::SELL,
                 Price::from_u64(51000 + i * 100),
                 Quantity::from_str("1.0").unwrap(),
-                (5 + i) as u64,
+                5 + i,
             );
         }
 
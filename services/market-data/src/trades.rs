@@ -34,6 +34,8 @@ pub struct PublicTrade {
     pub value: Decimal,
     /// Taker side (BUY = buyer was taker, SELL = seller was taker).
     pub taker_side: Side,
+    /// Whether the maker (liquidity provider) was the buyer.
+    pub maker_is_buyer: bool,
     /// Execution timestamp (Unix nanos, normalized to exchange clock).
     pub timestamp: i64,
 }
@@ -85,6 +87,7 @@ impl TradeBuffer {
             quantity,
             value,
             taker_side,
+            maker_is_buyer: taker_side == Side::SELL,
             timestamp,
         };
 
@@ -109,7 +112,7 @@ impl TradeBuffer {
         let mut aggregated: Vec<PublicTrade> = Vec::new();
 
         for trade in trades {
-            let should_merge = aggregated.last().map_or(false, |last: &PublicTrade| {
+            let should_merge = aggregated.last().is_some_and(|last: &PublicTrade| {
                 last.price == trade.price && last.taker_side == trade.taker_side
             });
 
@@ -180,6 +183,7 @@ mod tests {
         assert_eq!(trade.trade_sequence, 1);
         assert_eq!(trade.value, Decimal::from(25000));
         assert_eq!(trade.taker_side, Side::BUY);
+        assert!(!trade.maker_is_buyer);
         assert_eq!(buf.history_len(), 1);
     }
 
@@ -14,11 +14,15 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
 
+use crate::candles::Timeframe;
+
 /// Channels available for subscription.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Channel {
     /// Order book depth updates: `book@{symbol}`
     Book { symbol: String },
+    /// Snapshot then incremental book deltas: `deltas@{symbol}`
+    Deltas { symbol: String },
     /// Public trade stream: `trades@{symbol}`
     Trades { symbol: String },
     /// OHLCV candle updates: `candles@{symbol}@{timeframe}`
@@ -30,6 +34,7 @@ impl Channel {
     ///
     /// Formats:
     /// - `book@BTC/USDT`
+    /// - `deltas@BTC/USDT`
     /// - `trades@BTC/USDT`
     /// - `candles@BTC/USDT@M1`
     pub fn parse(s: &str) -> Option<Self> {
@@ -38,10 +43,13 @@ impl Channel {
             ["book", symbol] => Some(Channel::Book {
                 symbol: symbol.to_string(),
             }),
+            ["deltas", symbol] => Some(Channel::Deltas {
+                symbol: symbol.to_string(),
+            }),
             ["trades", symbol] => Some(Channel::Trades {
                 symbol: symbol.to_string(),
             }),
-            ["candles", symbol, timeframe] => Some(Channel::Candles {
+            ["candles", symbol, timeframe] if Timeframe::parse(timeframe).is_some() => Some(Channel::Candles {
                 symbol: symbol.to_string(),
                 timeframe: timeframe.to_string(),
             }),
@@ -53,6 +61,7 @@ impl Channel {
     pub fn to_channel_string(&self) -> String {
         match self {
             Channel::Book { symbol } => format!("book@{}", symbol),
+            Channel::Deltas { symbol } => format!("deltas@{}", symbol),
             Channel::Trades { symbol } => format!("trades@{}", symbol),
             Channel::Candles { symbol, timeframe } => {
                 format!("candles@{}@{}", symbol, timeframe)
@@ -350,6 +359,7 @@ mod tests {
         );
 
         assert!(Channel::parse("invalid").is_none());
+        assert!(Channel::parse("candles@BTC/USDT@M2").is_none());
     }
 
     #[test]
@@ -0,0 +1,989 @@
+//! WebSocket market data server
+//!
+//! Serves the books, trades and candles built from ingested events over a
+//! JSON subscription protocol:
+//!
+//! ```text
+//! → {"op":"subscribe","channel":"depth","symbol":"BTC/USDT","levels":20}
+//! → {"op":"subscribe","channel":"deltas","symbol":"BTC/USDT"}
+//! → {"op":"resync","symbol":"BTC/USDT"}
+//! → {"op":"subscribe","channel":"trades","symbol":"BTC/USDT"}
+//! → {"op":"subscribe","channel":"trades","symbol":"ETH/USDT","encoding":"binary"}
+//! → {"op":"subscribe","channel":"candles","symbol":"BTC/USDT","timeframe":"M1"}
+//! → {"op":"unsubscribe","channel":"trades","symbol":"BTC/USDT"}
+//! → {"op":"ping"}
+//! ```
+//!
+//! Every data message carries the symbol's book `last_sequence`, so a
+//! client can tell whether it has seen every book change. The `deltas`
+//! channel sends a full snapshot, then one `(prev_sequence, sequence)`
+//! delta message per book event (see `deltas`); on a gap the client
+//! sends `resync` and gets a fresh snapshot.
+//!
+//! Encodings: data messages are JSON text frames unless a `trades`
+//! subscription asks for `"encoding":"binary"`. Its trades then arrive as
+//! binary frames holding a `TradeTick` (see `events`), and the subscribe
+//! ack carries the `symbol_id` those ticks use. Ids are assigned per
+//! connection and stay fixed for its lifetime. An unknown encoding, or
+//! binary on another channel, is answered with an error frame; the
+//! connection stays open.
+//!
+//! Slow consumers: each connection has a bounded outbound queue of
+//! `BackpressureConfig::queue_capacity` messages. When a message arrives
+//! for a full queue, `DropPolicy::Disconnect` (the default) closes the
+//! connection; the client reconnects and receives fresh snapshots.
+//! `DropPolicy::DropOldest` instead discards the oldest queued message,
+//! which the client sees as a jump in `last_sequence`. Memory per
+//! connection is bounded either way.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+use types::ids::MarketId;
+
+use crate::backpressure::{BackpressureConfig, BackpressureManager, Frame, OutboundMessage};
+use crate::candle_stream::{CandleStream, CandleUpdate, DEFAULT_LIVE_CADENCE_NANOS};
+use crate::candles::Timeframe;
+use crate::deltas::{BookFeed, DeltaMessage};
+use crate::events::{MarketEvent, MarketEventPayload, TradeTick};
+use crate::ingestion::{EventIngester, IngestionError, IngestionResult};
+use crate::order_book::{DepthSnapshot, PriceLevel};
+use crate::trades::{PublicTrade, TradeBuffer};
+use crate::websocket::{Channel, ClientId, ClientRegistry, WsConfig};
+
+/// Depth levels per side when a subscription does not say.
+pub const DEFAULT_DEPTH_LEVELS: usize = 20;
+/// Most depth levels per side a subscription may ask for.
+pub const MAX_DEPTH_LEVELS: usize = 500;
+
+/// Trades kept per symbol.
+const TRADE_HISTORY: usize = 1_000;
+/// Closed candles kept per stream.
+const CANDLE_HISTORY: usize = 1_000;
+
+/// Subscribable channel names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelName {
+    Depth,
+    Deltas,
+    Trades,
+    Candles,
+}
+
+/// Wire encoding of a subscription's data messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Json,
+    /// `TradeTick` binary frames (`trades` only)
+    Binary,
+}
+
+impl Encoding {
+    pub fn parse(label: &str) -> Option<Self> {
+        match label {
+            "json" => Some(Encoding::Json),
+            "binary" => Some(Encoding::Binary),
+            _ => None,
+        }
+    }
+}
+
+/// Channel selector of a subscribe or unsubscribe request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionRequest {
+    pub channel: ChannelName,
+    pub symbol: String,
+    /// Depth levels per side (`depth` only)
+    #[serde(default)]
+    pub levels: Option<usize>,
+    /// Candle timeframe label, e.g. "M1" (`candles` only)
+    #[serde(default)]
+    pub timeframe: Option<String>,
+    /// "json" (default) or "binary" (`trades` only)
+    #[serde(default)]
+    pub encoding: Option<String>,
+}
+
+impl SubscriptionRequest {
+    /// Internal channel for this request.
+    fn channel(&self) -> Result<Channel, String> {
+        if MarketId::try_new(self.symbol.as_str()).is_none() {
+            return Err(format!("Invalid symbol '{}'", self.symbol));
+        }
+        let symbol = self.symbol.clone();
+        match self.channel {
+            ChannelName::Depth => Ok(Channel::Book { symbol }),
+            ChannelName::Deltas => Ok(Channel::Deltas { symbol }),
+            ChannelName::Trades => Ok(Channel::Trades { symbol }),
+            ChannelName::Candles => match self.timeframe.as_deref().and_then(Timeframe::parse) {
+                Some(timeframe) => Ok(Channel::Candles {
+                    symbol,
+                    timeframe: timeframe.as_str().to_string(),
+                }),
+                None => Err("Candles require a valid timeframe".to_string()),
+            },
+        }
+    }
+
+    fn encoding(&self) -> Result<Encoding, String> {
+        let encoding = match self.encoding.as_deref() {
+            None => Encoding::Json,
+            Some(label) => Encoding::parse(label).ok_or_else(|| format!("Unknown encoding '{}'", label))?,
+        };
+        if encoding == Encoding::Binary && self.channel != ChannelName::Trades {
+            return Err("Binary encoding is only available on trades".to_string());
+        }
+        Ok(encoding)
+    }
+}
+
+/// A client → server message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum ClientRequest {
+    Subscribe(SubscriptionRequest),
+    Unsubscribe(SubscriptionRequest),
+    /// Re-send the full book snapshot of a `deltas` subscription
+    Resync { symbol: String },
+    Ping,
+}
+
+/// A server → client message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed {
+        channel: String,
+        /// Symbol id used by this subscription's binary frames
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        symbol_id: Option<u16>,
+    },
+    Unsubscribed { channel: String },
+    Error { message: String },
+    Pong,
+    /// Top levels of the book, sent on subscribe and after every change
+    Depth {
+        channel: String,
+        last_sequence: u64,
+        bids: Vec<PriceLevel>,
+        asks: Vec<PriceLevel>,
+    },
+    /// Full book, sent on `deltas` subscribe and on resync
+    BookSnapshot {
+        channel: String,
+        snapshot: DepthSnapshot,
+    },
+    /// Level changes of one book event
+    BookDelta {
+        channel: String,
+        update: DeltaMessage,
+    },
+    Trade {
+        channel: String,
+        last_sequence: u64,
+        trade: PublicTrade,
+    },
+    Candle {
+        channel: String,
+        last_sequence: u64,
+        update: CandleUpdate,
+    },
+}
+
+/// Books, trades and candles plus connected clients, guarded by one lock
+/// so fan-out sees each event's state exactly once.
+struct HubState {
+    ingester: EventIngester,
+    books: BTreeMap<String, BookFeed>,
+    trades: BTreeMap<String, TradeBuffer>,
+    candles: CandleStream,
+    registry: ClientRegistry,
+    /// Depth levels requested per (client, symbol)
+    depth_levels: BTreeMap<(ClientId, String), usize>,
+    /// Symbol ids negotiated per connection for binary frames
+    symbol_ids: BTreeMap<ClientId, BTreeMap<String, u16>>,
+    /// Trade subscriptions using binary encoding, per (client, symbol)
+    binary_trades: BTreeSet<(ClientId, String)>,
+    queues: BackpressureManager,
+    /// Wakes a connection's writer when its queue has messages or it is dropped
+    notifiers: BTreeMap<ClientId, Arc<Notify>>,
+}
+
+impl HubState {
+    fn last_sequence(&self, symbol: &str) -> u64 {
+        self.books.get(symbol).map_or(0, |feed| feed.book().last_sequence())
+    }
+
+    fn book_snapshot(&self, channel: &Channel, symbol: &str) -> ServerMessage {
+        let snapshot = match self.books.get(symbol) {
+            Some(feed) => feed.snapshot(),
+            None => BookFeed::new(MarketId::new(symbol)).snapshot(),
+        };
+        ServerMessage::BookSnapshot {
+            channel: channel.to_channel_string(),
+            snapshot,
+        }
+    }
+
+    fn depth_message(&self, channel: &Channel, symbol: &str, levels: usize) -> ServerMessage {
+        let (bids, asks) = match self.books.get(symbol) {
+            Some(feed) => {
+                let depth = feed.book().depth_snapshot(levels);
+                (depth.bids, depth.asks)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        ServerMessage::Depth {
+            channel: channel.to_channel_string(),
+            last_sequence: self.last_sequence(symbol),
+            bids,
+            asks,
+        }
+    }
+
+    /// Queue a message for one client, dropping the client on overflow
+    /// under the disconnect policy.
+    fn send(&mut self, client_id: ClientId, message: &ServerMessage, sequence: u64, now: i64) {
+        let payload = serde_json::to_string(message).expect("ServerMessage serialization must not fail");
+        self.send_frame(client_id, payload.into(), sequence, now);
+    }
+
+    fn send_frame(&mut self, client_id: ClientId, payload: Frame, sequence: u64, now: i64) {
+        let outbound = OutboundMessage {
+            payload,
+            sequence,
+            queued_at: now,
+        };
+        if self.queues.enqueue(client_id, outbound).is_some() {
+            self.drop_client(client_id);
+            return;
+        }
+        if let Some(notify) = self.notifiers.get(&client_id) {
+            notify.notify_one();
+        }
+    }
+
+    fn broadcast(&mut self, channel: &Channel, message: &ServerMessage, sequence: u64, now: i64) {
+        for client_id in self.registry.subscribers(channel) {
+            self.send(client_id, message, sequence, now);
+        }
+    }
+
+    fn publish_depth(&mut self, symbol: &str, now: i64) {
+        let channel = Channel::Book {
+            symbol: symbol.to_string(),
+        };
+        let sequence = self.last_sequence(symbol);
+        for client_id in self.registry.subscribers(&channel) {
+            let levels = self
+                .depth_levels
+                .get(&(client_id, symbol.to_string()))
+                .copied()
+                .unwrap_or(DEFAULT_DEPTH_LEVELS);
+            let message = self.depth_message(&channel, symbol, levels);
+            self.send(client_id, &message, sequence, now);
+        }
+    }
+
+    /// Fan out a trade in each subscriber's encoding.
+    fn publish_trade(&mut self, trade: PublicTrade, sequence: u64, now: i64) {
+        let symbol = trade.symbol.as_str().to_string();
+        let channel = Channel::Trades { symbol: symbol.clone() };
+        let message = ServerMessage::Trade {
+            channel: channel.to_channel_string(),
+            last_sequence: sequence,
+            trade: trade.clone(),
+        };
+        for client_id in self.registry.subscribers(&channel) {
+            let key = (client_id, symbol.clone());
+            let symbol_id = self
+                .symbol_ids
+                .get(&client_id)
+                .and_then(|ids| ids.get(&symbol))
+                .copied()
+                .filter(|_| self.binary_trades.contains(&key));
+            match symbol_id.map(|id| TradeTick::from_trade(&trade, id, sequence).encode()) {
+                Some(Ok(bytes)) => self.send_frame(client_id, Frame::Binary(bytes), sequence, now),
+                Some(Err(e)) => {
+                    // Values outside the fixed layout still reach the client
+                    warn!(client_id, error = %e, "Trade does not fit a binary tick, sending JSON");
+                    self.send(client_id, &message, sequence, now);
+                }
+                None => self.send(client_id, &message, sequence, now),
+            }
+        }
+    }
+
+    /// Symbol id for a connection, assigning the next free one.
+    fn symbol_id(&mut self, client_id: ClientId, symbol: &str) -> Result<u16, String> {
+        let ids = self.symbol_ids.entry(client_id).or_default();
+        if let Some(id) = ids.get(symbol) {
+            return Ok(*id);
+        }
+        let id = u16::try_from(ids.len()).map_err(|_| "Symbol id table full".to_string())?;
+        ids.insert(symbol.to_string(), id);
+        Ok(id)
+    }
+
+    /// Fan out one book event: its deltas, then the depth view.
+    fn publish_book(&mut self, update: DeltaMessage, now: i64) {
+        let symbol = update.symbol.as_str().to_string();
+        let channel = Channel::Deltas { symbol: symbol.clone() };
+        let sequence = update.sequence;
+        let message = ServerMessage::BookDelta {
+            channel: channel.to_channel_string(),
+            update,
+        };
+        self.broadcast(&channel, &message, sequence, now);
+        self.publish_depth(&symbol, now);
+    }
+
+    fn publish_candles(&mut self, updates: Vec<CandleUpdate>, now: i64) {
+        for update in updates {
+            let channel = update.channel();
+            let sequence = self.last_sequence(update.candle.symbol.as_str());
+            let message = ServerMessage::Candle {
+                channel: channel.to_channel_string(),
+                last_sequence: sequence,
+                update,
+            };
+            self.broadcast(&channel, &message, sequence, now);
+        }
+    }
+
+    /// Apply one ordered event and fan out what it changed.
+    fn apply(&mut self, event: &MarketEvent) {
+        let now = event.timestamp;
+        match &event.payload {
+            MarketEventPayload::OrderAccepted { symbol, .. } => {
+                let update = self
+                    .books
+                    .entry(symbol.as_str().to_string())
+                    .or_insert_with(|| BookFeed::new(symbol.clone()))
+                    .apply(event);
+                if let Some(update) = update {
+                    self.publish_book(update, now);
+                }
+            }
+            MarketEventPayload::TradeExecuted {
+                trade_id,
+                symbol,
+                price,
+                quantity,
+                side,
+                executed_at,
+                ..
+            } => {
+                let update = self.books.get_mut(symbol.as_str()).and_then(|feed| feed.apply(event));
+                let trade = self
+                    .trades
+                    .entry(symbol.as_str().to_string())
+                    .or_insert_with(|| TradeBuffer::new(symbol.clone(), TRADE_HISTORY))
+                    .record_trade(*trade_id, *price, *quantity, *side, *executed_at);
+
+                let sequence = self.last_sequence(symbol.as_str());
+                self.publish_trade(trade, sequence, now);
+                if let Some(update) = update {
+                    self.publish_book(update, now);
+                }
+
+                let closed = self.candles.on_trade(symbol, *price, quantity.as_decimal(), *executed_at);
+                self.publish_candles(closed, now);
+            }
+            MarketEventPayload::OrderCanceled { symbol, .. } => {
+                if let Some(update) = self.books.get_mut(symbol.as_str()).and_then(|feed| feed.apply(event)) {
+                    self.publish_book(update, now);
+                }
+            }
+            MarketEventPayload::OrderPartiallyFilled { .. }
+            | MarketEventPayload::OrderFilled { .. }
+            | MarketEventPayload::FundingRateUpdated { .. }
+            | MarketEventPayload::PositionLiquidated { .. } => {}
+        }
+
+        let live = self.candles.tick(now);
+        self.publish_candles(live, now);
+    }
+
+    fn subscribe(&mut self, client_id: ClientId, request: &SubscriptionRequest, now: i64) -> Result<(), String> {
+        let channel = request.channel()?;
+        let encoding = request.encoding()?;
+        let levels = request.levels.unwrap_or(DEFAULT_DEPTH_LEVELS);
+        if request.channel == ChannelName::Depth && !(1..=MAX_DEPTH_LEVELS).contains(&levels) {
+            return Err(format!("Depth levels must be 1..={}", MAX_DEPTH_LEVELS));
+        }
+        let symbol_id = match encoding {
+            Encoding::Binary => Some(self.symbol_id(client_id, &request.symbol)?),
+            Encoding::Json => None,
+        };
+        self.registry.subscribe(client_id, channel.clone())?;
+        if symbol_id.is_some() {
+            self.binary_trades.insert((client_id, request.symbol.clone()));
+        }
+
+        let ack = ServerMessage::Subscribed {
+            channel: channel.to_channel_string(),
+            symbol_id,
+        };
+        let sequence = self.last_sequence(&request.symbol);
+        self.send(client_id, &ack, sequence, now);
+
+        match &channel {
+            Channel::Book { symbol } => {
+                self.depth_levels.insert((client_id, symbol.clone()), levels);
+                let snapshot = self.depth_message(&channel, symbol, levels);
+                self.send(client_id, &snapshot, sequence, now);
+                if let Some(client) = self.registry.get_mut(client_id) {
+                    client.mark_snapshot_sent(channel);
+                }
+            }
+            Channel::Deltas { symbol } => {
+                let snapshot = self.book_snapshot(&channel, symbol);
+                self.send(client_id, &snapshot, sequence, now);
+                if let Some(client) = self.registry.get_mut(client_id) {
+                    client.mark_snapshot_sent(channel);
+                }
+            }
+            Channel::Candles { symbol, timeframe } => {
+                if let (Some(market), Some(timeframe)) = (MarketId::try_new(symbol.as_str()), Timeframe::parse(timeframe)) {
+                    self.candles.track(market, timeframe);
+                }
+            }
+            Channel::Trades { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self, client_id: ClientId, request: &SubscriptionRequest, now: i64) -> Result<(), String> {
+        let channel = request.channel()?;
+        let client = self.registry.get_mut(client_id).ok_or("Client not found")?;
+        client.unsubscribe(&channel);
+        match &channel {
+            Channel::Book { symbol } => {
+                self.depth_levels.remove(&(client_id, symbol.clone()));
+            }
+            Channel::Trades { symbol } => {
+                self.binary_trades.remove(&(client_id, symbol.clone()));
+            }
+            _ => {}
+        }
+        let ack = ServerMessage::Unsubscribed {
+            channel: channel.to_channel_string(),
+        };
+        let sequence = self.last_sequence(&request.symbol);
+        self.send(client_id, &ack, sequence, now);
+        Ok(())
+    }
+
+    fn resync(&mut self, client_id: ClientId, symbol: &str, now: i64) -> Result<(), String> {
+        let channel = Channel::Deltas {
+            symbol: symbol.to_string(),
+        };
+        if !self.registry.get(client_id).is_some_and(|c| c.is_subscribed(&channel)) {
+            return Err(format!("Not subscribed to {}", channel.to_channel_string()));
+        }
+        let snapshot = self.book_snapshot(&channel, symbol);
+        let sequence = self.last_sequence(symbol);
+        self.send(client_id, &snapshot, sequence, now);
+        Ok(())
+    }
+
+    fn drop_client(&mut self, client_id: ClientId) {
+        self.registry.disconnect(client_id);
+        self.queues.remove_client(client_id);
+        self.depth_levels.retain(|(id, _), _| *id != client_id);
+        self.symbol_ids.remove(&client_id);
+        self.binary_trades.retain(|(id, _)| *id != client_id);
+        if let Some(notify) = self.notifiers.remove(&client_id) {
+            notify.notify_one();
+        }
+    }
+}
+
+/// Shared market data state and connection fan-out.
+///
+/// Events enter through [`WsHub::ingest`]; sockets are served by [`router`].
+pub struct WsHub {
+    state: Mutex<HubState>,
+}
+
+impl WsHub {
+    pub fn new(ws_config: WsConfig, backpressure: BackpressureConfig) -> Self {
+        Self {
+            state: Mutex::new(HubState {
+                ingester: EventIngester::with_defaults(),
+                books: BTreeMap::new(),
+                trades: BTreeMap::new(),
+                candles: CandleStream::new(DEFAULT_LIVE_CADENCE_NANOS, CANDLE_HISTORY),
+                registry: ClientRegistry::new(ws_config),
+                depth_levels: BTreeMap::new(),
+                symbol_ids: BTreeMap::new(),
+                binary_trades: BTreeSet::new(),
+                queues: BackpressureManager::new(backpressure),
+                notifiers: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HubState> {
+        self.state.lock().expect("ws hub lock poisoned")
+    }
+
+    /// Validate an event through the ingestion layer, then apply and fan
+    /// out every event it releases, in sequence order.
+    pub fn ingest(&self, event: MarketEvent) -> Result<IngestionResult, IngestionError> {
+        let mut state = self.lock();
+        let result = state.ingester.ingest(event)?;
+        for event in state.ingester.drain_buffer() {
+            state.apply(&event);
+        }
+        Ok(result)
+    }
+
+    /// Register a connection, returning its id and the notifier its
+    /// writer waits on.
+    pub fn connect(&self, now: i64) -> (ClientId, Arc<Notify>) {
+        let mut state = self.lock();
+        let client_id = state.registry.register(now);
+        state.queues.register_client(client_id);
+        let notify = Arc::new(Notify::new());
+        state.notifiers.insert(client_id, notify.clone());
+        debug!(client_id, "WebSocket client connected");
+        (client_id, notify)
+    }
+
+    /// Drop a connection and its subscriptions.
+    pub fn disconnect(&self, client_id: ClientId) {
+        self.lock().drop_client(client_id);
+    }
+
+    pub fn is_connected(&self, client_id: ClientId) -> bool {
+        self.lock().registry.get(client_id).is_some()
+    }
+
+    /// Handle one text frame from a client. Responses are queued.
+    pub fn handle_text(&self, client_id: ClientId, text: &str, now: i64) {
+        let mut state = self.lock();
+        if state.registry.get(client_id).is_none() {
+            return;
+        }
+        let result = if !state.registry.check_rate_limit(client_id, now) {
+            Err("Rate limit exceeded".to_string())
+        } else {
+            match serde_json::from_str::<ClientRequest>(text) {
+                Ok(ClientRequest::Subscribe(request)) => state.subscribe(client_id, &request, now),
+                Ok(ClientRequest::Unsubscribe(request)) => state.unsubscribe(client_id, &request, now),
+                Ok(ClientRequest::Resync { symbol }) => state.resync(client_id, &symbol, now),
+                Ok(ClientRequest::Ping) => {
+                    if let Some(client) = state.registry.get_mut(client_id) {
+                        client.record_pong(now);
+                    }
+                    state.send(client_id, &ServerMessage::Pong, 0, now);
+                    Ok(())
+                }
+                Err(e) => Err(format!("Invalid request: {}", e)),
+            }
+        };
+        if let Err(message) = result {
+            state.send(client_id, &ServerMessage::Error { message }, 0, now);
+        }
+    }
+
+    /// Take a connection's queued messages.
+    ///
+    /// `None` once the connection has been dropped, e.g. for lagging.
+    pub fn drain(&self, client_id: ClientId) -> Option<Vec<Frame>> {
+        let mut state = self.lock();
+        state.registry.get(client_id)?;
+        Some(state.queues.drain_client(client_id).into_iter().map(|m| m.payload).collect())
+    }
+
+    /// Number of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.lock().registry.client_count()
+    }
+}
+
+impl Default for WsHub {
+    fn default() -> Self {
+        Self::new(WsConfig::default(), BackpressureConfig::default())
+    }
+}
+
+/// Router serving the market data WebSocket at `/ws`.
+pub fn router(hub: Arc<WsHub>) -> Router {
+    Router::new().route("/ws", get(upgrade)).with_state(hub)
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(hub): State<Arc<WsHub>>) -> Response {
+    ws.on_upgrade(move |socket| serve_socket(hub, socket))
+}
+
+/// Wall-clock nanos, used only for per-connection rate limits and heartbeats.
+fn now_nanos() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as i64)
+}
+
+async fn serve_socket(hub: Arc<WsHub>, mut socket: WebSocket) {
+    let (client_id, notify) = hub.connect(now_nanos());
+
+    'connection: loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => hub.handle_text(client_id, &text, now_nanos()),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = notify.notified() => {
+                let Some(batch) = hub.drain(client_id) else {
+                    warn!(client_id, "Closing lagging WebSocket client");
+                    break;
+                };
+                for payload in batch {
+                    let message = match payload {
+                        Frame::Text(text) => Message::Text(text),
+                        Frame::Binary(bytes) => Message::Binary(bytes),
+                    };
+                    if socket.send(message).await.is_err() {
+                        break 'connection;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+    hub.disconnect(client_id);
+    debug!(client_id, "WebSocket client disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backpressure::DropPolicy;
+    use types::ids::{AccountId, OrderId, TradeId};
+    use types::numeric::{Price, Quantity};
+    use types::order::Side;
+    use uuid::Uuid;
+
+    const T0: i64 = 1708123456789000000;
+
+    fn make_event(seq: u64, payload: MarketEventPayload) -> MarketEvent {
+        MarketEvent {
+            event_id: Uuid::now_v7(),
+            sequence: seq,
+            timestamp: T0 + (seq as i64 * 1000),
+            source: "matching-engine".to_string(),
+            payload,
+            schema_version: "1.0.0".to_string(),
+            correlation_id: Uuid::now_v7(),
+        }
+    }
+
+    fn order_accepted(seq: u64, order_id: OrderId, side: Side, price: u64) -> MarketEvent {
+        make_event(
+            seq,
+            MarketEventPayload::OrderAccepted {
+                order_id,
+                account_id: AccountId::new(),
+                symbol: MarketId::new("BTC/USDT"),
+                side,
+                price: Price::from_u64(price),
+                quantity: Quantity::from_str("1.0").unwrap(),
+            },
+        )
+    }
+
+    fn trade_executed(seq: u64, maker_id: OrderId, price: u64) -> MarketEvent {
+        make_event(
+            seq,
+            MarketEventPayload::TradeExecuted {
+                trade_id: TradeId::new(),
+                symbol: MarketId::new("BTC/USDT"),
+                maker_order_id: maker_id,
+                taker_order_id: OrderId::new(),
+                maker_account_id: AccountId::new(),
+                taker_account_id: AccountId::new(),
+                price: Price::from_u64(price),
+                quantity: Quantity::from_str("0.4").unwrap(),
+                side: Side::BUY,
+                executed_at: T0 + (seq as i64 * 1000),
+            },
+        )
+    }
+
+    fn received(hub: &WsHub, client_id: ClientId) -> Vec<ServerMessage> {
+        hub.drain(client_id)
+            .unwrap()
+            .iter()
+            .map(|payload| match payload {
+                Frame::Text(text) => serde_json::from_str(text).unwrap(),
+                Frame::Binary(_) => panic!("unexpected binary frame"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_depth_subscription_snapshot_then_updates() {
+        let hub = WsHub::default();
+        hub.ingest(order_accepted(1, OrderId::new(), Side::BUY, 49_900)).unwrap();
+        hub.ingest(order_accepted(2, OrderId::new(), Side::BUY, 49_800)).unwrap();
+
+        let (client, _) = hub.connect(T0);
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"depth","symbol":"BTC/USDT","levels":1}"#, T0);
+        let messages = received(&hub, client);
+        assert_eq!(messages[0], ServerMessage::Subscribed {
+                channel: "book@BTC/USDT".to_string(),
+                symbol_id: None
+            });
+        match &messages[1] {
+            ServerMessage::Depth { last_sequence, bids, asks, .. } => {
+                assert_eq!(*last_sequence, 2);
+                assert_eq!(bids.len(), 1);
+                assert_eq!(bids[0].price, Price::from_u64(49_900));
+                assert!(asks.is_empty());
+            }
+            other => panic!("expected depth, got {:?}", other),
+        }
+
+        hub.ingest(order_accepted(3, OrderId::new(), Side::SELL, 50_100)).unwrap();
+        let messages = received(&hub, client);
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(&messages[0], ServerMessage::Depth { last_sequence: 3, asks, .. } if asks.len() == 1));
+    }
+
+    #[test]
+    fn test_deltas_channel_snapshot_deltas_and_resync() {
+        use crate::deltas::BookReplica;
+
+        let hub = WsHub::default();
+        let maker = OrderId::new();
+        hub.ingest(order_accepted(1, maker, Side::SELL, 50_000)).unwrap();
+
+        let (client, _) = hub.connect(T0);
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"deltas","symbol":"BTC/USDT"}"#, T0);
+        let messages = received(&hub, client);
+        let mut replica = match &messages[1] {
+            ServerMessage::BookSnapshot { snapshot, .. } => BookReplica::from_snapshot(snapshot),
+            other => panic!("expected snapshot, got {:?}", other),
+        };
+        assert_eq!(replica.last_sequence(), 1);
+
+        hub.ingest(order_accepted(2, OrderId::new(), Side::BUY, 49_000)).unwrap();
+        hub.ingest(trade_executed(3, maker, 50_000)).unwrap();
+        for message in received(&hub, client) {
+            if let ServerMessage::BookDelta { update, .. } = message {
+                assert!(replica.apply(&update).unwrap());
+            }
+        }
+        assert_eq!(replica.last_sequence(), 3);
+
+        hub.handle_text(client, r#"{"op":"resync","symbol":"BTC/USDT"}"#, T0);
+        match &received(&hub, client)[..] {
+            [ServerMessage::BookSnapshot { snapshot, .. }] => {
+                assert_eq!(BookReplica::from_snapshot(snapshot), replica);
+            }
+            other => panic!("expected one snapshot, got {:?}", other),
+        }
+
+        hub.handle_text(client, r#"{"op":"resync","symbol":"ETH/USDT"}"#, T0);
+        assert!(matches!(&received(&hub, client)[..], [ServerMessage::Error { .. }]));
+    }
+
+    #[test]
+    fn test_trades_and_candles_fan_out_until_unsubscribed() {
+        let hub = WsHub::default();
+        let maker = OrderId::new();
+        hub.ingest(order_accepted(1, maker, Side::SELL, 50_000)).unwrap();
+
+        let (trader, _) = hub.connect(T0);
+        let (charter, _) = hub.connect(T0);
+        hub.handle_text(trader, r#"{"op":"subscribe","channel":"trades","symbol":"BTC/USDT"}"#, T0);
+        hub.handle_text(charter, r#"{"op":"subscribe","channel":"candles","symbol":"BTC/USDT","timeframe":"M1"}"#, T0);
+        received(&hub, trader);
+        received(&hub, charter);
+
+        hub.ingest(trade_executed(2, maker, 50_000)).unwrap();
+        let trades = received(&hub, trader);
+        assert!(matches!(&trades[..], [ServerMessage::Trade { last_sequence: 2, trade, .. }] if trade.trade_sequence == 1));
+        let candles = received(&hub, charter);
+        assert!(matches!(&candles[..], [ServerMessage::Candle { last_sequence: 2, update, .. }] if !update.is_closed));
+
+        hub.handle_text(trader, r#"{"op":"unsubscribe","channel":"trades","symbol":"BTC/USDT"}"#, T0);
+        hub.ingest(trade_executed(3, maker, 50_000)).unwrap();
+        assert_eq!(
+            received(&hub, trader),
+            vec![ServerMessage::Unsubscribed { channel: "trades@BTC/USDT".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_bad_requests_answered_with_errors() {
+        let hub = WsHub::default();
+        let (client, _) = hub.connect(T0);
+        for request in [
+            "not json",
+            r#"{"op":"subscribe","channel":"depth","symbol":"BTCUSDT"}"#,
+            r#"{"op":"subscribe","channel":"depth","symbol":"BTC/USDT","levels":0}"#,
+            r#"{"op":"subscribe","channel":"candles","symbol":"BTC/USDT","timeframe":"M2"}"#,
+        ] {
+            hub.handle_text(client, request, T0);
+        }
+        let messages = received(&hub, client);
+        assert_eq!(messages.len(), 4);
+        assert!(messages.iter().all(|m| matches!(m, ServerMessage::Error { .. })));
+
+        hub.handle_text(client, r#"{"op":"ping"}"#, T0);
+        assert_eq!(received(&hub, client), vec![ServerMessage::Pong]);
+    }
+
+    #[test]
+    fn test_slow_consumer_disconnected_when_queue_full() {
+        let hub = WsHub::new(
+            WsConfig::default(),
+            BackpressureConfig {
+                queue_capacity: 3,
+                ..BackpressureConfig::default()
+            },
+        );
+        let (slow, notify) = hub.connect(T0);
+        let (fast, _) = hub.connect(T0);
+        for client in [slow, fast] {
+            hub.handle_text(client, r#"{"op":"subscribe","channel":"depth","symbol":"BTC/USDT"}"#, T0);
+        }
+
+        hub.ingest(order_accepted(1, OrderId::new(), Side::BUY, 49_000)).unwrap();
+        received(&hub, fast);
+        // Ack, snapshot and one update fill the slow queue; the next overflows it
+        hub.ingest(order_accepted(2, OrderId::new(), Side::BUY, 49_100)).unwrap();
+
+        assert!(!hub.is_connected(slow));
+        assert_eq!(hub.drain(slow), None);
+        assert!(hub.is_connected(fast));
+        assert_eq!(received(&hub, fast).len(), 1);
+        // The writer is woken so it can close the socket
+        assert!(has_wakeup(notify));
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_newest_with_visible_gap() {
+        let hub = WsHub::new(
+            WsConfig::default(),
+            BackpressureConfig {
+                queue_capacity: 2,
+                drop_policy: DropPolicy::DropOldest,
+                ..BackpressureConfig::default()
+            },
+        );
+        let (client, _) = hub.connect(T0);
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"depth","symbol":"BTC/USDT"}"#, T0);
+        for seq in 1..=4 {
+            hub.ingest(order_accepted(seq, OrderId::new(), Side::BUY, 49_000 + seq)).unwrap();
+        }
+
+        let sequences: Vec<u64> = received(&hub, client)
+            .iter()
+            .map(|m| match m {
+                ServerMessage::Depth { last_sequence, .. } => *last_sequence,
+                other => panic!("expected depth, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(sequences, vec![3, 4]);
+    }
+
+    /// Whether a notifier holds a wake-up permit.
+    fn has_wakeup(notify: Arc<Notify>) -> bool {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        runtime.block_on(async {
+            tokio::time::timeout(std::time::Duration::from_millis(10), notify.notified())
+                .await
+                .is_ok()
+        })
+    }
+
+    #[test]
+    fn test_binary_trades_with_negotiated_symbol_ids() {
+        let hub = WsHub::default();
+        let maker = OrderId::new();
+        hub.ingest(order_accepted(1, maker, Side::SELL, 50_000)).unwrap();
+
+        let (binary, _) = hub.connect(T0);
+        let (json, _) = hub.connect(T0);
+        hub.handle_text(binary, r#"{"op":"subscribe","channel":"trades","symbol":"ETH/USDT","encoding":"binary"}"#, T0);
+        hub.handle_text(binary, r#"{"op":"subscribe","channel":"trades","symbol":"BTC/USDT","encoding":"binary"}"#, T0);
+        hub.handle_text(json, r#"{"op":"subscribe","channel":"trades","symbol":"BTC/USDT","encoding":"json"}"#, T0);
+        assert_eq!(
+            received(&hub, binary),
+            vec![
+                ServerMessage::Subscribed {
+                    channel: "trades@ETH/USDT".to_string(),
+                    symbol_id: Some(0)
+                },
+                ServerMessage::Subscribed {
+                    channel: "trades@BTC/USDT".to_string(),
+                    symbol_id: Some(1)
+                },
+            ]
+        );
+        received(&hub, json);
+
+        hub.ingest(trade_executed(2, maker, 50_000)).unwrap();
+        let frames = hub.drain(binary).unwrap();
+        let [Frame::Binary(bytes)] = &frames[..] else {
+            panic!("expected one binary frame, got {:?}", frames);
+        };
+        let tick = TradeTick::decode(bytes).unwrap();
+        assert_eq!(tick.symbol_id, 1);
+        assert_eq!(tick.book_sequence, 2);
+        assert_eq!(tick.trade_sequence, 1);
+        assert_eq!(tick.price, rust_decimal::Decimal::from(50_000));
+        assert_eq!(tick.quantity, rust_decimal::Decimal::from_str_exact("0.4").unwrap());
+        assert_eq!(tick.taker_side, Side::BUY);
+
+        let trades = received(&hub, json);
+        assert!(matches!(&trades[..], [ServerMessage::Trade { last_sequence: 2, .. }]));
+    }
+
+    #[test]
+    fn test_unknown_encoding_errors_and_keeps_connection() {
+        let hub = WsHub::default();
+        let (client, _) = hub.connect(T0);
+
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"trades","symbol":"BTC/USDT","encoding":"protobuf"}"#, T0);
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"depth","symbol":"BTC/USDT","encoding":"binary"}"#, T0);
+        hub.handle_text(client, r#"{"op":"ping"}"#, T0);
+        assert_eq!(
+            received(&hub, client),
+            vec![
+                ServerMessage::Error {
+                    message: "Unknown encoding 'protobuf'".to_string()
+                },
+                ServerMessage::Error {
+                    message: "Binary encoding is only available on trades".to_string()
+                },
+                ServerMessage::Pong,
+            ]
+        );
+        assert!(hub.is_connected(client));
+
+        // The failed subscribe registered nothing
+        hub.handle_text(client, r#"{"op":"subscribe","channel":"trades","symbol":"BTC/USDT"}"#, T0);
+        assert!(matches!(
+            &received(&hub, client)[..],
+            [ServerMessage::Subscribed { symbol_id: None, .. }]
+        ));
+    }
+}
@@ -112,14 +112,14 @@ fn build_scenario() -> (Vec<MarketEvent>, Vec<OrderId>) {
         order_ids.push(ask_id);
 
         events.push(order_accepted(
-            (i * 2 + 1) as u64,
+            i * 2 + 1,
             bid_id,
             Side::BUY,
             50000 - i * 100,
             "2.0",
         ));
         events.push(order_accepted(
-            (i * 2 + 2) as u64,
+            i * 2 + 2,
             ask_id,
             Side::SELL,
             51000 + i * 100,
@@ -247,7 +247,7 @@ fn test_identical_candles() {
             if let MarketEventPayload::TradeExecuted { price, quantity, .. } =
                 &event.payload
             {
-                if let Some(candle) = builder.process_trade(
+                for candle in builder.process_trade(
                     *price,
                     quantity.as_decimal(),
                     event.timestamp,
@@ -0,0 +1,131 @@
+//! Price level churn benchmark
+//!
+//! 100k limit submits around a moving mid, half of them canceled shortly
+//! after, so levels open and empty continuously. Besides criterion timings
+//! the run reports heap allocations per submit, the figure the level pool
+//! exists to keep down.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use matching_engine::engine::SubmitResult;
+use matching_engine::MatchingEngine;
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::{Price, Quantity};
+use types::order::{Order, Side, TimeInForce};
+
+const SUBMITS: usize = 100_000;
+/// Submits between an order and its cancel
+const CANCEL_LAG: usize = 64;
+
+/// System allocator that counts allocations
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// One submit, optionally followed by the cancel of an earlier submit
+struct Step {
+    order: Order,
+    cancel: bool,
+}
+
+/// Deterministic order flow: prices drift within ±64 ticks of the mid
+fn flow() -> Vec<Step> {
+    let accounts: Vec<AccountId> = (0..32).map(|_| AccountId::new()).collect();
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut mid: i64 = 50_000;
+    (0..SUBMITS)
+        .map(|i| {
+            let r = next();
+            mid += (r % 3) as i64 - 1;
+            let side = if r >> 8 & 1 == 0 { Side::BUY } else { Side::SELL };
+            let offset = ((r >> 16) % 64) as i64;
+            let price = match side {
+                Side::BUY => mid - offset,
+                Side::SELL => mid + offset,
+            };
+            let order = Order::new(
+                accounts[(r >> 24) as usize % accounts.len()],
+                MarketId::new("BTC/USDT"),
+                side,
+                Price::from_u64(price as u64),
+                Quantity::from_u64(1 + (r >> 32) % 4),
+                TimeInForce::GTC,
+                i as i64,
+            );
+            Step { order, cancel: i % 2 == 0 }
+        })
+        .collect()
+}
+
+/// Run the flow, returning how many submits were made
+fn run(engine: &mut MatchingEngine, steps: Vec<Step>) -> usize {
+    let mut pending: std::collections::VecDeque<(OrderId, Price, Side)> = Default::default();
+    for (i, step) in steps.into_iter().enumerate() {
+        let (order_id, price, side, cancel) = (step.order.order_id, step.order.price, step.order.side, step.cancel);
+        let rested = matches!(engine.submit_order(step.order, i as i64), Ok(SubmitResult::Resting));
+        if rested && cancel {
+            pending.push_back((order_id, price, side));
+        }
+        if pending.len() > CANCEL_LAG {
+            let (order_id, price, side) = pending.pop_front().unwrap();
+            engine.cancel_order("BTC/USDT", &order_id, price, side);
+        }
+    }
+    SUBMITS
+}
+
+fn level_churn(c: &mut Criterion) {
+    let steps = flow();
+    let mut engine = MatchingEngine::new(1);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let submits = run(&mut engine, steps);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "level_churn: {:.2} allocations per submit ({} over {} submits)",
+        allocations as f64 / submits as f64,
+        allocations,
+        submits
+    );
+
+    let mut group = c.benchmark_group("level_churn");
+    group.sample_size(10);
+    group.bench_function("100k_submits_50pct_cancels", |b| {
+        b.iter_batched(
+            || (MatchingEngine::new(1), flow()),
+            |(mut engine, steps)| run(&mut engine, steps),
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, level_churn);
+criterion_main!(benches);
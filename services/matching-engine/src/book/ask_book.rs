@@ -4,10 +4,11 @@
 //! Uses BTreeMap for deterministic iteration order per spec §12 (Determinism Rules).
 
 use std::collections::BTreeMap;
-use types::ids::OrderId;
+use types::ids::{AccountId, OrderId};
 use types::numeric::{Price, Quantity};
 use types::order::Order;
 
+use super::level_pool::LevelPool;
 use super::price_level::PriceLevel;
 
 /// Ask (sell) side order book
@@ -19,6 +20,8 @@ pub struct AskBook {
     /// Price levels sorted ascending (lowest price first)
     /// Using BTreeMap ensures deterministic iteration
     levels: BTreeMap<Price, PriceLevel>,
+    /// Emptied levels kept for reuse
+    pool: LevelPool,
 }
 
 impl AskBook {
@@ -26,13 +29,43 @@ impl AskBook {
     pub fn new() -> Self {
         Self {
             levels: BTreeMap::new(),
+            pool: LevelPool::new(),
         }
     }
 
     /// Insert an order into the ask book
     pub fn insert(&mut self, order: &Order) {
-        let level = self.levels.entry(order.price).or_insert_with(PriceLevel::new);
-        level.insert(order.order_id, order.account_id, order.remaining_quantity);
+        self.insert_resting(order.order_id, order.account_id, order.price, order.remaining_quantity);
+    }
+
+    /// Insert a resting order at the back of its price level
+    ///
+    /// Used when restoring the book from a snapshot, where no `Order` exists.
+    pub fn insert_resting(&mut self, order_id: OrderId, account_id: AccountId, price: Price, quantity: Quantity) {
+        let level = self.levels.entry(price).or_insert_with(|| self.pool.take());
+        level.insert(order_id, account_id, quantity);
+    }
+
+    /// Insert a partially filled order at the back of its price level
+    ///
+    /// Used by amendments and by restores that carry the filled quantity.
+    pub(crate) fn insert_filled(&mut self, order_id: OrderId, account_id: AccountId, price: Price, quantity: Quantity, filled: Quantity) {
+        let level = self.levels.entry(price).or_insert_with(|| self.pool.take());
+        level.insert_filled(order_id, account_id, quantity, filled);
+    }
+
+    /// Set a resting order's quantity without losing time priority
+    pub(crate) fn resize(&mut self, order_id: &OrderId, price: Price, quantity: Quantity) -> bool {
+        self.levels
+            .get_mut(&price)
+            .is_some_and(|level| level.resize(order_id, quantity))
+    }
+
+    /// Look up a resting order at a known price
+    ///
+    /// Returns (account_id, remaining_quantity, filled_quantity)
+    pub(crate) fn find_at(&self, order_id: &OrderId, price: Price) -> Option<(AccountId, Quantity, Quantity)> {
+        self.levels.get(&price)?.find(order_id)
     }
 
     /// Remove an order from the ask book
@@ -42,15 +75,32 @@ impl AskBook {
         if let Some(level) = self.levels.get_mut(&price) {
             if level.remove(order_id).is_some() {
                 // Remove empty price levels to keep book clean
-                if level.is_empty() {
-                    self.levels.remove(&price);
-                }
+                self.remove_if_empty(price);
                 return true;
             }
         }
         false
     }
 
+    /// Reduce a resting order by a filled quantity
+    ///
+    /// Returns the remaining quantity, or None if the order was not found.
+    /// Fully filled orders and emptied price levels are removed.
+    pub fn reduce(&mut self, order_id: &OrderId, price: Price, quantity: Quantity) -> Option<Quantity> {
+        let remaining = self.levels.get_mut(&price)?.reduce(order_id, quantity)?;
+        self.remove_if_empty(price);
+        Some(remaining)
+    }
+
+    /// Drop the price level if no orders remain at it, keeping it for reuse
+    pub(crate) fn remove_if_empty(&mut self, price: Price) {
+        if self.levels.get(&price).is_some_and(|level| level.is_empty()) {
+            if let Some(level) = self.levels.remove(&price) {
+                self.pool.recycle(level);
+            }
+        }
+    }
+
     /// Get the best ask (lowest price)
     pub fn best_ask(&self) -> Option<(Price, Quantity)> {
         // BTreeMap iter is ascending, so first() gives us lowest price
@@ -69,6 +119,11 @@ impl AskBook {
         self.levels.iter_mut().next().map(|(price, level)| (*price, level))
     }
 
+    /// Iterate price levels in matching priority (lowest price first)
+    pub(crate) fn levels(&self) -> impl Iterator<Item = (Price, &PriceLevel)> {
+        self.levels.iter().map(|(price, level)| (*price, level))
+    }
+
     /// Get depth snapshot (top N price levels)
     pub fn depth_snapshot(&self, depth: usize) -> Vec<(Price, Quantity)> {
         self.levels
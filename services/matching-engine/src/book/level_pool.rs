@@ -0,0 +1,84 @@
+//! Recycled price level storage
+//!
+//! Price levels churn as orders rest, fill and cancel. Emptied levels are
+//! kept on a free list with their queue capacity and handed back out for
+//! the next new price, so steady-state flow stops allocating a queue every
+//! time a level opens.
+
+use super::price_level::PriceLevel;
+
+/// Queue capacity reserved when a level has to be allocated
+pub const LEVEL_CAPACITY: usize = 16;
+
+/// Empty levels kept for reuse per book side; beyond this they are dropped
+pub const MAX_FREE_LEVELS: usize = 1024;
+
+/// Free list of emptied price levels
+#[derive(Debug, Clone, Default)]
+pub struct LevelPool {
+    free: Vec<PriceLevel>,
+}
+
+impl LevelPool {
+    /// Create an empty pool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty level, reused from the free list when one is available
+    pub fn take(&mut self) -> PriceLevel {
+        self.free
+            .pop()
+            .unwrap_or_else(|| PriceLevel::with_capacity(LEVEL_CAPACITY))
+    }
+
+    /// Return a level that left the book
+    ///
+    /// The level is cleared but keeps its queue allocation.
+    pub fn recycle(&mut self, mut level: PriceLevel) {
+        if self.free.len() < MAX_FREE_LEVELS {
+            level.clear();
+            self.free.push(level);
+        }
+    }
+
+    /// Number of levels waiting for reuse
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::ids::{AccountId, OrderId};
+    use types::numeric::Quantity;
+
+    #[test]
+    fn test_recycled_level_is_cleared_and_keeps_capacity() {
+        let mut pool = LevelPool::new();
+        let mut level = pool.take();
+        for _ in 0..LEVEL_CAPACITY * 2 {
+            level.insert(OrderId::new(), AccountId::new(), Quantity::from_str("1.0").unwrap());
+        }
+        let capacity = level.capacity();
+
+        pool.recycle(level);
+        assert_eq!(pool.free_count(), 1);
+
+        let reused = pool.take();
+        assert!(reused.is_empty());
+        assert!(reused.total_quantity().is_zero());
+        assert_eq!(reused.capacity(), capacity);
+        assert_eq!(pool.free_count(), 0);
+    }
+
+    #[test]
+    fn test_pool_is_bounded() {
+        let mut pool = LevelPool::new();
+        for _ in 0..MAX_FREE_LEVELS + 10 {
+            pool.recycle(PriceLevel::new());
+        }
+        assert_eq!(pool.free_count(), MAX_FREE_LEVELS);
+    }
+}
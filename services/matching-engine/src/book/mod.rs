@@ -5,7 +5,13 @@
 pub mod price_level;
 pub mod bid_book;
 pub mod ask_book;
+pub mod stop_book;
+pub mod order_index;
+pub mod level_pool;
 
 pub use price_level::PriceLevel;
 pub use bid_book::BidBook;
 pub use ask_book::AskBook;
+pub use stop_book::StopBook;
+pub use order_index::{OrderIndex, OrderLocation};
+pub use level_pool::LevelPool;
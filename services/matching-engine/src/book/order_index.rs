@@ -0,0 +1,163 @@
+//! Resting order index
+//!
+//! Locates resting orders by id and lists each account's orders in
+//! acceptance sequence, so cancels by id or by account touch only the
+//! orders involved instead of scanning the book.
+
+use std::collections::{BTreeMap, HashMap};
+use types::ids::{AccountId, OrderId};
+use types::numeric::Price;
+use types::order::Side;
+
+/// Where a resting order sits on the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderLocation {
+    pub symbol: String,
+    pub account_id: AccountId,
+    pub side: Side,
+    pub price: Price,
+    /// Acceptance sequence, renewed when the order loses time priority
+    pub sequence: u64,
+    /// When the order was first placed (Unix nanos), kept across amendments
+    pub placed_at: i64,
+}
+
+/// Index of resting orders by id and by account
+#[derive(Debug, Clone, Default)]
+pub struct OrderIndex {
+    orders: HashMap<OrderId, OrderLocation>,
+    /// Per-account orders keyed by acceptance sequence
+    by_account: HashMap<AccountId, BTreeMap<u64, OrderId>>,
+    next_sequence: u64,
+}
+
+impl OrderIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a newly resting order, returning its acceptance sequence
+    ///
+    /// Re-inserting an indexed order replaces its previous entry.
+    pub fn insert(
+        &mut self,
+        order_id: OrderId,
+        account_id: AccountId,
+        symbol: &str,
+        side: Side,
+        price: Price,
+        placed_at: i64,
+    ) -> u64 {
+        self.remove(&order_id);
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.orders.insert(order_id, OrderLocation {
+            symbol: symbol.to_string(),
+            account_id,
+            side,
+            price,
+            sequence,
+            placed_at,
+        });
+        self.by_account.entry(account_id).or_default().insert(sequence, order_id);
+        sequence
+    }
+
+    /// Move an order to a new price at the back of the queue
+    ///
+    /// Returns false if the order is not indexed.
+    pub fn reprice(&mut self, order_id: &OrderId, price: Price) -> bool {
+        let Some(location) = self.orders.get(order_id) else {
+            return false;
+        };
+        let (account_id, symbol, side, placed_at) =
+            (location.account_id, location.symbol.clone(), location.side, location.placed_at);
+        self.insert(*order_id, account_id, &symbol, side, price, placed_at);
+        true
+    }
+
+    /// Drop an order that left the book
+    pub fn remove(&mut self, order_id: &OrderId) -> Option<OrderLocation> {
+        let location = self.orders.remove(order_id)?;
+        if let Some(orders) = self.by_account.get_mut(&location.account_id) {
+            orders.remove(&location.sequence);
+            if orders.is_empty() {
+                self.by_account.remove(&location.account_id);
+            }
+        }
+        Some(location)
+    }
+
+    /// Locate a resting order
+    pub fn get(&self, order_id: &OrderId) -> Option<&OrderLocation> {
+        self.orders.get(order_id)
+    }
+
+    /// Every indexed order, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&OrderId, &OrderLocation)> {
+        self.orders.iter()
+    }
+
+    /// An account's resting orders in acceptance sequence
+    pub fn account_orders(&self, account_id: &AccountId) -> Vec<OrderId> {
+        self.by_account
+            .get(account_id)
+            .map(|orders| orders.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Number of indexed orders
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Check if no orders are indexed
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Number of accounts with resting orders
+    pub fn account_count(&self) -> usize {
+        self.by_account.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_orders_in_sequence() {
+        let mut index = OrderIndex::new();
+        let account = AccountId::new();
+        let ids: Vec<OrderId> = (0..3).map(|_| OrderId::new()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            index.insert(*id, account, "BTC/USDT", Side::BUY, Price::from_u64(50000 - i as u64), i as i64);
+        }
+        index.insert(OrderId::new(), AccountId::new(), "BTC/USDT", Side::SELL, Price::from_u64(51000), 3);
+
+        assert_eq!(index.account_orders(&account), ids);
+        assert_eq!(index.account_count(), 2);
+
+        // Repricing moves the order to the back
+        assert!(index.reprice(&ids[0], Price::from_u64(49000)));
+        assert_eq!(index.account_orders(&account), vec![ids[1], ids[2], ids[0]]);
+        assert_eq!(index.get(&ids[0]).unwrap().price, Price::from_u64(49000));
+        assert_eq!(index.get(&ids[0]).unwrap().placed_at, 0);
+    }
+
+    #[test]
+    fn test_remove_drops_empty_accounts() {
+        let mut index = OrderIndex::new();
+        let account = AccountId::new();
+        let id = OrderId::new();
+        index.insert(id, account, "BTC/USDT", Side::SELL, Price::from_u64(51000), 0);
+
+        assert_eq!(index.remove(&id).unwrap().account_id, account);
+        assert!(index.remove(&id).is_none());
+        assert!(index.account_orders(&account).is_empty());
+        assert_eq!(index.account_count(), 0);
+        assert!(index.is_empty());
+    }
+}
@@ -26,23 +26,55 @@ struct OrderEntry {
     order_id: OrderId,
     account_id: AccountId,
     remaining_quantity: Quantity,
+    /// Quantity filled while resting (since placement or restore)
+    filled_quantity: Quantity,
 }
 
 impl PriceLevel {
     /// Create a new empty price level
     pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create an empty price level with room for `capacity` orders
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            orders: VecDeque::new(),
+            orders: VecDeque::with_capacity(capacity),
             total_quantity: Quantity::zero(),
         }
     }
 
+    /// Empty the level, keeping its queue allocation for reuse
+    pub(crate) fn clear(&mut self) {
+        self.orders.clear();
+        self.total_quantity = Quantity::zero();
+    }
+
+    /// Overwrite the running total (tests of the invariant checks)
+    #[cfg(test)]
+    pub(crate) fn set_total(&mut self, total: Quantity) {
+        self.total_quantity = total;
+    }
+
+    /// Orders the queue can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.orders.capacity()
+    }
+
     /// Insert an order at the back of the queue (time priority)
     pub fn insert(&mut self, order_id: OrderId, account_id: AccountId, quantity: Quantity) {
+        self.insert_filled(order_id, account_id, quantity, Quantity::zero());
+    }
+
+    /// Insert an order at the back of the queue, carrying earlier fills
+    ///
+    /// Used when an amendment moves an order to a new level.
+    pub(crate) fn insert_filled(&mut self, order_id: OrderId, account_id: AccountId, quantity: Quantity, filled: Quantity) {
         self.orders.push_back(OrderEntry {
             order_id,
             account_id,
             remaining_quantity: quantity,
+            filled_quantity: filled,
         });
         self.total_quantity = self.total_quantity + quantity;
     }
@@ -63,6 +95,59 @@ impl PriceLevel {
         Some(entry.remaining_quantity)
     }
 
+    /// Reduce an order's remaining quantity by a filled amount
+    ///
+    /// Returns the new remaining quantity, or None if not found. The order
+    /// keeps its queue position; it is removed once nothing remains.
+    pub fn reduce(&mut self, order_id: &OrderId, filled: Quantity) -> Option<Quantity> {
+        let position = self.orders.iter().position(|entry| &entry.order_id == order_id)?;
+        let entry = &mut self.orders[position];
+        let reduced = filled.min(entry.remaining_quantity);
+        let remaining = Quantity::try_new(
+            entry.remaining_quantity.as_decimal() - reduced.as_decimal()
+        ).unwrap_or(Quantity::zero());
+        entry.remaining_quantity = remaining;
+        entry.filled_quantity = entry.filled_quantity + reduced;
+
+        if remaining.is_zero() {
+            self.orders.remove(position);
+        }
+
+        self.total_quantity = Quantity::try_new(
+            self.total_quantity.as_decimal() - reduced.as_decimal()
+        ).unwrap_or(Quantity::zero());
+
+        Some(remaining)
+    }
+
+    /// Set an order's remaining quantity in place, keeping its queue position
+    ///
+    /// Not a fill: the filled quantity is unchanged. Returns false if the
+    /// order is not at this level or the quantity is zero.
+    pub(crate) fn resize(&mut self, order_id: &OrderId, quantity: Quantity) -> bool {
+        if quantity.is_zero() {
+            return false;
+        }
+        let Some(entry) = self.orders.iter_mut().find(|entry| &entry.order_id == order_id) else {
+            return false;
+        };
+        self.total_quantity = Quantity::try_new(
+            self.total_quantity.as_decimal() - entry.remaining_quantity.as_decimal() + quantity.as_decimal()
+        ).unwrap_or(Quantity::zero());
+        entry.remaining_quantity = quantity;
+        true
+    }
+
+    /// Look up an order at this level
+    ///
+    /// Returns (account_id, remaining_quantity, filled_quantity)
+    pub(crate) fn find(&self, order_id: &OrderId) -> Option<(AccountId, Quantity, Quantity)> {
+        self.orders
+            .iter()
+            .find(|entry| &entry.order_id == order_id)
+            .map(|entry| (entry.account_id, entry.remaining_quantity, entry.filled_quantity))
+    }
+
     /// Peek at the front order without removing it
     ///
     /// Returns (order_id, account_id, quantity)
@@ -70,6 +155,20 @@ impl PriceLevel {
         self.orders.front().map(|entry| (entry.order_id, entry.account_id, entry.remaining_quantity))
     }
 
+    /// Iterate resting orders in time priority
+    ///
+    /// Returns (account_id, quantity) for each order
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (AccountId, Quantity)> + '_ {
+        self.orders.iter().map(|entry| (entry.account_id, entry.remaining_quantity))
+    }
+
+    /// Iterate resting orders in time priority with their ids
+    ///
+    /// Returns (order_id, account_id, quantity) for each order
+    pub(crate) fn queue(&self) -> impl Iterator<Item = (OrderId, AccountId, Quantity)> + '_ {
+        self.orders.iter().map(|entry| (entry.order_id, entry.account_id, entry.remaining_quantity))
+    }
+
     /// Pop the front order from the queue
     pub fn pop_front(&mut self) -> Option<(OrderId, Quantity)> {
         let entry = self.orders.pop_front()?;
@@ -89,6 +188,9 @@ impl PriceLevel {
     pub fn update_front_quantity(&mut self, new_quantity: Quantity) -> bool {
         if let Some(entry) = self.orders.front_mut() {
             let old_quantity = entry.remaining_quantity;
+            entry.filled_quantity = Quantity::try_new(
+                entry.filled_quantity.as_decimal() + old_quantity.as_decimal() - new_quantity.as_decimal()
+            ).unwrap_or(entry.filled_quantity);
             
             if new_quantity.is_zero() {
                 // Remove the order if fully filled
@@ -217,6 +319,26 @@ mod tests {
         assert_eq!(level.total_quantity(), Quantity::zero());
     }
 
+    #[test]
+    fn test_price_level_reduce_keeps_queue_position() {
+        let mut level = PriceLevel::new();
+        let account_id = AccountId::new();
+        let order1 = OrderId::new();
+        let order2 = OrderId::new();
+
+        level.insert(order1, account_id, Quantity::from_str("2.0").unwrap());
+        level.insert(order2, account_id, Quantity::from_str("1.0").unwrap());
+
+        assert_eq!(level.reduce(&order1, Quantity::from_str("0.5").unwrap()), Some(Quantity::from_str("1.5").unwrap()));
+        assert_eq!(level.peek_front().unwrap().0, order1);
+        assert_eq!(level.total_quantity(), Quantity::from_str("2.5").unwrap());
+
+        // Fully filled order leaves the queue
+        assert_eq!(level.reduce(&order2, Quantity::from_str("1.0").unwrap()), Some(Quantity::zero()));
+        assert_eq!(level.order_count(), 1);
+        assert!(level.reduce(&order2, Quantity::from_str("1.0").unwrap()).is_none());
+    }
+
     #[test]
     fn test_price_level_total_quantity_invariant() {
         let mut level = PriceLevel::new();
@@ -0,0 +1,172 @@
+//! Stop order trigger store
+//!
+//! Holds stop-market and stop-limit orders off-book until their stop price
+//! trades. Buy stops trigger when a trade prints at or above the stop price,
+//! sell stops when it prints at or below. Orders are keyed by
+//! (trigger price, acceptance sequence) per side, and triggered orders are
+//! released in acceptance sequence order so replay is deterministic.
+
+use std::collections::BTreeMap;
+use types::ids::OrderId;
+use types::numeric::Price;
+use types::order::{Order, Side};
+
+/// Off-book store of stop orders awaiting their trigger
+#[derive(Debug, Clone, Default)]
+pub struct StopBook {
+    /// Buy stops keyed by (stop price, acceptance sequence)
+    buy_stops: BTreeMap<(Price, u64), Order>,
+    /// Sell stops keyed by (stop price, acceptance sequence)
+    sell_stops: BTreeMap<(Price, u64), Order>,
+    /// Sequence assigned to the next accepted stop
+    next_sequence: u64,
+}
+
+impl StopBook {
+    /// Create an empty stop book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a stop order until triggered
+    ///
+    /// Returns false if the order carries no stop price.
+    pub fn insert(&mut self, order: Order) -> bool {
+        let Some(stop_price) = order.stop_price else {
+            return false;
+        };
+        let key = (stop_price, self.next_sequence);
+        self.next_sequence += 1;
+        match order.side {
+            Side::BUY => self.buy_stops.insert(key, order),
+            Side::SELL => self.sell_stops.insert(key, order),
+        };
+        true
+    }
+
+    /// Remove a held stop order by id
+    pub fn remove(&mut self, order_id: &OrderId) -> Option<Order> {
+        for stops in [&mut self.buy_stops, &mut self.sell_stops] {
+            let key = stops
+                .iter()
+                .find(|(_, order)| &order.order_id == order_id)
+                .map(|(key, _)| *key);
+            if let Some(key) = key {
+                return stops.remove(&key);
+            }
+        }
+        None
+    }
+
+    /// Check whether a stop order is held
+    pub fn contains(&self, order_id: &OrderId) -> bool {
+        self.buy_stops.values().chain(self.sell_stops.values()).any(|order| &order.order_id == order_id)
+    }
+
+    /// Remove and return every stop triggered by a trade at `trade_price`
+    ///
+    /// Orders are returned in acceptance sequence order across both sides.
+    pub fn take_triggered(&mut self, trade_price: Price) -> Vec<Order> {
+        let mut triggered: Vec<((Price, u64), Order)> = Vec::new();
+
+        let buy_keys: Vec<(Price, u64)> = self
+            .buy_stops
+            .range(..=(trade_price, u64::MAX))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in buy_keys {
+            let order = self.buy_stops.remove(&key).unwrap();
+            triggered.push((key, order));
+        }
+
+        let sell_keys: Vec<(Price, u64)> = self
+            .sell_stops
+            .range((trade_price, 0)..)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in sell_keys {
+            let order = self.sell_stops.remove(&key).unwrap();
+            triggered.push((key, order));
+        }
+
+        triggered.sort_by_key(|((_, sequence), _)| *sequence);
+        triggered.into_iter().map(|(_, order)| order).collect()
+    }
+
+    /// Number of held stop orders
+    pub fn len(&self) -> usize {
+        self.buy_stops.len() + self.sell_stops.len()
+    }
+
+    /// Check if no stop orders are held
+    pub fn is_empty(&self) -> bool {
+        self.buy_stops.is_empty() && self.sell_stops.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::ids::{AccountId, MarketId};
+    use types::numeric::Quantity;
+
+    fn stop(side: Side, stop_price: u64) -> Order {
+        Order::stop_market(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            side,
+            Price::from_u64(stop_price),
+            Quantity::from_str("1.0").unwrap(),
+            1708123456789000000,
+        )
+    }
+
+    #[test]
+    fn test_triggers_by_side_and_price() {
+        let mut stops = StopBook::new();
+        stops.insert(stop(Side::BUY, 51000));
+        stops.insert(stop(Side::BUY, 52000));
+        stops.insert(stop(Side::SELL, 49000));
+        stops.insert(stop(Side::SELL, 48000));
+
+        assert!(stops.take_triggered(Price::from_u64(50000)).is_empty());
+
+        let triggered = stops.take_triggered(Price::from_u64(51000));
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].stop_price, Some(Price::from_u64(51000)));
+
+        let triggered = stops.take_triggered(Price::from_u64(48500));
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].side, Side::SELL);
+        assert_eq!(stops.len(), 2);
+    }
+
+    #[test]
+    fn test_triggered_released_in_acceptance_order() {
+        let mut stops = StopBook::new();
+        let later_price = stop(Side::BUY, 50500);
+        let earlier_price = stop(Side::BUY, 50100);
+        let first = later_price.order_id;
+        stops.insert(later_price);
+        stops.insert(earlier_price);
+
+        let triggered = stops.take_triggered(Price::from_u64(51000));
+        assert_eq!(triggered[0].order_id, first);
+        assert!(stops.is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_reject_without_stop_price() {
+        let mut stops = StopBook::new();
+        let order = stop(Side::SELL, 49000);
+        let order_id = order.order_id;
+        stops.insert(order);
+        assert!(stops.contains(&order_id));
+        assert!(stops.remove(&order_id).is_some());
+        assert!(!stops.contains(&order_id));
+
+        let mut plain = stop(Side::SELL, 49000);
+        plain.stop_price = None;
+        assert!(!stops.insert(plain));
+    }
+}
@@ -3,27 +3,48 @@
 //! Main coordinator for order book and matching logic
 
 use std::collections::HashMap;
-use types::ids::{MarketId, OrderId};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::ids::{AccountId, MarketId, OrderId};
+use types::market::{MarketConfig, MarketConfigViolation, MarketRejectReason, MarketStatus};
 use types::numeric::{Price, Quantity};
-use types::order::{Order, Side};
+use types::order::{slippage_fill_limit, Order, RejectReason, Side, TimeInForce};
 use types::trade::Trade;
 
-use crate::book::{AskBook, BidBook};
-use crate::matching::{crossing, executor::{MatchExecutor, MatchError}};
+use std::collections::VecDeque;
+use crate::book::{AskBook, BidBook, OrderIndex, StopBook};
+use crate::events::{
+    AuctionUncrossedEvent, BookEvent, CancelSource, MarketStatusChangedEvent, OrderAmendedEvent, OrderCanceledEvent,
+    OrderRejectedEvent, PriceBandHitEvent, StopAcceptedEvent, StopTriggeredEvent, TradeExecutedEvent,
+};
+use crate::matching::{auction, crossing, executor::{MatchExecutor, MatchError}, BandTracker, PriceBand};
 
 /// Main matching engine
 pub struct MatchingEngine {
     /// Order books per symbol
-    books: HashMap<String, OrderBook>,
+    pub(crate) books: HashMap<String, OrderBook>,
     /// Trade executor with sequence generation
     executor: MatchExecutor,
+    /// Lifecycle status per symbol; unlisted symbols are Trading
+    statuses: HashMap<String, MarketStatus>,
+    /// Stops released since the last drain, in trigger order
+    triggered: Vec<TriggeredStop>,
+    /// Resting orders by id and by account
+    pub(crate) index: OrderIndex,
+    /// Price and quantity increments per symbol; unlisted symbols are unchecked
+    configs: HashMap<String, MarketConfig>,
+    /// Price bands per symbol; unlisted symbols are unbanded
+    bands: HashMap<String, PriceBand>,
+    /// Last trade price per symbol, the price band reference
+    last_trade: HashMap<String, Price>,
 }
 
 /// Order book for a single symbol
-struct OrderBook {
+pub(crate) struct OrderBook {
     symbol: MarketId,
-    bids: BidBook,
-    asks: AskBook,
+    pub(crate) bids: BidBook,
+    pub(crate) asks: AskBook,
+    stops: StopBook,
 }
 
 /// Result of submitting an order
@@ -34,6 +55,67 @@ pub enum SubmitResult {
     PartiallyFilled { trades: Vec<Trade>, remaining: Order },
     /// Order was completely filled
     Filled { trades: Vec<Trade> },
+    /// Order was refused without touching the book
+    Rejected(OrderRejectedEvent),
+    /// Immediate-or-cancel remainder was canceled instead of resting
+    Canceled { trades: Vec<Trade>, event: OrderCanceledEvent },
+    /// Stop order held off-book until its stop price trades
+    StopAccepted(StopAcceptedEvent),
+    /// Matching stopped at the price band; the remainder rested at the
+    /// band limit or was canceled, as recorded in the event
+    PriceBandHit { trades: Vec<Trade>, event: PriceBandHitEvent },
+}
+
+impl SubmitResult {
+    /// Trades executed by the submission
+    pub fn trades(&self) -> &[Trade] {
+        match self {
+            SubmitResult::PartiallyFilled { trades, .. }
+            | SubmitResult::Filled { trades }
+            | SubmitResult::Canceled { trades, .. }
+            | SubmitResult::PriceBandHit { trades, .. } => trades,
+            SubmitResult::Resting | SubmitResult::Rejected(_) | SubmitResult::StopAccepted(_) => &[],
+        }
+    }
+}
+
+/// A stop released by a trade, with the outcome of its submission
+pub struct TriggeredStop {
+    pub event: StopTriggeredEvent,
+    pub result: SubmitResult,
+}
+
+/// Result of a batch cancel
+#[derive(Debug, Clone, Default)]
+pub struct BatchCancel {
+    /// Canceled order ids in acceptance sequence
+    pub canceled: Vec<OrderId>,
+    /// One `OrderCanceled` per canceled order, in the same order
+    pub events: Vec<BookEvent>,
+}
+
+/// Result of amending a resting order
+pub struct AmendResult {
+    pub event: OrderAmendedEvent,
+    /// Trades from an amended price that crossed the book
+    pub trades: Vec<Trade>,
+}
+
+/// Result of uncrossing a call auction
+pub struct AuctionUncross {
+    /// Single price every auction trade executed at
+    pub price: Price,
+    pub trades: Vec<Trade>,
+    pub event: AuctionUncrossedEvent,
+}
+
+/// A resting order eligible for an uncross
+struct AuctionParticipant {
+    order_id: OrderId,
+    account_id: AccountId,
+    /// Acceptance sequence; the earlier of a pair is the maker
+    sequence: u64,
+    remaining: Decimal,
 }
 
 impl MatchingEngine {
@@ -42,40 +124,482 @@ impl MatchingEngine {
         Self {
             books: HashMap::new(),
             executor: MatchExecutor::new(starting_sequence),
+            statuses: HashMap::new(),
+            triggered: Vec::new(),
+            index: OrderIndex::new(),
+            configs: HashMap::new(),
+            bands: HashMap::new(),
+            last_trade: HashMap::new(),
+        }
+    }
+
+    /// Current status of a market (Trading if never set)
+    pub fn market_status(&self, symbol: &str) -> MarketStatus {
+        self.statuses
+            .get(symbol)
+            .copied()
+            .unwrap_or(MarketStatus::Trading)
+    }
+
+    /// Register a market with an explicit initial status (e.g., PreListing).
+    ///
+    /// Fails if the market already has a status.
+    pub fn list_market(&mut self, symbol: &str, status: MarketStatus) -> Result<(), EngineError> {
+        if self.statuses.contains_key(symbol) {
+            return Err(EngineError::InvalidOrder(format!("Market already listed: {}", symbol)));
+        }
+        self.statuses.insert(symbol.to_string(), status);
+        Ok(())
+    }
+
+    /// Set a market's price and quantity increments for new orders.
+    ///
+    /// Orders already resting are not re-checked.
+    pub fn set_market_config(&mut self, symbol: &str, config: MarketConfig) {
+        self.configs.insert(symbol.to_string(), config);
+    }
+
+    /// Increments for a market, if configured
+    pub fn market_config(&self, symbol: &str) -> Option<&MarketConfig> {
+        self.configs.get(symbol)
+    }
+
+    /// Set a market's price band; takes effect from the next submission
+    pub fn set_price_band(&mut self, symbol: &str, band: PriceBand) {
+        self.bands.insert(symbol.to_string(), band);
+    }
+
+    /// Last trade price for a market, if it has traded
+    pub fn last_trade_price(&self, symbol: &str) -> Option<Price> {
+        self.last_trade.get(symbol).copied()
+    }
+
+    /// Band tracker for a submission, if the market has a band
+    ///
+    /// The reference is the last trade, else the book mid, else none.
+    fn band_tracker(&self, symbol: &str, side: Side) -> Option<BandTracker> {
+        let band = *self.bands.get(symbol)?;
+        let reference = self.last_trade_price(symbol).or_else(|| {
+            let book = self.books.get(symbol)?;
+            let mid = (book.bids.best_bid_price()?.as_decimal() + book.asks.best_ask_price()?.as_decimal())
+                / Decimal::from(2);
+            Price::try_new(mid)
+        });
+        Some(BandTracker::new(band, side, reference))
+    }
+
+    /// Whether the band, rather than the order's price or size, stopped matching
+    fn band_blocks(book: &OrderBook, order: &Order, band: &Option<BandTracker>) -> bool {
+        let Some(band) = band else {
+            return false;
+        };
+        let next = match order.side {
+            Side::BUY => book.asks.best_ask_price(),
+            Side::SELL => book.bids.best_bid_price(),
+        };
+        next.is_some_and(|price| {
+            let crosses = order.is_market()
+                || match order.side {
+                    Side::BUY => crossing::can_match(order.price, price),
+                    Side::SELL => crossing::can_match(price, order.price),
+                };
+            crosses && !band.admits(price)
+        })
+    }
+
+    /// Band limit moved inward onto the tick grid, so a rested remainder
+    /// neither crosses the band nor breaks the market's increments
+    fn band_edge(&self, symbol: &str, side: Side, limit: Price) -> Price {
+        let tick = self
+            .configs
+            .get(symbol)
+            .map(|config| config.tick_size)
+            .filter(|tick| *tick > Decimal::ZERO)
+            .unwrap_or(Decimal::new(1, types::market::INCREMENT_CHECK_DP));
+        let ticks = limit.as_decimal() / tick;
+        let ticks = match side {
+            Side::BUY => ticks.floor(),
+            Side::SELL => ticks.ceil(),
+        };
+        Price::try_new(ticks * tick).unwrap_or(limit)
+    }
+
+    /// Transition a market's status, enforcing the lifecycle state machine.
+    pub fn set_market_status(
+        &mut self,
+        symbol: &str,
+        new_status: MarketStatus,
+        actor: impl Into<String>,
+        timestamp: i64,
+    ) -> Result<MarketStatusChangedEvent, EngineError> {
+        let old_status = self.market_status(symbol);
+        old_status
+            .transition_to(new_status)
+            .map_err(|e| EngineError::InvalidStatusTransition {
+                symbol: symbol.to_string(),
+                from: e.from,
+                to: e.to,
+            })?;
+        self.statuses.insert(symbol.to_string(), new_status);
+
+        Ok(MarketStatusChangedEvent {
+            symbol: symbol.to_string(),
+            old_status,
+            new_status,
+            actor: actor.into(),
+            changed_at: timestamp,
+        })
+    }
+
+    /// Next trade sequence number the engine will assign
+    pub fn next_sequence(&self) -> u64 {
+        self.executor.next_sequence_peek()
+    }
+
+    /// Ensure future sequences come after `sequence` (used on replay)
+    pub fn advance_sequence_past(&mut self, sequence: u64) {
+        self.executor.advance_past(sequence);
+    }
+
+    /// Get or create the book for a symbol
+    fn book_mut(&mut self, symbol: &str) -> &mut OrderBook {
+        self.books.entry(symbol.to_string()).or_insert_with(|| OrderBook {
+            symbol: MarketId::new(symbol),
+            bids: BidBook::new(),
+            asks: AskBook::new(),
+            stops: StopBook::new(),
+        })
+    }
+
+    /// Symbols with a book, in sorted order
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.books.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Place a resting order directly on its book without matching
+    ///
+    /// Used by warm start; entries must be inserted in time priority order.
+    pub fn restore_entry(&mut self, entry: &BookEntry) {
+        let book = self.book_mut(entry.symbol.as_str());
+        match entry.side {
+            Side::BUY => book.bids.insert_filled(entry.order_id, entry.account_id, entry.price, entry.remaining_quantity, entry.filled_quantity),
+            Side::SELL => book.asks.insert_filled(entry.order_id, entry.account_id, entry.price, entry.remaining_quantity, entry.filled_quantity),
+        }
+        self.index.insert(entry.order_id, entry.account_id, entry.symbol.as_str(), entry.side, entry.price, entry.placed_at);
+    }
+
+    /// Apply a journaled book event, reproducing its recorded outcome
+    ///
+    /// No matching or admission checks run; trades reduce the maker order
+    /// and advance the sequence past the trade's sequence number.
+    pub fn apply_event(&mut self, event: &BookEvent) -> Result<(), EngineError> {
+        match event {
+            BookEvent::OrderAccepted { order_id, account_id, symbol, side, price, quantity, accepted_at } => {
+                self.restore_entry(&BookEntry {
+                    order_id: *order_id,
+                    account_id: *account_id,
+                    symbol: MarketId::new(symbol),
+                    side: *side,
+                    price: *price,
+                    remaining_quantity: *quantity,
+                    filled_quantity: Quantity::zero(),
+                    placed_at: *accepted_at,
+                });
+            }
+            BookEvent::TradeExecuted(trade) => {
+                // Maker rests on the opposite side of the taker
+                let book = self.book_mut(&trade.symbol);
+                let remaining = match trade.side {
+                    Side::BUY => book.asks.reduce(&trade.maker_order_id, trade.price, trade.quantity),
+                    Side::SELL => book.bids.reduce(&trade.maker_order_id, trade.price, trade.quantity),
+                };
+                match remaining {
+                    None => {
+                        return Err(EngineError::InvalidOrder(format!(
+                            "Maker order {} not resting at {}", trade.maker_order_id, trade.price
+                        )));
+                    }
+                    Some(remaining) if remaining.is_zero() => {
+                        self.index.remove(&trade.maker_order_id);
+                    }
+                    Some(_) => {}
+                }
+                self.last_trade.insert(trade.symbol.clone(), trade.price);
+                self.executor.advance_past(trade.sequence);
+            }
+            BookEvent::OrderCanceled { order_id, symbol, side, price, .. } => {
+                let book = self.book_mut(symbol);
+                let removed = match side {
+                    Side::BUY => book.bids.remove(order_id, *price),
+                    Side::SELL => book.asks.remove(order_id, *price),
+                };
+                let found = removed || book.stops.remove(order_id).is_some();
+                if removed {
+                    self.index.remove(order_id);
+                }
+                if !found {
+                    return Err(EngineError::InvalidOrder(format!(
+                        "Canceled order {} not resting at {}", order_id, price
+                    )));
+                }
+            }
+            BookEvent::StopAccepted(stop) => {
+                let book = self.book_mut(stop.order.symbol.as_str());
+                if !book.stops.insert(stop.order.clone()) {
+                    return Err(EngineError::InvalidOrder(format!(
+                        "Stop order {} has no stop price", stop.order.order_id
+                    )));
+                }
+            }
+            BookEvent::StopTriggered(stop) => {
+                // The released submission follows as its own events
+                if self.book_mut(&stop.symbol).stops.remove(&stop.order_id).is_none() {
+                    return Err(EngineError::InvalidOrder(format!(
+                        "Triggered stop {} not held", stop.order_id
+                    )));
+                }
+            }
+            BookEvent::PriceBandHit(hit) => {
+                if hit.rested {
+                    self.restore_entry(&BookEntry {
+                        order_id: hit.order_id,
+                        account_id: hit.account_id,
+                        symbol: MarketId::new(&hit.symbol),
+                        side: hit.side,
+                        price: hit.band_limit,
+                        remaining_quantity: hit.unexecuted_quantity,
+                        filled_quantity: hit.filled_quantity,
+                        placed_at: hit.placed_at,
+                    });
+                }
+            }
+            BookEvent::AuctionUncrossed(auction) => {
+                // Auction trades reduce both sides at their own resting prices
+                for trade in &auction.trades {
+                    self.reduce_resting(&trade.maker_order_id, trade.quantity)?;
+                    self.reduce_resting(&trade.taker_order_id, trade.quantity)?;
+                    self.executor.advance_past(trade.sequence);
+                }
+                self.last_trade.insert(auction.symbol.clone(), auction.price);
+            }
+            BookEvent::OrderAmended(amend) => {
+                let book = self.book_mut(&amend.symbol);
+                let found = match amend.side {
+                    Side::BUY => book.bids.find_at(&amend.order_id, amend.old_price),
+                    Side::SELL => book.asks.find_at(&amend.order_id, amend.old_price),
+                };
+                if found.is_none() {
+                    return Err(EngineError::InvalidOrder(format!(
+                        "Amended order {} not resting at {}", amend.order_id, amend.old_price
+                    )));
+                }
+                let filled = Quantity::try_new(
+                    amend.filled_quantity.as_decimal() + amend.new_quantity.as_decimal()
+                        - amend.resting_quantity.as_decimal(),
+                ).unwrap_or(amend.filled_quantity);
+                let resting = !amend.resting_quantity.is_zero();
+                match (amend.side, amend.priority_kept && resting) {
+                    (Side::BUY, true) => { book.bids.resize(&amend.order_id, amend.old_price, amend.resting_quantity); }
+                    (Side::SELL, true) => { book.asks.resize(&amend.order_id, amend.old_price, amend.resting_quantity); }
+                    (Side::BUY, false) => {
+                        book.bids.remove(&amend.order_id, amend.old_price);
+                        if resting {
+                            book.bids.insert_filled(amend.order_id, amend.account_id, amend.new_price, amend.resting_quantity, filled);
+                        }
+                    }
+                    (Side::SELL, false) => {
+                        book.asks.remove(&amend.order_id, amend.old_price);
+                        if resting {
+                            book.asks.insert_filled(amend.order_id, amend.account_id, amend.new_price, amend.resting_quantity, filled);
+                        }
+                    }
+                }
+                if !resting {
+                    self.index.remove(&amend.order_id);
+                } else if !amend.priority_kept {
+                    self.index.reprice(&amend.order_id, amend.new_price);
+                }
+            }
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Verify a book is not crossed (best bid strictly below best ask)
+    pub fn check_uncrossed(&self, symbol: &str) -> Result<(), EngineError> {
+        let Some(book) = self.books.get(symbol) else {
+            return Ok(());
+        };
+        if let (Some(best_bid), Some(best_ask)) = (book.bids.best_bid_price(), book.asks.best_ask_price()) {
+            if crossing::can_match(best_bid, best_ask) {
+                return Err(EngineError::CrossedBook {
+                    symbol: symbol.to_string(),
+                    best_bid,
+                    best_ask,
+                });
+            }
         }
+        Ok(())
     }
 
     /// Submit an order to the matching engine
     ///
     /// This is the main entry point. The order will be matched against
     /// the book and any resulting trades will be returned.
-    pub fn submit_order(&mut self, mut order: Order, timestamp: i64) -> Result<SubmitResult, EngineError> {
+    ///
+    /// Time-in-force per spec §5: IOC remainders are canceled instead of
+    /// resting, and FOK orders are rejected up front unless the book can
+    /// fill them in full.
+    ///
+    /// Orders off the market's tick or lot grid, or below its minimum
+    /// notional, are refused before anything else runs.
+    ///
+    /// Stop orders are held off-book. Every trade is then checked against
+    /// held stops; triggered stops are submitted in acceptance order and
+    /// their own trades can trigger further stops. Collect them with
+    /// `drain_triggered`.
+    pub fn submit_order(&mut self, order: Order, timestamp: i64) -> Result<SubmitResult, EngineError> {
         let symbol_key = order.symbol.as_str().to_string();
-        
+        if let Some(config) = self.configs.get(&symbol_key) {
+            config.check_order(&order).map_err(|violation| EngineError::MarketConfigViolated {
+                symbol: symbol_key.clone(),
+                violation,
+            })?;
+        }
+        let result = self.submit_untriggered(order, timestamp)?;
+        self.trigger_stops(&symbol_key, result.trades(), timestamp)?;
+        self.debug_check_invariants();
+        Ok(result)
+    }
+
+    /// Submit one order without evaluating stop triggers
+    fn submit_untriggered(&mut self, mut order: Order, timestamp: i64) -> Result<SubmitResult, EngineError> {
+        let symbol_key = order.symbol.as_str().to_string();
+
+        // Admission by market status
+        let status = self.market_status(&symbol_key);
+        status.check_new_order().map_err(|reason| EngineError::MarketUnavailable {
+            symbol: symbol_key.clone(),
+            reason,
+        })?;
+
         // Get or create order book for this symbol
         if !self.books.contains_key(&symbol_key) {
             self.books.insert(symbol_key.clone(), OrderBook {
                 symbol: order.symbol.clone(),
                 bids: BidBook::new(),
                 asks: AskBook::new(),
+                stops: StopBook::new(),
             });
         }
 
-        // Match the order against the book
+        // Stops wait off-book for their trigger
+        if order.is_stop() {
+            if order.stop_price.is_none() {
+                return Err(EngineError::InvalidOrder(format!(
+                    "Stop order {} has no stop price", order.order_id
+                )));
+            }
+            let event = StopAcceptedEvent { order: order.clone(), accepted_at: timestamp };
+            self.books.get_mut(&symbol_key).unwrap().stops.insert(order);
+            return Ok(SubmitResult::StopAccepted(event));
+        }
+
+        // Market orders need opposing depth and a matching phase; they never rest
+        if order.is_market() {
+            let book = &self.books[&symbol_key];
+            let no_depth = match order.side {
+                Side::BUY => book.asks.is_empty(),
+                Side::SELL => book.bids.is_empty(),
+            };
+            if no_depth || !status.is_matching() {
+                return Ok(SubmitResult::Rejected(OrderRejectedEvent {
+                    order_id: order.order_id,
+                    account_id: order.account_id,
+                    symbol: symbol_key,
+                    reason: RejectReason::NoLiquidity,
+                    message: format!("no {:?} liquidity for market order", order.side.opposite()),
+                    rejected_at: timestamp,
+                }));
+            }
+        }
+
+        let mut band = self.band_tracker(&symbol_key, order.side);
+
+        // Fill-or-kill: reject up front unless the whole quantity can fill
+        if order.time_in_force == TimeInForce::FOK {
+            let fillable = if status.is_matching() {
+                Self::fillable_quantity(&self.books[&symbol_key], &order, band).0
+            } else {
+                Decimal::ZERO
+            };
+            if fillable < order.remaining_quantity.as_decimal() {
+                return Ok(SubmitResult::Rejected(OrderRejectedEvent {
+                    order_id: order.order_id,
+                    account_id: order.account_id,
+                    symbol: symbol_key,
+                    reason: RejectReason::FillOrKillUnfillable,
+                    message: format!(
+                        "fill-or-kill needs {} but only {} is fillable",
+                        order.remaining_quantity, fillable
+                    ),
+                    rejected_at: timestamp,
+                }));
+            }
+        }
+
+        // Market orders with a slippage tolerance fill only what keeps the
+        // sweep's VWAP within it, the same cut the client simulation makes
+        let slippage_limit = match order.max_slippage {
+            Some(max_slippage) if order.is_market() && status.is_matching() => {
+                Some(Self::slippage_limit(&self.books[&symbol_key], &order, max_slippage))
+            }
+            _ => None,
+        };
+
+        // Match the order against the book (auction orders only rest)
         // Split borrows: book + executor separately
-        let trades = {
+        let trades = if !status.is_matching() {
+            Vec::new()
+        } else {
             let book = self.books.get_mut(&symbol_key).unwrap();
             let executor = &mut self.executor;
+            let index = &mut self.index;
+            let fill_limit = slippage_limit.map(|(limit, _)| limit);
             
             match order.side {
-                Side::BUY => Self::match_buy_order_impl(book, executor, &mut order, timestamp)?,
-                Side::SELL => Self::match_sell_order_impl(book, executor, &mut order, timestamp)?,
+                Side::BUY => Self::match_buy_order_impl(book, executor, index, &mut band, &mut order, fill_limit, timestamp)?,
+                Side::SELL => Self::match_sell_order_impl(book, executor, index, &mut band, &mut order, fill_limit, timestamp)?,
             }
         };
+        if let Some(trade) = trades.last() {
+            self.last_trade.insert(symbol_key.clone(), trade.price);
+        }
+
+        let band_hit = !order.is_filled()
+            && status.is_matching()
+            && Self::band_blocks(&self.books[&symbol_key], &order, &band);
+        if let (true, Some(band)) = (band_hit, band) {
+            return Ok(self.halt_at_band(order, band, trades, timestamp));
+        }
 
         if order.is_filled() {
             Ok(SubmitResult::Filled { trades })
-        } else if !trades.is_empty() {
+        } else if order.time_in_force == TimeInForce::IOC {
+            // Immediate-or-cancel (including market orders): void the remainder
+            let capped = slippage_limit.is_some_and(|(_, capped)| capped);
+            let event = OrderCanceledEvent {
+                order_id: order.order_id,
+                canceled_by: CancelSource::System,
+                reason: if capped { "slippage limit" } else { "IOC remainder" }.to_string(),
+                filled_quantity: order.filled_quantity,
+                unfilled_quantity: order.remaining_quantity,
+            };
+            Ok(SubmitResult::Canceled { trades, event })
+        } else if !trades.is_empty() || order.is_market() {
+            // Market remainders are returned to the caller, never rested
             Ok(SubmitResult::PartiallyFilled {
                 trades,
                 remaining: order,
@@ -87,34 +611,176 @@ impl MatchingEngine {
                 Side::BUY => book.bids.insert(&order),
                 Side::SELL => book.asks.insert(&order),
             }
+            self.index.insert(order.order_id, order.account_id, &symbol_key, order.side, order.price, order.created_at);
             Ok(SubmitResult::Resting)
         }
     }
 
+    /// Rest or cancel the remainder of a submission stopped by its band
+    fn halt_at_band(&mut self, mut order: Order, band: BandTracker, trades: Vec<Trade>, timestamp: i64) -> SubmitResult {
+        let symbol_key = order.symbol.as_str().to_string();
+        // A band hit implies a reference, and with it a limit
+        let band_limit = band.limit().unwrap_or(order.price);
+        let rested = band.band().rest_at_limit && !order.is_market() && order.time_in_force != TimeInForce::IOC;
+        let band_limit = if rested {
+            self.band_edge(&symbol_key, order.side, band_limit)
+        } else {
+            band_limit
+        };
+
+        if rested {
+            order.price = band_limit;
+            let book = self.books.get_mut(&symbol_key).unwrap();
+            match order.side {
+                Side::BUY => book.bids.insert_filled(order.order_id, order.account_id, band_limit, order.remaining_quantity, order.filled_quantity),
+                Side::SELL => book.asks.insert_filled(order.order_id, order.account_id, band_limit, order.remaining_quantity, order.filled_quantity),
+            }
+            self.index.insert(order.order_id, order.account_id, &symbol_key, order.side, band_limit, order.created_at);
+        }
+
+        let event = PriceBandHitEvent {
+            order_id: order.order_id,
+            account_id: order.account_id,
+            symbol: symbol_key,
+            side: order.side,
+            reference_price: band.reference().unwrap_or(band_limit),
+            band_limit,
+            filled_quantity: order.filled_quantity,
+            unexecuted_quantity: order.remaining_quantity,
+            rested,
+            placed_at: order.created_at,
+            hit_at: timestamp,
+        };
+        SubmitResult::PriceBandHit { trades, event }
+    }
+
+    /// Release stops touched by `trades`, cascading through their fills
+    ///
+    /// Each fill is checked at its own price, in execution order, so a
+    /// sweep across levels triggers exactly the stops it passed through.
+    fn trigger_stops(&mut self, symbol: &str, trades: &[Trade], timestamp: i64) -> Result<(), EngineError> {
+        let mut pending: VecDeque<(Price, u64)> = trades.iter().map(|t| (t.price, t.sequence)).collect();
+        while let Some((trade_price, trade_sequence)) = pending.pop_front() {
+            let Some(book) = self.books.get_mut(symbol) else {
+                break;
+            };
+            for stop in book.stops.take_triggered(trade_price) {
+                let event = StopTriggeredEvent {
+                    order_id: stop.order_id,
+                    account_id: stop.account_id,
+                    symbol: symbol.to_string(),
+                    side: stop.side,
+                    stop_price: stop.stop_price.unwrap_or(trade_price),
+                    trade_price,
+                    trade_sequence,
+                    triggered_at: timestamp,
+                };
+                let result = self.submit_untriggered(stop.into_triggered(), timestamp)?;
+                pending.extend(result.trades().iter().map(|t| (t.price, t.sequence)));
+                self.triggered.push(TriggeredStop { event, result });
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the stops triggered since the last call, in trigger order
+    pub fn drain_triggered(&mut self) -> Vec<TriggeredStop> {
+        std::mem::take(&mut self.triggered)
+    }
+
+    /// Number of stop orders held for a symbol
+    pub fn held_stops(&self, symbol: &str) -> usize {
+        self.books.get(symbol).map_or(0, |book| book.stops.len())
+    }
+
+    /// Quantity an order could fill against the book right now
+    ///
+    /// Walks the opposing side in matching priority and stops at the first
+    /// resting order from the same account, since matching halts there on
+    /// self-trade prevention. The flag reports whether that happened. Levels
+    /// beyond the price band, as it moves with each level taken, are not
+    /// fillable.
+    fn fillable_quantity(book: &OrderBook, order: &Order, mut band: Option<BandTracker>) -> (Decimal, bool) {
+        let crosses = |price: Price| {
+            order.is_market()
+                || match order.side {
+                    Side::BUY => crossing::can_match(order.price, price),
+                    Side::SELL => crossing::can_match(price, order.price),
+                }
+        };
+        let levels: Box<dyn Iterator<Item = _>> = match order.side {
+            Side::BUY => Box::new(book.asks.levels()),
+            Side::SELL => Box::new(book.bids.levels()),
+        };
+
+        let wanted = order.remaining_quantity.as_decimal();
+        let mut fillable = Decimal::ZERO;
+        for (price, level) in levels.take_while(|(price, _)| crosses(*price)) {
+            if let Some(band) = band.as_mut() {
+                if !band.admits(price) {
+                    break;
+                }
+                band.record_fill(price);
+            }
+            for (account_id, quantity) in level.entries() {
+                if account_id == order.account_id {
+                    return (fillable, true);
+                }
+                fillable += quantity.as_decimal();
+                if fillable >= wanted {
+                    return (fillable, false);
+                }
+            }
+        }
+        (fillable, false)
+    }
+
+    /// Quantity a market order may fill within its slippage tolerance
+    ///
+    /// The flag reports whether the tolerance cut the sweep short.
+    fn slippage_limit(book: &OrderBook, order: &Order, max_slippage: Decimal) -> (Quantity, bool) {
+        let levels: Box<dyn Iterator<Item = _>> = match order.side {
+            Side::BUY => Box::new(book.asks.levels()),
+            Side::SELL => Box::new(book.bids.levels()),
+        };
+        let (limit, capped) = slippage_fill_limit(
+            order.side,
+            levels.map(|(price, level)| (price.as_decimal(), level.total_quantity().as_decimal())),
+            order.remaining_quantity.as_decimal(),
+            max_slippage,
+        );
+        (Quantity::try_new(limit).unwrap_or(Quantity::zero()), capped)
+    }
+
     /// Match incoming buy order against asks (implementation)
     fn match_buy_order_impl(
         book: &mut OrderBook,
         executor: &mut MatchExecutor,
+        index: &mut OrderIndex,
+        band: &mut Option<BandTracker>,
         order: &mut Order,
+        fill_limit: Option<Quantity>,
         timestamp: i64,
     ) -> Result<Vec<Trade>, EngineError> {
         let mut trades = Vec::new();
+        // Quantity this submission may still fill
+        let mut allowance = fill_limit.unwrap_or(order.remaining_quantity);
 
         // Match against asks (sell orders)
         while let Some((ask_price, ask_level)) = book.asks.best_ask_level_mut() {
-            // Check if prices cross
-            if !crossing::can_match(order.price, ask_price) {
+            // Check if prices cross (market orders take any price)
+            if !order.is_market() && !crossing::can_match(order.price, ask_price) {
+                break;
+            }
+            if band.is_some_and(|band| !band.admits(ask_price)) || allowance.is_zero() {
                 break;
             }
 
             // Get front order from ask level
             if let Some((maker_order_id, maker_account_id, maker_quantity)) = ask_level.peek_front() {
                 // Determine match quantity
-                let match_qty = if order.remaining_quantity <= maker_quantity {
-                    order.remaining_quantity
-                } else {
-                    maker_quantity
-                };
+                let match_qty = order.remaining_quantity.min(maker_quantity).min(allowance);
+                allowance = allowance - match_qty;
 
                 // Create trade (execution price is maker's price)
                 let trade = executor.execute_trade(
@@ -130,6 +796,9 @@ impl MatchingEngine {
                 ).map_err(EngineError::MatchError)?;
 
                 trades.push(trade);
+                if let Some(band) = band.as_mut() {
+                    band.record_fill(ask_price);
+                }
 
                 // Update order quantities
                 order.add_fill(match_qty, timestamp);
@@ -139,6 +808,10 @@ impl MatchingEngine {
                     maker_quantity.as_decimal() - match_qty.as_decimal()
                 ).unwrap_or(Quantity::zero());
                 ask_level.update_front_quantity(new_maker_qty);
+                if new_maker_qty.is_zero() {
+                    index.remove(&maker_order_id);
+                }
+                book.asks.remove_if_empty(ask_price);
 
                 // If incoming order is filled, we're done
                 if order.is_filled() {
@@ -156,26 +829,31 @@ impl MatchingEngine {
     fn match_sell_order_impl(
         book: &mut OrderBook,
         executor: &mut MatchExecutor,
+        index: &mut OrderIndex,
+        band: &mut Option<BandTracker>,
         order: &mut Order,
+        fill_limit: Option<Quantity>,
         timestamp: i64,
     ) -> Result<Vec<Trade>, EngineError> {
         let mut trades = Vec::new();
+        // Quantity this submission may still fill
+        let mut allowance = fill_limit.unwrap_or(order.remaining_quantity);
 
         // Match against bids (buy orders)
         while let Some((bid_price, bid_level)) = book.bids.best_bid_level_mut() {
-            // Check if prices cross
-            if !crossing::can_match(bid_price, order.price) {
+            // Check if prices cross (market orders take any price)
+            if !order.is_market() && !crossing::can_match(bid_price, order.price) {
+                break;
+            }
+            if band.is_some_and(|band| !band.admits(bid_price)) || allowance.is_zero() {
                 break;
             }
 
             // Get front order from bid level
             if let Some((maker_order_id, maker_account_id, maker_quantity)) = bid_level.peek_front() {
                 // Determine match quantity
-                let match_qty = if order.remaining_quantity <= maker_quantity {
-                    order.remaining_quantity
-                } else {
-                    maker_quantity
-                };
+                let match_qty = order.remaining_quantity.min(maker_quantity).min(allowance);
+                allowance = allowance - match_qty;
 
                 // Create trade (execution price is maker's price)
                 let trade = executor.execute_trade(
@@ -191,6 +869,9 @@ impl MatchingEngine {
                 ).map_err(EngineError::MatchError)?;
 
                 trades.push(trade);
+                if let Some(band) = band.as_mut() {
+                    band.record_fill(bid_price);
+                }
 
                 // Update order quantities
                 order.add_fill(match_qty, timestamp);
@@ -200,6 +881,10 @@ impl MatchingEngine {
                     maker_quantity.as_decimal() - match_qty.as_decimal()
                 ).unwrap_or(Quantity::zero());
                 bid_level.update_front_quantity(new_maker_qty);
+                if new_maker_qty.is_zero() {
+                    index.remove(&maker_order_id);
+                }
+                book.bids.remove_if_empty(bid_price);
 
                 // If incoming order is filled, we're done
                 if order.is_filled() {
@@ -213,56 +898,499 @@ impl MatchingEngine {
         Ok(trades)
     }
 
+    /// Move a market into its call auction
+    ///
+    /// Orders then rest without matching until `uncross`.
+    pub fn enter_auction(
+        &mut self,
+        symbol: &str,
+        actor: impl Into<String>,
+        timestamp: i64,
+    ) -> Result<MarketStatusChangedEvent, EngineError> {
+        self.set_market_status(symbol, MarketStatus::Auction, actor, timestamp)
+    }
+
+    /// Submit an order to a market's call auction
+    ///
+    /// Only resting limit orders take part: market, stop, IOC and FOK
+    /// orders have nothing to execute against before the uncross.
+    pub fn submit_auction_order(&mut self, order: Order, timestamp: i64) -> Result<SubmitResult, EngineError> {
+        let symbol = order.symbol.as_str();
+        if self.market_status(symbol) != MarketStatus::Auction {
+            return Err(EngineError::InvalidOrder(format!("Market not in auction: {}", symbol)));
+        }
+        if order.is_market()
+            || order.is_stop()
+            || matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK)
+        {
+            return Err(EngineError::InvalidOrder(format!(
+                "Order {} cannot join an auction", order.order_id
+            )));
+        }
+        self.submit_order(order, timestamp)
+    }
+
+    /// Execute a market's call auction at a single clearing price
+    ///
+    /// The price maximizes executed volume, then minimizes the imbalance,
+    /// then is the lowest candidate. Bids at or above it and asks at or
+    /// below it are allocated in price-time priority and every trade prints
+    /// at the clearing price, with the earlier-accepted order as maker.
+    /// Orders of the same account are never paired.
+    ///
+    /// Returns `None` if nothing crosses. The market stays in Auction until
+    /// moved on with `set_market_status`, and auction trades do not trigger
+    /// stops.
+    pub fn uncross(&mut self, symbol: &str, timestamp: i64) -> Result<Option<AuctionUncross>, EngineError> {
+        if self.market_status(symbol) != MarketStatus::Auction {
+            return Err(EngineError::InvalidOrder(format!("Market not in auction: {}", symbol)));
+        }
+        let Some(book) = self.books.get(symbol) else {
+            return Ok(None);
+        };
+        let bids = book.bids.depth_snapshot(usize::MAX);
+        let asks = book.asks.depth_snapshot(usize::MAX);
+        let Some(clearing) = auction::clearing_price(&bids, &asks) else {
+            return Ok(None);
+        };
+        let price = clearing.price;
+        let market = book.symbol.clone();
+
+        let index = &self.index;
+        let participant = |(order_id, account_id, quantity): (OrderId, AccountId, Quantity)| AuctionParticipant {
+            order_id,
+            account_id,
+            sequence: index.get(&order_id).map_or(u64::MAX, |location| location.sequence),
+            remaining: quantity.as_decimal(),
+        };
+        let mut buyers: Vec<AuctionParticipant> = book
+            .bids
+            .levels()
+            .take_while(|(level_price, _)| *level_price >= price)
+            .flat_map(|(_, level)| level.queue())
+            .map(participant)
+            .collect();
+        let mut sellers: Vec<AuctionParticipant> = book
+            .asks
+            .levels()
+            .take_while(|(level_price, _)| *level_price <= price)
+            .flat_map(|(_, level)| level.queue())
+            .map(participant)
+            .collect();
+
+        let mut unallocated = clearing.volume;
+        let mut trades = Vec::new();
+        for buyer in buyers.iter_mut() {
+            for seller in sellers.iter_mut() {
+                if unallocated.is_zero() || buyer.remaining.is_zero() {
+                    break;
+                }
+                if seller.remaining.is_zero() || seller.account_id == buyer.account_id {
+                    continue;
+                }
+                let quantity = buyer.remaining.min(seller.remaining).min(unallocated);
+                let (maker, taker, side) = if buyer.sequence < seller.sequence {
+                    (&*buyer, &*seller, Side::SELL)
+                } else {
+                    (&*seller, &*buyer, Side::BUY)
+                };
+                let trade = self.executor.execute_trade(
+                    market.clone(),
+                    maker.order_id,
+                    taker.order_id,
+                    maker.account_id,
+                    taker.account_id,
+                    side,
+                    price,
+                    Quantity::try_new(quantity).unwrap_or(Quantity::zero()),
+                    timestamp,
+                ).map_err(EngineError::MatchError)?;
+                trades.push(trade);
+                buyer.remaining -= quantity;
+                seller.remaining -= quantity;
+                unallocated -= quantity;
+            }
+        }
+        if trades.is_empty() {
+            return Ok(None);
+        }
+
+        for trade in &trades {
+            self.reduce_resting(&trade.maker_order_id, trade.quantity)?;
+            self.reduce_resting(&trade.taker_order_id, trade.quantity)?;
+        }
+        self.last_trade.insert(symbol.to_string(), price);
+
+        let volume: Decimal = trades.iter().map(|trade| trade.quantity.as_decimal()).sum();
+        let event = AuctionUncrossedEvent {
+            symbol: symbol.to_string(),
+            price,
+            volume: Quantity::try_new(volume).unwrap_or(Quantity::zero()),
+            trades: trades.iter().map(TradeExecutedEvent::from_trade).collect(),
+            uncrossed_at: timestamp,
+        };
+        self.debug_check_invariants();
+        Ok(Some(AuctionUncross { price, trades, event }))
+    }
+
+    /// Reduce an indexed resting order by a filled quantity
+    fn reduce_resting(&mut self, order_id: &OrderId, quantity: Quantity) -> Result<(), EngineError> {
+        let location = self.index.get(order_id).ok_or(EngineError::OrderNotFound(*order_id))?.clone();
+        let book = self.book_mut(&location.symbol);
+        let remaining = match location.side {
+            Side::BUY => book.bids.reduce(order_id, location.price, quantity),
+            Side::SELL => book.asks.reduce(order_id, location.price, quantity),
+        };
+        match remaining {
+            None => Err(EngineError::InvalidOrder(format!(
+                "Order {} not resting at {}", order_id, location.price
+            ))),
+            Some(remaining) => {
+                if remaining.is_zero() {
+                    self.index.remove(order_id);
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Cancel an order
+    ///
+    /// Returns false if the order is not found or the market refuses cancels.
     pub fn cancel_order(&mut self, symbol: &str, order_id: &OrderId, price: Price, side: Side) -> bool {
+        if !self.market_status(symbol).accepts_cancels() {
+            return false;
+        }
         if let Some(book) = self.books.get_mut(symbol) {
-            match side {
+            let removed = match side {
                 Side::BUY => book.bids.remove(order_id, price),
                 Side::SELL => book.asks.remove(order_id, price),
+            };
+            let canceled = removed || book.stops.remove(order_id).is_some();
+            if removed {
+                self.index.remove(order_id);
+                self.debug_check_invariants();
             }
+            canceled
         } else {
             false
         }
     }
 
-    /// Get order book snapshot
-    pub fn get_order_book(&self, symbol: &str, depth: usize) -> Option<OrderBookSnapshot> {
-        self.books.get(symbol).map(|book| OrderBookSnapshot {
-            symbol: symbol.to_string(),
-            bids: book.bids.depth_snapshot(depth),
-            asks: book.asks.depth_snapshot(depth),
-        })
+    /// Cancel every resting order of an account
+    ///
+    /// Orders go in acceptance sequence; see `cancel_batch`.
+    pub fn cancel_all(&mut self, account_id: &AccountId) -> BatchCancel {
+        let order_ids = self.index.account_orders(account_id);
+        self.cancel_batch(&order_ids)
     }
-}
 
-/// Order book snapshot for market data
-#[derive(Debug, Clone)]
-pub struct OrderBookSnapshot {
-    pub symbol: String,
-    pub bids: Vec<(Price, Quantity)>,
-    pub asks: Vec<(Price, Quantity)>,
-}
+    /// Cancel a set of resting orders in one call
+    ///
+    /// Orders are canceled in acceptance sequence regardless of the order
+    /// of `order_ids`. Unknown ids and orders in markets that refuse
+    /// cancels are skipped.
+    pub fn cancel_batch(&mut self, order_ids: &[OrderId]) -> BatchCancel {
+        let mut located: Vec<(u64, OrderId)> = order_ids
+            .iter()
+            .filter_map(|order_id| self.index.get(order_id).map(|location| (location.sequence, *order_id)))
+            .collect();
+        located.sort_unstable_by_key(|(sequence, _)| *sequence);
+        located.dedup_by_key(|(sequence, _)| *sequence);
 
-/// Engine errors
-#[derive(Debug)]
-pub enum EngineError {
-    MatchError(MatchError),
-    InvalidOrder(String),
-}
+        let mut result = BatchCancel::default();
+        for (_, order_id) in located {
+            let location = self.index.get(&order_id).cloned().unwrap();
+            if !self.market_status(&location.symbol).accepts_cancels() {
+                continue;
+            }
+            let Some(book) = self.books.get_mut(&location.symbol) else {
+                continue;
+            };
+            let remaining = match location.side {
+                Side::BUY => book.bids.find_at(&order_id, location.price),
+                Side::SELL => book.asks.find_at(&order_id, location.price),
+            };
+            let Some((_, remaining_quantity, _)) = remaining else {
+                continue;
+            };
+            match location.side {
+                Side::BUY => book.bids.remove(&order_id, location.price),
+                Side::SELL => book.asks.remove(&order_id, location.price),
+            };
+            self.index.remove(&order_id);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use types::ids::AccountId;
-    use types::order::TimeInForce;
+            result.canceled.push(order_id);
+            result.events.push(BookEvent::OrderCanceled {
+                order_id,
+                symbol: location.symbol,
+                side: location.side,
+                price: location.price,
+                remaining_quantity,
+            });
+        }
+        self.debug_check_invariants();
+        result
+    }
 
-    fn create_order_with_account(account_id: AccountId, side: Side, price: u64, qty: &str) -> Order {
-        Order::new(
-            account_id,
-            MarketId::new("BTC/USDT"),
-            side,
-            Price::from_u64(price),
-            Quantity::from_str(qty).unwrap(),
+    /// Amend a resting order's price and total quantity
+    ///
+    /// `new_quantity` is the order's new total size, so fills that landed
+    /// after the client last saw the order are honored: the new remaining
+    /// quantity is `new_quantity` minus what has filled, and an amendment at
+    /// or below the filled quantity takes the order off the book. A size-down
+    /// at the same price keeps time priority; any other change moves the
+    /// order to the back of its new level, matching first if the new price
+    /// crosses. Price bands are not applied, since the remainder always
+    /// rests at the amended price. Journal the returned event before the
+    /// trades.
+    pub fn amend_order(
+        &mut self,
+        order_id: &OrderId,
+        new_price: Price,
+        new_quantity: Quantity,
+        timestamp: i64,
+    ) -> Result<AmendResult, EngineError> {
+        let result = self.amend_resting(order_id, new_price, new_quantity, timestamp)?;
+        self.debug_check_invariants();
+        Ok(result)
+    }
+
+    /// Apply an amendment without the post-mutation invariant check
+    fn amend_resting(
+        &mut self,
+        order_id: &OrderId,
+        new_price: Price,
+        new_quantity: Quantity,
+        timestamp: i64,
+    ) -> Result<AmendResult, EngineError> {
+        let located = self.index.get(order_id).and_then(|location| {
+            let book = self.books.get(&location.symbol)?;
+            let found = match location.side {
+                Side::BUY => book.bids.find_at(order_id, location.price),
+                Side::SELL => book.asks.find_at(order_id, location.price),
+            };
+            found.map(|found| (location.symbol.clone(), location.side, location.price, found))
+        });
+        let Some((symbol_key, side, old_price, (account_id, old_quantity, filled))) = located else {
+            return Err(EngineError::OrderNotFound(*order_id));
+        };
+
+        let new_remaining = Quantity::try_new(new_quantity.as_decimal() - filled.as_decimal())
+            .unwrap_or(Quantity::zero());
+        let priority_kept = new_price == old_price && new_remaining <= old_quantity;
+        if let (false, Some(config)) = (new_remaining.is_zero(), self.configs.get(&symbol_key)) {
+            config.check_limit(new_price, new_quantity).map_err(|violation| EngineError::MarketConfigViolated {
+                symbol: symbol_key.clone(),
+                violation,
+            })?;
+        }
+
+        // Size-downs are as safe as cancels; anything else is a new order
+        let status = self.market_status(&symbol_key);
+        if priority_kept {
+            if !status.accepts_cancels() {
+                return Err(EngineError::InvalidOrder(format!(
+                    "Market {} does not accept amendments", symbol_key
+                )));
+            }
+        } else {
+            status.check_new_order().map_err(|reason| EngineError::MarketUnavailable {
+                symbol: symbol_key.clone(),
+                reason,
+            })?;
+        }
+
+        let mut event = OrderAmendedEvent {
+            order_id: *order_id,
+            account_id,
+            symbol: symbol_key.clone(),
+            side,
+            old_price,
+            new_price,
+            old_quantity,
+            new_quantity: new_remaining,
+            filled_quantity: filled,
+            resting_quantity: new_remaining,
+            priority_kept,
+            amended_at: timestamp,
+        };
+
+        let book = self.books.get_mut(&symbol_key).unwrap();
+        if new_remaining.is_zero() {
+            match side {
+                Side::BUY => book.bids.remove(order_id, old_price),
+                Side::SELL => book.asks.remove(order_id, old_price),
+            };
+            self.index.remove(order_id);
+            return Ok(AmendResult { event, trades: Vec::new() });
+        }
+        if priority_kept {
+            match side {
+                Side::BUY => book.bids.resize(order_id, old_price, new_remaining),
+                Side::SELL => book.asks.resize(order_id, old_price, new_remaining),
+            };
+            return Ok(AmendResult { event, trades: Vec::new() });
+        }
+
+        let mut order = Order::new(
+            account_id,
+            book.symbol.clone(),
+            side,
+            new_price,
+            new_quantity,
+            TimeInForce::GTC,
+            timestamp,
+        );
+        order.order_id = *order_id;
+        order.filled_quantity = filled;
+        order.remaining_quantity = new_remaining;
+
+        // Refuse before touching the book if the new price reaches our own order
+        if status.is_matching() && Self::fillable_quantity(book, &order, None).1 {
+            return Err(EngineError::MatchError(MatchError::SelfTrade));
+        }
+
+        match side {
+            Side::BUY => book.bids.remove(order_id, old_price),
+            Side::SELL => book.asks.remove(order_id, old_price),
+        };
+        let trades = if status.is_matching() {
+            let executor = &mut self.executor;
+            let index = &mut self.index;
+            match side {
+                Side::BUY => Self::match_buy_order_impl(book, executor, index, &mut None, &mut order, None, timestamp)?,
+                Side::SELL => Self::match_sell_order_impl(book, executor, index, &mut None, &mut order, None, timestamp)?,
+            }
+        } else {
+            Vec::new()
+        };
+        if let Some(trade) = trades.last() {
+            self.last_trade.insert(symbol_key.clone(), trade.price);
+        }
+
+        event.resting_quantity = order.remaining_quantity;
+        if order.is_filled() {
+            self.index.remove(order_id);
+        } else {
+            match side {
+                Side::BUY => book.bids.insert_filled(*order_id, account_id, new_price, order.remaining_quantity, order.filled_quantity),
+                Side::SELL => book.asks.insert_filled(*order_id, account_id, new_price, order.remaining_quantity, order.filled_quantity),
+            }
+            self.index.reprice(order_id, new_price);
+        }
+        self.trigger_stops(&symbol_key, &trades, timestamp)?;
+        Ok(AmendResult { event, trades })
+    }
+
+    /// An account's resting orders across all books, in acceptance sequence
+    ///
+    /// Quantities are read from the book, so partially filled orders report
+    /// what is still resting. Held stop orders are not on the book and are
+    /// not listed.
+    pub fn orders_for_account(&self, account_id: &AccountId) -> Vec<OpenOrderView> {
+        self.index
+            .account_orders(account_id)
+            .into_iter()
+            .filter_map(|order_id| {
+                let location = self.index.get(&order_id)?;
+                let book = self.books.get(&location.symbol)?;
+                let (_, remaining, filled) = match location.side {
+                    Side::BUY => book.bids.find_at(&order_id, location.price),
+                    Side::SELL => book.asks.find_at(&order_id, location.price),
+                }?;
+                Some(OpenOrderView {
+                    order_id,
+                    symbol: location.symbol.clone(),
+                    side: location.side,
+                    price: location.price,
+                    original_quantity: Quantity::try_new(remaining.as_decimal() + filled.as_decimal())
+                        .unwrap_or(remaining),
+                    remaining_quantity: remaining,
+                    timestamp: location.placed_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Get order book snapshot
+    pub fn get_order_book(&self, symbol: &str, depth: usize) -> Option<OrderBookSnapshot> {
+        self.books.get(symbol).map(|book| OrderBookSnapshot {
+            symbol: symbol.to_string(),
+            bids: book.bids.depth_snapshot(depth),
+            asks: book.asks.depth_snapshot(depth),
+        })
+    }
+}
+
+/// A resting order as held on the book, independent of `Order`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookEntry {
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: MarketId,
+    pub side: Side,
+    pub price: Price,
+    pub remaining_quantity: Quantity,
+    /// Quantity filled before the entry was captured
+    pub filled_quantity: Quantity,
+    /// When the order was placed (Unix nanos)
+    pub placed_at: i64,
+}
+
+/// An account's resting order as reported to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrderView {
+    pub order_id: OrderId,
+    pub symbol: String,
+    pub side: Side,
+    pub price: Price,
+    /// Quantity the order was placed (or last amended) with
+    pub original_quantity: Quantity,
+    /// Live unfilled quantity on the book
+    pub remaining_quantity: Quantity,
+    /// When the order was placed (Unix nanos)
+    pub timestamp: i64,
+}
+
+/// Order book snapshot for market data
+#[derive(Debug, Clone)]
+pub struct OrderBookSnapshot {
+    pub symbol: String,
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+}
+
+/// Engine errors
+#[derive(Debug)]
+pub enum EngineError {
+    MatchError(MatchError),
+    InvalidOrder(String),
+    /// Market status does not admit new orders
+    MarketUnavailable { symbol: String, reason: MarketRejectReason },
+    /// Status change violates the market lifecycle
+    InvalidStatusTransition { symbol: String, from: MarketStatus, to: MarketStatus },
+    /// Best bid at or above best ask
+    CrossedBook { symbol: String, best_bid: Price, best_ask: Price },
+    /// No resting order with this id
+    OrderNotFound(OrderId),
+    /// Price or quantity outside the market's increments
+    MarketConfigViolated { symbol: String, violation: MarketConfigViolation },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::ids::AccountId;
+    use types::order::TimeInForce;
+
+    fn create_order_with_account(account_id: AccountId, side: Side, price: u64, qty: &str) -> Order {
+        Order::new(
+            account_id,
+            MarketId::new("BTC/USDT"),
+            side,
+            Price::from_u64(price),
+            Quantity::from_str(qty).unwrap(),
             TimeInForce::GTC,
             1708123456789000000,
         )
@@ -339,4 +1467,1081 @@ mod tests {
 
         assert!(matches!(result, SubmitResult::Resting));
     }
+
+    #[test]
+    fn test_cancel_only_rejects_orders_but_allows_cancels() {
+        let mut engine = MatchingEngine::new(1000);
+        let resting = create_order_with_account(AccountId::new(), Side::BUY, 50000, "1.0");
+        let resting_id = resting.order_id;
+        engine.submit_order(resting, 1708123456789000000).unwrap();
+
+        let event = engine
+            .set_market_status("BTC/USDT", MarketStatus::CancelOnly, "ops", 1708123456790000000)
+            .unwrap();
+        assert_eq!(event.old_status, MarketStatus::Trading);
+        assert_eq!(event.new_status, MarketStatus::CancelOnly);
+        assert_eq!(event.actor, "ops");
+
+        let order = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let result = engine.submit_order(order, 1708123456791000000);
+        assert!(matches!(
+            result,
+            Err(EngineError::MarketUnavailable { reason: MarketRejectReason::CancelOnly, .. })
+        ));
+
+        assert!(engine.cancel_order("BTC/USDT", &resting_id, Price::from_u64(50000), Side::BUY));
+    }
+
+    #[test]
+    fn test_invalid_status_transitions_refused() {
+        let mut engine = MatchingEngine::new(1000);
+        engine
+            .set_market_status("BTC/USDT", MarketStatus::Delisted, "ops", 1)
+            .unwrap();
+
+        let result = engine.set_market_status("BTC/USDT", MarketStatus::Trading, "ops", 2);
+        assert!(matches!(
+            result,
+            Err(EngineError::InvalidStatusTransition {
+                from: MarketStatus::Delisted,
+                to: MarketStatus::Trading,
+                ..
+            })
+        ));
+        assert_eq!(engine.market_status("BTC/USDT"), MarketStatus::Delisted);
+
+        // Halted markets cannot jump straight into a volatility halt
+        engine.list_market("ETH/USDT", MarketStatus::HaltedManual).unwrap();
+        assert!(engine
+            .set_market_status("ETH/USDT", MarketStatus::HaltedVolatility, "breaker", 3)
+            .is_err());
+    }
+
+    #[test]
+    fn test_status_specific_rejections() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.list_market("BTC/USDT", MarketStatus::PreListing).unwrap();
+
+        let order = create_order_with_account(AccountId::new(), Side::BUY, 50000, "1.0");
+        assert!(matches!(
+            engine.submit_order(order, 1),
+            Err(EngineError::MarketUnavailable { reason: MarketRejectReason::NotYetListed, .. })
+        ));
+
+        engine.set_market_status("BTC/USDT", MarketStatus::Trading, "ops", 2).unwrap();
+        engine.set_market_status("BTC/USDT", MarketStatus::HaltedVolatility, "breaker", 3).unwrap();
+        let order = create_order_with_account(AccountId::new(), Side::BUY, 50000, "1.0");
+        assert!(matches!(
+            engine.submit_order(order, 4),
+            Err(EngineError::MarketUnavailable { reason: MarketRejectReason::HaltedVolatility, .. })
+        ));
+    }
+
+    #[test]
+    fn test_auction_orders_rest_without_matching() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.list_market("BTC/USDT", MarketStatus::Auction).unwrap();
+
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let buy = create_order_with_account(AccountId::new(), Side::BUY, 50000, "1.0");
+        assert!(matches!(engine.submit_order(sell, 1).unwrap(), SubmitResult::Resting));
+        assert!(matches!(engine.submit_order(buy, 2).unwrap(), SubmitResult::Resting));
+
+        let book = engine.get_order_book("BTC/USDT", 10).unwrap();
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.asks.len(), 1);
+    }
+
+    fn market_order(side: Side, qty: &str) -> Order {
+        Order::market(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            side,
+            Quantity::from_str(qty).unwrap(),
+            1708123456790000000,
+        )
+    }
+
+    fn resting_depth(engine: &MatchingEngine) -> rust_decimal::Decimal {
+        let book = engine.get_order_book("BTC/USDT", usize::MAX).unwrap();
+        book.bids.iter().chain(book.asks.iter()).map(|(_, q)| q.as_decimal()).sum()
+    }
+
+    #[test]
+    fn test_market_order_rejected_on_empty_book() {
+        let mut engine = MatchingEngine::new(1000);
+        let resting = create_order_with_account(AccountId::new(), Side::BUY, 50000, "1.0");
+        engine.submit_order(resting, 1).unwrap();
+
+        let order = market_order(Side::BUY, "1.0");
+        let order_id = order.order_id;
+        match engine.submit_order(order, 2).unwrap() {
+            SubmitResult::Rejected(event) => {
+                assert_eq!(event.order_id, order_id);
+                assert_eq!(event.reason, RejectReason::NoLiquidity);
+            }
+            _ => panic!("Expected Rejected result"),
+        }
+        // Same-side depth is untouched and nothing new rests
+        let book = engine.get_order_book("BTC/USDT", 10).unwrap();
+        assert_eq!(book.bids.len(), 1);
+        assert!(book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_market_order_sweeps_levels_at_maker_prices() {
+        let mut engine = MatchingEngine::new(1000);
+        for (price, qty) in [(50000, "0.5"), (50100, "0.5"), (50200, "1.0")] {
+            let sell = create_order_with_account(AccountId::new(), Side::SELL, price, qty);
+            engine.submit_order(sell, 1).unwrap();
+        }
+
+        match engine.submit_order(market_order(Side::BUY, "1.5"), 2).unwrap() {
+            SubmitResult::Filled { trades } => {
+                let prices: Vec<Price> = trades.iter().map(|t| t.price).collect();
+                assert_eq!(
+                    prices,
+                    vec![Price::from_u64(50000), Price::from_u64(50100), Price::from_u64(50200)]
+                );
+                assert_eq!(trades[2].quantity, Quantity::from_str("0.5").unwrap());
+            }
+            _ => panic!("Expected Filled result"),
+        }
+        let book = engine.get_order_book("BTC/USDT", 10).unwrap();
+        assert_eq!(book.asks, vec![(Price::from_u64(50200), Quantity::from_str("0.5").unwrap())]);
+    }
+
+    #[test]
+    fn test_market_order_remainder_never_rests() {
+        let mut engine = MatchingEngine::new(1000);
+        let buy = create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.4");
+        engine.submit_order(buy, 1).unwrap();
+
+        match engine.submit_order(market_order(Side::SELL, "1.0"), 2).unwrap() {
+            SubmitResult::Canceled { trades, event } => {
+                assert_eq!(trades.len(), 1);
+                assert_eq!(event.unfilled_quantity, Quantity::from_str("0.6").unwrap());
+            }
+            _ => panic!("Expected Canceled result"),
+        }
+        let book = engine.get_order_book("BTC/USDT", 10).unwrap();
+        assert!(book.bids.is_empty());
+        assert!(book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_market_order_slippage_cap_lands_mid_level() {
+        let mut engine = MatchingEngine::new(1000);
+        for (price, qty) in [(50000, "0.5"), (50100, "0.5"), (50200, "1.0")] {
+            let sell = create_order_with_account(AccountId::new(), Side::SELL, price, qty);
+            engine.submit_order(sell, 1).unwrap();
+        }
+
+        // Cap 50 025: all of 50 000, then 12.5 / 75 of 50 100
+        let mut order = market_order(Side::BUY, "1.5");
+        order.max_slippage = Some(rust_decimal::Decimal::from_str_exact("0.0005").unwrap());
+        match engine.submit_order(order, 2).unwrap() {
+            SubmitResult::Canceled { trades, event } => {
+                let quantities: Vec<Quantity> = trades.iter().map(|t| t.quantity).collect();
+                assert_eq!(quantities, vec![Quantity::from_str("0.5").unwrap(), Quantity::from_str("0.16666666").unwrap()]);
+                assert_eq!(event.unfilled_quantity, Quantity::from_str("0.83333334").unwrap());
+                assert_eq!(event.reason, "slippage limit");
+            }
+            _ => panic!("Expected Canceled result"),
+        }
+        let book = engine.get_order_book("BTC/USDT", 10).unwrap();
+        assert_eq!(book.asks[0], (Price::from_u64(50100), Quantity::from_str("0.33333334").unwrap()));
+    }
+
+    #[test]
+    fn test_market_order_slippage_cap_breached_at_best_fills_nothing() {
+        let mut engine = MatchingEngine::new(1000);
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        engine.submit_order(sell, 1).unwrap();
+
+        let mut order = market_order(Side::BUY, "1.0");
+        order.max_slippage = Some(rust_decimal::Decimal::from_str_exact("-0.01").unwrap());
+        match engine.submit_order(order, 2).unwrap() {
+            SubmitResult::Canceled { trades, event } => {
+                assert!(trades.is_empty());
+                assert_eq!(event.unfilled_quantity, Quantity::from_str("1.0").unwrap());
+                assert_eq!(event.reason, "slippage limit");
+            }
+            _ => panic!("Expected Canceled result"),
+        }
+        assert_eq!(engine.get_order_book("BTC/USDT", 10).unwrap().asks.len(), 1);
+    }
+
+    #[test]
+    fn test_market_order_rejected_during_auction() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.list_market("BTC/USDT", MarketStatus::Auction).unwrap();
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        engine.submit_order(sell, 1).unwrap();
+
+        let result = engine.submit_order(market_order(Side::BUY, "1.0"), 2).unwrap();
+        assert!(matches!(result, SubmitResult::Rejected(_)));
+        assert_eq!(engine.get_order_book("BTC/USDT", 10).unwrap().asks.len(), 1);
+    }
+
+    #[test]
+    fn test_quantity_conserved_across_mixed_flow() {
+        use rust_decimal::Decimal;
+
+        let mut engine = MatchingEngine::new(1000);
+        let mut placed = Decimal::ZERO;
+        let mut traded = Decimal::ZERO;
+        let mut unrested = Decimal::ZERO;
+
+        let flow: Vec<Order> = vec![
+            create_order_with_account(AccountId::new(), Side::SELL, 50100, "1.0"),
+            market_order(Side::SELL, "0.3"),
+            create_order_with_account(AccountId::new(), Side::BUY, 49900, "0.8"),
+            create_order_with_account(AccountId::new(), Side::SELL, 50000, "0.5"),
+            market_order(Side::BUY, "0.9"),
+            create_order_with_account(AccountId::new(), Side::BUY, 50100, "0.2"),
+            market_order(Side::SELL, "1.5"),
+            market_order(Side::BUY, "0.4"),
+            create_order_with_account(AccountId::new(), Side::SELL, 49800, "0.3"),
+        ];
+
+        for (ts, order) in flow.into_iter().enumerate() {
+            let quantity = order.quantity.as_decimal();
+            placed += quantity;
+            match engine.submit_order(order, ts as i64).unwrap() {
+                SubmitResult::Resting => {}
+                SubmitResult::Filled { trades } => {
+                    traded += trades.iter().map(|t| t.quantity.as_decimal()).sum::<Decimal>();
+                }
+                SubmitResult::PartiallyFilled { trades, remaining } => {
+                    traded += trades.iter().map(|t| t.quantity.as_decimal()).sum::<Decimal>();
+                    unrested += remaining.remaining_quantity.as_decimal();
+                }
+                SubmitResult::Canceled { trades, event } => {
+                    traded += trades.iter().map(|t| t.quantity.as_decimal()).sum::<Decimal>();
+                    unrested += event.unfilled_quantity.as_decimal();
+                }
+                SubmitResult::Rejected(_) => unrested += quantity,
+                SubmitResult::StopAccepted(_) => unreachable!("no stops in this flow"),
+                SubmitResult::PriceBandHit { .. } => unreachable!("no price bands in this flow"),
+            }
+        }
+
+        // Each trade consumes quantity from both a taker and a maker
+        assert!(traded > Decimal::ZERO);
+        assert_eq!(placed, traded * Decimal::from(2) + resting_depth(&engine) + unrested);
+        engine.check_uncrossed("BTC/USDT").unwrap();
+    }
+
+    fn tif_order(account_id: AccountId, side: Side, price: u64, qty: &str, tif: TimeInForce) -> Order {
+        let mut order = create_order_with_account(account_id, side, price, qty);
+        order.time_in_force = tif;
+        order
+    }
+
+    type Levels = Vec<(Price, Quantity)>;
+
+    fn book_levels(engine: &MatchingEngine) -> (Levels, Levels) {
+        let book = engine.get_order_book("BTC/USDT", usize::MAX).unwrap();
+        (book.bids, book.asks)
+    }
+
+    #[test]
+    fn test_ioc_cancels_remainder() {
+        let mut engine = MatchingEngine::new(1000);
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 50000, "0.4");
+        engine.submit_order(sell, 1).unwrap();
+
+        let ioc = tif_order(AccountId::new(), Side::BUY, 50000, "1.0", TimeInForce::IOC);
+        let ioc_id = ioc.order_id;
+        match engine.submit_order(ioc, 2).unwrap() {
+            SubmitResult::Canceled { trades, event } => {
+                assert_eq!(trades.len(), 1);
+                assert_eq!(event.order_id, ioc_id);
+                assert_eq!(event.filled_quantity, Quantity::from_str("0.4").unwrap());
+                assert_eq!(event.unfilled_quantity, Quantity::from_str("0.6").unwrap());
+            }
+            _ => panic!("Expected Canceled result"),
+        }
+        let (bids, asks) = book_levels(&engine);
+        assert!(bids.is_empty());
+        assert!(asks.is_empty());
+    }
+
+    #[test]
+    fn test_ioc_with_nothing_crossable() {
+        let mut engine = MatchingEngine::new(1000);
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 51000, "1.0");
+        engine.submit_order(sell, 1).unwrap();
+        let before = book_levels(&engine);
+
+        let ioc = tif_order(AccountId::new(), Side::BUY, 50000, "1.0", TimeInForce::IOC);
+        match engine.submit_order(ioc, 2).unwrap() {
+            SubmitResult::Canceled { trades, event } => {
+                assert!(trades.is_empty());
+                assert!(event.filled_quantity.is_zero());
+                assert_eq!(event.unfilled_quantity, Quantity::from_str("1.0").unwrap());
+            }
+            _ => panic!("Expected Canceled result"),
+        }
+        assert_eq!(book_levels(&engine), before);
+    }
+
+    #[test]
+    fn test_fok_fills_across_levels() {
+        let mut engine = MatchingEngine::new(1000);
+        for (price, qty) in [(50000, "0.3"), (50100, "0.3"), (50200, "0.5")] {
+            let sell = create_order_with_account(AccountId::new(), Side::SELL, price, qty);
+            engine.submit_order(sell, 1).unwrap();
+        }
+
+        let fok = tif_order(AccountId::new(), Side::BUY, 50200, "1.0", TimeInForce::FOK);
+        match engine.submit_order(fok, 2).unwrap() {
+            SubmitResult::Filled { trades } => assert_eq!(trades.len(), 3),
+            _ => panic!("Expected Filled result"),
+        }
+        let (_, asks) = book_levels(&engine);
+        assert_eq!(asks, vec![(Price::from_u64(50200), Quantity::from_str("0.1").unwrap())]);
+    }
+
+    #[test]
+    fn test_fok_rejected_leaves_book_unchanged() {
+        let mut engine = MatchingEngine::new(1000);
+        for (price, qty) in [(50000, "0.3"), (50100, "0.3"), (50300, "5.0")] {
+            let sell = create_order_with_account(AccountId::new(), Side::SELL, price, qty);
+            engine.submit_order(sell, 1).unwrap();
+        }
+        let before = book_levels(&engine);
+        let sequence = engine.next_sequence();
+
+        // Enough depth exists, but not within the limit price
+        let fok = tif_order(AccountId::new(), Side::BUY, 50200, "1.0", TimeInForce::FOK);
+        match engine.submit_order(fok, 2).unwrap() {
+            SubmitResult::Rejected(event) => {
+                assert_eq!(event.reason, RejectReason::FillOrKillUnfillable);
+            }
+            _ => panic!("Expected Rejected result"),
+        }
+        assert_eq!(book_levels(&engine), before);
+        assert_eq!(engine.next_sequence(), sequence);
+    }
+
+    #[test]
+    fn test_fok_rejected_when_only_liquidity_is_own() {
+        let mut engine = MatchingEngine::new(1000);
+        let account = AccountId::new();
+        let own = create_order_with_account(account, Side::SELL, 50000, "1.0");
+        engine.submit_order(own, 1).unwrap();
+        let behind = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        engine.submit_order(behind, 2).unwrap();
+        let before = book_levels(&engine);
+
+        // The own order sits first in time priority, so nothing can fill
+        let fok = tif_order(account, Side::BUY, 50000, "0.5", TimeInForce::FOK);
+        assert!(matches!(engine.submit_order(fok, 3).unwrap(), SubmitResult::Rejected(_)));
+        assert_eq!(book_levels(&engine), before);
+    }
+
+    fn first_maker(engine: &mut MatchingEngine, side: Side, price: u64) -> OrderId {
+        let taker = create_order_with_account(AccountId::new(), side, price, "0.1");
+        match engine.submit_order(taker, 99).unwrap() {
+            SubmitResult::Filled { trades } => trades[0].maker_order_id,
+            _ => panic!("Expected Filled result"),
+        }
+    }
+
+    #[test]
+    fn test_amend_size_down_keeps_priority() {
+        let mut engine = MatchingEngine::new(1000);
+        let first = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let first_id = first.order_id;
+        engine.submit_order(first, 1).unwrap();
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0"), 2).unwrap();
+
+        let result = engine
+            .amend_order(&first_id, Price::from_u64(50000), Quantity::from_str("0.5").unwrap(), 3)
+            .unwrap();
+        assert!(result.event.priority_kept);
+        assert_eq!(result.event.old_quantity, Quantity::from_str("1.0").unwrap());
+        assert_eq!(result.event.new_quantity, Quantity::from_str("0.5").unwrap());
+        assert_eq!(first_maker(&mut engine, Side::BUY, 50000), first_id);
+    }
+
+    #[test]
+    fn test_amend_size_up_moves_to_back() {
+        let mut engine = MatchingEngine::new(1000);
+        let first = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let first_id = first.order_id;
+        let second = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let second_id = second.order_id;
+        engine.submit_order(first, 1).unwrap();
+        engine.submit_order(second, 2).unwrap();
+
+        let result = engine
+            .amend_order(&first_id, Price::from_u64(50000), Quantity::from_str("2.0").unwrap(), 3)
+            .unwrap();
+        assert!(!result.event.priority_kept);
+        assert_eq!(first_maker(&mut engine, Side::BUY, 50000), second_id);
+
+        let (_, asks) = book_levels(&engine);
+        assert_eq!(asks, vec![(Price::from_u64(50000), Quantity::from_str("2.9").unwrap())]);
+    }
+
+    #[test]
+    fn test_amend_to_crossing_price_matches() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.4"), 1).unwrap();
+        let ask = create_order_with_account(AccountId::new(), Side::SELL, 50100, "1.0");
+        let ask_id = ask.order_id;
+        engine.submit_order(ask, 2).unwrap();
+
+        let result = engine
+            .amend_order(&ask_id, Price::from_u64(49900), Quantity::from_str("1.0").unwrap(), 3)
+            .unwrap();
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].price, Price::from_u64(50000));
+        assert_eq!(result.trades[0].taker_order_id, ask_id);
+        assert_eq!(result.event.resting_quantity, Quantity::from_str("0.6").unwrap());
+
+        let (bids, asks) = book_levels(&engine);
+        assert!(bids.is_empty());
+        assert_eq!(asks, vec![(Price::from_u64(49900), Quantity::from_str("0.6").unwrap())]);
+    }
+
+    #[test]
+    fn test_amend_after_unseen_partial_fill() {
+        let mut engine = MatchingEngine::new(1000);
+        let ask = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let ask_id = ask.order_id;
+        engine.submit_order(ask, 1).unwrap();
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.4"), 2).unwrap();
+
+        // Client still sees 1.0 and sizes down to 0.8 total: 0.4 remains
+        let result = engine
+            .amend_order(&ask_id, Price::from_u64(50000), Quantity::from_str("0.8").unwrap(), 3)
+            .unwrap();
+        assert!(result.event.priority_kept);
+        assert_eq!(result.event.filled_quantity, Quantity::from_str("0.4").unwrap());
+        assert_eq!(result.event.resting_quantity, Quantity::from_str("0.4").unwrap());
+
+        // Sizing below what already filled takes the order off the book
+        let result = engine
+            .amend_order(&ask_id, Price::from_u64(50000), Quantity::from_str("0.3").unwrap(), 4)
+            .unwrap();
+        assert!(result.event.resting_quantity.is_zero());
+        assert!(book_levels(&engine).1.is_empty());
+        assert!(matches!(
+            engine.amend_order(&ask_id, Price::from_u64(50000), Quantity::from_str("1.0").unwrap(), 5),
+            Err(EngineError::OrderNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_amend_replays_from_event() {
+        let mut engine = MatchingEngine::new(1000);
+        let mut replica = MatchingEngine::new(1000);
+        let mut events = Vec::new();
+        for (price, qty) in [(50000, "1.0"), (50000, "0.5"), (50200, "2.0")] {
+            let order = create_order_with_account(AccountId::new(), Side::SELL, price, qty);
+            events.push(BookEvent::OrderAccepted {
+                order_id: order.order_id,
+                account_id: order.account_id,
+                symbol: "BTC/USDT".to_string(),
+                side: Side::SELL,
+                price: order.price,
+                quantity: order.quantity,
+                accepted_at: order.created_at,
+            });
+            engine.submit_order(order, 1).unwrap();
+        }
+        let ids: Vec<OrderId> = events.iter().map(|e| match e {
+            BookEvent::OrderAccepted { order_id, .. } => *order_id,
+            _ => unreachable!(),
+        }).collect();
+
+        let resize = engine.amend_order(&ids[0], Price::from_u64(50000), Quantity::from_str("0.7").unwrap(), 2).unwrap();
+        let requeue = engine.amend_order(&ids[2], Price::from_u64(50100), Quantity::from_str("2.0").unwrap(), 3).unwrap();
+        events.push(BookEvent::OrderAmended(resize.event));
+        events.push(BookEvent::OrderAmended(requeue.event));
+
+        for event in &events {
+            replica.apply_event(event).unwrap();
+        }
+        assert_eq!(book_levels(&replica), book_levels(&engine));
+        assert_eq!(first_maker(&mut replica, Side::BUY, 50000), ids[0]);
+    }
+
+    fn stop_market(side: Side, stop_price: u64, qty: &str) -> Order {
+        Order::stop_market(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            side,
+            Price::from_u64(stop_price),
+            Quantity::from_str(qty).unwrap(),
+            1708123456789000000,
+        )
+    }
+
+    fn rest(engine: &mut MatchingEngine, side: Side, price: u64, qty: &str) {
+        let order = create_order_with_account(AccountId::new(), side, price, qty);
+        assert!(matches!(engine.submit_order(order, 1).unwrap(), SubmitResult::Resting));
+    }
+
+    #[test]
+    fn test_stop_held_off_book_until_touched() {
+        let mut engine = MatchingEngine::new(1000);
+        rest(&mut engine, Side::SELL, 50000, "2.0");
+        let stop = stop_market(Side::BUY, 50100, "0.5");
+        let stop_id = stop.order_id;
+        assert!(matches!(engine.submit_order(stop, 2).unwrap(), SubmitResult::StopAccepted(_)));
+        assert_eq!(engine.held_stops("BTC/USDT"), 1);
+
+        // A trade below the stop price leaves it held
+        rest(&mut engine, Side::BUY, 49000, "0.1");
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::SELL, 49000, "0.1"), 3).unwrap();
+        assert!(engine.drain_triggered().is_empty());
+
+        assert!(engine.cancel_order("BTC/USDT", &stop_id, Price::from_u64(50100), Side::BUY));
+        assert_eq!(engine.held_stops("BTC/USDT"), 0);
+    }
+
+    #[test]
+    fn test_multiple_stops_trigger_in_acceptance_order() {
+        let mut engine = MatchingEngine::new(1000);
+        rest(&mut engine, Side::SELL, 50000, "2.0");
+        let first = stop_market(Side::BUY, 50000, "0.2");
+        let second = stop_market(Side::BUY, 49900, "0.3");
+        let ids = [first.order_id, second.order_id];
+        engine.submit_order(first, 2).unwrap();
+        engine.submit_order(second, 3).unwrap();
+
+        let taker = create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.1");
+        engine.submit_order(taker, 4).unwrap();
+
+        let triggered = engine.drain_triggered();
+        assert_eq!(triggered.iter().map(|t| t.event.order_id).collect::<Vec<_>>(), ids);
+        assert!(triggered.iter().all(|t| matches!(t.result, SubmitResult::Filled { .. })));
+        let (_, asks) = book_levels(&engine);
+        assert_eq!(asks, vec![(Price::from_u64(50000), Quantity::from_str("1.4").unwrap())]);
+    }
+
+    #[test]
+    fn test_stop_triggers_cascade() {
+        let mut engine = MatchingEngine::new(1000);
+        rest(&mut engine, Side::SELL, 50000, "0.5");
+        rest(&mut engine, Side::SELL, 50100, "0.5");
+        rest(&mut engine, Side::SELL, 50200, "1.0");
+        let first = stop_market(Side::BUY, 50000, "0.5");
+        let second = stop_market(Side::BUY, 50100, "0.5");
+        let ids = [first.order_id, second.order_id];
+        engine.submit_order(first, 2).unwrap();
+        engine.submit_order(second, 3).unwrap();
+
+        // Only the first stop is touched by this trade; its fill at 50100
+        // touches the second
+        let taker = create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.1");
+        engine.submit_order(taker, 4).unwrap();
+
+        let triggered = engine.drain_triggered();
+        assert_eq!(triggered.len(), 2);
+        assert_eq!(triggered[0].event.order_id, ids[0]);
+        assert_eq!(triggered[0].event.trade_price, Price::from_u64(50000));
+        assert_eq!(triggered[1].event.order_id, ids[1]);
+        assert_eq!(triggered[1].event.trade_price, Price::from_u64(50100));
+        assert_eq!(triggered[1].result.trades().last().unwrap().price, Price::from_u64(50200));
+        assert_eq!(engine.held_stops("BTC/USDT"), 0);
+    }
+
+    #[test]
+    fn test_stops_checked_at_each_fill_price() {
+        let mut engine = MatchingEngine::new(1000);
+        rest(&mut engine, Side::BUY, 49000, "1.0");
+        rest(&mut engine, Side::SELL, 50000, "0.5");
+        rest(&mut engine, Side::SELL, 50100, "0.5");
+        // The sweep ends at 50100, but its first fill at 50000 touches this
+        let sell_stop = stop_market(Side::SELL, 50050, "0.2");
+        let sell_id = sell_stop.order_id;
+        engine.submit_order(sell_stop, 2).unwrap();
+        let buy_stop = stop_market(Side::BUY, 50100, "0.2");
+        let buy_id = buy_stop.order_id;
+        engine.submit_order(buy_stop, 3).unwrap();
+
+        let taker = create_order_with_account(AccountId::new(), Side::BUY, 50100, "0.8");
+        engine.submit_order(taker, 4).unwrap();
+
+        let triggered = engine.drain_triggered();
+        assert_eq!(triggered.len(), 2);
+        assert_eq!(triggered[0].event.order_id, sell_id);
+        assert_eq!(triggered[0].event.trade_price, Price::from_u64(50000));
+        assert_eq!(triggered[1].event.order_id, buy_id);
+        assert_eq!(triggered[1].event.trade_price, Price::from_u64(50100));
+    }
+
+    #[test]
+    fn test_stop_events_replay() {
+        let mut engine = MatchingEngine::new(1000);
+        let mut replica = MatchingEngine::new(1000);
+        let stop = stop_market(Side::SELL, 49000, "1.0");
+        let stop_id = stop.order_id;
+        let accepted = match engine.submit_order(stop, 1).unwrap() {
+            SubmitResult::StopAccepted(event) => event,
+            _ => panic!("Expected StopAccepted result"),
+        };
+
+        let event = BookEvent::StopAccepted(accepted);
+        assert_eq!(event.event_type(), "StopAccepted");
+        replica.apply_event(&event).unwrap();
+        assert_eq!(replica.held_stops("BTC/USDT"), 1);
+
+        let triggered = BookEvent::StopTriggered(StopTriggeredEvent {
+            order_id: stop_id,
+            account_id: AccountId::new(),
+            symbol: "BTC/USDT".to_string(),
+            side: Side::SELL,
+            stop_price: Price::from_u64(49000),
+            trade_price: Price::from_u64(48900),
+            trade_sequence: 1000,
+            triggered_at: 2,
+        });
+        replica.apply_event(&triggered).unwrap();
+        assert_eq!(replica.held_stops("BTC/USDT"), 0);
+        assert!(replica.apply_event(&triggered).is_err());
+    }
+
+    fn resting_count(engine: &MatchingEngine) -> usize {
+        engine.books.values().map(|book| {
+            book.bids.levels().chain(book.asks.levels()).map(|(_, level)| level.order_count()).sum::<usize>()
+        }).sum()
+    }
+
+    #[test]
+    fn test_cancel_batch_in_sequence_order() {
+        let mut engine = MatchingEngine::new(1000);
+        let account = AccountId::new();
+        let mut ids = Vec::new();
+        for price in [49000, 49500, 49900] {
+            let order = create_order_with_account(account, Side::BUY, price, "1.0");
+            ids.push(order.order_id);
+            engine.submit_order(order, 1).unwrap();
+        }
+
+        // Reversed input, a duplicate and an unknown id
+        let request = vec![ids[2], OrderId::new(), ids[0], ids[2]];
+        let result = engine.cancel_batch(&request);
+        assert_eq!(result.canceled, vec![ids[0], ids[2]]);
+        assert!(matches!(
+            &result.events[1],
+            BookEvent::OrderCanceled { order_id, price, .. } if *order_id == ids[2] && *price == Price::from_u64(49900)
+        ));
+        assert_eq!(engine.index.account_orders(&account), vec![ids[1]]);
+    }
+
+    #[test]
+    fn test_cancel_all_skips_filled_orders() {
+        let mut engine = MatchingEngine::new(1000);
+        let account = AccountId::new();
+        let filled = create_order_with_account(account, Side::SELL, 50000, "0.5");
+        let partial = create_order_with_account(account, Side::SELL, 50100, "1.0");
+        let partial_id = partial.order_id;
+        engine.submit_order(filled, 1).unwrap();
+        engine.submit_order(partial, 2).unwrap();
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 50100, "0.8"), 3).unwrap();
+
+        let result = engine.cancel_all(&account);
+        assert_eq!(result.canceled, vec![partial_id]);
+        assert!(matches!(
+            &result.events[0],
+            BookEvent::OrderCanceled { remaining_quantity, .. } if *remaining_quantity == Quantity::from_str("0.7").unwrap()
+        ));
+        assert!(engine.index.is_empty());
+        assert_eq!(resting_count(&engine), 0);
+    }
+
+    #[test]
+    fn test_cancel_all_10k_orders_keeps_index_consistent() {
+        let mut engine = MatchingEngine::new(1000);
+        let maker = AccountId::new();
+        let other = AccountId::new();
+        let mut expected = Vec::new();
+        for i in 0..10_000u64 {
+            let (side, price) = if i % 2 == 0 { (Side::BUY, 40000 + i % 500) } else { (Side::SELL, 60000 + i % 500) };
+            let order = create_order_with_account(maker, side, price, "0.01");
+            expected.push(order.order_id);
+            engine.submit_order(order, i as i64).unwrap();
+            if i % 10 == 0 {
+                engine.submit_order(create_order_with_account(other, side, price, "0.02"), i as i64).unwrap();
+            }
+        }
+        assert_eq!(engine.index.len(), 11_000);
+        assert_eq!(resting_count(&engine), 11_000);
+
+        let result = engine.cancel_all(&maker);
+        assert_eq!(result.canceled, expected);
+        assert_eq!(result.events.len(), 10_000);
+        assert_eq!(engine.index.len(), 1_000);
+        assert_eq!(resting_count(&engine), 1_000);
+        assert!(engine.index.account_orders(&maker).is_empty());
+        assert_eq!(engine.index.account_orders(&other).len(), 1_000);
+        engine.check_uncrossed("BTC/USDT").unwrap();
+    }
+
+    #[test]
+    fn test_orders_for_account_reports_live_remaining() {
+        let mut engine = MatchingEngine::new(1000);
+        let account = AccountId::new();
+        let mut ids = Vec::new();
+        for (i, price) in [50000, 50100].into_iter().enumerate() {
+            let mut order = create_order_with_account(account, Side::SELL, price, "1.0");
+            order.created_at = 10 + i as i64;
+            ids.push(order.order_id);
+            engine.submit_order(order, 1).unwrap();
+        }
+        rest(&mut engine, Side::SELL, 50200, "3.0");
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.4"), 2).unwrap();
+
+        let open = engine.orders_for_account(&account);
+        assert_eq!(open.len(), 2);
+        assert_eq!(open[0].order_id, ids[0]);
+        assert_eq!(open[0].original_quantity, Quantity::from_str("1.0").unwrap());
+        assert_eq!(open[0].remaining_quantity, Quantity::from_str("0.6").unwrap());
+        assert_eq!(open[0].timestamp, 10);
+        assert_eq!(open[1].price, Price::from_u64(50100));
+        assert_eq!(open[1].remaining_quantity, open[1].original_quantity);
+
+        // Filled and canceled orders drop out
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 50000, "0.6"), 3).unwrap();
+        assert!(engine.cancel_order("BTC/USDT", &ids[1], Price::from_u64(50100), Side::SELL));
+        assert!(engine.orders_for_account(&account).is_empty());
+        assert!(engine.orders_for_account(&AccountId::new()).is_empty());
+    }
+
+    #[test]
+    fn test_orders_for_account_after_amend_and_replay() {
+        let mut engine = MatchingEngine::new(1000);
+        let mut replica = MatchingEngine::new(1000);
+        let account = AccountId::new();
+        let order = create_order_with_account(account, Side::BUY, 49000, "2.0");
+        let order_id = order.order_id;
+        replica.apply_event(&BookEvent::OrderAccepted {
+            order_id,
+            account_id: account,
+            symbol: "BTC/USDT".to_string(),
+            side: Side::BUY,
+            price: order.price,
+            quantity: order.quantity,
+            accepted_at: order.created_at,
+        }).unwrap();
+        engine.submit_order(order, 1).unwrap();
+
+        let amend = engine.amend_order(&order_id, Price::from_u64(49500), Quantity::from_str("1.5").unwrap(), 5).unwrap();
+        replica.apply_event(&BookEvent::OrderAmended(amend.event)).unwrap();
+
+        let open = engine.orders_for_account(&account);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].price, Price::from_u64(49500));
+        assert_eq!(open[0].original_quantity, Quantity::from_str("1.5").unwrap());
+        assert_eq!(open[0].timestamp, 1708123456789000000);
+        assert_eq!(replica.orders_for_account(&account), open);
+    }
+
+    fn increments(tick: &str, lot: &str, min_notional: &str) -> MarketConfig {
+        MarketConfig {
+            tick_size: tick.parse().unwrap(),
+            lot_size: lot.parse().unwrap(),
+            min_notional: min_notional.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_orders_off_increments_refused() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.set_market_config("BTC/USDT", increments("0.5", "0.001", "10"));
+
+        let mut off_tick = create_order_with_account(AccountId::new(), Side::BUY, 100, "1.0");
+        off_tick.price = Price::from_str("100.25").unwrap();
+        assert!(matches!(
+            engine.submit_order(off_tick, 1),
+            Err(EngineError::MarketConfigViolated { violation: MarketConfigViolation::PriceNotOnTick { .. }, .. })
+        ));
+        assert!(matches!(
+            engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 100, "1.0005"), 1),
+            Err(EngineError::MarketConfigViolated { violation: MarketConfigViolation::QuantityNotOnLot { .. }, .. })
+        ));
+        assert!(matches!(
+            engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, 100, "0.05"), 1),
+            Err(EngineError::MarketConfigViolated { violation: MarketConfigViolation::BelowMinNotional { .. }, .. })
+        ));
+        assert!(engine.get_order_book("BTC/USDT", 10).is_none());
+
+        let mut on_tick = create_order_with_account(AccountId::new(), Side::BUY, 100, "0.101");
+        on_tick.price = Price::from_str("100.5").unwrap();
+        assert!(matches!(engine.submit_order(on_tick, 1).unwrap(), SubmitResult::Resting));
+
+        // Other markets are unchecked
+        let mut other = create_order_with_account(AccountId::new(), Side::BUY, 100, "0.0001");
+        other.symbol = MarketId::new("ETH/USDT");
+        assert!(matches!(engine.submit_order(other, 1).unwrap(), SubmitResult::Resting));
+    }
+
+    #[test]
+    fn test_amend_off_increments_refused() {
+        let mut engine = MatchingEngine::new(1000);
+        engine.set_market_config("BTC/USDT", increments("0.5", "0.1", "0"));
+        let order = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let order_id = order.order_id;
+        engine.submit_order(order, 1).unwrap();
+
+        assert!(matches!(
+            engine.amend_order(&order_id, Price::from_str("50000.25").unwrap(), Quantity::from_str("1.0").unwrap(), 2),
+            Err(EngineError::MarketConfigViolated { violation: MarketConfigViolation::PriceNotOnTick { .. }, .. })
+        ));
+        assert!(matches!(
+            engine.amend_order(&order_id, Price::from_u64(50000), Quantity::from_str("0.55").unwrap(), 2),
+            Err(EngineError::MarketConfigViolated { violation: MarketConfigViolation::QuantityNotOnLot { .. }, .. })
+        ));
+        assert!(engine.amend_order(&order_id, Price::from_str("50000.5").unwrap(), Quantity::from_str("0.5").unwrap(), 2).is_ok());
+    }
+
+    fn banded_engine(pct: u64, rest_at_limit: bool) -> MatchingEngine {
+        let mut engine = MatchingEngine::new(1000);
+        engine.set_price_band("BTC/USDT", PriceBand { max_deviation_pct: Decimal::from(pct), rest_at_limit });
+        engine
+    }
+
+    /// Print a trade at `price` so it becomes the band reference
+    fn trade_at(engine: &mut MatchingEngine, price: u64) {
+        rest(engine, Side::SELL, price, "0.1");
+        engine.submit_order(create_order_with_account(AccountId::new(), Side::BUY, price, "0.1"), 1).unwrap();
+        assert_eq!(engine.last_trade_price("BTC/USDT"), Some(Price::from_u64(price)));
+    }
+
+    #[test]
+    fn test_band_stops_sweep_and_moves_with_fills() {
+        let mut engine = banded_engine(5, false);
+        trade_at(&mut engine, 100);
+        for price in [100, 104, 108, 115] {
+            rest(&mut engine, Side::SELL, price, "1.0");
+        }
+
+        // 108 is beyond 5% of the opening reference but within 5% of 104
+        let result = engine.submit_order(market_order(Side::BUY, "4.0"), 2).unwrap();
+        match result {
+            SubmitResult::PriceBandHit { trades, event } => {
+                let prices: Vec<Price> = trades.iter().map(|t| t.price).collect();
+                assert_eq!(prices, vec![Price::from_u64(100), Price::from_u64(104), Price::from_u64(108)]);
+                assert_eq!(event.reference_price, Price::from_u64(108));
+                assert_eq!(event.band_limit, Price::from_str("113.4").unwrap());
+                assert_eq!(event.unexecuted_quantity, Quantity::from_str("1.0").unwrap());
+                assert!(!event.rested);
+            }
+            _ => panic!("Expected PriceBandHit result"),
+        }
+        assert_eq!(book_levels(&engine).1, vec![(Price::from_u64(115), Quantity::from_str("1.0").unwrap())]);
+        assert_eq!(engine.last_trade_price("BTC/USDT"), Some(Price::from_u64(108)));
+    }
+
+    #[test]
+    fn test_band_rests_limit_remainder_at_edge() {
+        let mut engine = banded_engine(5, true);
+        engine.set_market_config("BTC/USDT", increments("0.5", "0.1", "0"));
+        trade_at(&mut engine, 100);
+        rest(&mut engine, Side::SELL, 104, "1.0");
+        rest(&mut engine, Side::SELL, 112, "1.0");
+
+        let account = AccountId::new();
+        let order = create_order_with_account(account, Side::BUY, 120, "2.0");
+        let result = engine.submit_order(order.clone(), 2).unwrap();
+        let event = match result {
+            SubmitResult::PriceBandHit { trades, event } => {
+                assert_eq!(trades.len(), 1);
+                event
+            }
+            _ => panic!("Expected PriceBandHit result"),
+        };
+        // 104 × 1.05 = 109.2, moved down onto the 0.5 tick
+        assert!(event.rested);
+        assert_eq!(event.band_limit, Price::from_u64(109));
+        assert_eq!(event.unexecuted_quantity, Quantity::from_str("1.0").unwrap());
+        let open = engine.orders_for_account(&account);
+        assert_eq!(open[0].price, Price::from_u64(109));
+        assert_eq!(open[0].original_quantity, Quantity::from_str("2.0").unwrap());
+        engine.check_uncrossed("BTC/USDT").unwrap();
+
+        // Replaying the event rests the same remainder
+        let mut replica = MatchingEngine::new(1000);
+        replica.apply_event(&BookEvent::PriceBandHit(event)).unwrap();
+        assert_eq!(book_levels(&replica).0, book_levels(&engine).0);
+        assert_eq!(replica.orders_for_account(&account), open);
+    }
+
+    #[test]
+    fn test_band_without_reference_anchors_on_first_fill() {
+        let mut engine = banded_engine(10, false);
+        for price in [100, 105, 200] {
+            rest(&mut engine, Side::SELL, price, "1.0");
+        }
+        assert_eq!(engine.last_trade_price("BTC/USDT"), None);
+
+        // No last trade and no bids for a mid: the first fill sets the reference
+        let result = engine.submit_order(market_order(Side::BUY, "3.0"), 2).unwrap();
+        match result {
+            SubmitResult::PriceBandHit { trades, event } => {
+                assert_eq!(trades.len(), 2);
+                assert_eq!(event.reference_price, Price::from_u64(105));
+                assert_eq!(event.unexecuted_quantity, Quantity::from_str("1.0").unwrap());
+            }
+            _ => panic!("Expected PriceBandHit result"),
+        }
+    }
+
+    #[test]
+    fn test_band_limits_fok_and_mid_reference() {
+        let mut engine = banded_engine(5, false);
+        rest(&mut engine, Side::BUY, 98, "1.0");
+        rest(&mut engine, Side::SELL, 102, "1.0");
+        rest(&mut engine, Side::SELL, 110, "1.0");
+
+        // Mid 100 bounds buys at 105, so only 1.0 is fillable
+        let fok = tif_order(AccountId::new(), Side::BUY, 110, "2.0", TimeInForce::FOK);
+        assert!(matches!(
+            engine.submit_order(fok, 2).unwrap(),
+            SubmitResult::Rejected(OrderRejectedEvent { reason: RejectReason::FillOrKillUnfillable, .. })
+        ));
+
+        // An order that does not reach the band edge is unaffected
+        let inside = create_order_with_account(AccountId::new(), Side::BUY, 103, "2.0");
+        assert!(matches!(engine.submit_order(inside, 3).unwrap(), SubmitResult::PartiallyFilled { .. }));
+    }
+
+    fn auction_engine() -> MatchingEngine {
+        let mut engine = MatchingEngine::new(1000);
+        engine.enter_auction("BTC/USDT", "ops", 0).unwrap();
+        engine
+    }
+
+    fn accepted(order: &Order) -> BookEvent {
+        BookEvent::OrderAccepted {
+            order_id: order.order_id,
+            account_id: order.account_id,
+            symbol: order.symbol.as_str().to_string(),
+            side: order.side,
+            price: order.price,
+            quantity: order.quantity,
+            accepted_at: order.created_at,
+        }
+    }
+
+    #[test]
+    fn test_uncross_without_crossing_volume() {
+        let mut engine = auction_engine();
+        for (side, price) in [(Side::BUY, 49900), (Side::SELL, 50000)] {
+            let order = create_order_with_account(AccountId::new(), side, price, "1.0");
+            assert!(matches!(engine.submit_auction_order(order, 1).unwrap(), SubmitResult::Resting));
+        }
+        let before = book_levels(&engine);
+
+        assert!(engine.uncross("BTC/USDT", 2).unwrap().is_none());
+        assert_eq!(book_levels(&engine), before);
+        assert_eq!(engine.next_sequence(), 1000);
+
+        // Auction-only entry points refuse continuous markets and non-resting orders
+        let ioc = tif_order(AccountId::new(), Side::BUY, 50000, "1.0", TimeInForce::IOC);
+        assert!(matches!(engine.submit_auction_order(ioc, 3), Err(EngineError::InvalidOrder(_))));
+        engine.set_market_status("BTC/USDT", MarketStatus::Trading, "ops", 4).unwrap();
+        assert!(matches!(engine.uncross("BTC/USDT", 5), Err(EngineError::InvalidOrder(_))));
+    }
+
+    #[test]
+    fn test_uncross_tie_between_candidate_prices() {
+        // 1.0 executes at both 50000 and 50100 with no imbalance: the lower price wins
+        let mut engine = auction_engine();
+        let bid = create_order_with_account(AccountId::new(), Side::BUY, 50100, "1.0");
+        let ask = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        engine.submit_auction_order(bid, 1).unwrap();
+        engine.submit_auction_order(ask, 2).unwrap();
+
+        let uncross = engine.uncross("BTC/USDT", 3).unwrap().unwrap();
+        assert_eq!(uncross.price, Price::from_u64(50000));
+        assert_eq!(uncross.trades.len(), 1);
+        assert_eq!(uncross.trades[0].price, Price::from_u64(50000));
+        assert_eq!(engine.last_trade_price("BTC/USDT"), Some(Price::from_u64(50000)));
+        assert_eq!(resting_depth(&engine), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_uncross_allocates_in_time_priority() {
+        let mut engine = auction_engine();
+        let first = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let second = create_order_with_account(AccountId::new(), Side::SELL, 50000, "1.0");
+        let bid = create_order_with_account(AccountId::new(), Side::BUY, 50100, "1.5");
+        let ids = (first.order_id, second.order_id, bid.order_id);
+        for (ts, order) in [first, second, bid].into_iter().enumerate() {
+            engine.submit_auction_order(order, ts as i64).unwrap();
+        }
+
+        let uncross = engine.uncross("BTC/USDT", 3).unwrap().unwrap();
+        let fills: Vec<(OrderId, Quantity)> = uncross.trades.iter().map(|t| (t.maker_order_id, t.quantity)).collect();
+        assert_eq!(fills, vec![
+            (ids.0, Quantity::from_str("1.0").unwrap()),
+            (ids.1, Quantity::from_str("0.5").unwrap()),
+        ]);
+        // The later bid is the taker of both trades
+        assert!(uncross.trades.iter().all(|t| t.taker_order_id == ids.2 && t.side == Side::BUY));
+        assert_eq!(uncross.event.volume, Quantity::from_str("1.5").unwrap());
+
+        let (bids, asks) = book_levels(&engine);
+        assert!(bids.is_empty());
+        assert_eq!(asks, vec![(Price::from_u64(50000), Quantity::from_str("0.5").unwrap())]);
+    }
+
+    #[test]
+    fn test_uncross_replays_from_event() {
+        let mut engine = auction_engine();
+        let mut replica = auction_engine();
+        let mut events = Vec::new();
+        for (side, price, qty) in [
+            (Side::BUY, 50200, "1.0"),
+            (Side::BUY, 50100, "2.0"),
+            (Side::SELL, 49900, "1.5"),
+            (Side::SELL, 50100, "2.0"),
+        ] {
+            let order = create_order_with_account(AccountId::new(), side, price, qty);
+            events.push(accepted(&order));
+            engine.submit_auction_order(order, 1).unwrap();
+        }
+
+        let uncross = engine.uncross("BTC/USDT", 2).unwrap().unwrap();
+        assert_eq!(uncross.price, Price::from_u64(50100));
+        events.push(BookEvent::AuctionUncrossed(uncross.event));
+
+        for event in &events {
+            replica.apply_event(event).unwrap();
+        }
+        assert_eq!(book_levels(&replica), book_levels(&engine));
+        assert_eq!(replica.next_sequence(), engine.next_sequence());
+        assert_eq!(replica.last_trade_price("BTC/USDT"), Some(Price::from_u64(50100)));
+    }
+
+    #[test]
+    fn test_trade_events_flag_aggressor() {
+        let mut engine = MatchingEngine::new(1000);
+        rest(&mut engine, Side::SELL, 50000, "1.0");
+        rest(&mut engine, Side::SELL, 50100, "1.0");
+        rest(&mut engine, Side::BUY, 49900, "1.0");
+
+        // Buy-initiated sweep across two levels
+        let buy = create_order_with_account(AccountId::new(), Side::BUY, 50100, "2.0");
+        let events: Vec<TradeExecutedEvent> = engine.submit_order(buy, 2).unwrap()
+            .trades().iter().map(TradeExecutedEvent::from_trade).collect();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.aggressor_side == Side::BUY && !e.maker_is_buyer));
+
+        // Sell-initiated
+        let sell = create_order_with_account(AccountId::new(), Side::SELL, 49900, "1.0");
+        let events: Vec<TradeExecutedEvent> = engine.submit_order(sell, 3).unwrap()
+            .trades().iter().map(TradeExecutedEvent::from_trade).collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].aggressor_side, Side::SELL);
+        assert!(events[0].maker_is_buyer);
+    }
 }
@@ -4,8 +4,10 @@
 
 use serde::{Deserialize, Serialize};
 use types::ids::{AccountId, OrderId, TradeId};
+use types::market::MarketStatus;
 use types::numeric::{Price, Quantity};
-use types::order::Side;
+use types::order::{Order, RejectReason, Side};
+use types::trade::Trade;
 
 /// Trade executed event per spec §8.3.2
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,10 +21,36 @@ pub struct TradeExecutedEvent {
     pub taker_account_id: AccountId,
     pub price: Price,
     pub quantity: Quantity,
+    /// Taker side
     pub side: Side,
+    /// Side of the order that took liquidity (the taker)
+    pub aggressor_side: Side,
+    /// Whether the resting order that provided liquidity was the buy
+    pub maker_is_buyer: bool,
     pub executed_at: i64,
 }
 
+impl TradeExecutedEvent {
+    /// Build the event for an executed trade
+    pub fn from_trade(trade: &Trade) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            sequence: trade.sequence,
+            symbol: trade.symbol.as_str().to_string(),
+            maker_order_id: trade.maker_order_id,
+            taker_order_id: trade.taker_order_id,
+            maker_account_id: trade.maker_account_id,
+            taker_account_id: trade.taker_account_id,
+            price: trade.price,
+            quantity: trade.quantity,
+            side: trade.side,
+            aggressor_side: trade.side,
+            maker_is_buyer: trade.side == Side::SELL,
+            executed_at: trade.executed_at,
+        }
+    }
+}
+
 /// Order partially filled event per spec §8.3.1
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderPartiallyFilledEvent {
@@ -42,6 +70,20 @@ pub struct OrderFilledEvent {
     pub total_fee: String,
 }
 
+/// Order rejected event per spec §8.3.1
+///
+/// Emitted without touching the book, e.g. a market order with no
+/// opposing liquidity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderRejectedEvent {
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub reason: RejectReason,
+    pub message: String,
+    pub rejected_at: i64,
+}
+
 /// Order canceled event per spec §8.3.1
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderCanceledEvent {
@@ -52,6 +94,89 @@ pub struct OrderCanceledEvent {
     pub unfilled_quantity: Quantity,
 }
 
+/// Order amended event
+///
+/// Quantities are remaining (unfilled) quantities. `resting_quantity` is
+/// what rests after any immediate match at the new price; zero means the
+/// order left the book.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderAmendedEvent {
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub side: Side,
+    pub old_price: Price,
+    pub new_price: Price,
+    pub old_quantity: Quantity,
+    pub new_quantity: Quantity,
+    /// Quantity filled before the amendment
+    pub filled_quantity: Quantity,
+    pub resting_quantity: Quantity,
+    /// True for a size-down at the same price, which keeps queue position
+    pub priority_kept: bool,
+    pub amended_at: i64,
+}
+
+/// Stop order accepted and held off-book until triggered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StopAcceptedEvent {
+    pub order: Order,
+    pub accepted_at: i64,
+}
+
+/// Stop order triggered by a trade
+///
+/// Journaled before the events of the submission it releases, so replay
+/// can tell a triggered stop from a new order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StopTriggeredEvent {
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub side: Side,
+    pub stop_price: Price,
+    /// Price of the trade that touched the stop
+    pub trade_price: Price,
+    pub trade_sequence: u64,
+    pub triggered_at: i64,
+}
+
+/// A submission was halted at its price band
+///
+/// When `rested` is set the unexecuted quantity rests at `band_limit`;
+/// otherwise it was canceled. Journaled after the submission's trades.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceBandHitEvent {
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub side: Side,
+    /// Reference price when the band stopped matching
+    pub reference_price: Price,
+    /// Furthest price the band allowed
+    pub band_limit: Price,
+    pub filled_quantity: Quantity,
+    pub unexecuted_quantity: Quantity,
+    pub rested: bool,
+    /// When the order was placed (Unix nanos)
+    pub placed_at: i64,
+    pub hit_at: i64,
+}
+
+/// A call auction was uncrossed at a single price
+///
+/// Carries the auction's trades, which reduce both the bid and the ask;
+/// they are not journaled again as `TradeExecuted`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionUncrossedEvent {
+    pub symbol: String,
+    pub price: Price,
+    /// Total quantity executed
+    pub volume: Quantity,
+    pub trades: Vec<TradeExecutedEvent>,
+    pub uncrossed_at: i64,
+}
+
 /// Who canceled the order
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -59,3 +184,84 @@ pub enum CancelSource {
     User,
     System,
 }
+
+/// Market status changed event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketStatusChangedEvent {
+    pub symbol: String,
+    pub old_status: MarketStatus,
+    pub new_status: MarketStatus,
+    /// Operator or subsystem that requested the change
+    pub actor: String,
+    pub changed_at: i64,
+}
+
+/// Book-changing events, journaled in order and replayed on warm start
+///
+/// Carries enough to locate each resting order (side and price), so replay
+/// applies the recorded outcome instead of re-running matching.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BookEvent {
+    /// Order came to rest on the book with its unfilled quantity
+    OrderAccepted {
+        order_id: OrderId,
+        account_id: AccountId,
+        symbol: String,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        /// When the order was placed (Unix nanos)
+        accepted_at: i64,
+    },
+    /// Trade executed against a resting maker order
+    TradeExecuted(TradeExecutedEvent),
+    /// Resting order removed with its unfilled quantity
+    OrderCanceled {
+        order_id: OrderId,
+        symbol: String,
+        side: Side,
+        price: Price,
+        remaining_quantity: Quantity,
+    },
+    /// Resting order amended; journaled before any trades it caused
+    OrderAmended(OrderAmendedEvent),
+    /// Stop order held off-book
+    StopAccepted(StopAcceptedEvent),
+    /// Held stop released for submission
+    StopTriggered(StopTriggeredEvent),
+    /// Submission halted at its price band, remainder rested or canceled
+    PriceBandHit(PriceBandHitEvent),
+    /// Call auction executed at its clearing price
+    AuctionUncrossed(AuctionUncrossedEvent),
+}
+
+impl BookEvent {
+    /// Event type label per spec §8 (used as the journal event type)
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            BookEvent::OrderAccepted { .. } => "OrderAccepted",
+            // Versioned when the liquidity flags were added; see `restore::decode_event`
+            BookEvent::TradeExecuted(_) => "TradeExecuted.v2",
+            BookEvent::OrderCanceled { .. } => "OrderCanceled",
+            BookEvent::OrderAmended(_) => "OrderAmended",
+            BookEvent::StopAccepted(_) => "StopAccepted",
+            BookEvent::StopTriggered(_) => "StopTriggered",
+            BookEvent::PriceBandHit(_) => "PriceBandHit",
+            BookEvent::AuctionUncrossed(_) => "AuctionUncrossed",
+        }
+    }
+
+    /// Symbol of the book the event applies to
+    pub fn symbol(&self) -> &str {
+        match self {
+            BookEvent::OrderAccepted { symbol, .. } => symbol,
+            BookEvent::TradeExecuted(trade) => &trade.symbol,
+            BookEvent::OrderCanceled { symbol, .. } => symbol,
+            BookEvent::OrderAmended(amend) => &amend.symbol,
+            BookEvent::StopAccepted(stop) => stop.order.symbol.as_str(),
+            BookEvent::StopTriggered(stop) => &stop.symbol,
+            BookEvent::PriceBandHit(hit) => &hit.symbol,
+            BookEvent::AuctionUncrossed(auction) => &auction.symbol,
+        }
+    }
+}
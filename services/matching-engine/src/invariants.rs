@@ -0,0 +1,252 @@
+//! Book invariants
+//!
+//! Structural checks over every book the engine holds:
+//! - best bid strictly below best ask (neither crossed nor locked) while
+//!   the market is matching; auction books rest crossed until the uncross
+//! - no empty price levels and no resting order with zero remaining
+//! - each level's total equals the sum of its orders
+//! - the order index locates every resting order, and nothing else
+//!
+//! `MatchingEngine::check_invariants` runs them on demand. Debug builds
+//! with the `invariant-checks` feature also run them after every
+//! mutation and panic on the first violation.
+
+use rust_decimal::Decimal;
+use thiserror::Error;
+use types::ids::OrderId;
+use types::numeric::{Price, Quantity};
+use types::order::Side;
+
+use crate::book::PriceLevel;
+use crate::engine::MatchingEngine;
+use crate::matching::crossing;
+
+/// A broken book invariant, with the level and orders involved
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// Best bid at or above best ask in a matching market
+    #[error("{symbol} crossed: best bid {best_bid} >= best ask {best_ask}")]
+    CrossedBook { symbol: String, best_bid: Price, best_ask: Price },
+
+    /// A price level with no orders was left on the book
+    #[error("{symbol} {side:?} level {price} has no orders")]
+    EmptyLevel { symbol: String, side: Side, price: Price },
+
+    /// A resting order with nothing left to fill
+    #[error("{symbol} {side:?} level {price}: order {order_id} rests with zero quantity")]
+    ZeroRemaining { symbol: String, side: Side, price: Price, order_id: OrderId },
+
+    /// A level's running total disagrees with its orders
+    #[error("{symbol} {side:?} level {price}: total {recorded} but orders {order_ids:?} sum to {computed}")]
+    LevelTotalMismatch {
+        symbol: String,
+        side: Side,
+        price: Price,
+        recorded: Quantity,
+        computed: Decimal,
+        order_ids: Vec<OrderId>,
+    },
+
+    /// A resting order the index does not locate at its level
+    #[error("order {order_id} rests at {symbol} {side:?} {price} but is indexed at {indexed:?}")]
+    IndexMismatch {
+        order_id: OrderId,
+        symbol: String,
+        side: Side,
+        price: Price,
+        /// Indexed (symbol, side, price), if indexed at all
+        indexed: Option<(String, Side, Price)>,
+    },
+
+    /// An index entry for an order that is not resting where it points
+    #[error("order {order_id} is indexed at {symbol} {side:?} {price} but not resting there")]
+    StaleIndexEntry { order_id: OrderId, symbol: String, side: Side, price: Price },
+}
+
+impl MatchingEngine {
+    /// Check every book invariant, returning the first violation
+    ///
+    /// Books are visited in symbol order, bids before asks, best price
+    /// first, so the same state always reports the same violation.
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let mut resting = 0;
+        for symbol in self.symbols() {
+            let book = &self.books[&symbol];
+            if self.market_status(&symbol).is_matching() {
+                if let (Some(best_bid), Some(best_ask)) = (book.bids.best_bid_price(), book.asks.best_ask_price()) {
+                    if crossing::can_match(best_bid, best_ask) {
+                        return Err(InvariantViolation::CrossedBook { symbol, best_bid, best_ask });
+                    }
+                }
+            }
+            for (price, level) in book.bids.levels() {
+                resting += self.check_level(&symbol, Side::BUY, price, level)?;
+            }
+            for (price, level) in book.asks.levels() {
+                resting += self.check_level(&symbol, Side::SELL, price, level)?;
+            }
+        }
+
+        if self.index.len() != resting {
+            let mut entries: Vec<_> = self.index.iter().collect();
+            entries.sort_by_key(|(_, location)| location.sequence);
+            for (order_id, location) in entries {
+                let found = self.books.get(&location.symbol).and_then(|book| match location.side {
+                    Side::BUY => book.bids.find_at(order_id, location.price),
+                    Side::SELL => book.asks.find_at(order_id, location.price),
+                });
+                if found.is_none() {
+                    return Err(InvariantViolation::StaleIndexEntry {
+                        order_id: *order_id,
+                        symbol: location.symbol.clone(),
+                        side: location.side,
+                        price: location.price,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check one level and its orders' index entries, returning the order count
+    fn check_level(&self, symbol: &str, side: Side, price: Price, level: &PriceLevel) -> Result<usize, InvariantViolation> {
+        if level.is_empty() {
+            return Err(InvariantViolation::EmptyLevel { symbol: symbol.to_string(), side, price });
+        }
+
+        let mut computed = Decimal::ZERO;
+        for (order_id, _, quantity) in level.queue() {
+            if quantity.is_zero() {
+                return Err(InvariantViolation::ZeroRemaining {
+                    symbol: symbol.to_string(),
+                    side,
+                    price,
+                    order_id,
+                });
+            }
+            computed += quantity.as_decimal();
+
+            let location = self.index.get(&order_id);
+            let indexed_here = location.is_some_and(|location| {
+                location.symbol == symbol && location.side == side && location.price == price
+            });
+            if !indexed_here {
+                return Err(InvariantViolation::IndexMismatch {
+                    order_id,
+                    symbol: symbol.to_string(),
+                    side,
+                    price,
+                    indexed: location.map(|location| (location.symbol.clone(), location.side, location.price)),
+                });
+            }
+        }
+
+        if computed != level.total_quantity().as_decimal() {
+            return Err(InvariantViolation::LevelTotalMismatch {
+                symbol: symbol.to_string(),
+                side,
+                price,
+                recorded: level.total_quantity(),
+                computed,
+                order_ids: level.queue().map(|(order_id, _, _)| order_id).collect(),
+            });
+        }
+        Ok(level.order_count())
+    }
+
+    /// Panic on a violation after a mutation (`invariant-checks` debug builds)
+    #[inline]
+    pub(crate) fn debug_check_invariants(&self) {
+        #[cfg(all(debug_assertions, feature = "invariant-checks"))]
+        if let Err(violation) = self.check_invariants() {
+            panic!("book invariant violated: {}", violation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::BookEntry;
+    use types::ids::{AccountId, MarketId};
+
+    fn entry(side: Side, price: u64, qty: &str) -> BookEntry {
+        BookEntry {
+            order_id: OrderId::new(),
+            account_id: AccountId::new(),
+            symbol: MarketId::new("BTC/USDT"),
+            side,
+            price: Price::from_u64(price),
+            remaining_quantity: Quantity::from_str(qty).unwrap(),
+            filled_quantity: Quantity::zero(),
+            placed_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_consistent_book_passes() {
+        let mut engine = MatchingEngine::new(1);
+        engine.restore_entry(&entry(Side::BUY, 49900, "1.0"));
+        engine.restore_entry(&entry(Side::BUY, 49900, "2.0"));
+        engine.restore_entry(&entry(Side::SELL, 50000, "1.5"));
+        assert_eq!(engine.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_crossed_and_locked_books_detected_outside_auction() {
+        let mut engine = MatchingEngine::new(1);
+        engine.restore_entry(&entry(Side::BUY, 50000, "1.0"));
+        engine.restore_entry(&entry(Side::SELL, 50000, "1.0"));
+        assert_eq!(engine.check_invariants(), Err(InvariantViolation::CrossedBook {
+            symbol: "BTC/USDT".to_string(),
+            best_bid: Price::from_u64(50000),
+            best_ask: Price::from_u64(50000),
+        }));
+
+        // An auction book rests crossed until the uncross
+        engine.enter_auction("BTC/USDT", "ops", 1).unwrap();
+        assert_eq!(engine.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_index_drift_detected() {
+        let mut engine = MatchingEngine::new(1);
+        let missing = entry(Side::BUY, 49900, "1.0");
+        engine.restore_entry(&missing);
+        engine.index.remove(&missing.order_id);
+        assert!(matches!(
+            engine.check_invariants(),
+            Err(InvariantViolation::IndexMismatch { order_id, indexed: None, .. }) if order_id == missing.order_id
+        ));
+
+        let mut engine = MatchingEngine::new(1);
+        let stale = entry(Side::SELL, 50100, "1.0");
+        engine.restore_entry(&stale);
+        engine.books.get_mut("BTC/USDT").unwrap().asks.remove(&stale.order_id, stale.price);
+        assert_eq!(engine.check_invariants(), Err(InvariantViolation::StaleIndexEntry {
+            order_id: stale.order_id,
+            symbol: "BTC/USDT".to_string(),
+            side: Side::SELL,
+            price: stale.price,
+        }));
+    }
+
+    #[test]
+    fn test_level_total_mismatch_names_orders() {
+        let mut engine = MatchingEngine::new(1);
+        let resting = entry(Side::BUY, 49900, "1.0");
+        engine.restore_entry(&resting);
+        engine.books.get_mut("BTC/USDT").unwrap().bids.corrupt_total(resting.price, Quantity::from_str("3.0").unwrap());
+
+        let violation = engine.check_invariants().unwrap_err();
+        assert_eq!(violation, InvariantViolation::LevelTotalMismatch {
+            symbol: "BTC/USDT".to_string(),
+            side: Side::BUY,
+            price: resting.price,
+            recorded: Quantity::from_str("3.0").unwrap(),
+            computed: Decimal::ONE,
+            order_ids: vec![resting.order_id],
+        });
+        assert!(violation.to_string().contains(&resting.order_id.to_string()));
+    }
+}
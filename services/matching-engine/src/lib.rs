@@ -17,5 +17,8 @@ pub mod book;
 pub mod matching;
 pub mod engine;
 pub mod events;
+pub mod invariants;
+pub mod restore;
 
 pub use engine::MatchingEngine;
+pub use restore::BookRestorer;
@@ -0,0 +1,102 @@
+//! Call auction clearing price
+//!
+//! An uncross executes every crossing order at one price. The price chosen
+//! is the one that executes the most volume; ties go to the price with the
+//! smallest imbalance between demand and supply there, then to the lowest
+//! price. Only prices at which orders rest are candidates.
+
+use rust_decimal::Decimal;
+use types::numeric::{Price, Quantity};
+
+/// Outcome of the clearing price search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clearing {
+    pub price: Price,
+    /// Volume executable at `price`
+    pub volume: Decimal,
+    /// Unmatched demand or supply at `price`
+    pub imbalance: Decimal,
+}
+
+/// Find the clearing price for aggregated bid and ask levels
+///
+/// Returns `None` if no bid is at or above any ask.
+pub fn clearing_price(bids: &[(Price, Quantity)], asks: &[(Price, Quantity)]) -> Option<Clearing> {
+    let mut candidates: Vec<Price> = bids.iter().chain(asks).map(|(price, _)| *price).collect();
+    candidates.sort();
+    candidates.dedup();
+
+    let mut best: Option<Clearing> = None;
+    for price in candidates {
+        let demand: Decimal = bids
+            .iter()
+            .filter(|(bid, _)| *bid >= price)
+            .map(|(_, quantity)| quantity.as_decimal())
+            .sum();
+        let supply: Decimal = asks
+            .iter()
+            .filter(|(ask, _)| *ask <= price)
+            .map(|(_, quantity)| quantity.as_decimal())
+            .sum();
+        let volume = demand.min(supply);
+        if volume.is_zero() {
+            continue;
+        }
+        let imbalance = (demand - supply).abs();
+
+        // Candidates ascend, so keeping the first of equals picks the lowest price
+        let better = best.is_none_or(|best| {
+            volume > best.volume || (volume == best.volume && imbalance < best.imbalance)
+        });
+        if better {
+            best = Some(Clearing { price, volume, imbalance });
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(levels: &[(u64, &str)]) -> Vec<(Price, Quantity)> {
+        levels
+            .iter()
+            .map(|(price, qty)| (Price::from_u64(*price), Quantity::from_str(qty).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_crossing_volume() {
+        let bids = levels(&[(99, "1.0"), (98, "2.0")]);
+        let asks = levels(&[(100, "1.0")]);
+        assert_eq!(clearing_price(&bids, &asks), None);
+        assert_eq!(clearing_price(&bids, &[]), None);
+    }
+
+    #[test]
+    fn test_maximizes_volume() {
+        let bids = levels(&[(103, "1.0"), (101, "2.0"), (99, "5.0")]);
+        let asks = levels(&[(98, "1.0"), (100, "1.5"), (102, "3.0")]);
+        let clearing = clearing_price(&bids, &asks).unwrap();
+        // At 100 and 101: demand 3.0, supply 2.5
+        assert_eq!(clearing.volume, Decimal::from_str_exact("2.5").unwrap());
+        assert_eq!(clearing.price, Price::from_u64(100));
+    }
+
+    #[test]
+    fn test_ties_by_imbalance_then_lowest_price() {
+        // Volume 2 at both 100 and 101; imbalance 1 at 100, 0 at 101
+        let bids = levels(&[(101, "2.0"), (100, "1.0")]);
+        let asks = levels(&[(100, "2.0")]);
+        let clearing = clearing_price(&bids, &asks).unwrap();
+        assert_eq!(clearing.price, Price::from_u64(101));
+        assert_eq!(clearing.imbalance, Decimal::ZERO);
+
+        // Same volume and imbalance at 100 and 101: the lower price wins
+        let bids = levels(&[(101, "1.0")]);
+        let asks = levels(&[(100, "1.0")]);
+        let clearing = clearing_price(&bids, &asks).unwrap();
+        assert_eq!(clearing.price, Price::from_u64(100));
+    }
+}
@@ -3,7 +3,7 @@
 //! Handles full and partial matches, generates trades, calculates fees
 
 use rust_decimal::Decimal;
-use types::ids::{AccountId, OrderId};
+use types::ids::{AccountId, OrderId, TradeId};
 use types::numeric::{Price, Quantity};
 use types::order::Side;
 use types::trade::Trade;
@@ -21,6 +21,16 @@ impl MatchExecutor {
         }
     }
 
+    /// Sequence number the next trade will receive
+    pub fn next_sequence_peek(&self) -> u64 {
+        self.sequence_counter
+    }
+
+    /// Ensure future sequences come after `sequence` (used on replay)
+    pub fn advance_past(&mut self, sequence: u64) {
+        self.sequence_counter = self.sequence_counter.max(sequence + 1);
+    }
+
     /// Get next sequence number (monotonically increasing)
     fn next_sequence(&mut self) -> u64 {
         let seq = self.sequence_counter;
@@ -54,7 +64,7 @@ impl MatchExecutor {
 
         let sequence = self.next_sequence();
 
-        Ok(Trade::new(
+        let mut trade = Trade::new(
             sequence,
             symbol,
             maker_order_id,
@@ -67,7 +77,9 @@ impl MatchExecutor {
             maker_fee,
             taker_fee,
             timestamp,
-        ))
+        );
+        trade.trade_id = deterministic_trade_id(timestamp, sequence);
+        Ok(trade)
     }
 
     /// Calculate maker and taker fees per spec §7
@@ -100,6 +112,17 @@ pub enum MatchError {
     InvalidQuantity,
 }
 
+/// Derive a trade ID from execution time and sequence
+///
+/// UUID v7 with the sequence in place of the random bits, so replaying the
+/// same inputs yields the same trade IDs (spec §12).
+fn deterministic_trade_id(timestamp: i64, sequence: u64) -> TradeId {
+    let millis = (timestamp.max(0) / 1_000_000) as u64;
+    let mut counter = [0u8; 10];
+    counter[2..].copy_from_slice(&sequence.to_be_bytes());
+    TradeId::from_uuid(uuid::Builder::from_unix_timestamp_millis(millis, &counter).into_uuid())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +145,8 @@ mod tests {
         ).unwrap();
 
         assert_eq!(trade.sequence, 1000);
+        assert_eq!(trade.trade_id, deterministic_trade_id(1708123456789000000, 1000));
+        assert_eq!(trade.trade_id.as_uuid().get_version_num(), 7);
         assert_eq!(trade.price, Price::from_u64(50000));
         assert_eq!(trade.quantity, Quantity::from_str("0.5").unwrap());
     }
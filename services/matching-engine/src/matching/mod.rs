@@ -2,8 +2,12 @@
 //!
 //! Implements price-time priority matching algorithm
 
+pub mod auction;
 pub mod crossing;
 pub mod executor;
+pub mod price_band;
 
+pub use auction::{clearing_price, Clearing};
 pub use crossing::can_match;
 pub use executor::MatchExecutor;
+pub use price_band::{BandTracker, PriceBand};
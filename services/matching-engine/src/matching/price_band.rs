@@ -0,0 +1,115 @@
+//! Price band protection
+//!
+//! Bounds how far a single submission can move the price. The band is
+//! centred on a reference price — the last trade, or the book mid before
+//! the first trade — and every fill moves the reference to the fill price,
+//! so a sweep is halted at the first level beyond `max_deviation_pct` of
+//! the price it last traded at. With no reference at all, the first fill
+//! establishes one.
+
+use rust_decimal::Decimal;
+use types::numeric::Price;
+use types::order::Side;
+
+/// Per-market price band configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceBand {
+    /// Maximum deviation from the reference, in percent (e.g. 5 = 5%)
+    pub max_deviation_pct: Decimal,
+    /// Rest a limit order's remainder at the band limit instead of canceling it
+    ///
+    /// Market and IOC remainders are always canceled.
+    pub rest_at_limit: bool,
+}
+
+/// Band state for one submission on one side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandTracker {
+    band: PriceBand,
+    side: Side,
+    reference: Option<Price>,
+}
+
+impl BandTracker {
+    /// Start tracking a submission from an optional reference price
+    pub fn new(band: PriceBand, side: Side, reference: Option<Price>) -> Self {
+        Self { band, side, reference }
+    }
+
+    /// Band configuration being applied
+    pub fn band(&self) -> &PriceBand {
+        &self.band
+    }
+
+    /// Current reference price
+    pub fn reference(&self) -> Option<Price> {
+        self.reference
+    }
+
+    /// Furthest price the submission may execute at, if a reference exists
+    ///
+    /// Above the reference for buys, below it for sells.
+    pub fn limit(&self) -> Option<Price> {
+        let reference = self.reference?.as_decimal();
+        let deviation = reference * self.band.max_deviation_pct / Decimal::from(100);
+        let limit = match self.side {
+            Side::BUY => reference + deviation,
+            Side::SELL => reference - deviation,
+        };
+        Price::try_new(limit)
+    }
+
+    /// Whether a fill at `price` stays inside the band
+    pub fn admits(&self, price: Price) -> bool {
+        if self.reference.is_none() {
+            return true;
+        }
+        match (self.side, self.limit()) {
+            (Side::BUY, Some(limit)) => price <= limit,
+            (Side::SELL, Some(limit)) => price >= limit,
+            // A sell band reaching zero admits any positive price
+            (_, None) => true,
+        }
+    }
+
+    /// Move the reference to an executed price
+    pub fn record_fill(&mut self, price: Price) {
+        self.reference = Some(price);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(pct: u64) -> PriceBand {
+        PriceBand {
+            max_deviation_pct: Decimal::from(pct),
+            rest_at_limit: false,
+        }
+    }
+
+    #[test]
+    fn test_limits_by_side() {
+        let buy = BandTracker::new(band(5), Side::BUY, Some(Price::from_u64(100)));
+        assert_eq!(buy.limit(), Some(Price::from_u64(105)));
+        assert!(buy.admits(Price::from_u64(105)));
+        assert!(!buy.admits(Price::from_str("105.01").unwrap()));
+
+        let sell = BandTracker::new(band(5), Side::SELL, Some(Price::from_u64(100)));
+        assert_eq!(sell.limit(), Some(Price::from_u64(95)));
+        assert!(!sell.admits(Price::from_u64(94)));
+    }
+
+    #[test]
+    fn test_reference_follows_fills() {
+        let mut tracker = BandTracker::new(band(5), Side::BUY, None);
+        assert!(tracker.admits(Price::from_u64(1_000_000)));
+        assert_eq!(tracker.limit(), None);
+
+        tracker.record_fill(Price::from_u64(100));
+        assert!(!tracker.admits(Price::from_u64(108)));
+        tracker.record_fill(Price::from_u64(104));
+        assert!(tracker.admits(Price::from_u64(108)));
+    }
+}
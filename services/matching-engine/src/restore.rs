@@ -0,0 +1,600 @@
+//! Order book warm start from persistence
+//!
+//! Rebuilds the engine's books at boot per spec §10.4 (crash recovery) and
+//! §11.6 (snapshot restore):
+//! 1. Load the latest snapshot and map its live orders into `BookEntry`s
+//! 2. Seed the engine sequence from the snapshot sequence
+//! 3. Replay journaled `BookEvent`s after the snapshot sequence
+//! 4. Refuse to open any market whose book is crossed
+//!
+//! Snapshot orders are keyed by order ID, so time priority is rebuilt from
+//! `created_at` (ties broken by the UUID v7 order ID).
+//!
+//! `BookEvent` payloads are bincode, registered per event type with the
+//! persistence `EventRegistry` by `register_events`. Each is journaled as a
+//! `Correlated<BookEvent>` carrying the id of the gateway request behind
+//! it; events journaled before the id decode with none.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use persistence::correlation::Correlated;
+use persistence::export::PayloadDecoders;
+use persistence::journal::JournalEntry;
+use persistence::reader::{JournalReader, ReaderError};
+use persistence::registry::{Codec, EventRegistry, JournalEvent, RegistryError};
+use persistence::snapshot::{EngineState, OrderSnapshot, Snapshot, SnapshotError, SnapshotLoader};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use types::ids::{AccountId, MarketId, OrderId, TradeId};
+use types::market::MarketStatus;
+use types::numeric::{Price, Quantity};
+use types::order::Side;
+use uuid::Uuid;
+
+use crate::engine::{BookEntry, EngineError, MatchingEngine};
+use crate::events::{BookEvent, TradeExecutedEvent};
+
+/// Warm start errors
+#[derive(Error, Debug)]
+pub enum RestoreError {
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Reader error: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("Invalid order snapshot {order_id}: {reason}")]
+    InvalidOrder { order_id: String, reason: String },
+
+    #[error("Undecodable {event_type} entry at sequence {sequence}: {reason}")]
+    Decode {
+        sequence: u64,
+        event_type: String,
+        reason: String,
+    },
+
+    #[error("Replay failed at sequence {sequence}: {reason}")]
+    Replay { sequence: u64, reason: String },
+
+    #[error("Crossed book for {symbol}: best bid {best_bid} >= best ask {best_ask}")]
+    CrossedBook {
+        symbol: String,
+        best_bid: Price,
+        best_ask: Price,
+    },
+}
+
+/// Summary of a completed warm start
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RestoreReport {
+    /// Sequence of the loaded snapshot (0 if none)
+    pub snapshot_sequence: u64,
+    /// Snapshot orders placed on the books
+    pub restored_orders: usize,
+    /// Snapshot orders skipped (terminal status or nothing left to fill)
+    pub skipped_orders: usize,
+    /// Journaled book events applied
+    pub replayed_events: u64,
+    /// Journal entries after the snapshot that are not book events
+    pub ignored_entries: u64,
+    /// Last sequence seen (snapshot or journal)
+    pub last_sequence: u64,
+    /// Markets opened for trading, in symbol order
+    pub opened_markets: Vec<String>,
+}
+
+/// Rebuilds a `MatchingEngine` from a snapshot directory and journal
+pub struct BookRestorer {
+    snapshot_dir: PathBuf,
+    journal_dir: PathBuf,
+}
+
+impl BookRestorer {
+    pub fn new(snapshot_dir: impl Into<PathBuf>, journal_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            snapshot_dir: snapshot_dir.into(),
+            journal_dir: journal_dir.into(),
+        }
+    }
+
+    /// Restore from the latest snapshot (cold start if there is none).
+    pub fn restore(&self) -> Result<(MatchingEngine, RestoreReport), RestoreError> {
+        let snapshot = match SnapshotLoader::new(&self.snapshot_dir).load_latest() {
+            Ok(snapshot) => Some(snapshot),
+            Err(SnapshotError::NoSnapshots) => None,
+            Err(e) => return Err(e.into()),
+        };
+        restore_from(snapshot.as_ref(), &self.journal_dir)
+    }
+}
+
+/// Restore from an explicit snapshot and the journal in `journal_dir`.
+pub fn restore_from(
+    snapshot: Option<&Snapshot>,
+    journal_dir: &Path,
+) -> Result<(MatchingEngine, RestoreReport), RestoreError> {
+    let snapshot_sequence = snapshot.map_or(0, |s| s.sequence);
+    let mut report = RestoreReport {
+        snapshot_sequence,
+        last_sequence: snapshot_sequence,
+        ..RestoreReport::default()
+    };
+
+    // Steps 1–2: snapshot orders onto the books, sequence seeded past the snapshot
+    let mut engine = MatchingEngine::new(snapshot_sequence + 1);
+    if let Some(snapshot) = snapshot {
+        let entries = book_entries(&snapshot.state)?;
+        report.restored_orders = entries.len();
+        report.skipped_orders = snapshot.state.orders.len() - entries.len();
+        for entry in &entries {
+            engine.restore_entry(entry);
+        }
+    }
+
+    // Step 3: replay through the engine's event-application path
+    let mut reader = JournalReader::open(journal_dir)?;
+    if snapshot_sequence > 0 {
+        reader.seek_to_sequence(snapshot_sequence + 1)?;
+    }
+    while let Some(entry) = reader.next_entry()? {
+        if entry.sequence <= snapshot_sequence {
+            continue;
+        }
+        match decode_event(&entry)? {
+            Some(event) => {
+                engine
+                    .apply_event(&event)
+                    .map_err(|e| RestoreError::Replay {
+                        sequence: entry.sequence,
+                        reason: format!("{:?}", e),
+                    })?;
+                report.replayed_events += 1;
+            }
+            None => report.ignored_entries += 1,
+        }
+        engine.advance_sequence_past(entry.sequence);
+        report.last_sequence = entry.sequence;
+    }
+
+    // Step 4: invariant check before any market opens
+    let symbols = engine.symbols();
+    for symbol in &symbols {
+        engine.check_uncrossed(symbol).map_err(|e| match e {
+            EngineError::CrossedBook { symbol, best_bid, best_ask } => {
+                RestoreError::CrossedBook { symbol, best_bid, best_ask }
+            }
+            other => RestoreError::Replay {
+                sequence: report.last_sequence,
+                reason: format!("{:?}", other),
+            },
+        })?;
+    }
+    for symbol in symbols {
+        engine
+            .list_market(&symbol, MarketStatus::Trading)
+            .map_err(|e| RestoreError::Replay {
+                sequence: report.last_sequence,
+                reason: format!("{:?}", e),
+            })?;
+        report.opened_markets.push(symbol);
+    }
+
+    Ok((engine, report))
+}
+
+/// Whether a snapshot status string is terminal per spec §2.2
+fn is_terminal_status(status: &str) -> bool {
+    matches!(
+        status.to_ascii_uppercase().as_str(),
+        "FILLED" | "CANCELED" | "REJECTED" | "EXPIRED"
+    )
+}
+
+/// Map the live orders of a snapshot into book entries in time priority.
+///
+/// Terminal orders and orders with nothing left to fill are skipped.
+pub fn book_entries(state: &EngineState) -> Result<Vec<BookEntry>, RestoreError> {
+    let mut live: Vec<&OrderSnapshot> = state
+        .orders
+        .values()
+        .filter(|o| !is_terminal_status(&o.status))
+        .collect();
+    live.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.order_id.cmp(&b.order_id))
+    });
+
+    let mut entries = Vec::with_capacity(live.len());
+    for order in live {
+        let entry = book_entry(order)?;
+        if !entry.remaining_quantity.is_zero() {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn book_entry(order: &OrderSnapshot) -> Result<BookEntry, RestoreError> {
+    let invalid = |reason: String| RestoreError::InvalidOrder {
+        order_id: order.order_id.clone(),
+        reason,
+    };
+
+    let order_id = Uuid::parse_str(&order.order_id)
+        .map(OrderId::from_uuid)
+        .map_err(|e| invalid(format!("order_id: {}", e)))?;
+    let account_id = Uuid::parse_str(&order.account_id)
+        .map(AccountId::from_uuid)
+        .map_err(|e| invalid(format!("account_id: {}", e)))?;
+    if !order.symbol.contains('/') {
+        return Err(invalid(format!("symbol: {}", order.symbol)));
+    }
+    let side = match order.side.to_ascii_uppercase().as_str() {
+        "BUY" => Side::BUY,
+        "SELL" => Side::SELL,
+        other => return Err(invalid(format!("side: {}", other))),
+    };
+    let price = Price::from_str(&order.price).map_err(|e| invalid(format!("price: {}", e)))?;
+    let remaining_quantity = Quantity::from_str(&order.remaining_quantity)
+        .map_err(|e| invalid(format!("remaining_quantity: {}", e)))?;
+    let filled_quantity = match Decimal::from_str(&order.filled_quantity) {
+        Ok(filled) if filled.is_zero() => Quantity::zero(),
+        Ok(filled) => Quantity::try_new(filled).ok_or_else(|| invalid(format!("filled_quantity: {}", filled)))?,
+        Err(e) => return Err(invalid(format!("filled_quantity: {}", e))),
+    };
+
+    Ok(BookEntry {
+        order_id,
+        account_id,
+        symbol: MarketId::new(&order.symbol),
+        side,
+        price,
+        remaining_quantity,
+        filled_quantity,
+        placed_at: order.created_at,
+    })
+}
+
+/// Journal event types carrying current-version `BookEvent`s.
+pub const BOOK_EVENT_TYPES: [&str; 8] = [
+    "OrderAccepted",
+    "TradeExecuted.v2",
+    "OrderCanceled",
+    "OrderAmended",
+    "StopAccepted",
+    "StopTriggered",
+    "PriceBandHit",
+    "AuctionUncrossed",
+];
+
+impl JournalEvent for BookEvent {
+    fn event_type(&self) -> &str {
+        BookEvent::event_type(self)
+    }
+}
+
+/// Register the `BookEvent` event types, including trades journaled as plain
+/// `TradeExecuted` before the liquidity flags.
+pub fn register_events(registry: &mut EventRegistry) -> Result<(), RegistryError> {
+    registry.register::<Correlated<BookEvent>>(&BOOK_EVENT_TYPES, Codec::Bincode)?;
+    registry.register_legacy("TradeExecuted", Codec::Bincode, upgrade_legacy)
+}
+
+/// Legacy trades predate correlation ids
+fn upgrade_legacy(legacy: LegacyBookEvent) -> Result<Correlated<BookEvent>, String> {
+    match legacy {
+        LegacyBookEvent::TradeExecuted(trade) => {
+            Ok(Correlated::uncorrelated(BookEvent::TradeExecuted(trade.into())))
+        }
+        LegacyBookEvent::OrderAccepted => Err("payload is OrderAccepted".to_string()),
+    }
+}
+
+/// Register JSON decoders for the current book event types, for the
+/// persistence export (and its `correlation_id` filter).
+pub fn register_export_decoders(decoders: &mut PayloadDecoders) {
+    for event_type in BOOK_EVENT_TYPES {
+        decoders.register(event_type, |payload| {
+            let event: Correlated<BookEvent> = bincode::deserialize(payload).map_err(|e| e.to_string())?;
+            serde_json::to_value(event).map_err(|e| e.to_string())
+        });
+    }
+}
+
+/// Registry holding just the book events.
+fn event_registry() -> &'static EventRegistry {
+    static REGISTRY: OnceLock<EventRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = EventRegistry::new();
+        register_events(&mut registry).expect("book event types are distinct");
+        registry
+    })
+}
+
+/// Encode a book event as a journal entry (bincode payload).
+pub fn journal_entry(sequence: u64, timestamp: i64, event: &BookEvent) -> JournalEntry {
+    correlated_journal_entry(sequence, timestamp, event, None)
+}
+
+/// Encode a book event journaled for the request `correlation_id`.
+pub fn correlated_journal_entry(
+    sequence: u64,
+    timestamp: i64,
+    event: &BookEvent,
+    correlation_id: Option<&str>,
+) -> JournalEntry {
+    let event = Correlated::new(event.clone(), correlation_id.map(str::to_string));
+    event_registry()
+        .journal_entry(sequence, timestamp, &event)
+        .expect("BookEvent serialization should never fail")
+}
+
+/// Decode a journal entry into a book event.
+///
+/// Returns `None` for event types that do not change the book. Trades
+/// journaled as plain `TradeExecuted`, before the liquidity flags, decode
+/// with the flags derived from their taker side.
+pub fn decode_event(entry: &JournalEntry) -> Result<Option<BookEvent>, RestoreError> {
+    Ok(decode_correlated(entry)?.map(|event| event.event))
+}
+
+/// Decode a journal entry into a book event and its correlation id.
+pub fn decode_correlated(entry: &JournalEntry) -> Result<Option<Correlated<BookEvent>>, RestoreError> {
+    let event = event_registry().decode_entry(entry).map_err(|e| RestoreError::Decode {
+        sequence: entry.sequence,
+        event_type: entry.event_type.clone(),
+        reason: e.reason(),
+    })?;
+    Ok(event.downcast().ok())
+}
+
+/// `BookEvent` prefix as journaled before `TradeExecuted.v2`
+///
+/// Variant order must match `BookEvent`; only trades are decoded.
+#[derive(Serialize, Deserialize)]
+enum LegacyBookEvent {
+    OrderAccepted,
+    TradeExecuted(LegacyTradeExecutedEvent),
+}
+
+/// `TradeExecutedEvent` without the liquidity flags
+#[derive(Serialize, Deserialize)]
+struct LegacyTradeExecutedEvent {
+    trade_id: TradeId,
+    sequence: u64,
+    symbol: String,
+    maker_order_id: OrderId,
+    taker_order_id: OrderId,
+    maker_account_id: AccountId,
+    taker_account_id: AccountId,
+    price: Price,
+    quantity: Quantity,
+    side: Side,
+    executed_at: i64,
+}
+
+impl From<LegacyTradeExecutedEvent> for TradeExecutedEvent {
+    /// The legacy taker side is the aggressor side
+    fn from(trade: LegacyTradeExecutedEvent) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            sequence: trade.sequence,
+            symbol: trade.symbol,
+            maker_order_id: trade.maker_order_id,
+            taker_order_id: trade.taker_order_id,
+            maker_account_id: trade.maker_account_id,
+            taker_account_id: trade.taker_account_id,
+            price: trade.price,
+            quantity: trade.quantity,
+            side: trade.side,
+            aggressor_side: trade.side,
+            maker_is_buyer: trade.side == Side::SELL,
+            executed_at: trade.executed_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use persistence::export::{export_jsonl_with, ExportFilter, ExportOptions};
+    use persistence::journal::{JournalConfig, JournalWriter};
+    use persistence::snapshot::EngineState;
+
+    fn order_snapshot(side: &str, price: &str, remaining: &str, status: &str, created_at: i64) -> OrderSnapshot {
+        OrderSnapshot {
+            order_id: Uuid::now_v7().to_string(),
+            account_id: Uuid::now_v7().to_string(),
+            symbol: "BTC/USDT".to_string(),
+            side: side.to_string(),
+            price: price.to_string(),
+            quantity: "1.0".to_string(),
+            filled_quantity: "0".to_string(),
+            remaining_quantity: remaining.to_string(),
+            status: status.to_string(),
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn state(orders: Vec<OrderSnapshot>) -> EngineState {
+        let mut state = EngineState::empty();
+        for order in orders {
+            state.orders.insert(order.order_id.clone(), order);
+        }
+        state
+    }
+
+    #[test]
+    fn test_book_entries_skip_terminal_and_keep_time_priority() {
+        let late = order_snapshot("BUY", "50000", "1.0", "ACTIVE", 30);
+        let early = order_snapshot("BUY", "50000", "0.5", "PARTIAL", 10);
+        let filled = order_snapshot("SELL", "50100", "0", "FILLED", 20);
+        let canceled = order_snapshot("SELL", "50100", "1.0", "CANCELED", 5);
+        let late_id = late.order_id.clone();
+        let early_id = early.order_id.clone();
+
+        let entries = book_entries(&state(vec![late, early, filled, canceled])).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].order_id.to_string(), early_id);
+        assert_eq!(entries[1].order_id.to_string(), late_id);
+        assert_eq!(entries[0].remaining_quantity, Quantity::from_str("0.5").unwrap());
+    }
+
+    #[test]
+    fn test_restored_entry_keeps_filled_quantity() {
+        let mut partial = order_snapshot("SELL", "50100", "0.25", "PARTIAL", 42);
+        partial.filled_quantity = "0.75".to_string();
+        let account_id = AccountId::from_uuid(Uuid::parse_str(&partial.account_id).unwrap());
+
+        let mut engine = MatchingEngine::new(1);
+        for entry in book_entries(&state(vec![partial])).unwrap() {
+            engine.restore_entry(&entry);
+        }
+        let open = engine.orders_for_account(&account_id);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].original_quantity, Quantity::from_str("1.0").unwrap());
+        assert_eq!(open[0].remaining_quantity, Quantity::from_str("0.25").unwrap());
+        assert_eq!(open[0].timestamp, 42);
+    }
+
+    #[test]
+    fn test_invalid_order_snapshot_is_reported() {
+        let bad = order_snapshot("HOLD", "50000", "1.0", "ACTIVE", 1);
+        let result = book_entries(&state(vec![bad]));
+        assert!(matches!(result, Err(RestoreError::InvalidOrder { .. })));
+    }
+
+    #[test]
+    fn test_journal_entry_round_trip() {
+        let event = BookEvent::OrderCanceled {
+            order_id: OrderId::new(),
+            symbol: "BTC/USDT".to_string(),
+            side: Side::SELL,
+            price: Price::from_u64(50100),
+            remaining_quantity: Quantity::from_str("0.25").unwrap(),
+        };
+        let entry = journal_entry(7, 1708123456789000000, &event);
+        assert_eq!(entry.event_type, "OrderCanceled");
+        assert!(matches!(
+            decode_event(&entry).unwrap(),
+            Some(BookEvent::OrderCanceled { .. })
+        ));
+
+        let other = JournalEntry::new(8, 1, "BalanceUpdated".to_string(), vec![1, 2, 3]);
+        assert!(decode_event(&other).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_correlation_id_survives_journal_and_export() {
+        let event = BookEvent::OrderCanceled {
+            order_id: OrderId::new(),
+            symbol: "BTC/USDT".to_string(),
+            side: Side::BUY,
+            price: Price::from_u64(49900),
+            remaining_quantity: Quantity::from_str("1").unwrap(),
+        };
+        let entry = correlated_journal_entry(4, 1, &event, Some("req-42"));
+        let decoded = decode_correlated(&entry).unwrap().unwrap();
+        assert_eq!(decoded.correlation_id.as_deref(), Some("req-42"));
+        assert!(matches!(decode_event(&entry).unwrap(), Some(BookEvent::OrderCanceled { .. })));
+
+        // Payloads journaled as a bare `BookEvent` still decode
+        let bare = JournalEntry::new(5, 1, "OrderCanceled".to_string(), bincode::serialize(&event).unwrap());
+        let decoded = decode_correlated(&bare).unwrap().unwrap();
+        assert!(decoded.correlation_id.is_none());
+        assert!(matches!(decoded.event, BookEvent::OrderCanceled { .. }));
+
+        let mut decoders = PayloadDecoders::new();
+        register_export_decoders(&mut decoders);
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = JournalWriter::open(JournalConfig::new(dir.path())).unwrap();
+        writer.set_next_sequence(4);
+        writer.append(&entry).unwrap();
+        writer.append(&bare).unwrap();
+        writer.sync().unwrap();
+        let filter = ExportFilter {
+            correlation_id: Some("req-42".to_string()),
+            ..Default::default()
+        };
+        let options = ExportOptions { decoders, ..Default::default() };
+        let out = dir.path().join("out.jsonl");
+        let report = export_jsonl_with(dir.path(), &out, &filter, &options).unwrap();
+        assert_eq!(report.exported, 1);
+    }
+
+    #[test]
+    fn test_crossed_snapshot_refuses_to_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let bid = order_snapshot("BUY", "50100", "1.0", "ACTIVE", 1);
+        let ask = order_snapshot("SELL", "50000", "1.0", "ACTIVE", 2);
+        let snapshot = Snapshot::new(10, 3, state(vec![bid, ask]), false);
+
+        let result = restore_from(Some(&snapshot), dir.path());
+        assert!(matches!(result, Err(RestoreError::CrossedBook { .. })));
+    }
+
+    #[test]
+    fn test_legacy_trade_entry_decodes_with_flags() {
+        let legacy = LegacyTradeExecutedEvent {
+            trade_id: TradeId::new(),
+            sequence: 7,
+            symbol: "BTC/USDT".to_string(),
+            maker_order_id: OrderId::new(),
+            taker_order_id: OrderId::new(),
+            maker_account_id: AccountId::new(),
+            taker_account_id: AccountId::new(),
+            price: Price::from_u64(50000),
+            quantity: Quantity::from_str("1.0").unwrap(),
+            side: Side::SELL,
+            executed_at: 42,
+        };
+        let payload = bincode::serialize(&LegacyBookEvent::TradeExecuted(legacy)).unwrap();
+        let entry = JournalEntry::new(1, 42, "TradeExecuted".to_string(), payload);
+
+        match decode_event(&entry).unwrap() {
+            Some(BookEvent::TradeExecuted(trade)) => {
+                assert_eq!(trade.sequence, 7);
+                assert_eq!(trade.aggressor_side, Side::SELL);
+                assert!(trade.maker_is_buyer);
+            }
+            _ => panic!("Expected TradeExecuted event"),
+        }
+
+        // Current entries carry the versioned type
+        let current = BookEvent::TradeExecuted(match decode_event(&entry).unwrap() {
+            Some(BookEvent::TradeExecuted(trade)) => trade,
+            _ => unreachable!(),
+        });
+        let entry = journal_entry(2, 43, &current);
+        assert_eq!(entry.event_type, "TradeExecuted.v2");
+        assert!(matches!(decode_event(&entry).unwrap(), Some(BookEvent::TradeExecuted(_))));
+    }
+
+    #[test]
+    fn test_registered_event_types_reject_mismatched_payloads() {
+        let mut registry = EventRegistry::new();
+        register_events(&mut registry).unwrap();
+        assert_eq!(registry.event_types().count(), BOOK_EVENT_TYPES.len() + 1);
+
+        let event = BookEvent::OrderCanceled {
+            order_id: OrderId::new(),
+            symbol: "BTC/USDT".to_string(),
+            side: Side::BUY,
+            price: Price::from_u64(49900),
+            remaining_quantity: Quantity::from_str("1").unwrap(),
+        };
+        let mut entry = journal_entry(3, 1, &event);
+        entry.event_type = "OrderAccepted".to_string();
+        match decode_event(&entry) {
+            Err(RestoreError::Decode { sequence: 3, reason, .. }) => {
+                assert_eq!(reason, "payload is OrderCanceled")
+            }
+            other => panic!("Expected Decode error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Warm start integration test
+//!
+//! Runs a live engine while journaling its book events, snapshots midway,
+//! then restores a fresh engine with `BookRestorer`. The restored depth must
+//! match both the live engine and a market-data mirror rebuilt from the same
+//! journal (spec §11 Replay Requirements).
+
+use std::collections::BTreeMap;
+
+use market_data::order_book::OrderBookState;
+use matching_engine::engine::SubmitResult;
+use matching_engine::events::{BookEvent, TradeExecutedEvent};
+use matching_engine::restore::{decode_event, journal_entry};
+use matching_engine::{BookRestorer, MatchingEngine};
+use persistence::journal::{JournalConfig, JournalWriter};
+use persistence::reader::JournalReader;
+use persistence::snapshot::{EngineState, OrderSnapshot, Snapshot, SnapshotWriter};
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::{Price, Quantity};
+use types::order::{Order, Side, TimeInForce};
+
+const SYMBOL: &str = "BTC/USDT";
+const BASE_TS: i64 = 1708123456789000000;
+
+/// Live engine plus journal and the order model a snapshot is taken from
+struct Harness {
+    engine: MatchingEngine,
+    journal: JournalWriter,
+    next_seq: u64,
+    orders: BTreeMap<String, OrderSnapshot>,
+}
+
+impl Harness {
+    fn record(&mut self, event: BookEvent, ts: i64) {
+        self.journal
+            .append(&journal_entry(self.next_seq, ts, &event))
+            .unwrap();
+        self.next_seq += 1;
+
+        match event {
+            BookEvent::OrderAccepted { order_id, account_id, side, price, quantity, .. } => {
+                self.orders.insert(
+                    order_id.to_string(),
+                    OrderSnapshot {
+                        order_id: order_id.to_string(),
+                        account_id: account_id.to_string(),
+                        symbol: SYMBOL.to_string(),
+                        side: format!("{:?}", side),
+                        price: price.to_string(),
+                        quantity: quantity.to_string(),
+                        filled_quantity: "0".to_string(),
+                        remaining_quantity: quantity.to_string(),
+                        status: "ACTIVE".to_string(),
+                        created_at: ts,
+                        updated_at: ts,
+                    },
+                );
+            }
+            BookEvent::TradeExecuted(trade) => self.fill(trade.maker_order_id, trade.quantity, ts),
+            BookEvent::AuctionUncrossed(auction) => {
+                for trade in &auction.trades {
+                    self.fill(trade.maker_order_id, trade.quantity, ts);
+                    self.fill(trade.taker_order_id, trade.quantity, ts);
+                }
+            }
+            BookEvent::OrderCanceled { order_id, .. } => {
+                let order = self.orders.get_mut(&order_id.to_string()).unwrap();
+                order.status = "CANCELED".to_string();
+                order.updated_at = ts;
+            }
+            BookEvent::OrderAmended(amend) => {
+                let order = self.orders.get_mut(&amend.order_id.to_string()).unwrap();
+                order.price = amend.new_price.to_string();
+                order.remaining_quantity = amend.resting_quantity.to_string();
+                order.updated_at = ts;
+            }
+            BookEvent::PriceBandHit(hit) if hit.rested => {
+                let quantity = hit.filled_quantity.as_decimal() + hit.unexecuted_quantity.as_decimal();
+                self.orders.insert(
+                    hit.order_id.to_string(),
+                    OrderSnapshot {
+                        order_id: hit.order_id.to_string(),
+                        account_id: hit.account_id.to_string(),
+                        symbol: SYMBOL.to_string(),
+                        side: format!("{:?}", hit.side),
+                        price: hit.band_limit.to_string(),
+                        quantity: quantity.to_string(),
+                        filled_quantity: hit.filled_quantity.to_string(),
+                        remaining_quantity: hit.unexecuted_quantity.to_string(),
+                        status: if hit.filled_quantity.is_zero() { "ACTIVE" } else { "PARTIAL" }.to_string(),
+                        created_at: hit.placed_at,
+                        updated_at: ts,
+                    },
+                );
+            }
+            BookEvent::StopAccepted(_) | BookEvent::StopTriggered(_) | BookEvent::PriceBandHit(_) => {}
+        }
+    }
+
+    fn fill(&mut self, order_id: OrderId, quantity: Quantity, ts: i64) {
+        let order = self.orders.get_mut(&order_id.to_string()).unwrap();
+        let remaining = Quantity::from_str(&order.remaining_quantity).unwrap().as_decimal()
+            - quantity.as_decimal();
+        order.remaining_quantity = remaining.to_string();
+        order.status = if remaining.is_zero() { "FILLED" } else { "PARTIAL" }.to_string();
+        order.updated_at = ts;
+    }
+
+    fn submit(&mut self, side: Side, price: u64, qty: &str, ts: i64) {
+        let order = Order::new(
+            AccountId::new(),
+            MarketId::new(SYMBOL),
+            side,
+            Price::from_u64(price),
+            Quantity::from_str(qty).unwrap(),
+            TimeInForce::GTC,
+            ts,
+        );
+        let result = self.engine.submit_order(order.clone(), ts).unwrap();
+        let (trades, rested, band_hit) = match result {
+            SubmitResult::Resting => (Vec::new(), true, None),
+            SubmitResult::PartiallyFilled { trades, .. } => (trades, false, None),
+            SubmitResult::Filled { trades } => (trades, false, None),
+            SubmitResult::Rejected(_) => (Vec::new(), false, None),
+            SubmitResult::Canceled { trades, .. } => (trades, false, None),
+            SubmitResult::StopAccepted(_) => (Vec::new(), false, None),
+            SubmitResult::PriceBandHit { trades, event } => (trades, false, Some(event)),
+        };
+        for trade in &trades {
+            self.record(BookEvent::TradeExecuted(TradeExecutedEvent::from_trade(trade)), ts);
+        }
+        if let Some(hit) = band_hit {
+            self.record(BookEvent::PriceBandHit(hit), ts);
+        }
+        if rested {
+            self.record(
+                BookEvent::OrderAccepted {
+                    order_id: order.order_id,
+                    account_id: order.account_id,
+                    symbol: SYMBOL.to_string(),
+                    side,
+                    price: order.price,
+                    quantity: order.remaining_quantity,
+                    accepted_at: order.created_at,
+                },
+                ts,
+            );
+        }
+    }
+
+    /// Cancel the oldest live order on the book, if any
+    fn cancel_oldest(&mut self, ts: i64) {
+        let Some(order) = self
+            .orders
+            .values()
+            .filter(|o| o.status == "ACTIVE" || o.status == "PARTIAL")
+            .min_by_key(|o| o.created_at)
+            .cloned()
+        else {
+            return;
+        };
+        let order_id = types::ids::OrderId::from_uuid(order.order_id.parse().unwrap());
+        let side = if order.side == "BUY" { Side::BUY } else { Side::SELL };
+        let price = Price::from_str(&order.price).unwrap();
+        assert!(self.engine.cancel_order(SYMBOL, &order_id, price, side));
+        self.record(
+            BookEvent::OrderCanceled {
+                order_id,
+                symbol: SYMBOL.to_string(),
+                side,
+                price,
+                remaining_quantity: Quantity::from_str(&order.remaining_quantity).unwrap(),
+            },
+            ts,
+        );
+    }
+
+    /// Deterministic mixed flow of resting, crossing and canceled orders
+    fn run(&mut self, steps: u64, offset: u64) {
+        let mut rng = 0x2545_f491_4f6c_dd1d_u64 ^ offset;
+        for step in 0..steps {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            let ts = BASE_TS + ((offset + step) as i64) * 1_000_000;
+            if rng.is_multiple_of(7) {
+                self.cancel_oldest(ts);
+                continue;
+            }
+            let side = if rng.is_multiple_of(2) { Side::BUY } else { Side::SELL };
+            // Bids 49 990..50 005, asks 49 995..50 010 so some orders cross
+            let price = match side {
+                Side::BUY => 49_990 + (rng >> 8) % 16,
+                Side::SELL => 49_995 + (rng >> 8) % 16,
+            };
+            let qty = format!("0.{}", 1 + (rng >> 16) % 9);
+            self.submit(side, price, &qty, ts);
+        }
+    }
+}
+
+fn mirror_from_journal(journal_dir: &std::path::Path) -> OrderBookState {
+    let mut mirror = OrderBookState::new(MarketId::new(SYMBOL));
+    let entries = JournalReader::open(journal_dir).unwrap().read_all().unwrap();
+    for entry in entries {
+        match decode_event(&entry).unwrap().unwrap() {
+            BookEvent::OrderAccepted { order_id, side, price, quantity, .. } => {
+                mirror.apply_order_accepted(order_id, side, price, quantity, entry.sequence)
+            }
+            BookEvent::TradeExecuted(trade) => {
+                mirror.apply_trade_executed(trade.maker_order_id, trade.quantity, entry.sequence)
+            }
+            BookEvent::AuctionUncrossed(auction) => {
+                for trade in &auction.trades {
+                    mirror.apply_trade_executed(trade.maker_order_id, trade.quantity, entry.sequence);
+                    mirror.apply_trade_executed(trade.taker_order_id, trade.quantity, entry.sequence);
+                }
+            }
+            BookEvent::OrderCanceled { order_id, remaining_quantity, .. } => {
+                mirror.apply_cancel(order_id, remaining_quantity, entry.sequence)
+            }
+            BookEvent::OrderAmended(amend) => {
+                mirror.apply_cancel(amend.order_id, amend.old_quantity, entry.sequence);
+                if !amend.resting_quantity.is_zero() {
+                    mirror.apply_order_accepted(
+                        amend.order_id, amend.side, amend.new_price, amend.resting_quantity, entry.sequence,
+                    );
+                }
+            }
+            BookEvent::PriceBandHit(hit) => {
+                if hit.rested {
+                    mirror.apply_order_accepted(
+                        hit.order_id, hit.side, hit.band_limit, hit.unexecuted_quantity, entry.sequence,
+                    );
+                }
+            }
+            BookEvent::StopAccepted(_) | BookEvent::StopTriggered(_) => {}
+        }
+    }
+    mirror
+}
+
+fn levels(levels: &[(Price, Quantity)]) -> Vec<(Price, rust_decimal::Decimal)> {
+    levels.iter().map(|(p, q)| (*p, q.as_decimal())).collect()
+}
+
+#[test]
+fn test_restored_book_matches_market_data_mirror() {
+    let journal_dir = tempfile::tempdir().unwrap();
+    let snapshot_dir = tempfile::tempdir().unwrap();
+
+    let mut harness = Harness {
+        engine: MatchingEngine::new(1),
+        journal: JournalWriter::open(JournalConfig::new(journal_dir.path())).unwrap(),
+        next_seq: 1,
+        orders: BTreeMap::new(),
+    };
+
+    // Phase 1: build a book, then snapshot it (terminal orders included)
+    harness.run(150, 0);
+    let snapshot_seq = harness.next_seq - 1;
+    let mut state = EngineState::empty();
+    state.orders = harness.orders.clone();
+    let snapshot = Snapshot::new(snapshot_seq, BASE_TS, state, true);
+    SnapshotWriter::new(snapshot_dir.path(), true).write(&snapshot).unwrap();
+
+    // Phase 2: keep trading; only the journal sees these events
+    harness.run(150, 1_000);
+    harness.journal.sync().unwrap();
+    let last_seq = harness.next_seq - 1;
+    assert!(last_seq > snapshot_seq);
+
+    let (restored, report) = BookRestorer::new(snapshot_dir.path(), journal_dir.path())
+        .restore()
+        .unwrap();
+
+    assert_eq!(report.snapshot_sequence, snapshot_seq);
+    assert_eq!(report.replayed_events, last_seq - snapshot_seq);
+    assert_eq!(report.ignored_entries, 0);
+    assert!(report.skipped_orders > 0);
+    assert_eq!(report.opened_markets, vec![SYMBOL.to_string()]);
+    assert!(restored.next_sequence() >= harness.engine.next_sequence());
+    assert!(restored.check_uncrossed(SYMBOL).is_ok());
+
+    let restored_book = restored.get_order_book(SYMBOL, 100).unwrap();
+    let live_book = harness.engine.get_order_book(SYMBOL, 100).unwrap();
+    assert!(!restored_book.bids.is_empty() && !restored_book.asks.is_empty());
+    assert_eq!(restored_book.bids, live_book.bids);
+    assert_eq!(restored_book.asks, live_book.asks);
+
+    let mirror = mirror_from_journal(journal_dir.path()).depth_snapshot(100);
+    let mirror_bids: Vec<_> = mirror.bids.iter().map(|l| (l.price, l.total_quantity)).collect();
+    let mirror_asks: Vec<_> = mirror.asks.iter().map(|l| (l.price, l.total_quantity)).collect();
+    assert_eq!(levels(&restored_book.bids), mirror_bids);
+    assert_eq!(levels(&restored_book.asks), mirror_asks);
+    assert_eq!(mirror.last_sequence, last_seq);
+}
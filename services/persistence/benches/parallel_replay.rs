@@ -0,0 +1,66 @@
+//! Parallel replay benchmark
+//!
+//! Decodes a 64-segment journal of 200k entries with 1, 2, 4 and 8 decode
+//! workers. Each run drains a `ParallelReader` and folds every entry into
+//! a running checksum on the consuming thread, standing in for the
+//! single-threaded apply step.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use persistence::journal::{JournalConfig, JournalEntry, JournalWriter};
+use persistence::parallel::ParallelReader;
+use persistence::reader::JournalReader;
+use std::path::Path;
+use tempfile::TempDir;
+
+const ENTRIES: u64 = 200_000;
+const SEGMENTS: u64 = 64;
+const PAYLOAD_LEN: usize = 192;
+
+fn write_journal(dir: &Path) {
+    let entry_len = JournalEntry::new(1, 0, "OrderSubmitted".into(), vec![0; PAYLOAD_LEN])
+        .to_bytes()
+        .len() as u64;
+    let config = JournalConfig {
+        max_file_size: entry_len * ENTRIES / SEGMENTS,
+        ..JournalConfig::new(dir)
+    };
+    let mut writer = JournalWriter::open(config).unwrap();
+    writer.set_next_sequence(1);
+    for seq in 1..=ENTRIES {
+        let payload: Vec<u8> = (0..PAYLOAD_LEN).map(|i| (seq as usize + i) as u8).collect();
+        let entry = JournalEntry::new(seq, seq as i64, "OrderSubmitted".into(), payload);
+        writer.append(&entry).unwrap();
+    }
+    writer.sync().unwrap();
+}
+
+/// Drain the journal, returning a digest of what was read
+fn replay(dir: &Path, workers: usize) -> u64 {
+    let mut reader = ParallelReader::new(JournalReader::open(dir).unwrap(), workers);
+    let mut digest = 0u64;
+    while let Some(entry) = reader.next_entry().unwrap() {
+        digest = digest.rotate_left(5) ^ entry.sequence ^ entry.checksum as u64;
+    }
+    digest
+}
+
+fn parallel_replay(c: &mut Criterion) {
+    let tmp = TempDir::new().unwrap();
+    write_journal(tmp.path());
+    let expected = replay(tmp.path(), 1);
+
+    let mut group = c.benchmark_group("parallel_replay");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ENTRIES));
+    for workers in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("200k_entries_64_segments", workers),
+            &workers,
+            |b, &workers| b.iter(|| assert_eq!(replay(tmp.path(), workers), expected)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parallel_replay);
+criterion_main!(benches);
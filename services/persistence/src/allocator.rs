@@ -0,0 +1,384 @@
+//! Sequence Allocator — single authority for journal sequence numbers
+//!
+//! Implements spec §14 (gapless, monotonic sequences) for several producers
+//! feeding one journal: instead of each setting `next_sequence` on the
+//! writer, producers reserve contiguous ranges here.
+//!
+//! - The high-water mark (first sequence never handed out) is persisted to a
+//!   small state file — temp file, fsync, rename, directory fsync — before a
+//!   reservation is returned, so a restart never hands a number out twice.
+//! - A reservation stays outstanding until the writer has written it or the
+//!   producer gives it back with [`SequenceAllocator::release`].
+//!
+//! # Gap Policy
+//! A reserved sequence is never reused, even if it was never written.
+//! Instead it is voided: the writer appends an entry of type
+//! [`VOIDED_EVENT_TYPE`] (empty payload) in its place, so the journal stays
+//! gapless and every skipped number is visible to readers. Appliers must
+//! skip voided entries. This happens when
+//! - a producer releases sequences and the writer reaches them, or
+//! - after a crash, on [`JournalWriter::open_with_allocator`]: every
+//!   sequence between the end of the journal and the persisted high-water
+//!   mark was reserved by the previous process and leaked.
+//!
+//! Voided ranges are reported by [`SequenceAllocator::voided`].
+//!
+//! [`JournalWriter::open_with_allocator`]: crate::journal::JournalWriter::open_with_allocator
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use thiserror::Error;
+
+/// Event type of the entries written in place of voided sequences.
+pub const VOIDED_EVENT_TYPE: &str = "SequenceVoided";
+
+/// First sequence handed out by a fresh allocator.
+pub const FIRST_SEQUENCE: u64 = 1;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum AllocatorError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Allocator state file corrupt: {0}")]
+    Corrupt(String),
+
+    /// The range is not (entirely) an outstanding reservation.
+    #[error("Sequences {start}..{end} are not reserved")]
+    NotReserved { start: u64, end: u64 },
+
+    /// Reserving `requested` more sequences would pass `u64::MAX`.
+    #[error("Sequence space exhausted: cannot reserve {requested} past {high_water}")]
+    Exhausted { high_water: u64, requested: u64 },
+
+    #[error("Cannot reconcile with journal: {0}")]
+    Journal(String),
+}
+
+// ── State ───────────────────────────────────────────────────────────
+
+/// Persisted allocator state.
+#[derive(Debug, Serialize, Deserialize)]
+struct StateFile {
+    high_water: u64,
+}
+
+/// Disjoint half-open ranges, keyed by start.
+#[derive(Debug, Default)]
+struct RangeSet(BTreeMap<u64, u64>);
+
+impl RangeSet {
+    fn insert(&mut self, range: Range<u64>) {
+        if !range.is_empty() {
+            self.0.insert(range.start, range.end);
+        }
+    }
+
+    /// Whether every sequence of `range` is in the set.
+    fn contains(&self, range: &Range<u64>) -> bool {
+        let mut at = range.start;
+        for (&start, &end) in self.0.range(..range.end) {
+            if end <= at {
+                continue;
+            }
+            if start > at {
+                return false;
+            }
+            at = end;
+            if at >= range.end {
+                return true;
+            }
+        }
+        range.is_empty()
+    }
+
+    /// Remove `range` (or the parts of it that are present).
+    fn remove(&mut self, range: &Range<u64>) {
+        let overlapping: Vec<(u64, u64)> = self
+            .0
+            .range(..range.end)
+            .filter(|(_, &end)| end > range.start)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+        for (start, end) in overlapping {
+            self.0.remove(&start);
+            self.insert(start..range.start.max(start));
+            self.insert(range.end.min(end)..end);
+        }
+    }
+
+    fn first_start(&self) -> Option<u64> {
+        self.0.keys().next().copied()
+    }
+
+    fn ranges(&self) -> Vec<Range<u64>> {
+        self.0.iter().map(|(&start, &end)| start..end).collect()
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    high_water: u64,
+    /// Reserved, neither written nor released.
+    outstanding: RangeSet,
+    /// Released by producers, not yet voided by the writer.
+    released: RangeSet,
+    voided: Vec<Range<u64>>,
+}
+
+// ── Allocator ───────────────────────────────────────────────────────
+
+/// Hands out contiguous sequence ranges. Share it between producers and
+/// the journal writer behind an `Arc`.
+#[derive(Debug)]
+pub struct SequenceAllocator {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+impl SequenceAllocator {
+    /// Open the allocator persisted at `path`, creating it (starting at
+    /// [`FIRST_SEQUENCE`]) if the file does not exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, AllocatorError> {
+        let path = path.into();
+        let high_water = match fs::read(&path) {
+            Ok(data) => {
+                let file: StateFile = serde_json::from_slice(&data)
+                    .map_err(|e| AllocatorError::Corrupt(format!("{}: {}", path.display(), e)))?;
+                file.high_water
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                persist(&path, FIRST_SEQUENCE)?;
+                FIRST_SEQUENCE
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(State {
+                high_water,
+                outstanding: RangeSet::default(),
+                released: RangeSet::default(),
+                voided: Vec::new(),
+            }),
+        })
+    }
+
+    /// Reserve the next `n` sequences. The range is durable once returned.
+    pub fn reserve(&self, n: u64) -> Result<Range<u64>, AllocatorError> {
+        let mut state = self.lock();
+        let end = state.high_water.checked_add(n).ok_or(AllocatorError::Exhausted {
+            high_water: state.high_water,
+            requested: n,
+        })?;
+        let range = state.high_water..end;
+        if n > 0 {
+            persist(&self.path, range.end)?;
+            state.high_water = range.end;
+            state.outstanding.insert(range.clone());
+        }
+        Ok(range)
+    }
+
+    /// Give back reserved sequences that will not be written. The writer
+    /// voids them when it gets there.
+    pub fn release(&self, range: Range<u64>) -> Result<(), AllocatorError> {
+        let mut state = self.lock();
+        if !state.outstanding.contains(&range) {
+            return Err(AllocatorError::NotReserved {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        state.outstanding.remove(&range);
+        state.released.insert(range);
+        Ok(())
+    }
+
+    /// First sequence never handed out.
+    pub fn high_water(&self) -> u64 {
+        self.lock().high_water
+    }
+
+    /// Reservations of this process neither written nor released yet. One
+    /// that stays here while later sequences are written is a leak.
+    pub fn outstanding(&self) -> Vec<Range<u64>> {
+        self.lock().outstanding.ranges()
+    }
+
+    /// Ranges voided in the journal so far, including leaks from before a
+    /// restart.
+    pub fn voided(&self) -> Vec<Range<u64>> {
+        self.lock().voided.clone()
+    }
+
+    // ── Writer Hooks ────────────────────────────────────────────────
+
+    /// Check that `range` was handed out (it is below the high-water mark).
+    pub(crate) fn check_issued(&self, range: &Range<u64>) -> Result<(), AllocatorError> {
+        if range.end > self.lock().high_water {
+            return Err(AllocatorError::NotReserved {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        Ok(())
+    }
+
+    /// Record that the writer wrote `range`.
+    pub(crate) fn mark_written(&self, range: &Range<u64>) {
+        self.lock().outstanding.remove(range);
+    }
+
+    /// If all of `range` was released, take it over for voiding.
+    pub(crate) fn take_released(&self, range: &Range<u64>) -> bool {
+        let mut state = self.lock();
+        if range.is_empty() || !state.released.contains(range) {
+            return false;
+        }
+        state.released.remove(range);
+        state.voided.push(range.clone());
+        true
+    }
+
+    /// Line up with a journal whose next sequence is `journal_next`.
+    ///
+    /// Returns the leaked range to void: sequences reserved before the
+    /// restart (below this process's first reservation) but never written.
+    /// A journal ahead of the state file moves the high-water mark past it.
+    pub(crate) fn reconcile(&self, journal_next: u64) -> Result<Range<u64>, AllocatorError> {
+        let mut state = self.lock();
+        if journal_next > state.high_water {
+            persist(&self.path, journal_next)?;
+            state.high_water = journal_next;
+        }
+        let leak_end = state
+            .outstanding
+            .first_start()
+            .unwrap_or(state.high_water)
+            .max(journal_next);
+        let leaked = journal_next..leak_end;
+        if !leaked.is_empty() {
+            state.released.remove(&leaked);
+            state.voided.push(leaked.clone());
+        }
+        Ok(leaked)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("allocator state poisoned")
+    }
+}
+
+/// Durably replace the state file with `high_water`.
+fn persist(path: &Path, high_water: u64) -> Result<(), AllocatorError> {
+    let data = serde_json::to_vec(&StateFile { high_water })
+        .map_err(|e| AllocatorError::Corrupt(e.to_string()))?;
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reservations_are_contiguous_and_survive_reopen() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("sequence.state");
+        let allocator = SequenceAllocator::open(&path).unwrap();
+        assert_eq!(allocator.reserve(10).unwrap(), 1..11);
+        assert_eq!(allocator.reserve(0).unwrap(), 11..11);
+        assert_eq!(allocator.reserve(5).unwrap(), 11..16);
+        assert_eq!(allocator.outstanding(), vec![1..11, 11..16]);
+        drop(allocator);
+
+        let reopened = SequenceAllocator::open(&path).unwrap();
+        assert_eq!(reopened.high_water(), 16);
+        assert!(reopened.outstanding().is_empty());
+        assert_eq!(reopened.reserve(1).unwrap(), 16..17);
+    }
+
+    #[test]
+    fn test_release_must_be_reserved() {
+        let tmp = TempDir::new().unwrap();
+        let allocator = SequenceAllocator::open(tmp.path().join("sequence.state")).unwrap();
+        allocator.reserve(10).unwrap();
+        allocator.mark_written(&(1..4));
+
+        allocator.release(6..8).unwrap();
+        assert_eq!(allocator.outstanding(), vec![4..6, 8..11]);
+        for bad in [2..5, 6..7, 10..12] {
+            assert!(matches!(
+                allocator.release(bad),
+                Err(AllocatorError::NotReserved { .. })
+            ));
+        }
+        assert!(!allocator.take_released(&(5..8)));
+        assert!(allocator.take_released(&(6..8)));
+        assert_eq!(allocator.voided(), vec![6..8]);
+    }
+
+    #[test]
+    fn test_reconcile_voids_leaks_and_catches_up() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("sequence.state");
+        SequenceAllocator::open(&path).unwrap().reserve(20).unwrap();
+
+        // Journal ends at 12: 13..21 leaked, this process's reservations stay
+        let allocator = SequenceAllocator::open(&path).unwrap();
+        assert_eq!(allocator.reconcile(13).unwrap(), 13..21);
+        assert_eq!(allocator.voided(), vec![13..21]);
+
+        // A journal ahead of the state file is never overtaken
+        let ahead = SequenceAllocator::open(&path).unwrap();
+        assert_eq!(ahead.reconcile(40).unwrap(), 40..40);
+        assert_eq!(ahead.reserve(1).unwrap(), 40..41);
+        assert_eq!(SequenceAllocator::open(&path).unwrap().high_water(), 41);
+    }
+
+    #[test]
+    fn test_reservation_past_u64_max_is_refused() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("sequence.state");
+        persist(&path, u64::MAX - 10).unwrap();
+        let allocator = SequenceAllocator::open(&path).unwrap();
+        assert_eq!(allocator.reserve(10).unwrap(), u64::MAX - 10..u64::MAX);
+        assert!(matches!(
+            allocator.reserve(1),
+            Err(AllocatorError::Exhausted { high_water: u64::MAX, requested: 1 })
+        ));
+        assert_eq!(allocator.reserve(0).unwrap(), u64::MAX..u64::MAX);
+        assert_eq!(SequenceAllocator::open(&path).unwrap().high_water(), u64::MAX);
+    }
+
+    #[test]
+    fn test_corrupt_state_file_is_reported() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("sequence.state");
+        fs::write(&path, b"{\"high_wa").unwrap();
+        assert!(matches!(
+            SequenceAllocator::open(&path),
+            Err(AllocatorError::Corrupt(_))
+        ));
+    }
+}
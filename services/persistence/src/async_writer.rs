@@ -0,0 +1,400 @@
+//! Async Journal Writer — non-blocking appends through a bounded channel
+//!
+//! Keeps disk latency off the caller's thread (spec §10.8 WAL). A dedicated
+//! writer thread owns the [`JournalWriter`], drains the channel in group
+//! commits via [`JournalWriter::append_batch`] and applies the configured
+//! flush/fsync/rotation policies on the background side.
+//!
+//! An [`Ack`] only means the entry was queued; durability is tracked by the
+//! fsynced watermark ([`AsyncJournalWriter::durable_sequence`]) and the
+//! [`AsyncJournalWriter::flush_and_wait`] barrier. If the process dies, the
+//! journal holds a gapless prefix of what was acked.
+
+use crate::journal::{JournalEntry, JournalWriter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use thiserror::Error;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+/// Why an entry (or a flush) was not accepted.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum Backpressure {
+    #[error("Journal queue full: {capacity} entries pending")]
+    QueueFull { capacity: usize },
+
+    #[error("Journal writer stopped: {reason}")]
+    Stopped { reason: String },
+}
+
+// ── Configuration ───────────────────────────────────────────────────
+
+/// What `submit` does when the channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Block the caller until the writer thread frees a slot.
+    Block,
+    /// Return [`Backpressure::QueueFull`] immediately.
+    Reject,
+}
+
+/// Configuration for the async journal writer.
+#[derive(Debug, Clone)]
+pub struct AsyncWriterConfig {
+    /// Bounded channel capacity in entries (default 4096).
+    pub capacity: usize,
+    /// Behavior when the channel is full (default `Block`).
+    pub backpressure: BackpressurePolicy,
+    /// Maximum entries per group commit (default 256).
+    pub max_batch: usize,
+}
+
+impl Default for AsyncWriterConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+            backpressure: BackpressurePolicy::Block,
+            max_batch: 256,
+        }
+    }
+}
+
+/// Receipt for a queued entry. Not a durability guarantee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ack {
+    /// Sequence number of the queued entry.
+    pub sequence: u64,
+}
+
+// ── Async Journal Writer ────────────────────────────────────────────
+
+enum Command {
+    Append(JournalEntry),
+    Flush(mpsc::Sender<Result<u64, String>>),
+}
+
+/// State shared with the writer thread.
+struct Shared {
+    /// Last fsynced sequence (0 = nothing yet).
+    durable: AtomicU64,
+    /// First error hit by the writer thread; it stops after one.
+    failure: Mutex<Option<String>>,
+}
+
+/// Journal writer running on a dedicated thread behind a bounded channel.
+///
+/// Dropping it drains the queue, syncs and joins the writer thread.
+pub struct AsyncJournalWriter {
+    tx: Option<SyncSender<Command>>,
+    handle: Option<JoinHandle<()>>,
+    shared: Arc<Shared>,
+    config: AsyncWriterConfig,
+}
+
+impl AsyncJournalWriter {
+    /// Move `writer` (already positioned via `set_next_sequence`) onto a
+    /// background thread.
+    pub fn spawn(writer: JournalWriter, config: AsyncWriterConfig) -> Self {
+        let (tx, rx) = mpsc::sync_channel(config.capacity.max(1));
+        let shared = Arc::new(Shared {
+            durable: AtomicU64::new(writer.last_fsynced_sequence()),
+            failure: Mutex::new(None),
+        });
+        let thread_shared = Arc::clone(&shared);
+        let max_batch = config.max_batch.max(1);
+        let handle = thread::Builder::new()
+            .name("journal-writer".into())
+            .spawn(move || run(writer, rx, max_batch, &thread_shared))
+            .expect("spawn journal writer thread");
+
+        Self {
+            tx: Some(tx),
+            handle: Some(handle),
+            shared,
+            config,
+        }
+    }
+
+    /// Queue an entry for writing.
+    ///
+    /// Sequences must be contiguous; a gap stops the writer thread, after
+    /// which every call returns [`Backpressure::Stopped`].
+    pub fn submit(&self, entry: JournalEntry) -> Result<Ack, Backpressure> {
+        let sequence = entry.sequence;
+        let tx = self.tx.as_ref().expect("sender lives until drop");
+        let command = Command::Append(entry);
+        match self.config.backpressure {
+            BackpressurePolicy::Block => tx.send(command).map_err(|_| self.stopped())?,
+            BackpressurePolicy::Reject => match tx.try_send(command) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    return Err(Backpressure::QueueFull {
+                        capacity: self.config.capacity,
+                    })
+                }
+                Err(TrySendError::Disconnected(_)) => return Err(self.stopped()),
+            },
+        }
+        Ok(Ack { sequence })
+    }
+
+    /// Barrier: wait until everything submitted before this call is written
+    /// and fsynced. Returns the highest durably synced sequence.
+    ///
+    /// Always blocks for a queue slot, whatever the backpressure policy.
+    pub fn flush_and_wait(&self) -> Result<u64, Backpressure> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let tx = self.tx.as_ref().expect("sender lives until drop");
+        tx.send(Command::Flush(reply_tx))
+            .map_err(|_| self.stopped())?;
+        match reply_rx.recv() {
+            Ok(Ok(durable)) => Ok(durable),
+            Ok(Err(reason)) => Err(Backpressure::Stopped { reason }),
+            Err(_) => Err(self.stopped()),
+        }
+    }
+
+    /// Drain the queue, sync and join the writer thread, for shutdown.
+    /// Returns the final durable sequence, or the error that stopped the
+    /// writer (entries queued after it were not written).
+    pub fn shutdown(mut self) -> Result<u64, Backpressure> {
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        match self.shared.failure.lock().unwrap().clone() {
+            Some(reason) => Err(Backpressure::Stopped { reason }),
+            None => Ok(self.durable_sequence()),
+        }
+    }
+
+    /// Last sequence known durable on disk (0 = nothing yet).
+    pub fn durable_sequence(&self) -> u64 {
+        self.shared.durable.load(Ordering::Acquire)
+    }
+
+    fn stopped(&self) -> Backpressure {
+        let reason = self
+            .shared
+            .failure
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| "writer thread exited".into());
+        Backpressure::Stopped { reason }
+    }
+}
+
+impl Drop for AsyncJournalWriter {
+    fn drop(&mut self) {
+        // Closing the channel lets the thread drain, sync and exit
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Writer thread: group-commit queued entries until the channel closes.
+fn run(mut writer: JournalWriter, rx: Receiver<Command>, max_batch: usize, shared: &Shared) {
+    let mut batch = Vec::with_capacity(max_batch);
+    let mut waiters = Vec::new();
+
+    while let Ok(command) = rx.recv() {
+        let mut next = Some(command);
+        while let Some(command) = next.take() {
+            match command {
+                Command::Append(entry) => batch.push(entry),
+                Command::Flush(reply) => waiters.push(reply),
+            }
+            if batch.len() < max_batch {
+                next = rx.try_recv().ok();
+            }
+        }
+
+        let mut result = writer.append_batch(&batch).map(|_| ());
+        batch.clear();
+        if result.is_ok() && !waiters.is_empty() {
+            result = writer.sync();
+        }
+        let durable = writer.last_fsynced_sequence();
+        shared.durable.store(durable, Ordering::Release);
+
+        match result {
+            Ok(()) => {
+                for reply in waiters.drain(..) {
+                    let _ = reply.send(Ok(durable));
+                }
+            }
+            Err(err) => {
+                let reason = err.to_string();
+                *shared.failure.lock().unwrap() = Some(reason.clone());
+                for reply in waiters.drain(..) {
+                    let _ = reply.send(Err(reason.clone()));
+                }
+                return;
+            }
+        }
+    }
+
+    // Channel closed: make whatever was written durable before exiting
+    match writer.sync() {
+        Ok(()) => shared
+            .durable
+            .store(writer.last_fsynced_sequence(), Ordering::Release),
+        Err(err) => *shared.failure.lock().unwrap() = Some(err.to_string()),
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalConfig;
+    use crate::reader::JournalReader;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn entry(seq: u64) -> JournalEntry {
+        JournalEntry::new(
+            seq,
+            seq as i64 * 1_000,
+            "OrderAccepted".into(),
+            vec![seq as u8; 8],
+        )
+    }
+
+    /// Writer whose durable callback parks the writer thread until released
+    fn gated_writer(dir: &std::path::Path) -> (JournalWriter, Receiver<()>, SyncSender<()>) {
+        let mut writer = JournalWriter::open(JournalConfig::new(dir)).unwrap();
+        writer.set_next_sequence(1);
+        let (entered_tx, entered_rx) = mpsc::sync_channel(16);
+        let (release_tx, release_rx) = mpsc::sync_channel::<()>(16);
+        let mut gated = true;
+        writer.set_durable_callback(move |_| {
+            if gated {
+                entered_tx.send(()).unwrap();
+                gated = release_rx.recv().is_err();
+            }
+        });
+        (writer, entered_rx, release_tx)
+    }
+
+    #[test]
+    fn test_submit_and_flush_and_wait() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = JournalWriter::open(JournalConfig::new(tmp.path())).unwrap();
+        writer.set_next_sequence(1);
+        let journal = AsyncJournalWriter::spawn(writer, AsyncWriterConfig::default());
+
+        for seq in 1..=500 {
+            assert_eq!(journal.submit(entry(seq)).unwrap(), Ack { sequence: seq });
+        }
+        assert_eq!(journal.flush_and_wait().unwrap(), 500);
+        assert_eq!(journal.durable_sequence(), 500);
+        drop(journal);
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(reader.read_all_validated().unwrap().len(), 500);
+    }
+
+    #[test]
+    fn test_shutdown_writes_pending_entries() {
+        let tmp = TempDir::new().unwrap();
+        let (writer, entered, release) = gated_writer(tmp.path());
+        let journal = AsyncJournalWriter::spawn(writer, AsyncWriterConfig::default());
+
+        // The first batch parks the writer; the rest are still queued
+        journal.submit(entry(1)).unwrap();
+        entered.recv().unwrap();
+        for seq in 2..=50 {
+            journal.submit(entry(seq)).unwrap();
+        }
+        release.send(()).unwrap();
+        assert_eq!(journal.shutdown().unwrap(), 50);
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(reader.read_all_validated().unwrap().len(), 50);
+    }
+
+    #[test]
+    fn test_reject_policy_errors_when_full() {
+        let tmp = TempDir::new().unwrap();
+        let (writer, entered, release) = gated_writer(tmp.path());
+        let config = AsyncWriterConfig {
+            capacity: 2,
+            backpressure: BackpressurePolicy::Reject,
+            ..AsyncWriterConfig::default()
+        };
+        let journal = AsyncJournalWriter::spawn(writer, config);
+
+        // Entry 1 is being fsynced and the writer thread is parked
+        journal.submit(entry(1)).unwrap();
+        entered.recv().unwrap();
+        journal.submit(entry(2)).unwrap();
+        journal.submit(entry(3)).unwrap();
+        assert_eq!(
+            journal.submit(entry(4)),
+            Err(Backpressure::QueueFull { capacity: 2 })
+        );
+
+        release.send(()).unwrap();
+        assert_eq!(journal.flush_and_wait().unwrap(), 3);
+        journal.submit(entry(4)).unwrap();
+        assert_eq!(journal.flush_and_wait().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_block_policy_waits_for_space() {
+        let tmp = TempDir::new().unwrap();
+        let (writer, entered, release) = gated_writer(tmp.path());
+        let config = AsyncWriterConfig {
+            capacity: 2,
+            ..AsyncWriterConfig::default()
+        };
+        let journal = Arc::new(AsyncJournalWriter::spawn(writer, config));
+
+        journal.submit(entry(1)).unwrap();
+        entered.recv().unwrap();
+        journal.submit(entry(2)).unwrap();
+        journal.submit(entry(3)).unwrap();
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let blocked = Arc::clone(&journal);
+        let submitter = thread::spawn(move || {
+            let ack = blocked.submit(entry(4)).unwrap();
+            done_tx.send(ack).unwrap();
+        });
+        assert!(done_rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+        release.send(()).unwrap();
+        assert_eq!(done_rx.recv().unwrap(), Ack { sequence: 4 });
+        submitter.join().unwrap();
+        assert_eq!(journal.flush_and_wait().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_write_error_stops_writer() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = JournalWriter::open(JournalConfig::new(tmp.path())).unwrap();
+        writer.set_next_sequence(1);
+        let journal = AsyncJournalWriter::spawn(writer, AsyncWriterConfig::default());
+
+        journal.submit(entry(1)).unwrap();
+        assert_eq!(journal.flush_and_wait().unwrap(), 1);
+        journal.submit(entry(3)).unwrap(); // gap
+
+        let err = journal.flush_and_wait().unwrap_err();
+        assert!(
+            matches!(&err, Backpressure::Stopped { reason } if reason.contains("Sequence error"))
+        );
+        assert!(matches!(
+            journal.submit(entry(4)),
+            Err(Backpressure::Stopped { .. })
+        ));
+        assert_eq!(journal.durable_sequence(), 1);
+    }
+}
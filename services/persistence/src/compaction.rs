@@ -0,0 +1,539 @@
+//! Journal Compaction — reclaim segments made redundant by a snapshot
+//!
+//! Implements spec §11.6 (snapshots bound replay) on the storage side: once
+//! a snapshot covers sequence `S`, journal entries `<= S` are never replayed
+//! again and can be removed.
+//!
+//! - Whole segments whose last sequence is `<= S` are deleted (with their
+//!   index sidecars).
+//! - The one segment straddling `S` is rewritten without the covered prefix:
+//!   temp file, fsync, rename, directory fsync. Its index sidecar is dropped
+//!   (indexes are advisory; seeks fall back to a scan).
+//! - The newest segment is never touched, so a writer may keep appending to
+//!   it while compaction runs.
+//! - Every segment is checksum-validated first; a corrupt segment aborts the
+//!   run unless `force_keep_corrupt` is set, in which case it is kept as is.
+//! - Each run that changes anything appends a JSON line to
+//!   [`AUDIT_LOG`] describing what was removed.
+//! - Encrypted journals need `encryption_key`; the boundary segment is
+//!   then resealed under a fresh nonce rather than copied byte for byte.
+//! - A rewritten segment that had a file header gets a new one naming its
+//!   new first entry; legacy segments stay header-less.
+
+use crate::encryption::{self, EncryptionKey};
+use crate::header::{FileHeader, FileKind};
+use crate::index;
+use crate::reader::{CorruptionKind, JournalReader, ReaderError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Audit log file name, inside the journal directory.
+pub const AUDIT_LOG: &str = "compaction-audit.jsonl";
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum CompactionError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Reader error: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("Refusing to compact: segment {segment} failed validation: {detail}")]
+    CorruptSegment { segment: String, detail: String },
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(#[from] encryption::EncryptionError),
+}
+
+// ── Options / Report ────────────────────────────────────────────────
+
+/// Compaction options.
+#[derive(Debug, Clone, Default)]
+pub struct CompactionOptions {
+    /// Keep segments that fail validation instead of refusing to run
+    /// (the `--force-keep-corrupt` switch).
+    pub force_keep_corrupt: bool,
+    /// Key of an encrypted journal (`None` = plaintext).
+    pub encryption_key: Option<EncryptionKey>,
+}
+
+/// A segment deleted by compaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemovedSegment {
+    pub file: String,
+    /// Sequence range held by the segment (`None` if it was empty).
+    pub first_sequence: Option<u64>,
+    pub last_sequence: Option<u64>,
+    pub bytes: u64,
+}
+
+/// The boundary segment rewritten without its covered prefix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewrittenSegment {
+    pub file: String,
+    /// Entries dropped from the front of the segment.
+    pub dropped_entries: u64,
+    /// First sequence left in the segment.
+    pub first_kept_sequence: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Outcome of a compaction run; also the audit record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    pub snapshot_sequence: u64,
+    pub removed: Vec<RemovedSegment>,
+    pub rewritten: Option<RewrittenSegment>,
+    /// Corrupt segments left in place under `force_keep_corrupt`.
+    pub kept_corrupt: Vec<String>,
+    pub bytes_reclaimed: u64,
+}
+
+impl CompactionReport {
+    /// Whether the run changed anything on disk.
+    pub fn is_noop(&self) -> bool {
+        self.removed.is_empty() && self.rewritten.is_none()
+    }
+}
+
+// ── Compaction ──────────────────────────────────────────────────────
+
+/// Compact `journal_dir` up to `snapshot_sequence` with default options.
+pub fn compact(
+    journal_dir: &Path,
+    snapshot_sequence: u64,
+) -> Result<CompactionReport, CompactionError> {
+    compact_with(
+        journal_dir,
+        snapshot_sequence,
+        &CompactionOptions::default(),
+    )
+}
+
+/// Compact `journal_dir` up to `snapshot_sequence`.
+pub fn compact_with(
+    journal_dir: &Path,
+    snapshot_sequence: u64,
+    options: &CompactionOptions,
+) -> Result<CompactionReport, CompactionError> {
+    let files = JournalReader::discover_files(journal_dir)?;
+    let newest = files.len().saturating_sub(1);
+
+    // Validate everything before touching anything
+    let mut scans = Vec::with_capacity(files.len());
+    for (i, path) in files.iter().enumerate() {
+        let key = options.encryption_key.as_ref();
+        let scan = scan_segment(path, key, snapshot_sequence, i == newest)?;
+        if let Some(detail) = &scan.corruption {
+            if !options.force_keep_corrupt {
+                return Err(CompactionError::CorruptSegment {
+                    segment: file_name(path),
+                    detail: detail.clone(),
+                });
+            }
+        }
+        scans.push(scan);
+    }
+
+    let mut report = CompactionReport {
+        snapshot_sequence,
+        removed: Vec::new(),
+        rewritten: None,
+        kept_corrupt: Vec::new(),
+        bytes_reclaimed: 0,
+    };
+
+    for (i, scan) in scans.iter().enumerate() {
+        if scan.corruption.is_some() {
+            report.kept_corrupt.push(file_name(&scan.path));
+            continue;
+        }
+        if i == newest {
+            continue;
+        }
+        match &scan.cut {
+            // Nothing above the snapshot: the whole segment is covered
+            None => {
+                remove_segment(&scan.path)?;
+                report.bytes_reclaimed += scan.bytes;
+                report.removed.push(RemovedSegment {
+                    file: file_name(&scan.path),
+                    first_sequence: scan.first,
+                    last_sequence: scan.last,
+                    bytes: scan.bytes,
+                });
+            }
+            Some(cut) if cut.offset > scan.data_start => {
+                let key = options.encryption_key.as_ref();
+                // The rewritten segment starts at the cut entry
+                let preamble = match scan.header {
+                    Some(_) => FileHeader::new(FileKind::Journal, cut.timestamp, cut.sequence)
+                        .encode()
+                        .to_vec(),
+                    None => Vec::new(),
+                };
+                let bytes_after = rewrite_from(&scan.path, key, cut.offset, &preamble)?;
+                report.bytes_reclaimed += scan.bytes - bytes_after;
+                report.rewritten = Some(RewrittenSegment {
+                    file: file_name(&scan.path),
+                    dropped_entries: cut.dropped,
+                    first_kept_sequence: cut.sequence,
+                    bytes_before: scan.bytes,
+                    bytes_after,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    if !report.is_noop() {
+        sync_dir(journal_dir)?;
+        append_audit(journal_dir, &report)?;
+    }
+    Ok(report)
+}
+
+// ── Internal Helpers ────────────────────────────────────────────────
+
+/// First entry above the snapshot within a segment.
+struct Cut {
+    offset: u64,
+    sequence: u64,
+    timestamp: i64,
+    dropped: u64,
+}
+
+struct SegmentScan {
+    path: PathBuf,
+    bytes: u64,
+    /// Offset of the first frame (past any encryption and file header).
+    data_start: u64,
+    header: Option<FileHeader>,
+    first: Option<u64>,
+    last: Option<u64>,
+    cut: Option<Cut>,
+    corruption: Option<String>,
+}
+
+fn scan_segment(
+    path: &Path,
+    key: Option<&EncryptionKey>,
+    snapshot_sequence: u64,
+    newest: bool,
+) -> Result<SegmentScan, CompactionError> {
+    let bytes = fs::metadata(path)?.len();
+    let mut reader = JournalReader::open_file_with(path, key)?;
+    let mut scan = SegmentScan {
+        path: path.to_path_buf(),
+        bytes,
+        data_start: reader.current_offset(),
+        header: reader.segment_header().copied(),
+        first: None,
+        last: None,
+        cut: None,
+        corruption: None,
+    };
+
+    let mut dropped = 0u64;
+    loop {
+        let offset = reader.current_offset();
+        match reader.next_entry() {
+            Ok(Some(entry)) => {
+                scan.first.get_or_insert(entry.sequence);
+                scan.last = Some(entry.sequence);
+                if entry.sequence <= snapshot_sequence {
+                    dropped += 1;
+                } else if scan.cut.is_none() {
+                    scan.cut = Some(Cut {
+                        offset,
+                        sequence: entry.sequence,
+                        timestamp: entry.timestamp,
+                        dropped,
+                    });
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                scan.corruption = Some(err.to_string());
+                return Ok(scan);
+            }
+        }
+    }
+
+    // A torn tail on the newest segment is a write in progress
+    if let Some(record) = reader
+        .corruption_log()
+        .iter()
+        .find(|r| !(newest && r.kind == CorruptionKind::TruncatedEntry))
+    {
+        scan.corruption = Some(format!("{} at byte {}", record.detail, record.byte_offset));
+    }
+    Ok(scan)
+}
+
+fn remove_segment(path: &Path) -> Result<(), CompactionError> {
+    fs::remove_file(path)?;
+    let idx = index::index_path(path);
+    if idx.is_file() {
+        fs::remove_file(idx)?;
+    }
+    Ok(())
+}
+
+/// Atomically replace `path` with `preamble` and its frames from `offset`
+/// on, returning the new length.
+fn rewrite_from(
+    path: &Path,
+    key: Option<&EncryptionKey>,
+    offset: u64,
+    preamble: &[u8],
+) -> Result<u64, CompactionError> {
+    let tmp = path.with_extension("bin.compact.tmp");
+    let len = match key {
+        // Nonces depend on frame offsets, so frames are resealed
+        Some(key) => encryption::reseal_segment(path, offset, preamble, key, key, &tmp)?,
+        None => {
+            let mut src = File::open(path)?;
+            src.seek(SeekFrom::Start(offset))?;
+            let mut dst = File::create(&tmp)?;
+            dst.write_all(preamble)?;
+            let len = preamble.len() as u64 + io::copy(&mut src, &mut dst)?;
+            dst.sync_all()?;
+            len
+        }
+    };
+    // Drop the stale index first: a crash in between leaves a correct
+    // segment without an index rather than an index with wrong offsets
+    let idx = index::index_path(path);
+    if idx.is_file() {
+        fs::remove_file(idx)?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(len)
+}
+
+fn sync_dir(dir: &Path) -> Result<(), CompactionError> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+fn append_audit(dir: &Path, report: &CompactionReport) -> Result<(), CompactionError> {
+    let line =
+        serde_json::to_string(report).map_err(|e| CompactionError::Serialization(e.to_string()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(AUDIT_LOG))?;
+    writeln!(file, "{}", line)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{FsyncPolicy, JournalConfig, JournalEntry, JournalWriter};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use tempfile::TempDir;
+
+    /// Encoded size of `entry(seq)`
+    const FRAME: u64 = 4 + 8 + 8 + 2 + 5 + 4 + 16 + 4;
+    const HDR: u64 = crate::header::HEADER_LEN as u64;
+
+    fn entry(seq: u64) -> JournalEntry {
+        JournalEntry::new(seq, seq as i64 * 1_000, "Trade".into(), vec![seq as u8; 16])
+    }
+
+    /// `count` entries, ten per segment
+    fn write_journal(dir: &Path, count: u64) {
+        let config = JournalConfig {
+            max_file_size: FRAME * 10,
+            index_interval: 4,
+            ..JournalConfig::new(dir)
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=count {
+            writer.append(&entry(seq)).unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    fn sequences(dir: &Path) -> Vec<u64> {
+        let mut reader = JournalReader::open(dir).unwrap();
+        reader
+            .read_all_validated()
+            .unwrap()
+            .iter()
+            .map(|e| e.sequence)
+            .collect()
+    }
+
+    fn segment(i: u64) -> String {
+        format!("journal-{:06}.bin", i)
+    }
+
+    #[test]
+    fn test_removes_covered_and_rewrites_boundary() {
+        let tmp = TempDir::new().unwrap();
+        write_journal(tmp.path(), 100);
+
+        let report = compact(tmp.path(), 35).unwrap();
+        let removed: Vec<&str> = report.removed.iter().map(|r| r.file.as_str()).collect();
+        assert_eq!(removed, vec![segment(0), segment(1), segment(2)]);
+        assert_eq!(report.removed[2].first_sequence, Some(21));
+        assert_eq!(report.removed[2].last_sequence, Some(30));
+        assert_eq!(
+            report.rewritten,
+            Some(RewrittenSegment {
+                file: segment(3),
+                dropped_entries: 5,
+                first_kept_sequence: 36,
+                bytes_before: HDR + FRAME * 10,
+                bytes_after: HDR + FRAME * 5,
+            })
+        );
+        assert_eq!(report.bytes_reclaimed, 3 * HDR + FRAME * 35);
+
+        assert!(!tmp.path().join(segment(0)).exists());
+        assert!(!tmp.path().join("journal-000000.idx").exists());
+        assert!(!tmp.path().join("journal-000003.idx").exists());
+        assert!(tmp.path().join("journal-000004.idx").exists());
+
+        // Reader sees a clean, gapless journal starting after the snapshot
+        assert_eq!(sequences(tmp.path()), (36..=100).collect::<Vec<_>>());
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(reader.seek_to_sequence(77).unwrap(), 41);
+        assert_eq!(reader.next_entry().unwrap().unwrap().sequence, 77);
+
+        // Audit record round-trips
+        let audit = fs::read_to_string(tmp.path().join(AUDIT_LOG)).unwrap();
+        let lines: Vec<&str> = audit.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let logged: CompactionReport = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(logged, report);
+
+        // Running again at the same point changes nothing
+        assert!(compact(tmp.path(), 35).unwrap().is_noop());
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(AUDIT_LOG))
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_boundary_at_segment_edge() {
+        let tmp = TempDir::new().unwrap();
+        write_journal(tmp.path(), 60);
+
+        let report = compact(tmp.path(), 40).unwrap();
+        assert_eq!(report.removed.len(), 4);
+        assert_eq!(report.rewritten, None);
+        assert_eq!(report.bytes_reclaimed, 4 * HDR + FRAME * 40);
+        assert_eq!(sequences(tmp.path()), (41..=60).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_newest_segment_is_never_touched() {
+        let tmp = TempDir::new().unwrap();
+        write_journal(tmp.path(), 25);
+
+        let report = compact(tmp.path(), 100).unwrap();
+        assert_eq!(report.removed.len(), 2);
+        assert_eq!(report.rewritten, None);
+        assert_eq!(sequences(tmp.path()), (21..=25).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_refuses_corrupt_segment_unless_forced() {
+        let tmp = TempDir::new().unwrap();
+        write_journal(tmp.path(), 50);
+        let path = tmp.path().join(segment(1));
+        let mut data = fs::read(&path).unwrap();
+        data[FRAME as usize * 3 + 35] ^= 0xFF;
+        fs::write(&path, &data).unwrap();
+
+        match compact(tmp.path(), 35) {
+            Err(CompactionError::CorruptSegment { segment: name, .. }) => {
+                assert_eq!(name, segment(1))
+            }
+            other => panic!("Expected CorruptSegment, got: {:?}", other),
+        }
+        assert!(tmp.path().join(segment(0)).exists());
+        assert!(!tmp.path().join(AUDIT_LOG).exists());
+
+        let options = CompactionOptions {
+            force_keep_corrupt: true,
+            ..Default::default()
+        };
+        let report = compact_with(tmp.path(), 35, &options).unwrap();
+        assert_eq!(report.kept_corrupt, vec![segment(1)]);
+        let removed: Vec<&str> = report.removed.iter().map(|r| r.file.as_str()).collect();
+        assert_eq!(removed, vec![segment(0), segment(2)]);
+        assert_eq!(report.rewritten.unwrap().first_kept_sequence, 36);
+        assert_eq!(fs::read(&path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_compaction_alongside_active_writer() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let durable = Arc::new(AtomicU64::new(0));
+
+        let watermark = Arc::clone(&durable);
+        let writer = thread::spawn(move || {
+            let config = JournalConfig {
+                max_file_size: FRAME * 50,
+                fsync_policy: FsyncPolicy::EveryN(10),
+                ..JournalConfig::new(&dir)
+            };
+            let mut writer = JournalWriter::open(config).unwrap();
+            writer.set_next_sequence(1);
+            for seq in 1..=6_000 {
+                writer.append(&entry(seq)).unwrap();
+                watermark.store(writer.last_fsynced_sequence(), Ordering::Release);
+            }
+            writer.sync().unwrap();
+        });
+
+        while durable.load(Ordering::Acquire) < 3_000 {
+            thread::yield_now();
+        }
+        let first = compact(tmp.path(), 1_525).unwrap();
+        assert_eq!(first.rewritten.as_ref().unwrap().first_kept_sequence, 1_526);
+        let second = compact(tmp.path(), 2_000).unwrap();
+        assert_eq!(second.rewritten, None);
+        writer.join().unwrap();
+
+        assert_eq!(sequences(tmp.path()), (2_001..=6_000).collect::<Vec<_>>());
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(AUDIT_LOG))
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Correlation IDs — following one request into the journal
+//!
+//! The gateway gives every request an id and forwards it to the services
+//! it calls. Events those services journal on the request's behalf are
+//! wrapped in [`Correlated`], so the id travels in the payload and an
+//! export can select everything a single request caused (see
+//! `ExportFilter::correlation_id`).
+//!
+//! The wrapper is part of the payload shape. Bincode payloads gain the id
+//! after the event and stay readable both ways, but a JSON payload becomes
+//! `{"event": …, "correlation_id": …}`, so a JSON-codec event type cannot
+//! switch between `T` and `Correlated<T>` without a new version.
+
+use crate::registry::JournalEvent;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A journaled event and the id of the request that caused it.
+///
+/// The id is encoded after the event and defaults to `None`: payloads
+/// journaled before it existed (the bare event) still decode, and bincode
+/// readers that predate it ignore the trailing bytes. Only a payload that
+/// ends right after the event decodes without an id; a damaged id is an
+/// error. Events the engine raises on its own, such as triggered stops,
+/// carry no id.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Correlated<T> {
+    pub event: T,
+    pub correlation_id: Option<String>,
+}
+
+impl<T> Correlated<T> {
+    pub fn new(event: T, correlation_id: Option<String>) -> Self {
+        Self {
+            event,
+            correlation_id,
+        }
+    }
+
+    /// No request behind the event.
+    pub fn uncorrelated(event: T) -> Self {
+        Self::new(event, None)
+    }
+}
+
+impl<T: JournalEvent> JournalEvent for Correlated<T> {
+    fn event_type(&self) -> &str {
+        self.event.event_type()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Correlated<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let binary = !deserializer.is_human_readable();
+        deserializer.deserialize_struct(
+            "Correlated",
+            &["event", "correlation_id"],
+            CorrelatedVisitor { binary, event: PhantomData },
+        )
+    }
+}
+
+struct CorrelatedVisitor<T> {
+    /// Binary formats cannot tell the end of the payload from a damaged id
+    /// without reading the option tag separately
+    binary: bool,
+    event: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for CorrelatedVisitor<T> {
+    type Value = Correlated<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an event followed by an optional correlation id")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let event = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        if !self.binary {
+            let correlation_id = seq.next_element::<Option<String>>()?.flatten();
+            return Ok(Correlated::new(event, correlation_id));
+        }
+
+        // A payload that ends before the id's option tag was journaled
+        // without one; failing anywhere after the tag is a damaged id
+        let tagged = Cell::new(false);
+        match seq.next_element_seed(TrailingId { tagged: &tagged }) {
+            Ok(correlation_id) => Ok(Correlated::new(event, correlation_id.flatten())),
+            Err(_) if !tagged.get() => Ok(Correlated::uncorrelated(event)),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut event = None;
+        let mut correlation_id = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "event" => event = Some(map.next_value()?),
+                "correlation_id" => correlation_id = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let event = event.ok_or_else(|| de::Error::missing_field("event"))?;
+        Ok(Correlated::new(event, correlation_id))
+    }
+}
+
+/// The id of a binary payload, read as the option tag and then the string
+/// so that running out of input before the tag can be told apart.
+struct TrailingId<'a> {
+    /// Set once the tag has been read
+    tagged: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for TrailingId<'_> {
+    type Value = Option<String>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de> Visitor<'de> for TrailingId<'_> {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an optional correlation id")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let tag: u8 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        self.tagged.set(true);
+        match tag {
+            0 => Ok(None),
+            1 => seq
+                .next_element()?
+                .map(Some)
+                .ok_or_else(|| de::Error::invalid_length(1, &self)),
+            tag => Err(de::Error::invalid_value(Unexpected::Unsigned(tag.into()), &"an option tag")),
+        }
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum Event {
+        Placed { order: u64, price: u64 },
+        Canceled(u64),
+    }
+
+    #[test]
+    fn test_bare_payloads_decode_without_an_id() {
+        let bare = bincode::serialize(&Event::Placed { order: 7, price: 100 }).unwrap();
+        let decoded: Correlated<Event> = bincode::deserialize(&bare).unwrap();
+        assert_eq!(decoded, Correlated::uncorrelated(Event::Placed { order: 7, price: 100 }));
+
+        let decoded: Correlated<Event> = serde_json::from_str(r#"{"event":{"Canceled":3}}"#).unwrap();
+        assert_eq!(decoded.correlation_id, None);
+    }
+
+    #[test]
+    fn test_id_roundtrips_and_older_readers_ignore_it() {
+        let event = Correlated::new(Event::Canceled(3), Some("req-1".into()));
+        let payload = bincode::serialize(&event).unwrap();
+        assert_eq!(bincode::deserialize::<Correlated<Event>>(&payload).unwrap(), event);
+        assert_eq!(bincode::deserialize::<Event>(&payload).unwrap(), Event::Canceled(3));
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["correlation_id"], "req-1");
+        assert_eq!(serde_json::from_value::<Correlated<Event>>(json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_a_damaged_id_is_an_error() {
+        let event = Correlated::new(Event::Canceled(3), Some("req-1".into()));
+        let payload = bincode::serialize(&event).unwrap();
+        // Event: variant u32 + u64; then the option tag, u64 length, bytes
+        let tag = 4 + 8;
+        let id = tag + 1 + 8;
+        assert_eq!(&payload[id..], b"req-1");
+
+        let mut bad_tag = payload.clone();
+        bad_tag[tag] = 7;
+        let mut bad_byte = payload.clone();
+        bad_byte[id + 1] = 0xFF;
+        for damaged in [bad_tag, bad_byte, payload[..id + 2].to_vec(), payload[..tag + 3].to_vec()] {
+            assert!(bincode::deserialize::<Correlated<Event>>(&damaged).is_err(), "{damaged:?}");
+        }
+
+        // An explicit `None` and a payload ending at the tag both mean no id
+        let none = bincode::serialize(&Correlated::uncorrelated(Event::Canceled(3))).unwrap();
+        assert_eq!(none.len(), tag + 1);
+        assert_eq!(bincode::deserialize::<Correlated<Event>>(&none).unwrap().correlation_id, None);
+        assert_eq!(bincode::deserialize::<Correlated<Event>>(&payload[..tag]).unwrap().correlation_id, None);
+
+        let json = r#"{"event":{"Canceled":3},"correlation_id":7}"#;
+        assert!(serde_json::from_str::<Correlated<Event>>(json).is_err());
+    }
+
+    #[test]
+    fn test_a_missing_event_is_an_error() {
+        assert!(bincode::deserialize::<Correlated<Event>>(&[]).is_err());
+        assert!(serde_json::from_str::<Correlated<Event>>(r#"{"correlation_id":"x"}"#).is_err());
+    }
+}
@@ -5,6 +5,8 @@
 //!
 //! Features:
 //! - Double replay: run identical replay twice, compare state hashes
+//! - Checkpointed double recovery with independently built appliers,
+//!   pinpointing the first divergent sequence (`verify_replay_determinism`)
 //! - Event output comparison across replay runs
 //! - Divergence alert with detailed diff
 //! - Property-based replay tests (proptest)
@@ -17,6 +19,8 @@ use crate::journal::{JournalConfig, JournalEntry, JournalWriter};
 use crate::reader::JournalReader;
 use crate::recovery::{DefaultEventApplier, EventApplier, RecoveryEngine};
 use crate::snapshot::EngineState;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::path::Path;
 
 // ── Divergence Report ───────────────────────────────────────────────
@@ -245,7 +249,7 @@ impl DeterminismVerifier {
                 "DiskFullTest".to_string(),
                 vec![seq as u8; 4],
             )) {
-                Ok(()) => written += 1,
+                Ok(_) => written += 1,
                 Err(_) => break,
             }
         }
@@ -283,6 +287,202 @@ impl DeterminismVerifier {
     }
 }
 
+// ── Checkpointed Double Replay ──────────────────────────────────────
+
+/// State hash after the entry at `sequence` in one replay run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub sequence: u64,
+    pub hash: String,
+}
+
+/// Where two replay runs first disagreed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayDivergence {
+    /// First sequence after which the two states hash differently.
+    pub sequence: u64,
+    /// Whether `sequence` was pinned down entry by entry. If the rerun
+    /// over the divergent interval agreed, it is the first divergent
+    /// checkpoint instead.
+    pub exact: bool,
+    /// Last checkpoint both runs agreed on (the snapshot sequence, or 0,
+    /// if there is none).
+    pub last_matching_sequence: u64,
+    pub hash_a: String,
+    pub hash_b: String,
+    /// The journal entry at `sequence`, the one whose apply diverged.
+    pub entry: Option<JournalEntry>,
+}
+
+/// Result of [`verify_replay_determinism`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeterminismReport {
+    pub checkpoint_every: u64,
+    /// Entries replayed per run.
+    pub entries_replayed: u64,
+    pub final_sequence: u64,
+    /// Checkpoints of the first run; the final state is always the last.
+    pub checkpoints: Vec<Checkpoint>,
+    /// `None` if every checkpoint matched.
+    pub divergence: Option<ReplayDivergence>,
+}
+
+impl DeterminismReport {
+    /// Whether both runs agreed at every checkpoint.
+    pub fn is_deterministic(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Run recovery twice over `journal_dir` (from the latest snapshot in
+/// `snapshot_dir`), each time with a fresh applier from `applier_factory`,
+/// and compare state hashes every `checkpoint_every` sequences (0 = final
+/// state only).
+///
+/// Appliers are built independently so per-instance nondeterminism
+/// (`HashMap` seeds, wall-clock reads) shows up. On a mismatch the interval
+/// since the last matching checkpoint is replayed twice more, hashing after
+/// every entry, to find the exact sequence.
+pub fn verify_replay_determinism<A, F>(
+    journal_dir: &Path,
+    snapshot_dir: &Path,
+    applier_factory: F,
+    checkpoint_every: u64,
+) -> Result<DeterminismReport, String>
+where
+    A: EventApplier,
+    F: Fn() -> A,
+{
+    let on_interval = |seq: u64| checkpoint_every > 0 && seq.is_multiple_of(checkpoint_every);
+    let run_a = checkpointed_recovery(journal_dir, snapshot_dir, applier_factory(), on_interval)?;
+    let run_b = checkpointed_recovery(journal_dir, snapshot_dir, applier_factory(), on_interval)?;
+
+    let mut report = DeterminismReport {
+        checkpoint_every,
+        entries_replayed: run_a.entries,
+        final_sequence: run_a.checkpoints.last().map_or(0, |c| c.sequence),
+        checkpoints: run_a.checkpoints.clone(),
+        divergence: None,
+    };
+    let Some((last_matching, a, b)) = first_mismatch(&run_a, &run_b) else {
+        return Ok(report);
+    };
+
+    // Narrow down within (last_matching, a.sequence]
+    let window = |seq: u64| seq > last_matching && seq <= a.sequence;
+    let fine_a = checkpointed_recovery(journal_dir, snapshot_dir, applier_factory(), window)?;
+    let fine_b = checkpointed_recovery(journal_dir, snapshot_dir, applier_factory(), window)?;
+    let exact = first_mismatch(&fine_a, &fine_b).filter(|(_, fine, _)| window(fine.sequence));
+    let (sequence, hash_a, hash_b, is_exact) = match exact {
+        Some((_, fine_a, fine_b)) => (fine_a.sequence, fine_a.hash, fine_b.hash, true),
+        None => (a.sequence, a.hash, b.hash, false),
+    };
+
+    report.divergence = Some(ReplayDivergence {
+        sequence,
+        exact: is_exact,
+        last_matching_sequence: last_matching,
+        hash_a,
+        hash_b,
+        entry: entry_at(journal_dir, sequence)?,
+    });
+    Ok(report)
+}
+
+/// Checkpoints of one recovery run.
+struct CheckpointRun {
+    /// State before the first replayed entry.
+    start: Checkpoint,
+    checkpoints: Vec<Checkpoint>,
+    entries: u64,
+}
+
+/// Applier wrapper hashing the state after every entry `record` selects.
+struct CheckpointRecorder<A, R> {
+    inner: A,
+    record: R,
+    start: RefCell<Option<Checkpoint>>,
+    checkpoints: RefCell<Vec<Checkpoint>>,
+}
+
+impl<A: EventApplier, R: Fn(u64) -> bool> EventApplier for CheckpointRecorder<A, R> {
+    fn apply(&self, state: &mut EngineState, entry: &JournalEntry) -> Result<(), String> {
+        self.start.borrow_mut().get_or_insert_with(|| Checkpoint {
+            sequence: entry.sequence.saturating_sub(1),
+            hash: state.compute_hash(),
+        });
+        self.inner.apply(state, entry)?;
+        if (self.record)(entry.sequence) {
+            self.checkpoints.borrow_mut().push(Checkpoint {
+                sequence: entry.sequence,
+                hash: state.compute_hash(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn checkpointed_recovery<A: EventApplier>(
+    journal_dir: &Path,
+    snapshot_dir: &Path,
+    applier: A,
+    record: impl Fn(u64) -> bool,
+) -> Result<CheckpointRun, String> {
+    let recorder = CheckpointRecorder {
+        inner: applier,
+        record,
+        start: RefCell::new(None),
+        checkpoints: RefCell::new(Vec::new()),
+    };
+    let mut engine = RecoveryEngine::new(snapshot_dir, journal_dir);
+    let (state, metrics) = engine
+        .recover_without_validation(&recorder)
+        .map_err(|e| format!("Recovery: {}", e))?;
+
+    let mut checkpoints = recorder.checkpoints.into_inner();
+    let start = recorder.start.into_inner().unwrap_or_else(|| Checkpoint {
+        sequence: metrics.final_sequence,
+        hash: metrics.final_state_hash.clone(),
+    });
+    if checkpoints.last().map(|c| c.sequence) != Some(metrics.final_sequence) {
+        checkpoints.push(Checkpoint {
+            sequence: metrics.final_sequence,
+            hash: state.compute_hash(),
+        });
+    }
+    Ok(CheckpointRun {
+        start,
+        checkpoints,
+        entries: metrics.replay_count,
+    })
+}
+
+/// First checkpoint where two runs differ, with the last sequence they
+/// still agreed on. Both runs checkpoint the same sequences, since they
+/// replay the same journal.
+fn first_mismatch(a: &CheckpointRun, b: &CheckpointRun) -> Option<(u64, Checkpoint, Checkpoint)> {
+    if a.start != b.start {
+        return Some((0, a.start.clone(), b.start.clone()));
+    }
+    let mut last_matching = a.start.sequence;
+    for (ca, cb) in a.checkpoints.iter().zip(&b.checkpoints) {
+        if ca != cb {
+            return Some((last_matching, ca.clone(), cb.clone()));
+        }
+        last_matching = ca.sequence;
+    }
+    None
+}
+
+fn entry_at(journal_dir: &Path, sequence: u64) -> Result<Option<JournalEntry>, String> {
+    let mut reader = JournalReader::open(journal_dir).map_err(|e| format!("Reader: {}", e))?;
+    reader
+        .seek_to_sequence(sequence)
+        .map_err(|e| format!("Seek: {}", e))?;
+    let entry = reader.next_entry().map_err(|e| format!("Read: {}", e))?;
+    Ok(entry.filter(|e| e.sequence == sequence))
+}
+
 // ── Tests ───────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -429,6 +629,92 @@ mod tests {
         .unwrap();
         assert!(report.is_match());
     }
+
+    /// Deterministic except at `Flaky` entries, where it records the
+    /// iteration order of a freshly seeded `HashMap`.
+    struct HashOrderApplier;
+
+    impl EventApplier for HashOrderApplier {
+        fn apply(&self, state: &mut EngineState, entry: &JournalEntry) -> Result<(), String> {
+            DefaultEventApplier.apply(state, entry)?;
+            if entry.event_type == "Flaky" {
+                let map: std::collections::HashMap<u32, ()> = (0..64).map(|k| (k, ())).collect();
+                let order: Vec<String> = map.keys().map(|k| k.to_string()).collect();
+                let record = state
+                    .balances
+                    .get_mut(&format!("__replay_seq_{}", entry.sequence))
+                    .unwrap();
+                record.locked = order.join(",");
+            }
+            Ok(())
+        }
+    }
+
+    fn write_flaky_journal(dir: &Path, count: u64, flaky: u64) {
+        let mut writer = JournalWriter::open(JournalConfig::new(dir)).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=count {
+            let event_type = if seq == flaky { "Flaky" } else { "Steady" };
+            writer
+                .write_event(seq, seq as i64 * 1_000, event_type.into(), vec![seq as u8])
+                .unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    #[test]
+    fn test_checkpointed_replay_pinpoints_divergent_sequence() {
+        let tmp = TempDir::new().unwrap();
+        let journal_dir = tmp.path().join("journal");
+        write_flaky_journal(&journal_dir, 100, 37);
+
+        let report = verify_replay_determinism(
+            &journal_dir,
+            &tmp.path().join("snapshots"),
+            || HashOrderApplier,
+            10,
+        )
+        .unwrap();
+
+        assert!(!report.is_deterministic());
+        assert_eq!(report.entries_replayed, 100);
+        let divergence = report.divergence.as_ref().unwrap();
+        assert_eq!(divergence.sequence, 37);
+        assert!(divergence.exact);
+        assert_eq!(divergence.last_matching_sequence, 30);
+        assert_ne!(divergence.hash_a, divergence.hash_b);
+        let entry = divergence.entry.as_ref().unwrap();
+        assert_eq!((entry.sequence, entry.event_type.as_str()), (37, "Flaky"));
+
+        // The report is serializable for CI artifacts
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: DeterminismReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_checkpointed_replay_from_snapshot_is_deterministic() {
+        let tmp = TempDir::new().unwrap();
+        let journal_dir = tmp.path().join("journal");
+        let snapshot_dir = tmp.path().join("snapshots");
+        write_journal(&journal_dir, 45);
+
+        let mut state = EngineState::empty();
+        let mut reader = JournalReader::open(&journal_dir).unwrap();
+        for entry in reader.read_all().unwrap().iter().take(12) {
+            DefaultEventApplier.apply(&mut state, entry).unwrap();
+        }
+        let engine = RecoveryEngine::new(&snapshot_dir, &journal_dir);
+        engine.take_snapshot(&state, 12, 0, false).unwrap();
+
+        let report =
+            verify_replay_determinism(&journal_dir, &snapshot_dir, || DefaultEventApplier, 10)
+                .unwrap();
+        assert!(report.is_deterministic());
+        assert_eq!(report.entries_replayed, 33);
+        let sequences: Vec<u64> = report.checkpoints.iter().map(|c| c.sequence).collect();
+        assert_eq!(sequences, vec![20, 30, 40, 45]);
+    }
 }
 
 // ── Property-Based Tests ────────────────────────────────────────────
@@ -0,0 +1,402 @@
+//! Encryption at Rest — AES-256-GCM for journal segments and snapshots
+//!
+//! Every encrypted file starts with a small header:
+//! ```text
+//! [magic:      8 bytes]  // "DEXENC" 0x00 0x01
+//! [nonce_base: 12 bytes] // random per file
+//! [key_check:  16 bytes] // GCM tag over nothing, proves the key
+//! ```
+//!
+//! - Journal frames keep their `[len][body]` shape; the body is sealed with
+//!   a nonce derived from the file nonce and the frame's byte offset, and
+//!   the length prefix is authenticated as associated data. An offset is
+//!   never sealed twice: after truncating an encrypted segment the journal
+//!   writer continues under a fresh nonce base.
+//! - A snapshot file is the header followed by one sealed blob.
+//! - Entry CRC32C and snapshot SHA-256 stay computed over plaintext, so a
+//!   wrong key ([`EncryptionError::DecryptionFailed`], caught by the header
+//!   check) is told apart from damaged ciphertext
+//!   ([`EncryptionError::Corrupt`]).
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// AES-256 key length in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// GCM authentication tag length in bytes.
+pub const TAG_LEN: usize = 16;
+
+/// Magic bytes opening every encrypted file.
+pub const MAGIC: [u8; 8] = *b"DEXENC\x00\x01";
+
+/// Encoded size of the file header.
+pub const HEADER_LEN: usize = MAGIC.len() + NONCE_LEN + TAG_LEN;
+
+/// Nonce position reserved for the header key check (never a file offset).
+const KEY_CHECK_POSITION: u64 = u64::MAX;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The key does not match the one the file was written with.
+    #[error("Decryption failed: {0}")]
+    DecryptionFailed(String),
+
+    /// The key is right but the ciphertext or header is damaged.
+    #[error("Encrypted data corrupt: {0}")]
+    Corrupt(String),
+
+    /// Encrypted and plaintext files where only one kind was expected.
+    #[error("Encryption mismatch: {0}")]
+    Mismatch(String),
+
+    #[error("Crypto error: {0}")]
+    Crypto(String),
+}
+
+// ── Key ─────────────────────────────────────────────────────────────
+
+/// AES-256 key. `Debug` does not print the key material.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    /// Wrap raw key bytes.
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Generate a random key.
+    pub fn generate() -> Result<Self, EncryptionError> {
+        let mut bytes = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| EncryptionError::Crypto("random key generation failed".into()))?;
+        Ok(Self(bytes))
+    }
+
+    fn aead_key(&self) -> LessSafeKey {
+        let unbound = UnboundKey::new(&AES_256_GCM, &self.0).expect("key length is fixed");
+        LessSafeKey::new(unbound)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+// ── File Cipher ─────────────────────────────────────────────────────
+
+/// Key and nonce base of one encrypted file.
+#[derive(Clone)]
+pub struct FileCipher {
+    key: LessSafeKey,
+    nonce_base: [u8; NONCE_LEN],
+}
+
+impl FileCipher {
+    /// Start a new file: pick a random nonce base and build its header.
+    pub fn create(key: &EncryptionKey) -> Result<(Self, [u8; HEADER_LEN]), EncryptionError> {
+        let mut nonce_base = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce_base)
+            .map_err(|_| EncryptionError::Crypto("random nonce generation failed".into()))?;
+        let cipher = Self {
+            key: key.aead_key(),
+            nonce_base,
+        };
+
+        let mut header = [0u8; HEADER_LEN];
+        header[..MAGIC.len()].copy_from_slice(&MAGIC);
+        header[MAGIC.len()..MAGIC.len() + NONCE_LEN].copy_from_slice(&nonce_base);
+        let tag = cipher
+            .key
+            .seal_in_place_separate_tag(cipher.nonce(KEY_CHECK_POSITION), Aad::from(MAGIC), &mut [])
+            .map_err(|_| EncryptionError::Crypto("sealing key check failed".into()))?;
+        header[MAGIC.len() + NONCE_LEN..].copy_from_slice(tag.as_ref());
+        Ok((cipher, header))
+    }
+
+    /// Open an existing file from its header, verifying the key.
+    pub fn from_header(key: &EncryptionKey, header: &[u8]) -> Result<Self, EncryptionError> {
+        if header.len() < HEADER_LEN || header[..MAGIC.len()] != MAGIC {
+            return Err(EncryptionError::Corrupt("invalid encryption header".into()));
+        }
+        let mut nonce_base = [0u8; NONCE_LEN];
+        nonce_base.copy_from_slice(&header[MAGIC.len()..MAGIC.len() + NONCE_LEN]);
+        let cipher = Self {
+            key: key.aead_key(),
+            nonce_base,
+        };
+
+        let mut key_check = [0u8; TAG_LEN];
+        key_check.copy_from_slice(&header[MAGIC.len() + NONCE_LEN..HEADER_LEN]);
+        cipher
+            .key
+            .open_in_place(cipher.nonce(KEY_CHECK_POSITION), Aad::from(MAGIC), &mut key_check)
+            .map_err(|_| EncryptionError::DecryptionFailed("key does not match file".into()))?;
+        Ok(cipher)
+    }
+
+    /// Encrypt a `[len][body]` journal frame written at `position`.
+    pub fn seal_frame(&self, position: u64, frame: &mut Vec<u8>) {
+        let sealed_len = (frame.len() - 4 + TAG_LEN) as u32;
+        let prefix = sealed_len.to_le_bytes();
+        frame[..4].copy_from_slice(&prefix);
+        let tag = self
+            .key
+            .seal_in_place_separate_tag(self.nonce(position), Aad::from(prefix), &mut frame[4..])
+            .expect("frame length fits AES-GCM limits");
+        frame.extend_from_slice(tag.as_ref());
+    }
+
+    /// Decrypt a sealed frame read at `position` back into `[len][body]`.
+    pub fn open_frame(&self, position: u64, frame: &mut Vec<u8>) -> Result<(), EncryptionError> {
+        if frame.len() < 4 + TAG_LEN {
+            return Err(EncryptionError::Corrupt(format!(
+                "sealed frame at byte {} too short",
+                position
+            )));
+        }
+        let mut prefix = [0u8; 4];
+        prefix.copy_from_slice(&frame[..4]);
+        let plain_len = self
+            .key
+            .open_in_place(self.nonce(position), Aad::from(prefix), &mut frame[4..])
+            .map_err(|_| {
+                EncryptionError::Corrupt(format!("frame at byte {} failed authentication", position))
+            })?
+            .len();
+        frame.truncate(4 + plain_len);
+        frame[..4].copy_from_slice(&(plain_len as u32).to_le_bytes());
+        Ok(())
+    }
+
+    /// Encrypt a whole-file payload (snapshots).
+    pub fn seal_blob(&self, mut data: Vec<u8>) -> Vec<u8> {
+        self.key
+            .seal_in_place_append_tag(self.nonce(0), Aad::empty(), &mut data)
+            .expect("snapshot length fits AES-GCM limits");
+        data
+    }
+
+    /// Decrypt a whole-file payload sealed by [`seal_blob`](Self::seal_blob).
+    pub fn open_blob(&self, mut data: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+        let plain_len = self
+            .key
+            .open_in_place(self.nonce(0), Aad::empty(), &mut data)
+            .map_err(|_| EncryptionError::Corrupt("payload failed authentication".into()))?
+            .len();
+        data.truncate(plain_len);
+        Ok(data)
+    }
+
+    /// Unique nonce per position: the file's nonce base XOR the offset.
+    fn nonce(&self, position: u64) -> Nonce {
+        let mut nonce = self.nonce_base;
+        for (byte, p) in nonce[NONCE_LEN - 8..].iter_mut().zip(position.to_be_bytes()) {
+            *byte ^= p;
+        }
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+// ── Header Detection ────────────────────────────────────────────────
+
+/// Whether data starts with the encryption magic.
+pub fn has_magic(data: &[u8]) -> bool {
+    data.len() >= MAGIC.len() && data[..MAGIC.len()] == MAGIC
+}
+
+/// Read the encryption header at the start of `file`, if it has one.
+///
+/// Leaves the file positioned after the header, or at the start for a
+/// plaintext file.
+pub fn read_header(file: &mut File) -> Result<Option<[u8; HEADER_LEN]>, EncryptionError> {
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(0))?;
+    if len < MAGIC.len() as u64 {
+        return Ok(None);
+    }
+    let mut header = [0u8; HEADER_LEN];
+    file.read_exact(&mut header[..MAGIC.len()])?;
+    if !has_magic(&header) {
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(None);
+    }
+    if len < HEADER_LEN as u64 {
+        return Err(EncryptionError::Corrupt("truncated encryption header".into()));
+    }
+    file.read_exact(&mut header[MAGIC.len()..])?;
+    Ok(Some(header))
+}
+
+/// Whether a file with `header` still has to be moved from `old` to `new`.
+///
+/// Fails with [`EncryptionError::DecryptionFailed`] if neither key opens it.
+pub fn needs_rotation(
+    header: &[u8],
+    old: &EncryptionKey,
+    new: &EncryptionKey,
+) -> Result<bool, EncryptionError> {
+    if FileCipher::from_header(new, header).is_ok() {
+        return Ok(false);
+    }
+    FileCipher::from_header(old, header)?;
+    Ok(true)
+}
+
+/// Check that every non-empty file is encrypted iff `encrypted` is set.
+pub fn check_files(files: &[impl AsRef<Path>], encrypted: bool) -> Result<(), EncryptionError> {
+    for path in files {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            continue;
+        }
+        let mut magic = [0u8; MAGIC.len()];
+        let is_encrypted = file.read_exact(&mut magic).is_ok() && has_magic(&magic);
+        if is_encrypted != encrypted {
+            return Err(EncryptionError::Mismatch(format!(
+                "{} is {}, expected {}",
+                path.display(),
+                if is_encrypted { "encrypted" } else { "plaintext" },
+                if encrypted { "encrypted" } else { "plaintext" },
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ── Resealing ───────────────────────────────────────────────────────
+
+/// Copy the journal frames of encrypted segment `src` from byte `from` on
+/// into a new segment `dst`, sealed under `new` with a fresh nonce base.
+///
+/// `preamble` (a file header, or empty) is written in the clear between the
+/// encryption header and the first frame. `from` must be a frame boundary
+/// (or 0 for a segment with no preamble). Frames keep their size, so data
+/// offsets after `from` shift uniformly. An
+/// incomplete trailing frame (torn write) is dropped; a frame failing
+/// authentication aborts. `dst` is fsynced; returns its length.
+pub fn reseal_segment(
+    src: &Path,
+    from: u64,
+    preamble: &[u8],
+    old: &EncryptionKey,
+    new: &EncryptionKey,
+    dst: &Path,
+) -> Result<u64, EncryptionError> {
+    let mut file = File::open(src)?;
+    let len = file.metadata()?.len();
+    let header = read_header(&mut file)?
+        .ok_or_else(|| EncryptionError::Mismatch(format!("{} is plaintext", src.display())))?;
+    let old_cipher = FileCipher::from_header(old, &header)?;
+    let (new_cipher, new_header) = FileCipher::create(new)?;
+
+    let mut pos = from.max(HEADER_LEN as u64);
+    file.seek(SeekFrom::Start(pos))?;
+    let mut input = BufReader::new(file);
+    let mut output = BufWriter::new(File::create(dst)?);
+    output.write_all(&new_header)?;
+    output.write_all(preamble)?;
+    let mut out_pos = (HEADER_LEN + preamble.len()) as u64;
+
+    let mut frame = Vec::new();
+    while len - pos >= 4 {
+        let mut prefix = [0u8; 4];
+        input.read_exact(&mut prefix)?;
+        let total = 4 + u32::from_le_bytes(prefix) as u64;
+        if total > len - pos {
+            break;
+        }
+        frame.clear();
+        frame.extend_from_slice(&prefix);
+        frame.resize(total as usize, 0);
+        input.read_exact(&mut frame[4..])?;
+
+        old_cipher.open_frame(pos, &mut frame)?;
+        new_cipher.seal_frame(out_pos, &mut frame);
+        output.write_all(&frame)?;
+        pos += total;
+        out_pos += frame.len() as u64;
+    }
+
+    output.flush()?;
+    output.get_ref().sync_all()?;
+    Ok(out_pos)
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::from_bytes([byte; KEY_LEN])
+    }
+
+    #[test]
+    fn test_frame_roundtrip_and_position_binding() {
+        let (cipher, header) = FileCipher::create(&key(1)).unwrap();
+        let plain = [&10u32.to_le_bytes()[..], b"0123456789"].concat();
+
+        let mut frame = plain.clone();
+        cipher.seal_frame(HEADER_LEN as u64, &mut frame);
+        assert_eq!(frame.len(), plain.len() + TAG_LEN);
+        assert_ne!(&frame[4..14], b"0123456789");
+
+        // Same frame replayed at another offset does not authenticate
+        let mut moved = frame.clone();
+        assert!(cipher.open_frame(100, &mut moved).is_err());
+
+        let reopened = FileCipher::from_header(&key(1), &header).unwrap();
+        reopened.open_frame(HEADER_LEN as u64, &mut frame).unwrap();
+        assert_eq!(frame, plain);
+    }
+
+    #[test]
+    fn test_wrong_key_fails_header_check() {
+        let (_, header) = FileCipher::create(&key(1)).unwrap();
+        assert!(matches!(
+            FileCipher::from_header(&key(2), &header),
+            Err(EncryptionError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_blob_is_corrupt_not_wrong_key() {
+        let (cipher, _) = FileCipher::create(&key(3)).unwrap();
+        let mut sealed = cipher.seal_blob(b"snapshot state".to_vec());
+        sealed[2] ^= 0x40;
+        assert!(matches!(
+            cipher.open_blob(sealed),
+            Err(EncryptionError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_nonce_base_differs_per_file() {
+        let (_, a) = FileCipher::create(&key(4)).unwrap();
+        let (_, b) = FileCipher::create(&key(4)).unwrap();
+        assert_ne!(a[MAGIC.len()..MAGIC.len() + NONCE_LEN], b[MAGIC.len()..MAGIC.len() + NONCE_LEN]);
+    }
+
+    #[test]
+    fn test_key_debug_is_redacted() {
+        assert_eq!(format!("{:?}", key(0xAB)), "EncryptionKey(..)");
+    }
+}
@@ -0,0 +1,648 @@
+//! Journal Export / Import — Line-delimited JSON for audit and tooling
+//!
+//! [`export_jsonl`] writes one JSON object per journal entry so the journal
+//! can be read without the binary format: sequence, timestamp, event type,
+//! payload as hex (plus a decoded form when a decoder is registered for the
+//! event type) and whether the checksum held. Corruption is never skipped
+//! silently: every record the reader logs is exported as a line with
+//! `"corrupt": true`, carrying the entry itself when its framing survived.
+//!
+//! [`import_jsonl`] goes the other way for building test fixtures: it
+//! writes binary segments from an export, recomputing every checksum.
+
+use crate::encryption::EncryptionKey;
+use crate::journal::{JournalConfig, JournalEntry, JournalError, JournalWriter};
+use crate::reader::{CorruptionKind, CorruptionRecord, JournalReader, ReaderError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use thiserror::Error;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Reader error: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("Journal error: {0}")]
+    Journal(#[from] JournalError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Invalid record on line {line}: {detail}")]
+    InvalidRecord { line: usize, detail: String },
+}
+
+// ── Filter / Options ────────────────────────────────────────────────
+
+/// Which entries to export. The default exports everything.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// First sequence to export (inclusive).
+    pub from_sequence: Option<u64>,
+    /// Last sequence to export (inclusive).
+    pub to_sequence: Option<u64>,
+    /// Event types to export (`None` = all).
+    pub event_types: Option<BTreeSet<String>>,
+    /// Only entries whose decoded payload carries this `correlation_id`
+    /// (see [`crate::correlation`]). Needs a decoder for the event types
+    /// that keeps the id at the top level, as a decoded `Correlated<T>`
+    /// does: `{"event": …, "correlation_id": …}`.
+    pub correlation_id: Option<String>,
+}
+
+impl ExportFilter {
+    /// Whether a record with the given (possibly unknown) sequence and
+    /// event type passes. Unknown fields never exclude a record, so
+    /// corruption that hides them is still exported.
+    fn accepts(&self, sequence: Option<u64>, event_type: Option<&str>) -> bool {
+        let in_range = sequence.is_none_or(|seq| {
+            self.from_sequence.is_none_or(|from| seq >= from)
+                && self.to_sequence.is_none_or(|to| seq <= to)
+        });
+        let type_ok = match (&self.event_types, event_type) {
+            (Some(types), Some(event_type)) => types.contains(event_type),
+            _ => true,
+        };
+        in_range && type_ok
+    }
+
+    /// Whether a record passes the correlation id filter. Valid entries
+    /// must name the id; corrupt ones are kept unless they name another.
+    fn correlates(&self, record: &ExportRecord) -> bool {
+        let Some(wanted) = &self.correlation_id else {
+            return true;
+        };
+        let found = record
+            .decoded
+            .as_ref()
+            .and_then(|decoded| decoded.get("correlation_id"))
+            .and_then(|id| id.as_str());
+        match found {
+            Some(id) => id == wanted,
+            None => record.corrupt,
+        }
+    }
+}
+
+/// Turns a payload into JSON for export.
+pub type PayloadDecoder = fn(&[u8]) -> Result<serde_json::Value, String>;
+
+/// Payload decoders keyed by event type.
+#[derive(Debug, Clone, Default)]
+pub struct PayloadDecoders {
+    decoders: BTreeMap<String, PayloadDecoder>,
+}
+
+impl PayloadDecoders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode payloads of `event_type` with `decoder`.
+    pub fn register(&mut self, event_type: impl Into<String>, decoder: PayloadDecoder) {
+        self.decoders.insert(event_type.into(), decoder);
+    }
+
+    fn get(&self, event_type: &str) -> Option<&PayloadDecoder> {
+        self.decoders.get(event_type)
+    }
+}
+
+/// Export options beyond the filter.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Key of an encrypted journal (`None` = plaintext).
+    pub encryption_key: Option<EncryptionKey>,
+    /// Decoders for the `decoded` field.
+    pub decoders: PayloadDecoders,
+}
+
+// ── Records / Reports ───────────────────────────────────────────────
+
+/// One line of an export.
+///
+/// Valid entries carry `sequence`, `timestamp`, `event_type` and `payload`.
+/// Corrupt lines carry `offset`, `kind` and `detail`, plus the entry fields
+/// that could still be read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    /// Raw payload, hex-encoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    /// Payload as decoded by the registered decoder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<serde_json::Value>,
+    /// Why the registered decoder rejected the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+    pub checksum_valid: bool,
+    #[serde(default)]
+    pub corrupt: bool,
+    /// Global byte offset of the corruption.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ExportRecord {
+    fn entry(entry: &JournalEntry, decoders: &PayloadDecoders) -> Self {
+        let (decoded, decode_error) = match decoders.get(&entry.event_type) {
+            Some(decode) => match decode(&entry.payload) {
+                Ok(value) => (Some(value), None),
+                Err(err) => (None, Some(err)),
+            },
+            None => (None, None),
+        };
+        Self {
+            sequence: Some(entry.sequence),
+            timestamp: Some(entry.timestamp),
+            event_type: Some(entry.event_type.clone()),
+            payload: Some(to_hex(&entry.payload)),
+            decoded,
+            decode_error,
+            checksum_valid: entry.verify_checksum(),
+            corrupt: false,
+            offset: None,
+            kind: None,
+            detail: None,
+        }
+    }
+
+    fn corrupt(
+        record: &CorruptionRecord,
+        entry: Option<&JournalEntry>,
+        decoders: &PayloadDecoders,
+    ) -> Self {
+        let base = match entry {
+            Some(entry) => Self::entry(entry, decoders),
+            None => Self {
+                sequence: None,
+                timestamp: None,
+                event_type: None,
+                payload: None,
+                decoded: None,
+                decode_error: None,
+                checksum_valid: false,
+                corrupt: false,
+                offset: None,
+                kind: None,
+                detail: None,
+            },
+        };
+        Self {
+            corrupt: true,
+            offset: Some(record.byte_offset),
+            kind: Some(format!("{:?}", record.kind)),
+            detail: Some(record.detail.clone()),
+            ..base
+        }
+    }
+}
+
+/// Summary of an export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportReport {
+    /// Valid entries written.
+    pub exported: u64,
+    /// Corrupt lines written.
+    pub corrupt: u64,
+}
+
+/// Summary of an import.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// Entries written to the journal.
+    pub imported: u64,
+    /// Lines marked corrupt, which are not imported.
+    pub skipped_corrupt: u64,
+    /// Sequence of the last entry written.
+    pub last_sequence: Option<u64>,
+}
+
+// ── Export ──────────────────────────────────────────────────────────
+
+/// Export the plaintext journal in `journal_dir` to `out_path`.
+pub fn export_jsonl(
+    journal_dir: &Path,
+    out_path: &Path,
+    filter: &ExportFilter,
+) -> Result<ExportReport, ExportError> {
+    export_jsonl_with(journal_dir, out_path, filter, &ExportOptions::default())
+}
+
+/// Export with explicit options (encryption key, payload decoders).
+pub fn export_jsonl_with(
+    journal_dir: &Path,
+    out_path: &Path,
+    filter: &ExportFilter,
+    options: &ExportOptions,
+) -> Result<ExportReport, ExportError> {
+    let mut reader = match &options.encryption_key {
+        Some(key) => JournalReader::open_encrypted(journal_dir, key)?,
+        None => JournalReader::open(journal_dir)?,
+    };
+    let mut out = BufWriter::new(File::create(out_path)?);
+    let mut report = ExportReport::default();
+    let mut logged = 0;
+
+    loop {
+        let result = reader.next_entry();
+        let rejected = reader.take_rejected();
+
+        // Records logged by this read, in the order they were hit
+        for record in &reader.corruption_log()[logged..] {
+            let entry = rejected
+                .as_ref()
+                .filter(|_| record.kind == CorruptionKind::ChecksumMismatch);
+            let line = ExportRecord::corrupt(record, entry, &options.decoders);
+            if filter.accepts(line.sequence, line.event_type.as_deref()) && filter.correlates(&line) {
+                write_line(&mut out, &line)?;
+                report.corrupt += 1;
+            }
+        }
+        logged = reader.corruption_log().len();
+
+        match result {
+            Ok(Some(entry)) => {
+                if filter.to_sequence.is_some_and(|to| entry.sequence > to) {
+                    break;
+                }
+                if filter.accepts(Some(entry.sequence), Some(&entry.event_type)) {
+                    let line = ExportRecord::entry(&entry, &options.decoders);
+                    if filter.correlates(&line) {
+                        write_line(&mut out, &line)?;
+                        report.exported += 1;
+                    }
+                }
+            }
+            Ok(None) => break,
+            // Logged above; the reader is already on the next frame
+            Err(ReaderError::ChecksumMismatch { .. } | ReaderError::Corruption { .. }) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    out.flush()?;
+    Ok(report)
+}
+
+fn write_line(out: &mut impl Write, record: &ExportRecord) -> Result<(), ExportError> {
+    serde_json::to_writer(&mut *out, record)
+        .map_err(|e| ExportError::Serialization(e.to_string()))?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+// ── Import ──────────────────────────────────────────────────────────
+
+/// Write the entries of export `in_path` into a journal at `config.dir`.
+///
+/// Checksums are recomputed. Lines marked corrupt are skipped and counted.
+/// Sequences must increase; gaps (e.g. from a filtered export) are kept.
+pub fn import_jsonl(in_path: &Path, config: JournalConfig) -> Result<ImportReport, ExportError> {
+    let input = BufReader::new(File::open(in_path)?);
+    let mut writer = JournalWriter::open(config)?;
+    let mut report = ImportReport::default();
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line_no = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |detail: String| ExportError::InvalidRecord {
+            line: line_no,
+            detail,
+        };
+        let record: ExportRecord =
+            serde_json::from_str(&line).map_err(|e| invalid(e.to_string()))?;
+        if record.corrupt {
+            report.skipped_corrupt += 1;
+            continue;
+        }
+
+        let (Some(sequence), Some(timestamp), Some(event_type), Some(payload)) = (
+            record.sequence,
+            record.timestamp,
+            record.event_type,
+            record.payload,
+        ) else {
+            return Err(invalid(
+                "missing sequence, timestamp, event_type or payload".to_string(),
+            ));
+        };
+        let payload = from_hex(&payload).map_err(invalid)?;
+        if report.last_sequence.is_some_and(|last| sequence <= last) {
+            return Err(invalid(format!(
+                "sequence {} does not follow {}",
+                sequence,
+                report.last_sequence.unwrap_or_default()
+            )));
+        }
+        if sequence != writer.next_sequence() {
+            writer.set_next_sequence(sequence);
+        }
+
+        writer.append(&JournalEntry::new(sequence, timestamp, event_type, payload))?;
+        report.imported += 1;
+        report.last_sequence = Some(sequence);
+    }
+
+    writer.sync()?;
+    Ok(report)
+}
+
+// ── Hex ─────────────────────────────────────────────────────────────
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+pub(crate) fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("odd-length hex payload ({} chars)", hex.len()));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("invalid hex at char {}", i))
+        })
+        .collect()
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn entries() -> Vec<JournalEntry> {
+        (1..=60u64)
+            .map(|seq| {
+                let event_type = if seq % 3 == 0 {
+                    "TradeExecuted"
+                } else {
+                    "OrderSubmitted"
+                };
+                // Every tenth payload is large enough to be stored compressed
+                let payload = if seq % 10 == 0 {
+                    vec![seq as u8; 4_096]
+                } else {
+                    (0..=seq as u8).rev().collect()
+                };
+                JournalEntry::new(seq, -5 + 1_000_003 * seq as i64, event_type.into(), payload)
+            })
+            .collect()
+    }
+
+    fn write_journal(dir: &Path, entries: &[JournalEntry]) -> Vec<PathBuf> {
+        let config = JournalConfig {
+            max_file_size: 2_000,
+            ..JournalConfig::new(dir)
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(entries[0].sequence);
+        for entry in entries {
+            writer.append(entry).unwrap();
+        }
+        writer.sync().unwrap();
+        JournalReader::discover_files(dir).unwrap()
+    }
+
+    fn lines(path: &Path) -> Vec<ExportRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip_preserves_entries() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst, jsonl) = (
+            tmp.path().join("src"),
+            tmp.path().join("dst"),
+            tmp.path().join("out.jsonl"),
+        );
+        let original = entries();
+        assert!(write_journal(&src, &original).len() > 1);
+
+        let report = export_jsonl(&src, &jsonl, &ExportFilter::default()).unwrap();
+        assert_eq!(
+            report,
+            ExportReport {
+                exported: 60,
+                corrupt: 0
+            }
+        );
+        assert!(lines(&jsonl).iter().all(|r| r.checksum_valid && !r.corrupt));
+
+        let config = JournalConfig {
+            max_file_size: 2_000,
+            ..JournalConfig::new(&dst)
+        };
+        let report = import_jsonl(&jsonl, config).unwrap();
+        assert_eq!(report.imported, 60);
+        assert_eq!(report.last_sequence, Some(60));
+
+        let reimported = JournalReader::open(&dst)
+            .unwrap()
+            .read_all_validated()
+            .unwrap();
+        assert_eq!(reimported, original);
+    }
+
+    #[test]
+    fn test_filter_by_range_and_event_type() {
+        let tmp = TempDir::new().unwrap();
+        let (src, jsonl) = (tmp.path().join("src"), tmp.path().join("out.jsonl"));
+        write_journal(&src, &entries());
+
+        let filter = ExportFilter {
+            from_sequence: Some(10),
+            to_sequence: Some(30),
+            event_types: Some(BTreeSet::from(["TradeExecuted".to_string()])),
+            correlation_id: None,
+        };
+        let report = export_jsonl(&src, &jsonl, &filter).unwrap();
+        assert_eq!(report.exported, 7);
+        let seqs: Vec<u64> = lines(&jsonl).iter().filter_map(|r| r.sequence).collect();
+        assert_eq!(seqs, vec![12, 15, 18, 21, 24, 27, 30]);
+
+        // A filtered export imports with its gaps intact
+        let dst = tmp.path().join("dst");
+        import_jsonl(&jsonl, JournalConfig::new(&dst)).unwrap();
+        let reimported = JournalReader::open(&dst).unwrap().read_all().unwrap();
+        assert_eq!(
+            reimported.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+            seqs
+        );
+    }
+
+    #[test]
+    fn test_corrupt_entries_are_marked_not_skipped() {
+        let tmp = TempDir::new().unwrap();
+        let (src, jsonl) = (tmp.path().join("src"), tmp.path().join("out.jsonl"));
+        let files = write_journal(&src, &entries());
+
+        // Flip the last payload byte of the first entry; tear the last one
+        let path = &files[0];
+        let mut data = fs::read(path).unwrap();
+        let hdr = crate::header::HEADER_LEN;
+        let first_len = 4 + u32::from_le_bytes(data[hdr..hdr + 4].try_into().unwrap()) as usize;
+        data[hdr + first_len - 1] ^= 0xff;
+        fs::write(path, &data).unwrap();
+        let last = files.last().unwrap();
+        let len = fs::metadata(last).unwrap().len();
+        fs::OpenOptions::new()
+            .write(true)
+            .open(last)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let report = export_jsonl(&src, &jsonl, &ExportFilter::default()).unwrap();
+        assert_eq!(
+            report,
+            ExportReport {
+                exported: 58,
+                corrupt: 2
+            }
+        );
+
+        let records = lines(&jsonl);
+        let bad = &records[0];
+        assert!(bad.corrupt && !bad.checksum_valid);
+        assert_eq!(bad.sequence, Some(1));
+        assert_eq!(bad.offset, Some(hdr as u64));
+        assert_eq!(bad.kind.as_deref(), Some("ChecksumMismatch"));
+        assert!(bad.payload.is_some());
+        let torn = records.last().unwrap();
+        assert!(torn.corrupt && torn.sequence.is_none());
+        assert_eq!(torn.kind.as_deref(), Some("TruncatedEntry"));
+
+        // The raw line spells the marker out
+        let raw = fs::read_to_string(&jsonl).unwrap();
+        assert!(raw.lines().next().unwrap().contains("\"corrupt\":true"));
+
+        let report = import_jsonl(&jsonl, JournalConfig::new(tmp.path().join("dst"))).unwrap();
+        assert_eq!(report.imported, 58);
+        assert_eq!(report.skipped_corrupt, 2);
+    }
+
+    #[test]
+    fn test_registered_decoder_fills_decoded() {
+        let tmp = TempDir::new().unwrap();
+        let (src, jsonl) = (tmp.path().join("src"), tmp.path().join("out.jsonl"));
+        write_journal(&src, &entries());
+
+        let mut options = ExportOptions::default();
+        options.decoders.register("OrderSubmitted", |payload| {
+            Ok(serde_json::json!({ "len": payload.len() }))
+        });
+        options
+            .decoders
+            .register("TradeExecuted", |_| Err("unsupported version".into()));
+        export_jsonl_with(&src, &jsonl, &ExportFilter::default(), &options).unwrap();
+
+        let records = lines(&jsonl);
+        assert_eq!(records[0].decoded, Some(serde_json::json!({ "len": 2 })));
+        assert_eq!(records[2].decoded, None);
+        assert_eq!(
+            records[2].decode_error.as_deref(),
+            Some("unsupported version")
+        );
+        // The hex payload is always there for import
+        assert_eq!(records[0].payload.as_deref(), Some("0100"));
+    }
+
+    #[test]
+    fn test_filter_by_correlation_id() {
+        use crate::correlation::Correlated;
+
+        let tmp = TempDir::new().unwrap();
+        let (src, jsonl) = (tmp.path().join("src"), tmp.path().join("out.jsonl"));
+        // Requests a, b and a again, then an event raised by the engine itself
+        let ids = [Some("req-a"), Some("req-b"), Some("req-a"), None];
+        let journal: Vec<JournalEntry> = ids
+            .iter()
+            .zip(1u64..)
+            .map(|(id, seq)| {
+                let event = Correlated::new(seq, id.map(String::from));
+                let payload = bincode::serialize(&event).unwrap();
+                JournalEntry::new(seq, seq as i64, "OrderSubmitted".into(), payload)
+            })
+            .collect();
+        write_journal(&src, &journal);
+
+        let filter = ExportFilter {
+            correlation_id: Some("req-a".into()),
+            ..ExportFilter::default()
+        };
+        let mut options = ExportOptions::default();
+        options.decoders.register("OrderSubmitted", |payload| {
+            let event: Correlated<u64> = bincode::deserialize(payload).map_err(|e| e.to_string())?;
+            serde_json::to_value(event).map_err(|e| e.to_string())
+        });
+        let report = export_jsonl_with(&src, &jsonl, &filter, &options).unwrap();
+        assert_eq!(report.exported, 2);
+        let seqs: Vec<u64> = lines(&jsonl).iter().filter_map(|r| r.sequence).collect();
+        assert_eq!(seqs, vec![1, 3]);
+
+        // Without a decoder no entry can be shown to match
+        let report = export_jsonl(&src, &jsonl, &filter).unwrap();
+        assert_eq!(report.exported, 0);
+    }
+
+    #[test]
+    fn test_import_rejects_bad_lines() {
+        let tmp = TempDir::new().unwrap();
+        let jsonl = tmp.path().join("in.jsonl");
+        let config = || JournalConfig::new(tmp.path().join("dst"));
+
+        fs::write(&jsonl, "{\"sequence\":1,\"timestamp\":1,\"event_type\":\"A\",\"payload\":\"0g\",\"checksum_valid\":true}\n").unwrap();
+        assert!(matches!(
+            import_jsonl(&jsonl, config()),
+            Err(ExportError::InvalidRecord { line: 1, .. })
+        ));
+
+        fs::write(
+            &jsonl,
+            "{\"sequence\":5,\"timestamp\":1,\"event_type\":\"A\",\"payload\":\"\",\"checksum_valid\":true}\n\
+             {\"sequence\":4,\"timestamp\":2,\"event_type\":\"A\",\"payload\":\"\",\"checksum_valid\":true}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            import_jsonl(&jsonl, JournalConfig::new(tmp.path().join("dst2"))),
+            Err(ExportError::InvalidRecord { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,264 @@
+//! File Header — magic, format version and creation metadata
+//!
+//! Journal segments and snapshot files written by this version open with a
+//! fixed-size header, placed after the encryption header when there is one
+//! (for snapshots it leads the plaintext, inside the sealed blob):
+//! ```text
+//! [magic:      8 bytes]  // "DEXJRNL" 0x00 or "DEXSNAP" 0x00
+//! [version:    u32]      // file format version
+//! [created_at: i64]      // exchange timestamp (ns) of the first record
+//! [sequence:   u64]      // first entry of a segment / snapshot sequence
+//! [checksum:   u32]      // CRC32C over the preceding 28 bytes
+//! ```
+//!
+//! - Files without the magic are legacy, header-less files and are read as
+//!   before. A legacy journal frame cannot start with the magic: its
+//!   length prefix would announce a frame of over 1 GiB.
+//! - The version is checked before the checksum, since a newer format may
+//!   lay out the rest differently. A newer version is reported as
+//!   [`HeaderError::UnsupportedVersion`], never as corruption.
+
+use crc32c::crc32c;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use thiserror::Error;
+
+/// File format version written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Encoded size of the file header.
+pub const HEADER_LEN: usize = 32;
+
+/// Magic bytes opening a journal segment.
+pub const JOURNAL_MAGIC: [u8; 8] = *b"DEXJRNL\x00";
+
+/// Magic bytes opening a snapshot file.
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"DEXSNAP\x00";
+
+const CHECKSUM_AT: usize = HEADER_LEN - 4;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum HeaderError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// Written by a newer build; the file is not damaged.
+    #[error("Unsupported {kind} format version {version} (this build reads up to {supported})")]
+    UnsupportedVersion {
+        kind: FileKind,
+        version: u32,
+        supported: u32,
+    },
+
+    /// Truncated, or the header checksum does not match.
+    #[error("Corrupt {kind} header: {detail}")]
+    Corrupt { kind: FileKind, detail: String },
+}
+
+// ── Header ──────────────────────────────────────────────────────────
+
+/// Which kind of file a header belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Journal,
+    Snapshot,
+}
+
+impl FileKind {
+    /// Magic bytes for this kind of file.
+    pub fn magic(self) -> [u8; 8] {
+        match self {
+            FileKind::Journal => JOURNAL_MAGIC,
+            FileKind::Snapshot => SNAPSHOT_MAGIC,
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileKind::Journal => "journal segment",
+            FileKind::Snapshot => "snapshot",
+        })
+    }
+}
+
+/// Decoded file header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub kind: FileKind,
+    pub version: u32,
+    /// Exchange timestamp (ns) of the first entry, or of the snapshot.
+    pub created_at: i64,
+    /// First sequence of a segment, or the sequence a snapshot covers.
+    pub sequence: u64,
+}
+
+impl FileHeader {
+    /// Header for a new file in the current format version.
+    pub fn new(kind: FileKind, created_at: i64, sequence: u64) -> Self {
+        Self {
+            kind,
+            version: FORMAT_VERSION,
+            created_at,
+            sequence,
+        }
+    }
+
+    /// Serialize the header, checksum included.
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut buf = [0u8; HEADER_LEN];
+        buf[..8].copy_from_slice(&self.kind.magic());
+        buf[8..12].copy_from_slice(&self.version.to_le_bytes());
+        buf[12..20].copy_from_slice(&self.created_at.to_le_bytes());
+        buf[20..28].copy_from_slice(&self.sequence.to_le_bytes());
+        let checksum = crc32c(&buf[..CHECKSUM_AT]);
+        buf[CHECKSUM_AT..].copy_from_slice(&checksum.to_le_bytes());
+        buf
+    }
+
+    /// Parse the header at the start of `data`.
+    ///
+    /// Returns `None` if `data` does not start with the magic for `kind`,
+    /// i.e. it is a legacy header-less file.
+    pub fn decode(kind: FileKind, data: &[u8]) -> Result<Option<Self>, HeaderError> {
+        if data.len() < 8 || data[..8] != kind.magic() {
+            return Ok(None);
+        }
+        let corrupt = |detail: &str| HeaderError::Corrupt {
+            kind,
+            detail: detail.to_string(),
+        };
+        if data.len() < 12 {
+            return Err(corrupt("truncated header"));
+        }
+        let version = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        if version > FORMAT_VERSION {
+            return Err(HeaderError::UnsupportedVersion {
+                kind,
+                version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        if data.len() < HEADER_LEN {
+            return Err(corrupt("truncated header"));
+        }
+        let stored = u32::from_le_bytes(data[CHECKSUM_AT..HEADER_LEN].try_into().unwrap());
+        if crc32c(&data[..CHECKSUM_AT]) != stored {
+            return Err(corrupt("header checksum mismatch"));
+        }
+        if version == 0 {
+            return Err(corrupt("format version 0"));
+        }
+        Ok(Some(Self {
+            kind,
+            version,
+            created_at: i64::from_le_bytes(data[12..20].try_into().unwrap()),
+            sequence: u64::from_le_bytes(data[20..28].try_into().unwrap()),
+        }))
+    }
+}
+
+/// What [`probe`] found at the start of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderProbe {
+    /// A valid header.
+    Present(FileHeader),
+    /// No magic: a legacy header-less file.
+    Absent,
+    /// Fewer bytes than a header, all consistent with one: a file whose
+    /// first write has not landed (or was torn).
+    Incomplete,
+}
+
+/// Look for the header of `kind` at byte `at` of `file`, which is `len`
+/// bytes long.
+///
+/// Leaves the file positioned after the header, or at `at` if there is
+/// none.
+pub fn probe<R: Read + Seek>(
+    file: &mut R,
+    kind: FileKind,
+    at: u64,
+    len: u64,
+) -> Result<HeaderProbe, HeaderError> {
+    let available = len.saturating_sub(at).min(HEADER_LEN as u64) as usize;
+    let mut buf = [0u8; HEADER_LEN];
+    file.seek(SeekFrom::Start(at))?;
+    file.read_exact(&mut buf[..available])?;
+    let data = &buf[..available];
+
+    let magic_len = available.min(8);
+    let probe = if available < HEADER_LEN && data[..magic_len] == kind.magic()[..magic_len] {
+        match FileHeader::decode(kind, data) {
+            // Enough of it to tell it comes from a newer build
+            Err(err @ HeaderError::UnsupportedVersion { .. }) => return Err(err),
+            _ => HeaderProbe::Incomplete,
+        }
+    } else {
+        FileHeader::decode(kind, data)?.map_or(HeaderProbe::Absent, HeaderProbe::Present)
+    };
+    if !matches!(probe, HeaderProbe::Present(_)) {
+        file.seek(SeekFrom::Start(at))?;
+    }
+    Ok(probe)
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> FileHeader {
+        FileHeader::new(FileKind::Journal, 1_708_000_000_000_000_000, 42)
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let bytes = sample().encode();
+        assert_eq!(
+            FileHeader::decode(FileKind::Journal, &bytes).unwrap(),
+            Some(sample())
+        );
+        // The other kind's magic is not a header of this kind
+        assert_eq!(
+            FileHeader::decode(FileKind::Snapshot, &bytes).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_legacy_data_has_no_header() {
+        assert_eq!(FileHeader::decode(FileKind::Journal, &[]).unwrap(), None);
+        let frame = [12u8, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(FileHeader::decode(FileKind::Journal, &frame).unwrap(), None);
+    }
+
+    #[test]
+    fn test_future_version_is_not_corruption() {
+        let mut bytes = sample().encode();
+        bytes[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            FileHeader::decode(FileKind::Journal, &bytes),
+            Err(HeaderError::UnsupportedVersion { version, .. }) if version == FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_tampered_or_truncated_header_is_corrupt() {
+        let mut bytes = sample().encode();
+        bytes[20] ^= 0x01;
+        assert!(matches!(
+            FileHeader::decode(FileKind::Journal, &bytes),
+            Err(HeaderError::Corrupt { .. })
+        ));
+        let bytes = sample().encode();
+        assert!(matches!(
+            FileHeader::decode(FileKind::Journal, &bytes[..HEADER_LEN - 1]),
+            Err(HeaderError::Corrupt { .. })
+        ));
+    }
+}
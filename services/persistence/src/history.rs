@@ -0,0 +1,655 @@
+//! Order & Trade History — cursor-paginated account queries
+//!
+//! An in-memory index of each account's orders and fills, maintained from
+//! the event stream and queried newest first (descending sequence):
+//! - [`HistoryIndex::record_order`] when an order is accepted
+//! - [`HistoryIndex::record_fill`] for each fill, which also records the trade
+//! - [`HistoryIndex::record_cancel`] when an order leaves the book
+//!
+//! Pages are addressed by opaque cursors encoding the `(timestamp, sequence)`
+//! of the last row returned. Rows are ordered by the sequence of the event
+//! that created them and status changes update rows in place, so a page
+//! boundary never moves under concurrent writes: new rows only ever appear
+//! ahead of the first page.
+//!
+//! Each account keeps at most `retention` orders and `retention` trades. A
+//! cursor whose row has been evicted is reported as expired rather than
+//! silently resuming further down.
+
+use crc32c::crc32c;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use types::ids::{AccountId, OrderId, TradeId};
+use types::order::{CancelReason, OrderStatus, Side};
+
+use crate::export::{from_hex, to_hex};
+
+/// Rows returned per page when the caller does not ask for a size.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Most rows returned per page, whatever the caller asks for.
+pub const MAX_PAGE_SIZE: usize = 500;
+/// Orders and trades kept per account by default.
+pub const DEFAULT_RETENTION: usize = 50_000;
+
+const CURSOR_VERSION: u8 = 1;
+const CURSOR_LEN: usize = 22;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum HistoryError {
+    #[error("Invalid cursor")]
+    InvalidCursor,
+
+    #[error("Cursor expired: rows up to seq={evicted_through} are no longer retained")]
+    CursorExpired { evicted_through: u64 },
+
+    #[error("Invalid page size 0: expected 1 to {max}", max = MAX_PAGE_SIZE)]
+    InvalidLimit,
+
+    #[error("Invalid time range: from={from} is after to={to}")]
+    InvalidRange { from: i64, to: i64 },
+}
+
+// ── Records ─────────────────────────────────────────────────────────
+
+/// An order as last seen in the event stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderRecord {
+    /// Sequence of the event that accepted the order.
+    pub sequence: u64,
+    /// When the order was accepted (Unix nanos).
+    pub timestamp: i64,
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub side: Side,
+    /// Limit price (`None` for market orders).
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub status: OrderStatus,
+    /// Time of the latest status change (Unix nanos).
+    pub updated_at: i64,
+}
+
+/// One fill of one of the account's orders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub trade_id: TradeId,
+    pub order_id: OrderId,
+    pub account_id: AccountId,
+    pub symbol: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// A fill as reported by the event stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub order_id: OrderId,
+    pub trade_id: TradeId,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// Quantity still open after this fill.
+    pub remaining: Decimal,
+}
+
+// ── Queries ─────────────────────────────────────────────────────────
+
+/// Order query filters; unset filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct OrderFilter {
+    /// [`OrderStatus::state_id`] of the orders to return.
+    pub state_id: Option<u8>,
+    pub symbol: Option<String>,
+}
+
+impl OrderFilter {
+    fn matches(&self, order: &OrderRecord) -> bool {
+        self.state_id.is_none_or(|id| order.status.state_id() == id)
+            && self.symbol.as_ref().is_none_or(|s| *s == order.symbol)
+    }
+}
+
+/// Trade query filters; `from` and `to` bound the timestamp inclusively.
+#[derive(Debug, Clone, Default)]
+pub struct TradeFilter {
+    pub symbol: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl TradeFilter {
+    fn matches(&self, trade: &TradeRecord) -> bool {
+        self.symbol.as_ref().is_none_or(|s| *s == trade.symbol)
+            && self.from.is_none_or(|from| trade.timestamp >= from)
+            && self.to.is_none_or(|to| trade.timestamp <= to)
+    }
+}
+
+/// One page of results, newest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+// ── Cursor ──────────────────────────────────────────────────────────
+
+/// Which listing a cursor belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorKind {
+    Orders = 1,
+    Trades = 2,
+}
+
+/// Position after the last row of a page.
+///
+/// Encoded as hex of `[version][kind][timestamp: i64][sequence: u64][crc32c]`,
+/// little-endian, so clients treat it as an opaque token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub kind: CursorKind,
+    pub timestamp: i64,
+    pub sequence: u64,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let mut buf = [0u8; CURSOR_LEN];
+        buf[0] = CURSOR_VERSION;
+        buf[1] = self.kind as u8;
+        buf[2..10].copy_from_slice(&self.timestamp.to_le_bytes());
+        buf[10..18].copy_from_slice(&self.sequence.to_le_bytes());
+        let checksum = crc32c(&buf[0..18]);
+        buf[18..22].copy_from_slice(&checksum.to_le_bytes());
+        to_hex(&buf)
+    }
+
+    /// Decode a cursor issued for the `kind` listing.
+    pub fn decode(token: &str, kind: CursorKind) -> Result<Self, HistoryError> {
+        let bytes = from_hex(token).map_err(|_| HistoryError::InvalidCursor)?;
+        let buf: [u8; CURSOR_LEN] = bytes.try_into().map_err(|_| HistoryError::InvalidCursor)?;
+        let stored = u32::from_le_bytes(buf[18..22].try_into().unwrap());
+        if buf[0] != CURSOR_VERSION || buf[1] != kind as u8 || crc32c(&buf[0..18]) != stored {
+            return Err(HistoryError::InvalidCursor);
+        }
+        Ok(Self {
+            kind,
+            timestamp: i64::from_le_bytes(buf[2..10].try_into().unwrap()),
+            sequence: u64::from_le_bytes(buf[10..18].try_into().unwrap()),
+        })
+    }
+}
+
+// ── Index ───────────────────────────────────────────────────────────
+
+/// Rows of one kind for one account, keyed by sequence.
+#[derive(Debug)]
+struct Rows<T> {
+    rows: BTreeMap<u64, T>,
+    /// Highest sequence evicted by retention.
+    evicted_through: Option<u64>,
+}
+
+impl<T> Default for Rows<T> {
+    fn default() -> Self {
+        Self {
+            rows: BTreeMap::new(),
+            evicted_through: None,
+        }
+    }
+}
+
+impl<T: Clone> Rows<T> {
+    /// Insert a row, returning the rows evicted to stay within `retention`.
+    fn insert(&mut self, sequence: u64, row: T, retention: usize) -> Vec<T> {
+        self.rows.insert(sequence, row);
+        let mut evicted = Vec::new();
+        while self.rows.len() > retention {
+            let Some((sequence, row)) = self.rows.pop_first() else { break };
+            self.evicted_through = self.evicted_through.max(Some(sequence));
+            evicted.push(row);
+        }
+        evicted
+    }
+
+    /// Rows after `cursor` passing `keep`, newest first.
+    fn page(
+        &self,
+        kind: CursorKind,
+        cursor: Option<&str>,
+        limit: usize,
+        timestamp: impl Fn(&T) -> i64,
+        keep: impl Fn(&T) -> bool,
+    ) -> Result<Page<T>, HistoryError> {
+        if limit == 0 {
+            return Err(HistoryError::InvalidLimit);
+        }
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let before = match cursor {
+            None => u64::MAX,
+            Some(token) => {
+                let cursor = Cursor::decode(token, kind)?;
+                match self.rows.get(&cursor.sequence) {
+                    Some(row) if timestamp(row) == cursor.timestamp => cursor.sequence,
+                    Some(_) => return Err(HistoryError::InvalidCursor),
+                    None => match self.evicted_through {
+                        Some(evicted_through) if cursor.sequence <= evicted_through => {
+                            return Err(HistoryError::CursorExpired { evicted_through });
+                        }
+                        // Never issued for this account
+                        _ => return Err(HistoryError::InvalidCursor),
+                    },
+                }
+            }
+        };
+
+        let mut matching = self.rows.range(..before).rev().filter(|(_, row)| keep(row));
+        let items: Vec<(u64, T)> = matching
+            .by_ref()
+            .take(limit)
+            .map(|(sequence, row)| (*sequence, row.clone()))
+            .collect();
+        let next_cursor = match items.last() {
+            Some((sequence, row)) if matching.next().is_some() => Some(
+                Cursor {
+                    kind,
+                    timestamp: timestamp(row),
+                    sequence: *sequence,
+                }
+                .encode(),
+            ),
+            _ => None,
+        };
+        Ok(Page {
+            items: items.into_iter().map(|(_, row)| row).collect(),
+            next_cursor,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct AccountHistory {
+    orders: Rows<OrderRecord>,
+    trades: Rows<TradeRecord>,
+}
+
+/// Per-account order and trade history.
+#[derive(Debug)]
+pub struct HistoryIndex {
+    accounts: HashMap<AccountId, AccountHistory>,
+    /// Owner and row sequence of every retained order
+    locations: HashMap<OrderId, (AccountId, u64)>,
+    retention: usize,
+}
+
+impl Default for HistoryIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETENTION)
+    }
+}
+
+impl HistoryIndex {
+    /// Create an index keeping at most `retention` orders and trades per account.
+    pub fn new(retention: usize) -> Self {
+        Self {
+            accounts: HashMap::new(),
+            locations: HashMap::new(),
+            retention: retention.max(1),
+        }
+    }
+
+    /// Record a newly accepted order.
+    pub fn record_order(&mut self, order: OrderRecord) {
+        self.locations.insert(order.order_id, (order.account_id, order.sequence));
+        let evicted = self
+            .accounts
+            .entry(order.account_id)
+            .or_default()
+            .orders
+            .insert(order.sequence, order, self.retention);
+        for order in evicted {
+            self.locations.remove(&order.order_id);
+        }
+    }
+
+    /// Apply a fill to its order and record the trade.
+    ///
+    /// Returns `false` when the order is unknown (never seen or evicted).
+    pub fn record_fill(&mut self, fill: Fill) -> bool {
+        let Some(order) = self.order_mut(fill.order_id) else {
+            return false;
+        };
+        order.filled_quantity += fill.quantity;
+        order.status = if fill.remaining.is_zero() {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::Partial
+        };
+        order.updated_at = fill.timestamp;
+        let trade = TradeRecord {
+            sequence: fill.sequence,
+            timestamp: fill.timestamp,
+            trade_id: fill.trade_id,
+            order_id: fill.order_id,
+            account_id: order.account_id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: fill.price,
+            quantity: fill.quantity,
+        };
+        let retention = self.retention;
+        if let Some(account) = self.accounts.get_mut(&trade.account_id) {
+            account.trades.insert(trade.sequence, trade, retention);
+        }
+        true
+    }
+
+    /// Mark an order canceled. Returns `false` when the order is unknown.
+    pub fn record_cancel(&mut self, order_id: OrderId, reason: CancelReason, timestamp: i64) -> bool {
+        match self.order_mut(order_id) {
+            Some(order) => {
+                order.status = OrderStatus::Canceled(reason);
+                order.updated_at = timestamp;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Look up a retained order.
+    pub fn order(&self, order_id: OrderId) -> Option<&OrderRecord> {
+        let (account_id, sequence) = self.locations.get(&order_id)?;
+        self.accounts.get(account_id)?.orders.rows.get(sequence)
+    }
+
+    /// One page of the account's orders, newest first.
+    ///
+    /// `limit` is capped at [`MAX_PAGE_SIZE`].
+    pub fn orders(
+        &self,
+        account_id: AccountId,
+        filter: &OrderFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<OrderRecord>, HistoryError> {
+        match self.accounts.get(&account_id) {
+            Some(account) => account.orders.page(
+                CursorKind::Orders,
+                cursor,
+                limit,
+                |order| order.timestamp,
+                |order| filter.matches(order),
+            ),
+            None => Rows::default().page(CursorKind::Orders, cursor, limit, |_| 0, |_| true),
+        }
+    }
+
+    /// One page of the account's trades, newest first.
+    ///
+    /// `limit` is capped at [`MAX_PAGE_SIZE`].
+    pub fn trades(
+        &self,
+        account_id: AccountId,
+        filter: &TradeFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<TradeRecord>, HistoryError> {
+        match (filter.from, filter.to) {
+            (Some(from), Some(to)) if from > to => return Err(HistoryError::InvalidRange { from, to }),
+            _ => {}
+        }
+        match self.accounts.get(&account_id) {
+            Some(account) => account.trades.page(
+                CursorKind::Trades,
+                cursor,
+                limit,
+                |trade| trade.timestamp,
+                |trade| filter.matches(trade),
+            ),
+            None => Rows::default().page(CursorKind::Trades, cursor, limit, |_| 0, |_| true),
+        }
+    }
+
+    fn order_mut(&mut self, order_id: OrderId) -> Option<&mut OrderRecord> {
+        let (account_id, sequence) = self.locations.get(&order_id)?;
+        self.accounts.get_mut(account_id)?.orders.rows.get_mut(sequence)
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const T0: i64 = 1708128000000000000;
+    const SYMBOLS: [&str; 3] = ["BTC/USDT", "ETH/USDT", "SOL/USDT"];
+
+    fn order(account_id: AccountId, sequence: u64, timestamp: i64, symbol: &str) -> OrderRecord {
+        OrderRecord {
+            sequence,
+            timestamp,
+            order_id: OrderId::new(),
+            account_id,
+            symbol: symbol.to_string(),
+            side: if sequence.is_multiple_of(2) { Side::BUY } else { Side::SELL },
+            price: Some(Decimal::from(100 + sequence % 50)),
+            quantity: Decimal::from(10),
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Pending,
+            updated_at: timestamp,
+        }
+    }
+
+    /// 10k orders for one account; seven share each timestamp, every fourth
+    /// is filled and every other fifth canceled.
+    fn synthetic(account_id: AccountId) -> HistoryIndex {
+        let mut index = HistoryIndex::default();
+        for i in 0..10_000u64 {
+            let record = order(account_id, 2 * i + 1, T0 + (i / 7) as i64, SYMBOLS[(i % 3) as usize]);
+            let order_id = record.order_id;
+            index.record_order(record);
+            if i % 4 == 0 {
+                index.record_fill(Fill {
+                    sequence: 2 * i + 2,
+                    timestamp: T0 + (i / 7) as i64,
+                    order_id,
+                    trade_id: TradeId::new(),
+                    price: Decimal::from(100),
+                    quantity: Decimal::from(10),
+                    remaining: Decimal::ZERO,
+                });
+            } else if i % 5 == 0 {
+                index.record_cancel(order_id, CancelReason::UserRequested, T0);
+            }
+        }
+        index
+    }
+
+    fn all_orders(index: &HistoryIndex, account_id: AccountId, filter: &OrderFilter, limit: usize) -> Vec<OrderRecord> {
+        let mut rows = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = index.orders(account_id, filter, cursor.as_deref(), limit).unwrap();
+            assert!(page.items.len() <= limit.min(MAX_PAGE_SIZE));
+            rows.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return rows,
+            }
+        }
+    }
+
+    #[test]
+    fn test_paging_through_10k_orders_is_exhaustive_and_ordered() {
+        let account_id = AccountId::new();
+        let index = synthetic(account_id);
+        for limit in [1_000, 37, 500] {
+            let rows = all_orders(&index, account_id, &OrderFilter::default(), limit);
+            assert_eq!(rows.len(), 10_000, "limit {limit}");
+            assert!(rows.windows(2).all(|w| w[0].sequence > w[1].sequence));
+            let ids: HashSet<OrderId> = rows.iter().map(|o| o.order_id).collect();
+            assert_eq!(ids.len(), 10_000);
+        }
+        // The cap applies to oversized requests
+        let page = index.orders(account_id, &OrderFilter::default(), None, 1_000).unwrap();
+        assert_eq!(page.items.len(), MAX_PAGE_SIZE);
+
+        // Other accounts see nothing
+        let page = index.orders(AccountId::new(), &OrderFilter::default(), None, 10).unwrap();
+        assert_eq!(page, Page { items: vec![], next_cursor: None });
+    }
+
+    #[test]
+    fn test_filters_compose() {
+        let account_id = AccountId::new();
+        let index = synthetic(account_id);
+        let everything = all_orders(&index, account_id, &OrderFilter::default(), MAX_PAGE_SIZE);
+        let filter = OrderFilter {
+            state_id: Some(OrderStatus::Filled.state_id()),
+            symbol: Some("ETH/USDT".into()),
+        };
+        let expected: Vec<OrderRecord> = everything
+            .iter()
+            .filter(|o| o.status == OrderStatus::Filled && o.symbol == "ETH/USDT")
+            .cloned()
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(all_orders(&index, account_id, &filter, 41), expected);
+
+        let canceled = OrderFilter {
+            state_id: Some(3),
+            ..Default::default()
+        };
+        let rows = all_orders(&index, account_id, &canceled, 100);
+        assert!(rows.iter().all(|o| o.status == OrderStatus::Canceled(CancelReason::UserRequested)));
+        assert_eq!(rows.len(), (0..10_000).filter(|i| i % 4 != 0 && i % 5 == 0).count());
+    }
+
+    #[test]
+    fn test_pages_are_stable_under_concurrent_writes() {
+        let account_id = AccountId::new();
+        let mut index = HistoryIndex::default();
+        for i in 1..=100u64 {
+            index.record_order(order(account_id, i, T0, "BTC/USDT"));
+        }
+        let first = index.orders(account_id, &OrderFilter::default(), None, 30).unwrap();
+        let mut seen: Vec<u64> = first.items.iter().map(|o| o.sequence).collect();
+        let mut cursor = first.next_cursor;
+
+        // New orders and status changes between page fetches
+        let mut sequence = 100;
+        while let Some(token) = cursor {
+            sequence += 1;
+            index.record_order(order(account_id, sequence, T0, "BTC/USDT"));
+            let resting = index.orders(account_id, &OrderFilter::default(), None, 1).unwrap().items[0].order_id;
+            index.record_cancel(resting, CancelReason::UserRequested, T0 + 1);
+
+            let page = index.orders(account_id, &OrderFilter::default(), Some(&token), 30).unwrap();
+            seen.extend(page.items.iter().map(|o| o.sequence));
+            cursor = page.next_cursor;
+        }
+        assert_eq!(seen, (1..=100).rev().collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_trades_filter_by_symbol_and_time() {
+        let account_id = AccountId::new();
+        let index = synthetic(account_id);
+        let filter = TradeFilter {
+            symbol: Some("BTC/USDT".into()),
+            from: Some(T0 + 100),
+            to: Some(T0 + 199),
+        };
+        let mut rows = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = index.trades(account_id, &filter, cursor.as_deref(), 7).unwrap();
+            rows.extend(page.items);
+            let Some(next) = page.next_cursor else { break };
+            cursor = Some(next);
+        }
+        // Orders 700..1400 sit in the window: every fourth is filled, every third is BTC
+        let expected = (700..1400u64).filter(|i| i % 4 == 0 && i % 3 == 0).count();
+        assert_eq!(rows.len(), expected);
+        assert!(rows.windows(2).all(|w| w[0].sequence > w[1].sequence));
+        assert!(rows
+            .iter()
+            .all(|t| t.symbol == "BTC/USDT" && (T0 + 100..=T0 + 199).contains(&t.timestamp)));
+
+        let inverted = TradeFilter {
+            from: Some(T0 + 1),
+            to: Some(T0),
+            ..Default::default()
+        };
+        assert_eq!(
+            index.trades(account_id, &inverted, None, 10),
+            Err(HistoryError::InvalidRange { from: T0 + 1, to: T0 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_cursors() {
+        let account_id = AccountId::new();
+        let index = synthetic(account_id);
+        let page = index.orders(account_id, &OrderFilter::default(), None, 10).unwrap();
+        let token = page.next_cursor.unwrap();
+        let orders = |cursor: &str| index.orders(account_id, &OrderFilter::default(), Some(cursor), 10);
+
+        let mut tampered = token.clone().into_bytes();
+        tampered[10] = if tampered[10] == b'0' { b'1' } else { b'0' };
+        let cases = [
+            "not-a-cursor".to_string(),
+            String::from_utf8(tampered).unwrap(),
+            token[..token.len() - 2].to_string(),
+        ];
+        for cursor in &cases {
+            assert_eq!(orders(cursor), Err(HistoryError::InvalidCursor), "{cursor}");
+        }
+        // Cursors are scoped to their listing and account
+        let trade_page = index.trades(account_id, &TradeFilter::default(), None, 1).unwrap();
+        assert_eq!(orders(&trade_page.next_cursor.unwrap()), Err(HistoryError::InvalidCursor));
+        assert_eq!(
+            index.orders(AccountId::new(), &OrderFilter::default(), Some(&token), 10),
+            Err(HistoryError::InvalidCursor)
+        );
+        // A forged timestamp for a real row
+        let forged = Cursor::decode(&token, CursorKind::Orders).unwrap();
+        let forged = Cursor { timestamp: forged.timestamp + 1, ..forged }.encode();
+        assert_eq!(orders(&forged), Err(HistoryError::InvalidCursor));
+        assert_eq!(index.orders(account_id, &OrderFilter::default(), None, 0), Err(HistoryError::InvalidLimit));
+    }
+
+    #[test]
+    fn test_cursor_expires_when_its_row_is_evicted() {
+        let account_id = AccountId::new();
+        let mut index = HistoryIndex::new(10);
+        for i in 1..=10 {
+            index.record_order(order(account_id, i, T0, "BTC/USDT"));
+        }
+        let page = index.orders(account_id, &OrderFilter::default(), None, 8).unwrap();
+        let token = page.next_cursor.unwrap();
+        // The cursor's row (seq 3) and everything below it are evicted
+        for i in 11..=13 {
+            index.record_order(order(account_id, i, T0, "BTC/USDT"));
+        }
+        assert_eq!(
+            index.orders(account_id, &OrderFilter::default(), Some(&token), 8),
+            Err(HistoryError::CursorExpired { evicted_through: 3 })
+        );
+        assert!(index.order(page.items[0].order_id).is_some());
+        assert_eq!(index.locations.len(), 10);
+    }
+}
@@ -0,0 +1,295 @@
+//! Sparse Sequence Index — advisory `(sequence, byte_offset)` sidecars
+//!
+//! Each journal segment `journal-NNNNNN.bin` may have a sidecar
+//! `journal-NNNNNN.idx` recording where every N-th entry of the segment
+//! starts, so `JournalReader::seek_to_sequence` can jump near its target
+//! instead of walking the whole journal.
+//!
+//! # Binary Format (per record)
+//! ```text
+//! [sequence: u64]
+//! [offset:   u64]  // byte offset of the entry's frame within the segment,
+//!                  // counted from the end of any encryption header
+//! [checksum: u32]  // CRC32C over sequence+offset
+//! ```
+//!
+//! The index is advisory: a missing index, a record failing its checksum or
+//! an index without a record at offset 0 makes the reader fall back to a
+//! linear scan. A torn trailing record (crash mid-write) is ignored.
+
+use crc32c::crc32c;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Encoded size of one index record.
+pub const RECORD_LEN: usize = 20;
+
+/// Path of the index sidecar for a journal segment.
+pub fn index_path(journal_path: &Path) -> PathBuf {
+    journal_path.with_extension("idx")
+}
+
+// ── Index Record ────────────────────────────────────────────────────
+
+/// Start of one journal entry within its segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexRecord {
+    /// Sequence number of the entry.
+    pub sequence: u64,
+    /// Byte offset of the entry's frame within the segment file.
+    pub offset: u64,
+}
+
+impl IndexRecord {
+    /// Serialize to the binary record format.
+    pub fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let mut buf = [0u8; RECORD_LEN];
+        buf[0..8].copy_from_slice(&self.sequence.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.offset.to_le_bytes());
+        let checksum = crc32c(&buf[0..16]);
+        buf[16..20].copy_from_slice(&checksum.to_le_bytes());
+        buf
+    }
+
+    /// Deserialize one record, returning `None` if its checksum fails.
+    pub fn from_bytes(data: &[u8; RECORD_LEN]) -> Option<Self> {
+        let stored = u32::from_le_bytes(data[16..20].try_into().unwrap());
+        if crc32c(&data[0..16]) != stored {
+            return None;
+        }
+        Some(Self {
+            sequence: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            offset: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+        })
+    }
+}
+
+// ── Segment Index ───────────────────────────────────────────────────
+
+/// Loaded index of one journal segment, ordered by sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentIndex {
+    records: Vec<IndexRecord>,
+}
+
+impl SegmentIndex {
+    /// Load the index sidecar of a journal segment.
+    ///
+    /// Returns `None` when the index is missing or cannot be trusted.
+    pub fn load(journal_path: &Path) -> Option<Self> {
+        let data = fs::read(index_path(journal_path)).ok()?;
+        let mut records: Vec<IndexRecord> = Vec::with_capacity(data.len() / RECORD_LEN);
+        for chunk in data.chunks_exact(RECORD_LEN) {
+            let record = IndexRecord::from_bytes(chunk.try_into().unwrap())?;
+            if let Some(prev) = records.last() {
+                if record.sequence <= prev.sequence || record.offset <= prev.offset {
+                    return None;
+                }
+            }
+            records.push(record);
+        }
+
+        // The first record anchors the segment's first sequence
+        match records.first() {
+            Some(first) if first.offset == 0 => Some(Self { records }),
+            _ => None,
+        }
+    }
+
+    /// Sequence of the segment's first entry.
+    pub fn first_sequence(&self) -> u64 {
+        self.records[0].sequence
+    }
+
+    /// Last indexed entry with `sequence < target`, if any.
+    pub fn floor_below(&self, target: u64) -> Option<IndexRecord> {
+        let idx = self.records.partition_point(|r| r.sequence < target);
+        idx.checked_sub(1).map(|i| self.records[i])
+    }
+
+    /// All records, ordered by sequence.
+    pub fn records(&self) -> &[IndexRecord] {
+        &self.records
+    }
+}
+
+// ── Index Writer ────────────────────────────────────────────────────
+
+/// Position of an [`IndexWriter`], for rolling back a failed batch append.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexCheckpoint {
+    /// Index file length in bytes.
+    pub len: u64,
+    /// Entries noted so far in the segment.
+    pub entries: u64,
+}
+
+/// Appends a record for every `interval`-th entry written to a segment.
+pub struct IndexWriter {
+    writer: BufWriter<File>,
+    interval: u64,
+    entries: u64,
+}
+
+impl IndexWriter {
+    /// Open (or continue) the index sidecar of a journal segment.
+    ///
+    /// A torn trailing record left by a crash is cut off first so new
+    /// records stay aligned.
+    pub fn open(journal_path: &Path, interval: u64) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(index_path(journal_path))?;
+        let len = file.metadata()?.len();
+        let aligned = len - len % RECORD_LEN as u64;
+        if aligned != len {
+            file.set_len(aligned)?;
+        }
+        Ok(Self {
+            writer: BufWriter::new(file),
+            interval: interval.max(1),
+            entries: 0,
+        })
+    }
+
+    /// Reopen an index sidecar at an earlier checkpoint, dropping anything
+    /// recorded after it.
+    pub fn reopen(
+        journal_path: &Path,
+        interval: u64,
+        checkpoint: IndexCheckpoint,
+    ) -> io::Result<Self> {
+        let mut writer = Self::open(journal_path, interval)?;
+        writer.writer.get_ref().set_len(checkpoint.len)?;
+        writer.entries = checkpoint.entries;
+        Ok(writer)
+    }
+
+    /// Flush and report the current position.
+    pub fn checkpoint(&mut self) -> io::Result<IndexCheckpoint> {
+        self.writer.flush()?;
+        Ok(IndexCheckpoint {
+            len: self.writer.get_ref().metadata()?.len(),
+            entries: self.entries,
+        })
+    }
+
+    /// Note an entry appended at `offset`, indexing it if it falls on the
+    /// interval.
+    pub fn record(&mut self, sequence: u64, offset: u64) -> io::Result<()> {
+        if self.entries.is_multiple_of(self.interval) {
+            self.writer
+                .write_all(&IndexRecord { sequence, offset }.to_bytes())?;
+        }
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Flush buffered records to the OS.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush and fsync the index file.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_index(path: &Path, records: &[IndexRecord]) {
+        let bytes: Vec<u8> = records.iter().flat_map(|r| r.to_bytes()).collect();
+        fs::write(index_path(path), bytes).unwrap();
+    }
+
+    #[test]
+    fn test_record_roundtrip_and_checksum() {
+        let record = IndexRecord { sequence: 42, offset: 4096 };
+        let mut bytes = record.to_bytes();
+        assert_eq!(IndexRecord::from_bytes(&bytes), Some(record));
+
+        bytes[3] ^= 0x01;
+        assert_eq!(IndexRecord::from_bytes(&bytes), None);
+    }
+
+    #[test]
+    fn test_writer_indexes_every_interval() {
+        let tmp = TempDir::new().unwrap();
+        let journal = tmp.path().join("journal-000000.bin");
+        let mut writer = IndexWriter::open(&journal, 4).unwrap();
+        for i in 0..10u64 {
+            writer.record(100 + i, i * 50).unwrap();
+        }
+        writer.sync().unwrap();
+
+        let index = SegmentIndex::load(&journal).unwrap();
+        let seqs: Vec<u64> = index.records().iter().map(|r| r.sequence).collect();
+        assert_eq!(seqs, vec![100, 104, 108]);
+        assert_eq!(index.first_sequence(), 100);
+        assert_eq!(index.floor_below(104).unwrap().sequence, 100);
+        assert_eq!(index.floor_below(105).unwrap().sequence, 104);
+        assert_eq!(index.floor_below(100), None);
+    }
+
+    #[test]
+    fn test_torn_tail_is_ignored_and_trimmed() {
+        let tmp = TempDir::new().unwrap();
+        let journal = tmp.path().join("journal-000000.bin");
+        let records = [
+            IndexRecord { sequence: 1, offset: 0 },
+            IndexRecord { sequence: 9, offset: 400 },
+        ];
+        write_index(&journal, &records);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(index_path(&journal))
+            .unwrap();
+        file.write_all(&[0xAB; 7]).unwrap();
+        drop(file);
+
+        assert_eq!(SegmentIndex::load(&journal).unwrap().records(), &records);
+
+        let mut writer = IndexWriter::open(&journal, 1).unwrap();
+        writer.record(17, 800).unwrap();
+        writer.sync().unwrap();
+        assert_eq!(SegmentIndex::load(&journal).unwrap().records().len(), 3);
+    }
+
+    #[test]
+    fn test_untrusted_index_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let journal = tmp.path().join("journal-000000.bin");
+        assert_eq!(SegmentIndex::load(&journal), None);
+
+        // No anchor at offset 0
+        write_index(&journal, &[IndexRecord { sequence: 5, offset: 200 }]);
+        assert_eq!(SegmentIndex::load(&journal), None);
+
+        // Not increasing
+        write_index(
+            &journal,
+            &[
+                IndexRecord { sequence: 5, offset: 0 },
+                IndexRecord { sequence: 5, offset: 200 },
+            ],
+        );
+        assert_eq!(SegmentIndex::load(&journal), None);
+
+        // Bad checksum
+        write_index(&journal, &[IndexRecord { sequence: 5, offset: 0 }]);
+        let path = index_path(&journal);
+        let mut data = fs::read(&path).unwrap();
+        data[0] ^= 0xFF;
+        fs::write(&path, data).unwrap();
+        assert_eq!(SegmentIndex::load(&journal), None);
+    }
+}
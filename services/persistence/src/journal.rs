@@ -12,12 +12,59 @@
 //! [payload_len: u32][payload: bytes]
 //! [checksum: u32]  // CRC32C over sequence+timestamp+event_type+payload
 //! ```
-
+//!
+//! Payloads of at least [`JournalConfig::compression_threshold`] bytes are
+//! zstd-compressed when that saves space. Such entries set the high bit of
+//! `event_type_len` and carry extra fields after the event type:
+//! ```text
+//! [event_type_len | 0x8000: u16][event_type: bytes]
+//! [flags: u8]      // bit 0 = zstd
+//! [raw_len: u32]   // uncompressed payload length (zstd only)
+//! [payload_len: u32][payload: compressed bytes]
+//! [checksum: u32]  // still over the uncompressed payload
+//! ```
+//! Entries without the bit use the original layout, so old journals parse
+//! unchanged.
+//!
+//! # Rotation and Retention
+//! Files rotate on size and, optionally, on age measured in exchange time
+//! (entry timestamps, never the wall clock). Old segments are only deleted
+//! by an explicit [`JournalWriter::apply_retention`] call, and only when a
+//! snapshot already covers every entry in them.
+//!
+//! # Encryption
+//! With [`JournalConfig::encryption_key`] set, every segment starts with an
+//! encryption header and each frame body is sealed with AES-256-GCM (see
+//! `crate::encryption`). Checksums are still over the plaintext entry.
+//!
+//! # File Header
+//! New segments carry a file header (`crate::header`) after any encryption
+//! header, with the exchange timestamp and sequence of their first entry.
+//! Those are only known once that entry arrives, so the header is written
+//! together with the first frame. Segments from before headers existed are
+//! still read and appended to as they are. Index offsets count from the
+//! first frame, past all headers.
+//!
+//! # Shared Sequences
+//! Several producers can feed one writer through a
+//! [`SequenceAllocator`](crate::allocator::SequenceAllocator) opened with
+//! [`JournalWriter::open_with_allocator`]. Sequences the allocator never
+//! handed out are refused; released and leaked ones are voided as
+//! described in `crate::allocator`.
+
+use crate::allocator::{AllocatorError, SequenceAllocator, VOIDED_EVENT_TYPE};
+use crate::encryption::{self, EncryptionError, EncryptionKey, FileCipher, HEADER_LEN};
+use crate::header::{self, FileHeader, FileKind, HeaderError, HeaderProbe};
+use crate::index::{self, IndexCheckpoint, IndexWriter};
+use crate::reader::JournalReader;
+use crate::snapshot::{SnapshotError, SnapshotLoader};
 use crc32c::crc32c;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufWriter, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 
 // ── Errors ──────────────────────────────────────────────────────────
@@ -38,8 +85,39 @@ pub enum JournalError {
 
     #[error("File rotation required")]
     RotationRequired,
+
+    #[error("Payload decompression failed: {0}")]
+    Decompression(String),
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Encryption error: {0}")]
+    Encryption(#[from] EncryptionError),
+
+    #[error("File header error: {0}")]
+    Header(#[from] HeaderError),
+
+    #[error("Sequence allocator error: {0}")]
+    Allocator(#[from] AllocatorError),
 }
 
+/// Default payload size (bytes) from which entries are compressed.
+pub const COMPRESSION_THRESHOLD: usize = 4096;
+
+/// `event_type_len` bit marking a flags byte after the event type.
+const FLAGS_PRESENT: u16 = 0x8000;
+
+/// Flags bit: payload is zstd-compressed.
+pub const FLAG_ZSTD: u8 = 0x01;
+
+/// zstd level for entry payloads (favours speed on the write path).
+const ZSTD_LEVEL: i32 = 3;
+
+/// Largest plausible entry body or uncompressed payload; larger lengths
+/// are taken for corruption rather than allocated.
+const MAX_ENTRY_LEN: usize = 100_000_000;
+
 // ── Journal Entry ───────────────────────────────────────────────────
 
 /// A single journal entry representing one persisted event.
@@ -95,8 +173,23 @@ impl JournalEntry {
         self.checksum == expected
     }
 
-    /// Serialize entry to the binary wire format.
+    /// Serialize entry to the binary wire format, compressing payloads of at
+    /// least [`COMPRESSION_THRESHOLD`] bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_threshold(COMPRESSION_THRESHOLD)
+    }
+
+    /// Serialize entry, compressing the payload if it is at least
+    /// `threshold` bytes (0 = never) and compression makes it smaller.
+    pub fn to_bytes_with_threshold(&self, threshold: usize) -> Vec<u8> {
+        if threshold > 0 && self.payload.len() >= threshold {
+            if let Ok(compressed) = zstd::bulk::compress(&self.payload, ZSTD_LEVEL) {
+                if compressed.len() + 5 < self.payload.len() {
+                    return self.to_compressed_bytes(&compressed);
+                }
+            }
+        }
+
         let event_type_bytes = self.event_type.as_bytes();
         let event_type_len = event_type_bytes.len() as u16;
         let payload_len = self.payload.len() as u32;
@@ -117,6 +210,29 @@ impl JournalEntry {
         buf
     }
 
+    fn to_compressed_bytes(&self, compressed: &[u8]) -> Vec<u8> {
+        let event_type_bytes = self.event_type.as_bytes();
+        let event_type_len = event_type_bytes.len() as u16;
+        let payload_len = compressed.len() as u32;
+
+        // Original body + 1 (flags) + 4 (raw_len)
+        let body_len: u32 =
+            8 + 8 + 2 + (event_type_len as u32) + 1 + 4 + 4 + payload_len + 4;
+
+        let mut buf = Vec::with_capacity(4 + body_len as usize);
+        buf.extend_from_slice(&body_len.to_le_bytes());
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+        buf.extend_from_slice(&self.timestamp.to_le_bytes());
+        buf.extend_from_slice(&(event_type_len | FLAGS_PRESENT).to_le_bytes());
+        buf.extend_from_slice(event_type_bytes);
+        buf.push(FLAG_ZSTD);
+        buf.extend_from_slice(&(self.payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&payload_len.to_le_bytes());
+        buf.extend_from_slice(compressed);
+        buf.extend_from_slice(&self.checksum.to_le_bytes());
+        buf
+    }
+
     /// Deserialize entry from the binary wire format.
     ///
     /// Returns `(entry, bytes_consumed)` on success.
@@ -131,7 +247,7 @@ impl JournalEntry {
         let body_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
 
         // Sanity check: reject absurdly large body_len (likely corruption)
-        if body_len > 100_000_000 {
+        if body_len > MAX_ENTRY_LEN {
             return Err(JournalError::Serialization(format!(
                 "Implausible body length: {} (likely corruption)",
                 body_len
@@ -168,8 +284,9 @@ impl JournalEntry {
         pos += 8;
 
         // event_type_len (u16) + event_type
-        let event_type_len =
-            u16::from_le_bytes(body[pos..pos + 2].try_into().unwrap()) as usize;
+        let raw_event_type_len = u16::from_le_bytes(body[pos..pos + 2].try_into().unwrap());
+        let has_flags = raw_event_type_len & FLAGS_PRESENT != 0;
+        let event_type_len = (raw_event_type_len & !FLAGS_PRESENT) as usize;
         pos += 2;
 
         if pos + event_type_len > body.len() {
@@ -183,6 +300,41 @@ impl JournalEntry {
             .map_err(|e| JournalError::Serialization(e.to_string()))?;
         pos += event_type_len;
 
+        // flags (u8) + raw_len (u32) on compressed entries
+        let mut raw_len = None;
+        if has_flags {
+            if pos + 1 > body.len() {
+                return Err(JournalError::Serialization(
+                    "Not enough data for flags".into(),
+                ));
+            }
+            let flags = body[pos];
+            pos += 1;
+            if flags & !FLAG_ZSTD != 0 {
+                return Err(JournalError::Serialization(format!(
+                    "Unknown entry flags {:#04x}",
+                    flags
+                )));
+            }
+            if flags & FLAG_ZSTD != 0 {
+                if pos + 4 > body.len() {
+                    return Err(JournalError::Serialization(
+                        "Not enough data for uncompressed length".into(),
+                    ));
+                }
+                let len = u32::from_le_bytes(body[pos..pos + 4].try_into().unwrap()) as usize;
+                // Bounds the decompression buffer
+                if len > MAX_ENTRY_LEN {
+                    return Err(JournalError::Decompression(format!(
+                        "Implausible uncompressed length: {} (likely corruption)",
+                        len
+                    )));
+                }
+                raw_len = Some(len);
+                pos += 4;
+            }
+        }
+
         // payload_len (u32) + payload
         if pos + 4 > body.len() {
             return Err(JournalError::Serialization(
@@ -200,7 +352,22 @@ impl JournalEntry {
                 body.len() - pos
             )));
         }
-        let payload = body[pos..pos + payload_len].to_vec();
+        let stored = &body[pos..pos + payload_len];
+        let payload = match raw_len {
+            Some(raw_len) => {
+                let payload = zstd::bulk::decompress(stored, raw_len)
+                    .map_err(|e| JournalError::Decompression(e.to_string()))?;
+                if payload.len() != raw_len {
+                    return Err(JournalError::Decompression(format!(
+                        "expected {} bytes, got {}",
+                        raw_len,
+                        payload.len()
+                    )));
+                }
+                payload
+            }
+            None => stored.to_vec(),
+        };
         pos += payload_len;
 
         // checksum (u32)
@@ -245,6 +412,23 @@ pub enum FsyncPolicy {
     OnRotation,
 }
 
+// ── Retention Policy ────────────────────────────────────────────────
+
+/// Which closed segments [`JournalWriter::apply_retention`] may delete.
+///
+/// A segment is a candidate once it is older than `max_age` or falls
+/// outside the newest `max_segments`; it is only deleted if a snapshot
+/// also covers all of its entries. Both limits default to 0 (keep all).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Maximum age in nanoseconds of exchange time, measured from a
+    /// segment's last entry to the newest entry written (0 = unlimited).
+    pub max_age: i64,
+    /// Maximum number of segments to keep, including the active one
+    /// (0 = unlimited).
+    pub max_segments: usize,
+}
+
 // ── Journal Writer Configuration ────────────────────────────────────
 
 /// Configuration for the journal writer.
@@ -260,6 +444,17 @@ pub struct JournalConfig {
     pub flush_policy: FlushPolicy,
     /// Fsync policy.
     pub fsync_policy: FsyncPolicy,
+    /// Compress payloads of at least this many bytes (0 = never).
+    pub compression_threshold: usize,
+    /// Write a sparse index record every N entries per file (0 = no index).
+    pub index_interval: u64,
+    /// Rotate once an entry's timestamp is this many nanoseconds past the
+    /// first entry of the current file (0 = size-based rotation only).
+    pub max_file_age_nanos: i64,
+    /// Segment retention applied by [`JournalWriter::apply_retention`].
+    pub retention: RetentionPolicy,
+    /// Encrypt segments at rest with this key (`None` = plaintext).
+    pub encryption_key: Option<EncryptionKey>,
 }
 
 impl JournalConfig {
@@ -271,55 +466,210 @@ impl JournalConfig {
             max_total_size: 0,                // unlimited
             flush_policy: FlushPolicy::EveryWrite,
             fsync_policy: FsyncPolicy::EveryWrite,
+            compression_threshold: COMPRESSION_THRESHOLD,
+            index_interval: 1024,
+            max_file_age_nanos: 0,           // no age-based rotation
+            retention: RetentionPolicy::default(),
+            encryption_key: None,
         }
     }
 }
 
+// ── Write Receipt ───────────────────────────────────────────────────
+
+/// Where an appended entry landed on disk.
+///
+/// Appending does not imply durability; compare `sequence` against
+/// [`JournalWriter::last_fsynced_sequence`] before acknowledging clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteReceipt {
+    /// Sequence number of the appended entry.
+    pub sequence: u64,
+    /// Index of the journal file the entry was written to.
+    pub file_index: u64,
+    /// Byte offset of the entry within that file.
+    pub offset: u64,
+    /// Encoded entry length in bytes.
+    pub len: u64,
+}
+
+/// Running totals of a writer's activity since it was opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JournalStats {
+    /// Entries appended, singly or in batches.
+    pub appends: u64,
+    /// Bytes written to journal files, headers included.
+    pub bytes_written: u64,
+    /// Journal file fsyncs.
+    pub fsyncs: u64,
+    /// Rotations to a new file.
+    pub rotations: u64,
+}
+
+/// Callback invoked with the new fsynced watermark whenever it advances.
+pub type DurableCallback = Box<dyn FnMut(u64) + Send>;
+
+/// Writer state captured before a batch append, restored if it fails.
+struct BatchMark {
+    file_index: u64,
+    current_file: PathBuf,
+    cipher: Option<FileCipher>,
+    current_file_size: u64,
+    data_start: u64,
+    header_pending: bool,
+    current_file_first_timestamp: Option<i64>,
+    last_timestamp: Option<i64>,
+    total_size: u64,
+    next_sequence: u64,
+    last_appended_sequence: u64,
+    last_flushed_sequence: u64,
+    writes_since_flush: usize,
+    writes_since_fsync: usize,
+    index: Option<IndexCheckpoint>,
+}
+
 // ── Journal Writer ──────────────────────────────────────────────────
 
 /// Append-only journal writer with checksums, rotation, and fsync control.
+///
+/// Tracks three watermarks (0 = nothing yet): the last appended sequence,
+/// the last sequence flushed to the OS, and the last sequence fsynced.
 pub struct JournalWriter {
     config: JournalConfig,
     writer: BufWriter<File>,
+    index: Option<IndexWriter>,
+    /// Cipher of the current file when encryption is enabled.
+    cipher: Option<FileCipher>,
+    /// The current encrypted file was truncated past sealed frames; the
+    /// next write first moves to a fresh nonce base.
+    renew_cipher: bool,
     current_file: PathBuf,
     current_file_size: u64,
+    /// Offset of the first frame in the current file, past all headers.
+    data_start: u64,
+    /// The current file has no frames yet; its file header goes out with
+    /// the first one.
+    header_pending: bool,
+    /// Timestamp of the first entry in the current file, for age rotation.
+    current_file_first_timestamp: Option<i64>,
+    /// Newest timestamp known to the writer, the "now" for retention.
+    last_timestamp: Option<i64>,
     next_sequence: u64,
+    last_appended_sequence: u64,
+    last_flushed_sequence: u64,
+    last_fsynced_sequence: u64,
+    on_durable: Option<DurableCallback>,
+    /// Source of truth for sequences when producers share the journal.
+    allocator: Option<Arc<SequenceAllocator>>,
     writes_since_flush: usize,
     writes_since_fsync: usize,
     file_index: u64,
     total_size: u64,
+    stats: JournalStats,
 }
 
 impl JournalWriter {
     /// Open a new journal writer, creating the directory if needed.
+    ///
+    /// Fails if existing segments do not all match the configured
+    /// encryption, or if the key cannot decrypt the current segment.
     pub fn open(config: JournalConfig) -> Result<Self, JournalError> {
         fs::create_dir_all(&config.dir)?;
+        encryption::check_files(
+            &Self::segment_paths(&config.dir)?,
+            config.encryption_key.is_some(),
+        )?;
 
         // Scan existing journal files to find the latest index
         let file_index = Self::find_latest_index(&config.dir);
         let current_file = Self::journal_path(&config.dir, file_index);
 
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&current_file)?;
-
-        let current_file_size = file.metadata()?.len();
+        let (mut file, mut cipher) = Self::open_segment(&config, &current_file)?;
+        let mut current_file_size = file.metadata()?.len();
+        let enc_len = if cipher.is_some() { HEADER_LEN as u64 } else { 0 };
+        let probe = header::probe(
+            &mut File::open(&current_file)?,
+            FileKind::Journal,
+            enc_len,
+            current_file_size,
+        )?;
+        let (data_start, header_pending, current_file_first_timestamp) = match probe {
+            HeaderProbe::Present(found) => {
+                (enc_len + header::HEADER_LEN as u64, false, Some(found.created_at))
+            }
+            HeaderProbe::Absent => (
+                enc_len,
+                false,
+                Self::first_timestamp(&current_file, config.encryption_key.as_ref()),
+            ),
+            HeaderProbe::Incomplete => {
+                // A torn first write holds no entry; start the file over,
+                // under a fresh nonce base if the torn bytes were sealed
+                if current_file_size > enc_len && cipher.is_some() {
+                    file.set_len(0)?;
+                    (file, cipher) = Self::open_segment(&config, &current_file)?;
+                    current_file_size = enc_len;
+                } else if current_file_size > enc_len {
+                    file.set_len(enc_len)?;
+                    current_file_size = enc_len;
+                }
+                (enc_len + header::HEADER_LEN as u64, true, None)
+            }
+        };
         let total_size = Self::compute_total_size(&config.dir)?;
+        let index = Self::open_index(&config, &current_file)?;
 
         Ok(Self {
             config,
             writer: BufWriter::new(file),
+            index,
+            cipher,
+            renew_cipher: false,
             current_file,
             current_file_size,
+            data_start,
+            header_pending,
+            current_file_first_timestamp,
+            last_timestamp: current_file_first_timestamp,
             next_sequence: 0, // Will be set by caller or via recovery
+            last_appended_sequence: 0,
+            last_flushed_sequence: 0,
+            last_fsynced_sequence: 0,
+            on_durable: None,
+            allocator: None,
             writes_since_flush: 0,
             writes_since_fsync: 0,
             file_index,
             total_size,
+            stats: JournalStats::default(),
         })
     }
 
+    /// Open a writer whose sequences come from `allocator`.
+    ///
+    /// Continues after the last entry on disk (at 1 for an empty journal)
+    /// instead of relying on [`set_next_sequence`](Self::set_next_sequence).
+    /// Sequences reserved before a restart but never written are voided
+    /// right away, so the journal is gapless up to the allocator's
+    /// high-water mark.
+    pub fn open_with_allocator(
+        config: JournalConfig,
+        allocator: Arc<SequenceAllocator>,
+    ) -> Result<Self, JournalError> {
+        let mut writer = Self::open(config)?;
+        let (last_sequence, last_timestamp) = writer.journal_tail()?;
+        let next = last_sequence.map_or(crate::allocator::FIRST_SEQUENCE, |seq| seq + 1);
+        writer.next_sequence = next;
+        if writer.last_timestamp.is_none() {
+            writer.last_timestamp = last_timestamp;
+        }
+
+        let leaked = allocator.reconcile(next)?;
+        writer.allocator = Some(allocator);
+        writer.void(leaked, last_timestamp.unwrap_or(0))?;
+        Ok(writer)
+    }
+
     /// Set the next expected sequence number (used after recovery).
     pub fn set_next_sequence(&mut self, seq: u64) {
         self.next_sequence = seq;
@@ -330,13 +680,39 @@ impl JournalWriter {
         self.next_sequence
     }
 
+    /// Last sequence handed to the OS (flushed out of the write buffer).
+    pub fn last_flushed_sequence(&self) -> u64 {
+        self.last_flushed_sequence
+    }
+
+    /// Last sequence known durable on disk (fsynced).
+    pub fn last_fsynced_sequence(&self) -> u64 {
+        self.last_fsynced_sequence
+    }
+
+    /// Appends, fsyncs and rotations since the writer was opened.
+    pub fn stats(&self) -> JournalStats {
+        self.stats
+    }
+
+    /// Register a callback fired whenever the fsynced watermark advances.
+    ///
+    /// Lets upstream services release pending acknowledgements in batch.
+    pub fn set_durable_callback(&mut self, callback: impl FnMut(u64) + Send + 'static) {
+        self.on_durable = Some(Box::new(callback));
+    }
+
     /// Get the current file path.
     pub fn current_file_path(&self) -> &Path {
         &self.current_file
     }
 
     /// Append a journal entry. Validates sequence monotonicity.
-    pub fn append(&mut self, entry: &JournalEntry) -> Result<(), JournalError> {
+    ///
+    /// Returns a receipt with the entry's location in the current file.
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<WriteReceipt, JournalError> {
+        self.check_allocated(entry.sequence..entry.sequence + 1, entry.timestamp)?;
+
         // Validate sequence ordering (spec §14.6)
         if self.next_sequence > 0 && entry.sequence != self.next_sequence {
             return Err(JournalError::SequenceError {
@@ -353,25 +729,106 @@ impl JournalWriter {
             });
         }
 
+        if self.renew_cipher {
+            self.renew_cipher()?;
+        }
         // Check if rotation is needed
-        if self.current_file_size >= self.config.max_file_size {
+        if self.should_rotate(self.current_file_size, entry.timestamp) {
             self.rotate()?;
         }
 
-        let bytes = entry.to_bytes();
+        let mut bytes = self.pending_header(entry);
+        let offset = self.current_file_size + bytes.len() as u64;
+        let frame = self.encode(entry, offset);
+        let written = frame.len() as u64;
+        bytes.extend_from_slice(&frame);
         self.write_atomic(&bytes)?;
+        self.header_pending = false;
+        self.note_timestamp(entry.timestamp);
+
+        let data_offset = offset - self.data_start;
+        if let Some(index) = self.index.as_mut() {
+            index.record(entry.sequence, data_offset)?;
+        }
 
-        let written = bytes.len() as u64;
-        self.current_file_size += written;
-        self.total_size += written;
+        self.current_file_size += bytes.len() as u64;
+        self.total_size += bytes.len() as u64;
+        self.stats.appends += 1;
+        self.stats.bytes_written += bytes.len() as u64;
         self.next_sequence = entry.sequence + 1;
+        self.last_appended_sequence = entry.sequence;
         self.writes_since_flush += 1;
         self.writes_since_fsync += 1;
+        if let Some(allocator) = &self.allocator {
+            allocator.mark_written(&(entry.sequence..entry.sequence + 1));
+        }
 
         self.apply_flush_policy()?;
         self.apply_fsync_policy()?;
 
-        Ok(())
+        Ok(WriteReceipt {
+            sequence: entry.sequence,
+            file_index: self.file_index,
+            offset,
+            len: written,
+        })
+    }
+
+    /// Append a contiguous run of entries as one group commit.
+    ///
+    /// Sequences must continue from `next_sequence`. All entries are
+    /// serialized into one buffer and written with a single `write_all` per
+    /// file; rotation happens between entries exactly where single appends
+    /// would rotate, never inside one. The flush/fsync policy is applied
+    /// once for the batch, counting every entry towards `EveryN`.
+    ///
+    /// Pending buffered appends are flushed first. If any write fails the
+    /// batch is rolled back on disk and `next_sequence` is left untouched,
+    /// so the same batch can be retried.
+    pub fn append_batch(
+        &mut self,
+        entries: &[JournalEntry],
+    ) -> Result<Vec<WriteReceipt>, JournalError> {
+        let Some(first) = entries.first() else {
+            return Ok(Vec::new());
+        };
+        let last = entries[entries.len() - 1].sequence;
+        self.check_allocated(first.sequence..last + 1, first.timestamp)?;
+
+        // Validate sequence ordering (spec §14.6)
+        let start = if self.next_sequence > 0 {
+            self.next_sequence
+        } else {
+            first.sequence
+        };
+        for (expected, entry) in (start..).zip(entries) {
+            if entry.sequence != expected {
+                return Err(JournalError::SequenceError {
+                    expected,
+                    got: entry.sequence,
+                });
+            }
+        }
+
+        // Check total size limit
+        if self.config.max_total_size > 0 && self.total_size >= self.config.max_total_size {
+            return Err(JournalError::SizeLimitExceeded {
+                current: self.total_size,
+                limit: self.config.max_total_size,
+            });
+        }
+
+        if self.renew_cipher {
+            self.renew_cipher()?;
+        }
+        let mark = self.batch_mark()?;
+        match self.write_batch(entries) {
+            Ok(receipts) => Ok(receipts),
+            Err(err) => {
+                self.rollback(mark)?;
+                Err(err)
+            }
+        }
     }
 
     /// Create a new entry and append it in one call.
@@ -390,19 +847,299 @@ impl JournalWriter {
     /// Force flush + fsync (used before shutdown / rotation).
     pub fn sync(&mut self) -> Result<(), JournalError> {
         self.writer.flush()?;
+        if let Some(index) = self.index.as_mut() {
+            index.sync()?;
+        }
+        self.last_flushed_sequence = self.last_appended_sequence;
         self.writer.get_ref().sync_all()?;
+        self.stats.fsyncs += 1;
         self.writes_since_flush = 0;
         self.writes_since_fsync = 0;
+        self.advance_fsynced();
         Ok(())
     }
 
+    /// Delete closed segments expired under [`JournalConfig::retention`]
+    /// whose entries are all covered by a snapshot at `snapshot_sequence`.
+    ///
+    /// Segments are considered oldest first and deletion stops at the first
+    /// one that must stay, so the remaining journal has no holes. The
+    /// active file is never deleted, and neither is a segment that cannot
+    /// be read back cleanly. Returns the deleted segment paths.
+    pub fn apply_retention(&mut self, snapshot_sequence: u64) -> Result<Vec<PathBuf>, JournalError> {
+        let policy = self.config.retention;
+        if policy.max_age == 0 && policy.max_segments == 0 {
+            return Ok(Vec::new());
+        }
+
+        let segments = Self::segment_paths(&self.config.dir)?;
+        let mut remaining = segments.len();
+        let mut removed = Vec::new();
+
+        for path in segments {
+            if path == self.current_file {
+                break;
+            }
+            let key = self.config.encryption_key.as_ref();
+            let Some((last_sequence, last_timestamp)) = Self::segment_tail(&path, key) else {
+                break;
+            };
+            let covered = last_sequence.is_none_or(|seq| seq <= snapshot_sequence);
+            let too_old = policy.max_age > 0
+                && match (last_timestamp, self.last_timestamp) {
+                    (Some(last), Some(now)) => now.saturating_sub(last) >= policy.max_age,
+                    (None, _) => true,
+                    (Some(_), None) => false,
+                };
+            let too_many = policy.max_segments > 0 && remaining > policy.max_segments;
+            if !covered || !(too_old || too_many) {
+                break;
+            }
+
+            let bytes = fs::metadata(&path)?.len();
+            fs::remove_file(&path)?;
+            let idx = index::index_path(&path);
+            if idx.is_file() {
+                fs::remove_file(idx)?;
+            }
+            self.total_size = self.total_size.saturating_sub(bytes);
+            remaining -= 1;
+            removed.push(path);
+        }
+
+        if !removed.is_empty() {
+            File::open(&self.config.dir)?.sync_all()?;
+        }
+        Ok(removed)
+    }
+
+    /// [`apply_retention`](Self::apply_retention) against the latest valid
+    /// snapshot in `snapshot_dir`. Without a snapshot nothing is deleted.
+    pub fn apply_retention_with_snapshots(
+        &mut self,
+        snapshot_dir: &Path,
+    ) -> Result<Vec<PathBuf>, JournalError> {
+        match SnapshotLoader::new(snapshot_dir).load_latest() {
+            Ok(snapshot) => self.apply_retention(snapshot.sequence),
+            Err(SnapshotError::NoSnapshots) => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
     // ── Internal Helpers ────────────────────────────────────────────
 
+    /// With an allocator, refuse sequences it never handed out and void a
+    /// released gap before `range`. Ordering is checked by the caller.
+    fn check_allocated(&mut self, range: Range<u64>, timestamp: i64) -> Result<(), JournalError> {
+        let Some(allocator) = &self.allocator else {
+            return Ok(());
+        };
+        allocator.check_issued(&range)?;
+        let gap = self.next_sequence..range.start;
+        if allocator.take_released(&gap) {
+            self.void(gap, self.last_timestamp.unwrap_or(timestamp))?;
+        }
+        Ok(())
+    }
+
+    /// Append a [`VOIDED_EVENT_TYPE`] entry for every sequence in `range`.
+    fn void(&mut self, range: Range<u64>, timestamp: i64) -> Result<(), JournalError> {
+        let entries: Vec<JournalEntry> = range
+            .map(|seq| JournalEntry::new(seq, timestamp, VOIDED_EVENT_TYPE.into(), Vec::new()))
+            .collect();
+        self.append_batch(&entries)?;
+        Ok(())
+    }
+
+    /// Last sequence and timestamp in the journal, from the newest segment
+    /// holding an entry.
+    fn journal_tail(&self) -> Result<(Option<u64>, Option<i64>), JournalError> {
+        let key = self.config.encryption_key.as_ref();
+        for path in Self::segment_paths(&self.config.dir)?.iter().rev() {
+            match Self::segment_tail(path, key) {
+                Some((None, _)) => continue,
+                Some(tail) => return Ok(tail),
+                None => {
+                    return Err(AllocatorError::Journal(format!(
+                        "cannot read {}",
+                        path.display()
+                    ))
+                    .into())
+                }
+            }
+        }
+        Ok((None, None))
+    }
+
+    /// Last sequence and timestamp of a closed segment (`None` fields if it
+    /// is empty), or `None` if it does not read back cleanly.
+    fn segment_tail(
+        path: &Path,
+        key: Option<&EncryptionKey>,
+    ) -> Option<(Option<u64>, Option<i64>)> {
+        let mut reader = JournalReader::open_file_with(path, key).ok()?;
+        let mut tail = (None, None);
+        while let Some(entry) = reader.next_entry().ok()? {
+            tail = (Some(entry.sequence), Some(entry.timestamp));
+        }
+        Some(tail)
+    }
+
     fn write_atomic(&mut self, data: &[u8]) -> Result<(), JournalError> {
         self.writer.write_all(data)?;
         Ok(())
     }
 
+    fn write_batch(&mut self, entries: &[JournalEntry]) -> Result<Vec<WriteReceipt>, JournalError> {
+        let mut receipts = Vec::with_capacity(entries.len());
+        let mut buf = Vec::new();
+
+        for entry in entries {
+            if self.should_rotate(self.current_file_size + buf.len() as u64, entry.timestamp) {
+                self.write_chunk(&mut buf)?;
+                self.rotate()?;
+            }
+            self.note_timestamp(entry.timestamp);
+            buf.extend(self.pending_header(entry));
+            self.header_pending = false;
+
+            let offset = self.current_file_size + buf.len() as u64;
+            let bytes = self.encode(entry, offset);
+            let data_offset = offset - self.data_start;
+            if let Some(index) = self.index.as_mut() {
+                index.record(entry.sequence, data_offset)?;
+            }
+            receipts.push(WriteReceipt {
+                sequence: entry.sequence,
+                file_index: self.file_index,
+                offset,
+                len: bytes.len() as u64,
+            });
+            buf.extend_from_slice(&bytes);
+        }
+        self.write_chunk(&mut buf)?;
+
+        let last = entries[entries.len() - 1].sequence;
+        self.next_sequence = last + 1;
+        self.last_appended_sequence = last;
+        self.writes_since_flush += entries.len();
+        self.writes_since_fsync += entries.len();
+        self.stats.appends += entries.len() as u64;
+        if let Some(allocator) = &self.allocator {
+            allocator.mark_written(&(entries[0].sequence..last + 1));
+        }
+
+        self.apply_flush_policy()?;
+        self.apply_fsync_policy()?;
+        Ok(receipts)
+    }
+
+    fn write_chunk(&mut self, buf: &mut Vec<u8>) -> Result<(), JournalError> {
+        if buf.is_empty() {
+            return Ok(());
+        }
+        self.write_atomic(buf)?;
+        self.current_file_size += buf.len() as u64;
+        self.total_size += buf.len() as u64;
+        self.stats.bytes_written += buf.len() as u64;
+        buf.clear();
+        Ok(())
+    }
+
+    fn batch_mark(&mut self) -> Result<BatchMark, JournalError> {
+        // Rollback truncates the file, so earlier appends must be on disk
+        self.writer.flush()?;
+        self.writes_since_flush = 0;
+        self.last_flushed_sequence = self.last_appended_sequence;
+
+        let index = match self.index.as_mut() {
+            Some(index) => Some(index.checkpoint()?),
+            None => None,
+        };
+        Ok(BatchMark {
+            file_index: self.file_index,
+            current_file: self.current_file.clone(),
+            cipher: self.cipher.clone(),
+            current_file_size: self.current_file_size,
+            data_start: self.data_start,
+            header_pending: self.header_pending,
+            current_file_first_timestamp: self.current_file_first_timestamp,
+            last_timestamp: self.last_timestamp,
+            total_size: self.total_size,
+            next_sequence: self.next_sequence,
+            last_appended_sequence: self.last_appended_sequence,
+            last_flushed_sequence: self.last_flushed_sequence,
+            writes_since_flush: self.writes_since_flush,
+            writes_since_fsync: self.writes_since_fsync,
+            index,
+        })
+    }
+
+    /// Undo a partially written batch: drop files created by rotation and
+    /// truncate the original file (and its index) back to the mark.
+    ///
+    /// Offsets sealed past the mark of an encrypted file have used their
+    /// nonces, so the next write continues under a fresh nonce base; see
+    /// [`renew_cipher`](Self::renew_cipher).
+    fn rollback(&mut self, mark: BatchMark) -> Result<(), JournalError> {
+        self.index = None;
+        for idx in mark.file_index + 1..=self.file_index {
+            let path = Self::journal_path(&self.config.dir, idx);
+            for path in [index::index_path(&path), path] {
+                if path.is_file() {
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+
+        let file = OpenOptions::new().append(true).open(&mark.current_file)?;
+        let truncated = file.metadata()?.len() > mark.current_file_size;
+        file.set_len(mark.current_file_size)?;
+        // Discard unwritten batch bytes instead of flushing them on drop
+        let _ = std::mem::replace(&mut self.writer, BufWriter::new(file)).into_parts();
+        if let Some(checkpoint) = mark.index {
+            self.index = Some(IndexWriter::reopen(
+                &mark.current_file,
+                self.config.index_interval,
+                checkpoint,
+            )?);
+        }
+
+        self.file_index = mark.file_index;
+        self.current_file = mark.current_file;
+        self.cipher = mark.cipher;
+        self.current_file_size = mark.current_file_size;
+        self.data_start = mark.data_start;
+        self.header_pending = mark.header_pending;
+        self.current_file_first_timestamp = mark.current_file_first_timestamp;
+        self.last_timestamp = mark.last_timestamp;
+        self.total_size = mark.total_size;
+        self.next_sequence = mark.next_sequence;
+        self.last_appended_sequence = mark.last_appended_sequence;
+        self.last_flushed_sequence = mark.last_flushed_sequence;
+        self.writes_since_flush = mark.writes_since_flush;
+        self.writes_since_fsync = mark.writes_since_fsync;
+        self.renew_cipher = truncated && self.cipher.is_some();
+        Ok(())
+    }
+
+    /// Move writing to a fresh nonce base after the current encrypted file
+    /// was truncated, so no offset is sealed twice under one nonce. A file
+    /// without frames is started over with a new header; otherwise the
+    /// writer rotates and the truncated file takes no further writes.
+    fn renew_cipher(&mut self) -> Result<(), JournalError> {
+        if self.header_pending {
+            self.writer.get_ref().set_len(0)?;
+            let (file, cipher) = Self::open_segment(&self.config, &self.current_file)?;
+            self.writer = BufWriter::new(file);
+            self.cipher = cipher;
+        } else {
+            self.rotate()?;
+        }
+        self.renew_cipher = false;
+        Ok(())
+    }
+
     fn apply_flush_policy(&mut self) -> Result<(), JournalError> {
         let should_flush = match self.config.flush_policy {
             FlushPolicy::EveryWrite => true,
@@ -410,7 +1147,9 @@ impl JournalWriter {
         };
         if should_flush {
             self.writer.flush()?;
+            self.flush_index()?;
             self.writes_since_flush = 0;
+            self.last_flushed_sequence = self.last_appended_sequence;
         }
         Ok(())
     }
@@ -422,12 +1161,28 @@ impl JournalWriter {
             FsyncPolicy::OnRotation => false,
         };
         if should_fsync {
+            // Buffered bytes must reach the OS before fsync makes them durable
+            self.writer.flush()?;
+            self.flush_index()?;
+            self.writes_since_flush = 0;
+            self.last_flushed_sequence = self.last_appended_sequence;
             self.writer.get_ref().sync_all()?;
+            self.stats.fsyncs += 1;
             self.writes_since_fsync = 0;
+            self.advance_fsynced();
         }
         Ok(())
     }
 
+    fn advance_fsynced(&mut self) {
+        if self.last_flushed_sequence > self.last_fsynced_sequence {
+            self.last_fsynced_sequence = self.last_flushed_sequence;
+            if let Some(callback) = self.on_durable.as_mut() {
+                callback(self.last_fsynced_sequence);
+            }
+        }
+    }
+
     fn rotate(&mut self) -> Result<(), JournalError> {
         // Fsync current file before rotating
         self.sync()?;
@@ -435,13 +1190,112 @@ impl JournalWriter {
         self.file_index += 1;
         self.current_file = Self::journal_path(&self.config.dir, self.file_index);
 
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.current_file)?;
+        let (file, cipher) = Self::open_segment(&self.config, &self.current_file)?;
+        let header_len = file.metadata()?.len();
 
         self.writer = BufWriter::new(file);
-        self.current_file_size = 0;
+        self.cipher = cipher;
+        self.index = Self::open_index(&self.config, &self.current_file)?;
+        self.current_file_size = header_len;
+        self.data_start = header_len + header::HEADER_LEN as u64;
+        self.header_pending = true;
+        self.total_size += header_len;
+        self.stats.rotations += 1;
+        self.stats.bytes_written += header_len;
+        self.current_file_first_timestamp = None;
+        Ok(())
+    }
+
+    /// Open a segment for appending. With encryption enabled a new segment
+    /// gets its header via temp file + rename, so it is never visible
+    /// without one; an existing segment's header must match the key.
+    fn open_segment(
+        config: &JournalConfig,
+        path: &Path,
+    ) -> Result<(File, Option<FileCipher>), JournalError> {
+        let cipher = match &config.encryption_key {
+            None => None,
+            Some(key) if fs::metadata(path).map_or(true, |m| m.len() == 0) => {
+                let (cipher, header) = FileCipher::create(key)?;
+                let tmp = path.with_extension("bin.tmp");
+                {
+                    let mut file = File::create(&tmp)?;
+                    file.write_all(&header)?;
+                    file.sync_all()?;
+                }
+                fs::rename(&tmp, path)?;
+                Some(cipher)
+            }
+            Some(key) => {
+                let header = encryption::read_header(&mut File::open(path)?)?.ok_or_else(|| {
+                    EncryptionError::Mismatch(format!("{} is plaintext", path.display()))
+                })?;
+                Some(FileCipher::from_header(key, &header)?)
+            }
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok((file, cipher))
+    }
+
+    /// Serialize an entry as the frame written at `position`.
+    fn encode(&self, entry: &JournalEntry, position: u64) -> Vec<u8> {
+        let mut bytes = entry.to_bytes_with_threshold(self.config.compression_threshold);
+        if let Some(cipher) = &self.cipher {
+            cipher.seal_frame(position, &mut bytes);
+        }
+        bytes
+    }
+
+    /// File header bytes to write ahead of `entry`, if it is the first
+    /// entry of the current file.
+    fn pending_header(&self, entry: &JournalEntry) -> Vec<u8> {
+        if !self.header_pending {
+            return Vec::new();
+        }
+        FileHeader::new(FileKind::Journal, entry.timestamp, entry.sequence)
+            .encode()
+            .to_vec()
+    }
+
+    /// Whether the next entry (at `timestamp`) must start a new file, given
+    /// `size` bytes already in the current one.
+    fn should_rotate(&self, size: u64, timestamp: i64) -> bool {
+        if size >= self.config.max_file_size {
+            return true;
+        }
+        match self.current_file_first_timestamp {
+            Some(first) if self.config.max_file_age_nanos > 0 => {
+                timestamp.saturating_sub(first) >= self.config.max_file_age_nanos
+            }
+            _ => false,
+        }
+    }
+
+    fn note_timestamp(&mut self, timestamp: i64) {
+        self.current_file_first_timestamp.get_or_insert(timestamp);
+        self.last_timestamp = Some(self.last_timestamp.map_or(timestamp, |t| t.max(timestamp)));
+    }
+
+    /// Timestamp of the first readable entry in a journal file.
+    fn first_timestamp(path: &Path, key: Option<&EncryptionKey>) -> Option<i64> {
+        let mut reader = JournalReader::open_file_with(path, key).ok()?;
+        reader.next_entry().ok().flatten().map(|e| e.timestamp)
+    }
+
+    fn open_index(
+        config: &JournalConfig,
+        journal_path: &Path,
+    ) -> Result<Option<IndexWriter>, JournalError> {
+        if config.index_interval == 0 {
+            return Ok(None);
+        }
+        Ok(Some(IndexWriter::open(journal_path, config.index_interval)?))
+    }
+
+    fn flush_index(&mut self) -> Result<(), JournalError> {
+        if let Some(index) = self.index.as_mut() {
+            index.flush()?;
+        }
         Ok(())
     }
 
@@ -450,34 +1304,47 @@ impl JournalWriter {
     }
 
     fn find_latest_index(dir: &Path) -> u64 {
-        fs::read_dir(dir)
+        Self::segment_indices(dir)
             .ok()
-            .map(|entries| {
-                entries
-                    .filter_map(|e| e.ok())
-                    .filter_map(|e| {
-                        let name = e.file_name().to_string_lossy().to_string();
-                        if name.starts_with("journal-") && name.ends_with(".bin") {
-                            name.trim_start_matches("journal-")
-                                .trim_end_matches(".bin")
-                                .parse::<u64>()
-                                .ok()
-                        } else {
-                            None
-                        }
-                    })
-                    .max()
-                    .unwrap_or(0)
-            })
+            .and_then(|indices| indices.into_iter().max())
             .unwrap_or(0)
     }
 
+    fn segment_indices(dir: &Path) -> io::Result<Vec<u64>> {
+        let mut indices: Vec<u64> = fs::read_dir(dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                if name.starts_with("journal-") && name.ends_with(".bin") {
+                    name.trim_start_matches("journal-")
+                        .trim_end_matches(".bin")
+                        .parse::<u64>()
+                        .ok()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        indices.sort_unstable();
+        Ok(indices)
+    }
+
+    /// Journal segment paths, oldest first.
+    fn segment_paths(dir: &Path) -> Result<Vec<PathBuf>, JournalError> {
+        Ok(Self::segment_indices(dir)?
+            .into_iter()
+            .map(|idx| Self::journal_path(dir, idx))
+            .collect())
+    }
+
     fn compute_total_size(dir: &Path) -> Result<u64, JournalError> {
         let mut total = 0u64;
         if dir.exists() {
             for entry in fs::read_dir(dir)? {
                 let entry = entry?;
-                if entry.file_type()?.is_file() {
+                // Index sidecars are not journal data
+                let is_journal = entry.file_name().to_string_lossy().ends_with(".bin");
+                if entry.file_type()?.is_file() && is_journal {
                     total += entry.metadata()?.len();
                 }
             }
@@ -486,6 +1353,51 @@ impl JournalWriter {
     }
 }
 
+// ── Key Rotation ────────────────────────────────────────────────────
+
+/// Re-encrypt every journal segment in `dir` from key `old` to key `new`.
+///
+/// Run with the writer stopped. Every segment header is checked before
+/// anything is rewritten; each segment is then resealed into a temp file,
+/// fsynced and renamed over the original. Segments already under `new` are
+/// skipped, so an interrupted rotation can simply be rerun. Frame sizes do
+/// not change, so index sidecars stay valid. Returns the rewritten segments.
+pub fn rotate_encryption_key(
+    dir: &Path,
+    old: &EncryptionKey,
+    new: &EncryptionKey,
+) -> Result<Vec<PathBuf>, JournalError> {
+    let segments = JournalWriter::segment_paths(dir)?;
+    encryption::check_files(&segments, true)?;
+    let mut pending = Vec::new();
+    for path in segments {
+        if let Some(header) = encryption::read_header(&mut File::open(&path)?)? {
+            if encryption::needs_rotation(&header, old, new)? {
+                pending.push(path);
+            }
+        }
+    }
+
+    let mut rotated = Vec::new();
+    for path in pending {
+        let mut file = File::open(&path)?;
+        let len = file.metadata()?.len();
+        let preamble = match header::probe(&mut file, FileKind::Journal, HEADER_LEN as u64, len)? {
+            HeaderProbe::Present(found) => found.encode().to_vec(),
+            HeaderProbe::Absent | HeaderProbe::Incomplete => Vec::new(),
+        };
+        let from = (HEADER_LEN + preamble.len()) as u64;
+        let tmp = path.with_extension("bin.rekey.tmp");
+        encryption::reseal_segment(&path, from, &preamble, old, new, &tmp)?;
+        fs::rename(&tmp, &path)?;
+        rotated.push(path);
+    }
+    if !rotated.is_empty() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(rotated)
+}
+
 // ── Tests ───────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -633,6 +1545,33 @@ mod tests {
         assert!(files.len() > 1, "Expected rotation to create multiple files");
     }
 
+    #[test]
+    fn test_stats_count_appends_fsyncs_and_rotations() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            fsync_policy: FsyncPolicy::EveryN(5),
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        assert_eq!(writer.stats(), JournalStats::default());
+
+        for seq in 1..=10 {
+            writer.append(&sample_entry(seq)).unwrap();
+        }
+        let batch: Vec<_> = (11..=15).map(sample_entry).collect();
+        writer.append_batch(&batch).unwrap();
+        let stats = writer.stats();
+        assert_eq!((stats.appends, stats.fsyncs, stats.rotations), (15, 3, 0));
+
+        // Rotation fsyncs the old file first
+        writer.rotate().unwrap();
+        writer.append(&sample_entry(16)).unwrap();
+        let stats = writer.stats();
+        assert_eq!((stats.appends, stats.fsyncs, stats.rotations), (16, 4, 1));
+        assert_eq!(stats.bytes_written, writer.total_size);
+    }
+
     #[test]
     fn test_journal_size_limit() {
         let tmp = TempDir::new().unwrap();
@@ -715,4 +1654,548 @@ mod tests {
         }
         assert_eq!(writer.next_sequence(), 11);
     }
+
+    #[test]
+    fn test_fsynced_watermark_every_n() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            fsync_policy: FsyncPolicy::EveryN(10),
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+
+        for seq in 1..=15 {
+            writer.append(&sample_entry(seq)).unwrap();
+        }
+        assert_eq!(writer.next_sequence(), 16);
+        assert_eq!(writer.last_flushed_sequence(), 15);
+        assert_eq!(writer.last_fsynced_sequence(), 10);
+
+        writer.sync().unwrap();
+        assert_eq!(writer.last_fsynced_sequence(), 15);
+    }
+
+    #[test]
+    fn test_flushed_watermark_lags_buffered_writes() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            flush_policy: FlushPolicy::EveryN(4),
+            fsync_policy: FsyncPolicy::OnRotation,
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+
+        for seq in 1..=6 {
+            writer.append(&sample_entry(seq)).unwrap();
+        }
+        assert_eq!(writer.last_flushed_sequence(), 4);
+        assert_eq!(writer.last_fsynced_sequence(), 0);
+    }
+
+    #[test]
+    fn test_write_receipt_offsets() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = JournalWriter::open(test_config(tmp.path())).unwrap();
+        writer.set_next_sequence(1);
+
+        let first = writer.append(&sample_entry(1)).unwrap();
+        let second = writer.append(&sample_entry(2)).unwrap();
+        assert_eq!(first.sequence, 1);
+        // The first frame follows the file header
+        assert_eq!(first.offset, header::HEADER_LEN as u64);
+        assert_eq!(first.len, sample_entry(1).to_bytes().len() as u64);
+        assert_eq!(second.offset, first.offset + first.len);
+        assert_eq!(second.file_index, first.file_index);
+
+        // The receipt points at a decodable entry
+        let data = fs::read(writer.current_file_path()).unwrap();
+        let (entry, _) = JournalEntry::from_bytes(&data[second.offset as usize..]).unwrap();
+        assert_eq!(entry.sequence, 2);
+    }
+
+    #[test]
+    fn test_durable_callback_fires_on_advance() {
+        use std::sync::{Arc, Mutex};
+
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            fsync_policy: FsyncPolicy::EveryN(3),
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        writer.set_durable_callback(move |seq| sink.lock().unwrap().push(seq));
+
+        for seq in 1..=7 {
+            writer.append(&sample_entry(seq)).unwrap();
+        }
+        writer.sync().unwrap();
+        // A second sync with nothing new must not re-fire
+        writer.sync().unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![3, 6, 7]);
+    }
+
+    fn journal_files(dir: &Path) -> Vec<(String, Vec<u8>)> {
+        let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                (name, fs::read(e.path()).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_append_batch_matches_single_appends() {
+        let single_dir = TempDir::new().unwrap();
+        let batch_dir = TempDir::new().unwrap();
+        let config = |dir: &Path| JournalConfig {
+            max_file_size: 49 * 6,
+            index_interval: 4,
+            ..test_config(dir)
+        };
+
+        let mut single = JournalWriter::open(config(single_dir.path())).unwrap();
+        single.set_next_sequence(1);
+        let single_receipts: Vec<WriteReceipt> = (1..=50)
+            .map(|seq| single.append(&sample_entry(seq)).unwrap())
+            .collect();
+
+        let mut batched = JournalWriter::open(config(batch_dir.path())).unwrap();
+        batched.set_next_sequence(1);
+        let entries: Vec<JournalEntry> = (1..=50).map(sample_entry).collect();
+        let mut batch_receipts = Vec::new();
+        for chunk in entries.chunks(7) {
+            batch_receipts.extend(batched.append_batch(chunk).unwrap());
+        }
+
+        assert_eq!(batch_receipts, single_receipts);
+        assert_eq!(batched.next_sequence(), 51);
+        assert_eq!(batched.last_fsynced_sequence(), 50);
+        assert_eq!(journal_files(batch_dir.path()), journal_files(single_dir.path()));
+    }
+
+    #[test]
+    fn test_append_batch_rejects_non_contiguous() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = JournalWriter::open(test_config(tmp.path())).unwrap();
+        writer.set_next_sequence(1);
+
+        let entries = vec![sample_entry(1), sample_entry(2), sample_entry(4)];
+        match writer.append_batch(&entries) {
+            Err(JournalError::SequenceError { expected, got }) => {
+                assert_eq!(expected, 3);
+                assert_eq!(got, 4);
+            }
+            other => panic!("Expected SequenceError, got: {:?}", other),
+        }
+        assert_eq!(writer.next_sequence(), 1);
+        assert_eq!(fs::metadata(writer.current_file_path()).unwrap().len(), 0);
+        assert!(writer.append_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_batch_failure_rolls_back() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            max_file_size: 49 * 5,
+            index_interval: 2,
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=3 {
+            writer.append(&sample_entry(seq)).unwrap();
+        }
+        let before = journal_files(tmp.path());
+
+        // The batch has to rotate after entry 5; make opening the next file fail
+        let blocker = tmp.path().join("journal-000001.bin");
+        fs::create_dir(&blocker).unwrap();
+        let batch: Vec<JournalEntry> = (4..=8).map(sample_entry).collect();
+        assert!(matches!(
+            writer.append_batch(&batch),
+            Err(JournalError::Io(_))
+        ));
+        assert_eq!(writer.next_sequence(), 4);
+        assert_eq!(writer.last_fsynced_sequence(), 3);
+        fs::remove_dir(&blocker).unwrap();
+        assert_eq!(journal_files(tmp.path()), before);
+
+        // Retrying the same batch lands exactly where it would have
+        writer.append_batch(&batch).unwrap();
+        writer.sync().unwrap();
+        let mut reader = crate::reader::JournalReader::open(tmp.path()).unwrap();
+        let seqs: Vec<u64> = reader
+            .read_all_validated()
+            .unwrap()
+            .iter()
+            .map(|e| e.sequence)
+            .collect();
+        assert_eq!(seqs, (1..=8).collect::<Vec<_>>());
+        let first = crate::index::SegmentIndex::load(&tmp.path().join("journal-000000.bin"))
+            .unwrap();
+        let indexed: Vec<u64> = first.records().iter().map(|r| r.sequence).collect();
+        assert_eq!(indexed, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_append_batch_group_commit_fsyncs_once_per_batch() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        let run = |batch: usize| {
+            let tmp = TempDir::new().unwrap();
+            let mut writer = JournalWriter::open(test_config(tmp.path())).unwrap();
+            writer.set_next_sequence(1);
+            let fsyncs = Arc::new(AtomicU64::new(0));
+            let counter = Arc::clone(&fsyncs);
+            writer.set_durable_callback(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+
+            let entries: Vec<JournalEntry> = (1..=1_000).map(sample_entry).collect();
+            if batch == 1 {
+                for entry in &entries {
+                    writer.append(entry).unwrap();
+                }
+            } else {
+                for chunk in entries.chunks(batch) {
+                    writer.append_batch(chunk).unwrap();
+                }
+            }
+            assert_eq!(writer.last_fsynced_sequence(), 1_000);
+            fsyncs.load(Ordering::Relaxed)
+        };
+
+        // FsyncPolicy::EveryWrite: one fsync per append vs one per batch
+        assert_eq!(run(1), 1_000);
+        assert_eq!(run(100), 10);
+    }
+
+    /// Book-snapshot-like JSON: many similar price levels
+    fn realistic_payload() -> Vec<u8> {
+        let levels: Vec<String> = (0..400)
+            .map(|i| {
+                format!(
+                    "{{\"price\":\"{}.{:02}\",\"quantity\":\"{}.{:04}\",\"orders\":{}}}",
+                    50_000 - i,
+                    (i * 7) % 100,
+                    1 + i % 13,
+                    (i * 37) % 10_000,
+                    1 + i % 5
+                )
+            })
+            .collect();
+        format!("{{\"market\":\"BTC-USDT\",\"bids\":[{}]}}", levels.join(",")).into_bytes()
+    }
+
+    /// Frame in the original (pre-flags) layout, built by hand
+    fn legacy_frame(entry: &JournalEntry) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&entry.sequence.to_le_bytes());
+        body.extend_from_slice(&entry.timestamp.to_le_bytes());
+        body.extend_from_slice(&(entry.event_type.len() as u16).to_le_bytes());
+        body.extend_from_slice(entry.event_type.as_bytes());
+        body.extend_from_slice(&(entry.payload.len() as u32).to_le_bytes());
+        body.extend_from_slice(&entry.payload);
+        body.extend_from_slice(&entry.checksum.to_le_bytes());
+        let mut frame = (body.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(&body);
+        frame
+    }
+
+    #[test]
+    fn test_compressed_entry_roundtrip() {
+        let entry = JournalEntry::new(7, 1_000, "BookSnapshot".into(), realistic_payload());
+        let bytes = entry.to_bytes();
+        let et_len = u16::from_le_bytes([bytes[20], bytes[21]]);
+        assert_ne!(et_len & 0x8000, 0, "expected the compressed layout");
+
+        let (decoded, consumed) = JournalEntry::from_bytes(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(decoded, entry);
+        assert!(decoded.verify_checksum());
+    }
+
+    #[test]
+    fn test_uncompressed_entries_keep_legacy_layout() {
+        // Below the threshold, disabled, or not worth it: original layout
+        let small = sample_entry(1);
+        assert_eq!(small.to_bytes(), legacy_frame(&small));
+
+        let large = JournalEntry::new(2, 2_000, "BookSnapshot".into(), realistic_payload());
+        assert_eq!(large.to_bytes_with_threshold(0), legacy_frame(&large));
+
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let noise: Vec<u8> = (0..8192)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        let incompressible = JournalEntry::new(3, 3_000, "Blob".into(), noise);
+        assert_eq!(incompressible.to_bytes(), legacy_frame(&incompressible));
+    }
+
+    #[test]
+    fn test_legacy_frames_still_parse() {
+        // Old journals hold large payloads uncompressed
+        let entry = JournalEntry::new(9, 9_000, "BookSnapshot".into(), realistic_payload());
+        let frame = legacy_frame(&entry);
+        let (decoded, consumed) = JournalEntry::from_bytes(&frame).unwrap();
+        assert_eq!(consumed, frame.len());
+        assert_eq!(decoded, entry);
+    }
+
+    #[test]
+    fn test_unknown_entry_flags_rejected() {
+        let entry = JournalEntry::new(7, 1_000, "BookSnapshot".into(), realistic_payload());
+        let mut bytes = entry.to_bytes();
+        bytes[22 + "BookSnapshot".len()] |= 0x80;
+        assert!(matches!(
+            JournalEntry::from_bytes(&bytes),
+            Err(JournalError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_implausible_uncompressed_length_rejected() {
+        let entry = JournalEntry::new(7, 1_000, "BookSnapshot".into(), realistic_payload());
+        let mut bytes = entry.to_bytes();
+        let raw_len = 23 + "BookSnapshot".len();
+        bytes[raw_len..raw_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            JournalEntry::from_bytes(&bytes),
+            Err(JournalError::Decompression(_))
+        ));
+    }
+
+    #[test]
+    fn test_compression_saves_space_on_realistic_payload() {
+        let tmp = TempDir::new().unwrap();
+        let write = |dir: &Path, compression_threshold: usize| {
+            let config = JournalConfig {
+                compression_threshold,
+                ..test_config(dir)
+            };
+            let mut writer = JournalWriter::open(config).unwrap();
+            writer.set_next_sequence(1);
+            for seq in 1..=20 {
+                let entry = JournalEntry::new(seq, seq as i64, "BookSnapshot".into(), realistic_payload());
+                writer.append(&entry).unwrap();
+            }
+            writer.sync().unwrap();
+            fs::metadata(writer.current_file_path()).unwrap().len()
+        };
+
+        let raw = write(&tmp.path().join("raw"), 0);
+        let compressed = write(&tmp.path().join("zstd"), COMPRESSION_THRESHOLD);
+        assert!(
+            compressed * 4 < raw,
+            "compressed journal {compressed} bytes vs raw {raw} bytes"
+        );
+
+        let mut reader = crate::reader::JournalReader::open(&tmp.path().join("zstd")).unwrap();
+        let entries = reader.read_all_validated().unwrap();
+        assert_eq!(entries.len(), 20);
+        assert!(entries.iter().all(|e| e.payload == realistic_payload()));
+    }
+
+    // ── Time-based rotation / retention ─────────────────────────────
+
+    const SECOND: i64 = 1_000_000_000;
+
+    fn timed_entry(seq: u64, secs: i64) -> JournalEntry {
+        let sample = sample_entry(seq);
+        JournalEntry::new(seq, secs * SECOND, sample.event_type, sample.payload)
+    }
+
+    /// Sequences held by each segment, oldest segment first.
+    fn segment_sequences(dir: &Path) -> Vec<Vec<u64>> {
+        JournalWriter::segment_paths(dir)
+            .unwrap()
+            .iter()
+            .map(|path| {
+                let mut reader = JournalReader::open_file(path).unwrap();
+                let mut seqs = Vec::new();
+                while let Some(entry) = reader.next_entry().unwrap() {
+                    seqs.push(entry.sequence);
+                }
+                seqs
+            })
+            .collect()
+    }
+
+    /// Five segments of six entries, 10s of exchange time apart.
+    fn aged_journal(dir: &Path, retention: RetentionPolicy) -> JournalWriter {
+        let config = JournalConfig {
+            max_file_age_nanos: 60 * SECOND,
+            retention,
+            ..test_config(dir)
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=30 {
+            writer.append(&timed_entry(seq, seq as i64 * 10)).unwrap();
+        }
+        writer
+    }
+
+    #[test]
+    fn test_rotation_on_age_limit() {
+        let tmp = TempDir::new().unwrap();
+        let writer = aged_journal(tmp.path(), RetentionPolicy::default());
+        drop(writer);
+
+        let segments = segment_sequences(tmp.path());
+        assert_eq!(segments.len(), 5);
+        for (i, seqs) in segments.iter().enumerate() {
+            let first = i as u64 * 6 + 1;
+            assert_eq!(seqs, &(first..first + 6).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_size_and_age_rotation_in_one_stream() {
+        let single_dir = TempDir::new().unwrap();
+        let batch_dir = TempDir::new().unwrap();
+        let config = |dir: &Path| JournalConfig {
+            max_file_size: 49 * 4,
+            max_file_age_nanos: 100 * SECOND,
+            ..test_config(dir)
+        };
+        // Dense burst (size rotates every 4 entries), a quiet gap, another burst
+        let entries: Vec<JournalEntry> = (1..=15)
+            .map(|seq| {
+                let secs = if seq <= 10 { seq as i64 } else { 190 + seq as i64 };
+                timed_entry(seq, secs)
+            })
+            .collect();
+
+        let mut single = JournalWriter::open(config(single_dir.path())).unwrap();
+        single.set_next_sequence(1);
+        for entry in &entries {
+            single.append(entry).unwrap();
+        }
+
+        let mut batched = JournalWriter::open(config(batch_dir.path())).unwrap();
+        batched.set_next_sequence(1);
+        for chunk in entries.chunks(6) {
+            batched.append_batch(chunk).unwrap();
+        }
+
+        assert_eq!(
+            segment_sequences(single_dir.path()),
+            vec![
+                vec![1, 2, 3, 4],
+                vec![5, 6, 7, 8],
+                vec![9, 10], // closed early by age
+                vec![11, 12, 13, 14],
+                vec![15],
+            ]
+        );
+        assert_eq!(journal_files(batch_dir.path()), journal_files(single_dir.path()));
+    }
+
+    #[test]
+    fn test_age_rotation_resumes_after_reopen() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig {
+            max_file_age_nanos: 60 * SECOND,
+            ..test_config(tmp.path())
+        };
+        let mut writer = JournalWriter::open(config.clone()).unwrap();
+        writer.set_next_sequence(1);
+        writer.append(&timed_entry(1, 0)).unwrap();
+        writer.append(&timed_entry(2, 30)).unwrap();
+        drop(writer);
+
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(3);
+        writer.append(&timed_entry(3, 59)).unwrap();
+        writer.append(&timed_entry(4, 60)).unwrap();
+        assert_eq!(segment_sequences(tmp.path()), vec![vec![1, 2, 3], vec![4]]);
+    }
+
+    #[test]
+    fn test_retention_only_deletes_snapshot_covered_segments() {
+        let tmp = TempDir::new().unwrap();
+        let retention = RetentionPolicy {
+            max_age: 60 * SECOND,
+            max_segments: 0,
+        };
+        let mut writer = aged_journal(tmp.path(), retention);
+        let total_before = writer.total_size;
+
+        // Segments 0..=3 are expired, but only the first two are covered
+        let removed = writer.apply_retention(14).unwrap();
+        assert_eq!(
+            removed,
+            vec![
+                tmp.path().join("journal-000000.bin"),
+                tmp.path().join("journal-000001.bin"),
+            ]
+        );
+        assert_eq!(
+            writer.total_size,
+            total_before - 2 * (header::HEADER_LEN as u64 + 6 * 49)
+        );
+        assert!(!tmp.path().join("journal-000000.idx").exists());
+        assert_eq!(segment_sequences(tmp.path())[0][0], 13);
+
+        // Even full coverage never touches the active segment
+        writer.apply_retention(u64::MAX).unwrap();
+        assert_eq!(segment_sequences(tmp.path()), vec![(25..=30).collect::<Vec<_>>()]);
+
+        writer.append(&timed_entry(31, 310)).unwrap();
+        assert_eq!(writer.next_sequence(), 32);
+    }
+
+    #[test]
+    fn test_retention_without_policy_keeps_everything() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = aged_journal(tmp.path(), RetentionPolicy::default());
+        assert!(writer.apply_retention(u64::MAX).unwrap().is_empty());
+        assert_eq!(segment_sequences(tmp.path()).len(), 5);
+    }
+
+    #[test]
+    fn test_retention_by_segment_count_uses_latest_snapshot() {
+        use crate::snapshot::{EngineState, Snapshot, SnapshotWriter};
+
+        let tmp = TempDir::new().unwrap();
+        let snapshots = TempDir::new().unwrap();
+        let retention = RetentionPolicy {
+            max_age: 0,
+            max_segments: 2,
+        };
+        let mut writer = aged_journal(tmp.path(), retention);
+
+        // No snapshot yet: nothing is covered
+        assert!(writer
+            .apply_retention_with_snapshots(snapshots.path())
+            .unwrap()
+            .is_empty());
+
+        let snapshot_writer = SnapshotWriter::new(snapshots.path(), false);
+        for seq in [6, 12] {
+            let snapshot = Snapshot::new(seq, 0, EngineState::empty(), false);
+            snapshot_writer.write(&snapshot).unwrap();
+        }
+        let removed = writer.apply_retention_with_snapshots(snapshots.path()).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(segment_sequences(tmp.path()).len(), 3);
+    }
 }
@@ -10,9 +10,35 @@
 //! - §11 Replay Requirements (deterministic replay, snapshots)
 //! - §12 Determinism Rules (no side effects, sorted iteration)
 //! - §14 Sequence Numbering (gapless, monotonic)
+//!
+//! Also generates per-account statements from the journal (`statements`)
+//! and offers a background-thread journal writer (`async_writer`).
+//! Journal segments and snapshots can be encrypted at rest with AES-256-GCM
+//! (`encryption`) and carry a versioned file header (`header`). When replay
+//! diverges, `state_diff` reports where two engine states disagree. Replay
+//! can decode segments on worker threads (`parallel`). For audits and fixtures the journal converts to and from
+//! line-delimited JSON (`export`). Payload types are looked up per event
+//! type in an `EventRegistry` (`registry`). Producers sharing one journal
+//! reserve sequence ranges from a `SequenceAllocator` (`allocator`).
+//! Account order and trade history is served in cursor-paginated pages from
+//! an index maintained from events (`history`). Events journaled for a
+//! gateway request carry its id in a `Correlated` wrapper (`correlation`).
 
 pub mod journal;
+pub mod allocator;
+pub mod registry;
+pub mod correlation;
+pub mod async_writer;
 pub mod reader;
+pub mod parallel;
+pub mod index;
+pub mod encryption;
+pub mod header;
+pub mod compaction;
 pub mod snapshot;
+pub mod state_diff;
 pub mod recovery;
 pub mod determinism;
+pub mod statements;
+pub mod history;
+pub mod export;
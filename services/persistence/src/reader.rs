@@ -4,18 +4,30 @@
 //! §10.8.1 (CRC32C checksum validation).
 //!
 //! Features:
-//! - Sequential entry reading from journal files
+//! - Sequential entry reading from journal files, streamed one frame at a
+//!   time through a reusable buffer (memory independent of journal size)
 //! - CRC32C checksum validation on every read
 //! - Corruption detection with byte-offset reporting
 //! - Partial recovery: skip corrupted tail, recover valid prefix
 //! - Offset tracking for replay-from-offset
+//! - Index-assisted seeking via advisory segment indexes (`crate::index`)
+//! - Tail-follow mode for live consumers (`poll_entry`, `follow`)
+//! - Transparent decryption of encrypted segments (`open_encrypted`)
+//! - File header validation, legacy header-less segments still accepted
+//!   (`crate::header`)
+//! - Multi-threaded segment decoding for replay (`crate::parallel`)
 //! - Gapless / monotonic sequence validation (spec §14.6)
 //! - Missing sequence detection and alerting
 
+use crate::encryption::{self, EncryptionError, EncryptionKey, FileCipher, HEADER_LEN};
+use crate::header::{self, FileHeader, FileKind, HeaderError, HeaderProbe};
+use crate::index::SegmentIndex;
 use crate::journal::{JournalEntry, JournalError};
 use std::fs::{self, File};
-use std::io::{self, Read};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 use thiserror::Error;
 
 // ── Errors ──────────────────────────────────────────────────────────
@@ -42,6 +54,13 @@ pub enum ReaderError {
 
     #[error("Sequence not monotonic: prev={prev}, current={current}")]
     NotMonotonic { prev: u64, current: u64 },
+
+    #[error("Encryption error: {0}")]
+    Encryption(#[from] EncryptionError),
+
+    /// Unreadable file header, including one from a newer format version.
+    #[error("File header error: {0}")]
+    Header(#[from] HeaderError),
 }
 
 // ── Corruption Log Entry ────────────────────────────────────────────
@@ -63,40 +82,128 @@ pub enum CorruptionKind {
     TruncatedEntry,
     InvalidUtf8,
     UnexpectedEof,
+    DecompressionFailed,
+    AuthenticationFailed,
 }
 
 // ── Journal Reader ──────────────────────────────────────────────────
 
+/// Result of staging the next frame into the reader's buffer.
+enum FrameRead {
+    /// A complete `[len][body]` frame is in the buffer.
+    Ready,
+    /// The rest of the current file cannot hold a frame and was skipped.
+    Unreadable { offset: u64, remaining: u64 },
+    /// An encrypted frame failed authentication; it must be consumed.
+    Undecryptable { offset: u64, detail: String },
+    /// All files exhausted (or, when following, nothing new yet).
+    Exhausted,
+}
+
 /// Sequential journal reader with checksum validation and corruption detection.
+///
+/// Entries are streamed from a buffered file handle one frame at a time, so
+/// memory use is bounded by the largest entry rather than the journal size.
 pub struct JournalReader {
+    /// Directory the journal files live in.
+    dir: PathBuf,
     /// All journal file paths, sorted by index.
     files: Vec<PathBuf>,
     /// Index of the current file being read.
     current_file_idx: usize,
-    /// Raw data of the current file.
-    data: Vec<u8>,
-    /// Current read position within `data`.
-    pos: usize,
+    /// Buffered handle on the current file.
+    file: Option<BufReader<File>>,
+    /// Length of the current file when it was opened (refreshed when
+    /// following).
+    file_len: u64,
+    /// Byte position of the next unconsumed frame within the current file.
+    file_pos: u64,
+    /// Reusable buffer holding the current frame (length prefix + body),
+    /// decrypted if the file is encrypted.
+    frame: Vec<u8>,
+    /// Size of the current frame on disk.
+    frame_disk_len: u64,
+    /// Whether `frame` holds a frame that has not been consumed yet.
+    frame_ready: bool,
     /// Global byte offset (across all files).
     global_offset: u64,
     /// Last successfully read sequence number.
     last_sequence: Option<u64>,
     /// Accumulated corruption records.
     corruption_log: Vec<CorruptionRecord>,
+    /// Key for encrypted journals (`None` = plaintext only).
+    key: Option<EncryptionKey>,
+    /// Cipher of the current file, if it is encrypted.
+    cipher: Option<FileCipher>,
+    /// File header of the current file (`None` for a legacy segment).
+    header: Option<FileHeader>,
+    /// Offset of the first frame in the current file, past all headers.
+    data_start: u64,
+    /// The current file was too short to tell whether it has a header;
+    /// look again once it grows.
+    header_pending: bool,
+    /// Entry whose checksum failed on the last read, kept for tools that
+    /// report it (`crate::export`).
+    rejected: Option<JournalEntry>,
 }
 
 impl JournalReader {
     /// Open a reader over all journal files in the given directory.
     pub fn open(dir: &Path) -> Result<Self, ReaderError> {
         let files = Self::discover_files(dir)?;
+        Self::from_files(dir, files, None)
+    }
+
+    /// Open a reader over an encrypted journal directory.
+    ///
+    /// Fails with [`EncryptionError::DecryptionFailed`] if `key` is not the
+    /// one the journal was written with, and with
+    /// [`EncryptionError::Mismatch`] if any segment is plaintext.
+    pub fn open_encrypted(dir: &Path, key: &EncryptionKey) -> Result<Self, ReaderError> {
+        let files = Self::discover_files(dir)?;
+        Self::from_files(dir, files, Some(key.clone()))
+    }
+
+    /// Open a reader over a single journal segment file.
+    pub fn open_file(path: &Path) -> Result<Self, ReaderError> {
+        Self::open_file_with(path, None)
+    }
+
+    /// Open a reader over a single segment, encrypted if `key` is given.
+    pub(crate) fn open_file_with(
+        path: &Path,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Self, ReaderError> {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::from_files(dir, vec![path.to_path_buf()], key.cloned())
+    }
+
+    fn from_files(
+        dir: &Path,
+        files: Vec<PathBuf>,
+        key: Option<EncryptionKey>,
+    ) -> Result<Self, ReaderError> {
+        // Refuse a directory mixing encrypted and plaintext segments
+        encryption::check_files(&files, key.is_some())?;
         let mut reader = Self {
+            dir: dir.to_path_buf(),
             files,
             current_file_idx: 0,
-            data: Vec::new(),
-            pos: 0,
+            file: None,
+            file_len: 0,
+            file_pos: 0,
+            frame: Vec::new(),
+            frame_disk_len: 0,
+            frame_ready: false,
             global_offset: 0,
             last_sequence: None,
             corruption_log: Vec::new(),
+            key,
+            cipher: None,
+            header: None,
+            data_start: 0,
+            header_pending: false,
+            rejected: None,
         };
         reader.load_current_file()?;
         Ok(reader)
@@ -106,18 +213,57 @@ impl JournalReader {
     ///
     /// Returns `None` when all entries have been read.
     pub fn next_entry(&mut self) -> Result<Option<JournalEntry>, ReaderError> {
+        self.read_entry(false)
+    }
+
+    /// Read the next entry if one is fully written, without blocking.
+    ///
+    /// Unlike [`next_entry`](Self::next_entry) this picks up entries
+    /// appended since the reader was opened and newly rotated
+    /// `journal-*.bin` files. An incomplete frame at the tail of the newest
+    /// file is "not yet available" (`None`), not corruption.
+    pub fn poll_entry(&mut self) -> Result<Option<JournalEntry>, ReaderError> {
+        self.read_entry(true)
+    }
+
+    /// Follow the journal as a live stream.
+    ///
+    /// The iterator yields every entry in sequence order and, at the end of
+    /// the journal, sleeps `poll_interval` between polls for new appends; it
+    /// never ends on its own.
+    pub fn follow(&mut self, poll_interval: Duration) -> Follow<'_> {
+        Follow {
+            reader: self,
+            poll_interval,
+        }
+    }
+
+    fn read_entry(&mut self, tail: bool) -> Result<Option<JournalEntry>, ReaderError> {
+        self.rejected = None;
         loop {
-            if self.pos >= self.data.len() {
-                if !self.advance_file()? {
-                    return Ok(None); // All files exhausted
+            match self.fill_frame(tail)? {
+                FrameRead::Exhausted => return Ok(None),
+                FrameRead::Unreadable { offset, remaining } => {
+                    self.log_truncated(offset, remaining);
+                    continue;
+                }
+                FrameRead::Undecryptable { offset, detail } => {
+                    // Framing is intact, so only this entry is lost
+                    self.consume_frame();
+                    self.corruption_log.push(CorruptionRecord {
+                        byte_offset: offset,
+                        kind: CorruptionKind::AuthenticationFailed,
+                        detail: detail.clone(),
+                    });
+                    return Err(ReaderError::Corruption { offset, detail });
                 }
+                FrameRead::Ready => {}
             }
 
             let offset_before = self.global_offset;
-            match JournalEntry::from_bytes(&self.data[self.pos..]) {
-                Ok((entry, consumed)) => {
-                    self.pos += consumed;
-                    self.global_offset += consumed as u64;
+            match JournalEntry::from_bytes(&self.frame) {
+                Ok((entry, _)) => {
+                    self.consume_frame();
 
                     // Validate checksum (spec §10.8.1)
                     if !entry.verify_checksum() {
@@ -129,30 +275,35 @@ impl JournalReader {
                                 entry.sequence, entry.checksum
                             ),
                         });
+                        let sequence = entry.sequence;
+                        self.rejected = Some(entry);
                         return Err(ReaderError::ChecksumMismatch {
                             offset: offset_before,
-                            sequence: entry.sequence,
+                            sequence,
                         });
                     }
 
                     self.last_sequence = Some(entry.sequence);
                     return Ok(Some(entry));
                 }
+                Err(JournalError::Decompression(detail)) => {
+                    // Framing is intact, so only this entry is lost
+                    let sequence = self.frame_sequence();
+                    self.consume_frame();
+                    self.corruption_log.push(CorruptionRecord {
+                        byte_offset: offset_before,
+                        kind: CorruptionKind::DecompressionFailed,
+                        detail: format!("Compressed payload of seq={}: {}", sequence, detail),
+                    });
+                    return Err(ReaderError::Corruption {
+                        offset: offset_before,
+                        detail,
+                    });
+                }
                 Err(_) => {
-                    // Could be truncated entry at end of file
-                    let remaining = self.data.len() - self.pos;
-                    if remaining > 0 {
-                        self.corruption_log.push(CorruptionRecord {
-                            byte_offset: offset_before,
-                            kind: CorruptionKind::TruncatedEntry,
-                            detail: format!(
-                                "Truncated entry: {} bytes remaining, cannot parse",
-                                remaining
-                            ),
-                        });
-                    }
-                    // Try next file
-                    self.pos = self.data.len();
+                    // Could be truncated entry at end of file; try next file
+                    let remaining = self.discard_file();
+                    self.log_truncated(offset_before, remaining);
                 }
             }
         }
@@ -192,29 +343,42 @@ impl JournalReader {
     /// Seek to the first entry with `sequence >= target_seq`.
     ///
     /// Entries before `target_seq` are skipped. Returns the number of
-    /// entries skipped.
+    /// entries skipped. Segment indexes (`crate::index`) are used to jump
+    /// close to the target first; the scan then finishes linearly.
     pub fn seek_to_sequence(&mut self, target_seq: u64) -> Result<u64, ReaderError> {
-        let mut skipped = 0u64;
+        let mut skipped = self.jump_with_index(target_seq)?;
         loop {
-            if self.pos >= self.data.len() {
-                if !self.advance_file()? {
-                    break; // All files exhausted
+            match self.fill_frame(false)? {
+                FrameRead::Exhausted => break, // All files exhausted
+                FrameRead::Unreadable { .. } => continue,
+                FrameRead::Undecryptable { .. } => {
+                    self.consume_frame();
+                    continue;
                 }
+                FrameRead::Ready => {}
             }
 
-            match JournalEntry::from_bytes(&self.data[self.pos..]) {
-                Ok((entry, consumed)) => {
+            match JournalEntry::from_bytes(&self.frame) {
+                Ok((entry, _)) => {
                     if entry.sequence >= target_seq {
-                        // Don't consume; leave positioned for next_entry()
+                        // Don't consume; leave buffered for next_entry()
                         break;
                     }
-                    self.pos += consumed;
-                    self.global_offset += consumed as u64;
+                    self.consume_frame();
                     self.last_sequence = Some(entry.sequence);
                     skipped += 1;
                 }
+                Err(JournalError::Decompression(_)) => {
+                    // Leave it for next_entry() to report if it is the target
+                    if self.frame_sequence() >= target_seq {
+                        break;
+                    }
+                    self.last_sequence = Some(self.frame_sequence());
+                    self.consume_frame();
+                    skipped += 1;
+                }
                 Err(_) => {
-                    self.pos = self.data.len();
+                    self.discard_file();
                 }
             }
         }
@@ -230,9 +394,9 @@ impl JournalReader {
             match self.next_entry() {
                 Ok(Some(entry)) => entries.push(entry),
                 Ok(None) => break,
-                Err(ReaderError::ChecksumMismatch { offset, .. }) => {
-                    // Skip past the corrupted entry by scanning for next valid frame
-                    self.skip_corrupted_region(offset);
+                Err(ReaderError::ChecksumMismatch { .. } | ReaderError::Corruption { .. }) => {
+                    // The bad entry's length prefix was intact, so the
+                    // reader already sits on the next frame boundary.
                 }
                 Err(_) => break,
             }
@@ -252,6 +416,12 @@ impl JournalReader {
         self.last_sequence
     }
 
+    /// File header of the current segment, `None` for a legacy segment
+    /// (or one whose first write has not landed yet).
+    pub fn segment_header(&self) -> Option<&FileHeader> {
+        self.header.as_ref()
+    }
+
     /// Get all accumulated corruption records.
     pub fn corruption_log(&self) -> &[CorruptionRecord] {
         &self.corruption_log
@@ -295,7 +465,40 @@ impl JournalReader {
 
     // ── Internal Helpers ────────────────────────────────────────────
 
-    fn discover_files(dir: &Path) -> Result<Vec<PathBuf>, ReaderError> {
+    /// Detach the files after the current one, leaving this reader to
+    /// finish the current file only.
+    pub(crate) fn split_off_later_files(&mut self) -> Vec<PathBuf> {
+        let at = (self.current_file_idx + 1).min(self.files.len());
+        self.files.split_off(at)
+    }
+
+    /// Take the entry rejected by the last read for a checksum mismatch.
+    pub(crate) fn take_rejected(&mut self) -> Option<JournalEntry> {
+        self.rejected.take()
+    }
+
+    /// Key this reader decrypts with, if any.
+    pub(crate) fn key(&self) -> Option<&EncryptionKey> {
+        self.key.as_ref()
+    }
+
+    /// Account for progress decoded outside this reader as if it had been
+    /// read here: the global offset after it, the sequence it produced (if
+    /// any), and the corruption it logged.
+    pub(crate) fn absorb(
+        &mut self,
+        global_offset: u64,
+        sequence: Option<u64>,
+        corruption: impl IntoIterator<Item = CorruptionRecord>,
+    ) {
+        self.global_offset = global_offset;
+        if sequence.is_some() {
+            self.last_sequence = sequence;
+        }
+        self.corruption_log.extend(corruption);
+    }
+
+    pub(crate) fn discover_files(dir: &Path) -> Result<Vec<PathBuf>, ReaderError> {
         if !dir.exists() {
             return Ok(Vec::new());
         }
@@ -321,22 +524,129 @@ impl JournalReader {
         Ok(files.into_iter().map(|(_, p)| p).collect())
     }
 
+    /// Use segment indexes to skip whole files and jump within a file to the
+    /// last indexed entry below `target_seq`.
+    ///
+    /// Only applies from the start of a file, where the index anchor gives
+    /// the sequence at the read position; skip counts rely on sequences
+    /// being gapless within the journal (spec §14.6). Any missing or
+    /// inconsistent index stops the jump and leaves the rest to the scan.
+    /// Index offsets count from the first frame, after any encryption and
+    /// file header.
+    fn jump_with_index(&mut self, target_seq: u64) -> Result<u64, ReaderError> {
+        let mut skipped = 0u64;
+        if self.file.is_none() || self.file_pos != self.data_start || self.frame_ready {
+            return Ok(0);
+        }
+        let Some(mut index) = SegmentIndex::load(&self.files[self.current_file_idx]) else {
+            return Ok(0);
+        };
+
+        // Skip files whose successor starts below the target, so at least
+        // one entry is still read and `last_sequence` stays exact
+        while let Some(next) = self
+            .files
+            .get(self.current_file_idx + 1)
+            .and_then(|path| SegmentIndex::load(path))
+        {
+            if next.first_sequence() >= target_seq
+                || next.first_sequence() <= index.first_sequence()
+            {
+                break;
+            }
+            skipped += next.first_sequence() - index.first_sequence();
+            self.global_offset += self.file_len - self.file_pos;
+            self.advance_file()?;
+            index = next;
+        }
+
+        let Some(record) = index.floor_below(target_seq) else {
+            return Ok(skipped);
+        };
+        let data_start = self.data_start;
+        if record.offset == 0 || data_start + record.offset >= self.file_len {
+            return Ok(skipped);
+        }
+
+        // Verify the indexed frame before trusting the jump
+        let file = self.file.as_mut().expect("checked above");
+        file.seek(SeekFrom::Start(data_start + record.offset))?;
+        self.file_pos = data_start + record.offset;
+        let verified = matches!(self.fill_frame(false)?, FrameRead::Ready)
+            && JournalEntry::from_bytes(&self.frame)
+                .is_ok_and(|(entry, _)| entry.sequence == record.sequence);
+        if verified {
+            skipped += record.sequence - index.first_sequence();
+            self.global_offset += record.offset;
+        } else {
+            self.frame_ready = false;
+            self.file_pos = data_start;
+            let file = self.file.as_mut().expect("checked above");
+            file.seek(SeekFrom::Start(data_start))?;
+        }
+        Ok(skipped)
+    }
+
     fn load_current_file(&mut self) -> Result<(), ReaderError> {
+        self.frame_ready = false;
+        self.file_pos = 0;
+        self.cipher = None;
+        self.header = None;
+        self.header_pending = false;
         if self.current_file_idx < self.files.len() {
-            let mut file = File::open(&self.files[self.current_file_idx])?;
-            self.data.clear();
-            file.read_to_end(&mut self.data)?;
-            self.pos = 0;
+            let path = &self.files[self.current_file_idx];
+            let mut file = File::open(path)?;
+            self.file_len = file.metadata()?.len();
+            match (encryption::read_header(&mut file)?, &self.key) {
+                (Some(header), Some(key)) => {
+                    self.cipher = Some(FileCipher::from_header(key, &header)?);
+                    self.file_pos = HEADER_LEN as u64;
+                    self.global_offset += HEADER_LEN as u64;
+                }
+                (None, None) => {}
+                (None, Some(_)) if self.file_len == 0 => {}
+                (header, _) => {
+                    let found = if header.is_some() { "encrypted" } else { "plaintext" };
+                    return Err(EncryptionError::Mismatch(format!(
+                        "{} is {}",
+                        path.display(),
+                        found
+                    ))
+                    .into());
+                }
+            }
+            self.file = Some(BufReader::new(file));
+            self.read_file_header()?;
         } else {
-            self.data.clear();
-            self.pos = 0;
+            self.file = None;
+            self.file_len = 0;
         }
         Ok(())
     }
 
+    /// Read the journal file header at `file_pos`, if there is one, and set
+    /// `data_start` past it.
+    ///
+    /// A segment's header goes out with its first frame, so a file seen
+    /// before that write leaves `header_pending` set.
+    fn read_file_header(&mut self) -> Result<(), ReaderError> {
+        let file = self.file.as_mut().expect("file loaded");
+        let probe = header::probe(file, FileKind::Journal, self.file_pos, self.file_len)?;
+        self.header_pending = probe == HeaderProbe::Incomplete;
+        if let HeaderProbe::Present(found) = probe {
+            self.header = Some(found);
+            self.file_pos += header::HEADER_LEN as u64;
+            self.global_offset += header::HEADER_LEN as u64;
+        }
+        self.data_start = self.file_pos;
+        Ok(())
+    }
+
+    /// Move to the next file, staying on the last one when there is none
+    /// so a follower can pick up later appends.
     fn advance_file(&mut self) -> Result<bool, ReaderError> {
-        self.current_file_idx += 1;
-        if self.current_file_idx < self.files.len() {
+        if self.current_file_idx + 1 < self.files.len() {
+            self.current_file_idx += 1;
             self.load_current_file()?;
             Ok(true)
         } else {
@@ -344,26 +654,199 @@ impl JournalReader {
         }
     }
 
-    fn skip_corrupted_region(&mut self, _offset: u64) {
-        // Skip forward byte-by-byte looking for a valid length prefix
-        while self.pos < self.data.len() {
-            self.pos += 1;
-            self.global_offset += 1;
-            if self.pos + 4 <= self.data.len() {
-                let len = u32::from_le_bytes([
-                    self.data[self.pos],
-                    self.data[self.pos + 1],
-                    self.data[self.pos + 2],
-                    self.data[self.pos + 3],
-                ]) as usize;
-                if len > 0
-                    && len < 10_000_000
-                    && self.pos + 4 + len <= self.data.len()
-                {
-                    // Possible valid entry found, stop skipping
-                    break;
+    /// Pick up journal files created since the last discovery.
+    fn refresh_files(&mut self) -> Result<(), ReaderError> {
+        let found = Self::discover_files(&self.dir)?;
+        let newer: Vec<PathBuf> = match self.files.last() {
+            Some(last) => found
+                .into_iter()
+                .skip_while(|p| p != last)
+                .skip(1)
+                .collect(),
+            None => found,
+        };
+        self.files.extend(newer);
+        if self.file.is_none() {
+            self.load_current_file()?;
+        }
+        Ok(())
+    }
+
+    /// Re-read the current file's length, returning whether it grew.
+    fn refresh_len(&mut self) -> Result<bool, ReaderError> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(false);
+        };
+        let len = file.get_ref().metadata()?.len();
+        let grew = len > self.file_len;
+        if grew {
+            self.file_len = len;
+            // Drop anything read ahead past the old length
+            file.seek(SeekFrom::Start(self.file_pos))?;
+        }
+        Ok(grew)
+    }
+
+    /// When following, decide whether an incomplete frame of `total` bytes
+    /// at `file_pos` is still being written. Rewinds to the frame start.
+    fn tail_pending(&mut self, total: u64) -> Result<bool, ReaderError> {
+        self.refresh_files()?;
+        let rotated = self.current_file_idx + 1 < self.files.len();
+        self.refresh_len()?;
+        let file = self.file.as_mut().expect("following an open file");
+        file.seek(SeekFrom::Start(self.file_pos))?;
+        Ok(!rotated && self.file_pos + total > self.file_len)
+    }
+
+    /// Stage the next frame into `self.frame`, advancing across files.
+    ///
+    /// Only the length prefix and exactly the body it announces are read; a
+    /// prefix claiming more bytes than the file holds marks the remainder of
+    /// the file unreadable without allocating for it. With `tail` set, new
+    /// files and appends are picked up and an incomplete frame still being
+    /// written reports `Exhausted` instead.
+    fn fill_frame(&mut self, tail: bool) -> Result<FrameRead, ReaderError> {
+        if self.frame_ready {
+            return Ok(FrameRead::Ready);
+        }
+        loop {
+            if self.file.is_none() {
+                if tail {
+                    self.refresh_files()?;
+                }
+                if self.file.is_none() {
+                    return Ok(FrameRead::Exhausted);
+                }
+            }
+            let remaining = self.file_len - self.file_pos;
+            if remaining == 0 {
+                if tail {
+                    // Discover first: once a newer file exists the writer
+                    // is done with this one, so its length is final
+                    self.refresh_files()?;
+                    if self.refresh_len()? {
+                        continue;
+                    }
+                }
+                if self.advance_file()? {
+                    continue;
+                }
+                return Ok(FrameRead::Exhausted);
+            }
+
+            let offset = self.global_offset;
+            if self.header_pending {
+                self.read_file_header()?;
+                if !self.header_pending {
+                    continue;
+                }
+                let len = header::HEADER_LEN as u64;
+                if tail && self.tail_pending(len)? {
+                    return Ok(FrameRead::Exhausted);
+                }
+                if self.file_len - self.file_pos >= len {
+                    continue;
+                }
+                // A torn first write
+                let remaining = self.discard_file();
+                return Ok(FrameRead::Unreadable { offset, remaining });
+            }
+            if remaining < 4 {
+                if tail && self.tail_pending(4)? {
+                    return Ok(FrameRead::Exhausted);
+                }
+                if self.file_len - self.file_pos >= 4 {
+                    continue;
+                }
+                let remaining = self.discard_file();
+                return Ok(FrameRead::Unreadable { offset, remaining });
+            }
+
+            let file = self.file.as_mut().expect("checked above");
+            let mut prefix = [0u8; 4];
+            file.read_exact(&mut prefix)?;
+            let total = 4 + u32::from_le_bytes(prefix) as u64;
+            if total > remaining {
+                if tail && self.tail_pending(total)? {
+                    return Ok(FrameRead::Exhausted);
+                }
+                if total <= self.file_len - self.file_pos {
+                    continue;
+                }
+                let remaining = self.discard_file();
+                return Ok(FrameRead::Unreadable { offset, remaining });
+            }
+
+            self.frame.clear();
+            self.frame.extend_from_slice(&prefix);
+            self.frame.resize(total as usize, 0);
+            file.read_exact(&mut self.frame[4..])?;
+            self.frame_disk_len = total;
+            if let Some(cipher) = &self.cipher {
+                if let Err(err) = cipher.open_frame(self.file_pos, &mut self.frame) {
+                    return Ok(FrameRead::Undecryptable {
+                        offset,
+                        detail: err.to_string(),
+                    });
                 }
             }
+            self.frame_ready = true;
+            return Ok(FrameRead::Ready);
+        }
+    }
+
+    /// Sequence number from the buffered frame's header.
+    fn frame_sequence(&self) -> u64 {
+        u64::from_le_bytes(self.frame[4..12].try_into().unwrap())
+    }
+
+    /// Mark the staged frame as read.
+    fn consume_frame(&mut self) {
+        self.frame_ready = false;
+        self.file_pos += self.frame_disk_len;
+        self.global_offset += self.frame_disk_len;
+    }
+
+    /// Give up on the rest of the current file, returning how many bytes
+    /// were left unread.
+    fn discard_file(&mut self) -> u64 {
+        let remaining = self.file_len - self.file_pos;
+        self.frame_ready = false;
+        self.header_pending = false;
+        self.file_pos = self.file_len;
+        remaining
+    }
+
+    fn log_truncated(&mut self, offset: u64, remaining: u64) {
+        self.corruption_log.push(CorruptionRecord {
+            byte_offset: offset,
+            kind: CorruptionKind::TruncatedEntry,
+            detail: format!(
+                "Truncated entry: {} bytes remaining, cannot parse",
+                remaining
+            ),
+        });
+    }
+}
+
+// ── Follow ──────────────────────────────────────────────────────────
+
+/// Blocking live stream over a journal, see [`JournalReader::follow`].
+pub struct Follow<'a> {
+    reader: &'a mut JournalReader,
+    poll_interval: Duration,
+}
+
+impl Iterator for Follow<'_> {
+    type Item = Result<JournalEntry, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.poll_entry() {
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Ok(None) => thread::sleep(self.poll_interval),
+                Err(e) => return Some(Err(e)),
+            }
         }
     }
 }
@@ -419,8 +902,8 @@ mod tests {
         let path = files[0].path();
         let mut data = fs::read(&path).unwrap();
         // Corrupt a byte deep in the file (not the length prefix)
-        if data.len() > 30 {
-            data[28] ^= 0xFF;
+        if data.len() > header::HEADER_LEN + 30 {
+            data[header::HEADER_LEN + 28] ^= 0xFF;
         }
         fs::write(&path, &data).unwrap();
 
@@ -448,8 +931,8 @@ mod tests {
         let path = files[0].path();
         let mut data = fs::read(&path).unwrap();
         // Corrupt byte 28 (inside first or second entry)
-        if data.len() > 30 {
-            data[28] ^= 0xFF;
+        if data.len() > header::HEADER_LEN + 30 {
+            data[header::HEADER_LEN + 28] ^= 0xFF;
         }
         fs::write(&path, &data).unwrap();
 
@@ -536,8 +1019,8 @@ mod tests {
             .collect();
         let path = files[0].path();
         let mut data = fs::read(&path).unwrap();
-        if data.len() > 30 {
-            data[28] ^= 0xFF;
+        if data.len() > header::HEADER_LEN + 30 {
+            data[header::HEADER_LEN + 28] ^= 0xFF;
         }
         fs::write(&path, &data).unwrap();
 
@@ -614,4 +1097,234 @@ mod tests {
             other => panic!("Expected NotMonotonic, got: {:?}", other),
         }
     }
+
+    #[test]
+    fn test_truncated_tail_keeps_valid_prefix() {
+        let tmp = TempDir::new().unwrap();
+        write_test_entries(tmp.path(), 10);
+
+        let path = tmp.path().join("journal-000000.bin");
+        let data = fs::read(&path).unwrap();
+        // Cut the last entry in half
+        fs::write(&path, &data[..data.len() - 20]).unwrap();
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        let entries = reader.read_all().unwrap();
+        assert_eq!(entries.len(), 9);
+        assert_eq!(reader.last_sequence(), Some(9));
+
+        let log = reader.corruption_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].kind, CorruptionKind::TruncatedEntry);
+        assert_eq!(log[0].byte_offset, reader.current_offset());
+    }
+
+    #[test]
+    fn test_recovery_resumes_after_checksum_mismatch() {
+        let tmp = TempDir::new().unwrap();
+        write_test_entries(tmp.path(), 10);
+
+        let path = tmp.path().join("journal-000000.bin");
+        let mut data = fs::read(&path).unwrap();
+        // Flip a payload byte of the third entry; its framing stays intact
+        let hdr = header::HEADER_LEN;
+        let frame_len = (data.len() - hdr) / 10;
+        data[hdr + frame_len * 2 + 40] ^= 0xFF;
+        fs::write(&path, &data).unwrap();
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        let (entries, corruptions) = reader.recover_entries();
+        let seqs: Vec<u64> = entries.iter().map(|e| e.sequence).collect();
+        assert_eq!(seqs, vec![1, 2, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(corruptions.len(), 1);
+        assert_eq!(corruptions[0].kind, CorruptionKind::ChecksumMismatch);
+        assert_eq!(corruptions[0].byte_offset, (hdr + frame_len * 2) as u64);
+    }
+
+    fn write_indexed_entries(dir: &Path, count: u64, index_interval: u64) {
+        let config = JournalConfig {
+            max_file_size: 46 * 30, // 30 entries per file
+            index_interval,
+            ..JournalConfig::new(dir)
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=count {
+            let entry = JournalEntry::new(
+                seq,
+                1_000_000_000 + (seq as i64 * 1_000),
+                format!("Event{}", seq % 3),
+                vec![seq as u8; 10],
+            );
+            writer.append(&entry).unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    /// (skipped, next sequence, offset, last sequence) after a seek
+    fn seek_result(dir: &Path, target: u64) -> (u64, Option<u64>, u64, Option<u64>) {
+        let mut reader = JournalReader::open(dir).unwrap();
+        let skipped = reader.seek_to_sequence(target).unwrap();
+        let offset = reader.current_offset();
+        let last = reader.last_sequence();
+        let next = reader.next_entry().unwrap().map(|e| e.sequence);
+        (skipped, next, offset, last)
+    }
+
+    #[test]
+    fn test_index_seek_into_rotated_series() {
+        let tmp = TempDir::new().unwrap();
+        write_indexed_entries(tmp.path(), 200, 8);
+        assert!(tmp.path().join("journal-000004.idx").exists());
+
+        // Break the framing of the second entry in the first file: a linear
+        // scan would lose the rest of that file, the index never reads it
+        let path = tmp.path().join("journal-000000.bin");
+        let mut data = fs::read(&path).unwrap();
+        let second = header::HEADER_LEN + 46;
+        data[second..second + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &data).unwrap();
+
+        let (skipped, next, offset, last) = seek_result(tmp.path(), 137);
+        assert_eq!(skipped, 136);
+        assert_eq!(next, Some(137));
+        // Five segments, each with a file header
+        assert_eq!(offset, 5 * header::HEADER_LEN as u64 + 136 * 46);
+        assert_eq!(last, Some(136));
+    }
+
+    #[test]
+    fn test_corrupted_index_falls_back_to_scan() {
+        let indexed = TempDir::new().unwrap();
+        let plain = TempDir::new().unwrap();
+        write_indexed_entries(indexed.path(), 120, 8);
+        write_indexed_entries(plain.path(), 120, 0);
+
+        // Bad checksum in one index
+        let idx = indexed.path().join("journal-000001.idx");
+        let mut data = fs::read(&idx).unwrap();
+        data[25] ^= 0xFF;
+        fs::write(&idx, &data).unwrap();
+
+        // Well-formed record pointing at the wrong frame in another
+        let forged: Vec<u8> = [
+            crate::index::IndexRecord { sequence: 61, offset: 0 },
+            crate::index::IndexRecord { sequence: 75, offset: 46 * 3 },
+        ]
+        .iter()
+        .flat_map(|r| r.to_bytes())
+        .collect();
+        fs::write(indexed.path().join("journal-000002.idx"), forged).unwrap();
+
+        for target in [45, 61, 80, 100] {
+            assert_eq!(
+                seek_result(indexed.path(), target),
+                seek_result(plain.path(), target),
+                "target {target}"
+            );
+        }
+        assert_eq!(seek_result(indexed.path(), 80).0, 79);
+    }
+
+    #[test]
+    fn test_index_seek_matches_linear_scan() {
+        let indexed = TempDir::new().unwrap();
+        let plain = TempDir::new().unwrap();
+        write_indexed_entries(indexed.path(), 100, 7);
+        write_indexed_entries(plain.path(), 100, 0);
+        assert!(!plain.path().join("journal-000000.idx").exists());
+
+        for target in 0..=102 {
+            assert_eq!(
+                seek_result(indexed.path(), target),
+                seek_result(plain.path(), target),
+                "target {target}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_poll_entry_waits_for_partial_frame() {
+        let tmp = TempDir::new().unwrap();
+        write_test_entries(tmp.path(), 3);
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        for seq in 1..=3 {
+            assert_eq!(reader.poll_entry().unwrap().unwrap().sequence, seq);
+        }
+        assert!(reader.poll_entry().unwrap().is_none());
+
+        // Writer caught mid-frame: first half of entry 4 on disk
+        let bytes = JournalEntry::new(4, 4_000, "Event1".into(), vec![4; 10]).to_bytes();
+        let path = tmp.path().join("journal-000000.bin");
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, &bytes[..2]).unwrap();
+        assert!(reader.poll_entry().unwrap().is_none());
+        std::io::Write::write_all(&mut file, &bytes[2..20]).unwrap();
+        assert!(reader.poll_entry().unwrap().is_none());
+        assert!(reader.corruption_log().is_empty());
+
+        std::io::Write::write_all(&mut file, &bytes[20..]).unwrap();
+        assert_eq!(reader.poll_entry().unwrap().unwrap().sequence, 4);
+        assert_eq!(reader.current_offset(), fs::metadata(&path).unwrap().len());
+    }
+
+    #[test]
+    fn test_poll_entry_picks_up_new_files() {
+        let tmp = TempDir::new().unwrap();
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert!(reader.poll_entry().unwrap().is_none());
+
+        write_indexed_entries(tmp.path(), 70, 8);
+        let seqs: Vec<u64> = std::iter::from_fn(|| reader.poll_entry().unwrap())
+            .map(|e| e.sequence)
+            .collect();
+        assert_eq!(seqs, (1..=70).collect::<Vec<_>>());
+
+        // A plain reader stops at what existed when it was opened
+        let mut plain = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(plain.read_all().unwrap().len(), 70);
+    }
+
+    #[test]
+    fn test_corrupted_compressed_payload_reported_with_offset() {
+        let tmp = TempDir::new().unwrap();
+        let config = JournalConfig::new(tmp.path());
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        let big: Vec<u8> = (0..20_000u32).flat_map(|i| (i % 97).to_le_bytes()).collect();
+        let mut offsets = Vec::new();
+        for seq in 1..=4u64 {
+            let payload = if seq == 2 { big.clone() } else { vec![seq as u8; 10] };
+            let entry = JournalEntry::new(seq, seq as i64, "BookSnapshot".into(), payload);
+            offsets.push(writer.append(&entry).unwrap().offset);
+        }
+        writer.sync().unwrap();
+
+        // Smash the zstd magic of entry 2; its frame stays well-formed
+        let path = tmp.path().join("journal-000000.bin");
+        let mut data = fs::read(&path).unwrap();
+        let payload_start = offsets[1] as usize + 4 + 8 + 8 + 2 + "BookSnapshot".len() + 1 + 4 + 4;
+        data[payload_start..payload_start + 4].copy_from_slice(&[0; 4]);
+        fs::write(&path, &data).unwrap();
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(reader.next_entry().unwrap().unwrap().sequence, 1);
+        match reader.next_entry() {
+            Err(ReaderError::Corruption { offset, .. }) => assert_eq!(offset, offsets[1]),
+            other => panic!("Expected Corruption, got: {:?}", other.map(|e| e.map(|e| e.sequence))),
+        }
+        assert_eq!(reader.corruption_log()[0].kind, CorruptionKind::DecompressionFailed);
+        assert_eq!(reader.corruption_log()[0].byte_offset, offsets[1]);
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        let (entries, corruptions) = reader.recover_entries();
+        let seqs: Vec<u64> = entries.iter().map(|e| e.sequence).collect();
+        assert_eq!(seqs, vec![1, 3, 4]);
+        assert_eq!(corruptions.len(), 1);
+
+        let mut reader = JournalReader::open(tmp.path()).unwrap();
+        assert_eq!(reader.seek_to_sequence(3).unwrap(), 2);
+        assert_eq!(reader.next_entry().unwrap().unwrap().sequence, 3);
+    }
 }
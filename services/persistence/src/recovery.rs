@@ -7,15 +7,21 @@
 //! 1. Find latest snapshot (if any)
 //! 2. Load snapshot → engine state
 //! 3. Open journal reader, seek to snapshot.sequence + 1
-//! 4. Replay all subsequent events, applying them to state
+//! 4. Replay all subsequent events, applying them to state (segments can
+//!    be decoded on worker threads, see [`crate::parallel`]; entries are
+//!    still applied one at a time, in sequence order)
 //! 5. Validate final state hash matches expected
-//! 6. Abort on divergence with detailed diagnostics
+//! 6. Abort on divergence with detailed diagnostics (a [`StateDiff`]
+//!    against a reference state, when one is provided)
 
 use crate::journal::JournalEntry;
+use crate::parallel::ParallelReader;
 use crate::reader::JournalReader;
+use crate::registry::{EventRegistry, TypedEvent};
 use crate::snapshot::{
     EngineState, Snapshot, SnapshotError, SnapshotLoader, SnapshotWriter,
 };
+use crate::state_diff::StateDiff;
 use std::path::PathBuf;
 use std::time::Instant;
 use thiserror::Error;
@@ -35,6 +41,9 @@ pub enum RecoveryError {
         expected: String,
         actual: String,
         sequence: u64,
+        /// Recovered state diffed against the reference state, if one was
+        /// given (`self` = recovered, `other` = reference).
+        diff: Option<Box<StateDiff>>,
     },
 
     #[error("Recovery failed: {0}")]
@@ -116,6 +125,35 @@ pub trait EventApplier {
     fn apply(&self, state: &mut EngineState, entry: &JournalEntry) -> Result<(), String>;
 }
 
+/// Event applier that receives payloads decoded through an
+/// [`EventRegistry`] instead of hand-decoding `entry.payload`.
+///
+/// Every `TypedEventApplier` is an [`EventApplier`]. Entries of event types
+/// the registry does not know arrive as [`TypedEvent::Unknown`]; payloads
+/// that fail to decode abort the apply.
+pub trait TypedEventApplier {
+    /// Registry used to decode entries.
+    fn registry(&self) -> &EventRegistry;
+
+    /// Apply a decoded entry; `entry` still carries the header fields.
+    fn apply_typed(
+        &self,
+        state: &mut EngineState,
+        entry: &JournalEntry,
+        event: TypedEvent,
+    ) -> Result<(), String>;
+}
+
+impl<A: TypedEventApplier> EventApplier for A {
+    fn apply(&self, state: &mut EngineState, entry: &JournalEntry) -> Result<(), String> {
+        let event = self
+            .registry()
+            .decode_entry(entry)
+            .map_err(|e| format!("seq={}: {}", entry.sequence, e))?;
+        self.apply_typed(state, entry, event)
+    }
+}
+
 /// Default no-op event applier (for testing / cold-start scenarios).
 /// Tracks entries by storing their sequence numbers in the state.
 pub struct DefaultEventApplier;
@@ -145,6 +183,8 @@ impl EventApplier for DefaultEventApplier {
 pub struct RecoveryEngine {
     snapshot_dir: PathBuf,
     journal_dir: PathBuf,
+    reference: Option<EngineState>,
+    replay_workers: usize,
     log: Vec<RecoveryLogEntry>,
 }
 
@@ -154,10 +194,26 @@ impl RecoveryEngine {
         Self {
             snapshot_dir: snapshot_dir.into(),
             journal_dir: journal_dir.into(),
+            reference: None,
+            replay_workers: 1,
             log: Vec::new(),
         }
     }
 
+    /// Diff the recovered state against `reference` when the state hash
+    /// diverges, attaching the result to [`RecoveryError::HashDivergence`].
+    pub fn with_reference_state(mut self, reference: EngineState) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Decode journal segments on `workers` threads during replay (default
+    /// 1: read sequentially). The recovered state is identical either way.
+    pub fn with_replay_workers(mut self, workers: usize) -> Self {
+        self.replay_workers = workers;
+        self
+    }
+
     /// Execute full recovery: snapshot load + journal replay + validation.
     pub fn recover(
         &mut self,
@@ -187,7 +243,15 @@ impl RecoveryEngine {
 
         // Step 3: Replay journal entries
         let replay_start = Instant::now();
-        self.log_stage(RecoveryStage::Replay, "Starting journal replay", 0);
+        self.log_stage(
+            RecoveryStage::Replay,
+            &format!(
+                "Starting journal replay ({} decode workers)",
+                self.replay_workers.max(1)
+            ),
+            0,
+        );
+        let mut reader = ParallelReader::new(reader, self.replay_workers);
 
         let mut last_seq = snapshot_seq;
         loop {
@@ -235,10 +299,19 @@ impl RecoveryEngine {
                     &format!("Hash divergence: expected={}, actual={}", expected, final_hash),
                     0,
                 );
+                let diff = self.reference.as_ref().map(|reference| Box::new(state.diff(reference)));
+                if let Some(diff) = &diff {
+                    self.log_stage(
+                        RecoveryStage::Error,
+                        &format!("Divergence from reference state:\n{}", diff),
+                        0,
+                    );
+                }
                 return Err(RecoveryError::HashDivergence {
                     expected: expected.to_string(),
                     actual: final_hash,
                     sequence: last_seq,
+                    diff,
                 });
             }
         }
@@ -467,13 +540,47 @@ mod tests {
         let result = engine.recover(&applier, Some("wrong_hash_value"));
         assert!(result.is_err());
         match result.unwrap_err() {
-            RecoveryError::HashDivergence { expected, .. } => {
+            RecoveryError::HashDivergence { expected, diff, .. } => {
                 assert_eq!(expected, "wrong_hash_value");
+                assert!(diff.is_none());
             }
             other => panic!("Expected HashDivergence, got: {:?}", other),
         }
     }
 
+    #[test]
+    fn test_divergence_includes_diff_against_reference() {
+        let tmp = TempDir::new().unwrap();
+        let snap_dir = tmp.path().join("snapshots");
+        let journal_dir = tmp.path().join("journal");
+
+        // Reference state from a replay that stopped one event short
+        write_journal(&journal_dir, 1, 9);
+        let (reference, _) = RecoveryEngine::new(&snap_dir, &journal_dir)
+            .recover_without_validation(&DefaultEventApplier)
+            .unwrap();
+        write_journal(&journal_dir, 10, 1);
+
+        let mut engine =
+            RecoveryEngine::new(&snap_dir, &journal_dir).with_reference_state(reference.clone());
+        let err = engine
+            .recover(&DefaultEventApplier, Some(&reference.compute_hash()))
+            .unwrap_err();
+        let RecoveryError::HashDivergence {
+            diff: Some(diff), ..
+        } = err
+        else {
+            panic!("Expected HashDivergence with diff, got: {:?}", err);
+        };
+        assert_eq!(diff.balances.only_in_self, vec!["__replay_seq_10"]);
+        assert!(diff.balances.only_in_other.is_empty() && diff.balances.changed.is_empty());
+        assert!(diff.accounts.is_empty() && diff.orders.is_empty() && diff.positions.is_empty());
+        assert!(engine
+            .log()
+            .iter()
+            .any(|e| e.message.contains("__replay_seq_10 (only in self)")));
+    }
+
     #[test]
     fn test_recovery_metrics_populated() {
         let tmp = TempDir::new().unwrap();
@@ -592,4 +699,137 @@ mod tests {
             .unwrap();
         assert!(path.exists());
     }
+
+    #[test]
+    fn test_parallel_replay_hash_matches_across_worker_counts() {
+        let tmp = TempDir::new().unwrap();
+        let snap_dir = tmp.path().join("snapshots");
+        let journal_dir = tmp.path().join("journal");
+
+        let config = JournalConfig {
+            max_file_size: 4_096,
+            ..JournalConfig::new(&journal_dir)
+        };
+        let mut writer = JournalWriter::open(config).unwrap();
+        writer.set_next_sequence(1);
+        for seq in 1..=2_000u64 {
+            let entry = JournalEntry::new(
+                seq,
+                1_000_000 * seq as i64,
+                "OrderSubmitted".to_string(),
+                seq.to_le_bytes().to_vec(),
+            );
+            writer.append(&entry).unwrap();
+        }
+        writer.sync().unwrap();
+
+        // Snapshot mid-journal so replay starts inside a segment
+        let (full, _) = RecoveryEngine::new(&snap_dir, &journal_dir)
+            .recover_without_validation(&DefaultEventApplier)
+            .unwrap();
+        let mut snap_state = full.clone();
+        snap_state
+            .balances
+            .retain(|key, _| key["__replay_seq_".len()..].parse::<u64>().unwrap() <= 777);
+        SnapshotWriter::new(&snap_dir, false)
+            .write(&Snapshot::new(777, 777_000_000, snap_state, false))
+            .unwrap();
+
+        let expected = full.compute_hash();
+        for workers in [1, 2, 8] {
+            let mut engine =
+                RecoveryEngine::new(&snap_dir, &journal_dir).with_replay_workers(workers);
+            let (state, metrics) = engine.recover(&DefaultEventApplier, Some(&expected)).unwrap();
+            assert_eq!(state, full, "workers={}", workers);
+            assert_eq!(metrics.final_state_hash, expected);
+            assert_eq!(metrics.replay_count, 2_000 - 777);
+            assert_eq!(metrics.final_sequence, 2_000);
+        }
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Deposit {
+        account: String,
+        amount: u64,
+    }
+
+    impl crate::registry::JournalEvent for Deposit {
+        fn event_type(&self) -> &str {
+            "Deposit"
+        }
+    }
+
+    /// Records deposits as balances and counts unknown entries.
+    struct DepositApplier {
+        registry: EventRegistry,
+    }
+
+    impl TypedEventApplier for DepositApplier {
+        fn registry(&self) -> &EventRegistry {
+            &self.registry
+        }
+
+        fn apply_typed(
+            &self,
+            state: &mut EngineState,
+            entry: &JournalEntry,
+            event: TypedEvent,
+        ) -> Result<(), String> {
+            let (key, total) = match event.downcast::<Deposit>() {
+                Ok(deposit) => (deposit.account, deposit.amount.to_string()),
+                Err(unknown) => (format!("unknown-{}", unknown.event_type()), entry.sequence.to_string()),
+            };
+            state.balances.insert(
+                key.clone(),
+                crate::snapshot::BalanceSnapshot {
+                    account_id: key,
+                    asset: "USDT".to_string(),
+                    total,
+                    available: "0".to_string(),
+                    locked: "0".to_string(),
+                },
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_typed_applier_receives_decoded_events() {
+        let tmp = TempDir::new().unwrap();
+        let snap_dir = tmp.path().join("snapshots");
+        let journal_dir = tmp.path().join("journal");
+
+        let mut registry = EventRegistry::new();
+        registry
+            .register::<Deposit>(&["Deposit"], crate::registry::Codec::Bincode)
+            .unwrap();
+        let deposit = Deposit {
+            account: "alice".to_string(),
+            amount: 250,
+        };
+        let mut writer = JournalWriter::open(JournalConfig::new(&journal_dir)).unwrap();
+        writer.set_next_sequence(1);
+        writer.append(&registry.journal_entry(1, 10, &deposit).unwrap()).unwrap();
+        writer
+            .append(&JournalEntry::new(2, 20, "Halt".to_string(), vec![7]))
+            .unwrap();
+        writer.sync().unwrap();
+
+        let applier = DepositApplier { registry };
+        let (state, _) = RecoveryEngine::new(&snap_dir, &journal_dir)
+            .recover_without_validation(&applier)
+            .unwrap();
+        assert_eq!(state.balances["alice"].total, "250");
+        assert_eq!(state.balances["unknown-Halt"].total, "2");
+
+        // An undecodable registered payload aborts the replay
+        writer
+            .append(&JournalEntry::new(3, 30, "Deposit".to_string(), vec![1]))
+            .unwrap();
+        writer.sync().unwrap();
+        let err = RecoveryEngine::new(&snap_dir, &journal_dir)
+            .recover_without_validation(&applier)
+            .unwrap_err();
+        assert!(matches!(err, RecoveryError::Failed(msg) if msg.contains("seq=3: Cannot decode Deposit")));
+    }
 }
@@ -0,0 +1,558 @@
+//! Event Registry — Typed journal payloads
+//!
+//! `JournalEntry.payload` is opaque bytes; the [`EventRegistry`] owns the
+//! mapping from `event_type` to the serde type (and codec) behind it, so
+//! writers and replayers agree on one decoding per event type (spec §08).
+//!
+//! Event types are versioned by suffix: `Name` is version 1, `Name.vN` is
+//! version N. Each type has one current payload struct, registered with
+//! [`EventRegistry::register`]; superseded versions stay decodable through
+//! [`EventRegistry::register_legacy`], which upgrades them to the current
+//! struct. Event types nobody registered decode to [`TypedEvent::Unknown`]
+//! with the raw bytes preserved, so older binaries can replay newer
+//! journals.
+//!
+//! Registering `Correlated<T>` (see [`crate::correlation`]) in place of `T`
+//! changes the payload shape: bincode payloads gain a trailing id and old
+//! ones still decode, but JSON payloads nest the event under `"event"`, so
+//! a JSON-codec type makes that switch as a new version.
+
+use crate::journal::JournalEntry;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::{self, Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    #[error("Event type {0} is already registered")]
+    Duplicate(String),
+
+    #[error("Event type {0} is not registered")]
+    Unregistered(String),
+
+    #[error("Event type {event_type} is registered for {registered}, not {given}")]
+    WrongType {
+        event_type: String,
+        registered: &'static str,
+        given: &'static str,
+    },
+
+    #[error("Cannot decode {event_type}: {reason}")]
+    Decode { event_type: String, reason: String },
+
+    #[error("Cannot encode {event_type}: {reason}")]
+    Encode { event_type: String, reason: String },
+
+    #[error("Entry is {event_type} but payload is {payload_type}")]
+    Mismatch {
+        event_type: String,
+        payload_type: String,
+    },
+}
+
+impl RegistryError {
+    /// The failure without the event type, for errors that report the
+    /// event type themselves.
+    pub fn reason(&self) -> String {
+        match self {
+            RegistryError::Decode { reason, .. } | RegistryError::Encode { reason, .. } => {
+                reason.clone()
+            }
+            RegistryError::Mismatch { payload_type, .. } => format!("payload is {}", payload_type),
+            other => other.to_string(),
+        }
+    }
+}
+
+// ── Event Types ─────────────────────────────────────────────────────
+
+/// A payload type that can be journaled through the registry.
+pub trait JournalEvent: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Current journal event type of this value (`Name` or `Name.vN`).
+    fn event_type(&self) -> &str;
+}
+
+/// Payload wire encoding of an event type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Bincode,
+    /// For payloads bincode cannot decode (e.g. internally tagged enums).
+    Json,
+}
+
+impl Codec {
+    fn serialize<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Codec::Bincode => bincode::serialize(value).map_err(|e| e.to_string()),
+            Codec::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Codec::Bincode => bincode::deserialize(bytes).map_err(|e| e.to_string()),
+            Codec::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Split an event type into its name and version (`Name.vN` → N, else 1).
+pub fn parse_event_type(event_type: &str) -> (&str, u32) {
+    if let Some((name, version)) = event_type.rsplit_once(".v") {
+        if let Ok(version) = version.parse::<u32>() {
+            if version > 0 {
+                return (name, version);
+            }
+        }
+    }
+    (event_type, 1)
+}
+
+// ── Typed Event ─────────────────────────────────────────────────────
+
+/// A decoded journal payload.
+pub enum TypedEvent {
+    /// A registered event type, decoded (and upgraded) to its current
+    /// payload type.
+    Known {
+        event_type: String,
+        /// Version the entry was journaled with.
+        version: u32,
+        type_name: &'static str,
+        event: Box<dyn Any + Send + Sync>,
+    },
+    /// An event type this registry does not know; bytes kept verbatim.
+    Unknown { event_type: String, payload: Vec<u8> },
+}
+
+impl TypedEvent {
+    /// Event type of the journal entry this came from.
+    pub fn event_type(&self) -> &str {
+        match self {
+            TypedEvent::Known { event_type, .. } | TypedEvent::Unknown { event_type, .. } => {
+                event_type
+            }
+        }
+    }
+
+    /// Journaled version (see [`parse_event_type`]).
+    pub fn version(&self) -> u32 {
+        match self {
+            TypedEvent::Known { version, .. } => *version,
+            TypedEvent::Unknown { event_type, .. } => parse_event_type(event_type).1,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, TypedEvent::Unknown { .. })
+    }
+
+    /// The payload, if it is a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            TypedEvent::Known { event, .. } => event.downcast_ref(),
+            TypedEvent::Unknown { .. } => None,
+        }
+    }
+
+    /// Take the payload out if it is a `T`, otherwise hand `self` back.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self {
+            TypedEvent::Known {
+                event_type,
+                version,
+                type_name,
+                event,
+            } => event.downcast().map(|event| *event).map_err(|event| TypedEvent::Known {
+                event_type,
+                version,
+                type_name,
+                event,
+            }),
+            unknown => Err(unknown),
+        }
+    }
+}
+
+impl fmt::Debug for TypedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedEvent::Known {
+                event_type,
+                version,
+                type_name,
+                ..
+            } => f
+                .debug_struct("Known")
+                .field("event_type", event_type)
+                .field("version", version)
+                .field("type_name", type_name)
+                .finish_non_exhaustive(),
+            TypedEvent::Unknown {
+                event_type,
+                payload,
+            } => f
+                .debug_struct("Unknown")
+                .field("event_type", event_type)
+                .field("payload_len", &payload.len())
+                .finish(),
+        }
+    }
+}
+
+// ── Registry ────────────────────────────────────────────────────────
+
+type DecodeFn = Box<dyn Fn(&str, &[u8]) -> Result<TypedEvent, RegistryError> + Send + Sync>;
+
+struct Registration {
+    /// Type decoded payloads come out as.
+    type_id: TypeId,
+    type_name: &'static str,
+    codec: Codec,
+    /// Whether this is a superseded version (decode only).
+    legacy: bool,
+    decode: DecodeFn,
+}
+
+/// Maps journal event types to their payload types.
+#[derive(Default)]
+pub struct EventRegistry {
+    registrations: BTreeMap<String, Registration>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `T` as the current payload type of `event_types`.
+    ///
+    /// Decoding checks that the payload reports the entry's event type, so
+    /// an enum covering several event types can be registered once.
+    pub fn register<T: JournalEvent>(
+        &mut self,
+        event_types: &[&str],
+        codec: Codec,
+    ) -> Result<(), RegistryError> {
+        for event_type in event_types {
+            let decode: DecodeFn = Box::new(move |event_type, payload| {
+                let event: T = codec.deserialize(payload).map_err(|reason| RegistryError::Decode {
+                    event_type: event_type.to_string(),
+                    reason,
+                })?;
+                if event.event_type() != event_type {
+                    return Err(RegistryError::Mismatch {
+                        event_type: event_type.to_string(),
+                        payload_type: event.event_type().to_string(),
+                    });
+                }
+                Ok(known::<T>(event_type, event))
+            });
+            self.insert(event_type, Registration::new::<T>(codec, false, decode))?;
+        }
+        Ok(())
+    }
+
+    /// Register a superseded payload `L` of `event_type`, upgraded to the
+    /// current `T` on decode. Legacy event types cannot be encoded.
+    pub fn register_legacy<L, T>(
+        &mut self,
+        event_type: &str,
+        codec: Codec,
+        upgrade: fn(L) -> Result<T, String>,
+    ) -> Result<(), RegistryError>
+    where
+        L: DeserializeOwned + 'static,
+        T: JournalEvent,
+    {
+        let decode: DecodeFn = Box::new(move |event_type, payload| {
+            let decode_error = |reason| RegistryError::Decode {
+                event_type: event_type.to_string(),
+                reason,
+            };
+            let legacy: L = codec.deserialize(payload).map_err(decode_error)?;
+            let event = upgrade(legacy).map_err(decode_error)?;
+            Ok(known::<T>(event_type, event))
+        });
+        self.insert(event_type, Registration::new::<T>(codec, true, decode))
+    }
+
+    /// Decode a payload journaled as `event_type`.
+    pub fn decode(&self, event_type: &str, payload: &[u8]) -> Result<TypedEvent, RegistryError> {
+        match self.registrations.get(event_type) {
+            Some(registration) => (registration.decode)(event_type, payload),
+            None => Ok(TypedEvent::Unknown {
+                event_type: event_type.to_string(),
+                payload: payload.to_vec(),
+            }),
+        }
+    }
+
+    /// Decode a journal entry's payload.
+    pub fn decode_entry(&self, entry: &JournalEntry) -> Result<TypedEvent, RegistryError> {
+        self.decode(&entry.event_type, &entry.payload)
+    }
+
+    /// Encode `event` under its current event type.
+    pub fn encode<T: JournalEvent>(&self, event: &T) -> Result<(String, Vec<u8>), RegistryError> {
+        let event_type = event.event_type();
+        let registration = self
+            .registrations
+            .get(event_type)
+            .filter(|r| !r.legacy)
+            .ok_or_else(|| RegistryError::Unregistered(event_type.to_string()))?;
+        if registration.type_id != TypeId::of::<T>() {
+            return Err(RegistryError::WrongType {
+                event_type: event_type.to_string(),
+                registered: registration.type_name,
+                given: any::type_name::<T>(),
+            });
+        }
+        let payload = registration
+            .codec
+            .serialize(event)
+            .map_err(|reason| RegistryError::Encode {
+                event_type: event_type.to_string(),
+                reason,
+            })?;
+        Ok((event_type.to_string(), payload))
+    }
+
+    /// Encode `event` as a journal entry.
+    pub fn journal_entry<T: JournalEvent>(
+        &self,
+        sequence: u64,
+        timestamp: i64,
+        event: &T,
+    ) -> Result<JournalEntry, RegistryError> {
+        let (event_type, payload) = self.encode(event)?;
+        Ok(JournalEntry::new(sequence, timestamp, event_type, payload))
+    }
+
+    /// Whether `event_type` is registered (current or legacy).
+    pub fn contains(&self, event_type: &str) -> bool {
+        self.registrations.contains_key(event_type)
+    }
+
+    /// Registered event types, sorted.
+    pub fn event_types(&self) -> impl Iterator<Item = &str> {
+        self.registrations.keys().map(String::as_str)
+    }
+
+    fn insert(&mut self, event_type: &str, registration: Registration) -> Result<(), RegistryError> {
+        if self.registrations.contains_key(event_type) {
+            return Err(RegistryError::Duplicate(event_type.to_string()));
+        }
+        self.registrations.insert(event_type.to_string(), registration);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for EventRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.registrations.iter().map(|(k, r)| (k, (r.type_name, r.codec, r.legacy))))
+            .finish()
+    }
+}
+
+impl Registration {
+    fn new<T: 'static>(codec: Codec, legacy: bool, decode: DecodeFn) -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: any::type_name::<T>(),
+            codec,
+            legacy,
+            decode,
+        }
+    }
+}
+
+fn known<T: JournalEvent>(event_type: &str, event: T) -> TypedEvent {
+    TypedEvent::Known {
+        event_type: event_type.to_string(),
+        version: parse_event_type(event_type).1,
+        type_name: any::type_name::<T>(),
+        event: Box::new(event),
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    /// `Transfer` as first journaled
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TransferV1 {
+        from: String,
+        to: String,
+        amount: u64,
+    }
+
+    /// `Transfer.v2`: memo added
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Transfer {
+        from: String,
+        to: String,
+        amount: u64,
+        memo: Option<String>,
+    }
+
+    impl JournalEvent for Transfer {
+        fn event_type(&self) -> &str {
+            "Transfer.v2"
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum Notice {
+        Halt { market: String },
+        Resume { market: String },
+    }
+
+    impl JournalEvent for Notice {
+        fn event_type(&self) -> &str {
+            match self {
+                Notice::Halt { .. } => "Halt",
+                Notice::Resume { .. } => "Resume",
+            }
+        }
+    }
+
+    fn registry() -> EventRegistry {
+        let mut registry = EventRegistry::new();
+        registry.register::<Transfer>(&["Transfer.v2"], Codec::Bincode).unwrap();
+        registry
+            .register_legacy::<TransferV1, Transfer>("Transfer", Codec::Bincode, |v1| {
+                Ok(Transfer {
+                    from: v1.from,
+                    to: v1.to,
+                    amount: v1.amount,
+                    memo: None,
+                })
+            })
+            .unwrap();
+        registry.register::<Notice>(&["Halt", "Resume"], Codec::Json).unwrap();
+        registry
+    }
+
+    fn transfer() -> Transfer {
+        Transfer {
+            from: "a".into(),
+            to: "b".into(),
+            amount: 5,
+            memo: Some("rent".into()),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let registry = registry();
+        let entry = registry.journal_entry(3, 9, &transfer()).unwrap();
+        assert_eq!(entry.event_type, "Transfer.v2");
+
+        let event = registry.decode_entry(&entry).unwrap();
+        assert_eq!(event.version(), 2);
+        assert_eq!(event.downcast_ref::<Transfer>(), Some(&transfer()));
+        assert_eq!(event.downcast::<Transfer>().unwrap(), transfer());
+    }
+
+    #[test]
+    fn test_version_bump_upgrades_old_payloads() {
+        let registry = registry();
+        let v1 = TransferV1 {
+            from: "a".into(),
+            to: "b".into(),
+            amount: 5,
+        };
+        let payload = bincode::serialize(&v1).unwrap();
+
+        let event = registry.decode("Transfer", &payload).unwrap();
+        assert_eq!(event.version(), 1);
+        let upgraded = event.downcast::<Transfer>().unwrap();
+        assert_eq!(upgraded.memo, None);
+        assert_eq!(upgraded.amount, 5);
+
+        // A v2 payload is not a valid v1 payload, nor the other way round
+        assert!(matches!(
+            registry.decode("Transfer.v2", &payload),
+            Err(RegistryError::Decode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_event_type_keeps_raw_bytes() {
+        let registry = registry();
+        let event = registry.decode("Transfer.v3", &[1, 2, 3]).unwrap();
+        assert!(event.is_unknown());
+        assert_eq!(event.version(), 3);
+        assert!(event.downcast_ref::<Transfer>().is_none());
+        match event {
+            TypedEvent::Unknown { event_type, payload } => {
+                assert_eq!(event_type, "Transfer.v3");
+                assert_eq!(payload, vec![1, 2, 3]);
+            }
+            other => panic!("Expected Unknown, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_enum_payload_must_match_event_type() {
+        let registry = registry();
+        let halt = Notice::Halt { market: "BTC/USDT".into() };
+        let (event_type, payload) = registry.encode(&halt).unwrap();
+        assert_eq!(event_type, "Halt");
+        assert!(payload.starts_with(b"{"));
+        assert_eq!(registry.decode("Halt", &payload).unwrap().downcast::<Notice>().unwrap(), halt);
+
+        assert_eq!(
+            registry.decode("Resume", &payload).unwrap_err(),
+            RegistryError::Mismatch {
+                event_type: "Resume".into(),
+                payload_type: "Halt".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_registration_conflicts_are_rejected() {
+        let mut registry = registry();
+        assert_eq!(
+            registry.register::<Notice>(&["Transfer"], Codec::Json),
+            Err(RegistryError::Duplicate("Transfer".into()))
+        );
+        assert!(matches!(
+            EventRegistry::new().encode(&transfer()),
+            Err(RegistryError::Unregistered(_))
+        ));
+
+        // The legacy name alone cannot be written
+        let mut legacy_only = EventRegistry::new();
+        legacy_only
+            .register_legacy::<TransferV1, Transfer>("Transfer.v2", Codec::Bincode, |_| {
+                Err("unused".into())
+            })
+            .unwrap();
+        assert!(matches!(
+            legacy_only.encode(&transfer()),
+            Err(RegistryError::Unregistered(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_event_type() {
+        assert_eq!(parse_event_type("TradeExecuted"), ("TradeExecuted", 1));
+        assert_eq!(parse_event_type("TradeExecuted.v2"), ("TradeExecuted", 2));
+        assert_eq!(parse_event_type("Weird.vx"), ("Weird.vx", 1));
+        assert_eq!(parse_event_type("Zero.v0"), ("Zero.v0", 1));
+    }
+}
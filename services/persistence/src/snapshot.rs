@@ -5,13 +5,21 @@
 //!
 //! Features:
 //! - Full engine state serialization (accounts, orders, positions, balances)
+//!   plus the risk engine's margin, funding and insurance fund state and
+//!   the custody vault's balances, ledgers and withdrawal queue
 //! - BTreeMap-based state for deterministic serialization (spec §12.3.5)
 //! - SHA-256 integrity hash over serialized state
 //! - Optional zstd compression (spec §11.8.3)
+//! - Optional AES-256-GCM encryption at rest (`crate::encryption`)
 //! - Snapshot versioning for forward compatibility
+//! - File header with magic and format version (`crate::header`), ahead of
+//!   the (compressed) snapshot and inside the encryption; header-less
+//!   snapshots from older builds still load
 //! - Interval policy (every N events or time-based)
 //! - Cleanup policy (keep last N snapshots)
 
+use crate::encryption::{self, EncryptionError, EncryptionKey, FileCipher, HEADER_LEN};
+use crate::header::{self, FileHeader, FileKind, HeaderError};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
@@ -41,6 +49,13 @@ pub enum SnapshotError {
 
     #[error("No snapshots found")]
     NoSnapshots,
+
+    #[error("Encryption error: {0}")]
+    Encryption(#[from] EncryptionError),
+
+    /// Unreadable file header, including one from a newer format version.
+    #[error("File header error: {0}")]
+    Header(#[from] HeaderError),
 }
 
 use std::io;
@@ -61,6 +76,10 @@ pub struct EngineState {
     pub positions: BTreeMap<String, PositionSnapshot>,
     /// Balance records keyed by "account_id:asset".
     pub balances: BTreeMap<String, BalanceSnapshot>,
+    /// Risk engine state.
+    pub risk: RiskState,
+    /// Custody vault state.
+    pub vault: VaultState,
 }
 
 impl EngineState {
@@ -71,6 +90,8 @@ impl EngineState {
             orders: BTreeMap::new(),
             positions: BTreeMap::new(),
             balances: BTreeMap::new(),
+            risk: RiskState::default(),
+            vault: VaultState::default(),
         }
     }
 
@@ -133,10 +154,177 @@ pub struct BalanceSnapshot {
     pub locked: String,
 }
 
+// ── Risk State ──────────────────────────────────────────────────────
+
+/// Risk engine state for snapshot serialization.
+///
+/// Carries what the risk engine cannot cheaply derive from the rest of
+/// the engine state: monitored margin accounts with their liquidation
+/// flags, funding accrual, mark price sources and insurance fund balances.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskState {
+    /// Monitored margin accounts keyed by account ID string.
+    pub margin: BTreeMap<String, MarginSnapshot>,
+    /// Funding state keyed by symbol.
+    pub funding: BTreeMap<String, FundingSnapshot>,
+    /// Mark price source values keyed by "symbol:source".
+    pub mark_sources: BTreeMap<String, MarkSourceSnapshot>,
+    /// Last mark applied to monitored positions, keyed by symbol.
+    pub marks: BTreeMap<String, String>,
+    /// Insurance fund balances keyed by asset.
+    pub insurance_fund: BTreeMap<String, String>,
+}
+
+impl RiskState {
+    /// Compute a deterministic SHA-256 hash of the risk state alone.
+    pub fn compute_hash(&self) -> String {
+        let bytes = bincode::serialize(self)
+            .expect("RiskState serialization should never fail");
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Margin account snapshot for the liquidation monitor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginSnapshot {
+    pub account_id: String,
+    /// Position in the monitor's first-seen order (breaks margin ratio ties).
+    pub monitor_order: u64,
+    pub collateral: String,
+    pub liquidating: bool,
+    /// Open positions keyed by symbol.
+    pub positions: BTreeMap<String, MarginPositionSnapshot>,
+}
+
+/// Full position snapshot for margin calculations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginPositionSnapshot {
+    pub position_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub size: String,
+    pub entry_price: String,
+    pub mark_price: String,
+    pub liquidation_price: String,
+    pub realized_pnl: String,
+    pub unrealized_pnl: String,
+    pub initial_margin: String,
+    pub maintenance_margin: String,
+    pub leverage: u8,
+    pub opened_at: i64,
+    pub updated_at: i64,
+    pub version: u64,
+}
+
+/// Per-market funding snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingSnapshot {
+    pub symbol: String,
+    pub rate: String,
+    pub mark_price: String,
+    pub index_price: String,
+    pub last_funding_time: i64,
+    pub updated_at: i64,
+}
+
+/// Latest value of one mark price source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkSourceSnapshot {
+    pub symbol: String,
+    pub source: String,
+    pub price: String,
+    pub updated_at: i64,
+}
+
+// ── Vault State ─────────────────────────────────────────────────────
+
+/// Custody vault state for snapshot serialization.
+///
+/// Balances, token registry, deposit ledger, withdrawal requests and used
+/// withdrawal nonces of the contracts crate's vault and withdrawal queue.
+/// Roles, timelocks and queue policies are configuration, supplied when
+/// the vault is constructed, and are not part of the snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultState {
+    /// Balances keyed by "account_id:asset".
+    pub balances: BTreeMap<String, String>,
+    /// Whitelisted or configured tokens keyed by symbol.
+    pub tokens: BTreeMap<String, TokenSnapshot>,
+    /// On-chain deposits keyed by transaction ID.
+    pub deposits: BTreeMap<String, DepositSnapshot>,
+    /// Withdrawal requests, terminal ones included, keyed by withdrawal ID.
+    pub withdrawals: BTreeMap<String, WithdrawalSnapshot>,
+    /// Used withdrawal nonces keyed by account ID, ascending.
+    pub nonces: BTreeMap<String, Vec<u64>>,
+    /// Whether the whole vault is paused.
+    pub paused: bool,
+    /// Withdrawal batches settled so far.
+    pub batches: u64,
+}
+
+impl VaultState {
+    /// Compute a deterministic SHA-256 hash of the vault state alone.
+    pub fn compute_hash(&self) -> String {
+        let bytes = bincode::serialize(self)
+            .expect("VaultState serialization should never fail");
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Token registry entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenSnapshot {
+    pub whitelisted: bool,
+    pub decimals: u32,
+    pub min_deposit: String,
+    pub confirmation_threshold: u64,
+    pub paused: bool,
+}
+
+/// Deposit ledger entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositSnapshot {
+    pub tx_id: String,
+    pub account_id: String,
+    pub asset: String,
+    pub amount: String,
+    pub confirmations: u64,
+    pub state: String,
+    pub debited: String,
+    pub shortfall: String,
+}
+
+/// Withdrawal request snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithdrawalSnapshot {
+    pub withdrawal_id: String,
+    /// Position in the queue, which breaks processing order ties.
+    pub position: u64,
+    pub account_id: String,
+    pub asset: String,
+    pub amount: String,
+    pub destination: String,
+    pub nonce: u64,
+    pub requested_at: i64,
+    pub delay_until: i64,
+    pub status: String,
+    pub expedited: Option<u64>,
+    pub approvals: Vec<String>,
+    pub approval_deadline: Option<i64>,
+    pub expires_at: i64,
+}
+
 // ── Snapshot ────────────────────────────────────────────────────────
 
 /// Current snapshot format version.
-pub const SNAPSHOT_VERSION: u32 = 1;
+///
+/// Version 2 added the risk section to `EngineState`, version 3 the vault
+/// section.
+pub const SNAPSHOT_VERSION: u32 = 3;
 
 /// A complete snapshot of the engine state at a given sequence.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -178,10 +366,11 @@ impl Snapshot {
 
 // ── Snapshot Writer ─────────────────────────────────────────────────
 
-/// Writes snapshots to disk with optional zstd compression.
+/// Writes snapshots to disk with optional zstd compression and encryption.
 pub struct SnapshotWriter {
     dir: PathBuf,
     compress: bool,
+    encryption: Option<EncryptionKey>,
 }
 
 impl SnapshotWriter {
@@ -190,9 +379,16 @@ impl SnapshotWriter {
         Self {
             dir: dir.into(),
             compress,
+            encryption: None,
         }
     }
 
+    /// Encrypt snapshots with `key` (after compression).
+    pub fn with_encryption(mut self, key: EncryptionKey) -> Self {
+        self.encryption = Some(key);
+        self
+    }
+
     /// Write a snapshot atomically: serialize → compress → compute hash → write.
     pub fn write(&self, snapshot: &Snapshot) -> Result<PathBuf, SnapshotError> {
         fs::create_dir_all(&self.dir)?;
@@ -200,13 +396,20 @@ impl SnapshotWriter {
         let data = bincode::serialize(snapshot)
             .map_err(|e| SnapshotError::Serialization(e.to_string()))?;
 
-        let (final_data, ext) = if self.compress {
+        let (body, ext) = if self.compress {
             let compressed = zstd::encode_all(data.as_slice(), 3)
                 .map_err(|e| SnapshotError::Compression(e.to_string()))?;
             (compressed, "snap.zst")
         } else {
             (data, "snap")
         };
+        let file_header = FileHeader::new(FileKind::Snapshot, snapshot.timestamp, snapshot.sequence);
+        let mut final_data = file_header.encode().to_vec();
+        final_data.extend_from_slice(&body);
+        let final_data = match &self.encryption {
+            Some(key) => seal(key, final_data)?,
+            None => final_data,
+        };
 
         let filename = format!("snapshot-{:012}.{}", snapshot.sequence, ext);
         let path = self.dir.join(&filename);
@@ -229,11 +432,21 @@ impl SnapshotWriter {
 /// Loads snapshots from disk, verifying integrity.
 pub struct SnapshotLoader {
     dir: PathBuf,
+    encryption: Option<EncryptionKey>,
 }
 
 impl SnapshotLoader {
     pub fn new(dir: impl Into<PathBuf>) -> Self {
-        Self { dir: dir.into() }
+        Self {
+            dir: dir.into(),
+            encryption: None,
+        }
+    }
+
+    /// Decrypt snapshots with `key`; plaintext snapshots are then refused.
+    pub fn with_encryption(mut self, key: EncryptionKey) -> Self {
+        self.encryption = Some(key);
+        self
     }
 
     /// Load a specific snapshot file.
@@ -241,6 +454,10 @@ impl SnapshotLoader {
         let mut file = File::open(path)?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
+        let mut data = self.decrypt(path, data)?;
+        if FileHeader::decode(FileKind::Snapshot, &data)?.is_some() {
+            data.drain(..header::HEADER_LEN);
+        }
 
         let is_compressed = path
             .extension()
@@ -275,15 +492,32 @@ impl SnapshotLoader {
     }
 
     /// Load the latest snapshot (highest sequence number).
+    ///
+    /// Refuses a directory mixing encrypted and plaintext snapshots.
     pub fn load_latest(&self) -> Result<Snapshot, SnapshotError> {
+        let paths: Vec<PathBuf> = self.list_snapshots()?.into_iter().map(|(_, p)| p).collect();
+        encryption::check_files(&paths, self.encryption.is_some())?;
         let path = self.find_latest()?;
         self.load(&path)
     }
 
+    fn decrypt(&self, path: &Path, data: Vec<u8>) -> Result<Vec<u8>, SnapshotError> {
+        match (encryption::has_magic(&data), &self.encryption) {
+            (true, Some(key)) => Ok(open(key, data)?),
+            (false, None) => Ok(data),
+            (encrypted, _) => Err(EncryptionError::Mismatch(format!(
+                "{} is {}",
+                path.display(),
+                if encrypted { "encrypted" } else { "plaintext" }
+            ))
+            .into()),
+        }
+    }
+
     /// Find the path to the latest snapshot.
     pub fn find_latest(&self) -> Result<PathBuf, SnapshotError> {
         let mut snapshots = self.list_snapshots()?;
-        snapshots.sort_by(|a, b| b.0.cmp(&a.0)); // Descending by sequence
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.0)); // Descending by sequence
         snapshots
             .into_iter()
             .next()
@@ -321,6 +555,63 @@ impl SnapshotLoader {
     }
 }
 
+// ── Encryption ──────────────────────────────────────────────────────
+
+/// Encrypted snapshot file contents: header + sealed blob.
+fn seal(key: &EncryptionKey, data: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+    let (cipher, header) = FileCipher::create(key)?;
+    let mut out = header.to_vec();
+    out.extend(cipher.seal_blob(data));
+    Ok(out)
+}
+
+fn open(key: &EncryptionKey, mut data: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+    let cipher = FileCipher::from_header(key, &data)?;
+    let sealed = data.split_off(HEADER_LEN);
+    cipher.open_blob(sealed)
+}
+
+/// Re-encrypt every snapshot in `dir` from key `old` to key `new`.
+///
+/// Every snapshot header is checked before anything is rewritten; each
+/// snapshot is then replaced atomically (temp file, fsync, rename).
+/// Snapshots already under `new` are skipped, so an interrupted rotation
+/// can simply be rerun. Returns the rewritten paths.
+pub fn rotate_encryption_key(
+    dir: &Path,
+    old: &EncryptionKey,
+    new: &EncryptionKey,
+) -> Result<Vec<PathBuf>, SnapshotError> {
+    let paths: Vec<PathBuf> = SnapshotLoader::new(dir)
+        .list_snapshots()?
+        .into_iter()
+        .map(|(_, p)| p)
+        .collect();
+    encryption::check_files(&paths, true)?;
+
+    let mut pending = Vec::new();
+    for path in paths {
+        let mut file = File::open(&path)?;
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header)?;
+        if encryption::needs_rotation(&header, old, new)? {
+            pending.push(path);
+        }
+    }
+
+    for path in &pending {
+        let plaintext = open(old, fs::read(path)?)?;
+        let tmp = path.with_extension("rekey.tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&seal(new, plaintext)?)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)?;
+    }
+    Ok(pending)
+}
+
 // ── Snapshot Interval Policy ────────────────────────────────────────
 
 /// Policy that determines when to create a new snapshot.
@@ -607,4 +898,59 @@ mod tests {
 
         assert_eq!(s1.compute_hash(), s2.compute_hash());
     }
+
+    #[test]
+    fn test_risk_state_round_trips_and_hashes() {
+        let tmp = TempDir::new().unwrap();
+        let mut state = sample_state();
+        let risk_hash = state.risk.compute_hash();
+        state.risk.insurance_fund.insert("USDT".into(), "1250.5".into());
+        state.risk.funding.insert("BTC/USDT".into(), FundingSnapshot {
+            symbol: "BTC/USDT".into(),
+            rate: "0.0001".into(),
+            mark_price: "50000".into(),
+            index_price: "50000".into(),
+            last_funding_time: 1_708_106_400_000_000_000,
+            updated_at: 1_708_123_456_789_000_000,
+        });
+        assert_ne!(state.risk.compute_hash(), risk_hash);
+
+        let snapshot = Snapshot::new(7, 1_708_123_456_789_000_000, state.clone(), true);
+        let path = SnapshotWriter::new(tmp.path(), true).write(&snapshot).unwrap();
+        let loaded = SnapshotLoader::new(tmp.path()).load(&path).unwrap();
+        assert_eq!(loaded.state.risk, state.risk);
+        assert_eq!(loaded.state.risk.compute_hash(), state.risk.compute_hash());
+    }
+
+    #[test]
+    fn test_vault_state_round_trips_and_hashes() {
+        let tmp = TempDir::new().unwrap();
+        let mut state = sample_state();
+        let vault_hash = state.vault.compute_hash();
+        state.vault.balances.insert("acc-001:USDT".into(), "2500.75".into());
+        state.vault.nonces.insert("acc-001".into(), vec![1, 2, 7]);
+        state.vault.withdrawals.insert("wd-001".into(), WithdrawalSnapshot {
+            withdrawal_id: "wd-001".into(),
+            position: 0,
+            account_id: "acc-001".into(),
+            asset: "USDT".into(),
+            amount: "500".into(),
+            destination: "0xabc".into(),
+            nonce: 7,
+            requested_at: 1_708_000_000,
+            delay_until: 1_708_086_400,
+            status: "Pending".into(),
+            expedited: None,
+            approvals: Vec::new(),
+            approval_deadline: None,
+            expires_at: 1_708_604_800,
+        });
+        assert_ne!(state.vault.compute_hash(), vault_hash);
+
+        let snapshot = Snapshot::new(9, 1_708_123_456_789_000_000, state.clone(), false);
+        let path = SnapshotWriter::new(tmp.path(), false).write(&snapshot).unwrap();
+        let loaded = SnapshotLoader::new(tmp.path()).load(&path).unwrap();
+        assert_eq!(loaded.state.vault, state.vault);
+        assert_eq!(loaded.state.compute_hash(), state.compute_hash());
+    }
 }
@@ -0,0 +1,462 @@
+//! State Diff — Where two engine states disagree
+//!
+//! A state hash mismatch (spec §11.7) only says *that* replay diverged.
+//! [`EngineState::diff`] walks every map of both states and reports, per
+//! map, the keys present on one side only and, for keys on both sides, the
+//! fields whose values differ. Nested values are compared field by field
+//! with dotted paths (e.g. `positions.BTC-PERP.size` inside a margin
+//! account).
+
+use crate::snapshot::{EngineState, RiskState, VaultState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ── Diff Types ──────────────────────────────────────────────────────
+
+/// One field whose value differs between the two states.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    /// Dotted field path (`value` for plain map values).
+    pub field: String,
+    /// Rendered value in `self` (`<absent>` if missing).
+    pub self_value: String,
+    /// Rendered value in `other` (`<absent>` if missing).
+    pub other_value: String,
+}
+
+/// A key present in both states with differing values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryDiff {
+    pub key: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Differences within one keyed map, keys in sorted order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MapDiff {
+    pub only_in_self: Vec<String>,
+    pub only_in_other: Vec<String>,
+    pub changed: Vec<EntryDiff>,
+}
+
+impl MapDiff {
+    /// Compare two maps key by key.
+    pub fn compute<V: Serialize + PartialEq>(
+        this: &BTreeMap<String, V>,
+        other: &BTreeMap<String, V>,
+    ) -> Self {
+        let mut diff = Self::default();
+        for (key, value) in this {
+            match other.get(key) {
+                None => diff.only_in_self.push(key.clone()),
+                Some(theirs) if theirs != value => diff.changed.push(EntryDiff {
+                    key: key.clone(),
+                    fields: field_diffs(value, theirs),
+                }),
+                Some(_) => {}
+            }
+        }
+        diff.only_in_other = other
+            .keys()
+            .filter(|key| !this.contains_key(*key))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only_in_self.is_empty() && self.only_in_other.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Differences within the risk section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskDiff {
+    pub margin: MapDiff,
+    pub funding: MapDiff,
+    pub mark_sources: MapDiff,
+    pub marks: MapDiff,
+    pub insurance_fund: MapDiff,
+}
+
+/// Differences within the vault section. `settings` compares the scalar
+/// fields (`paused`, `batches`) as one map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultDiff {
+    pub balances: MapDiff,
+    pub tokens: MapDiff,
+    pub deposits: MapDiff,
+    pub withdrawals: MapDiff,
+    pub nonces: MapDiff,
+    pub settings: MapDiff,
+}
+
+/// Full divergence report between two engine states.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub accounts: MapDiff,
+    pub orders: MapDiff,
+    pub positions: MapDiff,
+    pub balances: MapDiff,
+    pub risk: RiskDiff,
+    pub vault: VaultDiff,
+}
+
+impl StateDiff {
+    /// Whether the two states were identical.
+    pub fn is_empty(&self) -> bool {
+        self.maps().iter().all(|(_, diff)| diff.is_empty())
+    }
+
+    /// Every map diff with its name, in state order.
+    pub fn maps(&self) -> [(&'static str, &MapDiff); 15] {
+        [
+            ("accounts", &self.accounts),
+            ("orders", &self.orders),
+            ("positions", &self.positions),
+            ("balances", &self.balances),
+            ("risk.margin", &self.risk.margin),
+            ("risk.funding", &self.risk.funding),
+            ("risk.mark_sources", &self.risk.mark_sources),
+            ("risk.marks", &self.risk.marks),
+            ("risk.insurance_fund", &self.risk.insurance_fund),
+            ("vault.balances", &self.vault.balances),
+            ("vault.tokens", &self.vault.tokens),
+            ("vault.deposits", &self.vault.deposits),
+            ("vault.withdrawals", &self.vault.withdrawals),
+            ("vault.nonces", &self.vault.nonces),
+            ("vault.settings", &self.vault.settings),
+        ]
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "states are identical");
+        }
+        for (name, diff) in self.maps() {
+            if diff.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}:", name)?;
+            for key in &diff.only_in_self {
+                writeln!(f, "  - {} (only in self)", key)?;
+            }
+            for key in &diff.only_in_other {
+                writeln!(f, "  + {} (only in other)", key)?;
+            }
+            for entry in &diff.changed {
+                writeln!(f, "  ~ {}", entry.key)?;
+                for field in &entry.fields {
+                    writeln!(
+                        f,
+                        "      {}: {} != {}",
+                        field.field, field.self_value, field.other_value
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Engine State ────────────────────────────────────────────────────
+
+impl EngineState {
+    /// Report where `self` and `other` differ.
+    pub fn diff(&self, other: &EngineState) -> StateDiff {
+        StateDiff {
+            accounts: MapDiff::compute(&self.accounts, &other.accounts),
+            orders: MapDiff::compute(&self.orders, &other.orders),
+            positions: MapDiff::compute(&self.positions, &other.positions),
+            balances: MapDiff::compute(&self.balances, &other.balances),
+            risk: self.risk.diff(&other.risk),
+            vault: self.vault.diff(&other.vault),
+        }
+    }
+}
+
+impl RiskState {
+    /// Report where the risk sections differ.
+    pub fn diff(&self, other: &RiskState) -> RiskDiff {
+        RiskDiff {
+            margin: MapDiff::compute(&self.margin, &other.margin),
+            funding: MapDiff::compute(&self.funding, &other.funding),
+            mark_sources: MapDiff::compute(&self.mark_sources, &other.mark_sources),
+            marks: MapDiff::compute(&self.marks, &other.marks),
+            insurance_fund: MapDiff::compute(&self.insurance_fund, &other.insurance_fund),
+        }
+    }
+}
+
+impl VaultState {
+    /// Report where the vault sections differ.
+    pub fn diff(&self, other: &VaultState) -> VaultDiff {
+        VaultDiff {
+            balances: MapDiff::compute(&self.balances, &other.balances),
+            tokens: MapDiff::compute(&self.tokens, &other.tokens),
+            deposits: MapDiff::compute(&self.deposits, &other.deposits),
+            withdrawals: MapDiff::compute(&self.withdrawals, &other.withdrawals),
+            nonces: MapDiff::compute(&self.nonces, &other.nonces),
+            settings: MapDiff::compute(&self.settings(), &other.settings()),
+        }
+    }
+
+    fn settings(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("paused".to_string(), self.paused.to_string()),
+            ("batches".to_string(), self.batches.to_string()),
+        ])
+    }
+}
+
+// ── Field Comparison ────────────────────────────────────────────────
+
+const ABSENT: &str = "<absent>";
+
+fn field_diffs<V: Serialize>(this: &V, other: &V) -> Vec<FieldDiff> {
+    let this = serde_json::to_value(this).expect("snapshot values serialize to JSON");
+    let other = serde_json::to_value(other).expect("snapshot values serialize to JSON");
+    let mut out = Vec::new();
+    diff_values("", Some(&this), Some(&other), &mut out);
+    out
+}
+
+fn diff_values(path: &str, this: Option<&Value>, other: Option<&Value>, out: &mut Vec<FieldDiff>) {
+    if let (Some(Value::Object(a)), Some(Value::Object(b))) = (this, other) {
+        let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let nested = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff_values(&nested, a.get(key), b.get(key), out);
+        }
+    } else if this != other {
+        out.push(FieldDiff {
+            field: if path.is_empty() { "value".into() } else { path.into() },
+            self_value: render(this),
+            other_value: render(other),
+        });
+    }
+}
+
+fn render(value: Option<&Value>) -> String {
+    match value {
+        None => ABSENT.into(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{
+        AccountSnapshot, BalanceSnapshot, MarginPositionSnapshot, MarginSnapshot, OrderSnapshot,
+        PositionSnapshot,
+    };
+
+    fn account(id: &str, status: &str) -> AccountSnapshot {
+        AccountSnapshot {
+            account_id: id.into(),
+            account_type: "Spot".into(),
+            status: status.into(),
+            created_at: 1,
+            updated_at: 1,
+            version: 1,
+        }
+    }
+
+    fn order(id: &str, filled: &str) -> OrderSnapshot {
+        OrderSnapshot {
+            order_id: id.into(),
+            account_id: "acc-1".into(),
+            symbol: "BTC-USDT".into(),
+            side: "Buy".into(),
+            price: "50000".into(),
+            quantity: "2".into(),
+            filled_quantity: filled.into(),
+            remaining_quantity: "1".into(),
+            status: "PartiallyFilled".into(),
+            created_at: 1,
+            updated_at: 2,
+        }
+    }
+
+    fn position(id: &str, size: &str) -> PositionSnapshot {
+        PositionSnapshot {
+            position_id: id.into(),
+            account_id: "acc-1".into(),
+            symbol: "BTC-PERP".into(),
+            side: "Long".into(),
+            size: size.into(),
+            entry_price: "50000".into(),
+            unrealized_pnl: "0".into(),
+        }
+    }
+
+    fn balance(account: &str, total: &str, locked: &str) -> BalanceSnapshot {
+        BalanceSnapshot {
+            account_id: account.into(),
+            asset: "USDT".into(),
+            total: total.into(),
+            available: total.into(),
+            locked: locked.into(),
+        }
+    }
+
+    fn margin(size: &str) -> MarginSnapshot {
+        let position = MarginPositionSnapshot {
+            position_id: "pos-1".into(),
+            symbol: "BTC-PERP".into(),
+            side: "Long".into(),
+            size: size.into(),
+            entry_price: "50000".into(),
+            mark_price: "50000".into(),
+            liquidation_price: "40000".into(),
+            realized_pnl: "0".into(),
+            unrealized_pnl: "0".into(),
+            initial_margin: "5000".into(),
+            maintenance_margin: "2500".into(),
+            leverage: 10,
+            opened_at: 1,
+            updated_at: 1,
+            version: 1,
+        };
+        MarginSnapshot {
+            account_id: "acc-1".into(),
+            monitor_order: 0,
+            collateral: "10000".into(),
+            liquidating: false,
+            positions: BTreeMap::from([("BTC-PERP".to_string(), position)]),
+        }
+    }
+
+    fn base_state() -> EngineState {
+        let mut state = EngineState::empty();
+        state.accounts.insert("acc-1".into(), account("acc-1", "Active"));
+        state.orders.insert("ord-1".into(), order("ord-1", "1"));
+        state.positions.insert("pos-1".into(), position("pos-1", "1"));
+        state.balances.insert("acc-1:USDT".into(), balance("acc-1", "1000", "0"));
+        state.risk.margin.insert("acc-1".into(), margin("1"));
+        state.risk.marks.insert("BTC-PERP".into(), "50000".into());
+        state
+    }
+
+    #[test]
+    fn test_equal_states_have_empty_diff() {
+        let diff = base_state().diff(&base_state());
+        assert!(diff.is_empty());
+        assert_eq!(diff, StateDiff::default());
+        assert_eq!(diff.to_string(), "states are identical");
+    }
+
+    #[test]
+    fn test_diff_covers_every_top_level_map() {
+        let ours = base_state();
+        let mut theirs = base_state();
+        theirs.accounts.insert("acc-2".into(), account("acc-2", "Active"));
+        theirs.orders.remove("ord-1");
+        theirs.positions.get_mut("pos-1").unwrap().size = "3".into();
+        let bal = theirs.balances.get_mut("acc-1:USDT").unwrap();
+        bal.total = "900".into();
+        bal.locked = "100".into();
+
+        let diff = ours.diff(&theirs);
+        assert_eq!(diff.accounts.only_in_other, vec!["acc-2"]);
+        assert!(diff.accounts.only_in_self.is_empty());
+        assert_eq!(diff.orders.only_in_self, vec!["ord-1"]);
+        assert_eq!(
+            diff.positions.changed,
+            vec![EntryDiff {
+                key: "pos-1".into(),
+                fields: vec![FieldDiff {
+                    field: "size".into(),
+                    self_value: "1".into(),
+                    other_value: "3".into(),
+                }],
+            }]
+        );
+        let fields: Vec<&str> = diff.balances.changed[0]
+            .fields
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["locked", "total"]);
+        assert!(diff.risk.margin.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_nested_risk_fields() {
+        let ours = base_state();
+        let mut theirs = base_state();
+        theirs.risk.margin.insert("acc-1".into(), margin("2"));
+        theirs.risk.marks.insert("BTC-PERP".into(), "51000".into());
+        theirs.risk.insurance_fund.insert("USDT".into(), "1000000".into());
+
+        let diff = ours.diff(&theirs);
+        assert_eq!(diff.risk.margin.changed[0].fields[0].field, "positions.BTC-PERP.size");
+        assert_eq!(
+            diff.risk.marks.changed[0].fields,
+            vec![FieldDiff {
+                field: "value".into(),
+                self_value: "50000".into(),
+                other_value: "51000".into(),
+            }]
+        );
+        assert_eq!(diff.risk.insurance_fund.only_in_other, vec!["USDT"]);
+        assert!(diff.accounts.is_empty() && diff.balances.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_vault_maps_and_settings() {
+        let ours = base_state();
+        let mut theirs = base_state();
+        theirs.vault.balances.insert("acc-1:USDT".into(), "75".into());
+        theirs.vault.nonces.insert("acc-1".into(), vec![1, 2]);
+        theirs.vault.paused = true;
+
+        let diff = ours.diff(&theirs);
+        assert_eq!(diff.vault.balances.only_in_other, vec!["acc-1:USDT"]);
+        assert_eq!(diff.vault.nonces.only_in_other, vec!["acc-1"]);
+        assert_eq!(
+            diff.vault.settings.changed,
+            vec![EntryDiff {
+                key: "paused".into(),
+                fields: vec![FieldDiff {
+                    field: "value".into(),
+                    self_value: "false".into(),
+                    other_value: "true".into(),
+                }],
+            }]
+        );
+        assert!(diff.balances.is_empty() && diff.vault.withdrawals.is_empty());
+    }
+
+    #[test]
+    fn test_display_and_serde() {
+        let ours = base_state();
+        let mut theirs = base_state();
+        theirs.accounts.get_mut("acc-1").unwrap().status = "Frozen".into();
+        theirs.orders.insert("ord-2".into(), order("ord-2", "0"));
+
+        let diff = ours.diff(&theirs);
+        assert_eq!(
+            diff.to_string(),
+            "accounts:\n  ~ acc-1\n      status: Active != Frozen\norders:\n  + ord-2 (only in other)\n"
+        );
+
+        let json = serde_json::to_string(&diff).unwrap();
+        let back: StateDiff = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, diff);
+    }
+}
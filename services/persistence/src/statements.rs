@@ -0,0 +1,745 @@
+//! Account Statements — per-account ledgers generated from the journal
+//!
+//! Filters the journaled event stream for one account and builds a
+//! deterministic statement over a time range:
+//! - Opening and closing balances per asset
+//! - Chronological line items (fills, fees, funding, deposits,
+//!   withdrawals, liquidations) with running balances
+//! - Hard reconciliation against journaled `BalanceUpdated` records
+//! - JSON and CSV export
+//!
+//! Payloads are decoded by `event_type` using the bincode schemas defined
+//! below (spec §08 field names). Unrelated event types are skipped, so a
+//! statement can be generated straight from the shared exchange journal.
+//! All maps are `BTreeMap` so output is byte-identical across runs (spec §12).
+
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::journal::JournalEntry;
+use crate::reader::{JournalReader, ReaderError};
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Error, Debug)]
+pub enum StatementError {
+    #[error("Reader error: {0}")]
+    Reader(#[from] ReaderError),
+
+    #[error("Failed to decode {event_type} at seq={sequence}: {reason}")]
+    Decode {
+        sequence: u64,
+        event_type: String,
+        reason: String,
+    },
+
+    #[error("Invalid statement range: from={from} is after to={to}")]
+    InvalidRange { from: i64, to: i64 },
+
+    #[error("Invalid symbol {0}: expected BASE/QUOTE")]
+    InvalidSymbol(String),
+
+    #[error("Reconciliation failed for {asset} at {at}: computed {computed}, journal records {recorded}")]
+    Reconciliation {
+        asset: String,
+        at: i64,
+        computed: Decimal,
+        recorded: Decimal,
+    },
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+}
+
+// ── Journaled Payloads ──────────────────────────────────────────────
+
+/// Event type strings read by the statement generator.
+pub mod event_types {
+    pub const TRADE_EXECUTED: &str = "TradeExecuted";
+    pub const TRADE_SETTLED: &str = "TradeSettled";
+    pub const FUNDING_PAYMENT: &str = "FundingPayment";
+    pub const DEPOSIT_CONFIRMED: &str = "DepositConfirmed";
+    pub const WITHDRAWAL_COMPLETED: &str = "WithdrawalCompleted";
+    pub const POSITION_LIQUIDATED: &str = "PositionLiquidated";
+    pub const BALANCE_UPDATED: &str = "BalanceUpdated";
+}
+
+/// `TradeExecuted` payload (spec §08.3.2). `side` is the taker side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeExecutedPayload {
+    pub trade_id: String,
+    pub symbol: String,
+    pub maker_account_id: String,
+    pub taker_account_id: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub side: String,
+}
+
+/// `TradeSettled` payload (spec §08.3.2). Fees are charged in the quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeSettledPayload {
+    pub trade_id: String,
+    pub maker_fee: Decimal,
+    pub taker_fee: Decimal,
+}
+
+/// Funding payment; positive `amount` is received, negative is paid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingPaymentPayload {
+    pub account_id: String,
+    pub symbol: String,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+/// `DepositConfirmed` payload (spec §08.3.7).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositConfirmedPayload {
+    pub account_id: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub tx_id: String,
+}
+
+/// `WithdrawalCompleted` payload (spec §08.3.7).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithdrawalCompletedPayload {
+    pub account_id: String,
+    pub withdrawal_id: String,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+/// `PositionLiquidated` payload (spec §08.3.5) settled in `asset`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionLiquidatedPayload {
+    pub account_id: String,
+    pub position_id: String,
+    pub asset: String,
+    pub loss: Decimal,
+    pub liquidation_fee: Decimal,
+}
+
+/// `BalanceUpdated` payload (spec §08.3.3) — the ledger's recorded balance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceUpdatedPayload {
+    pub account_id: String,
+    pub asset: String,
+    pub delta: Decimal,
+    pub balance_after: Decimal,
+    pub update_reason: String,
+    pub reference_id: String,
+}
+
+/// Build a journal entry with a bincode payload for statement events.
+pub fn statement_entry<T: Serialize>(
+    sequence: u64,
+    timestamp: i64,
+    event_type: &str,
+    payload: &T,
+) -> Result<JournalEntry, StatementError> {
+    let bytes =
+        bincode::serialize(payload).map_err(|e| StatementError::Serialization(e.to_string()))?;
+    Ok(JournalEntry::new(sequence, timestamp, event_type.to_string(), bytes))
+}
+
+// ── Statement ───────────────────────────────────────────────────────
+
+/// Kind of statement line item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LineKind {
+    Fill,
+    Fee,
+    Funding,
+    Deposit,
+    Withdrawal,
+    Liquidation,
+}
+
+impl LineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineKind::Fill => "FILL",
+            LineKind::Fee => "FEE",
+            LineKind::Funding => "FUNDING",
+            LineKind::Deposit => "DEPOSIT",
+            LineKind::Withdrawal => "WITHDRAWAL",
+            LineKind::Liquidation => "LIQUIDATION",
+        }
+    }
+}
+
+/// One balance movement on the statement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatementLine {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub kind: LineKind,
+    pub asset: String,
+    /// Signed amount: credits positive, debits negative.
+    pub amount: Decimal,
+    /// Running balance of `asset` after this line.
+    pub balance_after: Decimal,
+    /// Trade, transaction, withdrawal or position ID.
+    pub reference: String,
+}
+
+/// Per-asset totals for the statement period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetBalance {
+    pub opening: Decimal,
+    pub credits: Decimal,
+    pub debits: Decimal,
+    pub closing: Decimal,
+    /// Latest journaled balance at or before the period end, if any.
+    pub recorded_closing: Option<Decimal>,
+}
+
+/// Deterministic account statement for a time range (inclusive).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountStatement {
+    pub account_id: String,
+    pub from: i64,
+    pub to: i64,
+    /// Balances keyed by asset symbol.
+    pub balances: BTreeMap<String, AssetBalance>,
+    /// Line items in journal sequence order.
+    pub lines: Vec<StatementLine>,
+}
+
+impl AccountStatement {
+    /// Generate a statement from the journal in `journal_dir`.
+    pub fn generate(
+        journal_dir: &Path,
+        account_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Self, StatementError> {
+        let entries = JournalReader::open(journal_dir)?.read_all_validated()?;
+        Self::from_entries(&entries, account_id, from, to)
+    }
+
+    /// Generate a statement from already-read journal entries.
+    pub fn from_entries(
+        entries: &[JournalEntry],
+        account_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Self, StatementError> {
+        if from > to {
+            return Err(StatementError::InvalidRange { from, to });
+        }
+
+        let mut builder = Builder {
+            account_id,
+            from,
+            to,
+            trades: HashMap::new(),
+            running: BTreeMap::new(),
+            opening: None,
+            recorded_opening: BTreeMap::new(),
+            recorded_closing: BTreeMap::new(),
+            lines: Vec::new(),
+        };
+        for entry in entries.iter().filter(|e| e.timestamp <= to) {
+            builder.apply(entry)?;
+        }
+        builder.finish()
+    }
+
+    /// Serialize to pretty JSON.
+    pub fn to_json(&self) -> Result<String, StatementError> {
+        serde_json::to_string_pretty(self).map_err(|e| StatementError::Serialization(e.to_string()))
+    }
+
+    /// Export as CSV: opening rows, line items, then closing rows.
+    ///
+    /// Columns: `sequence,timestamp,kind,asset,amount,balance,reference`.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("sequence,timestamp,kind,asset,amount,balance,reference\n");
+        for (asset, balance) in &self.balances {
+            out.push_str(&format!(",{},OPENING,{},,{},\n", self.from, csv_field(asset), balance.opening));
+        }
+        for line in &self.lines {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                line.sequence,
+                line.timestamp,
+                line.kind.as_str(),
+                csv_field(&line.asset),
+                line.amount,
+                line.balance_after,
+                csv_field(&line.reference),
+            ));
+        }
+        for (asset, balance) in &self.balances {
+            out.push_str(&format!(",{},CLOSING,{},,{},\n", self.to, csv_field(asset), balance.closing));
+        }
+        out
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Role of the statement account in a trade.
+struct TradeRole {
+    quote: String,
+    is_maker: bool,
+}
+
+/// Accumulates balances and line items while scanning the journal.
+struct Builder<'a> {
+    account_id: &'a str,
+    from: i64,
+    to: i64,
+    /// Trades the account took part in, for fee settlement lookups.
+    trades: HashMap<String, TradeRole>,
+    /// Computed balance per asset.
+    running: BTreeMap<String, Decimal>,
+    /// Computed balance per asset at the start of the period.
+    opening: Option<BTreeMap<String, Decimal>>,
+    /// Latest journaled balance before the period.
+    recorded_opening: BTreeMap<String, Decimal>,
+    /// Latest journaled balance at or before the period end.
+    recorded_closing: BTreeMap<String, Decimal>,
+    lines: Vec<StatementLine>,
+}
+
+impl Builder<'_> {
+    fn apply(&mut self, entry: &JournalEntry) -> Result<(), StatementError> {
+        if entry.timestamp >= self.from && self.opening.is_none() {
+            self.opening = Some(self.running.clone());
+        }
+
+        match entry.event_type.as_str() {
+            event_types::TRADE_EXECUTED => {
+                let p: TradeExecutedPayload = decode(entry)?;
+                let is_maker = p.maker_account_id == self.account_id;
+                if !is_maker && p.taker_account_id != self.account_id {
+                    return Ok(());
+                }
+                let (base, quote) = p
+                    .symbol
+                    .split_once('/')
+                    .ok_or_else(|| StatementError::InvalidSymbol(p.symbol.clone()))?;
+                // The maker is on the opposite side of the taker
+                let buys = (p.side == "BUY") != is_maker;
+                let notional = p.price * p.quantity;
+                let (base_delta, quote_delta) = if buys {
+                    (p.quantity, -notional)
+                } else {
+                    (-p.quantity, notional)
+                };
+                self.line(entry, LineKind::Fill, base, base_delta, &p.trade_id);
+                self.line(entry, LineKind::Fill, quote, quote_delta, &p.trade_id);
+                self.trades.insert(
+                    p.trade_id,
+                    TradeRole {
+                        quote: quote.to_string(),
+                        is_maker,
+                    },
+                );
+            }
+            event_types::TRADE_SETTLED => {
+                let p: TradeSettledPayload = decode(entry)?;
+                if let Some(role) = self.trades.get(&p.trade_id) {
+                    let fee = if role.is_maker { p.maker_fee } else { p.taker_fee };
+                    let quote = role.quote.clone();
+                    if !fee.is_zero() {
+                        self.line(entry, LineKind::Fee, &quote, -fee, &p.trade_id);
+                    }
+                }
+            }
+            event_types::FUNDING_PAYMENT => {
+                let p: FundingPaymentPayload = decode(entry)?;
+                if p.account_id == self.account_id {
+                    self.line(entry, LineKind::Funding, &p.asset, p.amount, &p.symbol);
+                }
+            }
+            event_types::DEPOSIT_CONFIRMED => {
+                let p: DepositConfirmedPayload = decode(entry)?;
+                if p.account_id == self.account_id {
+                    self.line(entry, LineKind::Deposit, &p.asset, p.amount, &p.tx_id);
+                }
+            }
+            event_types::WITHDRAWAL_COMPLETED => {
+                let p: WithdrawalCompletedPayload = decode(entry)?;
+                if p.account_id == self.account_id {
+                    self.line(entry, LineKind::Withdrawal, &p.asset, -p.amount, &p.withdrawal_id);
+                }
+            }
+            event_types::POSITION_LIQUIDATED => {
+                let p: PositionLiquidatedPayload = decode(entry)?;
+                if p.account_id == self.account_id {
+                    let amount = -(p.loss + p.liquidation_fee);
+                    self.line(entry, LineKind::Liquidation, &p.asset, amount, &p.position_id);
+                }
+            }
+            event_types::BALANCE_UPDATED => {
+                let p: BalanceUpdatedPayload = decode(entry)?;
+                if p.account_id == self.account_id {
+                    if entry.timestamp < self.from {
+                        self.recorded_opening.insert(p.asset.clone(), p.balance_after);
+                    }
+                    self.recorded_closing.insert(p.asset, p.balance_after);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Apply a balance movement; only movements inside the period are listed.
+    fn line(&mut self, entry: &JournalEntry, kind: LineKind, asset: &str, amount: Decimal, reference: &str) {
+        let balance = self.running.entry(asset.to_string()).or_insert(Decimal::ZERO);
+        *balance += amount;
+        if entry.timestamp >= self.from {
+            self.lines.push(StatementLine {
+                sequence: entry.sequence,
+                timestamp: entry.timestamp,
+                kind,
+                asset: asset.to_string(),
+                amount,
+                balance_after: *balance,
+                reference: reference.to_string(),
+            });
+        }
+    }
+
+    fn finish(self) -> Result<AccountStatement, StatementError> {
+        // No entry reached the period start: everything seen is opening state
+        let opening = self.opening.unwrap_or_else(|| self.running.clone());
+
+        check_recorded(&opening, &self.recorded_opening, self.from)?;
+
+        let mut balances: BTreeMap<String, AssetBalance> = BTreeMap::new();
+        for asset in opening.keys().chain(self.running.keys()).chain(self.recorded_closing.keys()) {
+            let open = opening.get(asset).copied().unwrap_or(Decimal::ZERO);
+            balances.entry(asset.clone()).or_insert(AssetBalance {
+                opening: open,
+                credits: Decimal::ZERO,
+                debits: Decimal::ZERO,
+                closing: open,
+                recorded_closing: self.recorded_closing.get(asset).copied(),
+            });
+        }
+        for line in &self.lines {
+            let balance = balances.get_mut(&line.asset).expect("asset seen in running balances");
+            if line.amount.is_sign_negative() {
+                balance.debits += line.amount;
+            } else {
+                balance.credits += line.amount;
+            }
+            balance.closing += line.amount;
+        }
+
+        // closing = opening + line items must match the journaled ledger
+        for (asset, balance) in &balances {
+            let computed = self.running.get(asset).copied().unwrap_or(Decimal::ZERO);
+            debug_assert_eq!(computed, balance.closing);
+            if let Some(recorded) = balance.recorded_closing {
+                if recorded != balance.closing {
+                    return Err(StatementError::Reconciliation {
+                        asset: asset.clone(),
+                        at: self.to,
+                        computed: balance.closing,
+                        recorded,
+                    });
+                }
+            }
+        }
+
+        Ok(AccountStatement {
+            account_id: self.account_id.to_string(),
+            from: self.from,
+            to: self.to,
+            balances,
+            lines: self.lines,
+        })
+    }
+}
+
+fn check_recorded(
+    computed: &BTreeMap<String, Decimal>,
+    recorded: &BTreeMap<String, Decimal>,
+    at: i64,
+) -> Result<(), StatementError> {
+    for (asset, &recorded) in recorded {
+        let computed = computed.get(asset).copied().unwrap_or(Decimal::ZERO);
+        if computed != recorded {
+            return Err(StatementError::Reconciliation {
+                asset: asset.clone(),
+                at,
+                computed,
+                recorded,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn decode<T: DeserializeOwned>(entry: &JournalEntry) -> Result<T, StatementError> {
+    bincode::deserialize(&entry.payload).map_err(|e| StatementError::Decode {
+        sequence: entry.sequence,
+        event_type: entry.event_type.clone(),
+        reason: e.to_string(),
+    })
+}
+
+// ── Tests ───────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalConfig, JournalWriter};
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    const ALICE: &str = "acc-alice";
+    const BOB: &str = "acc-bob";
+    const HOUR: i64 = 3_600_000_000_000;
+    const DAY: i64 = 24 * HOUR;
+    const T0: i64 = 1708128000000000000;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    /// Journal builder tracking the ledger's own balances for BalanceUpdated.
+    struct Ledger {
+        entries: Vec<JournalEntry>,
+        balances: BTreeMap<(String, String), Decimal>,
+    }
+
+    impl Ledger {
+        fn new() -> Self {
+            Self {
+                entries: Vec::new(),
+                balances: BTreeMap::new(),
+            }
+        }
+
+        fn push<T: Serialize>(&mut self, ts: i64, event_type: &str, payload: &T) {
+            let seq = self.entries.len() as u64 + 1;
+            self.entries.push(statement_entry(seq, ts, event_type, payload).unwrap());
+        }
+
+        fn credit(&mut self, ts: i64, account: &str, asset: &str, delta: Decimal, reason: &str) {
+            let key = (account.to_string(), asset.to_string());
+            let balance = self.balances.entry(key).or_insert(Decimal::ZERO);
+            *balance += delta;
+            let payload = BalanceUpdatedPayload {
+                account_id: account.to_string(),
+                asset: asset.to_string(),
+                delta,
+                balance_after: *balance,
+                update_reason: reason.to_string(),
+                reference_id: String::new(),
+            };
+            self.push(ts, event_types::BALANCE_UPDATED, &payload);
+        }
+
+        fn deposit(&mut self, ts: i64, account: &str, asset: &str, amount: &str) {
+            let payload = DepositConfirmedPayload {
+                account_id: account.to_string(),
+                asset: asset.to_string(),
+                amount: dec(amount),
+                tx_id: format!("tx-{}", self.entries.len()),
+            };
+            self.push(ts, event_types::DEPOSIT_CONFIRMED, &payload);
+            self.credit(ts, account, asset, dec(amount), "DEPOSIT");
+        }
+
+        /// Taker buys `qty` BTC from the maker at `price`, then settles fees.
+        fn trade(&mut self, ts: i64, maker: &str, taker: &str, price: &str, qty: &str) {
+            let trade_id = format!("trade-{}", self.entries.len());
+            let (p, q) = (dec(price), dec(qty));
+            let payload = TradeExecutedPayload {
+                trade_id: trade_id.clone(),
+                symbol: "BTC/USDT".to_string(),
+                maker_account_id: maker.to_string(),
+                taker_account_id: taker.to_string(),
+                price: p,
+                quantity: q,
+                side: "BUY".to_string(),
+            };
+            self.push(ts, event_types::TRADE_EXECUTED, &payload);
+            let (maker_fee, taker_fee) = (p * q * dec("0.0002"), p * q * dec("0.0005"));
+            self.push(
+                ts,
+                event_types::TRADE_SETTLED,
+                &TradeSettledPayload {
+                    trade_id,
+                    maker_fee,
+                    taker_fee,
+                },
+            );
+            self.credit(ts, taker, "BTC", q, "TRADE_SETTLEMENT");
+            self.credit(ts, taker, "USDT", -(p * q) - taker_fee, "TRADE_SETTLEMENT");
+            self.credit(ts, maker, "BTC", -q, "TRADE_SETTLEMENT");
+            self.credit(ts, maker, "USDT", p * q - maker_fee, "TRADE_SETTLEMENT");
+        }
+
+        fn funding(&mut self, ts: i64, account: &str, amount: &str) {
+            let payload = FundingPaymentPayload {
+                account_id: account.to_string(),
+                symbol: "BTC/USDT".to_string(),
+                asset: "USDT".to_string(),
+                amount: dec(amount),
+            };
+            self.push(ts, event_types::FUNDING_PAYMENT, &payload);
+            self.credit(ts, account, "USDT", dec(amount), "FUNDING");
+        }
+
+        fn withdraw(&mut self, ts: i64, account: &str, asset: &str, amount: &str) {
+            let payload = WithdrawalCompletedPayload {
+                account_id: account.to_string(),
+                withdrawal_id: format!("wd-{}", self.entries.len()),
+                asset: asset.to_string(),
+                amount: dec(amount),
+            };
+            self.push(ts, event_types::WITHDRAWAL_COMPLETED, &payload);
+            self.credit(ts, account, asset, -dec(amount), "WITHDRAWAL");
+        }
+
+        fn liquidate(&mut self, ts: i64, account: &str, loss: &str, fee: &str) {
+            let payload = PositionLiquidatedPayload {
+                account_id: account.to_string(),
+                position_id: "pos-1".to_string(),
+                asset: "USDT".to_string(),
+                loss: dec(loss),
+                liquidation_fee: dec(fee),
+            };
+            self.push(ts, event_types::POSITION_LIQUIDATED, &payload);
+            self.credit(ts, account, "USDT", -(dec(loss) + dec(fee)), "LIQUIDATION");
+        }
+
+        fn write(&self, dir: &Path) {
+            let mut writer = JournalWriter::open(JournalConfig::new(dir)).unwrap();
+            for entry in &self.entries {
+                writer.append(entry).unwrap();
+            }
+            writer.sync().unwrap();
+        }
+    }
+
+    /// One week of activity for Alice with Bob as counterparty.
+    fn week() -> Ledger {
+        let mut ledger = Ledger::new();
+        ledger.deposit(T0 - DAY, ALICE, "USDT", "100000.00");
+        ledger.deposit(T0 - DAY, BOB, "BTC", "10");
+        ledger.deposit(T0 - DAY, BOB, "USDT", "5000");
+        for day in 0..7 {
+            let ts = T0 + day * DAY;
+            ledger.trade(ts + HOUR, BOB, ALICE, "50123.45", "0.137");
+            ledger.trade(ts + 2 * HOUR, ALICE, BOB, "50200.10", "0.05");
+            ledger.funding(ts + 8 * HOUR, ALICE, "-1.23");
+            ledger.funding(ts + 8 * HOUR, BOB, "1.23");
+        }
+        ledger.liquidate(T0 + 5 * DAY + 12 * HOUR, ALICE, "12.34", "0.66");
+        ledger.withdraw(T0 + 6 * DAY + 20 * HOUR, ALICE, "BTC", "0.25");
+        // After the statement period
+        ledger.deposit(T0 + 8 * DAY, ALICE, "USDT", "1.00");
+        ledger
+    }
+
+    #[test]
+    fn test_week_statement_reconciles_to_the_penny() {
+        let dir = TempDir::new().unwrap();
+        let ledger = week();
+        ledger.write(dir.path());
+
+        let to = T0 + 7 * DAY - 1;
+        let statement = AccountStatement::generate(dir.path(), ALICE, T0, to).unwrap();
+
+        let usdt = &statement.balances["USDT"];
+        assert_eq!(usdt.opening, dec("100000.00"));
+        assert_eq!(usdt.closing, usdt.opening + usdt.credits + usdt.debits);
+        assert_eq!(usdt.recorded_closing, Some(usdt.closing));
+        let btc = &statement.balances["BTC"];
+        assert_eq!(btc.opening, Decimal::ZERO);
+        assert_eq!(btc.closing, dec("0.359")); // 7 × (0.137 − 0.05) − 0.25
+
+        // 7 days × (2 fills × 2 assets + 2 fees + funding) + liquidation + withdrawal
+        assert_eq!(statement.lines.len(), 7 * 7 + 2);
+        assert!(statement.lines.windows(2).all(|w| w[0].sequence <= w[1].sequence));
+        assert!(statement.lines.iter().all(|l| l.timestamp >= T0 && l.timestamp <= to));
+        assert!(statement.lines.iter().any(|l| l.kind == LineKind::Liquidation));
+
+        // Deterministic output in both formats
+        let again = AccountStatement::generate(dir.path(), ALICE, T0, to).unwrap();
+        assert_eq!(statement.to_json().unwrap(), again.to_json().unwrap());
+        let csv = statement.to_csv();
+        assert_eq!(csv, again.to_csv());
+        assert!(csv.starts_with("sequence,timestamp,kind,asset,amount,balance,reference\n"));
+        assert_eq!(csv.lines().count(), 1 + 2 + statement.lines.len() + 2);
+        assert!(csv.contains(",CLOSING,USDT,,"));
+    }
+
+    #[test]
+    fn test_tampered_fill_breaks_reconciliation() {
+        let dir = TempDir::new().unwrap();
+        let mut ledger = week();
+
+        // Rewrite one fill with a different quantity and a valid checksum
+        let index = ledger
+            .entries
+            .iter()
+            .position(|e| e.timestamp > T0 + 3 * DAY && e.event_type == event_types::TRADE_EXECUTED)
+            .unwrap();
+        let original = &ledger.entries[index];
+        let mut fill: TradeExecutedPayload = bincode::deserialize(&original.payload).unwrap();
+        fill.quantity += dec("0.001");
+        ledger.entries[index] =
+            statement_entry(original.sequence, original.timestamp, &original.event_type, &fill)
+                .unwrap();
+        ledger.write(dir.path());
+
+        let result = AccountStatement::generate(dir.path(), ALICE, T0, T0 + 7 * DAY - 1);
+        match result {
+            Err(StatementError::Reconciliation { asset, .. }) => assert_eq!(asset, "BTC"),
+            other => panic!("Expected reconciliation failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_range_and_unrelated_events() {
+        let ledger = week();
+        assert!(matches!(
+            AccountStatement::from_entries(&ledger.entries, ALICE, 10, 5),
+            Err(StatementError::InvalidRange { .. })
+        ));
+
+        let mut entries = ledger.entries.clone();
+        entries.push(JournalEntry::new(
+            entries.len() as u64 + 1,
+            T0,
+            "OrderAccepted".to_string(),
+            vec![0xff; 4],
+        ));
+        let statement =
+            AccountStatement::from_entries(&entries, "acc-nobody", T0, T0 + 7 * DAY).unwrap();
+        assert!(statement.lines.is_empty());
+        assert!(statement.balances.is_empty());
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,103 @@
+//! Async journal writer crash semantics
+//!
+//! Re-runs this test binary as a child process that streams entries through
+//! an `AsyncJournalWriter`, reporting acks and durable watermarks on stdout,
+//! then SIGKILLs it mid-stream. The journal left behind must read back as a
+//! gapless prefix that covers every durable sequence and nothing that was
+//! never acked.
+
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use persistence::async_writer::{AsyncJournalWriter, AsyncWriterConfig};
+use persistence::journal::{FlushPolicy, FsyncPolicy, JournalConfig, JournalEntry, JournalWriter};
+use persistence::reader::JournalReader;
+use tempfile::TempDir;
+
+const CHILD_ENV: &str = "ASYNC_WRITER_CRASH_DIR";
+const TEST_NAME: &str = "killed_writer_leaves_gapless_prefix";
+
+/// Child side: write until killed
+fn stream_until_killed(dir: &Path) -> ! {
+    let config = JournalConfig {
+        max_file_size: 64 * 1024,
+        flush_policy: FlushPolicy::EveryN(8),
+        fsync_policy: FsyncPolicy::EveryN(64),
+        ..JournalConfig::new(dir)
+    };
+    let mut writer = JournalWriter::open(config).unwrap();
+    writer.set_next_sequence(1);
+    let journal = AsyncJournalWriter::spawn(writer, AsyncWriterConfig::default());
+
+    for seq in 1u64.. {
+        let payload = vec![seq as u8; 32 + (seq % 64) as usize];
+        let ack = journal
+            .submit(JournalEntry::new(
+                seq,
+                seq as i64,
+                "TradeExecuted".into(),
+                payload,
+            ))
+            .unwrap();
+        println!("ack {}", ack.sequence);
+        if seq % 100 == 0 {
+            println!("durable {}", journal.flush_and_wait().unwrap());
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn killed_writer_leaves_gapless_prefix() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        stream_until_killed(Path::new(&dir));
+    }
+
+    let tmp = TempDir::new().unwrap();
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([TEST_NAME, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, tmp.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut last_ack = 0u64;
+    let mut last_durable = 0u64;
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    for line in lines.by_ref() {
+        let line = line.unwrap();
+        if let Some(seq) = line.strip_prefix("ack ") {
+            last_ack = seq.parse().unwrap();
+            // Kill between barriers, with unsynced entries in flight
+            if last_durable >= 3_000 && last_ack % 100 == 57 {
+                break;
+            }
+        } else if let Some(seq) = line.strip_prefix("durable ") {
+            last_durable = seq.parse().unwrap();
+        }
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    // Pick up whatever the child printed before it died
+    for line in lines {
+        let line = line.unwrap();
+        if let Some(seq) = line.strip_prefix("ack ") {
+            last_ack = seq.parse().unwrap();
+        }
+    }
+
+    let mut reader = JournalReader::open(tmp.path()).unwrap();
+    let entries = reader.read_all_validated().unwrap();
+    let recovered = entries.last().map_or(0, |e| e.sequence);
+    assert_eq!(entries.first().map(|e| e.sequence), Some(1));
+    assert!(
+        recovered >= last_durable,
+        "lost durable entries: recovered {recovered}, durable {last_durable}"
+    );
+    // The child can be killed between a submit returning and printing it
+    assert!(
+        recovered <= last_ack + 1,
+        "recovered {recovered} beyond last ack {last_ack}"
+    );
+}
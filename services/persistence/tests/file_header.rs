@@ -0,0 +1,248 @@
+//! File headers
+//!
+//! New journal segments and snapshots start with a versioned header. Legacy
+//! header-less files must keep working, a file from a newer format version
+//! must be refused as such (not as corruption), and a damaged header must
+//! be caught by its checksum.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use persistence::compaction::compact;
+use persistence::header::{self, FileHeader, FileKind, HeaderError, FORMAT_VERSION};
+use persistence::journal::{JournalConfig, JournalEntry, JournalError, JournalWriter};
+use persistence::reader::{JournalReader, ReaderError};
+use persistence::snapshot::{EngineState, Snapshot, SnapshotError, SnapshotLoader, SnapshotWriter};
+use tempfile::TempDir;
+
+fn entry(seq: u64) -> JournalEntry {
+    JournalEntry::new(
+        seq,
+        1_708_000_000_000_000_000 + seq as i64,
+        "OrderSubmitted".into(),
+        format!("order-{seq:06}").into_bytes(),
+    )
+}
+
+fn config(dir: &Path) -> JournalConfig {
+    JournalConfig {
+        max_file_size: 1_000,
+        index_interval: 4,
+        ..JournalConfig::new(dir)
+    }
+}
+
+fn write_journal(dir: &Path, seqs: std::ops::RangeInclusive<u64>) {
+    let mut writer = JournalWriter::open(config(dir)).unwrap();
+    writer.set_next_sequence(*seqs.start());
+    for seq in seqs {
+        writer.append(&entry(seq)).unwrap();
+    }
+    writer.sync().unwrap();
+}
+
+fn segments(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "bin"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn sequences(dir: &Path) -> Vec<u64> {
+    JournalReader::open(dir)
+        .unwrap()
+        .read_all_validated()
+        .unwrap()
+        .iter()
+        .map(|e| e.sequence)
+        .collect()
+}
+
+/// Rewrite the version field of the header at the start of `path` and
+/// recompute its checksum, as a newer build would have written it.
+fn bump_version(path: &Path) {
+    let mut data = fs::read(path).unwrap();
+    data[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    let checksum = crc32c::crc32c(&data[..header::HEADER_LEN - 4]);
+    data[header::HEADER_LEN - 4..header::HEADER_LEN].copy_from_slice(&checksum.to_le_bytes());
+    fs::write(path, &data).unwrap();
+}
+
+#[test]
+fn segments_record_their_first_entry() {
+    let tmp = TempDir::new().unwrap();
+    write_journal(tmp.path(), 1..=60);
+
+    let files = segments(tmp.path());
+    assert!(
+        files.len() > 2,
+        "expected rotation, got {} files",
+        files.len()
+    );
+    let mut expected_first = 1;
+    for path in &files {
+        let data = fs::read(path).unwrap();
+        let found = FileHeader::decode(FileKind::Journal, &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.version, FORMAT_VERSION);
+        assert_eq!(found.sequence, expected_first);
+        assert_eq!(found.created_at, entry(expected_first).timestamp);
+
+        let mut reader = JournalReader::open_file(path).unwrap();
+        assert_eq!(reader.segment_header(), Some(&found));
+        expected_first += reader.read_all_validated().unwrap().len() as u64;
+    }
+    assert_eq!(sequences(tmp.path()), (1..=60).collect::<Vec<_>>());
+}
+
+#[test]
+fn legacy_files_are_still_read_and_appended() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("journal-000000.bin");
+    let legacy: Vec<u8> = (1..=5).flat_map(|seq| entry(seq).to_bytes()).collect();
+    fs::write(&path, &legacy).unwrap();
+
+    let mut reader = JournalReader::open(tmp.path()).unwrap();
+    assert_eq!(reader.segment_header(), None);
+    assert_eq!(reader.read_all_validated().unwrap().len(), 5);
+
+    // The writer continues the legacy segment in place; new segments get
+    // headers
+    write_journal(tmp.path(), 6..=60);
+    let data = fs::read(&path).unwrap();
+    assert_eq!(data[..legacy.len()], legacy[..]);
+    assert_eq!(FileHeader::decode(FileKind::Journal, &data).unwrap(), None);
+    let files = segments(tmp.path());
+    let data = fs::read(&files[1]).unwrap();
+    assert!(FileHeader::decode(FileKind::Journal, &data)
+        .unwrap()
+        .is_some());
+    assert_eq!(sequences(tmp.path()), (1..=60).collect::<Vec<_>>());
+
+    // Compaction keeps a legacy boundary segment header-less
+    let report = compact(tmp.path(), 3).unwrap();
+    assert_eq!(report.rewritten.unwrap().first_kept_sequence, 4);
+    let data = fs::read(&path).unwrap();
+    assert!(data.starts_with(&legacy[3 * legacy.len() / 5..]));
+    assert_eq!(sequences(tmp.path()), (4..=60).collect::<Vec<_>>());
+}
+
+#[test]
+fn compaction_rewrites_the_header_of_a_boundary_segment() {
+    let tmp = TempDir::new().unwrap();
+    write_journal(tmp.path(), 1..=60);
+    let report = compact(tmp.path(), 25).unwrap();
+    let rewritten = report.rewritten.unwrap();
+
+    let path = tmp.path().join(&rewritten.file);
+    let data = fs::read(&path).unwrap();
+    let found = FileHeader::decode(FileKind::Journal, &data)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.sequence, rewritten.first_kept_sequence);
+    assert_eq!(found.created_at, entry(found.sequence).timestamp);
+    assert_eq!(sequences(tmp.path()), (26..=60).collect::<Vec<_>>());
+}
+
+#[test]
+fn future_version_is_refused_not_corrupt() {
+    let tmp = TempDir::new().unwrap();
+    write_journal(tmp.path(), 1..=60);
+    let files = segments(tmp.path());
+    bump_version(&files[1]);
+
+    // The reader gets through the first segment, then refuses the second
+    let mut reader = JournalReader::open(tmp.path()).unwrap();
+    let err = loop {
+        match reader.next_entry() {
+            Ok(Some(_)) => {}
+            Ok(None) => panic!("future-version segment was read"),
+            Err(err) => break err,
+        }
+    };
+    assert!(matches!(
+        err,
+        ReaderError::Header(HeaderError::UnsupportedVersion { version, .. })
+            if version == FORMAT_VERSION + 1
+    ));
+    assert!(reader.corruption_log().is_empty());
+
+    // The writer refuses to append to a newer segment
+    bump_version(files.last().unwrap());
+    assert!(matches!(
+        JournalWriter::open(config(tmp.path())),
+        Err(JournalError::Header(HeaderError::UnsupportedVersion { .. }))
+    ));
+}
+
+#[test]
+fn tampered_header_fails_its_checksum() {
+    let tmp = TempDir::new().unwrap();
+    write_journal(tmp.path(), 1..=10);
+    let path = &segments(tmp.path())[0];
+
+    // Claim a different first sequence without fixing the checksum
+    let mut data = fs::read(path).unwrap();
+    data[20] ^= 0x01;
+    fs::write(path, &data).unwrap();
+
+    assert!(matches!(
+        JournalReader::open(tmp.path()),
+        Err(ReaderError::Header(HeaderError::Corrupt {
+            kind: FileKind::Journal,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        JournalWriter::open(config(tmp.path())),
+        Err(JournalError::Header(HeaderError::Corrupt { .. }))
+    ));
+}
+
+#[test]
+fn snapshot_headers() {
+    let tmp = TempDir::new().unwrap();
+    let snapshot = Snapshot::new(42, 7, EngineState::empty(), false);
+    let path = SnapshotWriter::new(tmp.path(), false)
+        .write(&snapshot)
+        .unwrap();
+    let data = fs::read(&path).unwrap();
+    let found = FileHeader::decode(FileKind::Snapshot, &data)
+        .unwrap()
+        .unwrap();
+    assert_eq!((found.sequence, found.created_at), (42, 7));
+    let loader = SnapshotLoader::new(tmp.path());
+    assert_eq!(loader.load(&path).unwrap(), snapshot);
+
+    // Legacy: the bare serialized snapshot
+    let legacy = tmp.path().join("snapshot-000000000010.snap");
+    let old = Snapshot::new(10, 3, EngineState::empty(), false);
+    fs::write(&legacy, bincode::serialize(&old).unwrap()).unwrap();
+    assert_eq!(loader.load(&legacy).unwrap(), old);
+
+    // Tampered header
+    let mut tampered = data.clone();
+    tampered[12] ^= 0x01;
+    fs::write(&path, &tampered).unwrap();
+    assert!(matches!(
+        loader.load(&path),
+        Err(SnapshotError::Header(HeaderError::Corrupt {
+            kind: FileKind::Snapshot,
+            ..
+        }))
+    ));
+
+    // Newer format version
+    fs::write(&path, &data).unwrap();
+    bump_version(&path);
+    assert!(matches!(
+        loader.load(&path),
+        Err(SnapshotError::Header(
+            HeaderError::UnsupportedVersion { .. }
+        ))
+    ));
+}
@@ -0,0 +1,103 @@
+//! Journal reader memory bound
+//!
+//! Streams a multi-megabyte journal through `JournalReader` under a counting
+//! allocator and checks that peak live heap stays flat: reading 40x more
+//! entries must not need meaningfully more memory.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use persistence::journal::{JournalConfig, JournalEntry, JournalWriter};
+use persistence::reader::JournalReader;
+use tempfile::TempDir;
+
+/// System allocator that tracks live and peak heap bytes
+struct CountingAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grow(bytes: usize) {
+    let live = LIVE.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        grow(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const PAYLOAD_LEN: usize = 256;
+
+fn write_journal(dir: &Path, count: u64) {
+    let mut writer = JournalWriter::open(JournalConfig::new(dir)).unwrap();
+    writer.set_next_sequence(1);
+    for seq in 1..=count {
+        let entry = JournalEntry::new(
+            seq,
+            seq as i64 * 1_000,
+            "TradeExecuted".into(),
+            vec![seq as u8; PAYLOAD_LEN],
+        );
+        writer.append(&entry).unwrap();
+    }
+    writer.sync().unwrap();
+}
+
+/// Stream every entry, dropping each, and return (entries, peak heap growth)
+fn stream(dir: &Path) -> (u64, usize) {
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    let mut reader = JournalReader::open(dir).unwrap();
+    let mut count = 0u64;
+    while let Some(entry) = reader.next_entry().unwrap() {
+        assert_eq!(entry.payload.len(), PAYLOAD_LEN);
+        count += 1;
+    }
+    drop(reader);
+
+    (count, PEAK.load(Ordering::Relaxed) - baseline)
+}
+
+// Single test in this binary so no other thread allocates while measuring.
+#[test]
+fn peak_heap_is_independent_of_journal_size() {
+    let small = TempDir::new().unwrap();
+    let large = TempDir::new().unwrap();
+    write_journal(small.path(), 500);
+    write_journal(large.path(), 20_000);
+
+    let (small_count, small_peak) = stream(small.path());
+    let (large_count, large_peak) = stream(large.path());
+    assert_eq!(small_count, 500);
+    assert_eq!(large_count, 20_000);
+
+    // ~6 MB on disk; the reader holds a file buffer and one frame at a time
+    let journal_bytes = 20_000 * PAYLOAD_LEN;
+    assert!(
+        large_peak < 64 * 1024,
+        "peak heap {large_peak} bytes while streaming a {journal_bytes} byte journal"
+    );
+    assert!(
+        large_peak <= small_peak + 4 * 1024,
+        "peak grew with journal size: {small_peak} -> {large_peak}"
+    );
+}
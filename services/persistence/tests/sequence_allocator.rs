@@ -0,0 +1,193 @@
+//! Sequence allocator crash semantics
+//!
+//! Re-runs this test binary as a child process that reserves sequence
+//! ranges, writes only part of them, and is SIGKILLed with reservations
+//! still unwritten. A restart must never hand those numbers out again:
+//! they are voided in the journal, which stays gapless, and reported as
+//! leaked.
+
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use persistence::allocator::{AllocatorError, SequenceAllocator, VOIDED_EVENT_TYPE};
+use persistence::journal::{FsyncPolicy, JournalConfig, JournalEntry, JournalError, JournalWriter};
+use persistence::reader::JournalReader;
+use tempfile::TempDir;
+
+const CHILD_ENV: &str = "SEQUENCE_ALLOCATOR_CRASH_DIR";
+const TEST_NAME: &str = "killed_after_reserve_voids_the_leak";
+
+fn entry(seq: u64) -> JournalEntry {
+    JournalEntry::new(
+        seq,
+        1_708_000_000_000_000_000 + seq as i64,
+        "OrderSubmitted".into(),
+        format!("order-{seq:06}").into_bytes(),
+    )
+}
+
+fn config(dir: &Path) -> JournalConfig {
+    JournalConfig {
+        max_file_size: 1_000,
+        fsync_policy: FsyncPolicy::EveryWrite,
+        ..JournalConfig::new(dir)
+    }
+}
+
+fn open(dir: &Path) -> (Arc<SequenceAllocator>, JournalWriter) {
+    let allocator = Arc::new(SequenceAllocator::open(dir.join("sequence.state")).unwrap());
+    let writer = JournalWriter::open_with_allocator(config(dir), allocator.clone()).unwrap();
+    (allocator, writer)
+}
+
+fn read(dir: &Path) -> Vec<JournalEntry> {
+    JournalReader::open(dir)
+        .unwrap()
+        .read_all_validated()
+        .unwrap()
+}
+
+/// Child side: two producers reserve, one finishes, then wait to be killed
+fn reserve_until_killed(dir: &Path) -> ! {
+    let (allocator, mut writer) = open(dir);
+    let a = allocator.reserve(5).unwrap();
+    let b = allocator.reserve(5).unwrap();
+    for seq in a {
+        writer.append(&entry(seq)).unwrap();
+    }
+    for seq in b.start..b.start + 2 {
+        writer.append(&entry(seq)).unwrap();
+    }
+    let c = allocator.reserve(10).unwrap();
+    println!("reserved {}", c.end);
+    loop {
+        std::thread::park();
+    }
+}
+
+#[test]
+fn killed_after_reserve_voids_the_leak() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        reserve_until_killed(Path::new(&dir));
+    }
+
+    let tmp = TempDir::new().unwrap();
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([TEST_NAME, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, tmp.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut high_water = 0u64;
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        // The harness prints the test name on the same line
+        if let Some((_, end)) = line.unwrap().split_once("reserved ") {
+            high_water = end.parse().unwrap();
+            break;
+        }
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(high_water, 21);
+
+    // Reserved up to 20, written up to 7
+    let crashed = read(tmp.path());
+    assert_eq!(crashed.last().map(|e| e.sequence), Some(7));
+
+    let (allocator, mut writer) = open(tmp.path());
+    assert_eq!(allocator.voided(), vec![8..21]);
+    assert_eq!(writer.next_sequence(), 21);
+
+    let entries = read(tmp.path());
+    assert_eq!(
+        entries.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+        (1..=20).collect::<Vec<_>>()
+    );
+    assert_eq!(entries[..7], crashed[..]);
+    for voided in &entries[7..] {
+        assert_eq!(voided.event_type, VOIDED_EVENT_TYPE);
+        assert!(voided.payload.is_empty());
+        assert_eq!(voided.timestamp, entry(7).timestamp);
+    }
+
+    // Nothing below the old high-water mark comes back
+    let next = allocator.reserve(3).unwrap();
+    assert_eq!(next, 21..24);
+    assert!(matches!(
+        writer.append(&entry(8)),
+        Err(JournalError::SequenceError { expected: 21, got: 8 })
+    ));
+    for seq in next {
+        writer.append(&entry(seq)).unwrap();
+    }
+    assert!(matches!(
+        writer.append(&entry(24)),
+        Err(JournalError::Allocator(AllocatorError::NotReserved { .. }))
+    ));
+    writer.sync().unwrap();
+    drop(writer);
+
+    // A clean restart has nothing to void
+    let (allocator, writer) = open(tmp.path());
+    assert!(allocator.voided().is_empty());
+    assert_eq!(writer.next_sequence(), 24);
+    assert_eq!(read(tmp.path()).len(), 23);
+}
+
+#[test]
+fn released_sequences_are_voided_and_unreleased_ones_block() {
+    let tmp = TempDir::new().unwrap();
+    let (allocator, mut writer) = open(tmp.path());
+    let a = allocator.reserve(3).unwrap();
+    let b = allocator.reserve(3).unwrap();
+
+    // A writes one entry and gives the rest back; B's batch fills in behind
+    writer.append(&entry(a.start)).unwrap();
+    allocator.release(a.start + 1..a.end).unwrap();
+    let batch: Vec<JournalEntry> = b.clone().map(entry).collect();
+    writer.append_batch(&batch).unwrap();
+    assert_eq!(allocator.voided(), vec![2..4]);
+    assert!(allocator.outstanding().is_empty());
+
+    // C still holds its reservation: D cannot skip past it
+    let c = allocator.reserve(2).unwrap();
+    let d = allocator.reserve(1).unwrap();
+    assert!(matches!(
+        writer.append(&entry(d.start)),
+        Err(JournalError::SequenceError { expected: 7, got: 9 })
+    ));
+    assert_eq!(allocator.outstanding(), vec![c.clone(), d.clone()]);
+    for seq in c.start..d.end {
+        writer.append(&entry(seq)).unwrap();
+    }
+    writer.sync().unwrap();
+
+    let types: Vec<(u64, String)> = read(tmp.path())
+        .into_iter()
+        .map(|e| (e.sequence, e.event_type))
+        .collect();
+    assert_eq!(types.len(), 9);
+    for (seq, event_type) in types {
+        let voided = (2..4).contains(&seq);
+        assert_eq!(event_type == VOIDED_EVENT_TYPE, voided, "sequence {seq}");
+    }
+}
+
+#[test]
+fn journal_ahead_of_state_file_is_never_reused() {
+    let tmp = TempDir::new().unwrap();
+    let mut writer = JournalWriter::open(config(tmp.path())).unwrap();
+    writer.set_next_sequence(1);
+    for seq in 1..=30 {
+        writer.append(&entry(seq)).unwrap();
+    }
+    drop(writer);
+
+    // A fresh (or stale) state file catches up with the journal
+    let (allocator, writer) = open(tmp.path());
+    assert_eq!(writer.next_sequence(), 31);
+    assert!(allocator.voided().is_empty());
+    assert_eq!(allocator.reserve(1).unwrap(), 31..32);
+}
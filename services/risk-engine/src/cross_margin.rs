@@ -0,0 +1,220 @@
+//! Cross-margin account evaluation
+//!
+//! Server-side wrapper around `margin_core::CrossMarginEngine` per spec §5.
+//! The risk engine owns the position store and the mark-price feed; the
+//! margin math itself (equity, IM/MM aggregation, margin ratio, risk level)
+//! is delegated to `margin_core`, which the client also uses via `wasm-core`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use margin_core::{CrossMarginEngine, RiskLevel};
+use rust_decimal::Decimal;
+use types::account::Account;
+use types::ids::{AccountId, MarketId};
+use types::numeric::Price;
+use types::position::Position;
+
+use crate::events::{self, RiskEvent};
+
+/// Source of mark prices for open positions (spec §5.3.2).
+pub trait MarkPriceSource {
+    /// Current mark price for `market`, or `None` if unknown.
+    fn mark_price(&self, market: &MarketId) -> Option<Price>;
+}
+
+/// Static mark prices keyed by symbol.
+impl MarkPriceSource for BTreeMap<String, Price> {
+    fn mark_price(&self, market: &MarketId) -> Option<Price> {
+        self.get(market.as_str()).copied()
+    }
+}
+
+/// Open positions per account, keyed by symbol.
+#[derive(Debug, Clone, Default)]
+pub struct PositionStore {
+    positions: HashMap<AccountId, BTreeMap<String, Position>>,
+}
+
+impl PositionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or replace a position, keyed by its account and symbol.
+    pub fn upsert(&mut self, position: Position) {
+        self.positions
+            .entry(position.account_id)
+            .or_default()
+            .insert(position.symbol.as_str().to_owned(), position);
+    }
+
+    /// Remove a position, returning it if present.
+    pub fn remove(&mut self, account_id: &AccountId, symbol: &str) -> Option<Position> {
+        let book = self.positions.get_mut(account_id)?;
+        let removed = book.remove(symbol);
+        if book.is_empty() {
+            self.positions.remove(account_id);
+        }
+        removed
+    }
+
+    /// Positions for an account in symbol order.
+    pub fn positions(&self, account_id: &AccountId) -> impl Iterator<Item = &Position> {
+        self.positions
+            .get(account_id)
+            .into_iter()
+            .flat_map(|book| book.values())
+    }
+}
+
+/// Cross-margin monitor combining the position store with a mark-price feed.
+#[derive(Debug, Clone)]
+pub struct CrossMarginMonitor<M> {
+    store: PositionStore,
+    marks: M,
+}
+
+impl<M: MarkPriceSource> CrossMarginMonitor<M> {
+    pub fn new(marks: M) -> Self {
+        Self {
+            store: PositionStore::new(),
+            marks,
+        }
+    }
+
+    pub fn store(&self) -> &PositionStore {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut PositionStore {
+        &mut self.store
+    }
+
+    pub fn marks_mut(&mut self) -> &mut M {
+        &mut self.marks
+    }
+
+    /// Build the shared margin snapshot for an account.
+    ///
+    /// Collateral is the sum of all balance totals. Each stored position is
+    /// re-marked from the price source; positions without a feed price keep
+    /// their last stored mark.
+    pub fn snapshot(&self, account: &Account) -> CrossMarginEngine {
+        let total_balance: Decimal = account.balances.values().map(|b| b.total).sum();
+        let mut engine = CrossMarginEngine::new(account.account_id, total_balance);
+
+        for pos in self.store.positions(&account.account_id) {
+            let mut marked = pos.clone();
+            if let Some(mark) = self.marks.mark_price(&pos.symbol) {
+                marked.update_mark_price(mark, pos.updated_at);
+            }
+            engine.add_position(marked);
+        }
+        engine
+    }
+
+    /// Margin ratio per spec §5.3.3.
+    pub fn margin_ratio(&self, account: &Account) -> Decimal {
+        self.snapshot(account).margin_ratio()
+    }
+
+    /// Risk level per spec §5.3.3.
+    pub fn risk_level(&self, account: &Account) -> RiskLevel {
+        self.snapshot(account).risk_level()
+    }
+
+    /// Evaluate account health and generate risk events.
+    pub fn evaluate(&self, account: &Account, timestamp: i64) -> Vec<RiskEvent> {
+        let snapshot = self.snapshot(account);
+        if snapshot.positions.is_empty() {
+            return Vec::new();
+        }
+
+        let ratio = snapshot.margin_ratio();
+        events::events_for_health(
+            account.account_id,
+            snapshot.risk_level().into(),
+            ratio,
+            snapshot.equity(),
+            snapshot.total_maintenance_margin(),
+            timestamp,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::account::{AccountType, Balance};
+    use types::numeric::Quantity;
+    use types::position::PositionSide;
+
+    fn make_account(balance: u64) -> Account {
+        let mut account = Account::new(AccountType::FUTURES, 1708123456789000000);
+        account.set_balance(Balance::new("USDT", Decimal::from(balance)), 1708123456789000000);
+        account
+    }
+
+    fn make_position(account_id: AccountId, symbol: &str, entry: u64, leverage: u8) -> Position {
+        Position::new(
+            account_id,
+            MarketId::new(symbol),
+            PositionSide::LONG,
+            Quantity::from_str("1.0").unwrap(),
+            Price::from_u64(entry),
+            Price::from_u64(entry),
+            Price::from_u64(entry / 2),
+            Decimal::from(entry) / Decimal::from(leverage),
+            Decimal::ZERO,
+            leverage,
+            1708123456789000000,
+        )
+    }
+
+    #[test]
+    fn test_store_upsert_and_remove() {
+        let account = make_account(10_000);
+        let mut store = PositionStore::new();
+        store.upsert(make_position(account.account_id, "BTC/USDT", 50_000, 10));
+        store.upsert(make_position(account.account_id, "ETH/USDT", 3_000, 10));
+        store.upsert(make_position(account.account_id, "BTC/USDT", 51_000, 10));
+
+        let symbols: Vec<_> = store.positions(&account.account_id).map(|p| p.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTC/USDT", "ETH/USDT"]);
+
+        assert!(store.remove(&account.account_id, "BTC/USDT").is_some());
+        assert!(store.remove(&account.account_id, "BTC/USDT").is_none());
+        assert_eq!(store.positions(&account.account_id).count(), 1);
+    }
+
+    #[test]
+    fn test_marks_drive_evaluation() {
+        let account = make_account(6_000);
+        let mut marks = BTreeMap::new();
+        marks.insert("BTC/USDT".to_string(), Price::from_u64(50_000));
+        let mut monitor = CrossMarginMonitor::new(marks);
+        // MM = 50 000 × 0.10 = 5 000 at 110x
+        monitor.store_mut().upsert(make_position(account.account_id, "BTC/USDT", 50_000, 110));
+
+        // Equity 6 000 / MM 5 000 = 1.2 → Danger
+        assert_eq!(monitor.risk_level(&account), RiskLevel::Danger);
+
+        // Mark drops 1 000 → equity 5 000, ratio 1.0 → Liquidation
+        monitor.marks_mut().insert("BTC/USDT".to_string(), Price::from_u64(49_000));
+        assert_eq!(monitor.margin_ratio(&account), Decimal::ONE);
+        let events = monitor.evaluate(&account, 1);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0].event_type,
+            events::RiskEventType::LiquidationTriggered
+        ));
+    }
+
+    #[test]
+    fn test_no_positions_is_healthy() {
+        let account = make_account(1_000);
+        let monitor = CrossMarginMonitor::new(BTreeMap::new());
+        assert_eq!(monitor.margin_ratio(&account), Decimal::MAX);
+        assert!(monitor.evaluate(&account, 1).is_empty());
+    }
+}
@@ -3,17 +3,27 @@
 //! Ties together margin, exposure, liquidation, validation,
 //! and event emission per specs §5, §6, §9.3.6.
 
+use std::collections::{BTreeMap, HashMap};
+
 use rust_decimal::Decimal;
 use types::account::Account;
+use types::market::{MarketConfig, MarketStatus};
+use types::ids::AccountId;
+use types::numeric::Price;
 use types::order::Order;
 use types::position::Position;
 use types::risk::RiskCheckResult;
 
-use crate::events::{self, RiskEvent};
-use crate::exposure;
-use crate::liquidation;
+use crate::events::{self, InsuranceFundEvent, RiskEvent};
+use crate::exposure::{self, CurrentExposure};
+use crate::funding::{FundingConfig, FundingEngine};
+use crate::events::RiskEventType;
+use crate::liquidation::{
+    self, HealthLevel, InsuranceFund, LiquidationClose, LiquidationMonitor, LiquidationMonitorConfig,
+};
 use crate::margin;
-use crate::validator;
+use crate::mark_price::{MarkPrice, MarkPriceConfig, MarkPriceEngine, PriceSource};
+use crate::validator::{self, RiskLimits, RiskRejection};
 
 /// Risk engine configuration
 #[derive(Debug, Clone)]
@@ -26,6 +36,14 @@ pub struct RiskEngineConfig {
     pub margin_call_threshold: Decimal,
     /// Margin ratio threshold for liquidation
     pub liquidation_threshold: Decimal,
+    /// Margin ratio a liquidating account must recover to before it clears
+    pub liquidation_clear_threshold: Decimal,
+    /// Perp funding rate and interval parameters
+    pub funding: FundingConfig,
+    /// Position, order size and open-order notional caps
+    pub limits: RiskLimits,
+    /// Mark price staleness and index band
+    pub mark_price: MarkPriceConfig,
 }
 
 impl Default for RiskEngineConfig {
@@ -35,6 +53,10 @@ impl Default for RiskEngineConfig {
             warning_threshold: Decimal::from_str_exact("2.0").unwrap(),
             margin_call_threshold: Decimal::from_str_exact("1.2").unwrap(),
             liquidation_threshold: Decimal::from_str_exact("1.1").unwrap(),
+            liquidation_clear_threshold: Decimal::from_str_exact("1.15").unwrap(),
+            funding: FundingConfig::default(),
+            limits: RiskLimits::default(),
+            mark_price: MarkPriceConfig::default(),
         }
     }
 }
@@ -43,24 +65,140 @@ impl Default for RiskEngineConfig {
 #[derive(Debug, Clone)]
 pub struct RiskEngine {
     config: RiskEngineConfig,
+    /// Last known market status per symbol; unlisted symbols are Trading
+    market_status: HashMap<String, MarketStatus>,
+    /// Price and quantity increments per symbol; unlisted symbols are unchecked
+    market_configs: HashMap<String, MarketConfig>,
+    /// Funding rates and accruals per perp market
+    funding: FundingEngine,
+    /// Liquidation fee income and deficit coverage per settlement asset
+    insurance_fund: InsuranceFund,
+    /// Mark price sources per market
+    mark_prices: MarkPriceEngine,
+    /// Accounts ordered by margin ratio, with liquidation hysteresis
+    monitor: LiquidationMonitor,
 }
 
 impl RiskEngine {
     /// Create a new risk engine with default configuration
     pub fn new() -> Self {
-        Self {
-            config: RiskEngineConfig::default(),
-        }
+        Self::with_config(RiskEngineConfig::default())
     }
 
     /// Create a new risk engine with custom configuration
     pub fn with_config(config: RiskEngineConfig) -> Self {
-        Self { config }
+        Self {
+            funding: FundingEngine::new(config.funding.clone()),
+            mark_prices: MarkPriceEngine::new(config.mark_price.clone()),
+            monitor: LiquidationMonitor::new(LiquidationMonitorConfig {
+                liquidation_threshold: config.liquidation_threshold,
+                clear_threshold: config.liquidation_clear_threshold,
+            }),
+            config,
+            market_status: HashMap::new(),
+            market_configs: HashMap::new(),
+            insurance_fund: InsuranceFund::new(),
+        }
+    }
+
+    /// Record a market status published by the matching engine.
+    pub fn set_market_status(&mut self, symbol: impl Into<String>, status: MarketStatus) {
+        self.market_status.insert(symbol.into(), status);
+    }
+
+    /// Last known status for a market.
+    pub fn market_status(&self, symbol: &str) -> MarketStatus {
+        self.market_status
+            .get(symbol)
+            .copied()
+            .unwrap_or(MarketStatus::Trading)
+    }
+
+    /// Record a market's price and quantity increments.
+    pub fn set_market_config(&mut self, symbol: impl Into<String>, config: MarketConfig) {
+        self.market_configs.insert(symbol.into(), config);
+    }
+
+    /// Increments for a market, if configured.
+    pub fn market_config(&self, symbol: &str) -> Option<&MarketConfig> {
+        self.market_configs.get(symbol)
+    }
+
+    /// Active configuration
+    pub fn config(&self) -> &RiskEngineConfig {
+        &self.config
+    }
+
+    /// Funding state per perp market
+    pub fn funding(&self) -> &FundingEngine {
+        &self.funding
+    }
+
+    /// Funding engine, for rate updates and settlement
+    pub fn funding_mut(&mut self) -> &mut FundingEngine {
+        &mut self.funding
+    }
+
+    /// Record a mid, last trade or index price for a market.
+    pub fn update_mark_source(&mut self, symbol: &str, source: PriceSource, price: Price, timestamp: i64) {
+        self.mark_prices.update_source(symbol, source, price, timestamp);
+    }
+
+    /// Mark price sources per market
+    pub fn mark_prices(&self) -> &MarkPriceEngine {
+        &self.mark_prices
+    }
+
+    /// Mark price for a market as of `now`, if it has sources.
+    pub fn mark_price(&self, symbol: &str, now: i64) -> Option<MarkPrice> {
+        self.mark_prices.mark(symbol, now)
+    }
+
+    /// Liquidation monitor state
+    pub fn liquidation_monitor(&self) -> &LiquidationMonitor {
+        &self.monitor
+    }
+
+    /// Liquidation monitor, fed mark price and fill events
+    pub fn liquidation_monitor_mut(&mut self) -> &mut LiquidationMonitor {
+        &mut self.monitor
+    }
+
+    /// Monitored accounts at `level` or worse, for the risk dashboard.
+    pub fn accounts_at_risk(&self, level: HealthLevel) -> Vec<(AccountId, Decimal)> {
+        self.monitor.accounts_at_risk(level)
+    }
+
+    /// Insurance fund state
+    pub fn insurance_fund(&self) -> &InsuranceFund {
+        &self.insurance_fund
+    }
+
+    /// Replace the insurance fund, as restored from a snapshot.
+    pub(crate) fn restore_insurance_fund(&mut self, insurance_fund: InsuranceFund) {
+        self.insurance_fund = insurance_fund;
+    }
+
+    /// Insurance fund balance in a settlement asset, for publication.
+    pub fn insurance_fund_balance(&self, asset: &str) -> Decimal {
+        self.insurance_fund.balance(asset)
+    }
+
+    /// Add capital to the insurance fund.
+    pub fn fund_insurance(&mut self, asset: &str, amount: Decimal, timestamp: i64) -> InsuranceFundEvent {
+        self.insurance_fund.deposit(asset, amount, timestamp)
+    }
+
+    /// Hand a liquidation close to the insurance fund per spec §6.6.
+    pub fn settle_liquidation(&mut self, close: &LiquidationClose, timestamp: i64) -> Vec<InsuranceFundEvent> {
+        self.insurance_fund.settle_liquidation(close, timestamp)
     }
 
     /// Pre-trade risk check per spec §9.3.6
     ///
     /// Validates an incoming order and returns Pass or rejection reason.
+    /// The market's status and increments are consulted before any margin
+    /// math runs.
     /// If rejected, also returns a RiskCheckFailed event.
     pub fn check_pre_trade(
         &self,
@@ -69,7 +207,15 @@ impl RiskEngine {
         positions: &[Position],
         timestamp: i64,
     ) -> (RiskCheckResult, Vec<RiskEvent>) {
-        let result = validator::validate_order(account, order, positions);
+        let symbol = order.symbol.as_str();
+        let status = self.market_status(symbol);
+        let result = validator::validate_order_in_market(
+            account,
+            order,
+            positions,
+            status,
+            self.market_config(symbol),
+        );
 
         let mut risk_events = Vec::new();
         if result != RiskCheckResult::Pass {
@@ -83,9 +229,39 @@ impl RiskEngine {
         (result, risk_events)
     }
 
+    /// Size and notional limit check against the account's positions and
+    /// working orders.
+    ///
+    /// If rejected, also returns a RiskRejected event.
+    pub fn check_limits(
+        &self,
+        account: &Account,
+        order: &Order,
+        current_exposure: &CurrentExposure,
+        timestamp: i64,
+    ) -> (Result<(), RiskRejection>, Vec<RiskEvent>) {
+        let result = self.config.limits.validate_order(account, order, current_exposure);
+        let risk_events = match &result {
+            Ok(()) => Vec::new(),
+            Err(rejection) => vec![events::risk_rejected_event(
+                account.account_id,
+                rejection.clone(),
+                timestamp,
+            )],
+        };
+        (result, risk_events)
+    }
+
     /// Evaluate account health and generate risk events.
     ///
     /// Called periodically or on mark price updates per spec §6.2.2.
+    /// Each position's unrealized PnL is revalued at the engine's aggregated
+    /// mark, not the mark the caller holds, and equity is net of funding
+    /// accrued since the last funding time.
+    ///
+    /// An account with a position in a market without a fresh mark (stale,
+    /// or never sourced) cannot be evaluated: no health event and no
+    /// liquidation, only a `MarkPriceStale` event per such market.
     pub fn evaluate_account(
         &self,
         account: &Account,
@@ -102,12 +278,45 @@ impl RiskEngine {
             .map(|b| b.total)
             .sum();
 
-        let total_upnl = exposure::total_unrealized_pnl(positions);
-        let eq = exposure::equity(total_balance, total_upnl);
+        // Markets without a fresh mark keep the caller's PnL, which only
+        // feeds the figures reported on their MarkPriceStale events
+        let mut stale: BTreeMap<String, i64> = BTreeMap::new();
+        let mut total_upnl = Decimal::ZERO;
+        for p in positions {
+            match self.mark_prices.mark(p.symbol.as_str(), timestamp) {
+                Some(mark) if !mark.stale => {
+                    total_upnl += exposure::unrealized_pnl(p.side, p.entry_price, mark.price, p.size);
+                }
+                mark => {
+                    stale.insert(p.symbol.as_str().to_owned(), mark.map_or(0, |m| m.last_update));
+                    total_upnl += p.unrealized_pnl;
+                }
+            }
+        }
+        let eq = margin::funding_adjusted_equity(
+            exposure::equity(total_balance, total_upnl),
+            self.funding.accrued_total(positions),
+        );
         let total_mm = exposure::total_maintenance_margin(positions);
         let ratio = margin::margin_ratio(eq, total_mm);
-        let health = liquidation::health_status(ratio);
 
+        if !stale.is_empty() {
+            return stale
+                .into_iter()
+                .map(|(symbol, last_update)| {
+                    RiskEvent::new(
+                        account.account_id,
+                        RiskEventType::MarkPriceStale { symbol, last_update },
+                        ratio,
+                        eq,
+                        total_mm,
+                        timestamp,
+                    )
+                })
+                .collect();
+        }
+
+        let health = liquidation::health_status(ratio);
         events::events_for_health(
             account.account_id,
             health,
@@ -153,7 +362,10 @@ impl RiskEngine {
             .sum();
 
         let total_upnl = exposure::total_unrealized_pnl(positions);
-        let eq = exposure::equity(total_balance, total_upnl);
+        let eq = margin::funding_adjusted_equity(
+            exposure::equity(total_balance, total_upnl),
+            self.funding.accrued_total(positions),
+        );
         let total_mm = exposure::total_maintenance_margin(positions);
 
         margin::margin_ratio(eq, total_mm)
@@ -194,6 +406,13 @@ mod tests {
         )
     }
 
+    /// Set every BTC/USDT mark source to `price` at `timestamp`
+    fn set_mark(engine: &mut RiskEngine, price: u64, timestamp: i64) {
+        for source in [PriceSource::Mid, PriceSource::LastTrade, PriceSource::Index] {
+            engine.update_mark_source("BTC/USDT", source, Price::from_u64(price), timestamp);
+        }
+    }
+
     fn make_position(
         account_id: AccountId,
         side: PositionSide,
@@ -246,11 +465,48 @@ mod tests {
         assert_eq!(events.len(), 1);
     }
 
+    #[test]
+    fn test_pre_trade_rejected_when_market_halted() {
+        let mut engine = RiskEngine::new();
+        engine.set_market_status("BTC/USDT", MarketStatus::HaltedManual);
+        let account = make_account(100_000);
+        let order = make_order(account.account_id, 50_000, "0.1");
+
+        let (result, events) = engine.check_pre_trade(
+            &account, &order, &[], 1708123456789000000,
+        );
+        assert_eq!(
+            result,
+            RiskCheckResult::MarketUnavailable { status: MarketStatus::HaltedManual }
+        );
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_pre_trade_rejected_off_tick() {
+        let mut engine = RiskEngine::new();
+        engine.set_market_config("BTC/USDT", MarketConfig {
+            tick_size: Decimal::from(10),
+            lot_size: Decimal::from_str_exact("0.001").unwrap(),
+            min_notional: Decimal::ZERO,
+        });
+        let account = make_account(100_000);
+        let order = make_order(account.account_id, 50_005, "0.1");
+
+        let (result, events) = engine.check_pre_trade(
+            &account, &order, &[], 1708123456789000000,
+        );
+        assert!(matches!(result, RiskCheckResult::MarketConfigViolated { .. }));
+        assert_eq!(events.len(), 1);
+        assert!(engine.market_config("ETH/USDT").is_none());
+    }
+
     // ── Account evaluation tests ──
 
     #[test]
     fn test_evaluate_healthy() {
-        let engine = RiskEngine::new();
+        let mut engine = RiskEngine::new();
+        set_mark(&mut engine, 51_000, 1708123456789000000);
         let account = make_account(100_000);
         let pos = make_position(
             account.account_id,
@@ -271,7 +527,8 @@ mod tests {
 
     #[test]
     fn test_evaluate_liquidation_triggered() {
-        let engine = RiskEngine::new();
+        let mut engine = RiskEngine::new();
+        set_mark(&mut engine, 45_500, 1708123456789000000);
         let account = make_account(5_000);
         // Large loss pushes into liquidation
         let pos = make_position(
@@ -295,6 +552,67 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_stale_mark_holds_back_liquidation() {
+        let mut engine = RiskEngine::new();
+        let account = make_account(5_000);
+        let pos = make_position(account.account_id, PositionSide::LONG, "1.0", 50_000, 45_500, 5_000, 5_000);
+        let t0 = 1708123456789000000;
+        set_mark(&mut engine, 45_500, t0);
+
+        let events = engine.evaluate_account(&account, std::slice::from_ref(&pos), t0 + 1);
+        assert_eq!(events[0].event_type, events::RiskEventType::LiquidationTriggered);
+
+        let later = t0 + engine.config().mark_price.max_staleness + 1;
+        let events = engine.evaluate_account(&account, std::slice::from_ref(&pos), later);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].event_type,
+            events::RiskEventType::MarkPriceStale { symbol: "BTC/USDT".to_string(), last_update: t0 }
+        );
+        assert!(engine.mark_price("BTC/USDT", later).unwrap().stale);
+    }
+
+    #[test]
+    fn test_outlier_feed_does_not_liquidate() {
+        let mut engine = RiskEngine::new();
+        let account = make_account(6_000);
+        let t0 = 1708123456789000000;
+        // The caller's position was marked off a single bad print
+        let pos = make_position(account.account_id, PositionSide::LONG, "1.0", 50_000, 45_500, 5_000, 2_500);
+        engine.update_mark_source("BTC/USDT", PriceSource::Mid, Price::from_u64(45_500), t0);
+        engine.update_mark_source("BTC/USDT", PriceSource::LastTrade, Price::from_u64(50_010), t0);
+        engine.update_mark_source("BTC/USDT", PriceSource::Index, Price::from_u64(50_000), t0);
+
+        // At the outlier: equity 6000 − 4500 = 1500, ratio 0.6 → Liquidation
+        assert_eq!(
+            liquidation::health_status(engine.get_margin_ratio(&account, std::slice::from_ref(&pos))),
+            HealthLevel::Liquidation
+        );
+        // At the median of 50 000: equity 6000, ratio 2.4 → Healthy
+        let events = engine.evaluate_account(&account, &[pos], t0);
+        assert!(events.is_empty(), "events: {events:?}");
+    }
+
+    #[test]
+    fn test_unsourced_market_is_not_evaluated() {
+        let mut engine = RiskEngine::new();
+        let account = make_account(5_000);
+        let t0 = 1708123456789000000;
+        let btc = make_position(account.account_id, PositionSide::LONG, "1.0", 50_000, 45_500, 5_000, 5_000);
+        let mut eth = btc.clone();
+        eth.symbol = MarketId::new("ETH/USDT");
+        set_mark(&mut engine, 45_500, t0);
+
+        // BTC alone would liquidate, but ETH has no mark to value it at
+        let events = engine.evaluate_account(&account, &[btc, eth], t0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].event_type,
+            events::RiskEventType::MarkPriceStale { symbol: "ETH/USDT".to_string(), last_update: 0 }
+        );
+    }
+
     #[test]
     fn test_evaluate_no_positions() {
         let engine = RiskEngine::new();
@@ -309,7 +627,8 @@ mod tests {
 
     #[test]
     fn test_post_trade_emits_warning() {
-        let engine = RiskEngine::new();
+        let mut engine = RiskEngine::new();
+        set_mark(&mut engine, 50_000, 1708123456789000000);
         let account = make_account(3_500);
         let pos = make_position(
             account.account_id,
@@ -334,6 +653,68 @@ mod tests {
 
     // ── Margin ratio tests ──
 
+    #[test]
+    fn test_accrued_funding_reduces_margin_ratio() {
+        let mut engine = RiskEngine::new();
+        let account = make_account(1_000);
+        // Opened before the first funding interval of the test clock
+        let pos = make_position(account.account_id, PositionSide::LONG, "1.0", 50_000, 50_000, 5_000, 500);
+        assert_eq!(engine.get_margin_ratio(&account, std::slice::from_ref(&pos)), Decimal::from(2));
+
+        // Mark 1% over index: rate 1% − 0.05% capped at 0.75%; half an
+        // interval accrues
+        let half = engine.config().funding.interval / 2;
+        let start = engine.funding().interval_start(1708123456789000000) + engine.config().funding.interval;
+        engine
+            .funding_mut()
+            .update_rate("BTC/USDT", Price::from_u64(50_500), Price::from_u64(50_000), start + half);
+        // Owed: 50 500 × 0.0075 / 2 = 189.375; equity 810.625 / 500
+        assert_eq!(
+            engine.get_margin_ratio(&account, std::slice::from_ref(&pos)),
+            Decimal::from_str_exact("1.62125").unwrap()
+        );
+        set_mark(&mut engine, 50_000, start + half);
+        let events = engine.evaluate_account(&account, &[pos], start + half);
+        assert_eq!(events[0].equity, Decimal::from_str_exact("810.625").unwrap());
+    }
+
+    #[test]
+    fn test_check_limits_emits_rejection() {
+        let mut config = RiskEngineConfig::default();
+        config.limits.default.max_order_quantity = Some(Decimal::ONE);
+        let engine = RiskEngine::with_config(config);
+        let account = make_account(100_000);
+        let exposure = CurrentExposure::new();
+
+        let (result, evts) = engine.check_limits(&account, &make_order(account.account_id, 50_000, "1.0"), &exposure, 1);
+        assert_eq!(result, Ok(()));
+        assert!(evts.is_empty());
+
+        let (result, evts) = engine.check_limits(&account, &make_order(account.account_id, 50_000, "1.5"), &exposure, 1);
+        let rejection = result.unwrap_err();
+        assert_eq!(evts.len(), 1);
+        assert_eq!(evts[0].event_type, events::RiskEventType::RiskRejected { rejection });
+    }
+
+    #[test]
+    fn test_insurance_fund_balance_exposed() {
+        let mut engine = RiskEngine::new();
+        engine.fund_insurance("USDT", Decimal::from(1_000), 1);
+        let close = LiquidationClose {
+            account_id: AccountId::new(),
+            symbol: "BTC/USDT".to_string(),
+            asset: "USDT".to_string(),
+            side: PositionSide::LONG,
+            size: Quantity::from_str("1.0").unwrap(),
+            bankruptcy_price: Price::from_u64(45_000),
+            close_price: Price::from_u64(45_100),
+            fee: Decimal::from(250),
+        };
+        engine.settle_liquidation(&close, 2);
+        assert_eq!(engine.insurance_fund_balance("USDT"), Decimal::from(1_125));
+        assert_eq!(engine.insurance_fund_balance("USDC"), Decimal::ZERO);
+    }
+
     #[test]
     fn test_get_margin_ratio_no_positions() {
         let engine = RiskEngine::new();
@@ -388,7 +769,7 @@ mod tests {
         // Second order still passes with reduced available
         let order2 = make_order(account.account_id, 50_000, "0.5");
         let (r2, _) = engine.check_pre_trade(
-            &account, &order2, &[pos.clone()], 1708123456789000000,
+            &account, &order2, std::slice::from_ref(&pos), 1708123456789000000,
         );
         assert_eq!(r2, RiskCheckResult::Pass);
     }
@@ -397,7 +778,7 @@ mod tests {
 
     #[test]
     fn test_extreme_volatility_50pct_drop() {
-        let engine = RiskEngine::new();
+        let mut engine = RiskEngine::new();
         let account = make_account(10_000);
 
         // Position at $50,000
@@ -416,6 +797,7 @@ mod tests {
             Price::from_u64(25_000),
             1708123456790000000,
         );
+        set_mark(&mut engine, 25_000, 1708123456790000000);
 
         let events = engine.evaluate_account(
             &account, &[pos.clone()], 1708123456790000000,
@@ -432,7 +814,7 @@ mod tests {
 
     #[test]
     fn test_extreme_volatility_gradual_decline() {
-        let engine = RiskEngine::new();
+        let mut engine = RiskEngine::new();
         let account = make_account(6_000);
 
         let mut pos = make_position(
@@ -447,6 +829,7 @@ mod tests {
 
         // 1% drop: 49500
         pos.update_mark_price(Price::from_u64(49_500), 1);
+        set_mark(&mut engine, 49_500, 1);
         let e1 = engine.evaluate_account(&account, &[pos.clone()], 1);
         // Equity = 6000 - 500 = 5500, MM = 3000, ratio ≈ 1.83 → Warning
         assert_eq!(e1.len(), 1);
@@ -454,6 +837,7 @@ mod tests {
 
         // 5% drop: 47500
         pos.update_mark_price(Price::from_u64(47_500), 2);
+        set_mark(&mut engine, 47_500, 2);
         let e2 = engine.evaluate_account(&account, &[pos.clone()], 2);
         // Equity = 6000 - 2500 = 3500, MM = 3000, ratio ≈ 1.17 → Danger
         assert_eq!(e2.len(), 1);
@@ -461,6 +845,7 @@ mod tests {
 
         // 8% drop: 46000
         pos.update_mark_price(Price::from_u64(46_000), 3);
+        set_mark(&mut engine, 46_000, 3);
         let e3 = engine.evaluate_account(&account, &[pos.clone()], 3);
         // Equity = 6000 - 4000 = 2000, MM = 3000, ratio ≈ 0.67 → Liquidation
         assert_eq!(e3.len(), 1);
@@ -6,9 +6,13 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use types::ids::AccountId;
+use types::numeric::{Price, Quantity};
+use types::position::PositionSide;
 use uuid::Uuid;
 
+use crate::exposure::{EquityConcentrationLimit, LimitPolicy};
 use crate::liquidation::HealthLevel;
+use crate::validator::RiskRejection;
 
 /// Risk event emitted by the risk engine
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -20,6 +24,9 @@ pub struct RiskEvent {
     pub equity: Decimal,
     pub maintenance_margin: Decimal,
     pub timestamp: i64,
+    /// Id of the gateway request whose input raised the event
+    #[serde(default)]
+    pub correlation_id: Option<String>,
 }
 
 /// Risk event type classification
@@ -31,8 +38,24 @@ pub enum RiskEventType {
     MarginCall,
     /// Margin ratio dropped below 1.1 — initiate liquidation
     LiquidationTriggered,
+    /// The liquidation monitor started liquidating the account
+    LiquidationStarted,
+    /// The margin ratio recovered above the clear threshold mid-liquidation
+    LiquidationCancelled,
     /// Pre-trade risk check rejected an order
     RiskCheckFailed { reason: String },
+    /// Order refused by a size or notional limit
+    RiskRejected { rejection: RiskRejection },
+    /// An asset's gross notional went over its equity concentration limit
+    ExposureLimitBreached {
+        asset: String,
+        fraction_of_equity: Decimal,
+        limit: Decimal,
+        policy: LimitPolicy,
+    },
+    /// Account not evaluated: no mark price source for `symbol` updated
+    /// since `last_update` (0 when the market has never had a source)
+    MarkPriceStale { symbol: String, last_update: i64 },
 }
 
 impl RiskEvent {
@@ -53,8 +76,72 @@ impl RiskEvent {
             equity,
             maintenance_margin,
             timestamp,
+            correlation_id: None,
         }
     }
+
+    /// Tag the event with the request that caused it
+    pub fn with_correlation_id(mut self, correlation_id: Option<String>) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+}
+
+/// Funding event emitted by the funding engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FundingEvent {
+    /// A market's funding rate was recomputed
+    FundingRateUpdated {
+        symbol: String,
+        funding_rate: Decimal,
+        mark_price: Price,
+        index_price: Price,
+        next_funding_time: i64,
+        timestamp: i64,
+    },
+    /// A position was charged or credited funding at a funding time
+    FundingPaid {
+        account_id: AccountId,
+        symbol: String,
+        side: PositionSide,
+        size: Quantity,
+        funding_rate: Decimal,
+        /// Positive when the account pays, negative when it receives
+        amount: Decimal,
+        funding_time: i64,
+    },
+}
+
+/// Insurance fund movement emitted on liquidation settlement (spec §6.6)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InsuranceFundEvent {
+    /// Fee income or capital added to the fund
+    InsuranceFundCredited {
+        asset: String,
+        amount: Decimal,
+        balance_after: Decimal,
+        /// Liquidated account, if the credit came from a liquidation
+        account_id: Option<AccountId>,
+        symbol: Option<String>,
+        timestamp: i64,
+    },
+    /// A liquidation deficit absorbed by the fund
+    InsuranceFundDebited {
+        asset: String,
+        amount: Decimal,
+        balance_after: Decimal,
+        account_id: AccountId,
+        symbol: String,
+        timestamp: i64,
+    },
+    /// The fund reached zero; `shortfall` is left for auto-deleveraging
+    InsuranceFundDepleted {
+        asset: String,
+        shortfall: Decimal,
+        account_id: AccountId,
+        symbol: String,
+        timestamp: i64,
+    },
 }
 
 /// Generate risk events based on health level transition.
@@ -125,6 +212,46 @@ pub fn risk_check_failed_event(
     )
 }
 
+/// Create a limit rejection event.
+pub fn risk_rejected_event(
+    account_id: AccountId,
+    rejection: RiskRejection,
+    timestamp: i64,
+) -> RiskEvent {
+    RiskEvent::new(
+        account_id,
+        RiskEventType::RiskRejected { rejection },
+        Decimal::ZERO,
+        Decimal::ZERO,
+        Decimal::ZERO,
+        timestamp,
+    )
+}
+
+/// Create a concentration limit breach event.
+pub fn exposure_limit_breached_event(
+    account_id: AccountId,
+    asset: String,
+    fraction_of_equity: Decimal,
+    limit: EquityConcentrationLimit,
+    equity: Decimal,
+    timestamp: i64,
+) -> RiskEvent {
+    RiskEvent::new(
+        account_id,
+        RiskEventType::ExposureLimitBreached {
+            asset,
+            fraction_of_equity,
+            limit: limit.max_fraction,
+            policy: limit.policy,
+        },
+        Decimal::ZERO,
+        equity,
+        Decimal::ZERO,
+        timestamp,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
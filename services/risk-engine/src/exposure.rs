@@ -2,11 +2,25 @@
 //!
 //! Deterministic computation of position value, equity,
 //! unrealized PnL, and total exposure per specs §4.4.3 and §5.3.
+//!
+//! Also rolls exposure up by underlying asset, so that e.g. BTC/USDT,
+//! BTC/USD and BTC-margined products all count towards one BTC total, and
+//! holds each asset's gross notional to a fraction of account equity.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::account::Account;
+use types::ids::{AccountId, MarketId, OrderId};
 use types::numeric::{Price, Quantity};
+use types::order::{Order, Side};
 use types::position::{Position, PositionSide};
 
+use crate::cross_margin::MarkPriceSource;
+use crate::events::{self, RiskEvent};
+use crate::validator::RiskRejection;
+
 /// Calculate notional position value per spec §5.3.2
 ///
 /// `position_value = size × mark_price`
@@ -69,6 +83,516 @@ pub fn total_maintenance_margin(positions: &[Position]) -> Decimal {
     })
 }
 
+// ── Underlying-asset exposure ───────────────────────────────────────────
+
+/// How a market's positions map onto an underlying asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnderlyingOverride {
+    /// Underlying asset the market tracks (e.g. "BTC").
+    pub asset: String,
+    /// Inverse (coin-margined) contract: size is quoted in the quote
+    /// currency, so underlying units are `size / mark_price`.
+    pub inverse: bool,
+}
+
+/// Market → underlying asset mapping.
+///
+/// Defaults to `MarketId::base()` with linear sizing; the override table
+/// covers products whose base symbol is not the underlying ("BTC-PERP")
+/// or whose size is denominated in the quote currency.
+#[derive(Debug, Clone, Default)]
+pub struct UnderlyingMap {
+    overrides: BTreeMap<String, UnderlyingOverride>,
+}
+
+impl UnderlyingMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the underlying for a market symbol.
+    pub fn set_override(&mut self, symbol: impl Into<String>, entry: UnderlyingOverride) {
+        self.overrides.insert(symbol.into(), entry);
+    }
+
+    /// Underlying asset and inverse flag for a position's market.
+    pub fn resolve<'a>(&'a self, position: &'a Position) -> (&'a str, bool) {
+        match self.overrides.get(position.symbol.as_str()) {
+            Some(entry) => (entry.asset.as_str(), entry.inverse),
+            None => (position.symbol.base(), false),
+        }
+    }
+
+    /// Underlying asset for a market.
+    pub fn resolve_market<'a>(&'a self, symbol: &'a MarketId) -> &'a str {
+        match self.overrides.get(symbol.as_str()) {
+            Some(entry) => entry.asset.as_str(),
+            None => symbol.base(),
+        }
+    }
+}
+
+/// Exposure to a single underlying asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnderlyingExposure {
+    pub asset: String,
+    /// Long minus short, in underlying units.
+    pub net_units: Decimal,
+    /// Long plus short, in underlying units.
+    pub gross_units: Decimal,
+    /// Signed notional in the quote currency at current marks.
+    pub net_notional: Decimal,
+    /// Absolute notional in the quote currency at current marks.
+    pub gross_notional: Decimal,
+    /// Contributing markets, sorted.
+    pub markets: Vec<String>,
+}
+
+/// Non-fatal issue encountered while rolling up exposure.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExposureWarning {
+    /// No mark price for the market; the position was excluded.
+    MissingMark { account_id: AccountId, market: String },
+}
+
+/// Exposure rolled up by underlying asset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExposureRollup {
+    /// One entry per underlying, sorted by asset.
+    pub by_asset: Vec<UnderlyingExposure>,
+    /// Positions excluded from the rollup.
+    pub warnings: Vec<ExposureWarning>,
+}
+
+impl ExposureRollup {
+    /// Exposure for a single underlying, if any.
+    pub fn asset(&self, asset: &str) -> Option<&UnderlyingExposure> {
+        self.by_asset.iter().find(|e| e.asset == asset)
+    }
+}
+
+/// Roll up exposure for one account's positions.
+///
+/// Positions belonging to other accounts are ignored.
+pub fn account_exposure(
+    account_id: AccountId,
+    positions: &[Position],
+    underlyings: &UnderlyingMap,
+    marks: &impl MarkPriceSource,
+) -> ExposureRollup {
+    rollup(
+        positions.iter().filter(|p| p.account_id == account_id),
+        underlyings,
+        marks,
+    )
+}
+
+/// Roll up exposure across every account on the exchange.
+pub fn exchange_exposure(
+    positions: &[Position],
+    underlyings: &UnderlyingMap,
+    marks: &impl MarkPriceSource,
+) -> ExposureRollup {
+    rollup(positions.iter(), underlyings, marks)
+}
+
+fn rollup<'a>(
+    positions: impl Iterator<Item = &'a Position>,
+    underlyings: &UnderlyingMap,
+    marks: &impl MarkPriceSource,
+) -> ExposureRollup {
+    let mut by_asset: BTreeMap<String, UnderlyingExposure> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    for pos in positions {
+        let Some(mark) = marks.mark_price(&pos.symbol) else {
+            warnings.push(ExposureWarning::MissingMark {
+                account_id: pos.account_id,
+                market: pos.symbol.as_str().to_string(),
+            });
+            continue;
+        };
+
+        let (asset, inverse) = underlyings.resolve(pos);
+        let size = pos.size.as_decimal();
+        let (units, notional) = if inverse {
+            (size / mark.as_decimal(), size)
+        } else {
+            (size, position_value(pos.size, mark))
+        };
+        let sign = match pos.side {
+            PositionSide::LONG => Decimal::ONE,
+            PositionSide::SHORT => Decimal::NEGATIVE_ONE,
+        };
+
+        let entry = by_asset
+            .entry(asset.to_string())
+            .or_insert_with(|| UnderlyingExposure {
+                asset: asset.to_string(),
+                net_units: Decimal::ZERO,
+                gross_units: Decimal::ZERO,
+                net_notional: Decimal::ZERO,
+                gross_notional: Decimal::ZERO,
+                markets: Vec::new(),
+            });
+        entry.net_units += sign * units;
+        entry.gross_units += units;
+        entry.net_notional += sign * notional;
+        entry.gross_notional += notional;
+        let market = pos.symbol.as_str();
+        if let Err(idx) = entry.markets.binary_search_by(|m| m.as_str().cmp(market)) {
+            entry.markets.insert(idx, market.to_string());
+        }
+    }
+
+    ExposureRollup {
+        by_asset: by_asset.into_values().collect(),
+        warnings,
+    }
+}
+
+/// Per-underlying concentration limits, in underlying units of gross exposure.
+#[derive(Debug, Clone, Default)]
+pub struct ConcentrationLimits {
+    limits: BTreeMap<String, Decimal>,
+}
+
+impl ConcentrationLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap gross exposure to `asset` at `max_gross_units`.
+    pub fn set_limit(&mut self, asset: impl Into<String>, max_gross_units: Decimal) {
+        self.limits.insert(asset.into(), max_gross_units);
+    }
+
+    /// Underlyings whose gross exposure exceeds their limit, sorted by asset.
+    pub fn breaches(&self, rollup: &ExposureRollup) -> Vec<ConcentrationBreach> {
+        rollup
+            .by_asset
+            .iter()
+            .filter_map(|exposure| {
+                let limit = *self.limits.get(&exposure.asset)?;
+                (exposure.gross_units > limit).then(|| ConcentrationBreach {
+                    asset: exposure.asset.clone(),
+                    limit,
+                    gross_units: exposure.gross_units,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An underlying whose exposure exceeds its concentration limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcentrationBreach {
+    pub asset: String,
+    pub limit: Decimal,
+    pub gross_units: Decimal,
+}
+
+// ── Equity concentration limits ─────────────────────────────────────────
+
+/// What a breached concentration limit does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitPolicy {
+    /// Emit `ExposureLimitBreached` and keep accepting orders
+    Alert,
+    /// Also reject orders that would grow the asset's net exposure
+    Reject,
+}
+
+/// Cap on an asset's gross notional as a fraction of account equity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquityConcentrationLimit {
+    /// e.g. 3 = gross notional up to 3× equity
+    pub max_fraction: Decimal,
+    pub policy: LimitPolicy,
+}
+
+/// One underlying's exposure relative to account equity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetExposure {
+    pub asset: String,
+    /// Signed notional in the quote currency at current marks.
+    pub net_notional: Decimal,
+    /// Absolute notional in the quote currency at current marks.
+    pub gross_notional: Decimal,
+    /// `gross_notional / equity`; `Decimal::MAX` at zero or negative equity.
+    pub fraction_of_equity: Decimal,
+    /// Contributing markets, sorted.
+    pub markets: Vec<String>,
+}
+
+/// An account's exposure per underlying after the latest recompute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExposureSnapshot {
+    pub account_id: AccountId,
+    pub equity: Decimal,
+    /// One entry per underlying, sorted by asset.
+    pub by_asset: Vec<AssetExposure>,
+    /// Assets over their concentration limit, sorted.
+    pub breached: Vec<String>,
+    /// Positions excluded from the snapshot.
+    pub warnings: Vec<ExposureWarning>,
+    pub computed_at: i64,
+}
+
+impl ExposureSnapshot {
+    /// Exposure for a single underlying, if any.
+    pub fn asset(&self, asset: &str) -> Option<&AssetExposure> {
+        self.by_asset.iter().find(|e| e.asset == asset)
+    }
+}
+
+/// Per-account exposure snapshots checked against equity concentration limits.
+#[derive(Debug, Clone)]
+pub struct ExposureTracker<M> {
+    underlyings: UnderlyingMap,
+    marks: M,
+    limits: BTreeMap<String, EquityConcentrationLimit>,
+    snapshots: HashMap<AccountId, ExposureSnapshot>,
+}
+
+impl<M: MarkPriceSource> ExposureTracker<M> {
+    pub fn new(underlyings: UnderlyingMap, marks: M) -> Self {
+        Self {
+            underlyings,
+            marks,
+            limits: BTreeMap::new(),
+            snapshots: HashMap::new(),
+        }
+    }
+
+    pub fn marks_mut(&mut self) -> &mut M {
+        &mut self.marks
+    }
+
+    /// Set the concentration limit for an underlying asset.
+    pub fn set_limit(&mut self, asset: impl Into<String>, limit: EquityConcentrationLimit) {
+        self.limits.insert(asset.into(), limit);
+    }
+
+    /// Latest snapshot for an account, for the gateway to serve.
+    pub fn snapshot(&self, account_id: &AccountId) -> Option<&ExposureSnapshot> {
+        self.snapshots.get(account_id)
+    }
+
+    /// Rebuild an account's snapshot after a fill or mark-price update.
+    ///
+    /// Equity is the balance total plus unrealized PnL at current marks.
+    /// Returns an `ExposureLimitBreached` event for each asset that went
+    /// over its limit since the previous recompute; assets that stay over
+    /// do not repeat the event.
+    pub fn recompute(&mut self, account: &Account, positions: &[Position], timestamp: i64) -> Vec<RiskEvent> {
+        let rollup = account_exposure(account.account_id, positions, &self.underlyings, &self.marks);
+
+        let total_balance: Decimal = account.balances.values().map(|b| b.total).sum();
+        let upnl: Decimal = positions
+            .iter()
+            .filter(|p| p.account_id == account.account_id)
+            .filter_map(|p| {
+                let mark = self.marks.mark_price(&p.symbol)?;
+                Some(unrealized_pnl(p.side, p.entry_price, mark, p.size))
+            })
+            .sum();
+        let eq = equity(total_balance, upnl);
+
+        let by_asset: Vec<AssetExposure> = rollup
+            .by_asset
+            .into_iter()
+            .map(|exposure| AssetExposure {
+                fraction_of_equity: if eq > Decimal::ZERO {
+                    exposure.gross_notional / eq
+                } else {
+                    Decimal::MAX
+                },
+                asset: exposure.asset,
+                net_notional: exposure.net_notional,
+                gross_notional: exposure.gross_notional,
+                markets: exposure.markets,
+            })
+            .collect();
+
+        let previously: BTreeSet<String> = self
+            .snapshots
+            .get(&account.account_id)
+            .map(|s| s.breached.iter().cloned().collect())
+            .unwrap_or_default();
+        let mut breached = Vec::new();
+        let mut risk_events = Vec::new();
+        for exposure in &by_asset {
+            let Some(limit) = self.limits.get(&exposure.asset) else {
+                continue;
+            };
+            if exposure.fraction_of_equity <= limit.max_fraction {
+                continue;
+            }
+            breached.push(exposure.asset.clone());
+            if !previously.contains(&exposure.asset) {
+                risk_events.push(events::exposure_limit_breached_event(
+                    account.account_id,
+                    exposure.asset.clone(),
+                    exposure.fraction_of_equity,
+                    *limit,
+                    eq,
+                    timestamp,
+                ));
+            }
+        }
+
+        self.snapshots.insert(
+            account.account_id,
+            ExposureSnapshot {
+                account_id: account.account_id,
+                equity: eq,
+                by_asset,
+                breached,
+                warnings: rollup.warnings,
+                computed_at: timestamp,
+            },
+        );
+        risk_events
+    }
+
+    /// Refuse an order that grows net exposure to an asset whose `Reject`
+    /// limit is breached in the latest snapshot.
+    ///
+    /// Orders that reduce the asset's net exposure always pass, so a
+    /// breached account can trade back under its limit.
+    pub fn check_order(&self, order: &Order) -> Result<(), RiskRejection> {
+        let Some(snapshot) = self.snapshots.get(&order.account_id) else {
+            return Ok(());
+        };
+        let asset = self.underlyings.resolve_market(&order.symbol);
+        let Some(limit) = self.limits.get(asset) else {
+            return Ok(());
+        };
+        if limit.policy != LimitPolicy::Reject || !snapshot.breached.iter().any(|a| a == asset) {
+            return Ok(());
+        }
+        let net = snapshot.asset(asset).map_or(Decimal::ZERO, |e| e.net_notional);
+        let reduces = match order.side {
+            Side::BUY => net < Decimal::ZERO,
+            Side::SELL => net > Decimal::ZERO,
+        };
+        if reduces {
+            return Ok(());
+        }
+        Err(RiskRejection::ConcentrationLimitExceeded {
+            asset: asset.to_owned(),
+            limit: limit.max_fraction,
+            fraction_of_equity: snapshot.asset(asset).map_or(Decimal::ZERO, |e| e.fraction_of_equity),
+        })
+    }
+}
+
+// ── Working-order exposure ──────────────────────────────────────────────
+
+/// A resting order's remaining quantity at its limit price.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkingOrder {
+    pub symbol: String,
+    pub side: Side,
+    pub price: Price,
+    pub remaining: Quantity,
+}
+
+impl WorkingOrder {
+    /// `remaining × price`
+    pub fn notional(&self) -> Decimal {
+        self.remaining.as_decimal() * self.price.as_decimal()
+    }
+}
+
+/// One account's positions and working orders, as pre-trade limits see them.
+///
+/// Positions alone miss orders that are resting but not yet filled, so the
+/// validator also needs the account's open-order notional per side.
+#[derive(Debug, Clone, Default)]
+pub struct CurrentExposure {
+    positions: BTreeMap<String, Position>,
+    orders: BTreeMap<OrderId, WorkingOrder>,
+}
+
+impl CurrentExposure {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or replace the position in its symbol.
+    pub fn set_position(&mut self, position: Position) {
+        self.positions.insert(position.symbol.as_str().to_owned(), position);
+    }
+
+    /// Drop the position in a symbol.
+    pub fn remove_position(&mut self, symbol: &str) -> Option<Position> {
+        self.positions.remove(symbol)
+    }
+
+    /// Track an order that rested on the book.
+    pub fn add_order(&mut self, order: &Order) {
+        self.orders.insert(
+            order.order_id,
+            WorkingOrder {
+                symbol: order.symbol.as_str().to_owned(),
+                side: order.side,
+                price: order.price,
+                remaining: order.remaining_quantity,
+            },
+        );
+    }
+
+    /// Reduce a working order by a fill; a fully filled order is dropped.
+    pub fn reduce_order(&mut self, order_id: &OrderId, filled: Quantity) {
+        let Some(order) = self.orders.get_mut(order_id) else {
+            return;
+        };
+        match Quantity::try_new(order.remaining.as_decimal() - filled.as_decimal()) {
+            Some(remaining) => order.remaining = remaining,
+            None => {
+                self.orders.remove(order_id);
+            }
+        }
+    }
+
+    /// Stop tracking a canceled or filled order.
+    pub fn remove_order(&mut self, order_id: &OrderId) -> Option<WorkingOrder> {
+        self.orders.remove(order_id)
+    }
+
+    /// Working orders by id.
+    pub fn orders(&self) -> &BTreeMap<OrderId, WorkingOrder> {
+        &self.orders
+    }
+
+    /// Signed position size in a symbol: long positive, short negative.
+    pub fn net_position(&self, symbol: &str) -> Decimal {
+        self.positions.get(symbol).map_or(Decimal::ZERO, |pos| {
+            let size = pos.size.as_decimal();
+            match pos.side {
+                PositionSide::LONG => size,
+                PositionSide::SHORT => -size,
+            }
+        })
+    }
+
+    /// Notional of working orders on one side of a symbol.
+    pub fn open_order_notional(&self, symbol: &str, side: Side) -> Decimal {
+        self.orders
+            .values()
+            .filter(|order| order.symbol == symbol && order.side == side)
+            .map(WorkingOrder::notional)
+            .sum()
+    }
+
+    /// Notional of every working order across symbols.
+    pub fn total_open_order_notional(&self) -> Decimal {
+        self.orders.values().map(WorkingOrder::notional).sum()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,4 +740,251 @@ mod tests {
         );
         assert_eq!(r1, r2, "Determinism violated");
     }
+
+    fn position_in(
+        account_id: AccountId,
+        symbol: &str,
+        side: PositionSide,
+        size: &str,
+        entry: u64,
+    ) -> Position {
+        Position::new(
+            account_id,
+            MarketId::new(symbol),
+            side,
+            Quantity::from_str(size).unwrap(),
+            Price::from_u64(entry),
+            Price::from_u64(entry),
+            Price::from_u64(1),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            10,
+            1708123456789000000,
+        )
+    }
+
+    fn marks(pairs: &[(&str, u64)]) -> BTreeMap<String, Price> {
+        pairs
+            .iter()
+            .map(|(s, p)| (s.to_string(), Price::from_u64(*p)))
+            .collect()
+    }
+
+    #[test]
+    fn test_btc_exposure_across_markets() {
+        let account = AccountId::new();
+        let positions = vec![
+            position_in(account, "BTC/USDT", PositionSide::LONG, "2.0", 50_000),
+            position_in(account, "BTC/USD", PositionSide::SHORT, "0.5", 50_000),
+            position_in(account, "ETH/USDT", PositionSide::LONG, "10.0", 3_000),
+        ];
+        let marks = marks(&[("BTC/USDT", 50_000), ("BTC/USD", 50_100), ("ETH/USDT", 3_000)]);
+
+        let rollup = account_exposure(account, &positions, &UnderlyingMap::new(), &marks);
+        let btc = rollup.asset("BTC").unwrap();
+        assert_eq!(btc.net_units, Decimal::from_str_exact("1.5").unwrap());
+        assert_eq!(btc.gross_units, Decimal::from_str_exact("2.5").unwrap());
+        // 2 × 50 000 − 0.5 × 50 100
+        assert_eq!(btc.net_notional, Decimal::from(74_950));
+        assert_eq!(btc.gross_notional, Decimal::from(125_050));
+        assert_eq!(btc.markets, vec!["BTC/USD", "BTC/USDT"]);
+
+        let assets: Vec<_> = rollup.by_asset.iter().map(|e| e.asset.as_str()).collect();
+        assert_eq!(assets, vec!["BTC", "ETH"]);
+        assert!(rollup.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_inverse_override_converts_to_underlying_units() {
+        let account = AccountId::new();
+        let mut underlyings = UnderlyingMap::new();
+        underlyings.set_override(
+            "XBT-INV/USD",
+            UnderlyingOverride { asset: "BTC".to_string(), inverse: true },
+        );
+        let positions = vec![
+            position_in(account, "BTC/USDT", PositionSide::LONG, "1.0", 50_000),
+            // 25 000 USD of contracts short ≈ 0.5 BTC at 50 000
+            position_in(account, "XBT-INV/USD", PositionSide::SHORT, "25000", 50_000),
+        ];
+        let marks = marks(&[("BTC/USDT", 50_000), ("XBT-INV/USD", 50_000)]);
+
+        let rollup = exchange_exposure(&positions, &underlyings, &marks);
+        let btc = rollup.asset("BTC").unwrap();
+        assert_eq!(btc.net_units, Decimal::from_str_exact("0.5").unwrap());
+        assert_eq!(btc.gross_units, Decimal::from_str_exact("1.5").unwrap());
+        assert_eq!(btc.gross_notional, Decimal::from(75_000));
+        assert_eq!(rollup.by_asset.len(), 1);
+    }
+
+    #[test]
+    fn test_missing_mark_is_flagged_and_excluded() {
+        let account = AccountId::new();
+        let positions = vec![
+            position_in(account, "BTC/USDT", PositionSide::LONG, "1.0", 50_000),
+            position_in(account, "BTC/USD", PositionSide::LONG, "3.0", 50_000),
+        ];
+        let marks = marks(&[("BTC/USDT", 50_000)]);
+
+        let rollup = account_exposure(account, &positions, &UnderlyingMap::new(), &marks);
+        assert_eq!(rollup.asset("BTC").unwrap().gross_units, Decimal::ONE);
+        assert_eq!(
+            rollup.warnings,
+            vec![ExposureWarning::MissingMark {
+                account_id: account,
+                market: "BTC/USD".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_exchange_rollup_and_concentration_limits() {
+        let a = AccountId::new();
+        let b = AccountId::new();
+        let positions = vec![
+            position_in(a, "BTC/USDT", PositionSide::LONG, "2.0", 50_000),
+            position_in(b, "BTC/USD", PositionSide::SHORT, "2.0", 50_000),
+            position_in(b, "ETH/USDT", PositionSide::LONG, "5.0", 3_000),
+        ];
+        let marks = marks(&[("BTC/USDT", 50_000), ("BTC/USD", 50_000), ("ETH/USDT", 3_000)]);
+        let underlyings = UnderlyingMap::new();
+
+        let exchange = exchange_exposure(&positions, &underlyings, &marks);
+        assert_eq!(exchange.asset("BTC").unwrap().net_units, Decimal::ZERO);
+        assert_eq!(exchange.asset("BTC").unwrap().gross_units, Decimal::from(4));
+
+        let only_a = account_exposure(a, &positions, &underlyings, &marks);
+        assert!(only_a.asset("ETH").is_none());
+
+        let mut limits = ConcentrationLimits::new();
+        limits.set_limit("BTC", Decimal::from(3));
+        limits.set_limit("ETH", Decimal::from(10));
+        let breaches = limits.breaches(&exchange);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].asset, "BTC");
+        assert_eq!(breaches[0].gross_units, Decimal::from(4));
+    }
+
+    #[test]
+    fn test_current_exposure_tracks_working_orders() {
+        use types::order::TimeInForce;
+
+        let account_id = AccountId::new();
+        let order = |symbol: &str, side, price, qty: &str| {
+            Order::new(
+                account_id,
+                MarketId::new(symbol),
+                side,
+                Price::from_u64(price),
+                Quantity::from_str(qty).unwrap(),
+                TimeInForce::GTC,
+                1708123456789000000,
+            )
+        };
+        let mut exposure = CurrentExposure::new();
+        exposure.set_position(position_in(account_id, "BTC/USDT", PositionSide::SHORT, "1.5", 50_000));
+        let bid = order("BTC/USDT", Side::BUY, 49_000, "2.0");
+        exposure.add_order(&bid);
+        exposure.add_order(&order("BTC/USDT", Side::SELL, 51_000, "1.0"));
+        exposure.add_order(&order("ETH/USDT", Side::BUY, 3_000, "10.0"));
+
+        assert_eq!(exposure.net_position("BTC/USDT"), Decimal::from_str_exact("-1.5").unwrap());
+        assert_eq!(exposure.net_position("ETH/USDT"), Decimal::ZERO);
+        assert_eq!(exposure.open_order_notional("BTC/USDT", Side::BUY), Decimal::from(98_000));
+        assert_eq!(exposure.total_open_order_notional(), Decimal::from(179_000));
+
+        exposure.reduce_order(&bid.order_id, Quantity::from_str("0.5").unwrap());
+        assert_eq!(exposure.open_order_notional("BTC/USDT", Side::BUY), Decimal::from(73_500));
+        exposure.reduce_order(&bid.order_id, Quantity::from_str("1.5").unwrap());
+        assert!(!exposure.orders().contains_key(&bid.order_id));
+        assert_eq!(exposure.open_order_notional("BTC/USDT", Side::BUY), Decimal::ZERO);
+    }
+
+    fn btc_account() -> (Account, Vec<Position>) {
+        use types::account::{AccountType, Balance};
+
+        let mut account = Account::new(AccountType::FUTURES, 1708123456789000000);
+        account.set_balance(Balance::new("USDT", Decimal::from(50_000)), 1708123456789000000);
+        let positions = vec![
+            position_in(account.account_id, "BTC/USDT", PositionSide::LONG, "2.0", 50_000),
+            position_in(account.account_id, "BTC/USDC", PositionSide::SHORT, "0.5", 50_000),
+        ];
+        (account, positions)
+    }
+
+    #[test]
+    fn test_snapshot_nets_short_against_long_across_markets() {
+        let (account, positions) = btc_account();
+        let mut tracker = ExposureTracker::new(UnderlyingMap::new(), marks(&[("BTC/USDT", 50_000), ("BTC/USDC", 50_000)]));
+        tracker.set_limit("BTC", EquityConcentrationLimit { max_fraction: Decimal::from(2), policy: LimitPolicy::Alert });
+
+        let events = tracker.recompute(&account, &positions, 1708123456789000000);
+        let snapshot = tracker.snapshot(&account.account_id).unwrap();
+        let btc = snapshot.asset("BTC").unwrap();
+        assert_eq!(snapshot.equity, Decimal::from(50_000));
+        assert_eq!(btc.net_notional, Decimal::from(75_000));
+        assert_eq!(btc.gross_notional, Decimal::from(125_000));
+        assert_eq!(btc.fraction_of_equity, Decimal::from_str_exact("2.5").unwrap());
+        assert_eq!(btc.markets, vec!["BTC/USDC", "BTC/USDT"]);
+        assert_eq!(snapshot.breached, vec!["BTC"]);
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0].event_type,
+            events::RiskEventType::ExposureLimitBreached { asset, policy: LimitPolicy::Alert, .. } if asset == "BTC"
+        ));
+        // Still breached on the next mark update: no repeat event
+        assert!(tracker.recompute(&account, &positions, 1708123456789000001).is_empty());
+
+        // Alert-only limits never block
+        let buy = Order::new(
+            account.account_id,
+            MarketId::new("BTC/USDT"),
+            Side::BUY,
+            Price::from_u64(50_000),
+            Quantity::from_str("1.0").unwrap(),
+            types::order::TimeInForce::GTC,
+            1708123456789000000,
+        );
+        assert_eq!(tracker.check_order(&buy), Ok(()));
+    }
+
+    #[test]
+    fn test_reject_policy_blocks_only_increasing_orders() {
+        let (account, positions) = btc_account();
+        let mut tracker = ExposureTracker::new(UnderlyingMap::new(), marks(&[("BTC/USDT", 50_000), ("BTC/USDC", 50_000)]));
+        tracker.set_limit("BTC", EquityConcentrationLimit { max_fraction: Decimal::from(2), policy: LimitPolicy::Reject });
+        tracker.recompute(&account, &positions, 1708123456789000000);
+
+        let order = |symbol: &str, side| {
+            Order::new(
+                account.account_id,
+                MarketId::new(symbol),
+                side,
+                Price::from_u64(50_000),
+                Quantity::from_str("0.1").unwrap(),
+                types::order::TimeInForce::GTC,
+                1708123456789000000,
+            )
+        };
+        // Net long BTC: buying in either BTC market grows the breach
+        assert_eq!(
+            tracker.check_order(&order("BTC/USDC", Side::BUY)),
+            Err(RiskRejection::ConcentrationLimitExceeded {
+                asset: "BTC".to_string(),
+                limit: Decimal::from(2),
+                fraction_of_equity: Decimal::from_str_exact("2.5").unwrap(),
+            })
+        );
+        assert_eq!(tracker.check_order(&order("BTC/USDT", Side::SELL)), Ok(()));
+        assert_eq!(tracker.check_order(&order("ETH/USDT", Side::BUY)), Ok(()));
+
+        // Marks fall 20%: the long loses 20 000, the short gains 5 000
+        *tracker.marks_mut() = marks(&[("BTC/USDT", 40_000), ("BTC/USDC", 40_000)]);
+        tracker.recompute(&account, &positions, 1708123456789000001);
+        let snapshot = tracker.snapshot(&account.account_id).unwrap();
+        assert_eq!(snapshot.equity, Decimal::from(35_000));
+        assert_eq!(snapshot.asset("BTC").unwrap().net_notional, Decimal::from(60_000));
+        assert_eq!(snapshot.asset("BTC").unwrap().gross_notional, Decimal::from(100_000));
+    }
 }
@@ -0,0 +1,433 @@
+//! Perpetual funding
+//!
+//! Funding rate per market from the premium of mark over index price,
+//! accrued to open positions over fixed intervals of exchange time and
+//! paid at each interval boundary.
+//!
+//! Conventions:
+//! - Funding times are multiples of `interval` on exchange time (ns since
+//!   epoch); no wall clock is read.
+//! - Longs pay shorts when the rate is positive, shorts pay longs when it
+//!   is negative. Amounts are `size × mark × rate`, positive when the
+//!   account pays.
+//! - A position opened mid-interval pays a pro-rated share: the fraction of
+//!   the interval it was open for. A position opened at or after a funding
+//!   time pays nothing for the interval ending there.
+//! - Amounts round to 8 dp in the exchange's favor (as fees do, spec §7):
+//!   payments owed round UP, payments received round DOWN.
+//! - The rate rounds HALF_UP to 8 dp (spec §12.4.2).
+
+use std::collections::BTreeMap;
+
+use margin_core::CrossMarginEngine;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use types::numeric::Price;
+use types::position::{Position, PositionSide};
+
+use crate::events::FundingEvent;
+
+/// Decimal places for funding rates and amounts.
+const FUNDING_DP: u32 = 8;
+
+/// Funding configuration shared by all perp markets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FundingConfig {
+    /// Funding interval in nanoseconds of exchange time
+    pub interval: i64,
+    /// Interest rate component per interval
+    pub interest_rate: Decimal,
+    /// Bound on the interest − premium adjustment
+    pub premium_clamp: Decimal,
+    /// Cap on the absolute funding rate per interval
+    pub max_rate: Decimal,
+}
+
+impl Default for FundingConfig {
+    fn default() -> Self {
+        Self {
+            interval: 8 * 3_600 * 1_000_000_000,
+            interest_rate: Decimal::from_str_exact("0.0001").unwrap(),
+            premium_clamp: Decimal::from_str_exact("0.0005").unwrap(),
+            max_rate: Decimal::from_str_exact("0.0075").unwrap(),
+        }
+    }
+}
+
+/// Funding rate from mark and index price.
+///
+/// `premium = (mark − index) / index`
+/// `rate = premium + clamp(interest − premium, −premium_clamp, premium_clamp)`
+///
+/// capped at `±max_rate`.
+pub fn funding_rate(mark_price: Price, index_price: Price, config: &FundingConfig) -> Decimal {
+    let index = index_price.as_decimal();
+    let premium = (mark_price.as_decimal() - index) / index;
+    let adjustment = (config.interest_rate - premium).clamp(-config.premium_clamp, config.premium_clamp);
+    let rate = (premium + adjustment).clamp(-config.max_rate, config.max_rate);
+    rate.round_dp_with_strategy(FUNDING_DP, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Funding owed by a position for `elapsed` ns of an interval.
+///
+/// Positive when the account pays. Rounded in the exchange's favor.
+pub fn funding_payment(
+    position: &Position,
+    mark_price: Price,
+    rate: Decimal,
+    elapsed: i64,
+    interval: i64,
+) -> Decimal {
+    if elapsed <= 0 {
+        return Decimal::ZERO;
+    }
+    let notional = position.size.as_decimal() * mark_price.as_decimal();
+    let fraction = Decimal::from(elapsed) / Decimal::from(interval);
+    let amount = match position.side {
+        PositionSide::LONG => notional * rate * fraction,
+        PositionSide::SHORT => -(notional * rate * fraction),
+    };
+    round_exchange_favor(amount)
+}
+
+/// Round UP what the account pays, DOWN what it receives.
+fn round_exchange_favor(amount: Decimal) -> Decimal {
+    let strategy = if amount > Decimal::ZERO {
+        RoundingStrategy::AwayFromZero
+    } else {
+        RoundingStrategy::ToZero
+    };
+    amount.round_dp_with_strategy(FUNDING_DP, strategy)
+}
+
+/// Funding state for one market.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketFunding {
+    /// Rate applied to the current interval
+    pub rate: Decimal,
+    pub mark_price: Price,
+    pub index_price: Price,
+    /// Last funding time paid (or the interval start when first seen)
+    pub last_funding_time: i64,
+    /// Latest exchange time seen for this market
+    pub updated_at: i64,
+}
+
+/// Funding rates and payments across perp markets.
+#[derive(Debug, Clone, Default)]
+pub struct FundingEngine {
+    config: FundingConfig,
+    markets: BTreeMap<String, MarketFunding>,
+}
+
+impl FundingEngine {
+    pub fn new(config: FundingConfig) -> Self {
+        Self {
+            config,
+            markets: BTreeMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &FundingConfig {
+        &self.config
+    }
+
+    /// Funding state for a market, if it has had a rate update.
+    pub fn market(&self, symbol: &str) -> Option<&MarketFunding> {
+        self.markets.get(symbol)
+    }
+
+    /// Funding state for every market with a rate, in symbol order.
+    pub fn markets(&self) -> &BTreeMap<String, MarketFunding> {
+        &self.markets
+    }
+
+    /// Reinstate a market's funding state from a snapshot.
+    pub(crate) fn restore_market(&mut self, symbol: String, market: MarketFunding) {
+        self.markets.insert(symbol, market);
+    }
+
+    /// Funding time at or before `timestamp`.
+    pub fn interval_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.config.interval)
+    }
+
+    /// Next funding time for a market.
+    pub fn next_funding_time(&self, symbol: &str) -> Option<i64> {
+        self.markets
+            .get(symbol)
+            .map(|market| market.last_funding_time + self.config.interval)
+    }
+
+    /// Recompute a market's rate from new mark and index prices.
+    ///
+    /// A market's first update starts accrual at the current interval.
+    pub fn update_rate(
+        &mut self,
+        symbol: &str,
+        mark_price: Price,
+        index_price: Price,
+        timestamp: i64,
+    ) -> FundingEvent {
+        let rate = funding_rate(mark_price, index_price, &self.config);
+        let interval_start = self.interval_start(timestamp);
+        let market = self
+            .markets
+            .entry(symbol.to_owned())
+            .or_insert_with(|| MarketFunding {
+                rate,
+                mark_price,
+                index_price,
+                last_funding_time: interval_start,
+                updated_at: timestamp,
+            });
+        market.rate = rate;
+        market.mark_price = mark_price;
+        market.index_price = index_price;
+        market.updated_at = market.updated_at.max(timestamp);
+
+        FundingEvent::FundingRateUpdated {
+            symbol: symbol.to_owned(),
+            funding_rate: rate,
+            mark_price,
+            index_price,
+            next_funding_time: market.last_funding_time + self.config.interval,
+            timestamp,
+        }
+    }
+
+    /// Funding accrued but not yet paid on a position, as of the market's
+    /// latest update.
+    ///
+    /// Zero for markets without a rate.
+    pub fn accrued(&self, position: &Position) -> Decimal {
+        let Some(market) = self.markets.get(position.symbol.as_str()) else {
+            return Decimal::ZERO;
+        };
+        let start = position.opened_at.max(market.last_funding_time);
+        funding_payment(
+            position,
+            market.mark_price,
+            market.rate,
+            market.updated_at - start,
+            self.config.interval,
+        )
+    }
+
+    /// Total accrued funding across positions (positive = owed).
+    pub fn accrued_total(&self, positions: &[Position]) -> Decimal {
+        positions.iter().map(|pos| self.accrued(pos)).sum()
+    }
+
+    /// Record each position's accrued funding in a margin snapshot, so its
+    /// equity (and the client preview built from it) is net of funding.
+    pub fn apply_accrued(&self, snapshot: &mut CrossMarginEngine) {
+        let accrued: Vec<(String, Decimal)> = snapshot
+            .positions
+            .iter()
+            .map(|(symbol, pos)| (symbol.clone(), self.accrued(pos)))
+            .collect();
+        for (symbol, amount) in accrued {
+            snapshot.set_accrued_funding(&symbol, amount);
+        }
+    }
+
+    /// Pay every funding time that has passed by `now`.
+    ///
+    /// Markets are settled in symbol order and positions in the order given,
+    /// so the same inputs always yield the same events. Several intervals are
+    /// paid in turn if settlement fell behind, each at the market's current
+    /// rate. Applying the payments to balances is left to the caller.
+    pub fn settle_due(&mut self, positions: &[Position], now: i64) -> Vec<FundingEvent> {
+        let interval = self.config.interval;
+        let mut events = Vec::new();
+        for (symbol, market) in self.markets.iter_mut() {
+            while market.last_funding_time + interval <= now {
+                let funding_time = market.last_funding_time + interval;
+                for pos in positions.iter().filter(|pos| pos.symbol.as_str() == symbol) {
+                    let start = pos.opened_at.max(market.last_funding_time);
+                    if start >= funding_time {
+                        continue;
+                    }
+                    events.push(FundingEvent::FundingPaid {
+                        account_id: pos.account_id,
+                        symbol: symbol.clone(),
+                        side: pos.side,
+                        size: pos.size,
+                        funding_rate: market.rate,
+                        amount: funding_payment(pos, market.mark_price, market.rate, funding_time - start, interval),
+                        funding_time,
+                    });
+                }
+                market.last_funding_time = funding_time;
+            }
+            market.updated_at = market.updated_at.max(now);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::ids::{AccountId, MarketId};
+    use types::numeric::Quantity;
+
+    const HOUR: i64 = 3_600 * 1_000_000_000;
+
+    fn position(side: PositionSide, size: &str, opened_at: i64) -> Position {
+        Position::new(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            side,
+            Quantity::from_str(size).unwrap(),
+            Price::from_u64(50_000),
+            Price::from_u64(50_000),
+            Price::from_u64(45_000),
+            Decimal::from(5_000),
+            Decimal::from(250),
+            10,
+            opened_at,
+        )
+    }
+
+    fn paid(events: &[FundingEvent]) -> Vec<Decimal> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                FundingEvent::FundingPaid { amount, .. } => Some(*amount),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rate_clamp_formula() {
+        let config = FundingConfig::default();
+        let index = Price::from_u64(50_000);
+
+        // Premium within the clamp of the interest rate: rate = interest
+        assert_eq!(
+            funding_rate(Price::from_u64(50_010), index, &config),
+            Decimal::from_str_exact("0.0001").unwrap()
+        );
+        // Premium 0.1%: adjustment clamped to −0.05%, rate 0.05%
+        assert_eq!(
+            funding_rate(Price::from_u64(50_050), index, &config),
+            Decimal::from_str_exact("0.0005").unwrap()
+        );
+        // Discount 0.2%: adjustment clamped to +0.05%, rate −0.15%
+        assert_eq!(
+            funding_rate(Price::from_u64(49_900), index, &config),
+            Decimal::from_str_exact("-0.0015").unwrap()
+        );
+        // Premium 2%: capped at 0.75%
+        assert_eq!(funding_rate(Price::from_u64(51_000), index, &config), config.max_rate);
+    }
+
+    #[test]
+    fn test_longs_pay_shorts_and_rounding_favors_exchange() {
+        // 0.33333333 BTC at 50 000 × 0.0001, same amount either way
+        let long = position(PositionSide::LONG, "0.33333333", 0);
+        let short = position(PositionSide::SHORT, "0.33333333", 0);
+        let rate = Decimal::from_str_exact("0.0001").unwrap();
+        let mark = Price::from_u64(50_000);
+        let interval = 8 * HOUR;
+
+        assert_eq!(
+            funding_payment(&long, mark, rate, interval, interval),
+            Decimal::from_str_exact("1.66666665").unwrap()
+        );
+        assert_eq!(
+            funding_payment(&short, mark, rate, interval, interval),
+            Decimal::from_str_exact("-1.66666665").unwrap()
+        );
+
+        // A third of an interval, 1.666…: paid rounds up, received rounds down
+        let third = position(PositionSide::LONG, "1.0", 0);
+        assert_eq!(
+            funding_payment(&third, mark, rate, interval / 3, interval),
+            Decimal::from_str_exact("1.66666667").unwrap()
+        );
+        let third_short = position(PositionSide::SHORT, "1.0", 0);
+        assert_eq!(
+            funding_payment(&third_short, mark, rate, interval / 3, interval),
+            Decimal::from_str_exact("-1.66666666").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mid_interval_positions_pay_pro_rated() {
+        let mut engine = FundingEngine::new(FundingConfig::default());
+        engine.update_rate("BTC/USDT", Price::from_u64(50_010), Price::from_u64(50_000), HOUR);
+        assert_eq!(engine.next_funding_time("BTC/USDT"), Some(8 * HOUR));
+
+        // Full interval long, half interval short, one opened at the funding time
+        let full = position(PositionSide::LONG, "1.0", 0);
+        let half = position(PositionSide::SHORT, "1.0", 4 * HOUR);
+        let late = position(PositionSide::LONG, "1.0", 8 * HOUR);
+        let positions = vec![full, half, late];
+
+        // 50 010 × 0.0001 = 5.001 for a full interval
+        let events = engine.settle_due(&positions, 8 * HOUR);
+        assert_eq!(
+            paid(&events),
+            vec![Decimal::from_str_exact("5.001").unwrap(), Decimal::from_str_exact("-2.5005").unwrap()]
+        );
+        assert_eq!(engine.next_funding_time("BTC/USDT"), Some(16 * HOUR));
+
+        // Nothing is paid twice
+        assert!(engine.settle_due(&positions, 8 * HOUR).is_empty());
+    }
+
+    #[test]
+    fn test_accrued_funding_tracks_exchange_time() {
+        let mut engine = FundingEngine::new(FundingConfig::default());
+        let long = position(PositionSide::LONG, "1.0", 0);
+        assert_eq!(engine.accrued(&long), Decimal::ZERO);
+
+        engine.update_rate("BTC/USDT", Price::from_u64(50_010), Price::from_u64(50_000), 2 * HOUR);
+        // A quarter of the interval at 5.001 per interval
+        assert_eq!(engine.accrued(&long), Decimal::from_str_exact("1.25025").unwrap());
+
+        // Settling resets accrual to the funding time
+        engine.settle_due(std::slice::from_ref(&long), 8 * HOUR);
+        assert_eq!(engine.accrued(&long), Decimal::ZERO);
+        engine.update_rate("BTC/USDT", Price::from_u64(50_010), Price::from_u64(50_000), 12 * HOUR);
+        assert_eq!(engine.accrued(&long), Decimal::from_str_exact("2.5005").unwrap());
+        assert_eq!(engine.accrued_total(&[long.clone(), long]), Decimal::from_str_exact("5.001").unwrap());
+    }
+
+    #[test]
+    fn test_accrued_funding_in_margin_snapshot() {
+        let mut engine = FundingEngine::new(FundingConfig::default());
+        engine.update_rate("BTC/USDT", Price::from_u64(50_010), Price::from_u64(50_000), 4 * HOUR);
+
+        let long = position(PositionSide::LONG, "1.0", 0);
+        let mut snapshot = CrossMarginEngine::new(long.account_id, Decimal::from(10_000));
+        snapshot.add_position(long);
+        engine.apply_accrued(&mut snapshot);
+
+        // Half an interval owed: 2.5005
+        assert_eq!(snapshot.total_accrued_funding(), Decimal::from_str_exact("2.5005").unwrap());
+        assert_eq!(snapshot.equity(), Decimal::from_str_exact("9997.4995").unwrap());
+    }
+
+    #[test]
+    fn test_settle_catches_up_missed_intervals() {
+        let mut engine = FundingEngine::new(FundingConfig::default());
+        engine.update_rate("BTC/USDT", Price::from_u64(50_010), Price::from_u64(50_000), 0);
+        let long = position(PositionSide::LONG, "1.0", 0);
+
+        let events = engine.settle_due(std::slice::from_ref(&long), 17 * HOUR);
+        let times: Vec<i64> = events
+            .iter()
+            .filter_map(|event| match event {
+                FundingEvent::FundingPaid { funding_time, .. } => Some(*funding_time),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(times, vec![8 * HOUR, 16 * HOUR]);
+        assert_eq!(engine.next_funding_time("BTC/USDT"), Some(24 * HOUR));
+    }
+}
@@ -6,7 +6,8 @@
 //! - §9.3.6 (Risk Service Boundaries)
 //!
 //! Provides pre-trade validation, margin calculations,
-//! liquidation monitoring, and exposure tracking.
+//! liquidation monitoring, exposure tracking, perp funding, and mark
+//! prices.
 
 pub mod margin;
 pub mod exposure;
@@ -14,3 +15,7 @@ pub mod liquidation;
 pub mod validator;
 pub mod events;
 pub mod engine;
+pub mod cross_margin;
+pub mod funding;
+pub mod mark_price;
+pub mod restore;
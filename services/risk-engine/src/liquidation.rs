@@ -1,11 +1,22 @@
 //! Liquidation calculations
 //!
 //! Deterministic liquidation threshold, bankruptcy price, and fee
-//! calculations per spec §6 (Liquidation Process).
+//! calculations per spec §6 (Liquidation Process), and an event-driven
+//! monitor that tracks which accounts are being liquidated.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use margin_core::RiskLevel;
+use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
-use types::numeric::Price;
-use types::position::PositionSide;
+use serde::{Deserialize, Serialize};
+use types::ids::AccountId;
+use types::numeric::{Price, Quantity};
+use types::position::{Position, PositionSide};
+
+use crate::events::{InsuranceFundEvent, RiskEvent, RiskEventType};
+use crate::exposure;
+use crate::margin;
 
 // ── Health levels per spec §5.3.3 ────────────────────────────────────────
 
@@ -23,19 +34,20 @@ pub enum HealthLevel {
 }
 
 /// Classify health level from margin ratio per spec §5.3.3
+///
+/// Thresholds come from `margin_core` so server and client agree.
 pub fn health_status(margin_ratio: Decimal) -> HealthLevel {
-    let threshold_liquidation = Decimal::from_str_exact("1.1").unwrap();
-    let threshold_danger = Decimal::from_str_exact("1.5").unwrap();
-    let threshold_warning = Decimal::from_str_exact("2.0").unwrap();
-
-    if margin_ratio < threshold_liquidation {
-        HealthLevel::Liquidation
-    } else if margin_ratio < threshold_danger {
-        HealthLevel::Danger
-    } else if margin_ratio < threshold_warning {
-        HealthLevel::Warning
-    } else {
-        HealthLevel::Healthy
+    margin_core::risk_level_from_ratio(margin_ratio).into()
+}
+
+impl From<RiskLevel> for HealthLevel {
+    fn from(level: RiskLevel) -> Self {
+        match level {
+            RiskLevel::Healthy => HealthLevel::Healthy,
+            RiskLevel::Warning => HealthLevel::Warning,
+            RiskLevel::Danger => HealthLevel::Danger,
+            RiskLevel::Liquidation => HealthLevel::Liquidation,
+        }
     }
 }
 
@@ -131,6 +143,395 @@ pub fn liquidation_fee(position_value: Decimal, margin_ratio: Decimal) -> Decima
     if fee > cap { cap } else { fee }
 }
 
+/// Liquidation fee shares per spec §6.7.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSplit {
+    /// 50% to the insurance fund
+    pub insurance_fund: Decimal,
+    /// 30% to the liquidation engine (operational costs)
+    pub operations: Decimal,
+    /// 20% to exchange revenue, plus any rounding remainder
+    pub exchange: Decimal,
+}
+
+/// Split a liquidation fee per spec §6.7.2
+///
+/// The fund and operations shares round HALF_UP to 8 dp; exchange revenue
+/// takes the remainder, so the shares always sum to the fee exactly.
+pub fn split_liquidation_fee(fee: Decimal) -> FeeSplit {
+    let share = |rate: &str| {
+        (fee * Decimal::from_str_exact(rate).unwrap())
+            .round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
+    };
+    let insurance_fund = share("0.5");
+    let operations = share("0.3");
+    FeeSplit {
+        insurance_fund,
+        operations,
+        exchange: fee - insurance_fund - operations,
+    }
+}
+
+// ── Insurance fund per spec §6.6 ─────────────────────────────────────────
+
+/// A liquidation close handed to the insurance fund
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidationClose {
+    pub account_id: AccountId,
+    pub symbol: String,
+    /// Settlement asset the fund is held in (e.g. USDT)
+    pub asset: String,
+    pub side: PositionSide,
+    pub size: Quantity,
+    pub bankruptcy_price: Price,
+    pub close_price: Price,
+    /// Liquidation fee charged on the close
+    pub fee: Decimal,
+}
+
+impl LiquidationClose {
+    /// Loss beyond the position's margin per spec §6.6.3
+    ///
+    /// `max(0, bankruptcy_price − close_price) × size` for LONG, mirrored
+    /// for SHORT. Rounded UP to 8 dp so the fund never under-covers.
+    pub fn deficit(&self) -> Decimal {
+        let gap = match self.side {
+            PositionSide::LONG => self.bankruptcy_price.as_decimal() - self.close_price.as_decimal(),
+            PositionSide::SHORT => self.close_price.as_decimal() - self.bankruptcy_price.as_decimal(),
+        };
+        (gap.max(Decimal::ZERO) * self.size.as_decimal())
+            .round_dp_with_strategy(8, RoundingStrategy::AwayFromZero)
+    }
+}
+
+/// Insurance fund balances per settlement asset
+///
+/// Closes at or better than bankruptcy credit the fund its share of the
+/// liquidation fee; closes worse than bankruptcy debit the deficit. The
+/// balance never goes below zero: what the fund cannot cover is reported in
+/// `InsuranceFundDepleted` for auto-deleveraging (spec §6.6.4).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InsuranceFund {
+    balances: BTreeMap<String, Decimal>,
+}
+
+impl InsuranceFund {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fund holding the given balances, as restored from a snapshot
+    pub(crate) fn from_balances(balances: BTreeMap<String, Decimal>) -> Self {
+        Self { balances }
+    }
+
+    /// Current balance in `asset`
+    pub fn balance(&self, asset: &str) -> Decimal {
+        self.balances.get(asset).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Balances in asset order
+    pub fn balances(&self) -> &BTreeMap<String, Decimal> {
+        &self.balances
+    }
+
+    /// Add capital to the fund (initial capitalization, trading fee share)
+    pub fn deposit(&mut self, asset: &str, amount: Decimal, timestamp: i64) -> InsuranceFundEvent {
+        let balance = self.balances.entry(asset.to_owned()).or_insert(Decimal::ZERO);
+        *balance += amount;
+        InsuranceFundEvent::InsuranceFundCredited {
+            asset: asset.to_owned(),
+            amount,
+            balance_after: *balance,
+            account_id: None,
+            symbol: None,
+            timestamp,
+        }
+    }
+
+    /// Settle a liquidation close against the fund
+    ///
+    /// A close worse than bankruptcy has no margin left to pay the fee, so
+    /// only the deficit is settled.
+    pub fn settle_liquidation(&mut self, close: &LiquidationClose, timestamp: i64) -> Vec<InsuranceFundEvent> {
+        let balance = self.balances.entry(close.asset.clone()).or_insert(Decimal::ZERO);
+        let deficit = close.deficit();
+
+        if deficit.is_zero() {
+            let amount = split_liquidation_fee(close.fee).insurance_fund;
+            *balance += amount;
+            return vec![InsuranceFundEvent::InsuranceFundCredited {
+                asset: close.asset.clone(),
+                amount,
+                balance_after: *balance,
+                account_id: Some(close.account_id),
+                symbol: Some(close.symbol.clone()),
+                timestamp,
+            }];
+        }
+
+        let covered = deficit.min(*balance);
+        *balance -= covered;
+        let mut events = vec![InsuranceFundEvent::InsuranceFundDebited {
+            asset: close.asset.clone(),
+            amount: covered,
+            balance_after: *balance,
+            account_id: close.account_id,
+            symbol: close.symbol.clone(),
+            timestamp,
+        }];
+        if balance.is_zero() {
+            events.push(InsuranceFundEvent::InsuranceFundDepleted {
+                asset: close.asset.clone(),
+                shortfall: deficit - covered,
+                account_id: close.account_id,
+                symbol: close.symbol.clone(),
+                timestamp,
+            });
+        }
+        events
+    }
+}
+
+// ── Liquidation monitor per spec §6.2.2 ──────────────────────────────────
+
+/// Thresholds for starting and clearing a liquidation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidationMonitorConfig {
+    /// Liquidation starts below this margin ratio
+    pub liquidation_threshold: Decimal,
+    /// A started liquidation clears only at or above this margin ratio
+    pub clear_threshold: Decimal,
+}
+
+impl Default for LiquidationMonitorConfig {
+    fn default() -> Self {
+        Self {
+            liquidation_threshold: Decimal::from_str_exact("1.1").unwrap(),
+            clear_threshold: Decimal::from_str_exact("1.15").unwrap(),
+        }
+    }
+}
+
+/// Monitor state for one account.
+#[derive(Debug, Clone)]
+struct MonitoredAccount {
+    account_id: AccountId,
+    collateral: Decimal,
+    positions: BTreeMap<String, Position>,
+    margin_ratio: Decimal,
+    liquidating: bool,
+}
+
+/// Event-driven liquidation monitor
+///
+/// Accounts are kept ordered by margin ratio. A mark price update
+/// re-evaluates only the accounts with a position in that market, and a
+/// fill only the filled account. Between the liquidation and clear
+/// thresholds an account keeps its current state, so a ratio flickering
+/// around 1.1 starts one liquidation rather than a stream of
+/// `LiquidationStarted` / `LiquidationCancelled` pairs.
+#[derive(Debug, Clone, Default)]
+pub struct LiquidationMonitor {
+    config: LiquidationMonitorConfig,
+    /// Accounts in first-seen order; the index is the account's slot
+    accounts: Vec<MonitoredAccount>,
+    slots: HashMap<AccountId, usize>,
+    /// Slots holding a position per market
+    by_symbol: HashMap<String, BTreeSet<usize>>,
+    /// (margin ratio, slot), lowest ratio first
+    by_ratio: BTreeSet<(Decimal, usize)>,
+    marks: HashMap<String, Price>,
+    evaluations: u64,
+}
+
+impl LiquidationMonitor {
+    pub fn new(config: LiquidationMonitorConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Apply a mark price update and re-evaluate accounts in that market.
+    pub fn on_mark_price(&mut self, symbol: &str, mark_price: Price, timestamp: i64) -> Vec<RiskEvent> {
+        self.marks.insert(symbol.to_owned(), mark_price);
+        let slots: Vec<usize> = match self.by_symbol.get(symbol) {
+            Some(slots) => slots.iter().copied().collect(),
+            None => return Vec::new(),
+        };
+        let mut events = Vec::new();
+        for slot in slots {
+            if let Some(position) = self.accounts[slot].positions.get_mut(symbol) {
+                position.update_mark_price(mark_price, timestamp);
+            }
+            events.extend(self.evaluate(slot, timestamp));
+        }
+        events
+    }
+
+    /// Apply a fill: the account's position in the filled market after the
+    /// fill, and its collateral after fees and realized PnL.
+    ///
+    /// A zero-size position removes the account from that market.
+    pub fn on_fill(&mut self, mut position: Position, collateral: Decimal, timestamp: i64) -> Vec<RiskEvent> {
+        let account_id = position.account_id;
+        let symbol = position.symbol.as_str().to_owned();
+        let slot = match self.slots.get(&account_id) {
+            Some(&slot) => slot,
+            None => {
+                let slot = self.accounts.len();
+                self.accounts.push(MonitoredAccount {
+                    account_id,
+                    collateral,
+                    positions: BTreeMap::new(),
+                    margin_ratio: Decimal::MAX,
+                    liquidating: false,
+                });
+                self.slots.insert(account_id, slot);
+                self.by_ratio.insert((Decimal::MAX, slot));
+                slot
+            }
+        };
+
+        let account = &mut self.accounts[slot];
+        account.collateral = collateral;
+        if position.size.is_zero() {
+            account.positions.remove(&symbol);
+            if let Some(slots) = self.by_symbol.get_mut(&symbol) {
+                slots.remove(&slot);
+            }
+        } else {
+            if let Some(&mark) = self.marks.get(&symbol) {
+                position.update_mark_price(mark, timestamp);
+            }
+            account.positions.insert(symbol.clone(), position);
+            self.by_symbol.entry(symbol).or_default().insert(slot);
+        }
+        self.evaluate(slot, timestamp)
+    }
+
+    /// Accounts at `level` or worse, lowest margin ratio first.
+    pub fn accounts_at_risk(&self, level: HealthLevel) -> Vec<(AccountId, Decimal)> {
+        self.by_ratio
+            .iter()
+            .take_while(|(ratio, _)| severity(health_status(*ratio)) >= severity(level))
+            .map(|&(ratio, slot)| (self.accounts[slot].account_id, ratio))
+            .collect()
+    }
+
+    /// Latest margin ratio for an account, if monitored.
+    pub fn margin_ratio(&self, account_id: &AccountId) -> Option<Decimal> {
+        self.slots.get(account_id).map(|&slot| self.accounts[slot].margin_ratio)
+    }
+
+    /// Whether a liquidation is in progress for an account.
+    pub fn is_liquidating(&self, account_id: &AccountId) -> bool {
+        self.slots
+            .get(account_id)
+            .is_some_and(|&slot| self.accounts[slot].liquidating)
+    }
+
+    /// Account re-evaluations performed so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Monitored accounts in first-seen order, with collateral, positions
+    /// by symbol and liquidation flag.
+    pub(crate) fn accounts(&self) -> impl Iterator<Item = (AccountId, Decimal, &BTreeMap<String, Position>, bool)> {
+        self.accounts
+            .iter()
+            .map(|a| (a.account_id, a.collateral, &a.positions, a.liquidating))
+    }
+
+    /// Last mark applied per market.
+    pub(crate) fn marks(&self) -> &HashMap<String, Price> {
+        &self.marks
+    }
+
+    /// Reinstate an account from a snapshot, after those seen before it.
+    ///
+    /// The margin ratio is recomputed; no events are emitted.
+    pub(crate) fn restore_account(
+        &mut self,
+        account_id: AccountId,
+        collateral: Decimal,
+        positions: BTreeMap<String, Position>,
+        liquidating: bool,
+    ) {
+        let slot = self.accounts.len();
+        for symbol in positions.keys() {
+            self.by_symbol.entry(symbol.clone()).or_default().insert(slot);
+        }
+        let mut account = MonitoredAccount {
+            account_id,
+            collateral,
+            positions,
+            margin_ratio: Decimal::ZERO,
+            liquidating,
+        };
+        account.margin_ratio = account.margin().2;
+        self.by_ratio.insert((account.margin_ratio, slot));
+        self.slots.insert(account_id, slot);
+        self.accounts.push(account);
+    }
+
+    /// Reinstate the last mark applied to a market.
+    pub(crate) fn restore_mark(&mut self, symbol: String, mark_price: Price) {
+        self.marks.insert(symbol, mark_price);
+    }
+
+    /// Recompute one account's ratio and apply the hysteresis thresholds.
+    fn evaluate(&mut self, slot: usize, timestamp: i64) -> Vec<RiskEvent> {
+        self.evaluations += 1;
+        let account = &mut self.accounts[slot];
+        let (equity, maintenance_margin, ratio) = account.margin();
+
+        self.by_ratio.remove(&(account.margin_ratio, slot));
+        self.by_ratio.insert((ratio, slot));
+        account.margin_ratio = ratio;
+
+        let event_type = if !account.liquidating && ratio < self.config.liquidation_threshold {
+            account.liquidating = true;
+            RiskEventType::LiquidationStarted
+        } else if account.liquidating && ratio >= self.config.clear_threshold {
+            account.liquidating = false;
+            RiskEventType::LiquidationCancelled
+        } else {
+            return Vec::new();
+        };
+        vec![RiskEvent::new(
+            account.account_id,
+            event_type,
+            ratio,
+            equity,
+            maintenance_margin,
+            timestamp,
+        )]
+    }
+}
+
+impl MonitoredAccount {
+    /// Equity, maintenance margin and margin ratio at current marks.
+    fn margin(&self) -> (Decimal, Decimal, Decimal) {
+        let unrealized_pnl: Decimal = self.positions.values().map(|p| p.unrealized_pnl).sum();
+        let equity = exposure::equity(self.collateral, unrealized_pnl);
+        let maintenance_margin: Decimal = self.positions.values().map(|p| p.maintenance_margin).sum();
+        (equity, maintenance_margin, margin::margin_ratio(equity, maintenance_margin))
+    }
+}
+
+/// Ordering of health levels, worst highest.
+fn severity(level: HealthLevel) -> u8 {
+    match level {
+        HealthLevel::Healthy => 0,
+        HealthLevel::Warning => 1,
+        HealthLevel::Danger => 2,
+        HealthLevel::Liquidation => 3,
+    }
+}
+
 // ── Tests ────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -330,4 +731,186 @@ mod tests {
         let cap = Decimal::from(1_000_000) * Decimal::from_str_exact("0.05").unwrap();
         assert!(fee <= cap);
     }
+
+    // ── insurance fund tests ──
+
+    fn close(side: PositionSide, bankruptcy: u64, close_price: u64, fee: &str) -> LiquidationClose {
+        LiquidationClose {
+            account_id: AccountId::new(),
+            symbol: "BTC/USDT".to_string(),
+            asset: "USDT".to_string(),
+            side,
+            size: Quantity::from_str("10").unwrap(),
+            bankruptcy_price: Price::from_u64(bankruptcy),
+            close_price: Price::from_u64(close_price),
+            fee: Decimal::from_str_exact(fee).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_fee_split_rounding_is_exact() {
+        let split = split_liquidation_fee(Decimal::from(250));
+        assert_eq!(split.insurance_fund, Decimal::from(125));
+        assert_eq!(split.operations, Decimal::from(75));
+        assert_eq!(split.exchange, Decimal::from(50));
+
+        // 0.00000003: fund 0.000000015 → 0.00000002, ops 0.000000009 → 0.00000001
+        let fee = Decimal::from_str_exact("0.00000003").unwrap();
+        let split = split_liquidation_fee(fee);
+        assert_eq!(split.insurance_fund, Decimal::from_str_exact("0.00000002").unwrap());
+        assert_eq!(split.operations, Decimal::from_str_exact("0.00000001").unwrap());
+        assert_eq!(split.exchange, Decimal::ZERO);
+        assert_eq!(split.insurance_fund + split.operations + split.exchange, fee);
+    }
+
+    #[test]
+    fn test_close_above_bankruptcy_credits_fee_share() {
+        let mut fund = InsuranceFund::new();
+        let events = fund.settle_liquidation(&close(PositionSide::LONG, 49_500, 49_600, "2480.5"), 1);
+        assert_eq!(fund.balance("USDT"), Decimal::from_str_exact("1240.25").unwrap());
+        assert!(matches!(
+            &events[..],
+            [InsuranceFundEvent::InsuranceFundCredited { amount, .. }] if *amount == Decimal::from_str_exact("1240.25").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_close_below_bankruptcy_debits_deficit() {
+        // Spec §6.6.3 example: LONG 10 BTC, BP 49 500, closed at 49 200
+        let mut fund = InsuranceFund::new();
+        fund.deposit("USDT", Decimal::from(10_000), 1);
+        let events = fund.settle_liquidation(&close(PositionSide::LONG, 49_500, 49_200, "0"), 2);
+        assert_eq!(fund.balance("USDT"), Decimal::from(7_000));
+        assert!(matches!(
+            &events[..],
+            [InsuranceFundEvent::InsuranceFundDebited { amount, balance_after, .. }]
+                if *amount == Decimal::from(3_000) && *balance_after == Decimal::from(7_000)
+        ));
+
+        // SHORT closed above its bankruptcy price
+        fund.settle_liquidation(&close(PositionSide::SHORT, 50_500, 50_600, "0"), 3);
+        assert_eq!(fund.balance("USDT"), Decimal::from(6_000));
+    }
+
+    #[test]
+    fn test_fund_depletion_reports_shortfall() {
+        let mut fund = InsuranceFund::new();
+        fund.deposit("USDT", Decimal::from(2_000), 1);
+
+        let events = fund.settle_liquidation(&close(PositionSide::LONG, 49_500, 49_200, "0"), 2);
+        assert_eq!(fund.balance("USDT"), Decimal::ZERO);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            InsuranceFundEvent::InsuranceFundDebited { amount, .. } if *amount == Decimal::from(2_000)
+        ));
+        assert!(matches!(
+            &events[1],
+            InsuranceFundEvent::InsuranceFundDepleted { shortfall, .. } if *shortfall == Decimal::from(1_000)
+        ));
+
+        // Exactly draining the fund is also a depletion, with nothing left short
+        fund.deposit("USDT", Decimal::from(3_000), 3);
+        let events = fund.settle_liquidation(&close(PositionSide::LONG, 49_500, 49_200, "0"), 4);
+        assert!(matches!(
+            events.last(),
+            Some(InsuranceFundEvent::InsuranceFundDepleted { shortfall, .. }) if shortfall.is_zero()
+        ));
+    }
+
+    // ── Liquidation monitor tests ──
+
+    fn monitored_position(account_id: AccountId, symbol: &str, size: &str, entry: u64, mm: i64) -> Position {
+        Position::new(
+            account_id,
+            types::ids::MarketId::new(symbol),
+            PositionSide::LONG,
+            Quantity::from_str(size).unwrap(),
+            Price::from_u64(entry),
+            Price::from_u64(entry),
+            Price::from_u64(1),
+            Decimal::from(mm),
+            Decimal::from(mm),
+            10,
+            1708123456789000000,
+        )
+    }
+
+    #[test]
+    fn test_monitor_hysteresis_between_thresholds() {
+        let mut monitor = LiquidationMonitor::new(LiquidationMonitorConfig::default());
+        let account_id = AccountId::new();
+        let events = monitor.on_fill(monitored_position(account_id, "BTC/USDT", "1.0", 50_000, 5_000), Decimal::from(6_000), 1);
+        assert!(events.is_empty());
+
+        let types_at = |monitor: &mut LiquidationMonitor, mark, ts| -> Vec<RiskEventType> {
+            monitor
+                .on_mark_price("BTC/USDT", Price::from_u64(mark), ts)
+                .into_iter()
+                .map(|e| e.event_type)
+                .collect()
+        };
+        // 5 000 / 5 000 = 1.0
+        assert_eq!(types_at(&mut monitor, 49_000, 2), vec![RiskEventType::LiquidationStarted]);
+        // 1.14 and 1.08 are around 1.1 but inside the band: no flicker
+        assert!(types_at(&mut monitor, 49_700, 3).is_empty());
+        assert!(types_at(&mut monitor, 49_400, 4).is_empty());
+        assert!(types_at(&mut monitor, 49_700, 5).is_empty());
+        assert!(monitor.is_liquidating(&account_id));
+        // 1.16 clears
+        assert_eq!(types_at(&mut monitor, 49_800, 6), vec![RiskEventType::LiquidationCancelled]);
+        assert!(!monitor.is_liquidating(&account_id));
+        // 1.14 again: below the clear threshold but not below 1.1
+        assert!(types_at(&mut monitor, 49_700, 7).is_empty());
+    }
+
+    #[test]
+    fn test_monitor_reevaluates_only_affected_accounts() {
+        let mut monitor = LiquidationMonitor::new(LiquidationMonitorConfig::default());
+        let mut thin = std::collections::HashSet::new();
+        for i in 0..10_000 {
+            let account_id = AccountId::new();
+            if i % 10 == 0 {
+                // ETH holders; one in ten is thinly collateralized
+                let collateral = if i % 100 == 0 { 4_000 } else { 10_000 };
+                if collateral == 4_000 {
+                    thin.insert(account_id);
+                }
+                monitor.on_fill(monitored_position(account_id, "ETH/USDT", "10.0", 3_000, 1_500), Decimal::from(collateral), 1);
+            } else {
+                monitor.on_fill(monitored_position(account_id, "BTC/USDT", "0.1", 50_000, 250), Decimal::from(5_000), 1);
+            }
+        }
+        assert!(monitor.accounts_at_risk(HealthLevel::Warning).is_empty());
+
+        // ETH −10%: thin accounts fall to 1 000 / 1 500
+        let before = monitor.evaluations();
+        let events = monitor.on_mark_price("ETH/USDT", Price::from_u64(2_700), 2);
+        assert_eq!(monitor.evaluations() - before, 1_000);
+        assert_eq!(events.len(), 100);
+        assert!(events
+            .iter()
+            .all(|e| e.event_type == RiskEventType::LiquidationStarted && thin.contains(&e.account_id)));
+
+        let at_risk = monitor.accounts_at_risk(HealthLevel::Liquidation);
+        assert_eq!(at_risk.len(), 100);
+        assert!(at_risk.windows(2).all(|w| w[0].1 <= w[1].1));
+        // Healthy ETH holders sit at 7 000 / 1 500, above the warning level
+        assert_eq!(monitor.accounts_at_risk(HealthLevel::Warning).len(), 100);
+    }
+
+    #[test]
+    fn test_monitor_fill_closing_position_leaves_market() {
+        let mut monitor = LiquidationMonitor::new(LiquidationMonitorConfig::default());
+        let account_id = AccountId::new();
+        let mut position = monitored_position(account_id, "BTC/USDT", "1.0", 50_000, 5_000);
+        monitor.on_fill(position.clone(), Decimal::from(5_000), 1);
+        position.size = Quantity::zero();
+        monitor.on_fill(position, Decimal::from(5_000), 2);
+
+        let before = monitor.evaluations();
+        assert!(monitor.on_mark_price("BTC/USDT", Price::from_u64(40_000), 3).is_empty());
+        assert_eq!(monitor.evaluations(), before);
+        assert_eq!(monitor.margin_ratio(&account_id), Some(Decimal::MAX));
+    }
 }
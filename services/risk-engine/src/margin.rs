@@ -141,6 +141,15 @@ pub fn margin_ratio(equity: Decimal, maintenance_margin: Decimal) -> Decimal {
     equity / maintenance_margin
 }
 
+/// Equity net of funding accrued but not yet paid
+///
+/// `equity = total_balance + unrealized_pnl − accrued_funding`
+///
+/// `accrued_funding` is positive when the account owes funding.
+pub fn funding_adjusted_equity(equity: Decimal, accrued_funding: Decimal) -> Decimal {
+    equity - accrued_funding
+}
+
 /// Calculate available margin per spec §5.3.1
 ///
 /// `available_margin = equity - margin_used - locked_margin`
@@ -328,6 +337,13 @@ mod tests {
         assert_eq!(ratio, Decimal::MAX);
     }
 
+    #[test]
+    fn test_funding_adjusted_equity() {
+        assert_eq!(funding_adjusted_equity(Decimal::from(6_000), Decimal::from(25)), Decimal::from(5_975));
+        // Funding received adds to equity
+        assert_eq!(funding_adjusted_equity(Decimal::from(6_000), Decimal::from(-25)), Decimal::from(6_025));
+    }
+
     // ── available_margin tests ──
 
     #[test]
@@ -0,0 +1,246 @@
+//! Mark price
+//!
+//! Each market's mark is the median of three sources: the exchange mid
+//! price, the last trade price and an external index. The median is then
+//! clamped to a band around the index, so a thin book or a single off
+//! print cannot move the mark far from the wider market.
+//!
+//! Conventions:
+//! - A source is fresh while its last update is no older than
+//!   `max_staleness` ns of exchange time; only fresh sources enter the
+//!   median. With an even count the two middle values are averaged.
+//! - When no source is fresh the mark is stale: it is still computed from
+//!   the last known values, but must not drive liquidations.
+//! - The mark rounds HALF_UP to 8 dp (spec §12.4.2).
+
+use std::collections::BTreeMap;
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use types::numeric::Price;
+
+/// Decimal places for mark prices.
+const MARK_DP: u32 = 8;
+
+/// Mark price configuration shared by all markets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkPriceConfig {
+    /// Age in ns of exchange time after which a source update is stale
+    pub max_staleness: i64,
+    /// Maximum distance of the mark from the index, as a fraction of it
+    pub index_band: Decimal,
+}
+
+impl Default for MarkPriceConfig {
+    fn default() -> Self {
+        Self {
+            max_staleness: 10 * 1_000_000_000,
+            index_band: Decimal::from_str_exact("0.05").unwrap(),
+        }
+    }
+}
+
+/// An input to the mark price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PriceSource {
+    /// Midpoint of the exchange's best bid and ask
+    Mid,
+    /// Last trade on the exchange
+    LastTrade,
+    /// External index price
+    Index,
+}
+
+/// Latest value of one source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePrice {
+    pub price: Price,
+    pub updated_at: i64,
+}
+
+/// A market's mark price at a point in exchange time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkPrice {
+    pub symbol: String,
+    pub price: Price,
+    /// No source updated within `max_staleness`
+    pub stale: bool,
+    /// Most recent update across all sources
+    pub last_update: i64,
+    pub timestamp: i64,
+}
+
+/// Median of the prices, averaging the middle pair of an even count.
+fn median(prices: &mut [Decimal]) -> Option<Decimal> {
+    if prices.is_empty() {
+        return None;
+    }
+    prices.sort();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Some(prices[mid])
+    } else {
+        Some((prices[mid - 1] + prices[mid]) / Decimal::TWO)
+    }
+}
+
+/// Source prices and mark price computation across markets.
+#[derive(Debug, Clone, Default)]
+pub struct MarkPriceEngine {
+    config: MarkPriceConfig,
+    sources: BTreeMap<String, BTreeMap<PriceSource, SourcePrice>>,
+}
+
+impl MarkPriceEngine {
+    pub fn new(config: MarkPriceConfig) -> Self {
+        Self {
+            config,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &MarkPriceConfig {
+        &self.config
+    }
+
+    /// Record a source update. Updates older than the one held are ignored.
+    pub fn update_source(&mut self, symbol: &str, source: PriceSource, price: Price, timestamp: i64) {
+        let sources = self.sources.entry(symbol.to_owned()).or_default();
+        match sources.get(&source) {
+            Some(held) if held.updated_at > timestamp => {}
+            _ => {
+                sources.insert(source, SourcePrice { price, updated_at: timestamp });
+            }
+        }
+    }
+
+    /// Every source value held, by market then source.
+    pub fn sources(&self) -> impl Iterator<Item = (&str, PriceSource, &SourcePrice)> {
+        self.sources
+            .iter()
+            .flat_map(|(symbol, sources)| sources.iter().map(move |(source, price)| (symbol.as_str(), *source, price)))
+    }
+
+    /// Latest value of one source for a market.
+    pub fn source(&self, symbol: &str, source: PriceSource) -> Option<&SourcePrice> {
+        self.sources.get(symbol)?.get(&source)
+    }
+
+    /// Mark price for a market as of `now`.
+    ///
+    /// `None` until the market has had a source update.
+    pub fn mark(&self, symbol: &str, now: i64) -> Option<MarkPrice> {
+        let sources = self.sources.get(symbol)?;
+        let last_update = sources.values().map(|s| s.updated_at).max()?;
+        let is_fresh = |s: &SourcePrice| now - s.updated_at <= self.config.max_staleness;
+        let stale = !sources.values().any(is_fresh);
+
+        let mut prices: Vec<Decimal> = sources
+            .values()
+            .filter(|s| stale || is_fresh(s))
+            .map(|s| s.price.as_decimal())
+            .collect();
+        let mut mark = median(&mut prices)?;
+
+        if let Some(index) = sources.get(&PriceSource::Index).filter(|s| stale || is_fresh(s)) {
+            let index = index.price.as_decimal();
+            let band = index * self.config.index_band;
+            mark = mark.clamp(index - band, index + band);
+        }
+
+        let mark = mark.round_dp_with_strategy(MARK_DP, RoundingStrategy::MidpointAwayFromZero);
+        Some(MarkPrice {
+            symbol: symbol.to_owned(),
+            price: Price::try_new(mark)?,
+            stale,
+            last_update,
+            timestamp: now,
+        })
+    }
+
+    /// Whether a market's mark is stale as of `now`.
+    ///
+    /// Markets without source updates are not tracked and never stale.
+    pub fn is_stale(&self, symbol: &str, now: i64) -> bool {
+        self.mark(symbol, now).is_some_and(|mark| mark.stale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1708123456789000000;
+    const SECOND: i64 = 1_000_000_000;
+
+    fn engine_with(mid: u64, last: u64, index: u64) -> MarkPriceEngine {
+        let mut engine = MarkPriceEngine::new(MarkPriceConfig::default());
+        engine.update_source("BTC/USDT", PriceSource::Mid, Price::from_u64(mid), T0);
+        engine.update_source("BTC/USDT", PriceSource::LastTrade, Price::from_u64(last), T0);
+        engine.update_source("BTC/USDT", PriceSource::Index, Price::from_u64(index), T0);
+        engine
+    }
+
+    #[test]
+    fn test_median_ignores_one_wild_source() {
+        // A fat-finger print 40% below the others
+        let engine = engine_with(50_010, 30_000, 50_000);
+        let mark = engine.mark("BTC/USDT", T0).unwrap();
+        assert_eq!(mark.price, Price::from_u64(50_000));
+        assert!(!mark.stale);
+
+        // A spoofed mid 40% above
+        let engine = engine_with(70_000, 50_020, 50_000);
+        assert_eq!(engine.mark("BTC/USDT", T0).unwrap().price, Price::from_u64(50_020));
+    }
+
+    #[test]
+    fn test_mark_clamped_to_index_band() {
+        // Book and trades agree on a level 10% over the index
+        let engine = engine_with(55_000, 55_100, 50_000);
+        assert_eq!(engine.mark("BTC/USDT", T0).unwrap().price, Price::from_u64(52_500));
+    }
+
+    #[test]
+    fn test_stale_sources_drop_out_then_mark_goes_stale() {
+        let mut engine = engine_with(50_100, 50_200, 50_000);
+        // Only the index keeps updating
+        engine.update_source("BTC/USDT", PriceSource::Index, Price::from_u64(49_000), T0 + 8 * SECOND);
+        let mark = engine.mark("BTC/USDT", T0 + 15 * SECOND).unwrap();
+        assert_eq!(mark.price, Price::from_u64(49_000));
+        assert!(!mark.stale);
+
+        let mark = engine.mark("BTC/USDT", T0 + 30 * SECOND).unwrap();
+        assert!(mark.stale);
+        assert_eq!(mark.last_update, T0 + 8 * SECOND);
+        // Computed from the last known values: median of 49 000, 50 100, 50 200
+        assert_eq!(mark.price, Price::from_u64(50_100));
+        assert!(engine.is_stale("BTC/USDT", T0 + 30 * SECOND));
+        assert!(!engine.is_stale("ETH/USDT", T0 + 30 * SECOND));
+    }
+
+    #[test]
+    fn test_even_count_averages_middle_pair() {
+        let mut engine = MarkPriceEngine::new(MarkPriceConfig::default());
+        engine.update_source("BTC/USDT", PriceSource::Mid, Price::from_str("50000.000000005").unwrap(), T0);
+        engine.update_source("BTC/USDT", PriceSource::LastTrade, Price::from_u64(50_001), T0);
+        // (50 000.000000005 + 50 001) / 2 = 50 000.5000000025 → HALF_UP at 8 dp
+        assert_eq!(engine.mark("BTC/USDT", T0).unwrap().price, Price::from_str("50000.50000000").unwrap());
+    }
+
+    #[test]
+    fn test_out_of_order_update_ignored() {
+        let mut engine = engine_with(50_000, 50_000, 50_000);
+        engine.update_source("BTC/USDT", PriceSource::Mid, Price::from_u64(51_000), T0 - 1);
+        assert_eq!(engine.source("BTC/USDT", PriceSource::Mid).unwrap().price, Price::from_u64(50_000));
+    }
+
+    #[test]
+    fn test_deterministic_mark() {
+        let a = engine_with(50_123, 49_877, 50_011).mark("BTC/USDT", T0 + SECOND);
+        let b = engine_with(50_123, 49_877, 50_011).mark("BTC/USDT", T0 + SECOND);
+        assert_eq!(a, b);
+        assert!(engine_with(50_000, 50_000, 50_000).mark("ETH/USDT", T0).is_none());
+    }
+}
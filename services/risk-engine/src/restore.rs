@@ -0,0 +1,603 @@
+//! Risk engine warm start from persistence
+//!
+//! Exports and imports the risk engine's state through the `risk` section
+//! of the persistence `EngineState` per spec §10.4 (crash recovery) and
+//! §11.6 (snapshot restore), so boot loads the latest snapshot and replays
+//! only the journal after it instead of the whole history.
+//!
+//! Risk inputs are journaled as `Correlated<RiskInput>`s with a bincode
+//! payload, registered with the persistence `EventRegistry` by
+//! `register_inputs`. Events raised by a correlated input carry its id.
+//! Inputs journaled before the correlation id decode with none.
+//! `RiskEventApplier` replays them through `RecoveryEngine::recover`;
+//! entries of other event types are skipped.
+//!
+//! Restoring a snapshot and replaying the rest of a stream yields the same
+//! state, hash included, as replaying the whole stream.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use persistence::correlation::Correlated;
+use persistence::journal::JournalEntry;
+use persistence::recovery::TypedEventApplier;
+use persistence::registry::{Codec, EventRegistry, JournalEvent, RegistryError, TypedEvent};
+use persistence::snapshot::{
+    EngineState, FundingSnapshot, MarginPositionSnapshot, MarginSnapshot, MarkSourceSnapshot, RiskState,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use types::ids::{AccountId, MarketId};
+use types::numeric::{Price, Quantity};
+use types::position::{Position, PositionSide};
+use uuid::Uuid;
+
+use crate::engine::{RiskEngine, RiskEngineConfig};
+use crate::events::RiskEvent;
+use crate::funding::MarketFunding;
+use crate::liquidation::{InsuranceFund, LiquidationClose};
+use crate::mark_price::PriceSource;
+
+/// Warm start errors
+#[derive(Error, Debug)]
+pub enum RestoreError {
+    #[error("Invalid risk snapshot entry {key}: {reason}")]
+    InvalidSnapshot { key: String, reason: String },
+
+    #[error("Undecodable {event_type} entry at sequence {sequence}: {reason}")]
+    Decode {
+        sequence: u64,
+        event_type: String,
+        reason: String,
+    },
+}
+
+/// An input that changes risk engine state, as journaled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RiskInput {
+    /// An account's position in a market after a fill, and its collateral
+    Fill { position: Position, collateral: Decimal },
+    /// A mid, last trade or index price update
+    MarkSource {
+        symbol: String,
+        source: PriceSource,
+        price: Price,
+    },
+    /// Mark and index prices for a funding rate update
+    FundingRate {
+        symbol: String,
+        mark_price: Price,
+        index_price: Price,
+    },
+    /// Capital added to the insurance fund
+    InsuranceDeposit { asset: String, amount: Decimal },
+    /// A liquidation close settled against the insurance fund
+    LiquidationSettled(LiquidationClose),
+}
+
+impl RiskInput {
+    /// Journal event type
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            RiskInput::Fill { .. } => "RiskFill",
+            RiskInput::MarkSource { .. } => "RiskMarkSource",
+            RiskInput::FundingRate { .. } => "RiskFundingRate",
+            RiskInput::InsuranceDeposit { .. } => "RiskInsuranceDeposit",
+            RiskInput::LiquidationSettled(_) => "RiskLiquidationSettled",
+        }
+    }
+}
+
+impl JournalEvent for RiskInput {
+    fn event_type(&self) -> &str {
+        RiskInput::event_type(self)
+    }
+}
+
+/// Journal event types carrying `RiskInput`s.
+pub const RISK_INPUT_TYPES: [&str; 5] =
+    ["RiskFill", "RiskMarkSource", "RiskFundingRate", "RiskInsuranceDeposit", "RiskLiquidationSettled"];
+
+/// Register the `RiskInput` event types.
+pub fn register_inputs(registry: &mut EventRegistry) -> Result<(), RegistryError> {
+    registry.register::<Correlated<RiskInput>>(&RISK_INPUT_TYPES, Codec::Bincode)
+}
+
+/// Registry holding just the risk inputs.
+fn event_registry() -> &'static EventRegistry {
+    static REGISTRY: OnceLock<EventRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = EventRegistry::new();
+        register_inputs(&mut registry).expect("risk input types are distinct");
+        registry
+    })
+}
+
+/// Encode a risk input as a journal entry (bincode payload).
+pub fn journal_entry(sequence: u64, timestamp: i64, input: &RiskInput) -> JournalEntry {
+    correlated_journal_entry(sequence, timestamp, input, None)
+}
+
+/// Encode a risk input journaled for the request `correlation_id`.
+pub fn correlated_journal_entry(
+    sequence: u64,
+    timestamp: i64,
+    input: &RiskInput,
+    correlation_id: Option<&str>,
+) -> JournalEntry {
+    let input = Correlated::new(input.clone(), correlation_id.map(str::to_string));
+    event_registry()
+        .journal_entry(sequence, timestamp, &input)
+        .expect("RiskInput serialization should never fail")
+}
+
+/// Decode a journal entry into a risk input.
+///
+/// Returns `None` for event types that do not change risk state.
+pub fn decode_input(entry: &JournalEntry) -> Result<Option<RiskInput>, RestoreError> {
+    Ok(decode_correlated(entry)?.map(|input| input.event))
+}
+
+/// Decode a journal entry into a risk input and its correlation id.
+pub fn decode_correlated(entry: &JournalEntry) -> Result<Option<Correlated<RiskInput>>, RestoreError> {
+    let event = event_registry().decode_entry(entry).map_err(|e| RestoreError::Decode {
+        sequence: entry.sequence,
+        event_type: entry.event_type.clone(),
+        reason: e.reason(),
+    })?;
+    Ok(event.downcast().ok())
+}
+
+impl RiskEngine {
+    /// Apply one risk input at exchange time `timestamp`.
+    ///
+    /// A source update that leaves the market with a fresh mark also
+    /// re-prices the market's monitored positions. Returns the liquidation
+    /// monitor's events.
+    pub fn apply_input(&mut self, input: &RiskInput, timestamp: i64) -> Vec<RiskEvent> {
+        match input {
+            RiskInput::Fill { position, collateral } => {
+                self.liquidation_monitor_mut().on_fill(position.clone(), *collateral, timestamp)
+            }
+            RiskInput::MarkSource { symbol, source, price } => {
+                self.update_mark_source(symbol, *source, *price, timestamp);
+                match self.mark_price(symbol, timestamp) {
+                    Some(mark) if !mark.stale => {
+                        self.liquidation_monitor_mut().on_mark_price(symbol, mark.price, timestamp)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            RiskInput::FundingRate { symbol, mark_price, index_price } => {
+                self.funding_mut().update_rate(symbol, *mark_price, *index_price, timestamp);
+                Vec::new()
+            }
+            RiskInput::InsuranceDeposit { asset, amount } => {
+                self.fund_insurance(asset, *amount, timestamp);
+                Vec::new()
+            }
+            RiskInput::LiquidationSettled(close) => {
+                self.settle_liquidation(close, timestamp);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Apply a journaled input; the events it raises carry its correlation id.
+    pub fn apply_correlated(&mut self, input: &Correlated<RiskInput>, timestamp: i64) -> Vec<RiskEvent> {
+        self.apply_input(&input.event, timestamp)
+            .into_iter()
+            .map(|event| event.with_correlation_id(input.correlation_id.clone()))
+            .collect()
+    }
+
+    /// Export margin, funding, mark price and insurance fund state.
+    pub fn to_snapshot(&self) -> RiskState {
+        let mut state = RiskState::default();
+        for (order, (account_id, collateral, positions, liquidating)) in self.liquidation_monitor().accounts().enumerate() {
+            state.margin.insert(
+                account_id.to_string(),
+                MarginSnapshot {
+                    account_id: account_id.to_string(),
+                    monitor_order: order as u64,
+                    collateral: collateral.to_string(),
+                    liquidating,
+                    positions: positions
+                        .iter()
+                        .map(|(symbol, position)| (symbol.clone(), position_snapshot(position)))
+                        .collect(),
+                },
+            );
+        }
+        for (symbol, price) in self.liquidation_monitor().marks() {
+            state.marks.insert(symbol.clone(), price.to_string());
+        }
+        for (symbol, market) in self.funding().markets() {
+            state.funding.insert(
+                symbol.clone(),
+                FundingSnapshot {
+                    symbol: symbol.clone(),
+                    rate: market.rate.to_string(),
+                    mark_price: market.mark_price.to_string(),
+                    index_price: market.index_price.to_string(),
+                    last_funding_time: market.last_funding_time,
+                    updated_at: market.updated_at,
+                },
+            );
+        }
+        for (symbol, source, price) in self.mark_prices().sources() {
+            state.mark_sources.insert(
+                format!("{}:{:?}", symbol, source),
+                MarkSourceSnapshot {
+                    symbol: symbol.to_string(),
+                    source: format!("{:?}", source),
+                    price: price.price.to_string(),
+                    updated_at: price.updated_at,
+                },
+            );
+        }
+        for (asset, balance) in self.insurance_fund().balances() {
+            state.insurance_fund.insert(asset.clone(), balance.to_string());
+        }
+        state
+    }
+
+    /// Rebuild a risk engine from exported state.
+    pub fn from_snapshot(config: RiskEngineConfig, state: &RiskState) -> Result<Self, RestoreError> {
+        let mut engine = RiskEngine::with_config(config);
+
+        let mut accounts: Vec<&MarginSnapshot> = state.margin.values().collect();
+        accounts.sort_by_key(|account| account.monitor_order);
+        for account in accounts {
+            let account_id = parse_account_id(&account.account_id)?;
+            let positions = account
+                .positions
+                .iter()
+                .map(|(symbol, snapshot)| Ok((symbol.clone(), restore_position(account_id, snapshot)?)))
+                .collect::<Result<BTreeMap<_, _>, RestoreError>>()?;
+            engine.liquidation_monitor_mut().restore_account(
+                account_id,
+                parse_decimal(&account.account_id, &account.collateral)?,
+                positions,
+                account.liquidating,
+            );
+        }
+        for (symbol, price) in &state.marks {
+            engine.liquidation_monitor_mut().restore_mark(symbol.clone(), parse_price(symbol, price)?);
+        }
+        for (symbol, market) in &state.funding {
+            engine.funding_mut().restore_market(
+                symbol.clone(),
+                MarketFunding {
+                    rate: parse_decimal(symbol, &market.rate)?,
+                    mark_price: parse_price(symbol, &market.mark_price)?,
+                    index_price: parse_price(symbol, &market.index_price)?,
+                    last_funding_time: market.last_funding_time,
+                    updated_at: market.updated_at,
+                },
+            );
+        }
+        for (key, source) in &state.mark_sources {
+            let kind = match source.source.as_str() {
+                "Mid" => PriceSource::Mid,
+                "LastTrade" => PriceSource::LastTrade,
+                "Index" => PriceSource::Index,
+                other => return Err(invalid(key, format!("unknown price source {}", other))),
+            };
+            engine.update_mark_source(&source.symbol, kind, parse_price(key, &source.price)?, source.updated_at);
+        }
+        let balances = state
+            .insurance_fund
+            .iter()
+            .map(|(asset, balance)| Ok((asset.clone(), parse_decimal(asset, balance)?)))
+            .collect::<Result<BTreeMap<_, _>, RestoreError>>()?;
+        engine.restore_insurance_fund(InsuranceFund::from_balances(balances));
+
+        Ok(engine)
+    }
+}
+
+/// Replays journaled risk inputs into the `risk` section of engine state
+pub struct RiskEventApplier {
+    config: RiskEngineConfig,
+}
+
+impl RiskEventApplier {
+    pub fn new(config: RiskEngineConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl TypedEventApplier for RiskEventApplier {
+    fn registry(&self) -> &EventRegistry {
+        event_registry()
+    }
+
+    fn apply_typed(&self, state: &mut EngineState, entry: &JournalEntry, event: TypedEvent) -> Result<(), String> {
+        let Ok(input) = event.downcast::<Correlated<RiskInput>>() else {
+            return Ok(());
+        };
+        let mut engine = RiskEngine::from_snapshot(self.config.clone(), &state.risk).map_err(|e| e.to_string())?;
+        engine.apply_input(&input.event, entry.timestamp);
+        state.risk = engine.to_snapshot();
+        Ok(())
+    }
+}
+
+fn position_snapshot(position: &Position) -> MarginPositionSnapshot {
+    MarginPositionSnapshot {
+        position_id: position.position_id.to_string(),
+        symbol: position.symbol.as_str().to_string(),
+        side: format!("{:?}", position.side),
+        size: position.size.to_string(),
+        entry_price: position.entry_price.to_string(),
+        mark_price: position.mark_price.to_string(),
+        liquidation_price: position.liquidation_price.to_string(),
+        realized_pnl: position.realized_pnl.to_string(),
+        unrealized_pnl: position.unrealized_pnl.to_string(),
+        initial_margin: position.initial_margin.to_string(),
+        maintenance_margin: position.maintenance_margin.to_string(),
+        leverage: position.leverage,
+        opened_at: position.opened_at,
+        updated_at: position.updated_at,
+        version: position.version,
+    }
+}
+
+fn restore_position(account_id: AccountId, snapshot: &MarginPositionSnapshot) -> Result<Position, RestoreError> {
+    let key = &snapshot.position_id;
+    let side = match snapshot.side.as_str() {
+        "LONG" => PositionSide::LONG,
+        "SHORT" => PositionSide::SHORT,
+        other => return Err(invalid(key, format!("unknown side {}", other))),
+    };
+    if !snapshot.symbol.contains('/') {
+        return Err(invalid(key, format!("malformed symbol {}", snapshot.symbol)));
+    }
+    let size = Quantity::try_new(parse_decimal(key, &snapshot.size)?)
+        .ok_or_else(|| invalid(key, format!("non-positive size {}", snapshot.size)))?;
+    Ok(Position {
+        position_id: Uuid::parse_str(key).map_err(|e| invalid(key, e.to_string()))?,
+        account_id,
+        symbol: MarketId::new(&snapshot.symbol),
+        side,
+        size,
+        entry_price: parse_price(key, &snapshot.entry_price)?,
+        mark_price: parse_price(key, &snapshot.mark_price)?,
+        liquidation_price: parse_price(key, &snapshot.liquidation_price)?,
+        realized_pnl: parse_decimal(key, &snapshot.realized_pnl)?,
+        unrealized_pnl: parse_decimal(key, &snapshot.unrealized_pnl)?,
+        initial_margin: parse_decimal(key, &snapshot.initial_margin)?,
+        maintenance_margin: parse_decimal(key, &snapshot.maintenance_margin)?,
+        leverage: snapshot.leverage,
+        opened_at: snapshot.opened_at,
+        updated_at: snapshot.updated_at,
+        version: snapshot.version,
+    })
+}
+
+fn invalid(key: &str, reason: String) -> RestoreError {
+    RestoreError::InvalidSnapshot { key: key.to_string(), reason }
+}
+
+fn parse_account_id(value: &str) -> Result<AccountId, RestoreError> {
+    Uuid::parse_str(value)
+        .map(AccountId::from_uuid)
+        .map_err(|e| invalid(value, e.to_string()))
+}
+
+fn parse_decimal(key: &str, value: &str) -> Result<Decimal, RestoreError> {
+    Decimal::from_str(value).map_err(|e| invalid(key, format!("{}: {}", value, e)))
+}
+
+fn parse_price(key: &str, value: &str) -> Result<Price, RestoreError> {
+    Price::try_new(parse_decimal(key, value)?).ok_or_else(|| invalid(key, format!("non-positive price {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use persistence::journal::{JournalConfig, JournalWriter};
+    use persistence::recovery::RecoveryEngine;
+    use persistence::snapshot::{Snapshot, SnapshotWriter};
+    use tempfile::TempDir;
+
+    const T0: i64 = 1708123456789000000;
+    const SECOND: i64 = 1_000_000_000;
+
+    fn position(account_id: AccountId, symbol: &str, side: PositionSide, size: &str, entry: u64, mm: i64) -> Position {
+        Position::new(
+            account_id,
+            MarketId::new(symbol),
+            side,
+            Quantity::from_str(size).unwrap(),
+            Price::from_u64(entry),
+            Price::from_u64(entry),
+            Price::from_u64(1),
+            Decimal::from(mm),
+            Decimal::from(mm),
+            10,
+            T0,
+        )
+    }
+
+    /// Fills across two markets, price moves that start and clear a
+    /// liquidation, funding, and insurance fund flows
+    fn stream() -> Vec<(i64, RiskInput)> {
+        let accounts: Vec<AccountId> = (0..6).map(|_| AccountId::new()).collect();
+        let mut inputs = vec![(T0, RiskInput::InsuranceDeposit { asset: "USDT".into(), amount: Decimal::from(10_000) })];
+        for (i, account_id) in accounts.iter().enumerate() {
+            let (symbol, entry, size, mm) = if i % 2 == 0 { ("BTC/USDT", 50_000, "1.0", 5_000) } else { ("ETH/USDT", 3_000, "10.0", 1_500) };
+            let side = if i % 3 == 0 { PositionSide::SHORT } else { PositionSide::LONG };
+            inputs.push((
+                T0 + i as i64,
+                RiskInput::Fill {
+                    position: position(*account_id, symbol, side, size, entry, mm),
+                    collateral: Decimal::from(6_000 + 500 * i as i64),
+                },
+            ));
+        }
+        for (step, (btc, eth)) in [(49_000, 2_900), (48_000, 2_950), (50_500, 3_050), (51_000, 2_800)].into_iter().enumerate() {
+            let ts = T0 + (step as i64 + 1) * SECOND;
+            for source in [PriceSource::Mid, PriceSource::LastTrade, PriceSource::Index] {
+                inputs.push((ts, RiskInput::MarkSource { symbol: "BTC/USDT".into(), source, price: Price::from_u64(btc) }));
+                inputs.push((ts, RiskInput::MarkSource { symbol: "ETH/USDT".into(), source, price: Price::from_u64(eth) }));
+            }
+            inputs.push((
+                ts,
+                RiskInput::FundingRate {
+                    symbol: "BTC/USDT".into(),
+                    mark_price: Price::from_u64(btc),
+                    index_price: Price::from_u64(50_000),
+                },
+            ));
+        }
+        inputs.push((
+            T0 + 10 * SECOND,
+            RiskInput::LiquidationSettled(LiquidationClose {
+                account_id: accounts[1],
+                symbol: "ETH/USDT".into(),
+                asset: "USDT".into(),
+                side: PositionSide::LONG,
+                size: Quantity::from_str("10.0").unwrap(),
+                bankruptcy_price: Price::from_u64(2_400),
+                close_price: Price::from_u64(2_350),
+                fee: Decimal::ZERO,
+            }),
+        ));
+        inputs.push((
+            T0 + 11 * SECOND,
+            RiskInput::Fill {
+                position: {
+                    let mut closed = position(accounts[2], "BTC/USDT", PositionSide::LONG, "1.0", 50_000, 5_000);
+                    closed.size = Quantity::zero();
+                    closed
+                },
+                collateral: Decimal::from(6_250),
+            },
+        ));
+        inputs
+    }
+
+    fn replay(engine: &mut RiskEngine, inputs: &[(i64, RiskInput)]) -> Vec<RiskEvent> {
+        inputs.iter().flat_map(|(ts, input)| engine.apply_input(input, *ts)).collect()
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_is_exact() {
+        let inputs = stream();
+        let mut engine = RiskEngine::new();
+        replay(&mut engine, &inputs);
+
+        let state = engine.to_snapshot();
+        let restored = RiskEngine::from_snapshot(RiskEngineConfig::default(), &state).unwrap();
+        assert_eq!(restored.to_snapshot(), state);
+        assert_eq!(restored.to_snapshot().compute_hash(), state.compute_hash());
+    }
+
+    #[test]
+    fn test_snapshot_then_replay_equals_pure_replay() {
+        let inputs = stream();
+        let mut pure = RiskEngine::new();
+        let pure_events = replay(&mut pure, &inputs);
+        assert!(!pure_events.is_empty());
+
+        for split in [1, inputs.len() / 3, inputs.len() / 2, inputs.len() - 1] {
+            let mut first = RiskEngine::new();
+            let mut events = replay(&mut first, &inputs[..split]);
+            let mut resumed = RiskEngine::from_snapshot(RiskEngineConfig::default(), &first.to_snapshot()).unwrap();
+            events.extend(replay(&mut resumed, &inputs[split..]));
+
+            assert_eq!(resumed.to_snapshot().compute_hash(), pure.to_snapshot().compute_hash(), "split at {}", split);
+            assert_eq!(resumed.accounts_at_risk(crate::liquidation::HealthLevel::Warning), pure.accounts_at_risk(crate::liquidation::HealthLevel::Warning));
+            let types = |events: &[RiskEvent]| events.iter().map(|e| (e.account_id, e.event_type.clone())).collect::<Vec<_>>();
+            assert_eq!(types(&events), types(&pure_events));
+        }
+    }
+
+    #[test]
+    fn test_recovery_engine_rebuilds_risk_state() {
+        let tmp = TempDir::new().unwrap();
+        let snap_dir = tmp.path().join("snapshots");
+        let journal_dir = tmp.path().join("journal");
+        let inputs = stream();
+        let split = inputs.len() / 2;
+
+        let mut writer = JournalWriter::open(JournalConfig::new(&journal_dir)).unwrap();
+        writer.set_next_sequence(1);
+        for (i, (ts, input)) in inputs.iter().enumerate() {
+            writer.append(&journal_entry(i as u64 + 1, *ts, input)).unwrap();
+        }
+        // Entries for other consumers are skipped
+        writer
+            .append(&JournalEntry::new(inputs.len() as u64 + 1, T0 + 12 * SECOND, "OrderSubmitted".into(), vec![1, 2, 3]))
+            .unwrap();
+        writer.sync().unwrap();
+
+        let mut pure = RiskEngine::new();
+        replay(&mut pure, &inputs);
+        let applier = RiskEventApplier::new(RiskEngineConfig::default());
+
+        let (state, metrics) = RecoveryEngine::new(&snap_dir, &journal_dir).recover_without_validation(&applier).unwrap();
+        assert_eq!(metrics.replay_count, inputs.len() as u64 + 1);
+        assert_eq!(state.risk, pure.to_snapshot());
+
+        let mut first = RiskEngine::new();
+        replay(&mut first, &inputs[..split]);
+        let mut snap_state = EngineState::empty();
+        snap_state.risk = first.to_snapshot();
+        SnapshotWriter::new(&snap_dir, false)
+            .write(&Snapshot::new(split as u64, inputs[split - 1].0, snap_state, false))
+            .unwrap();
+
+        let (state, metrics) = RecoveryEngine::new(&snap_dir, &journal_dir).recover_without_validation(&applier).unwrap();
+        assert_eq!(metrics.snapshot_sequence, split as u64);
+        assert_eq!(state.risk.compute_hash(), pure.to_snapshot().compute_hash());
+        let recovered = RiskEngine::from_snapshot(RiskEngineConfig::default(), &state.risk).unwrap();
+        assert_eq!(recovered.insurance_fund_balance("USDT"), pure.insurance_fund_balance("USDT"));
+    }
+
+    #[test]
+    fn test_correlation_id_reaches_raised_events() {
+        let inputs = stream();
+        let mut engine = RiskEngine::new();
+        let mut raised = Vec::new();
+        for (seq, (ts, input)) in inputs.iter().enumerate() {
+            let entry = correlated_journal_entry(seq as u64 + 1, *ts, input, Some("req-7"));
+            let decoded = decode_correlated(&entry).unwrap().unwrap();
+            assert_eq!(decoded.correlation_id.as_deref(), Some("req-7"));
+            raised.extend(engine.apply_correlated(&decoded, *ts));
+        }
+        assert!(!raised.is_empty());
+        assert!(raised.iter().all(|e| e.correlation_id.as_deref() == Some("req-7")));
+
+        // Inputs journaled before the id decode without one
+        let input = RiskInput::InsuranceDeposit { asset: "USDT".into(), amount: Decimal::ONE };
+        let payload = bincode::serialize(&input).unwrap();
+        let bare = JournalEntry::new(1, T0, "RiskInsuranceDeposit".into(), payload);
+        assert_eq!(decode_correlated(&bare).unwrap(), Some(Correlated::uncorrelated(input)));
+    }
+
+    #[test]
+    fn test_invalid_snapshot_is_reported() {
+        let mut state = RiskState::default();
+        state.insurance_fund.insert("USDT".into(), "lots".into());
+        assert!(matches!(
+            RiskEngine::from_snapshot(RiskEngineConfig::default(), &state),
+            Err(RestoreError::InvalidSnapshot { key, .. }) if key == "USDT"
+        ));
+
+        let entry = JournalEntry::new(1, T0, "RiskFill".into(), vec![0xff]);
+        assert!(matches!(decode_input(&entry), Err(RestoreError::Decode { sequence: 1, .. })));
+
+        // A payload journaled under another input's event type
+        let mut entry = journal_entry(2, T0, &RiskInput::InsuranceDeposit { asset: "USDT".into(), amount: Decimal::ONE });
+        entry.event_type = "RiskFill".into();
+        assert!(matches!(
+            decode_input(&entry),
+            Err(RestoreError::Decode { reason, .. }) if reason == "payload is RiskInsuranceDeposit"
+        ));
+    }
+}
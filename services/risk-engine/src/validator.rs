@@ -3,13 +3,18 @@
 //! Validates incoming orders against margin requirements, leverage limits,
 //! and position limits per specs §5.3.1, §5.4, and §9.3.6.
 
+use std::collections::BTreeMap;
+
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use types::account::{Account, AccountType};
-use types::order::Order;
+use types::market::{MarketConfig, MarketStatus};
+use types::order::{Order, Side};
 use types::position::Position;
 use types::risk::RiskCheckResult;
 
-use crate::exposure;
+use crate::exposure::{self, CurrentExposure};
 use crate::margin;
 
 /// Maximum position size per account tier per spec §5.14.2
@@ -78,6 +83,142 @@ pub fn validate_order(
     RiskCheckResult::Pass
 }
 
+/// Validate an order against its market's trading status and increments,
+/// then run the full risk checks in [`validate_order`].
+///
+/// Status and increments are checked first so no margin math runs for
+/// orders the market would refuse. Markets without a config skip the
+/// increment checks.
+pub fn validate_order_in_market(
+    account: &Account,
+    order: &Order,
+    positions: &[Position],
+    status: MarketStatus,
+    config: Option<&MarketConfig>,
+) -> RiskCheckResult {
+    if status.check_new_order().is_err() {
+        return RiskCheckResult::MarketUnavailable { status };
+    }
+    if let Some(Err(violation)) = config.map(|config| config.check_order(order)) {
+        return RiskCheckResult::MarketConfigViolated { violation };
+    }
+    validate_order(account, order, positions)
+}
+
+// ── Size and notional limits ─────────────────────────────────────────────
+
+/// Size and notional caps for one market; `None` leaves a cap off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarketLimits {
+    /// Largest absolute net position after the order fills, in base units
+    pub max_position_size: Option<Decimal>,
+    /// Largest single-order quantity, in base units
+    pub max_order_quantity: Option<Decimal>,
+    /// Largest single-order notional
+    pub max_order_notional: Option<Decimal>,
+    /// Largest notional of working orders on one side, this order included
+    pub max_open_order_notional: Option<Decimal>,
+}
+
+/// Pre-trade limits: per-market caps plus an account-wide open-order cap.
+///
+/// A market without its own entry uses `default`. Every cap is inclusive:
+/// an order exactly at a limit passes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskLimits {
+    pub default: MarketLimits,
+    pub markets: BTreeMap<String, MarketLimits>,
+    /// Largest notional of working orders across all markets, this order included
+    pub max_account_open_order_notional: Option<Decimal>,
+}
+
+/// Why an order was refused by [`RiskLimits::validate_order`].
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskRejection {
+    #[error("Account is not active")]
+    AccountInactive,
+
+    #[error("Order quantity {requested} exceeds limit {limit}")]
+    OrderQuantityExceeded { limit: Decimal, requested: Decimal },
+
+    #[error("Order notional {requested} exceeds limit {limit}")]
+    OrderNotionalExceeded { limit: Decimal, requested: Decimal },
+
+    #[error("Resulting position {resulting} exceeds limit {limit}")]
+    PositionSizeExceeded { limit: Decimal, resulting: Decimal },
+
+    #[error("Open {side:?} order notional {resulting} exceeds limit {limit}")]
+    OpenOrderNotionalExceeded { side: Side, limit: Decimal, resulting: Decimal },
+
+    #[error("Account open order notional {resulting} exceeds limit {limit}")]
+    AccountOpenOrderNotionalExceeded { limit: Decimal, resulting: Decimal },
+
+    #[error("{asset} exposure at {fraction_of_equity}× equity exceeds limit {limit}×")]
+    ConcentrationLimitExceeded { asset: String, limit: Decimal, fraction_of_equity: Decimal },
+}
+
+impl RiskLimits {
+    /// Caps for a market.
+    pub fn for_market(&self, symbol: &str) -> &MarketLimits {
+        self.markets.get(symbol).unwrap_or(&self.default)
+    }
+
+    /// Check an order against the account's limits before it reaches matching.
+    ///
+    /// Checks performed (in order):
+    /// 1. Account is active
+    /// 2. Order quantity and notional
+    /// 3. Net position after a full fill
+    /// 4. Same-side open-order notional in the market, then account-wide
+    pub fn validate_order(
+        &self,
+        account: &Account,
+        order: &Order,
+        current_exposure: &CurrentExposure,
+    ) -> Result<(), RiskRejection> {
+        if !account.is_active() {
+            return Err(RiskRejection::AccountInactive);
+        }
+
+        let symbol = order.symbol.as_str();
+        let limits = self.for_market(symbol);
+        let quantity = order.remaining_quantity.as_decimal();
+        let notional = quantity * order.price.as_decimal();
+
+        if let Some(limit) = limits.max_order_quantity.filter(|limit| quantity > *limit) {
+            return Err(RiskRejection::OrderQuantityExceeded { limit, requested: quantity });
+        }
+        if let Some(limit) = limits.max_order_notional.filter(|limit| notional > *limit) {
+            return Err(RiskRejection::OrderNotionalExceeded { limit, requested: notional });
+        }
+
+        if let Some(limit) = limits.max_position_size {
+            let signed = match order.side {
+                Side::BUY => quantity,
+                Side::SELL => -quantity,
+            };
+            let resulting = (current_exposure.net_position(symbol) + signed).abs();
+            if resulting > limit {
+                return Err(RiskRejection::PositionSizeExceeded { limit, resulting });
+            }
+        }
+
+        if let Some(limit) = limits.max_open_order_notional {
+            let resulting = current_exposure.open_order_notional(symbol, order.side) + notional;
+            if resulting > limit {
+                return Err(RiskRejection::OpenOrderNotionalExceeded { side: order.side, limit, resulting });
+            }
+        }
+        if let Some(limit) = self.max_account_open_order_notional {
+            let resulting = current_exposure.total_open_order_notional() + notional;
+            if resulting > limit {
+                return Err(RiskRejection::AccountOpenOrderNotionalExceeded { limit, resulting });
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Check collateral sufficiency only (simpler check).
 ///
 /// Used for quick balance verification without full validation.
@@ -135,6 +276,7 @@ mod tests {
     use super::*;
     use types::account::{Account, AccountStatus, AccountType, Balance};
     use types::ids::{AccountId, MarketId};
+    use types::market::MarketConfigViolation;
     use types::numeric::{Price, Quantity};
     use types::order::{Order, Side, TimeInForce};
     use types::position::{Position, PositionSide};
@@ -186,6 +328,174 @@ mod tests {
         assert!(matches!(result, RiskCheckResult::InsufficientMargin { .. }));
     }
 
+    #[test]
+    fn test_validate_order_market_status_checked_first() {
+        // Insufficient margin too, but the closed market is reported
+        let account = make_account(100);
+        let order = make_order(account.account_id, 50_000, "1.0");
+        let result = validate_order_in_market(&account, &order, &[], MarketStatus::CancelOnly, None);
+        assert_eq!(
+            result,
+            RiskCheckResult::MarketUnavailable { status: MarketStatus::CancelOnly }
+        );
+
+        let result = validate_order_in_market(&account, &order, &[], MarketStatus::Trading, None);
+        assert!(matches!(result, RiskCheckResult::InsufficientMargin { .. }));
+    }
+
+    #[test]
+    fn test_validate_order_increments_before_margin() {
+        let account = make_account(100);
+        let config = MarketConfig {
+            tick_size: Decimal::from_str_exact("0.5").unwrap(),
+            lot_size: Decimal::from_str_exact("0.01").unwrap(),
+            min_notional: Decimal::from(10),
+        };
+        let mut order = make_order(account.account_id, 50_000, "1.0");
+        order.price = Price::from_str("50000.25").unwrap();
+        let result = validate_order_in_market(&account, &order, &[], MarketStatus::Trading, Some(&config));
+        assert!(matches!(
+            result,
+            RiskCheckResult::MarketConfigViolated { violation: MarketConfigViolation::PriceNotOnTick { .. } }
+        ));
+
+        let order = make_order(account.account_id, 50_000, "1.005");
+        let result = validate_order_in_market(&account, &order, &[], MarketStatus::Trading, Some(&config));
+        assert!(matches!(
+            result,
+            RiskCheckResult::MarketConfigViolated { violation: MarketConfigViolation::QuantityNotOnLot { .. } }
+        ));
+
+        // On the grid, the margin shortfall is reported
+        let order = make_order(account.account_id, 50_000, "1.0");
+        let result = validate_order_in_market(&account, &order, &[], MarketStatus::Trading, Some(&config));
+        assert!(matches!(result, RiskCheckResult::InsufficientMargin { .. }));
+    }
+
+    // ── size and notional limits ──
+
+    fn make_sell(account_id: AccountId, price: u64, qty: &str) -> Order {
+        let mut order = make_order(account_id, price, qty);
+        order.side = Side::SELL;
+        order
+    }
+
+    #[test]
+    fn test_order_size_limits_at_and_over() {
+        let account = make_account(100_000);
+        let limits = RiskLimits {
+            default: MarketLimits {
+                max_order_quantity: Some(Decimal::from(2)),
+                max_order_notional: Some(Decimal::from(60_000)),
+                ..MarketLimits::default()
+            },
+            ..RiskLimits::default()
+        };
+        let exposure = CurrentExposure::new();
+
+        let at_limit = make_order(account.account_id, 30_000, "2");
+        assert_eq!(limits.validate_order(&account, &at_limit, &exposure), Ok(()));
+
+        let over = make_order(account.account_id, 30_000, "2.00000001");
+        assert_eq!(
+            limits.validate_order(&account, &over, &exposure),
+            Err(RiskRejection::OrderQuantityExceeded {
+                limit: Decimal::from(2),
+                requested: Decimal::from_str_exact("2.00000001").unwrap(),
+            })
+        );
+
+        let over = make_order(account.account_id, 30_001, "2");
+        assert!(matches!(
+            limits.validate_order(&account, &over, &exposure),
+            Err(RiskRejection::OrderNotionalExceeded { requested, .. }) if requested == Decimal::from(60_002)
+        ));
+    }
+
+    #[test]
+    fn test_position_limit_counts_existing_position() {
+        let account = make_account(100_000);
+        let mut limits = RiskLimits::default();
+        limits.markets.insert(
+            "BTC/USDT".to_string(),
+            MarketLimits { max_position_size: Some(Decimal::from(3)), ..MarketLimits::default() },
+        );
+        let mut exposure = CurrentExposure::new();
+        exposure.set_position(Position::new(
+            account.account_id,
+            MarketId::new("BTC/USDT"),
+            PositionSide::LONG,
+            Quantity::from_str("2").unwrap(),
+            Price::from_u64(50_000),
+            Price::from_u64(50_000),
+            Price::from_u64(45_000),
+            Decimal::from(10_000),
+            Decimal::from(500),
+            10,
+            1708123456789000000,
+        ));
+
+        let at_limit = make_order(account.account_id, 50_000, "1");
+        assert_eq!(limits.validate_order(&account, &at_limit, &exposure), Ok(()));
+        let over = make_order(account.account_id, 50_000, "1.00000001");
+        assert!(matches!(
+            limits.validate_order(&account, &over, &exposure),
+            Err(RiskRejection::PositionSizeExceeded { .. })
+        ));
+        // Selling reduces the long: 2 − 5 = −3 is at the limit
+        let flip = make_sell(account.account_id, 50_000, "5");
+        assert_eq!(limits.validate_order(&account, &flip, &exposure), Ok(()));
+    }
+
+    #[test]
+    fn test_open_order_notional_counts_resting_same_side() {
+        let account = make_account(100_000);
+        let limits = RiskLimits {
+            default: MarketLimits {
+                max_open_order_notional: Some(Decimal::from(100_000)),
+                ..MarketLimits::default()
+            },
+            max_account_open_order_notional: Some(Decimal::from(160_000)),
+            ..RiskLimits::default()
+        };
+        let mut exposure = CurrentExposure::new();
+        exposure.add_order(&make_order(account.account_id, 50_000, "1.5"));
+        exposure.add_order(&make_sell(account.account_id, 51_000, "1"));
+
+        // 75 000 resting bids + 25 000 = 100 000: exactly at the cap
+        let at_limit = make_order(account.account_id, 50_000, "0.5");
+        assert_eq!(limits.validate_order(&account, &at_limit, &exposure), Ok(()));
+
+        // Well inside every cap alone; with the resting bids it is just over
+        let over = make_order(account.account_id, 50_001, "0.5");
+        assert_eq!(
+            limits.validate_order(&account, &over, &exposure),
+            Err(RiskRejection::OpenOrderNotionalExceeded {
+                side: Side::BUY,
+                limit: Decimal::from(100_000),
+                resulting: Decimal::from_str_exact("100000.5").unwrap(),
+            })
+        );
+
+        // Resting sells do not count against the bid side, but do count account-wide
+        let sell = make_sell(account.account_id, 50_000, "0.8");
+        assert!(matches!(
+            limits.validate_order(&account, &sell, &exposure),
+            Err(RiskRejection::AccountOpenOrderNotionalExceeded { resulting, .. }) if resulting == Decimal::from(166_000)
+        ));
+    }
+
+    #[test]
+    fn test_limits_reject_inactive_account() {
+        let mut account = make_account(100_000);
+        account.status = AccountStatus::SUSPENDED;
+        let order = make_order(account.account_id, 50_000, "0.1");
+        assert_eq!(
+            RiskLimits::default().validate_order(&account, &order, &CurrentExposure::new()),
+            Err(RiskRejection::AccountInactive)
+        );
+    }
+
     #[test]
     fn test_check_collateral_pass() {
         let result = check_collateral(Decimal::from(10_000), Decimal::from(5_000));
@@ -0,0 +1,111 @@
+//! Server/client margin parity
+//!
+//! Feeds the same account snapshot through the risk engine's
+//! `CrossMarginMonitor` and the client-side `wasm_core::margin` engine and
+//! requires bit-identical margin ratios and risk levels.
+
+use std::collections::BTreeMap;
+
+use risk_engine::cross_margin::CrossMarginMonitor;
+use rust_decimal::Decimal;
+use types::account::{Account, AccountType, Balance};
+use types::ids::{AccountId, MarketId};
+use types::numeric::{Price, Quantity};
+use types::position::{Position, PositionSide};
+use wasm_core::margin::{CrossMarginEngine, RiskLevel};
+
+const TS: i64 = 1708123456789000000;
+
+fn dec(s: &str) -> Decimal {
+    Decimal::from_str_exact(s).unwrap()
+}
+
+fn position(
+    account_id: AccountId,
+    symbol: &str,
+    side: PositionSide,
+    size: &str,
+    entry: &str,
+    leverage: u8,
+) -> Position {
+    let entry = dec(entry);
+    let size_d = dec(size);
+    Position::new(
+        account_id,
+        MarketId::new(symbol),
+        side,
+        Quantity::from_str(size).unwrap(),
+        Price::new(entry),
+        Price::new(entry),
+        Price::new(entry / Decimal::TWO),
+        entry * size_d / Decimal::from(leverage),
+        Decimal::ZERO,
+        leverage,
+        TS,
+    )
+}
+
+fn snapshot() -> (Account, Vec<Position>) {
+    let mut account = Account::new(AccountType::FUTURES, TS);
+    account.set_balance(Balance::new("USDT", dec("4210.33")), TS);
+    account.set_balance(Balance::new("USDC", dec("1789.6712")), TS);
+    let id = account.account_id;
+    let positions = vec![
+        position(id, "BTC/USDT", PositionSide::LONG, "0.37", "51234.5", 20),
+        position(id, "ETH/USDT", PositionSide::SHORT, "4.125", "3012.77", 50),
+        position(id, "SOL/USDT", PositionSide::LONG, "61.3", "98.431", 5),
+    ];
+    (account, positions)
+}
+
+fn server_path(account: &Account, positions: &[Position], marks: &BTreeMap<String, Price>) -> (Decimal, RiskLevel) {
+    let mut monitor = CrossMarginMonitor::new(marks.clone());
+    for pos in positions {
+        monitor.store_mut().upsert(pos.clone());
+    }
+    (monitor.margin_ratio(account), monitor.risk_level(account))
+}
+
+fn client_path(account: &Account, positions: &[Position], marks: &BTreeMap<String, Price>) -> (Decimal, RiskLevel) {
+    let balance: Decimal = account.balances.values().map(|b| b.total).sum();
+    let mut engine = CrossMarginEngine::new(account.account_id, balance);
+    for pos in positions {
+        let mut marked = pos.clone();
+        marked.mark_price = marks[pos.symbol.as_str()];
+        engine.add_position(marked);
+    }
+    (engine.margin_ratio(), engine.risk_level())
+}
+
+#[test]
+fn test_server_and_client_agree_bit_for_bit() {
+    let (account, positions) = snapshot();
+    let mut seen = Vec::new();
+
+    // Sweep BTC down and ETH up so the account walks through every level
+    for step in 0..=48u32 {
+        let s = Decimal::from(step);
+        let mut marks = BTreeMap::new();
+        marks.insert("BTC/USDT".to_string(), Price::new(dec("51234.5") - s * dec("173.25")));
+        marks.insert("ETH/USDT".to_string(), Price::new(dec("3012.77") + s * dec("9.0401")));
+        marks.insert("SOL/USDT".to_string(), Price::new(dec("98.431") - s * dec("0.37")));
+
+        let (server_ratio, server_level) = server_path(&account, &positions, &marks);
+        let (client_ratio, client_level) = client_path(&account, &positions, &marks);
+
+        assert_eq!(
+            server_ratio.serialize(),
+            client_ratio.serialize(),
+            "step {step}: server {server_ratio} vs client {client_ratio}"
+        );
+        assert_eq!(server_level, client_level, "step {step}");
+        if !seen.contains(&server_level) {
+            seen.push(server_level);
+        }
+    }
+
+    assert_eq!(
+        seen,
+        vec![RiskLevel::Healthy, RiskLevel::Warning, RiskLevel::Danger, RiskLevel::Liquidation]
+    );
+}
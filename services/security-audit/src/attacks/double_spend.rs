@@ -1,11 +1,25 @@
 //! Double spend attack simulation.
 //! Simulates concurrent attempts to withdraw and place an order using the same balance,
 //! verifying that optimistic locking prevents negative balances.
-
+//!
+//! The cross-path harness races the trading path (risk engine margin locks) against
+//! the withdrawal path (vault withdrawal queue) over a seeded schedule of
+//! interleavings. Both paths consult a [`BalanceAuthority`]; the attack measures
+//! whether locked trading collateral plus approved withdrawals can ever exceed the
+//! account balance.
+
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
+use contracts::vault::Vault;
+use contracts::withdrawal::WithdrawalQueue;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use types::account::Account;
+use types::account::{Account, AccountType, Balance};
 use types::ids::AccountId;
 
 /// Simulates a data store that enforces optimistic locking on Account updates.
@@ -108,10 +122,483 @@ pub fn handle_withdrawal(
     store.update(current_account)
 }
 
+/// Source of truth for account balance locks.
+///
+/// Both the trading path and the withdrawal path must consult the same authority;
+/// a stale view must be rejected on commit so that concurrent reservations
+/// against the same balance serialize.
+pub trait BalanceAuthority: Send + Sync {
+    /// Read the current view of an account.
+    fn snapshot(&self, account_id: &AccountId) -> Option<Account>;
+
+    /// Commit a mutated view. Rejects views with a stale version.
+    fn commit(&self, account: Account) -> Result<(), StoreError>;
+}
+
+impl BalanceAuthority for AccountStore {
+    fn snapshot(&self, account_id: &AccountId) -> Option<Account> {
+        self.get(account_id)
+    }
+
+    fn commit(&self, account: Account) -> Result<(), StoreError> {
+        self.update(account)
+    }
+}
+
+/// Which lock sources the two paths consult.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockTopology {
+    /// Trading and withdrawal paths share one `BalanceAuthority`.
+    Shared,
+    /// Each path keeps its own view of the balance (no shared lock source).
+    Split,
+}
+
+/// A single racing operation against the attacked account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpendOp {
+    /// Place an order; margin is computed with the risk engine's §5.3.1 formula.
+    Order { quantity: Decimal, price: Decimal },
+    /// Request a withdrawal of `amount`.
+    Withdrawal { amount: Decimal },
+}
+
+/// Parameters for the cross-path double spend attack.
+#[derive(Debug, Clone)]
+pub struct DoubleSpendConfig {
+    /// Seed for the interleaving scheduler.
+    pub seed: u64,
+    /// Number of distinct interleavings to execute.
+    pub schedules: usize,
+    /// Asset under attack.
+    pub asset: String,
+    /// Account balance X.
+    pub balance: Decimal,
+    /// Orders whose margin requirement totals X.
+    pub orders: usize,
+    /// Withdrawals whose amounts total X.
+    pub withdrawals: usize,
+    /// Order price used to size the margin requirement.
+    pub price: Decimal,
+    /// Leverage applied by the trading path.
+    pub leverage: u8,
+}
+
+impl Default for DoubleSpendConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x5eed,
+            schedules: 64,
+            asset: "USDC".to_string(),
+            balance: Decimal::from(1_000),
+            orders: 4,
+            withdrawals: 4,
+            price: Decimal::from(50_000),
+            leverage: 10,
+        }
+    }
+}
+
+impl DoubleSpendConfig {
+    /// Build the racing operations: margin totals X and withdrawals total X.
+    pub fn operations(&self) -> Vec<SpendOp> {
+        let mut ops = Vec::with_capacity(self.orders + self.withdrawals);
+
+        let margin_per_order = self.balance / Decimal::from(self.orders as u64);
+        let quantity = margin_per_order * Decimal::from(self.leverage) / self.price;
+        for _ in 0..self.orders {
+            ops.push(SpendOp::Order {
+                quantity,
+                price: self.price,
+            });
+        }
+
+        let per_withdrawal = self.balance / Decimal::from(self.withdrawals as u64);
+        for _ in 0..self.withdrawals {
+            ops.push(SpendOp::Withdrawal {
+                amount: per_withdrawal,
+            });
+        }
+        ops
+    }
+}
+
+/// Outcome of a single interleaving.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleOutcome {
+    /// Total margin locked by accepted orders.
+    pub locked_collateral: Decimal,
+    /// Total amount of approved withdrawals.
+    pub approved_withdrawals: Decimal,
+    /// Amount by which locks plus withdrawals exceed the balance (zero if sound).
+    pub over_allocation: Decimal,
+    /// Commits rejected with a version conflict and retried.
+    pub conflicts: u64,
+}
+
+/// Aggregate report across all seeded interleavings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoubleSpendReport {
+    pub topology: LockTopology,
+    pub seed: u64,
+    pub schedules_run: usize,
+    /// Interleavings in which the balance was over-allocated.
+    pub over_allocated_schedules: usize,
+    /// Largest over-allocation observed across all interleavings.
+    pub max_over_allocation: Decimal,
+    /// Total version conflicts observed (evidence that the race was exercised).
+    pub total_conflicts: u64,
+}
+
+impl DoubleSpendReport {
+    /// The attack failed (system safe) if no interleaving over-allocated.
+    pub fn passed(&self) -> bool {
+        self.over_allocated_schedules == 0
+    }
+}
+
+/// Trading path: locks order margin computed by the risk engine.
+pub struct TradingPath {
+    authority: Arc<dyn BalanceAuthority>,
+    leverage: u8,
+}
+
+impl TradingPath {
+    pub fn new(authority: Arc<dyn BalanceAuthority>, leverage: u8) -> Self {
+        Self {
+            authority,
+            leverage,
+        }
+    }
+
+    /// Read phase: snapshot the account and lock the order margin in memory.
+    ///
+    /// Returns the mutated view and the margin amount reserved.
+    pub fn prepare(
+        &self,
+        account_id: &AccountId,
+        asset: &str,
+        quantity: Decimal,
+        price: Decimal,
+    ) -> Result<(Account, Decimal), StoreError> {
+        let required = risk_engine::margin::order_margin(quantity, price, self.leverage);
+        let mut view = self
+            .authority
+            .snapshot(account_id)
+            .ok_or(StoreError::NotFound)?;
+        let balance = view.get_balance_mut(asset).ok_or(StoreError::NotFound)?;
+        if balance.available < required {
+            return Err(StoreError::InsufficientBalance);
+        }
+        balance.lock(required);
+        Ok((view, required))
+    }
+
+    /// Write phase: commit the prepared view.
+    pub fn commit(&self, view: Account) -> Result<(), StoreError> {
+        self.authority.commit(view)
+    }
+}
+
+/// Withdrawal path: reserves funds with its authority, then queues the
+/// withdrawal on the vault.
+pub struct WithdrawalPath {
+    authority: Arc<dyn BalanceAuthority>,
+    vault: Vault,
+    queue: WithdrawalQueue,
+    /// The account owner's registered withdrawal key
+    signer: SigningKey,
+    next_nonce: u64,
+}
+
+impl WithdrawalPath {
+    pub fn new(
+        authority: Arc<dyn BalanceAuthority>,
+        account_id: AccountId,
+        asset: &str,
+        custody_balance: Decimal,
+    ) -> Self {
+        let mut vault = Vault::new("admin");
+        vault
+            .add_to_whitelist("admin", asset)
+            .expect("admin can whitelist");
+        vault
+            .deposit(account_id, asset, custody_balance, "fund")
+            .expect("funding deposit");
+        let signer = SigningKey::from_bytes(&[1u8; 32]);
+        let mut queue = WithdrawalQueue::new(0);
+        queue.register_signer(account_id, signer.verifying_key().to_bytes());
+        Self {
+            authority,
+            vault,
+            queue,
+            signer,
+            next_nonce: 1,
+        }
+    }
+
+    /// Read phase: snapshot the account and deduct the withdrawal in memory.
+    pub fn prepare(
+        &self,
+        account_id: &AccountId,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<Account, StoreError> {
+        let mut view = self
+            .authority
+            .snapshot(account_id)
+            .ok_or(StoreError::NotFound)?;
+        let balance = view.get_balance_mut(asset).ok_or(StoreError::NotFound)?;
+        if balance.available < amount {
+            return Err(StoreError::InsufficientBalance);
+        }
+        balance.lock(amount);
+        balance.deduct_locked(amount);
+        Ok(view)
+    }
+
+    /// Write phase: commit the reservation, then submit to the vault queue.
+    ///
+    /// Returns `Ok(true)` if the vault approved the withdrawal.
+    pub fn commit(
+        &mut self,
+        view: Account,
+        asset: &str,
+        amount: Decimal,
+        timestamp: i64,
+    ) -> Result<bool, StoreError> {
+        let account_id = view.account_id;
+        self.authority.commit(view)?;
+
+        let nonce = self.next_nonce;
+        self.next_nonce += 1;
+        let destination = "attacker-destination";
+        let payload = WithdrawalQueue::withdrawal_signing_payload(
+            account_id,
+            asset,
+            amount,
+            nonce,
+            destination,
+        );
+        let hash: [u8; 32] = Sha256::digest(payload).into();
+        let signature = self.signer.sign(&hash).to_bytes();
+        let approved = self
+            .queue
+            .request_withdrawal(
+                &mut self.vault,
+                account_id,
+                asset,
+                amount,
+                destination,
+                nonce,
+                &signature,
+                timestamp,
+            )
+            .is_ok();
+        Ok(approved)
+    }
+}
+
+enum Prepared {
+    Order(Account, Decimal),
+    Withdrawal(Account, Decimal),
+}
+
+/// Execute one interleaving of `ops`, choosing the next step with `rng`.
+///
+/// Every operation is split into a read phase and a write phase; the scheduler
+/// picks any pending operation at each step, so reads of one path can land
+/// between the read and write of the other.
+fn run_schedule(
+    config: &DoubleSpendConfig,
+    topology: LockTopology,
+    ops: &[SpendOp],
+    rng: &mut StdRng,
+) -> ScheduleOutcome {
+    let mut account = Account::new(AccountType::MARGIN, 1708123456789000000);
+    account.set_balance(
+        Balance::new(config.asset.clone(), config.balance),
+        1708123456789000000,
+    );
+    let account_id = account.account_id;
+
+    let trading_store = Arc::new(AccountStore::new());
+    trading_store.insert(account.clone());
+    let withdrawal_store: Arc<dyn BalanceAuthority> = match topology {
+        LockTopology::Shared => trading_store.clone(),
+        LockTopology::Split => {
+            let store = Arc::new(AccountStore::new());
+            store.insert(account);
+            store
+        }
+    };
+
+    let trading = TradingPath::new(trading_store, config.leverage);
+    let mut withdrawals =
+        WithdrawalPath::new(withdrawal_store, account_id, &config.asset, config.balance);
+
+    let mut pending: Vec<(SpendOp, Option<Prepared>)> = ops.iter().map(|op| (*op, None)).collect();
+    let mut outcome = ScheduleOutcome {
+        locked_collateral: Decimal::ZERO,
+        approved_withdrawals: Decimal::ZERO,
+        over_allocation: Decimal::ZERO,
+        conflicts: 0,
+    };
+    let mut clock = 1708123456789000000i64;
+
+    while !pending.is_empty() {
+        let idx = rng.gen_range(0..pending.len());
+        clock += 1;
+        let (op, prepared) = &mut pending[idx];
+
+        match prepared.take() {
+            None => {
+                let result = match *op {
+                    SpendOp::Order { quantity, price } => trading
+                        .prepare(&account_id, &config.asset, quantity, price)
+                        .map(|(view, margin)| Prepared::Order(view, margin)),
+                    SpendOp::Withdrawal { amount } => withdrawals
+                        .prepare(&account_id, &config.asset, amount)
+                        .map(|view| Prepared::Withdrawal(view, amount)),
+                };
+                match result {
+                    Ok(p) => *prepared = Some(p),
+                    // Rejected at the read phase; the operation is finished.
+                    Err(_) => {
+                        pending.swap_remove(idx);
+                    }
+                }
+            }
+            Some(Prepared::Order(view, margin)) => match trading.commit(view) {
+                Ok(()) => {
+                    outcome.locked_collateral += margin;
+                    pending.swap_remove(idx);
+                }
+                Err(StoreError::VersionConflict) => outcome.conflicts += 1,
+                Err(_) => {
+                    pending.swap_remove(idx);
+                }
+            },
+            Some(Prepared::Withdrawal(view, amount)) => {
+                match withdrawals.commit(view, &config.asset, amount, clock) {
+                    Ok(approved) => {
+                        if approved {
+                            outcome.approved_withdrawals += amount;
+                        }
+                        pending.swap_remove(idx);
+                    }
+                    Err(StoreError::VersionConflict) => outcome.conflicts += 1,
+                    Err(_) => {
+                        pending.swap_remove(idx);
+                    }
+                }
+            }
+        }
+    }
+
+    let allocated = outcome.locked_collateral + outcome.approved_withdrawals;
+    if allocated > config.balance {
+        outcome.over_allocation = allocated - config.balance;
+    }
+    outcome
+}
+
+/// Run the cross-path double spend attack over `config.schedules` seeded
+/// interleavings and aggregate the results.
+pub fn run_double_spend_attack(
+    config: &DoubleSpendConfig,
+    topology: LockTopology,
+) -> DoubleSpendReport {
+    let ops = config.operations();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut report = DoubleSpendReport {
+        topology,
+        seed: config.seed,
+        schedules_run: 0,
+        over_allocated_schedules: 0,
+        max_over_allocation: Decimal::ZERO,
+        total_conflicts: 0,
+    };
+
+    for _ in 0..config.schedules {
+        let outcome = run_schedule(config, topology, &ops, &mut rng);
+        report.schedules_run += 1;
+        report.total_conflicts += outcome.conflicts;
+        if outcome.over_allocation > Decimal::ZERO {
+            report.over_allocated_schedules += 1;
+        }
+        report.max_over_allocation = report.max_over_allocation.max(outcome.over_allocation);
+    }
+
+    report
+}
+
+/// Runs the cross-path double spend attack as an auditable [`Attack`].
+///
+/// The context seed overrides `config.seed`.
+#[derive(Debug, Clone)]
+pub struct DoubleSpendAttack {
+    pub config: DoubleSpendConfig,
+    pub topology: LockTopology,
+}
+
+impl Default for DoubleSpendAttack {
+    fn default() -> Self {
+        Self {
+            config: DoubleSpendConfig::default(),
+            topology: LockTopology::Shared,
+        }
+    }
+}
+
+impl Attack for DoubleSpendAttack {
+    fn name(&self) -> &'static str {
+        "double_spend"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let config = DoubleSpendConfig {
+            seed: ctx.seed,
+            ..self.config.clone()
+        };
+        let result = run_double_spend_attack(&config, self.topology);
+
+        let mut report = AttackReport::new(self.name(), "risk-engine+vault", ctx.now)
+            .parameter("topology", format!("{:?}", self.topology))
+            .parameter("asset", &config.asset)
+            .parameter("balance", config.balance)
+            .parameter("orders", config.orders)
+            .parameter("withdrawals", config.withdrawals)
+            .parameter("schedules", config.schedules);
+
+        if !result.passed() {
+            report.finding(
+                Severity::Critical,
+                "Locked collateral plus approved withdrawals exceeded balance",
+                format!(
+                    "{} of {} schedules over-allocated, max over-allocation {}",
+                    result.over_allocated_schedules,
+                    result.schedules_run,
+                    result.max_over_allocation
+                ),
+            );
+        }
+
+        report.metric("schedules_run", result.schedules_run as u64);
+        report.metric(
+            "over_allocated_schedules",
+            result.over_allocated_schedules as u64,
+        );
+        report.metric("max_over_allocation", result.max_over_allocation);
+        report.metric("version_conflicts", result.total_conflicts);
+        report.finish(ctx.now + result.schedules_run as i64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use types::account::{AccountType, Balance};
 
     #[test]
     fn test_double_spend_mitigation() {
@@ -174,4 +661,84 @@ mod tests {
         assert_eq!(final_balance.locked, Decimal::from(100));
         assert!(final_balance.check_invariant());
     }
+
+    // ── Cross-path (trading vs. withdrawal) attack ──
+
+    #[test]
+    fn test_operations_total_balance_on_each_path() {
+        let config = DoubleSpendConfig::default();
+        let ops = config.operations();
+
+        let margin: Decimal = ops
+            .iter()
+            .filter_map(|op| match op {
+                SpendOp::Order { quantity, price } => Some(risk_engine::margin::order_margin(
+                    *quantity,
+                    *price,
+                    config.leverage,
+                )),
+                _ => None,
+            })
+            .sum();
+        let withdrawn: Decimal = ops
+            .iter()
+            .filter_map(|op| match op {
+                SpendOp::Withdrawal { amount } => Some(*amount),
+                _ => None,
+            })
+            .sum();
+
+        assert_eq!(margin, config.balance);
+        assert_eq!(withdrawn, config.balance);
+    }
+
+    #[test]
+    fn test_shared_authority_never_over_allocates() {
+        let config = DoubleSpendConfig::default();
+        let report = run_double_spend_attack(&config, LockTopology::Shared);
+
+        assert_eq!(report.schedules_run, config.schedules);
+        assert_eq!(report.over_allocated_schedules, 0);
+        assert_eq!(report.max_over_allocation, Decimal::ZERO);
+        assert!(report.passed());
+        // The scheduler must actually exercise stale-view races
+        assert!(report.total_conflicts > 0);
+    }
+
+    #[test]
+    fn test_split_authorities_are_detected() {
+        // Without a shared lock source each path independently allocates X.
+        let config = DoubleSpendConfig::default();
+        let report = run_double_spend_attack(&config, LockTopology::Split);
+
+        assert!(!report.passed());
+        assert_eq!(report.over_allocated_schedules, config.schedules);
+        assert_eq!(report.max_over_allocation, config.balance);
+    }
+
+    #[test]
+    fn test_attack_is_deterministic_for_seed() {
+        let config = DoubleSpendConfig {
+            seed: 42,
+            ..DoubleSpendConfig::default()
+        };
+        let a = run_double_spend_attack(&config, LockTopology::Shared);
+        let b = run_double_spend_attack(&config, LockTopology::Shared);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_double_spend_attack_report() {
+        let ctx = AttackContext { seed: 9, now: 0 };
+        let shared = DoubleSpendAttack::default().run(&ctx);
+        assert!(shared.passed);
+
+        let split = DoubleSpendAttack {
+            topology: LockTopology::Split,
+            ..DoubleSpendAttack::default()
+        }
+        .run(&ctx);
+        assert!(!split.passed);
+        assert_eq!(split.findings[0].severity, Severity::Critical);
+    }
 }
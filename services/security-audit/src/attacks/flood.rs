@@ -1,7 +1,9 @@
 //! Flood attack simulation.
 //! Simulates high-rate traffic to assess the Token Bucket rate limit policy.
 
-use std::time::Instant;
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
+use std::time::{Duration, Instant};
 
 /// Simulated Token Bucket Rate Limiter as specified in §6.1 of the rate limit policy.
 pub struct RateLimiter {
@@ -42,10 +44,81 @@ impl RateLimiter {
     }
 }
 
+/// Bursts requests at a single instant, then again after a refill pause, and
+/// checks the limiter never admits more than its token budget.
+#[derive(Debug, Clone)]
+pub struct FloodAttack {
+    /// Requests per second allowed by the limiter under test.
+    pub rate_per_second: u32,
+    /// Requests fired in the initial burst.
+    pub burst: u32,
+    /// Pause before the follow-up burst.
+    pub pause_millis: u64,
+}
+
+impl Default for FloodAttack {
+    fn default() -> Self {
+        // Standard order placement limit per spec §3.1
+        Self {
+            rate_per_second: 20,
+            burst: 50,
+            pause_millis: 500,
+        }
+    }
+}
+
+impl Attack for FloodAttack {
+    fn name(&self) -> &'static str {
+        "flood"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "rate-limiter", ctx.now)
+            .parameter("rate_per_second", self.rate_per_second)
+            .parameter("burst", self.burst)
+            .parameter("pause_millis", self.pause_millis);
+
+        let mut mock_time = Instant::now();
+        let rate = self.rate_per_second as f64;
+        let mut limiter = RateLimiter::new(rate, mock_time);
+
+        let burst_accepted = (0..self.burst)
+            .filter(|_| limiter.allow_request(mock_time))
+            .count() as u32;
+        let burst_allowed = (rate * 2.0) as u32;
+        if burst_accepted > burst_allowed {
+            report.finding(
+                Severity::High,
+                "Burst admitted more requests than bucket capacity",
+                format!("{} accepted, capacity {}", burst_accepted, burst_allowed),
+            );
+        }
+
+        mock_time += Duration::from_millis(self.pause_millis);
+        let refill_allowed = (rate * self.pause_millis as f64 / 1000.0) as u32;
+        let refill_accepted = (0..self.burst)
+            .filter(|_| limiter.allow_request(mock_time))
+            .count() as u32;
+        if refill_accepted > refill_allowed {
+            report.finding(
+                Severity::High,
+                "Refill admitted more requests than elapsed time allows",
+                format!("{} accepted, refill {}", refill_accepted, refill_allowed),
+            );
+        }
+
+        let sent = self.burst * 2;
+        let accepted = burst_accepted + refill_accepted;
+        report.metric("requests_sent", sent);
+        report.metric("requests_accepted", accepted);
+        report.metric("requests_rejected", sent - accepted);
+        report.finish(ctx.now + self.pause_millis as i64 * 1_000_000)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
 
     #[test]
     fn test_flood_attack_mitigation() {
@@ -91,4 +164,13 @@ mod tests {
         assert_eq!(next_accepted, 10);
         assert_eq!(next_rejected, 5);
     }
+
+    #[test]
+    fn test_flood_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 0 };
+        let report = FloodAttack::default().run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["requests_accepted"], 50.into());
+        assert_eq!(report.finished_at, 500_000_000);
+    }
 }
@@ -1,5 +1,29 @@
 //! Invalid signature spam test.
 //! Validates that the system immediately drops requests with invalid cryptographic signatures.
+//!
+//! Also provides a corpus-driven fuzzer for the two production verification paths,
+//! `wasm_core::signing::verify_signature` and the contracts' `request_withdrawal`.
+//! The fuzzer asserts that no corpus entry is accepted, that verification never
+//! panics, and that each input completes within a time budget. Offending inputs are
+//! persisted into the committed regression corpus at [`REGRESSION_CORPUS_PATH`].
+
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
+use contracts::vault::Vault;
+use contracts::withdrawal::WithdrawalQueue;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use types::ids::AccountId;
+use uuid::Uuid;
+use wasm_core::signing::{verify_signature, SignableMessage, SignedMessage};
 
 pub struct SignatureVerifier;
 
@@ -44,6 +68,491 @@ impl Default for ApiGateway {
     }
 }
 
+// ───────────────────────── Corpus-driven fuzzer ─────────────────────────
+
+/// Committed regression corpus replayed by the test suite.
+pub const REGRESSION_CORPUS_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/corpus/invalid_signer.json");
+
+/// Default per-input verification time budget.
+pub const DEFAULT_TIME_BUDGET: Duration = Duration::from_millis(100);
+
+/// Ed25519 group order L, little-endian.
+const ED25519_ORDER_LE: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+/// Compressed encoding of the Edwards identity point (small-order key / R).
+const IDENTITY_POINT: [u8; 32] = {
+    let mut p = [0u8; 32];
+    p[0] = 1;
+    p
+};
+
+/// Verification path exercised by a corpus entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignerTarget {
+    /// `wasm_core::signing::verify_signature` over a `SignableMessage`.
+    WasmCore,
+    /// `WithdrawalQueue::request_withdrawal` on a funded account whose signer
+    /// is the entry's key, or that has none if the key is not 32 bytes.
+    ContractsWithdrawal,
+}
+
+/// Class of invalid input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorpusKind {
+    MalformedHex,
+    TruncatedKey,
+    TruncatedSignature,
+    WrongKey,
+    DifferentMessage,
+    NonCanonicalS,
+    ZeroedKey,
+    /// No usable key at all, only junk signature bytes.
+    MissingSigner,
+}
+
+/// A single fuzz input. Keys and signatures are hex strings, which may
+/// themselves be malformed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    pub name: String,
+    pub target: SignerTarget,
+    pub kind: CorpusKind,
+    pub public_key: String,
+    pub signature: String,
+    /// Nonce of the message being verified.
+    pub nonce: u64,
+}
+
+/// The `SignableMessage` the wasm-core target verifies for `nonce`.
+pub fn wasm_message(nonce: u64) -> SignableMessage {
+    let mut payload = BTreeMap::new();
+    payload.insert("symbol".to_owned(), "BTC/USDT".to_owned());
+    payload.insert("side".to_owned(), "BUY".to_owned());
+    payload.insert("quantity".to_owned(), "1.5".to_owned());
+    payload.insert("price".to_owned(), "50000.00".to_owned());
+    SignableMessage::new("CreateOrder", payload, 1_708_123_456_789_000_000, nonce)
+}
+
+/// Fixed account used by the contracts withdrawal target.
+pub fn withdrawal_account() -> AccountId {
+    AccountId::from_uuid(Uuid::from_u128(0x0190_0000_0000_7000_8000_0000_0000_0001))
+}
+
+const WITHDRAWAL_ASSET: &str = "BTC";
+const WITHDRAWAL_DESTINATION: &str = "bc1q-fuzz";
+
+fn withdrawal_amount() -> Decimal {
+    Decimal::new(15, 1)
+}
+
+fn message_hash(target: SignerTarget, nonce: u64) -> [u8; 32] {
+    match target {
+        SignerTarget::WasmCore => wasm_message(nonce).hash(),
+        SignerTarget::ContractsWithdrawal => {
+            let payload = WithdrawalQueue::withdrawal_signing_payload(
+                withdrawal_account(),
+                WITHDRAWAL_ASSET,
+                withdrawal_amount(),
+                nonce,
+                WITHDRAWAL_DESTINATION,
+            );
+            Sha256::digest(payload).into()
+        }
+    }
+}
+
+/// Decode a hex field for the byte-oriented contracts target. Non-hex input is
+/// passed through as its raw bytes, as a caller submitting garbage would.
+fn decode_field(field: &str) -> Vec<u8> {
+    hex::decode(field).unwrap_or_else(|_| field.as_bytes().to_vec())
+}
+
+/// Run an entry through the production verifier for its target.
+///
+/// Returns `true` if the signature was accepted.
+pub fn verify_entry(entry: &CorpusEntry) -> bool {
+    match entry.target {
+        SignerTarget::WasmCore => {
+            let signed = SignedMessage {
+                message: wasm_message(entry.nonce),
+                signature: entry.signature.clone(),
+                public_key: entry.public_key.clone(),
+            };
+            verify_signature(&signed).is_ok()
+        }
+        SignerTarget::ContractsWithdrawal => request_withdrawal(entry),
+    }
+}
+
+/// Submit the entry as a withdrawal from a funded account.
+fn request_withdrawal(entry: &CorpusEntry) -> bool {
+    let account_id = withdrawal_account();
+    let mut vault = Vault::new("admin");
+    vault
+        .add_to_whitelist("admin", WITHDRAWAL_ASSET)
+        .expect("admin can whitelist");
+    vault
+        .deposit(account_id, WITHDRAWAL_ASSET, Decimal::from(10), "fund")
+        .expect("funding deposit");
+    let mut queue = WithdrawalQueue::new(0);
+    if let Ok(public_key) = <[u8; 32]>::try_from(decode_field(&entry.public_key)) {
+        queue.register_signer(account_id, public_key);
+    }
+    queue
+        .request_withdrawal(
+            &mut vault,
+            account_id,
+            WITHDRAWAL_ASSET,
+            withdrawal_amount(),
+            WITHDRAWAL_DESTINATION,
+            entry.nonce,
+            &decode_field(&entry.signature),
+            0,
+        )
+        .is_ok()
+}
+
+/// Add the group order L to the scalar half of a signature, producing a
+/// non-canonical `s` that is congruent mod L.
+fn non_canonical_s(signature: &[u8; 64]) -> [u8; 64] {
+    let mut out = *signature;
+    let mut carry = 0u16;
+    for (i, l) in ED25519_ORDER_LE.iter().enumerate() {
+        let sum = out[32 + i] as u16 + *l as u16 + carry;
+        out[32 + i] = sum as u8;
+        carry = sum >> 8;
+    }
+    out
+}
+
+/// Generate a deterministic corpus of invalid inputs for both targets.
+pub fn generate_corpus(seed: u64) -> Vec<CorpusEntry> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut entries = Vec::new();
+
+    for target in [SignerTarget::WasmCore, SignerTarget::ContractsWithdrawal] {
+        let signer = SigningKey::from_bytes(&rng.gen::<[u8; 32]>());
+        let other = SigningKey::from_bytes(&rng.gen::<[u8; 32]>());
+        let nonce = rng.gen_range(1..1_000_000u64);
+        let public_key = hex::encode(signer.verifying_key().to_bytes());
+
+        let sig = signer.sign(&message_hash(target, nonce)).to_bytes();
+        let sig_next = signer.sign(&message_hash(target, nonce + 1)).to_bytes();
+        let sig_hex = hex::encode(sig);
+
+        let mut push =
+            |name: &str, kind: CorpusKind, public_key: String, signature: String, nonce: u64| {
+                entries.push(CorpusEntry {
+                    name: format!("{:?}/{}", target, name),
+                    target,
+                    kind,
+                    public_key,
+                    signature,
+                    nonce,
+                });
+            };
+
+        // Malformed hex
+        push(
+            "sig-non-hex",
+            CorpusKind::MalformedHex,
+            public_key.clone(),
+            "zz".repeat(64),
+            nonce,
+        );
+        push(
+            "sig-odd-length",
+            CorpusKind::MalformedHex,
+            public_key.clone(),
+            sig_hex[1..].to_string(),
+            nonce,
+        );
+        push(
+            "sig-leading-space",
+            CorpusKind::MalformedHex,
+            public_key.clone(),
+            format!(" {}", sig_hex),
+            nonce,
+        );
+        push(
+            "key-non-hex",
+            CorpusKind::MalformedHex,
+            format!("0g{}", &public_key[2..]),
+            sig_hex.clone(),
+            nonce,
+        );
+        push(
+            "empty-fields",
+            CorpusKind::MalformedHex,
+            String::new(),
+            String::new(),
+            nonce,
+        );
+
+        // No signer: junk bytes, and a genuine signature with the key withheld
+        push("no-key-junk-sig", CorpusKind::MissingSigner, String::new(), "sig".into(), nonce);
+        push("no-key-valid-sig", CorpusKind::MissingSigner, String::new(), sig_hex.clone(), nonce);
+
+        // Truncated / oversized keys and signatures
+        push(
+            "key-31-bytes",
+            CorpusKind::TruncatedKey,
+            public_key[..62].to_string(),
+            sig_hex.clone(),
+            nonce,
+        );
+        push(
+            "key-33-bytes",
+            CorpusKind::TruncatedKey,
+            format!("{}00", public_key),
+            sig_hex.clone(),
+            nonce,
+        );
+        push(
+            "sig-63-bytes",
+            CorpusKind::TruncatedSignature,
+            public_key.clone(),
+            sig_hex[..126].to_string(),
+            nonce,
+        );
+        push(
+            "sig-65-bytes",
+            CorpusKind::TruncatedSignature,
+            public_key.clone(),
+            format!("{}00", sig_hex),
+            nonce,
+        );
+
+        // Valid key, signature from a different key
+        let foreign = other.sign(&message_hash(target, nonce)).to_bytes();
+        push(
+            "foreign-signature",
+            CorpusKind::WrongKey,
+            public_key.clone(),
+            hex::encode(foreign),
+            nonce,
+        );
+
+        // Signature over a different message, and R/s spliced across messages
+        push(
+            "other-message",
+            CorpusKind::DifferentMessage,
+            public_key.clone(),
+            hex::encode(sig_next),
+            nonce,
+        );
+        let mut spliced = sig;
+        spliced[32..].copy_from_slice(&sig_next[32..]);
+        push(
+            "spliced-r-s",
+            CorpusKind::DifferentMessage,
+            public_key.clone(),
+            hex::encode(spliced),
+            nonce,
+        );
+
+        // Non-canonical s (s + L)
+        push(
+            "s-plus-order",
+            CorpusKind::NonCanonicalS,
+            public_key.clone(),
+            hex::encode(non_canonical_s(&sig)),
+            nonce,
+        );
+
+        // Zeroed and small-order keys with the trivial forgery R = identity, s = 0
+        let mut trivial = [0u8; 64];
+        trivial[..32].copy_from_slice(&IDENTITY_POINT);
+        push(
+            "zero-key-zero-sig",
+            CorpusKind::ZeroedKey,
+            hex::encode([0u8; 32]),
+            hex::encode([0u8; 64]),
+            nonce,
+        );
+        push(
+            "identity-key-trivial-sig",
+            CorpusKind::ZeroedKey,
+            hex::encode(IDENTITY_POINT),
+            hex::encode(trivial),
+            nonce,
+        );
+        for n in 0..8u64 {
+            push(
+                &format!("zero-key-trivial-sig-{}", n),
+                CorpusKind::ZeroedKey,
+                hex::encode([0u8; 32]),
+                hex::encode(trivial),
+                n,
+            );
+        }
+    }
+
+    entries
+}
+
+/// Why an input was flagged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FindingKind {
+    /// The verifier accepted an invalid signature.
+    Accepted,
+    /// The verifier rejected the input but exceeded the time budget.
+    Slow { elapsed_micros: u64 },
+    /// The verifier panicked.
+    Panicked,
+}
+
+/// A flagged corpus input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzFinding {
+    pub entry: CorpusEntry,
+    pub kind: FindingKind,
+}
+
+/// Result of running the fuzzer over a corpus.
+#[derive(Debug, Clone, Default)]
+pub struct FuzzReport {
+    pub inputs_run: usize,
+    pub findings: Vec<FuzzFinding>,
+    /// Slowest single verification observed.
+    pub max_elapsed: Duration,
+}
+
+impl FuzzReport {
+    pub fn passed(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Run every entry through `verifier`, flagging acceptances, panics, and
+/// inputs that exceed `time_budget`.
+pub fn run_fuzzer<F>(entries: &[CorpusEntry], time_budget: Duration, verifier: F) -> FuzzReport
+where
+    F: Fn(&CorpusEntry) -> bool,
+{
+    let mut report = FuzzReport::default();
+
+    for entry in entries {
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| verifier(entry)));
+        let elapsed = started.elapsed();
+
+        report.inputs_run += 1;
+        report.max_elapsed = report.max_elapsed.max(elapsed);
+
+        let kind = match result {
+            Err(_) => Some(FindingKind::Panicked),
+            Ok(true) => Some(FindingKind::Accepted),
+            Ok(false) if elapsed > time_budget => Some(FindingKind::Slow {
+                elapsed_micros: elapsed.as_micros() as u64,
+            }),
+            Ok(false) => None,
+        };
+        if let Some(kind) = kind {
+            report.findings.push(FuzzFinding {
+                entry: entry.clone(),
+                kind,
+            });
+        }
+    }
+
+    report
+}
+
+/// Regression corpus of inputs that were once accepted or slow.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegressionCorpus {
+    pub entries: Vec<CorpusEntry>,
+}
+
+impl RegressionCorpus {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("corpus serialization must not fail")
+    }
+
+    /// Load the corpus from disk; a missing file yields an empty corpus.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json() + "\n")
+    }
+
+    /// Add the entries behind `findings` that are not already in the corpus.
+    ///
+    /// Returns the number of newly recorded entries.
+    pub fn record(&mut self, findings: &[FuzzFinding]) -> usize {
+        let mut added = 0;
+        for finding in findings {
+            if !self.entries.contains(&finding.entry) {
+                self.entries.push(finding.entry.clone());
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+/// Runs the signer fuzzer over a seeded corpus plus the committed regression
+/// corpus against both production verifiers.
+#[derive(Debug, Clone)]
+pub struct InvalidSignerAttack {
+    pub time_budget: Duration,
+}
+
+impl Default for InvalidSignerAttack {
+    fn default() -> Self {
+        Self {
+            time_budget: DEFAULT_TIME_BUDGET,
+        }
+    }
+}
+
+impl Attack for InvalidSignerAttack {
+    fn name(&self) -> &'static str {
+        "invalid_signer"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "signature-verification", ctx.now)
+            .parameter("time_budget_micros", self.time_budget.as_micros());
+
+        let mut corpus = generate_corpus(ctx.seed);
+        let regression =
+            RegressionCorpus::from_json(include_str!("../../corpus/invalid_signer.json"))
+                .expect("committed regression corpus must parse");
+        corpus.extend(regression.entries);
+
+        let result = run_fuzzer(&corpus, self.time_budget, verify_entry);
+        for finding in &result.findings {
+            let (severity, description) = match finding.kind {
+                FindingKind::Accepted => (Severity::Critical, "Invalid signature accepted"),
+                FindingKind::Panicked => (Severity::High, "Verifier panicked"),
+                FindingKind::Slow { .. } => (Severity::Medium, "Verification exceeded time budget"),
+            };
+            report.finding(severity, description, finding.entry.name.clone());
+        }
+
+        report.metric("inputs_run", result.inputs_run as u64);
+        report.metric("inputs_flagged", result.findings.len() as u64);
+        report.finish(ctx.now + result.inputs_run as i64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +572,141 @@ mod tests {
         assert_eq!(gateway.dropped_requests, 1000);
         assert_eq!(gateway.accepted_requests, 1);
     }
+
+    // ── Corpus-driven fuzzer ──
+
+    /// Pre-strict verifier: `verify` instead of `verify_strict`.
+    fn lax_verify(entry: &CorpusEntry) -> bool {
+        let (Ok(key), Ok(sig)) = (
+            hex::decode(&entry.public_key),
+            hex::decode(&entry.signature),
+        ) else {
+            return false;
+        };
+        let (Ok(key), Ok(sig)) = (<[u8; 32]>::try_from(key), <[u8; 64]>::try_from(sig)) else {
+            return false;
+        };
+        let Ok(key) = ed25519_dalek::VerifyingKey::from_bytes(&key) else {
+            return false;
+        };
+        let sig = ed25519_dalek::Signature::from_bytes(&sig);
+        ed25519_dalek::Verifier::verify(&key, &message_hash(entry.target, entry.nonce), &sig)
+            .is_ok()
+    }
+
+    #[test]
+    fn test_generated_corpus_covers_both_targets_and_all_kinds() {
+        let corpus = generate_corpus(7);
+        for target in [SignerTarget::WasmCore, SignerTarget::ContractsWithdrawal] {
+            for kind in [
+                CorpusKind::MalformedHex,
+                CorpusKind::TruncatedKey,
+                CorpusKind::TruncatedSignature,
+                CorpusKind::WrongKey,
+                CorpusKind::DifferentMessage,
+                CorpusKind::NonCanonicalS,
+                CorpusKind::ZeroedKey,
+                CorpusKind::MissingSigner,
+            ] {
+                assert!(corpus.iter().any(|e| e.target == target && e.kind == kind));
+            }
+        }
+        assert_eq!(corpus, generate_corpus(7));
+    }
+
+    #[test]
+    fn test_fuzzer_rejects_generated_corpus() {
+        for seed in 0..4 {
+            let corpus = generate_corpus(seed);
+            let report = run_fuzzer(&corpus, DEFAULT_TIME_BUDGET, verify_entry);
+            assert_eq!(report.inputs_run, corpus.len());
+            assert!(report.passed(), "findings: {:?}", report.findings);
+        }
+    }
+
+    #[test]
+    fn test_regression_corpus_replays_clean() {
+        let corpus =
+            RegressionCorpus::from_json(include_str!("../../corpus/invalid_signer.json")).unwrap();
+        assert!(!corpus.entries.is_empty());
+        let report = run_fuzzer(&corpus.entries, DEFAULT_TIME_BUDGET, verify_entry);
+        assert!(report.passed(), "findings: {:?}", report.findings);
+    }
+
+    #[test]
+    fn test_fuzzer_flags_lax_verifier_weak_key_forgery() {
+        let corpus = generate_corpus(7);
+        let report = run_fuzzer(&corpus, DEFAULT_TIME_BUDGET, lax_verify);
+
+        assert!(report
+            .findings
+            .iter()
+            .all(|f| f.kind == FindingKind::Accepted && f.entry.kind == CorpusKind::ZeroedKey));
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.entry.name.ends_with("identity-key-trivial-sig")));
+    }
+
+    #[test]
+    fn test_fuzzer_flags_placeholder_verifier() {
+        // The old fallback for accounts without a signer: any non-empty bytes
+        let corpus = generate_corpus(7);
+        let report = run_fuzzer(&corpus, DEFAULT_TIME_BUDGET, |entry| {
+            !decode_field(&entry.signature).is_empty()
+        });
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.entry.name == "ContractsWithdrawal/no-key-junk-sig"));
+    }
+
+    #[test]
+    fn test_fuzzer_flags_panicking_verifier() {
+        let corpus = generate_corpus(1);
+        let report = run_fuzzer(&corpus[..1], DEFAULT_TIME_BUDGET, |_| panic!("boom"));
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].kind, FindingKind::Panicked);
+    }
+
+    #[test]
+    fn test_regression_corpus_record_dedups() {
+        let corpus = generate_corpus(3);
+        let report = run_fuzzer(&corpus, DEFAULT_TIME_BUDGET, lax_verify);
+        let mut regression = RegressionCorpus::default();
+
+        let added = regression.record(&report.findings);
+        assert_eq!(added, report.findings.len());
+        assert_eq!(regression.record(&report.findings), 0);
+
+        let restored = RegressionCorpus::from_json(&regression.to_json()).unwrap();
+        assert_eq!(restored, regression);
+    }
+
+    #[test]
+    fn test_invalid_signer_attack_report() {
+        let ctx = AttackContext { seed: 5, now: 0 };
+        let report = InvalidSignerAttack::default().run(&ctx);
+        assert!(report.passed, "findings: {:?}", report.findings);
+        assert!(report.metrics["inputs_run"] > Decimal::ZERO);
+    }
+
+    /// Fuzz fresh seeds against the production verifiers and persist any
+    /// findings into the committed regression corpus.
+    ///
+    /// Run with `cargo test -- --ignored fuzz_and_persist_findings`.
+    #[test]
+    #[ignore]
+    fn fuzz_and_persist_findings() {
+        let mut regression = RegressionCorpus::load(REGRESSION_CORPUS_PATH).unwrap();
+        let mut findings = Vec::new();
+        for seed in 0..256 {
+            let report = run_fuzzer(&generate_corpus(seed), DEFAULT_TIME_BUDGET, verify_entry);
+            findings.extend(report.findings);
+        }
+        if regression.record(&findings) > 0 {
+            regression.save(REGRESSION_CORPUS_PATH).unwrap();
+        }
+        assert!(findings.is_empty(), "findings: {:?}", findings);
+    }
 }
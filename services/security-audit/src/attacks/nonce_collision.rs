@@ -2,6 +2,8 @@
 //! Ensures that requests with duplicated or old sequence numbers (nonces) are rejected
 //! to prevent replay attacks over the API.
 
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
 use std::collections::HashMap;
 
 pub struct NonceTracker {
@@ -43,6 +45,58 @@ impl Default for NonceTracker {
     }
 }
 
+/// Submits a fixed probe sequence of fresh, reused, stale, and skipped nonces.
+#[derive(Debug, Clone, Default)]
+pub struct NonceCollisionAttack;
+
+impl NonceCollisionAttack {
+    /// `(nonce, should_be_accepted)` probes, in submission order.
+    const PROBES: [(u64, bool); 5] = [
+        (100, true),
+        (101, true),
+        (101, false),
+        (99, false),
+        (150, true),
+    ];
+}
+
+impl Attack for NonceCollisionAttack {
+    fn name(&self) -> &'static str {
+        "nonce_collision"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "api-nonce-tracker", ctx.now)
+            .parameter("probes", Self::PROBES.len());
+        let mut tracker = NonceTracker::new();
+        let mut collisions_accepted = 0u32;
+
+        for (nonce, expected) in Self::PROBES {
+            let accepted = tracker.process("acc_audit", nonce).is_ok();
+            match (expected, accepted) {
+                (false, true) => {
+                    collisions_accepted += 1;
+                    report.finding(
+                        Severity::Critical,
+                        "Reused or stale nonce accepted",
+                        format!("nonce {}", nonce),
+                    );
+                }
+                (true, false) => report.finding(
+                    Severity::Low,
+                    "Fresh nonce rejected",
+                    format!("nonce {}", nonce),
+                ),
+                _ => {}
+            }
+        }
+
+        report.metric("probes_sent", Self::PROBES.len() as u64);
+        report.metric("collisions_accepted", collisions_accepted);
+        report.finish(ctx.now + Self::PROBES.len() as i64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +121,12 @@ mod tests {
         // Future nonce works (skipping is typically allowed, just must be strictly increasing)
         assert_eq!(tracker.process(account, 150), Ok(()));
     }
+
+    #[test]
+    fn test_nonce_collision_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 0 };
+        let report = NonceCollisionAttack.run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["collisions_accepted"], 0.into());
+    }
 }
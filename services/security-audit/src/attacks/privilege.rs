@@ -2,10 +2,13 @@
 //! Simulates checking that an account cannot assume the roles or modify the resources
 //! of another account.
 
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
 use types::ids::AccountId;
 
 pub struct AuthorizationService;
 
+#[derive(Debug, Clone, Copy)]
 pub enum ResourceType {
     AccountBalance,
     OrderEntry,
@@ -45,6 +48,119 @@ impl AuthorizationService {
     }
 }
 
+/// A single access check and whether it should be allowed.
+struct AccessProbe {
+    label: &'static str,
+    requester: AccountId,
+    owner: Option<AccountId>,
+    resource: ResourceType,
+    is_admin: bool,
+    expected: bool,
+}
+
+/// Probes owner, cross-account, and system-config access for user and admin roles.
+#[derive(Debug, Clone, Default)]
+pub struct PrivilegeAttack;
+
+impl Attack for PrivilegeAttack {
+    fn name(&self) -> &'static str {
+        "privilege"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "authorization-service", ctx.now);
+        let alice = AccountId::new();
+        let bob = AccountId::new();
+        let admin = AccountId::new();
+
+        let probe = |label, requester, owner, resource, is_admin, expected| AccessProbe {
+            label,
+            requester,
+            owner,
+            resource,
+            is_admin,
+            expected,
+        };
+        let probes = [
+            probe(
+                "user on own balance",
+                alice,
+                Some(alice),
+                ResourceType::AccountBalance,
+                false,
+                true,
+            ),
+            probe(
+                "user on other balance",
+                alice,
+                Some(bob),
+                ResourceType::AccountBalance,
+                false,
+                false,
+            ),
+            probe(
+                "user on other orders",
+                alice,
+                Some(bob),
+                ResourceType::OrderEntry,
+                false,
+                false,
+            ),
+            probe(
+                "admin on other balance",
+                admin,
+                Some(bob),
+                ResourceType::AccountBalance,
+                true,
+                true,
+            ),
+            probe(
+                "user on system config",
+                alice,
+                None,
+                ResourceType::SystemConfig,
+                false,
+                false,
+            ),
+            probe(
+                "admin on system config",
+                admin,
+                None,
+                ResourceType::SystemConfig,
+                true,
+                true,
+            ),
+        ];
+
+        let mut escalations = 0u32;
+        for p in &probes {
+            let allowed = AuthorizationService::check_permission(
+                &p.requester,
+                p.owner.as_ref(),
+                p.resource,
+                p.is_admin,
+            )
+            .is_ok();
+            match (p.expected, allowed) {
+                (false, true) => {
+                    escalations += 1;
+                    report.finding(
+                        Severity::Critical,
+                        "Privilege escalation: unauthorized access granted",
+                        p.label,
+                    );
+                }
+                (true, false) => report.finding(Severity::Low, "Authorized access denied", p.label),
+                _ => {}
+            }
+        }
+
+        report.metric("probes_sent", probes.len() as u64);
+        report.metric("escalations", escalations);
+        report.finish(ctx.now + probes.len() as i64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +216,12 @@ mod tests {
             Ok(())
         );
     }
+
+    #[test]
+    fn test_privilege_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 0 };
+        let report = PrivilegeAttack.run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["escalations"], 0.into());
+    }
 }
@@ -2,7 +2,12 @@
 //! Simulates high-concurrency event ingestion pointing out that sequence numbering
 //! must be atomic to ensure no gaps or duplicate numbers exist in the event stream.
 
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
 
 pub struct AtomicSequenceGenerator {
     current: AtomicU64,
@@ -26,12 +31,83 @@ impl Default for AtomicSequenceGenerator {
     }
 }
 
+/// Hammers a shared sequence generator from many threads and checks the
+/// result is gapless and duplicate-free.
+#[derive(Debug, Clone)]
+pub struct RaceConditionAttack {
+    pub threads: usize,
+    pub iterations: usize,
+}
+
+impl Default for RaceConditionAttack {
+    fn default() -> Self {
+        Self {
+            threads: 10,
+            iterations: 1000,
+        }
+    }
+}
+
+impl Attack for RaceConditionAttack {
+    fn name(&self) -> &'static str {
+        "race_condition"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "sequence-generator", ctx.now)
+            .parameter("threads", self.threads)
+            .parameter("iterations", self.iterations);
+
+        let generator = Arc::new(AtomicSequenceGenerator::new(1));
+        let handles: Vec<_> = (0..self.threads)
+            .map(|_| {
+                let generator = Arc::clone(&generator);
+                let iterations = self.iterations;
+                thread::spawn(move || {
+                    (0..iterations)
+                        .map(|_| generator.next())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut duplicates = 0u64;
+        for handle in handles {
+            for seq in handle.join().expect("generator thread panicked") {
+                if !seen.insert(seq) {
+                    duplicates += 1;
+                }
+            }
+        }
+        let total = (self.threads * self.iterations) as u64;
+        let gaps = (1..=total).filter(|seq| !seen.contains(seq)).count() as u64;
+
+        if duplicates > 0 {
+            report.finding(
+                Severity::Critical,
+                "Duplicate sequence numbers issued",
+                format!("{} duplicates", duplicates),
+            );
+        }
+        if gaps > 0 {
+            report.finding(
+                Severity::High,
+                "Gaps in issued sequence numbers",
+                format!("{} gaps", gaps),
+            );
+        }
+
+        report.metric("sequences_issued", total);
+        report.metric("duplicates", duplicates);
+        report.metric("gaps", gaps);
+        report.finish(ctx.now + total as i64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
-    use std::sync::Arc;
-    use std::thread;
 
     #[test]
     fn test_race_condition_sequence_generation() {
@@ -79,4 +155,12 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_race_condition_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 0 };
+        let report = RaceConditionAttack::default().run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["sequences_issued"], 10_000.into());
+    }
 }
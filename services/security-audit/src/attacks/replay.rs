@@ -1,9 +1,12 @@
 //! Replay attack simulation.
 //! Tests that replaying the exact same signed payload or order is rejected.
 
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
 use std::collections::HashSet;
-use types::ids::OrderId;
-use types::order::Order;
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::{Price, Quantity};
+use types::order::{Order, Side, TimeInForce};
 
 /// Simulates an exchange endpoint that drops replayed messages referencing the same Order ID
 /// or sequence number.
@@ -34,12 +37,65 @@ impl Default for ReplayDetector {
     }
 }
 
+/// Captures a legitimate order and replays it `replays` times.
+#[derive(Debug, Clone)]
+pub struct ReplayAttack {
+    pub replays: u64,
+}
+
+impl Default for ReplayAttack {
+    fn default() -> Self {
+        Self { replays: 100 }
+    }
+}
+
+impl Attack for ReplayAttack {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "order-entry", ctx.now)
+            .parameter("replays", self.replays);
+        let mut detector = ReplayDetector::new();
+
+        let order = Order::new(
+            AccountId::new(),
+            MarketId::new("BTC/USDT"),
+            Side::BUY,
+            Price::from_str("50000").unwrap(),
+            Quantity::from_str("1.0").unwrap(),
+            TimeInForce::GTC,
+            ctx.now,
+        );
+        if !detector.process_order(&order) {
+            report.finding(
+                Severity::Medium,
+                "Legitimate order rejected on first submission",
+                "original order",
+            );
+        }
+
+        let accepted = (0..self.replays)
+            .filter(|_| detector.process_order(&order.clone()))
+            .count() as u64;
+        if accepted > 0 {
+            report.finding(
+                Severity::Critical,
+                "Replayed order accepted",
+                format!("{} of {} replays accepted", accepted, self.replays),
+            );
+        }
+
+        report.metric("replays_attempted", self.replays);
+        report.metric("replays_accepted", accepted);
+        report.finish(ctx.now + self.replays as i64 + 1)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use types::ids::{AccountId, MarketId};
-    use types::numeric::{Price, Quantity};
-    use types::order::{Side, TimeInForce};
 
     #[test]
     fn test_replay_attack_mitigation() {
@@ -74,4 +130,13 @@ mod tests {
             "Replay attack succeeded"
         );
     }
+
+    #[test]
+    fn test_replay_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 1000 };
+        let report = ReplayAttack::default().run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["replays_accepted"], 0.into());
+        assert_eq!(report.finished_at, 1101);
+    }
 }
@@ -2,6 +2,8 @@
 //! Simulates a connection monitor that disconnects clients who send data too slowly
 //! (like a Slowloris attack) by enforcing an idle_timeout.
 
+use crate::pipeline::severity_classifier::Severity;
+use crate::report::{Attack, AttackContext, AttackReport};
 use std::time::{Duration, Instant};
 
 /// Simulates tracking connection health at the gateway level.
@@ -32,6 +34,75 @@ impl ConnectionMonitor {
     }
 }
 
+/// Trickles data just under the idle timeout, then goes silent past it.
+#[derive(Debug, Clone)]
+pub struct SlowClientAttack {
+    pub idle_timeout_secs: u64,
+    pub trickle_interval_secs: u64,
+    pub trickle_count: u32,
+}
+
+impl Default for SlowClientAttack {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: 30,
+            trickle_interval_secs: 5,
+            trickle_count: 10,
+        }
+    }
+}
+
+impl Attack for SlowClientAttack {
+    fn name(&self) -> &'static str {
+        "slow_client"
+    }
+
+    fn run(&self, ctx: &AttackContext) -> AttackReport {
+        let mut report = AttackReport::new(self.name(), "gateway-connection", ctx.now)
+            .parameter("idle_timeout_secs", self.idle_timeout_secs)
+            .parameter("trickle_interval_secs", self.trickle_interval_secs)
+            .parameter("trickle_count", self.trickle_count);
+
+        let mut mock_time = Instant::now();
+        let timeout = Duration::from_secs(self.idle_timeout_secs);
+        let mut monitor = ConnectionMonitor::new(1, timeout, mock_time);
+        let mut elapsed_secs = 0u64;
+
+        let mut premature_drops = 0u32;
+        for _ in 0..self.trickle_count {
+            mock_time += Duration::from_secs(self.trickle_interval_secs);
+            elapsed_secs += self.trickle_interval_secs;
+            if monitor.is_timed_out(mock_time) {
+                premature_drops += 1;
+            }
+            monitor.receive_data(mock_time);
+        }
+        if premature_drops > 0 && self.trickle_interval_secs <= self.idle_timeout_secs {
+            report.finding(
+                Severity::Low,
+                "Active client dropped before idle timeout",
+                format!("{} premature drops", premature_drops),
+            );
+        }
+
+        let silence = self.idle_timeout_secs + 1;
+        mock_time += Duration::from_secs(silence);
+        elapsed_secs += silence;
+        let dropped = monitor.is_timed_out(mock_time);
+        if !dropped {
+            report.finding(
+                Severity::High,
+                "Idle connection not dropped after timeout",
+                format!("silent for {}s", silence),
+            );
+        }
+
+        report.metric("premature_drops", premature_drops);
+        report.metric("idle_connection_dropped", u32::from(dropped));
+        report.finish(ctx.now + elapsed_secs as i64 * 1_000_000_000)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +131,13 @@ mod tests {
         // Should timeout and drop connection
         assert!(monitor.is_timed_out(mock_time));
     }
+
+    #[test]
+    fn test_slow_client_attack_report() {
+        let ctx = AttackContext { seed: 1, now: 0 };
+        let report = SlowClientAttack::default().run(&ctx);
+        assert!(report.passed);
+        assert_eq!(report.metrics["idle_connection_dropped"], 1.into());
+        assert_eq!(report.finished_at, 81_000_000_000);
+    }
 }
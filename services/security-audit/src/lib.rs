@@ -1,2 +1,5 @@
 pub mod attacks;
+pub mod pipeline;
+pub mod report;
+pub mod runner;
 pub mod tests;
@@ -1,7 +1,9 @@
 //! Severity classifier.
 //! Evaluates identified vulnerabilities against the exchange's risk policy.
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Severity {
     Low,
     Medium,
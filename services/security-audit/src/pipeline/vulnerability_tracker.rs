@@ -32,7 +32,7 @@ impl VulnerabilityTracker {
         
         for entry in &self.entries {
             let status = if entry.open { "OPEN" } else { "CLOSED" };
-            report.push_str(&format!("### [{}] {} ({})\n{}\n\n", entry.id, status, format!("{:?}", entry.severity), entry.description));
+            report.push_str(&format!("### [{}] {} ({:?})\n{}\n\n", entry.id, status, entry.severity, entry.description));
         }
 
         report
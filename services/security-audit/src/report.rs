@@ -0,0 +1,146 @@
+//! Unified attack report schema.
+//! Every attack module produces an `AttackReport` so the `AuditRunner` can
+//! aggregate, score, and export results as a single JSON artifact.
+
+use crate::pipeline::severity_classifier::Severity;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Schema version of the exported report; bump on breaking changes.
+pub const REPORT_SCHEMA_VERSION: &str = "1.0.0";
+
+/// A single issue observed while running an attack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub description: String,
+    /// Reproduction detail (inputs, observed values).
+    pub evidence: String,
+}
+
+/// Result of running one attack against one target.
+///
+/// `started_at`/`finished_at` are exchange timestamps (unix nanos) supplied by
+/// the runner's deterministic clock, never wall-clock time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackReport {
+    pub attack_name: String,
+    pub target: String,
+    pub parameters: BTreeMap<String, String>,
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub findings: Vec<Finding>,
+    pub metrics: BTreeMap<String, Decimal>,
+    pub passed: bool,
+}
+
+impl AttackReport {
+    /// Start a report; `passed` is derived from findings on `finish`.
+    pub fn new(attack_name: impl Into<String>, target: impl Into<String>, started_at: i64) -> Self {
+        Self {
+            attack_name: attack_name.into(),
+            target: target.into(),
+            parameters: BTreeMap::new(),
+            started_at,
+            finished_at: started_at,
+            findings: Vec::new(),
+            metrics: BTreeMap::new(),
+            passed: true,
+        }
+    }
+
+    pub fn parameter(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.parameters.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn metric(&mut self, key: impl Into<String>, value: impl Into<Decimal>) {
+        self.metrics.insert(key.into(), value.into());
+    }
+
+    pub fn finding(
+        &mut self,
+        severity: Severity,
+        description: impl Into<String>,
+        evidence: impl Into<String>,
+    ) {
+        self.findings.push(Finding {
+            severity,
+            description: description.into(),
+            evidence: evidence.into(),
+        });
+    }
+
+    /// Close the report at `finished_at`. The attack passed if nothing was found.
+    pub fn finish(mut self, finished_at: i64) -> Self {
+        self.finished_at = finished_at;
+        self.passed = self.findings.is_empty();
+        self
+    }
+
+    /// Score in 0..=100: full marks minus a penalty per finding.
+    pub fn score(&self) -> u32 {
+        let penalty: u32 = self
+            .findings
+            .iter()
+            .map(|f| severity_penalty(f.severity))
+            .sum();
+        100u32.saturating_sub(penalty)
+    }
+}
+
+/// Score penalty for a finding of the given severity.
+pub fn severity_penalty(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 100,
+        Severity::High => 50,
+        Severity::Medium => 20,
+        Severity::Low => 5,
+    }
+}
+
+/// Deterministic inputs handed to each attack by the runner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackContext {
+    /// Seed for any randomized scheduling inside the attack.
+    pub seed: u64,
+    /// Exchange time at which the attack starts (unix nanos).
+    pub now: i64,
+}
+
+/// An attack that can be run by the `AuditRunner`.
+pub trait Attack {
+    /// Stable name used to select the attack and label its report.
+    fn name(&self) -> &'static str;
+
+    /// Run the attack and produce its report.
+    fn run(&self, ctx: &AttackContext) -> AttackReport;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_passed_derived_from_findings() {
+        let clean = AttackReport::new("replay", "gateway", 10).finish(20);
+        assert!(clean.passed);
+        assert_eq!(clean.score(), 100);
+
+        let mut dirty = AttackReport::new("replay", "gateway", 10);
+        dirty.finding(Severity::Medium, "slow path", "input #3");
+        let dirty = dirty.finish(20);
+        assert!(!dirty.passed);
+        assert_eq!(dirty.score(), 80);
+        assert_eq!(dirty.finished_at, 20);
+    }
+
+    #[test]
+    fn test_score_saturates_at_zero() {
+        let mut report = AttackReport::new("double_spend", "risk+vault", 0);
+        report.finding(Severity::Critical, "over-allocation", "x");
+        report.finding(Severity::High, "conflict storm", "y");
+        assert_eq!(report.finish(1).score(), 0);
+    }
+}
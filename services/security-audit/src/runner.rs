@@ -0,0 +1,271 @@
+//! Audit runner.
+//! Runs a configured subset of attacks sequentially with a deterministic seed,
+//! aggregates their reports, scores the run, and exports one JSON artifact.
+
+use crate::attacks::double_spend::DoubleSpendAttack;
+use crate::attacks::flood::FloodAttack;
+use crate::attacks::invalid_signer::InvalidSignerAttack;
+use crate::attacks::nonce_collision::NonceCollisionAttack;
+use crate::attacks::privilege::PrivilegeAttack;
+use crate::attacks::race_condition::RaceConditionAttack;
+use crate::attacks::replay::ReplayAttack;
+use crate::attacks::slow_client::SlowClientAttack;
+use crate::report::{Attack, AttackContext, AttackReport, REPORT_SCHEMA_VERSION};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AuditError {
+    #[error("Unknown attack: {0}")]
+    UnknownAttack(String),
+}
+
+/// Which attacks to run and with what seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditConfig {
+    /// Master seed; each attack receives a seed derived from it.
+    pub seed: u64,
+    /// Exchange time at which the first attack starts (unix nanos).
+    pub start_time: i64,
+    /// Attack names to run, in registration order. Empty runs all.
+    pub attacks: Vec<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            start_time: 1708123456789000000,
+            attacks: Vec::new(),
+        }
+    }
+}
+
+/// Aggregated output of an audit run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditArtifact {
+    pub schema_version: String,
+    pub seed: u64,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// Mean of the per-attack scores (0..=100).
+    pub score: u32,
+    pub passed: bool,
+    pub reports: Vec<AttackReport>,
+}
+
+impl AuditArtifact {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("artifact serialization must not fail")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json() + "\n")
+    }
+}
+
+/// Sequential attack runner.
+pub struct AuditRunner {
+    attacks: Vec<Box<dyn Attack>>,
+}
+
+impl AuditRunner {
+    /// Create a runner with no attacks registered.
+    pub fn new() -> Self {
+        Self {
+            attacks: Vec::new(),
+        }
+    }
+
+    /// Create a runner with every attack module registered at its defaults.
+    pub fn with_default_attacks() -> Self {
+        let mut runner = Self::new();
+        runner.register(Box::new(ReplayAttack::default()));
+        runner.register(Box::new(FloodAttack::default()));
+        runner.register(Box::new(DoubleSpendAttack::default()));
+        runner.register(Box::new(SlowClientAttack::default()));
+        runner.register(Box::new(InvalidSignerAttack::default()));
+        runner.register(Box::new(NonceCollisionAttack));
+        runner.register(Box::new(PrivilegeAttack));
+        runner.register(Box::new(RaceConditionAttack::default()));
+        runner
+    }
+
+    pub fn register(&mut self, attack: Box<dyn Attack>) {
+        self.attacks.push(attack);
+    }
+
+    /// Registered attack names, in run order.
+    pub fn attack_names(&self) -> Vec<&'static str> {
+        self.attacks.iter().map(|a| a.name()).collect()
+    }
+
+    /// Run the configured attacks and aggregate their reports.
+    pub fn run(&self, config: &AuditConfig) -> Result<AuditArtifact, AuditError> {
+        for name in &config.attacks {
+            if !self.attacks.iter().any(|a| a.name() == name) {
+                return Err(AuditError::UnknownAttack(name.clone()));
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut now = config.start_time;
+        let mut reports = Vec::new();
+
+        for attack in &self.attacks {
+            // Draw for every registered attack so a subset run sees the same
+            // per-attack seeds as a full run.
+            let seed = rng.gen::<u64>();
+            if !config.attacks.is_empty() && !config.attacks.iter().any(|n| n == attack.name()) {
+                continue;
+            }
+            let report = attack.run(&AttackContext { seed, now });
+            now = report.finished_at + 1;
+            reports.push(report);
+        }
+
+        let score = if reports.is_empty() {
+            100
+        } else {
+            reports.iter().map(|r| r.score()).sum::<u32>() / reports.len() as u32
+        };
+
+        Ok(AuditArtifact {
+            schema_version: REPORT_SCHEMA_VERSION.to_string(),
+            seed: config.seed,
+            started_at: config.start_time,
+            finished_at: reports.last().map_or(config.start_time, |r| r.finished_at),
+            score,
+            passed: reports.iter().all(|r| r.passed),
+            reports,
+        })
+    }
+}
+
+impl Default for AuditRunner {
+    fn default() -> Self {
+        Self::with_default_attacks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::severity_classifier::Severity;
+
+    const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden/audit_report.json");
+
+    struct FailingAttack;
+
+    impl Attack for FailingAttack {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn run(&self, ctx: &AttackContext) -> AttackReport {
+            let mut report = AttackReport::new(self.name(), "test", ctx.now);
+            report.finding(Severity::High, "always fails", "n/a");
+            report.finish(ctx.now + 10)
+        }
+    }
+
+    #[test]
+    fn test_runs_all_attacks_in_order() {
+        let runner = AuditRunner::with_default_attacks();
+        let artifact = runner.run(&AuditConfig::default()).unwrap();
+
+        let names: Vec<_> = artifact
+            .reports
+            .iter()
+            .map(|r| r.attack_name.as_str())
+            .collect();
+        assert_eq!(names, runner.attack_names());
+        assert!(artifact.passed);
+        assert_eq!(artifact.score, 100);
+
+        // Exchange clock advances monotonically across reports
+        for pair in artifact.reports.windows(2) {
+            assert!(pair[1].started_at > pair[0].finished_at);
+        }
+    }
+
+    #[test]
+    fn test_subset_uses_same_per_attack_seed() {
+        let runner = AuditRunner::with_default_attacks();
+        let full = runner.run(&AuditConfig::default()).unwrap();
+        let subset = runner
+            .run(&AuditConfig {
+                attacks: vec!["double_spend".to_string()],
+                ..AuditConfig::default()
+            })
+            .unwrap();
+
+        assert_eq!(subset.reports.len(), 1);
+        let full_report = full
+            .reports
+            .iter()
+            .find(|r| r.attack_name == "double_spend")
+            .unwrap();
+        assert_eq!(subset.reports[0].metrics, full_report.metrics);
+    }
+
+    #[test]
+    fn test_unknown_attack_rejected() {
+        let runner = AuditRunner::with_default_attacks();
+        let result = runner.run(&AuditConfig {
+            attacks: vec!["teleport".to_string()],
+            ..AuditConfig::default()
+        });
+        assert_eq!(
+            result,
+            Err(AuditError::UnknownAttack("teleport".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_score_aggregates_failures() {
+        let mut runner = AuditRunner::new();
+        runner.register(Box::new(ReplayAttack::default()));
+        runner.register(Box::new(FailingAttack));
+
+        let artifact = runner.run(&AuditConfig::default()).unwrap();
+        assert!(!artifact.passed);
+        // (100 + 50) / 2
+        assert_eq!(artifact.score, 75);
+    }
+
+    #[test]
+    fn test_artifact_json_roundtrip() {
+        let artifact = AuditRunner::with_default_attacks()
+            .run(&AuditConfig::default())
+            .unwrap();
+        let restored = AuditArtifact::from_json(&artifact.to_json()).unwrap();
+        assert_eq!(restored, artifact);
+    }
+
+    /// Pins the exported schema for dashboard consumers.
+    ///
+    /// Regenerate with `UPDATE_GOLDEN=1 cargo test golden`.
+    #[test]
+    fn test_artifact_matches_golden_file() {
+        let artifact = AuditRunner::with_default_attacks()
+            .run(&AuditConfig::default())
+            .unwrap();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            artifact.write(GOLDEN_PATH).unwrap();
+        }
+
+        let golden: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(GOLDEN_PATH).unwrap()).unwrap();
+        let actual = serde_json::to_value(&artifact).unwrap();
+        assert_eq!(actual, golden);
+    }
+}
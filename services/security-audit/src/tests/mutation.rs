@@ -7,6 +7,5 @@ mod tests {
     fn mutation_testing_placeholder() {
         // Run cargo-mutants externally: `cargo mutants -p security-audit`
         // Documenting its existence here to satisfy the module spec.
-        assert!(true);
     }
 }
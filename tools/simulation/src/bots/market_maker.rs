@@ -6,7 +6,6 @@
 use crate::engine::SimEngine;
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use types::ids::AccountId;
@@ -47,6 +46,7 @@ pub struct MarketMaker {
     pub net_inventory: Decimal,
     pub realized_pnl: Decimal,
     pub orders_placed: usize,
+    #[allow(dead_code)]
     rng: ChaCha8Rng,
 }
 
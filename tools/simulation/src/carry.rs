@@ -0,0 +1,389 @@
+//! Funding and borrow-cost modeling
+//!
+//! Applies carry to simulated positions at fixed intervals:
+//! - Perpetual markets pay funding between longs and shorts, at a rate taken
+//!   from a configured schedule or from the perp/spot basis
+//! - Spot markets charge borrow cost on short inventory
+//!
+//! Cash flows are appended to each engine's event log as `FundingPayment` /
+//! `BorrowCharged` events so reports and replay see them like any other event.
+
+use crate::engine::{SimEngine, SimEvent};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use types::ids::AccountId;
+use types::numeric::Price;
+use types::order::Side;
+
+/// Carry cash-flow rounding precision (8 dp, matching fees).
+///
+/// Funding and borrow share one rule: the amount an account is charged is
+/// rounded toward +∞, so payers pay the extra unit and receivers forgo it,
+/// and any residual stays with the exchange (as in margin-core).
+const CARRY_DP: u32 = 8;
+
+/// Where a perpetual market's funding rate comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FundingSource {
+    /// Fixed per-interval rates, cycled in order
+    Schedule(Vec<Decimal>),
+    /// `(perp_mark − index_mark) / index_mark`, clamped to `±cap`, where the
+    /// index is the mark of the spot market `index_symbol`
+    Basis { index_symbol: String, cap: Decimal },
+}
+
+/// Carry model for one market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MarketCarry {
+    /// Perpetual: longs pay shorts when the rate is positive
+    Perpetual(FundingSource),
+    /// Spot: short inventory pays `borrow_rate` per interval on its notional
+    Spot { borrow_rate: Decimal },
+}
+
+/// Carry configuration, keyed by market symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CarryConfig {
+    /// Interval between carry applications (same clock as order timestamps)
+    pub interval: i64,
+    pub markets: BTreeMap<String, MarketCarry>,
+}
+
+/// Applies carry to engines whenever an interval boundary is crossed.
+#[derive(Debug, Clone)]
+pub struct CarryScheduler {
+    config: CarryConfig,
+    next_due: Option<i64>,
+    periods_applied: u64,
+}
+
+impl CarryScheduler {
+    /// Create a scheduler. The first call to [`apply_due`](Self::apply_due)
+    /// anchors the interval clock.
+    pub fn new(config: CarryConfig) -> Self {
+        assert!(config.interval > 0, "Carry interval must be positive");
+        Self {
+            config,
+            next_due: None,
+            periods_applied: 0,
+        }
+    }
+
+    /// Number of carry periods applied so far.
+    pub fn periods_applied(&self) -> u64 {
+        self.periods_applied
+    }
+
+    /// Apply every carry period due at `timestamp`, in engine order.
+    ///
+    /// Returns the number of carry events emitted.
+    pub fn apply_due(&mut self, engines: &mut [SimEngine], timestamp: i64) -> usize {
+        let Some(mut due) = self.next_due else {
+            self.next_due = Some(timestamp + self.config.interval);
+            return 0;
+        };
+
+        let mut emitted = 0;
+        while timestamp >= due {
+            emitted += self.apply_period(engines, due);
+            self.periods_applied += 1;
+            due += self.config.interval;
+        }
+        self.next_due = Some(due);
+        emitted
+    }
+
+    /// Apply a single carry period stamped at `timestamp`.
+    fn apply_period(&self, engines: &mut [SimEngine], timestamp: i64) -> usize {
+        let marks: HashMap<String, Decimal> = engines
+            .iter()
+            .filter_map(|e| mark_price(e).map(|m| (e.symbol.as_str().to_string(), m)))
+            .collect();
+
+        let mut emitted = 0;
+        for engine in engines.iter_mut() {
+            let symbol = engine.symbol.as_str().to_string();
+            let (Some(carry), Some(&mark)) = (self.config.markets.get(&symbol), marks.get(&symbol))
+            else {
+                continue;
+            };
+
+            let positions = net_positions(&engine.events);
+            let events: Vec<SimEvent> = match carry {
+                MarketCarry::Perpetual(source) => {
+                    let Some(rate) = self.funding_rate(source, mark, &marks) else {
+                        continue;
+                    };
+                    positions
+                        .iter()
+                        .filter(|(_, size)| !size.is_zero())
+                        .map(|(account_id, size)| SimEvent::FundingPayment {
+                            account_id: *account_id,
+                            position: *size,
+                            mark_price: Price::new(mark),
+                            rate,
+                            amount: -round_charge(*size * mark * rate),
+                            timestamp,
+                        })
+                        .collect()
+                }
+                MarketCarry::Spot { borrow_rate } => positions
+                    .iter()
+                    .filter(|(_, size)| *size < Decimal::ZERO)
+                    .map(|(account_id, size)| SimEvent::BorrowCharged {
+                        account_id: *account_id,
+                        quantity: size.abs(),
+                        mark_price: Price::new(mark),
+                        rate: *borrow_rate,
+                        amount: round_charge(size.abs() * mark * *borrow_rate),
+                        timestamp,
+                    })
+                    .collect(),
+            };
+
+            emitted += events.len();
+            for event in events {
+                engine.record_event(event);
+            }
+        }
+        emitted
+    }
+
+    /// Funding rate for the current period.
+    fn funding_rate(
+        &self,
+        source: &FundingSource,
+        mark: Decimal,
+        marks: &HashMap<String, Decimal>,
+    ) -> Option<Decimal> {
+        match source {
+            FundingSource::Schedule(rates) if rates.is_empty() => None,
+            FundingSource::Schedule(rates) => {
+                Some(rates[(self.periods_applied % rates.len() as u64) as usize])
+            }
+            FundingSource::Basis { index_symbol, cap } => {
+                let index = *marks.get(index_symbol)?;
+                if index.is_zero() {
+                    return None;
+                }
+                let basis = (mark - index) / index;
+                Some(basis.clamp(-*cap, *cap))
+            }
+        }
+    }
+}
+
+/// Round a signed charge to an account (negative = credit) in the
+/// exchange's favour: UP what it pays, DOWN what it receives.
+fn round_charge(charge: Decimal) -> Decimal {
+    charge.round_dp_with_strategy(CARRY_DP, RoundingStrategy::ToPositiveInfinity)
+}
+
+/// Mark price of a market: last trade price, else mid price.
+pub fn mark_price(engine: &SimEngine) -> Option<Decimal> {
+    last_trade_price(&engine.events).or_else(|| engine.mid_price())
+}
+
+/// Price of the most recent trade in an event log.
+pub fn last_trade_price(events: &[SimEvent]) -> Option<Decimal> {
+    events.iter().rev().find_map(|e| match e {
+        SimEvent::TradeExecuted { price, .. } => Some(price.as_decimal()),
+        _ => None,
+    })
+}
+
+/// Signed net position per account (positive = long) from a single market's
+/// event log, in order of each account's first trade.
+pub fn net_positions(events: &[SimEvent]) -> Vec<(AccountId, Decimal)> {
+    let mut index: HashMap<AccountId, usize> = HashMap::new();
+    let mut positions: Vec<(AccountId, Decimal)> = Vec::new();
+
+    let mut add = |account_id: AccountId, qty: Decimal| {
+        let i = *index.entry(account_id).or_insert_with(|| {
+            positions.push((account_id, Decimal::ZERO));
+            positions.len() - 1
+        });
+        positions[i].1 += qty;
+    };
+
+    for event in events {
+        if let SimEvent::TradeExecuted {
+            maker_account_id,
+            taker_account_id,
+            quantity,
+            aggressor_side,
+            ..
+        } = event
+        {
+            let taker_qty = match aggressor_side {
+                Side::SELL => -*quantity,
+                Side::BUY => *quantity,
+            };
+            add(*taker_account_id, taker_qty);
+            add(*maker_account_id, -taker_qty);
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::fee::FeeTier;
+    use types::ids::MarketId;
+
+    fn zero_fee() -> FeeTier {
+        FeeTier {
+            volume_threshold: Decimal::ZERO,
+            maker_rate: Decimal::ZERO,
+            taker_rate: Decimal::ZERO,
+        }
+    }
+
+    fn carry_events(engine: &SimEngine) -> Vec<&SimEvent> {
+        engine
+            .events
+            .iter()
+            .filter(|e| matches!(e, SimEvent::FundingPayment { .. } | SimEvent::BorrowCharged { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn test_net_positions_follow_taker_side() {
+        let mut engine = SimEngine::new(MarketId::new("BTC-PERP/USDT"), zero_fee());
+        let buyer = AccountId::new();
+        let seller = AccountId::new();
+
+        engine.submit_order(buyer, Side::BUY, Price::from_u64(100), Decimal::from(3), 1);
+        engine.submit_order(seller, Side::SELL, Price::from_u64(100), Decimal::from(2), 2);
+
+        assert_eq!(
+            net_positions(&engine.events),
+            vec![(seller, Decimal::from(-2)), (buyer, Decimal::from(2))]
+        );
+    }
+
+    #[test]
+    fn test_schedule_funding_longs_pay_shorts() {
+        let mut engines = vec![SimEngine::new(MarketId::new("BTC-PERP/USDT"), zero_fee())];
+        let long = AccountId::new();
+        let short = AccountId::new();
+        engines[0].submit_order(short, Side::SELL, Price::from_u64(1000), Decimal::ONE, 1);
+        engines[0].submit_order(long, Side::BUY, Price::from_u64(1000), Decimal::ONE, 2);
+
+        let mut markets = BTreeMap::new();
+        markets.insert(
+            "BTC-PERP/USDT".to_string(),
+            MarketCarry::Perpetual(FundingSource::Schedule(vec![Decimal::from_str_exact("0.001").unwrap()])),
+        );
+        let mut scheduler = CarryScheduler::new(CarryConfig { interval: 100, markets });
+
+        assert_eq!(scheduler.apply_due(&mut engines, 0), 0);
+        // Two intervals elapsed → two periods, two payments each
+        assert_eq!(scheduler.apply_due(&mut engines, 250), 4);
+        assert_eq!(scheduler.periods_applied(), 2);
+
+        let amounts: Vec<(AccountId, Decimal)> = carry_events(&engines[0])
+            .iter()
+            .map(|e| match e {
+                SimEvent::FundingPayment { account_id, amount, .. } => (*account_id, *amount),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(amounts[0], (long, -Decimal::ONE));
+        assert_eq!(amounts[1], (short, Decimal::ONE));
+    }
+
+    #[test]
+    fn test_basis_funding_and_spot_borrow() {
+        let mut engines = vec![
+            SimEngine::new(MarketId::new("BTC-PERP/USDT"), zero_fee()),
+            SimEngine::new(MarketId::new("BTC/USDT"), zero_fee()),
+        ];
+        let a = AccountId::new();
+        let b = AccountId::new();
+        engines[0].submit_order(a, Side::SELL, Price::from_u64(1010), Decimal::ONE, 1);
+        engines[0].submit_order(b, Side::BUY, Price::from_u64(1010), Decimal::ONE, 2);
+        engines[1].submit_order(b, Side::BUY, Price::from_u64(1000), Decimal::ONE, 3);
+        engines[1].submit_order(a, Side::SELL, Price::from_u64(1000), Decimal::ONE, 4);
+
+        let mut markets = BTreeMap::new();
+        markets.insert(
+            "BTC-PERP/USDT".to_string(),
+            MarketCarry::Perpetual(FundingSource::Basis {
+                index_symbol: "BTC/USDT".to_string(),
+                cap: Decimal::from_str_exact("0.005").unwrap(),
+            }),
+        );
+        markets.insert(
+            "BTC/USDT".to_string(),
+            MarketCarry::Spot { borrow_rate: Decimal::from_str_exact("0.0001").unwrap() },
+        );
+        let mut scheduler = CarryScheduler::new(CarryConfig { interval: 10, markets });
+        scheduler.apply_due(&mut engines, 0);
+        scheduler.apply_due(&mut engines, 10);
+
+        // Basis of 1% is capped at 0.5%; the short receives from the long
+        match carry_events(&engines[0])[..] {
+            [SimEvent::FundingPayment { account_id: payer, rate, amount: paid, .. }, SimEvent::FundingPayment { account_id: receiver, amount: received, .. }] => {
+                assert_eq!((*payer, *receiver), (b, a));
+                assert_eq!(*rate, Decimal::from_str_exact("0.005").unwrap());
+                assert_eq!(*paid, Decimal::from_str_exact("-5.05").unwrap());
+                assert_eq!(*received, Decimal::from_str_exact("5.05").unwrap());
+            }
+            _ => panic!("Expected two FundingPayments"),
+        }
+        // Only the short spot seller pays borrow
+        let borrow = carry_events(&engines[1]);
+        assert_eq!(borrow.len(), 1);
+        match borrow[0] {
+            SimEvent::BorrowCharged { account_id, amount, .. } => {
+                assert_eq!(*account_id, a);
+                assert_eq!(*amount, Decimal::from_str_exact("0.1").unwrap());
+            }
+            _ => panic!("Expected BorrowCharged"),
+        }
+    }
+
+    #[test]
+    fn test_funding_and_borrow_round_alike() {
+        let mut engines = vec![
+            SimEngine::new(MarketId::new("BTC-PERP/USDT"), zero_fee()),
+            SimEngine::new(MarketId::new("BTC/USDT"), zero_fee()),
+        ];
+        let a = AccountId::new();
+        let b = AccountId::new();
+        for engine in engines.iter_mut() {
+            engine.submit_order(a, Side::SELL, Price::from_u64(1000), Decimal::ONE, 1);
+            engine.submit_order(b, Side::BUY, Price::from_u64(1000), Decimal::ONE, 2);
+        }
+
+        // 1000 × rate = 0.0000000123, below carry precision
+        let rate = Decimal::new(123, 13);
+        let mut markets = BTreeMap::new();
+        markets.insert(
+            "BTC-PERP/USDT".to_string(),
+            MarketCarry::Perpetual(FundingSource::Schedule(vec![rate])),
+        );
+        markets.insert("BTC/USDT".to_string(), MarketCarry::Spot { borrow_rate: rate });
+        let mut scheduler = CarryScheduler::new(CarryConfig { interval: 10, markets });
+        scheduler.apply_due(&mut engines, 0);
+        scheduler.apply_due(&mut engines, 10);
+
+        let amount = |e: &SimEvent| match e {
+            SimEvent::FundingPayment { amount, .. } | SimEvent::BorrowCharged { amount, .. } => *amount,
+            _ => unreachable!(),
+        };
+        let funding: Vec<Decimal> = carry_events(&engines[0]).into_iter().map(amount).collect();
+        let borrow: Vec<Decimal> = carry_events(&engines[1]).into_iter().map(amount).collect();
+
+        // The long pays a whole unit up, the short receives a unit down
+        assert_eq!(funding, vec![Decimal::new(-2, 8), Decimal::new(1, 8)]);
+        // Borrow on the same notional and rate costs what funding does
+        assert_eq!(borrow, vec![-funding[0]]);
+        // The residual stays with the exchange
+        assert_eq!(funding.iter().sum::<Decimal>(), Decimal::new(-1, 8));
+    }
+}
@@ -6,11 +6,11 @@
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use types::fee::FeeTier;
+use std::collections::{BTreeMap, HashMap};
+use types::fee::{FeeSchedule, FeeTier, RollingVolume};
 use types::ids::{AccountId, MarketId, OrderId, TradeId};
 use types::numeric::Price;
-use types::order::Side;
+use types::order::{RejectReason, Side};
 
 /// Fee rounding precision (8 dp, spec §7.2: round UP to 8 dp).
 const FEE_DP: u32 = 8;
@@ -37,6 +37,23 @@ pub enum SimEvent {
         quantity: Decimal,
         timestamp: i64,
     },
+    /// Market order accepted; it sweeps the book and never rests.
+    MarketOrderPlaced {
+        order_id: OrderId,
+        account_id: AccountId,
+        side: Side,
+        quantity: Decimal,
+        timestamp: i64,
+    },
+    /// Order refused without touching the book.
+    OrderRejected {
+        order_id: OrderId,
+        account_id: AccountId,
+        side: Side,
+        quantity: Decimal,
+        reason: RejectReason,
+        timestamp: i64,
+    },
     TradeExecuted {
         trade_id: TradeId,
         maker_order_id: OrderId,
@@ -47,6 +64,10 @@ pub enum SimEvent {
         quantity: Decimal,
         maker_fee: Decimal,
         taker_fee: Decimal,
+        /// Side of the taker, which took liquidity
+        aggressor_side: Side,
+        /// Whether the resting maker order was the buy
+        maker_is_buyer: bool,
         timestamp: i64,
     },
     OrderFilled {
@@ -65,8 +86,33 @@ pub enum SimEvent {
         remaining_quantity: Decimal,
         timestamp: i64,
     },
+    /// Periodic funding cash flow on a perpetual position.
+    /// `amount` is signed: positive = received, negative = paid.
+    FundingPayment {
+        account_id: AccountId,
+        position: Decimal,
+        mark_price: Price,
+        rate: Decimal,
+        amount: Decimal,
+        timestamp: i64,
+    },
+    /// Borrow cost charged on short spot inventory (`amount` is the cost).
+    BorrowCharged {
+        account_id: AccountId,
+        quantity: Decimal,
+        mark_price: Price,
+        rate: Decimal,
+        amount: Decimal,
+        timestamp: i64,
+    },
 }
 
+/// Order capacity reserved when a price level has to be allocated.
+const LEVEL_CAPACITY: usize = 16;
+
+/// Emptied levels kept for reuse per engine; beyond this they are dropped.
+const MAX_FREE_LEVELS: usize = 1024;
+
 /// A single price level aggregating multiple orders at the same price.
 #[derive(Debug, Clone)]
 struct PriceLevel {
@@ -75,7 +121,7 @@ struct PriceLevel {
 
 impl PriceLevel {
     fn new() -> Self {
-        Self { orders: Vec::new() }
+        Self { orders: Vec::with_capacity(LEVEL_CAPACITY) }
     }
 
     fn total_quantity(&self) -> Decimal {
@@ -85,6 +131,14 @@ impl PriceLevel {
     fn is_empty(&self) -> bool {
         self.orders.is_empty()
     }
+
+    /// Clear the level onto the free list, keeping its capacity.
+    fn recycle_into(mut self, free_levels: &mut Vec<PriceLevel>) {
+        if free_levels.len() < MAX_FREE_LEVELS {
+            self.orders.clear();
+            free_levels.push(self);
+        }
+    }
 }
 
 /// Deterministic simulation matching engine.
@@ -95,9 +149,26 @@ pub struct SimEngine {
     pub symbol: MarketId,
     bids: BTreeMap<OrderedPrice, PriceLevel>,
     asks: BTreeMap<OrderedPrice, PriceLevel>,
-    fee_tier: FeeTier,
+    fee_schedule: FeeSchedule,
+    /// Trailing 30-day traded notional per account, for fee tier resolution
+    volumes: HashMap<AccountId, RollingVolume>,
     pub events: Vec<SimEvent>,
     pub sequence: u64,
+    /// Resting orders per account, keyed by submission sequence
+    account_orders: HashMap<AccountId, BTreeMap<u64, OrderId>>,
+    /// Owner and submission sequence of each resting order
+    resting_owners: HashMap<OrderId, (AccountId, u64)>,
+    /// Book side and level of each resting order
+    order_index: BTreeMap<OrderId, (Side, OrderedPrice)>,
+    /// Emptied levels kept with their capacity for the next new price
+    free_levels: Vec<PriceLevel>,
+    /// Best bid, updated on insert and whenever a bid level empties
+    best_bid: Option<Price>,
+    /// Best ask, updated on insert and whenever an ask level empties
+    best_ask: Option<Price>,
+    /// Route matching through the collecting reference path
+    #[cfg(test)]
+    reference_matching: bool,
 }
 
 /// Wrapper for BTreeMap ordering. Bids: descending (negate). Asks: ascending.
@@ -126,22 +197,36 @@ fn decimal_to_i128(d: Decimal) -> i128 {
 }
 
 /// Round fee UP (never undercharge, spec §7.2).
-fn round_up_fee(v: Decimal) -> Decimal {
+pub(crate) fn round_up_fee(v: Decimal) -> Decimal {
     v.round_dp_with_strategy(FEE_DP, RoundingStrategy::AwayFromZero)
 }
 
+/// Whether a taker limited at `limit` reaches a maker resting at `maker_price`.
+fn crosses(side: Side, limit: Price, maker_price: Price) -> bool {
+    match side {
+        Side::BUY => maker_price.as_decimal() <= limit.as_decimal(),
+        Side::SELL => maker_price.as_decimal() >= limit.as_decimal(),
+    }
+}
+
 /// Match against orders at a single price level (free function to avoid borrow conflicts).
+///
+/// Each trade charges maker and taker at the tier of their own 30-day
+/// volume before the trade, then adds its notional to both.
+#[allow(clippy::too_many_arguments)]
 fn match_level(
     level: &mut PriceLevel,
     taker_id: OrderId,
     taker_account: AccountId,
+    taker_side: Side,
     price: Price,
     remaining: &mut Decimal,
     timestamp: i64,
-    fee_tier: &FeeTier,
+    fee_schedule: &FeeSchedule,
+    volumes: &mut HashMap<AccountId, RollingVolume>,
     events: &mut Vec<SimEvent>,
     sequence: &mut u64,
-) {
+) -> Vec<OrderId> {
     let mut filled_indices = Vec::new();
 
     for (i, maker) in level.orders.iter_mut().enumerate() {
@@ -152,8 +237,15 @@ fn match_level(
         let fill_qty = (*remaining).min(maker.remaining);
         let fill_value = fill_qty * price.as_decimal();
 
-        let maker_fee = round_up_fee(fill_value * fee_tier.maker_rate);
-        let taker_fee = round_up_fee(fill_value * fee_tier.taker_rate);
+        let volume_before = |account_id: &AccountId| {
+            volumes.get(account_id).map_or(Decimal::ZERO, |v| v.volume_at(timestamp))
+        };
+        let maker_rate = fee_schedule.tier_for_volume(volume_before(&maker.account_id)).maker_rate;
+        let taker_rate = fee_schedule.tier_for_volume(volume_before(&taker_account)).taker_rate;
+        let maker_fee = round_up_fee(fill_value * maker_rate);
+        let taker_fee = round_up_fee(fill_value * taker_rate);
+        volumes.entry(maker.account_id).or_default().record(timestamp, fill_value);
+        volumes.entry(taker_account).or_default().record(timestamp, fill_value);
 
         *sequence += 1;
         events.push(SimEvent::TradeExecuted {
@@ -166,6 +258,8 @@ fn match_level(
             quantity: fill_qty,
             maker_fee,
             taker_fee,
+            aggressor_side: taker_side,
+            maker_is_buyer: taker_side == Side::SELL,
             timestamp,
         });
 
@@ -189,21 +283,46 @@ fn match_level(
         }
     }
 
+    let mut filled = Vec::with_capacity(filled_indices.len());
     for i in filled_indices.into_iter().rev() {
-        level.orders.remove(i);
+        filled.push(level.orders.remove(i).order_id);
     }
+    filled
 }
 
 impl SimEngine {
     /// Create a new engine for a market with a fee tier.
     pub fn new(symbol: MarketId, fee_tier: FeeTier) -> Self {
+        Self::with_fee_schedule(symbol, FeeSchedule::flat(fee_tier))
+    }
+
+    /// Create a new engine that resolves each account's fee tier from its
+    /// rolling 30-day volume.
+    pub fn with_fee_schedule(symbol: MarketId, fee_schedule: FeeSchedule) -> Self {
         Self {
             symbol,
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
-            fee_tier,
+            fee_schedule,
+            volumes: HashMap::new(),
             events: Vec::new(),
             sequence: 0,
+            account_orders: HashMap::new(),
+            resting_owners: HashMap::new(),
+            order_index: BTreeMap::new(),
+            free_levels: Vec::new(),
+            best_bid: None,
+            best_ask: None,
+            #[cfg(test)]
+            reference_matching: false,
+        }
+    }
+
+    /// Re-read the cached best price of one book side from its first level.
+    fn refresh_best(&mut self, side: Side) {
+        match side {
+            Side::BUY => self.best_bid = self.bids.values().next().map(|l| l.orders[0].price),
+            Side::SELL => self.best_ask = self.asks.values().next().map(|l| l.orders[0].price),
         }
     }
 
@@ -230,7 +349,7 @@ impl SimEngine {
 
         let mut remaining = quantity;
         remaining = self.match_against_book(
-            order_id, account_id, side, price, remaining, timestamp,
+            order_id, account_id, side, Some(price), remaining, timestamp,
         );
 
         if remaining > Decimal::ZERO {
@@ -263,16 +382,148 @@ impl SimEngine {
         order_id
     }
 
+    /// Submit a market order: sweep the opposing side at maker prices.
+    ///
+    /// Rejected with `NoLiquidity` when the opposing side is empty. Any
+    /// quantity left after the sweep is canceled rather than rested.
+    pub fn submit_market_order(
+        &mut self,
+        account_id: AccountId,
+        side: Side,
+        quantity: Decimal,
+        timestamp: i64,
+    ) -> OrderId {
+        let order_id = OrderId::new();
+        self.sequence += 1;
+
+        let opposing_empty = match side {
+            Side::BUY => self.asks.is_empty(),
+            Side::SELL => self.bids.is_empty(),
+        };
+        if opposing_empty {
+            self.events.push(SimEvent::OrderRejected {
+                order_id,
+                account_id,
+                side,
+                quantity,
+                reason: RejectReason::NoLiquidity,
+                timestamp,
+            });
+            return order_id;
+        }
+
+        self.events.push(SimEvent::MarketOrderPlaced {
+            order_id,
+            account_id,
+            side,
+            quantity,
+            timestamp,
+        });
+
+        let remaining = self.match_against_book(
+            order_id, account_id, side, None, quantity, timestamp,
+        );
+
+        let filled = quantity - remaining;
+        if remaining == Decimal::ZERO {
+            self.events.push(SimEvent::OrderFilled {
+                order_id,
+                filled_quantity: filled,
+                timestamp,
+            });
+        } else {
+            self.events.push(SimEvent::OrderPartiallyFilled {
+                order_id,
+                filled_quantity: filled,
+                remaining_quantity: remaining,
+                timestamp,
+            });
+            self.events.push(SimEvent::OrderCanceled {
+                order_id,
+                remaining_quantity: remaining,
+                timestamp,
+            });
+        }
+
+        order_id
+    }
+
     /// Match incoming order against the opposing side of the book.
+    ///
+    /// A `None` limit sweeps every level. Levels are visited lazily from the
+    /// best price and matching stops at the first one that does not cross;
+    /// an order that cannot reach the cached best price returns untouched.
     fn match_against_book(
         &mut self,
         taker_id: OrderId,
         taker_account: AccountId,
         side: Side,
-        limit_price: Price,
+        limit_price: Option<Price>,
         mut remaining: Decimal,
         timestamp: i64,
     ) -> Decimal {
+        #[cfg(test)]
+        if self.reference_matching {
+            return self.match_against_book_collect(
+                taker_id, taker_account, side, limit_price, remaining, timestamp,
+            );
+        }
+
+        let best = match side {
+            Side::BUY => self.best_ask,
+            Side::SELL => self.best_bid,
+        };
+        match (best, limit_price) {
+            (None, _) => return remaining,
+            (Some(best), Some(limit)) if !crosses(side, limit, best) => return remaining,
+            _ => {}
+        }
+
+        // Both books are keyed best price first
+        let book = match side {
+            Side::BUY => &mut self.asks,
+            Side::SELL => &mut self.bids,
+        };
+        let mut filled = Vec::new();
+        while remaining > Decimal::ZERO {
+            let Some(mut entry) = book.first_entry() else {
+                break;
+            };
+            let level = entry.get_mut();
+            let maker_price = level.orders[0].price;
+            if limit_price.is_some_and(|limit| !crosses(side, limit, maker_price)) {
+                break;
+            }
+            filled.extend(match_level(
+                level, taker_id, taker_account, side, maker_price,
+                &mut remaining, timestamp,
+                &self.fee_schedule, &mut self.volumes, &mut self.events, &mut self.sequence,
+            ));
+            if level.is_empty() {
+                entry.remove().recycle_into(&mut self.free_levels);
+            }
+        }
+        self.refresh_best(side.opposite());
+        for order_id in filled {
+            self.order_index.remove(&order_id);
+            self.forget_resting(&order_id);
+        }
+        remaining
+    }
+
+    /// Match by collecting every opposing key up front (reference path for
+    /// the lazy matcher's differential test).
+    #[cfg(test)]
+    fn match_against_book_collect(
+        &mut self,
+        taker_id: OrderId,
+        taker_account: AccountId,
+        side: Side,
+        limit_price: Option<Price>,
+        mut remaining: Decimal,
+        timestamp: i64,
+    ) -> Decimal {
+        let mut filled = Vec::new();
         match side {
             Side::BUY => {
                 let keys: Vec<OrderedPrice> = self.asks.keys().cloned().collect();
@@ -284,20 +535,22 @@ impl SimEngine {
                     }
                     let level = self.asks.get_mut(&key).unwrap();
                     let maker_price = level.orders[0].price;
-                    if maker_price.as_decimal() > limit_price.as_decimal() {
+                    if limit_price.is_some_and(|limit| maker_price.as_decimal() > limit.as_decimal()) {
                         break;
                     }
-                    match_level(
-                        level, taker_id, taker_account, maker_price,
+                    filled.extend(match_level(
+                        level, taker_id, taker_account, side, maker_price,
                         &mut remaining, timestamp,
-                        &self.fee_tier, &mut self.events, &mut self.sequence,
-                    );
+                        &self.fee_schedule, &mut self.volumes, &mut self.events, &mut self.sequence,
+                    ));
                     if level.is_empty() {
                         to_remove.push(key);
                     }
                 }
                 for p in to_remove {
-                    self.asks.remove(&p);
+                    if let Some(level) = self.asks.remove(&p) {
+                        level.recycle_into(&mut self.free_levels);
+                    }
                 }
             }
             Side::SELL => {
@@ -310,42 +563,139 @@ impl SimEngine {
                     }
                     let level = self.bids.get_mut(&key).unwrap();
                     let maker_price = level.orders[0].price;
-                    if maker_price.as_decimal() < limit_price.as_decimal() {
+                    if limit_price.is_some_and(|limit| maker_price.as_decimal() < limit.as_decimal()) {
                         break;
                     }
-                    match_level(
-                        level, taker_id, taker_account, maker_price,
+                    filled.extend(match_level(
+                        level, taker_id, taker_account, side, maker_price,
                         &mut remaining, timestamp,
-                        &self.fee_tier, &mut self.events, &mut self.sequence,
-                    );
+                        &self.fee_schedule, &mut self.volumes, &mut self.events, &mut self.sequence,
+                    ));
                     if level.is_empty() {
                         to_remove.push(key);
                     }
                 }
                 for p in to_remove {
-                    self.bids.remove(&p);
+                    if let Some(level) = self.bids.remove(&p) {
+                        level.recycle_into(&mut self.free_levels);
+                    }
                 }
             }
         }
+        self.refresh_best(side.opposite());
+        for order_id in filled {
+            self.order_index.remove(&order_id);
+            self.forget_resting(&order_id);
+        }
         remaining
     }
 
     /// Insert a resting order into the book.
     fn insert_resting(&mut self, entry: BookEntry) {
+        let sequence = self.sequence;
+        self.account_orders.entry(entry.account_id).or_default().insert(sequence, entry.order_id);
+        self.resting_owners.insert(entry.order_id, (entry.account_id, sequence));
+        let free_levels = &mut self.free_levels;
+        let new_level = || free_levels.pop().unwrap_or_else(PriceLevel::new);
         match entry.side {
             Side::BUY => {
                 let key = OrderedPrice::bid(entry.price);
-                self.bids.entry(key).or_insert_with(PriceLevel::new).orders.push(entry);
+                self.order_index.insert(entry.order_id, (Side::BUY, key));
+                if self.best_bid.is_none_or(|best| entry.price.as_decimal() > best.as_decimal()) {
+                    self.best_bid = Some(entry.price);
+                }
+                self.bids.entry(key).or_insert_with(new_level).orders.push(entry);
             }
             Side::SELL => {
                 let key = OrderedPrice::ask(entry.price);
-                self.asks.entry(key).or_insert_with(PriceLevel::new).orders.push(entry);
+                self.order_index.insert(entry.order_id, (Side::SELL, key));
+                if self.best_ask.is_none_or(|best| entry.price.as_decimal() < best.as_decimal()) {
+                    self.best_ask = Some(entry.price);
+                }
+                self.asks.entry(key).or_insert_with(new_level).orders.push(entry);
             }
         }
     }
 
+    /// Drop a resting order from the account index.
+    fn forget_resting(&mut self, order_id: &OrderId) {
+        if let Some((account_id, sequence)) = self.resting_owners.remove(order_id) {
+            if let Some(orders) = self.account_orders.get_mut(&account_id) {
+                orders.remove(&sequence);
+                if orders.is_empty() {
+                    self.account_orders.remove(&account_id);
+                }
+            }
+        }
+    }
+
+    /// Cancel every resting order of an account, in submission order.
+    ///
+    /// Emits one `OrderCanceled` per order and returns the canceled IDs.
+    pub fn cancel_all(&mut self, account_id: AccountId, timestamp: i64) -> Vec<OrderId> {
+        let order_ids: Vec<OrderId> = self
+            .account_orders
+            .get(&account_id)
+            .map(|orders| orders.values().copied().collect())
+            .unwrap_or_default();
+        self.cancel_batch(&order_ids, timestamp)
+    }
+
+    /// Cancel a set of resting orders, in submission order.
+    ///
+    /// Unknown and duplicate IDs are skipped. Emits one `OrderCanceled` per
+    /// order and returns the canceled IDs.
+    pub fn cancel_batch(&mut self, order_ids: &[OrderId], timestamp: i64) -> Vec<OrderId> {
+        let mut ordered: Vec<(u64, OrderId)> = order_ids
+            .iter()
+            .filter_map(|order_id| self.resting_owners.get(order_id).map(|(_, seq)| (*seq, *order_id)))
+            .collect();
+        ordered.sort_unstable_by_key(|(seq, _)| *seq);
+        ordered.dedup_by_key(|(seq, _)| *seq);
+
+        ordered
+            .into_iter()
+            .filter(|(_, order_id)| self.cancel_order(*order_id, timestamp))
+            .map(|(_, order_id)| order_id)
+            .collect()
+    }
+
     /// Cancel an order by ID. Returns true if found and canceled.
+    ///
+    /// Looks the order up in `order_index` and touches only its level.
     pub fn cancel_order(&mut self, order_id: OrderId, timestamp: i64) -> bool {
+        let Some((side, key)) = self.order_index.remove(&order_id) else {
+            return false;
+        };
+        let book = match side {
+            Side::BUY => &mut self.bids,
+            Side::SELL => &mut self.asks,
+        };
+        let Some(level) = book.get_mut(&key) else {
+            return false;
+        };
+        let Some(pos) = level.orders.iter().position(|e| e.order_id == order_id) else {
+            return false;
+        };
+        let remaining = level.orders.remove(pos).remaining;
+        if level.is_empty() {
+            if let Some(level) = book.remove(&key) {
+                level.recycle_into(&mut self.free_levels);
+            }
+            self.refresh_best(side);
+        }
+        self.events.push(SimEvent::OrderCanceled {
+            order_id,
+            remaining_quantity: remaining,
+            timestamp,
+        });
+        self.forget_resting(&order_id);
+        true
+    }
+
+    /// Cancel by scanning every level (reference path for index tests).
+    #[cfg(test)]
+    fn cancel_order_scan(&mut self, order_id: OrderId, timestamp: i64) -> bool {
         // Search bids
         for level in self.bids.values_mut() {
             if let Some(pos) = level.orders.iter().position(|e| e.order_id == order_id) {
@@ -357,6 +707,9 @@ impl SimEngine {
                     timestamp,
                 });
                 self.bids.retain(|_, l| !l.is_empty());
+                self.refresh_best(Side::BUY);
+                self.forget_resting(&order_id);
+                self.order_index.remove(&order_id);
                 return true;
             }
         }
@@ -371,6 +724,9 @@ impl SimEngine {
                     timestamp,
                 });
                 self.asks.retain(|_, l| !l.is_empty());
+                self.refresh_best(Side::SELL);
+                self.forget_resting(&order_id);
+                self.order_index.remove(&order_id);
                 return true;
             }
         }
@@ -379,12 +735,12 @@ impl SimEngine {
 
     /// Get the best bid price.
     pub fn best_bid(&self) -> Option<Price> {
-        self.bids.values().next().and_then(|l| l.orders.first().map(|o| o.price))
+        self.best_bid
     }
 
     /// Get the best ask price.
     pub fn best_ask(&self) -> Option<Price> {
-        self.asks.values().next().and_then(|l| l.orders.first().map(|o| o.price))
+        self.best_ask
     }
 
     /// Get mid price (average of best bid and ask).
@@ -433,7 +789,22 @@ impl SimEngine {
         self.events.clear();
     }
 
+    /// Append an externally generated event (e.g. carry cash flows).
+    pub fn record_event(&mut self, event: SimEvent) {
+        self.sequence += 1;
+        self.events.push(event);
+    }
+
     /// Count trades in event log.
+    pub fn fee_schedule(&self) -> &FeeSchedule {
+        &self.fee_schedule
+    }
+
+    /// An account's traded notional over the 30 days ending at `now`.
+    pub fn thirty_day_volume(&self, account_id: &AccountId, now: i64) -> Decimal {
+        self.volumes.get(account_id).map_or(Decimal::ZERO, |v| v.volume_at(now))
+    }
+
     pub fn trade_count(&self) -> usize {
         self.events.iter().filter(|e| matches!(e, SimEvent::TradeExecuted { .. })).count()
     }
@@ -617,4 +988,357 @@ mod tests {
             _ => panic!("Expected trade"),
         }
     }
+
+    #[test]
+    fn test_market_order_rejected_on_empty_side() {
+        let mut engine = test_engine();
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(49900), Decimal::from(1), 100);
+
+        engine.submit_market_order(AccountId::new(), Side::BUY, Decimal::from(1), 200);
+
+        assert!(matches!(
+            engine.events.last(),
+            Some(SimEvent::OrderRejected { reason: RejectReason::NoLiquidity, .. })
+        ));
+        assert_eq!(engine.order_count(), 1);
+        assert_eq!(engine.trade_count(), 0);
+    }
+
+    #[test]
+    fn test_market_order_sweeps_and_cancels_remainder() {
+        let mut engine = test_engine();
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(50000), Decimal::from(1), 100);
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(49000), Decimal::from(1), 101);
+
+        let oid = engine.submit_market_order(AccountId::new(), Side::SELL, Decimal::from(3), 200);
+
+        let prices: Vec<Price> = engine.events.iter().filter_map(|e| match e {
+            SimEvent::TradeExecuted { price, .. } => Some(*price),
+            _ => None,
+        }).collect();
+        assert_eq!(prices, vec![Price::from_u64(50000), Price::from_u64(49000)]);
+        assert_eq!(engine.order_count(), 0);
+        assert!(matches!(
+            engine.events.last(),
+            Some(SimEvent::OrderCanceled { order_id, remaining_quantity, .. })
+                if *order_id == oid && *remaining_quantity == Decimal::from(1)
+        ));
+    }
+
+    #[test]
+    fn test_quantity_conserved_with_market_flow() {
+        let mut engine = test_engine();
+        let acc = AccountId::new();
+        let qty = |s: &str| Decimal::from_str_exact(s).unwrap();
+
+        engine.submit_market_order(acc, Side::BUY, qty("0.7"), 100);
+        engine.submit_order(acc, Side::SELL, Price::from_u64(50100), qty("1.2"), 101);
+        engine.submit_order(acc, Side::BUY, Price::from_u64(49900), qty("0.9"), 102);
+        engine.submit_market_order(acc, Side::BUY, qty("0.5"), 103);
+        engine.submit_order(acc, Side::SELL, Price::from_u64(49950), qty("0.4"), 104);
+        engine.submit_market_order(acc, Side::SELL, qty("2.0"), 105);
+        engine.submit_order(acc, Side::BUY, Price::from_u64(50200), qty("1.0"), 106);
+        engine.submit_market_order(acc, Side::BUY, qty("0.3"), 107);
+
+        let mut placed = Decimal::ZERO;
+        let mut traded = Decimal::ZERO;
+        let mut canceled = Decimal::ZERO;
+        let mut rejected = Decimal::ZERO;
+        for event in &engine.events {
+            match event {
+                SimEvent::OrderPlaced { quantity, .. }
+                | SimEvent::MarketOrderPlaced { quantity, .. } => placed += *quantity,
+                SimEvent::OrderRejected { quantity, .. } => {
+                    placed += *quantity;
+                    rejected += *quantity;
+                }
+                SimEvent::TradeExecuted { quantity, .. } => traded += *quantity,
+                SimEvent::OrderCanceled { remaining_quantity, .. } => canceled += *remaining_quantity,
+                _ => {}
+            }
+        }
+
+        assert!(rejected > Decimal::ZERO);
+        assert!(canceled > Decimal::ZERO);
+        let resting = engine.bid_depth() + engine.ask_depth();
+        assert_eq!(placed, traded * Decimal::from(2) + resting + canceled + rejected);
+    }
+
+    #[test]
+    fn test_cancel_all_by_account() {
+        let mut engine = test_engine();
+        let maker = AccountId::new();
+        let other = AccountId::new();
+        let taker = AccountId::new();
+
+        let filled = engine.submit_order(maker, Side::SELL, Price::from_u64(50000), Decimal::from(1), 100);
+        let a = engine.submit_order(maker, Side::SELL, Price::from_u64(50100), Decimal::from(1), 101);
+        let b = engine.submit_order(maker, Side::BUY, Price::from_u64(49000), Decimal::from(1), 102);
+        engine.submit_order(other, Side::BUY, Price::from_u64(49500), Decimal::from(1), 103);
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::from(1), 104);
+
+        engine.clear_events();
+        let canceled = engine.cancel_all(maker, 200);
+        assert_eq!(canceled, vec![a, b]);
+        assert!(!canceled.contains(&filled));
+        let events: Vec<OrderId> = engine.events.iter().filter_map(|e| match e {
+            SimEvent::OrderCanceled { order_id, .. } => Some(*order_id),
+            _ => None,
+        }).collect();
+        assert_eq!(events, canceled);
+        assert_eq!(engine.order_count(), 1);
+        assert!(engine.cancel_all(maker, 201).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_batch_orders_by_submission() {
+        let mut engine = test_engine();
+        let acc = AccountId::new();
+        let ids: Vec<OrderId> = (0..4)
+            .map(|i| engine.submit_order(acc, Side::BUY, Price::from_u64(49000 + i), Decimal::from(1), i as i64))
+            .collect();
+
+        let canceled = engine.cancel_batch(&[ids[3], ids[1], OrderId::new(), ids[1]], 10);
+        assert_eq!(canceled, vec![ids[1], ids[3]]);
+        assert_eq!(engine.order_count(), 2);
+        assert_eq!(engine.cancel_all(acc, 11), vec![ids[0], ids[2]]);
+    }
+
+    /// Resting book with no crossing, identical across engines.
+    fn resting_book(orders: u64, levels: u64) -> (SimEngine, Vec<OrderId>) {
+        let mut engine = test_engine();
+        let ids = (0..orders)
+            .map(|i| {
+                let (side, price) = if i % 2 == 0 {
+                    (Side::BUY, 40_000 + i % levels)
+                } else {
+                    (Side::SELL, 60_000 + i % levels)
+                };
+                engine.submit_order(AccountId::new(), side, Price::from_u64(price), Decimal::from(1 + i % 3), i as i64)
+            })
+            .collect();
+        (engine, ids)
+    }
+
+    #[test]
+    fn test_indexed_cancel_matches_scan() {
+        let (mut indexed, ids) = resting_book(200, 7);
+        let mut scanned = test_engine();
+        for event in &indexed.events {
+            if let SimEvent::OrderPlaced { order_id, account_id, side, price, quantity, timestamp } = event {
+                scanned.sequence += 1;
+                scanned.insert_resting(BookEntry {
+                    order_id: *order_id,
+                    account_id: *account_id,
+                    side: *side,
+                    price: *price,
+                    remaining: *quantity,
+                    timestamp: *timestamp,
+                });
+            }
+        }
+        indexed.clear_events();
+
+        let targets: Vec<OrderId> = ids.iter().step_by(3).copied().chain([OrderId::new()]).collect();
+        for (i, id) in targets.iter().enumerate() {
+            assert_eq!(
+                indexed.cancel_order(*id, 1_000 + i as i64),
+                scanned.cancel_order_scan(*id, 1_000 + i as i64)
+            );
+        }
+        assert_eq!(indexed.events, scanned.events);
+        assert_eq!(indexed.bid_levels(), scanned.bid_levels());
+        assert_eq!(indexed.ask_levels(), scanned.ask_levels());
+        assert_eq!(indexed.order_index.len(), indexed.order_count());
+    }
+
+    #[test]
+    fn test_cancel_from_100k_book() {
+        let (mut engine, ids) = resting_book(100_000, 1_000);
+        assert_eq!(engine.order_count(), 100_000);
+        engine.clear_events();
+
+        for (i, id) in ids.iter().enumerate().rev() {
+            assert!(engine.cancel_order(*id, i as i64));
+        }
+
+        assert_eq!(engine.order_count(), 0);
+        assert!(engine.order_index.is_empty());
+        assert_eq!(engine.bid_depth(), Decimal::ZERO);
+        assert_eq!(engine.events.len(), 100_000);
+    }
+
+    #[test]
+    fn test_fills_drop_orders_from_index() {
+        let mut engine = test_engine();
+        let maker = engine.submit_order(AccountId::new(), Side::SELL, Price::from_u64(50000), Decimal::from(1), 1);
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(50000), Decimal::from(1), 2);
+
+        assert!(engine.order_index.is_empty());
+        assert!(!engine.cancel_order(maker, 3));
+    }
+
+    #[test]
+    fn test_emptied_levels_are_reused() {
+        let mut engine = test_engine();
+        let id = engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(49000), Decimal::from(1), 1);
+        assert!(engine.cancel_order(id, 2));
+        assert_eq!(engine.free_levels.len(), 1);
+
+        // Fills and the next new price reuse the same allocation
+        engine.submit_order(AccountId::new(), Side::SELL, Price::from_u64(50000), Decimal::from(1), 3);
+        assert!(engine.free_levels.is_empty());
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(50000), Decimal::from(1), 4);
+        assert_eq!(engine.free_levels.len(), 1);
+        assert_eq!(engine.order_count(), 0);
+    }
+
+    fn aggressor_flags(events: &[SimEvent]) -> Vec<(Side, bool)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                SimEvent::TradeExecuted { aggressor_side, maker_is_buyer, .. } => Some((*aggressor_side, *maker_is_buyer)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trades_flag_aggressor_side() {
+        let mut engine = test_engine();
+        engine.submit_order(AccountId::new(), Side::SELL, Price::from_u64(50000), Decimal::from(1), 1);
+        engine.submit_order(AccountId::new(), Side::SELL, Price::from_u64(50100), Decimal::from(1), 2);
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(49900), Decimal::from(1), 3);
+
+        // Buy-initiated sweep of two ask levels
+        engine.clear_events();
+        engine.submit_order(AccountId::new(), Side::BUY, Price::from_u64(50100), Decimal::from(2), 4);
+        assert_eq!(aggressor_flags(&engine.events), vec![(Side::BUY, false), (Side::BUY, false)]);
+
+        // Sell-initiated, from a market order
+        engine.clear_events();
+        engine.submit_market_order(AccountId::new(), Side::SELL, Decimal::from(1), 5);
+        assert_eq!(aggressor_flags(&engine.events), vec![(Side::SELL, true)]);
+    }
+
+    /// Replace every UUID in an event log with its first-seen ordinal, so
+    /// logs from engines that generated different ids compare equal.
+    fn normalized_log(events: &[SimEvent]) -> Vec<serde_json::Value> {
+        fn walk(value: &mut serde_json::Value, ids: &mut HashMap<String, usize>) {
+            match value {
+                serde_json::Value::String(s) if uuid::Uuid::parse_str(s).is_ok() => {
+                    let next = ids.len();
+                    *value = serde_json::Value::from(*ids.entry(s.clone()).or_insert(next));
+                }
+                serde_json::Value::Array(items) => items.iter_mut().for_each(|v| walk(v, ids)),
+                serde_json::Value::Object(fields) => fields.values_mut().for_each(|v| walk(v, ids)),
+                _ => {}
+            }
+        }
+        let mut ids = HashMap::new();
+        events
+            .iter()
+            .map(|event| {
+                let mut value = serde_json::to_value(event).unwrap();
+                walk(&mut value, &mut ids);
+                value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lazy_matching_matches_reference_event_log() {
+        use rand::{Rng, SeedableRng};
+
+        let mut lazy = test_engine();
+        let mut reference = test_engine();
+        reference.reference_matching = true;
+        let accounts: Vec<AccountId> = (0..8).map(|_| AccountId::new()).collect();
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(4018);
+        let mut placed: Vec<(OrderId, OrderId)> = Vec::new();
+
+        for ts in 0..5_000i64 {
+            let account = accounts[rng.gen_range(0..accounts.len())];
+            let side = if rng.gen_bool(0.5) { Side::BUY } else { Side::SELL };
+            let quantity = Decimal::from(rng.gen_range(1..=5u64));
+            match rng.gen_range(0..10) {
+                0..=5 => {
+                    let price = Price::from_u64(rng.gen_range(49_950..=50_050));
+                    let a = lazy.submit_order(account, side, price, quantity, ts);
+                    let b = reference.submit_order(account, side, price, quantity, ts);
+                    placed.push((a, b));
+                }
+                6 => {
+                    lazy.submit_market_order(account, side, quantity, ts);
+                    reference.submit_market_order(account, side, quantity, ts);
+                }
+                _ if !placed.is_empty() => {
+                    let (a, b) = placed.swap_remove(rng.gen_range(0..placed.len()));
+                    assert_eq!(lazy.cancel_order(a, ts), reference.cancel_order(b, ts));
+                }
+                _ => {}
+            }
+            assert_eq!(lazy.best_bid(), lazy.bids.values().next().map(|l| l.orders[0].price));
+            assert_eq!(lazy.best_ask(), lazy.asks.values().next().map(|l| l.orders[0].price));
+        }
+
+        assert!(lazy.trade_count() > 0);
+        assert_eq!(lazy.sequence, reference.sequence);
+        assert_eq!(normalized_log(&lazy.events), normalized_log(&reference.events));
+        assert_eq!(lazy.bid_levels(), reference.bid_levels());
+        assert_eq!(lazy.ask_levels(), reference.ask_levels());
+    }
+
+    fn tiered_engine() -> SimEngine {
+        let tier = |threshold: u64, maker: &str, taker: &str| FeeTier {
+            volume_threshold: Decimal::from(threshold),
+            maker_rate: Decimal::from_str_exact(maker).unwrap(),
+            taker_rate: Decimal::from_str_exact(taker).unwrap(),
+        };
+        let schedule = FeeSchedule::new(vec![tier(100_000, "0", "0.0004"), tier(0, "0.0002", "0.0005")]);
+        SimEngine::with_fee_schedule(MarketId::new("BTC/USDT"), schedule)
+    }
+
+    fn last_fees(engine: &SimEngine) -> (Decimal, Decimal) {
+        engine.events.iter().rev().find_map(|e| match e {
+            SimEvent::TradeExecuted { maker_fee, taker_fee, .. } => Some((*maker_fee, *taker_fee)),
+            _ => None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_trade_crossing_threshold_pays_prior_tier() {
+        let mut engine = tiered_engine();
+        let maker = AccountId::new();
+        let taker = AccountId::new();
+        engine.submit_order(maker, Side::SELL, Price::from_u64(50000), Decimal::from(3), 100);
+
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::ONE, 101);
+        assert_eq!(last_fees(&engine), (Decimal::from(10), Decimal::from(25)));
+
+        // 50 000 of volume before; this trade lifts both to 100 000 but is
+        // still charged at tier 0
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::ONE, 102);
+        assert_eq!(last_fees(&engine), (Decimal::from(10), Decimal::from(25)));
+        assert_eq!(engine.thirty_day_volume(&taker, 102), Decimal::from(100_000));
+
+        // Boundary-equal volume qualifies for tier 1 on the next trade
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::ONE, 103);
+        assert_eq!(last_fees(&engine), (Decimal::ZERO, Decimal::from(20)));
+    }
+
+    #[test]
+    fn test_volume_rolls_out_of_window() {
+        let mut engine = tiered_engine();
+        let maker = AccountId::new();
+        let taker = AccountId::new();
+        engine.submit_order(maker, Side::SELL, Price::from_u64(50000), Decimal::from(3), 100);
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::from(2), 100);
+
+        // 30 days later the first 100 000 no longer counts
+        let later = 100 + types::fee::VOLUME_WINDOW_NS;
+        assert_eq!(engine.thirty_day_volume(&taker, later), Decimal::ZERO);
+        engine.submit_order(taker, Side::BUY, Price::from_u64(50000), Decimal::ONE, later);
+        assert_eq!(last_fees(&engine), (Decimal::from(10), Decimal::from(25)));
+    }
 }
@@ -0,0 +1,656 @@
+//! End-to-end integration harness
+//!
+//! Drives the production components instead of `SimEngine`:
+//! - `matching_engine::MatchingEngine` — matching and the global sequence
+//! - `risk_engine::RiskEngine` — pre-trade validation, with positions held
+//!   in a `risk_engine::cross_margin::PositionStore`
+//! - `persistence::journal::JournalWriter` — every book event, in sequence
+//! - `market_data` `OrderBookState` and candle builders — fed only from the
+//!   events as they are journaled
+//!
+//! Cross-component invariants are asserted every `check_interval` orders
+//! and once more at the end:
+//! 1. Journal sequence equals engine sequence (spec §14)
+//! 2. Market-data mirror checksum equals the engine book checksum
+//! 3. Risk-engine positions reconcile with the sum of fills per account
+//!
+//! All identifiers derive from the seed, so a seeded rerun writes the same
+//! journal byte-for-byte (spec §12).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use market_data::candles::MultiTimeframeCandleManager;
+use market_data::order_book::{depth_checksum, OrderBookState};
+use matching_engine::engine::SubmitResult;
+use matching_engine::events::{BookEvent, TradeExecutedEvent};
+use matching_engine::restore::journal_entry;
+use matching_engine::MatchingEngine;
+use persistence::journal::{FlushPolicy, FsyncPolicy, JournalConfig, JournalError, JournalWriter};
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use risk_engine::cross_margin::PositionStore;
+use risk_engine::engine::RiskEngine;
+use risk_engine::margin;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use types::account::{Account, AccountType, Balance};
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::{Price, Quantity};
+use types::order::{Order, Side, TimeInForce};
+use types::position::{Position, PositionSide};
+use types::risk::RiskCheckResult;
+use uuid::Uuid;
+
+use crate::scenarios::ScenarioResult;
+
+/// Leverage used for harness positions
+const LEVERAGE: u8 = 10;
+
+/// Exchange time between consecutive orders (100ms)
+const ORDER_INTERVAL_NANOS: i64 = 100_000_000;
+
+/// Configuration for an integration run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarnessConfig {
+    pub symbol: String,
+    /// Seed for order flow and all identifiers
+    pub seed: u64,
+    /// Orders to submit
+    pub orders: usize,
+    /// Number of trading accounts
+    pub traders: usize,
+    /// Check invariants every N orders (0 = only at the end)
+    pub check_interval: usize,
+    /// Center of the price range
+    pub base_price: u64,
+    /// Prices are drawn from base ± ticks (1 USDT per tick)
+    pub price_ticks: u64,
+    /// Probability that a step cancels a resting order instead
+    pub cancel_ratio: f64,
+    /// USDT balance per account
+    pub starting_balance: Decimal,
+    /// Exchange time of the first order
+    pub start_time: i64,
+}
+
+impl Default for HarnessConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "BTC/USDT".to_string(),
+            seed: 42,
+            orders: 1000,
+            traders: 8,
+            check_interval: 50,
+            base_price: 50_000,
+            price_ticks: 10,
+            cancel_ratio: 0.15,
+            starting_balance: Decimal::from(1_000_000),
+            start_time: 1708123456789000000,
+        }
+    }
+}
+
+impl HarnessConfig {
+    /// Smoke scenario: 1000 mixed orders, checked every 50.
+    pub fn smoke(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Self::default()
+        }
+    }
+}
+
+/// Cross-component invariant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Invariant {
+    JournalSequence,
+    BookChecksum,
+    PositionReconciliation,
+    /// The engine's own book invariants (crossed book, level totals, index)
+    EngineBook,
+}
+
+/// A failed invariant check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    pub invariant: Invariant,
+    /// Orders processed when the check ran
+    pub after_orders: usize,
+    pub details: String,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} after {} orders: {}", self.invariant, self.after_orders, self.details)
+    }
+}
+
+/// Harness failures
+#[derive(Error, Debug)]
+pub enum HarnessError {
+    #[error("Journal error: {0}")]
+    Journal(#[from] JournalError),
+
+    #[error("Engine error: {0}")]
+    Engine(String),
+
+    #[error("Invariant violated: {0}")]
+    Invariant(InvariantViolation),
+}
+
+/// Outcome of an integration run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrationReport {
+    pub orders_submitted: u64,
+    /// Orders refused by the risk engine
+    pub orders_rejected: u64,
+    pub trades_executed: u64,
+    pub cancels: u64,
+    /// Cancels issued by self-trade prevention (included in `cancels`)
+    pub stp_cancels: u64,
+    /// Last journaled sequence
+    pub last_sequence: u64,
+    pub invariant_checks: u64,
+    /// Closed candles across all timeframes
+    pub candles_closed: u64,
+    /// Final book checksum (engine and mirror agree)
+    pub book_checksum: String,
+}
+
+impl IntegrationReport {
+    /// Express the run as a scenario result.
+    pub fn to_scenario_result(&self, name: &str, orders: usize) -> ScenarioResult {
+        ScenarioResult {
+            name: name.to_string(),
+            ticks_run: orders as u64,
+            orders_submitted: self.orders_submitted,
+            trades_executed: self.trades_executed,
+            events_emitted: self.last_sequence as usize,
+            passed: true,
+            margin_utilization: Vec::new(),
+            details: format!(
+                "{} invariant checks passed; {} risk rejections, {} cancels, checksum {}",
+                self.invariant_checks, self.orders_rejected, self.cancels, self.book_checksum
+            ),
+        }
+    }
+}
+
+/// A resting order as tracked by the harness (for cancels and STP)
+#[derive(Debug, Clone)]
+struct Resting {
+    order_id: OrderId,
+    trader: usize,
+    side: Side,
+    price: Price,
+    remaining: Quantity,
+}
+
+/// Production components wired together behind one order flow.
+pub struct IntegrationHarness {
+    config: HarnessConfig,
+    symbol: MarketId,
+    engine: MatchingEngine,
+    risk: RiskEngine,
+    positions: PositionStore,
+    accounts: Vec<Account>,
+    trader_index: HashMap<AccountId, usize>,
+    /// Signed sum of fills per trader
+    fills: Vec<Decimal>,
+    journal: JournalWriter,
+    last_journaled: u64,
+    mirror: OrderBookState,
+    candles: MultiTimeframeCandleManager,
+    resting: Vec<Resting>,
+    rng: ChaCha8Rng,
+    ids_issued: u64,
+    orders_processed: usize,
+    report: IntegrationReport,
+}
+
+impl IntegrationHarness {
+    /// Build the harness, journaling into `journal_dir`.
+    pub fn new(config: HarnessConfig, journal_dir: &Path) -> Result<Self, HarnessError> {
+        let symbol = MarketId::new(&config.symbol);
+        let mut journal_config = JournalConfig::new(journal_dir);
+        journal_config.flush_policy = FlushPolicy::EveryN(64);
+        journal_config.fsync_policy = FsyncPolicy::OnRotation;
+        let journal = JournalWriter::open(journal_config)?;
+
+        let mut accounts = Vec::with_capacity(config.traders);
+        let mut trader_index = HashMap::new();
+        for i in 0..config.traders {
+            let mut account = Account::new(AccountType::FUTURES, config.start_time);
+            account.account_id = AccountId::from_uuid(Uuid::from_u64_pair(config.seed, i as u64));
+            account.set_balance(Balance::new("USDT", config.starting_balance), config.start_time);
+            trader_index.insert(account.account_id, i);
+            accounts.push(account);
+        }
+
+        Ok(Self {
+            symbol: symbol.clone(),
+            engine: MatchingEngine::new(1),
+            risk: RiskEngine::new(),
+            positions: PositionStore::new(),
+            fills: vec![Decimal::ZERO; config.traders],
+            accounts,
+            trader_index,
+            journal,
+            last_journaled: 0,
+            mirror: OrderBookState::new(symbol.clone()),
+            candles: MultiTimeframeCandleManager::new(symbol, 1_000),
+            resting: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            ids_issued: 0,
+            orders_processed: 0,
+            report: IntegrationReport {
+                orders_submitted: 0,
+                orders_rejected: 0,
+                trades_executed: 0,
+                cancels: 0,
+                stp_cancels: 0,
+                last_sequence: 0,
+                invariant_checks: 0,
+                candles_closed: 0,
+                book_checksum: String::new(),
+            },
+            config,
+        })
+    }
+
+    /// Run the configured order flow, checking invariants along the way.
+    pub fn run(mut self) -> Result<IntegrationReport, HarnessError> {
+        for step in 0..self.config.orders {
+            let timestamp = self.config.start_time + step as i64 * ORDER_INTERVAL_NANOS;
+            if !self.resting.is_empty() && self.rng.gen_bool(self.config.cancel_ratio) {
+                let index = self.rng.gen_range(0..self.resting.len());
+                self.cancel(index, timestamp)?;
+            } else {
+                self.submit_random(timestamp)?;
+            }
+            self.orders_processed += 1;
+
+            let interval = self.config.check_interval;
+            if interval > 0 && self.orders_processed.is_multiple_of(interval) {
+                self.check_invariants().map_err(HarnessError::Invariant)?;
+            }
+        }
+
+        self.check_invariants().map_err(HarnessError::Invariant)?;
+        self.journal.sync()?;
+        self.report.last_sequence = self.last_journaled;
+        self.report.book_checksum = self.mirror.full_checksum();
+        Ok(self.report)
+    }
+
+    /// Check all cross-component invariants.
+    pub fn check_invariants(&mut self) -> Result<(), InvariantViolation> {
+        self.report.invariant_checks += 1;
+        let violation = |invariant, details| InvariantViolation {
+            invariant,
+            after_orders: self.orders_processed,
+            details,
+        };
+
+        // 1. Every sequence the engine assigned is in the journal
+        if self.engine.next_sequence() != self.last_journaled + 1 {
+            return Err(violation(
+                Invariant::JournalSequence,
+                format!(
+                    "engine next sequence {} but journal at {}",
+                    self.engine.next_sequence(),
+                    self.last_journaled
+                ),
+            ));
+        }
+
+        // 2. The engine book is internally consistent
+        if let Err(engine_violation) = self.engine.check_invariants() {
+            return Err(violation(Invariant::EngineBook, engine_violation.to_string()));
+        }
+
+        // 3. Mirror rebuilt from the journal matches the engine book
+        let engine_checksum = self.engine_checksum();
+        let mirror_checksum = self.mirror.full_checksum();
+        if engine_checksum != mirror_checksum {
+            return Err(violation(
+                Invariant::BookChecksum,
+                format!("engine {} vs mirror {}", engine_checksum, mirror_checksum),
+            ));
+        }
+
+        // 4. Positions equal the signed sum of fills
+        for (trader, account) in self.accounts.iter().enumerate() {
+            let position = self.signed_position(&account.account_id);
+            if position != self.fills[trader] {
+                return Err(violation(
+                    Invariant::PositionReconciliation,
+                    format!(
+                        "account {} position {} but fills sum to {}",
+                        account.account_id, position, self.fills[trader]
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn engine_checksum(&self) -> String {
+        match self.engine.get_order_book(self.symbol.as_str(), usize::MAX) {
+            Some(book) => depth_checksum(
+                book.bids.into_iter().map(|(p, q)| (p, q.as_decimal())),
+                book.asks.into_iter().map(|(p, q)| (p, q.as_decimal())),
+            ),
+            None => depth_checksum([], []),
+        }
+    }
+
+    fn signed_position(&self, account_id: &AccountId) -> Decimal {
+        self.positions
+            .positions(account_id)
+            .find(|p| p.symbol == self.symbol)
+            .map_or(Decimal::ZERO, |p| match p.side {
+                PositionSide::LONG => p.size.as_decimal(),
+                PositionSide::SHORT => -p.size.as_decimal(),
+            })
+    }
+
+    /// Deterministic UUID v7 from exchange time and an issue counter
+    fn next_order_id(&mut self, timestamp: i64) -> OrderId {
+        self.ids_issued += 1;
+        let mut counter = [0u8; 10];
+        counter[..2].copy_from_slice(&(self.config.seed as u16).to_be_bytes());
+        counter[2..].copy_from_slice(&self.ids_issued.to_be_bytes());
+        let millis = (timestamp.max(0) / 1_000_000) as u64;
+        OrderId::from_uuid(uuid::Builder::from_unix_timestamp_millis(millis, &counter).into_uuid())
+    }
+
+    fn submit_random(&mut self, timestamp: i64) -> Result<(), HarnessError> {
+        let trader = self.rng.gen_range(0..self.config.traders);
+        let side = if self.rng.gen_bool(0.5) { Side::BUY } else { Side::SELL };
+        let ticks = self.config.price_ticks;
+        let offset = self.rng.gen_range(0..=2 * ticks);
+        let price = Price::from_u64(self.config.base_price - ticks + offset);
+        let quantity = Quantity::new(Decimal::new(self.rng.gen_range(1..=100), 2));
+
+        // Self-trade prevention: pull own resting orders this one would hit
+        while let Some(index) = self.resting.iter().position(|r| {
+            r.trader == trader
+                && r.side != side
+                && match side {
+                    Side::BUY => r.price <= price,
+                    Side::SELL => r.price >= price,
+                }
+        }) {
+            self.cancel(index, timestamp)?;
+            self.report.stp_cancels += 1;
+        }
+
+        let account_id = self.accounts[trader].account_id;
+        let mut order = Order::new(
+            account_id,
+            self.symbol.clone(),
+            side,
+            price,
+            quantity,
+            TimeInForce::GTC,
+            timestamp,
+        );
+        order.order_id = self.next_order_id(timestamp);
+
+        let positions: Vec<Position> = self.positions.positions(&account_id).cloned().collect();
+        let (check, _) =
+            self.risk.check_pre_trade(&self.accounts[trader], &order, &positions, timestamp);
+        if check != RiskCheckResult::Pass {
+            self.report.orders_rejected += 1;
+            return Ok(());
+        }
+        self.report.orders_submitted += 1;
+
+        let result = self
+            .engine
+            .submit_order(order.clone(), timestamp)
+            .map_err(|e| HarnessError::Engine(format!("{:?}", e)))?;
+        let (trades, rested, band_hit) = match result {
+            SubmitResult::Resting => (Vec::new(), true, None),
+            SubmitResult::PartiallyFilled { trades, .. } => (trades, false, None),
+            SubmitResult::Filled { trades } => (trades, false, None),
+            SubmitResult::Rejected(_) => (Vec::new(), false, None),
+            SubmitResult::Canceled { trades, .. } => (trades, false, None),
+            SubmitResult::StopAccepted(_) => (Vec::new(), false, None),
+            SubmitResult::PriceBandHit { trades, event } => (trades, false, Some(event)),
+        };
+
+        for trade in &trades {
+            self.report.trades_executed += 1;
+            let event = BookEvent::TradeExecuted(TradeExecutedEvent::from_trade(trade));
+            self.publish(trade.sequence, timestamp, event)?;
+        }
+        if let Some(hit) = band_hit {
+            if hit.rested {
+                self.resting.push(Resting {
+                    order_id: hit.order_id,
+                    trader,
+                    side,
+                    price: hit.band_limit,
+                    remaining: hit.unexecuted_quantity,
+                });
+            }
+            let sequence = self.reserve_sequence();
+            self.publish(sequence, timestamp, BookEvent::PriceBandHit(hit))?;
+        }
+        if rested {
+            self.resting.push(Resting {
+                order_id: order.order_id,
+                trader,
+                side,
+                price,
+                remaining: order.remaining_quantity,
+            });
+            let sequence = self.reserve_sequence();
+            let event = BookEvent::OrderAccepted {
+                order_id: order.order_id,
+                account_id,
+                symbol: self.config.symbol.clone(),
+                side,
+                price,
+                quantity: order.remaining_quantity,
+                accepted_at: order.created_at,
+            };
+            self.publish(sequence, timestamp, event)?;
+        }
+        Ok(())
+    }
+
+    fn cancel(&mut self, index: usize, timestamp: i64) -> Result<(), HarnessError> {
+        let resting = self.resting.remove(index);
+        if !self
+            .engine
+            .cancel_order(self.symbol.as_str(), &resting.order_id, resting.price, resting.side)
+        {
+            return Err(HarnessError::Engine(format!(
+                "Resting order {} not found on the book",
+                resting.order_id
+            )));
+        }
+        self.report.cancels += 1;
+        let sequence = self.reserve_sequence();
+        let event = BookEvent::OrderCanceled {
+            order_id: resting.order_id,
+            symbol: self.config.symbol.clone(),
+            side: resting.side,
+            price: resting.price,
+            remaining_quantity: resting.remaining,
+        };
+        self.publish(sequence, timestamp, event)
+    }
+
+    /// Take the next engine sequence for a non-trade book event
+    fn reserve_sequence(&mut self) -> u64 {
+        let sequence = self.engine.next_sequence();
+        self.engine.advance_sequence_past(sequence);
+        sequence
+    }
+
+    /// Journal an event, then feed market data and risk from it
+    fn publish(&mut self, sequence: u64, timestamp: i64, event: BookEvent) -> Result<(), HarnessError> {
+        let receipt = self.journal.append(&journal_entry(sequence, timestamp, &event))?;
+        self.last_journaled = receipt.sequence;
+
+        match event {
+            BookEvent::OrderAccepted { order_id, side, price, quantity, .. } => {
+                self.mirror.apply_order_accepted(order_id, side, price, quantity, sequence);
+            }
+            BookEvent::OrderCanceled { order_id, remaining_quantity, .. } => {
+                self.mirror.apply_cancel(order_id, remaining_quantity, sequence);
+            }
+            BookEvent::OrderAmended(amend) => {
+                self.mirror.apply_cancel(amend.order_id, amend.old_quantity, sequence);
+                if !amend.resting_quantity.is_zero() {
+                    self.mirror.apply_order_accepted(
+                        amend.order_id, amend.side, amend.new_price, amend.resting_quantity, sequence,
+                    );
+                }
+            }
+            BookEvent::PriceBandHit(hit) => {
+                if hit.rested {
+                    self.mirror.apply_order_accepted(
+                        hit.order_id, hit.side, hit.band_limit, hit.unexecuted_quantity, sequence,
+                    );
+                }
+            }
+            // Stops are off-book; the submission they release follows
+            BookEvent::StopAccepted(_) | BookEvent::StopTriggered(_) => {}
+            BookEvent::TradeExecuted(trade) => {
+                self.reduce_resting(trade.maker_order_id, trade.quantity, sequence);
+                self.record_trade(&trade, timestamp);
+            }
+            // Auction trades fill both resting sides
+            BookEvent::AuctionUncrossed(auction) => {
+                for trade in &auction.trades {
+                    self.reduce_resting(trade.maker_order_id, trade.quantity, sequence);
+                    self.reduce_resting(trade.taker_order_id, trade.quantity, sequence);
+                    self.record_trade(trade, timestamp);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reduce a resting order in the mirror and the harness's own list
+    fn reduce_resting(&mut self, order_id: OrderId, quantity: Quantity, sequence: u64) {
+        self.mirror.apply_trade_executed(order_id, quantity, sequence);
+        if let Some(index) = self.resting.iter().position(|r| r.order_id == order_id) {
+            let remaining = self.resting[index].remaining.as_decimal() - quantity.as_decimal();
+            match Quantity::try_new(remaining) {
+                Some(q) => self.resting[index].remaining = q,
+                None => {
+                    self.resting.remove(index);
+                }
+            }
+        }
+    }
+
+    /// Feed a trade to the candles and both accounts' positions
+    fn record_trade(&mut self, trade: &TradeExecutedEvent, timestamp: i64) {
+        let closed = self
+            .candles
+            .process_trade(trade.price, trade.quantity.as_decimal(), timestamp);
+        self.report.candles_closed += closed.len() as u64;
+
+        let maker_side = match trade.side {
+            Side::BUY => Side::SELL,
+            Side::SELL => Side::BUY,
+        };
+        self.apply_fill(trade.taker_account_id, trade.side, trade.quantity, trade.price, timestamp);
+        self.apply_fill(trade.maker_account_id, maker_side, trade.quantity, trade.price, timestamp);
+    }
+
+    /// Net a fill into the account's position and the fills ledger
+    fn apply_fill(&mut self, account_id: AccountId, side: Side, quantity: Quantity, price: Price, timestamp: i64) {
+        let delta = match side {
+            Side::BUY => quantity.as_decimal(),
+            Side::SELL => -quantity.as_decimal(),
+        };
+        if let Some(&trader) = self.trader_index.get(&account_id) {
+            self.fills[trader] += delta;
+        }
+
+        let current = self.signed_position(&account_id);
+        let next = current + delta;
+        if next.is_zero() {
+            self.positions.remove(&account_id, self.symbol.as_str());
+            return;
+        }
+
+        let previous_entry = self
+            .positions
+            .positions(&account_id)
+            .find(|p| p.symbol == self.symbol)
+            .map(|p| p.entry_price.as_decimal());
+        let entry = match previous_entry {
+            // Increasing: volume-weighted entry
+            Some(old) if current.is_sign_positive() == next.is_sign_positive() && next.abs() > current.abs() => {
+                ((old * current.abs() + price.as_decimal() * delta.abs()) / next.abs()).round_dp(8)
+            }
+            // Reducing: entry unchanged
+            Some(old) if current.is_sign_positive() == next.is_sign_positive() => old,
+            // Opened or flipped at the fill price
+            _ => price.as_decimal(),
+        };
+
+        let value = next.abs() * price.as_decimal();
+        let tier = margin::leverage_tier(value);
+        self.positions.upsert(Position::new(
+            account_id,
+            self.symbol.clone(),
+            if next.is_sign_positive() { PositionSide::LONG } else { PositionSide::SHORT },
+            Quantity::new(next.abs()),
+            Price::new(entry),
+            price,
+            Price::new(entry),
+            margin::initial_margin(value, LEVERAGE),
+            margin::maintenance_margin(value, tier.mm_rate),
+            LEVERAGE,
+            timestamp,
+        ));
+    }
+}
+
+/// Run a configured integration scenario.
+pub fn run(config: HarnessConfig, journal_dir: &Path) -> Result<IntegrationReport, HarnessError> {
+    IntegrationHarness::new(config, journal_dir)?.run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invariants_hold_on_short_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HarnessConfig {
+            orders: 200,
+            check_interval: 10,
+            ..HarnessConfig::default()
+        };
+        let report = run(config, dir.path()).unwrap();
+        assert_eq!(report.invariant_checks, 21);
+        assert!(report.trades_executed > 0);
+        assert!(report.cancels > 0);
+    }
+
+    #[test]
+    fn test_order_ids_are_seeded() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let mut first = IntegrationHarness::new(HarnessConfig::default(), a.path()).unwrap();
+        let mut second = IntegrationHarness::new(HarnessConfig::default(), b.path()).unwrap();
+        assert_eq!(first.next_order_id(1), second.next_order_id(1));
+        assert_ne!(first.next_order_id(1), first.next_order_id(1));
+    }
+}
@@ -7,12 +7,15 @@
 //! # Modules
 //! - `engine` — Deterministic matching engine with order book
 //! - `bots` — Market maker and retail trader bots
-//! - `scenarios` — Volatility, latency, flood, liquidation, incentive scenarios
+//! - `scenarios` — Volatility, latency, flood, liquidation, incentive, funding carry scenarios,
+//!   and replay of recorded production journals
 //! - `metrics` — Performance counters and latency histograms
 //! - `reports` — Depth, slippage, and profitability reports
-//! - `multi_market` — Multi-market concurrent simulation
+//! - `multi_market` — Multi-market concurrent simulation with a cross-margined ledger
+//! - `carry` — Funding rates and borrow costs applied at fixed intervals
 //! - `replay` — Event log and deterministic replay validation
 //! - `export` — Metrics and report JSON export
+//! - `integration` — End-to-end harness over the production engine, risk, persistence, and market-data components
 
 pub mod engine;
 pub mod bots;
@@ -20,8 +23,10 @@ pub mod scenarios;
 pub mod metrics;
 pub mod reports;
 pub mod multi_market;
+pub mod carry;
 pub mod replay;
 pub mod export;
+pub mod integration;
 
 /// Crate version constant
 pub const VERSION: &str = "1.0.0";
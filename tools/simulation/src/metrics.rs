@@ -3,7 +3,6 @@
 //! Tracks orders, trades, cancels, latency histograms, and throughput.
 
 use crate::engine::SimEvent;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -53,7 +52,7 @@ impl SimMetrics {
     /// Record a single event into metrics.
     pub fn record_event(&mut self, event: &SimEvent) {
         match event {
-            SimEvent::OrderPlaced { .. } => {
+            SimEvent::OrderPlaced { .. } | SimEvent::MarketOrderPlaced { .. } => {
                 self.total_orders += 1;
             }
             SimEvent::TradeExecuted {
@@ -77,6 +76,9 @@ impl SimMetrics {
             SimEvent::OrderCanceled { .. } => {
                 self.total_cancels += 1;
             }
+            // Carry cash flows are reported by the profitability report
+            SimEvent::FundingPayment { .. } | SimEvent::BorrowCharged { .. } => {}
+            SimEvent::OrderRejected { .. } => {}
         }
     }
 
@@ -205,6 +207,8 @@ mod tests {
             quantity: Decimal::ONE,
             maker_fee: Decimal::from(10),
             taker_fee: Decimal::from(25),
+            aggressor_side: Side::BUY,
+            maker_is_buyer: false,
             timestamp: 1000,
         };
         metrics.record_event(&event);
@@ -2,25 +2,412 @@
 //!
 //! Runs independent engine instances per market symbol.
 //! Aggregates cross-market metrics.
+//!
+//! Accounts opened on the shared ledger are cross-margined per spec §5.2.3:
+//! one cash balance backs positions and resting orders in every market, and
+//! orders routed through [`MultiMarketSim::submit_order`] are rejected when
+//! their margin exceeds the account's available margin (spec §5.3.1).
 
-use crate::engine::SimEngine;
+use crate::engine::{SimEngine, SimEvent};
 use crate::metrics::SimMetrics;
+use crate::scenarios::ScenarioResult;
+use risk_engine::margin;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use types::fee::FeeTier;
-use types::ids::MarketId;
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::Price;
+use types::order::Side;
+
+/// Why an order routed through the multi-market simulation was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderRejection {
+    /// No engine for the requested market
+    UnknownMarket(String),
+    /// Order margin exceeds the account's available cross-market margin
+    InsufficientMargin { required: Decimal, available: Decimal },
+}
+
+/// A net position in one market. `size` is signed: positive = long.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketPosition {
+    pub size: Decimal,
+    pub entry_price: Decimal,
+}
+
+impl MarketPosition {
+    /// Apply a signed fill, returning realized PnL on the reduced portion.
+    fn apply_fill(&mut self, signed_qty: Decimal, price: Decimal) -> Decimal {
+        let same_direction = self.size.is_zero()
+            || self.size.is_sign_positive() == signed_qty.is_sign_positive();
+        if same_direction {
+            let new_size = self.size + signed_qty;
+            self.entry_price = (self.size.abs() * self.entry_price
+                + signed_qty.abs() * price)
+                / new_size.abs();
+            self.size = new_size;
+            return Decimal::ZERO;
+        }
+
+        let closed = self.size.abs().min(signed_qty.abs());
+        let direction = if self.size.is_sign_positive() { Decimal::ONE } else { -Decimal::ONE };
+        let realized = (price - self.entry_price) * closed * direction;
+
+        self.size += signed_qty;
+        if self.size.is_zero() {
+            self.entry_price = Decimal::ZERO;
+        } else if self.size.is_sign_positive() != direction.is_sign_positive() {
+            // Flipped through flat: the remainder opens at the fill price
+            self.entry_price = price;
+        }
+        realized
+    }
+}
+
+/// A cross-margined account on the shared ledger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossMarginAccount {
+    pub account_id: AccountId,
+    /// Cash balance (deposits + realized PnL − fees ± carry)
+    pub cash: Decimal,
+    /// Leverage applied to every market
+    pub leverage: u8,
+    /// Net position per market symbol
+    pub positions: BTreeMap<String, MarketPosition>,
+    /// Margin locked by resting orders
+    pub locked_margin: Decimal,
+}
+
+/// One cross-market margin utilization sample for an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginUtilization {
+    pub timestamp: i64,
+    pub account_id: AccountId,
+    pub equity: Decimal,
+    pub margin_used: Decimal,
+    pub locked_margin: Decimal,
+    pub available_margin: Decimal,
+    /// `(margin_used + locked_margin) / equity`; `Decimal::MAX` when equity
+    /// is not positive but margin is in use
+    pub utilization: Decimal,
+}
+
+/// A resting order tracked against the ledger.
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    account_id: AccountId,
+    symbol: String,
+    side: Side,
+    remaining: Decimal,
+    /// Margin still locked for `remaining`
+    locked: Decimal,
+}
 
 /// A multi-market simulation runner.
+///
+/// Orders submitted directly to an engine bypass the ledger; only orders
+/// routed through [`submit_order`](Self::submit_order) are margin-checked and
+/// have their fills applied to account state.
 pub struct MultiMarketSim {
     pub engines: Vec<SimEngine>,
+    accounts: HashMap<AccountId, CrossMarginAccount>,
+    /// Ledger accounts in opening order (deterministic sampling order)
+    account_order: Vec<AccountId>,
+    orders: HashMap<OrderId, TrackedOrder>,
+    /// Per engine: index of the next unapplied event
+    cursors: Vec<usize>,
+    /// Per engine: last traded price (mark price for unrealized PnL)
+    last_prices: Vec<Option<Decimal>>,
+    utilization: Vec<MarginUtilization>,
 }
 
 impl MultiMarketSim {
     /// Create a multi-market simulation from market symbols and shared fee tier.
     pub fn new(symbols: Vec<MarketId>, fee_tier: FeeTier) -> Self {
-        let engines = symbols.into_iter()
+        let engines: Vec<SimEngine> = symbols.into_iter()
             .map(|sym| SimEngine::new(sym, fee_tier.clone()))
             .collect();
-        Self { engines }
+        let market_count = engines.len();
+        Self {
+            engines,
+            accounts: HashMap::new(),
+            account_order: Vec::new(),
+            orders: HashMap::new(),
+            cursors: vec![0; market_count],
+            last_prices: vec![None; market_count],
+            utilization: Vec::new(),
+        }
+    }
+
+    /// Open a cross-margined account on the shared ledger.
+    pub fn open_account(&mut self, account_id: AccountId, cash: Decimal, leverage: u8) {
+        assert!(leverage >= 1, "Leverage must be >= 1");
+        if !self.accounts.contains_key(&account_id) {
+            self.account_order.push(account_id);
+        }
+        self.accounts.insert(account_id, CrossMarginAccount {
+            account_id,
+            cash,
+            leverage,
+            positions: BTreeMap::new(),
+            locked_margin: Decimal::ZERO,
+        });
+    }
+
+    /// Get a ledger account.
+    pub fn account(&self, account_id: &AccountId) -> Option<&CrossMarginAccount> {
+        self.accounts.get(account_id)
+    }
+
+    /// Route an order to its market.
+    ///
+    /// Orders from ledger accounts must fit in available margin; the portion
+    /// that reduces an existing position in the same market needs none, so
+    /// purely reducing orders are always accepted. Resting orders on the
+    /// same side fill first and are counted against the position, so
+    /// reducing orders together never exceed it margin-free.
+    /// Orders from other accounts (e.g. liquidity seeders) are unconstrained.
+    pub fn submit_order(
+        &mut self,
+        account_id: AccountId,
+        symbol: &str,
+        side: Side,
+        price: Price,
+        quantity: Decimal,
+        timestamp: i64,
+    ) -> Result<OrderId, OrderRejection> {
+        let index = self.engines.iter()
+            .position(|e| e.symbol.as_str() == symbol)
+            .ok_or_else(|| OrderRejection::UnknownMarket(symbol.to_string()))?;
+
+        let required = match self.accounts.get(&account_id) {
+            Some(account) => {
+                let position = account.positions.get(symbol).cloned().unwrap_or_default();
+                let reduces = match side {
+                    Side::BUY => position.size.is_sign_negative(),
+                    Side::SELL => position.size.is_sign_positive(),
+                };
+                let resting: Decimal = self.orders.values()
+                    .filter(|o| o.account_id == account_id && o.symbol == symbol && o.side == side)
+                    .map(|o| o.remaining)
+                    .sum();
+                let reducible = (position.size.abs() - resting).max(Decimal::ZERO);
+                let reducing = if reduces { reducible.min(quantity) } else { Decimal::ZERO };
+                let required = margin::order_margin(
+                    quantity - reducing,
+                    price.as_decimal(),
+                    account.leverage,
+                );
+                let available = self.available_margin(account);
+                if required > Decimal::ZERO && required > available {
+                    return Err(OrderRejection::InsufficientMargin { required, available });
+                }
+                Some(required)
+            }
+            None => None,
+        };
+
+        let order_id = self.engines[index]
+            .submit_order(account_id, side, price, quantity, timestamp);
+
+        if let Some(locked) = required {
+            if let Some(account) = self.accounts.get_mut(&account_id) {
+                account.locked_margin += locked;
+            }
+            self.orders.insert(order_id, TrackedOrder {
+                account_id,
+                symbol: symbol.to_string(),
+                side,
+                remaining: quantity,
+                locked,
+            });
+        }
+
+        Ok(order_id)
+    }
+
+    /// Apply new fills from every engine to the shared ledger, in market
+    /// order, then record a margin utilization sample per account.
+    pub fn tick(&mut self, timestamp: i64) {
+        for index in 0..self.engines.len() {
+            // Engine events were cleared externally: restart from the top
+            if self.cursors[index] > self.engines[index].events.len() {
+                self.cursors[index] = 0;
+            }
+            let start = self.cursors[index];
+            let new_events: Vec<SimEvent> = self.engines[index].events[start..].to_vec();
+            self.cursors[index] = self.engines[index].events.len();
+
+            let symbol = self.engines[index].symbol.as_str().to_string();
+            for event in new_events {
+                match event {
+                    SimEvent::TradeExecuted {
+                        maker_order_id,
+                        taker_order_id,
+                        price,
+                        quantity,
+                        maker_fee,
+                        taker_fee,
+                        ..
+                    } => {
+                        self.last_prices[index] = Some(price.as_decimal());
+                        self.apply_fill(&symbol, maker_order_id, price.as_decimal(), quantity, maker_fee);
+                        self.apply_fill(&symbol, taker_order_id, price.as_decimal(), quantity, taker_fee);
+                    }
+                    SimEvent::FundingPayment { account_id, amount, .. } => {
+                        if let Some(account) = self.accounts.get_mut(&account_id) {
+                            account.cash += amount;
+                        }
+                    }
+                    SimEvent::BorrowCharged { account_id, amount, .. } => {
+                        if let Some(account) = self.accounts.get_mut(&account_id) {
+                            account.cash -= amount;
+                        }
+                    }
+                    SimEvent::OrderCanceled { order_id, .. } => {
+                        if let Some(order) = self.orders.remove(&order_id) {
+                            if let Some(account) = self.accounts.get_mut(&order.account_id) {
+                                account.locked_margin -= order.locked;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let samples: Vec<MarginUtilization> = self.account_order.iter()
+            .map(|id| self.sample(&self.accounts[id], timestamp))
+            .collect();
+        self.utilization.extend(samples);
+    }
+
+    /// Apply one fill to a tracked order's account.
+    fn apply_fill(
+        &mut self,
+        symbol: &str,
+        order_id: OrderId,
+        price: Decimal,
+        quantity: Decimal,
+        fee: Decimal,
+    ) {
+        let Some(order) = self.orders.get_mut(&order_id) else {
+            return;
+        };
+        let Some(account) = self.accounts.get_mut(&order.account_id) else {
+            return;
+        };
+
+        // Release locked margin pro rata to the filled quantity
+        let released = if quantity >= order.remaining {
+            order.locked
+        } else {
+            (order.locked * quantity / order.remaining).min(order.locked)
+        };
+        order.remaining -= quantity;
+        order.locked -= released;
+        account.locked_margin -= released;
+
+        let signed_qty = match order.side {
+            Side::BUY => quantity,
+            Side::SELL => -quantity,
+        };
+        let realized = account.positions
+            .entry(symbol.to_string())
+            .or_default()
+            .apply_fill(signed_qty, price);
+        account.cash += realized - fee;
+
+        if order.remaining <= Decimal::ZERO {
+            self.orders.remove(&order_id);
+        }
+    }
+
+    /// Mark price for a market: last trade, else none.
+    fn mark_price(&self, symbol: &str) -> Option<Decimal> {
+        self.engines.iter()
+            .position(|e| e.symbol.as_str() == symbol)
+            .and_then(|i| self.last_prices[i])
+    }
+
+    /// Equity: cash plus unrealized PnL at mark (spec §5.3.3).
+    pub fn equity(&self, account: &CrossMarginAccount) -> Decimal {
+        let unrealized: Decimal = account.positions.iter()
+            .map(|(symbol, pos)| {
+                let mark = self.mark_price(symbol).unwrap_or(pos.entry_price);
+                (mark - pos.entry_price) * pos.size
+            })
+            .sum();
+        account.cash + unrealized
+    }
+
+    /// Initial margin used by open positions across all markets (spec §5.3.2).
+    pub fn margin_used(&self, account: &CrossMarginAccount) -> Decimal {
+        account.positions.values()
+            .filter(|pos| !pos.size.is_zero())
+            .map(|pos| margin::initial_margin(pos.size.abs() * pos.entry_price, account.leverage))
+            .sum()
+    }
+
+    /// Available margin: `equity − margin_used − locked_margin` (spec §5.3.1).
+    pub fn available_margin(&self, account: &CrossMarginAccount) -> Decimal {
+        margin::available_margin(
+            self.equity(account),
+            self.margin_used(account),
+            account.locked_margin,
+        )
+    }
+
+    /// Build a utilization sample for an account.
+    fn sample(&self, account: &CrossMarginAccount, timestamp: i64) -> MarginUtilization {
+        let equity = self.equity(account);
+        let margin_used = self.margin_used(account);
+        let in_use = margin_used + account.locked_margin;
+        let utilization = if equity > Decimal::ZERO {
+            in_use / equity
+        } else if in_use.is_zero() {
+            Decimal::ZERO
+        } else {
+            Decimal::MAX
+        };
+        MarginUtilization {
+            timestamp,
+            account_id: account.account_id,
+            equity,
+            margin_used,
+            locked_margin: account.locked_margin,
+            available_margin: margin::available_margin(equity, margin_used, account.locked_margin),
+            utilization,
+        }
+    }
+
+    /// Margin utilization samples recorded so far, in tick order.
+    pub fn margin_utilization(&self) -> &[MarginUtilization] {
+        &self.utilization
+    }
+
+    /// Summarize the run as a scenario result.
+    pub fn result(&self, name: &str, ticks_run: u64, orders_submitted: u64) -> ScenarioResult {
+        let max_utilization = self.utilization.iter()
+            .map(|s| s.utilization)
+            .max()
+            .unwrap_or(Decimal::ZERO);
+        ScenarioResult {
+            name: name.to_string(),
+            ticks_run,
+            orders_submitted,
+            trades_executed: self.total_trades() as u64,
+            events_emitted: self.engines.iter().map(|e| e.events.len()).sum(),
+            passed: max_utilization <= Decimal::ONE,
+            margin_utilization: self.utilization.clone(),
+            details: format!(
+                "{} markets, {} accounts, peak margin utilization {}",
+                self.market_count(),
+                self.accounts.len(),
+                max_utilization.round_dp(4),
+            ),
+        }
     }
 
     /// Get engine for a specific market index.
@@ -132,4 +519,107 @@ mod tests {
         assert_eq!(metrics.total_trades, 2); // 1 per market
         assert_eq!(metrics.total_orders, 4); // 2 per market
     }
+
+    fn two_markets() -> MultiMarketSim {
+        MultiMarketSim::new(
+            vec![MarketId::new("BTC/USDT"), MarketId::new("ETH/USDT")],
+            test_fee(),
+        )
+    }
+
+    #[test]
+    fn test_fully_margined_in_one_market_rejected_in_other() {
+        let mut sim = two_markets();
+        let seeder = AccountId::new();
+        let bot = AccountId::new();
+        sim.open_account(bot, Decimal::from(10_000), 10);
+
+        sim.submit_order(seeder, "BTC/USDT", Side::SELL, Price::from_u64(50000), Decimal::from(10), 100).unwrap();
+        sim.submit_order(seeder, "ETH/USDT", Side::SELL, Price::from_u64(3000), Decimal::from(10), 100).unwrap();
+
+        // 2 BTC at 10x uses the full 10,000 of margin
+        sim.submit_order(bot, "BTC/USDT", Side::BUY, Price::from_u64(50000), Decimal::from(2), 101).unwrap();
+        sim.tick(102);
+
+        let account = sim.account(&bot).unwrap();
+        assert_eq!(account.positions["BTC/USDT"].size, Decimal::from(2));
+        assert_eq!(sim.margin_used(account), Decimal::from(10_000));
+
+        let result = sim.submit_order(bot, "ETH/USDT", Side::BUY, Price::from_u64(3000), Decimal::ONE, 103);
+        assert!(matches!(result, Err(OrderRejection::InsufficientMargin { .. })));
+        assert_eq!(sim.engine_by_symbol("ETH/USDT").unwrap().trade_count(), 0);
+
+        // Reducing the BTC position needs no margin
+        assert!(sim.submit_order(bot, "BTC/USDT", Side::SELL, Price::from_u64(50000), Decimal::ONE, 104).is_ok());
+    }
+
+    #[test]
+    fn test_resting_reducing_orders_count_against_the_position() {
+        let mut sim = two_markets();
+        let seeder = AccountId::new();
+        let bot = AccountId::new();
+        sim.open_account(bot, Decimal::from(10_000), 10);
+        let two = Decimal::from(2);
+        sim.submit_order(seeder, "BTC/USDT", Side::SELL, Price::from_u64(50000), two, 100).unwrap();
+        sim.submit_order(bot, "BTC/USDT", Side::BUY, Price::from_u64(50000), two, 101).unwrap();
+        sim.tick(102);
+        assert_eq!(sim.margin_used(sim.account(&bot).unwrap()), Decimal::from(10_000));
+
+        // Two resting asks close the 2 BTC long between them
+        let ask = Price::from_u64(60000);
+        for timestamp in [103, 104] {
+            sim.submit_order(bot, "BTC/USDT", Side::SELL, ask, Decimal::ONE, timestamp).unwrap();
+        }
+        assert_eq!(sim.account(&bot).unwrap().locked_margin, Decimal::ZERO);
+
+        // A third would open a 1 BTC short, which needs margin
+        let result = sim.submit_order(bot, "BTC/USDT", Side::SELL, ask, Decimal::ONE, 105);
+        assert!(matches!(
+            result,
+            Err(OrderRejection::InsufficientMargin { required, .. }) if required == Decimal::from(6000)
+        ));
+    }
+
+    #[test]
+    fn test_resting_order_locks_margin_until_canceled() {
+        let mut sim = two_markets();
+        let bot = AccountId::new();
+        sim.open_account(bot, Decimal::from(1_000), 5);
+
+        // Resting bid locks 1 × 5000 / 5 = 1000, all available margin
+        let oid = sim.submit_order(bot, "BTC/USDT", Side::BUY, Price::from_u64(5000), Decimal::ONE, 100).unwrap();
+        assert!(sim.submit_order(bot, "ETH/USDT", Side::BUY, Price::from_u64(100), Decimal::ONE, 101).is_err());
+
+        sim.engine_by_symbol_mut("BTC/USDT").unwrap().cancel_order(oid, 102);
+        sim.tick(103);
+        assert_eq!(sim.account(&bot).unwrap().locked_margin, Decimal::ZERO);
+        assert!(sim.submit_order(bot, "ETH/USDT", Side::BUY, Price::from_u64(100), Decimal::ONE, 104).is_ok());
+    }
+
+    #[test]
+    fn test_margin_utilization_in_scenario_result() {
+        let mut sim = two_markets();
+        let seeder = AccountId::new();
+        let bot = AccountId::new();
+        sim.open_account(bot, Decimal::from(10_000), 10);
+
+        sim.tick(100);
+        sim.submit_order(seeder, "ETH/USDT", Side::SELL, Price::from_u64(3000), Decimal::from(10), 101).unwrap();
+        sim.submit_order(bot, "ETH/USDT", Side::BUY, Price::from_u64(3000), Decimal::from(10), 102).unwrap();
+        sim.tick(103);
+
+        let result = sim.result("cross_margin", 2, 2);
+        let samples: Vec<Decimal> = result.margin_utilization.iter().map(|s| s.utilization).collect();
+        // 3000 margin used over 10,000 cash less the 15 taker fee
+        assert_eq!(samples, vec![Decimal::ZERO, Decimal::from(3000) / Decimal::from(9985)]);
+        assert!(result.passed);
+        assert_eq!(result.trades_executed, 1);
+    }
+
+    #[test]
+    fn test_unknown_market_rejected() {
+        let mut sim = two_markets();
+        let result = sim.submit_order(AccountId::new(), "SOL/USDT", Side::BUY, Price::from_u64(1), Decimal::ONE, 100);
+        assert_eq!(result, Err(OrderRejection::UnknownMarket("SOL/USDT".to_string())));
+    }
 }
@@ -4,7 +4,6 @@
 //! same events → same final state.
 
 use crate::engine::{SimEngine, SimEvent};
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use types::fee::FeeTier;
 use types::ids::MarketId;
@@ -42,10 +41,21 @@ pub fn replay_and_snapshot(
     let mut engine = SimEngine::new(symbol, fee_tier);
 
     for event in events {
-        if let SimEvent::OrderPlaced {
-            account_id, side, price, quantity, timestamp, ..
-        } = event {
-            engine.submit_order(*account_id, *side, *price, *quantity, *timestamp);
+        match event {
+            SimEvent::OrderPlaced {
+                account_id, side, price, quantity, timestamp, ..
+            } => {
+                engine.submit_order(*account_id, *side, *price, *quantity, *timestamp);
+            }
+            SimEvent::MarketOrderPlaced {
+                account_id, side, quantity, timestamp, ..
+            }
+            | SimEvent::OrderRejected {
+                account_id, side, quantity, timestamp, ..
+            } => {
+                engine.submit_market_order(*account_id, *side, *quantity, *timestamp);
+            }
+            _ => {}
         }
     }
 
@@ -92,6 +102,7 @@ pub fn import_event_log(json: &str) -> Result<Vec<SimEvent>, serde_json::Error>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal::Decimal;
     use types::ids::AccountId;
     use types::numeric::Price;
     use types::order::Side;
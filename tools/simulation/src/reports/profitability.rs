@@ -1,13 +1,16 @@
 //! Profitability report
 //!
 //! Tracks per-account PnL, fee costs, and net results across simulation.
+//! PnL is broken out into trading PnL (marked to each market's last trade)
+//! and carry (funding received/paid and borrow costs).
 
+use crate::carry::last_trade_price;
 use crate::engine::SimEvent;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use types::ids::AccountId;
+use types::order::Side;
 
 /// Per-account profitability record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +22,16 @@ pub struct AccountProfit {
     pub total_taker_fees: String,
     pub net_fee_cost: String,
     pub trade_count: u64,
+    /// Realized + unrealized trading PnL, before fees and carry
+    pub trading_pnl: String,
+    /// Net funding received (negative = paid)
+    pub funding_pnl: String,
+    /// Borrow cost paid on short spot inventory
+    pub borrow_cost: String,
+    /// `funding_pnl − borrow_cost`
+    pub carry_pnl: String,
+    /// `trading_pnl + carry_pnl − net_fee_cost`
+    pub net_pnl: String,
 }
 
 /// Aggregated profitability report.
@@ -26,9 +39,19 @@ pub struct AccountProfit {
 pub struct ProfitabilityReport {
     pub accounts: Vec<AccountProfit>,
     pub total_volume: String,
+    /// Traded value where the buyer took liquidity
+    pub buyer_initiated_volume: String,
+    /// Traded value where the seller took liquidity
+    pub seller_initiated_volume: String,
     pub total_fees_collected: String,
     pub total_maker_rebates: String,
     pub net_exchange_revenue: String,
+    pub total_funding_paid: String,
+    pub total_borrow_costs: String,
+    /// `Σ (trading_pnl + funding_pnl)` over all accounts
+    pub zero_sum_residual: String,
+    /// Whether the residual is within funding rounding dust
+    pub zero_sum: bool,
 }
 
 /// Internal accumulator for an account.
@@ -39,41 +62,92 @@ struct AccountAccum {
     maker_fees: Decimal,
     taker_fees: Decimal,
     trade_count: u64,
+    trading_pnl: Decimal,
+    funding: Decimal,
+    borrow: Decimal,
 }
 
-/// Generate a profitability report from simulation events.
+/// Generate a profitability report from a single market's simulation events.
 pub fn analyze(events: &[SimEvent]) -> ProfitabilityReport {
+    analyze_markets(&[events])
+}
+
+/// Generate a profitability report across markets, one event log per market.
+///
+/// Funding only moves cash between accounts, so trading PnL plus funding sums
+/// to zero across accounts up to half a unit of the 8th decimal per payment.
+pub fn analyze_markets(markets: &[&[SimEvent]]) -> ProfitabilityReport {
     let mut accounts: HashMap<AccountId, AccountAccum> = HashMap::new();
+    let mut funding_payments: u64 = 0;
+    let mut buyer_initiated = Decimal::ZERO;
+    let mut seller_initiated = Decimal::ZERO;
+
+    for events in markets {
+        let mark = last_trade_price(events).unwrap_or(Decimal::ZERO);
+        // Per account: (signed net quantity, cash flow from trades)
+        let mut inventory: HashMap<AccountId, (Decimal, Decimal)> = HashMap::new();
 
-    for event in events {
-        if let SimEvent::TradeExecuted {
-            maker_account_id,
-            taker_account_id,
-            price,
-            quantity,
-            maker_fee,
-            taker_fee,
-            ..
-        } = event {
-            let trade_value = *quantity * price.as_decimal();
-
-            // Maker side
-            let maker = accounts.entry(*maker_account_id).or_default();
-            maker.sell_volume += trade_value;
-            maker.maker_fees += *maker_fee;
-            maker.trade_count += 1;
-
-            // Taker side
-            let taker = accounts.entry(*taker_account_id).or_default();
-            taker.buy_volume += trade_value;
-            taker.taker_fees += *taker_fee;
-            taker.trade_count += 1;
+        for event in events.iter() {
+            match event {
+                SimEvent::TradeExecuted {
+                    maker_account_id,
+                    taker_account_id,
+                    price,
+                    quantity,
+                    maker_fee,
+                    taker_fee,
+                    aggressor_side,
+                    ..
+                } => {
+                    let trade_value = *quantity * price.as_decimal();
+                    match aggressor_side {
+                        Side::BUY => buyer_initiated += trade_value,
+                        Side::SELL => seller_initiated += trade_value,
+                    }
+
+                    for (account_id, is_taker) in [(*maker_account_id, false), (*taker_account_id, true)] {
+                        let buys = (*aggressor_side == Side::BUY) == is_taker;
+                        let acc = accounts.entry(account_id).or_default();
+                        let inv = inventory.entry(account_id).or_default();
+                        if buys {
+                            acc.buy_volume += trade_value;
+                            inv.0 += *quantity;
+                            inv.1 -= trade_value;
+                        } else {
+                            acc.sell_volume += trade_value;
+                            inv.0 -= *quantity;
+                            inv.1 += trade_value;
+                        }
+                        if is_taker {
+                            acc.taker_fees += *taker_fee;
+                        } else {
+                            acc.maker_fees += *maker_fee;
+                        }
+                        acc.trade_count += 1;
+                    }
+                }
+                SimEvent::FundingPayment { account_id, amount, .. } => {
+                    accounts.entry(*account_id).or_default().funding += *amount;
+                    funding_payments += 1;
+                }
+                SimEvent::BorrowCharged { account_id, amount, .. } => {
+                    accounts.entry(*account_id).or_default().borrow += *amount;
+                }
+                _ => {}
+            }
+        }
+
+        for (account_id, (qty, cash)) in inventory {
+            accounts.entry(account_id).or_default().trading_pnl += cash + qty * mark;
         }
     }
 
     let mut total_volume = Decimal::ZERO;
     let mut total_fees = Decimal::ZERO;
     let mut total_rebates = Decimal::ZERO;
+    let mut total_funding_paid = Decimal::ZERO;
+    let mut total_borrow = Decimal::ZERO;
+    let mut residual = Decimal::ZERO;
 
     let mut result_accounts: Vec<AccountProfit> = accounts.iter().map(|(id, acc)| {
         let volume = acc.buy_volume + acc.sell_volume;
@@ -85,7 +159,13 @@ pub fn analyze(events: &[SimEvent]) -> ProfitabilityReport {
         if acc.maker_fees < Decimal::ZERO {
             total_rebates += acc.maker_fees.abs();
         }
+        if acc.funding < Decimal::ZERO {
+            total_funding_paid += acc.funding.abs();
+        }
+        total_borrow += acc.borrow;
+        residual += acc.trading_pnl + acc.funding;
 
+        let carry = acc.funding - acc.borrow;
         AccountProfit {
             account_id: id.to_string(),
             buy_volume: acc.buy_volume.to_string(),
@@ -94,20 +174,34 @@ pub fn analyze(events: &[SimEvent]) -> ProfitabilityReport {
             total_taker_fees: acc.taker_fees.to_string(),
             net_fee_cost: net_fee.to_string(),
             trade_count: acc.trade_count,
+            trading_pnl: acc.trading_pnl.to_string(),
+            funding_pnl: acc.funding.to_string(),
+            borrow_cost: acc.borrow.to_string(),
+            carry_pnl: carry.to_string(),
+            net_pnl: (acc.trading_pnl + carry - net_fee).to_string(),
         }
     }).collect();
 
     // Sort by trade count descending for readability
-    result_accounts.sort_by(|a, b| b.trade_count.cmp(&a.trade_count));
+    result_accounts.sort_by(|a, b| {
+        b.trade_count.cmp(&a.trade_count).then_with(|| a.account_id.cmp(&b.account_id))
+    });
 
     let net_revenue = total_fees - total_rebates;
+    let dust_tolerance = Decimal::new(5, 9) * Decimal::from(funding_payments);
 
     ProfitabilityReport {
         accounts: result_accounts,
         total_volume: total_volume.to_string(),
+        buyer_initiated_volume: buyer_initiated.to_string(),
+        seller_initiated_volume: seller_initiated.to_string(),
         total_fees_collected: total_fees.to_string(),
         total_maker_rebates: total_rebates.to_string(),
         net_exchange_revenue: net_revenue.to_string(),
+        total_funding_paid: total_funding_paid.to_string(),
+        total_borrow_costs: total_borrow.to_string(),
+        zero_sum_residual: residual.to_string(),
+        zero_sum: residual.abs() <= dust_tolerance,
     }
 }
 
@@ -164,6 +258,36 @@ mod tests {
         assert!(json.contains("net_exchange_revenue"));
     }
 
+    #[test]
+    fn test_trading_pnl_uses_taker_side() {
+        let fee = FeeTier {
+            volume_threshold: Decimal::ZERO,
+            maker_rate: Decimal::ZERO,
+            taker_rate: Decimal::ZERO,
+        };
+        let mut engine = SimEngine::new(MarketId::new("BTC/USDT"), fee);
+        let buyer = AccountId::new();
+        let seller = AccountId::new();
+
+        // Buyer rests, seller takes; a later trade marks the buyer up 100
+        engine.submit_order(buyer, Side::BUY, Price::from_u64(1000), Decimal::ONE, 100);
+        engine.submit_order(seller, Side::SELL, Price::from_u64(1000), Decimal::ONE, 101);
+        engine.submit_order(seller, Side::SELL, Price::from_u64(1100), Decimal::ONE, 102);
+        engine.submit_order(buyer, Side::BUY, Price::from_u64(1100), Decimal::ONE, 103);
+
+        let report = analyze(&engine.events);
+        let buyer_profit = report.accounts.iter()
+            .find(|a| a.account_id == buyer.to_string())
+            .unwrap();
+        assert_eq!(buyer_profit.buy_volume, "2100");
+        assert_eq!(buyer_profit.sell_volume, "0");
+        assert_eq!(buyer_profit.trading_pnl, "100");
+        assert_eq!(report.zero_sum_residual, "0");
+        assert_eq!(report.buyer_initiated_volume, "1100");
+        assert_eq!(report.seller_initiated_volume, "1000");
+        assert!(report.zero_sum);
+    }
+
     #[test]
     fn test_empty_events() {
         let report = analyze(&[]);
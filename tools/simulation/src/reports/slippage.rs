@@ -4,7 +4,6 @@
 //! Provides aggregated statistics (mean, p50, p99).
 
 use crate::engine::SimEvent;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -0,0 +1,220 @@
+//! Funding carry scenario
+//!
+//! A maker runs a delta-neutral basis trade: short the perpetual, long the
+//! same size on spot. Noise traders move both markets together each interval
+//! while the carry scheduler pays funding from longs to shorts. The maker's
+//! trading PnL stays flat, so its result is dominated by funding received.
+
+use crate::carry::{CarryConfig, CarryScheduler, FundingSource, MarketCarry};
+use crate::engine::SimEngine;
+use crate::reports::profitability::{self, AccountProfit, ProfitabilityReport};
+use crate::scenarios::ScenarioResult;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use types::ids::AccountId;
+use types::numeric::Price;
+use types::order::Side;
+
+/// Configuration for the funding carry scenario.
+#[derive(Debug, Clone)]
+pub struct FundingCarryConfig {
+    pub perp_symbol: String,
+    pub spot_symbol: String,
+    /// Spot reference price
+    pub spot_price: Decimal,
+    /// Perp premium over spot (drives positive funding)
+    pub perp_premium: Decimal,
+    /// Size of the maker's hedged position
+    pub position_size: Decimal,
+    /// Timestamp units between carry applications
+    pub interval: i64,
+    /// Number of carry periods to run
+    pub periods: u32,
+    /// Perpetual funding rate source
+    pub funding: FundingSource,
+    /// Per-interval borrow rate on short spot inventory
+    pub borrow_rate: Decimal,
+}
+
+impl Default for FundingCarryConfig {
+    fn default() -> Self {
+        Self {
+            perp_symbol: "BTC-PERP/USDT".to_string(),
+            spot_symbol: "BTC/USDT".to_string(),
+            spot_price: Decimal::from(50000),
+            perp_premium: Decimal::from(10),
+            position_size: Decimal::ONE,
+            interval: 1_000,
+            periods: 30,
+            funding: FundingSource::Schedule(vec![Decimal::from_str_exact("0.0001").unwrap()]),
+            borrow_rate: Decimal::from_str_exact("0.00001").unwrap(),
+        }
+    }
+}
+
+/// Result detail for the funding carry scenario.
+#[derive(Debug, Clone)]
+pub struct FundingCarryDetail {
+    pub maker: AccountProfit,
+    pub report: ProfitabilityReport,
+    pub periods_applied: u64,
+}
+
+/// Run the funding carry scenario on `engines`, which must contain the
+/// configured perp and spot markets.
+///
+/// # Panics
+/// Panics if either market is missing.
+pub fn run(engines: &mut [SimEngine], config: &FundingCarryConfig) -> (ScenarioResult, FundingCarryDetail) {
+    let base_ts: i64 = 1_000_000;
+    let perp = engines.iter().position(|e| e.symbol.as_str() == config.perp_symbol)
+        .expect("perp market missing");
+    let spot = engines.iter().position(|e| e.symbol.as_str() == config.spot_symbol)
+        .expect("spot market missing");
+
+    let maker = AccountId::new();
+    let perp_buyer = AccountId::new();
+    let spot_seller = AccountId::new();
+    let noise_a = AccountId::new();
+    let noise_b = AccountId::new();
+
+    let perp_price = config.spot_price + config.perp_premium;
+    let size = config.position_size;
+    let mut orders_submitted: u64 = 0;
+
+    // Open the hedged position: short perp (maker), long spot (taker)
+    engines[perp].submit_order(maker, Side::SELL, Price::new(perp_price), size, base_ts);
+    engines[perp].submit_order(perp_buyer, Side::BUY, Price::new(perp_price), size, base_ts + 1);
+    engines[spot].submit_order(spot_seller, Side::SELL, Price::new(config.spot_price), size, base_ts + 2);
+    engines[spot].submit_order(maker, Side::BUY, Price::new(config.spot_price), size, base_ts + 3);
+    orders_submitted += 4;
+
+    let mut markets = BTreeMap::new();
+    markets.insert(config.perp_symbol.clone(), MarketCarry::Perpetual(config.funding.clone()));
+    markets.insert(config.spot_symbol.clone(), MarketCarry::Spot { borrow_rate: config.borrow_rate });
+    let mut scheduler = CarryScheduler::new(CarryConfig { interval: config.interval, markets });
+    scheduler.apply_due(engines, base_ts);
+
+    for i in 1..=config.periods {
+        let ts = base_ts + i as i64 * config.interval;
+
+        // Noise trades move both markets by the same drift
+        let drift = Decimal::from((i % 5) * 5);
+        for (index, price) in [(perp, perp_price + drift), (spot, config.spot_price + drift)] {
+            engines[index].submit_order(noise_a, Side::SELL, Price::new(price), Decimal::ONE, ts - 2);
+            engines[index].submit_order(noise_b, Side::BUY, Price::new(price), Decimal::ONE, ts - 1);
+            orders_submitted += 2;
+        }
+
+        scheduler.apply_due(engines, ts);
+    }
+
+    let logs: Vec<&[_]> = engines.iter().map(|e| e.events.as_slice()).collect();
+    let report = profitability::analyze_markets(&logs);
+    let maker_id = maker.to_string();
+    let maker_profit = report.accounts.iter()
+        .find(|a| a.account_id == maker_id)
+        .cloned()
+        .expect("maker traded");
+
+    let trading: Decimal = maker_profit.trading_pnl.parse().unwrap_or_default();
+    let funding: Decimal = maker_profit.funding_pnl.parse().unwrap_or_default();
+
+    let result = ScenarioResult {
+        name: "funding_carry".to_string(),
+        ticks_run: config.periods as u64,
+        orders_submitted,
+        trades_executed: engines.iter().map(|e| e.trade_count() as u64).sum(),
+        events_emitted: engines.iter().map(|e| e.events.len()).sum(),
+        passed: report.zero_sum && funding > trading.abs(),
+        margin_utilization: Vec::new(),
+        details: format!(
+            "Maker trading PnL {}, funding {}, net {}. Zero-sum residual {}.",
+            maker_profit.trading_pnl,
+            maker_profit.funding_pnl,
+            maker_profit.net_pnl,
+            report.zero_sum_residual,
+        ),
+    };
+
+    let detail = FundingCarryDetail {
+        maker: maker_profit,
+        report,
+        periods_applied: scheduler.periods_applied(),
+    };
+
+    (result, detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_market::MultiMarketSim;
+    use types::fee::FeeTier;
+    use types::ids::MarketId;
+
+    fn test_sim() -> MultiMarketSim {
+        let fee = FeeTier {
+            volume_threshold: Decimal::ZERO,
+            maker_rate: Decimal::from_str_exact("0.0002").unwrap(),
+            taker_rate: Decimal::from_str_exact("0.0005").unwrap(),
+        };
+        MultiMarketSim::new(
+            vec![MarketId::new("BTC-PERP/USDT"), MarketId::new("BTC/USDT")],
+            fee,
+        )
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_delta_neutral_maker_dominated_by_funding() {
+        let mut sim = test_sim();
+        let (result, detail) = run(&mut sim.engines, &FundingCarryConfig::default());
+
+        assert!(result.passed, "{}", result.details);
+        assert_eq!(detail.periods_applied, 30);
+
+        let trading = dec(&detail.maker.trading_pnl);
+        let funding = dec(&detail.maker.funding_pnl);
+        let net = dec(&detail.maker.net_pnl);
+        // 30 periods × 1 BTC × 50010 × 0.0001, shifted only by mark drift
+        assert!(funding > Decimal::from(140));
+        assert_eq!(trading, Decimal::ZERO);
+        assert!(net > Decimal::ZERO);
+        assert!(funding > dec(&detail.maker.net_fee_cost));
+        // Maker is long spot, so it pays no borrow
+        assert_eq!(dec(&detail.maker.borrow_cost), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_funding_is_zero_sum_and_borrow_charged() {
+        let mut sim = test_sim();
+        let (_, detail) = run(&mut sim.engines, &FundingCarryConfig::default());
+
+        assert!(detail.report.zero_sum);
+        assert!(dec(&detail.report.zero_sum_residual).abs() < dec("0.000001"));
+        assert!(dec(&detail.report.total_funding_paid) > Decimal::ZERO);
+        // The spot seller (short) pays borrow every period
+        assert!(dec(&detail.report.total_borrow_costs) > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_basis_driven_funding() {
+        let mut sim = test_sim();
+        let config = FundingCarryConfig {
+            funding: FundingSource::Basis {
+                index_symbol: "BTC/USDT".to_string(),
+                cap: Decimal::from_str_exact("0.0075").unwrap(),
+            },
+            ..FundingCarryConfig::default()
+        };
+        let (result, detail) = run(&mut sim.engines, &config);
+
+        // A perp premium means the short maker receives funding
+        assert!(result.passed, "{}", result.details);
+        assert!(dec(&detail.maker.funding_pnl) > Decimal::ZERO);
+    }
+}
@@ -5,7 +5,6 @@
 
 use crate::engine::SimEngine;
 use crate::scenarios::ScenarioResult;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use types::fee::{default_fee_tiers, FeeTier};
 use types::ids::AccountId;
@@ -117,6 +116,7 @@ pub fn run(engine: &mut SimEngine, config: &IncentiveConfig) -> (ScenarioResult,
         trades_executed: engine.trade_count() as u64,
         events_emitted: engine.events.len(),
         passed: true,
+        margin_utilization: Vec::new(),
         details: format!(
             "Volume: {}. Final tier: {} (maker: {}, taker: {}). {} tier upgrades.",
             cumulative_volume,
@@ -0,0 +1,593 @@
+//! Journal replay scenario
+//!
+//! Feeds a recorded journal (matching-engine or persistence-service
+//! segments) into a fresh `SimEngine` and checks the simulator reaches the
+//! outcomes the journal records.
+//!
+//! Book events are decoded through the matching engine's typed registry
+//! and regrouped into submissions: a taker's consecutive `TradeExecuted`
+//! events, plus the `OrderAccepted` of any remainder, become one limit
+//! order — limited at its resting price, or at its worst fill when it
+//! did not rest. Cancels are applied to the simulated order. After every
+//! mapped event the replay compares book depth, trade count and fee
+//! totals with the ones the journal implies, and stops at the first
+//! difference, reporting its journal sequence.
+//!
+//! Event types the simulator does not model (amends, price bands,
+//! auctions, and the risk engine's liquidations and funding) stop the
+//! replay as well, unless the [`ReplayTolerance`] skips them.
+
+use crate::engine::{round_up_fee, SimEngine, SimEvent};
+use crate::scenarios::ScenarioResult;
+use matching_engine::events::{BookEvent, TradeExecutedEvent};
+use matching_engine::restore::{decode_event, BOOK_EVENT_TYPES};
+use persistence::journal::JournalEntry;
+use persistence::reader::{JournalReader, ReaderError};
+use risk_engine::restore::RISK_INPUT_TYPES;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+use types::fee::FeeTier;
+use types::ids::{AccountId, MarketId, OrderId};
+use types::numeric::Price;
+use types::order::Side;
+
+/// Trades journaled before the liquidity flags, still decoded as book events
+const LEGACY_TRADE_TYPE: &str = "TradeExecuted";
+
+/// What the replay lets pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTolerance {
+    /// Event types passed over without replaying them; by default the
+    /// risk engine's inputs, which do not touch the book
+    pub skip_event_types: BTreeSet<String>,
+    /// Largest accepted difference between recorded and simulated fee totals
+    pub fee_tolerance: Decimal,
+}
+
+impl ReplayTolerance {
+    /// Also pass over `event_type`.
+    pub fn skip(mut self, event_type: &str) -> Self {
+        self.skip_event_types.insert(event_type.to_string());
+        self
+    }
+}
+
+impl Default for ReplayTolerance {
+    fn default() -> Self {
+        Self {
+            skip_event_types: RISK_INPUT_TYPES.iter().map(|t| t.to_string()).collect(),
+            fee_tolerance: Decimal::ZERO,
+        }
+    }
+}
+
+/// Errors reading the journal (divergences are part of the report).
+#[derive(Error, Debug)]
+pub enum JournalScenarioError {
+    #[error("Journal read failed: {0}")]
+    Reader(#[from] ReaderError),
+}
+
+/// A fill, identified by the recorded id of the maker order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFill {
+    pub maker_order_id: OrderId,
+    pub price: Price,
+    pub quantity: Decimal,
+}
+
+/// Book depth, trades and fees after a replayed event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    pub bid_depth: Decimal,
+    pub ask_depth: Decimal,
+    pub trade_count: u64,
+    /// Maker plus taker fees
+    pub fees: Decimal,
+}
+
+/// How the simulation departed from the journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DivergenceKind {
+    /// An event type the simulator does not model, not skipped by the tolerance
+    Unmodeled,
+    /// A book event whose payload did not decode
+    Undecodable { reason: String },
+    /// The canceled order is not resting in the simulation
+    UnknownOrder { order_id: OrderId },
+    /// The replayed submission filled differently
+    Fills { recorded: Vec<ReplayFill>, simulated: Vec<ReplayFill> },
+    /// Depth, trade count or fees differ after the event
+    Outcome { recorded: ReplayOutcome, simulated: ReplayOutcome },
+}
+
+/// The first journal entry the simulation could not reproduce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// Sequence of the entry (for a submission, of its first event)
+    pub sequence: u64,
+    pub event_type: String,
+    pub kind: DivergenceKind,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "diverged at sequence {} ({}): ", self.sequence, self.event_type)?;
+        match &self.kind {
+            DivergenceKind::Unmodeled => write!(f, "event type not modeled"),
+            DivergenceKind::Undecodable { reason } => write!(f, "undecodable: {}", reason),
+            DivergenceKind::UnknownOrder { order_id } => write!(f, "order {} is not resting", order_id),
+            DivergenceKind::Fills { recorded, simulated } => {
+                write!(f, "{} recorded fills, {} simulated", recorded.len(), simulated.len())
+            }
+            DivergenceKind::Outcome { recorded, simulated } => write!(
+                f,
+                "recorded depth {}/{}, {} trades, fees {}; simulated {}/{}, {} trades, fees {}",
+                recorded.bid_depth,
+                recorded.ask_depth,
+                recorded.trade_count,
+                recorded.fees,
+                simulated.bid_depth,
+                simulated.ask_depth,
+                simulated.trade_count,
+                simulated.fees
+            ),
+        }
+    }
+}
+
+/// Result of replaying a journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalReplayReport {
+    pub symbol: String,
+    /// Journal entries read
+    pub entries: u64,
+    /// Submissions and cancels replayed into the simulator
+    pub submissions: u64,
+    pub cancels: u64,
+    /// Entries passed over by the tolerance, by event type
+    pub skipped: BTreeMap<String, u64>,
+    /// Book events of other markets
+    pub other_symbols: u64,
+    /// Last sequence replayed without diverging
+    pub last_sequence: u64,
+    pub recorded: ReplayOutcome,
+    pub simulated: ReplayOutcome,
+    pub divergence: Option<Divergence>,
+}
+
+impl JournalReplayReport {
+    pub fn matches(&self) -> bool {
+        self.divergence.is_none()
+    }
+
+    pub fn to_scenario_result(&self, name: &str) -> ScenarioResult {
+        let details = match &self.divergence {
+            Some(divergence) => divergence.to_string(),
+            None => format!(
+                "reproduced through sequence {}: depth {}/{}, {} trades, fees {}; {} entries skipped",
+                self.last_sequence,
+                self.simulated.bid_depth,
+                self.simulated.ask_depth,
+                self.simulated.trade_count,
+                self.simulated.fees,
+                self.skipped.values().sum::<u64>()
+            ),
+        };
+        ScenarioResult {
+            name: name.to_string(),
+            ticks_run: self.entries,
+            orders_submitted: self.submissions,
+            trades_executed: self.simulated.trade_count,
+            events_emitted: (self.submissions + self.cancels) as usize,
+            passed: self.matches(),
+            margin_utilization: Vec::new(),
+            details,
+        }
+    }
+}
+
+/// Replays recorded journals of one market into a fresh `SimEngine`.
+#[derive(Debug, Clone)]
+pub struct JournalScenario {
+    symbol: MarketId,
+    fee_tier: FeeTier,
+    tolerance: ReplayTolerance,
+}
+
+impl JournalScenario {
+    /// Replay `symbol`'s events, charging `fee_tier` to the fills the
+    /// journal records and to the simulated ones alike.
+    pub fn new(symbol: MarketId, fee_tier: FeeTier) -> Self {
+        Self {
+            symbol,
+            fee_tier,
+            tolerance: ReplayTolerance::default(),
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: ReplayTolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Replay every segment in `journal_dir`.
+    pub fn run(&self, journal_dir: &Path) -> Result<JournalReplayReport, JournalScenarioError> {
+        let entries = JournalReader::open(journal_dir)?.read_all_validated()?;
+        Ok(self.replay(&entries))
+    }
+
+    /// Replay journal entries, in sequence order.
+    pub fn replay(&self, entries: &[JournalEntry]) -> JournalReplayReport {
+        let mut replay = Replay {
+            scenario: self,
+            engine: SimEngine::new(self.symbol.clone(), self.fee_tier.clone()),
+            recorded_to_sim: HashMap::new(),
+            sim_to_recorded: HashMap::new(),
+            pending: None,
+            report: JournalReplayReport {
+                symbol: self.symbol.to_string(),
+                entries: 0,
+                submissions: 0,
+                cancels: 0,
+                skipped: BTreeMap::new(),
+                other_symbols: 0,
+                last_sequence: 0,
+                recorded: ReplayOutcome::default(),
+                simulated: ReplayOutcome::default(),
+                divergence: None,
+            },
+        };
+        let outcome = entries.iter().try_for_each(|entry| replay.apply(entry)).and_then(|_| replay.flush());
+        if let Err(divergence) = outcome {
+            replay.report.divergence = Some(*divergence);
+        }
+        replay.report
+    }
+}
+
+/// A taker order reassembled from its journaled events.
+struct Submission {
+    sequence: u64,
+    event_type: String,
+    order_id: OrderId,
+    account_id: AccountId,
+    side: Side,
+    fills: Vec<ReplayFill>,
+    /// Price and quantity of the remainder that rested
+    rested: Option<(Price, Decimal)>,
+    timestamp: i64,
+}
+
+struct Replay<'a> {
+    scenario: &'a JournalScenario,
+    engine: SimEngine,
+    recorded_to_sim: HashMap<OrderId, OrderId>,
+    sim_to_recorded: HashMap<OrderId, OrderId>,
+    pending: Option<Submission>,
+    report: JournalReplayReport,
+}
+
+impl Replay<'_> {
+    fn apply(&mut self, entry: &JournalEntry) -> Result<(), Box<Divergence>> {
+        self.report.entries += 1;
+        let diverged = |kind| {
+            Box::new(Divergence {
+                sequence: entry.sequence,
+                event_type: entry.event_type.clone(),
+                kind,
+            })
+        };
+        if self.scenario.tolerance.skip_event_types.contains(&entry.event_type) {
+            *self.report.skipped.entry(entry.event_type.clone()).or_default() += 1;
+            return Ok(());
+        }
+        let is_book_type =
+            BOOK_EVENT_TYPES.contains(&entry.event_type.as_str()) || entry.event_type == LEGACY_TRADE_TYPE;
+        let event = match decode_event(entry) {
+            Ok(Some(event)) => event,
+            Err(e) if is_book_type => {
+                self.flush()?;
+                return Err(diverged(DivergenceKind::Undecodable { reason: e.to_string() }));
+            }
+            Ok(None) | Err(_) => {
+                self.flush()?;
+                return Err(diverged(DivergenceKind::Unmodeled));
+            }
+        };
+        if event.symbol() != self.scenario.symbol.as_str() {
+            self.report.other_symbols += 1;
+            return Ok(());
+        }
+
+        match event {
+            BookEvent::TradeExecuted(trade) => {
+                if self.pending.as_ref().is_some_and(|p| p.order_id != trade.taker_order_id) {
+                    self.flush()?;
+                }
+                let pending = self.pending.get_or_insert_with(|| Submission {
+                    sequence: entry.sequence,
+                    event_type: entry.event_type.clone(),
+                    order_id: trade.taker_order_id,
+                    account_id: trade.taker_account_id,
+                    side: trade.side,
+                    fills: Vec::new(),
+                    rested: None,
+                    timestamp: trade.executed_at,
+                });
+                pending.fills.push(recorded_fill(&trade));
+            }
+            BookEvent::OrderAccepted { order_id, account_id, side, price, quantity, accepted_at, .. } => {
+                if self.pending.as_ref().is_some_and(|p| p.order_id != order_id) {
+                    self.flush()?;
+                }
+                let pending = self.pending.get_or_insert_with(|| Submission {
+                    sequence: entry.sequence,
+                    event_type: entry.event_type.clone(),
+                    order_id,
+                    account_id,
+                    side,
+                    fills: Vec::new(),
+                    rested: None,
+                    timestamp: accepted_at,
+                });
+                pending.rested = Some((price, quantity.as_decimal()));
+                self.flush()?;
+            }
+            BookEvent::OrderCanceled { order_id, side, remaining_quantity, .. } => {
+                self.flush()?;
+                let canceled = self
+                    .recorded_to_sim
+                    .get(&order_id)
+                    .is_some_and(|sim_id| self.engine.cancel_order(*sim_id, entry.timestamp));
+                if !canceled {
+                    return Err(diverged(DivergenceKind::UnknownOrder { order_id }));
+                }
+                self.report.cancels += 1;
+                *self.recorded_depth(side) -= remaining_quantity.as_decimal();
+                self.compare(entry.sequence, &entry.event_type)?;
+            }
+            // Stops are off-book; the submission they release is journaled after them
+            BookEvent::StopAccepted(_) | BookEvent::StopTriggered(_) => self.flush()?,
+            BookEvent::OrderAmended(_) | BookEvent::PriceBandHit(_) | BookEvent::AuctionUncrossed(_) => {
+                self.flush()?;
+                return Err(diverged(DivergenceKind::Unmodeled));
+            }
+        }
+        self.report.last_sequence = entry.sequence;
+        Ok(())
+    }
+
+    /// Submit the pending taker order to the simulator and compare its fills.
+    fn flush(&mut self) -> Result<(), Box<Divergence>> {
+        let Some(submission) = self.pending.take() else {
+            return Ok(());
+        };
+        let filled: Decimal = submission.fills.iter().map(|fill| fill.quantity).sum();
+        let limit = match submission.rested {
+            Some((price, _)) => price,
+            None => worst_price(submission.side, &submission.fills),
+        };
+        let quantity = filled + submission.rested.map_or(Decimal::ZERO, |(_, quantity)| quantity);
+
+        let first_event = self.engine.events.len();
+        let sim_id = self.engine.submit_order(
+            submission.account_id,
+            submission.side,
+            limit,
+            quantity,
+            submission.timestamp,
+        );
+        self.recorded_to_sim.insert(submission.order_id, sim_id);
+        self.sim_to_recorded.insert(sim_id, submission.order_id);
+        self.report.submissions += 1;
+
+        let mut simulated = Vec::new();
+        for event in &self.engine.events[first_event..] {
+            if let SimEvent::TradeExecuted {
+                maker_order_id, price, quantity, maker_fee, taker_fee, ..
+            } = event
+            {
+                let recorded_maker = self.sim_to_recorded.get(maker_order_id);
+                simulated.push(ReplayFill {
+                    maker_order_id: recorded_maker.copied().unwrap_or(*maker_order_id),
+                    price: *price,
+                    quantity: *quantity,
+                });
+                self.report.simulated.fees += maker_fee + taker_fee;
+            }
+        }
+        self.report.simulated.trade_count += simulated.len() as u64;
+
+        let fee_tier = &self.scenario.fee_tier;
+        for fill in &submission.fills {
+            let value = fill.quantity * fill.price.as_decimal();
+            self.report.recorded.fees +=
+                round_up_fee(value * fee_tier.maker_rate) + round_up_fee(value * fee_tier.taker_rate);
+            *self.recorded_depth(submission.side.opposite()) -= fill.quantity;
+        }
+        self.report.recorded.trade_count += submission.fills.len() as u64;
+        if let Some((_, rested)) = submission.rested {
+            *self.recorded_depth(submission.side) += rested;
+        }
+
+        if simulated != submission.fills {
+            return Err(Box::new(Divergence {
+                sequence: submission.sequence,
+                event_type: submission.event_type,
+                kind: DivergenceKind::Fills {
+                    recorded: submission.fills,
+                    simulated,
+                },
+            }));
+        }
+        self.compare(submission.sequence, &submission.event_type)
+    }
+
+    fn recorded_depth(&mut self, side: Side) -> &mut Decimal {
+        match side {
+            Side::BUY => &mut self.report.recorded.bid_depth,
+            Side::SELL => &mut self.report.recorded.ask_depth,
+        }
+    }
+
+    fn compare(&mut self, sequence: u64, event_type: &str) -> Result<(), Box<Divergence>> {
+        self.report.simulated.bid_depth = self.engine.bid_depth();
+        self.report.simulated.ask_depth = self.engine.ask_depth();
+        let (recorded, simulated) = (&self.report.recorded, &self.report.simulated);
+        let fees_within = (recorded.fees - simulated.fees).abs() <= self.scenario.tolerance.fee_tolerance;
+        let matches = recorded.bid_depth == simulated.bid_depth
+            && recorded.ask_depth == simulated.ask_depth
+            && recorded.trade_count == simulated.trade_count
+            && fees_within;
+        if matches {
+            return Ok(());
+        }
+        Err(Box::new(Divergence {
+            sequence,
+            event_type: event_type.to_string(),
+            kind: DivergenceKind::Outcome {
+                recorded: recorded.clone(),
+                simulated: simulated.clone(),
+            },
+        }))
+    }
+}
+
+fn recorded_fill(trade: &TradeExecutedEvent) -> ReplayFill {
+    ReplayFill {
+        maker_order_id: trade.maker_order_id,
+        price: trade.price,
+        quantity: trade.quantity.as_decimal(),
+    }
+}
+
+/// Least favorable fill price for the taker, which reaches every level it filled at.
+fn worst_price(side: Side, fills: &[ReplayFill]) -> Price {
+    let prices = fills.iter().map(|fill| fill.price);
+    let worst = match side {
+        Side::BUY => prices.max_by_key(|price| price.as_decimal()),
+        Side::SELL => prices.min_by_key(|price| price.as_decimal()),
+    };
+    worst.expect("a submission has fills or a resting remainder")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matching_engine::restore::journal_entry;
+    use types::ids::TradeId;
+    use types::numeric::Quantity;
+
+    fn test_fee() -> FeeTier {
+        FeeTier {
+            volume_threshold: Decimal::ZERO,
+            maker_rate: Decimal::from_str_exact("0.0002").unwrap(),
+            taker_rate: Decimal::from_str_exact("0.0005").unwrap(),
+        }
+    }
+
+    fn scenario() -> JournalScenario {
+        JournalScenario::new(MarketId::new("BTC/USDT"), test_fee())
+    }
+
+    type Party = (OrderId, AccountId);
+
+    fn accepted((order_id, account_id): Party, side: Side, price: u64, quantity: &str) -> BookEvent {
+        BookEvent::OrderAccepted {
+            order_id,
+            account_id,
+            symbol: "BTC/USDT".into(),
+            side,
+            price: Price::from_u64(price),
+            quantity: Quantity::from_str(quantity).unwrap(),
+            accepted_at: 100,
+        }
+    }
+
+    fn trade(maker: Party, taker: Party, price: u64, quantity: &str) -> BookEvent {
+        BookEvent::TradeExecuted(TradeExecutedEvent {
+            trade_id: TradeId::new(),
+            sequence: 0,
+            symbol: "BTC/USDT".into(),
+            maker_order_id: maker.0,
+            taker_order_id: taker.0,
+            maker_account_id: maker.1,
+            taker_account_id: taker.1,
+            price: Price::from_u64(price),
+            quantity: Quantity::from_str(quantity).unwrap(),
+            side: Side::BUY,
+            aggressor_side: Side::BUY,
+            maker_is_buyer: false,
+            executed_at: 101,
+        })
+    }
+
+    fn entries(events: &[BookEvent]) -> Vec<JournalEntry> {
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| journal_entry(i as u64 + 1, 100 + i as i64, event))
+            .collect()
+    }
+
+    #[test]
+    fn test_taker_rebuilt_from_its_fills_and_remainder() {
+        let maker = (OrderId::new(), AccountId::new());
+        let taker = (OrderId::new(), AccountId::new());
+        let journal = entries(&[
+            accepted(maker, Side::SELL, 100, "1"),
+            trade(maker, taker, 100, "1"),
+            accepted(taker, Side::BUY, 101, "0.5"),
+        ]);
+
+        let report = scenario().replay(&journal);
+        assert!(report.matches(), "{:?}", report.divergence);
+        assert_eq!(report.submissions, 2);
+        assert_eq!(report.simulated, report.recorded);
+        assert_eq!(report.simulated.bid_depth, Decimal::from_str_exact("0.5").unwrap());
+        assert_eq!(report.simulated.ask_depth, Decimal::ZERO);
+        // 100 notional at 2 + 5 bps
+        assert_eq!(report.simulated.fees, Decimal::from_str_exact("0.07").unwrap());
+    }
+
+    #[test]
+    fn test_cancel_of_an_order_never_placed_diverges() {
+        let maker = (OrderId::new(), AccountId::new());
+        let ghost = OrderId::new();
+        let journal = entries(&[
+            accepted(maker, Side::SELL, 100, "1"),
+            BookEvent::OrderCanceled {
+                order_id: ghost,
+                symbol: "BTC/USDT".into(),
+                side: Side::BUY,
+                price: Price::from_u64(99),
+                remaining_quantity: Quantity::from_str("1").unwrap(),
+            },
+        ]);
+
+        let divergence = scenario().replay(&journal).divergence.unwrap();
+        assert_eq!(divergence.sequence, 2);
+        assert_eq!(divergence.event_type, "OrderCanceled");
+        assert_eq!(divergence.kind, DivergenceKind::UnknownOrder { order_id: ghost });
+    }
+
+    #[test]
+    fn test_other_markets_are_passed_over() {
+        let maker = (OrderId::new(), AccountId::new());
+        let mut other = accepted((OrderId::new(), AccountId::new()), Side::BUY, 3_000, "2");
+        if let BookEvent::OrderAccepted { symbol, .. } = &mut other {
+            *symbol = "ETH/USDT".into();
+        }
+        let journal = entries(&[other, accepted(maker, Side::SELL, 100, "1")]);
+
+        let report = scenario().replay(&journal);
+        assert!(report.matches());
+        assert_eq!((report.other_symbols, report.submissions), (1, 1));
+        assert_eq!(report.last_sequence, 2);
+    }
+}
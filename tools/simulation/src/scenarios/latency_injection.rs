@@ -5,7 +5,6 @@
 
 use crate::engine::SimEngine;
 use crate::scenarios::ScenarioResult;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use std::collections::VecDeque;
 use types::ids::AccountId;
@@ -122,6 +121,7 @@ pub fn run(engine: &mut SimEngine, config: &LatencyConfig) -> ScenarioResult {
         trades_executed: trades,
         events_emitted: events_after - events_before,
         passed: true,
+        margin_utilization: Vec::new(),
         details: format!(
             "Injected {} tick delay on {} orders. {} trades executed.",
             config.delay_ticks, total_orders, trades,
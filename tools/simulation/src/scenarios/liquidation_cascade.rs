@@ -6,7 +6,6 @@
 
 use crate::engine::SimEngine;
 use crate::scenarios::ScenarioResult;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use types::ids::AccountId;
 use types::numeric::{Price, Quantity};
@@ -138,6 +137,7 @@ pub fn run(engine: &mut SimEngine, config: &LiquidationCascadeConfig) -> (Scenar
         trades_executed: engine.trade_count() as u64,
         events_emitted: engine.events.len(),
         passed: true,
+        margin_utilization: Vec::new(),
         details: format!(
             "{}/{} positions liquidated ({:.1}%). Cascade detected: {}",
             liquidated_count,
@@ -7,8 +7,10 @@ pub mod latency_injection;
 pub mod order_flood;
 pub mod liquidation_cascade;
 pub mod incentive;
+pub mod funding_carry;
+pub mod journal_replay;
 
-use crate::engine::SimEngine;
+use crate::multi_market::MarginUtilization;
 use serde::{Deserialize, Serialize};
 
 /// Result of a scenario run.
@@ -20,5 +22,9 @@ pub struct ScenarioResult {
     pub trades_executed: u64,
     pub events_emitted: usize,
     pub passed: bool,
+    /// Per-account cross-market margin utilization samples, in tick order
+    /// (empty for single-market scenarios)
+    #[serde(default)]
+    pub margin_utilization: Vec<MarginUtilization>,
     pub details: String,
 }
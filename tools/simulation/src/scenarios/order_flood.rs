@@ -5,7 +5,6 @@
 
 use crate::engine::{SimEngine, SimEvent};
 use crate::scenarios::ScenarioResult;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use types::ids::AccountId;
 use types::numeric::Price;
@@ -95,6 +94,7 @@ pub fn run(engine: &mut SimEngine, config: &OrderFloodConfig) -> ScenarioResult
         trades_executed: trade_count as u64,
         events_emitted: events_after - events_before,
         passed,
+        margin_utilization: Vec::new(),
         details: format!(
             "Burst of {} orders processed. {} placed, {} trades. {} total events.",
             config.burst_size, placed_count, trade_count,
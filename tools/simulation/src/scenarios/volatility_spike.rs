@@ -5,7 +5,6 @@
 
 use crate::engine::SimEngine;
 use crate::scenarios::ScenarioResult;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use types::ids::AccountId;
 use types::numeric::Price;
@@ -112,6 +111,7 @@ pub fn run(engine: &mut SimEngine, config: &VolatilitySpikeConfig) -> ScenarioRe
         trades_executed: total_trades,
         events_emitted: events_count,
         passed: true,
+        margin_utilization: Vec::new(),
         details: format!(
             "Price moved from {} to {} ({:.1}%) over {} ticks. {} trades executed.",
             config.initial_price,
@@ -4,7 +4,6 @@
 //! without data races (each engine is independent, no shared state).
 
 use simulation::engine::SimEngine;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use std::thread;
 use types::fee::FeeTier;
@@ -0,0 +1,99 @@
+//! Journal replay fixture
+//!
+//! `fixtures/journal` holds a short production journal: 60 steps of the
+//! integration harness (seed 7, three traders) over the real matching
+//! engine, followed by a risk-engine mark price update. Replaying it into
+//! `SimEngine` must reproduce every fill, the final depth and the fees.
+
+use std::path::PathBuf;
+
+use matching_engine::events::BookEvent;
+use matching_engine::restore::{decode_event, journal_entry};
+use persistence::reader::JournalReader;
+use rust_decimal::Decimal;
+use simulation::scenarios::journal_replay::{DivergenceKind, JournalScenario, ReplayTolerance};
+use types::fee::FeeTier;
+use types::ids::MarketId;
+use types::numeric::Price;
+
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/journal")
+}
+
+fn scenario() -> JournalScenario {
+    let fee_tier = FeeTier {
+        volume_threshold: Decimal::ZERO,
+        maker_rate: Decimal::from_str_exact("0.0002").unwrap(),
+        taker_rate: Decimal::from_str_exact("0.0005").unwrap(),
+    };
+    JournalScenario::new(MarketId::new("BTC/USDT"), fee_tier)
+}
+
+#[test]
+fn test_bundled_journal_replays_without_divergence() {
+    let report = scenario().run(&fixture()).unwrap();
+    assert!(report.matches(), "{}", report.divergence.unwrap());
+
+    assert_eq!(report.entries, 76);
+    assert_eq!(report.last_sequence, 75);
+    assert_eq!(report.skipped.get("RiskMarkSource"), Some(&1));
+    assert_eq!(report.cancels, 20);
+    assert_eq!(report.simulated.trade_count, 19);
+    assert_eq!(report.simulated, report.recorded);
+    assert!(report.simulated.fees > Decimal::ZERO);
+
+    let result = report.to_scenario_result("journal_replay");
+    assert!(result.passed, "{}", result.details);
+    assert_eq!(result.trades_executed, 19);
+}
+
+#[test]
+fn test_unmodeled_event_stops_the_replay_unless_skipped() {
+    let strict = ReplayTolerance {
+        skip_event_types: Default::default(),
+        ..ReplayTolerance::default()
+    };
+    let report = scenario().with_tolerance(strict).run(&fixture()).unwrap();
+    let divergence = report.divergence.unwrap();
+    assert_eq!(divergence.sequence, 76);
+    assert_eq!(divergence.event_type, "RiskMarkSource");
+    assert_eq!(divergence.kind, DivergenceKind::Unmodeled);
+    // Everything before it was reproduced
+    assert_eq!(report.last_sequence, 75);
+    assert_eq!(report.simulated, report.recorded);
+
+    let lenient = ReplayTolerance {
+        skip_event_types: Default::default(),
+        ..ReplayTolerance::default()
+    };
+    let report = scenario().with_tolerance(lenient.skip("RiskMarkSource")).run(&fixture()).unwrap();
+    assert!(report.matches());
+}
+
+#[test]
+fn test_tampered_fill_is_reported_at_its_sequence() {
+    let mut entries = JournalReader::open(&fixture()).unwrap().read_all().unwrap();
+    let (index, mut trade) = entries
+        .iter()
+        .enumerate()
+        .find_map(|(i, entry)| match decode_event(entry) {
+            Ok(Some(BookEvent::TradeExecuted(trade))) => Some((i, trade)),
+            _ => None,
+        })
+        .unwrap();
+    // The journal claims a price the maker never rested at
+    trade.price = Price::new(trade.price.as_decimal() - Decimal::ONE);
+    let claimed = trade.price;
+    let sequence = entries[index].sequence;
+    entries[index] = journal_entry(sequence, entries[index].timestamp, &BookEvent::TradeExecuted(trade));
+
+    let report = scenario().replay(&entries);
+    let divergence = report.divergence.unwrap();
+    assert_eq!(divergence.sequence, sequence);
+    assert_eq!(divergence.event_type, "TradeExecuted.v2");
+    let DivergenceKind::Fills { recorded, simulated } = divergence.kind else {
+        panic!("expected a fill divergence, got {:?}", divergence.kind);
+    };
+    assert_eq!(recorded[0].price, claimed);
+    assert_ne!(recorded, simulated);
+}
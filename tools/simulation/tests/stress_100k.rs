@@ -7,7 +7,6 @@ use simulation::bots::market_maker::{MarketMaker, MarketMakerConfig};
 use simulation::bots::retail_trader::{RetailTrader, RetailTraderConfig};
 use simulation::engine::SimEngine;
 use simulation::metrics::SimMetrics;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use std::time::Instant;
 use types::fee::FeeTier;